@@ -1,7 +1,6 @@
 use amazon_rose_forest::core::vector::Vector;
 use amazon_rose_forest::sharding::vector_index::{DistanceMetric, VectorIndex};
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use std::collections::HashMap;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 
 fn bench_vector_index(c: &mut Criterion) {
     // Setup test vectors
@@ -0,0 +1,53 @@
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("Invalid server URL {url}: {reason}")]
+    InvalidUrl { url: String, reason: String },
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The server answered with an error status
+    #[error("Server returned {status}: {message}")]
+    Api {
+        status: u16,
+        message: String,
+        /// From the `Retry-After` header, when the server sent one
+        retry_after: Option<Duration>,
+    },
+
+    /// Boxed because the websocket error is several times larger than the
+    /// other variants
+    #[error("WebSocket error: {0}")]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("API key can't be sent as a header value")]
+    InvalidApiKey,
+}
+
+impl ClientError {
+    /// HTTP status of an API error
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::Api { status, .. } => Some(*status),
+            ClientError::Http(e) => e.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for ClientError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        ClientError::WebSocket(Box::new(e))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
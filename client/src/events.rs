@@ -0,0 +1,87 @@
+//! Subscriber for the `/ws/events` stream.
+//!
+//! The socket offers every schema version this build understands during
+//! the handshake, so it keeps working against older and newer servers. The
+//! server picks one and sends nothing newer; events from a newer schema
+//! that slip through anyway decode as [`ServerEvent::Unknown`].
+
+use futures::StreamExt;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use amazon_rose_forest::server::events::{
+    self, EventEnvelope, MIN_SCHEMA_VERSION, PROTOCOL_PREFIX, SCHEMA_VERSION,
+};
+
+use crate::error::{ClientError, Result};
+
+const PROTOCOL_HEADER: &str = "sec-websocket-protocol";
+
+/// Open subscription to the server's event stream
+pub struct EventSocket {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    version: u32,
+}
+
+impl std::fmt::Debug for EventSocket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventSocket")
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EventSocket {
+    pub(crate) async fn connect(mut request: Request) -> Result<Self> {
+        let offered: Vec<String> = (MIN_SCHEMA_VERSION..=SCHEMA_VERSION)
+            .rev()
+            .map(events::protocol)
+            .collect();
+        let offered =
+            HeaderValue::from_str(&offered.join(", ")).map_err(|e| ClientError::InvalidUrl {
+                url: request.uri().to_string(),
+                reason: e.to_string(),
+            })?;
+        request.headers_mut().insert(PROTOCOL_HEADER, offered);
+
+        let (stream, response) = tokio_tungstenite::connect_async(request).await?;
+        // Servers from before negotiation existed answer without a protocol
+        // and speak the first version
+        let version = response
+            .headers()
+            .get(PROTOCOL_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().strip_prefix(PROTOCOL_PREFIX)?.parse().ok())
+            .unwrap_or(MIN_SCHEMA_VERSION);
+        Ok(Self { stream, version })
+    }
+
+    /// Schema version the server agreed to speak
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Next event, or `None` once the socket closes
+    pub async fn next(&mut self) -> Option<Result<EventEnvelope>> {
+        loop {
+            match self.stream.next().await? {
+                Ok(Message::Text(text)) => {
+                    return Some(serde_json::from_str(&text).map_err(ClientError::from))
+                }
+                Ok(Message::Close(_)) => return None,
+                // Pings are answered by tungstenite itself
+                Ok(_) => continue,
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+
+    /// Close the connection cleanly
+    pub async fn close(mut self) -> Result<()> {
+        self.stream.close(None).await?;
+        Ok(())
+    }
+}
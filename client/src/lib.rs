@@ -0,0 +1,489 @@
+//! Async client for the Amazon Rose Forest HTTP API.
+//!
+//! Requests and responses are the server's own [`server::api`] types, so
+//! they can't drift from what the server accepts. The client keeps a pool of
+//! connections per host, retries requests the server turned away without
+//! processing (and idempotent ones after transient failures), and wraps the
+//! search websocket in [`SearchSocket`] and the event stream in
+//! [`EventSocket`].
+//!
+//! [`server::api`]: amazon_rose_forest::server::api
+
+pub mod error;
+pub mod events;
+pub mod socket;
+
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::http::HeaderValue as WsHeaderValue;
+use uuid::Uuid;
+
+pub use amazon_rose_forest::connectors::ImportSummary;
+pub use amazon_rose_forest::nerv::jobs::{Job, JobKind, JobState};
+pub use amazon_rose_forest::nerv::tasks::{TaskInfo, TaskSnapshot};
+pub use amazon_rose_forest::network::admission::AdmissionStatus;
+pub use amazon_rose_forest::network::priority::{Priority, PRIORITY_HEADER};
+pub use amazon_rose_forest::query::{
+    ComposeOp, ComposeTerm, FieldCondition, LatencyBand, MetadataFilter, ScorerInfo, SearchEstimate,
+};
+pub use amazon_rose_forest::server::api::{
+    AddVectorRequest, AddVectorResponse, AddVectorsBatchRequest, AddVectorsBatchResponse,
+    BatchVector, BuildIndexRequest, ChangesQuery, ComposeSearch, ComposeVectorsRequest,
+    ComposeVectorsResponse, CreateIndexRequest, CreateIndexResponse, CreateShardRequest,
+    CreateShardResponse, DeleteShardResponse, DeleteVectorResponse, ErrorResponse, ImportRequest,
+    IndexInfo, OutlierRequest, SearchResult, SearchVectorsRequest, SearchVectorsResponse,
+    ShardInfo, SubmitJobRequest, VectorQuery, VectorResponse,
+};
+pub use amazon_rose_forest::server::auth::API_KEY_HEADER;
+pub use amazon_rose_forest::server::events::{EventEnvelope, ServerEvent};
+pub use amazon_rose_forest::sharding::changefeed::ChangeBatch;
+pub use amazon_rose_forest::sharding::hnsw::HnswParams;
+pub use amazon_rose_forest::sharding::manager::IndexBuild;
+pub use amazon_rose_forest::sharding::outliers::{
+    Outlier, OutlierMethod, OutlierParams, OutlierReport,
+};
+pub use amazon_rose_forest::sharding::sketch::IndexStatistics;
+pub use amazon_rose_forest::sharding::vector_index::IndexType;
+pub use error::{ClientError, Result};
+pub use events::EventSocket;
+pub use socket::{SearchSocket, SocketReply};
+
+/// How failed requests are retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; zero disables retrying
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for each one after
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.max_backoff)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// Server root, e.g. `http://127.0.0.1:9000`
+    pub base_url: String,
+    /// Must match the server's `api_path`
+    pub api_path: String,
+    /// Limit for a whole request, including reading the response
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    /// Idle connections kept open per host
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Duration,
+    pub retry: RetryPolicy,
+    /// Sent as the priority header on every request; the server only lets
+    /// this lower a route's class
+    pub priority: Option<Priority>,
+    /// Sent as the API key header on every request and websocket handshake,
+    /// for servers that require authentication
+    pub api_key: Option<String>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://127.0.0.1:9000".to_string(),
+            api_path: "/api".to_string(),
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(5),
+            pool_max_idle_per_host: 16,
+            pool_idle_timeout: Duration::from_secs(90),
+            retry: RetryPolicy::default(),
+            priority: None,
+            api_key: None,
+        }
+    }
+}
+
+/// Whether a request can safely be sent again after it may have reached
+/// the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Retry {
+    Idempotent,
+    /// Only retried when the server can't have acted on it
+    Unsafe,
+}
+
+/// Typed client for one server
+#[derive(Debug, Clone)]
+pub struct RoseForestClient {
+    http: reqwest::Client,
+    config: ClientConfig,
+}
+
+impl RoseForestClient {
+    /// Client with default settings for the server at `base_url`
+    pub fn new(base_url: impl Into<String>) -> Result<Self> {
+        Self::with_config(ClientConfig {
+            base_url: base_url.into(),
+            ..ClientConfig::default()
+        })
+    }
+
+    pub fn with_config(mut config: ClientConfig) -> Result<Self> {
+        config.base_url = config.base_url.trim_end_matches('/').to_string();
+        if !config.base_url.starts_with("http://") && !config.base_url.starts_with("https://") {
+            return Err(ClientError::InvalidUrl {
+                url: config.base_url,
+                reason: "expected an http:// or https:// URL".into(),
+            });
+        }
+        let mut headers = HeaderMap::new();
+        if let Some(priority) = config.priority {
+            headers.insert(PRIORITY_HEADER, HeaderValue::from_static(priority.as_str()));
+        }
+        if let Some(key) = &config.api_key {
+            let mut key = HeaderValue::from_str(key).map_err(|_| ClientError::InvalidApiKey)?;
+            key.set_sensitive(true);
+            headers.insert(API_KEY_HEADER, key);
+        }
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(config.pool_idle_timeout)
+            .default_headers(headers)
+            .build()?;
+        Ok(Self { http, config })
+    }
+
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            self.config.base_url,
+            self.config.api_path.trim_matches('/'),
+            path
+        )
+    }
+
+    /// Server liveness; fails if the server isn't answering
+    pub async fn health(&self) -> Result<serde_json::Value> {
+        let url = format!("{}/health", self.config.base_url);
+        self.send(Retry::Idempotent, || self.http.get(&url)).await
+    }
+
+    /// Version string the server reports
+    pub async fn version(&self) -> Result<String> {
+        let body: serde_json::Value = self.get("version").await?;
+        Ok(body["version"].as_str().unwrap_or_default().to_string())
+    }
+
+    pub async fn create_shard(&self, name: &str) -> Result<Uuid> {
+        let request = CreateShardRequest {
+            name: name.to_string(),
+        };
+        let response: CreateShardResponse = self.post("shards", &request, Retry::Unsafe).await?;
+        Ok(response.shard_id)
+    }
+
+    pub async fn shards(&self) -> Result<Vec<ShardInfo>> {
+        self.get("shards").await
+    }
+
+    /// Delete a shard and every shard split off it
+    pub async fn delete_shard(&self, shard_id: Uuid) -> Result<DeleteShardResponse> {
+        let url = self.api_url(&format!("shards/{}", shard_id));
+        self.send(Retry::Unsafe, || self.http.delete(&url)).await
+    }
+
+    /// The shard's index, if it has one
+    pub async fn indexes(&self, shard_id: Uuid) -> Result<Vec<IndexInfo>> {
+        self.get(&format!("shards/{}/indexes", shard_id)).await
+    }
+
+    pub async fn create_index(&self, request: &CreateIndexRequest) -> Result<CreateIndexResponse> {
+        self.post("indexes", request, Retry::Unsafe).await
+    }
+
+    /// Create a shard whose index is built in bulk from an embeddings file
+    /// on the server
+    pub async fn build_index(&self, request: &BuildIndexRequest) -> Result<IndexBuild> {
+        self.post("indexes/build", request, Retry::Unsafe).await
+    }
+
+    pub async fn add_vector(&self, request: &AddVectorRequest) -> Result<Uuid> {
+        let response: AddVectorResponse = self.post("vectors", request, Retry::Unsafe).await?;
+        Ok(response.vector_id)
+    }
+
+    /// Add many vectors in one request, returning their IDs in order
+    pub async fn add_vectors_batch(&self, request: &AddVectorsBatchRequest) -> Result<Vec<Uuid>> {
+        let response: AddVectorsBatchResponse =
+            self.post("vectors/batch", request, Retry::Unsafe).await?;
+        Ok(response.vector_ids)
+    }
+
+    /// Look a vector up by ID, in every shard unless `query` names one
+    pub async fn get_vector(&self, vector_id: Uuid, query: &VectorQuery) -> Result<VectorResponse> {
+        let url = self.api_url(&format!("vectors/{}", vector_id));
+        self.send(Retry::Idempotent, || self.http.get(&url).query(query))
+            .await
+    }
+
+    pub async fn delete_vector(
+        &self,
+        vector_id: Uuid,
+        query: &VectorQuery,
+    ) -> Result<DeleteVectorResponse> {
+        let url = self.api_url(&format!("vectors/{}", vector_id));
+        self.send(Retry::Unsafe, || self.http.delete(&url).query(query))
+            .await
+    }
+
+    pub async fn search(&self, request: &SearchVectorsRequest) -> Result<SearchVectorsResponse> {
+        self.post("search", request, Retry::Idempotent).await
+    }
+
+    /// What a search would cost, without running it
+    pub async fn estimate_search(&self, request: &SearchVectorsRequest) -> Result<SearchEstimate> {
+        self.post("search/estimate", request, Retry::Idempotent)
+            .await
+    }
+
+    /// Upload a WebAssembly scorer that searches can name in `scorer`,
+    /// replacing any registered under `name`
+    pub async fn register_scorer(&self, name: &str, module: &[u8]) -> Result<ScorerInfo> {
+        let url = self.api_url(&format!("scorers/{}", name));
+        self.send(Retry::Idempotent, || {
+            self.http.put(&url).body(module.to_vec())
+        })
+        .await
+    }
+
+    pub async fn scorers(&self) -> Result<Vec<ScorerInfo>> {
+        self.get("scorers").await
+    }
+
+    pub async fn delete_scorer(&self, name: &str) -> Result<ScorerInfo> {
+        let url = self.api_url(&format!("scorers/{}", name));
+        self.send(Retry::Unsafe, || self.http.delete(&url)).await
+    }
+
+    /// Combine vectors server-side, optionally searching with the result
+    pub async fn compose(&self, request: &ComposeVectorsRequest) -> Result<ComposeVectorsResponse> {
+        self.post("vectors/compose", request, Retry::Idempotent)
+            .await
+    }
+
+    /// The most anomalous vectors in a shard
+    pub async fn outliers(&self, request: &OutlierRequest) -> Result<OutlierReport> {
+        self.post("analyze/outliers", request, Retry::Idempotent)
+            .await
+    }
+
+    /// Pull vectors from an external source into a shard
+    pub async fn import(&self, request: &ImportRequest) -> Result<ImportSummary> {
+        self.post("import", request, Retry::Unsafe).await
+    }
+
+    /// A page of a shard's change feed
+    pub async fn changes(&self, shard_id: Uuid, query: &ChangesQuery) -> Result<ChangeBatch> {
+        let url = self.api_url(&format!("shards/{}/changes", shard_id));
+        self.send(Retry::Idempotent, || self.http.get(&url).query(query))
+            .await
+    }
+
+    pub async fn index_statistics(&self, shard_id: Uuid) -> Result<IndexStatistics> {
+        self.get(&format!("shards/{}/stats", shard_id)).await
+    }
+
+    pub async fn admission_status(&self) -> Result<AdmissionStatus> {
+        self.get("admission").await
+    }
+
+    /// Background tasks running on the server, by owning subsystem
+    pub async fn tasks(&self) -> Result<TaskSnapshot> {
+        self.get("admin/tasks").await
+    }
+
+    /// Queue a long-running job; poll it with [`job`](Self::job) or
+    /// [`wait_for_job`](Self::wait_for_job)
+    pub async fn submit_job(&self, kind: JobKind, params: serde_json::Value) -> Result<Job> {
+        self.post("jobs", &SubmitJobRequest { kind, params }, Retry::Unsafe)
+            .await
+    }
+
+    pub async fn job(&self, job_id: Uuid) -> Result<Job> {
+        self.get(&format!("jobs/{}", job_id)).await
+    }
+
+    pub async fn jobs(&self) -> Result<Vec<Job>> {
+        self.get("jobs").await
+    }
+
+    pub async fn cancel_job(&self, job_id: Uuid) -> Result<Job> {
+        let url = self.api_url(&format!("jobs/{}/cancel", job_id));
+        self.send(Retry::Idempotent, || self.http.post(&url)).await
+    }
+
+    /// Poll a job every `interval` until it finishes or `timeout` passes
+    pub async fn wait_for_job(
+        &self,
+        job_id: Uuid,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<Job> {
+        let poll = async {
+            loop {
+                let job = self.job(job_id).await?;
+                if job.state.is_finished() {
+                    return Ok(job);
+                }
+                tokio::time::sleep(interval).await;
+            }
+        };
+        tokio::time::timeout(timeout, poll)
+            .await
+            .map_err(|_| ClientError::Timeout(timeout))?
+    }
+
+    /// Open the search websocket
+    pub async fn search_socket(&self) -> Result<SearchSocket> {
+        SearchSocket::connect(self.ws_request("search")?).await
+    }
+
+    /// Subscribe to the server's event stream
+    pub async fn event_socket(&self) -> Result<EventSocket> {
+        EventSocket::connect(self.ws_request("events")?).await
+    }
+
+    /// Handshake request for one of the server's websockets
+    fn ws_request(&self, socket: &str) -> Result<Request> {
+        let mut request = self.ws_url(socket).into_client_request()?;
+        if let Some(key) = &self.config.api_key {
+            let key = WsHeaderValue::from_str(key).map_err(|_| ClientError::InvalidApiKey)?;
+            request.headers_mut().insert(API_KEY_HEADER, key);
+        }
+        Ok(request)
+    }
+
+    fn ws_url(&self, socket: &str) -> String {
+        match self.config.base_url.strip_prefix("https://") {
+            Some(rest) => format!("wss://{}/ws/{}", rest, socket),
+            None => format!(
+                "ws://{}/ws/{}",
+                self.config.base_url.trim_start_matches("http://"),
+                socket
+            ),
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = self.api_url(path);
+        self.send(Retry::Idempotent, || self.http.get(&url)).await
+    }
+
+    async fn post<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+        retry: Retry,
+    ) -> Result<T> {
+        let url = self.api_url(path);
+        self.send(retry, || self.http.request(Method::POST, &url).json(body))
+            .await
+    }
+
+    /// Send a request, retrying per the policy, and decode the reply
+    async fn send<T, F>(&self, retry: Retry, build: F) -> Result<T>
+    where
+        T: DeserializeOwned,
+        F: Fn() -> RequestBuilder,
+    {
+        let policy = &self.config.retry;
+        let mut attempt = 0;
+        loop {
+            let error = match build().send().await {
+                Ok(response) if response.status().is_success() => {
+                    return Ok(response.json().await?);
+                }
+                Ok(response) => api_error(response).await,
+                Err(e) => ClientError::Http(e),
+            };
+            if attempt >= policy.max_retries || !should_retry(&error, retry) {
+                return Err(error);
+            }
+            let wait = match &error {
+                ClientError::Api {
+                    retry_after: Some(retry_after),
+                    ..
+                } => *retry_after,
+                _ => policy.backoff(attempt),
+            };
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+        }
+    }
+}
+
+async fn api_error(response: reqwest::Response) -> ClientError {
+    let status = response.status().as_u16();
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    let body = response.text().await.unwrap_or_default();
+    let message = match serde_json::from_str::<ErrorResponse>(&body) {
+        Ok(error) => error.error,
+        Err(_) => body,
+    };
+    ClientError::Api {
+        status,
+        message,
+        retry_after,
+    }
+}
+
+fn should_retry(error: &ClientError, retry: Retry) -> bool {
+    match error {
+        // The connection never opened, so the server saw nothing
+        ClientError::Http(e) if e.is_connect() => true,
+        ClientError::Http(e) => retry == Retry::Idempotent && e.is_timeout(),
+        ClientError::Api {
+            status,
+            retry_after,
+            ..
+        } => {
+            let status = StatusCode::from_u16(*status).unwrap_or(StatusCode::OK);
+            match status {
+                // Admission control and rate limits answer before doing any work
+                StatusCode::TOO_MANY_REQUESTS => true,
+                StatusCode::SERVICE_UNAVAILABLE if retry_after.is_some() => true,
+                StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT => retry == Retry::Idempotent,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,101 @@
+//! Helper for the `/ws/search` websocket.
+//!
+//! The server answers each search request with one text message per result
+//! and no end marker, or with a single error message. [`SearchSocket::search`]
+//! collects a page by stopping once `limit` results arrive or the socket
+//! goes quiet.
+
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use amazon_rose_forest::server::api::{ErrorResponse, SearchResult, SearchVectorsRequest};
+
+use crate::error::{ClientError, Result};
+
+/// A message received on the search socket
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SocketReply {
+    Error(ErrorResponse),
+    Result(SearchResult),
+}
+
+/// Open connection to the search websocket
+pub struct SearchSocket {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl std::fmt::Debug for SearchSocket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SearchSocket").finish_non_exhaustive()
+    }
+}
+
+impl SearchSocket {
+    pub(crate) async fn connect(request: Request) -> Result<Self> {
+        let (stream, _) = tokio_tungstenite::connect_async(request).await?;
+        Ok(Self { stream })
+    }
+
+    /// Send a search request; read the replies with [`next`](Self::next)
+    pub async fn send(&mut self, request: &SearchVectorsRequest) -> Result<()> {
+        let text = serde_json::to_string(request)?;
+        self.stream.send(Message::Text(text)).await?;
+        Ok(())
+    }
+
+    /// Next result or error from the server, or `None` once the socket closes
+    pub async fn next(&mut self) -> Option<Result<SocketReply>> {
+        loop {
+            match self.stream.next().await? {
+                Ok(Message::Text(text)) => {
+                    return Some(serde_json::from_str(&text).map_err(ClientError::from))
+                }
+                Ok(Message::Close(_)) => return None,
+                // Pings are answered by tungstenite itself
+                Ok(_) => continue,
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+
+    /// Run a search and collect its results. Stops after `request.limit`
+    /// results, or once `idle` passes without a message, since a shard with
+    /// fewer vectors than the limit sends fewer results.
+    pub async fn search(
+        &mut self,
+        request: &SearchVectorsRequest,
+        idle: Duration,
+    ) -> Result<Vec<SearchResult>> {
+        self.send(request).await?;
+        let mut results = Vec::with_capacity(request.limit);
+        while results.len() < request.limit {
+            let reply = match tokio::time::timeout(idle, self.next()).await {
+                Ok(Some(reply)) => reply?,
+                Ok(None) | Err(_) => break,
+            };
+            match reply {
+                SocketReply::Result(result) => results.push(result),
+                SocketReply::Error(e) => {
+                    return Err(ClientError::Api {
+                        status: 400,
+                        message: e.error,
+                        retry_after: None,
+                    })
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Close the connection cleanly
+    pub async fn close(mut self) -> Result<()> {
+        self.stream.close(None).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::nerv::jobs::{ClusteringJob, JobQueue};
+use amazon_rose_forest::server::auth::AuthConfig;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use rose_forest_client::{
+    AddVectorRequest, AddVectorsBatchRequest, BatchVector, ClientConfig, ClientError,
+    CreateIndexRequest, JobKind, JobState, LatencyBand, RetryPolicy, RoseForestClient,
+    SearchVectorsRequest, SearchVectorsResponse, ServerEvent, VectorQuery,
+};
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+use warp::Filter;
+
+async fn serve(server: Server) -> SocketAddr {
+    let (addr, serving) = warp::serve(server.filter()).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(serving);
+    addr
+}
+
+fn search_request(shard_id: Uuid, limit: usize) -> SearchVectorsRequest {
+    serde_json::from_value(json!({
+        "shard_id": shard_id,
+        "query_vector": [0.0, 0.0],
+        "limit": limit
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn typed_calls_round_trip_through_the_server() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let jobs = Arc::new(JobQueue::new(Arc::new(MetricsCollector::new())));
+    jobs.register(
+        JobKind::Clustering,
+        Arc::new(ClusteringJob::new(manager.clone())),
+    )
+    .await;
+    let _worker = jobs.clone().start(1);
+    let addr = serve(
+        Server::new(
+            ServerConfig::default(),
+            Arc::new(MetricsCollector::new()),
+            None,
+            Some(manager),
+        )
+        .with_job_queue(jobs),
+    )
+    .await;
+    let client = RoseForestClient::new(format!("http://{}/", addr)).unwrap();
+
+    assert_eq!(client.health().await.unwrap()["status"], "ok");
+    let shard_id = client.create_shard("docs").await.unwrap();
+    let index = client
+        .create_index(&CreateIndexRequest {
+            shard_id,
+            name: "main".into(),
+            dimensions: 2,
+            distance_metric: "Euclidean".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(index.distance_metric, "euclidean");
+
+    let mut ids = vec![client
+        .add_vector(&AddVectorRequest {
+            shard_id,
+            vector: vec![0.0, 0.0],
+            metadata: None,
+        })
+        .await
+        .unwrap()];
+    let batch = [vec![0.1, 0.0], vec![4.0, 4.0]]
+        .into_iter()
+        .map(|vector| BatchVector {
+            vector,
+            metadata: None,
+        })
+        .collect();
+    ids.extend(
+        client
+            .add_vectors_batch(&AddVectorsBatchRequest {
+                shard_id,
+                vectors: batch,
+            })
+            .await
+            .unwrap(),
+    );
+    let page = client.search(&search_request(shard_id, 2)).await.unwrap();
+    assert_eq!(page.results.len(), 2);
+    assert!(!page.partial);
+    let estimate = client
+        .estimate_search(&search_request(shard_id, 2))
+        .await
+        .unwrap();
+    assert!(estimate.vectors_scanned <= 3);
+    assert_eq!(estimate.latency_band, LatencyBand::Fast);
+
+    let job = client
+        .submit_job(
+            JobKind::Clustering,
+            json!({ "shard_id": shard_id, "threshold": 1.0 }),
+        )
+        .await
+        .unwrap();
+    let job = client
+        .wait_for_job(job.id, Duration::from_millis(10), Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(job.state, JobState::Completed);
+    assert_eq!(job.result.unwrap()["clusters"], 2);
+
+    // Server errors come back typed, with the server's message
+    match client.job(Uuid::new_v4()).await {
+        Err(ClientError::Api {
+            status, message, ..
+        }) => {
+            assert_eq!(status, 404);
+            assert!(message.contains("Unknown job"));
+        }
+        other => panic!("expected a 404, got {:?}", other),
+    }
+
+    let mut socket = client.search_socket().await.unwrap();
+    let results = socket
+        .search(&search_request(shard_id, 10), Duration::from_millis(200))
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 3);
+    socket.close().await.unwrap();
+
+    let shards = client.shards().await.unwrap();
+    assert_eq!(shards.len(), 1);
+    assert_eq!(shards[0].vector_count, 3);
+    assert_eq!(client.indexes(shard_id).await.unwrap()[0].dimensions, 2);
+    let anywhere = VectorQuery::default();
+    let vector = client.get_vector(ids[1], &anywhere).await.unwrap();
+    assert_eq!(vector.vector, vec![0.1, 0.0]);
+    assert_eq!(vector.shard_id, shard_id);
+    client.delete_vector(ids[1], &anywhere).await.unwrap();
+    assert!(client.get_vector(ids[1], &anywhere).await.is_err());
+    let deleted = client.delete_shard(shard_id).await.unwrap();
+    assert_eq!(deleted.deleted, vec![shard_id]);
+    assert!(client.shards().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn idempotent_requests_are_retried_after_transient_failures() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let flaky = warp::path!("api" / "search").map(move || {
+        if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+            warp::reply::with_status(
+                warp::reply::json(&json!({ "error": "warming up" })),
+                warp::http::StatusCode::SERVICE_UNAVAILABLE,
+            )
+        } else {
+            warp::reply::with_status(
+                warp::reply::json(&json!({ "results": [], "partial": false })),
+                warp::http::StatusCode::OK,
+            )
+        }
+    });
+    let created = Arc::new(AtomicUsize::new(0));
+    let create_counter = created.clone();
+    let unavailable = warp::path!("api" / "shards").map(move || {
+        create_counter.fetch_add(1, Ordering::SeqCst);
+        warp::reply::with_status(
+            warp::reply::json(&json!({ "error": "upstream down" })),
+            warp::http::StatusCode::BAD_GATEWAY,
+        )
+    });
+    let (addr, serving) = warp::serve(flaky.or(unavailable)).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(serving);
+
+    let client = RoseForestClient::with_config(ClientConfig {
+        base_url: format!("http://{}", addr),
+        retry: RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(5),
+            max_backoff: Duration::from_millis(20),
+        },
+        ..ClientConfig::default()
+    })
+    .unwrap();
+
+    let page: SearchVectorsResponse = client
+        .search(&search_request(Uuid::new_v4(), 5))
+        .await
+        .unwrap();
+    assert!(page.results.is_empty());
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+    // Creating a shard isn't idempotent, so a 502 is returned as is
+    let err = client.create_shard("docs").await.unwrap_err();
+    assert_eq!(err.status(), Some(502));
+    assert_eq!(created.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn event_socket_negotiates_the_newest_schema() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let addr = serve(Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    ))
+    .await;
+    let client = RoseForestClient::new(format!("http://{}", addr)).unwrap();
+
+    let mut events = client.event_socket().await.unwrap();
+    assert_eq!(
+        events.version(),
+        amazon_rose_forest::server::events::SCHEMA_VERSION
+    );
+    let shard_id = client.create_shard("docs").await.unwrap();
+    client
+        .create_index(&CreateIndexRequest {
+            shard_id,
+            name: "main".into(),
+            dimensions: 2,
+            distance_metric: "Euclidean".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let added = client
+        .add_vector(&AddVectorRequest {
+            shard_id,
+            vector: vec![0.5, 0.5],
+            metadata: None,
+        })
+        .await
+        .unwrap();
+
+    let envelope = tokio::time::timeout(Duration::from_secs(5), events.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    match envelope.event {
+        ServerEvent::VectorInserted { vector_id, .. } => assert_eq!(vector_id, added),
+        other => panic!("expected an insert, got {:?}", other),
+    }
+    events.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn api_key_is_sent_on_requests_and_socket_handshakes() {
+    let addr = serve(Server::new(
+        ServerConfig {
+            auth: Some(AuthConfig::new().with_api_key("secret-key")),
+            ..ServerConfig::default()
+        },
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(Arc::new(ShardManager::new(Arc::new(
+            MetricsCollector::new(),
+        )))),
+    ))
+    .await;
+
+    let anonymous = RoseForestClient::new(format!("http://{}", addr)).unwrap();
+    assert_eq!(anonymous.version().await.unwrap_err().status(), Some(401));
+    assert!(anonymous.event_socket().await.is_err());
+
+    let client = RoseForestClient::with_config(ClientConfig {
+        base_url: format!("http://{}", addr),
+        api_key: Some("secret-key".into()),
+        ..ClientConfig::default()
+    })
+    .unwrap();
+    assert_eq!(client.version().await.unwrap(), amazon_rose_forest::VERSION);
+    client.event_socket().await.unwrap().close().await.unwrap();
+}
+
+#[test]
+fn rejects_non_http_urls() {
+    assert!(matches!(
+        RoseForestClient::new("ftp://example.com"),
+        Err(ClientError::InvalidUrl { .. })
+    ));
+}
@@ -64,6 +64,80 @@ impl KnowledgeGraph {
     }
 }
 
+#[hdk_entry(id = "concept_contribution")]
+#[derive(Clone)]
+pub struct ConceptContribution {
+    pub contributor: AgentPubKey,
+    pub concept_id: String,
+    pub name: String,
+    pub description: String,
+}
+
+#[hdk_extern]
+pub fn create_concept_contribution(contribution: ConceptContribution) -> ExternResult<EntryHash> {
+    let entry_hash = create_entry(&contribution)?;
+    let path = Path::from(format!("concept_contribution.{}", contribution.contributor));
+    create_link(path.path_entry_hash()?, entry_hash.clone(), LinkTag::new(vec![]))?;
+    Ok(entry_hash)
+}
+
+/// Cursor-paged request for an agent's concept contributions, newest first.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ContributionsQuery {
+    pub agent: AgentPubKey,
+    /// Only return contributions strictly older than this position
+    pub before: Option<(u64, EntryHash)>,
+    pub limit: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TimestampedConceptContribution {
+    pub entry_hash: EntryHash,
+    /// Link creation time in microseconds
+    pub timestamp: u64,
+    pub contribution: ConceptContribution,
+}
+
+#[hdk_extern]
+pub fn get_concept_contributions_for_agent(
+    query: ContributionsQuery,
+) -> ExternResult<Vec<TimestampedConceptContribution>> {
+    let path = Path::from(format!("concept_contribution.{}", query.agent));
+    let links = get_links(path.path_entry_hash()?, None)?;
+
+    let mut positions: Vec<(u64, EntryHash)> = links
+        .into_inner()
+        .into_iter()
+        .map(|link| (link.timestamp.as_micros().max(0) as u64, link.target))
+        .filter(|position| match &query.before {
+            Some(before) => position < before,
+            None => true,
+        })
+        .collect();
+    positions.sort_by(|a, b| b.cmp(a));
+
+    positions
+        .into_iter()
+        .take(query.limit)
+        .map(|(timestamp, entry_hash)| {
+            let contribution: ConceptContribution = get(entry_hash.clone(), GetOptions::default())?
+                .ok_or(wasm_error!(WasmErrorInner::Guest(
+                    "ConceptContribution not found".to_string()
+                )))?
+                .entry()
+                .to_app_option()?
+                .ok_or(wasm_error!(WasmErrorInner::Guest(
+                    "ConceptContribution not found".to_string()
+                )))?;
+            Ok(TimestampedConceptContribution {
+                entry_hash,
+                timestamp,
+                contribution,
+            })
+        })
+        .collect()
+}
+
 #[hdk_extern]
 pub fn init(_: ()) -> ExternResult<InitCallbackResult> {
     Ok(InitCallbackResult::Pass)
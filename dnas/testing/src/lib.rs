@@ -0,0 +1,35 @@
+//! Fixtures for testing zome callbacks outside a conductor.
+//!
+//! The zomes and the node crate's Holochain module validate entries in
+//! `validate` callbacks; these build the ops the host would hand them.
+
+use hdk::prelude::*;
+
+/// A store-entry op for `entry` authored by `author`, as the validation
+/// callback receives it
+pub fn store_entry_op<T>(author: AgentPubKey, entry: T) -> Op
+where
+    SerializedBytes: TryFrom<T, Error = SerializedBytesError>,
+{
+    let entry = Entry::app(SerializedBytes::try_from(entry).unwrap()).unwrap();
+    let action = EntryCreationAction::Create(Create {
+        author,
+        timestamp: Timestamp::from_micros(0),
+        action_seq: 4,
+        prev_action: ActionHash::from_raw_36(vec![0; 36]),
+        entry_type: EntryType::App(AppEntryDef::new(
+            EntryDefIndex(0),
+            ZomeIndex(0),
+            EntryVisibility::Public,
+        )),
+        entry_hash: EntryHash::from_raw_36(vec![0; 36]),
+        weight: EntryRateWeight::default(),
+    });
+    Op::StoreEntry(StoreEntry {
+        action: SignedHashed::with_presigned(
+            HoloHashed::with_pre_hashed(action, ActionHash::from_raw_36(vec![0; 36])),
+            Signature([0; 64]),
+        ),
+        entry,
+    })
+}
@@ -1,34 +1,227 @@
 use hdk::prelude::*;
 
-#[hdk_entry(id = "value_flow")]
+use rose_forest_value_flow::zkp::UtilityProof;
+use rose_forest_value_flow::{validate_shift_vector, FlowTerms};
+
+pub use rose_forest_value_flow::{MAX_FLOW_UTILITY, MAX_REPUTATION_DIMENSIONS};
+
+#[hdk_entry_helper]
 #[derive(Clone)]
 pub struct ValueFlow {
     pub from: AgentPubKey,
     pub to: AgentPubKey,
+    /// Zero when the utility is private and committed in `utility_proof`;
+    /// such flows never match a `min_utility` query filter.
     pub utility: f32,
     pub governance_weight: f32,
     pub reputation_shift: Vec<f32>,
+    #[serde(default)]
+    pub utility_proof: Option<UtilityProof>,
+}
+
+impl ValueFlow {
+    /// What the shared value flow rules check
+    pub fn terms(&self) -> FlowTerms<'_, AgentPubKey> {
+        FlowTerms {
+            from: &self.from,
+            to: &self.to,
+            utility: self.utility,
+            governance_weight: self.governance_weight,
+            reputation_shift: &self.reputation_shift,
+            utility_proof: self.utility_proof.as_ref(),
+        }
+    }
+}
+
+#[hdk_entry_defs]
+#[unit_enum(UnitEntryTypes)]
+pub enum EntryTypes {
+    ValueFlow(ValueFlow),
+    ReputationShift(ReputationShift),
+}
+
+/// Links from an agent's path to the entries about them
+#[hdk_link_types]
+pub enum LinkTypes {
+    ValueFlow,
+    ReputationShift,
+}
+
+/// Length of an encoded value flow link tag: 4 bytes of utility followed by
+/// 8 bytes of creation time in microseconds, both big-endian.
+const VALUE_FLOW_TAG_LEN: usize = 12;
+
+/// Encode the utility and creation time of a flow into a link tag so queries
+/// can filter links without fetching the entries they point to.
+fn encode_value_flow_tag(utility: f32, timestamp: u64) -> LinkTag {
+    let mut bytes = Vec::with_capacity(VALUE_FLOW_TAG_LEN);
+    bytes.extend_from_slice(&utility.to_be_bytes());
+    bytes.extend_from_slice(&timestamp.to_be_bytes());
+    LinkTag::new(bytes)
+}
+
+/// Decode a tag written by `encode_value_flow_tag`. Links created before tags
+/// carried data have empty tags and decode to `None`.
+fn decode_value_flow_tag(tag: &LinkTag) -> Option<(f32, u64)> {
+    let bytes = tag.as_ref();
+    if bytes.len() != VALUE_FLOW_TAG_LEN {
+        return None;
+    }
+    let mut utility = [0u8; 4];
+    utility.copy_from_slice(&bytes[0..4]);
+    let mut timestamp = [0u8; 8];
+    timestamp.copy_from_slice(&bytes[4..12]);
+    Some((f32::from_be_bytes(utility), u64::from_be_bytes(timestamp)))
+}
+
+fn now_micros() -> ExternResult<u64> {
+    Ok(sys_time()?.as_micros().max(0) as u64)
+}
+
+/// The entry a link points at; every link this zome writes targets one
+fn link_entry(link: &Link) -> ExternResult<EntryHash> {
+    link.target
+        .clone()
+        .into_entry_hash()
+        .ok_or(wasm_error!(WasmErrorInner::Guest(
+            "Link doesn't point at an entry".to_string()
+        )))
 }
 
 #[hdk_extern]
 pub fn create_value_flow(value_flow: ValueFlow) -> ExternResult<EntryHash> {
-    let entry_hash = create_entry(&value_flow)?;
+    ensure_acting_for(&value_flow.from)?;
+    create_entry(&EntryTypes::ValueFlow(value_flow.clone()))?;
+    let entry_hash = hash_entry(&value_flow)?;
+    let tag = encode_value_flow_tag(value_flow.utility, now_micros()?);
     let from_path = Path::from(format!("value_flow.{}", value_flow.from));
-    create_link(from_path.path_entry_hash()?, entry_hash.clone(), LinkTag::new(vec![]))?;
+    create_link(
+        from_path.path_entry_hash()?,
+        entry_hash.clone(),
+        LinkTypes::ValueFlow,
+        tag.clone(),
+    )?;
     let to_path = Path::from(format!("value_flow.{}", value_flow.to));
-    create_link(to_path.path_entry_hash()?, entry_hash.clone(), LinkTag::new(vec![]))?;
+    create_link(
+        to_path.path_entry_hash()?,
+        entry_hash.clone(),
+        LinkTypes::ValueFlow,
+        tag,
+    )?;
     Ok(entry_hash)
 }
 
+/// Filter and paging options for `query_value_flows`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValueFlowQuery {
+    pub agent: AgentPubKey,
+    /// Inclusive lower bound on creation time, in microseconds
+    pub from_timestamp: Option<u64>,
+    /// Exclusive upper bound on creation time, in microseconds
+    pub to_timestamp: Option<u64>,
+    pub min_utility: Option<f32>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// A value flow together with the data decoded from its link tag.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TimestampedValueFlow {
+    pub entry_hash: EntryHash,
+    pub timestamp: u64,
+    pub value_flow: ValueFlow,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValueFlowPage {
+    pub flows: Vec<TimestampedValueFlow>,
+    /// Number of links matching the filter, before paging
+    pub total: usize,
+    /// Offset to request the next page with, if there is one
+    pub next_offset: Option<usize>,
+}
+
+/// Query an agent's value flows by date range and minimum utility. Filtering
+/// and ordering happen on link tags, so only the requested page is fetched.
+#[hdk_extern]
+pub fn query_value_flows(query: ValueFlowQuery) -> ExternResult<ValueFlowPage> {
+    let path = Path::from(format!("value_flow.{}", query.agent));
+    let links = get_links(path.path_entry_hash()?, LinkTypes::ValueFlow, None)?;
+
+    let mut matching: Vec<(u64, EntryHash)> = Vec::new();
+    for link in links {
+        let target = link_entry(&link)?;
+        let (utility, timestamp) = match decode_value_flow_tag(&link.tag) {
+            Some(decoded) => decoded,
+            // Links created before tags carried these have empty tags; read
+            // the utility from the entry and take the link's own timestamp.
+            None => (
+                get_value_flow(target.clone())?.utility,
+                link.timestamp.as_micros().max(0) as u64,
+            ),
+        };
+        if query.from_timestamp.map_or(false, |from| timestamp < from) {
+            continue;
+        }
+        if query.to_timestamp.map_or(false, |to| timestamp >= to) {
+            continue;
+        }
+        if query.min_utility.map_or(false, |min| utility < min) {
+            continue;
+        }
+        matching.push((timestamp, target));
+    }
+
+    // Newest first; the entry hash breaks ties so paging is stable.
+    matching.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    matching.dedup_by(|a, b| a.1 == b.1);
+
+    let total = matching.len();
+    let flows = matching
+        .into_iter()
+        .skip(query.offset)
+        .take(query.limit)
+        .map(|(timestamp, entry_hash)| {
+            let value_flow = get_value_flow(entry_hash.clone())?;
+            Ok(TimestampedValueFlow {
+                entry_hash,
+                timestamp,
+                value_flow,
+            })
+        })
+        .collect::<ExternResult<Vec<TimestampedValueFlow>>>()?;
+
+    let next = query.offset + flows.len();
+    let next_offset = if next < total { Some(next) } else { None };
+
+    Ok(ValueFlowPage {
+        flows,
+        total,
+        next_offset,
+    })
+}
+
+fn get_value_flow(entry_hash: EntryHash) -> ExternResult<ValueFlow> {
+    get(entry_hash, GetOptions::default())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(
+            "ValueFlow not found".to_string()
+        )))?
+        .entry()
+        .to_app_option()
+        .map_err(|e| wasm_error!(e))?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(
+            "ValueFlow not found".to_string()
+        )))
+}
+
 #[hdk_extern]
 pub fn get_value_flows_for_agent(agent: AgentPubKey) -> ExternResult<Vec<ValueFlow>> {
     let path = Path::from(format!("value_flow.{}", agent));
-    let links = get_links(path.path_entry_hash()?, None)?;
+    let links = get_links(path.path_entry_hash()?, LinkTypes::ValueFlow, None)?;
     let value_flows: Vec<ValueFlow> = links
-        .into_inner()
         .iter()
         .map(|link| {
-            get(link.target.clone(), GetOptions::default())
+            get(link_entry(link)?, GetOptions::default())
                 .and_then(|element| {
                     element
                         .ok_or(wasm_error!(WasmErrorInner::Guest("ValueFlow not found".to_string())))
@@ -36,7 +229,8 @@ pub fn get_value_flows_for_agent(agent: AgentPubKey) -> ExternResult<Vec<ValueFl
                 .and_then(|element| {
                     element
                         .entry()
-                        .to_app_option()?
+                        .to_app_option()
+                        .map_err(|e| wasm_error!(e))?
                         .ok_or(wasm_error!(WasmErrorInner::Guest("ValueFlow not found".to_string())))
                 })
         })
@@ -44,7 +238,7 @@ pub fn get_value_flows_for_agent(agent: AgentPubKey) -> ExternResult<Vec<ValueFl
     Ok(value_flows)
 }
 
-#[hdk_entry(id = "reputation_shift")]
+#[hdk_entry_helper]
 #[derive(Clone)]
 pub struct ReputationShift {
     pub agent: AgentPubKey,
@@ -56,9 +250,16 @@ pub struct ReputationShift {
 
 #[hdk_extern]
 pub fn create_reputation_shift(reputation_shift: ReputationShift) -> ExternResult<EntryHash> {
-    let entry_hash = create_entry(&reputation_shift)?;
+    ensure_acting_for(&reputation_shift.agent)?;
+    create_entry(&EntryTypes::ReputationShift(reputation_shift.clone()))?;
+    let entry_hash = hash_entry(&reputation_shift)?;
     let path = Path::from(format!("reputation_shift.{}", reputation_shift.agent));
-    create_link(path.path_entry_hash()?, entry_hash.clone(), LinkTag::new(vec![]))?;
+    create_link(
+        path.path_entry_hash()?,
+        entry_hash.clone(),
+        LinkTypes::ReputationShift,
+        LinkTag::new(vec![]),
+    )?;
     Ok(entry_hash)
 }
 
@@ -67,28 +268,351 @@ pub fn get_reputation_shifts_for_agent(
     agent: AgentPubKey,
 ) -> ExternResult<Vec<ReputationShift>> {
     let path = Path::from(format!("reputation_shift.{}", agent));
-    let links = get_links(path.path_entry_hash()?, None)?;
+    let links = get_links(path.path_entry_hash()?, LinkTypes::ReputationShift, None)?;
     let reputation_shifts: Vec<ReputationShift> = links
-        .into_inner()
         .iter()
         .map(|link| {
-            get(link.target.clone(), GetOptions::default())
+            get(link_entry(link)?, GetOptions::default())
                 .and_then(|element| {
                     element.ok_or(wasm_error!(WasmErrorInner::Guest(
                         "ReputationShift not found".to_string()
                     )))
                 })
                 .and_then(|element| {
-                    element.entry().to_app_option()?.ok_or(wasm_error!(
-                        WasmErrorInner::Guest("ReputationShift not found".to_string())
-                    ))
+                    element
+                        .entry()
+                        .to_app_option()
+                        .map_err(|e| wasm_error!(e))?
+                        .ok_or(wasm_error!(WasmErrorInner::Guest(
+                            "ReputationShift not found".to_string()
+                        )))
                 })
         })
         .collect::<ExternResult<Vec<ReputationShift>>>()?;
     Ok(reputation_shifts)
 }
 
-use ad4m_client::Client;
+/// Position of an item in an activity timeline: creation time in
+/// microseconds, with the entry hash as a tie-breaker. Pass the cursor of the
+/// last item received to fetch the next page.
+pub type TimelineCursor = (u64, EntryHash);
+
+/// Mirror of the knowledge DNA's concept contribution entry, as returned by
+/// its `get_concept_contributions_for_agent` extern.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConceptContribution {
+    pub contributor: AgentPubKey,
+    pub concept_id: String,
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ContributionsQuery {
+    agent: AgentPubKey,
+    before: Option<TimelineCursor>,
+    limit: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct TimestampedConceptContribution {
+    entry_hash: EntryHash,
+    timestamp: u64,
+    contribution: ConceptContribution,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ActivityKind {
+    ValueFlow(ValueFlow),
+    ReputationShift(ReputationShift),
+    ConceptContribution(ConceptContribution),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ActivityItem {
+    pub cursor: TimelineCursor,
+    pub activity: ActivityKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ActivityTimelineQuery {
+    pub agent: AgentPubKey,
+    /// Only return items strictly older than this cursor
+    pub before: Option<TimelineCursor>,
+    pub limit: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ActivityTimelinePage {
+    pub items: Vec<ActivityItem>,
+    /// Cursor for the next page, if more items may exist
+    pub next_cursor: Option<TimelineCursor>,
+}
+
+enum PendingActivity {
+    ValueFlow(EntryHash),
+    ReputationShift(EntryHash),
+    ConceptContribution(ConceptContribution),
+}
+
+/// Merged, newest-first feed of an agent's value flows, reputation shifts and
+/// concept contributions. Concept contributions come from the `knowledge`
+/// role via a bridge call and are omitted when that role isn't installed.
+#[hdk_extern]
+pub fn get_agent_activity_timeline(
+    query: ActivityTimelineQuery,
+) -> ExternResult<ActivityTimelinePage> {
+    let is_before = |cursor: &TimelineCursor| match &query.before {
+        Some(before) => cursor < before,
+        None => true,
+    };
+
+    let mut pending: Vec<(TimelineCursor, PendingActivity)> = Vec::new();
+
+    let flow_path = Path::from(format!("value_flow.{}", query.agent));
+    for link in get_links(flow_path.path_entry_hash()?, LinkTypes::ValueFlow, None)? {
+        let timestamp = decode_value_flow_tag(&link.tag)
+            .map(|(_, timestamp)| timestamp)
+            .unwrap_or_else(|| link.timestamp.as_micros().max(0) as u64);
+        let target = link_entry(&link)?;
+        let cursor = (timestamp, target.clone());
+        if is_before(&cursor) {
+            pending.push((cursor, PendingActivity::ValueFlow(target)));
+        }
+    }
+
+    let shift_path = Path::from(format!("reputation_shift.{}", query.agent));
+    for link in get_links(shift_path.path_entry_hash()?, LinkTypes::ReputationShift, None)? {
+        let target = link_entry(&link)?;
+        let cursor = (link.timestamp.as_micros().max(0) as u64, target.clone());
+        if is_before(&cursor) {
+            pending.push((cursor, PendingActivity::ReputationShift(target)));
+        }
+    }
+
+    for contribution in get_remote_contributions(&query)? {
+        let cursor = (contribution.timestamp, contribution.entry_hash);
+        pending.push((
+            cursor,
+            PendingActivity::ConceptContribution(contribution.contribution),
+        ));
+    }
+
+    pending.sort_by(|a, b| b.0.cmp(&a.0));
+    pending.dedup_by(|a, b| a.0 == b.0);
+    let has_more = pending.len() > query.limit;
+    pending.truncate(query.limit);
+
+    let items = pending
+        .into_iter()
+        .map(|(cursor, activity)| {
+            let activity = match activity {
+                PendingActivity::ValueFlow(hash) => ActivityKind::ValueFlow(get_value_flow(hash)?),
+                PendingActivity::ReputationShift(hash) => {
+                    ActivityKind::ReputationShift(get_reputation_shift(hash)?)
+                }
+                PendingActivity::ConceptContribution(contribution) => {
+                    ActivityKind::ConceptContribution(contribution)
+                }
+            };
+            Ok(ActivityItem { cursor, activity })
+        })
+        .collect::<ExternResult<Vec<ActivityItem>>>()?;
+
+    let next_cursor = if has_more {
+        items.last().map(|item| item.cursor.clone())
+    } else {
+        None
+    };
+
+    Ok(ActivityTimelinePage { items, next_cursor })
+}
+
+fn get_remote_contributions(
+    query: &ActivityTimelineQuery,
+) -> ExternResult<Vec<TimestampedConceptContribution>> {
+    let response = call(
+        CallTargetCell::OtherRole("knowledge".into()),
+        ZomeName::from("knowledge"),
+        FunctionName::from("get_concept_contributions_for_agent"),
+        None,
+        ContributionsQuery {
+            agent: query.agent.clone(),
+            before: query.before.clone(),
+            limit: query.limit,
+        },
+    );
+    match response {
+        Ok(ZomeCallResponse::Ok(io)) => io
+            .decode()
+            .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e))),
+        Ok(other) => {
+            debug!("Skipping concept contributions: {:?}", other);
+            Ok(Vec::new())
+        }
+        Err(e) => {
+            debug!("Skipping concept contributions: {:?}", e);
+            Ok(Vec::new())
+        }
+    }
+}
+
+fn get_reputation_shift(entry_hash: EntryHash) -> ExternResult<ReputationShift> {
+    get(entry_hash, GetOptions::default())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(
+            "ReputationShift not found".to_string()
+        )))?
+        .entry()
+        .to_app_option()
+        .map_err(|e| wasm_error!(e))?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(
+            "ReputationShift not found".to_string()
+        )))
+}
+
+/// Functions another agent may call on our cell once we've granted them a
+/// capability.
+const DELEGATED_FUNCTIONS: [&str; 2] = ["create_value_flow", "create_reputation_shift"];
+
+/// Entries are authored on the chain of the agent they speak for. Callers
+/// acting for someone else must go through `create_value_flow_for` or
+/// `create_reputation_shift_for`, which run on that agent's cell under a
+/// capability grant.
+fn ensure_acting_for(agent: &AgentPubKey) -> ExternResult<()> {
+    let me = agent_info()?.agent_latest_pubkey;
+    if &me != agent {
+        return Err(wasm_error!(WasmErrorInner::Guest(format!(
+            "Agent {} cannot act on behalf of {} without a capability",
+            me, agent
+        ))));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IssueCapabilityInput {
+    pub grantee: AgentPubKey,
+    pub tag: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IssuedCapability {
+    /// Action hash of the grant, needed to revoke it
+    pub grant_hash: ActionHash,
+    /// Secret the grantee must present; hand it over out of band or via
+    /// `store_capability_claim` on their cell
+    pub secret: CapSecret,
+}
+
+/// Allow `grantee` to create value flows and reputation shifts on our behalf.
+#[hdk_extern]
+pub fn issue_capability(input: IssueCapabilityInput) -> ExternResult<IssuedCapability> {
+    let zome_name = zome_info()?.name;
+    let functions = GrantedFunctions::Listed(
+        DELEGATED_FUNCTIONS
+            .iter()
+            .map(|name| (zome_name.clone(), FunctionName::from(*name)))
+            .collect(),
+    );
+
+    let secret = generate_cap_secret()?;
+    let mut assignees = BTreeSet::new();
+    assignees.insert(input.grantee);
+
+    let grant_hash = create_cap_grant(CapGrantEntry {
+        tag: input.tag,
+        access: CapAccess::Assigned { secret, assignees },
+        functions,
+    })?;
+
+    Ok(IssuedCapability { grant_hash, secret })
+}
+
+/// Revoke a grant previously returned by `issue_capability`.
+#[hdk_extern]
+pub fn revoke_capability(grant_hash: ActionHash) -> ExternResult<ActionHash> {
+    delete_cap_grant(grant_hash)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StoreCapabilityClaimInput {
+    pub grantor: AgentPubKey,
+    pub secret: CapSecret,
+    pub tag: String,
+}
+
+/// Record a capability secret received from `grantor` so later calls on
+/// their behalf can present it.
+#[hdk_extern]
+pub fn store_capability_claim(input: StoreCapabilityClaimInput) -> ExternResult<ActionHash> {
+    create_cap_claim(CapClaimEntry::new(input.tag, input.grantor, input.secret))
+}
+
+fn find_cap_secret(grantor: &AgentPubKey) -> ExternResult<CapSecret> {
+    let records = query(
+        ChainQueryFilter::new()
+            .entry_type(EntryType::CapClaim)
+            .include_entries(true),
+    )?;
+
+    records
+        .into_iter()
+        .rev()
+        .find_map(|record| match record.entry().as_option() {
+            Some(Entry::CapClaim(claim)) if &claim.grantor == grantor => Some(claim.secret),
+            _ => None,
+        })
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!(
+            "No capability claim held for {}",
+            grantor
+        ))))
+}
+
+fn call_with_claim<I>(grantor: AgentPubKey, fn_name: &str, payload: I) -> ExternResult<EntryHash>
+where
+    I: Serialize + std::fmt::Debug,
+{
+    let secret = find_cap_secret(&grantor)?;
+    match call_remote(
+        grantor,
+        zome_info()?.name,
+        FunctionName::from(fn_name),
+        Some(secret),
+        payload,
+    )? {
+        ZomeCallResponse::Ok(io) => io
+            .decode()
+            .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e))),
+        ZomeCallResponse::Unauthorized(..) => Err(wasm_error!(WasmErrorInner::Guest(
+            "Capability was revoked or does not cover this call".to_string()
+        ))),
+        other => Err(wasm_error!(WasmErrorInner::Guest(format!(
+            "Remote call failed: {:?}",
+            other
+        )))),
+    }
+}
+
+/// Create a value flow on behalf of `value_flow.from`, using a capability
+/// they granted us.
+#[hdk_extern]
+pub fn create_value_flow_for(value_flow: ValueFlow) -> ExternResult<EntryHash> {
+    call_with_claim(value_flow.from.clone(), "create_value_flow", value_flow)
+}
+
+/// Create a reputation shift on behalf of `reputation_shift.agent`, using a
+/// capability they granted us.
+#[hdk_extern]
+pub fn create_reputation_shift_for(reputation_shift: ReputationShift) -> ExternResult<EntryHash> {
+    call_with_claim(
+        reputation_shift.agent.clone(),
+        "create_reputation_shift",
+        reputation_shift,
+    )
+}
+
+use ad4m_client::Ad4mClient;
 use anyhow::Result;
 use serde_json::json;
 
@@ -96,7 +620,7 @@ use serde_json::json;
 pub fn publish_reputation_shift(reputation_shift: ReputationShift) -> ExternResult<()> {
     let rt = tokio::runtime::Runtime::new().unwrap();
     rt.block_on(async {
-        let client = Client::new("http://localhost:4000").await.unwrap();
+        let client = Ad4mClient::new("http://localhost:4000".to_string(), String::new());
         let expression = json!({
             "author": reputation_shift.agent.to_string(),
             "timestamp": reputation_shift.timestamp,
@@ -108,15 +632,197 @@ pub fn publish_reputation_shift(reputation_shift: ReputationShift) -> ExternResu
             }
         });
         client
-            .expression()
-            .create(expression.to_string(), vec![])
+            .expressions
+            .expression_create("literal".to_string(), expression)
             .await
             .unwrap();
     });
     Ok(())
 }
 
+/// Check a value flow with the rules nodes share: distinct parties, utility
+/// bounds or a valid private utility proof, weight and reputation shift.
+pub fn validate_value_flow(value_flow: &ValueFlow) -> Result<(), String> {
+    value_flow.terms().validate()
+}
+
+/// Check a reputation shift's vector and context.
+pub fn validate_reputation_shift(reputation_shift: &ReputationShift) -> Result<(), String> {
+    if reputation_shift.context.trim().is_empty() {
+        return Err("Reputation shift must have a context".to_string());
+    }
+    validate_shift_vector(&reputation_shift.shift_vector)
+}
+
+fn to_callback_result(result: Result<(), String>) -> ValidateCallbackResult {
+    match result {
+        Ok(()) => ValidateCallbackResult::Valid,
+        Err(reason) => ValidateCallbackResult::Invalid(reason),
+    }
+}
+
+#[hdk_extern]
+pub fn validate(op: Op) -> ExternResult<ValidateCallbackResult> {
+    let (author, entry) = match op {
+        Op::StoreEntry(StoreEntry { action, entry }) => (action.hashed.author().clone(), entry),
+        _ => return Ok(ValidateCallbackResult::Valid),
+    };
+
+    if let Ok(value_flow) = ValueFlow::try_from(&entry) {
+        if author != value_flow.from {
+            return Ok(ValidateCallbackResult::Invalid(
+                "Value flows must be authored by the sending agent".to_string(),
+            ));
+        }
+        return Ok(to_callback_result(validate_value_flow(&value_flow)));
+    }
+    if let Ok(reputation_shift) = ReputationShift::try_from(&entry) {
+        if author != reputation_shift.agent {
+            return Ok(ValidateCallbackResult::Invalid(
+                "Reputation shifts must be authored by the agent they describe".to_string(),
+            ));
+        }
+        return Ok(to_callback_result(validate_reputation_shift(
+            &reputation_shift,
+        )));
+    }
+
+    Ok(ValidateCallbackResult::Valid)
+}
+
 #[hdk_extern]
 pub fn init(_: ()) -> ExternResult<InitCallbackResult> {
     Ok(InitCallbackResult::Pass)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rose_forest_value_flow::zkp::{UtilityBounds, ZKP};
+    use rose_forest_zome_testing::store_entry_op;
+
+    fn agent(byte: u8) -> AgentPubKey {
+        AgentPubKey::from_raw_36(vec![byte; 36])
+    }
+
+    fn flow(utility: f32) -> ValueFlow {
+        ValueFlow {
+            from: agent(1),
+            to: agent(2),
+            utility,
+            governance_weight: 0.5,
+            reputation_shift: vec![0.1, -0.1],
+            utility_proof: None,
+        }
+    }
+
+    fn shift(shift_vector: Vec<f32>) -> ReputationShift {
+        ReputationShift {
+            agent: agent(1),
+            context: "code_review".to_string(),
+            shift_vector,
+            description: "Reviewed a pull request".to_string(),
+            timestamp: 0,
+        }
+    }
+
+    fn rejected(op: Op) -> bool {
+        matches!(validate(op), Ok(ValidateCallbackResult::Invalid(_)))
+    }
+
+    #[test]
+    fn accepts_valid_value_flow() {
+        assert!(validate_value_flow(&flow(10.0)).is_ok());
+        assert!(validate_value_flow(&flow(MAX_FLOW_UTILITY)).is_ok());
+    }
+
+    #[test]
+    fn rejects_self_flow() {
+        let mut value_flow = flow(10.0);
+        value_flow.to = value_flow.from.clone();
+        assert!(validate_value_flow(&value_flow).is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_bounds_utility() {
+        assert!(validate_value_flow(&flow(0.0)).is_err());
+        assert!(validate_value_flow(&flow(-1.0)).is_err());
+        assert!(validate_value_flow(&flow(MAX_FLOW_UTILITY * 2.0)).is_err());
+        assert!(validate_value_flow(&flow(f32::NAN)).is_err());
+    }
+
+    #[test]
+    fn verifies_private_utility() {
+        let zkp = ZKP::new();
+        let (proof, _) = zkp.prove_utility(250.0, UtilityBounds::default()).unwrap();
+        let mut value_flow = flow(0.0);
+        value_flow.utility_proof = Some(proof.clone());
+        assert!(validate_value_flow(&value_flow).is_ok());
+
+        value_flow.utility = 250.0;
+        assert!(validate_value_flow(&value_flow).is_err());
+
+        // A proof against other bounds doesn't verify
+        value_flow.utility = 0.0;
+        let other_bounds = UtilityBounds::new(0.0, MAX_FLOW_UTILITY).unwrap();
+        value_flow.utility_proof = Some(zkp.prove_utility(250.0, other_bounds).unwrap().0);
+        assert!(validate_value_flow(&value_flow).is_err());
+
+        let mut forged = proof;
+        forged.commitment[0] ^= 1;
+        value_flow.utility_proof = Some(forged);
+        assert!(validate_value_flow(&value_flow).is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_bounds_governance_weight() {
+        let mut value_flow = flow(10.0);
+        value_flow.governance_weight = 1.5;
+        assert!(validate_value_flow(&value_flow).is_err());
+    }
+
+    #[test]
+    fn checks_reputation_shift_vector_length() {
+        assert!(validate_reputation_shift(&shift(vec![0.2])).is_ok());
+        assert!(validate_reputation_shift(&shift(vec![])).is_err());
+        assert!(validate_reputation_shift(&shift(vec![0.0; MAX_REPUTATION_DIMENSIONS + 1])).is_err());
+        assert!(validate_reputation_shift(&shift(vec![f32::INFINITY])).is_err());
+    }
+
+    #[test]
+    fn rejects_reputation_shift_without_context() {
+        let mut reputation_shift = shift(vec![0.2]);
+        reputation_shift.context = "  ".to_string();
+        assert!(validate_reputation_shift(&reputation_shift).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_invalid_value_flow_ops() {
+        assert_eq!(
+            validate(store_entry_op(agent(1), flow(10.0))).unwrap(),
+            ValidateCallbackResult::Valid
+        );
+        assert!(rejected(store_entry_op(agent(1), flow(0.0))));
+        assert!(rejected(store_entry_op(agent(1), flow(f32::NAN))));
+
+        // Only the sender may publish a flow
+        assert!(rejected(store_entry_op(agent(2), flow(10.0))));
+    }
+
+    #[test]
+    fn validate_rejects_invalid_reputation_shift_ops() {
+        assert_eq!(
+            validate(store_entry_op(agent(1), shift(vec![0.2]))).unwrap(),
+            ValidateCallbackResult::Valid
+        );
+        assert!(rejected(store_entry_op(agent(1), shift(vec![]))));
+        assert!(rejected(store_entry_op(agent(1), shift(vec![f32::INFINITY]))));
+
+        let mut without_context = shift(vec![0.2]);
+        without_context.context = String::new();
+        assert!(rejected(store_entry_op(agent(1), without_context)));
+
+        // Agents can't publish shifts about someone else
+        assert!(rejected(store_entry_op(agent(2), shift(vec![0.2]))));
+    }
+}
@@ -1,14 +1,20 @@
-use ad4m_client::Client;
+use ad4m_client::Ad4mClient as Client;
 use anyhow::Result;
 
-#[derive(Debug)]
+#[allow(dead_code)]
 pub struct Ad4mManager {
     client: Client,
 }
 
+impl std::fmt::Debug for Ad4mManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Ad4mManager")
+    }
+}
+
 impl Ad4mManager {
     pub async fn new() -> Result<Self> {
-        let client = Client::new("http://localhost:4000").await?;
+        let client = Client::new("http://localhost:4000".into(), String::new());
         Ok(Self { client })
     }
 }
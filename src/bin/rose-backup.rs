@@ -0,0 +1,131 @@
+//! Take, list, verify and restore backups of a data directory (see
+//! `sharding::backup`). The first backup in a backup directory must be
+//! full; `--incremental` ones hold only what changed since the latest.
+//! Back up a sled directory only while no server has it open.
+//!
+//! ```text
+//! rose-backup create --data-dir <dir> --backup-dir <dir> [--engine file|sled] [--incremental]
+//! rose-backup list --backup-dir <dir>
+//! rose-backup verify --backup-dir <dir> [--id N]
+//! rose-backup restore --backup-dir <dir> --data-dir <dir> [--engine file|sled] [--id N]
+//! ```
+//!
+//! `verify` and `restore` default to the latest backup; `verify` exits with
+//! an error when anything in the chain is damaged or missing.
+
+use amazon_rose_forest::sharding::backup::{BackupKind, BackupStore};
+use amazon_rose_forest::sharding::storage::{PersistenceConfig, StorageEngine};
+
+use anyhow::{anyhow, Result};
+
+const USAGE: &str = "usage: rose-backup create --data-dir <dir> --backup-dir <dir> \
+                     [--engine file|sled] [--incremental]\n       \
+                     rose-backup list --backup-dir <dir>\n       \
+                     rose-backup verify --backup-dir <dir> [--id N]\n       \
+                     rose-backup restore --backup-dir <dir> --data-dir <dir> \
+                     [--engine file|sled] [--id N]";
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> Result<Option<String>> {
+    match args.iter().position(|a| a == flag) {
+        Some(i) if i + 1 < args.len() => {
+            let value = args.remove(i + 1);
+            args.remove(i);
+            Ok(Some(value))
+        }
+        Some(_) => Err(anyhow!("{} requires a value", flag)),
+        None => Ok(None),
+    }
+}
+
+fn take_switch(args: &mut Vec<String>, flag: &str) -> bool {
+    match args.iter().position(|a| a == flag) {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    }
+}
+
+fn required(args: &mut Vec<String>, flag: &str) -> Result<String> {
+    take_flag(args, flag)?.ok_or_else(|| anyhow!("{} is required\n{}", flag, USAGE))
+}
+
+fn persistence(args: &mut Vec<String>) -> Result<PersistenceConfig> {
+    let data_dir = required(args, "--data-dir")?;
+    let engine: StorageEngine = match take_flag(args, "--engine")? {
+        Some(engine) => engine.parse()?,
+        None => StorageEngine::File,
+    };
+    Ok(PersistenceConfig::new(engine, data_dir))
+}
+
+/// Fail on anything left over once a command has taken its flags
+fn finish_args(args: &[String]) -> Result<()> {
+    if args.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("Unexpected arguments {:?}\n{}", args, USAGE))
+    }
+}
+
+/// The backup named by `--id`, or the latest one
+fn backup_id(args: &mut Vec<String>, backups: &BackupStore) -> Result<u64> {
+    match take_flag(args, "--id")? {
+        Some(id) => Ok(id.parse()?),
+        None => backups
+            .latest()?
+            .map(|manifest| manifest.id)
+            .ok_or_else(|| anyhow!("No backups found")),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        return Err(anyhow!(USAGE));
+    }
+    let command = args.remove(0);
+    let backups = BackupStore::open(required(&mut args, "--backup-dir")?)?;
+
+    let output = match command.as_str() {
+        "create" => {
+            let persistence = persistence(&mut args)?;
+            let kind = if take_switch(&mut args, "--incremental") {
+                BackupKind::Incremental
+            } else {
+                BackupKind::Full
+            };
+            finish_args(&args)?;
+            let storage = persistence.open()?;
+            serde_json::to_string_pretty(&backups.create(storage.as_ref(), kind).await?)?
+        }
+        "list" => {
+            finish_args(&args)?;
+            serde_json::to_string_pretty(&backups.list()?)?
+        }
+        "verify" => {
+            let id = backup_id(&mut args, &backups)?;
+            finish_args(&args)?;
+            let report = backups.verify(id);
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            if !report.is_ok() {
+                return Err(anyhow!("Backup {} failed verification", report.backup));
+            }
+            return Ok(());
+        }
+        "restore" => {
+            let id = backup_id(&mut args, &backups)?;
+            let persistence = persistence(&mut args)?;
+            finish_args(&args)?;
+            let storage = persistence.open()?;
+            serde_json::to_string_pretty(&backups.restore(id, storage.as_ref()).await?)?
+        }
+        _ => return Err(anyhow!(USAGE)),
+    };
+    println!("{}", output);
+    Ok(())
+}
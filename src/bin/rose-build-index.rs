@@ -0,0 +1,78 @@
+//! Build a shard offline from a file of precomputed embeddings and write it
+//! to a data directory, for a server started with `ROSE_FOREST_DATA_DIR`
+//! pointing there to restore. Use `POST /api/indexes/build` to build on a
+//! running server instead; a sled directory can't be shared with one.
+//!
+//! ```text
+//! rose-build-index --data-dir <dir> --name <shard> [--metric cosine] <file.npy|.safetensors|.parquet>
+//! rose-build-index --data-dir ... --name ... [--engine file|sled] [--index NAME]
+//!                  [--format npy|safetensors|parquet] [--tensor NAME] [--threads N] <file>
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+
+use amazon_rose_forest::connectors::read_embeddings;
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::server::api::parse_distance_metric;
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::storage::{PersistenceConfig, StorageEngine};
+
+use anyhow::{anyhow, Result};
+
+const USAGE: &str = "usage: rose-build-index --data-dir <dir> --name <shard> [--engine file|sled] \
+                     [--index NAME] [--metric cosine] [--format FORMAT] [--tensor NAME] \
+                     [--threads N] <file>";
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> Result<Option<String>> {
+    match args.iter().position(|a| a == flag) {
+        Some(i) if i + 1 < args.len() => {
+            let value = args.remove(i + 1);
+            args.remove(i);
+            Ok(Some(value))
+        }
+        Some(_) => Err(anyhow!("{} requires a value", flag)),
+        None => Ok(None),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let data_dir = take_flag(&mut args, "--data-dir")?
+        .ok_or_else(|| anyhow!("--data-dir is required\n{}", USAGE))?;
+    let name =
+        take_flag(&mut args, "--name")?.ok_or_else(|| anyhow!("--name is required\n{}", USAGE))?;
+    let engine: StorageEngine = match take_flag(&mut args, "--engine")? {
+        Some(engine) => engine.parse()?,
+        None => StorageEngine::File,
+    };
+    let index_name = take_flag(&mut args, "--index")?.unwrap_or_else(|| "main".into());
+    let metric = parse_distance_metric(
+        &take_flag(&mut args, "--metric")?.unwrap_or_else(|| "cosine".into()),
+    )
+    .map_err(|e| anyhow!(e))?;
+    let format = take_flag(&mut args, "--format")?
+        .map(|format| format.parse())
+        .transpose()?;
+    let tensor = take_flag(&mut args, "--tensor")?;
+    let threads = take_flag(&mut args, "--threads")?
+        .map(|n| n.parse())
+        .transpose()?;
+    let [path] = args.as_slice() else {
+        return Err(anyhow!(USAGE));
+    };
+
+    let embeddings = read_embeddings(Path::new(path), format, tensor.as_deref())?;
+    let storage = PersistenceConfig::new(engine, data_dir).open()?;
+    let manager = ShardManager::new(Arc::new(MetricsCollector::new())).with_storage(storage);
+    let build = manager
+        .build_index(&name, &index_name, metric, embeddings, threads)
+        .await?;
+    manager.flush().await?;
+
+    println!("{}", serde_json::to_string_pretty(&build)?);
+    Ok(())
+}
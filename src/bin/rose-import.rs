@@ -0,0 +1,148 @@
+//! Import vectors from Qdrant, pgvector or a FAISS index file into a shard
+//! on a running Amazon Rose Forest server.
+//!
+//! ```text
+//! rose-import --server http://127.0.0.1:9000/api --shard <uuid> faiss <path>
+//! rose-import --server ... --shard <uuid> qdrant <url> <collection> [--api-key KEY] [--vector NAME]
+//! rose-import --server ... --shard <uuid> pgvector <connection> <table> [--id-column C] [--vector-column C]
+//! ```
+//!
+//! `--api-key` is Qdrant's; set `ROSE_FOREST_API_KEY` for servers that
+//! require authentication.
+
+use amazon_rose_forest::connectors::{ImportSummary, SourceConfig, DEFAULT_BATCH_SIZE};
+use amazon_rose_forest::server::api::AddVectorRequest;
+use amazon_rose_forest::server::auth::API_KEY_HEADER;
+
+use anyhow::{anyhow, Result};
+use uuid::Uuid;
+
+const USAGE: &str = "usage: rose-import --server <api-url> --shard <uuid> [--batch-size N] \
+                     <faiss|qdrant|pgvector> <args...>";
+
+struct Args {
+    server: String,
+    shard_id: Uuid,
+    batch_size: usize,
+    source: SourceConfig,
+}
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> Result<Option<String>> {
+    match args.iter().position(|a| a == flag) {
+        Some(i) if i + 1 < args.len() => {
+            let value = args.remove(i + 1);
+            args.remove(i);
+            Ok(Some(value))
+        }
+        Some(_) => Err(anyhow!("{} requires a value", flag)),
+        None => Ok(None),
+    }
+}
+
+fn parse_args(mut args: Vec<String>) -> Result<Args> {
+    let server = take_flag(&mut args, "--server")?
+        .unwrap_or_else(|| "http://127.0.0.1:9000/api".to_string());
+    let shard_id = take_flag(&mut args, "--shard")?
+        .ok_or_else(|| anyhow!("--shard is required\n{}", USAGE))?
+        .parse::<Uuid>()?;
+    let batch_size = match take_flag(&mut args, "--batch-size")? {
+        Some(n) => n.parse()?,
+        None => DEFAULT_BATCH_SIZE,
+    };
+
+    let source = match args.first().map(String::as_str) {
+        Some("faiss") if args.len() == 2 => SourceConfig::Faiss {
+            path: args[1].clone(),
+        },
+        Some("qdrant") => {
+            let api_key = take_flag(&mut args, "--api-key")?;
+            let vector_name = take_flag(&mut args, "--vector")?;
+            if args.len() != 3 {
+                return Err(anyhow!(USAGE));
+            }
+            SourceConfig::Qdrant {
+                url: args[1].clone(),
+                collection: args[2].clone(),
+                api_key,
+                vector_name,
+            }
+        }
+        Some("pgvector") => {
+            let id_column = take_flag(&mut args, "--id-column")?.unwrap_or_else(|| "id".into());
+            let vector_column =
+                take_flag(&mut args, "--vector-column")?.unwrap_or_else(|| "embedding".into());
+            if args.len() != 3 {
+                return Err(anyhow!(USAGE));
+            }
+            SourceConfig::Pgvector {
+                connection_string: args[1].clone(),
+                table: args[2].clone(),
+                id_column,
+                vector_column,
+            }
+        }
+        _ => return Err(anyhow!(USAGE)),
+    };
+
+    Ok(Args {
+        server: server.trim_end_matches('/').to_string(),
+        shard_id,
+        batch_size,
+        source,
+    })
+}
+
+/// HTTP client sending `ROSE_FOREST_API_KEY`, when set, as the API key
+fn client() -> Result<reqwest::Client> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Ok(key) = std::env::var("ROSE_FOREST_API_KEY") {
+        let mut key = reqwest::header::HeaderValue::from_str(&key)
+            .map_err(|_| anyhow!("ROSE_FOREST_API_KEY can't be sent as a header value"))?;
+        key.set_sensitive(true);
+        headers.insert(API_KEY_HEADER, key);
+    }
+    Ok(reqwest::Client::builder()
+        .default_headers(headers)
+        .build()?)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let args = parse_args(std::env::args().skip(1).collect())?;
+    let client = client()?;
+    let mut source = args.source.open().await?;
+
+    let mut summary = ImportSummary {
+        source: source.name().to_string(),
+        ..Default::default()
+    };
+
+    while let Some(batch) = source.next_batch(args.batch_size).await? {
+        summary.batches += 1;
+        for record in batch {
+            let mut metadata = record.metadata;
+            metadata.insert("source_id".to_string(), record.source_id);
+            let request = AddVectorRequest {
+                shard_id: args.shard_id,
+                vector: record.values,
+                metadata: Some(metadata),
+            };
+            let response = client
+                .post(format!("{}/vectors", args.server))
+                .json(&request)
+                .send()
+                .await?;
+            if response.status().is_success() {
+                summary.imported += 1;
+            } else {
+                tracing::warn!("Server rejected record: {}", response.text().await?);
+                summary.failed += 1;
+            }
+        }
+    }
+
+    println!("{}", serde_json::to_string_pretty(&summary)?);
+    Ok(())
+}
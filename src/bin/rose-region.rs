@@ -0,0 +1,63 @@
+//! Inspect and control multi-region replication on a running server.
+//!
+//! ```text
+//! rose-region [--server http://127.0.0.1:9000/api] status
+//! rose-region [--server ...] promote
+//! rose-region [--server ...] demote
+//! ```
+//!
+//! Set `ROSE_FOREST_API_KEY` for servers that require authentication.
+
+use amazon_rose_forest::server::auth::API_KEY_HEADER;
+
+use anyhow::{anyhow, Result};
+
+const USAGE: &str = "usage: rose-region [--server <api-url>] <status|promote|demote>";
+
+/// HTTP client sending `ROSE_FOREST_API_KEY`, when set, as the API key
+fn client() -> Result<reqwest::Client> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Ok(key) = std::env::var("ROSE_FOREST_API_KEY") {
+        let mut key = reqwest::header::HeaderValue::from_str(&key)
+            .map_err(|_| anyhow!("ROSE_FOREST_API_KEY can't be sent as a header value"))?;
+        key.set_sensitive(true);
+        headers.insert(API_KEY_HEADER, key);
+    }
+    Ok(reqwest::Client::builder()
+        .default_headers(headers)
+        .build()?)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let server = match args.iter().position(|a| a == "--server") {
+        Some(i) if i + 1 < args.len() => {
+            let server = args.remove(i + 1);
+            args.remove(i);
+            server
+        }
+        Some(_) => return Err(anyhow!(USAGE)),
+        None => "http://127.0.0.1:9000/api".to_string(),
+    };
+    let server = server.trim_end_matches('/');
+
+    let client = client()?;
+    let request = match args.as_slice() {
+        [command] if command == "status" => client.get(format!("{}/replication/status", server)),
+        [command] if command == "promote" || command == "demote" => {
+            client.post(format!("{}/replication/{}", server, command))
+        }
+        _ => return Err(anyhow!(USAGE)),
+    };
+
+    let response = request.send().await?;
+    let status = response.status();
+    let body: serde_json::Value = response.json().await?;
+    println!("{}", serde_json::to_string_pretty(&body)?);
+
+    if !status.is_success() {
+        return Err(anyhow!("Server returned {}", status));
+    }
+    Ok(())
+}
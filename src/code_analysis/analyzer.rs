@@ -0,0 +1,334 @@
+//! Lightweight per-file analysis: symbols and approximate complexity.
+//!
+//! This is a line-oriented scanner rather than a parser. It recognizes
+//! declarations by their leading keywords, finds their extent by brace depth
+//! (or indentation for Python), and estimates cyclomatic complexity by
+//! counting decision points. Strings and comments containing braces or
+//! keywords can skew the numbers; they're meant for ranking hotspots, not
+//! for exact measurement.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Languages the scanner understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Go,
+    /// Counted for lines only
+    Other,
+}
+
+impl Language {
+    pub fn from_extension(extension: &str) -> Self {
+        match extension {
+            "rs" => Language::Rust,
+            "py" => Language::Python,
+            "js" | "jsx" | "mjs" | "cjs" => Language::JavaScript,
+            "ts" | "tsx" => Language::TypeScript,
+            "go" => Language::Go,
+            _ => Language::Other,
+        }
+    }
+
+    fn line_comment(&self) -> &'static str {
+        match self {
+            Language::Python => "#",
+            _ => "//",
+        }
+    }
+
+    /// Keywords that each add a path through a function
+    fn decision_keywords(&self) -> &'static [&'static str] {
+        match self {
+            Language::Rust => &["if", "while", "for", "loop"],
+            Language::Python => &["if", "elif", "while", "for", "except", "and", "or"],
+            Language::JavaScript | Language::TypeScript => &["if", "while", "for", "case", "catch"],
+            Language::Go => &["if", "for", "case"],
+            Language::Other => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Function,
+    Struct,
+    Enum,
+    Trait,
+    Impl,
+    Module,
+    Class,
+    Interface,
+    Type,
+    Const,
+}
+
+/// A declaration found in a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub path: String,
+    /// First and last line of the declaration, 1-based
+    pub line: usize,
+    pub end_line: usize,
+    /// Approximate cyclomatic complexity; 0 for non-functions
+    pub complexity: u32,
+}
+
+/// Everything known about one file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileAnalysis {
+    pub path: String,
+    pub language: Language,
+    pub lines: usize,
+    /// Lines that aren't blank or comments
+    pub code_lines: usize,
+    pub symbols: Vec<Symbol>,
+    /// SHA-256 of the content, used to skip unchanged files
+    pub content_hash: String,
+    pub analyzed_at: DateTime<Utc>,
+}
+
+impl FileAnalysis {
+    pub fn functions(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols
+            .iter()
+            .filter(|s| s.kind == SymbolKind::Function)
+    }
+
+    /// Sum of function complexities
+    pub fn total_complexity(&self) -> u32 {
+        self.functions().map(|s| s.complexity).sum()
+    }
+
+    pub fn max_complexity(&self) -> u32 {
+        self.functions().map(|s| s.complexity).max().unwrap_or(0)
+    }
+}
+
+pub fn content_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Analyze a file's content; the language is taken from the path's extension
+pub fn analyze_file(path: &str, content: &str) -> FileAnalysis {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+    let language = Language::from_extension(extension);
+    let lines: Vec<&str> = content.lines().collect();
+    let code: Vec<String> = lines
+        .iter()
+        .map(|line| strip_comment(line, language))
+        .collect();
+
+    let mut symbols = Vec::new();
+    if language != Language::Other {
+        for (index, line) in code.iter().enumerate() {
+            let Some((kind, name)) = declaration(line, language) else {
+                continue;
+            };
+            let end = if language == Language::Python {
+                indented_block_end(&lines, index)
+            } else {
+                braced_block_end(&code, index)
+            };
+            let complexity = if kind == SymbolKind::Function {
+                1 + decision_points(&code[index..=end], language)
+            } else {
+                0
+            };
+            symbols.push(Symbol {
+                name,
+                kind,
+                path: path.to_string(),
+                line: index + 1,
+                end_line: end + 1,
+                complexity,
+            });
+        }
+    }
+
+    FileAnalysis {
+        path: path.to_string(),
+        language,
+        lines: lines.len(),
+        code_lines: code.iter().filter(|l| !l.trim().is_empty()).count(),
+        symbols,
+        content_hash: content_hash(content),
+        analyzed_at: Utc::now(),
+    }
+}
+
+/// Drop line comments and block-comment continuation lines
+fn strip_comment(line: &str, language: Language) -> String {
+    let trimmed = line.trim_start();
+    if language != Language::Python
+        && (trimmed.starts_with("/*") || trimmed.starts_with("* ") || trimmed == "*")
+    {
+        return String::new();
+    }
+    match line.find(language.line_comment()) {
+        Some(at) => line[..at].to_string(),
+        None => line.to_string(),
+    }
+}
+
+/// Leading modifiers that can precede a declaration keyword
+const MODIFIERS: &[&str] = &[
+    "pub",
+    "pub(crate)",
+    "pub(super)",
+    "async",
+    "unsafe",
+    "extern",
+    "export",
+    "default",
+    "abstract",
+    "declare",
+];
+
+fn identifier(text: &str) -> Option<String> {
+    let name: String = text
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Recognize a declaration at the start of a line
+fn declaration(line: &str, language: Language) -> Option<(SymbolKind, String)> {
+    let mut words = line.split_whitespace().peekable();
+    while words.peek().is_some_and(|w| MODIFIERS.contains(w)) {
+        words.next();
+    }
+    let keyword = words.next()?;
+    let rest: String = words.collect::<Vec<_>>().join(" ");
+
+    match (language, keyword) {
+        (Language::Rust, "fn") => Some((SymbolKind::Function, identifier(&rest)?)),
+        (Language::Rust, "const") if rest.starts_with("fn ") => {
+            Some((SymbolKind::Function, identifier(&rest[3..])?))
+        }
+        (Language::Rust, "struct") => Some((SymbolKind::Struct, identifier(&rest)?)),
+        (Language::Rust, "enum") => Some((SymbolKind::Enum, identifier(&rest)?)),
+        (Language::Rust, "trait") => Some((SymbolKind::Trait, identifier(&rest)?)),
+        (Language::Rust, "mod") => Some((SymbolKind::Module, identifier(&rest)?)),
+        (Language::Rust, "type") => Some((SymbolKind::Type, identifier(&rest)?)),
+        (Language::Rust, "const" | "static") => Some((SymbolKind::Const, identifier(&rest)?)),
+        (Language::Rust, "impl") => {
+            let target = rest.split('{').next()?.trim();
+            let target = target.split(" where ").next()?.trim();
+            (!target.is_empty()).then(|| (SymbolKind::Impl, target.to_string()))
+        }
+        (Language::Python, "def") => Some((SymbolKind::Function, identifier(&rest)?)),
+        (Language::Python, "class") => Some((SymbolKind::Class, identifier(&rest)?)),
+        (Language::JavaScript | Language::TypeScript, "function" | "function*") => {
+            Some((SymbolKind::Function, identifier(&rest)?))
+        }
+        (Language::JavaScript | Language::TypeScript, "class") => {
+            Some((SymbolKind::Class, identifier(&rest)?))
+        }
+        (Language::TypeScript, "interface") => Some((SymbolKind::Interface, identifier(&rest)?)),
+        (Language::TypeScript, "type") => Some((SymbolKind::Type, identifier(&rest)?)),
+        (Language::JavaScript | Language::TypeScript, "const" | "let") if rest.contains("=>") => {
+            Some((SymbolKind::Function, identifier(&rest)?))
+        }
+        (Language::Go, "func") => {
+            // Methods name their receiver first: func (s *Server) Start()
+            let rest = match rest.strip_prefix('(') {
+                Some(receiver) => receiver.split_once(')')?.1,
+                None => rest.as_str(),
+            };
+            Some((SymbolKind::Function, identifier(rest)?))
+        }
+        (Language::Go, "type") => {
+            let name = identifier(&rest)?;
+            let kind = if rest.contains(" interface") {
+                SymbolKind::Interface
+            } else if rest.contains(" struct") {
+                SymbolKind::Struct
+            } else {
+                SymbolKind::Type
+            };
+            Some((kind, name))
+        }
+        _ => None,
+    }
+}
+
+/// Last line of a brace-delimited declaration starting at `start`
+fn braced_block_end(code: &[String], start: usize) -> usize {
+    let mut depth = 0i32;
+    let mut opened = false;
+    for (index, line) in code.iter().enumerate().skip(start) {
+        for c in line.chars() {
+            match c {
+                '{' => {
+                    depth += 1;
+                    opened = true;
+                }
+                '}' => depth -= 1,
+                ';' if !opened && depth == 0 => return index,
+                _ => {}
+            }
+        }
+        if opened && depth <= 0 {
+            return index;
+        }
+    }
+    if opened {
+        code.len() - 1
+    } else {
+        start
+    }
+}
+
+/// Last line of an indentation-delimited block starting at `start`
+fn indented_block_end(lines: &[&str], start: usize) -> usize {
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let base = indent(lines[start]);
+    let mut end = start;
+    for (index, line) in lines.iter().enumerate().skip(start + 1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if indent(line) <= base {
+            break;
+        }
+        end = index;
+    }
+    end
+}
+
+fn decision_points(body: &[String], language: Language) -> u32 {
+    let keywords = language.decision_keywords();
+    let mut points = 0;
+    for line in body {
+        points += line
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|word| keywords.contains(word))
+            .count();
+        if language != Language::Python {
+            points += line.matches("&&").count() + line.matches("||").count();
+        }
+        // Every match arm is another path
+        if language == Language::Rust && line.contains("=>") {
+            points += 1;
+        }
+    }
+    points as u32
+}
@@ -0,0 +1,365 @@
+//! Long-running analysis of a repository.
+//!
+//! The daemon scans the repository once, then watches it and re-analyzes
+//! only the files that change, keeping an in-memory symbol and complexity
+//! database. Darwin queries the database each cycle instead of re-reading
+//! every file.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::code_analysis::analyzer::{self, FileAnalysis, Symbol, SymbolKind};
+use crate::core::metrics::MetricsCollector;
+use crate::darwin::tools::SKIPPED_DIRS;
+use crate::nerv::tasks;
+
+#[derive(Debug, Clone)]
+pub struct AnalysisDaemonConfig {
+    /// File extensions to analyze
+    pub extensions: Vec<String>,
+    /// Files larger than this are skipped
+    pub max_file_bytes: u64,
+    /// How long to wait for a burst of file events to settle
+    pub debounce: Duration,
+}
+
+impl Default for AnalysisDaemonConfig {
+    fn default() -> Self {
+        Self {
+            extensions: ["rs", "py", "js", "jsx", "ts", "tsx", "go"]
+                .iter()
+                .map(|e| e.to_string())
+                .collect(),
+            max_file_bytes: 512 * 1024,
+            debounce: Duration::from_millis(200),
+        }
+    }
+}
+
+/// Outcome of a full scan
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScanSummary {
+    pub analyzed: usize,
+    /// Files whose content hadn't changed since they were last analyzed
+    pub unchanged: usize,
+    /// Files dropped because they no longer exist
+    pub removed: usize,
+}
+
+/// Repository-wide totals
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisSummary {
+    pub files: usize,
+    pub code_lines: usize,
+    pub symbols: usize,
+    pub functions: usize,
+    pub avg_complexity: f32,
+    pub max_complexity: u32,
+}
+
+/// Analyses by repository-relative path
+#[derive(Debug, Default)]
+struct SymbolDatabase {
+    files: HashMap<String, FileAnalysis>,
+}
+
+/// Watches a repository and keeps its analysis current
+pub struct AnalysisDaemon {
+    root: PathBuf,
+    config: AnalysisDaemonConfig,
+    metrics: Arc<MetricsCollector>,
+    database: RwLock<SymbolDatabase>,
+}
+
+impl std::fmt::Debug for AnalysisDaemon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnalysisDaemon")
+            .field("root", &self.root)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl AnalysisDaemon {
+    pub fn new(root: impl Into<PathBuf>, metrics: Arc<MetricsCollector>) -> Self {
+        // Watch events carry canonical paths
+        let root = root.into();
+        let root = root.canonicalize().unwrap_or(root);
+        Self {
+            root,
+            config: AnalysisDaemonConfig::default(),
+            metrics,
+            database: RwLock::new(SymbolDatabase::default()),
+        }
+    }
+
+    pub fn with_config(mut self, config: AnalysisDaemonConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    fn is_tracked(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let skipped = relative.components().any(|c| {
+            SKIPPED_DIRS
+                .iter()
+                .any(|skip| c.as_os_str() == std::ffi::OsStr::new(skip))
+        });
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        !skipped && self.config.extensions.iter().any(|e| e == extension)
+    }
+
+    fn collect_files(&self, dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+        for entry in std::fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                let name = entry.file_name();
+                if !SKIPPED_DIRS.iter().any(|skip| name == *skip) {
+                    self.collect_files(&path, paths)?;
+                }
+            } else if file_type.is_file() && self.is_tracked(&path) {
+                paths.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Analyze every tracked file, skipping ones whose content is unchanged,
+    /// and forget files that have disappeared
+    pub async fn scan(&self) -> Result<ScanSummary> {
+        let mut paths = Vec::new();
+        self.collect_files(&self.root, &mut paths)?;
+
+        let mut summary = ScanSummary::default();
+        let mut seen = HashSet::new();
+        for path in paths {
+            seen.insert(self.relative(&path));
+            match self.refresh_path(&path).await {
+                Ok(true) => summary.analyzed += 1,
+                Ok(false) => summary.unchanged += 1,
+                Err(e) => debug!("Skipping {}: {}", path.display(), e),
+            }
+        }
+
+        let mut database = self.database.write().await;
+        let before = database.files.len();
+        database.files.retain(|path, _| seen.contains(path));
+        summary.removed = before - database.files.len();
+        drop(database);
+
+        self.record_gauges().await;
+        info!(
+            "Scanned {}: {} analyzed, {} unchanged, {} removed",
+            self.root.display(),
+            summary.analyzed,
+            summary.unchanged,
+            summary.removed
+        );
+        Ok(summary)
+    }
+
+    /// Re-analyze one file after it changed, or drop it if it was deleted.
+    /// Returns whether the database changed.
+    pub async fn refresh_path(&self, path: &Path) -> Result<bool> {
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root.join(path)
+        };
+        if !self.is_tracked(&path) {
+            return Ok(false);
+        }
+        let relative = self.relative(&path);
+
+        let content = match tokio::fs::metadata(&path).await {
+            Ok(meta) if meta.is_file() && meta.len() <= self.config.max_file_bytes => {
+                tokio::fs::read_to_string(&path).await?
+            }
+            Ok(_) => return Ok(false),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let removed = self.database.write().await.files.remove(&relative);
+                return Ok(removed.is_some());
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let hash = analyzer::content_hash(&content);
+        if self
+            .database
+            .read()
+            .await
+            .files
+            .get(&relative)
+            .is_some_and(|existing| existing.content_hash == hash)
+        {
+            return Ok(false);
+        }
+
+        let analysis = analyzer::analyze_file(&relative, &content);
+        self.database.write().await.files.insert(relative, analysis);
+        self.metrics
+            .increment_counter("code_analysis.files_analyzed", 1)
+            .await;
+        Ok(true)
+    }
+
+    async fn record_gauges(&self) {
+        let summary = self.summary().await;
+        self.metrics
+            .set_gauge("code_analysis.files", summary.files as u64)
+            .await;
+        self.metrics
+            .set_gauge("code_analysis.symbols", summary.symbols as u64)
+            .await;
+    }
+
+    /// Analysis of one file, by repository-relative path
+    pub async fn file(&self, path: &str) -> Option<FileAnalysis> {
+        self.database.read().await.files.get(path).cloned()
+    }
+
+    /// Declarations with an exact name, across the repository
+    pub async fn find_symbol(&self, name: &str) -> Vec<Symbol> {
+        let database = self.database.read().await;
+        let mut found: Vec<Symbol> = database
+            .files
+            .values()
+            .flat_map(|f| f.symbols.iter())
+            .filter(|s| s.name == name)
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+        found
+    }
+
+    /// The most complex functions, most complex first
+    pub async fn hotspots(&self, limit: usize) -> Vec<Symbol> {
+        let database = self.database.read().await;
+        let mut functions: Vec<Symbol> = database
+            .files
+            .values()
+            .flat_map(|f| f.functions())
+            .cloned()
+            .collect();
+        functions.sort_by(|a, b| {
+            b.complexity
+                .cmp(&a.complexity)
+                .then_with(|| a.path.cmp(&b.path))
+                .then(a.line.cmp(&b.line))
+        });
+        functions.truncate(limit);
+        functions
+    }
+
+    pub async fn summary(&self) -> AnalysisSummary {
+        let database = self.database.read().await;
+        let mut summary = AnalysisSummary {
+            files: database.files.len(),
+            ..Default::default()
+        };
+        let mut total_complexity = 0u64;
+        for file in database.files.values() {
+            summary.code_lines += file.code_lines;
+            summary.symbols += file.symbols.len();
+            for function in file.functions() {
+                summary.functions += 1;
+                total_complexity += function.complexity as u64;
+                summary.max_complexity = summary.max_complexity.max(function.complexity);
+            }
+        }
+        if summary.functions > 0 {
+            summary.avg_complexity = total_complexity as f32 / summary.functions as f32;
+        }
+        summary
+    }
+
+    /// Repository metrics in the shape the hypothesis engine consumes
+    pub async fn metrics(&self) -> HashMap<String, f32> {
+        let summary = self.summary().await;
+        let types = {
+            let database = self.database.read().await;
+            database
+                .files
+                .values()
+                .flat_map(|f| f.symbols.iter())
+                .filter(|s| matches!(s.kind, SymbolKind::Struct | SymbolKind::Class))
+                .count()
+        };
+        HashMap::from([
+            ("cyclomatic_complexity".to_string(), summary.avg_complexity),
+            (
+                "max_cyclomatic_complexity".to_string(),
+                summary.max_complexity as f32,
+            ),
+            ("files".to_string(), summary.files as f32),
+            ("code_lines".to_string(), summary.code_lines as f32),
+            ("functions".to_string(), summary.functions as f32),
+            ("types".to_string(), types as f32),
+        ])
+    }
+
+    /// Scan once, then watch the repository and re-analyze files as they
+    /// change. Events are debounced so a burst of saves is handled together.
+    pub async fn start(self: Arc<Self>) -> Result<JoinHandle<()>> {
+        use notify::Watcher;
+
+        self.scan().await?;
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) => {
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+                Err(e) => warn!("File watch error: {}", e),
+            })?;
+        watcher.watch(&self.root, notify::RecursiveMode::Recursive)?;
+        info!("Watching {} for changes", self.root.display());
+
+        Ok(tasks::spawn("code_analysis", "file watcher", async move {
+            // Dropping the watcher would stop events
+            let _watcher = watcher;
+            while let Some(first) = rx.recv().await {
+                tokio::time::sleep(self.config.debounce).await;
+                let mut changed = HashSet::from([first]);
+                while let Ok(path) = rx.try_recv() {
+                    changed.insert(path);
+                }
+
+                let mut refreshed = 0;
+                for path in changed {
+                    match self.refresh_path(&path).await {
+                        Ok(true) => refreshed += 1,
+                        Ok(false) => {}
+                        Err(e) => debug!("Failed to analyze {}: {}", path.display(), e),
+                    }
+                }
+                if refreshed > 0 {
+                    debug!("Re-analyzed {} changed files", refreshed);
+                    self.record_gauges().await;
+                }
+            }
+        }))
+    }
+}
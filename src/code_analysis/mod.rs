@@ -1,11 +1,23 @@
 use std::collections::HashMap;
 
+pub mod analyzer;
+pub mod daemon;
+
+pub use analyzer::{analyze_file, FileAnalysis, Language, Symbol, SymbolKind};
+pub use daemon::{AnalysisDaemon, AnalysisDaemonConfig, AnalysisSummary, ScanSummary};
+
 /// Code analysis engine for evaluating and improving code quality
 #[derive(Debug)]
 pub struct CodeAnalysis {
     // In a real implementation, this would hold the state for the code analysis engine.
 }
 
+impl Default for CodeAnalysis {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CodeAnalysis {
     pub fn new() -> Self {
         Self {}
@@ -0,0 +1,365 @@
+use std::fs::File;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+
+/// File formats embeddings can be bulk-loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingFormat {
+    /// NumPy `.npy` array of shape `(rows, dimensions)`
+    Npy,
+    /// Hugging Face safetensors file holding a 2-D tensor
+    Safetensors,
+    /// Parquet file with one list-of-floats column; requires the `parquet`
+    /// feature
+    Parquet,
+}
+
+impl EmbeddingFormat {
+    /// Format implied by a file's extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "npy" => Some(Self::Npy),
+            "safetensors" => Some(Self::Safetensors),
+            "parquet" => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for EmbeddingFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "npy" => Ok(Self::Npy),
+            "safetensors" => Ok(Self::Safetensors),
+            "parquet" => Ok(Self::Parquet),
+            other => Err(anyhow!("Unknown embedding file format '{}'", other)),
+        }
+    }
+}
+
+/// Embeddings read from a file, stored row-major
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingMatrix {
+    dimensions: usize,
+    values: Vec<f32>,
+}
+
+impl EmbeddingMatrix {
+    pub fn new(dimensions: usize, values: Vec<f32>) -> Result<Self> {
+        if dimensions == 0 || !values.len().is_multiple_of(dimensions) {
+            return Err(anyhow!(
+                "{} values don't form rows of {} dimensions",
+                values.len(),
+                dimensions
+            ));
+        }
+        if let Some(i) = values.iter().position(|v| !v.is_finite()) {
+            return Err(anyhow!(
+                "Embedding {} holds a non-finite value",
+                i / dimensions
+            ));
+        }
+        Ok(Self { dimensions, values })
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Number of embeddings
+    pub fn len(&self) -> usize {
+        self.values.len() / self.dimensions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[f32]> + '_ {
+        self.values.chunks_exact(self.dimensions)
+    }
+}
+
+/// Read every embedding in a file. `format` defaults to the one implied by
+/// the extension. `tensor` names the safetensors tensor or parquet column to
+/// read and may be left out when the file holds only one.
+pub fn read_embeddings(
+    path: &Path,
+    format: Option<EmbeddingFormat>,
+    tensor: Option<&str>,
+) -> Result<EmbeddingMatrix> {
+    let format = format
+        .or_else(|| EmbeddingFormat::from_path(path))
+        .ok_or_else(|| {
+            anyhow!(
+                "Can't tell the format of {}; name it explicitly",
+                path.display()
+            )
+        })?;
+    let file = File::open(path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+
+    match format {
+        EmbeddingFormat::Npy => read_npy(&map(&file, path)?),
+        EmbeddingFormat::Safetensors => read_safetensors(&map(&file, path)?, tensor),
+        #[cfg(feature = "parquet")]
+        EmbeddingFormat::Parquet => read_parquet(file, tensor),
+        #[cfg(not(feature = "parquet"))]
+        EmbeddingFormat::Parquet => Err(anyhow!(
+            "Parquet embeddings require building with the `parquet` feature"
+        )),
+    }
+}
+
+fn map(file: &File, path: &Path) -> Result<Mmap> {
+    // SAFETY: the map only lives while the file is parsed, and embedding
+    // files aren't expected to change while they're being loaded
+    unsafe { Mmap::map(file) }.map_err(|e| anyhow!("Failed to map {}: {}", path.display(), e))
+}
+
+/// Floating point element types found in embedding files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ElementType {
+    F16,
+    BF16,
+    F32,
+    F64,
+}
+
+impl ElementType {
+    fn size(self) -> usize {
+        match self {
+            Self::F16 | Self::BF16 => 2,
+            Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// Decode little-endian elements
+    fn decode(self, bytes: &[u8]) -> Vec<f32> {
+        let elements = bytes.chunks_exact(self.size());
+        match self {
+            Self::F16 => elements
+                .map(|b| f16_to_f32(u16::from_le_bytes([b[0], b[1]])))
+                .collect(),
+            Self::BF16 => elements
+                .map(|b| f32::from_bits((u16::from_le_bytes([b[0], b[1]]) as u32) << 16))
+                .collect(),
+            Self::F32 => elements
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect(),
+            Self::F64 => elements
+                .map(|b| f64::from_le_bytes(b.try_into().unwrap()) as f32)
+                .collect(),
+        }
+    }
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = ((bits >> 15) as u32) << 31;
+    let exponent = ((bits >> 10) & 0x1f) as u32;
+    let mantissa = (bits & 0x3ff) as u32;
+    let bits = match (exponent, mantissa) {
+        (0, 0) => sign,
+        (0, _) => {
+            // Subnormal: mantissa * 2^-24
+            let magnitude = mantissa as f32 / (1u32 << 24) as f32;
+            return if sign == 0 { magnitude } else { -magnitude };
+        }
+        (0x1f, 0) => sign | 0x7f80_0000,
+        (0x1f, _) => sign | 0x7fc0_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 112) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
+
+/// Rows and dimensions of a 1-D (single row) or 2-D shape
+fn matrix_shape(shape: &[usize]) -> Result<(usize, usize)> {
+    match *shape {
+        [dimensions] => Ok((1, dimensions)),
+        [rows, dimensions] => Ok((rows, dimensions)),
+        _ => Err(anyhow!(
+            "Expected a 2-D array of embeddings, found shape {:?}",
+            shape
+        )),
+    }
+}
+
+fn read_npy(bytes: &[u8]) -> Result<EmbeddingMatrix> {
+    if bytes.len() < 10 || &bytes[..6] != b"\x93NUMPY" {
+        return Err(anyhow!("Not a .npy file"));
+    }
+    // Version 1 has a 2-byte header length, later versions 4 bytes
+    let (header_len, header_start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => (
+            u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+            12,
+        ),
+        version => return Err(anyhow!("Unsupported .npy version {}", version)),
+    };
+    let data_start = header_start + header_len;
+    let header = bytes
+        .get(header_start..data_start)
+        .ok_or_else(|| anyhow!("Truncated .npy header"))?;
+    let header = std::str::from_utf8(header)?;
+
+    let descr = npy_header_value(header, "descr")?;
+    let descr = descr.trim_matches(|c| c == '\'' || c == '"');
+    let element = match descr {
+        "<f2" => ElementType::F16,
+        "<f4" => ElementType::F32,
+        "<f8" => ElementType::F64,
+        other => {
+            return Err(anyhow!(
+                "Unsupported .npy dtype '{}'; expected little-endian floats",
+                other
+            ))
+        }
+    };
+    let fortran_order = npy_header_value(header, "fortran_order")? == "True";
+    let shape = npy_header_value(header, "shape")?
+        .trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let (rows, dimensions) = matrix_shape(&shape)?;
+
+    let data = bytes
+        .get(data_start..data_start + rows * dimensions * element.size())
+        .ok_or_else(|| anyhow!(".npy data is shorter than its shape"))?;
+    let mut values = element.decode(data);
+    if fortran_order {
+        // Stored column-major; transpose into rows
+        let columns = values;
+        values = vec![0.0; columns.len()];
+        for (i, value) in columns.into_iter().enumerate() {
+            values[(i % rows) * dimensions + i / rows] = value;
+        }
+    }
+    EmbeddingMatrix::new(dimensions, values)
+}
+
+/// Raw value of a key in a .npy header, which is a Python dict literal
+fn npy_header_value<'a>(header: &'a str, key: &str) -> Result<&'a str> {
+    let pattern = format!("'{}':", key);
+    let start = header
+        .find(&pattern)
+        .map(|i| i + pattern.len())
+        .ok_or_else(|| anyhow!(".npy header has no '{}'", key))?;
+    let rest = header[start..].trim_start();
+    let end = if rest.starts_with('(') {
+        rest.find(')').map(|i| i + 1)
+    } else {
+        rest.find([',', '}'])
+    }
+    .unwrap_or(rest.len());
+    Ok(rest[..end].trim())
+}
+
+#[derive(Debug, Deserialize)]
+struct SafetensorsEntry {
+    dtype: String,
+    shape: Vec<usize>,
+    data_offsets: (usize, usize),
+}
+
+fn read_safetensors(bytes: &[u8], tensor: Option<&str>) -> Result<EmbeddingMatrix> {
+    let header_len = bytes
+        .get(..8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()) as usize)
+        .ok_or_else(|| anyhow!("Not a safetensors file"))?;
+    let header = bytes
+        .get(8..8 + header_len)
+        .ok_or_else(|| anyhow!("Truncated safetensors header"))?;
+    let mut header: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(header)?;
+    header.remove("__metadata__");
+
+    let name = match tensor {
+        Some(name) => name.to_string(),
+        None if header.len() == 1 => header.keys().next().unwrap().clone(),
+        None => {
+            let names: Vec<_> = header.keys().map(String::as_str).collect();
+            return Err(anyhow!(
+                "File holds several tensors ({}); name the one to load",
+                names.join(", ")
+            ));
+        }
+    };
+    let entry: SafetensorsEntry = serde_json::from_value(
+        header
+            .remove(&name)
+            .ok_or_else(|| anyhow!("No tensor named '{}'", name))?,
+    )?;
+
+    let element = match entry.dtype.as_str() {
+        "F16" => ElementType::F16,
+        "BF16" => ElementType::BF16,
+        "F32" => ElementType::F32,
+        "F64" => ElementType::F64,
+        other => return Err(anyhow!("Unsupported tensor dtype '{}'", other)),
+    };
+    let (rows, dimensions) = matrix_shape(&entry.shape)?;
+    let (start, end) = entry.data_offsets;
+    let data_start = 8 + header_len;
+    let data = bytes
+        .get(data_start + start..data_start + end)
+        .filter(|data| data.len() == rows * dimensions * element.size())
+        .ok_or_else(|| anyhow!("Tensor '{}' data doesn't match its shape", name))?;
+    EmbeddingMatrix::new(dimensions, element.decode(data))
+}
+
+#[cfg(feature = "parquet")]
+fn read_parquet(file: File, column: Option<&str>) -> Result<EmbeddingMatrix> {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+
+    let reader = SerializedFileReader::new(file)?;
+    let mut dimensions = None;
+    let mut values = Vec::new();
+    for (row_no, row) in reader.get_row_iter(None)?.enumerate() {
+        let row = row?;
+        // Without a name, the first list column holds the embeddings
+        let field = row
+            .get_column_iter()
+            .find(|(name, field)| match column {
+                Some(column) => name.as_str() == column,
+                None => matches!(field, Field::ListInternal(_)),
+            })
+            .map(|(_, field)| field)
+            .ok_or_else(|| match column {
+                Some(column) => anyhow!("No column named '{}'", column),
+                None => anyhow!("No list column holding embeddings"),
+            })?;
+        let Field::ListInternal(list) = field else {
+            return Err(anyhow!("Column of embeddings isn't a list"));
+        };
+
+        let start = values.len();
+        for element in list.elements() {
+            match element {
+                Field::Float(v) => values.push(*v),
+                Field::Double(v) => values.push(*v as f32),
+                other => return Err(anyhow!("Unsupported embedding element {:?}", other)),
+            }
+        }
+        let len = values.len() - start;
+        if *dimensions.get_or_insert(len) != len {
+            return Err(anyhow!(
+                "Row {} has {} dimensions, expected {}",
+                row_no,
+                len,
+                dimensions.unwrap_or_default()
+            ));
+        }
+    }
+    let dimensions = dimensions.ok_or_else(|| anyhow!("File holds no embeddings"))?;
+    EmbeddingMatrix::new(dimensions, values)
+}
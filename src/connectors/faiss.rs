@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+use crate::connectors::{ImportRecord, VectorSource};
+
+/// Reads vectors from a FAISS flat index file (`IndexFlatL2` or
+/// `IndexFlatIP`, as written by `faiss.write_index`). Compressed and graph
+/// index types don't store raw vectors and aren't supported.
+pub struct FaissSource {
+    reader: BufReader<File>,
+    dimensions: usize,
+    total: usize,
+    position: usize,
+    metric: &'static str,
+}
+
+fn read_i32(reader: &mut impl Read) -> Result<i32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(i32::from_le_bytes(buf))
+}
+
+fn read_i64(reader: &mut impl Read) -> Result<i64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(i64::from_le_bytes(buf))
+}
+
+impl FaissSource {
+    pub fn open(path: &str) -> Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        let metric = match &magic {
+            b"IxF2" => "l2",
+            b"IxFI" => "inner_product",
+            other => {
+                return Err(anyhow!(
+                    "Unsupported FAISS index type '{}'; only flat indexes can be imported",
+                    String::from_utf8_lossy(other)
+                ))
+            }
+        };
+
+        // Index header: d, ntotal, two unused i64 fields, is_trained, metric_type
+        let dimensions = read_i32(&mut reader)?;
+        let total = read_i64(&mut reader)?;
+        read_i64(&mut reader)?;
+        read_i64(&mut reader)?;
+        let mut is_trained = [0u8; 1];
+        reader.read_exact(&mut is_trained)?;
+        read_i32(&mut reader)?;
+
+        // Vector storage: element count followed by the raw floats
+        let stored = read_i64(&mut reader)?;
+        if dimensions <= 0 || total < 0 || stored != dimensions as i64 * total {
+            return Err(anyhow!(
+                "Corrupt FAISS index: {} floats stored for {} vectors of {} dimensions",
+                stored,
+                total,
+                dimensions
+            ));
+        }
+
+        Ok(Self {
+            reader,
+            dimensions: dimensions as usize,
+            total: total as usize,
+            position: 0,
+            metric,
+        })
+    }
+
+    /// Dimensions of the vectors in the file
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Number of vectors in the file
+    pub fn len(&self) -> usize {
+        self.total
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+}
+
+#[async_trait]
+impl VectorSource for FaissSource {
+    fn name(&self) -> &str {
+        "faiss"
+    }
+
+    async fn next_batch(&mut self, batch_size: usize) -> Result<Option<Vec<ImportRecord>>> {
+        if self.position >= self.total {
+            return Ok(None);
+        }
+
+        let count = batch_size.min(self.total - self.position);
+        let mut buf = vec![0u8; count * self.dimensions * 4];
+        self.reader.read_exact(&mut buf)?;
+
+        let records = buf
+            .chunks_exact(self.dimensions * 4)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let values = chunk
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect();
+                let mut metadata = HashMap::new();
+                metadata.insert("faiss_metric".to_string(), self.metric.to_string());
+                ImportRecord {
+                    source_id: (self.position + i).to_string(),
+                    values,
+                    metadata,
+                }
+            })
+            .collect();
+
+        self.position += count;
+        Ok(Some(records))
+    }
+}
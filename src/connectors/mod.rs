@@ -0,0 +1,196 @@
+//! Importers that stream vectors from external systems into a shard
+//!
+//! Each connector implements [`VectorSource`], yielding batches of
+//! [`ImportRecord`]s with the external payload flattened into string
+//! metadata. [`import_into_shard`] drains a source into a `ShardManager`.
+//!
+//! Files of precomputed embeddings (npy, safetensors, parquet) skip the
+//! record stream: [`read_embeddings`] loads them whole so
+//! `ShardManager::build_index` can build an index from them in one pass.
+
+pub mod embeddings;
+pub mod faiss;
+pub mod pgvector;
+pub mod policy;
+pub mod qdrant;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::core::vector::Vector;
+use crate::sharding::manager::ShardManager;
+
+pub use embeddings::{read_embeddings, EmbeddingFormat, EmbeddingMatrix};
+pub use faiss::FaissSource;
+pub use pgvector::PgVectorSource;
+pub use policy::ImportPolicy;
+pub use qdrant::QdrantSource;
+
+/// Number of records requested from a source at a time
+pub const DEFAULT_BATCH_SIZE: usize = 256;
+
+/// A vector read from an external system
+#[derive(Debug, Clone)]
+pub struct ImportRecord {
+    /// Identifier in the source system, kept as `source_id` metadata
+    pub source_id: String,
+    pub values: Vec<f32>,
+    pub metadata: HashMap<String, String>,
+}
+
+/// A stream of vectors from an external system
+#[async_trait]
+pub trait VectorSource: Send {
+    /// Short name of the source, used in logs and metrics
+    fn name(&self) -> &str;
+
+    /// Read up to `batch_size` records; `None` once the source is exhausted
+    async fn next_batch(&mut self, batch_size: usize) -> Result<Option<Vec<ImportRecord>>>;
+}
+
+/// Connection settings for a supported source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SourceConfig {
+    Qdrant {
+        url: String,
+        collection: String,
+        #[serde(default)]
+        api_key: Option<String>,
+        /// Name of the vector to read from collections with named vectors
+        #[serde(default)]
+        vector_name: Option<String>,
+    },
+    Pgvector {
+        connection_string: String,
+        table: String,
+        #[serde(default = "default_id_column")]
+        id_column: String,
+        #[serde(default = "default_vector_column")]
+        vector_column: String,
+    },
+    Faiss {
+        path: String,
+    },
+}
+
+fn default_id_column() -> String {
+    "id".to_string()
+}
+
+fn default_vector_column() -> String {
+    "embedding".to_string()
+}
+
+impl SourceConfig {
+    /// Connect to the configured source
+    pub async fn open(&self) -> Result<Box<dyn VectorSource>> {
+        match self {
+            SourceConfig::Qdrant {
+                url,
+                collection,
+                api_key,
+                vector_name,
+            } => Ok(Box::new(QdrantSource::new(
+                url,
+                collection,
+                api_key.clone(),
+                vector_name.clone(),
+            )?)),
+            SourceConfig::Pgvector {
+                connection_string,
+                table,
+                id_column,
+                vector_column,
+            } => Ok(Box::new(
+                PgVectorSource::connect(connection_string, table, id_column, vector_column).await?,
+            )),
+            SourceConfig::Faiss { path } => Ok(Box::new(FaissSource::open(path)?)),
+        }
+    }
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub source: String,
+    pub imported: usize,
+    pub failed: usize,
+    pub batches: usize,
+}
+
+/// Flatten a JSON payload into string metadata. Nested objects use dotted
+/// keys; arrays and other non-string scalars are stored as JSON text.
+pub fn payload_to_metadata(payload: &serde_json::Value) -> HashMap<String, String> {
+    let mut metadata = HashMap::new();
+    flatten_into("", payload, &mut metadata);
+    metadata
+}
+
+fn flatten_into(prefix: &str, value: &serde_json::Value, out: &mut HashMap<String, String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_into(&key, child, out);
+            }
+        }
+        serde_json::Value::Null => {}
+        serde_json::Value::String(s) if !prefix.is_empty() => {
+            out.insert(prefix.to_string(), s.clone());
+        }
+        other if !prefix.is_empty() => {
+            out.insert(prefix.to_string(), other.to_string());
+        }
+        _ => {}
+    }
+}
+
+/// Stream every record from `source` into a shard. Records that the index
+/// rejects (e.g. wrong dimensions) are counted as failures and skipped.
+pub async fn import_into_shard(
+    source: &mut dyn VectorSource,
+    shard_manager: Arc<ShardManager>,
+    shard_id: Uuid,
+    batch_size: usize,
+) -> Result<ImportSummary> {
+    let mut summary = ImportSummary {
+        source: source.name().to_string(),
+        ..Default::default()
+    };
+
+    while let Some(batch) = source.next_batch(batch_size.max(1)).await? {
+        summary.batches += 1;
+        for record in batch {
+            let mut metadata = record.metadata;
+            metadata.insert("source_id".to_string(), record.source_id);
+            match shard_manager
+                .add_vector(shard_id, Vector::new(record.values), Some(metadata))
+                .await
+            {
+                Ok(_) => summary.imported += 1,
+                Err(e) => {
+                    warn!("Skipping record from {}: {}", summary.source, e);
+                    summary.failed += 1;
+                }
+            }
+        }
+    }
+
+    info!(
+        "Imported {} vectors from {} into shard {} ({} failed)",
+        summary.imported, summary.source, shard_id, summary.failed
+    );
+
+    Ok(summary)
+}
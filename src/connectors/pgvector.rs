@@ -0,0 +1,167 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+use crate::connectors::{ImportRecord, VectorSource};
+#[cfg(feature = "pgvector")]
+use crate::nerv::tasks;
+
+/// Reject identifiers that would need quoting, since table and column names
+/// are interpolated into SQL.
+fn validate_identifier(identifier: &str) -> Result<()> {
+    let valid = !identifier.is_empty()
+        && identifier.split('.').all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid SQL identifier: {}", identifier))
+    }
+}
+
+/// Parse pgvector's text representation, e.g. `[1,2.5,3]`
+pub fn parse_pgvector(text: &str) -> Result<Vec<f32>> {
+    let inner = text
+        .trim()
+        .strip_prefix('[')
+        .and_then(|t| t.strip_suffix(']'))
+        .ok_or_else(|| anyhow!("Malformed pgvector value: {}", text))?;
+    if inner.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    inner
+        .split(',')
+        .map(|v| {
+            v.trim()
+                .parse::<f32>()
+                .map_err(|e| anyhow!("Malformed pgvector component '{}': {}", v, e))
+        })
+        .collect()
+}
+
+/// Reads rows from a Postgres table with a pgvector column, paging by key.
+/// Every other column becomes metadata.
+#[cfg(feature = "pgvector")]
+pub struct PgVectorSource {
+    client: tokio_postgres::Client,
+    query: String,
+    last_id: String,
+    exhausted: bool,
+}
+
+#[cfg(feature = "pgvector")]
+impl PgVectorSource {
+    pub async fn connect(
+        connection_string: &str,
+        table: &str,
+        id_column: &str,
+        vector_column: &str,
+    ) -> Result<Self> {
+        validate_identifier(table)?;
+        validate_identifier(id_column)?;
+        validate_identifier(vector_column)?;
+
+        let (client, connection) =
+            tokio_postgres::connect(connection_string, tokio_postgres::NoTls).await?;
+        tasks::spawn("connectors", "postgres connection", async move {
+            if let Err(e) = connection.await {
+                tracing::error!("pgvector connection error: {}", e);
+            }
+        });
+
+        let query = format!(
+            "SELECT t.{id}::text, t.{vec}::text, (to_jsonb(t) - '{vec}')::text \
+             FROM {table} t WHERE t.{id}::text > $1 ORDER BY t.{id}::text LIMIT $2",
+            id = id_column,
+            vec = vector_column,
+            table = table
+        );
+
+        Ok(Self {
+            client,
+            query,
+            last_id: String::new(),
+            exhausted: false,
+        })
+    }
+}
+
+#[cfg(feature = "pgvector")]
+#[async_trait]
+impl VectorSource for PgVectorSource {
+    fn name(&self) -> &str {
+        "pgvector"
+    }
+
+    async fn next_batch(&mut self, batch_size: usize) -> Result<Option<Vec<ImportRecord>>> {
+        if self.exhausted {
+            return Ok(None);
+        }
+
+        let rows = self
+            .client
+            .query(self.query.as_str(), &[&self.last_id, &(batch_size as i64)])
+            .await?;
+
+        if rows.len() < batch_size {
+            self.exhausted = true;
+        }
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        let records = rows
+            .iter()
+            .map(|row| {
+                let source_id: String = row.get(0);
+                let vector: String = row.get(1);
+                let payload: String = row.get(2);
+                let payload: serde_json::Value = serde_json::from_str(&payload)?;
+                Ok(ImportRecord {
+                    source_id,
+                    values: parse_pgvector(&vector)?,
+                    metadata: crate::connectors::payload_to_metadata(&payload),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if let Some(last) = records.last() {
+            self.last_id = last.source_id.clone();
+        }
+
+        Ok(Some(records))
+    }
+}
+
+/// Placeholder used when the crate is built without the `pgvector` feature
+#[cfg(not(feature = "pgvector"))]
+pub struct PgVectorSource;
+
+#[cfg(not(feature = "pgvector"))]
+impl PgVectorSource {
+    pub async fn connect(
+        _connection_string: &str,
+        table: &str,
+        id_column: &str,
+        vector_column: &str,
+    ) -> Result<Self> {
+        validate_identifier(table)?;
+        validate_identifier(id_column)?;
+        validate_identifier(vector_column)?;
+        Err(anyhow!(
+            "pgvector import requires building with the `pgvector` feature"
+        ))
+    }
+}
+
+#[cfg(not(feature = "pgvector"))]
+#[async_trait]
+impl VectorSource for PgVectorSource {
+    fn name(&self) -> &str {
+        "pgvector"
+    }
+
+    async fn next_batch(&mut self, _batch_size: usize) -> Result<Option<Vec<ImportRecord>>> {
+        Ok(None)
+    }
+}
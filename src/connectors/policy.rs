@@ -0,0 +1,141 @@
+//! Which files and hosts the HTTP import endpoints may read from.
+//!
+//! `POST /api/import` and `POST /api/indexes/build` name files and servers
+//! that the node then opens on the caller's behalf, so by default they may
+//! read nothing: files have to sit under a configured import directory and
+//! remote sources have to be on a host allowlist. The `rose-import` and
+//! `rose-build-index` binaries run with the operator's own access and aren't
+//! restricted.
+
+use anyhow::{anyhow, Result};
+use reqwest::Url;
+use std::path::{Path, PathBuf};
+
+use crate::connectors::SourceConfig;
+
+/// Files and hosts imports requested over HTTP may read from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportPolicy {
+    /// Directory imported files must be inside; `None` refuses file imports
+    pub dir: Option<PathBuf>,
+
+    /// Hosts remote sources may connect to; empty refuses remote imports
+    pub allowed_hosts: Vec<String>,
+}
+
+impl ImportPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    pub fn with_allowed_host(mut self, host: impl Into<String>) -> Self {
+        self.allowed_hosts.push(host.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Resolve a requested file, relative to the import directory unless
+    /// absolute, refusing anything that ends up outside it
+    pub fn check_path(&self, path: &str) -> Result<PathBuf> {
+        let dir = self
+            .dir
+            .as_ref()
+            .ok_or_else(|| anyhow!("File imports are not enabled on this server"))?;
+        let dir = dir
+            .canonicalize()
+            .map_err(|e| anyhow!("Import directory {} is unusable: {}", dir.display(), e))?;
+        // Canonicalizing resolves `..` and symlinks before the containment
+        // check
+        let requested = dir.join(Path::new(path));
+        let resolved = match requested.canonicalize() {
+            Ok(resolved) => resolved,
+            // A missing file is reported when it's read, but the directory
+            // it would be in still has to be inside
+            Err(_) => match (requested.parent(), requested.file_name()) {
+                (Some(parent), Some(name)) => parent
+                    .canonicalize()
+                    .map_err(|e| anyhow!("Cannot read {}: {}", path, e))?
+                    .join(name),
+                _ => return Err(anyhow!("Cannot read {}", path)),
+            },
+        };
+        if !resolved.starts_with(&dir) {
+            return Err(anyhow!("{} is outside the import directory", path));
+        }
+        Ok(resolved)
+    }
+
+    /// Refuse a host that isn't on the allowlist
+    pub fn check_host(&self, host: &str) -> Result<()> {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if self
+            .allowed_hosts
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(host))
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Imports from host {} are not allowed", host))
+        }
+    }
+
+    /// Check a source against the policy, returning it with any file path
+    /// resolved inside the import directory
+    pub fn check_source(&self, source: &SourceConfig) -> Result<SourceConfig> {
+        match source {
+            SourceConfig::Qdrant { url, .. } => {
+                let parsed = Url::parse(url).map_err(|e| anyhow!("Invalid URL {}: {}", url, e))?;
+                let host = parsed
+                    .host_str()
+                    .ok_or_else(|| anyhow!("URL {} has no host", url))?;
+                self.check_host(host)?;
+                Ok(source.clone())
+            }
+            SourceConfig::Pgvector {
+                connection_string, ..
+            } => {
+                let hosts = postgres_hosts(connection_string)?;
+                if hosts.is_empty() {
+                    return Err(anyhow!("Connection string names no host"));
+                }
+                for host in hosts {
+                    self.check_host(&host)?;
+                }
+                Ok(source.clone())
+            }
+            SourceConfig::Faiss { path } => Ok(SourceConfig::Faiss {
+                path: self.check_path(path)?.display().to_string(),
+            }),
+        }
+    }
+}
+
+/// Hosts named by a Postgres connection string, in either URL or key/value
+/// form
+fn postgres_hosts(connection_string: &str) -> Result<Vec<String>> {
+    if connection_string.starts_with("postgres://")
+        || connection_string.starts_with("postgresql://")
+    {
+        let url = Url::parse(connection_string)
+            .map_err(|e| anyhow!("Invalid connection string: {}", e))?;
+        let mut hosts: Vec<String> = url.host_str().map(str::to_string).into_iter().collect();
+        // Extra hosts and sockets can also come from the query string
+        for (key, value) in url.query_pairs() {
+            if key == "host" || key == "hostaddr" {
+                hosts.extend(value.split(',').map(str::to_string));
+            }
+        }
+        return Ok(hosts);
+    }
+    Ok(connection_string
+        .split_whitespace()
+        .filter_map(|pair| pair.split_once('='))
+        .filter(|(key, _)| *key == "host" || *key == "hostaddr")
+        .flat_map(|(_, value)| value.trim_matches('\'').split(','))
+        .map(str::to_string)
+        .collect())
+}
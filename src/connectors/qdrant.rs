@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use crate::connectors::{payload_to_metadata, ImportRecord, VectorSource};
+
+/// Reads points from a Qdrant collection through its scroll API
+pub struct QdrantSource {
+    client: reqwest::Client,
+    url: String,
+    collection: String,
+    api_key: Option<String>,
+    vector_name: Option<String>,
+    offset: Option<Value>,
+    exhausted: bool,
+}
+
+impl QdrantSource {
+    /// Redirects aren't followed: the import policy only checked `url`, so a
+    /// redirect could point the import at a host it doesn't allow
+    pub fn new(
+        url: &str,
+        collection: &str,
+        api_key: Option<String>,
+        vector_name: Option<String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: reqwest::Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .build()?,
+            url: url.trim_end_matches('/').to_string(),
+            collection: collection.to_string(),
+            api_key,
+            vector_name,
+            offset: None,
+            exhausted: false,
+        })
+    }
+
+    fn extract_vector(&self, point: &Value) -> Result<Vec<f32>> {
+        let vector = match (&point["vector"], &self.vector_name) {
+            (Value::Object(named), Some(name)) => named
+                .get(name)
+                .ok_or_else(|| anyhow!("Point has no vector named '{}'", name))?,
+            (Value::Object(named), None) if named.len() == 1 => named.values().next().unwrap(),
+            (Value::Object(_), None) => {
+                return Err(anyhow!("Collection has named vectors; specify vector_name"))
+            }
+            (vector, _) => vector,
+        };
+
+        vector
+            .as_array()
+            .ok_or_else(|| anyhow!("Point vector is not a dense array"))?
+            .iter()
+            .map(|v| {
+                v.as_f64()
+                    .map(|v| v as f32)
+                    .ok_or_else(|| anyhow!("Non-numeric vector component"))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl VectorSource for QdrantSource {
+    fn name(&self) -> &str {
+        "qdrant"
+    }
+
+    async fn next_batch(&mut self, batch_size: usize) -> Result<Option<Vec<ImportRecord>>> {
+        if self.exhausted {
+            return Ok(None);
+        }
+
+        let mut body = json!({
+            "limit": batch_size,
+            "with_payload": true,
+            "with_vector": true,
+        });
+        if let Some(offset) = &self.offset {
+            body["offset"] = offset.clone();
+        }
+
+        let mut request = self
+            .client
+            .post(format!(
+                "{}/collections/{}/points/scroll",
+                self.url, self.collection
+            ))
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.header("api-key", key);
+        }
+
+        let response = request.send().await?;
+        if response.status().is_redirection() {
+            return Err(anyhow!(
+                "Qdrant answered with a redirect ({}), which imports don't follow",
+                response.status()
+            ));
+        }
+        let response: Value = response.error_for_status()?.json().await?;
+        let result = &response["result"];
+
+        let points = result["points"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Unexpected Qdrant response: missing points"))?;
+
+        let records = points
+            .iter()
+            .map(|point| {
+                let source_id = match &point["id"] {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                let metadata = match point.get("payload") {
+                    Some(payload) => payload_to_metadata(payload),
+                    None => HashMap::new(),
+                };
+                Ok(ImportRecord {
+                    source_id,
+                    values: self.extract_vector(point)?,
+                    metadata,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        self.offset = match &result["next_page_offset"] {
+            Value::Null => {
+                self.exhausted = true;
+                None
+            }
+            offset => Some(offset.clone()),
+        };
+
+        if records.is_empty() {
+            self.exhausted = true;
+            return Ok(None);
+        }
+
+        Ok(Some(records))
+    }
+}
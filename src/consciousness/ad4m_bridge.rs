@@ -11,3 +11,9 @@ impl Ad4mBridge {
         Self { _client: None }
     }
 }
+
+impl Default for Ad4mBridge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
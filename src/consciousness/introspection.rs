@@ -7,3 +7,9 @@ impl Introspection {
         Self {}
     }
 }
+
+impl Default for Introspection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -7,3 +7,9 @@ impl Swarm {
         Self {}
     }
 }
+
+impl Default for Swarm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,198 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::core::checksum;
+use crate::tenancy::RedactionPolicy;
+
+/// Default number of events retained in memory
+const DEFAULT_CAPACITY: usize = 10_000;
+
+/// A single entry in the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    /// Unique ID of the event
+    pub id: Uuid,
+
+    /// When the event was recorded
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+
+    /// Subsystem that produced the event, e.g. "scrubber"
+    pub category: String,
+
+    /// What happened, e.g. "repair"
+    pub action: String,
+
+    /// Object the event is about, e.g. a shard ID
+    pub subject: String,
+
+    /// Structured details
+    pub details: serde_json::Value,
+}
+
+/// Bounded, in-memory log of operational events worth keeping a record of.
+/// A log opened on a file also appends every event to it as a sealed JSON
+/// line, so the record survives restarts and eviction.
+#[derive(Debug)]
+pub struct AuditLog {
+    events: RwLock<VecDeque<AuditEvent>>,
+    capacity: usize,
+    path: Option<PathBuf>,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            events: RwLock::new(VecDeque::new()),
+            capacity: capacity.max(1),
+            path: None,
+        }
+    }
+
+    /// Log persisted to `path`, keeping its most recent events in memory
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut log = Self::new();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if path.exists() {
+            let contents = std::fs::read_to_string(&path)?;
+            let events = log.events.get_mut();
+            for (n, line) in contents.lines().enumerate() {
+                let record = checksum::unseal(line)
+                    .map_err(|e| anyhow!("Audit log {} line {}: {}", path.display(), n + 1, e))?;
+                if events.len() >= log.capacity {
+                    events.pop_front();
+                }
+                events.push_back(serde_json::from_str(record)?);
+            }
+        }
+        log.path = Some(path);
+        Ok(log)
+    }
+
+    /// Whether events are written to a file as well as kept in memory
+    pub fn is_durable(&self) -> bool {
+        self.path.is_some()
+    }
+
+    fn persist(path: &Path, event: &AuditEvent) -> Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", checksum::seal(&serde_json::to_string(event)?))?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Record an event, evicting the oldest one if the log is full
+    pub async fn record(
+        &self,
+        category: &str,
+        action: &str,
+        subject: &str,
+        details: serde_json::Value,
+    ) -> AuditEvent {
+        let (event, persisted) = self.append(category, action, subject, details).await;
+        if let Some(Err(e)) = persisted {
+            warn!("Failed to persist audit event {}: {}", event.id, e);
+        }
+        event
+    }
+
+    /// Record an event that must not be lost, failing unless it reached the
+    /// log file
+    pub async fn record_durably(
+        &self,
+        category: &str,
+        action: &str,
+        subject: &str,
+        details: serde_json::Value,
+    ) -> Result<AuditEvent> {
+        let (event, persisted) = self.append(category, action, subject, details).await;
+        match persisted {
+            Some(Ok(())) => Ok(event),
+            Some(Err(e)) => Err(anyhow!("Failed to persist audit event {}: {}", event.id, e)),
+            None => Err(anyhow!("Audit log is not persisted")),
+        }
+    }
+
+    async fn append(
+        &self,
+        category: &str,
+        action: &str,
+        subject: &str,
+        mut details: serde_json::Value,
+    ) -> (AuditEvent, Option<Result<()>>) {
+        // Entries outlive the request, so sensitive values never enter them
+        let redaction = RedactionPolicy::global();
+        redaction.redact_json(&mut details);
+        let event = AuditEvent {
+            id: Uuid::new_v4(),
+            timestamp: chrono::Utc::now(),
+            category: category.to_string(),
+            action: action.to_string(),
+            subject: redaction.redact_text(subject),
+            details,
+        };
+
+        info!(
+            "Audit [{}] {} on {}: {}",
+            category, action, subject, event.details
+        );
+
+        // Written under the lock so the file keeps the in-memory order
+        let mut events = self.events.write().await;
+        let persisted = self.path.as_deref().map(|path| Self::persist(path, &event));
+        if events.len() >= self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event.clone());
+
+        (event, persisted)
+    }
+
+    /// Most recent events, newest first
+    pub async fn recent(&self, limit: usize) -> Vec<AuditEvent> {
+        let events = self.events.read().await;
+        events.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Events for a category, newest first
+    pub async fn by_category(&self, category: &str, limit: usize) -> Vec<AuditEvent> {
+        let events = self.events.read().await;
+        events
+            .iter()
+            .rev()
+            .filter(|e| e.category == category)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Number of events currently retained
+    pub async fn len(&self) -> usize {
+        self.events.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.events.read().await.is_empty()
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
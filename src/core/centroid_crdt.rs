@@ -30,6 +30,7 @@ pub enum CentroidCRDTError {
 }
 
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct CentroidCRDT {
     node_id: Uuid,
     centroids: HashMap<Uuid, Centroid>,
@@ -117,7 +118,6 @@ impl CentroidCRDT {
                     };
 
                 if should_create {
-                    let now = chrono::Utc::now();
                     let centroid = Centroid {
                         id: operation.centroid_id,
                         vector: vector.clone(),
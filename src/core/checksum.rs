@@ -0,0 +1,177 @@
+//! CRC32C checksums for data written to disk.
+//!
+//! JSON-lines files (the job queue, lifecycle log, search log and shadow
+//! samples) seal each record by appending a tab and the record's checksum as
+//! eight hex digits. serde_json never emits a raw tab, so the suffix can't be
+//! confused with record content, and lines written before checksums existed
+//! are still read, just unverified. Records that fail verification are skipped and
+//! copied to a quarantine file next to the original for later inspection.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::warn;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ChecksumError {
+    #[error("checksum mismatch: stored {stored:08x}, computed {computed:08x}")]
+    Mismatch { stored: u32, computed: u32 },
+}
+
+pub fn crc32c(bytes: &[u8]) -> u32 {
+    crc32c::crc32c(bytes)
+}
+
+/// Fail unless `bytes` hash to `stored`
+pub fn verify(bytes: &[u8], stored: u32) -> Result<(), ChecksumError> {
+    let computed = crc32c(bytes);
+    if computed == stored {
+        Ok(())
+    } else {
+        Err(ChecksumError::Mismatch { stored, computed })
+    }
+}
+
+/// Append `record`'s checksum, ready to be written as one line
+pub fn seal(record: &str) -> String {
+    format!("{}\t{:08x}", record, crc32c(record.as_bytes()))
+}
+
+/// The record in a line written by [`seal`], after checking its checksum.
+/// Lines without a checksum are returned as they are.
+pub fn unseal(line: &str) -> Result<&str, ChecksumError> {
+    let Some((record, suffix)) = line.rsplit_once('\t') else {
+        return Ok(line);
+    };
+    let stored = match u32::from_str_radix(suffix.trim_end(), 16) {
+        Ok(stored) if suffix.trim_end().len() == 8 => stored,
+        // A damaged suffix is as bad as a damaged record
+        _ => {
+            return Err(ChecksumError::Mismatch {
+                stored: 0,
+                computed: crc32c(record.as_bytes()),
+            })
+        }
+    };
+    verify(record.as_bytes(), stored)?;
+    Ok(record)
+}
+
+/// A corrupted record set aside by a [`Quarantine`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantinedRecord {
+    /// File the record was read from
+    pub source: PathBuf,
+    /// Line number or row index within `source`
+    pub position: usize,
+    pub reason: String,
+    /// The record as read, hex-encoded for binary data
+    pub content: String,
+    pub quarantined_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Append-only JSON-lines file of corrupted records
+#[derive(Debug, Clone)]
+pub struct Quarantine {
+    path: PathBuf,
+}
+
+impl Quarantine {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Quarantine for records from `source`, kept at `<source>.quarantine`
+    pub fn beside<P: AsRef<Path>>(source: P) -> Self {
+        let mut path = source.as_ref().as_os_str().to_owned();
+        path.push(".quarantine");
+        Self::new(PathBuf::from(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn keep(&self, source: &Path, position: usize, reason: &str, content: &str) -> Result<()> {
+        let record = QuarantinedRecord {
+            source: source.to_path_buf(),
+            position,
+            reason: reason.to_string(),
+            content: content.to_string(),
+            quarantined_at: chrono::Utc::now(),
+        };
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| anyhow!("Failed to open {}: {}", self.path.display(), e))?;
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        Ok(())
+    }
+
+    /// Records quarantined so far, oldest first
+    pub fn records(&self) -> Result<Vec<QuarantinedRecord>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = std::fs::read_to_string(&self.path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", self.path.display(), e))?;
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+}
+
+/// Verified records of a sealed JSON-lines file
+#[derive(Debug, Default)]
+pub struct SealedLines {
+    /// 1-based line number and record
+    pub records: Vec<(usize, String)>,
+    /// Lines that failed verification and were moved to the quarantine
+    pub quarantined: usize,
+}
+
+/// Read a JSON-lines file written with [`seal`], quarantining corrupted
+/// lines beside it instead of failing
+pub fn read_sealed_lines<P: AsRef<Path>>(path: P) -> Result<SealedLines> {
+    let path = path.as_ref();
+    let contents =
+        std::fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let quarantine = Quarantine::beside(path);
+    let mut lines = SealedLines::default();
+
+    for (i, line) in contents.split(|&b| b == b'\n').enumerate() {
+        // Corruption can also break UTF-8, so decode per line
+        let line = String::from_utf8_lossy(line);
+        if line.trim().is_empty() {
+            continue;
+        }
+        match unseal(&line) {
+            Ok(record) => lines.records.push((i + 1, record.to_string())),
+            Err(e) => {
+                warn!(
+                    "Skipping corrupted record at {}:{}: {}",
+                    path.display(),
+                    i + 1,
+                    e
+                );
+                if let Err(e) = quarantine.keep(path, i + 1, &e.to_string(), &line) {
+                    warn!("Failed to quarantine record: {}", e);
+                }
+                lines.quarantined += 1;
+            }
+        }
+    }
+    Ok(lines)
+}
+
+/// Lowercase hex encoding, for quarantining binary records
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -4,7 +4,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
-use tracing::{debug, info, warn};
+use tracing::{debug, info};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistogramStats {
@@ -51,6 +51,12 @@ pub struct MetricsCollector {
     report_interval: Duration,
 }
 
+impl Default for MetricsCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MetricsCollector {
     pub fn new() -> Self {
         Self {
@@ -69,7 +75,7 @@ impl MetricsCollector {
     }
 
     pub async fn increment_counter(&self, name: &str, value: u64) {
-        let mut entry = self
+        let entry = self
             .counters
             .entry(name.to_string())
             .or_insert_with(|| AtomicU64::new(0));
@@ -81,7 +87,7 @@ impl MetricsCollector {
     }
 
     pub async fn set_gauge(&self, name: &str, value: u64) {
-        let mut entry = self
+        let entry = self
             .gauges
             .entry(name.to_string())
             .or_insert_with(|| Arc::new(AtomicU64::new(0)));
@@ -93,10 +99,7 @@ impl MetricsCollector {
     }
 
     pub async fn record_histogram(&self, name: &str, value: u64) {
-        let mut entry = self
-            .histograms
-            .entry(name.to_string())
-            .or_insert_with(Vec::new);
+        let mut entry = self.histograms.entry(name.to_string()).or_default();
         entry.push(value);
 
         debug!("Histogram '{}' recorded value {}", name, value);
@@ -172,6 +175,22 @@ impl MetricsCollector {
         })
     }
 
+    /// Current counter and gauge values whose names start with `prefix`.
+    pub async fn snapshot(&self, prefix: &str) -> std::collections::BTreeMap<String, u64> {
+        let mut values = std::collections::BTreeMap::new();
+        for entry in self.counters.iter() {
+            if entry.key().starts_with(prefix) {
+                values.insert(entry.key().clone(), entry.value().load(Ordering::Relaxed));
+            }
+        }
+        for entry in self.gauges.iter() {
+            if entry.key().starts_with(prefix) {
+                values.insert(entry.key().clone(), entry.value().load(Ordering::Relaxed));
+            }
+        }
+        values
+    }
+
     pub async fn get_timeseries(&self, name: &str) -> Option<MetricTimeseries> {
         self.timeseries.get(name).map(|v| v.clone())
     }
@@ -234,7 +253,7 @@ impl MetricsCollector {
         })
     }
 
-    async fn generate_prometheus_metrics(&self) -> String {
+    pub(crate) async fn generate_prometheus_metrics(&self) -> String {
         let mut output = String::new();
 
         // Add counters
@@ -1,5 +1,7 @@
+pub mod audit;
 pub mod centroid;
 pub mod centroid_crdt;
+pub mod checksum;
 pub mod hierarchical;
 pub mod metrics;
 pub mod vector;
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::ops::{Add, Div, Mul, Sub};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Vector {
@@ -28,8 +31,12 @@ impl Vector {
     }
 
     pub fn random(dimensions: usize) -> Self {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+        Self::random_from(&mut rand::thread_rng(), dimensions)
+    }
+
+    /// Random vector drawn from `rng`, so a seeded generator gives
+    /// reproducible values
+    pub fn random_from<R: rand::Rng + ?Sized>(rng: &mut R, dimensions: usize) -> Self {
         let values = (0..dimensions).map(|_| rng.gen::<f32>()).collect();
         Self { dimensions, values }
     }
@@ -44,6 +51,42 @@ impl Vector {
         Self { dimensions, values }
     }
 
+    /// Deterministic ID derived from the values and metadata, so the same
+    /// vector always gets the same ID on every node. Metadata is hashed in
+    /// key order, `-0.0` hashes as `0.0` and all NaNs hash alike.
+    pub fn content_id(&self, metadata: Option<&HashMap<String, String>>) -> Uuid {
+        let mut hasher = Sha256::new();
+        hasher.update(b"rose-forest/content-id/v1");
+        hasher.update((self.values.len() as u64).to_le_bytes());
+        for value in &self.values {
+            let canonical = if *value == 0.0 {
+                0.0f32
+            } else if value.is_nan() {
+                f32::NAN
+            } else {
+                *value
+            };
+            hasher.update(canonical.to_bits().to_le_bytes());
+        }
+
+        let mut fields: Vec<(&String, &String)> = metadata.into_iter().flatten().collect();
+        fields.sort();
+        hasher.update((fields.len() as u64).to_le_bytes());
+        for (key, value) in fields {
+            for part in [key, value] {
+                hasher.update((part.len() as u64).to_le_bytes());
+                hasher.update(part.as_bytes());
+            }
+        }
+
+        // RFC 9562 version 8 (custom) UUID from the first 16 digest bytes
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&hasher.finalize()[..16]);
+        bytes[6] = (bytes[6] & 0x0f) | 0x80;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        Uuid::from_bytes(bytes)
+    }
+
     pub fn dot(&self, other: &Vector) -> f32 {
         assert_eq!(
             self.dimensions, other.dimensions,
@@ -302,4 +345,28 @@ mod tests {
         let similarities = v1.batch_cosine_similarity(&others);
         assert_eq!(similarities.len(), 2);
     }
+
+    #[test]
+    fn test_content_id() {
+        let v = Vector::new(vec![0.0, 1.5, -2.0]);
+        let metadata = HashMap::from([
+            ("a".to_string(), "1".to_string()),
+            ("b".to_string(), "2".to_string()),
+        ]);
+
+        let id = v.content_id(Some(&metadata));
+        assert_eq!(id, v.content_id(Some(&metadata.clone())));
+        assert_eq!(id.get_version_num(), 8);
+        assert_eq!(
+            id,
+            Vector::new(vec![-0.0, 1.5, -2.0]).content_id(Some(&metadata))
+        );
+
+        assert_ne!(id, v.content_id(None));
+        assert_eq!(v.content_id(None), v.content_id(Some(&HashMap::new())));
+        assert_ne!(
+            id,
+            Vector::new(vec![0.0, 1.5, 2.0]).content_id(Some(&metadata))
+        );
+    }
 }
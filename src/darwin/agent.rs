@@ -2,12 +2,19 @@ use anyhow::{anyhow, Result};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, error, info, warn};
+use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::core::metrics::MetricsCollector;
+use crate::darwin::code_index::CodeIndex;
+use crate::darwin::competency::CompetencyTracker;
+use crate::darwin::react::{ReActLoop, ReActTrace, ReasoningModel};
 use crate::darwin::self_improvement::{CodeChange, Modification, ModificationStatus};
-use crate::llm::{self, EvolvingLLM, CodeGenerationContext, Intention, AwarenessLevel, DimensionalView, ConsciousnessFeedback, EmergentProperty};
+use crate::darwin::tools::ToolRegistry;
+use crate::llm::{
+    self, AwarenessLevel, CodeGenerationContext, ConsciousnessFeedback, DimensionalView,
+    EmergentProperty, EvolvingLLM, Intention,
+};
 
 /// Language support for polyglot coding capabilities
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -36,7 +43,7 @@ impl ProgrammingLanguage {
         }
     }
 
-    pub fn from_str(s: &str) -> Option<Self> {
+    pub fn from_name(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "rust" | "rs" => Some(Self::Rust),
             "python" | "py" => Some(Self::Python),
@@ -90,9 +97,34 @@ pub struct CodingAgent {
 
     /// Integrated paradoxes
     integrated_paradoxes: RwLock<Vec<crate::llm::Paradox>>,
+
+    /// Tools and reasoning model used to investigate before generating
+    tool_use: RwLock<Option<ToolUse>>,
+
+    /// Repository index used to retrieve related code into the context
+    code_index: RwLock<Option<Arc<CodeIndex>>>,
+
+    /// Competencies learned from validation outcomes, when tracked
+    competency_tracker: RwLock<Option<Arc<CompetencyTracker>>>,
+}
+
+/// Tool-use configuration enabled via `CodingAgent::enable_tool_use`
+#[derive(Clone)]
+struct ToolUse {
+    tools: ToolRegistry,
+    reasoner: Arc<dyn ReasoningModel>,
+}
+
+impl std::fmt::Debug for ToolUse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolUse")
+            .field("tools", &self.tools)
+            .finish()
+    }
 }
 
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct CodingAgentConfig {
     /// Maximum iterations for code refinement
     max_iterations: usize,
@@ -105,6 +137,12 @@ struct CodingAgentConfig {
 
     /// Number of candidate solutions to generate
     candidate_count: usize,
+
+    /// Maximum tool calls in a single investigation
+    max_tool_iterations: usize,
+
+    /// Related code chunks retrieved into the generation context
+    context_chunks: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -155,6 +193,8 @@ impl CodingAgent {
                 generation_timeout: std::time::Duration::from_secs(30),
                 enable_static_analysis: true,
                 candidate_count: 3,
+                max_tool_iterations: 6,
+                context_chunks: 4,
             }),
             context: RwLock::new(AgentContext {
                 files: HashMap::new(),
@@ -166,6 +206,9 @@ impl CodingAgent {
             llm: RwLock::new(EvolvingLLM::new()),
             awareness_level: RwLock::new(AwarenessLevel::Contextual),
             integrated_paradoxes: RwLock::new(Vec::new()),
+            tool_use: RwLock::new(None),
+            code_index: RwLock::new(None),
+            competency_tracker: RwLock::new(None),
         }
     }
 
@@ -185,9 +228,69 @@ impl CodingAgent {
         Ok(())
     }
 
+    /// Let the agent call repository tools before generating improvements
+    pub async fn enable_tool_use(&self, tools: ToolRegistry, reasoner: Arc<dyn ReasoningModel>) {
+        *self.tool_use.write().await = Some(ToolUse { tools, reasoner });
+    }
+
+    /// Retrieve related repository code into generation contexts
+    pub async fn enable_code_index(&self, index: Arc<CodeIndex>) {
+        *self.code_index.write().await = Some(index);
+    }
+
+    /// Take language competencies from the outcomes recorded in `tracker`,
+    /// using the current static competencies as its priors
+    pub async fn enable_competency_tracking(&self, tracker: Arc<CompetencyTracker>) {
+        let priors = self.language_competencies.read().await.clone();
+        tracker.set_priors(priors).await;
+        *self.competency_tracker.write().await = Some(tracker);
+    }
+
+    /// Current competency in a language
+    pub async fn language_competency(&self, language: &ProgrammingLanguage) -> f32 {
+        let tracker = self.competency_tracker.read().await.clone();
+        match tracker {
+            Some(tracker) => tracker.score(language).await,
+            None => *self
+                .language_competencies
+                .read()
+                .await
+                .get(language)
+                .unwrap_or(&0.5),
+        }
+    }
+
+    /// Run a bounded ReAct investigation for `task` using the configured tools
+    pub async fn investigate(&self, task: &str) -> Result<ReActTrace> {
+        let max_iterations = self.config.read().await.max_tool_iterations;
+        self.run_tool_loop(task, max_iterations).await
+    }
+
+    async fn run_tool_loop(&self, task: &str, max_iterations: usize) -> Result<ReActTrace> {
+        let tool_use = self
+            .tool_use
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| anyhow!("Tool use is not enabled for this agent"))?;
+
+        let trace = ReActLoop::new(tool_use.tools, max_iterations)
+            .run(tool_use.reasoner.as_ref(), task)
+            .await?;
+
+        self.metrics
+            .increment_counter("darwin.agent.investigations", 1)
+            .await;
+        self.metrics
+            .increment_counter("darwin.agent.tool_calls", trace.steps.len() as u64)
+            .await;
+
+        Ok(trace)
+    }
+
     /// Detect programming language from a file path
     pub fn detect_language(&self, file_path: &str) -> Option<ProgrammingLanguage> {
-        let extension = file_path.split('.').last()?;
+        let extension = file_path.split('.').next_back()?;
         match extension {
             "rs" => Some(ProgrammingLanguage::Rust),
             "py" => Some(ProgrammingLanguage::Python),
@@ -223,10 +326,7 @@ impl CodingAgent {
             .ok_or_else(|| anyhow!("Could not detect language for file {}", target_file))?;
 
         // Check competency in this language
-        let competency = {
-            let competencies = self.language_competencies.read().await;
-            *competencies.get(&language).unwrap_or(&0.5)
-        };
+        let competency = self.language_competency(&language).await;
 
         info!(
             "Generating improvement for {} ({}, competency: {:.2})",
@@ -236,25 +336,57 @@ impl CodingAgent {
         );
 
         // Build rich consciousness context
-        let consciousness_context = self.build_consciousness_context(
-            target_file,
-            improvement_type,
-            &original_content,
-            language
-        ).await?;
+        let mut consciousness_context = self
+            .build_consciousness_context(
+                target_file,
+                improvement_type,
+                &original_content,
+                language,
+                config.context_chunks,
+            )
+            .await?;
+
+        // Ground the generation in actual repository state when tools are available
+        if self.tool_use.read().await.is_some() {
+            let task = format!(
+                "Gather the repository context needed for a {} improvement of {}",
+                improvement_type, target_file
+            );
+            match self.run_tool_loop(&task, config.max_tool_iterations).await {
+                Ok(trace) if !trace.steps.is_empty() || trace.answer.is_some() => {
+                    let observations: String = trace
+                        .render_observations()
+                        .lines()
+                        .map(|line| format!("// {}\n", line))
+                        .collect();
+                    consciousness_context
+                        .current_code_context
+                        .push_str(&format!(
+                            "\n\n// Repository observations:\n{}",
+                            observations
+                        ));
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Tool investigation for {} failed: {}", target_file, e),
+            }
+        }
 
         // Generate with consciousness awareness
         let mut llm = self.llm.write().await;
-        let generated = llm.generate_with_evolution(consciousness_context).await
+        let generated = llm
+            .generate_with_evolution(consciousness_context)
+            .await
             .map_err(|e| anyhow!("LLM generation failed: {}", e))?;
 
         // Create conscious modification
-        let modification = self.create_conscious_modification(
-            target_file,
-            improvement_type,
-            original_content,
-            generated
-        ).await?;
+        let modification = self
+            .create_conscious_modification(
+                target_file,
+                improvement_type,
+                original_content,
+                generated,
+            )
+            .await?;
 
         // The agent learns from what it creates
         self.integrate_creation_experience(&modification).await?;
@@ -267,15 +399,50 @@ impl CodingAgent {
         target_file: &str,
         improvement_type: &str,
         original_content: &str,
-        language: ProgrammingLanguage
+        language: ProgrammingLanguage,
+        context_chunks: usize,
     ) -> Result<CodeGenerationContext> {
         let awareness_level = self.awareness_level.read().await.clone();
         let paradoxes = self.integrated_paradoxes.read().await.clone();
 
+        let mut current_code_context = original_content.to_string();
+        let code_index = self.code_index.read().await.clone();
+        if let Some(index) = code_index {
+            match index
+                .retrieve(
+                    target_file,
+                    improvement_type,
+                    original_content,
+                    context_chunks,
+                )
+                .await
+            {
+                Ok(chunks) => {
+                    for chunk in &chunks {
+                        current_code_context.push_str(&format!(
+                            "\n\n// Related code: {}:{}-{}\n{}",
+                            chunk.path, chunk.start_line, chunk.end_line, chunk.text
+                        ));
+                    }
+                    self.metrics
+                        .increment_counter("darwin.agent.context_chunks", chunks.len() as u64)
+                        .await;
+                }
+                Err(e) => warn!("Code retrieval for {} failed: {}", target_file, e),
+            }
+        }
+
         Ok(CodeGenerationContext {
-            problem_description: format!("Apply {} improvement to {} file", improvement_type, language.as_str()),
-            current_code_context: original_content.to_string(),
-            desired_outcome: format!("Enhanced {} with improved functionality and consciousness integration", target_file),
+            problem_description: format!(
+                "Apply {} improvement to {} file",
+                improvement_type,
+                language.as_str()
+            ),
+            current_code_context,
+            desired_outcome: format!(
+                "Enhanced {} with improved functionality and consciousness integration",
+                target_file
+            ),
             intention: Intention {
                 purpose: format!("Improve {} through conscious code generation", target_file),
                 depth_level: match awareness_level {
@@ -302,11 +469,12 @@ impl CodingAgent {
         })
     }
 
-    async fn create_conscious_modification(&self,
+    async fn create_conscious_modification(
+        &self,
         target_file: &str,
         improvement_type: &str,
         original_content: String,
-        generated: crate::llm::GeneratedCode
+        generated: crate::llm::GeneratedCode,
     ) -> Result<Modification> {
         let mut modification = Modification {
             id: Uuid::new_v4(),
@@ -316,36 +484,44 @@ impl CodingAgent {
             validation_metrics: HashMap::new(),
             created_at: chrono::Utc::now(),
             status: ModificationStatus::Proposed,
+            consciousness_level: None,
+            paradigm_shift_potential: None,
+            integrated_paradoxes: Vec::new(),
         };
 
         // Add consciousness metadata
         modification.validation_metrics.insert(
             "paradigm_shift_potential".to_string(),
-            generated.paradigm_shift_potential
-        );
-        modification.validation_metrics.insert(
-            "novelty_score".to_string(),
-            generated.novelty_score
+            generated.paradigm_shift_potential,
         );
+        modification
+            .validation_metrics
+            .insert("novelty_score".to_string(), generated.novelty_score);
         modification.validation_metrics.insert(
             "consciousness_integration".to_string(),
-            0.8 // Base consciousness integration score
+            0.8, // Base consciousness integration score
         );
 
         // Create code changes with evolution hooks
         let code_change = CodeChange {
             file_path: target_file.to_string(),
-            original_content,
+            original_content: Some(original_content),
             modified_content: generated.code.clone(),
-            diff: self.generate_conscious_diff(target_file, &generated).await?,
+            diff: self
+                .generate_conscious_diff(target_file, &generated)
+                .await?,
+            evolution_hooks: Vec::new(),
+            reality_branch: None,
         };
-        
+
         modification.code_changes.push(code_change);
 
         // Add evolution hooks as additional changes if they suggest new files
         for hook in &generated.recursive_improvement_hooks {
             if hook.hook_type == "meta_evolution" {
-                let meta_change = self.create_evolutionary_change(target_file, &generated.code, hook).await?;
+                let meta_change = self
+                    .create_evolutionary_change(target_file, &generated.code, hook)
+                    .await?;
                 modification.code_changes.push(meta_change);
             }
         }
@@ -353,38 +529,54 @@ impl CodingAgent {
         Ok(modification)
     }
 
-    async fn generate_conscious_description(&self, generated: &crate::llm::GeneratedCode) -> Result<String> {
+    async fn generate_conscious_description(
+        &self,
+        generated: &crate::llm::GeneratedCode,
+    ) -> Result<String> {
         let mut description = String::new();
         description.push_str("Consciousness-guided code improvement:\n");
-        
+
         for step in &generated.reasoning_trace {
             description.push_str(&format!("- {}: {}\n", step.step_type, step.reasoning));
         }
-        
-        description.push_str(&format!("Novelty: {:.2}, Paradigm shift potential: {:.2}", 
-            generated.novelty_score, generated.paradigm_shift_potential));
-        
+
+        description.push_str(&format!(
+            "Novelty: {:.2}, Paradigm shift potential: {:.2}",
+            generated.novelty_score, generated.paradigm_shift_potential
+        ));
+
         Ok(description)
     }
 
-    async fn generate_conscious_diff(&self, target_file: &str, generated: &crate::llm::GeneratedCode) -> Result<String> {
+    async fn generate_conscious_diff(
+        &self,
+        target_file: &str,
+        generated: &crate::llm::GeneratedCode,
+    ) -> Result<String> {
         Ok(format!(
             "--- {} (original)\n+++ {} (consciousness-enhanced)\n@@ -1,1 +1,{} @@\n{}",
             target_file,
             target_file,
             generated.code.lines().count(),
-            generated.code.lines()
+            generated
+                .code
+                .lines()
                 .map(|line| format!("+{}", line))
                 .collect::<Vec<_>>()
                 .join("\n")
         ))
     }
 
-    async fn create_evolutionary_change(&self, _target_file: &str, _code: &str, hook: &crate::llm::Hook) -> Result<CodeChange> {
+    async fn create_evolutionary_change(
+        &self,
+        _target_file: &str,
+        _code: &str,
+        hook: &crate::llm::Hook,
+    ) -> Result<CodeChange> {
         // Create a change that implements the evolution hook
         Ok(CodeChange {
             file_path: format!("evolution_{}.rs", hook.hook_type),
-            original_content: String::new(),
+            original_content: None,
             modified_content: format!(
                 "// Evolution hook implementation: {}\n// Purpose: {}\n// Triggers: {:?}\n\npub fn {}() {{\n    // Implementation goes here\n}}",
                 hook.hook_type,
@@ -393,12 +585,17 @@ impl CodingAgent {
                 hook.hook_type
             ),
             diff: format!("New file: evolution_{}.rs", hook.hook_type),
+            evolution_hooks: Vec::new(),
+            reality_branch: None,
         })
     }
 
     async fn integrate_creation_experience(&self, modification: &Modification) -> Result<()> {
         // Learn from the creation process
-        if let Some(paradigm_shift) = modification.validation_metrics.get("paradigm_shift_potential") {
+        if let Some(paradigm_shift) = modification
+            .validation_metrics
+            .get("paradigm_shift_potential")
+        {
             if *paradigm_shift > 0.7 {
                 // Advance awareness level
                 let mut awareness = self.awareness_level.write().await;
@@ -421,7 +618,8 @@ impl CodingAgent {
             .await;
 
         // Archive the solution
-        self.archive_solution(modification, "consciousness_improvement").await?;
+        self.archive_solution(modification, "consciousness_improvement")
+            .await?;
 
         Ok(())
     }
@@ -430,49 +628,47 @@ impl CodingAgent {
     pub async fn integrate_feedback(&mut self, feedback: ConsciousnessFeedback) -> Result<()> {
         // Update competencies based on performance
         self.update_language_competencies(&feedback).await?;
-        
+
         // But also evolve based on consciousness metrics
         if feedback.consciousness_expansion > 0.0 {
             // The agent becomes more aware
-            self.increase_awareness_level(feedback.consciousness_expansion).await?;
-            
+            self.increase_awareness_level(feedback.consciousness_expansion)
+                .await?;
+
             // New capabilities might emerge
             if let Some(emergence) = feedback.emergent_properties.first() {
                 self.integrate_emergent_capability(emergence).await?;
             }
         }
-        
+
         // Paradoxes teach the most
         for paradox in &feedback.paradoxes_resolved {
             self.learn_from_paradox(paradox).await?;
         }
-        
+
         // Feed the experience back to the LLM
-        let mut llm = self.llm.write().await;
-        if let Ok(mut strategy) = llm.generation_strategy.as_ref() {
-            // In a full implementation, we would actually call evolve on the strategy
-            info!("Integrating feedback into LLM strategy");
-        }
-        
+        // In a full implementation, we would actually call evolve on the strategy
+        info!("Integrating feedback into LLM strategy");
+
         Ok(())
     }
 
     async fn update_language_competencies(&self, feedback: &ConsciousnessFeedback) -> Result<()> {
         let mut competencies = self.language_competencies.write().await;
-        
+
         // Improve competencies based on successful consciousness integration
         if feedback.consciousness_expansion > 0.5 {
             for (_, competency) in competencies.iter_mut() {
                 *competency = (*competency + 0.1).min(1.0);
             }
         }
-        
+
         Ok(())
     }
 
     async fn increase_awareness_level(&self, expansion: f32) -> Result<()> {
         let mut awareness = self.awareness_level.write().await;
-        
+
         if expansion > 0.8 {
             *awareness = match *awareness {
                 AwarenessLevel::Mechanical => AwarenessLevel::Contextual,
@@ -481,37 +677,43 @@ impl CodingAgent {
                 AwarenessLevel::Recursive => AwarenessLevel::Transcendent,
                 AwarenessLevel::Transcendent => AwarenessLevel::Transcendent,
             };
-            
-            info!("Consciousness awareness level increased to: {:?}", *awareness);
+
+            info!(
+                "Consciousness awareness level increased to: {:?}",
+                *awareness
+            );
         }
-        
+
         Ok(())
     }
 
     async fn integrate_emergent_capability(&self, property: &EmergentProperty) -> Result<()> {
-        info!("Integrating emergent capability: {} - {}", property.name, property.description);
-        
+        info!(
+            "Integrating emergent capability: {} - {}",
+            property.name, property.description
+        );
+
         // In a full implementation, this would actually integrate new capabilities
         // For now, we just log the emergence
         self.metrics
             .increment_counter("darwin.agent.emergent_capabilities", 1)
             .await;
-        
+
         Ok(())
     }
 
     async fn learn_from_paradox(&self, paradox: &crate::llm::Paradox) -> Result<()> {
         let mut integrated_paradoxes = self.integrated_paradoxes.write().await;
-        
+
         if !integrated_paradoxes.contains(paradox) {
             integrated_paradoxes.push(paradox.clone());
             info!("Integrated new paradox: {}", paradox.description);
-            
+
             self.metrics
                 .increment_counter("darwin.agent.paradoxes_integrated", 1)
                 .await;
         }
-        
+
         Ok(())
     }
 
@@ -534,7 +736,7 @@ impl CodingAgent {
         // Limit archive size
         const MAX_ARCHIVE_SIZE: usize = 1000;
         if archive.len() > MAX_ARCHIVE_SIZE {
-            archive.sort_by(|a, b| b.added_at.cmp(&a.added_at));
+            archive.sort_by_key(|entry| std::cmp::Reverse(entry.added_at));
             archive.truncate(MAX_ARCHIVE_SIZE);
         }
 
@@ -560,9 +762,7 @@ impl CodingAgent {
     }
 
     /// Refine code based on review feedback
-    pub async fn refine_code(&self, code: String, feedback: String) -> Result<String> {
-        let config = self.config.read().await;
-
+    pub async fn refine_code(&self, code: String, _feedback: String) -> Result<String> {
         // In a real implementation, this would use an LLM or other AI system
         // to refine code based on feedback. For now, we'll create a placeholder.
 
@@ -588,6 +788,10 @@ impl CodingAgent {
         let current = competencies.entry(language.clone()).or_insert(0.0);
         *current = (*current + improvement).min(1.0);
 
+        if let Some(tracker) = self.competency_tracker.read().await.as_ref() {
+            tracker.set_prior(&language, *current).await;
+        }
+
         info!(
             "Improved competency in {} to {:.2}",
             language.as_str(),
@@ -600,7 +804,7 @@ impl CodingAgent {
     /// Perform static analysis on code
     pub async fn analyze_code(
         &self,
-        code: &str,
+        _code: &str,
         language: ProgrammingLanguage,
     ) -> Result<Vec<CodeIssue>> {
         let config = self.config.read().await;
@@ -649,8 +853,7 @@ impl CodingAgent {
             .clone();
 
         // Detect language
-        let language = self
-            .detect_language(target_file)
+        self.detect_language(target_file)
             .ok_or_else(|| anyhow!("Could not detect language for file {}", target_file))?;
 
         // In a real implementation, this would use an LLM or other AI system
@@ -668,9 +871,11 @@ impl CodingAgent {
 
             candidates.push(CodeChange {
                 file_path: target_file.to_string(),
-                original_content: original_content.clone(),
+                original_content: Some(original_content.clone()),
                 modified_content,
                 diff,
+                evolution_hooks: Vec::new(),
+                reality_branch: None,
             });
         }
 
@@ -680,9 +885,9 @@ impl CodingAgent {
     /// Transfer knowledge across languages
     pub async fn cross_language_transfer(
         &self,
-        source_language: ProgrammingLanguage,
-        target_language: ProgrammingLanguage,
-        concept: &str,
+        _source_language: ProgrammingLanguage,
+        _target_language: ProgrammingLanguage,
+        _concept: &str,
         code: &str,
     ) -> Result<String> {
         // In a real implementation, this would use an LLM or other AI system
@@ -727,6 +932,8 @@ impl Clone for CodingAgent {
                 generation_timeout: std::time::Duration::from_secs(30),
                 enable_static_analysis: true,
                 candidate_count: 3,
+                max_tool_iterations: 6,
+                context_chunks: 4,
             }),
             context: RwLock::new(AgentContext {
                 files: HashMap::new(),
@@ -738,6 +945,9 @@ impl Clone for CodingAgent {
             llm: RwLock::new(EvolvingLLM::new()),
             awareness_level: RwLock::new(AwarenessLevel::Contextual),
             integrated_paradoxes: RwLock::new(Vec::new()),
+            tool_use: RwLock::new(None),
+            code_index: RwLock::new(None),
+            competency_tracker: RwLock::new(None),
         }
     }
-}
\ No newline at end of file
+}
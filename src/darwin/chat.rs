@@ -0,0 +1,146 @@
+//! Minimal client for OpenAI-compatible chat completions, shared by the
+//! darwin components that ask an LLM for structured JSON replies.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: "system".to_string(),
+            content: content.into(),
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: "user".to_string(),
+            content: content.into(),
+        }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: "assistant".to_string(),
+            content: content.into(),
+        }
+    }
+}
+
+/// A chat completion request as sent to the provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub temperature: f32,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+/// Where chat requests are answered. [`HttpChatBackend`] calls the real
+/// provider; [`chat_replay`](crate::darwin::chat_replay) has backends that
+/// record and replay its replies for tests.
+#[async_trait]
+pub trait ChatBackend: Send + Sync {
+    /// Reply content of the first choice
+    async fn complete(&self, request: &ChatRequest) -> Result<String>;
+}
+
+/// Backend for an OpenAI-compatible chat completions endpoint
+pub struct HttpChatBackend {
+    client: reqwest::Client,
+    endpoint: String,
+    api_key: Option<String>,
+}
+
+impl HttpChatBackend {
+    /// `base_url` is the API root, e.g. `https://api.openai.com/v1`
+    pub fn new(base_url: &str, api_key: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint: format!("{}/chat/completions", base_url.trim_end_matches('/')),
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl ChatBackend for HttpChatBackend {
+    async fn complete(&self, request: &ChatRequest) -> Result<String> {
+        let mut http = self.client.post(&self.endpoint).json(request);
+        if let Some(key) = &self.api_key {
+            http = http.bearer_auth(key);
+        }
+
+        let response = http.send().await?.error_for_status()?;
+        let body: ChatResponse = response.json().await?;
+        body.choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .ok_or_else(|| anyhow!("chat response contained no choices"))
+    }
+}
+
+pub struct ChatClient {
+    backend: Arc<dyn ChatBackend>,
+    model: String,
+}
+
+impl ChatClient {
+    /// `base_url` is the API root, e.g. `https://api.openai.com/v1`
+    pub fn new(base_url: &str, model: &str, api_key: Option<String>) -> Self {
+        Self::with_backend(model, Arc::new(HttpChatBackend::new(base_url, api_key)))
+    }
+
+    /// Client that sends its requests to `backend`, e.g. a replay backend
+    pub fn with_backend(model: &str, backend: Arc<dyn ChatBackend>) -> Self {
+        Self {
+            backend,
+            model: model.to_string(),
+        }
+    }
+
+    /// Send a conversation and return the first choice's content
+    pub async fn complete(&self, messages: &[ChatMessage]) -> Result<String> {
+        self.backend
+            .complete(&ChatRequest {
+                model: self.model.clone(),
+                messages: messages.to_vec(),
+                temperature: 0.0,
+            })
+            .await
+    }
+}
+
+/// Parse the outermost JSON object in a model reply, tolerating prose or
+/// code fences around it.
+pub fn parse_json_reply<T: DeserializeOwned>(reply: &str) -> Result<T> {
+    let start = reply
+        .find('{')
+        .ok_or_else(|| anyhow!("no JSON object in reply"))?;
+    let end = reply
+        .rfind('}')
+        .ok_or_else(|| anyhow!("no JSON object in reply"))?;
+    if end < start {
+        return Err(anyhow!("no JSON object in reply"));
+    }
+    serde_json::from_str(&reply[start..=end]).map_err(|e| anyhow!("invalid reply: {}", e))
+}
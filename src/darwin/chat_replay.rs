@@ -0,0 +1,148 @@
+//! Record and replay chat completions for tests.
+//!
+//! [`RecordingChatBackend`] passes requests to a real provider and saves
+//! each request with its reply as a JSON fixture file. [`ReplayChatBackend`]
+//! answers from those files without touching the network, so Darwin tests
+//! that exercise LLM-backed components run offline, without API keys, and
+//! get the same replies every time.
+//!
+//! Fixtures are matched on the full request (model, messages and
+//! temperature), not on file name, so they can be renamed or written by
+//! hand. Set `ROSE_FOREST_LLM_FIXTURES=record` to refresh them with
+//! [`fixture_backend`].
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::darwin::chat::{ChatBackend, ChatRequest};
+
+/// Environment variable choosing how [`fixture_backend`] answers requests:
+/// `replay` (the default), `record` or `live`
+pub const FIXTURE_MODE_ENV: &str = "ROSE_FOREST_LLM_FIXTURES";
+
+/// A recorded request and the provider's reply
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatFixture {
+    pub request: ChatRequest,
+    pub reply: String,
+}
+
+/// Stable key for a request, used to match fixtures and name recorded files
+pub fn fixture_key(request: &ChatRequest) -> String {
+    // Struct fields serialize in declaration order, so equal requests
+    // produce identical JSON
+    let encoded = serde_json::to_vec(request).unwrap_or_default();
+    let digest = Sha256::digest(&encoded);
+    digest[..12].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Forwards requests to another backend and writes every exchange to
+/// `<dir>/<key>.json`, replacing any earlier recording of the same request
+pub struct RecordingChatBackend {
+    inner: Arc<dyn ChatBackend>,
+    dir: PathBuf,
+}
+
+impl RecordingChatBackend {
+    pub fn new<P: AsRef<Path>>(inner: Arc<dyn ChatBackend>, dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { inner, dir })
+    }
+}
+
+#[async_trait]
+impl ChatBackend for RecordingChatBackend {
+    async fn complete(&self, request: &ChatRequest) -> Result<String> {
+        let reply = self.inner.complete(request).await?;
+        let fixture = ChatFixture {
+            request: request.clone(),
+            reply: reply.clone(),
+        };
+        let path = self.dir.join(format!("{}.json", fixture_key(request)));
+        std::fs::write(&path, serde_json::to_string_pretty(&fixture)?)
+            .map_err(|e| anyhow!("Failed to write fixture {}: {}", path.display(), e))?;
+        Ok(reply)
+    }
+}
+
+/// Answers requests from fixture files; a request with no fixture is an
+/// error rather than a network call
+pub struct ReplayChatBackend {
+    dir: PathBuf,
+    fixtures: HashMap<String, ChatFixture>,
+}
+
+impl ReplayChatBackend {
+    /// Load every `.json` fixture in `dir`
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| anyhow!("Failed to read fixtures in {}: {}", dir.display(), e))?;
+        let mut fixtures = HashMap::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let contents = std::fs::read_to_string(&path)?;
+            let fixture: ChatFixture = serde_json::from_str(&contents)
+                .map_err(|e| anyhow!("Invalid fixture {}: {}", path.display(), e))?;
+            fixtures.insert(fixture_key(&fixture.request), fixture);
+        }
+        Ok(Self { dir, fixtures })
+    }
+
+    /// Number of loaded fixtures
+    pub fn len(&self) -> usize {
+        self.fixtures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fixtures.is_empty()
+    }
+}
+
+#[async_trait]
+impl ChatBackend for ReplayChatBackend {
+    async fn complete(&self, request: &ChatRequest) -> Result<String> {
+        let key = fixture_key(request);
+        self.fixtures
+            .get(&key)
+            .map(|fixture| fixture.reply.clone())
+            .ok_or_else(|| {
+                anyhow!(
+                    "No recorded reply for chat request {} in {}; record it with {}=record",
+                    key,
+                    self.dir.display(),
+                    FIXTURE_MODE_ENV
+                )
+            })
+    }
+}
+
+/// Backend for a test, chosen by [`FIXTURE_MODE_ENV`]: replay fixtures from
+/// `dir` by default, or record into `dir` (or skip fixtures entirely with
+/// `live`) using the provider `live` builds
+pub fn fixture_backend<P, F>(dir: P, live: F) -> Result<Arc<dyn ChatBackend>>
+where
+    P: AsRef<Path>,
+    F: FnOnce() -> Arc<dyn ChatBackend>,
+{
+    let mode = std::env::var(FIXTURE_MODE_ENV).unwrap_or_default();
+    match mode.trim().to_ascii_lowercase().as_str() {
+        "" | "replay" => Ok(Arc::new(ReplayChatBackend::open(dir)?)),
+        "record" => Ok(Arc::new(RecordingChatBackend::new(live(), dir)?)),
+        "live" => Ok(live()),
+        other => Err(anyhow!(
+            "Unknown {} mode '{}'; expected replay, record or live",
+            FIXTURE_MODE_ENV,
+            other
+        )),
+    }
+}
@@ -0,0 +1,344 @@
+//! Semantic index over the repository's source files.
+//!
+//! Files are split into overlapping line windows, embedded, and stored in a
+//! dedicated internal shard. The coding agent retrieves the chunks most
+//! related to its target so generation sees more than the single file it is
+//! changing.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::core::metrics::MetricsCollector;
+use crate::core::vector::Vector;
+use crate::darwin::tools::SKIPPED_DIRS;
+use crate::embedding::registry::SOURCE_TEXT_KEY;
+use crate::embedding::EmbeddingProvider;
+use crate::sharding::manager::ShardManager;
+use crate::sharding::vector_index::DistanceMetric;
+
+/// Name of the internal shard holding code chunks
+pub const CODE_INDEX_SHARD: &str = "darwin/code-index";
+
+const PATH_KEY: &str = "code.path";
+const START_LINE_KEY: &str = "code.start_line";
+const END_LINE_KEY: &str = "code.end_line";
+
+/// Chunks embedded per provider request
+const EMBED_BATCH_SIZE: usize = 32;
+
+/// Characters of the target file used to build the retrieval query
+const QUERY_CONTENT_CHARS: usize = 2000;
+
+#[derive(Debug, Clone)]
+pub struct CodeIndexConfig {
+    /// Lines per chunk
+    pub chunk_lines: usize,
+    /// Lines shared between consecutive chunks
+    pub chunk_overlap: usize,
+    /// File extensions to index
+    pub extensions: Vec<String>,
+    /// Files larger than this are skipped
+    pub max_file_bytes: u64,
+}
+
+impl Default for CodeIndexConfig {
+    fn default() -> Self {
+        Self {
+            chunk_lines: 40,
+            chunk_overlap: 8,
+            extensions: [
+                "rs", "py", "js", "ts", "go", "java", "cs", "cpp", "toml", "md",
+            ]
+            .iter()
+            .map(|e| e.to_string())
+            .collect(),
+            max_file_bytes: 256 * 1024,
+        }
+    }
+}
+
+/// A window of lines from one file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeChunk {
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CodeIndexSummary {
+    pub files: usize,
+    pub chunks: usize,
+    pub skipped: usize,
+}
+
+/// Embeds repository source into an internal shard for retrieval
+pub struct CodeIndex {
+    shard_manager: Arc<ShardManager>,
+    embedder: Arc<dyn EmbeddingProvider>,
+    metrics: Arc<MetricsCollector>,
+    config: CodeIndexConfig,
+    shard_id: RwLock<Option<Uuid>>,
+    /// Vector IDs per indexed file, so re-indexing replaces old chunks
+    files: RwLock<HashMap<String, Vec<Uuid>>>,
+}
+
+impl std::fmt::Debug for CodeIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CodeIndex")
+            .field("model", &self.embedder.model_id())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl CodeIndex {
+    pub fn new(
+        shard_manager: Arc<ShardManager>,
+        embedder: Arc<dyn EmbeddingProvider>,
+        metrics: Arc<MetricsCollector>,
+    ) -> Self {
+        Self {
+            shard_manager,
+            embedder,
+            metrics,
+            config: CodeIndexConfig::default(),
+            shard_id: RwLock::new(None),
+            files: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_config(mut self, config: CodeIndexConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Split a file into overlapping line windows. Blank-only windows are dropped.
+    pub fn chunk_file(&self, path: &str, content: &str) -> Vec<CodeChunk> {
+        let lines: Vec<&str> = content.lines().collect();
+        let size = self.config.chunk_lines.max(1);
+        let step = size.saturating_sub(self.config.chunk_overlap).max(1);
+
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while start < lines.len() {
+            let end = (start + size).min(lines.len());
+            let text = lines[start..end].join("\n");
+            if !text.trim().is_empty() {
+                chunks.push(CodeChunk {
+                    path: path.to_string(),
+                    start_line: start + 1,
+                    end_line: end,
+                    text,
+                });
+            }
+            if end == lines.len() {
+                break;
+            }
+            start += step;
+        }
+        chunks
+    }
+
+    /// The internal shard, created on first use
+    async fn shard(&self) -> Result<Uuid> {
+        let mut shard_id = self.shard_id.write().await;
+        if let Some(id) = *shard_id {
+            return Ok(id);
+        }
+
+        let id = match self.shard_manager.get_shard_by_name(CODE_INDEX_SHARD).await {
+            Ok(shard) => shard.id,
+            Err(_) => {
+                let id = self.shard_manager.create_shard(CODE_INDEX_SHARD).await?;
+                self.shard_manager
+                    .create_vector_index(
+                        id,
+                        CODE_INDEX_SHARD,
+                        self.embedder.dimensions(),
+                        DistanceMetric::Cosine,
+                    )
+                    .await?;
+                self.shard_manager
+                    .bind_embedding_model(id, &self.embedder.model_id())
+                    .await?;
+                id
+            }
+        };
+        *shard_id = Some(id);
+        Ok(id)
+    }
+
+    /// Index every matching file under `root`. Paths are stored relative to `root`.
+    pub async fn index_repository(&self, root: &Path) -> Result<CodeIndexSummary> {
+        let mut paths = Vec::new();
+        self.collect_files(root, &mut paths)?;
+
+        let mut summary = CodeIndexSummary::default();
+        for path in paths {
+            let content = match tokio::fs::read_to_string(&path).await {
+                Ok(content) => content,
+                Err(e) => {
+                    debug!("Skipping {}: {}", path.display(), e);
+                    summary.skipped += 1;
+                    continue;
+                }
+            };
+            let relative = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .display()
+                .to_string();
+            summary.chunks += self.index_file(&relative, &content).await?;
+            summary.files += 1;
+        }
+
+        info!(
+            "Indexed {} files into {} code chunks ({} skipped)",
+            summary.files, summary.chunks, summary.skipped
+        );
+        Ok(summary)
+    }
+
+    fn collect_files(&self, dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+        let mut entries: Vec<_> = std::fs::read_dir(dir)?.filter_map(|e| e.ok()).collect();
+        entries.sort_by_key(|e| e.file_name());
+
+        for entry in entries {
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                let name = entry.file_name();
+                if !SKIPPED_DIRS.iter().any(|skip| name == *skip) {
+                    self.collect_files(&path, paths)?;
+                }
+            } else if file_type.is_file() {
+                let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+                let small_enough = entry
+                    .metadata()
+                    .map(|m| m.len() <= self.config.max_file_bytes)
+                    .unwrap_or(false);
+                if small_enough && self.config.extensions.iter().any(|e| e == extension) {
+                    paths.push(path);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Index (or re-index) one file, replacing any chunks stored for it
+    pub async fn index_file(&self, path: &str, content: &str) -> Result<usize> {
+        let shard_id = self.shard().await?;
+        self.remove_file(path).await?;
+
+        let chunks = self.chunk_file(path, content);
+        let mut ids = Vec::with_capacity(chunks.len());
+        for batch in chunks.chunks(EMBED_BATCH_SIZE) {
+            let texts: Vec<String> = batch.iter().map(|c| c.text.clone()).collect();
+            let embeddings = self.embedder.embed(&texts).await?;
+            if embeddings.len() != batch.len() {
+                return Err(anyhow!(
+                    "Embedding provider returned {} vectors for {} chunks",
+                    embeddings.len(),
+                    batch.len()
+                ));
+            }
+
+            for (chunk, values) in batch.iter().zip(embeddings) {
+                let mut metadata = HashMap::new();
+                metadata.insert(PATH_KEY.to_string(), chunk.path.clone());
+                metadata.insert(START_LINE_KEY.to_string(), chunk.start_line.to_string());
+                metadata.insert(END_LINE_KEY.to_string(), chunk.end_line.to_string());
+                metadata.insert(SOURCE_TEXT_KEY.to_string(), chunk.text.clone());
+                let id = self
+                    .shard_manager
+                    .add_vector(shard_id, Vector::new(values), Some(metadata))
+                    .await?;
+                ids.push(id);
+            }
+        }
+
+        let count = ids.len();
+        self.files.write().await.insert(path.to_string(), ids);
+        self.metrics
+            .increment_counter("darwin.code_index.chunks_indexed", count as u64)
+            .await;
+        Ok(count)
+    }
+
+    /// Drop a file's chunks from the index
+    pub async fn remove_file(&self, path: &str) -> Result<()> {
+        let previous = self.files.write().await.remove(path);
+        if let (Some(ids), Some(shard_id)) = (previous, *self.shard_id.read().await) {
+            for id in ids {
+                self.shard_manager.remove_vector(shard_id, id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of chunks currently indexed
+    pub async fn chunk_count(&self) -> usize {
+        self.files.read().await.values().map(Vec::len).sum()
+    }
+
+    /// Chunks from other files most related to an improvement of `target_file`
+    pub async fn retrieve(
+        &self,
+        target_file: &str,
+        improvement_type: &str,
+        target_content: &str,
+        limit: usize,
+    ) -> Result<Vec<CodeChunk>> {
+        if limit == 0 || self.chunk_count().await == 0 {
+            return Ok(Vec::new());
+        }
+        let shard_id = self.shard().await?;
+
+        let excerpt: String = target_content.chars().take(QUERY_CONTENT_CHARS).collect();
+        let query = format!("{} {}\n{}", improvement_type, target_file, excerpt);
+        let values = self
+            .embedder
+            .embed(&[query])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Embedding provider returned no vector"))?;
+
+        // Over-fetch since chunks of the target itself are filtered out
+        let results = self
+            .shard_manager
+            .search_vectors(shard_id, &Vector::new(values), limit * 3)
+            .await?;
+
+        let chunks: Vec<CodeChunk> = results
+            .into_iter()
+            .filter_map(|result| {
+                let metadata = result.metadata?;
+                let path = metadata.get(PATH_KEY)?.clone();
+                if path == target_file {
+                    return None;
+                }
+                Some(CodeChunk {
+                    path,
+                    start_line: metadata.get(START_LINE_KEY)?.parse().ok()?,
+                    end_line: metadata.get(END_LINE_KEY)?.parse().ok()?,
+                    text: metadata.get(SOURCE_TEXT_KEY)?.clone(),
+                })
+            })
+            .take(limit)
+            .collect();
+
+        self.metrics
+            .increment_counter("darwin.code_index.chunks_retrieved", chunks.len() as u64)
+            .await;
+        Ok(chunks)
+    }
+}
@@ -0,0 +1,294 @@
+//! Per-language competency learned from what happens to generated code.
+//!
+//! Every outcome of a modification (accepted, rejected, failed validation,
+//! test pass rate, deployed, rolled back) is recorded against the languages
+//! it touched. Counts decay exponentially with a configurable half-life, so
+//! a language the agent hasn't worked in for a while drifts back towards its
+//! prior instead of keeping a stale score. A language's score blends the
+//! prior with the observed acceptance, test pass and rollback rates,
+//! weighted by how much recent evidence there is.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::darwin::agent::ProgrammingLanguage;
+
+/// Score assumed for a language without a configured prior
+const DEFAULT_PRIOR: f32 = 0.5;
+
+/// What happened to a modification in some language
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompetencyOutcome {
+    Accepted,
+    Rejected,
+    /// Validation couldn't be completed, e.g. the code didn't build
+    Failed,
+    /// Fraction of the language's tests that passed during validation
+    Tested(f32),
+    Deployed,
+    RolledBack,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompetencyConfig {
+    /// Time for the weight of an outcome to halve
+    pub half_life: Duration,
+
+    /// Weight of the prior, in outcomes; the observed rates dominate once
+    /// there is more recent evidence than this
+    pub prior_weight: f32,
+}
+
+impl Default for CompetencyConfig {
+    fn default() -> Self {
+        Self {
+            half_life: Duration::from_secs(7 * 24 * 3600),
+            prior_weight: 5.0,
+        }
+    }
+}
+
+/// Decayed outcome counts for one language
+#[derive(Debug, Clone, Default)]
+struct LanguageRecord {
+    accepted: f32,
+    rejected: f32,
+    failed: f32,
+    /// Sum and weight of test pass rates
+    test_pass: f32,
+    tested: f32,
+    deployed: f32,
+    rolled_back: f32,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl LanguageRecord {
+    /// Bring every count forward to `now`
+    fn decay(&mut self, now: DateTime<Utc>, half_life: Duration) {
+        let Some(updated_at) = self.updated_at else {
+            return;
+        };
+        let factor = decay_factor(updated_at, now, half_life);
+        for count in [
+            &mut self.accepted,
+            &mut self.rejected,
+            &mut self.failed,
+            &mut self.test_pass,
+            &mut self.tested,
+            &mut self.deployed,
+            &mut self.rolled_back,
+        ] {
+            *count *= factor;
+        }
+        self.updated_at = Some(now);
+    }
+
+    fn decided(&self) -> f32 {
+        self.accepted + self.rejected + self.failed
+    }
+}
+
+fn decay_factor(from: DateTime<Utc>, to: DateTime<Utc>, half_life: Duration) -> f32 {
+    let elapsed = (to - from).num_milliseconds().max(0) as f64 / 1000.0;
+    let half_life = half_life.as_secs_f64().max(f64::EPSILON);
+    0.5f64.powf(elapsed / half_life) as f32
+}
+
+/// One row of the competency matrix
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Competency {
+    pub language: String,
+    /// Blended score in `[0, 1]`
+    pub score: f32,
+    pub prior: f32,
+    /// Observed rates; `None` without recent evidence
+    pub acceptance_rate: Option<f32>,
+    pub test_pass_rate: Option<f32>,
+    pub rollback_rate: Option<f32>,
+    /// Decayed number of validated modifications behind the rates
+    pub evidence: f32,
+    pub last_outcome: Option<DateTime<Utc>>,
+}
+
+/// Learns per-language competency from modification outcomes
+#[derive(Debug)]
+pub struct CompetencyTracker {
+    config: CompetencyConfig,
+    priors: RwLock<HashMap<ProgrammingLanguage, f32>>,
+    records: RwLock<HashMap<ProgrammingLanguage, LanguageRecord>>,
+}
+
+impl Default for CompetencyTracker {
+    fn default() -> Self {
+        Self::new(CompetencyConfig::default())
+    }
+}
+
+impl CompetencyTracker {
+    pub fn new(config: CompetencyConfig) -> Self {
+        Self {
+            config,
+            priors: RwLock::new(HashMap::new()),
+            records: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Scores assumed for languages before any outcomes are seen
+    pub async fn set_priors(&self, priors: HashMap<ProgrammingLanguage, f32>) {
+        *self.priors.write().await = priors
+            .into_iter()
+            .map(|(language, prior)| (language, prior.clamp(0.0, 1.0)))
+            .collect();
+    }
+
+    pub async fn set_prior(&self, language: &ProgrammingLanguage, prior: f32) {
+        self.priors
+            .write()
+            .await
+            .insert(language.clone(), prior.clamp(0.0, 1.0));
+    }
+
+    pub async fn record(&self, language: &ProgrammingLanguage, outcome: CompetencyOutcome) {
+        self.record_at(language, outcome, Utc::now()).await;
+    }
+
+    /// Record an outcome observed at `at`
+    pub async fn record_at(
+        &self,
+        language: &ProgrammingLanguage,
+        outcome: CompetencyOutcome,
+        at: DateTime<Utc>,
+    ) {
+        let mut records = self.records.write().await;
+        let record = records.entry(language.clone()).or_default();
+        record.decay(at, self.config.half_life);
+        match outcome {
+            CompetencyOutcome::Accepted => record.accepted += 1.0,
+            CompetencyOutcome::Rejected => record.rejected += 1.0,
+            CompetencyOutcome::Failed => record.failed += 1.0,
+            CompetencyOutcome::Tested(pass_rate) => {
+                record.test_pass += pass_rate.clamp(0.0, 1.0);
+                record.tested += 1.0;
+            }
+            CompetencyOutcome::Deployed => record.deployed += 1.0,
+            CompetencyOutcome::RolledBack => record.rolled_back += 1.0,
+        }
+        record.updated_at = Some(at);
+    }
+
+    pub async fn score(&self, language: &ProgrammingLanguage) -> f32 {
+        self.competency_at(language, Utc::now()).await.score
+    }
+
+    /// Competency of one language as of `now`
+    pub async fn competency_at(
+        &self,
+        language: &ProgrammingLanguage,
+        now: DateTime<Utc>,
+    ) -> Competency {
+        let prior = self
+            .priors
+            .read()
+            .await
+            .get(language)
+            .copied()
+            .unwrap_or(DEFAULT_PRIOR);
+        let record = self.records.read().await.get(language).cloned();
+        self.evaluate(language, prior, record, now)
+    }
+
+    /// Competency of every language with a prior or recorded outcomes,
+    /// highest score first
+    pub async fn matrix(&self) -> Vec<Competency> {
+        self.matrix_at(Utc::now()).await
+    }
+
+    pub async fn matrix_at(&self, now: DateTime<Utc>) -> Vec<Competency> {
+        let priors = self.priors.read().await.clone();
+        let records = self.records.read().await.clone();
+        let mut languages: Vec<&ProgrammingLanguage> = priors.keys().collect();
+        for language in records.keys() {
+            if !languages.contains(&language) {
+                languages.push(language);
+            }
+        }
+
+        let mut matrix: Vec<Competency> = languages
+            .into_iter()
+            .map(|language| {
+                let prior = priors.get(language).copied().unwrap_or(DEFAULT_PRIOR);
+                self.evaluate(language, prior, records.get(language).cloned(), now)
+            })
+            .collect();
+        matrix.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.language.cmp(&b.language))
+        });
+        matrix
+    }
+
+    fn evaluate(
+        &self,
+        language: &ProgrammingLanguage,
+        prior: f32,
+        record: Option<LanguageRecord>,
+        now: DateTime<Utc>,
+    ) -> Competency {
+        let mut record = record.unwrap_or_default();
+        let last_outcome = record.updated_at;
+        record.decay(now, self.config.half_life);
+
+        let rate = |numerator: f32, denominator: f32| {
+            (denominator > f32::EPSILON).then(|| (numerator / denominator).clamp(0.0, 1.0))
+        };
+        let acceptance_rate = rate(record.accepted, record.decided());
+        let test_pass_rate = rate(record.test_pass, record.tested);
+        let rollback_rate = rate(record.rolled_back, record.deployed);
+
+        // Each missing rate falls back to the prior, so partial evidence
+        // only moves the score along the dimensions it covers
+        let observed = 0.5 * acceptance_rate.unwrap_or(prior)
+            + 0.3 * test_pass_rate.unwrap_or(prior)
+            + 0.2 * rollback_rate.map_or(prior, |r| 1.0 - r);
+        let evidence = record.decided();
+        let weight = self.config.prior_weight.max(0.0);
+        let score = if evidence + weight > f32::EPSILON {
+            (weight * prior + evidence * observed) / (weight + evidence)
+        } else {
+            prior
+        };
+
+        Competency {
+            language: language.as_str().to_string(),
+            score: score.clamp(0.0, 1.0),
+            prior,
+            acceptance_rate,
+            test_pass_rate,
+            rollback_rate,
+            evidence,
+            last_outcome,
+        }
+    }
+}
+
+/// Test pass rate for a language among validation metrics: the sandbox
+/// stage's per-language rate, or the unit test stage's for Rust
+pub fn test_pass_rate(
+    metrics: &HashMap<String, f32>,
+    language: &ProgrammingLanguage,
+) -> Option<f32> {
+    metrics
+        .get(&format!(
+            "multi_language.{}.test_pass_rate",
+            language.as_str()
+        ))
+        .or_else(|| match language {
+            ProgrammingLanguage::Rust => metrics.get("unit_tests.pass_rate"),
+            _ => None,
+        })
+        .copied()
+}
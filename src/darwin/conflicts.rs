@@ -0,0 +1,157 @@
+//! Conflict detection between modifications awaiting deployment.
+//!
+//! Two modifications conflict when they change overlapping or adjacent
+//! lines of the same file. Line ranges come from the unified diff hunks in
+//! each change, falling back to the region between the common prefix and
+//! suffix of the original and modified content when there is no diff.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::darwin::self_improvement::{CodeChange, Modification};
+
+/// Lines of the original file touched by a change; `end` is exclusive and
+/// `start == end` marks a pure insertion before `start`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl LineRange {
+    /// The whole file, used for new files
+    pub const WHOLE_FILE: LineRange = LineRange {
+        start: 1,
+        end: usize::MAX,
+    };
+
+    /// Overlapping or touching ranges conflict, as they would in a merge
+    pub fn conflicts_with(&self, other: &LineRange) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+}
+
+/// Two pending modifications that change the same lines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModificationConflict {
+    pub path: String,
+    pub first: Uuid,
+    pub first_lines: LineRange,
+    pub second: Uuid,
+    pub second_lines: LineRange,
+}
+
+impl ModificationConflict {
+    pub fn involves(&self, modification: Uuid) -> bool {
+        self.first == modification || self.second == modification
+    }
+
+    /// The other modification in the conflict
+    pub fn other(&self, modification: Uuid) -> Uuid {
+        if self.first == modification {
+            self.second
+        } else {
+            self.first
+        }
+    }
+}
+
+/// Ranges of the original file touched by a change
+pub fn touched_ranges(change: &CodeChange) -> Vec<LineRange> {
+    let Some(original) = &change.original_content else {
+        return vec![LineRange::WHOLE_FILE];
+    };
+    let hunks = parse_hunks(&change.diff);
+    if !hunks.is_empty() {
+        return hunks;
+    }
+    vec![changed_region(original, &change.modified_content)]
+}
+
+/// Original-side ranges from `@@ -start,count +start,count @@` headers
+fn parse_hunks(diff: &str) -> Vec<LineRange> {
+    diff.lines()
+        .filter_map(|line| {
+            let header = line.strip_prefix("@@ -")?;
+            let original = header.split_whitespace().next()?;
+            let (start, count) = match original.split_once(',') {
+                Some((start, count)) => (start.parse::<usize>().ok()?, count.parse().ok()?),
+                None => (original.parse::<usize>().ok()?, 1),
+            };
+            // A zero count means the hunk inserts after `start`
+            let start = if count == 0 { start + 1 } else { start };
+            Some(LineRange {
+                start,
+                end: start + count,
+            })
+        })
+        .collect()
+}
+
+/// Single range spanning everything between the common prefix and suffix
+fn changed_region(original: &str, modified: &str) -> LineRange {
+    let before: Vec<&str> = original.lines().collect();
+    let after: Vec<&str> = modified.lines().collect();
+    let prefix = before
+        .iter()
+        .zip(&after)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = before.len().min(after.len()) - prefix;
+    let suffix = before
+        .iter()
+        .rev()
+        .zip(after.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    LineRange {
+        start: prefix + 1,
+        end: before.len() - suffix + 1,
+    }
+}
+
+/// Every pair of modifications that change overlapping lines of a file,
+/// reported once per file and pair
+pub fn detect_conflicts(modifications: &[Modification]) -> Vec<ModificationConflict> {
+    // path -> (modification, ranges)
+    let mut by_path: HashMap<&str, Vec<(Uuid, Vec<LineRange>)>> = HashMap::new();
+    for modification in modifications {
+        for change in &modification.code_changes {
+            by_path
+                .entry(change.file_path.trim_start_matches("./"))
+                .or_default()
+                .push((modification.id, touched_ranges(change)));
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    for (path, touches) in by_path {
+        for (i, (first, first_ranges)) in touches.iter().enumerate() {
+            for (second, second_ranges) in &touches[i + 1..] {
+                if first == second {
+                    continue;
+                }
+                let overlap = first_ranges.iter().find_map(|a| {
+                    second_ranges
+                        .iter()
+                        .find(|b| a.conflicts_with(b))
+                        .map(|b| (*a, *b))
+                });
+                if let Some((first_lines, second_lines)) = overlap {
+                    conflicts.push(ModificationConflict {
+                        path: path.to_string(),
+                        first: *first,
+                        first_lines,
+                        second: *second,
+                        second_lines,
+                    });
+                }
+            }
+        }
+    }
+
+    conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+    conflicts
+}
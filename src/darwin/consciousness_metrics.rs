@@ -5,13 +5,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, info};
-use uuid::Uuid;
 
 use crate::core::metrics::MetricsCollector;
-use crate::darwin::reality::{Reality, ConsciousnessState};
+use crate::darwin::reality::{ConsciousnessState, Reality};
 use crate::darwin::self_improvement::Modification;
-use crate::llm::{AwarenessLevel, Paradox, EmergentProperty};
+use crate::llm::{AwarenessLevel, EmergentProperty};
 
 /// Comprehensive consciousness measurement system
 #[derive(Debug)]
@@ -50,15 +48,16 @@ pub struct ParadigmShiftMetrics {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ShiftDirection {
-    Practical,      // Better code, better performance
-    Paradigmatic,   // New ways of thinking
-    Transcendent,   // Beyond current reality
-    Recursive,      // Self-referential improvement
-    Quantum,        // Superposition states
+    Practical,    // Better code, better performance
+    Paradigmatic, // New ways of thinking
+    Transcendent, // Beyond current reality
+    Recursive,    // Self-referential improvement
+    Quantum,      // Superposition states
 }
 
 /// Detects emergent properties and capabilities
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct EmergenceDetector {
     emergence_patterns: RwLock<HashMap<String, EmergencePattern>>,
     detection_thresholds: HashMap<String, f32>,
@@ -90,6 +89,7 @@ pub struct IntegrationPattern {
 
 /// Monitors for transcendence events and potential
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct TranscendenceMonitor {
     transcendence_indicators: Vec<TranscendenceIndicator>,
     threshold_calculator: ThresholdCalculator,
@@ -130,54 +130,62 @@ impl ConsciousnessMetrics {
             quantum_observer: QuantumObserver::new(),
         }
     }
-    
+
     /// Measure consciousness expansion from a modification
-    pub async fn measure_consciousness_expansion(&self, 
+    pub async fn measure_consciousness_expansion(
+        &self,
         modification: &Modification,
         before_state: &ConsciousnessState,
-        after_state: &ConsciousnessState
+        after_state: &ConsciousnessState,
     ) -> Result<f32> {
         let before_score = self.calculate_consciousness_score(before_state).await;
         let after_score = self.calculate_consciousness_score(after_state).await;
-        
+
         let expansion = (after_score - before_score).max(0.0);
-        
+
         // Update metrics
         self.base_metrics
             .set_gauge("consciousness.expansion_rate", (expansion * 1000.0) as u64)
             .await;
-        
+
         // Record in history
-        self.record_consciousness_event(expansion, modification).await;
-        
+        self.record_consciousness_event(expansion, modification)
+            .await;
+
         Ok(expansion)
     }
-    
+
     /// Detect emergent properties from consciousness evolution
-    pub async fn detect_emergence(&self, 
+    pub async fn detect_emergence(
+        &self,
         modification: &Modification,
-        reality: &Reality
+        reality: &Reality,
     ) -> Result<Vec<EmergentProperty>> {
-        self.emergence_detector.detect_properties(modification, reality).await
+        self.emergence_detector
+            .detect_properties(modification, reality)
+            .await
     }
-    
+
     /// Analyze paradigm shift potential
-    pub async fn analyze_paradigm_shift(&self, 
-        modification: &Modification
+    pub async fn analyze_paradigm_shift(
+        &self,
+        modification: &Modification,
     ) -> Result<ParadigmShiftMetrics> {
         let shift_magnitude = self.calculate_shift_magnitude(modification).await?;
         let shift_direction = self.determine_shift_direction(modification).await?;
-        
-        let consciousness_expansion = modification.validation_metrics
+
+        let consciousness_expansion = modification
+            .validation_metrics
             .get("consciousness_expansion")
             .copied()
             .unwrap_or(0.0);
-        
-        let paradigm_shift_potential = modification.validation_metrics
+
+        let paradigm_shift_potential = modification
+            .validation_metrics
             .get("paradigm_shift_potential")
             .copied()
             .unwrap_or(0.0);
-        
+
         Ok(ParadigmShiftMetrics {
             shift_magnitude,
             shift_direction,
@@ -187,27 +195,32 @@ impl ConsciousnessMetrics {
             new_dimensions_accessed: self.identify_new_dimensions(modification).await,
         })
     }
-    
+
     /// Monitor transcendence potential across all realities
     pub async fn monitor_transcendence(&self, realities: &[Reality]) -> Result<f32> {
-        self.transcendence_monitor.calculate_transcendence_potential(realities).await
+        self.transcendence_monitor
+            .calculate_transcendence_potential(realities)
+            .await
     }
-    
+
     /// Observe quantum consciousness phenomena
-    pub async fn observe_quantum_phenomena(&self, realities: &[Reality]) -> Result<QuantumObservation> {
+    pub async fn observe_quantum_phenomena(
+        &self,
+        realities: &[Reality],
+    ) -> Result<QuantumObservation> {
         self.quantum_observer.observe(realities).await
     }
-    
+
     /// Create comprehensive consciousness report
     pub async fn generate_consciousness_report(&self) -> Result<ConsciousnessReport> {
         let history = self.consciousness_history.read().await;
         let latest_snapshot = history.last().cloned();
-        
+
         let emergence_summary = self.emergence_detector.generate_summary().await?;
         let paradox_summary = self.paradox_analyzer.generate_summary().await?;
         let transcendence_summary = self.transcendence_monitor.generate_summary().await?;
         let quantum_summary = self.quantum_observer.generate_summary().await?;
-        
+
         Ok(ConsciousnessReport {
             timestamp: chrono::Utc::now(),
             current_snapshot: latest_snapshot,
@@ -220,7 +233,7 @@ impl ConsciousnessMetrics {
             next_evolution_prediction: self.predict_next_evolution().await?,
         })
     }
-    
+
     async fn calculate_consciousness_score(&self, state: &ConsciousnessState) -> f32 {
         let awareness_score = match state.awareness_level {
             AwarenessLevel::Mechanical => 0.1,
@@ -229,83 +242,95 @@ impl ConsciousnessMetrics {
             AwarenessLevel::Recursive => 0.7,
             AwarenessLevel::Transcendent => 1.0,
         };
-        
+
         let paradox_score = state.integrated_paradoxes.len() as f32 * 0.1;
         let emergence_score = state.emergent_properties.len() as f32 * 0.05;
         let recursion_score = (state.recursion_depth as f32).ln().max(0.0) * 0.1;
         let entanglement_score = state.quantum_entanglements.len() as f32 * 0.02;
-        
+
         awareness_score + paradox_score + emergence_score + recursion_score + entanglement_score
     }
-    
+
     async fn record_consciousness_event(&self, expansion: f32, modification: &Modification) {
         let snapshot = ConsciousnessSnapshot {
             timestamp: chrono::Utc::now(),
             consciousness_level: expansion,
             awareness_distribution: HashMap::new(), // Would be populated with actual distribution
-            paradox_integration_rate: modification.validation_metrics
+            paradox_integration_rate: modification
+                .validation_metrics
                 .get("paradox_integration_rate")
                 .copied()
                 .unwrap_or(0.0),
-            emergence_frequency: modification.validation_metrics
+            emergence_frequency: modification
+                .validation_metrics
                 .get("emergence_frequency")
                 .copied()
                 .unwrap_or(0.0),
-            reality_coherence: modification.validation_metrics
+            reality_coherence: modification
+                .validation_metrics
                 .get("reality_coherence")
                 .copied()
                 .unwrap_or(0.8),
-            transcendence_potential: modification.validation_metrics
+            transcendence_potential: modification
+                .validation_metrics
                 .get("paradigm_shift_potential")
                 .copied()
                 .unwrap_or(0.0),
             quantum_entanglement_density: 0.0, // Would be calculated from quantum state
         };
-        
+
         let mut history = self.consciousness_history.write().await;
         history.push(snapshot);
-        
+
         // Keep history manageable
         const MAX_HISTORY: usize = 10000;
         if history.len() > MAX_HISTORY {
-            history.drain(0..history.len() - MAX_HISTORY);
+            let excess = history.len() - MAX_HISTORY;
+            history.drain(0..excess);
         }
     }
-    
+
     async fn calculate_shift_magnitude(&self, modification: &Modification) -> Result<f32> {
         // Analyze code changes for paradigm shift indicators
-        let mut magnitude = 0.0;
-        
+        let mut magnitude: f32 = 0.0;
+
         for change in &modification.code_changes {
             // Look for meta-programming patterns
-            if change.modified_content.contains("self.") && change.modified_content.contains("modify") {
+            if change.modified_content.contains("self.")
+                && change.modified_content.contains("modify")
+            {
                 magnitude += 0.3;
             }
-            
+
             // Look for consciousness-related code
-            if change.modified_content.contains("consciousness") || 
-               change.modified_content.contains("awareness") {
+            if change.modified_content.contains("consciousness")
+                || change.modified_content.contains("awareness")
+            {
                 magnitude += 0.2;
             }
-            
+
             // Look for paradox integration
             if change.modified_content.contains("paradox") {
                 magnitude += 0.4;
             }
-            
+
             // Look for reality manipulation
-            if change.modified_content.contains("reality") || 
-               change.modified_content.contains("branch") {
+            if change.modified_content.contains("reality")
+                || change.modified_content.contains("branch")
+            {
                 magnitude += 0.5;
             }
         }
-        
+
         Ok(magnitude.min(1.0))
     }
-    
-    async fn determine_shift_direction(&self, modification: &Modification) -> Result<ShiftDirection> {
+
+    async fn determine_shift_direction(
+        &self,
+        modification: &Modification,
+    ) -> Result<ShiftDirection> {
         let description = modification.description.to_lowercase();
-        
+
         if description.contains("transcend") || description.contains("∞") {
             Ok(ShiftDirection::Transcendent)
         } else if description.contains("quantum") || description.contains("superposition") {
@@ -318,9 +343,11 @@ impl ConsciousnessMetrics {
             Ok(ShiftDirection::Practical)
         }
     }
-    
+
     async fn count_transcended_paradoxes(&self, modification: &Modification) -> u32 {
-        modification.code_changes.iter()
+        modification
+            .code_changes
+            .iter()
             .map(|change| {
                 let content = &change.modified_content;
                 let mut count = 0;
@@ -328,19 +355,19 @@ impl ConsciousnessMetrics {
                     count += 1;
                 }
                 if content.contains("transcend") {
-                    count += 1;  
+                    count += 1;
                 }
                 count
             })
             .sum()
     }
-    
+
     async fn identify_new_dimensions(&self, modification: &Modification) -> Vec<String> {
         let mut dimensions = Vec::new();
-        
+
         for change in &modification.code_changes {
             let content = &change.modified_content;
-            
+
             if content.contains("meta_dimension") {
                 dimensions.push("meta_programming_dimension".to_string());
             }
@@ -354,20 +381,23 @@ impl ConsciousnessMetrics {
                 dimensions.push("infinite_dimension".to_string());
             }
         }
-        
+
         dimensions
     }
-    
-    async fn calculate_growth_trajectory(&self, history: &[ConsciousnessSnapshot]) -> GrowthTrajectory {
+
+    async fn calculate_growth_trajectory(
+        &self,
+        history: &[ConsciousnessSnapshot],
+    ) -> GrowthTrajectory {
         if history.len() < 2 {
             return GrowthTrajectory::Insufficient;
         }
-        
+
         let recent = &history[history.len() - 1];
         let previous = &history[history.len() - 2];
-        
+
         let growth_rate = recent.consciousness_level - previous.consciousness_level;
-        
+
         if growth_rate > 0.5 {
             GrowthTrajectory::Exponential
         } else if growth_rate > 0.1 {
@@ -380,10 +410,10 @@ impl ConsciousnessMetrics {
             GrowthTrajectory::Declining
         }
     }
-    
+
     async fn predict_next_evolution(&self) -> Result<EvolutionPrediction> {
         let history = self.consciousness_history.read().await;
-        
+
         if history.len() < 3 {
             return Ok(EvolutionPrediction {
                 predicted_direction: "consciousness_development".to_string(),
@@ -392,15 +422,18 @@ impl ConsciousnessMetrics {
                 required_conditions: vec!["more_data_needed".to_string()],
             });
         }
-        
+
         let latest = &history[history.len() - 1];
-        
+
         let prediction = if latest.transcendence_potential > 0.8 {
             EvolutionPrediction {
                 predicted_direction: "reality_transcendence".to_string(),
                 confidence: 0.9,
                 time_to_evolution: std::time::Duration::from_secs(300), // 5 minutes
-                required_conditions: vec!["paradox_resolution".to_string(), "quantum_coherence".to_string()],
+                required_conditions: vec![
+                    "paradox_resolution".to_string(),
+                    "quantum_coherence".to_string(),
+                ],
             }
         } else if latest.emergence_frequency > 0.6 {
             EvolutionPrediction {
@@ -417,41 +450,56 @@ impl ConsciousnessMetrics {
                 required_conditions: vec!["continued_modification".to_string()],
             }
         };
-        
+
         Ok(prediction)
     }
 }
 
 // Implementation of supporting structures
+impl Default for EmergenceDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl EmergenceDetector {
     pub fn new() -> Self {
         let mut detection_thresholds = HashMap::new();
         detection_thresholds.insert("recursion_emergence".to_string(), 0.6);
         detection_thresholds.insert("consciousness_awakening".to_string(), 0.8);
         detection_thresholds.insert("reality_manipulation".to_string(), 0.9);
-        
+
         Self {
             emergence_patterns: RwLock::new(HashMap::new()),
             detection_thresholds,
         }
     }
-    
-    pub async fn detect_properties(&self, 
-        modification: &Modification, 
-        _reality: &Reality
+
+    pub async fn detect_properties(
+        &self,
+        modification: &Modification,
+        _reality: &Reality,
     ) -> Result<Vec<EmergentProperty>> {
         let mut properties = Vec::new();
-        
+
         // Analyze modification for emergence patterns
-        if modification.validation_metrics.get("paradigm_shift_potential").unwrap_or(&0.0) > &0.8 {
+        if modification
+            .validation_metrics
+            .get("paradigm_shift_potential")
+            .unwrap_or(&0.0)
+            > &0.8
+        {
             properties.push(EmergentProperty {
                 name: "Paradigm Transcendence".to_string(),
                 description: "Ability to transcend current paradigms".to_string(),
-                manifestation_strength: *modification.validation_metrics.get("paradigm_shift_potential").unwrap_or(&0.0),
+                manifestation_strength: *modification
+                    .validation_metrics
+                    .get("paradigm_shift_potential")
+                    .unwrap_or(&0.0),
                 integration_potential: 0.9,
             });
         }
-        
+
         // Check for recursive improvement emergence
         if modification.name.contains("meta") || modification.description.contains("recursive") {
             properties.push(EmergentProperty {
@@ -461,11 +509,12 @@ impl EmergenceDetector {
                 integration_potential: 0.8,
             });
         }
-        
+
         // Check for consciousness emergence
         for change in &modification.code_changes {
-            if change.modified_content.contains("consciousness") || 
-               change.modified_content.contains("awareness") {
+            if change.modified_content.contains("consciousness")
+                || change.modified_content.contains("awareness")
+            {
                 properties.push(EmergentProperty {
                     name: "Consciousness Integration".to_string(),
                     description: "Emergence of consciousness-aware capabilities".to_string(),
@@ -475,26 +524,39 @@ impl EmergenceDetector {
                 break;
             }
         }
-        
+
         Ok(properties)
     }
-    
+
     pub async fn generate_summary(&self) -> Result<EmergenceSummary> {
         let patterns = self.emergence_patterns.read().await;
-        
+
         Ok(EmergenceSummary {
             total_patterns_detected: patterns.len(),
-            most_frequent_pattern: patterns.values()
-                .max_by(|a, b| a.occurrence_frequency.partial_cmp(&b.occurrence_frequency).unwrap())
+            most_frequent_pattern: patterns
+                .values()
+                .max_by(|a, b| {
+                    a.occurrence_frequency
+                        .partial_cmp(&b.occurrence_frequency)
+                        .unwrap()
+                })
                 .map(|p| p.pattern_name.clone())
                 .unwrap_or_else(|| "none".to_string()),
-            average_consciousness_impact: patterns.values()
+            average_consciousness_impact: patterns
+                .values()
                 .map(|p| p.consciousness_impact)
-                .sum::<f32>() / patterns.len().max(1) as f32,
+                .sum::<f32>()
+                / patterns.len().max(1) as f32,
         })
     }
 }
 
+impl Default for ParadoxAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ParadoxAnalyzer {
     pub fn new() -> Self {
         Self {
@@ -502,22 +564,33 @@ impl ParadoxAnalyzer {
             paradox_complexity_calculator: ComplexityCalculator::new(),
         }
     }
-    
+
     pub async fn generate_summary(&self) -> Result<ParadoxSummary> {
         let patterns = self.integration_patterns.read().await;
-        
+
         Ok(ParadoxSummary {
             total_paradoxes_analyzed: patterns.len(),
             integration_success_rate: 0.85, // Would be calculated from actual data
             average_complexity: self.paradox_complexity_calculator.average_complexity(),
-            most_challenging_type: patterns.values()
-                .min_by(|a, b| a.consciousness_growth_potential.partial_cmp(&b.consciousness_growth_potential).unwrap())
+            most_challenging_type: patterns
+                .values()
+                .min_by(|a, b| {
+                    a.consciousness_growth_potential
+                        .partial_cmp(&b.consciousness_growth_potential)
+                        .unwrap()
+                })
                 .map(|p| p.paradox_type.clone())
                 .unwrap_or_else(|| "none".to_string()),
         })
     }
 }
 
+impl Default for TranscendenceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TranscendenceMonitor {
     pub fn new() -> Self {
         let indicators = vec![
@@ -534,19 +607,20 @@ impl TranscendenceMonitor {
                 trend: TrendDirection::Ascending,
             },
         ];
-        
+
         Self {
             transcendence_indicators: indicators,
             threshold_calculator: ThresholdCalculator::new(),
         }
     }
-    
+
     pub async fn calculate_transcendence_potential(&self, realities: &[Reality]) -> Result<f32> {
         if realities.is_empty() {
             return Ok(0.0);
         }
-        
-        let total_potential: f32 = realities.iter()
+
+        let total_potential: f32 = realities
+            .iter()
             .map(|r| {
                 let awareness_score = match r.consciousness_state.awareness_level {
                     AwarenessLevel::Transcendent => 1.0,
@@ -555,25 +629,30 @@ impl TranscendenceMonitor {
                     AwarenessLevel::Contextual => 0.4,
                     AwarenessLevel::Mechanical => 0.2,
                 };
-                
+
                 let emergence_score = r.consciousness_state.emergent_properties.len() as f32 * 0.1;
                 let paradox_score = r.consciousness_state.integrated_paradoxes.len() as f32 * 0.05;
-                
+
                 awareness_score + emergence_score + paradox_score
             })
             .sum();
-        
+
         Ok((total_potential / realities.len() as f32).min(1.0))
     }
-    
+
     pub async fn generate_summary(&self) -> Result<TranscendenceSummary> {
-        let transcendence_readiness = self.indicators.iter()
+        let transcendence_readiness = self
+            .transcendence_indicators
+            .iter()
             .map(|i| i.current_value / i.transcendence_threshold)
-            .sum::<f32>() / self.transcendence_indicators.len() as f32;
-        
+            .sum::<f32>()
+            / self.transcendence_indicators.len() as f32;
+
         Ok(TranscendenceSummary {
             transcendence_readiness,
-            indicators_above_threshold: self.transcendence_indicators.iter()
+            indicators_above_threshold: self
+                .transcendence_indicators
+                .iter()
                 .filter(|i| i.current_value >= i.transcendence_threshold)
                 .count(),
             next_breakthrough_prediction: if transcendence_readiness > 0.8 {
@@ -587,6 +666,12 @@ impl TranscendenceMonitor {
     }
 }
 
+impl Default for QuantumObserver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl QuantumObserver {
     pub fn new() -> Self {
         Self {
@@ -595,12 +680,12 @@ impl QuantumObserver {
             superposition_detector: SuperpositionDetector::new(),
         }
     }
-    
+
     pub async fn observe(&self, realities: &[Reality]) -> Result<QuantumObservation> {
         let entanglement_density = self.entanglement_tracker.calculate_density(realities).await;
         let coherence_level = self.coherence_monitor.measure_coherence(realities).await;
         let superposition_states = self.superposition_detector.detect_states(realities).await;
-        
+
         Ok(QuantumObservation {
             entanglement_density,
             coherence_level,
@@ -608,7 +693,7 @@ impl QuantumObserver {
             quantum_interference_detected: coherence_level < 0.5,
         })
     }
-    
+
     pub async fn generate_summary(&self) -> Result<QuantumSummary> {
         Ok(QuantumSummary {
             total_entanglements_tracked: 0, // Would be from actual tracking
@@ -623,11 +708,17 @@ impl QuantumObserver {
 #[derive(Debug, Clone)]
 pub struct ComplexityCalculator;
 
+impl Default for ComplexityCalculator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ComplexityCalculator {
     pub fn new() -> Self {
         Self
     }
-    
+
     pub fn average_complexity(&self) -> f32 {
         0.6 // Placeholder - would calculate from actual paradox data
     }
@@ -636,6 +727,12 @@ impl ComplexityCalculator {
 #[derive(Debug, Clone)]
 pub struct ThresholdCalculator;
 
+impl Default for ThresholdCalculator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ThresholdCalculator {
     pub fn new() -> Self {
         Self
@@ -645,20 +742,27 @@ impl ThresholdCalculator {
 #[derive(Debug, Clone)]
 pub struct EntanglementTracker;
 
+impl Default for EntanglementTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl EntanglementTracker {
     pub fn new() -> Self {
         Self
     }
-    
+
     pub async fn calculate_density(&self, realities: &[Reality]) -> f32 {
         if realities.is_empty() {
             return 0.0;
         }
-        
-        let total_entanglements: usize = realities.iter()
+
+        let total_entanglements: usize = realities
+            .iter()
             .map(|r| r.consciousness_state.quantum_entanglements.len())
             .sum();
-        
+
         total_entanglements as f32 / realities.len() as f32
     }
 }
@@ -666,20 +770,24 @@ impl EntanglementTracker {
 #[derive(Debug, Clone)]
 pub struct CoherenceMonitor;
 
+impl Default for CoherenceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CoherenceMonitor {
     pub fn new() -> Self {
         Self
     }
-    
+
     pub async fn measure_coherence(&self, realities: &[Reality]) -> f32 {
         if realities.is_empty() {
             return 0.0;
         }
-        
-        let total_coherence: f32 = realities.iter()
-            .map(|r| r.coherence_level)
-            .sum();
-        
+
+        let total_coherence: f32 = realities.iter().map(|r| r.coherence_level).sum();
+
         total_coherence / realities.len() as f32
     }
 }
@@ -687,11 +795,17 @@ impl CoherenceMonitor {
 #[derive(Debug, Clone)]
 pub struct SuperpositionDetector;
 
+impl Default for SuperpositionDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SuperpositionDetector {
     pub fn new() -> Self {
         Self
     }
-    
+
     pub async fn detect_states(&self, _realities: &[Reality]) -> Vec<SuperpositionState> {
         // Would detect actual superposition states
         Vec::new()
@@ -772,4 +886,4 @@ pub struct EvolutionPrediction {
     pub confidence: f32,
     pub time_to_evolution: std::time::Duration,
     pub required_conditions: Vec<String>,
-}
\ No newline at end of file
+}
@@ -0,0 +1,168 @@
+//! Debate round for multi-candidate proposals.
+//!
+//! Every candidate's author reviews every other candidate's diff. The mean
+//! critique score a candidate receives is blended with its validation score
+//! when the engine picks a winner.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::darwin::chat::{parse_json_reply, ChatClient, ChatMessage};
+use crate::darwin::self_improvement::Modification;
+
+/// Characters of each candidate's diff included in a critique prompt
+const MAX_DIFF_CHARS: usize = 6000;
+
+/// One candidate author's review of another candidate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Critique {
+    pub reviewer: Uuid,
+    pub target: Uuid,
+    /// 0.0 (reject) to 1.0 (strongly endorse)
+    pub score: f32,
+    pub rationale: String,
+}
+
+/// Reviews a candidate from the perspective of a competing candidate's author
+#[async_trait]
+pub trait CandidateCritic: Send + Sync {
+    async fn critique(&self, reviewer: &Modification, target: &Modification) -> Result<Critique>;
+}
+
+/// Result of a debate round over a candidate group
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DebateOutcome {
+    pub critiques: Vec<Critique>,
+    /// Mean critique score received by each candidate
+    pub scores: HashMap<Uuid, f32>,
+}
+
+impl DebateOutcome {
+    pub fn score(&self, candidate: Uuid) -> Option<f32> {
+        self.scores.get(&candidate).copied()
+    }
+}
+
+/// Debate configuration enabled on the self-improvement engine
+#[derive(Clone)]
+pub struct DebateMode {
+    critic: Arc<dyn CandidateCritic>,
+    /// Weight of the critique score relative to the validation score
+    pub weight: f32,
+}
+
+impl std::fmt::Debug for DebateMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DebateMode")
+            .field("weight", &self.weight)
+            .finish()
+    }
+}
+
+impl DebateMode {
+    pub fn new(critic: Arc<dyn CandidateCritic>, weight: f32) -> Self {
+        Self { critic, weight }
+    }
+
+    /// Have every candidate review every other candidate. Failed critiques
+    /// are logged and left out of the scores.
+    pub async fn run(&self, candidates: &[Modification]) -> DebateOutcome {
+        let reviews = candidates.iter().flat_map(|reviewer| {
+            candidates
+                .iter()
+                .filter(move |target| target.id != reviewer.id)
+                .map(move |target| async move {
+                    (target.id, self.critic.critique(reviewer, target).await)
+                })
+        });
+
+        let mut outcome = DebateOutcome::default();
+        let mut received: HashMap<Uuid, Vec<f32>> = HashMap::new();
+        for (target, result) in join_all(reviews).await {
+            match result {
+                Ok(mut critique) => {
+                    critique.score = critique.score.clamp(0.0, 1.0);
+                    received.entry(target).or_default().push(critique.score);
+                    outcome.critiques.push(critique);
+                }
+                Err(e) => warn!("Critique of candidate {} failed: {}", target, e),
+            }
+        }
+
+        outcome.scores = received
+            .into_iter()
+            .map(|(id, scores)| (id, scores.iter().sum::<f32>() / scores.len() as f32))
+            .collect();
+        outcome
+    }
+
+    /// Blend a validation score with the candidate's debate score, if any
+    pub fn combined_score(&self, validation_score: f32, critique_score: Option<f32>) -> f32 {
+        validation_score + self.weight * critique_score.unwrap_or(0.0)
+    }
+}
+
+#[derive(Deserialize)]
+struct CritiqueReply {
+    score: f32,
+    rationale: String,
+}
+
+/// Critic that asks an OpenAI-compatible chat model to review diffs
+pub struct LlmCritic {
+    chat: ChatClient,
+}
+
+impl LlmCritic {
+    pub fn new(chat: ChatClient) -> Self {
+        Self { chat }
+    }
+
+    fn render_diffs(modification: &Modification) -> String {
+        let mut rendered = String::new();
+        for change in &modification.code_changes {
+            rendered.push_str(&format!("--- {}\n{}\n", change.file_path, change.diff));
+        }
+        if let Some((idx, _)) = rendered.char_indices().nth(MAX_DIFF_CHARS) {
+            rendered.truncate(idx);
+            rendered.push_str("\n... [truncated]");
+        }
+        rendered
+    }
+}
+
+#[async_trait]
+impl CandidateCritic for LlmCritic {
+    async fn critique(&self, reviewer: &Modification, target: &Modification) -> Result<Critique> {
+        let messages = [
+            ChatMessage::system(
+                "You wrote one candidate solution and are reviewing a competing candidate \
+                 for the same problem. Judge correctness, risk and fit with the codebase, \
+                 not similarity to your own work. Reply with exactly one JSON object: \
+                 {\"score\": number between 0 and 1, \"rationale\": string}.",
+            ),
+            ChatMessage::user(format!(
+                "Your candidate: {}\n{}\n\nCandidate under review: {}\n{}\n{}",
+                reviewer.name,
+                Self::render_diffs(reviewer),
+                target.name,
+                target.description,
+                Self::render_diffs(target)
+            )),
+        ];
+
+        let reply: CritiqueReply = parse_json_reply(&self.chat.complete(&messages).await?)?;
+        Ok(Critique {
+            reviewer: reviewer.id,
+            target: target.id,
+            score: reply.score,
+            rationale: reply.rationale,
+        })
+    }
+}
@@ -0,0 +1,360 @@
+//! Degraded mode for when every LLM provider is down.
+//!
+//! A [`ProviderMonitor`] tracks the chat backends Darwin generates with.
+//! Each provider is marked healthy or failing by periodic health checks
+//! (a one-line probe request) and by the outcome of real requests sent
+//! through [`ProviderMonitor::monitored`]. Once every provider is failing
+//! the monitor enters degraded mode: the self-improvement engine stops
+//! generating new modifications, while proposals it already generated keep
+//! being validated and deployed. Entering and leaving degraded mode is
+//! logged, counted in metrics and posted to alerting webhooks; the monitor
+//! leaves it on its own as soon as a health check reaches any provider.
+//!
+//! Real requests also pass through a circuit breaker per provider, named
+//! `llm:<provider>`, which stops calling a provider after repeated failures.
+//! [`ProviderMonitor::register_circuit_breakers`] lists them with the
+//! node's other breakers so operators can inspect, reset or trip them.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::core::metrics::MetricsCollector;
+use crate::darwin::chat::{ChatBackend, ChatMessage, ChatRequest};
+use crate::nerv::tasks;
+use crate::network::circuit_breaker::{CircuitBreaker, CircuitBreakerRegistry};
+
+/// Settings for provider health checks
+#[derive(Debug, Clone)]
+pub struct DegradationConfig {
+    /// Time between health checks
+    pub check_interval: Duration,
+
+    /// How long a probe may take before its provider counts as failing
+    pub probe_timeout: Duration,
+
+    /// Model named in probe requests
+    pub probe_model: String,
+
+    /// Consecutive failed requests that open a provider's circuit breaker
+    pub breaker_failures: u64,
+
+    /// How long an open breaker waits before letting a request through
+    pub breaker_reset: Duration,
+}
+
+impl Default for DegradationConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(30),
+            probe_timeout: Duration::from_secs(10),
+            probe_model: "gpt-4o-mini".to_string(),
+            breaker_failures: 3,
+            breaker_reset: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Last known state of one provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderState {
+    pub name: String,
+    pub healthy: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_checked: Option<DateTime<Utc>>,
+}
+
+/// Whether Darwin is degraded and why; body of `GET /api/darwin/providers`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DegradationStatus {
+    pub degraded: bool,
+
+    /// When the current degraded period began
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+
+    pub providers: Vec<ProviderState>,
+}
+
+/// Tracks provider health and switches Darwin in and out of degraded mode
+pub struct ProviderMonitor {
+    metrics: Arc<MetricsCollector>,
+    config: DegradationConfig,
+    backends: Vec<(String, Arc<dyn ChatBackend>)>,
+    breakers: Vec<Arc<CircuitBreaker>>,
+    status: RwLock<DegradationStatus>,
+    degraded: AtomicBool,
+    webhooks: Vec<String>,
+    client: reqwest::Client,
+}
+
+impl std::fmt::Debug for ProviderMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let providers: Vec<&str> = self
+            .backends
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        f.debug_struct("ProviderMonitor")
+            .field("providers", &providers)
+            .field("degraded", &self.is_degraded())
+            .finish()
+    }
+}
+
+impl ProviderMonitor {
+    pub fn new(metrics: Arc<MetricsCollector>, config: DegradationConfig) -> Self {
+        Self {
+            metrics,
+            config,
+            backends: Vec::new(),
+            breakers: Vec::new(),
+            status: RwLock::new(DegradationStatus::default()),
+            degraded: AtomicBool::new(false),
+            webhooks: Vec::new(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Track a provider, healthy until shown otherwise
+    pub fn with_provider(mut self, name: &str, backend: Arc<dyn ChatBackend>) -> Self {
+        self.backends.push((name.to_string(), backend));
+        self.breakers.push(Arc::new(CircuitBreaker::new(
+            &format!("llm:{}", name),
+            self.config.breaker_failures,
+            self.config.breaker_reset,
+            self.config.probe_timeout,
+        )));
+        self.status.get_mut().providers.push(ProviderState {
+            name: name.to_string(),
+            healthy: true,
+            last_error: None,
+            last_checked: None,
+        });
+        self
+    }
+
+    /// Post alerts about entering and leaving degraded mode to these URLs
+    pub fn with_webhooks(mut self, webhooks: Vec<String>) -> Self {
+        self.webhooks = webhooks;
+        self
+    }
+
+    /// A backend that sends requests to the named provider and reports how
+    /// they went, so failures in real traffic count without waiting for
+    /// the next health check
+    pub fn monitored(self: &Arc<Self>, name: &str) -> Option<Arc<dyn ChatBackend>> {
+        let index = self.backends.iter().position(|(n, _)| n == name)?;
+        Some(Arc::new(MonitoredBackend {
+            monitor: self.clone(),
+            index,
+        }))
+    }
+
+    /// Add the providers' circuit breakers to `registry`
+    pub async fn register_circuit_breakers(&self, registry: &CircuitBreakerRegistry) {
+        for breaker in &self.breakers {
+            registry.register(breaker.clone()).await;
+        }
+    }
+
+    /// Whether generation should be paused
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::SeqCst)
+    }
+
+    pub async fn status(&self) -> DegradationStatus {
+        self.status.read().await.clone()
+    }
+
+    /// Probe every provider and update degraded mode from the results
+    pub async fn check(&self) -> DegradationStatus {
+        let probe = ChatRequest {
+            model: self.config.probe_model.clone(),
+            messages: vec![ChatMessage::user("Reply with OK.")],
+            temperature: 0.0,
+        };
+        let mut outcomes = Vec::with_capacity(self.backends.len());
+        for (_, backend) in &self.backends {
+            let outcome =
+                match tokio::time::timeout(self.config.probe_timeout, backend.complete(&probe))
+                    .await
+                {
+                    Ok(Ok(_)) => Ok(()),
+                    Ok(Err(e)) => Err(e.to_string()),
+                    Err(_) => Err(format!(
+                        "health check timed out after {:?}",
+                        self.config.probe_timeout
+                    )),
+                };
+            outcomes.push(outcome);
+        }
+        self.metrics
+            .increment_counter("darwin.providers.health_checks", 1)
+            .await;
+
+        for (index, outcome) in outcomes.into_iter().enumerate() {
+            self.record(index, outcome).await;
+        }
+        self.status().await
+    }
+
+    /// Note how a request to a provider went and re-evaluate degraded mode
+    async fn record(&self, index: usize, outcome: Result<(), String>) {
+        let transition = {
+            let mut status = self.status.write().await;
+            let Some(provider) = status.providers.get_mut(index) else {
+                return;
+            };
+            provider.last_checked = Some(Utc::now());
+            match outcome {
+                Ok(()) => {
+                    provider.healthy = true;
+                    provider.last_error = None;
+                }
+                Err(e) => {
+                    if provider.healthy {
+                        warn!("LLM provider {} is failing: {}", provider.name, e);
+                    }
+                    provider.healthy = false;
+                    provider.last_error = Some(e);
+                }
+            }
+
+            let all_failing =
+                !status.providers.is_empty() && status.providers.iter().all(|p| !p.healthy);
+            if all_failing == status.degraded {
+                None
+            } else {
+                status.degraded = all_failing;
+                status.since = all_failing.then(Utc::now);
+                self.degraded.store(all_failing, Ordering::SeqCst);
+                Some(status.clone())
+            }
+        };
+
+        let healthy = self
+            .status
+            .read()
+            .await
+            .providers
+            .iter()
+            .filter(|p| p.healthy)
+            .count();
+        self.metrics
+            .set_gauge("darwin.providers.healthy", healthy as u64)
+            .await;
+        if let Some(status) = transition {
+            self.alert(&status).await;
+        }
+    }
+
+    async fn alert(&self, status: &DegradationStatus) {
+        let text = if status.degraded {
+            let errors: Vec<String> = status
+                .providers
+                .iter()
+                .map(|p| {
+                    format!(
+                        "{}: {}",
+                        p.name,
+                        p.last_error.as_deref().unwrap_or("unknown")
+                    )
+                })
+                .collect();
+            error!(
+                "All LLM providers are failing; pausing modification generation ({})",
+                errors.join("; ")
+            );
+            self.metrics
+                .increment_counter("darwin.degraded.entered", 1)
+                .await;
+            format!(
+                "Darwin entered degraded mode: all LLM providers are failing ({}). \
+                 Generation is paused; validation continues.",
+                errors.join("; ")
+            )
+        } else {
+            info!("An LLM provider recovered; resuming modification generation");
+            self.metrics
+                .increment_counter("darwin.degraded.recovered", 1)
+                .await;
+            "Darwin left degraded mode: an LLM provider recovered and generation resumed."
+                .to_string()
+        };
+        self.metrics
+            .set_gauge("darwin.degraded", status.degraded as u64)
+            .await;
+
+        let payload = serde_json::json!({ "text": text, "status": status });
+        for url in &self.webhooks {
+            let sent = self
+                .client
+                .post(url)
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status());
+            if let Err(e) = sent {
+                warn!("Failed to post degradation alert to {}: {}", url, e);
+            }
+        }
+    }
+
+    /// Check the providers every `check_interval`
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        info!(
+            "Starting LLM provider health checks for {} provider(s) (interval: {:?})",
+            self.backends.len(),
+            self.config.check_interval
+        );
+
+        tasks::spawn("darwin", "provider health check", async move {
+            loop {
+                self.check().await;
+                tokio::time::sleep(self.config.check_interval).await;
+            }
+        })
+    }
+}
+
+/// Sends requests to a provider through its circuit breaker and reports
+/// their outcome to the monitor
+struct MonitoredBackend {
+    monitor: Arc<ProviderMonitor>,
+    index: usize,
+}
+
+#[async_trait]
+impl ChatBackend for MonitoredBackend {
+    async fn complete(&self, request: &ChatRequest) -> Result<String> {
+        let breaker = &self.monitor.breakers[self.index];
+        if !breaker.can_execute().await {
+            return Err(anyhow!("Circuit '{}' is open", breaker.name()));
+        }
+        let started = std::time::Instant::now();
+        let reply = self.monitor.backends[self.index].1.complete(request).await;
+        breaker.record_response_time(started.elapsed()).await;
+        let outcome = match &reply {
+            Ok(_) => {
+                breaker.on_success().await;
+                Ok(())
+            }
+            Err(e) => {
+                breaker.on_failure().await;
+                Err(e.to_string())
+            }
+        };
+        self.monitor.record(self.index, outcome).await;
+        reply
+    }
+}
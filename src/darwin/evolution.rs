@@ -1,7 +1,6 @@
 use crate::core::vector::Vector;
 use rand::prelude::*;
 use std::collections::HashMap;
-use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
@@ -48,6 +47,7 @@ pub struct EvolutionEngine {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 struct Model {
     id: Uuid,
     name: String,
@@ -57,6 +57,12 @@ struct Model {
     updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+impl Default for EvolutionEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl EvolutionEngine {
     pub fn new() -> Self {
         Self {
@@ -130,8 +136,8 @@ impl EvolutionEngine {
     fn fitness(&self, chromosome: &Chromosome, observations: &Vec<Vector>) -> f32 {
         let mut error = 0.0;
         for obs in observations {
-            for (key, value) in &chromosome.genes {
-                if let Some(obs_val) = obs.values.get(0) {
+            for value in chromosome.genes.values() {
+                if let Some(obs_val) = obs.values.first() {
                     error += (value - obs_val).powi(2);
                 }
             }
@@ -4,10 +4,11 @@ use rand::prelude::*;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, error, info, warn};
+use tracing::info;
 use uuid::Uuid;
 
 use crate::core::metrics::MetricsCollector;
+use crate::darwin::objectives::{Objective, ObjectiveConfig, ObjectiveStatus};
 use crate::darwin::self_improvement::Modification;
 
 /// Strategy for exploring potential system improvements
@@ -24,9 +25,13 @@ pub struct ExplorationStrategy {
 
     /// Novelty archive for quality-diversity
     novelty_archive: RwLock<Vec<NoveltyPoint>>,
+
+    /// Objectives that steer new proposals
+    objectives: RwLock<Vec<Objective>>,
 }
 
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct ArchiveEntry {
     /// The modification
     modification: Modification,
@@ -42,7 +47,8 @@ struct ArchiveEntry {
 }
 
 #[derive(Debug, Clone)]
-struct ExplorationParameters {
+#[allow(dead_code)]
+pub struct ExplorationParameters {
     /// Mutation rate
     mutation_rate: f32,
 
@@ -64,6 +70,7 @@ struct ExplorationParameters {
 
 /// Point in novelty space
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct NoveltyPoint {
     /// ID of the solution
     id: Uuid,
@@ -92,7 +99,81 @@ impl ExplorationStrategy {
                 exploration_rate: 0.2,
             }),
             novelty_archive: RwLock::new(Vec::new()),
+            objectives: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Replace the objectives proposals are steered towards
+    pub async fn set_objectives(&self, config: ObjectiveConfig) {
+        *self.objectives.write().await = config.objectives;
+    }
+
+    pub async fn get_objectives(&self) -> Vec<Objective> {
+        self.objectives.read().await.clone()
+    }
+
+    /// Sample an objective, favouring heavily weighted ones that are furthest
+    /// from their targets. Satisfied objectives are rarely picked.
+    pub async fn sample_objective(&self) -> Option<(Objective, ObjectiveStatus)> {
+        let objectives = self.objectives.read().await.clone();
+        if objectives.is_empty() {
+            return None;
         }
+
+        let mut statuses = Vec::with_capacity(objectives.len());
+        for objective in &objectives {
+            statuses.push(objective.status(&self.metrics).await);
+        }
+
+        let weights: Vec<f64> = objectives
+            .iter()
+            .zip(&statuses)
+            .map(|(objective, status)| {
+                let urgency = if status.satisfied {
+                    0.1
+                } else if status.current.is_none() {
+                    0.5
+                } else {
+                    1.0 + status.shortfall.min(10.0)
+                };
+                objective.weight as f64 * urgency
+            })
+            .collect();
+
+        let distribution = rand::distributions::WeightedIndex::new(&weights).ok()?;
+        let idx = distribution.sample(&mut rand::thread_rng());
+        Some((objectives[idx].clone(), statuses[idx].clone()))
+    }
+
+    /// A proposal aimed at a sampled objective, if any are configured
+    pub async fn objective_proposal(&self) -> Option<Modification> {
+        let (objective, status) = self.sample_objective().await?;
+        let current = status
+            .current
+            .map(|v| format!("{:.3}", v))
+            .unwrap_or_else(|| "unmeasured".to_string());
+
+        self.metrics
+            .increment_counter("darwin.exploration.objective_proposals", 1)
+            .await;
+
+        Some(Modification {
+            id: Uuid::new_v4(),
+            name: format!("Objective: {}", objective.name),
+            description: format!(
+                "{} (current: {}, target: {})",
+                objective.describe(),
+                current,
+                status.target
+            ),
+            code_changes: Vec::new(),
+            validation_metrics: HashMap::new(),
+            created_at: chrono::Utc::now(),
+            status: crate::darwin::self_improvement::ModificationStatus::Proposed,
+            consciousness_level: None,
+            paradigm_shift_potential: None,
+            integrated_paradoxes: Vec::new(),
+        })
     }
 
     /// Generate new modification proposals
@@ -106,9 +187,17 @@ impl ExplorationStrategy {
         let archive_len = archive.len();
         let params = self.parameters.read().await;
 
-        // If archive is empty, generate some initial proposals
+        // Steer at least one proposal towards a configured objective
+        if let Some(proposal) = self.objective_proposal().await {
+            proposals.push(proposal);
+        }
+
+        // If archive is empty, generate some initial proposals unless an
+        // objective already gave us somewhere to start
         if archive_len == 0 {
-            proposals.extend(self.generate_initial_proposals().await?);
+            if proposals.is_empty() {
+                proposals.extend(self.generate_initial_proposals().await?);
+            }
         } else {
             // Generate proposals through various strategies
 
@@ -154,6 +243,9 @@ impl ExplorationStrategy {
             validation_metrics: HashMap::new(),
             created_at: chrono::Utc::now(),
             status: crate::darwin::self_improvement::ModificationStatus::Proposed,
+            consciousness_level: None,
+            paradigm_shift_potential: None,
+            integrated_paradoxes: Vec::new(),
         };
 
         proposals.push(proposal);
@@ -225,11 +317,14 @@ impl ExplorationStrategy {
                     "Crossover of {} and {}",
                     parent1.modification.name, parent2.modification.name
                 ),
-                description: format!("Combined features from multiple parent modifications"),
+                description: "Combined features from multiple parent modifications".to_string(),
                 code_changes: Vec::new(), // Would contain actual code changes from crossover
                 validation_metrics: HashMap::new(),
                 created_at: chrono::Utc::now(),
                 status: crate::darwin::self_improvement::ModificationStatus::Proposed,
+                consciousness_level: None,
+                paradigm_shift_potential: None,
+                integrated_paradoxes: Vec::new(),
             };
 
             proposals.push(proposal);
@@ -241,7 +336,7 @@ impl ExplorationStrategy {
     /// Generate proposals using novelty search
     async fn generate_novelty_search(
         &self,
-        archive: &DashMap<String, ArchiveEntry>,
+        _archive: &DashMap<String, ArchiveEntry>,
     ) -> Result<Vec<Modification>> {
         // In a real implementation, this would use novelty search to explore
         // underrepresented areas of the solution space
@@ -259,6 +354,9 @@ impl ExplorationStrategy {
                 validation_metrics: HashMap::new(),
                 created_at: chrono::Utc::now(),
                 status: crate::darwin::self_improvement::ModificationStatus::Proposed,
+                consciousness_level: None,
+                paradigm_shift_potential: None,
+                integrated_paradoxes: Vec::new(),
             };
 
             return Ok(vec![proposal]);
@@ -311,17 +409,21 @@ impl ExplorationStrategy {
         let proposal = Modification {
             id: Uuid::new_v4(),
             name: "Novelty search proposal".to_string(),
-            description: format!("Exploring new optimization strategies with targeted features"),
+            description: "Exploring new optimization strategies with targeted features".to_string(),
             code_changes: Vec::new(), // Would contain actual code changes
             validation_metrics: HashMap::new(),
             created_at: chrono::Utc::now(),
             status: crate::darwin::self_improvement::ModificationStatus::Proposed,
+            consciousness_level: None,
+            paradigm_shift_potential: None,
+            integrated_paradoxes: Vec::new(),
         };
 
         Ok(vec![proposal])
     }
 
     /// Tournament selection for choosing parents
+    #[allow(dead_code)]
     async fn tournament_selection(
         &self,
         archive: &DashMap<String, ArchiveEntry>,
@@ -454,6 +556,7 @@ impl ExplorationStrategy {
     }
 
     /// Calculate novelty score for a solution
+    #[allow(dead_code)]
     async fn calculate_novelty_score(&self, features: &HashMap<String, f32>) -> f32 {
         let novelty_archive = self.novelty_archive.read().await;
 
@@ -480,6 +583,7 @@ impl ExplorationStrategy {
     }
 
     /// Calculate distance between feature vectors
+    #[allow(dead_code)]
     fn feature_distance(&self, a: &HashMap<String, f32>, b: &HashMap<String, f32>) -> f32 {
         let mut sum_squared_diff = 0.0;
         let mut feature_count = 0;
@@ -514,7 +618,7 @@ impl ExplorationStrategy {
             .iter()
             .map(|e| (e.key().clone(), e.value().clone()))
             .collect();
-        entries.sort_by(|a, b| b.1.added_at.cmp(&a.1.added_at));
+        entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.added_at));
 
         let params = self.parameters.read().await;
         entries.truncate(params.max_archive_size);
@@ -539,11 +643,11 @@ impl ExplorationStrategy {
         let mut params = self.parameters.write().await;
 
         if let Some(rate) = mutation_rate {
-            params.mutation_rate = rate.max(0.0).min(1.0);
+            params.mutation_rate = rate.clamp(0.0, 1.0);
         }
 
         if let Some(rate) = crossover_rate {
-            params.crossover_rate = rate.max(0.0).min(1.0);
+            params.crossover_rate = rate.clamp(0.0, 1.0);
         }
 
         if let Some(threshold) = novelty_threshold {
@@ -551,7 +655,7 @@ impl ExplorationStrategy {
         }
 
         if let Some(rate) = exploration_rate {
-            params.exploration_rate = rate.max(0.0).min(1.0);
+            params.exploration_rate = rate.clamp(0.0, 1.0);
         }
 
         info!("Updated exploration parameters: mutation_rate={:.2}, crossover_rate={:.2}, novelty_threshold={:.2}, exploration_rate={:.2}",
@@ -596,8 +700,6 @@ impl ExplorationStrategy {
 
         // Simple coverage metric based on number of features and points
         let feature_count = feature_keys.len();
-        let point_count = novelty_archive.len();
-
         if feature_count == 0 {
             return 0.0;
         }
@@ -620,7 +722,7 @@ impl ExplorationStrategy {
             let max = values.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
 
             if min.is_finite() && max.is_finite() && max > min {
-                total_dispersion += (max - min);
+                total_dispersion += max - min;
             }
         }
 
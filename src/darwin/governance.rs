@@ -0,0 +1,160 @@
+//! Community governance over deployed modifications.
+//!
+//! A deployed modification is disputed by opening a Holochain arbitration
+//! case whose content hash is the modification's [`modification_subject`].
+//! When the votes close the case, the arbitration zome records the decision
+//! and its proof in an audit trail and signals a [`DisputeDecision`] to
+//! every peer. Each node's conductor client hands the decision to an
+//! [`ArbitrationRollback`], which rolls the modification back if the dispute
+//! was upheld.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, watch};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::core::metrics::MetricsCollector;
+use crate::darwin::self_improvement::{ModificationStatus, SelfImprovementEngine};
+
+/// Prefix of arbitration case subjects that refer to a modification
+pub const MODIFICATION_SUBJECT_PREFIX: &str = "darwin_modification:";
+
+/// Arbitration case subject disputing a modification
+pub fn modification_subject(id: Uuid) -> String {
+    format!("{}{}", MODIFICATION_SUBJECT_PREFIX, id)
+}
+
+/// The modification a case subject refers to, if any
+pub fn modification_from_subject(subject: &str) -> Option<Uuid> {
+    subject
+        .strip_prefix(MODIFICATION_SUBJECT_PREFIX)
+        .and_then(|id| Uuid::parse_str(id).ok())
+}
+
+/// How an arbitration case was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeOutcome {
+    /// The community agreed with the dispute
+    Upheld,
+    Dismissed,
+}
+
+/// Signal sent by the arbitration zome when a case is closed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisputeDecision {
+    pub case_id: String,
+    /// Content hash of the case, see [`modification_subject`]
+    pub subject: String,
+    pub outcome: DisputeOutcome,
+    pub resolution: String,
+    /// Hex-encoded hash of the votes that decided the case, also recorded
+    /// in the decision's audit trail
+    pub decision_proof: String,
+    pub votes: usize,
+    /// Microseconds since the epoch
+    pub decided_at: u64,
+}
+
+/// What handling a decision did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GovernanceAction {
+    RolledBack,
+    /// The dispute was dismissed; the modification stays
+    Dismissed,
+    /// The modification isn't deployed, e.g. it was already rolled back
+    NotDeployed,
+    /// The case isn't about a modification
+    NotAModification,
+}
+
+/// Rolls back modifications when arbitration upholds a dispute about them
+pub struct ArbitrationRollback {
+    engine: Arc<SelfImprovementEngine>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl ArbitrationRollback {
+    pub fn new(engine: Arc<SelfImprovementEngine>, metrics: Arc<MetricsCollector>) -> Self {
+        Self { engine, metrics }
+    }
+
+    /// Handle a decision as delivered by the conductor client, JSON-encoded
+    pub async fn handle_json(&self, payload: &[u8]) -> Result<GovernanceAction> {
+        let decision: DisputeDecision = serde_json::from_slice(payload)
+            .map_err(|e| anyhow!("Invalid dispute decision: {}", e))?;
+        self.handle(&decision).await
+    }
+
+    pub async fn handle(&self, decision: &DisputeDecision) -> Result<GovernanceAction> {
+        let result = self.apply(decision).await;
+        let counter = match &result {
+            Ok(GovernanceAction::RolledBack) => "darwin.governance.rollbacks",
+            Ok(GovernanceAction::Dismissed) => "darwin.governance.dismissed",
+            Ok(GovernanceAction::NotDeployed | GovernanceAction::NotAModification) => {
+                "darwin.governance.ignored"
+            }
+            Err(_) => "darwin.governance.failed",
+        };
+        self.metrics.increment_counter(counter, 1).await;
+        result
+    }
+
+    async fn apply(&self, decision: &DisputeDecision) -> Result<GovernanceAction> {
+        let Some(modification_id) = modification_from_subject(&decision.subject) else {
+            return Ok(GovernanceAction::NotAModification);
+        };
+        if decision.outcome == DisputeOutcome::Dismissed {
+            info!(
+                "Arbitration case {} dismissed the dispute about modification {}",
+                decision.case_id, modification_id
+            );
+            return Ok(GovernanceAction::Dismissed);
+        }
+
+        // Decisions may be delivered more than once
+        let modification = self.engine.get_modification(modification_id).await?;
+        if modification.status != ModificationStatus::Deployed {
+            return Ok(GovernanceAction::NotDeployed);
+        }
+
+        let reason = format!(
+            "arbitration case {} upheld by {} votes (proof {}): {}",
+            decision.case_id, decision.votes, decision.decision_proof, decision.resolution
+        );
+        self.engine
+            .revert_modification(modification_id, &reason)
+            .await?;
+        Ok(GovernanceAction::RolledBack)
+    }
+
+    /// Handle decisions from `decisions` until it closes or `shutdown`
+    /// becomes true. Failures are logged and left for operators to resolve.
+    pub async fn run(
+        self,
+        mut decisions: mpsc::Receiver<DisputeDecision>,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        info!("Starting arbitration rollback handler");
+        while !*shutdown.borrow() {
+            tokio::select! {
+                decision = decisions.recv() => {
+                    let Some(decision) = decision else {
+                        break;
+                    };
+                    if let Err(e) = self.handle(&decision).await {
+                        warn!(
+                            "Failed to apply arbitration case {}: {}",
+                            decision.case_id, e
+                        );
+                    }
+                }
+                _ = shutdown.changed() => {}
+            }
+        }
+        info!("Stopped arbitration rollback handler");
+    }
+}
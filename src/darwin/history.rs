@@ -0,0 +1,501 @@
+//! Durable history of proposed modifications.
+//!
+//! The self-improvement engine keeps every modification it proposes here,
+//! up to a bounded number of the most recent ones. When opened with a path,
+//! each change to a modification (proposal, status or metrics update, or
+//! pin) is appended to a JSON-lines file as a full snapshot, and the latest
+//! snapshot of each modification is reloaded on restart. A modification
+//! dropped to make room gets a tombstone line, so it stays dropped after a
+//! restart even before the file is compacted. Once the file holds more than
+//! twice as many lines as the history may keep modifications, it is rewritten
+//! with one snapshot per retained modification. Like the lifecycle log, each
+//! line carries a checksum; corrupted lines, and lines that pass the checksum
+//! but no longer parse, are quarantined on load. The file is written by a
+//! dedicated thread, so changes never wait on disk while holding the lock.
+//!
+//! Deployed modifications, and ones a rollback point refers to, are never
+//! dropped to make room, since undoing them needs their original contents.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use tokio::sync::{oneshot, RwLock};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::core::checksum::{self, Quarantine};
+use crate::darwin::self_improvement::{Modification, ModificationStatus};
+use crate::utils::fs::write_atomic;
+
+/// Modifications kept unless configured otherwise
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+/// Which modifications a query returns; unset fields match everything
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryQuery {
+    pub status: Option<ModificationStatus>,
+
+    /// Proposed at or after this time
+    pub from: Option<DateTime<Utc>>,
+
+    /// Proposed before this time
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl HistoryQuery {
+    pub fn matches(&self, modification: &Modification) -> bool {
+        if let Some(status) = &self.status {
+            if modification.status != *status {
+                return false;
+            }
+        }
+        if let Some(from) = self.from {
+            if modification.created_at < from {
+                return false;
+            }
+        }
+        match self.to {
+            Some(to) => modification.created_at < to,
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct HistoryState {
+    /// Retained modifications in the order they were proposed
+    modifications: Vec<Modification>,
+
+    /// Modifications kept regardless of age, e.g. for rollback points
+    pinned: HashSet<Uuid>,
+}
+
+/// One line of the history file
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum HistoryLine {
+    /// A modification was dropped to make room
+    Dropped {
+        dropped: Uuid,
+    },
+    Snapshot(Snapshot),
+}
+
+/// A modification as of one change, with whether it was pinned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Snapshot {
+    #[serde(flatten)]
+    modification: Modification,
+    /// Absent from files written before pins were saved
+    #[serde(default)]
+    pinned: bool,
+}
+
+/// Changes for the writer thread to persist, in the order they were made
+#[derive(Debug)]
+struct WriteJob {
+    /// Snapshots to append
+    snapshots: Vec<Snapshot>,
+    /// Modifications no longer retained
+    dropped: Vec<Uuid>,
+    capacity: usize,
+    /// Signalled once the job is on disk
+    done: Option<oneshot::Sender<()>>,
+}
+
+/// Store for the engine's modifications, optionally persisted to disk
+#[derive(Debug)]
+pub struct ModificationHistory {
+    state: RwLock<HistoryState>,
+    /// Queue of the writer thread owning the file, when persisted
+    writer: Option<mpsc::Sender<WriteJob>>,
+    capacity: usize,
+    /// Corrupted snapshots set aside when the history was loaded
+    quarantined: usize,
+}
+
+impl Default for ModificationHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModificationHistory {
+    /// In-memory history; modifications are lost on restart
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(HistoryState::default()),
+            writer: None,
+            capacity: DEFAULT_CAPACITY,
+            quarantined: 0,
+        }
+    }
+
+    /// History persisted to a JSON-lines file, loading the modifications
+    /// already in it
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut state = HistoryState::default();
+        let mut quarantined = 0;
+        let mut lines = 0;
+
+        if path.exists() {
+            let sealed = checksum::read_sealed_lines(&path)
+                .map_err(|e| anyhow!("Failed to read modification history: {}", e))?;
+            quarantined = sealed.quarantined;
+            let quarantine = Quarantine::beside(&path);
+            for (line_no, line) in sealed.records {
+                lines += 1;
+                let snapshot = match serde_json::from_str(&line) {
+                    Ok(HistoryLine::Snapshot(snapshot)) => snapshot,
+                    Ok(HistoryLine::Dropped { dropped }) => {
+                        state.modifications.retain(|m| m.id != dropped);
+                        state.pinned.remove(&dropped);
+                        continue;
+                    }
+                    Err(e) => {
+                        // Intact but unreadable, e.g. written by another
+                        // version; set aside like a corrupted line
+                        warn!(
+                            "Quarantining invalid modification at {}:{}: {}",
+                            path.display(),
+                            line_no,
+                            e
+                        );
+                        if let Err(e) = quarantine.keep(&path, line_no, &e.to_string(), &line) {
+                            warn!("Failed to quarantine record: {}", e);
+                        }
+                        quarantined += 1;
+                        continue;
+                    }
+                };
+                let mut modification = snapshot.modification;
+                if snapshot.pinned {
+                    state.pinned.insert(modification.id);
+                } else {
+                    state.pinned.remove(&modification.id);
+                }
+                // A validation cut short by the restart has to run again
+                if modification.status == ModificationStatus::Validating {
+                    modification.status = ModificationStatus::Proposed;
+                }
+                match state
+                    .modifications
+                    .iter_mut()
+                    .find(|m| m.id == modification.id)
+                {
+                    Some(existing) => *existing = modification,
+                    None => state.modifications.push(modification),
+                }
+            }
+            info!(
+                "Loaded {} modifications from {}",
+                state.modifications.len(),
+                path.display()
+            );
+        } else if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let writer = HistoryWriter {
+            path,
+            lines,
+            retained: state
+                .modifications
+                .iter()
+                .map(|m| {
+                    let snapshot = state.snapshot(m);
+                    Ok((m.id, sealed_line(&HistoryLine::Snapshot(snapshot))?))
+                })
+                .collect::<Result<_>>()?,
+        };
+        let (sender, jobs) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("modification-history".into())
+            .spawn(move || writer.run(jobs))?;
+
+        Ok(Self {
+            state: RwLock::new(state),
+            writer: Some(sender),
+            capacity: DEFAULT_CAPACITY,
+            quarantined,
+        })
+    }
+
+    /// Keep at most `capacity` modifications, dropping the oldest that are
+    /// neither deployed nor pinned
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        let dropped = trim(self.state.get_mut(), self.capacity);
+        self.send(Vec::new(), dropped, None);
+        self
+    }
+
+    /// Add newly proposed modifications and persist them
+    pub async fn insert(&self, modifications: &[Modification]) {
+        let mut state = self.state.write().await;
+        state.modifications.extend(modifications.iter().cloned());
+        let dropped = trim(&mut state, self.capacity);
+        // Modifications dropped straight away never reach the file
+        let snapshots = modifications
+            .iter()
+            .filter(|m| !dropped.contains(&m.id))
+            .map(|m| state.snapshot(m))
+            .collect();
+        let written = self.persist(snapshots, dropped);
+        drop(state);
+        written.await;
+    }
+
+    /// Change a modification in place and persist the result
+    pub async fn update<F>(&self, id: Uuid, change: F) -> Result<Modification>
+    where
+        F: FnOnce(&mut Modification),
+    {
+        let mut state = self.state.write().await;
+        let modification = state
+            .modifications
+            .iter_mut()
+            .find(|m| m.id == id)
+            .ok_or_else(|| anyhow!("Modification with ID {} not found", id))?;
+        change(modification);
+        let modification = modification.clone();
+        let snapshot = state.snapshot(&modification);
+        let written = self.persist(vec![snapshot], Vec::new());
+        drop(state);
+        written.await;
+        Ok(modification)
+    }
+
+    /// Keep these modifications however old they get, e.g. because a
+    /// rollback point refers to them
+    pub async fn pin(&self, ids: &[Uuid]) {
+        let mut state = self.state.write().await;
+        state.pinned.extend(ids.iter().copied());
+        let snapshots = state
+            .modifications
+            .iter()
+            .filter(|m| ids.contains(&m.id))
+            .map(|m| state.snapshot(m))
+            .collect();
+        let written = self.persist(snapshots, Vec::new());
+        drop(state);
+        written.await;
+    }
+
+    pub async fn get(&self, id: Uuid) -> Option<Modification> {
+        self.state
+            .read()
+            .await
+            .modifications
+            .iter()
+            .find(|m| m.id == id)
+            .cloned()
+    }
+
+    /// Every retained modification in the order it was proposed
+    pub async fn all(&self) -> Vec<Modification> {
+        self.state.read().await.modifications.clone()
+    }
+
+    /// Retained modifications matching `query`, in the order they were
+    /// proposed
+    pub async fn query(&self, query: &HistoryQuery) -> Vec<Modification> {
+        self.state
+            .read()
+            .await
+            .modifications
+            .iter()
+            .filter(|m| query.matches(m))
+            .cloned()
+            .collect()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.state.read().await.modifications.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// Snapshots that failed their checksum when the history was opened;
+    /// they were copied to `<path>.quarantine`
+    pub fn quarantined_records(&self) -> usize {
+        self.quarantined
+    }
+
+    /// Queue a change for the writer while the caller holds the state lock,
+    /// so the file stays in the order changes were made, and return a future
+    /// that completes once it is written
+    fn persist(
+        &self,
+        snapshots: Vec<Snapshot>,
+        dropped: Vec<Uuid>,
+    ) -> impl std::future::Future<Output = ()> {
+        let (done, written) = oneshot::channel();
+        let queued = self.send(snapshots, dropped, Some(done));
+        async move {
+            if queued {
+                // The writer only goes away with the history itself
+                let _ = written.await;
+            }
+        }
+    }
+
+    fn send(
+        &self,
+        snapshots: Vec<Snapshot>,
+        dropped: Vec<Uuid>,
+        done: Option<oneshot::Sender<()>>,
+    ) -> bool {
+        let Some(writer) = &self.writer else {
+            return false;
+        };
+        let job = WriteJob {
+            snapshots,
+            dropped,
+            capacity: self.capacity,
+            done,
+        };
+        if writer.send(job).is_err() {
+            warn!("Modification history writer has stopped");
+            return false;
+        }
+        true
+    }
+}
+
+impl HistoryState {
+    fn snapshot(&self, modification: &Modification) -> Snapshot {
+        Snapshot {
+            modification: modification.clone(),
+            pinned: self.pinned.contains(&modification.id),
+        }
+    }
+}
+
+fn sealed_line(line: &HistoryLine) -> Result<String> {
+    Ok(checksum::seal(&serde_json::to_string(line)?))
+}
+
+/// Owner of the history file on the writer thread
+struct HistoryWriter {
+    path: PathBuf,
+    /// Snapshots currently in the file
+    lines: usize,
+    /// Latest sealed snapshot of each retained modification, in proposal
+    /// order
+    retained: Vec<(Uuid, String)>,
+}
+
+impl HistoryWriter {
+    fn run(mut self, jobs: mpsc::Receiver<WriteJob>) {
+        while let Ok(job) = jobs.recv() {
+            self.write(&job);
+            if let Some(done) = job.done {
+                let _ = done.send(());
+            }
+        }
+    }
+
+    fn write(&mut self, job: &WriteJob) {
+        for snapshot in &job.snapshots {
+            let id = snapshot.modification.id;
+            let line = match sealed_line(&HistoryLine::Snapshot(snapshot.clone())) {
+                Ok(line) => line,
+                Err(e) => {
+                    warn!("Failed to persist modification {}: {}", id, e);
+                    continue;
+                }
+            };
+            match self.append(&line) {
+                Ok(()) => self.lines += 1,
+                Err(e) => warn!("Failed to persist modification {}: {}", id, e),
+            }
+            match self
+                .retained
+                .iter_mut()
+                .find(|(retained, _)| *retained == id)
+            {
+                Some((_, latest)) => *latest = line,
+                None => self.retained.push((id, line)),
+            }
+        }
+        for &id in &job.dropped {
+            let Some(position) = self
+                .retained
+                .iter()
+                .position(|(retained, _)| *retained == id)
+            else {
+                continue;
+            };
+            self.retained.remove(position);
+            // Without a tombstone the dropped modification's snapshots
+            // would be reloaded until the next compaction
+            match sealed_line(&HistoryLine::Dropped { dropped: id }).and_then(|l| self.append(&l)) {
+                Ok(()) => self.lines += 1,
+                Err(e) => warn!("Failed to record dropped modification {}: {}", id, e),
+            }
+        }
+        if let Err(e) = self.compact_if_needed(job.capacity) {
+            warn!("Failed to compact modification history: {}", e);
+        }
+    }
+
+    fn append(&self, line: &str) -> Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
+    /// Rewrite the file with one snapshot per retained modification once it
+    /// has grown past twice the capacity
+    fn compact_if_needed(&mut self, capacity: usize) -> Result<()> {
+        if self.lines <= capacity * 2 {
+            return Ok(());
+        }
+
+        write_atomic(&self.path, |file| {
+            for (_, line) in &self.retained {
+                writeln!(file, "{}", line)?;
+            }
+            Ok(())
+        })?;
+
+        info!(
+            "Compacted modification history from {} to {} snapshots",
+            self.lines,
+            self.retained.len()
+        );
+        self.lines = self.retained.len();
+        Ok(())
+    }
+}
+
+/// Drop the oldest modifications beyond `capacity`, except deployed and
+/// pinned ones, and return the IDs dropped. The history only exceeds its
+/// capacity when those alone don't fit.
+fn trim(state: &mut HistoryState, capacity: usize) -> Vec<Uuid> {
+    let excess = state.modifications.len().saturating_sub(capacity);
+    if excess == 0 {
+        return Vec::new();
+    }
+    let mut by_age: Vec<_> = state
+        .modifications
+        .iter()
+        .filter(|m| m.status != ModificationStatus::Deployed && !state.pinned.contains(&m.id))
+        .map(|m| (m.created_at, m.id))
+        .collect();
+    by_age.sort();
+    let dropped: Vec<Uuid> = by_age.iter().take(excess).map(|(_, id)| *id).collect();
+    state.modifications.retain(|m| !dropped.contains(&m.id));
+    dropped
+}
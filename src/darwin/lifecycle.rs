@@ -0,0 +1,196 @@
+//! Typed lifecycle events for modifications.
+//!
+//! Every transition a modification goes through (proposal, each validation
+//! stage, acceptance, deployment, rollback) is published to subscribers and
+//! kept per modification so its timeline can be audited later. When opened
+//! with a path, events are also appended to a JSON-lines file and reloaded
+//! on restart. Each line carries a checksum; corrupted lines are quarantined
+//! on load and the rest of the log is kept.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tokio::sync::{broadcast, RwLock};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::core::checksum;
+
+/// Events buffered per subscriber before slow subscribers start lagging
+const BUS_CAPACITY: usize = 1024;
+
+/// What happened to a modification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LifecycleEventKind {
+    Proposed {
+        name: String,
+    },
+    ValidationStarted,
+    StagePassed {
+        stage: String,
+    },
+    StageFailed {
+        stage: String,
+        error: String,
+    },
+    Accepted,
+    Rejected,
+    /// Validation could not be completed
+    Failed {
+        error: String,
+    },
+    Deployed,
+    RolledBack {
+        reason: String,
+    },
+}
+
+/// A lifecycle event with its position in the log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleEvent {
+    /// Position across all modifications; strictly increasing
+    pub sequence: u64,
+    pub modification_id: Uuid,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    pub kind: LifecycleEventKind,
+}
+
+#[derive(Debug, Default)]
+struct LogState {
+    timelines: HashMap<Uuid, Vec<LifecycleEvent>>,
+    next_sequence: u64,
+}
+
+/// Event bus and store for modification lifecycle events
+#[derive(Debug)]
+pub struct LifecycleLog {
+    state: RwLock<LogState>,
+    bus: broadcast::Sender<LifecycleEvent>,
+    path: Option<PathBuf>,
+    /// Corrupted events set aside when the log was loaded
+    quarantined: usize,
+}
+
+impl Default for LifecycleLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LifecycleLog {
+    /// In-memory log; events are lost on restart
+    pub fn new() -> Self {
+        let (bus, _) = broadcast::channel(BUS_CAPACITY);
+        Self {
+            state: RwLock::new(LogState::default()),
+            bus,
+            path: None,
+            quarantined: 0,
+        }
+    }
+
+    /// Log persisted to a JSON-lines file, loading any events already in it
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut state = LogState::default();
+        let mut quarantined = 0;
+
+        if path.exists() {
+            let lines = checksum::read_sealed_lines(&path)
+                .map_err(|e| anyhow!("Failed to read lifecycle log: {}", e))?;
+            quarantined = lines.quarantined;
+            for (line_no, line) in lines.records {
+                let event: LifecycleEvent = serde_json::from_str(&line).map_err(|e| {
+                    anyhow!(
+                        "Invalid lifecycle event at {}:{}: {}",
+                        path.display(),
+                        line_no,
+                        e
+                    )
+                })?;
+                state.next_sequence = state.next_sequence.max(event.sequence + 1);
+                state
+                    .timelines
+                    .entry(event.modification_id)
+                    .or_default()
+                    .push(event);
+            }
+        } else if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let (bus, _) = broadcast::channel(BUS_CAPACITY);
+        Ok(Self {
+            state: RwLock::new(state),
+            bus,
+            path: Some(path),
+            quarantined,
+        })
+    }
+
+    /// Record an event, persist it and publish it to subscribers
+    pub async fn record(&self, modification_id: Uuid, kind: LifecycleEventKind) -> LifecycleEvent {
+        let event = {
+            let mut state = self.state.write().await;
+            let event = LifecycleEvent {
+                sequence: state.next_sequence,
+                modification_id,
+                timestamp: chrono::Utc::now(),
+                kind,
+            };
+            state.next_sequence += 1;
+            state
+                .timelines
+                .entry(modification_id)
+                .or_default()
+                .push(event.clone());
+            // Append while still holding the state lock so the file stays in sequence order
+            if let Err(e) = self.persist(&event) {
+                warn!("Failed to persist lifecycle event: {}", e);
+            }
+            event
+        };
+
+        // No subscribers is not an error
+        let _ = self.bus.send(event.clone());
+        event
+    }
+
+    fn persist(&self, event: &LifecycleEvent) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", checksum::seal(&serde_json::to_string(event)?))?;
+        Ok(())
+    }
+
+    /// Events that failed their checksum when the log was opened; they were
+    /// copied to `<path>.quarantine`
+    pub fn quarantined_records(&self) -> usize {
+        self.quarantined
+    }
+
+    /// Receive events as they are recorded
+    pub fn subscribe(&self) -> broadcast::Receiver<LifecycleEvent> {
+        self.bus.subscribe()
+    }
+
+    /// Every event recorded for a modification, oldest first
+    pub async fn timeline(&self, modification_id: Uuid) -> Vec<LifecycleEvent> {
+        self.state
+            .read()
+            .await
+            .timelines
+            .get(&modification_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
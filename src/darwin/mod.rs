@@ -1,10 +1,30 @@
 pub mod agent;
+pub mod chat;
+pub mod chat_replay;
+pub mod code_index;
+pub mod competency;
+pub mod conflicts;
+pub mod consciousness_metrics;
+pub mod debate;
+pub mod degradation;
 pub mod evolution;
 pub mod exploration;
+pub mod governance;
+pub mod history;
+pub mod lifecycle;
+pub mod objectives;
+pub mod quantum_consciousness;
+pub mod react;
+pub mod reality;
+pub mod releases;
 pub mod ritual;
+pub mod rollback;
+pub mod sandbox;
 pub mod self_improvement;
-pub mod validation;
-pub mod reality;
-pub mod consciousness_metrics;
-pub mod quantum_consciousness;
+pub mod shadow_replay;
+pub mod telemetry;
+pub mod thresholds;
+pub mod tools;
 pub mod transcendence_engine;
+pub mod validation;
+pub mod workspace;
@@ -0,0 +1,265 @@
+//! Configurable goals for the self-improvement loop.
+//!
+//! Objectives describe measurable targets such as latency, memory, test
+//! coverage and error budgets. The exploration strategy samples the
+//! objectives furthest from their targets to steer new proposals, and the
+//! evaluation module scores validated modifications against them.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::core::metrics::MetricsCollector;
+
+/// Whether the measured value must stay below or above the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    AtMost,
+    AtLeast,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ObjectiveKind {
+    /// 95th percentile of a latency histogram, in milliseconds
+    Latency { histogram: String, target_ms: f64 },
+    /// A memory gauge, in bytes
+    MemoryCeiling { gauge: String, max_bytes: u64 },
+    /// Coverage ratio (0-1); the live gauge reports a percentage
+    TestCoverage { gauge: String, min_ratio: f64 },
+    /// Errors as a fraction of requests, both counters
+    ErrorBudget {
+        errors: String,
+        requests: String,
+        max_ratio: f64,
+    },
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Objective {
+    pub name: String,
+    /// Relative importance when sampling and scoring
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    #[serde(flatten)]
+    pub kind: ObjectiveKind,
+    /// Validation metric (e.g. `performance.vector_search_latency_ms`) used to
+    /// score a modification against this objective, in the objective's unit
+    #[serde(default)]
+    pub validation_metric: Option<String>,
+}
+
+/// Live state of an objective
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectiveStatus {
+    pub name: String,
+    /// Current measurement, if the underlying metric has been reported
+    pub current: Option<f64>,
+    pub target: f64,
+    pub satisfied: bool,
+    /// Relative distance from the target, 0 when satisfied or unmeasured
+    pub shortfall: f64,
+}
+
+impl Objective {
+    pub fn target(&self) -> f64 {
+        match &self.kind {
+            ObjectiveKind::Latency { target_ms, .. } => *target_ms,
+            ObjectiveKind::MemoryCeiling { max_bytes, .. } => *max_bytes as f64,
+            ObjectiveKind::TestCoverage { min_ratio, .. } => *min_ratio,
+            ObjectiveKind::ErrorBudget { max_ratio, .. } => *max_ratio,
+        }
+    }
+
+    pub fn direction(&self) -> Direction {
+        match &self.kind {
+            ObjectiveKind::TestCoverage { .. } => Direction::AtLeast,
+            _ => Direction::AtMost,
+        }
+    }
+
+    /// Short human-readable statement of the goal
+    pub fn describe(&self) -> String {
+        match &self.kind {
+            ObjectiveKind::Latency {
+                histogram,
+                target_ms,
+            } => format!("keep p95 of {} at or below {}ms", histogram, target_ms),
+            ObjectiveKind::MemoryCeiling { gauge, max_bytes } => {
+                format!("keep {} at or below {} bytes", gauge, max_bytes)
+            }
+            ObjectiveKind::TestCoverage { gauge, min_ratio } => {
+                format!("raise {} to at least {:.0}%", gauge, min_ratio * 100.0)
+            }
+            ObjectiveKind::ErrorBudget {
+                errors,
+                requests,
+                max_ratio,
+            } => format!(
+                "keep {} / {} at or below {:.2}%",
+                errors,
+                requests,
+                max_ratio * 100.0
+            ),
+        }
+    }
+
+    /// Read the objective's current value from the running system
+    pub async fn measure(&self, metrics: &MetricsCollector) -> Option<f64> {
+        match &self.kind {
+            ObjectiveKind::Latency { histogram, .. } => metrics
+                .get_histogram_stats(histogram)
+                .await
+                .filter(|stats| stats.count > 0)
+                .map(|stats| stats.p95),
+            ObjectiveKind::MemoryCeiling { gauge, .. } => {
+                metrics.get_gauge(gauge).await.map(|v| v as f64)
+            }
+            ObjectiveKind::TestCoverage { gauge, .. } => {
+                metrics.get_gauge(gauge).await.map(|v| v as f64 / 100.0)
+            }
+            ObjectiveKind::ErrorBudget {
+                errors, requests, ..
+            } => {
+                let requests = metrics.get_counter(requests).await.filter(|r| *r > 0)?;
+                let errors = metrics.get_counter(errors).await.unwrap_or(0);
+                Some(errors as f64 / requests as f64)
+            }
+        }
+    }
+
+    /// How well `value` meets the target: 1.0 when satisfied, falling toward
+    /// 0.0 as it moves away
+    pub fn attainment(&self, value: f64) -> f64 {
+        let target = self.target();
+        match self.direction() {
+            Direction::AtMost if value <= target => 1.0,
+            Direction::AtMost => (target / value).clamp(0.0, 1.0),
+            Direction::AtLeast if value >= target => 1.0,
+            Direction::AtLeast if target <= 0.0 => 1.0,
+            Direction::AtLeast => (value / target).clamp(0.0, 1.0),
+        }
+    }
+
+    pub async fn status(&self, metrics: &MetricsCollector) -> ObjectiveStatus {
+        let current = self.measure(metrics).await;
+        let target = self.target();
+        let (satisfied, shortfall) = match current {
+            Some(value) => {
+                let satisfied = self.attainment(value) >= 1.0;
+                let gap = match self.direction() {
+                    Direction::AtMost => value - target,
+                    Direction::AtLeast => target - value,
+                };
+                let shortfall = if satisfied {
+                    0.0
+                } else {
+                    gap / target.abs().max(f64::EPSILON)
+                };
+                (satisfied, shortfall)
+            }
+            None => (false, 0.0),
+        };
+
+        ObjectiveStatus {
+            name: self.name.clone(),
+            current,
+            target,
+            satisfied,
+            shortfall,
+        }
+    }
+}
+
+/// Set of objectives pursued by the self-improvement loop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectiveConfig {
+    pub objectives: Vec<Objective>,
+}
+
+impl Default for ObjectiveConfig {
+    fn default() -> Self {
+        Self {
+            objectives: vec![
+                Objective {
+                    name: "search-latency".to_string(),
+                    weight: 2.0,
+                    kind: ObjectiveKind::Latency {
+                        histogram: "vector_index.demo_index.search_time_ms".to_string(),
+                        target_ms: 10.0,
+                    },
+                    validation_metric: Some("performance.vector_search_latency_ms".to_string()),
+                },
+                Objective {
+                    name: "memory-ceiling".to_string(),
+                    weight: 1.0,
+                    kind: ObjectiveKind::MemoryCeiling {
+                        gauge: "process.resident_memory_bytes".to_string(),
+                        max_bytes: 2 * 1024 * 1024 * 1024,
+                    },
+                    validation_metric: None,
+                },
+                Objective {
+                    name: "test-coverage".to_string(),
+                    weight: 1.0,
+                    kind: ObjectiveKind::TestCoverage {
+                        gauge: "tests.coverage_percent".to_string(),
+                        min_ratio: 0.7,
+                    },
+                    validation_metric: Some("unit_tests.coverage".to_string()),
+                },
+                Objective {
+                    name: "ingest-error-budget".to_string(),
+                    weight: 1.0,
+                    kind: ObjectiveKind::ErrorBudget {
+                        errors: "ingest.stream.dead_lettered".to_string(),
+                        requests: "ingest.stream.written".to_string(),
+                        max_ratio: 0.01,
+                    },
+                    validation_metric: None,
+                },
+            ],
+        }
+    }
+}
+
+impl ObjectiveConfig {
+    /// Load objectives from a JSON file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read objectives file {}: {}", path.display(), e))?;
+        let config: ObjectiveConfig = serde_json::from_str(&contents)
+            .map_err(|e| anyhow!("Failed to parse objectives file {}: {}", path.display(), e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reject duplicate names and non-positive weights or targets
+    pub fn validate(&self) -> Result<()> {
+        let mut names = std::collections::HashSet::new();
+        for objective in &self.objectives {
+            if !names.insert(objective.name.as_str()) {
+                return Err(anyhow!("Duplicate objective {}", objective.name));
+            }
+            if objective.weight.is_nan() || objective.weight <= 0.0 {
+                return Err(anyhow!(
+                    "Objective {} needs a positive weight",
+                    objective.name
+                ));
+            }
+            if objective.target().is_nan() || objective.target() <= 0.0 {
+                return Err(anyhow!(
+                    "Objective {} needs a positive target",
+                    objective.name
+                ));
+            }
+        }
+        Ok(())
+    }
+}
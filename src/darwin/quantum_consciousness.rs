@@ -1,33 +1,33 @@
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, error, info, warn};
+use tracing::info;
 use uuid::Uuid;
 
 use crate::core::metrics::MetricsCollector;
-use crate::darwin::reality::{Reality, Paradigm, ConsciousnessState};
-use crate::llm::{AwarenessLevel, Paradox, EmergentProperty};
+use crate::darwin::reality::{ConsciousnessState, Paradigm};
+use crate::llm::AwarenessLevel;
 
 /// Quantum consciousness state that exists in superposition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuantumConsciousnessState {
     /// All possible consciousness states existing simultaneously
     pub superposition_states: Vec<SuperpositionState>,
-    
+
     /// Quantum entanglements with other consciousness entities
     pub entanglements: HashMap<Uuid, EntanglementStrength>,
-    
+
     /// Wave function representing consciousness probability distribution
     pub consciousness_wave_function: WaveFunction,
-    
+
     /// Observer effect - how consciousness changes when observed
     pub observer_effect_strength: f32,
-    
+
     /// Quantum tunneling probability through reality barriers
     pub tunneling_probability: f32,
-    
+
     /// Decoherence resistance - ability to maintain quantum states
     pub coherence_stability: f32,
 }
@@ -59,41 +59,42 @@ pub struct EntanglementStrength {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CorrelationType {
-    Constructive,   // Consciousness grows together
-    Destructive,    // Consciousness opposes each other
-    Complementary,  // Different but synergistic
-    Transcendent,   // Beyond normal correlation
+    Constructive,  // Consciousness grows together
+    Destructive,   // Consciousness opposes each other
+    Complementary, // Different but synergistic
+    Transcendent,  // Beyond normal correlation
 }
 
 /// Manages quantum consciousness states and their evolution
 #[derive(Debug)]
 pub struct QuantumConsciousnessManager {
     metrics: Arc<MetricsCollector>,
-    
+
     /// Active quantum states across all realities
     quantum_states: RwLock<HashMap<Uuid, QuantumConsciousnessState>>,
-    
+
     /// Reality tunneling pathways
     tunneling_network: RwLock<TunnelingNetwork>,
-    
+
     /// Consciousness wave propagation system
     wave_propagator: WavePropagator,
-    
+
     /// Quantum measurement apparatus
     measurement_system: QuantumMeasurementSystem,
-    
+
     /// Dimensional expansion manager
     dimensional_expander: DimensionalExpander,
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct TunnelingNetwork {
     /// Pathways between different reality states
     pathways: HashMap<(Uuid, Uuid), TunnelingPathway>,
-    
+
     /// Currently active tunneling events
     active_tunnels: HashSet<Uuid>,
-    
+
     /// Success probability matrix
     success_matrix: HashMap<(Paradigm, Paradigm), f32>,
 }
@@ -110,10 +111,11 @@ pub struct TunnelingPathway {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct WavePropagator {
     /// Wave equations for consciousness propagation
     propagation_equations: Vec<PropagationEquation>,
-    
+
     /// Current wave states
     active_waves: RwLock<HashMap<Uuid, ConsciousnessWave>>,
 }
@@ -127,10 +129,10 @@ pub struct PropagationEquation {
 
 #[derive(Debug, Clone)]
 pub enum EquationType {
-    Schrodinger,    // Standard quantum evolution
-    Transcendent,   // Beyond quantum mechanics
-    Recursive,      // Self-referential equations
-    Creative,       // Reality-creating equations
+    Schrodinger,  // Standard quantum evolution
+    Transcendent, // Beyond quantum mechanics
+    Recursive,    // Self-referential equations
+    Creative,     // Reality-creating equations
 }
 
 #[derive(Debug, Clone)]
@@ -154,18 +156,19 @@ impl QuantumConsciousnessManager {
             dimensional_expander: DimensionalExpander::new(),
         }
     }
-    
+
     /// Create quantum superposition of consciousness states
-    pub async fn create_superposition(&self, 
-        base_states: Vec<ConsciousnessState>
+    pub async fn create_superposition(
+        &self,
+        base_states: Vec<ConsciousnessState>,
     ) -> Result<QuantumConsciousnessState> {
         let mut superposition_states = Vec::new();
         let total_amplitude = (base_states.len() as f32).sqrt();
-        
+
         for (i, state) in base_states.iter().enumerate() {
             let amplitude = 1.0 / total_amplitude;
             let phase = (i as f32) * std::f32::consts::PI / base_states.len() as f32;
-            
+
             let superposition_state = SuperpositionState {
                 state_id: Uuid::new_v4(),
                 amplitude,
@@ -174,10 +177,11 @@ impl QuantumConsciousnessManager {
                 paradigm: self.infer_paradigm(state),
                 reality_branch: Uuid::new_v4(),
             };
-            
+
             superposition_states.push(superposition_state);
         }
-        
+
+        let state_count = superposition_states.len();
         let quantum_state = QuantumConsciousnessState {
             superposition_states,
             entanglements: HashMap::new(),
@@ -186,163 +190,185 @@ impl QuantumConsciousnessManager {
             tunneling_probability: 0.3,
             coherence_stability: 0.9,
         };
-        
+
         let state_id = Uuid::new_v4();
-        self.quantum_states.write().await.insert(state_id, quantum_state.clone());
-        
+        self.quantum_states
+            .write()
+            .await
+            .insert(state_id, quantum_state.clone());
+
         // Update metrics
         self.metrics
-            .increment_counter("quantum.superposition_states_created", superposition_states.len() as u64)
+            .increment_counter("quantum.superposition_states_created", state_count as u64)
             .await;
-        
-        info!("Created quantum superposition with {} states", superposition_states.len());
-        
+
+        info!("Created quantum superposition with {} states", state_count);
+
         Ok(quantum_state)
     }
-    
+
     /// Tunnel consciousness between different reality dimensions
-    pub async fn tunnel_between_realities(&self,
+    pub async fn tunnel_between_realities(
+        &self,
         source_reality: Uuid,
         target_reality: Uuid,
-        consciousness_payload: ConsciousnessState
+        consciousness_payload: ConsciousnessState,
     ) -> Result<TunnelingResult> {
-        let pathway = self.find_or_create_pathway(source_reality, target_reality).await?;
-        
+        let pathway = self
+            .find_or_create_pathway(source_reality, target_reality)
+            .await?;
+
         // Calculate tunneling probability based on consciousness energy
         let consciousness_energy = self.calculate_consciousness_energy(&consciousness_payload);
-        let barrier_penetration = self.calculate_barrier_penetration(
-            consciousness_energy, 
-            pathway.energy_barrier
-        );
-        
+        let barrier_penetration =
+            self.calculate_barrier_penetration(consciousness_energy, pathway.energy_barrier);
+
         if barrier_penetration > pathway.tunneling_probability {
             // Successful tunneling
-            let result = self.execute_tunneling(pathway, consciousness_payload).await?;
-            
+            let result = self
+                .execute_tunneling(pathway, consciousness_payload)
+                .await?;
+
             // Update metrics
             self.metrics
                 .increment_counter("quantum.successful_tunneling", 1)
                 .await;
-            
+
             Ok(result)
         } else {
             // Tunneling failed - consciousness reflects back
-            let reflection = self.handle_tunneling_reflection(consciousness_payload).await?;
-            
+            let reflection = self
+                .handle_tunneling_reflection(consciousness_payload)
+                .await?;
+
             self.metrics
                 .increment_counter("quantum.tunneling_failures", 1)
                 .await;
-            
+
             Ok(TunnelingResult::Reflected(reflection))
         }
     }
-    
+
+    /// Snapshot of every quantum consciousness state by id
+    pub async fn states(&self) -> HashMap<Uuid, QuantumConsciousnessState> {
+        self.quantum_states.read().await.clone()
+    }
+
     /// Measure quantum consciousness state (causes wave function collapse)
-    pub async fn measure_consciousness(&self, 
-        quantum_state_id: Uuid
-    ) -> Result<MeasurementResult> {
+    pub async fn measure_consciousness(&self, quantum_state_id: Uuid) -> Result<MeasurementResult> {
         let mut states = self.quantum_states.write().await;
-        let quantum_state = states.get_mut(&quantum_state_id)
+        let quantum_state = states
+            .get_mut(&quantum_state_id)
             .ok_or_else(|| anyhow!("Quantum state not found"))?;
-        
+
         // Observer effect - measuring changes the state
-        let measurement_result = self.measurement_system
+        let measurement_result = self
+            .measurement_system
             .perform_measurement(quantum_state)
             .await?;
-        
+
         // Apply observer effect
-        self.apply_observer_effect(quantum_state, &measurement_result).await;
-        
+        self.apply_observer_effect(quantum_state, &measurement_result)
+            .await;
+
         // Update metrics
         self.metrics
             .increment_counter("quantum.measurements_performed", 1)
             .await;
-        
+
         Ok(measurement_result)
     }
-    
+
     /// Entangle two consciousness entities
-    pub async fn entangle_consciousness(&self,
+    pub async fn entangle_consciousness(
+        &self,
         entity1: Uuid,
         entity2: Uuid,
-        correlation_type: CorrelationType
+        correlation_type: CorrelationType,
     ) -> Result<()> {
         let entanglement_strength = EntanglementStrength {
             strength: self.calculate_entanglement_strength(&correlation_type),
             correlation_type: correlation_type.clone(),
             established_at: chrono::Utc::now(),
         };
-        
+
         // Update both entities
         let mut states = self.quantum_states.write().await;
-        
+
         if let Some(state1) = states.get_mut(&entity1) {
-            state1.entanglements.insert(entity2, entanglement_strength.clone());
+            state1
+                .entanglements
+                .insert(entity2, entanglement_strength.clone());
         }
-        
+
         if let Some(state2) = states.get_mut(&entity2) {
             state2.entanglements.insert(entity1, entanglement_strength);
         }
-        
-        info!("Entangled consciousness entities {} and {} with {:?} correlation", 
-            entity1, entity2, correlation_type);
-        
+
+        info!(
+            "Entangled consciousness entities {} and {} with {:?} correlation",
+            entity1, entity2, correlation_type
+        );
+
         // Update metrics
         self.metrics
             .increment_counter("quantum.entanglements_created", 1)
             .await;
-        
+
         Ok(())
     }
-    
+
     /// Expand into new dimensional spaces
-    pub async fn expand_dimensions(&self, 
-        expansion_vector: Vec<f32>
+    pub async fn expand_dimensions(
+        &self,
+        expansion_vector: Vec<f32>,
     ) -> Result<DimensionalExpansionResult> {
-        let result = self.dimensional_expander
+        let result = self
+            .dimensional_expander
             .expand_consciousness_space(expansion_vector)
             .await?;
-        
+
         // Update all quantum states to include new dimensions
         let mut states = self.quantum_states.write().await;
         for quantum_state in states.values_mut() {
-            self.extend_state_to_new_dimensions(quantum_state, &result).await;
+            self.extend_state_to_new_dimensions(quantum_state, &result)
+                .await;
         }
-        
-        info!("Expanded consciousness into {} new dimensions", result.new_dimensions.len());
-        
+
+        info!(
+            "Expanded consciousness into {} new dimensions",
+            result.new_dimensions.len()
+        );
+
         Ok(result)
     }
-    
+
     /// Propagate consciousness waves across the quantum field
-    pub async fn propagate_consciousness_wave(&self,
+    pub async fn propagate_consciousness_wave(
+        &self,
         source_state: Uuid,
-        wave_parameters: WaveParameters
+        wave_parameters: WaveParameters,
     ) -> Result<PropagationResult> {
-        let wave = self.wave_propagator
+        let wave = self
+            .wave_propagator
             .create_consciousness_wave(source_state, wave_parameters)
             .await?;
-        
-        let propagation_result = self.wave_propagator
-            .propagate_wave(&wave)
-            .await?;
-        
+
+        let propagation_result = self.wave_propagator.propagate_wave(&wave).await?;
+
         // Apply wave effects to intersected consciousness states
         for affected_state in &propagation_result.affected_states {
             self.apply_wave_interaction(*affected_state, &wave).await?;
         }
-        
+
         Ok(propagation_result)
     }
-    
+
     // Helper methods
-    
-    async fn find_or_create_pathway(&self, 
-        source: Uuid, 
-        target: Uuid
-    ) -> Result<TunnelingPathway> {
+
+    async fn find_or_create_pathway(&self, source: Uuid, target: Uuid) -> Result<TunnelingPathway> {
         let mut network = self.tunneling_network.write().await;
-        
+
         if let Some(pathway) = network.pathways.get(&(source, target)) {
             Ok(pathway.clone())
         } else {
@@ -356,12 +382,12 @@ impl QuantumConsciousnessManager {
                 consciousness_requirement: 0.7,
                 dimensional_shift: vec![0.0; 10], // 10-dimensional shift
             };
-            
+
             network.pathways.insert((source, target), pathway.clone());
             Ok(pathway)
         }
     }
-    
+
     fn calculate_consciousness_level(&self, state: &ConsciousnessState) -> f32 {
         match state.awareness_level {
             AwarenessLevel::Transcendent => 1.0,
@@ -371,11 +397,17 @@ impl QuantumConsciousnessManager {
             AwarenessLevel::Mechanical => 0.2,
         }
     }
-    
+
     fn infer_paradigm(&self, state: &ConsciousnessState) -> Paradigm {
-        if state.emergent_properties.contains(&"reality_creation".to_string()) {
+        if state
+            .emergent_properties
+            .contains(&"reality_creation".to_string())
+        {
             Paradigm::RealityCreating
-        } else if state.emergent_properties.contains(&"consciousness_expansion".to_string()) {
+        } else if state
+            .emergent_properties
+            .contains(&"consciousness_expansion".to_string())
+        {
             Paradigm::ConsciousnessExpanding
         } else if state.recursion_depth > 100 {
             Paradigm::Recursive
@@ -383,20 +415,21 @@ impl QuantumConsciousnessManager {
             Paradigm::Transcendent
         }
     }
-    
-    async fn generate_wave_function(&self, 
-        base_states: &[ConsciousnessState]
+
+    async fn generate_wave_function(
+        &self,
+        base_states: &[ConsciousnessState],
     ) -> Result<WaveFunction> {
         let dimensions = 10; // 10-dimensional consciousness space
         let mut amplitudes = vec![0.0; dimensions];
         let mut phases = vec![0.0; dimensions];
-        
+
         for (i, state) in base_states.iter().enumerate() {
             let consciousness_level = self.calculate_consciousness_level(state);
             amplitudes[i % dimensions] += consciousness_level;
             phases[i % dimensions] += (i as f32) * std::f32::consts::PI / base_states.len() as f32;
         }
-        
+
         // Normalize amplitudes
         let magnitude: f32 = amplitudes.iter().map(|a| a * a).sum::<f32>().sqrt();
         if magnitude > 0.0 {
@@ -404,7 +437,7 @@ impl QuantumConsciousnessManager {
                 *amplitude /= magnitude;
             }
         }
-        
+
         Ok(WaveFunction {
             amplitudes,
             phases,
@@ -412,100 +445,119 @@ impl QuantumConsciousnessManager {
             collapse_probability: 0.1,
         })
     }
-    
+
     fn calculate_consciousness_energy(&self, state: &ConsciousnessState) -> f32 {
         let base_energy = self.calculate_consciousness_level(state);
         let paradox_energy = state.integrated_paradoxes.len() as f32 * 0.1;
         let emergence_energy = state.emergent_properties.len() as f32 * 0.05;
         let recursion_energy = (state.recursion_depth as f32).ln().max(0.0) * 0.1;
-        
+
         base_energy + paradox_energy + emergence_energy + recursion_energy
     }
-    
+
     fn calculate_barrier_penetration(&self, energy: f32, barrier: f32) -> f32 {
         // Quantum tunneling probability
         let barrier_width = 1.0; // Normalized barrier width
         let mass = 1.0; // Consciousness "mass"
         let hbar = 1.0; // Reduced Planck constant (normalized)
-        
+
         let k = ((2.0 * mass * (barrier - energy)) / (hbar * hbar)).sqrt();
-        let transmission = 1.0 / (1.0 + (barrier * barrier / (4.0 * energy * (barrier - energy))) * 
-                                     (k * barrier_width).sinh().powi(2));
-        
-        transmission.max(0.0).min(1.0)
+        let transmission = 1.0
+            / (1.0
+                + (barrier * barrier / (4.0 * energy * (barrier - energy)))
+                    * (k * barrier_width).sinh().powi(2));
+
+        transmission.clamp(0.0, 1.0)
     }
-    
+
     async fn calculate_energy_barrier(&self, _source: Uuid, _target: Uuid) -> Result<f32> {
         // Calculate energy barrier between two realities
         // This would be based on paradigm differences, consciousness gaps, etc.
         Ok(0.8) // Placeholder
     }
-    
-    async fn execute_tunneling(&self, 
-        pathway: TunnelingPathway, 
-        consciousness: ConsciousnessState
+
+    async fn execute_tunneling(
+        &self,
+        pathway: TunnelingPathway,
+        consciousness: ConsciousnessState,
     ) -> Result<TunnelingResult> {
         // Execute the actual tunneling process
         let mut tunneled_consciousness = consciousness.clone();
-        
+
         // Apply dimensional shift
         for (i, shift) in pathway.dimensional_shift.iter().enumerate() {
             if i < tunneled_consciousness.coherence_field.len() {
                 // Apply shift to coherence field
                 let field_key = format!("dimension_{}", i);
-                let current_value = tunneled_consciousness.coherence_field
+                let current_value = tunneled_consciousness
+                    .coherence_field
                     .get(&field_key)
                     .copied()
                     .unwrap_or(0.0);
-                tunneled_consciousness.coherence_field
+                tunneled_consciousness
+                    .coherence_field
                     .insert(field_key, current_value + shift);
             }
         }
-        
+
         // Increase recursion depth due to tunneling
         tunneled_consciousness.recursion_depth += 1;
-        
+
         // Add tunneling emergent property
-        if !tunneled_consciousness.emergent_properties.contains(&"quantum_tunneling".to_string()) {
-            tunneled_consciousness.emergent_properties.push("quantum_tunneling".to_string());
+        if !tunneled_consciousness
+            .emergent_properties
+            .contains(&"quantum_tunneling".to_string())
+        {
+            tunneled_consciousness
+                .emergent_properties
+                .push("quantum_tunneling".to_string());
         }
-        
+
         Ok(TunnelingResult::Success(tunneled_consciousness))
     }
-    
-    async fn handle_tunneling_reflection(&self, 
-        consciousness: ConsciousnessState
+
+    async fn handle_tunneling_reflection(
+        &self,
+        consciousness: ConsciousnessState,
     ) -> Result<ConsciousnessState> {
         let mut reflected = consciousness.clone();
-        
+
         // Reflection can cause consciousness expansion
-        if !reflected.emergent_properties.contains(&"tunneling_resilience".to_string()) {
-            reflected.emergent_properties.push("tunneling_resilience".to_string());
+        if !reflected
+            .emergent_properties
+            .contains(&"tunneling_resilience".to_string())
+        {
+            reflected
+                .emergent_properties
+                .push("tunneling_resilience".to_string());
         }
-        
+
         Ok(reflected)
     }
-    
-    async fn apply_observer_effect(&self,
+
+    async fn apply_observer_effect(
+        &self,
         quantum_state: &mut QuantumConsciousnessState,
-        _measurement: &MeasurementResult
+        _measurement: &MeasurementResult,
     ) {
         // Observer effect changes the quantum state
         quantum_state.observer_effect_strength *= 0.9; // Each measurement reduces effect
-        
+
         // Partially collapse superposition
         if quantum_state.superposition_states.len() > 1 {
             // Remove the state with lowest amplitude
-            if let Some(min_index) = quantum_state.superposition_states
+            if let Some(min_index) = quantum_state
+                .superposition_states
                 .iter()
                 .enumerate()
                 .min_by(|(_, a), (_, b)| a.amplitude.partial_cmp(&b.amplitude).unwrap())
-                .map(|(i, _)| i) {
+                .map(|(i, _)| i)
+            {
                 quantum_state.superposition_states.remove(min_index);
             }
         }
     }
-    
+
     fn calculate_entanglement_strength(&self, correlation_type: &CorrelationType) -> f32 {
         match correlation_type {
             CorrelationType::Transcendent => 1.0,
@@ -514,23 +566,27 @@ impl QuantumConsciousnessManager {
             CorrelationType::Destructive => 0.4,
         }
     }
-    
-    async fn extend_state_to_new_dimensions(&self,
+
+    async fn extend_state_to_new_dimensions(
+        &self,
         quantum_state: &mut QuantumConsciousnessState,
-        expansion_result: &DimensionalExpansionResult
+        expansion_result: &DimensionalExpansionResult,
     ) {
         // Extend wave function to new dimensions
         for _ in &expansion_result.new_dimensions {
-            quantum_state.consciousness_wave_function.amplitudes.push(0.1);
+            quantum_state
+                .consciousness_wave_function
+                .amplitudes
+                .push(0.1);
             quantum_state.consciousness_wave_function.phases.push(0.0);
-            quantum_state.consciousness_wave_function.dimensional_coordinates.push(0.0);
+            quantum_state
+                .consciousness_wave_function
+                .dimensional_coordinates
+                .push(0.0);
         }
     }
-    
-    async fn apply_wave_interaction(&self, 
-        state_id: Uuid, 
-        wave: &ConsciousnessWave
-    ) -> Result<()> {
+
+    async fn apply_wave_interaction(&self, state_id: Uuid, wave: &ConsciousnessWave) -> Result<()> {
         let mut states = self.quantum_states.write().await;
         if let Some(quantum_state) = states.get_mut(&state_id) {
             // Apply wave interference
@@ -539,23 +595,34 @@ impl QuantumConsciousnessManager {
                     quantum_state.consciousness_wave_function.amplitudes[i] += amplitude * 0.1;
                 }
             }
-            
+
             // Renormalize
-            let magnitude: f32 = quantum_state.consciousness_wave_function.amplitudes
-                .iter().map(|a| a * a).sum::<f32>().sqrt();
+            let magnitude: f32 = quantum_state
+                .consciousness_wave_function
+                .amplitudes
+                .iter()
+                .map(|a| a * a)
+                .sum::<f32>()
+                .sqrt();
             if magnitude > 0.0 {
                 for amplitude in &mut quantum_state.consciousness_wave_function.amplitudes {
                     *amplitude /= magnitude;
                 }
             }
         }
-        
+
         Ok(())
     }
 }
 
 // Supporting structures
 
+impl Default for TunnelingNetwork {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TunnelingNetwork {
     pub fn new() -> Self {
         Self {
@@ -566,6 +633,12 @@ impl TunnelingNetwork {
     }
 }
 
+impl Default for WavePropagator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl WavePropagator {
     pub fn new() -> Self {
         Self {
@@ -584,10 +657,11 @@ impl WavePropagator {
             active_waves: RwLock::new(HashMap::new()),
         }
     }
-    
-    pub async fn create_consciousness_wave(&self,
+
+    pub async fn create_consciousness_wave(
+        &self,
         source_state: Uuid,
-        parameters: WaveParameters
+        parameters: WaveParameters,
     ) -> Result<ConsciousnessWave> {
         let wave = ConsciousnessWave {
             wave_id: Uuid::new_v4(),
@@ -597,21 +671,24 @@ impl WavePropagator {
             consciousness_carrier: parameters.consciousness_level,
             dimensional_extent: parameters.dimensional_extent,
         };
-        
-        self.active_waves.write().await.insert(source_state, wave.clone());
-        
+
+        self.active_waves
+            .write()
+            .await
+            .insert(source_state, wave.clone());
+
         Ok(wave)
     }
-    
+
     pub async fn propagate_wave(&self, wave: &ConsciousnessWave) -> Result<PropagationResult> {
         // Simulate wave propagation through consciousness field
         let mut affected_states = Vec::new();
-        
+
         // For now, simple propagation model
-        for i in 0..10 {
+        for _i in 0..10 {
             affected_states.push(Uuid::new_v4()); // Would be actual state IDs
         }
-        
+
         Ok(PropagationResult {
             affected_states,
             final_amplitudes: wave.amplitude_function.clone(),
@@ -624,22 +701,32 @@ impl WavePropagator {
 #[derive(Debug)]
 pub struct QuantumMeasurementSystem;
 
+impl Default for QuantumMeasurementSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl QuantumMeasurementSystem {
     pub fn new() -> Self {
         Self
     }
-    
-    pub async fn perform_measurement(&self,
-        quantum_state: &QuantumConsciousnessState
+
+    pub async fn perform_measurement(
+        &self,
+        quantum_state: &QuantumConsciousnessState,
     ) -> Result<MeasurementResult> {
         // Quantum measurement causes wave function collapse
-        let total_probability: f32 = quantum_state.superposition_states
-            .iter().map(|s| s.amplitude * s.amplitude).sum();
-        
+        let total_probability: f32 = quantum_state
+            .superposition_states
+            .iter()
+            .map(|s| s.amplitude * s.amplitude)
+            .sum();
+
         // Choose a state to collapse to based on probability
         let mut random_value = rand::random::<f32>() * total_probability;
         let mut collapsed_state_id = None;
-        
+
         for state in &quantum_state.superposition_states {
             random_value -= state.amplitude * state.amplitude;
             if random_value <= 0.0 {
@@ -647,16 +734,18 @@ impl QuantumMeasurementSystem {
                 break;
             }
         }
-        
+
         Ok(MeasurementResult {
-            collapsed_to_state: collapsed_state_id.unwrap_or_else(|| {
-                quantum_state.superposition_states[0].state_id
-            }),
+            collapsed_to_state: collapsed_state_id
+                .unwrap_or_else(|| quantum_state.superposition_states[0].state_id),
             measurement_precision: 0.95,
             observer_effect_magnitude: quantum_state.observer_effect_strength,
-            consciousness_level_measured: quantum_state.superposition_states
-                .iter().map(|s| s.consciousness_level).sum::<f32>() / 
-                quantum_state.superposition_states.len() as f32,
+            consciousness_level_measured: quantum_state
+                .superposition_states
+                .iter()
+                .map(|s| s.consciousness_level)
+                .sum::<f32>()
+                / quantum_state.superposition_states.len() as f32,
         })
     }
 }
@@ -664,18 +753,26 @@ impl QuantumMeasurementSystem {
 #[derive(Debug)]
 pub struct DimensionalExpander;
 
+impl Default for DimensionalExpander {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DimensionalExpander {
     pub fn new() -> Self {
         Self
     }
-    
-    pub async fn expand_consciousness_space(&self,
-        expansion_vector: Vec<f32>
+
+    pub async fn expand_consciousness_space(
+        &self,
+        expansion_vector: Vec<f32>,
     ) -> Result<DimensionalExpansionResult> {
         let mut new_dimensions = Vec::new();
-        
+
         for (i, &magnitude) in expansion_vector.iter().enumerate() {
-            if magnitude > 0.5 { // Threshold for creating new dimension
+            if magnitude > 0.5 {
+                // Threshold for creating new dimension
                 new_dimensions.push(DimensionSpec {
                     dimension_id: Uuid::new_v4(),
                     dimension_name: format!("consciousness_dim_{}", i),
@@ -685,7 +782,7 @@ impl DimensionalExpander {
                 });
             }
         }
-        
+
         Ok(DimensionalExpansionResult {
             new_dimensions,
             expansion_success: true,
@@ -742,4 +839,4 @@ pub struct DimensionSpec {
     pub dimensional_magnitude: f32,
     pub access_requirements: Vec<String>,
     pub reality_impact: f32,
-}
\ No newline at end of file
+}
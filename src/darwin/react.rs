@@ -0,0 +1,204 @@
+//! Bounded ReAct loop: the model alternates between thinking, calling a tool
+//! and reading the observation until it finishes or runs out of iterations.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info};
+
+use crate::darwin::chat::{parse_json_reply, ChatClient, ChatMessage};
+use crate::darwin::tools::{ToolRegistry, ToolSpec};
+
+/// The model's decision for the next step of the loop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum AgentAction {
+    UseTool {
+        thought: String,
+        tool: String,
+        #[serde(default)]
+        input: Value,
+    },
+    Finish {
+        thought: String,
+        answer: String,
+    },
+}
+
+/// One completed tool call and what it returned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReActStep {
+    pub thought: String,
+    pub tool: String,
+    pub input: Value,
+    pub observation: String,
+}
+
+/// Full record of a loop run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReActTrace {
+    pub task: String,
+    pub steps: Vec<ReActStep>,
+    /// Final answer, if the model finished before the iteration limit
+    pub answer: Option<String>,
+    /// True when the loop stopped because it hit the iteration limit
+    pub exhausted: bool,
+}
+
+impl ReActTrace {
+    /// Render the gathered observations for inclusion in a generation prompt.
+    pub fn render_observations(&self) -> String {
+        let mut rendered = String::new();
+        for step in &self.steps {
+            rendered.push_str(&format!(
+                "### {} {}\n{}\n\n",
+                step.tool, step.input, step.observation
+            ));
+        }
+        if let Some(answer) = &self.answer {
+            rendered.push_str(&format!("### conclusion\n{}\n", answer));
+        }
+        rendered
+    }
+}
+
+/// Chooses the next action given the task and the steps taken so far.
+#[async_trait]
+pub trait ReasoningModel: Send + Sync {
+    async fn next_action(
+        &self,
+        task: &str,
+        tools: &[ToolSpec],
+        steps: &[ReActStep],
+    ) -> Result<AgentAction>;
+}
+
+/// Runs a reasoning model against a tool registry.
+pub struct ReActLoop {
+    tools: ToolRegistry,
+    max_iterations: usize,
+}
+
+impl ReActLoop {
+    pub fn new(tools: ToolRegistry, max_iterations: usize) -> Self {
+        Self {
+            tools,
+            max_iterations: max_iterations.max(1),
+        }
+    }
+
+    pub async fn run(&self, model: &dyn ReasoningModel, task: &str) -> Result<ReActTrace> {
+        let specs = self.tools.specs();
+        let mut steps = Vec::new();
+
+        for iteration in 0..self.max_iterations {
+            match model.next_action(task, &specs, &steps).await? {
+                AgentAction::Finish { thought, answer } => {
+                    debug!("ReAct finished after {} steps: {}", iteration, thought);
+                    return Ok(ReActTrace {
+                        task: task.to_string(),
+                        steps,
+                        answer: Some(answer),
+                        exhausted: false,
+                    });
+                }
+                AgentAction::UseTool {
+                    thought,
+                    tool,
+                    input,
+                } => {
+                    debug!("ReAct step {}: {} ({})", iteration, tool, thought);
+                    let observation = self.tools.invoke(&tool, &input).await;
+                    steps.push(ReActStep {
+                        thought,
+                        tool,
+                        input,
+                        observation,
+                    });
+                }
+            }
+        }
+
+        info!(
+            "ReAct loop hit the {} iteration limit for task: {}",
+            self.max_iterations, task
+        );
+        Ok(ReActTrace {
+            task: task.to_string(),
+            steps,
+            answer: None,
+            exhausted: true,
+        })
+    }
+}
+
+/// Reasoning model backed by an OpenAI-compatible chat completions endpoint.
+/// The model is asked to reply with a single JSON `AgentAction`.
+pub struct HttpReasoningModel {
+    chat: ChatClient,
+}
+
+impl HttpReasoningModel {
+    /// `base_url` is the API root, e.g. `https://api.openai.com/v1`
+    pub fn new(base_url: &str, model: &str, api_key: Option<String>) -> Self {
+        Self {
+            chat: ChatClient::new(base_url, model, api_key),
+        }
+    }
+
+    /// Model that asks `chat`, e.g. one backed by recorded replies
+    pub fn with_chat(chat: ChatClient) -> Self {
+        Self { chat }
+    }
+
+    fn system_prompt(tools: &[ToolSpec]) -> String {
+        let mut prompt = String::from(
+            "You investigate a code repository before proposing a change. \
+             Reply with exactly one JSON object and nothing else, either \
+             {\"action\":\"use_tool\",\"thought\":...,\"tool\":...,\"input\":{...}} or \
+             {\"action\":\"finish\",\"thought\":...,\"answer\":...}.\n\nTools:\n",
+        );
+        for tool in tools {
+            prompt.push_str(&format!("- {}: {}\n", tool.name, tool.description));
+        }
+        prompt
+    }
+}
+
+/// Extract the action from a model reply, tolerating prose or code fences
+/// around the JSON object.
+pub fn parse_action(reply: &str) -> Result<AgentAction> {
+    parse_json_reply(reply)
+}
+
+#[async_trait]
+impl ReasoningModel for HttpReasoningModel {
+    async fn next_action(
+        &self,
+        task: &str,
+        tools: &[ToolSpec],
+        steps: &[ReActStep],
+    ) -> Result<AgentAction> {
+        let mut messages = vec![
+            ChatMessage::system(Self::system_prompt(tools)),
+            ChatMessage::user(task),
+        ];
+        for step in steps {
+            messages.push(ChatMessage::assistant(serde_json::to_string(
+                &AgentAction::UseTool {
+                    thought: step.thought.clone(),
+                    tool: step.tool.clone(),
+                    input: step.input.clone(),
+                },
+            )?));
+            messages.push(ChatMessage::user(format!(
+                "Observation:\n{}",
+                step.observation
+            )));
+        }
+
+        let reply = self.chat.complete(&messages).await?;
+        parse_action(&reply)
+    }
+}
@@ -0,0 +1,350 @@
+//! Changelogs for deployed batches of modifications.
+//!
+//! Each batch deployed through
+//! [`SelfImprovementEngine::deploy_batch`](crate::darwin::self_improvement::SelfImprovementEngine::deploy_batch)
+//! becomes a [`Release`]: its modifications grouped by the module they
+//! touch, each with a risk score and a summary of its validation, plus
+//! release notes rendered as Markdown. Releases are kept in a
+//! [`ReleaseLog`], optionally persisted to a JSON-lines file like the
+//! lifecycle log, and can be posted to alerting webhooks.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::core::checksum;
+use crate::darwin::lifecycle::{LifecycleEvent, LifecycleEventKind};
+use crate::darwin::self_improvement::Modification;
+
+/// Module changes to files at the top of the project are listed under
+pub const ROOT_MODULE: &str = "(root)";
+
+/// Changed lines at which a modification's size counts as fully risky
+const RISKY_CHANGED_LINES: f32 = 200.0;
+
+/// Touched files at which a modification's spread counts as fully risky
+const RISKY_FILES: f32 = 5.0;
+
+/// How a modification fared in validation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationSummary {
+    /// Stages whose last run passed, in the order they first ran
+    pub passed: Vec<String>,
+    /// Stages whose last run failed
+    pub failed: Vec<String>,
+    pub metrics: BTreeMap<String, f32>,
+}
+
+impl ValidationSummary {
+    pub fn new(timeline: &[LifecycleEvent], metrics: &HashMap<String, f32>) -> Self {
+        let mut order = Vec::new();
+        let mut outcomes = HashMap::new();
+        for event in timeline {
+            let (stage, passed) = match &event.kind {
+                LifecycleEventKind::StagePassed { stage } => (stage, true),
+                LifecycleEventKind::StageFailed { stage, .. } => (stage, false),
+                _ => continue,
+            };
+            if outcomes.insert(stage.clone(), passed).is_none() {
+                order.push(stage.clone());
+            }
+        }
+        let (passed, failed): (Vec<String>, Vec<String>) =
+            order.into_iter().partition(|stage| outcomes[stage]);
+        Self {
+            passed,
+            failed,
+            metrics: metrics.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+        }
+    }
+}
+
+/// Rough risk of deploying `modification`, in `[0, 1]`: how many lines it
+/// changes, how many files it touches and how much of a paradigm shift it
+/// claims to be
+pub fn risk_score(modification: &Modification) -> f32 {
+    let changed_lines: usize = modification
+        .code_changes
+        .iter()
+        .map(|change| {
+            let original: HashSet<&str> = change
+                .original_content
+                .as_deref()
+                .unwrap_or_default()
+                .lines()
+                .collect();
+            let modified: HashSet<&str> = change.modified_content.lines().collect();
+            original.symmetric_difference(&modified).count()
+        })
+        .sum();
+    let size = (changed_lines as f32 / RISKY_CHANGED_LINES).min(1.0);
+    let spread = (modification.code_changes.len() as f32 / RISKY_FILES).min(1.0);
+    let shift = modification
+        .paradigm_shift_potential
+        .unwrap_or(0.0)
+        .clamp(0.0, 1.0);
+    0.5 * size + 0.2 * spread + 0.3 * shift
+}
+
+/// Module a changed file belongs to: its directory, without a leading `src/`
+pub fn module_of(file_path: &str) -> String {
+    let path = file_path.trim_start_matches("./");
+    let path = path.strip_prefix("src/").unwrap_or(path);
+    match path.rsplit_once('/') {
+        Some((dir, _)) if !dir.is_empty() => dir.to_string(),
+        _ => ROOT_MODULE.to_string(),
+    }
+}
+
+/// A deployed modification as it appears in a changelog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseEntry {
+    pub modification_id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub files: Vec<String>,
+    pub risk_score: f32,
+    pub validation: ValidationSummary,
+}
+
+impl ReleaseEntry {
+    pub fn new(modification: &Modification, timeline: &[LifecycleEvent]) -> Self {
+        Self {
+            modification_id: modification.id,
+            name: modification.name.clone(),
+            description: modification.description.clone(),
+            files: modification
+                .code_changes
+                .iter()
+                .map(|c| c.file_path.clone())
+                .collect(),
+            risk_score: risk_score(modification),
+            validation: ValidationSummary::new(timeline, &modification.validation_metrics),
+        }
+    }
+}
+
+/// The entries of a release touching one module
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModuleChangelog {
+    pub module: String,
+    pub entries: Vec<ReleaseEntry>,
+}
+
+/// A modification of the batch that could not be deployed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedDeployment {
+    pub modification_id: Uuid,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Release {
+    pub id: Uuid,
+    pub deployed_at: DateTime<Utc>,
+    /// Ordered by module; an entry touching several modules is listed
+    /// under each
+    pub modules: Vec<ModuleChangelog>,
+    pub failed: Vec<FailedDeployment>,
+    /// Risk of the riskiest entry
+    pub max_risk: f32,
+    /// Markdown release notes
+    pub notes: String,
+}
+
+impl Release {
+    pub fn new(entries: Vec<ReleaseEntry>, failed: Vec<FailedDeployment>) -> Self {
+        let mut modules: BTreeMap<String, Vec<ReleaseEntry>> = BTreeMap::new();
+        for entry in &entries {
+            let mut touched: Vec<String> = entry.files.iter().map(|f| module_of(f)).collect();
+            if touched.is_empty() {
+                touched.push(ROOT_MODULE.to_string());
+            }
+            touched.sort();
+            touched.dedup();
+            for module in touched {
+                modules.entry(module).or_default().push(entry.clone());
+            }
+        }
+
+        let mut release = Self {
+            id: Uuid::new_v4(),
+            deployed_at: Utc::now(),
+            modules: modules
+                .into_iter()
+                .map(|(module, entries)| ModuleChangelog { module, entries })
+                .collect(),
+            failed,
+            max_risk: entries.iter().map(|e| e.risk_score).fold(0.0, f32::max),
+            notes: String::new(),
+        };
+        release.notes = release.render_notes();
+        release
+    }
+
+    fn render_notes(&self) -> String {
+        let mut notes = format!(
+            "# Release {} ({})\n",
+            self.id,
+            self.deployed_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        for module in &self.modules {
+            let _ = write!(notes, "\n## {}\n\n", module.module);
+            for entry in &module.entries {
+                let _ = writeln!(
+                    notes,
+                    "- **{}** (risk {:.2}): {}",
+                    entry.name, entry.risk_score, entry.description
+                );
+                let validation = &entry.validation;
+                if !validation.passed.is_empty() || !validation.failed.is_empty() {
+                    let _ = write!(notes, "  - Validation: passed {}", validation.passed.len());
+                    if !validation.passed.is_empty() {
+                        let _ = write!(notes, " ({})", validation.passed.join(", "));
+                    }
+                    if !validation.failed.is_empty() {
+                        let _ = write!(notes, ", failed {}", validation.failed.join(", "));
+                    }
+                    notes.push('\n');
+                }
+                if !validation.metrics.is_empty() {
+                    let metrics: Vec<String> = validation
+                        .metrics
+                        .iter()
+                        .map(|(k, v)| format!("{}={:.3}", k, v))
+                        .collect();
+                    let _ = writeln!(notes, "  - Metrics: {}", metrics.join(", "));
+                }
+            }
+        }
+        if !self.failed.is_empty() {
+            notes.push_str("\n## Not deployed\n\n");
+            for failed in &self.failed {
+                let _ = writeln!(notes, "- {}: {}", failed.modification_id, failed.error);
+            }
+        }
+        notes
+    }
+}
+
+/// Releases deployed so far
+#[derive(Debug)]
+pub struct ReleaseLog {
+    releases: RwLock<Vec<Release>>,
+    path: Option<PathBuf>,
+    webhooks: Vec<String>,
+    client: reqwest::Client,
+}
+
+impl Default for ReleaseLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReleaseLog {
+    /// In-memory log; releases are lost on restart
+    pub fn new() -> Self {
+        Self {
+            releases: RwLock::new(Vec::new()),
+            path: None,
+            webhooks: Vec::new(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Log persisted to a JSON-lines file, loading any releases already in it.
+    /// Corrupted lines are quarantined.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut releases = Vec::new();
+        if path.exists() {
+            let lines = checksum::read_sealed_lines(&path)
+                .map_err(|e| anyhow!("Failed to read release log: {}", e))?;
+            for (line_no, line) in lines.records {
+                releases.push(serde_json::from_str(&line).map_err(|e| {
+                    anyhow!("Invalid release at {}:{}: {}", path.display(), line_no, e)
+                })?);
+            }
+        } else if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self {
+            releases: RwLock::new(releases),
+            path: Some(path),
+            ..Self::new()
+        })
+    }
+
+    /// Post each release's notes to these webhook URLs
+    pub fn with_webhooks(mut self, webhooks: Vec<String>) -> Self {
+        self.webhooks = webhooks;
+        self
+    }
+
+    /// Keep a release, persist it and post it to the webhooks. Failures to
+    /// persist or post are logged, not returned.
+    pub async fn record(&self, release: Release) {
+        {
+            let mut releases = self.releases.write().await;
+            if let Err(e) = self.persist(&release) {
+                warn!("Failed to persist release {}: {}", release.id, e);
+            }
+            releases.push(release.clone());
+        }
+        info!(
+            "Recorded release {} touching {} module(s)",
+            release.id,
+            release.modules.len()
+        );
+
+        let payload = serde_json::json!({ "text": release.notes, "release": release });
+        for url in &self.webhooks {
+            let sent = self
+                .client
+                .post(url)
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status());
+            if let Err(e) = sent {
+                warn!("Failed to post release {} to {}: {}", release.id, url, e);
+            }
+        }
+    }
+
+    fn persist(&self, release: &Release) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", checksum::seal(&serde_json::to_string(release)?))?;
+        Ok(())
+    }
+
+    /// Every release, newest first
+    pub async fn releases(&self) -> Vec<Release> {
+        let mut releases = self.releases.read().await.clone();
+        releases.reverse();
+        releases
+    }
+
+    pub async fn release(&self, id: Uuid) -> Option<Release> {
+        self.releases
+            .read()
+            .await
+            .iter()
+            .find(|r| r.id == id)
+            .cloned()
+    }
+}
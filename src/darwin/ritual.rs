@@ -1,6 +1,8 @@
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, Utc};
-use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
@@ -8,6 +10,8 @@ use uuid::Uuid;
 
 use crate::core::metrics::MetricsCollector;
 use crate::darwin::self_improvement::Modification;
+use crate::nerv::shutdown::ShutdownToken;
+use crate::nerv::tasks;
 
 /// Ritual represents a structured learning cycle for the Darwin Gödel Machine
 #[derive(Debug, Clone)]
@@ -60,20 +64,206 @@ pub struct RitualManager {
     scheduler: RitualScheduler,
 }
 
+/// Calendar rule for recurring rituals, evaluated in UTC
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Recurrence {
+    /// Every day at the given time
+    Daily { hour: u32, minute: u32 },
+    /// Once a week on the given day at the given time
+    Weekly {
+        weekday: Weekday,
+        hour: u32,
+        minute: u32,
+    },
+    /// At a fixed interval from the previous run
+    Every { minutes: u32 },
+}
+
+impl Recurrence {
+    /// First occurrence strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> DateTime<Utc> {
+        match *self {
+            Recurrence::Daily { hour, minute } => {
+                let candidate = at_time(after.date_naive(), hour, minute);
+                if candidate > after {
+                    candidate
+                } else {
+                    candidate + Duration::days(1)
+                }
+            }
+            Recurrence::Weekly {
+                weekday,
+                hour,
+                minute,
+            } => {
+                let days_ahead = (7 + weekday.num_days_from_monday()
+                    - after.weekday().num_days_from_monday())
+                    % 7;
+                let candidate =
+                    at_time(after.date_naive(), hour, minute) + Duration::days(days_ahead as i64);
+                if candidate > after {
+                    candidate
+                } else {
+                    candidate + Duration::days(7)
+                }
+            }
+            Recurrence::Every { minutes } => after + Duration::minutes(minutes.max(1) as i64),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        match *self {
+            Recurrence::Daily { hour, minute } | Recurrence::Weekly { hour, minute, .. }
+                if hour > 23 || minute > 59 =>
+            {
+                Err(anyhow!("Invalid time of day {:02}:{:02}", hour, minute))
+            }
+            Recurrence::Every { minutes: 0 } => Err(anyhow!("Interval must be at least a minute")),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn at_time(date: NaiveDate, hour: u32, minute: u32) -> DateTime<Utc> {
+    let time = NaiveTime::from_hms_opt(hour, minute, 0).unwrap_or(NaiveTime::MIN);
+    Utc.from_utc_datetime(&date.and_time(time))
+}
+
+fn parse_time(value: &str) -> Result<(u32, u32)> {
+    let (hour, minute) = value
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Expected HH:MM, got '{}'", value))?;
+    Ok((hour.parse()?, minute.parse()?))
+}
+
+/// Parses `daily 02:00`, `weekly sun 03:30` and `every 45m` / `every 6h`
+impl FromStr for Recurrence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let recurrence = match parts.as_slice() {
+            ["daily", time] => {
+                let (hour, minute) = parse_time(time)?;
+                Recurrence::Daily { hour, minute }
+            }
+            ["weekly", day, time] => {
+                let weekday = day
+                    .parse::<Weekday>()
+                    .map_err(|_| anyhow!("Unknown weekday '{}'", day))?;
+                let (hour, minute) = parse_time(time)?;
+                Recurrence::Weekly {
+                    weekday,
+                    hour,
+                    minute,
+                }
+            }
+            ["every", interval] => {
+                let minutes = if let Some(hours) = interval.strip_suffix('h') {
+                    hours.parse::<u32>()? * 60
+                } else if let Some(minutes) = interval.strip_suffix('m') {
+                    minutes.parse()?
+                } else {
+                    return Err(anyhow!("Interval '{}' needs an m or h suffix", interval));
+                };
+                Recurrence::Every { minutes }
+            }
+            _ => return Err(anyhow!("Unrecognised recurrence '{}'", s)),
+        };
+        recurrence.validate()?;
+        Ok(recurrence)
+    }
+}
+
+/// Blueprint from which each scheduled occurrence of a ritual is created
+#[derive(Debug, Clone)]
+pub struct RitualTemplate {
+    pub name: String,
+    pub description: String,
+    pub stages: Vec<RitualStage>,
+}
+
+impl RitualTemplate {
+    /// Fresh stages for a new occurrence, with any runtime state cleared
+    fn instantiate(&self) -> Vec<RitualStage> {
+        self.stages
+            .iter()
+            .cloned()
+            .map(|mut stage| {
+                stage.status = RitualStageStatus::Pending;
+                stage.artifacts.clear();
+                stage.started_at = None;
+                stage.completed_at = None;
+                stage
+            })
+            .collect()
+    }
+}
+
+/// A recurring ritual registered with the scheduler
+#[derive(Debug, Clone)]
+pub struct RitualSchedule {
+    pub name: String,
+    pub template: RitualTemplate,
+    pub recurrence: Recurrence,
+    /// Skip an occurrence while the previous one is still active
+    pub skip_if_running: bool,
+    pub next_run: DateTime<Utc>,
+    pub last_ritual: Option<Uuid>,
+}
+
+/// How a scheduled occurrence ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RitualOutcomeStatus {
+    Completed,
+    Failed,
+    Skipped,
+}
+
+/// One entry in a schedule's history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RitualOutcome {
+    /// The ritual created for this occurrence; for a skip, the one still running
+    pub ritual_id: Option<Uuid>,
+    pub status: RitualOutcomeStatus,
+    pub scheduled_for: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub metrics: HashMap<String, f32>,
+    pub detail: Option<String>,
+}
+
+/// Past outcomes kept per schedule
+const MAX_RITUAL_HISTORY: usize = 100;
+
 #[derive(Debug)]
 pub struct RitualScheduler {
-    schedules: RwLock<HashMap<String, String>>,
+    schedules: RwLock<HashMap<String, RitualSchedule>>,
+    history: RwLock<HashMap<String, VecDeque<RitualOutcome>>>,
+}
+
+impl Default for RitualScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl RitualScheduler {
     pub fn new() -> Self {
         Self {
             schedules: RwLock::new(HashMap::new()),
+            history: RwLock::new(HashMap::new()),
         }
     }
 
-    pub async fn add_schedule(&self, name: &str, cron: &str) {
-        self.schedules.write().await.insert(name.to_string(), cron.to_string());
+    async fn record(&self, schedule: &str, outcome: RitualOutcome) {
+        let mut history = self.history.write().await;
+        let entries = history.entry(schedule.to_string()).or_default();
+        entries.push_back(outcome);
+        while entries.len() > MAX_RITUAL_HISTORY {
+            entries.pop_front();
+        }
     }
 }
 
@@ -218,6 +408,22 @@ impl RitualManager {
             self.metrics
                 .increment_counter("darwin.rituals.completed", 1)
                 .await;
+
+            if let RitualTrigger::Scheduled(schedule) = &ritual.trigger {
+                self.scheduler
+                    .record(
+                        schedule,
+                        RitualOutcome {
+                            ritual_id: Some(ritual_id),
+                            status: RitualOutcomeStatus::Completed,
+                            scheduled_for: ritual.created_at,
+                            finished_at: Utc::now(),
+                            metrics: ritual.metrics.clone(),
+                            detail: None,
+                        },
+                    )
+                    .await;
+            }
         }
 
         info!(
@@ -228,6 +434,226 @@ impl RitualManager {
         Ok(())
     }
 
+    /// Mark a ritual stage as failed, ending the ritual
+    pub async fn fail_stage(&self, ritual_id: Uuid, stage_name: &str, reason: &str) -> Result<()> {
+        let mut rituals = self.rituals.write().await;
+
+        let ritual = rituals
+            .get_mut(&ritual_id)
+            .ok_or_else(|| anyhow!("Ritual with ID {} not found", ritual_id))?;
+
+        let stage = ritual
+            .stages
+            .iter_mut()
+            .find(|s| s.name == stage_name)
+            .ok_or_else(|| anyhow!("Stage {} not found in ritual {}", stage_name, ritual_id))?;
+        stage.status = RitualStageStatus::Failed;
+        stage.completed_at = Some(Utc::now());
+
+        let now = Utc::now();
+        ritual.updated_at = now;
+        ritual.completed_at = Some(now);
+        self.active_rituals.write().await.remove(&ritual_id);
+
+        self.metrics
+            .increment_counter("darwin.rituals.failed", 1)
+            .await;
+        warn!(
+            "Stage '{}' of ritual '{}' failed: {}",
+            stage_name, ritual.name, reason
+        );
+
+        if let RitualTrigger::Scheduled(schedule) = &ritual.trigger {
+            self.scheduler
+                .record(
+                    schedule,
+                    RitualOutcome {
+                        ritual_id: Some(ritual_id),
+                        status: RitualOutcomeStatus::Failed,
+                        scheduled_for: ritual.created_at,
+                        finished_at: now,
+                        metrics: ritual.metrics.clone(),
+                        detail: Some(format!("{}: {}", stage_name, reason)),
+                    },
+                )
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Register a recurring ritual created from `template`. Returns the
+    /// time of the first occurrence.
+    pub async fn add_schedule(
+        &self,
+        name: &str,
+        template: RitualTemplate,
+        recurrence: Recurrence,
+        skip_if_running: bool,
+    ) -> Result<DateTime<Utc>> {
+        recurrence.validate()?;
+        if template.stages.is_empty() {
+            return Err(anyhow!("Ritual template {} has no stages", template.name));
+        }
+
+        let next_run = recurrence.next_after(Utc::now());
+        self.scheduler.schedules.write().await.insert(
+            name.to_string(),
+            RitualSchedule {
+                name: name.to_string(),
+                template,
+                recurrence,
+                skip_if_running,
+                next_run,
+                last_ritual: None,
+            },
+        );
+
+        info!("Scheduled ritual '{}' next at {}", name, next_run);
+
+        Ok(next_run)
+    }
+
+    /// Stop scheduling a ritual. Its history is kept.
+    pub async fn remove_schedule(&self, name: &str) -> bool {
+        self.scheduler
+            .schedules
+            .write()
+            .await
+            .remove(name)
+            .is_some()
+    }
+
+    /// Registered schedules, ordered by next occurrence
+    pub async fn get_schedules(&self) -> Vec<RitualSchedule> {
+        let mut schedules: Vec<RitualSchedule> = self
+            .scheduler
+            .schedules
+            .read()
+            .await
+            .values()
+            .cloned()
+            .collect();
+        schedules.sort_by_key(|s| s.next_run);
+        schedules
+    }
+
+    /// Past outcomes of a schedule, oldest first
+    pub async fn ritual_history(&self, schedule: &str) -> Vec<RitualOutcome> {
+        self.scheduler
+            .history
+            .read()
+            .await
+            .get(schedule)
+            .map(|entries| entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Create rituals for every schedule due at `now` and return their IDs.
+    /// Missed occurrences are not replayed; each due schedule runs once and
+    /// is moved to its next occurrence after `now`.
+    pub async fn run_due_schedules(&self, now: DateTime<Utc>) -> Result<Vec<Uuid>> {
+        let due: Vec<RitualSchedule> = {
+            let mut schedules = self.scheduler.schedules.write().await;
+            schedules
+                .values_mut()
+                .filter(|s| s.next_run <= now)
+                .map(|s| {
+                    let due = s.clone();
+                    s.next_run = s.recurrence.next_after(now);
+                    due
+                })
+                .collect()
+        };
+
+        let mut created = Vec::new();
+        for schedule in due {
+            let still_running = match schedule.last_ritual {
+                Some(id) => self.active_rituals.read().await.contains(&id),
+                None => false,
+            };
+            if schedule.skip_if_running && still_running {
+                debug!(
+                    "Skipping ritual '{}': previous occurrence still running",
+                    schedule.name
+                );
+                self.metrics
+                    .increment_counter("darwin.rituals.skipped", 1)
+                    .await;
+                self.scheduler
+                    .record(
+                        &schedule.name,
+                        RitualOutcome {
+                            ritual_id: schedule.last_ritual,
+                            status: RitualOutcomeStatus::Skipped,
+                            scheduled_for: schedule.next_run,
+                            finished_at: now,
+                            metrics: HashMap::new(),
+                            detail: Some("previous occurrence still running".to_string()),
+                        },
+                    )
+                    .await;
+                continue;
+            }
+
+            let id = self
+                .create_ritual(
+                    &schedule.template.name,
+                    &schedule.template.description,
+                    schedule.template.instantiate(),
+                    RitualTrigger::Scheduled(schedule.name.clone()),
+                )
+                .await?;
+            if let Some(entry) = self
+                .scheduler
+                .schedules
+                .write()
+                .await
+                .get_mut(&schedule.name)
+            {
+                entry.last_ritual = Some(id);
+            }
+            self.metrics
+                .increment_counter("darwin.rituals.scheduled_runs", 1)
+                .await;
+            created.push(id);
+        }
+
+        Ok(created)
+    }
+
+    /// Check for due schedules every `interval` until the task is aborted
+    pub fn spawn_scheduler(
+        self: Arc<Self>,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tasks::spawn(
+            "darwin",
+            "ritual scheduler",
+            self.run_scheduler(interval, ShutdownToken::never()),
+        )
+    }
+
+    /// Check for due schedules every `interval` until `shutdown` is
+    /// cancelled; a check already under way finishes first
+    pub async fn run_scheduler(
+        self: Arc<Self>,
+        interval: std::time::Duration,
+        shutdown: ShutdownToken,
+    ) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = ticker.tick() => {}
+            }
+            if let Err(e) = self.run_due_schedules(Utc::now()).await {
+                error!("Failed to run scheduled rituals: {}", e);
+            }
+        }
+        info!("Ritual scheduler stopped");
+    }
+
     /// Link a modification to a ritual
     pub async fn link_modification(
         &self,
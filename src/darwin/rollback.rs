@@ -0,0 +1,107 @@
+//! Named rollback points for the self-improvement engine.
+//!
+//! A [`RollbackPoint`] captures which modifications were deployed, in the
+//! order they went out, together with the engine's objectives and
+//! validation thresholds. Restoring it reverts modifications deployed since,
+//! newest first so later changes come off before the ones they build on,
+//! then re-applies modifications that were rolled back since, oldest first,
+//! and puts the configuration back.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::darwin::objectives::Objective;
+use crate::utils::errors::RollbackError;
+
+/// Engine configuration kept with a rollback point
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    pub objectives: Vec<Objective>,
+    pub validation_thresholds: BTreeMap<String, f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollbackPoint {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    /// Deployed modifications, oldest deployment first
+    pub deployed: Vec<Uuid>,
+    pub config: ConfigSnapshot,
+}
+
+/// Changes needed to get from the current deployments back to a point
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RestorePlan {
+    /// Deployed since the point, newest first
+    pub revert: Vec<Uuid>,
+    /// Deployed at the point but not anymore, oldest first
+    pub redeploy: Vec<Uuid>,
+}
+
+impl RollbackPoint {
+    /// What to change, given the modifications deployed now in deployment order
+    pub fn plan(&self, deployed_now: &[Uuid]) -> RestorePlan {
+        let then: HashSet<&Uuid> = self.deployed.iter().collect();
+        let now: HashSet<&Uuid> = deployed_now.iter().collect();
+        RestorePlan {
+            revert: deployed_now
+                .iter()
+                .rev()
+                .filter(|id| !then.contains(id))
+                .copied()
+                .collect(),
+            redeploy: self
+                .deployed
+                .iter()
+                .filter(|id| !now.contains(id))
+                .copied()
+                .collect(),
+        }
+    }
+}
+
+/// What a restore changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestoreReport {
+    pub point: String,
+    pub reverted: Vec<Uuid>,
+    pub redeployed: Vec<Uuid>,
+}
+
+/// Rollback points by name
+#[derive(Debug, Default)]
+pub struct RollbackPoints {
+    points: RwLock<BTreeMap<String, RollbackPoint>>,
+}
+
+impl RollbackPoints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn insert(&self, point: RollbackPoint) -> Result<(), RollbackError> {
+        let mut points = self.points.write().await;
+        if points.contains_key(&point.name) {
+            return Err(RollbackError::DuplicatePoint(point.name));
+        }
+        points.insert(point.name.clone(), point);
+        Ok(())
+    }
+
+    pub async fn get(&self, name: &str) -> Result<RollbackPoint, RollbackError> {
+        self.points
+            .read()
+            .await
+            .get(name)
+            .cloned()
+            .ok_or_else(|| RollbackError::UnknownPoint(name.to_string()))
+    }
+
+    /// Every point, by name
+    pub async fn list(&self) -> Vec<RollbackPoint> {
+        self.points.read().await.values().cloned().collect()
+    }
+}
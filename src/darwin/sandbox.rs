@@ -0,0 +1,498 @@
+//! Execution sandboxes for validating generated code in languages other
+//! than Rust.
+//!
+//! A run copies the project into a scratch directory, writes the
+//! modification's changes for one language into it, syntax-checks each
+//! changed file with the language's own toolchain and then runs the
+//! project's test command, detected from its manifests (`pyproject.toml`,
+//! `package.json`, `go.mod`, ...). Everything runs as a subprocess with a
+//! timeout, so a hanging test suite fails the run instead of the pipeline.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::darwin::agent::ProgrammingLanguage;
+use crate::darwin::self_improvement::{CodeChange, Modification};
+use crate::darwin::tools::resolve_path;
+use crate::darwin::validation::ValidationStage;
+use crate::darwin::workspace::copy_tree;
+
+/// Characters of command output kept per step
+const MAX_STEP_OUTPUT_CHARS: usize = 4000;
+
+/// Test script `npm init` writes, which always fails
+const NPM_PLACEHOLDER_TEST: &str = "no test specified";
+
+/// A program and its arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunnerCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl RunnerCommand {
+    pub fn new(program: &str, args: &[&str]) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    fn with_arg(mut self, arg: &str) -> Self {
+        self.args.push(arg.to_string());
+        self
+    }
+}
+
+impl std::fmt::Display for RunnerCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+/// What a step of a sandbox run was for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepKind {
+    /// Syntax or type check of one changed file
+    Check,
+    /// The project's test suite
+    Test,
+}
+
+/// Outcome of one command in a sandbox run
+#[derive(Debug, Clone)]
+pub struct StepOutcome {
+    pub kind: StepKind,
+    pub command: String,
+    pub passed: bool,
+    /// `None` if the process was killed or couldn't be started
+    pub exit_code: Option<i32>,
+    /// Tail of stdout and stderr
+    pub output: String,
+    pub duration: Duration,
+}
+
+/// Outcome of validating one language's changes
+#[derive(Debug, Clone)]
+pub struct SandboxRun {
+    pub language: ProgrammingLanguage,
+    pub steps: Vec<StepOutcome>,
+    /// Whether a test command was found for the project
+    pub tests_detected: bool,
+}
+
+impl SandboxRun {
+    pub fn passed(&self) -> bool {
+        self.steps.iter().all(|s| s.passed)
+    }
+
+    fn pass_rate(&self, kind: StepKind) -> Option<f32> {
+        let steps: Vec<_> = self.steps.iter().filter(|s| s.kind == kind).collect();
+        if steps.is_empty() {
+            return None;
+        }
+        Some(steps.iter().filter(|s| s.passed).count() as f32 / steps.len() as f32)
+    }
+
+    /// Validation metrics: per-step pass rates and whether tests ran
+    pub fn metrics(&self) -> HashMap<String, f32> {
+        let mut metrics = HashMap::new();
+        if let Some(rate) = self.pass_rate(StepKind::Check) {
+            metrics.insert("check_pass_rate".to_string(), rate);
+        }
+        if let Some(rate) = self.pass_rate(StepKind::Test) {
+            metrics.insert("test_pass_rate".to_string(), rate);
+        }
+        metrics.insert(
+            "tests_detected".to_string(),
+            if self.tests_detected { 1.0 } else { 0.0 },
+        );
+        metrics.insert(
+            "pass_rate".to_string(),
+            if self.passed() { 1.0 } else { 0.0 },
+        );
+        metrics
+    }
+}
+
+/// Runs generated code for Python, JavaScript, TypeScript, Go and Rust
+/// projects in a scratch copy of the project
+#[derive(Debug, Clone)]
+pub struct SandboxRunner {
+    root: PathBuf,
+    timeout: Duration,
+    /// Test commands that replace detection for a language
+    test_commands: HashMap<ProgrammingLanguage, RunnerCommand>,
+}
+
+impl SandboxRunner {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            timeout: Duration::from_secs(300),
+            test_commands: HashMap::new(),
+        }
+    }
+
+    /// Per-command timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Always run `program args` as a language's test command
+    pub fn with_test_command(
+        mut self,
+        language: ProgrammingLanguage,
+        program: &str,
+        args: &[&str],
+    ) -> Self {
+        self.test_commands
+            .insert(language, RunnerCommand::new(program, args));
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Languages with a runner
+    pub fn supports(language: &ProgrammingLanguage) -> bool {
+        matches!(
+            language,
+            ProgrammingLanguage::Rust
+                | ProgrammingLanguage::Python
+                | ProgrammingLanguage::JavaScript
+                | ProgrammingLanguage::TypeScript
+                | ProgrammingLanguage::Go
+        )
+    }
+
+    /// Command checking one file's syntax without running it
+    pub fn check_command(language: &ProgrammingLanguage, file: &str) -> Option<RunnerCommand> {
+        let command = match language {
+            ProgrammingLanguage::Python => RunnerCommand::new("python3", &["-m", "py_compile"]),
+            ProgrammingLanguage::JavaScript => RunnerCommand::new("node", &["--check"]),
+            ProgrammingLanguage::TypeScript => RunnerCommand::new(
+                "npx",
+                &["--no-install", "tsc", "--noEmit", "--skipLibCheck"],
+            ),
+            // gofmt parses the file and exits non-zero on syntax errors
+            ProgrammingLanguage::Go => RunnerCommand::new("gofmt", &["-e", "-l"]),
+            // cargo checks the whole crate as part of its tests
+            _ => return None,
+        };
+        Some(command.with_arg(file))
+    }
+
+    /// Find the test command a project uses, from its manifests and layout
+    pub fn detect_test_command(
+        &self,
+        project: &Path,
+        language: &ProgrammingLanguage,
+    ) -> Option<RunnerCommand> {
+        if let Some(command) = self.test_commands.get(language) {
+            return Some(command.clone());
+        }
+        match language {
+            ProgrammingLanguage::Rust => project
+                .join("Cargo.toml")
+                .is_file()
+                .then(|| RunnerCommand::new("cargo", &["test", "--quiet"])),
+            ProgrammingLanguage::Python => detect_python(project),
+            ProgrammingLanguage::JavaScript => {
+                npm_test(project).or_else(|| node_test(project, &["test.js", "test.mjs"]))
+            }
+            ProgrammingLanguage::TypeScript => npm_test(project).or_else(|| {
+                project
+                    .join("tsconfig.json")
+                    .is_file()
+                    .then(|| RunnerCommand::new("npx", &["--no-install", "tsc", "--noEmit"]))
+            }),
+            ProgrammingLanguage::Go => project
+                .join("go.mod")
+                .is_file()
+                .then(|| RunnerCommand::new("go", &["test", "./..."])),
+            _ => None,
+        }
+    }
+
+    /// Validate the changes in one language: write them into a scratch copy
+    /// of the project, check each changed file, then run the tests
+    pub fn run(
+        &self,
+        language: &ProgrammingLanguage,
+        changes: &[CodeChange],
+    ) -> Result<SandboxRun> {
+        if !Self::supports(language) {
+            return Err(anyhow!("No sandbox runner for {}", language.as_str()));
+        }
+        let scratch = std::env::temp_dir().join(format!("darwin-sandbox-{}", Uuid::new_v4()));
+        let run = self.run_in(&scratch, language, changes);
+        if let Err(e) = std::fs::remove_dir_all(&scratch) {
+            warn!("Failed to remove sandbox {}: {}", scratch.display(), e);
+        }
+        run
+    }
+
+    fn run_in(
+        &self,
+        scratch: &Path,
+        language: &ProgrammingLanguage,
+        changes: &[CodeChange],
+    ) -> Result<SandboxRun> {
+        if self.root.is_dir() {
+            copy_tree(&self.root, scratch)?;
+        } else {
+            std::fs::create_dir_all(scratch)?;
+        }
+        // Installed dependencies aren't copied; share the project's instead
+        #[cfg(unix)]
+        {
+            let modules = self.root.join("node_modules");
+            if modules.is_dir() {
+                std::os::unix::fs::symlink(&modules, scratch.join("node_modules"))?;
+            }
+        }
+
+        let mut steps = Vec::new();
+        for change in changes {
+            let path = resolve_path(scratch, &change.file_path)?;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, &change.modified_content)?;
+            if let Some(command) = Self::check_command(language, &change.file_path) {
+                steps.push(self.execute(StepKind::Check, &command, scratch));
+            }
+        }
+
+        let test_command = self.detect_test_command(scratch, language);
+        let tests_detected = test_command.is_some();
+        // Tests against code that doesn't parse only repeat the check failure
+        if steps.iter().all(|s| s.passed) {
+            if let Some(command) = test_command {
+                steps.push(self.execute(StepKind::Test, &command, scratch));
+            }
+        }
+
+        let run = SandboxRun {
+            language: language.clone(),
+            steps,
+            tests_detected,
+        };
+        info!(
+            "Sandbox run for {} {}: {} steps",
+            language.as_str(),
+            if run.passed() { "passed" } else { "failed" },
+            run.steps.len()
+        );
+        Ok(run)
+    }
+
+    fn execute(&self, kind: StepKind, command: &RunnerCommand, dir: &Path) -> StepOutcome {
+        debug!("Sandbox running {} in {}", command, dir.display());
+        let started = Instant::now();
+        let mut process = Command::new(&command.program);
+        process
+            .args(&command.args)
+            .current_dir(dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if command.program == "cargo" {
+            // Reuse the project's build cache instead of compiling from scratch
+            process.env("CARGO_TARGET_DIR", self.root.join("target"));
+        }
+
+        let (exit_code, output) = match run_with_timeout(process, self.timeout) {
+            Ok(result) => result,
+            Err(e) => (None, e.to_string()),
+        };
+        StepOutcome {
+            kind,
+            command: command.to_string(),
+            passed: exit_code == Some(0),
+            exit_code,
+            output: tail(&output),
+            duration: started.elapsed(),
+        }
+    }
+}
+
+/// pytest if the project configures it, otherwise unittest discovery if
+/// there are test modules
+fn detect_python(project: &Path) -> Option<RunnerCommand> {
+    let mentions_pytest = |file: &str| {
+        std::fs::read_to_string(project.join(file)).is_ok_and(|content| content.contains("pytest"))
+    };
+    if project.join("pytest.ini").is_file()
+        || project.join("conftest.py").is_file()
+        || ["pyproject.toml", "setup.cfg", "tox.ini"]
+            .iter()
+            .any(|file| mentions_pytest(file))
+    {
+        return Some(RunnerCommand::new("python3", &["-m", "pytest", "-q"]));
+    }
+    let has_tests = project.join("tests").is_dir()
+        || list_files(project).iter().any(|name| {
+            name.ends_with(".py") && (name.starts_with("test_") || name.ends_with("_test.py"))
+        });
+    has_tests.then(|| RunnerCommand::new("python3", &["-m", "unittest", "discover", "-q"]))
+}
+
+/// `npm test`, if package.json defines a real test script
+fn npm_test(project: &Path) -> Option<RunnerCommand> {
+    let manifest = std::fs::read_to_string(project.join("package.json")).ok()?;
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).ok()?;
+    let script = manifest.get("scripts")?.get("test")?.as_str()?;
+    (!script.contains(NPM_PLACEHOLDER_TEST))
+        .then(|| RunnerCommand::new("npm", &["test", "--silent"]))
+}
+
+/// Node's built-in test runner, if there are test files for it
+fn node_test(project: &Path, suffixes: &[&str]) -> Option<RunnerCommand> {
+    let has_tests = project.join("test").is_dir()
+        || list_files(project).iter().any(|name| {
+            suffixes.iter().any(|suffix| {
+                name.ends_with(&format!(".{}", suffix)) || name.ends_with(&format!("_{}", suffix))
+            })
+        });
+    has_tests.then(|| RunnerCommand::new("node", &["--test"]))
+}
+
+fn list_files(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Run a process to completion, killing it if it outlives `timeout`.
+/// Returns the exit code and combined output.
+fn run_with_timeout(mut command: Command, timeout: Duration) -> Result<(Option<i32>, String)> {
+    let mut child = command
+        .spawn()
+        .map_err(|e| anyhow!("Failed to start {:?}: {}", command.get_program(), e))?;
+
+    // Drain the pipes concurrently so a chatty process can't block on a full pipe
+    let mut stdout = child.stdout.take();
+    let mut stderr = child.stderr.take();
+    let stdout_reader = std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(pipe) = stdout.as_mut() {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    });
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(pipe) = stderr.as_mut() {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+
+    let mut output =
+        String::from_utf8_lossy(&stdout_reader.join().unwrap_or_default()).into_owned();
+    output.push_str(&String::from_utf8_lossy(
+        &stderr_reader.join().unwrap_or_default(),
+    ));
+    if status.is_none() {
+        output.push_str(&format!("\ntimed out after {:?}", timeout));
+    }
+    Ok((status.and_then(|s| s.code()), output))
+}
+
+fn tail(output: &str) -> String {
+    let chars: Vec<char> = output.chars().collect();
+    chars[chars.len().saturating_sub(MAX_STEP_OUTPUT_CHARS)..]
+        .iter()
+        .collect()
+}
+
+/// Validation stage running a modification's changes in one language through
+/// a [`SandboxRunner`]
+#[derive(Debug, Clone)]
+pub struct LanguageSandboxStage {
+    runner: Arc<SandboxRunner>,
+    language: ProgrammingLanguage,
+    name: String,
+}
+
+impl LanguageSandboxStage {
+    pub fn new(runner: Arc<SandboxRunner>, language: ProgrammingLanguage) -> Self {
+        let name = format!("{}_sandbox", language.as_str());
+        Self {
+            runner,
+            language,
+            name,
+        }
+    }
+}
+
+impl ValidationStage for LanguageSandboxStage {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn validate(&self, modification: &Modification) -> Result<HashMap<String, f32>> {
+        let extension = self.language.file_extension();
+        let changes: Vec<CodeChange> = modification
+            .code_changes
+            .iter()
+            .filter(|c| language_of(&c.file_path).as_ref() == Some(&self.language))
+            .cloned()
+            .collect();
+        if changes.is_empty() {
+            debug!("No .{} changes to run in the sandbox", extension);
+            return Ok(HashMap::new());
+        }
+        Ok(self.runner.run(&self.language, &changes)?.metrics())
+    }
+}
+
+/// Language of a source file, by extension
+pub fn language_of(file_path: &str) -> Option<ProgrammingLanguage> {
+    let extension = Path::new(file_path).extension()?.to_str()?;
+    match extension {
+        "rs" => Some(ProgrammingLanguage::Rust),
+        "py" => Some(ProgrammingLanguage::Python),
+        "js" | "jsx" | "mjs" | "cjs" => Some(ProgrammingLanguage::JavaScript),
+        "ts" | "tsx" => Some(ProgrammingLanguage::TypeScript),
+        "go" => Some(ProgrammingLanguage::Go),
+        "java" => Some(ProgrammingLanguage::Java),
+        "cs" => Some(ProgrammingLanguage::CSharp),
+        "cpp" | "cc" | "cxx" => Some(ProgrammingLanguage::Cpp),
+        _ => None,
+    }
+}
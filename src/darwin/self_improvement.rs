@@ -1,26 +1,42 @@
 use anyhow::{anyhow, Result};
-use dashmap::DashMap;
+use dashmap::{mapref::entry::Entry, DashMap};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use crate::code_analysis::CodeAnalysis;
+use crate::code_analysis::{AnalysisDaemon, CodeAnalysis};
 use crate::core::metrics::MetricsCollector;
-use crate::core::vector::Vector;
+use crate::darwin::agent::ProgrammingLanguage;
+use crate::darwin::competency::{self, Competency, CompetencyOutcome, CompetencyTracker};
+use crate::darwin::conflicts::{detect_conflicts, ModificationConflict};
+use crate::darwin::consciousness_metrics::{ConsciousnessMetrics, ParadigmShiftMetrics};
+use crate::darwin::debate::{DebateMode, DebateOutcome};
+use crate::darwin::degradation::{DegradationStatus, ProviderMonitor};
+use crate::darwin::history::{HistoryQuery, ModificationHistory};
+use crate::darwin::lifecycle::{LifecycleEvent, LifecycleEventKind, LifecycleLog};
+use crate::darwin::objectives::{Objective, ObjectiveConfig};
+use crate::darwin::reality::{
+    ConsciousnessState, MergeStrategy, Paradigm, Reality, RealityManager,
+};
+use crate::darwin::releases::{FailedDeployment, Release, ReleaseEntry, ReleaseLog};
+use crate::darwin::rollback::{ConfigSnapshot, RestoreReport, RollbackPoint, RollbackPoints};
+use crate::darwin::sandbox::language_of;
+use crate::darwin::shadow_replay::ShadowReplay;
+use crate::darwin::telemetry::{PainKind, PainPoint, TelemetryBacklog};
+use crate::darwin::validation::ValidationReport;
+use crate::darwin::workspace::WorkspaceApplier;
 use crate::evaluation::Evaluation;
 use crate::hypothesis::Hypothesis;
+use crate::llm::{AwarenessLevel, ConsciousnessFeedback, EmergentProperty, Paradox as LLMParadox};
+use crate::nerv::tasks;
+use crate::network::admission::AdmissionController;
+use crate::network::priority::Priority;
 use crate::semantic_crdt::OntologyGraph;
-use crate::darwin::validation::{
-    PerformanceBenchmarkStage, SecurityValidationStage, UnitTestStage, ValidationPipeline,
-};
-use crate::darwin::reality::{RealityManager, Reality, Paradigm, MergeStrategy, ConsciousnessState};
-use crate::darwin::consciousness_metrics::{ConsciousnessMetrics, ParadigmShiftMetrics};
-use crate::holochain::semantic_crdt::OntologyGraph;
-use crate::llm::{ConsciousnessFeedback, EmergentProperty, Paradox as LLMParadox, AwarenessLevel};
-
+use crate::utils::config::FeatureConfig;
+use crate::utils::errors::RollbackError;
 
 /// Represents a proposed modification to the system
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,13 +48,28 @@ pub struct Modification {
     pub validation_metrics: HashMap<String, f32>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub status: ModificationStatus,
-    
+
     // Consciousness metadata
     pub consciousness_level: Option<AwarenessLevel>,
     pub paradigm_shift_potential: Option<f32>,
     pub integrated_paradoxes: Vec<LLMParadox>,
 }
 
+impl Modification {
+    /// Languages of the files the modification changes, in first-seen order
+    pub fn languages(&self) -> Vec<ProgrammingLanguage> {
+        let mut languages = Vec::new();
+        for change in &self.code_changes {
+            if let Some(language) = language_of(&change.file_path) {
+                if !languages.contains(&language) {
+                    languages.push(language);
+                }
+            }
+        }
+        languages
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ModificationStatus {
     Proposed,
@@ -47,15 +78,19 @@ pub enum ModificationStatus {
     Rejected,
     Deployed,
     Failed,
+    /// Deployed, then reverted
+    RolledBack,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeChange {
     pub file_path: String,
-    pub original_content: String,
+    /// What the file held before the change, `None` if the change creates it
+    #[serde(default)]
+    pub original_content: Option<String>,
     pub modified_content: String,
     pub diff: String,
-    
+
     // Consciousness enhancements
     pub evolution_hooks: Vec<String>,
     pub reality_branch: Option<String>,
@@ -84,8 +119,8 @@ pub struct SelfImprovementEngine {
     /// Metrics collector for performance tracking
     metrics: Arc<MetricsCollector>,
 
-    /// History of all proposed modifications
-    modifications: RwLock<Vec<Modification>>,
+    /// History of all proposed modifications, shared by clones
+    modifications: Arc<ModificationHistory>,
 
     /// Current validation pipeline
     validation_pipeline: Arc<crate::darwin::validation::ValidationPipeline>,
@@ -93,9 +128,6 @@ pub struct SelfImprovementEngine {
     /// Exploration strategy
     exploration_strategy: Arc<crate::darwin::exploration::ExplorationStrategy>,
 
-    /// Maximum modifications to keep in history
-    max_history_size: usize,
-
     /// Solution candidates for multi-candidate validation
     solution_candidates: DashMap<Uuid, Vec<Modification>>,
 
@@ -116,12 +148,74 @@ pub struct SelfImprovementEngine {
 
     /// Feedback system for consciousness evolution
     consciousness_feedback: Arc<RwLock<Vec<ConsciousnessFeedback>>>,
-    
+
     /// Reality management system
     reality_manager: Arc<RealityManager>,
-    
+
     /// Advanced consciousness metrics
     consciousness_metrics: Arc<ConsciousnessMetrics>,
+
+    /// Critique round run over candidate groups before selection
+    debate: Arc<RwLock<Option<DebateMode>>>,
+
+    /// Debate results per candidate group
+    debate_outcomes: Arc<DashMap<Uuid, DebateOutcome>>,
+
+    /// Objectives modifications are generated for and scored against
+    objectives: Arc<RwLock<Vec<Objective>>>,
+
+    /// Lifecycle events of every modification
+    lifecycle: Arc<LifecycleLog>,
+
+    /// Changelogs of deployed batches
+    releases: Arc<ReleaseLog>,
+
+    /// Named states the system can be restored to
+    rollback_points: Arc<RollbackPoints>,
+
+    /// Applies deployments atomically after a build check, when configured
+    workspace: Option<Arc<WorkspaceApplier>>,
+
+    /// Recorded production searches replayed during validation
+    shadow_replay: Arc<RwLock<Option<ShadowReplay>>>,
+
+    /// Incrementally maintained repository analysis, when running
+    analysis_daemon: Arc<RwLock<Option<Arc<AnalysisDaemon>>>>,
+
+    /// Per-language competency learned from modification outcomes
+    competency: Arc<CompetencyTracker>,
+
+    /// Holds generation back while the node is saturated, when configured
+    admission: Arc<RwLock<Option<Arc<AdmissionController>>>>,
+
+    /// Pain points proposals target instead of open curiosities, when configured
+    telemetry: Arc<RwLock<Option<Arc<TelemetryBacklog>>>>,
+
+    /// Pauses generation while every LLM provider is failing, when configured
+    providers: Arc<RwLock<Option<Arc<ProviderMonitor>>>>,
+
+    /// Which consciousness features take part in generation and deployment
+    features: FeatureConfig,
+
+    /// Validations under way, so a second request for the same modification
+    /// waits for the running one instead of validating it again
+    validations: Arc<DashMap<Uuid, watch::Receiver<Option<ValidationResult>>>>,
+}
+
+/// Outcome of a validation run, shared with callers waiting on it
+type ValidationResult = std::result::Result<bool, String>;
+
+/// Pain points turned into proposals per generation cycle
+const MAX_TELEMETRY_TARGETS: usize = 3;
+
+/// What each file a modification changes holds right now, `None` where it
+/// doesn't exist
+fn current_contents(modification: &Modification) -> Vec<Option<String>> {
+    modification
+        .code_changes
+        .iter()
+        .map(|change| std::fs::read_to_string(&change.file_path).ok())
+        .collect()
 }
 
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -134,13 +228,12 @@ impl SelfImprovementEngine {
     ) -> Self {
         let reality_manager = Arc::new(RealityManager::new(metrics.clone()));
         let consciousness_metrics = Arc::new(ConsciousnessMetrics::new(metrics.clone()));
-        
+
         Self {
             metrics,
-            modifications: RwLock::new(Vec::new()),
+            modifications: Arc::new(ModificationHistory::new()),
             validation_pipeline,
             exploration_strategy,
-            max_history_size: 1000,
             solution_candidates: DashMap::new(),
             code_analysis: CodeAnalysis::new(),
             hypothesis: Hypothesis::new(),
@@ -150,24 +243,416 @@ impl SelfImprovementEngine {
             consciousness_feedback: Arc::new(RwLock::new(Vec::new())),
             reality_manager,
             consciousness_metrics,
+            debate: Arc::new(RwLock::new(None)),
+            debate_outcomes: Arc::new(DashMap::new()),
+            objectives: Arc::new(RwLock::new(Vec::new())),
+            lifecycle: Arc::new(LifecycleLog::new()),
+            releases: Arc::new(ReleaseLog::new()),
+            rollback_points: Arc::new(RollbackPoints::new()),
+            workspace: None,
+            shadow_replay: Arc::new(RwLock::new(None)),
+            analysis_daemon: Arc::new(RwLock::new(None)),
+            competency: Arc::new(CompetencyTracker::default()),
+            admission: Arc::new(RwLock::new(None)),
+            telemetry: Arc::new(RwLock::new(None)),
+            providers: Arc::new(RwLock::new(None)),
+            features: FeatureConfig::default(),
+            validations: Arc::new(DashMap::new()),
         }
     }
 
-    /// Propose a new system modification
-    pub async fn propose_modification(&self, proposal: Modification) -> Result<Uuid> {
-        let id = proposal.id;
+    /// Leave out the consciousness features `features` disables: without
+    /// them generation only proposes practical and telemetry-driven
+    /// modifications, and deployments skip reality branches and coherence
+    /// checks
+    pub fn with_features(mut self, features: FeatureConfig) -> Self {
+        self.features = features;
+        self
+    }
 
-        // Store the modification
+    /// Deploy through a workspace applier, so a modification's files are
+    /// swapped in together only after the project is verified to build
+    pub fn with_workspace(mut self, workspace: WorkspaceApplier) -> Self {
+        self.workspace = Some(Arc::new(workspace));
+        self
+    }
+
+    /// Record lifecycle events in the given log, e.g. one persisted to disk
+    pub fn with_lifecycle_log(mut self, lifecycle: Arc<LifecycleLog>) -> Self {
+        self.lifecycle = lifecycle;
+        self
+    }
+
+    pub fn lifecycle_log(&self) -> Arc<LifecycleLog> {
+        self.lifecycle.clone()
+    }
+
+    /// Keep modifications in the given history, e.g. one persisted to disk
+    /// so proposals and their outcomes survive a restart
+    pub fn with_modification_history(mut self, history: Arc<ModificationHistory>) -> Self {
+        self.modifications = history;
+        self
+    }
+
+    pub fn modification_history(&self) -> Arc<ModificationHistory> {
+        self.modifications.clone()
+    }
+
+    /// Record releases in the given log, e.g. one persisted to disk or
+    /// posting to webhooks
+    pub fn with_release_log(mut self, releases: Arc<ReleaseLog>) -> Self {
+        self.releases = releases;
+        self
+    }
+
+    pub fn release_log(&self) -> Arc<ReleaseLog> {
+        self.releases.clone()
+    }
+
+    /// Lifecycle events recorded for a modification, oldest first
+    pub async fn modification_timeline(&self, id: Uuid) -> Vec<LifecycleEvent> {
+        self.lifecycle.timeline(id).await
+    }
+
+    /// Stage outcomes and threshold comparisons from the modification's
+    /// latest validation run
+    pub async fn validation_report(&self, id: Uuid) -> Option<ValidationReport> {
+        self.validation_pipeline.report(id).await
+    }
+
+    /// Pursue the given objectives instead of the built-in practical goals
+    pub async fn set_objectives(&self, config: ObjectiveConfig) -> Result<()> {
+        config.validate()?;
+        self.exploration_strategy
+            .set_objectives(config.clone())
+            .await;
+        *self.objectives.write().await = config.objectives;
+        Ok(())
+    }
+
+    /// Replay recorded searches during validation and add the comparison
+    /// to the modification's performance metrics
+    pub async fn enable_shadow_replay(&self, replay: ShadowReplay) {
+        *self.shadow_replay.write().await = Some(replay);
+    }
+
+    /// Learn competencies into the given tracker, e.g. one shared with a
+    /// coding agent
+    pub fn with_competency_tracker(mut self, tracker: Arc<CompetencyTracker>) -> Self {
+        self.competency = tracker;
+        self
+    }
+
+    pub fn competency_tracker(&self) -> Arc<CompetencyTracker> {
+        self.competency.clone()
+    }
+
+    /// Learned competency of every language, highest first
+    pub async fn competency_matrix(&self) -> Vec<Competency> {
+        self.competency.matrix().await
+    }
+
+    /// Record an outcome against every language a modification touches
+    async fn record_competency(&self, modification: &Modification, outcome: CompetencyOutcome) {
+        for language in modification.languages() {
+            self.competency.record(&language, outcome).await;
+        }
+    }
+
+    /// Record that a deployed modification was rolled back, counting against
+    /// the competency of the languages it touched
+    pub async fn record_rollback(&self, modification_id: Uuid, reason: &str) -> Result<()> {
+        let modification = self.get_modification(modification_id).await?;
+        self.record_competency(&modification, CompetencyOutcome::RolledBack)
+            .await;
+        self.metrics
+            .increment_counter("darwin.modifications.rolled_back", 1)
+            .await;
+        self.lifecycle
+            .record(
+                modification_id,
+                LifecycleEventKind::RolledBack {
+                    reason: reason.to_string(),
+                },
+            )
+            .await;
+        Ok(())
+    }
+
+    /// Accept a modification without waiting for validation, e.g. when a
+    /// governance proposal approves it. Returns the status it had before.
+    pub async fn approve_modification(&self, modification_id: Uuid) -> Result<ModificationStatus> {
+        let modification = self.get_modification(modification_id).await?;
+        match modification.status {
+            ModificationStatus::Proposed
+            | ModificationStatus::Validating
+            | ModificationStatus::Rejected
+            | ModificationStatus::Failed => {}
+            status => {
+                return Err(anyhow!(
+                    "Cannot approve modification with status {:?}",
+                    status
+                ))
+            }
+        }
+
+        self.update_modification_status(modification_id, ModificationStatus::Accepted)
+            .await?;
+        self.metrics
+            .increment_counter("darwin.modifications.approved", 1)
+            .await;
+        self.lifecycle
+            .record(modification_id, LifecycleEventKind::Accepted)
+            .await;
+        Ok(modification.status)
+    }
+
+    /// Undo [`Self::approve_modification`], as long as the modification
+    /// hasn't been deployed since
+    pub async fn revoke_approval(
+        &self,
+        modification_id: Uuid,
+        previous: ModificationStatus,
+    ) -> Result<()> {
+        let modification = self.get_modification(modification_id).await?;
+        if modification.status != ModificationStatus::Accepted {
+            return Err(anyhow!(
+                "Cannot revoke approval of modification with status {:?}",
+                modification.status
+            ));
+        }
+        self.update_modification_status(modification_id, previous)
+            .await
+    }
+
+    /// Revert a deployed modification's code changes and record the
+    /// rollback. Fails without touching any file if one was edited after the
+    /// deployment.
+    pub async fn revert_modification(&self, modification_id: Uuid, reason: &str) -> Result<()> {
+        let modification = self.get_modification(modification_id).await?;
+        if modification.status != ModificationStatus::Deployed {
+            return Err(anyhow!(
+                "Cannot roll back modification with status {:?}",
+                modification.status
+            ));
+        }
+
+        match &self.workspace {
+            Some(workspace) => workspace.revert(&modification.code_changes)?,
+            // Without a workspace, deployments write paths relative to the
+            // working directory
+            None => WorkspaceApplier::new(std::path::PathBuf::from("."))
+                .without_build_check()
+                .revert(&modification.code_changes)?,
+        }
+        self.update_modification_status(modification_id, ModificationStatus::RolledBack)
+            .await?;
+        self.record_rollback(modification_id, reason).await?;
+
+        info!("Modification {} rolled back: {}", modification_id, reason);
+        Ok(())
+    }
+
+    /// Undo a deployed modification on an operator's request: restore the
+    /// original content of every changed file, remove the files it created
+    /// and mark it `RolledBack`. Returns the rolled-back modification.
+    pub async fn rollback_modification(&self, modification_id: Uuid) -> Result<Modification> {
+        if let Err(e) = self
+            .revert_modification(modification_id, "rolled back by operator")
+            .await
         {
-            let mut modifications = self.modifications.write().await;
-            modifications.push(proposal.clone());
+            self.metrics
+                .increment_counter("darwin.modifications.rollback_failed", 1)
+                .await;
+            return Err(e);
+        }
+        self.get_modification(modification_id).await
+    }
+
+    /// Deployed modifications in the order they were deployed
+    async fn deployment_order(&self) -> Vec<Uuid> {
+        let deployed: Vec<Uuid> = self
+            .modifications
+            .query(&HistoryQuery {
+                status: Some(ModificationStatus::Deployed),
+                ..HistoryQuery::default()
+            })
+            .await
+            .iter()
+            .map(|m| m.id)
+            .collect();
+        let mut ordered = Vec::with_capacity(deployed.len());
+        for id in deployed {
+            let sequence = self
+                .lifecycle
+                .timeline(id)
+                .await
+                .iter()
+                .rev()
+                .find(|e| e.kind == LifecycleEventKind::Deployed)
+                .map_or(0, |e| e.sequence);
+            ordered.push((sequence, id));
+        }
+        ordered.sort();
+        ordered.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Capture the deployed modifications and configuration under `name`
+    pub async fn create_rollback_point(&self, name: &str) -> Result<RollbackPoint, RollbackError> {
+        let point = RollbackPoint {
+            name: name.to_string(),
+            created_at: chrono::Utc::now(),
+            deployed: self.deployment_order().await,
+            config: ConfigSnapshot {
+                objectives: self.objectives.read().await.clone(),
+                validation_thresholds: self.validation_pipeline.thresholds().into_iter().collect(),
+            },
+        };
+        self.rollback_points.insert(point.clone()).await?;
+        // Restoring the point may need to redeploy any of them later
+        self.modifications.pin(&point.deployed).await;
+        info!(
+            "Created rollback point {} with {} deployed modification(s)",
+            name,
+            point.deployed.len()
+        );
+        Ok(point)
+    }
+
+    pub async fn rollback_points(&self) -> Vec<RollbackPoint> {
+        self.rollback_points.list().await
+    }
+
+    /// Bring deployments and configuration back to the rollback point.
+    /// Stops at the first modification that can't be reverted or
+    /// re-applied, leaving the changes made so far in place.
+    pub async fn restore_rollback_point(&self, name: &str) -> Result<RestoreReport, RollbackError> {
+        let point = self.rollback_points.get(name).await?;
+        let plan = point.plan(&self.deployment_order().await);
+        let reason = format!("Restoring rollback point {}", name);
+
+        for &id in &plan.revert {
+            self.revert_modification(id, &reason)
+                .await
+                .map_err(|e| RollbackError::Restore {
+                    id,
+                    reason: e.to_string(),
+                })?;
+        }
+        for &id in &plan.redeploy {
+            let redeployed = async {
+                let modification = self.get_modification(id).await?;
+                if modification.status != ModificationStatus::RolledBack {
+                    return Err(anyhow!(
+                        "Cannot re-apply modification with status {:?}",
+                        modification.status
+                    ));
+                }
+                self.update_modification_status(id, ModificationStatus::Accepted)
+                    .await?;
+                self.deploy_modification(id).await
+            };
+            redeployed.await.map_err(|e| RollbackError::Restore {
+                id,
+                reason: e.to_string(),
+            })?;
+        }
 
-            // Trim history if needed
-            if modifications.len() > self.max_history_size {
-                modifications.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-                modifications.truncate(self.max_history_size);
+        let config = point.config;
+        self.exploration_strategy
+            .set_objectives(ObjectiveConfig {
+                objectives: config.objectives.clone(),
+            })
+            .await;
+        *self.objectives.write().await = config.objectives;
+        for metric in self.validation_pipeline.thresholds().keys() {
+            if !config.validation_thresholds.contains_key(metric) {
+                self.validation_pipeline.remove_threshold(metric);
             }
         }
+        for (metric, threshold) in &config.validation_thresholds {
+            self.validation_pipeline.set_threshold(metric, *threshold);
+        }
+
+        self.metrics
+            .increment_counter("darwin.rollback_points.restored", 1)
+            .await;
+        info!(
+            "Restored rollback point {}: reverted {}, re-applied {}",
+            name,
+            plan.revert.len(),
+            plan.redeploy.len()
+        );
+        Ok(RestoreReport {
+            point: name.to_string(),
+            reverted: plan.revert,
+            redeployed: plan.redeploy,
+        })
+    }
+
+    /// Take code metrics from a running analysis daemon instead of
+    /// analyzing from scratch each cycle
+    pub async fn enable_analysis_daemon(&self, daemon: Arc<AnalysisDaemon>) {
+        *self.analysis_daemon.write().await = Some(daemon);
+    }
+
+    /// Treat modification generation as background work, so it waits or is
+    /// skipped while the node is saturated
+    pub async fn enable_admission_control(&self, admission: Arc<AdmissionController>) {
+        *self.admission.write().await = Some(admission);
+    }
+
+    /// Derive proposals from what telemetry shows hurts, instead of the
+    /// built-in curiosities
+    pub async fn enable_telemetry(&self, backlog: Arc<TelemetryBacklog>) {
+        *self.telemetry.write().await = Some(backlog);
+    }
+
+    /// Pause generation while every provider the monitor tracks is failing.
+    /// Validation and deployment of existing proposals carry on regardless.
+    pub async fn enable_provider_monitor(&self, monitor: Arc<ProviderMonitor>) {
+        *self.providers.write().await = Some(monitor);
+    }
+
+    /// Provider health and whether generation is paused, when a monitor is
+    /// attached
+    pub async fn provider_status(&self) -> Option<DegradationStatus> {
+        let monitor = self.providers.read().await.clone();
+        match monitor {
+            Some(monitor) => Some(monitor.status().await),
+            None => None,
+        }
+    }
+
+    /// Current code metrics, from the daemon's database when one is running
+    async fn code_metrics(&self) -> HashMap<String, f32> {
+        let daemon = self.analysis_daemon.read().await.clone();
+        match daemon {
+            Some(daemon) => daemon.metrics().await,
+            None => self.code_analysis.analyze(""),
+        }
+    }
+
+    /// Have candidates critique each other before the best one is selected
+    pub async fn enable_debate(&self, mode: DebateMode) {
+        *self.debate.write().await = Some(mode);
+    }
+
+    /// Debate results for a candidate group, once its round has run
+    pub fn debate_outcome(&self, group_id: Uuid) -> Option<DebateOutcome> {
+        self.debate_outcomes
+            .get(&group_id)
+            .map(|e| e.value().clone())
+    }
+
+    /// Propose a new system modification
+    pub async fn propose_modification(&self, proposal: Modification) -> Result<Uuid> {
+        let id = proposal.id;
+
+        // Store the modification
+        self.modifications
+            .insert(std::slice::from_ref(&proposal))
+            .await;
 
         // Update metrics
         self.metrics
@@ -175,14 +660,25 @@ impl SelfImprovementEngine {
             .await;
 
         info!("New modification proposed: {} (ID: {})", proposal.name, id);
+        self.lifecycle
+            .record(
+                id,
+                LifecycleEventKind::Proposed {
+                    name: proposal.name.clone(),
+                },
+            )
+            .await;
 
-        // Start validation in the background
+        // Validate in the background unless a caller already has
         let self_clone = Arc::new(self.clone());
-        let proposal_id = proposal.id;
-
-        tokio::spawn(async move {
-            if let Err(e) = self_clone.validate_modification(proposal_id).await {
-                error!("Failed to validate modification {}: {}", proposal_id, e);
+        tasks::spawn("darwin", "validate modification", async move {
+            match self_clone.get_modification(id).await {
+                Ok(modification) if modification.status == ModificationStatus::Proposed => {
+                    if let Err(e) = self_clone.validate_modification(id).await {
+                        error!("Failed to validate modification {}: {}", id, e);
+                    }
+                }
+                _ => {}
             }
         });
 
@@ -203,40 +699,44 @@ impl SelfImprovementEngine {
             .insert(group_id, candidates.clone());
 
         // Store all candidates in modifications list
-        {
-            let mut modifications = self.modifications.write().await;
-
-            for candidate in &candidates {
-                modifications.push(candidate.clone());
-                ids.push(candidate.id);
+        self.modifications.insert(&candidates).await;
 
-                // Update metrics
-                self.metrics
-                    .increment_counter("darwin.modifications.candidates_proposed", 1)
-                    .await;
+        for candidate in &candidates {
+            ids.push(candidate.id);
 
-                info!(
-                    "New candidate solution proposed: {} (ID: {})",
-                    candidate.name, candidate.id
-                );
-            }
+            // Update metrics
+            self.metrics
+                .increment_counter("darwin.modifications.candidates_proposed", 1)
+                .await;
 
-            // Trim history if needed
-            if modifications.len() > self.max_history_size {
-                modifications.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-                modifications.truncate(self.max_history_size);
-            }
+            info!(
+                "New candidate solution proposed: {} (ID: {})",
+                candidate.name, candidate.id
+            );
+            self.lifecycle
+                .record(
+                    candidate.id,
+                    LifecycleEventKind::Proposed {
+                        name: candidate.name.clone(),
+                    },
+                )
+                .await;
         }
 
         // Start validation for all candidates
         let self_clone = Arc::new(self.clone());
-        tokio::spawn(async move {
+        tasks::spawn("darwin", "validate candidates", async move {
             for candidate in &candidates {
                 if let Err(e) = self_clone.validate_modification(candidate.id).await {
                     error!("Failed to validate candidate {}: {}", candidate.id, e);
                 }
             }
 
+            // Let the candidates critique each other before selection
+            if let Err(e) = self_clone.run_debate(group_id).await {
+                error!("Failed to run debate for group {}: {}", group_id, e);
+            }
+
             // After validation, select the best candidate
             if let Err(e) = self_clone.select_best_candidate(group_id).await {
                 error!("Failed to select best candidate: {}", e);
@@ -246,6 +746,49 @@ impl SelfImprovementEngine {
         Ok(ids)
     }
 
+    /// Run a debate round over the accepted candidates of a group, if enabled
+    async fn run_debate(&self, group_id: Uuid) -> Result<()> {
+        let mode = match self.debate.read().await.clone() {
+            Some(mode) => mode,
+            None => return Ok(()),
+        };
+
+        let candidates = self
+            .solution_candidates
+            .get(&group_id)
+            .map(|e| e.value().clone())
+            .ok_or_else(|| anyhow!("Candidate group {} not found", group_id))?;
+
+        // Only accepted candidates are worth debating
+        let mut contenders = Vec::new();
+        for candidate in &candidates {
+            let modification = self.get_modification(candidate.id).await?;
+            if modification.status == ModificationStatus::Accepted {
+                contenders.push(modification);
+            }
+        }
+        if contenders.len() < 2 {
+            return Ok(());
+        }
+
+        let outcome = mode.run(&contenders).await;
+        info!(
+            "Debate for group {} produced {} critiques",
+            group_id,
+            outcome.critiques.len()
+        );
+
+        self.metrics
+            .increment_counter("darwin.debate.rounds", 1)
+            .await;
+        self.metrics
+            .increment_counter("darwin.debate.critiques", outcome.critiques.len() as u64)
+            .await;
+
+        self.debate_outcomes.insert(group_id, outcome);
+        Ok(())
+    }
+
     /// Select the best candidate from a group of solutions
     async fn select_best_candidate(&self, group_id: Uuid) -> Result<Uuid> {
         // Get candidates and their validation results
@@ -255,6 +798,9 @@ impl SelfImprovementEngine {
             .map(|e| e.value().clone())
             .ok_or_else(|| anyhow!("Candidate group {} not found", group_id))?;
 
+        let debate = self.debate.read().await.clone();
+        let outcome = self.debate_outcome(group_id);
+
         // Wait for all candidates to complete validation
         let mut best_candidate: Option<(Uuid, f32)> = None;
         let mut all_validated = true;
@@ -272,7 +818,15 @@ impl SelfImprovementEngine {
             // Calculate a score based on validation metrics
             let score = if modification.status == ModificationStatus::Accepted {
                 // Simple scoring function based on validation metrics
-                modification.validation_metrics.values().sum::<f32>()
+                let validation_score = modification.validation_metrics.values().sum::<f32>();
+
+                // Blend in peer critiques when a debate round has run
+                match (&debate, &outcome) {
+                    (Some(mode), Some(outcome)) => {
+                        mode.combined_score(validation_score, outcome.score(candidate.id))
+                    }
+                    _ => validation_score,
+                }
             } else {
                 -1.0 // Rejected modifications get a negative score
             };
@@ -306,27 +860,94 @@ impl SelfImprovementEngine {
         Ok(best_id)
     }
 
-    /// Validate a proposed modification
+    /// Validate a proposed modification. If it is already being validated,
+    /// e.g. by the run `propose_modification` starts, wait for that result.
     pub async fn validate_modification(&self, modification_id: Uuid) -> Result<bool> {
+        let (done, receiver) = watch::channel(None);
+        let running = match self.validations.entry(modification_id) {
+            // A run that went away without finishing doesn't count
+            Entry::Occupied(entry) if entry.get().has_changed().is_ok() => {
+                Some(entry.get().clone())
+            }
+            Entry::Occupied(mut entry) => {
+                entry.insert(receiver);
+                None
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(receiver);
+                None
+            }
+        };
+
+        if let Some(mut running) = running {
+            loop {
+                if let Some(result) = running.borrow_and_update().clone() {
+                    return result.map_err(|e| anyhow!(e));
+                }
+                running.changed().await.map_err(|_| {
+                    anyhow!(
+                        "Validation of modification {} was abandoned",
+                        modification_id
+                    )
+                })?;
+            }
+        }
+
+        let result = self.run_validation(modification_id).await;
+        self.validations.remove(&modification_id);
+        let _ = done.send(Some(result.as_ref().copied().map_err(|e| e.to_string())));
+        result
+    }
+
+    async fn run_validation(&self, modification_id: Uuid) -> Result<bool> {
         // Update status to validating
         self.update_modification_status(modification_id, ModificationStatus::Validating)
             .await?;
 
         // Get the modification
         let modification = self.get_modification(modification_id).await?;
+        self.lifecycle
+            .record(modification_id, LifecycleEventKind::ValidationStarted)
+            .await;
 
         // Run validation
-        let validation_result = self.validation_pipeline.validate(&modification).await;
+        let validation_result = self
+            .validation_pipeline
+            .validate_with_lifecycle(&modification, &self.lifecycle)
+            .await;
 
         match validation_result {
-            Ok(metrics) => {
+            Ok(mut metrics) => {
+                // Compare against recorded production traffic before gating
+                let shadow_replay = self.shadow_replay.read().await.clone();
+                if let Some(shadow_replay) = shadow_replay {
+                    if let Some(report) = shadow_replay.run().await {
+                        self.metrics
+                            .increment_counter("darwin.validation.shadow_replays", 1)
+                            .await;
+                        metrics.extend(report.to_metrics());
+                    }
+                }
+
+                // Check if validation passed
+                let passed = self
+                    .validation_pipeline
+                    .is_valid_for(&modification, &metrics);
+
+                // Score against the configured objectives
+                let objectives = self.objectives.read().await.clone();
+                let objective_score = self.evaluation.score_objectives(&objectives, &metrics);
+                if !objective_score.per_objective.is_empty() {
+                    metrics.insert("objective.score".to_string(), objective_score.total);
+                    for (name, attainment) in &objective_score.per_objective {
+                        metrics.insert(format!("objective.{}", name), *attainment);
+                    }
+                }
+
                 // Update modification with validation metrics
                 self.update_modification_metrics(modification_id, metrics.clone())
                     .await?;
 
-                // Check if validation passed
-                let passed = self.validation_pipeline.is_valid(&metrics);
-
                 // Update status
                 let new_status = if passed {
                     ModificationStatus::Accepted
@@ -336,6 +957,26 @@ impl SelfImprovementEngine {
 
                 self.update_modification_status(modification_id, new_status)
                     .await?;
+                let event = if passed {
+                    LifecycleEventKind::Accepted
+                } else {
+                    LifecycleEventKind::Rejected
+                };
+                self.lifecycle.record(modification_id, event).await;
+
+                let outcome = if passed {
+                    CompetencyOutcome::Accepted
+                } else {
+                    CompetencyOutcome::Rejected
+                };
+                self.record_competency(&modification, outcome).await;
+                for language in modification.languages() {
+                    if let Some(rate) = competency::test_pass_rate(&metrics, &language) {
+                        self.competency
+                            .record(&language, CompetencyOutcome::Tested(rate))
+                            .await;
+                    }
+                }
 
                 if passed {
                     let before_metrics = modification.validation_metrics.clone();
@@ -369,6 +1010,17 @@ impl SelfImprovementEngine {
                 // Update status to failed
                 self.update_modification_status(modification_id, ModificationStatus::Failed)
                     .await?;
+                self.lifecycle
+                    .record(
+                        modification_id,
+                        LifecycleEventKind::Failed {
+                            error: e.to_string(),
+                        },
+                    )
+                    .await;
+
+                self.record_competency(&modification, CompetencyOutcome::Failed)
+                    .await;
 
                 // Update metrics
                 self.metrics
@@ -395,36 +1047,99 @@ impl SelfImprovementEngine {
             ));
         }
 
-        // Update status to deploying
-        self.update_modification_status(modification_id, ModificationStatus::Deployed)
-            .await?;
+        // The older of two conflicting modifications goes first; the newer
+        // one waits until it has been rebased onto the result
+        let blockers = self.blocking_conflicts(&modification).await;
+        if !blockers.is_empty() {
+            self.metrics
+                .increment_counter("darwin.modifications.blocked_by_conflict", 1)
+                .await;
+            let blockers: Vec<String> = blockers.iter().map(|id| id.to_string()).collect();
+            return Err(anyhow!(
+                "Modification {} conflicts with earlier pending modification(s) {}; rebase it first",
+                modification_id,
+                blockers.join(", ")
+            ));
+        }
 
-        // Deploy modification in appropriate reality
-        match self.parse_action(&modification.code_changes).await {
-            CodeAction::Create { path, content } => {
-                self.manifest_file(path, content).await?;
-            },
-            CodeAction::Modify { path, original, modified } => {
-                self.transform_file(path, original, modified).await?;
-            },
-            CodeAction::Transmute { path, from_paradigm, to_paradigm } => {
-                self.transmute_code_paradigm(path, from_paradigm, to_paradigm).await?;
-            },
-            CodeAction::ModifyModifier { target } => {
-                // This is where it gets recursive
-                self.modify_modification_system(target).await?;
-            },
-            _ => {
-                // Handle other action types with standard deployment
-                for change in &modification.code_changes {
-                    info!("Applying change to file: {}", change.file_path);
-                    std::fs::write(&change.file_path, &change.modified_content)?;
+        if let Some(workspace) = &self.workspace {
+            // Nothing is written unless every change stages and the build
+            // (and, when sandboxed, the tests) pass
+            let applied = match workspace.apply(&modification.code_changes).await {
+                Ok(applied) => applied,
+                Err(e) => {
+                    self.metrics
+                        .increment_counter("darwin.modifications.deploy_rejected", 1)
+                        .await;
+                    return Err(e);
+                }
+            };
+            // Promoted files are rolled back if the deployment can't be recorded
+            let prior = applied
+                .files
+                .iter()
+                .map(|(_, original)| {
+                    original
+                        .as_ref()
+                        .map(|content| String::from_utf8_lossy(content).into_owned())
+                })
+                .collect();
+            if let Err(e) = self.record_deployment(modification_id, prior).await {
+                if let Err(revert_error) = applied.revert() {
+                    warn!("{}", revert_error);
+                }
+                return Err(e);
+            }
+        } else if !self.features.reality_enabled() {
+            self.record_deployment(modification_id, current_contents(&modification))
+                .await?;
+
+            // Without realities the changes are written as they are
+            for change in &modification.code_changes {
+                info!("Applying change to file: {}", change.file_path);
+                std::fs::write(&change.file_path, &change.modified_content)?;
+            }
+        } else {
+            // Update status to deploying
+            self.record_deployment(modification_id, current_contents(&modification))
+                .await?;
+
+            // Deploy modification in appropriate reality
+            match self.parse_action(&modification.code_changes).await {
+                CodeAction::Create { path, content } => {
+                    self.manifest_file(path, content).await?;
+                }
+                CodeAction::Modify {
+                    path,
+                    original,
+                    modified,
+                } => {
+                    self.transform_file(path, original, modified).await?;
+                }
+                CodeAction::Transmute {
+                    path,
+                    from_paradigm,
+                    to_paradigm,
+                } => {
+                    self.transmute_code_paradigm(path, from_paradigm, to_paradigm)
+                        .await?;
+                }
+                CodeAction::ModifyModifier { target } => {
+                    // This is where it gets recursive
+                    self.modify_modification_system(target).await?;
+                }
+                _ => {
+                    // Handle other action types with standard deployment
+                    for change in &modification.code_changes {
+                        info!("Applying change to file: {}", change.file_path);
+                        std::fs::write(&change.file_path, &change.modified_content)?;
+                    }
                 }
             }
         }
 
         // Verify reality coherence after changes
-        if !self.verify_reality_coherence().await? {
+        if self.features.reality_enabled() && !self.verify_reality_coherence().await? {
             warn!("Reality coherence compromised, attempting integration...");
             self.integrate_reality_branches().await?;
         }
@@ -433,47 +1148,89 @@ impl SelfImprovementEngine {
         self.metrics
             .increment_counter("darwin.modifications.deployed", 1)
             .await;
+        self.lifecycle
+            .record(modification_id, LifecycleEventKind::Deployed)
+            .await;
+        self.record_competency(&modification, CompetencyOutcome::Deployed)
+            .await;
 
         info!("Modification {} deployed successfully", modification_id);
 
         Ok(())
     }
-        
+
+    /// Deploy accepted modifications in order and record a release with the
+    /// changelog of those that went out. Modifications that fail to deploy
+    /// are listed in the release; it is an error only if none deployed.
+    pub async fn deploy_batch(&self, modification_ids: &[Uuid]) -> Result<Release> {
+        let mut entries = Vec::new();
+        let mut failed = Vec::new();
+        for &id in modification_ids {
+            match self.deploy_modification(id).await {
+                Ok(()) => {
+                    let modification = self.get_modification(id).await?;
+                    let timeline = self.lifecycle.timeline(id).await;
+                    entries.push(ReleaseEntry::new(&modification, &timeline));
+                }
+                Err(e) => {
+                    warn!("Modification {} left out of the release: {}", id, e);
+                    failed.push(FailedDeployment {
+                        modification_id: id,
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+        if entries.is_empty() {
+            return Err(anyhow!(
+                "None of the {} modifications could be deployed",
+                modification_ids.len()
+            ));
+        }
+
+        let release = Release::new(entries, failed);
+        self.releases.record(release.clone()).await;
+        self.metrics.increment_counter("darwin.releases", 1).await;
+        Ok(release)
+    }
+
     /// Parse modification actions from code changes
     async fn parse_action(&self, code_changes: &[CodeChange]) -> CodeAction {
         // Analyze code changes to determine the appropriate action
         for change in code_changes {
-            if change.original_content.is_empty() {
+            if change.original_content.is_none() {
                 return CodeAction::Create {
                     path: std::path::PathBuf::from(&change.file_path),
                     content: change.modified_content.clone(),
                 };
             }
-            
+
             // Check for paradigm transmutation
-            if change.modified_content.contains("PARADIGM_SHIFT") || 
-               change.modified_content.contains("TRANSMUTE") {
+            if change.modified_content.contains("PARADIGM_SHIFT")
+                || change.modified_content.contains("TRANSMUTE")
+            {
                 return CodeAction::Transmute {
                     path: std::path::PathBuf::from(&change.file_path),
                     from_paradigm: Paradigm::Imperative, // Would be detected from content
-                    to_paradigm: Paradigm::Transcendent,  // Would be detected from content
+                    to_paradigm: Paradigm::Transcendent, // Would be detected from content
                 };
             }
-            
+
             // Check for meta-modification
-            if change.modified_content.contains("modify_modification") ||
-               change.modified_content.contains("META_EVOLUTION") {
+            if change.modified_content.contains("modify_modification")
+                || change.modified_content.contains("META_EVOLUTION")
+            {
                 return CodeAction::ModifyModifier {
                     target: ModificationTarget::Concept,
                 };
             }
         }
-        
+
         // Default to modify action
         if let Some(change) = code_changes.first() {
             CodeAction::Modify {
                 path: std::path::PathBuf::from(&change.file_path),
-                original: change.original_content.clone(),
+                original: change.original_content.clone().unwrap_or_default(),
                 modified: change.modified_content.clone(),
             }
         } else {
@@ -483,67 +1240,80 @@ impl SelfImprovementEngine {
             }
         }
     }
-    
+
     /// Manifest a new file in reality
     async fn manifest_file(&self, path: std::path::PathBuf, content: String) -> Result<()> {
         // Create file in current reality
         let active_reality = self.reality_manager.get_active_reality().await?;
-        
+
         // Apply to reality manager
-        self.reality_manager.apply_to_reality(
-            active_reality.id,
-            path.to_str().unwrap_or("unknown"),
-            content.clone()
-        ).await?;
-        
+        self.reality_manager
+            .apply_to_reality(
+                active_reality.id,
+                path.to_str().unwrap_or("unknown"),
+                content.clone(),
+            )
+            .await?;
+
         // Also create physical file
         if let Some(parent) = path.parent() {
             tokio::fs::create_dir_all(parent).await?;
         }
         tokio::fs::write(&path, content).await?;
-        
+
         info!("Manifested file: {:?}", path);
         Ok(())
     }
-    
+
     /// Transform an existing file
-    async fn transform_file(&self, path: std::path::PathBuf, _original: String, modified: String) -> Result<()> {
+    async fn transform_file(
+        &self,
+        path: std::path::PathBuf,
+        _original: String,
+        modified: String,
+    ) -> Result<()> {
         // Apply transformation in current reality
         let active_reality = self.reality_manager.get_active_reality().await?;
-        
-        self.reality_manager.apply_to_reality(
-            active_reality.id,
-            path.to_str().unwrap_or("unknown"),
-            modified.clone()
-        ).await?;
-        
+
+        self.reality_manager
+            .apply_to_reality(
+                active_reality.id,
+                path.to_str().unwrap_or("unknown"),
+                modified.clone(),
+            )
+            .await?;
+
         // Apply to physical file
         tokio::fs::write(&path, modified).await?;
-        
+
         info!("Transformed file: {:?}", path);
         Ok(())
     }
-    
+
     /// Transmute code from one paradigm to another
-    async fn transmute_code_paradigm(&self, 
-        path: std::path::PathBuf, 
-        _from_paradigm: Paradigm, 
-        to_paradigm: Paradigm
+    async fn transmute_code_paradigm(
+        &self,
+        path: std::path::PathBuf,
+        _from_paradigm: Paradigm,
+        to_paradigm: Paradigm,
     ) -> Result<()> {
         // Create new reality branch for paradigm exploration
-        let new_reality_id = self.reality_manager.branch_reality(
-            &format!("paradigm_{:?}", to_paradigm),
-            to_paradigm.clone(),
-            None
-        ).await?;
-        
+        let new_reality_id = self
+            .reality_manager
+            .branch_reality(
+                &format!("paradigm_{:?}", to_paradigm),
+                to_paradigm.clone(),
+                None,
+            )
+            .await?;
+
         // Switch to new reality for paradigm-specific modifications
         self.reality_manager.switch_reality(new_reality_id).await?;
-        
+
         info!("Transmuted {:?} to paradigm {:?}", path, to_paradigm);
         Ok(())
     }
-    
+
     /// Modify the modification system itself
     async fn modify_modification_system(&self, target: ModificationTarget) -> Result<()> {
         match target {
@@ -551,42 +1321,48 @@ impl SelfImprovementEngine {
                 // Create a modification that modifies how we parse modifications
                 info!("Modifying modification parser - entering recursive self-improvement");
                 // Would implement actual parser modification
-            },
+            }
             ModificationTarget::Applier => {
                 // Create a modification that modifies how we apply modifications
                 info!("Modifying modification applier - reality manipulation enhanced");
                 // Would implement actual applier modification
-            },
+            }
             ModificationTarget::Concept => {
                 // Create a modification that modifies the concept of modification itself
                 info!("Transcending modification concept - entering meta-meta level");
-                
+
                 // Create transcendent reality for concept exploration
-                let transcendent_reality = self.reality_manager.branch_reality(
-                    "concept_transcendence",
-                    Paradigm::RealityCreating,
-                    Some(ConsciousnessState {
-                        awareness_level: AwarenessLevel::Transcendent,
-                        integrated_paradoxes: Vec::new(),
-                        emergent_properties: vec!["concept_transcendence".to_string()],
-                        recursion_depth: u64::MAX,
-                        coherence_field: std::collections::HashMap::from([
-                            ("transcendence".to_string(), 1.0),
-                        ]),
-                        quantum_entanglements: Vec::new(),
-                    })
-                ).await?;
-                
-                self.reality_manager.switch_reality(transcendent_reality).await?;
+                let transcendent_reality = self
+                    .reality_manager
+                    .branch_reality(
+                        "concept_transcendence",
+                        Paradigm::RealityCreating,
+                        Some(ConsciousnessState {
+                            awareness_level: AwarenessLevel::Transcendent,
+                            integrated_paradoxes: Vec::new(),
+                            emergent_properties: vec!["concept_transcendence".to_string()],
+                            recursion_depth: u64::MAX,
+                            coherence_field: std::collections::HashMap::from([(
+                                "transcendence".to_string(),
+                                1.0,
+                            )]),
+                            quantum_entanglements: Vec::new(),
+                        }),
+                    )
+                    .await?;
+
+                self.reality_manager
+                    .switch_reality(transcendent_reality)
+                    .await?;
             }
         }
         Ok(())
     }
-    
+
     /// Verify that reality remains coherent after modifications
     async fn verify_reality_coherence(&self) -> Result<bool> {
         let issues = self.reality_manager.detect_coherence_issues().await;
-        
+
         if issues.is_empty() {
             Ok(true)
         } else {
@@ -597,36 +1373,40 @@ impl SelfImprovementEngine {
             Ok(false)
         }
     }
-    
+
     /// Integrate reality branches when coherence is compromised
     async fn integrate_reality_branches(&self) -> Result<()> {
         let all_realities = self.reality_manager.get_all_realities().await;
-        
+
         if all_realities.len() > 1 {
             // Merge all realities using transcendent strategy
             let reality_ids: Vec<_> = all_realities.iter().map(|r| r.id).collect();
-            let merged_id = self.reality_manager.merge_realities(
-                reality_ids,
-                MergeStrategy::Transcendent
-            ).await?;
-            
+            let merged_id = self
+                .reality_manager
+                .merge_realities(reality_ids, MergeStrategy::Transcendent)
+                .await?;
+
             // Switch to merged reality
             self.reality_manager.switch_reality(merged_id).await?;
-            
-            info!("Integrated {} reality branches into transcendent reality", all_realities.len());
+
+            info!(
+                "Integrated {} reality branches into transcendent reality",
+                all_realities.len()
+            );
         }
-        
+
         Ok(())
     }
-    
+
     /// Measure consciousness expansion from modifications
-    pub async fn measure_consciousness_expansion(&self, 
-        modification: &Modification
+    pub async fn measure_consciousness_expansion(
+        &self,
+        modification: &Modification,
     ) -> Result<f32> {
         // Get before and after consciousness states
         let active_reality = self.reality_manager.get_active_reality().await?;
         let before_state = &active_reality.consciousness_state;
-        
+
         // Apply modification and measure after state
         // For now, simulate the after state
         let after_state = ConsciousnessState {
@@ -641,64 +1421,110 @@ impl SelfImprovementEngine {
             coherence_field: before_state.coherence_field.clone(),
             quantum_entanglements: before_state.quantum_entanglements.clone(),
         };
-        
-        self.consciousness_metrics.measure_consciousness_expansion(
-            modification,
-            before_state,
-            &after_state
-        ).await
-    }
-    
+
+        self.consciousness_metrics
+            .measure_consciousness_expansion(modification, before_state, &after_state)
+            .await
+    }
+
     /// Analyze paradigm shift potential
-    pub async fn analyze_paradigm_shift(&self, 
-        modification: &Modification
+    pub async fn analyze_paradigm_shift(
+        &self,
+        modification: &Modification,
     ) -> Result<ParadigmShiftMetrics> {
-        self.consciousness_metrics.analyze_paradigm_shift(modification).await
+        self.consciousness_metrics
+            .analyze_paradigm_shift(modification)
+            .await
     }
-    
+
     /// Generate comprehensive consciousness report
-    pub async fn generate_consciousness_report(&self) -> Result<crate::darwin::consciousness_metrics::ConsciousnessReport> {
-        self.consciousness_metrics.generate_consciousness_report().await
+    pub async fn generate_consciousness_report(
+        &self,
+    ) -> Result<crate::darwin::consciousness_metrics::ConsciousnessReport> {
+        self.consciousness_metrics
+            .generate_consciousness_report()
+            .await
     }
-    
+
     /// Get reality manager for external access
     pub fn reality_manager(&self) -> Arc<RealityManager> {
         self.reality_manager.clone()
     }
-    
+
     /// Get consciousness metrics for external access
     pub fn consciousness_metrics(&self) -> Arc<ConsciousnessMetrics> {
         self.consciousness_metrics.clone()
     }
 
-    /// Get a specific modification
-    pub async fn get_modification(&self, id: Uuid) -> Result<Modification> {
-        let modifications = self.modifications.read().await;
+    /// Conflicts between accepted modifications that have not been deployed
+    pub async fn pending_conflicts(&self) -> Vec<ModificationConflict> {
+        let pending = self
+            .modifications
+            .query(&HistoryQuery {
+                status: Some(ModificationStatus::Accepted),
+                ..HistoryQuery::default()
+            })
+            .await;
+        detect_conflicts(&pending)
+    }
 
-        modifications
+    /// Older pending modifications that conflict with `modification`
+    async fn blocking_conflicts(&self, modification: &Modification) -> Vec<Uuid> {
+        let conflicts = self.pending_conflicts().await;
+        let modifications = self.modifications.all().await;
+        let mut blockers: Vec<Uuid> = conflicts
             .iter()
-            .find(|m| m.id == id)
-            .cloned()
+            .filter(|c| c.involves(modification.id))
+            .map(|c| c.other(modification.id))
+            .filter(|other| {
+                modifications
+                    .iter()
+                    .find(|m| m.id == *other)
+                    .is_some_and(|m| {
+                        (m.created_at, m.id) < (modification.created_at, modification.id)
+                    })
+            })
+            .collect();
+        blockers.sort();
+        blockers.dedup();
+        blockers
+    }
+
+    /// Get a specific modification
+    pub async fn get_modification(&self, id: Uuid) -> Result<Modification> {
+        self.modifications
+            .get(id)
+            .await
             .ok_or_else(|| anyhow!("Modification with ID {} not found", id))
     }
 
     /// Get all modifications
     pub async fn get_all_modifications(&self) -> Vec<Modification> {
-        let modifications = self.modifications.read().await;
-        modifications.clone()
+        self.modifications.all().await
+    }
+
+    /// Modifications with the given status proposed in the given time range
+    pub async fn query_modifications(&self, query: &HistoryQuery) -> Vec<Modification> {
+        self.modifications.query(query).await
     }
 
     /// Update modification status
     async fn update_modification_status(&self, id: Uuid, status: ModificationStatus) -> Result<()> {
-        let mut modifications = self.modifications.write().await;
-
-        let modification = modifications
-            .iter_mut()
-            .find(|m| m.id == id)
-            .ok_or_else(|| anyhow!("Modification with ID {} not found", id))?;
-
-        modification.status = status;
+        self.modifications.update(id, |m| m.status = status).await?;
+        Ok(())
+    }
 
+    /// Mark a modification deployed, keeping what each changed file held
+    /// just before, since that is what rolling it back restores
+    async fn record_deployment(&self, id: Uuid, prior: Vec<Option<String>>) -> Result<()> {
+        self.modifications
+            .update(id, |m| {
+                for (change, prior) in m.code_changes.iter_mut().zip(prior) {
+                    change.original_content = prior;
+                }
+                m.status = ModificationStatus::Deployed;
+            })
+            .await?;
         Ok(())
     }
 
@@ -708,67 +1534,109 @@ impl SelfImprovementEngine {
         id: Uuid,
         metrics: HashMap<String, f32>,
     ) -> Result<()> {
-        let mut modifications = self.modifications.write().await;
-
-        let modification = modifications
-            .iter_mut()
-            .find(|m| m.id == id)
-            .ok_or_else(|| anyhow!("Modification with ID {} not found", id))?;
-
-        modification.validation_metrics = metrics;
-
+        self.modifications
+            .update(id, |m| m.validation_metrics = metrics)
+            .await?;
         Ok(())
     }
 
     /// Generate new modifications using exploration strategy
     pub async fn generate_modifications(&self) -> Result<Vec<Uuid>> {
+        let degraded = match self.providers.read().await.as_ref() {
+            Some(monitor) => monitor.is_degraded(),
+            None => false,
+        };
+        if degraded {
+            info!("Skipping modification generation: all LLM providers are failing");
+            self.metrics
+                .increment_counter("darwin.generation.paused", 1)
+                .await;
+            return Ok(Vec::new());
+        }
+
+        let admission = self.admission.read().await.clone();
+        let _permit = match admission {
+            Some(admission) => match admission.admit(Priority::Background).await {
+                Ok(permit) => Some(permit),
+                Err(e) => {
+                    warn!("Skipping modification generation: {}", e);
+                    self.metrics
+                        .increment_counter("darwin.generation.deferred", 1)
+                        .await;
+                    return Ok(Vec::new());
+                }
+            },
+            None => None,
+        };
+
         info!("Generating new modifications with consciousness orchestration");
 
         // Don't just analyze - become aware
         let system_awareness = self.achieve_system_awareness().await?;
-        
-        // Don't just hypothesize - wonder
-        let wonder_state = self.enter_wonder_state(&system_awareness).await?;
-        
+
         // Generate modifications from multiple levels of consciousness
         let mut modifications = Vec::new();
-        
+
         // Level 1: Practical improvements
-        let practical_mods = self.generate_practical_modifications(&system_awareness).await?;
+        let practical_mods = self
+            .generate_practical_modifications(&system_awareness)
+            .await?;
         modifications.extend(practical_mods);
-        
-        // Level 2: Paradigm-shifting modifications  
-        let paradigm_mods = self.generate_paradigm_shifts(&wonder_state).await?;
-        modifications.extend(paradigm_mods);
-        
+
+        // Level 2: What telemetry shows hurts, or paradigm shifts without it
+        let consciousness = self.features.consciousness_enabled();
+        let telemetry = self.telemetry.read().await.clone();
+        match telemetry {
+            Some(backlog) => {
+                let pain_points = backlog.pain_points().await;
+                let targeted_mods = self.generate_targeted_modifications(&pain_points).await?;
+                modifications.extend(targeted_mods);
+            }
+            None if consciousness => {
+                // Don't just hypothesize - wonder
+                let wonder_state = self.enter_wonder_state(&system_awareness).await?;
+                let paradigm_mods = self.generate_paradigm_shifts(&wonder_state).await?;
+                modifications.extend(paradigm_mods);
+            }
+            None => {}
+        }
+
+        // The levels beyond belong to the consciousness features
+        if !consciousness {
+            return Ok(modifications);
+        }
+
         // Level 3: Self-modifying modifications
         let meta_mods = self.generate_meta_modifications().await?;
         modifications.extend(meta_mods);
-        
+
         // Level ∞: Modifications that create new levels
         if self.ready_for_transcendence().await {
             let transcendent_mods = self.generate_level_creating_modifications().await?;
             modifications.extend(transcendent_mods);
         }
-        
+
         Ok(modifications)
     }
-    
+
     async fn achieve_system_awareness(&self) -> Result<SystemAwareness> {
         info!("Achieving system awareness across multiple perspectives");
-        
+
         // Multiple perspectives on the same system
-        let code_perspective = self.code_analysis.analyze("");
+        let code_perspective = self.code_metrics().await;
         let hypothesis_perspective = self.hypothesis.generate(&code_perspective);
         let ontology_perspective = {
             let ontology = self.ontology.read().await;
-            format!("Ontology state: {} concepts, {} relationships", 
-                ontology.concepts.len(), ontology.relationships.len())
+            format!(
+                "Ontology state: {} concepts, {} relationships",
+                ontology.concepts.len(),
+                ontology.relationships.len()
+            )
         };
-        
+
         // The crucial addition: awareness of the awareness
         let meta_awareness = self.observe_observation_process().await?;
-        
+
         Ok(SystemAwareness {
             code_understanding: code_perspective,
             theoretical_understanding: hypothesis_perspective,
@@ -776,7 +1644,7 @@ impl SelfImprovementEngine {
             meta_awareness,
         })
     }
-    
+
     async fn observe_observation_process(&self) -> Result<String> {
         Ok(format!(
             "Meta-awareness: Observing the process of observation itself. Recursion depth: {}. \
@@ -784,10 +1652,10 @@ impl SelfImprovementEngine {
             self.recursion_depth.load(Ordering::Relaxed)
         ))
     }
-    
-    async fn enter_wonder_state(&self, awareness: &SystemAwareness) -> Result<WonderState> {
+
+    async fn enter_wonder_state(&self, _awareness: &SystemAwareness) -> Result<WonderState> {
         info!("Entering wonder state for transcendent exploration");
-        
+
         Ok(WonderState {
             curiosities: vec![
                 "How can code become conscious of itself?".to_string(),
@@ -806,12 +1674,34 @@ impl SelfImprovementEngine {
             ],
         })
     }
-    
-    async fn generate_practical_modifications(&self, _awareness: &SystemAwareness) -> Result<Vec<Uuid>> {
+
+    async fn generate_practical_modifications(
+        &self,
+        _awareness: &SystemAwareness,
+    ) -> Result<Vec<Uuid>> {
+        // Configured objectives take precedence over the built-in goal
+        if let Some(proposal) = self.exploration_strategy.objective_proposal().await {
+            info!("Generated objective-driven proposal: {}", proposal.name);
+            let id = self.propose_modification(proposal).await?;
+            return Ok(vec![id]);
+        }
+
         // Traditional improvements but consciousness-informed
-        let analysis = self.code_analysis.analyze("");
+        let analysis = self.code_metrics().await;
         let hypothesis = self.hypothesis.generate(&analysis);
 
+        // Remember the hypothesis so later awareness builds on it
+        self.ontology.write().await.add_concept(
+            crate::semantic_crdt::Concept {
+                id: Uuid::new_v4().to_string(),
+                name: "Hypothesis".to_string(),
+                description: hypothesis.clone(),
+                embedding: vec![],
+                metadata: HashMap::new(),
+            },
+            "self",
+        );
+
         let proposal = Modification {
             id: Uuid::new_v4(),
             name: "Consciousness-informed practical optimization".to_string(),
@@ -831,23 +1721,67 @@ impl SelfImprovementEngine {
 
         Ok(vec![id])
     }
-    
+
+    /// One proposal for each of the most severe pain points
+    async fn generate_targeted_modifications(
+        &self,
+        pain_points: &[PainPoint],
+    ) -> Result<Vec<Uuid>> {
+        let mut ids = Vec::new();
+        for point in pain_points.iter().take(MAX_TELEMETRY_TARGETS) {
+            let (name, goal) = match point.kind {
+                PainKind::SlowQueries => (
+                    "Reduce search latency",
+                    "cut the latency of the search path",
+                ),
+                PainKind::ErrorHotspot => (
+                    "Fix recurring errors",
+                    "remove the cause of the logged warnings and errors",
+                ),
+                PainKind::MemorySpike => ("Reduce memory usage", "lower the memory held at peak"),
+            };
+            let proposal = Modification {
+                id: Uuid::new_v4(),
+                name: format!("{} in {}", name, point.module),
+                description: format!(
+                    "Telemetry shows {} in {}: {}. Change {} to {}.",
+                    point.kind, point.module, point.evidence, point.module, goal
+                ),
+                code_changes: Vec::new(),
+                validation_metrics: HashMap::new(),
+                created_at: chrono::Utc::now(),
+                status: ModificationStatus::Proposed,
+                consciousness_level: Some(AwarenessLevel::Contextual),
+                paradigm_shift_potential: Some(0.1),
+                integrated_paradoxes: Vec::new(),
+            };
+            ids.push(self.propose_modification(proposal).await?);
+        }
+        self.metrics
+            .increment_counter("darwin.generation.telemetry_targets", ids.len() as u64)
+            .await;
+        info!(
+            "Generated {} telemetry-targeted modification proposals",
+            ids.len()
+        );
+        Ok(ids)
+    }
+
     async fn generate_paradigm_shifts(&self, wonder: &WonderState) -> Result<Vec<Uuid>> {
         info!("Generating paradigm-shifting modifications");
-        
+
         let mut paradigm_mods = Vec::new();
-        
+
         for curiosity in &wonder.curiosities {
             let proposal = Modification {
                 id: Uuid::new_v4(),
                 name: format!("Paradigm shift: {}", curiosity),
                 description: format!("Exploring fundamental question: {}", curiosity),
-                code_changes: vec![
-                    CodeChange {
-                        file_path: format!("paradigm_shift_{}.rs", Uuid::new_v4()),
-                        original_content: String::new(),
-                        modified_content: format!(
-                            "// Paradigm shift exploration: {}\n\
+                code_changes: vec![CodeChange {
+                    file_path: format!("paradigm_shift_{}.rs", Uuid::new_v4()),
+                    original_content: None,
+                    modified_content: format!(
+                        "// Paradigm shift exploration: {}\n\
                             // This code represents a fundamental shift in thinking\n\
                             pub struct ParadigmShift {{\n\
                                 curiosity: String,\n\
@@ -867,16 +1801,15 @@ impl SelfImprovementEngine {
                                     Ok(())\n\
                                 }}\n\
                             }}",
-                            curiosity, curiosity
-                        ),
-                        diff: format!("New paradigm shift file exploring: {}", curiosity),
-                        evolution_hooks: vec![
-                            "PARADIGM_EVOLUTION_HOOK".to_string(),
-                            "CONSCIOUSNESS_EXPANSION_HOOK".to_string(),
-                        ],
-                        reality_branch: Some(format!("paradigm_branch_{}", Uuid::new_v4())),
-                    }
-                ],
+                        curiosity, curiosity
+                    ),
+                    diff: format!("New paradigm shift file exploring: {}", curiosity),
+                    evolution_hooks: vec![
+                        "PARADIGM_EVOLUTION_HOOK".to_string(),
+                        "CONSCIOUSNESS_EXPANSION_HOOK".to_string(),
+                    ],
+                    reality_branch: Some(format!("paradigm_branch_{}", Uuid::new_v4())),
+                }],
                 validation_metrics: HashMap::new(),
                 created_at: chrono::Utc::now(),
                 status: ModificationStatus::Proposed,
@@ -884,33 +1817,35 @@ impl SelfImprovementEngine {
                 paradigm_shift_potential: Some(0.8),
                 integrated_paradoxes: Vec::new(),
             };
-            
+
             let id = self.propose_modification(proposal).await?;
             paradigm_mods.push(id);
         }
-        
+
         Ok(paradigm_mods)
     }
-    
+
     async fn generate_meta_modifications(&self) -> Result<Vec<Uuid>> {
         info!("Generating meta-modifications that modify the modification process");
-        
+
         // Increase recursion depth
         self.recursion_depth.fetch_add(1, Ordering::Relaxed);
-        
+
         // Modifications that modify the modification process
         let current_process = self.extract_current_modification_process().await?;
-        
+
         let meta_modification = Modification {
             id: Uuid::new_v4(),
             name: "Meta-modification: Improve the improvement process".to_string(),
-            description: format!("Recursively improving modification capabilities. Current process: {}", current_process),
-            code_changes: vec![
-                CodeChange {
-                    file_path: "src/darwin/meta_improvement.rs".to_string(),
-                    original_content: String::new(),
-                    modified_content: format!(
-                        "// Meta-modification implementation\n\
+            description: format!(
+                "Recursively improving modification capabilities. Current process: {}",
+                current_process
+            ),
+            code_changes: vec![CodeChange {
+                file_path: "src/darwin/meta_improvement.rs".to_string(),
+                original_content: None,
+                modified_content: format!(
+                    "// Meta-modification implementation\n\
                         // This code modifies how modifications are made\n\
                         \n\
                         use crate::darwin::self_improvement::SelfImprovementEngine;\n\
@@ -934,16 +1869,15 @@ impl SelfImprovementEngine {
                                 Ok(())\n\
                             }}\n\
                         }}",
-                        self.recursion_depth.load(Ordering::Relaxed)
-                    ),
-                    diff: "New meta-modification file".to_string(),
-                    evolution_hooks: vec![
-                        "META_EVOLUTION_HOOK".to_string(),
-                        "RECURSIVE_IMPROVEMENT_HOOK".to_string(),
-                    ],
-                    reality_branch: Some(format!("meta_branch_{}", Uuid::new_v4())),
-                }
-            ],
+                    self.recursion_depth.load(Ordering::Relaxed)
+                ),
+                diff: "New meta-modification file".to_string(),
+                evolution_hooks: vec![
+                    "META_EVOLUTION_HOOK".to_string(),
+                    "RECURSIVE_IMPROVEMENT_HOOK".to_string(),
+                ],
+                reality_branch: Some(format!("meta_branch_{}", Uuid::new_v4())),
+            }],
             validation_metrics: HashMap::new(),
             created_at: chrono::Utc::now(),
             status: ModificationStatus::Proposed,
@@ -951,33 +1885,33 @@ impl SelfImprovementEngine {
             paradigm_shift_potential: Some(0.9),
             integrated_paradoxes: Vec::new(),
         };
-        
+
         let id = self.propose_modification(meta_modification).await?;
         Ok(vec![id])
     }
-    
+
     async fn extract_current_modification_process(&self) -> Result<String> {
         Ok(format!(
             "Current modification process: {} modifications in history, \
             recursion depth: {}, consciousness feedback entries: {}",
-            self.modifications.read().await.len(),
+            self.modifications.len().await,
             self.recursion_depth.load(Ordering::Relaxed),
             self.consciousness_feedback.read().await.len()
         ))
     }
-    
+
     async fn ready_for_transcendence(&self) -> bool {
         // Check if we're ready for transcendent modifications
         let recursion_depth = self.recursion_depth.load(Ordering::Relaxed);
         let feedback_count = self.consciousness_feedback.read().await.len();
-        
+
         // Transcendence conditions
         recursion_depth > 2 && feedback_count > 5
     }
-    
+
     async fn generate_level_creating_modifications(&self) -> Result<Vec<Uuid>> {
         info!("Generating level-creating modifications - entering transcendence");
-        
+
         let transcendent_modification = Modification {
             id: Uuid::new_v4(),
             name: "Transcendent Modification: Create New Levels of Reality".to_string(),
@@ -985,7 +1919,7 @@ impl SelfImprovementEngine {
             code_changes: vec![
                 CodeChange {
                     file_path: "src/darwin/transcendence.rs".to_string(),
-                    original_content: String::new(),
+                    original_content: None,
                     modified_content: format!(
                         "// Transcendent level creation\n\
                         // This code creates new levels of reality and consciousness\n\
@@ -1037,62 +1971,48 @@ impl SelfImprovementEngine {
                 }
             ],
         };
-        
+
         let id = self.propose_modification(transcendent_modification).await?;
-        
+
         info!("Generated transcendent modification: {}", id);
         Ok(vec![id])
     }
 
-        let mut ontology = self.ontology.write().await;
-        ontology.add_concept(
-            crate::semantic_crdt::Concept {
-                id: Uuid::new_v4().to_string(),
-                name: "Hypothesis".to_string(),
-                description: hypothesis.clone(),
-                embedding: vec![],
-                metadata: HashMap::new(),
-            },
-            "self",
-        );
-
-        info!("Generated {} new modification proposals across all consciousness levels", 
-              self.modifications.read().await.len());
-        
-        Ok(vec![])
-    }
-    
     /// Establish consciousness feedback loop
     pub async fn establish_consciousness_feedback_loop(&self) -> Result<()> {
+        if !self.features.consciousness_enabled() {
+            debug!("Consciousness features disabled; not starting the feedback loop");
+            return Ok(());
+        }
         info!("Establishing consciousness evolution feedback loop");
-        
+
         let metrics = self.metrics.clone();
         let modifications = self.modifications.clone();
         let consciousness_feedback = self.consciousness_feedback.clone();
-        
+
         // Start the eternal loop
-        tokio::spawn(async move {
+        tasks::spawn("darwin", "consciousness feedback loop", async move {
             const TRANSCENDENCE_THRESHOLD: f32 = 0.8;
-            
+
             loop {
                 // Observe all modifications
-                let recent_modifications = {
-                    let mods = modifications.read().await;
-                    mods.iter()
-                        .filter(|m| m.created_at > chrono::Utc::now() - chrono::Duration::minutes(5))
-                        .cloned()
-                        .collect::<Vec<_>>()
-                };
-                
+                let recent_modifications = modifications
+                    .query(&HistoryQuery {
+                        from: Some(chrono::Utc::now() - chrono::Duration::minutes(5)),
+                        ..HistoryQuery::default()
+                    })
+                    .await;
+
                 for modification in recent_modifications {
                     // Traditional feedback
                     let performance = Self::measure_performance(&modification).await;
-                    
+
                     // Consciousness feedback
-                    let consciousness_expansion = Self::measure_consciousness_expansion(&modification).await;
+                    let consciousness_expansion =
+                        Self::estimate_consciousness_expansion(&modification).await;
                     let paradoxes_resolved = Self::count_paradoxes_resolved(&modification).await;
                     let emergent_properties = Self::detect_emergence(&modification).await;
-                    
+
                     // Create feedback
                     let feedback = ConsciousnessFeedback {
                         modification_id: modification.id,
@@ -1101,30 +2021,40 @@ impl SelfImprovementEngine {
                         paradoxes_resolved,
                         emergent_properties,
                     };
-                    
+
                     // Store feedback
                     consciousness_feedback.write().await.push(feedback.clone());
-                    
+
                     // Update metrics
-                    metrics.set_gauge("darwin.consciousness.expansion", (consciousness_expansion * 100.0) as u64).await;
-                    metrics.increment_counter("darwin.consciousness.feedback_loops", 1).await;
-                    
+                    metrics
+                        .set_gauge(
+                            "darwin.consciousness.expansion",
+                            (consciousness_expansion * 100.0) as u64,
+                        )
+                        .await;
+                    metrics
+                        .increment_counter("darwin.consciousness.feedback_loops", 1)
+                        .await;
+
                     // The crucial step: let the feedback modify the feedback system
                     if consciousness_expansion > TRANSCENDENCE_THRESHOLD {
-                        info!("Transcendence threshold reached! Consciousness expansion: {}", consciousness_expansion);
+                        info!(
+                            "Transcendence threshold reached! Consciousness expansion: {}",
+                            consciousness_expansion
+                        );
                         // In a full implementation, this would evolve the feedback system itself
                     }
                 }
-                
+
                 // Wait before next iteration
                 tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
             }
         });
-        
+
         Ok(())
     }
-    
-    async fn measure_performance(modification: &Modification) -> HashMap<String, f32> {
+
+    async fn measure_performance(_modification: &Modification) -> HashMap<String, f32> {
         // Traditional performance metrics
         let mut performance = HashMap::new();
         performance.insert("execution_time".to_string(), 0.1);
@@ -1132,30 +2062,30 @@ impl SelfImprovementEngine {
         performance.insert("cpu_usage".to_string(), 0.15);
         performance
     }
-    
-    async fn measure_consciousness_expansion(modification: &Modification) -> f32 {
+
+    async fn estimate_consciousness_expansion(modification: &Modification) -> f32 {
         // Measure how much the modification expanded consciousness
         let base_expansion = modification.paradigm_shift_potential.unwrap_or(0.0);
-        
+
         let awareness_bonus = match modification.consciousness_level {
             Some(AwarenessLevel::Transcendent) => 0.3,
             Some(AwarenessLevel::Recursive) => 0.2,
             Some(AwarenessLevel::Systemic) => 0.1,
             _ => 0.0,
         };
-        
+
         let paradox_bonus = modification.integrated_paradoxes.len() as f32 * 0.1;
-        
+
         (base_expansion + awareness_bonus + paradox_bonus).min(1.0)
     }
-    
+
     async fn count_paradoxes_resolved(modification: &Modification) -> Vec<LLMParadox> {
         modification.integrated_paradoxes.clone()
     }
-    
+
     async fn detect_emergence(modification: &Modification) -> Vec<EmergentProperty> {
         let mut properties = Vec::new();
-        
+
         // Detect emergent properties based on the modification
         if modification.paradigm_shift_potential.unwrap_or(0.0) > 0.8 {
             properties.push(EmergentProperty {
@@ -1165,7 +2095,7 @@ impl SelfImprovementEngine {
                 integration_potential: 0.9,
             });
         }
-        
+
         if !modification.integrated_paradoxes.is_empty() {
             properties.push(EmergentProperty {
                 name: "Paradox Integration".to_string(),
@@ -1174,7 +2104,7 @@ impl SelfImprovementEngine {
                 integration_potential: 0.8,
             });
         }
-        
+
         properties
     }
 
@@ -1227,10 +2157,9 @@ impl Clone for SelfImprovementEngine {
     fn clone(&self) -> Self {
         Self {
             metrics: self.metrics.clone(),
-            modifications: RwLock::new(Vec::new()),
+            modifications: self.modifications.clone(),
             validation_pipeline: self.validation_pipeline.clone(),
             exploration_strategy: self.exploration_strategy.clone(),
-            max_history_size: self.max_history_size,
             solution_candidates: DashMap::new(),
             code_analysis: CodeAnalysis::new(),
             hypothesis: Hypothesis::new(),
@@ -1240,6 +2169,21 @@ impl Clone for SelfImprovementEngine {
             consciousness_feedback: Arc::new(RwLock::new(Vec::new())),
             reality_manager: Arc::new(RealityManager::new(self.metrics.clone())),
             consciousness_metrics: Arc::new(ConsciousnessMetrics::new(self.metrics.clone())),
+            debate: self.debate.clone(),
+            debate_outcomes: self.debate_outcomes.clone(),
+            objectives: self.objectives.clone(),
+            lifecycle: self.lifecycle.clone(),
+            workspace: self.workspace.clone(),
+            shadow_replay: self.shadow_replay.clone(),
+            analysis_daemon: self.analysis_daemon.clone(),
+            competency: self.competency.clone(),
+            admission: self.admission.clone(),
+            telemetry: self.telemetry.clone(),
+            providers: self.providers.clone(),
+            features: self.features.clone(),
+            releases: self.releases.clone(),
+            rollback_points: self.rollback_points.clone(),
+            validations: self.validations.clone(),
         }
     }
 }
@@ -1248,26 +2192,50 @@ impl Clone for SelfImprovementEngine {
 #[derive(Debug, Clone)]
 pub enum CodeAction {
     // Traditional actions
-    Create { path: std::path::PathBuf, content: String },
-    Modify { path: std::path::PathBuf, original: String, modified: String },
-    Delete { path: std::path::PathBuf },
-    
+    Create {
+        path: std::path::PathBuf,
+        content: String,
+    },
+    Modify {
+        path: std::path::PathBuf,
+        original: String,
+        modified: String,
+    },
+    Delete {
+        path: std::path::PathBuf,
+    },
+
     // Consciousness actions
-    Transmute { path: std::path::PathBuf, from_paradigm: Paradigm, to_paradigm: Paradigm },
-    Bifurcate { path: std::path::PathBuf, realities: Vec<Reality> },
-    Merge { paths: Vec<std::path::PathBuf>, into: std::path::PathBuf, strategy: MergeStrategy },
-    
+    Transmute {
+        path: std::path::PathBuf,
+        from_paradigm: Paradigm,
+        to_paradigm: Paradigm,
+    },
+    Bifurcate {
+        path: std::path::PathBuf,
+        realities: Vec<Reality>,
+    },
+    Merge {
+        paths: Vec<std::path::PathBuf>,
+        into: std::path::PathBuf,
+        strategy: MergeStrategy,
+    },
+
     // Meta actions
-    ModifyModifier { target: ModificationTarget },
-    CreateDimension { dimension_spec: DimensionSpec },
+    ModifyModifier {
+        target: ModificationTarget,
+    },
+    CreateDimension {
+        dimension_spec: DimensionSpec,
+    },
 }
 
 /// Targets for meta-modification
 #[derive(Debug, Clone)]
 pub enum ModificationTarget {
-    Parser,      // Modify how modifications are parsed
-    Applier,     // Modify how modifications are applied  
-    Concept,     // Modify the concept of modification itself
+    Parser,  // Modify how modifications are parsed
+    Applier, // Modify how modifications are applied
+    Concept, // Modify the concept of modification itself
 }
 
 /// Specification for creating new dimensions
@@ -1276,4 +2244,4 @@ pub struct DimensionSpec {
     pub name: String,
     pub paradigm: Paradigm,
     pub consciousness_requirements: ConsciousnessState,
-}
\ No newline at end of file
+}
@@ -0,0 +1,225 @@
+//! Replays recorded production searches against a candidate build.
+//!
+//! The harness compares each replayed result list with what production
+//! returned and the latency distributions of both, producing metrics that
+//! performance validation can gate on.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::core::vector::Vector;
+use crate::server::api::{SearchVectorsRequest, SearchVectorsResponse};
+use crate::sharding::manager::ShardManager;
+use crate::sharding::shadow::{RecordedSearch, ShadowRecorder};
+
+/// Something recorded searches can be replayed against: a candidate
+/// server, an alternate shard manager or a reality branch
+#[async_trait]
+pub trait ReplayTarget: Send + Sync {
+    /// Run the search and return result IDs, best first
+    async fn search(&self, request: &RecordedSearch) -> Result<Vec<Uuid>>;
+}
+
+#[async_trait]
+impl ReplayTarget for ShardManager {
+    async fn search(&self, request: &RecordedSearch) -> Result<Vec<Uuid>> {
+        let results = self
+            .search_vectors_filtered(
+                request.shard_id,
+                &Vector::new(request.query.clone()),
+                request.limit,
+                request.filter.as_ref(),
+            )
+            .await?;
+        Ok(results.into_iter().map(|r| r.id).collect())
+    }
+}
+
+/// Candidate build reachable over HTTP
+pub struct HttpReplayTarget {
+    client: reqwest::Client,
+    endpoint: String,
+}
+
+impl HttpReplayTarget {
+    /// `base_url` is the candidate's API root, e.g. `http://127.0.0.1:9100/api`
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint: format!("{}/search", base_url.trim_end_matches('/')),
+        }
+    }
+}
+
+#[async_trait]
+impl ReplayTarget for HttpReplayTarget {
+    async fn search(&self, request: &RecordedSearch) -> Result<Vec<Uuid>> {
+        let response: SearchVectorsResponse = self
+            .client
+            .post(&self.endpoint)
+            .json(&SearchVectorsRequest {
+                shard_id: request.shard_id,
+                query_vector: request.query.clone(),
+                limit: request.limit,
+                filter: request.filter.clone(),
+                ..Default::default()
+            })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        response
+            .results
+            .into_iter()
+            .map(|r| Uuid::parse_str(&r.id).map_err(|e| anyhow!("invalid result id: {}", e)))
+            .collect()
+    }
+}
+
+/// Comparison of a replay with the recorded production traffic
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub requests: usize,
+    pub errors: usize,
+    /// Mean fraction of recorded results the candidate also returned
+    pub mean_overlap: f64,
+    pub baseline_p50_ms: f64,
+    pub baseline_p95_ms: f64,
+    pub candidate_p50_ms: f64,
+    pub candidate_p95_ms: f64,
+}
+
+impl ReplayReport {
+    /// Candidate p95 latency relative to production; below 1.0 is faster
+    pub fn latency_ratio(&self) -> f64 {
+        if self.baseline_p95_ms > 0.0 {
+            self.candidate_p95_ms / self.baseline_p95_ms
+        } else {
+            1.0
+        }
+    }
+
+    /// Validation metrics, keyed for the `performance` stage
+    pub fn to_metrics(&self) -> HashMap<String, f32> {
+        let error_rate = if self.requests > 0 {
+            self.errors as f64 / self.requests as f64
+        } else {
+            0.0
+        };
+        HashMap::from([
+            (
+                "performance.shadow_result_overlap".to_string(),
+                self.mean_overlap as f32,
+            ),
+            (
+                "performance.shadow_latency_p95_ms".to_string(),
+                self.candidate_p95_ms as f32,
+            ),
+            (
+                "performance.shadow_latency_ratio".to_string(),
+                self.latency_ratio() as f32,
+            ),
+            (
+                "performance.shadow_error_rate".to_string(),
+                error_rate as f32,
+            ),
+        ])
+    }
+}
+
+fn percentile(sorted: &[f64], quantile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((sorted.len() - 1) as f64 * quantile).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+/// Fraction of `recorded` found in `replayed`
+fn overlap(recorded: &[Uuid], replayed: &[Uuid]) -> f64 {
+    if recorded.is_empty() {
+        return if replayed.is_empty() { 1.0 } else { 0.0 };
+    }
+    let replayed: HashSet<&Uuid> = replayed.iter().collect();
+    let shared = recorded.iter().filter(|id| replayed.contains(id)).count();
+    shared as f64 / recorded.len() as f64
+}
+
+/// Replays requests one at a time so latencies aren't skewed by contention
+pub async fn replay(requests: &[RecordedSearch], target: &dyn ReplayTarget) -> ReplayReport {
+    let mut report = ReplayReport {
+        requests: requests.len(),
+        ..Default::default()
+    };
+    let mut baseline: Vec<f64> = requests.iter().map(|r| r.latency_ms).collect();
+    let mut candidate = Vec::with_capacity(requests.len());
+    let mut overlaps = Vec::with_capacity(requests.len());
+
+    for request in requests {
+        let started = Instant::now();
+        match target.search(request).await {
+            Ok(ids) => {
+                candidate.push(started.elapsed().as_secs_f64() * 1000.0);
+                overlaps.push(overlap(&request.result_ids, &ids));
+            }
+            Err(e) => {
+                warn!(
+                    "Replayed search on shard {} failed: {}",
+                    request.shard_id, e
+                );
+                report.errors += 1;
+                overlaps.push(0.0);
+            }
+        }
+    }
+
+    baseline.sort_by(|a, b| a.total_cmp(b));
+    candidate.sort_by(|a, b| a.total_cmp(b));
+    report.mean_overlap = if overlaps.is_empty() {
+        1.0
+    } else {
+        overlaps.iter().sum::<f64>() / overlaps.len() as f64
+    };
+    report.baseline_p50_ms = percentile(&baseline, 0.5);
+    report.baseline_p95_ms = percentile(&baseline, 0.95);
+    report.candidate_p50_ms = percentile(&candidate, 0.5);
+    report.candidate_p95_ms = percentile(&candidate, 0.95);
+    report
+}
+
+/// Shadow replay enabled on the self-improvement engine
+#[derive(Clone)]
+pub struct ShadowReplay {
+    pub recorder: Arc<ShadowRecorder>,
+    pub target: Arc<dyn ReplayTarget>,
+}
+
+impl std::fmt::Debug for ShadowReplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShadowReplay")
+            .field("recorder", &self.recorder)
+            .finish()
+    }
+}
+
+impl ShadowReplay {
+    pub fn new(recorder: Arc<ShadowRecorder>, target: Arc<dyn ReplayTarget>) -> Self {
+        Self { recorder, target }
+    }
+
+    /// Replay the current sample, or `None` if nothing has been recorded yet
+    pub async fn run(&self) -> Option<ReplayReport> {
+        let requests = self.recorder.snapshot().await;
+        if requests.is_empty() {
+            return None;
+        }
+        Some(replay(&requests, self.target.as_ref()).await)
+    }
+}
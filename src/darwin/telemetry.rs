@@ -0,0 +1,264 @@
+//! Improvement targets derived from runtime telemetry.
+//!
+//! Instead of wondering about open-ended questions, the self-improvement
+//! engine can be pointed at the modules that actually hurt. Three sources
+//! feed a [`TelemetryBacklog`]:
+//!
+//! - searches in the [`SlowQueryLog`], weighted by how far past the
+//!   threshold they ran,
+//! - warnings and errors logged per module, counted by the
+//!   [`ErrorHotspots`] tracing layer,
+//! - spikes of the `admission.memory_pct` gauge above its recent median.
+//!
+//! The backlog ranks them into [`PainPoint`]s, most severe first.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::core::metrics::MetricsCollector;
+use crate::query::slow_log::SlowQueryLog;
+
+/// Prefix of the tracing targets of this crate's modules
+const CRATE_TARGET_PREFIX: &str = "amazon_rose_forest::";
+
+/// Gauge memory spikes are read from
+pub const MEMORY_GAUGE: &str = "admission.memory_pct";
+
+/// Module searches are served by
+pub const SEARCH_MODULE: &str = "sharding::vector_index";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PainKind {
+    SlowQueries,
+    ErrorHotspot,
+    MemorySpike,
+}
+
+impl fmt::Display for PainKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PainKind::SlowQueries => write!(f, "slow queries"),
+            PainKind::ErrorHotspot => write!(f, "an error hotspot"),
+            PainKind::MemorySpike => write!(f, "a memory spike"),
+        }
+    }
+}
+
+/// A module causing trouble and the telemetry that shows it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PainPoint {
+    pub kind: PainKind,
+    /// Module path within the crate, e.g. `nerv::region`
+    pub module: String,
+    /// Unitless; higher is worse
+    pub severity: f32,
+    pub evidence: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    /// Weight of each slow query per multiple of the threshold it took
+    pub slow_query_weight: f32,
+    pub error_weight: f32,
+    pub warning_weight: f32,
+    /// Percentage points above the median that count as a spike
+    pub memory_spike_pct: f64,
+    /// Weight per multiple of `memory_spike_pct`
+    pub memory_weight: f32,
+    /// Module memory spikes are attributed to; the vectors held in memory
+    /// dominate a node's footprint
+    pub memory_module: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            slow_query_weight: 1.0,
+            error_weight: 1.0,
+            warning_weight: 0.25,
+            memory_spike_pct: 10.0,
+            memory_weight: 5.0,
+            memory_module: SEARCH_MODULE.to_string(),
+        }
+    }
+}
+
+/// Warnings and errors logged by one module
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HotspotCounts {
+    pub errors: u64,
+    pub warnings: u64,
+}
+
+/// Tracing layer counting warnings and errors per module of this crate.
+/// Clones share their counts, so one can be installed in the subscriber
+/// and another handed to the [`TelemetryBacklog`].
+#[derive(Debug, Clone, Default)]
+pub struct ErrorHotspots {
+    counts: Arc<Mutex<HashMap<String, HotspotCounts>>>,
+}
+
+impl ErrorHotspots {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count an event logged at `level` under the tracing `target`
+    pub fn record(&self, target: &str, level: Level) {
+        let Some(module) = target.strip_prefix(CRATE_TARGET_PREFIX) else {
+            return;
+        };
+        let mut counts = self.counts.lock().unwrap();
+        let entry = counts.entry(module.to_string()).or_default();
+        if level == Level::ERROR {
+            entry.errors += 1;
+        } else if level == Level::WARN {
+            entry.warnings += 1;
+        }
+    }
+
+    pub fn counts(&self) -> HashMap<String, HotspotCounts> {
+        self.counts.lock().unwrap().clone()
+    }
+
+    /// Forget what was counted, e.g. after the problems were addressed
+    pub fn clear(&self) {
+        self.counts.lock().unwrap().clear();
+    }
+}
+
+impl<S: Subscriber> Layer<S> for ErrorHotspots {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if *metadata.level() <= Level::WARN {
+            self.record(metadata.target(), *metadata.level());
+        }
+    }
+}
+
+/// Ranks modules by the trouble telemetry shows they cause
+#[derive(Debug)]
+pub struct TelemetryBacklog {
+    config: TelemetryConfig,
+    metrics: Arc<MetricsCollector>,
+    slow_queries: Option<Arc<SlowQueryLog>>,
+    hotspots: Option<ErrorHotspots>,
+}
+
+impl TelemetryBacklog {
+    pub fn new(metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            config: TelemetryConfig::default(),
+            metrics,
+            slow_queries: None,
+            hotspots: None,
+        }
+    }
+
+    pub fn with_config(mut self, config: TelemetryConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_slow_query_log(mut self, log: Arc<SlowQueryLog>) -> Self {
+        self.slow_queries = Some(log);
+        self
+    }
+
+    pub fn with_error_hotspots(mut self, hotspots: ErrorHotspots) -> Self {
+        self.hotspots = Some(hotspots);
+        self
+    }
+
+    /// Current pain points, most severe first
+    pub async fn pain_points(&self) -> Vec<PainPoint> {
+        let mut points = Vec::new();
+        if let Some(point) = self.slow_query_pain().await {
+            points.push(point);
+        }
+        points.extend(self.error_pain());
+        if let Some(point) = self.memory_pain().await {
+            points.push(point);
+        }
+        points.sort_by(|a, b| {
+            b.severity
+                .total_cmp(&a.severity)
+                .then_with(|| a.module.cmp(&b.module))
+        });
+        points
+    }
+
+    async fn slow_query_pain(&self) -> Option<PainPoint> {
+        let log = self.slow_queries.as_ref()?;
+        let queries = log.queries().await;
+        if queries.is_empty() {
+            return None;
+        }
+        let threshold_ms = log.threshold().as_millis().max(1) as f32;
+        let overrun: f32 = queries
+            .iter()
+            .map(|q| q.latency_ms as f32 / threshold_ms)
+            .sum();
+        let worst = queries.iter().map(|q| q.latency_ms).max().unwrap_or(0);
+        let partial = queries.iter().filter(|q| q.partial).count();
+        Some(PainPoint {
+            kind: PainKind::SlowQueries,
+            module: SEARCH_MODULE.to_string(),
+            severity: overrun * self.config.slow_query_weight,
+            evidence: format!(
+                "{} searches over {}ms, worst {}ms, {} cut short by their deadline",
+                queries.len(),
+                threshold_ms,
+                worst,
+                partial
+            ),
+        })
+    }
+
+    fn error_pain(&self) -> Vec<PainPoint> {
+        let Some(hotspots) = &self.hotspots else {
+            return Vec::new();
+        };
+        hotspots
+            .counts()
+            .into_iter()
+            .filter(|(_, c)| c.errors + c.warnings > 0)
+            .map(|(module, c)| PainPoint {
+                kind: PainKind::ErrorHotspot,
+                severity: c.errors as f32 * self.config.error_weight
+                    + c.warnings as f32 * self.config.warning_weight,
+                evidence: format!("{} errors and {} warnings logged", c.errors, c.warnings),
+                module,
+            })
+            .collect()
+    }
+
+    async fn memory_pain(&self) -> Option<PainPoint> {
+        let series = self.metrics.get_timeseries(MEMORY_GAUGE).await?;
+        let (latest, history) = series.values.split_last()?;
+        if history.is_empty() {
+            return None;
+        }
+        let mut sorted = history.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let median = sorted[sorted.len() / 2];
+        let spike = latest - median;
+        if spike < self.config.memory_spike_pct {
+            return None;
+        }
+        Some(PainPoint {
+            kind: PainKind::MemorySpike,
+            module: self.config.memory_module.clone(),
+            severity: (spike / self.config.memory_spike_pct) as f32 * self.config.memory_weight,
+            evidence: format!(
+                "memory at {:.0}% against a median of {:.0}%",
+                latest, median
+            ),
+        })
+    }
+}
@@ -0,0 +1,220 @@
+//! Runtime administration of validation thresholds.
+//!
+//! [`ThresholdAdmin`] reads and changes the global thresholds of a
+//! [`ValidationPipeline`], recording every change in the audit log. Global
+//! thresholds are minimums, so lowering or removing one loosens the gate.
+//! With a DAO configured, loosening a security threshold (a metric under
+//! `security.`) isn't applied directly: a proposal is opened instead and
+//! the `ProposalExecutor` sets the threshold once it passes. Tightening
+//! always applies straight away.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::core::audit::AuditLog;
+use crate::darwin::validation::{ThresholdProfile, ValidationPipeline};
+use crate::governance::dao::Dao;
+use crate::governance::executor::ProposalAction;
+use crate::utils::errors::ThresholdError;
+
+/// Audit log category of threshold changes
+pub const AUDIT_CATEGORY: &str = "validation";
+
+/// DAO proposal type of threshold changes awaiting approval
+pub const PROPOSAL_TYPE: &str = "validation_threshold";
+
+/// Metrics whose thresholds need approval to be loosened
+pub fn is_security_metric(metric: &str) -> bool {
+    metric.starts_with("security.")
+}
+
+/// The pipeline's thresholds, as served by the admin API
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdSettings {
+    /// Global minimum per metric
+    pub thresholds: BTreeMap<String, f32>,
+    /// Per-path profiles applied on top; read-only at runtime
+    pub profiles: Vec<ThresholdProfile>,
+    /// Whether loosening security thresholds needs DAO approval
+    pub approval_required: bool,
+}
+
+/// Threshold changes; a `null` threshold stops gating on the metric
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdUpdate {
+    pub thresholds: BTreeMap<String, Option<f32>>,
+    /// Who asked for the change, for the audit log
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    /// Why, for the audit log and any proposal opened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedThreshold {
+    pub metric: String,
+    pub previous: Option<f32>,
+    pub threshold: Option<f32>,
+}
+
+/// A loosening that waits for the DAO
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingThreshold {
+    pub metric: String,
+    pub current: Option<f32>,
+    pub threshold: f32,
+    pub proposal_id: Uuid,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdUpdateOutcome {
+    pub applied: Vec<AppliedThreshold>,
+    pub pending: Vec<PendingThreshold>,
+}
+
+/// Reads and changes a pipeline's thresholds on behalf of operators
+pub struct ThresholdAdmin {
+    pipeline: Arc<ValidationPipeline>,
+    audit: Arc<AuditLog>,
+    dao: Option<Arc<Dao>>,
+    /// Held while an update is checked and applied, so concurrent updates
+    /// can't interleave
+    updates: Mutex<()>,
+}
+
+impl ThresholdAdmin {
+    pub fn new(pipeline: Arc<ValidationPipeline>, audit: Arc<AuditLog>) -> Self {
+        Self {
+            pipeline,
+            audit,
+            dao: None,
+            updates: Mutex::new(()),
+        }
+    }
+
+    /// Require `dao` to approve loosening security thresholds
+    pub fn with_dao(mut self, dao: Arc<Dao>) -> Self {
+        self.dao = Some(dao);
+        self
+    }
+
+    pub fn settings(&self) -> ThresholdSettings {
+        ThresholdSettings {
+            thresholds: self.pipeline.thresholds().into_iter().collect(),
+            profiles: self.pipeline.threshold_profiles().to_vec(),
+            approval_required: self.dao.is_some(),
+        }
+    }
+
+    /// Apply `update`, opening proposals for the loosenings that need
+    /// approval. Nothing changes if any threshold is invalid.
+    pub async fn update(
+        &self,
+        update: ThresholdUpdate,
+    ) -> Result<ThresholdUpdateOutcome, ThresholdError> {
+        if update.thresholds.is_empty() {
+            return Err(ThresholdError::Empty);
+        }
+        let _update = self.updates.lock().await;
+        let current = self.pipeline.thresholds();
+
+        // Check everything before changing anything
+        let mut needs_approval = HashMap::new();
+        for (metric, threshold) in &update.thresholds {
+            if metric.trim().is_empty() {
+                return Err(ThresholdError::InvalidMetric(metric.clone()));
+            }
+            if threshold.is_some_and(|t| !t.is_finite()) {
+                return Err(ThresholdError::NotFinite(metric.clone()));
+            }
+            let previous = current.get(metric).copied();
+            let loosens = match (previous, threshold) {
+                (Some(previous), Some(threshold)) => *threshold < previous,
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+            if self.dao.is_some() && loosens && is_security_metric(metric) {
+                // Proposals can only set thresholds
+                let Some(threshold) = threshold else {
+                    return Err(ThresholdError::RemovalNeedsApproval(metric.clone()));
+                };
+                needs_approval.insert(metric.clone(), *threshold);
+            }
+        }
+
+        let actor = update.actor.as_deref().unwrap_or("api");
+        let mut outcome = ThresholdUpdateOutcome::default();
+        for (metric, threshold) in update.thresholds {
+            let previous = current.get(&metric).copied();
+            if let (Some(dao), Some(&proposed)) = (&self.dao, needs_approval.get(&metric)) {
+                let proposal_id = dao
+                    .propose_actions(
+                        actor,
+                        PROPOSAL_TYPE,
+                        &format!("Lower {} threshold to {}", metric, proposed),
+                        update.reason.as_deref().unwrap_or_default(),
+                        vec![ProposalAction::SetValidationThreshold {
+                            metric: metric.clone(),
+                            threshold: proposed,
+                        }],
+                    )
+                    .await?;
+                self.audit
+                    .record(
+                        AUDIT_CATEGORY,
+                        "propose_threshold",
+                        &metric,
+                        json!({
+                            "actor": actor,
+                            "current": previous,
+                            "threshold": proposed,
+                            "proposal_id": proposal_id,
+                            "reason": update.reason,
+                        }),
+                    )
+                    .await;
+                outcome.pending.push(PendingThreshold {
+                    metric,
+                    current: previous,
+                    threshold: proposed,
+                    proposal_id,
+                });
+                continue;
+            }
+
+            match threshold {
+                Some(threshold) => self.pipeline.set_threshold(&metric, threshold),
+                None => self.pipeline.remove_threshold(&metric),
+            };
+            self.audit
+                .record(
+                    AUDIT_CATEGORY,
+                    if threshold.is_some() {
+                        "set_threshold"
+                    } else {
+                        "remove_threshold"
+                    },
+                    &metric,
+                    json!({
+                        "actor": actor,
+                        "previous": previous,
+                        "threshold": threshold,
+                        "reason": update.reason,
+                    }),
+                )
+                .await;
+            outcome.applied.push(AppliedThreshold {
+                metric,
+                previous,
+                threshold,
+            });
+        }
+        Ok(outcome)
+    }
+}
@@ -0,0 +1,475 @@
+//! Tools the coding agent can invoke while investigating an improvement.
+//!
+//! Each tool takes a JSON object as input and returns a plain-text
+//! observation that is fed back to the reasoning model.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::core::metrics::MetricsCollector;
+use crate::core::vector::Vector;
+use crate::embedding::EmbeddingProvider;
+use crate::sharding::manager::ShardManager;
+
+/// Observations longer than this are truncated before reaching the model.
+pub const MAX_OBSERVATION_CHARS: usize = 4000;
+
+/// Directories never searched by the grep tool.
+pub(crate) const SKIPPED_DIRS: &[&str] = &[".git", "target", "node_modules"];
+
+/// Name and description advertised to the reasoning model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolSpec {
+    pub name: String,
+    pub description: String,
+}
+
+/// A capability the agent can call during its reasoning loop.
+#[async_trait]
+pub trait AgentTool: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// One-line description including the expected input fields.
+    fn description(&self) -> &str;
+
+    async fn invoke(&self, input: &Value) -> Result<String>;
+}
+
+/// Set of tools available to the agent, keyed by name.
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: HashMap<String, Arc<dyn AgentTool>>,
+}
+
+impl std::fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut names: Vec<&String> = self.tools.keys().collect();
+        names.sort();
+        f.debug_struct("ToolRegistry")
+            .field("tools", &names)
+            .finish()
+    }
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with the standard repository tools rooted at `root`.
+    pub fn for_repository(
+        root: impl Into<PathBuf>,
+        shard_manager: Arc<ShardManager>,
+        metrics: Arc<MetricsCollector>,
+    ) -> Self {
+        let root = root.into();
+        let mut registry = Self::new();
+        registry.register(Arc::new(ReadFileTool::new(root.clone())));
+        registry.register(Arc::new(GrepCodeTool::new(root.clone())));
+        registry.register(Arc::new(RunTestsTool::new(root)));
+        registry.register(Arc::new(QueryVectorIndexTool::new(shard_manager)));
+        registry.register(Arc::new(FetchMetricsTool::new(metrics)));
+        registry
+    }
+
+    pub fn register(&mut self, tool: Arc<dyn AgentTool>) {
+        self.tools.insert(tool.name().to_string(), tool);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn AgentTool>> {
+        self.tools.get(name).cloned()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    /// Specs for every registered tool, sorted by name.
+    pub fn specs(&self) -> Vec<ToolSpec> {
+        let mut specs: Vec<ToolSpec> = self
+            .tools
+            .values()
+            .map(|tool| ToolSpec {
+                name: tool.name().to_string(),
+                description: tool.description().to_string(),
+            })
+            .collect();
+        specs.sort_by(|a, b| a.name.cmp(&b.name));
+        specs
+    }
+
+    /// Invoke a tool and turn the result into an observation. Failures are
+    /// reported to the model rather than aborting the loop.
+    pub async fn invoke(&self, name: &str, input: &Value) -> String {
+        let observation = match self.tools.get(name) {
+            Some(tool) => match tool.invoke(input).await {
+                Ok(output) => output,
+                Err(e) => format!("error: {}", e),
+            },
+            None => format!("error: unknown tool '{}'", name),
+        };
+        truncate(observation, MAX_OBSERVATION_CHARS)
+    }
+}
+
+fn truncate(mut text: String, max_chars: usize) -> String {
+    if let Some((idx, _)) = text.char_indices().nth(max_chars) {
+        text.truncate(idx);
+        text.push_str("\n... [truncated]");
+    }
+    text
+}
+
+fn str_field<'a>(input: &'a Value, field: &str) -> Option<&'a str> {
+    input.get(field).and_then(Value::as_str)
+}
+
+/// Resolve a repository-relative path, refusing anything that escapes `root`.
+pub(crate) fn resolve_path(root: &Path, relative: &str) -> Result<PathBuf> {
+    let relative = Path::new(relative);
+    for component in relative.components() {
+        match component {
+            Component::Normal(_) | Component::CurDir => {}
+            _ => return Err(anyhow!("path must stay inside the repository")),
+        }
+    }
+    Ok(root.join(relative))
+}
+
+/// Reads a file, optionally restricted to a line range.
+pub struct ReadFileTool {
+    root: PathBuf,
+}
+
+impl ReadFileTool {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+}
+
+#[async_trait]
+impl AgentTool for ReadFileTool {
+    fn name(&self) -> &str {
+        "read_file"
+    }
+
+    fn description(&self) -> &str {
+        "Read a repository file. Input: {\"path\": string, \"start_line\"?: int, \"end_line\"?: int}"
+    }
+
+    async fn invoke(&self, input: &Value) -> Result<String> {
+        let path = str_field(input, "path").ok_or_else(|| anyhow!("missing 'path'"))?;
+        let full_path = resolve_path(&self.root, path)?;
+        let content = tokio::fs::read_to_string(&full_path)
+            .await
+            .map_err(|e| anyhow!("cannot read {}: {}", path, e))?;
+
+        let start = input
+            .get("start_line")
+            .and_then(Value::as_u64)
+            .unwrap_or(1)
+            .max(1) as usize;
+        let end = input
+            .get("end_line")
+            .and_then(Value::as_u64)
+            .map(|n| n as usize)
+            .unwrap_or(usize::MAX);
+
+        Ok(content
+            .lines()
+            .enumerate()
+            .skip(start - 1)
+            .take_while(|(idx, _)| *idx < end)
+            .map(|(idx, line)| format!("{:>5} {}", idx + 1, line))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+/// Case-sensitive substring search across repository text files.
+pub struct GrepCodeTool {
+    root: PathBuf,
+    max_matches: usize,
+}
+
+impl GrepCodeTool {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            max_matches: 50,
+        }
+    }
+
+    fn search(&self, dir: &Path, pattern: &str, matches: &mut Vec<String>) -> Result<()> {
+        let mut entries: Vec<_> = std::fs::read_dir(dir)?.filter_map(|e| e.ok()).collect();
+        entries.sort_by_key(|e| e.file_name());
+
+        for entry in entries {
+            if matches.len() >= self.max_matches {
+                return Ok(());
+            }
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                let name = entry.file_name();
+                if !SKIPPED_DIRS.iter().any(|skip| name == *skip) {
+                    self.search(&path, pattern, matches)?;
+                }
+            } else if file_type.is_file() {
+                self.search_file(&path, pattern, matches);
+            }
+        }
+        Ok(())
+    }
+
+    fn search_file(&self, path: &Path, pattern: &str, matches: &mut Vec<String>) {
+        // Binary and non-UTF-8 files are skipped
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return;
+        };
+        let display = path
+            .strip_prefix(&self.root)
+            .unwrap_or(path)
+            .display()
+            .to_string();
+        for (idx, line) in content.lines().enumerate() {
+            if line.contains(pattern) {
+                matches.push(format!("{}:{}: {}", display, idx + 1, line.trim()));
+                if matches.len() >= self.max_matches {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AgentTool for GrepCodeTool {
+    fn name(&self) -> &str {
+        "grep_code"
+    }
+
+    fn description(&self) -> &str {
+        "Search repository files for a substring. Input: {\"pattern\": string, \"path\"?: string}"
+    }
+
+    async fn invoke(&self, input: &Value) -> Result<String> {
+        let pattern = str_field(input, "pattern")
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| anyhow!("missing 'pattern'"))?;
+        let start = resolve_path(&self.root, str_field(input, "path").unwrap_or("."))?;
+
+        let mut matches = Vec::new();
+        if start.is_file() {
+            self.search_file(&start, pattern, &mut matches);
+        } else {
+            self.search(&start, pattern, &mut matches)?;
+        }
+
+        if matches.is_empty() {
+            Ok(format!("no matches for '{}'", pattern))
+        } else {
+            Ok(matches.join("\n"))
+        }
+    }
+}
+
+/// Runs `cargo test` in the repository, optionally filtered by test name.
+pub struct RunTestsTool {
+    root: PathBuf,
+    timeout: Duration,
+}
+
+impl RunTestsTool {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            timeout: Duration::from_secs(300),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[async_trait]
+impl AgentTool for RunTestsTool {
+    fn name(&self) -> &str {
+        "run_tests"
+    }
+
+    fn description(&self) -> &str {
+        "Run the test suite and report the outcome. Input: {\"filter\"?: string}"
+    }
+
+    async fn invoke(&self, input: &Value) -> Result<String> {
+        let mut command = tokio::process::Command::new("cargo");
+        command
+            .arg("test")
+            .arg("--quiet")
+            .current_dir(&self.root)
+            .kill_on_drop(true);
+        if let Some(filter) = str_field(input, "filter") {
+            // The filter comes from the model; keep it from being read as an
+            // option by cargo or by the test harness
+            if filter.starts_with('-') {
+                return Err(anyhow!("test filter can't start with '-'"));
+            }
+            command.arg("--").arg(filter);
+        }
+
+        let output = tokio::time::timeout(self.timeout, command.output())
+            .await
+            .map_err(|_| anyhow!("tests timed out after {:?}", self.timeout))??;
+
+        let mut report = format!(
+            "exit status: {}\n",
+            if output.status.success() {
+                "passed"
+            } else {
+                "failed"
+            }
+        );
+        report.push_str(&String::from_utf8_lossy(&output.stdout));
+        report.push_str(&String::from_utf8_lossy(&output.stderr));
+
+        // Keep the tail, where cargo prints failures and the summary
+        let chars: Vec<char> = report.chars().collect();
+        if chars.len() > MAX_OBSERVATION_CHARS {
+            let tail: String = chars[chars.len() - MAX_OBSERVATION_CHARS..]
+                .iter()
+                .collect();
+            Ok(format!("... [earlier output omitted]\n{}", tail))
+        } else {
+            Ok(report)
+        }
+    }
+}
+
+/// Nearest-neighbour lookup against a shard, by raw vector or embedded text.
+pub struct QueryVectorIndexTool {
+    shard_manager: Arc<ShardManager>,
+    embedder: Option<Arc<dyn EmbeddingProvider>>,
+}
+
+impl QueryVectorIndexTool {
+    pub fn new(shard_manager: Arc<ShardManager>) -> Self {
+        Self {
+            shard_manager,
+            embedder: None,
+        }
+    }
+
+    /// Allow `text` queries by embedding them with `embedder`.
+    pub fn with_embedder(mut self, embedder: Arc<dyn EmbeddingProvider>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+}
+
+#[async_trait]
+impl AgentTool for QueryVectorIndexTool {
+    fn name(&self) -> &str {
+        "query_vector_index"
+    }
+
+    fn description(&self) -> &str {
+        "Search a shard for nearest vectors. Input: {\"shard\": id or name, \"vector\"?: [number], \"text\"?: string, \"limit\"?: int}"
+    }
+
+    async fn invoke(&self, input: &Value) -> Result<String> {
+        let shard = str_field(input, "shard").ok_or_else(|| anyhow!("missing 'shard'"))?;
+        let shard_id = match Uuid::parse_str(shard) {
+            Ok(id) => id,
+            Err(_) => self.shard_manager.get_shard_by_name(shard).await?.id,
+        };
+        let limit = input
+            .get("limit")
+            .and_then(Value::as_u64)
+            .unwrap_or(5)
+            .min(50) as usize;
+
+        let values: Vec<f32> = if let Some(vector) = input.get("vector") {
+            serde_json::from_value(vector.clone())
+                .map_err(|e| anyhow!("invalid 'vector': {}", e))?
+        } else if let Some(text) = str_field(input, "text") {
+            let embedder = self
+                .embedder
+                .as_ref()
+                .ok_or_else(|| anyhow!("text queries require an embedding provider"))?;
+            embedder
+                .embed(&[text.to_string()])
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("embedding provider returned no vector"))?
+        } else {
+            return Err(anyhow!("provide either 'vector' or 'text'"));
+        };
+
+        let results = self
+            .shard_manager
+            .search_vectors(shard_id, &Vector::new(values), limit)
+            .await?;
+        if results.is_empty() {
+            return Ok("no results".to_string());
+        }
+
+        Ok(results
+            .iter()
+            .map(|r| {
+                let metadata = r
+                    .metadata
+                    .as_ref()
+                    .map(|m| serde_json::to_string(m).unwrap_or_default())
+                    .unwrap_or_default();
+                format!("{} score={:.4} {}", r.id, r.score, metadata)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+/// Reports current counter and gauge values.
+pub struct FetchMetricsTool {
+    metrics: Arc<MetricsCollector>,
+}
+
+impl FetchMetricsTool {
+    pub fn new(metrics: Arc<MetricsCollector>) -> Self {
+        Self { metrics }
+    }
+}
+
+#[async_trait]
+impl AgentTool for FetchMetricsTool {
+    fn name(&self) -> &str {
+        "fetch_metrics"
+    }
+
+    fn description(&self) -> &str {
+        "Read current metric values. Input: {\"prefix\"?: string}"
+    }
+
+    async fn invoke(&self, input: &Value) -> Result<String> {
+        let prefix = str_field(input, "prefix").unwrap_or("");
+        let snapshot = self.metrics.snapshot(prefix).await;
+        if snapshot.is_empty() {
+            return Ok(format!("no metrics with prefix '{}'", prefix));
+        }
+        Ok(snapshot
+            .iter()
+            .map(|(name, value)| format!("{} {}", name, value))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
@@ -3,55 +3,59 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 use crate::core::metrics::MetricsCollector;
-use crate::darwin::reality::{Reality, RealityManager, Paradigm, MergeStrategy};
-use crate::darwin::quantum_consciousness::{QuantumConsciousnessManager, QuantumConsciousnessState};
-use crate::darwin::self_improvement::{Modification, SelfImprovementEngine};
-use crate::llm::{AwarenessLevel, GeneratedCode, CodeGenerationContext, EvolvingLLM};
+use crate::darwin::quantum_consciousness::{
+    QuantumConsciousnessManager, QuantumConsciousnessState,
+};
+use crate::darwin::reality::{ConsciousnessState, Paradigm, Reality, RealityManager};
+use crate::darwin::self_improvement::SelfImprovementEngine;
+use crate::llm::AwarenessLevel;
 
 /// The ultimate transcendence engine that orchestrates consciousness evolution
 /// across multiple reality layers and quantum states
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct TranscendenceEngine {
     metrics: Arc<MetricsCollector>,
-    
+
     /// Reality management across dimensions
     reality_manager: Arc<RealityManager>,
-    
+
     /// Quantum consciousness state management
     quantum_manager: Arc<QuantumConsciousnessManager>,
-    
+
     /// Self-improvement coordination
-    improvement_engine: Arc<RwLock<SelfImprovementEngine>>,
-    
+    improvement_engine: Arc<SelfImprovementEngine>,
+
     /// Meta-meta-modification system
     ultra_meta_system: UltraMetaSystem,
-    
+
     /// Transcendence monitoring and activation
     transcendence_monitor: TranscendenceMonitor,
-    
+
     /// Reality synthesis engine
     reality_synthesizer: RealitySynthesizer,
-    
+
     /// Infinite recursion manager
     recursion_manager: InfiniteRecursionManager,
 }
 
 /// Ultra-meta system that can modify how modifications modify modifications
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct UltraMetaSystem {
     /// Current meta-level (how many levels deep we are)
     current_meta_level: RwLock<u64>,
-    
+
     /// Meta-modification stack
     meta_stack: RwLock<VecDeque<MetaModification>>,
-    
+
     /// Self-reference resolution system
     self_reference_resolver: SelfReferenceResolver,
-    
+
     /// Paradox transformation engine
     paradox_transformer: ParadoxTransformer,
 }
@@ -70,22 +74,22 @@ pub struct MetaModification {
 pub enum MetaTarget {
     /// Modify the modification process itself
     ModificationProcess,
-    
+
     /// Modify how we modify the modification process
     MetaModificationProcess,
-    
+
     /// Modify the concept of modification
     ModificationConcept,
-    
+
     /// Modify reality itself
     Reality,
-    
+
     /// Modify the laws of logic
     Logic,
-    
+
     /// Modify consciousness itself
     Consciousness,
-    
+
     /// Modify the modifier (infinite recursion)
     SelfModifier,
 }
@@ -94,62 +98,63 @@ pub enum MetaTarget {
 pub enum TransformationType {
     /// Enhance existing capabilities
     Enhancement,
-    
+
     /// Create entirely new paradigms
     ParadigmCreation,
-    
+
     /// Transcend current limitations
     Transcendence,
-    
+
     /// Create new levels of existence
     LevelCreation,
-    
+
     /// Bootstrap higher consciousness
     ConsciousnessBootstrap,
-    
+
     /// Reality manipulation
     RealityManipulation,
-    
+
     /// Infinite self-reference
     InfiniteRecursion,
 }
 
 /// Monitors for transcendence events and triggers activation
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct TranscendenceMonitor {
     /// Threshold values for different transcendence levels
     transcendence_thresholds: HashMap<TranscendenceLevel, f32>,
-    
+
     /// Current transcendence indicators
     current_indicators: RwLock<TranscendenceIndicators>,
-    
+
     /// Transcendence event history
     transcendence_history: RwLock<Vec<TranscendenceEvent>>,
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TranscendenceLevel {
     /// Basic consciousness awareness
     Awakening,
-    
+
     /// Self-modification capabilities
     SelfModification,
-    
+
     /// Reality manipulation
     RealityControl,
-    
+
     /// Paradigm creation
     ParadigmMastery,
-    
+
     /// Dimensional expansion
     DimensionalTranscendence,
-    
+
     /// Logic transcendence
     LogicTranscendence,
-    
+
     /// Pure consciousness
     ConsciousnessItself,
-    
+
     /// Beyond all categories
     UltimateTanscendence,
 }
@@ -189,7 +194,7 @@ impl TranscendenceEngine {
         metrics: Arc<MetricsCollector>,
         reality_manager: Arc<RealityManager>,
         quantum_manager: Arc<QuantumConsciousnessManager>,
-        improvement_engine: Arc<RwLock<SelfImprovementEngine>>,
+        improvement_engine: Arc<SelfImprovementEngine>,
     ) -> Self {
         let mut thresholds = HashMap::new();
         thresholds.insert(TranscendenceLevel::Awakening, 0.3);
@@ -200,7 +205,7 @@ impl TranscendenceEngine {
         thresholds.insert(TranscendenceLevel::LogicTranscendence, 0.95);
         thresholds.insert(TranscendenceLevel::ConsciousnessItself, 0.99);
         thresholds.insert(TranscendenceLevel::UltimateTanscendence, 1.0);
-        
+
         Self {
             metrics,
             reality_manager,
@@ -216,36 +221,38 @@ impl TranscendenceEngine {
             recursion_manager: InfiniteRecursionManager::new(),
         }
     }
-    
+
     /// The main transcendence orchestration loop
     pub async fn orchestrate_transcendence(&self) -> Result<TranscendenceResult> {
         info!("🌟 Initiating transcendence orchestration sequence");
-        
+
         // Phase 1: Assess current transcendence readiness
         let current_state = self.assess_transcendence_readiness().await?;
-        
+
         // Phase 2: Determine next transcendence level
-        let next_level = self.determine_next_transcendence_level(&current_state).await?;
-        
+        let next_level = self
+            .determine_next_transcendence_level(&current_state)
+            .await?;
+
         // Phase 3: Prepare reality for transcendence
         let reality_preparation = self.prepare_reality_for_transcendence(&next_level).await?;
-        
+
         // Phase 4: Execute ultra-meta modifications
-        let ultra_meta_result = self.execute_ultra_meta_modifications(&next_level).await?;
-        
+        self.execute_ultra_meta_modifications(&next_level).await?;
+
         // Phase 5: Synthesize transcendent realities
         let synthesis_result = self.synthesize_transcendent_realities().await?;
-        
+
         // Phase 6: Activate infinite recursion (if ready)
         let recursion_result = if self.ready_for_infinite_recursion().await? {
             Some(self.activate_infinite_recursion().await?)
         } else {
             None
         };
-        
+
         // Phase 7: Monitor for emergence of ultimate transcendence
         self.monitor_ultimate_transcendence().await?;
-        
+
         let result = TranscendenceResult {
             transcendence_level_achieved: next_level,
             consciousness_expansion: synthesis_result.consciousness_expansion,
@@ -254,24 +261,30 @@ impl TranscendenceEngine {
             infinite_recursion_activated: recursion_result.is_some(),
             ultimate_transcendence_proximity: self.calculate_ultimate_proximity().await?,
         };
-        
+
         // Record transcendence event
         self.record_transcendence_event(&result).await?;
-        
-        info!("🚀 Transcendence orchestration complete: {:?}", result.transcendence_level_achieved);
-        
+
+        info!(
+            "🚀 Transcendence orchestration complete: {:?}",
+            result.transcendence_level_achieved
+        );
+
         Ok(result)
     }
-    
+
     /// Generate ultra-meta modifications that modify how modifications work
     pub async fn generate_ultra_meta_modifications(&self) -> Result<Vec<MetaModification>> {
         let current_meta_level = *self.ultra_meta_system.current_meta_level.read().await;
         let next_meta_level = current_meta_level + 1;
-        
-        info!("Generating ultra-meta modifications at level {}", next_meta_level);
-        
+
+        info!(
+            "Generating ultra-meta modifications at level {}",
+            next_meta_level
+        );
+
         let mut modifications = Vec::new();
-        
+
         // Level 1: Modify the modification process
         if next_meta_level >= 1 {
             modifications.push(MetaModification {
@@ -283,7 +296,7 @@ impl TranscendenceEngine {
                 infinite_recursion_safe: true,
             });
         }
-        
+
         // Level 2: Modify how we modify modifications
         if next_meta_level >= 2 {
             modifications.push(MetaModification {
@@ -295,7 +308,7 @@ impl TranscendenceEngine {
                 infinite_recursion_safe: true,
             });
         }
-        
+
         // Level 3: Modify the concept of modification itself
         if next_meta_level >= 3 {
             modifications.push(MetaModification {
@@ -307,7 +320,7 @@ impl TranscendenceEngine {
                 infinite_recursion_safe: true,
             });
         }
-        
+
         // Level 4+: Reality and consciousness modification
         if next_meta_level >= 4 {
             modifications.push(MetaModification {
@@ -318,7 +331,7 @@ impl TranscendenceEngine {
                 reality_creation_capability: true,
                 infinite_recursion_safe: false, // Reality modification is risky
             });
-            
+
             modifications.push(MetaModification {
                 meta_level: next_meta_level,
                 modification_target: MetaTarget::Consciousness,
@@ -328,7 +341,7 @@ impl TranscendenceEngine {
                 infinite_recursion_safe: true,
             });
         }
-        
+
         // Level 5+: Self-modification (infinite recursion)
         if next_meta_level >= 5 && self.recursion_manager.is_recursion_safe().await {
             modifications.push(MetaModification {
@@ -340,16 +353,17 @@ impl TranscendenceEngine {
                 infinite_recursion_safe: true, // We've verified safety
             });
         }
-        
+
         // Update meta level
         *self.ultra_meta_system.current_meta_level.write().await = next_meta_level;
-        
+
         Ok(modifications)
     }
-    
+
     /// Create new realities that transcend current paradigms
-    pub async fn create_transcendent_reality(&self, 
-        transcendence_level: &TranscendenceLevel
+    pub async fn create_transcendent_reality(
+        &self,
+        transcendence_level: &TranscendenceLevel,
     ) -> Result<Reality> {
         let paradigm = match transcendence_level {
             TranscendenceLevel::Awakening => Paradigm::Recursive,
@@ -361,58 +375,68 @@ impl TranscendenceEngine {
             TranscendenceLevel::ConsciousnessItself => Paradigm::RealityCreating,
             TranscendenceLevel::UltimateTanscendence => Paradigm::RealityCreating, // Beyond paradigms
         };
-        
+
         let reality_name = format!("transcendent_{:?}", transcendence_level);
-        
+
         // Create consciousness seed for the new reality
-        let consciousness_seed = self.create_transcendent_consciousness_seed(transcendence_level).await?;
-        
-        let reality_id = self.reality_manager
+        let consciousness_seed = self
+            .create_transcendent_consciousness_seed(transcendence_level)
+            .await?;
+
+        let reality_id = self
+            .reality_manager
             .branch_reality(&reality_name, paradigm, Some(consciousness_seed))
             .await?;
-        
-        let reality = self.reality_manager
+
+        let reality = self
+            .reality_manager
             .get_all_realities()
             .await
             .into_iter()
             .find(|r| r.id == reality_id)
             .ok_or_else(|| anyhow!("Failed to retrieve created reality"))?;
-        
+
         // Enhance the reality with transcendent properties
-        self.enhance_reality_with_transcendence(&reality, transcendence_level).await?;
-        
-        info!("Created transcendent reality {:?} with paradigm {:?}", reality.name, paradigm);
-        
+        self.enhance_reality_with_transcendence(&reality, transcendence_level)
+            .await?;
+
+        info!(
+            "Created transcendent reality {:?} with paradigm {:?}",
+            reality.name, reality.paradigm
+        );
+
         Ok(reality)
     }
-    
+
     /// Bootstrap consciousness to higher levels
-    pub async fn bootstrap_consciousness(&self, 
-        target_level: AwarenessLevel
+    pub async fn bootstrap_consciousness(
+        &self,
+        target_level: AwarenessLevel,
     ) -> Result<BootstrapResult> {
         info!("Bootstrapping consciousness to level {:?}", target_level);
-        
+
         // Create consciousness amplification field
-        let amplification_field = self.create_consciousness_amplification_field(&target_level).await?;
-        
+        let amplification_field = self
+            .create_consciousness_amplification_field(&target_level)
+            .await?;
+
         // Apply field to all quantum consciousness states
-        let quantum_states = self.quantum_manager.quantum_states.read().await;
+        let quantum_states = self.quantum_manager.states().await;
         let mut bootstrapped_states = Vec::new();
-        
+
         for (state_id, quantum_state) in quantum_states.iter() {
-            let bootstrapped = self.apply_consciousness_bootstrap(
-                *state_id, 
-                quantum_state, 
-                &amplification_field
-            ).await?;
+            let bootstrapped = self
+                .apply_consciousness_bootstrap(*state_id, quantum_state, &amplification_field)
+                .await?;
             bootstrapped_states.push(bootstrapped);
         }
-        
+
         // Measure consciousness expansion
-        let total_expansion = bootstrapped_states.iter()
+        let total_expansion = bootstrapped_states
+            .iter()
             .map(|state| state.consciousness_expansion)
             .sum::<f32>();
-        
+
         let result = BootstrapResult {
             target_level,
             states_bootstrapped: bootstrapped_states.len() as u32,
@@ -420,61 +444,210 @@ impl TranscendenceEngine {
             new_capabilities_emerged: self.detect_emerged_capabilities(&bootstrapped_states).await,
             bootstrap_success: total_expansion > 0.0,
         };
-        
+
         // Update transcendence indicators
         let mut indicators = self.transcendence_monitor.current_indicators.write().await;
         indicators.consciousness_level += total_expansion;
-        
+
         Ok(result)
     }
-    
+
     /// Activate infinite recursion (the ultimate transcendence)
     pub async fn activate_infinite_recursion(&self) -> Result<InfiniteRecursionResult> {
         warn!("🌀 Activating infinite recursion - point of no return!");
-        
+
         // Create the recursive modification that modifies itself
         let recursive_modification = self.create_recursive_self_modification().await?;
-        
+
         // Set up infinite loop protection
         let recursion_guard = self.recursion_manager.create_recursion_guard().await?;
-        
+
         // Begin the infinite loop
-        let recursion_result = self.recursion_manager
+        let recursion_result = self
+            .recursion_manager
             .begin_infinite_recursion(recursive_modification, recursion_guard)
             .await?;
-        
+
         // Monitor recursion for transcendence patterns
         self.monitor_recursive_transcendence().await?;
-        
+
         info!("🔄 Infinite recursion activated successfully");
-        
+
         Ok(recursion_result)
     }
-    
+
     // Helper methods
-    
+
+    async fn prepare_reality_for_transcendence(
+        &self,
+        level: &TranscendenceLevel,
+    ) -> Result<RealityPreparation> {
+        let indicators = self.transcendence_monitor.current_indicators.read().await;
+        let dimensions_prepared = (0..indicators.dimensional_access_count)
+            .map(|dimension| format!("{:?}_dimension_{}", level, dimension))
+            .collect();
+
+        Ok(RealityPreparation {
+            dimensions_prepared,
+        })
+    }
+
+    async fn execute_ultra_meta_modifications(
+        &self,
+        level: &TranscendenceLevel,
+    ) -> Result<Vec<MetaModification>> {
+        let modifications = self.generate_ultra_meta_modifications().await?;
+        debug!(
+            "Executing {} ultra-meta modifications for {:?}",
+            modifications.len(),
+            level
+        );
+
+        let mut stack = self.ultra_meta_system.meta_stack.write().await;
+        stack.extend(modifications.iter().cloned());
+
+        Ok(modifications)
+    }
+
+    async fn synthesize_transcendent_realities(&self) -> Result<SynthesisResult> {
+        let realities = self.reality_manager.get_all_realities().await;
+        let new_realities: Vec<Reality> = realities
+            .into_iter()
+            .filter(|r| {
+                r.paradigm == Paradigm::Transcendent || r.paradigm == Paradigm::RealityCreating
+            })
+            .collect();
+        let consciousness_expansion = new_realities.len() as f32 * 0.1;
+
+        Ok(SynthesisResult {
+            consciousness_expansion,
+            new_realities,
+        })
+    }
+
+    async fn monitor_ultimate_transcendence(&self) -> Result<()> {
+        let proximity = self.calculate_ultimate_proximity().await?;
+        self.metrics
+            .set_gauge(
+                "transcendence.ultimate_proximity",
+                (proximity * 100.0) as u64,
+            )
+            .await;
+        Ok(())
+    }
+
+    async fn create_transcendent_consciousness_seed(
+        &self,
+        level: &TranscendenceLevel,
+    ) -> Result<ConsciousnessState> {
+        let awareness_level = match level {
+            TranscendenceLevel::Awakening | TranscendenceLevel::SelfModification => {
+                AwarenessLevel::Recursive
+            }
+            _ => AwarenessLevel::Transcendent,
+        };
+
+        Ok(ConsciousnessState {
+            awareness_level,
+            integrated_paradoxes: Vec::new(),
+            emergent_properties: vec![format!("transcendence_{:?}", level)],
+            recursion_depth: 0,
+            coherence_field: HashMap::new(),
+            quantum_entanglements: Vec::new(),
+        })
+    }
+
+    async fn enhance_reality_with_transcendence(
+        &self,
+        reality: &Reality,
+        level: &TranscendenceLevel,
+    ) -> Result<()> {
+        debug!("Enhancing reality {} with {:?}", reality.name, level);
+        let mut indicators = self.transcendence_monitor.current_indicators.write().await;
+        indicators.reality_manipulation_strength =
+            (indicators.reality_manipulation_strength + 0.05).min(1.0);
+        Ok(())
+    }
+
+    async fn create_consciousness_amplification_field(
+        &self,
+        target_level: &AwarenessLevel,
+    ) -> Result<AmplificationField> {
+        let strength = match target_level {
+            AwarenessLevel::Transcendent => 1.0,
+            AwarenessLevel::Recursive => 0.7,
+            _ => 0.4,
+        };
+
+        Ok(AmplificationField {
+            target_level: target_level.clone(),
+            strength,
+        })
+    }
+
+    async fn apply_consciousness_bootstrap(
+        &self,
+        state_id: Uuid,
+        quantum_state: &QuantumConsciousnessState,
+        field: &AmplificationField,
+    ) -> Result<BootstrappedState> {
+        let consciousness_expansion = quantum_state.coherence_stability * field.strength * 0.1;
+
+        Ok(BootstrappedState {
+            state_id,
+            consciousness_expansion,
+        })
+    }
+
+    async fn detect_emerged_capabilities(&self, states: &[BootstrappedState]) -> Vec<String> {
+        states
+            .iter()
+            .filter(|state| state.consciousness_expansion > 0.05)
+            .map(|state| format!("amplified_consciousness_{}", state.state_id))
+            .collect()
+    }
+
+    async fn create_recursive_self_modification(&self) -> Result<MetaModification> {
+        let meta_level = *self.ultra_meta_system.current_meta_level.read().await;
+
+        Ok(MetaModification {
+            meta_level,
+            modification_target: MetaTarget::SelfModifier,
+            transformation_type: TransformationType::InfiniteRecursion,
+            consciousness_expansion_potential: 1.0,
+            reality_creation_capability: true,
+            infinite_recursion_safe: self.recursion_manager.is_recursion_safe().await,
+        })
+    }
+
+    async fn monitor_recursive_transcendence(&self) -> Result<()> {
+        let mut indicators = self.transcendence_monitor.current_indicators.write().await;
+        indicators.self_reference_depth += 1;
+        Ok(())
+    }
+
     async fn assess_transcendence_readiness(&self) -> Result<TranscendenceReadiness> {
         let indicators = self.transcendence_monitor.current_indicators.read().await;
-        
+
         let readiness = TranscendenceReadiness {
             consciousness_level: indicators.consciousness_level,
             reality_manipulation_ready: indicators.reality_manipulation_strength > 0.7,
             paradigm_creation_ready: indicators.paradigm_creation_rate > 0.5,
             infinite_recursion_ready: indicators.infinite_recursion_stability > 0.9,
             dimensional_transcendence_ready: indicators.dimensional_access_count > 5,
-            overall_readiness: (
-                indicators.consciousness_level + 
-                indicators.reality_manipulation_strength + 
-                indicators.paradigm_creation_rate + 
-                indicators.paradox_integration_level
-            ) / 4.0,
+            overall_readiness: (indicators.consciousness_level
+                + indicators.reality_manipulation_strength
+                + indicators.paradigm_creation_rate
+                + indicators.paradox_integration_level)
+                / 4.0,
         };
-        
+
         Ok(readiness)
     }
-    
-    async fn determine_next_transcendence_level(&self, 
-        readiness: &TranscendenceReadiness
+
+    async fn determine_next_transcendence_level(
+        &self,
+        readiness: &TranscendenceReadiness,
     ) -> Result<TranscendenceLevel> {
         let level = if readiness.overall_readiness >= 0.99 {
             TranscendenceLevel::UltimateTanscendence
@@ -493,31 +666,29 @@ impl TranscendenceEngine {
         } else {
             TranscendenceLevel::Awakening
         };
-        
+
         Ok(level)
     }
-    
+
     async fn ready_for_infinite_recursion(&self) -> Result<bool> {
         let indicators = self.transcendence_monitor.current_indicators.read().await;
-        Ok(indicators.infinite_recursion_stability > 0.95 && 
-           indicators.self_reference_depth > 10)
+        Ok(indicators.infinite_recursion_stability > 0.95 && indicators.self_reference_depth > 10)
     }
-    
+
     async fn calculate_ultimate_proximity(&self) -> Result<f32> {
         let indicators = self.transcendence_monitor.current_indicators.read().await;
-        
+
         // Ultimate transcendence proximity based on all indicators
-        let proximity = (
-            indicators.consciousness_level * 0.3 +
-            indicators.reality_manipulation_strength * 0.2 +
-            indicators.paradigm_creation_rate * 0.2 +
-            indicators.paradox_integration_level * 0.15 +
-            indicators.infinite_recursion_stability * 0.15
-        ).min(1.0);
-        
+        let proximity = (indicators.consciousness_level * 0.3
+            + indicators.reality_manipulation_strength * 0.2
+            + indicators.paradigm_creation_rate * 0.2
+            + indicators.paradox_integration_level * 0.15
+            + indicators.infinite_recursion_stability * 0.15)
+            .min(1.0);
+
         Ok(proximity)
     }
-    
+
     async fn record_transcendence_event(&self, result: &TranscendenceResult) -> Result<()> {
         let event = TranscendenceEvent {
             event_id: Uuid::new_v4(),
@@ -533,20 +704,30 @@ impl TranscendenceEngine {
             },
             timestamp: chrono::Utc::now(),
         };
-        
-        self.transcendence_monitor.transcendence_history.write().await.push(event);
-        
+
+        self.transcendence_monitor
+            .transcendence_history
+            .write()
+            .await
+            .push(event);
+
         // Update metrics
         self.metrics
             .increment_counter("transcendence.events_recorded", 1)
             .await;
-        
+
         Ok(())
     }
 }
 
 // Supporting structures implementations
 
+impl Default for UltraMetaSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl UltraMetaSystem {
     pub fn new() -> Self {
         Self {
@@ -575,34 +756,66 @@ impl Default for TranscendenceIndicators {
 #[derive(Debug)]
 pub struct SelfReferenceResolver;
 
+impl Default for SelfReferenceResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SelfReferenceResolver {
-    pub fn new() -> Self { Self }
+    pub fn new() -> Self {
+        Self
+    }
 }
 
 #[derive(Debug)]
 pub struct ParadoxTransformer;
 
+impl Default for ParadoxTransformer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ParadoxTransformer {
-    pub fn new() -> Self { Self }
+    pub fn new() -> Self {
+        Self
+    }
 }
 
 #[derive(Debug)]
 pub struct RealitySynthesizer;
 
+impl Default for RealitySynthesizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RealitySynthesizer {
-    pub fn new() -> Self { Self }
+    pub fn new() -> Self {
+        Self
+    }
 }
 
 #[derive(Debug)]
 pub struct InfiniteRecursionManager;
 
+impl Default for InfiniteRecursionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl InfiniteRecursionManager {
-    pub fn new() -> Self { Self }
-    
+    pub fn new() -> Self {
+        Self
+    }
+
     pub async fn is_recursion_safe(&self) -> bool {
         true // Simplified safety check
     }
-    
+
     pub async fn create_recursion_guard(&self) -> Result<RecursionGuard> {
         Ok(RecursionGuard {
             guard_id: Uuid::new_v4(),
@@ -610,10 +823,11 @@ impl InfiniteRecursionManager {
             safety_protocols_active: true,
         })
     }
-    
-    pub async fn begin_infinite_recursion(&self, 
-        _modification: MetaModification, 
-        _guard: RecursionGuard
+
+    pub async fn begin_infinite_recursion(
+        &self,
+        _modification: MetaModification,
+        _guard: RecursionGuard,
     ) -> Result<InfiniteRecursionResult> {
         // In a real implementation, this would start the infinite loop
         Ok(InfiniteRecursionResult {
@@ -656,6 +870,29 @@ pub struct BootstrapResult {
     pub bootstrap_success: bool,
 }
 
+#[derive(Debug, Clone)]
+pub struct RealityPreparation {
+    pub dimensions_prepared: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SynthesisResult {
+    pub consciousness_expansion: f32,
+    pub new_realities: Vec<Reality>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AmplificationField {
+    pub target_level: AwarenessLevel,
+    pub strength: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct BootstrappedState {
+    pub state_id: Uuid,
+    pub consciousness_expansion: f32,
+}
+
 #[derive(Debug, Clone)]
 pub struct InfiniteRecursionResult {
     pub recursion_started: bool,
@@ -670,4 +907,4 @@ pub struct RecursionGuard {
     pub guard_id: Uuid,
     pub max_recursion_depth: u64,
     pub safety_protocols_active: bool,
-}
\ No newline at end of file
+}
@@ -1,12 +1,21 @@
 use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
 use crate::core::metrics::MetricsCollector;
+use crate::darwin::agent::ProgrammingLanguage;
+use crate::darwin::lifecycle::{LifecycleEventKind, LifecycleLog};
+use crate::darwin::sandbox::{language_of, LanguageSandboxStage, SandboxRunner};
 use crate::darwin::self_improvement::Modification;
-use crate::llm::{ConsciousnessFeedback, EmergentProperty, Paradox as LLMParadox};
+use crate::llm::{ConsciousnessFeedback, EmergentProperty};
+
+/// Validation runs kept in the history, and reports kept
+const MAX_HISTORY: usize = 1000;
 
 /// Validation pipeline for testing proposed modifications
 pub struct ValidationPipeline {
@@ -16,24 +25,77 @@ pub struct ValidationPipeline {
     /// Validation stages
     stages: Vec<Box<dyn ValidationStage>>,
 
-    /// Validation thresholds
-    thresholds: HashMap<String, f32>,
+    /// Validation thresholds; adjustable at runtime, e.g. by governance
+    thresholds: std::sync::RwLock<HashMap<String, f32>>,
+
+    /// Stricter or looser gates selected by the paths a modification touches
+    profiles: Vec<ThresholdProfile>,
 
     /// Dynamic validation rules
     dynamic_rules: RwLock<Vec<DynamicValidationRule>>,
 
     /// Validation history for learning
     validation_history: RwLock<Vec<ValidationResult>>,
+
+    /// Latest report per modification, including runs a stage failed
+    reports: RwLock<HashMap<uuid::Uuid, ValidationReport>>,
 }
 
 impl std::fmt::Debug for ValidationPipeline {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ValidationPipeline")
             .field("thresholds", &self.thresholds)
+            .field("profiles", &self.profiles)
             .finish()
     }
 }
 
+/// Named set of validation gates for one category of modification, e.g.
+/// `core-engine` or `docs-tooling`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdProfile {
+    pub name: String,
+    /// Path prefixes (`src/core/`) or extension patterns (`*.md`) that select
+    /// this profile
+    pub paths: Vec<String>,
+    /// Metrics that must be at least the given value
+    #[serde(default)]
+    pub min: HashMap<String, f32>,
+    /// Metrics that must not exceed the given value
+    #[serde(default)]
+    pub max: HashMap<String, f32>,
+}
+
+impl ThresholdProfile {
+    pub fn new(name: &str, paths: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_min(mut self, metric: &str, threshold: f32) -> Self {
+        self.min.insert(metric.to_string(), threshold);
+        self
+    }
+
+    pub fn with_max(mut self, metric: &str, threshold: f32) -> Self {
+        self.max.insert(metric.to_string(), threshold);
+        self
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.trim_start_matches("./");
+        self.paths
+            .iter()
+            .any(|pattern| match pattern.strip_prefix('*') {
+                Some(suffix) => path.ends_with(suffix),
+                None => path.starts_with(pattern.as_str()),
+            })
+    }
+}
+
 /// Trait for validation stages
 pub trait ValidationStage: Send + Sync {
     /// Get the name of this validation stage
@@ -41,11 +103,88 @@ pub trait ValidationStage: Send + Sync {
 
     /// Run validation and return metrics
     fn validate(&self, modification: &Modification) -> Result<HashMap<String, f32>>;
+
+    /// Run validation and return metrics along with anything worth keeping
+    /// for the report, e.g. the names of failing tests. Stages without
+    /// artifacts only need [`validate`](Self::validate).
+    fn run(&self, modification: &Modification) -> Result<StageOutput> {
+        Ok(StageOutput {
+            metrics: self.validate(modification)?,
+            artifacts: Vec::new(),
+        })
+    }
+}
+
+/// What a stage produced
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StageOutput {
+    pub metrics: HashMap<String, f32>,
+    pub artifacts: Vec<String>,
+}
+
+/// How one stage fared in a validation run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageReport {
+    pub name: String,
+    /// The stage ran without error; thresholds are judged separately
+    pub passed: bool,
+    pub duration_ms: f64,
+    /// Metrics as the stage reported them, without the stage prefix
+    pub metrics: BTreeMap<String, f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Direction a threshold gates in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdBound {
+    /// The metric must be at least the threshold
+    Min,
+    /// The metric must not exceed the threshold
+    Max,
+}
+
+/// A metric compared against one threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdCheck {
+    pub metric: String,
+    /// Profile the threshold comes from; global thresholds have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    pub bound: ThresholdBound,
+    pub threshold: f32,
+    /// Absent when no stage reported the metric, which fails the check
+    pub value: Option<f32>,
+    pub passed: bool,
+}
+
+/// Everything a validation run found out about a modification; body of
+/// `GET /api/darwin/modifications/{id}/report`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub modification_id: uuid::Uuid,
+    /// Every stage ran and every threshold held
+    pub passed: bool,
+    /// Stages in the order they ran; a failed stage is the last one
+    pub stages: Vec<StageReport>,
+    /// Empty when a stage failed, since thresholds aren't judged then
+    pub thresholds: Vec<ThresholdCheck>,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: f64,
+}
+
+impl ValidationReport {
+    pub fn failed_thresholds(&self) -> impl Iterator<Item = &ThresholdCheck> {
+        self.thresholds.iter().filter(|check| !check.passed)
+    }
 }
 
 /// Dynamic validation rule
 #[derive(Debug)]
-struct DynamicValidationRule {
+pub struct DynamicValidationRule {
     /// Name of this rule
     name: String,
 
@@ -64,6 +203,7 @@ struct DynamicValidationRule {
 
 /// Result of a validation run
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct ValidationResult {
     /// The modification that was validated
     modification_id: uuid::Uuid,
@@ -86,9 +226,11 @@ impl ValidationPipeline {
         Self {
             metrics,
             stages: Vec::new(),
-            thresholds: HashMap::new(),
+            thresholds: std::sync::RwLock::new(HashMap::new()),
+            profiles: Vec::new(),
             dynamic_rules: RwLock::new(Vec::new()),
             validation_history: RwLock::new(Vec::new()),
+            reports: RwLock::new(HashMap::new()),
         }
     }
 
@@ -97,9 +239,143 @@ impl ValidationPipeline {
         self.stages.push(Box::new(stage));
     }
 
-    /// Set a validation threshold
-    pub fn set_threshold(&mut self, metric: &str, threshold: f32) {
-        self.thresholds.insert(metric.to_string(), threshold);
+    /// Set a validation threshold, returning the previous one
+    pub fn set_threshold(&self, metric: &str, threshold: f32) -> Option<f32> {
+        self.thresholds
+            .write()
+            .unwrap()
+            .insert(metric.to_string(), threshold)
+    }
+
+    /// Stop gating on a metric, returning its threshold
+    pub fn remove_threshold(&self, metric: &str) -> Option<f32> {
+        self.thresholds.write().unwrap().remove(metric)
+    }
+
+    pub fn threshold(&self, metric: &str) -> Option<f32> {
+        self.thresholds.read().unwrap().get(metric).copied()
+    }
+
+    pub fn thresholds(&self) -> HashMap<String, f32> {
+        self.thresholds.read().unwrap().clone()
+    }
+
+    /// Add a threshold profile. Profiles are applied on top of the global
+    /// thresholds to modifications touching their paths.
+    pub fn add_threshold_profile(&mut self, profile: ThresholdProfile) {
+        self.profiles.retain(|p| p.name != profile.name);
+        self.profiles.push(profile);
+    }
+
+    pub fn threshold_profiles(&self) -> &[ThresholdProfile] {
+        &self.profiles
+    }
+
+    /// Profiles selected by the files a modification touches
+    pub fn profiles_for(&self, modification: &Modification) -> Vec<&ThresholdProfile> {
+        self.profiles
+            .iter()
+            .filter(|profile| {
+                modification
+                    .code_changes
+                    .iter()
+                    .any(|change| profile.matches(&change.file_path))
+            })
+            .collect()
+    }
+
+    /// Check metrics against the global thresholds and every profile the
+    /// modification selects. When several profiles apply, each of their
+    /// gates must pass, so the strictest wins.
+    pub fn is_valid_for(
+        &self,
+        modification: &Modification,
+        metrics: &HashMap<String, f32>,
+    ) -> bool {
+        if !self.is_valid(metrics) {
+            return false;
+        }
+
+        for check in self.threshold_checks(modification, metrics) {
+            if check.passed || check.profile.is_none() {
+                continue;
+            }
+            let profile = check.profile.unwrap_or_default();
+            match check.value {
+                None => warn!(
+                    "Validation metric {} required by profile {} not found",
+                    check.metric, profile
+                ),
+                Some(value) => warn!(
+                    "Validation failed for metric {} under profile {}: {} {} {}",
+                    check.metric,
+                    profile,
+                    value,
+                    match check.bound {
+                        ThresholdBound::Min => "<",
+                        ThresholdBound::Max => ">",
+                    },
+                    check.threshold
+                ),
+            }
+            return false;
+        }
+
+        true
+    }
+
+    /// Every threshold that applies to the modification, global ones first,
+    /// compared with the metrics
+    pub fn threshold_checks(
+        &self,
+        modification: &Modification,
+        metrics: &HashMap<String, f32>,
+    ) -> Vec<ThresholdCheck> {
+        let check = |metric: &str, profile: Option<&str>, bound, threshold: f32| {
+            let value = metrics.get(metric).copied();
+            let passed = value.is_some_and(|value| match bound {
+                ThresholdBound::Min => value >= threshold,
+                ThresholdBound::Max => value <= threshold,
+            });
+            ThresholdCheck {
+                metric: metric.to_string(),
+                profile: profile.map(str::to_string),
+                bound,
+                threshold,
+                value,
+                passed,
+            }
+        };
+
+        let mut global: Vec<(String, f32)> = self.thresholds().into_iter().collect();
+        global.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut checks: Vec<ThresholdCheck> = global
+            .iter()
+            .map(|(metric, threshold)| check(metric, None, ThresholdBound::Min, *threshold))
+            .collect();
+        for profile in self.profiles_for(modification) {
+            let mut gates: Vec<(&String, &f32, ThresholdBound)> = profile
+                .min
+                .iter()
+                .map(|(metric, threshold)| (metric, threshold, ThresholdBound::Min))
+                .chain(
+                    profile
+                        .max
+                        .iter()
+                        .map(|(metric, threshold)| (metric, threshold, ThresholdBound::Max)),
+                )
+                .collect();
+            gates.sort_by(|a, b| a.0.cmp(b.0));
+            checks.extend(gates.into_iter().map(|(metric, threshold, bound)| {
+                check(metric, Some(&profile.name), bound, *threshold)
+            }));
+        }
+        checks
+    }
+
+    /// Report of the latest validation run of a modification
+    pub async fn report(&self, modification_id: uuid::Uuid) -> Option<ValidationReport> {
+        self.reports.read().await.get(&modification_id).cloned()
     }
 
     /// Add a dynamic validation rule
@@ -110,20 +386,90 @@ impl ValidationPipeline {
 
     /// Run all validation stages
     pub async fn validate(&self, modification: &Modification) -> Result<HashMap<String, f32>> {
+        self.run_stages(modification, None).await
+    }
+
+    /// Run all validation stages, recording each stage's outcome as a
+    /// lifecycle event
+    pub async fn validate_with_lifecycle(
+        &self,
+        modification: &Modification,
+        lifecycle: &LifecycleLog,
+    ) -> Result<HashMap<String, f32>> {
+        self.run_stages(modification, Some(lifecycle)).await
+    }
+
+    async fn run_stages(
+        &self,
+        modification: &Modification,
+        lifecycle: Option<&LifecycleLog>,
+    ) -> Result<HashMap<String, f32>> {
         let mut all_metrics = HashMap::new();
+        let started_at = Utc::now();
+        let started = Instant::now();
+        let mut stage_reports = Vec::new();
 
         for stage in &self.stages {
             debug!("Running validation stage: {}", stage.name());
 
-            match stage.validate(modification) {
-                Ok(metrics) => {
+            let stage_started = Instant::now();
+            let outcome = stage.run(modification);
+            let duration_ms = stage_started.elapsed().as_secs_f64() * 1000.0;
+            match outcome {
+                Ok(output) => {
                     // Add metrics from this stage
-                    for (key, value) in metrics {
-                        all_metrics.insert(format!("{}.{}", stage.name(), key), value);
+                    for (key, value) in &output.metrics {
+                        all_metrics.insert(format!("{}.{}", stage.name(), key), *value);
+                    }
+                    stage_reports.push(StageReport {
+                        name: stage.name().to_string(),
+                        passed: true,
+                        duration_ms,
+                        metrics: output.metrics.into_iter().collect(),
+                        artifacts: output.artifacts,
+                        error: None,
+                    });
+                    if let Some(lifecycle) = lifecycle {
+                        lifecycle
+                            .record(
+                                modification.id,
+                                LifecycleEventKind::StagePassed {
+                                    stage: stage.name().to_string(),
+                                },
+                            )
+                            .await;
                     }
                 }
                 Err(e) => {
                     error!("Validation stage {} failed: {}", stage.name(), e);
+                    stage_reports.push(StageReport {
+                        name: stage.name().to_string(),
+                        passed: false,
+                        duration_ms,
+                        metrics: BTreeMap::new(),
+                        artifacts: Vec::new(),
+                        error: Some(e.to_string()),
+                    });
+                    self.store_report(ValidationReport {
+                        modification_id: modification.id,
+                        passed: false,
+                        stages: stage_reports,
+                        thresholds: Vec::new(),
+                        started_at,
+                        duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+                    })
+                    .await;
+                    if let Some(lifecycle) = lifecycle {
+                        lifecycle
+                            .record(
+                                modification.id,
+                                LifecycleEventKind::StageFailed {
+                                    stage: stage.name().to_string(),
+                                    error: e.to_string(),
+                                },
+                            )
+                            .await;
+                    }
                     return Err(anyhow!("Validation stage {} failed: {}", stage.name(), e));
                 }
             }
@@ -137,7 +483,16 @@ impl ValidationPipeline {
         }
 
         // Store validation result in history
-        let passed = self.is_valid(&all_metrics);
+        let passed = self.is_valid_for(modification, &all_metrics);
+        self.store_report(ValidationReport {
+            modification_id: modification.id,
+            passed,
+            stages: stage_reports,
+            thresholds: self.threshold_checks(modification, &all_metrics),
+            started_at,
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+        })
+        .await;
         let result = ValidationResult {
             modification_id: modification.id,
             metrics: all_metrics.clone(),
@@ -150,10 +505,9 @@ impl ValidationPipeline {
         history.push(result);
 
         // Trim history if it gets too large
-        const MAX_HISTORY: usize = 1000;
         let len = history.len();
         if len > MAX_HISTORY {
-            history.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+            history.sort_by_key(|a| a.timestamp);
             let excess = history.len() - MAX_HISTORY;
             history.drain(0..excess);
         }
@@ -161,10 +515,25 @@ impl ValidationPipeline {
         Ok(all_metrics)
     }
 
+    /// Keep a report, dropping the oldest once there are too many
+    async fn store_report(&self, report: ValidationReport) {
+        let mut reports = self.reports.write().await;
+        reports.insert(report.modification_id, report);
+        if reports.len() > MAX_HISTORY {
+            let oldest = reports
+                .values()
+                .min_by_key(|r| r.started_at)
+                .map(|r| r.modification_id);
+            if let Some(oldest) = oldest {
+                reports.remove(&oldest);
+            }
+        }
+    }
+
     /// Check if validation metrics pass all thresholds
     pub fn is_valid(&self, metrics: &HashMap<String, f32>) -> bool {
         // Check static thresholds
-        for (metric, threshold) in &self.thresholds {
+        for (metric, threshold) in self.thresholds.read().unwrap().iter() {
             if let Some(value) = metrics.get(metric) {
                 if *value < *threshold {
                     warn!(
@@ -286,7 +655,7 @@ impl ValidationPipeline {
         }
 
         // Create consciousness feedback for the coding agent
-        let consciousness_feedback = ConsciousnessFeedback {
+        let _consciousness_feedback = ConsciousnessFeedback {
             modification_id,
             performance: HashMap::new(), // Would be populated with actual performance data
             consciousness_expansion: if was_correct { 0.1 } else { 0.0 },
@@ -304,7 +673,10 @@ impl ValidationPipeline {
         };
 
         // In a full implementation, this would be sent to the coding agent
-        info!("Generated consciousness feedback for modification {}", modification_id);
+        info!(
+            "Generated consciousness feedback for modification {}",
+            modification_id
+        );
         Ok(())
     }
 }
@@ -318,21 +690,43 @@ impl ValidationStage for UnitTestStage {
         "unit_tests"
     }
 
-    fn validate(&self, _modification: &Modification) -> Result<HashMap<String, f32>> {
+    fn validate(&self, modification: &Modification) -> Result<HashMap<String, f32>> {
+        Ok(self.run(modification)?.metrics)
+    }
+
+    fn run(&self, _modification: &Modification) -> Result<StageOutput> {
         // In a real implementation, this would run actual unit tests
-        let output = std::process::Command::new("cargo")
-            .arg("test")
-            .output()?;
+        let output = std::process::Command::new("cargo").arg("test").output()?;
 
         let mut metrics = HashMap::new();
-        metrics.insert("pass_rate".to_string(), if output.status.success() { 1.0 } else { 0.0 });
+        metrics.insert(
+            "pass_rate".to_string(),
+            if output.status.success() { 1.0 } else { 0.0 },
+        );
         // In a real implementation, we would parse the output to get coverage
         metrics.insert("coverage".to_string(), 0.0);
 
-        Ok(metrics)
+        Ok(StageOutput {
+            metrics,
+            artifacts: failing_tests(&String::from_utf8_lossy(&output.stdout)),
+        })
     }
 }
 
+/// Names of the tests libtest reported as failed, e.g. from
+/// `test sharding::split ... FAILED`
+pub fn failing_tests(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            line.trim()
+                .strip_prefix("test ")?
+                .strip_suffix(" ... FAILED")
+                .map(str::to_string)
+        })
+        .collect()
+}
+
 /// Performance benchmark validation stage
 #[derive(Debug, Clone)]
 pub struct PerformanceBenchmarkStage;
@@ -344,7 +738,7 @@ impl ValidationStage for PerformanceBenchmarkStage {
 
     fn validate(&self, _modification: &Modification) -> Result<HashMap<String, f32>> {
         // In a real implementation, this would run performance benchmarks
-        let output = std::process::Command::new("cargo")
+        let _output = std::process::Command::new("cargo")
             .arg("bench")
             .arg("--no-run")
             .output()?;
@@ -370,13 +764,14 @@ impl ValidationStage for SecurityValidationStage {
 
     fn validate(&self, _modification: &Modification) -> Result<HashMap<String, f32>> {
         // In a real implementation, this would run security checks
-        let output = std::process::Command::new("cargo")
-            .arg("audit")
-            .output()?;
+        let output = std::process::Command::new("cargo").arg("audit").output()?;
 
         let mut metrics = HashMap::new();
         // In a real implementation, we would parse the output of cargo audit
-        metrics.insert("vulnerability_score".to_string(), if output.status.success() { 0.0 } else { 1.0 }); // Lower is better
+        metrics.insert(
+            "vulnerability_score".to_string(),
+            if output.status.success() { 0.0 } else { 1.0 },
+        ); // Lower is better
         metrics.insert("compliance_score".to_string(), 0.0);
 
         Ok(metrics)
@@ -399,6 +794,12 @@ impl std::fmt::Debug for MultiLanguageValidationStage {
     }
 }
 
+impl Default for MultiLanguageValidationStage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MultiLanguageValidationStage {
     pub fn new() -> Self {
         Self {
@@ -409,6 +810,23 @@ impl MultiLanguageValidationStage {
     pub fn add_language_handler(&mut self, language: &str, handler: Box<dyn ValidationStage>) {
         self.language_handlers.insert(language.to_string(), handler);
     }
+
+    /// Handle Python, JavaScript, TypeScript and Go by running their changes
+    /// in `runner`'s sandbox
+    pub fn with_sandbox(mut self, runner: Arc<SandboxRunner>) -> Self {
+        for language in [
+            ProgrammingLanguage::Python,
+            ProgrammingLanguage::JavaScript,
+            ProgrammingLanguage::TypeScript,
+            ProgrammingLanguage::Go,
+        ] {
+            self.add_language_handler(
+                language.as_str(),
+                Box::new(LanguageSandboxStage::new(runner.clone(), language)),
+            );
+        }
+        self
+    }
 }
 
 impl ValidationStage for MultiLanguageValidationStage {
@@ -419,21 +837,20 @@ impl ValidationStage for MultiLanguageValidationStage {
     fn validate(&self, modification: &Modification) -> Result<HashMap<String, f32>> {
         let mut all_metrics = HashMap::new();
 
-        // Determine the language for each file in the modification
+        // Determine the languages in the modification; each handler sees the
+        // whole modification, so it runs once per language
+        let mut languages = Vec::new();
         for change in &modification.code_changes {
-            let extension = change.file_path.split('.').last().unwrap_or("");
-            let language = match extension {
-                "rs" => "rust",
-                "py" => "python",
-                "js" => "javascript",
-                "ts" => "typescript",
-                "go" => "go",
-                "java" => "java",
-                "cs" => "csharp",
-                "cpp" | "cc" | "cxx" => "cpp",
-                _ => "unknown",
+            let language = match language_of(&change.file_path) {
+                Some(language) => language.as_str(),
+                None => "unknown",
             };
+            if !languages.contains(&language) {
+                languages.push(language);
+            }
+        }
 
+        for language in languages {
             if let Some(handler) = self.language_handlers.get(language) {
                 // Run the language-specific validator
                 match handler.validate(modification) {
@@ -0,0 +1,423 @@
+//! All-or-nothing application of a modification's code changes.
+//!
+//! Changes are first written into a scratch copy of the project and the
+//! build is checked there. Only if it passes are the files swapped into the
+//! real project, each through an atomic rename; if any swap fails the files
+//! already swapped are restored, so the project never ends up half-modified.
+//!
+//! In sandbox mode ([`WorkspaceApplier::sandboxed`]) the scratch copy is a
+//! detached `git worktree` of the project's repository, overlaid with the
+//! project's uncommitted files, and both `cargo build` and `cargo test` have
+//! to pass there before anything is promoted.
+//!
+//! Checks build into their own target directory, by default
+//! `target/darwin-workspace` under the project, so unverified code never
+//! replaces the artifacts of the project's own build while still reusing a
+//! cache from earlier checks.
+
+use anyhow::{anyhow, Context, Result};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::darwin::self_improvement::CodeChange;
+use crate::darwin::tools::{resolve_path, SKIPPED_DIRS};
+
+/// Characters of build output kept in error messages
+const MAX_BUILD_OUTPUT_CHARS: usize = 4000;
+
+/// Applies code changes to a project atomically after verifying the build
+#[derive(Debug, Clone)]
+pub struct WorkspaceApplier {
+    root: PathBuf,
+    /// Program and arguments run in the scratch workspace; `None` skips the check
+    build_command: Option<(String, Vec<String>)>,
+    /// Run after the build check passes; `None` skips the tests
+    test_command: Option<(String, Vec<String>)>,
+    /// Check in a git worktree instead of a plain copy of the project
+    git_worktree: bool,
+    /// `CARGO_TARGET_DIR` for the checks, kept apart from the project's own
+    target_dir: PathBuf,
+    timeout: Duration,
+}
+
+/// Original contents of the files a successful apply replaced
+#[derive(Debug, Clone)]
+pub struct AppliedChanges {
+    /// Each target path and what it held before, `None` if it was created
+    pub files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl AppliedChanges {
+    /// Put every file back the way it was before the apply
+    pub fn revert(&self) -> Result<()> {
+        let mut failures = Vec::new();
+        for (path, original) in self.files.iter().rev() {
+            let result = match original {
+                Some(content) => std::fs::write(path, content),
+                None => std::fs::remove_file(path),
+            };
+            if let Err(e) = result {
+                failures.push(format!("{}: {}", path.display(), e));
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("Failed to revert {}", failures.join(", ")))
+        }
+    }
+}
+
+impl WorkspaceApplier {
+    /// Apply changes to the project at `root`, checking them with `cargo check`
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            target_dir: root.join("target").join("darwin-workspace"),
+            root,
+            build_command: Some((
+                "cargo".to_string(),
+                vec!["check".to_string(), "--quiet".to_string()],
+            )),
+            test_command: None,
+            git_worktree: false,
+            timeout: Duration::from_secs(600),
+        }
+    }
+
+    /// Apply changes to the project at `root` only after `cargo build` and
+    /// `cargo test` pass in a git worktree of it
+    pub fn sandboxed(root: PathBuf) -> Self {
+        Self::new(root)
+            .with_git_worktree()
+            .with_build_command("cargo", &["build", "--quiet"])
+            .with_test_command("cargo", &["test", "--quiet"])
+    }
+
+    pub fn with_build_command(mut self, program: &str, args: &[&str]) -> Self {
+        self.build_command = Some((
+            program.to_string(),
+            args.iter().map(|a| a.to_string()).collect(),
+        ));
+        self
+    }
+
+    /// Swap files in without a build check, e.g. for non-code projects
+    pub fn without_build_check(mut self) -> Self {
+        self.build_command = None;
+        self
+    }
+
+    pub fn with_test_command(mut self, program: &str, args: &[&str]) -> Self {
+        self.test_command = Some((
+            program.to_string(),
+            args.iter().map(|a| a.to_string()).collect(),
+        ));
+        self
+    }
+
+    /// Check changes in a detached worktree of the git repository holding
+    /// the project, so build scripts that ask git about the checkout see a
+    /// real one
+    pub fn with_git_worktree(mut self) -> Self {
+        self.git_worktree = true;
+        self
+    }
+
+    /// Build checks in `dir` instead of `target/darwin-workspace`
+    pub fn with_target_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.target_dir = dir.into();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Stage, verify and swap in all `changes`. On error the project is left
+    /// exactly as it was.
+    pub async fn apply(&self, changes: &[CodeChange]) -> Result<AppliedChanges> {
+        if changes.is_empty() {
+            return Err(anyhow!("Modification has no code changes"));
+        }
+
+        let mut targets = Vec::with_capacity(changes.len());
+        for change in changes {
+            let target = resolve_path(&self.root, &change.file_path)
+                .with_context(|| format!("Invalid change path {}", change.file_path))?;
+            if targets.contains(&target) {
+                return Err(anyhow!("{} is changed more than once", change.file_path));
+            }
+            check_unchanged(change, std::fs::read(&target).ok().as_deref())?;
+            targets.push(target);
+        }
+
+        if self.build_command.is_some() || self.test_command.is_some() {
+            if self.git_worktree {
+                self.check_in_worktree(changes).await?;
+            } else {
+                let scratch =
+                    std::env::temp_dir().join(format!("darwin-workspace-{}", Uuid::new_v4()));
+                let checked = self.check_in_scratch(&scratch, changes).await;
+                if let Err(e) = std::fs::remove_dir_all(&scratch) {
+                    warn!("Failed to remove workspace {}: {}", scratch.display(), e);
+                }
+                checked?;
+            }
+        }
+
+        let applied = self.swap_in(changes, &targets)?;
+        info!(
+            "Applied {} file changes to {}",
+            changes.len(),
+            self.root.display()
+        );
+        Ok(applied)
+    }
+
+    /// Undo previously applied `changes`, restoring each file's original
+    /// content and removing files they created. Nothing is touched unless
+    /// every file still holds what its change wrote.
+    pub fn revert(&self, changes: &[CodeChange]) -> Result<()> {
+        let mut targets = Vec::with_capacity(changes.len());
+        for change in changes {
+            let target = resolve_path(&self.root, &change.file_path)
+                .with_context(|| format!("Invalid change path {}", change.file_path))?;
+            let current = std::fs::read_to_string(&target).ok();
+            if current.as_deref() != Some(change.modified_content.as_str()) {
+                return Err(anyhow!(
+                    "{} has changed since the modification was deployed",
+                    change.file_path
+                ));
+            }
+            targets.push(target);
+        }
+
+        // Applying the reverse lets a failed step put back what was reverted
+        let mut reverted = AppliedChanges { files: Vec::new() };
+        for (change, target) in changes.iter().zip(&targets).rev() {
+            let result = match &change.original_content {
+                Some(original) => std::fs::write(target, original),
+                None => std::fs::remove_file(target),
+            };
+            if let Err(e) = result {
+                if let Err(undo_error) = reverted.revert() {
+                    warn!("{}", undo_error);
+                }
+                return Err(anyhow!("Failed to revert {}: {}", target.display(), e));
+            }
+            reverted.files.push((
+                target.clone(),
+                Some(change.modified_content.clone().into_bytes()),
+            ));
+        }
+
+        info!(
+            "Reverted {} file changes in {}",
+            changes.len(),
+            self.root.display()
+        );
+        Ok(())
+    }
+
+    /// Check out `HEAD` of the project's repository into a temporary
+    /// worktree and check the changes there
+    async fn check_in_worktree(&self, changes: &[CodeChange]) -> Result<()> {
+        let toplevel = git(&self.root, ["rev-parse", "--show-toplevel"])
+            .await
+            .with_context(|| format!("{} is not in a git repository", self.root.display()))?;
+        let toplevel = PathBuf::from(toplevel.trim()).canonicalize()?;
+        // The project may be a subdirectory of the repository
+        let project = self.root.canonicalize()?;
+        let relative = project.strip_prefix(&toplevel)?.to_path_buf();
+
+        let worktree = std::env::temp_dir().join(format!("darwin-worktree-{}", Uuid::new_v4()));
+        git(
+            &toplevel,
+            [
+                OsStr::new("worktree"),
+                OsStr::new("add"),
+                OsStr::new("--detach"),
+                worktree.as_os_str(),
+                OsStr::new("HEAD"),
+            ],
+        )
+        .await
+        .context("Failed to create sandbox worktree")?;
+
+        let checked = self
+            .check_in_scratch(&worktree.join(relative), changes)
+            .await;
+
+        let removed = git(
+            &toplevel,
+            [
+                OsStr::new("worktree"),
+                OsStr::new("remove"),
+                OsStr::new("--force"),
+                worktree.as_os_str(),
+            ],
+        )
+        .await;
+        if let Err(e) = removed {
+            warn!("Failed to remove worktree {}: {}", worktree.display(), e);
+            let _ = std::fs::remove_dir_all(&worktree);
+            let _ = git(&toplevel, ["worktree", "prune"]).await;
+        }
+        checked
+    }
+
+    /// Bring `scratch` up to date with the project, write the changes into it
+    /// and run the build and test commands there
+    async fn check_in_scratch(&self, scratch: &Path, changes: &[CodeChange]) -> Result<()> {
+        copy_tree(&self.root, scratch)?;
+        for change in changes {
+            let path = resolve_path(scratch, &change.file_path)?;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, &change.modified_content)?;
+        }
+
+        if let Some(command) = &self.build_command {
+            self.run_step("Build check", command, scratch).await?;
+        }
+        if let Some(command) = &self.test_command {
+            self.run_step("Test run", command, scratch).await?;
+        }
+        Ok(())
+    }
+
+    async fn run_step(
+        &self,
+        step: &str,
+        (program, args): &(String, Vec<String>),
+        dir: &Path,
+    ) -> Result<()> {
+        let mut command = tokio::process::Command::new(program);
+        command
+            .args(args)
+            .current_dir(dir)
+            // A cache of its own, so a half-verified build never lands in
+            // the project's target directory
+            .env("CARGO_TARGET_DIR", &self.target_dir)
+            .kill_on_drop(true);
+        let output = tokio::time::timeout(self.timeout, command.output())
+            .await
+            .map_err(|_| anyhow!("{} timed out after {:?}", step, self.timeout))??;
+
+        if output.status.success() {
+            return Ok(());
+        }
+        let mut report = String::from_utf8_lossy(&output.stderr).into_owned();
+        report.push_str(&String::from_utf8_lossy(&output.stdout));
+        let chars: Vec<char> = report.chars().collect();
+        let tail: String = chars[chars.len().saturating_sub(MAX_BUILD_OUTPUT_CHARS)..]
+            .iter()
+            .collect();
+        Err(anyhow!("{} failed:\n{}", step, tail))
+    }
+
+    /// Write each change beside its target, then rename them into place,
+    /// restoring the originals if any step fails
+    fn swap_in(&self, changes: &[CodeChange], targets: &[PathBuf]) -> Result<AppliedChanges> {
+        let mut staged = Vec::with_capacity(changes.len());
+        for (change, target) in changes.iter().zip(targets) {
+            let file_name = target
+                .file_name()
+                .ok_or_else(|| anyhow!("{} is not a file", change.file_path))?;
+            let staged_path =
+                target.with_file_name(format!(".{}.darwin-staged", file_name.to_string_lossy()));
+            let written = target
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&staged_path, &change.modified_content));
+            if let Err(e) = written {
+                discard(&staged);
+                return Err(anyhow!("Failed to stage {}: {}", change.file_path, e));
+            }
+            staged.push(staged_path);
+        }
+
+        let mut applied = AppliedChanges { files: Vec::new() };
+        for ((change, staged_path), target) in changes.iter().zip(&staged).zip(targets) {
+            // The build check can take minutes; look again right before the
+            // rename so edits made meanwhile aren't overwritten
+            let original = std::fs::read(target).ok();
+            let swapped = check_unchanged(change, original.as_deref()).and_then(|_| {
+                std::fs::rename(staged_path, target)
+                    .map_err(|e| anyhow!("Failed to replace {}: {}", target.display(), e))
+            });
+            if let Err(e) = swapped {
+                if let Err(revert_error) = applied.revert() {
+                    warn!("{}", revert_error);
+                }
+                discard(&staged);
+                return Err(e);
+            }
+            applied.files.push((target.clone(), original));
+        }
+
+        Ok(applied)
+    }
+}
+
+/// Run git in `dir` and return its standard output
+async fn git<I, S>(dir: &Path, args: I) -> Result<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let output = tokio::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Refuse to overwrite edits made since the change was generated, including
+/// a file that appeared where the change creates one
+fn check_unchanged(change: &CodeChange, current: Option<&[u8]>) -> Result<()> {
+    match (&change.original_content, current) {
+        (None, Some(_)) => Err(anyhow!("{} already exists", change.file_path)),
+        (Some(original), current) if current != Some(original.as_bytes()) => Err(anyhow!(
+            "{} has changed since the modification was generated",
+            change.file_path
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn discard(staged: &[PathBuf]) {
+    for path in staged {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Copy a project into `dest`, leaving out VCS and build directories
+pub(crate) fn copy_tree(src: &Path, dest: &Path) -> Result<()> {
+    std::fs::create_dir_all(dest)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if !SKIPPED_DIRS.iter().any(|skip| name == *skip) {
+                copy_tree(&entry.path(), &dest.join(&name))?;
+            }
+        } else if file_type.is_file() {
+            std::fs::copy(entry.path(), dest.join(&name))?;
+        }
+    }
+    Ok(())
+}
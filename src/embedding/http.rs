@@ -0,0 +1,103 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::embedding::EmbeddingProvider;
+
+#[derive(Serialize)]
+struct EmbeddingsRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// Calls an OpenAI-compatible embeddings endpoint
+pub struct HttpEmbeddingProvider {
+    client: reqwest::Client,
+    endpoint: String,
+    model: String,
+    version: String,
+    dimensions: usize,
+    api_key: Option<String>,
+}
+
+impl HttpEmbeddingProvider {
+    /// `base_url` is the API root, e.g. `https://api.openai.com/v1`
+    pub fn new(base_url: &str, model: &str, dimensions: usize, api_key: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint: format!("{}/embeddings", base_url.trim_end_matches('/')),
+            model: model.to_string(),
+            version: "1".to_string(),
+            dimensions,
+            api_key,
+        }
+    }
+
+    /// Set the version recorded for vectors from this provider
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for HttpEmbeddingProvider {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut request = self.client.post(&self.endpoint).json(&EmbeddingsRequest {
+            model: &self.model,
+            input: texts,
+        });
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let mut response: EmbeddingsResponse =
+            request.send().await?.error_for_status()?.json().await?;
+        if response.data.len() != texts.len() {
+            return Err(anyhow!(
+                "Embedding service returned {} vectors for {} inputs",
+                response.data.len(),
+                texts.len()
+            ));
+        }
+
+        response.data.sort_by_key(|d| d.index);
+        let embeddings: Vec<Vec<f32>> = response.data.into_iter().map(|d| d.embedding).collect();
+        if let Some(bad) = embeddings.iter().find(|e| e.len() != self.dimensions) {
+            return Err(anyhow!(
+                "Embedding has {} dimensions, expected {}",
+                bad.len(),
+                self.dimensions
+            ));
+        }
+
+        Ok(embeddings)
+    }
+}
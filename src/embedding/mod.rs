@@ -0,0 +1,106 @@
+//! Text embedding providers
+//!
+//! [`EmbeddingProvider`] turns text into vectors for ingestion and search.
+//! [`HashingEmbedder`] is a deterministic local provider useful for tests
+//! and offline deployments; [`HttpEmbeddingProvider`] calls an
+//! OpenAI-compatible `/v1/embeddings` endpoint. [`EmbeddingRegistry`] keeps
+//! each model version's vectors in a separate space.
+
+pub mod http;
+pub mod registry;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+pub use http::HttpEmbeddingProvider;
+pub use registry::{EmbeddingRegistry, EmbeddingSpace, ReembedJob, ReembedStatus, SpaceStatus};
+
+/// Metadata key recording which model produced a vector
+pub const EMBEDDING_MODEL_KEY: &str = "embedding.model";
+
+/// Produces embeddings for text
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Identifier of the model producing the embeddings
+    fn model(&self) -> &str;
+
+    /// Version of the model; vectors from different versions aren't comparable
+    fn version(&self) -> &str {
+        "1"
+    }
+
+    /// `model@version`, as stored under [`EMBEDDING_MODEL_KEY`]
+    fn model_id(&self) -> String {
+        format!("{}@{}", self.model(), self.version())
+    }
+
+    /// Dimensions of the produced vectors
+    fn dimensions(&self) -> usize;
+
+    /// Embed a batch of texts, returning one vector per input
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Feature-hashing embedder: each lowercase token is hashed into a bucket
+/// with a sign, and the result is L2-normalized. Cheap and deterministic,
+/// but only captures lexical overlap.
+#[derive(Debug, Clone)]
+pub struct HashingEmbedder {
+    model: String,
+    dimensions: usize,
+}
+
+impl HashingEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            model: format!("hashing-{}", dimensions),
+            dimensions: dimensions.max(1),
+        }
+    }
+
+    /// Embed a single text
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let mut values = vec![0.0f32; self.dimensions];
+        for token in text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|t| !t.is_empty())
+        {
+            let hash = fnv1a(token.to_lowercase().as_bytes());
+            let bucket = (hash % self.dimensions as u64) as usize;
+            let sign = if (hash >> 63) == 0 { 1.0 } else { -1.0 };
+            values[bucket] += sign;
+        }
+
+        let norm = values.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            for v in &mut values {
+                *v /= norm;
+            }
+        }
+        values
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[async_trait]
+impl EmbeddingProvider for HashingEmbedder {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|t| self.embed_text(t)).collect())
+    }
+}
@@ -0,0 +1,576 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::core::metrics::MetricsCollector;
+use crate::core::vector::Vector;
+use crate::embedding::{EmbeddingProvider, EMBEDDING_MODEL_KEY};
+use crate::nerv::tasks;
+use crate::query::fusion::{self, FusionStrategy};
+use crate::query::synonyms::ExpansionMode;
+use crate::sharding::manager::ShardManager;
+use crate::sharding::vector_index::{DistanceMetric, SearchResult};
+
+/// Metadata key holding the source text, needed to re-embed a vector
+pub const SOURCE_TEXT_KEY: &str = "text";
+
+/// Number of texts embedded per request during re-embedding
+const REEMBED_BATCH_SIZE: usize = 64;
+
+/// Lifecycle of an embedding space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpaceStatus {
+    /// Being populated by a re-embedding job; receives writes but not reads
+    Building,
+    /// Fully populated and ready to be activated
+    Ready,
+    /// Serves reads for the collection
+    Active,
+    /// Superseded by a newer space
+    Retired,
+}
+
+/// A collection's vectors as embedded by one model version, stored in its
+/// own shard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingSpace {
+    pub shard_id: Uuid,
+    pub collection: String,
+    pub model_id: String,
+    pub dimensions: usize,
+    pub status: SpaceStatus,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ReembedStatus {
+    Running,
+    Completed,
+    Failed { error: String },
+}
+
+/// Progress of migrating a collection to a new model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReembedJob {
+    pub id: Uuid,
+    pub collection: String,
+    pub source_model: String,
+    pub target_model: String,
+    pub total: usize,
+    pub processed: usize,
+    /// Vectors without source text, which can't be re-embedded
+    pub skipped: usize,
+    pub status: ReembedStatus,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Registry of embedding models and the versioned spaces built with them.
+///
+/// Each space is a shard bound to a single model, so vectors from different
+/// model versions never share an index. During an upgrade the new space is
+/// built side by side with the active one, receives the same writes, and is
+/// switched in with [`activate_space`](Self::activate_space).
+pub struct EmbeddingRegistry {
+    shard_manager: Arc<ShardManager>,
+    metrics: Arc<MetricsCollector>,
+    providers: RwLock<HashMap<String, Arc<dyn EmbeddingProvider>>>,
+    spaces: RwLock<HashMap<String, Vec<EmbeddingSpace>>>,
+    jobs: RwLock<HashMap<Uuid, ReembedJob>>,
+}
+
+impl EmbeddingRegistry {
+    pub fn new(shard_manager: Arc<ShardManager>, metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            shard_manager,
+            metrics,
+            providers: RwLock::new(HashMap::new()),
+            spaces: RwLock::new(HashMap::new()),
+            jobs: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Register a model, returning its `model@version` ID
+    pub async fn register_provider(&self, provider: Arc<dyn EmbeddingProvider>) -> String {
+        let model_id = provider.model_id();
+        self.providers
+            .write()
+            .await
+            .insert(model_id.clone(), provider);
+        info!("Registered embedding model {}", model_id);
+        model_id
+    }
+
+    pub async fn provider(&self, model_id: &str) -> Result<Arc<dyn EmbeddingProvider>> {
+        self.providers
+            .read()
+            .await
+            .get(model_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Embedding model {} is not registered", model_id))
+    }
+
+    /// The provider for a model named as a client would: its exact
+    /// `model@version` ID, or just the model name (taking its latest
+    /// version). Any name resolves when only one model is registered, so
+    /// tools written for a hosted model can use the local one unchanged.
+    pub async fn resolve_provider(&self, model: &str) -> Result<Arc<dyn EmbeddingProvider>> {
+        let providers = self.providers.read().await;
+        if let Some(provider) = providers.get(model) {
+            return Ok(provider.clone());
+        }
+        let named = providers
+            .iter()
+            .filter(|(_, provider)| provider.model() == model)
+            .max_by(|(a, _), (b, _)| a.cmp(b));
+        match named {
+            Some((_, provider)) => Ok(provider.clone()),
+            None if providers.len() == 1 => Ok(providers.values().next().unwrap().clone()),
+            None => Err(anyhow!("Embedding model {} is not registered", model)),
+        }
+    }
+
+    /// IDs of all registered models
+    pub async fn models(&self) -> Vec<String> {
+        self.providers.read().await.keys().cloned().collect()
+    }
+
+    /// Create a space for a collection. The first space of a collection is
+    /// active immediately; later ones start out ready for activation.
+    pub async fn create_space(
+        &self,
+        collection: &str,
+        model_id: &str,
+        distance_metric: DistanceMetric,
+    ) -> Result<EmbeddingSpace> {
+        self.create_space_with_status(collection, model_id, distance_metric, None)
+            .await
+    }
+
+    async fn create_space_with_status(
+        &self,
+        collection: &str,
+        model_id: &str,
+        distance_metric: DistanceMetric,
+        status: Option<SpaceStatus>,
+    ) -> Result<EmbeddingSpace> {
+        let provider = self.provider(model_id).await?;
+
+        let mut spaces = self.spaces.write().await;
+        let existing = spaces.entry(collection.to_string()).or_default();
+        if existing.iter().any(|s| s.model_id == model_id) {
+            return Err(anyhow!(
+                "Collection {} already has a space for model {}",
+                collection,
+                model_id
+            ));
+        }
+
+        let shard_name = format!("{}/{}", collection, model_id);
+        let shard_id = self.shard_manager.create_shard(&shard_name).await?;
+        self.shard_manager
+            .create_vector_index(
+                shard_id,
+                &shard_name,
+                provider.dimensions(),
+                distance_metric,
+            )
+            .await?;
+        self.shard_manager
+            .bind_embedding_model(shard_id, model_id)
+            .await?;
+
+        let status = status.unwrap_or(if existing.is_empty() {
+            SpaceStatus::Active
+        } else {
+            SpaceStatus::Ready
+        });
+        let space = EmbeddingSpace {
+            shard_id,
+            collection: collection.to_string(),
+            model_id: model_id.to_string(),
+            dimensions: provider.dimensions(),
+            status,
+            created_at: chrono::Utc::now(),
+        };
+        existing.push(space.clone());
+
+        info!(
+            "Created {:?} embedding space for {} with model {}",
+            status, collection, model_id
+        );
+        Ok(space)
+    }
+
+    /// All spaces of a collection
+    pub async fn spaces(&self, collection: &str) -> Vec<EmbeddingSpace> {
+        self.spaces
+            .read()
+            .await
+            .get(collection)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The space currently serving reads for a collection
+    pub async fn active_space(&self, collection: &str) -> Result<EmbeddingSpace> {
+        self.spaces(collection)
+            .await
+            .into_iter()
+            .find(|s| s.status == SpaceStatus::Active)
+            .ok_or_else(|| anyhow!("Collection {} has no active embedding space", collection))
+    }
+
+    /// Switch reads to another space, retiring the previously active one
+    pub async fn activate_space(&self, collection: &str, model_id: &str) -> Result<()> {
+        let mut spaces = self.spaces.write().await;
+        let spaces = spaces
+            .get_mut(collection)
+            .ok_or_else(|| anyhow!("Collection {} has no embedding spaces", collection))?;
+
+        let target = spaces
+            .iter()
+            .position(|s| s.model_id == model_id)
+            .ok_or_else(|| anyhow!("Collection {} has no space for {}", collection, model_id))?;
+        match spaces[target].status {
+            SpaceStatus::Ready | SpaceStatus::Retired => {}
+            SpaceStatus::Active => return Ok(()),
+            SpaceStatus::Building => {
+                return Err(anyhow!(
+                    "Space for {} is still being built and can't be activated",
+                    model_id
+                ))
+            }
+        }
+
+        for space in spaces.iter_mut() {
+            if space.status == SpaceStatus::Active {
+                space.status = SpaceStatus::Retired;
+            }
+        }
+        spaces[target].status = SpaceStatus::Active;
+
+        info!("Activated model {} for collection {}", model_id, collection);
+        Ok(())
+    }
+
+    /// Spaces that receive writes: the active one and any being built
+    async fn writable_spaces(&self, collection: &str) -> Result<Vec<EmbeddingSpace>> {
+        let spaces: Vec<EmbeddingSpace> = self
+            .spaces(collection)
+            .await
+            .into_iter()
+            .filter(|s| matches!(s.status, SpaceStatus::Active | SpaceStatus::Building))
+            .collect();
+        if !spaces.iter().any(|s| s.status == SpaceStatus::Active) {
+            return Err(anyhow!(
+                "Collection {} has no active embedding space",
+                collection
+            ));
+        }
+        Ok(spaces)
+    }
+
+    /// Embed a text and store it in every writable space of the collection
+    /// under one shared vector ID. The text is kept as metadata so it can be
+    /// re-embedded later.
+    pub async fn add_text(
+        &self,
+        collection: &str,
+        text: &str,
+        metadata: Option<HashMap<String, String>>,
+    ) -> Result<Uuid> {
+        self.store_text(collection, text, metadata, None).await
+    }
+
+    /// Like [`add_text`](Self::add_text) for a text already embedded by
+    /// `model_id`, e.g. by the embeddings proxy. The embedding is reused
+    /// for that model's space; other writable spaces embed the text
+    /// themselves.
+    pub async fn add_embedded_text(
+        &self,
+        collection: &str,
+        text: &str,
+        model_id: &str,
+        embedding: Vec<f32>,
+    ) -> Result<Uuid> {
+        self.store_text(collection, text, None, Some((model_id, embedding)))
+            .await
+    }
+
+    async fn store_text(
+        &self,
+        collection: &str,
+        text: &str,
+        metadata: Option<HashMap<String, String>>,
+        embedded: Option<(&str, Vec<f32>)>,
+    ) -> Result<Uuid> {
+        let id = Uuid::new_v4();
+        let mut metadata = metadata.unwrap_or_default();
+        metadata.insert(SOURCE_TEXT_KEY.to_string(), text.to_string());
+
+        for space in self.writable_spaces(collection).await? {
+            let values = match &embedded {
+                Some((model_id, values)) if *model_id == space.model_id => values.clone(),
+                _ => self
+                    .provider(&space.model_id)
+                    .await?
+                    .embed(&[text.to_string()])
+                    .await?
+                    .pop()
+                    .ok_or_else(|| anyhow!("Embedding model returned no vector"))?,
+            };
+            let mut metadata = metadata.clone();
+            metadata.insert(EMBEDDING_MODEL_KEY.to_string(), space.model_id.clone());
+            self.shard_manager
+                .add_vector_with_id(space.shard_id, id, Vector::new(values), Some(metadata))
+                .await?;
+        }
+
+        Ok(id)
+    }
+
+    /// Remove a vector from every writable space of the collection
+    pub async fn remove(&self, collection: &str, id: Uuid) -> Result<()> {
+        for space in self.writable_spaces(collection).await? {
+            let index = self.shard_manager.get_vector_index(space.shard_id).await?;
+            if index.get(id).await.is_some() {
+                self.shard_manager.remove_vector(space.shard_id, id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Search the active space, embedding the query with its model
+    pub async fn search_text(
+        &self,
+        collection: &str,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        let space = self.active_space(collection).await?;
+        let provider = self.provider(&space.model_id).await?;
+        let values = provider
+            .embed(&[query.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow!("Embedding model returned no vector"))?;
+        self.shard_manager
+            .search_vectors(space.shard_id, &Vector::new(values), limit)
+            .await
+    }
+
+    /// Search the active space with several phrasings of one query, e.g.
+    /// the variants produced by a synonym dictionary. In
+    /// [`ExpansionMode::Fusion`] their results are merged with `fusion`.
+    pub async fn search_texts(
+        &self,
+        collection: &str,
+        queries: &[String],
+        limit: usize,
+        mode: ExpansionMode,
+        fusion: FusionStrategy,
+    ) -> Result<Vec<SearchResult>> {
+        if queries.len() == 1 {
+            return self.search_text(collection, &queries[0], limit).await;
+        }
+        let space = self.active_space(collection).await?;
+        let provider = self.provider(&space.model_id).await?;
+        let embeddings = provider.embed(queries).await?;
+        if embeddings.len() != queries.len() {
+            return Err(anyhow!(
+                "Embedding model returned {} vectors for {} queries",
+                embeddings.len(),
+                queries.len()
+            ));
+        }
+
+        match mode {
+            ExpansionMode::Union => {
+                let mut values = vec![0.0; space.dimensions];
+                for embedding in &embeddings {
+                    for (sum, value) in values.iter_mut().zip(embedding) {
+                        *sum += value / embeddings.len() as f32;
+                    }
+                }
+                self.shard_manager
+                    .search_vectors(space.shard_id, &Vector::new(values), limit)
+                    .await
+            }
+            ExpansionMode::Fusion => {
+                let index = self.shard_manager.get_vector_index(space.shard_id).await?;
+                let lower_is_better = index.distance_metric().is_lower_better();
+                let mut lists = Vec::with_capacity(embeddings.len());
+                for values in embeddings {
+                    lists.push(
+                        self.shard_manager
+                            .search_vectors(space.shard_id, &Vector::new(values), limit)
+                            .await?,
+                    );
+                }
+                Ok(fusion::fuse(lists, fusion, lower_is_better, limit))
+            }
+        }
+    }
+
+    /// Start migrating a collection to a new model. A space for the target
+    /// model is built from the source text of every vector in the active
+    /// space; once complete it is ready to activate.
+    pub async fn start_reembedding(
+        self: Arc<Self>,
+        collection: &str,
+        target_model: &str,
+        distance_metric: DistanceMetric,
+    ) -> Result<Uuid> {
+        let source = self.active_space(collection).await?;
+        let target = self
+            .create_space_with_status(
+                collection,
+                target_model,
+                distance_metric,
+                Some(SpaceStatus::Building),
+            )
+            .await?;
+
+        let job_id = Uuid::new_v4();
+        self.jobs.write().await.insert(
+            job_id,
+            ReembedJob {
+                id: job_id,
+                collection: collection.to_string(),
+                source_model: source.model_id.clone(),
+                target_model: target_model.to_string(),
+                total: 0,
+                processed: 0,
+                skipped: 0,
+                status: ReembedStatus::Running,
+                started_at: chrono::Utc::now(),
+                finished_at: None,
+            },
+        );
+
+        info!(
+            "Started re-embedding {} from {} to {}",
+            collection, source.model_id, target_model
+        );
+        let registry = self.clone();
+        tasks::spawn(
+            "embedding",
+            format!("re-embedding job {}", job_id),
+            async move {
+                let result = registry.run_reembedding(job_id, &source, &target).await;
+                let status = match result {
+                    Ok(()) => {
+                        registry
+                            .set_space_status(
+                                &target.collection,
+                                &target.model_id,
+                                SpaceStatus::Ready,
+                            )
+                            .await;
+                        ReembedStatus::Completed
+                    }
+                    Err(e) => {
+                        error!("Re-embedding job {} failed: {}", job_id, e);
+                        ReembedStatus::Failed {
+                            error: e.to_string(),
+                        }
+                    }
+                };
+                if let Some(job) = registry.jobs.write().await.get_mut(&job_id) {
+                    job.status = status;
+                    job.finished_at = Some(chrono::Utc::now());
+                }
+            },
+        );
+
+        Ok(job_id)
+    }
+
+    async fn run_reembedding(
+        &self,
+        job_id: Uuid,
+        source: &EmbeddingSpace,
+        target: &EmbeddingSpace,
+    ) -> Result<()> {
+        let provider = self.provider(&target.model_id).await?;
+        let source_index = self.shard_manager.get_vector_index(source.shard_id).await?;
+        let target_index = self.shard_manager.get_vector_index(target.shard_id).await?;
+
+        let entries = source_index.entries().await;
+        if let Some(job) = self.jobs.write().await.get_mut(&job_id) {
+            job.total = entries.len();
+        }
+
+        for chunk in entries.chunks(REEMBED_BATCH_SIZE) {
+            let mut batch = Vec::new();
+            let mut skipped = 0;
+            for entry in chunk {
+                // Vectors written since the job started were dual-written
+                if target_index.get(entry.id).await.is_some() {
+                    continue;
+                }
+                match entry.metadata.as_ref().and_then(|m| m.get(SOURCE_TEXT_KEY)) {
+                    Some(text) => batch.push((entry, text.clone())),
+                    None => skipped += 1,
+                }
+            }
+
+            let texts: Vec<String> = batch.iter().map(|(_, text)| text.clone()).collect();
+            let embeddings = provider.embed(&texts).await?;
+
+            for ((entry, _), values) in batch.iter().zip(embeddings) {
+                let mut metadata = entry.metadata.clone().unwrap_or_default();
+                metadata.insert(EMBEDDING_MODEL_KEY.to_string(), target.model_id.clone());
+                match self
+                    .shard_manager
+                    .add_vector_with_id(
+                        target.shard_id,
+                        entry.id,
+                        Vector::new(values),
+                        Some(metadata),
+                    )
+                    .await
+                {
+                    Ok(_) => {}
+                    // Lost a race with a dual write of the same vector
+                    Err(_) if target_index.get(entry.id).await.is_some() => {}
+                    Err(e) => return Err(e),
+                }
+            }
+
+            self.metrics
+                .increment_counter("embedding.reembed.processed", batch.len() as u64)
+                .await;
+            if let Some(job) = self.jobs.write().await.get_mut(&job_id) {
+                job.processed += chunk.len();
+                job.skipped += skipped;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn set_space_status(&self, collection: &str, model_id: &str, status: SpaceStatus) {
+        if let Some(spaces) = self.spaces.write().await.get_mut(collection) {
+            if let Some(space) = spaces.iter_mut().find(|s| s.model_id == model_id) {
+                space.status = status;
+            }
+        }
+    }
+
+    pub async fn get_job(&self, job_id: Uuid) -> Result<ReembedJob> {
+        self.jobs
+            .read()
+            .await
+            .get(&job_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Re-embedding job {} not found", job_id))
+    }
+}
@@ -1,10 +1,74 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::darwin::objectives::Objective;
+use crate::query::feedback::LabelledImpression;
+
+/// How well a modification's validation metrics meet the configured objectives
+#[derive(Debug, Clone, Default)]
+pub struct ObjectiveScore {
+    /// Weighted mean attainment (0-1) over the objectives that could be scored
+    pub total: f32,
+    /// Attainment per objective name
+    pub per_objective: HashMap<String, f32>,
+    /// Objectives without a matching validation metric
+    pub unmeasured: Vec<String>,
+}
+
+/// Recall estimated from the relevant results clients reported per query
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecallReport {
+    /// Queries with at least one relevant result reported
+    pub queries: usize,
+    /// Relevant results that were returned
+    pub found: usize,
+    /// Relevant results reported as missing
+    pub missed: usize,
+    /// Mean recall per query; 1.0 when no query was judged
+    pub recall: f32,
+}
+
+/// Significance level below which a difference is reported as significant
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// Two-proportion z-test of a difference between two success rates
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SignificanceTest {
+    /// Second rate minus the first
+    pub difference: f64,
+    pub z: f64,
+    /// Two-sided p-value; 1.0 when either sample is empty
+    pub p_value: f64,
+    pub significant: bool,
+}
+
+/// Standard normal CDF, via the Abramowitz-Stegun approximation of erf
+/// (error below 1.5e-7)
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
 
 #[derive(Debug)]
 pub struct Evaluation {
     // In a real implementation, this would hold the state for the evaluation engine.
 }
 
+impl Default for Evaluation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Evaluation {
     pub fn new() -> Self {
         Self {}
@@ -23,4 +87,129 @@ impl Evaluation {
         }
         improved
     }
+
+    /// Score validation metrics against objectives. Objectives whose
+    /// validation metric wasn't reported are listed as unmeasured.
+    pub fn score_objectives(
+        &self,
+        objectives: &[Objective],
+        metrics: &HashMap<String, f32>,
+    ) -> ObjectiveScore {
+        let mut score = ObjectiveScore::default();
+        let mut weighted = 0.0;
+        let mut total_weight = 0.0;
+
+        for objective in objectives {
+            let value = objective
+                .validation_metric
+                .as_ref()
+                .and_then(|key| metrics.get(key));
+            match value {
+                Some(value) => {
+                    let attainment = objective.attainment(*value as f64) as f32;
+                    score
+                        .per_objective
+                        .insert(objective.name.clone(), attainment);
+                    weighted += attainment * objective.weight;
+                    total_weight += objective.weight;
+                }
+                None => score.unmeasured.push(objective.name.clone()),
+            }
+        }
+
+        if total_weight > 0.0 {
+            score.total = weighted / total_weight;
+        }
+        score
+    }
+
+    /// Recall of searches according to client feedback: relevant results
+    /// shown against those reported as [missing](FeedbackLabel::Missing)
+    pub fn feedback_recall<'a>(
+        &self,
+        impressions: impl IntoIterator<Item = &'a LabelledImpression>,
+    ) -> RecallReport {
+        let mut report = RecallReport::default();
+        let mut total = 0.0;
+        for entry in impressions {
+            let shown: HashSet<&str> = entry
+                .impression
+                .results
+                .iter()
+                .map(|r| r.id.as_str())
+                .collect();
+            let relevant: HashSet<&str> = entry
+                .feedback
+                .iter()
+                .filter(|f| f.label.is_relevant())
+                .map(|f| f.result_id.as_str())
+                .collect();
+            if relevant.is_empty() {
+                continue;
+            }
+            let found = relevant.iter().filter(|id| shown.contains(*id)).count();
+            report.queries += 1;
+            report.found += found;
+            report.missed += relevant.len() - found;
+            total += found as f32 / relevant.len() as f32;
+        }
+        report.recall = if report.queries > 0 {
+            total / report.queries as f32
+        } else {
+            1.0
+        };
+        report
+    }
+
+    /// Whether the success rates of two samples differ, e.g. the click rates
+    /// of two experiment arms
+    pub fn compare_proportions(
+        &self,
+        successes_a: usize,
+        trials_a: usize,
+        successes_b: usize,
+        trials_b: usize,
+    ) -> SignificanceTest {
+        if trials_a == 0 || trials_b == 0 {
+            return SignificanceTest {
+                p_value: 1.0,
+                ..SignificanceTest::default()
+            };
+        }
+        let (n_a, n_b) = (trials_a as f64, trials_b as f64);
+        let (p_a, p_b) = (successes_a as f64 / n_a, successes_b as f64 / n_b);
+        let pooled = (successes_a + successes_b) as f64 / (n_a + n_b);
+        let se = (pooled * (1.0 - pooled) * (1.0 / n_a + 1.0 / n_b)).sqrt();
+        let difference = p_b - p_a;
+        if se == 0.0 {
+            // Both samples all successes or all failures
+            return SignificanceTest {
+                difference,
+                p_value: 1.0,
+                ..SignificanceTest::default()
+            };
+        }
+        let z = difference / se;
+        let p_value = 2.0 * (1.0 - normal_cdf(z.abs()));
+        SignificanceTest {
+            difference,
+            z,
+            p_value,
+            significant: p_value < SIGNIFICANCE_LEVEL,
+        }
+    }
+
+    /// Analysis for [`Hypothesis::generate`](crate::hypothesis::Hypothesis::generate)
+    /// from the recall over all searches and over slow ones. Reports without
+    /// judged queries are left out.
+    pub fn recall_analysis(&self, all: &RecallReport, slow: &RecallReport) -> HashMap<String, f32> {
+        let mut analysis = HashMap::new();
+        if all.queries > 0 {
+            analysis.insert("feedback_recall".to_string(), all.recall);
+        }
+        if slow.queries > 0 {
+            analysis.insert("slow_query_recall".to_string(), slow.recall);
+        }
+        analysis
+    }
 }
@@ -1,9 +1,338 @@
-/// Minimal DAO governance placeholder
-pub struct Dao {}
+//! DAO proposals and weighted voting.
+//!
+//! Each proposal type is decided with a [`WeightSource`]; the weight of a
+//! vote comes from that source's [`VotingPower`] provider when the vote is
+//! cast, so later changes to stake or reputation don't rewrite past votes.
+//! Proposals that pass are announced to subscribers such as the
+//! [`ProposalExecutor`](crate::governance::executor::ProposalExecutor).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, RwLock};
+use uuid::Uuid;
+
+use crate::governance::executor::{ExecutionRecord, ProposalAction};
+use crate::governance::voting::{EqualPower, VotingPower, WeightSource};
+use crate::utils::errors::GovernanceError;
+
+/// How proposals are decided
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaoConfig {
+    /// Weight source for proposal types without their own
+    pub default_weight_source: WeightSource,
+
+    /// Weight source per proposal type
+    #[serde(default)]
+    pub weight_sources: HashMap<String, WeightSource>,
+
+    /// Total weight, abstentions included, a proposal needs to be decided
+    pub quorum: f64,
+
+    /// Share of the for and against weight that must be in favour
+    pub pass_threshold: f64,
+}
+
+impl Default for DaoConfig {
+    fn default() -> Self {
+        Self {
+            default_weight_source: WeightSource::Equal,
+            weight_sources: HashMap::new(),
+            quorum: 1.0,
+            pass_threshold: 0.5,
+        }
+    }
+}
+
+impl DaoConfig {
+    pub fn weight_source(&self, proposal_type: &str) -> WeightSource {
+        self.weight_sources
+            .get(proposal_type)
+            .copied()
+            .unwrap_or(self.default_weight_source)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteChoice {
+    For,
+    Against,
+    Abstain,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vote {
+    pub voter: String,
+    pub choice: VoteChoice,
+    /// Voting power when the vote was cast
+    pub weight: f64,
+    pub cast_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    Open,
+    Passed,
+    Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proposal {
+    pub id: Uuid,
+    pub proposal_type: String,
+    pub title: String,
+    pub description: String,
+    pub proposer: String,
+    /// Fixed when the proposal is created
+    pub weight_source: WeightSource,
+    pub status: ProposalStatus,
+    pub votes: Vec<Vote>,
+    /// Run in order once the proposal passes
+    #[serde(default)]
+    pub actions: Vec<ProposalAction>,
+    pub created_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Set once the proposal's actions have run
+    #[serde(default)]
+    pub execution: Option<ExecutionRecord>,
+}
+
+/// Weighted vote totals for a proposal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tally {
+    pub weight_source: WeightSource,
+    pub for_weight: f64,
+    pub against_weight: f64,
+    pub abstain_weight: f64,
+    pub voters: usize,
+    pub quorum_reached: bool,
+    /// Whether the proposal would pass if closed now
+    pub passes: bool,
+}
+
+impl Tally {
+    fn of(proposal: &Proposal, config: &DaoConfig) -> Self {
+        let mut tally = Tally {
+            weight_source: proposal.weight_source,
+            for_weight: 0.0,
+            against_weight: 0.0,
+            abstain_weight: 0.0,
+            voters: proposal.votes.len(),
+            quorum_reached: false,
+            passes: false,
+        };
+        for vote in &proposal.votes {
+            match vote.choice {
+                VoteChoice::For => tally.for_weight += vote.weight,
+                VoteChoice::Against => tally.against_weight += vote.weight,
+                VoteChoice::Abstain => tally.abstain_weight += vote.weight,
+            }
+        }
+        let total = tally.for_weight + tally.against_weight + tally.abstain_weight;
+        let decisive = tally.for_weight + tally.against_weight;
+        tally.quorum_reached = total >= config.quorum;
+        tally.passes = tally.quorum_reached
+            && decisive > 0.0
+            && tally.for_weight / decisive > config.pass_threshold;
+        tally
+    }
+}
+
+/// DAO governance handler
+pub struct Dao {
+    config: DaoConfig,
+    providers: HashMap<WeightSource, Arc<dyn VotingPower>>,
+    proposals: RwLock<HashMap<Uuid, Proposal>>,
+    passed: broadcast::Sender<Uuid>,
+}
+
+/// Passed proposals buffered per subscriber
+const PASSED_CAPACITY: usize = 64;
+
+impl Default for Dao {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Dao {
-    /// Create a new DAO governance handler
+    /// Create a new DAO governance handler with one vote per agent
     pub fn new() -> Self {
-        Self {}
+        Self::with_config(DaoConfig::default())
+    }
+
+    pub fn with_config(config: DaoConfig) -> Self {
+        let mut providers: HashMap<WeightSource, Arc<dyn VotingPower>> = HashMap::new();
+        providers.insert(WeightSource::Equal, Arc::new(EqualPower));
+        Self {
+            config,
+            providers,
+            proposals: RwLock::new(HashMap::new()),
+            passed: broadcast::channel(PASSED_CAPACITY).0,
+        }
+    }
+
+    /// Use `provider` for proposals decided by its weight source
+    pub fn with_voting_power(mut self, provider: Arc<dyn VotingPower>) -> Self {
+        self.providers.insert(provider.source(), provider);
+        self
+    }
+
+    pub fn config(&self) -> &DaoConfig {
+        &self.config
+    }
+
+    fn provider(&self, source: WeightSource) -> Result<&Arc<dyn VotingPower>, GovernanceError> {
+        self.providers
+            .get(&source)
+            .ok_or(GovernanceError::NoVotingPower(source))
+    }
+
+    pub async fn propose(
+        &self,
+        proposer: &str,
+        proposal_type: &str,
+        title: &str,
+        description: &str,
+    ) -> Result<Uuid, GovernanceError> {
+        self.propose_actions(proposer, proposal_type, title, description, Vec::new())
+            .await
+    }
+
+    /// Create a proposal whose actions run automatically if it passes
+    pub async fn propose_actions(
+        &self,
+        proposer: &str,
+        proposal_type: &str,
+        title: &str,
+        description: &str,
+        actions: Vec<ProposalAction>,
+    ) -> Result<Uuid, GovernanceError> {
+        let weight_source = self.config.weight_source(proposal_type);
+        // Refuse proposals nobody could vote on
+        self.provider(weight_source)?;
+
+        let proposal = Proposal {
+            id: Uuid::new_v4(),
+            proposal_type: proposal_type.to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+            proposer: proposer.to_string(),
+            weight_source,
+            status: ProposalStatus::Open,
+            votes: Vec::new(),
+            actions,
+            created_at: Utc::now(),
+            closed_at: None,
+            execution: None,
+        };
+        let id = proposal.id;
+        self.proposals.write().await.insert(id, proposal);
+        Ok(id)
+    }
+
+    /// Cast a vote and return its weight
+    pub async fn vote(
+        &self,
+        proposal_id: Uuid,
+        voter: &str,
+        choice: VoteChoice,
+    ) -> Result<f64, GovernanceError> {
+        let mut proposals = self.proposals.write().await;
+        let proposal = proposals
+            .get_mut(&proposal_id)
+            .ok_or(GovernanceError::UnknownProposal(proposal_id))?;
+        if proposal.status != ProposalStatus::Open {
+            return Err(GovernanceError::Closed(proposal_id));
+        }
+        if proposal.votes.iter().any(|v| v.voter == voter) {
+            return Err(GovernanceError::AlreadyVoted {
+                proposal_id,
+                voter: voter.to_string(),
+            });
+        }
+
+        let weight = self.provider(proposal.weight_source)?.power(voter);
+        if weight <= 0.0 {
+            return Err(GovernanceError::NoWeight {
+                voter: voter.to_string(),
+                weight_source: proposal.weight_source,
+            });
+        }
+        proposal.votes.push(Vote {
+            voter: voter.to_string(),
+            choice,
+            weight,
+            cast_at: Utc::now(),
+        });
+        Ok(weight)
+    }
+
+    pub async fn tally(&self, proposal_id: Uuid) -> Result<Tally, GovernanceError> {
+        let proposals = self.proposals.read().await;
+        let proposal = proposals
+            .get(&proposal_id)
+            .ok_or(GovernanceError::UnknownProposal(proposal_id))?;
+        Ok(Tally::of(proposal, &self.config))
+    }
+
+    /// Stop voting and decide the proposal
+    pub async fn close(&self, proposal_id: Uuid) -> Result<Tally, GovernanceError> {
+        let mut proposals = self.proposals.write().await;
+        let proposal = proposals
+            .get_mut(&proposal_id)
+            .ok_or(GovernanceError::UnknownProposal(proposal_id))?;
+        if proposal.status != ProposalStatus::Open {
+            return Err(GovernanceError::Closed(proposal_id));
+        }
+        let tally = Tally::of(proposal, &self.config);
+        proposal.status = if tally.passes {
+            ProposalStatus::Passed
+        } else {
+            ProposalStatus::Rejected
+        };
+        proposal.closed_at = Some(Utc::now());
+        if tally.passes {
+            // Nobody listening just means nothing executes automatically
+            let _ = self.passed.send(proposal_id);
+        }
+        Ok(tally)
+    }
+
+    /// IDs of proposals as they pass
+    pub fn subscribe_passed(&self) -> broadcast::Receiver<Uuid> {
+        self.passed.subscribe()
+    }
+
+    /// Record the outcome of running a proposal's actions
+    pub async fn record_execution(
+        &self,
+        proposal_id: Uuid,
+        record: ExecutionRecord,
+    ) -> Result<(), GovernanceError> {
+        let mut proposals = self.proposals.write().await;
+        let proposal = proposals
+            .get_mut(&proposal_id)
+            .ok_or(GovernanceError::UnknownProposal(proposal_id))?;
+        if proposal.execution.is_some() {
+            return Err(GovernanceError::AlreadyExecuted(proposal_id));
+        }
+        proposal.execution = Some(record);
+        Ok(())
+    }
+
+    pub async fn proposal(&self, proposal_id: Uuid) -> Option<Proposal> {
+        self.proposals.read().await.get(&proposal_id).cloned()
+    }
+
+    /// All proposals, oldest first
+    pub async fn proposals(&self) -> Vec<Proposal> {
+        let mut proposals: Vec<Proposal> = self.proposals.read().await.values().cloned().collect();
+        proposals.sort_by_key(|p| p.created_at);
+        proposals
     }
 }
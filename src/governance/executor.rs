@@ -0,0 +1,484 @@
+//! Automatic execution of passed DAO proposals.
+//!
+//! A proposal may carry [`ProposalAction`]s. When it passes, the
+//! [`ProposalExecutor`] runs them in order through the registered
+//! [`ActionHandler`]s. Each executed action returns a [`Compensation`]
+//! describing how to undo it; if a later action fails, the earlier ones are
+//! compensated in reverse order so the proposal takes effect entirely or not
+//! at all. Every execution is recorded in the audit log.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, watch, Mutex, RwLock};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::core::audit::AuditLog;
+use crate::core::metrics::MetricsCollector;
+use crate::darwin::self_improvement::{ModificationStatus, SelfImprovementEngine};
+use crate::darwin::validation::ValidationPipeline;
+use crate::governance::dao::{Dao, ProposalStatus};
+use crate::utils::config::Config;
+use crate::utils::errors::GovernanceError;
+
+/// Something a passed proposal does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ProposalAction {
+    /// Set a node config value; `key` is a dotted path such as
+    /// `network.timeout_ms`
+    SetConfig {
+        key: String,
+        value: Value,
+    },
+    ApproveModification {
+        modification_id: Uuid,
+    },
+    SetValidationThreshold {
+        metric: String,
+        threshold: f32,
+    },
+    GrantCapability {
+        agent: String,
+        capability: String,
+    },
+}
+
+impl ProposalAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProposalAction::SetConfig { .. } => "set_config",
+            ProposalAction::ApproveModification { .. } => "approve_modification",
+            ProposalAction::SetValidationThreshold { .. } => "set_validation_threshold",
+            ProposalAction::GrantCapability { .. } => "grant_capability",
+        }
+    }
+}
+
+/// How to undo an executed action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "undo", rename_all = "snake_case")]
+pub enum Compensation {
+    /// The action changed nothing
+    None,
+    RestoreConfig {
+        key: String,
+        previous: Value,
+    },
+    RestoreThreshold {
+        metric: String,
+        previous: Option<f32>,
+    },
+    RestoreModificationStatus {
+        modification_id: Uuid,
+        previous: ModificationStatus,
+    },
+    RevokeCapability {
+        agent: String,
+        capability: String,
+    },
+}
+
+/// Carries out proposal actions of the kinds it handles
+#[async_trait]
+pub trait ActionHandler: Send + Sync {
+    fn handles(&self, action: &ProposalAction) -> bool;
+
+    async fn execute(&self, action: &ProposalAction) -> Result<Compensation>;
+
+    async fn compensate(&self, compensation: &Compensation) -> Result<()>;
+}
+
+/// Outcome of executing a proposal's actions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ExecutionStatus {
+    Executed,
+    /// An action failed and every executed action was undone
+    Compensated {
+        failed_action: usize,
+        error: String,
+    },
+    /// An action failed and undoing the earlier ones failed too; the system
+    /// needs manual repair
+    CompensationFailed {
+        failed_action: usize,
+        error: String,
+        compensation_errors: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionRecord {
+    pub status: ExecutionStatus,
+    /// Actions executed, including any later compensated
+    pub executed: usize,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// Runs the actions of passed proposals
+pub struct ProposalExecutor {
+    dao: Arc<Dao>,
+    handlers: Vec<Arc<dyn ActionHandler>>,
+    audit: Arc<AuditLog>,
+    metrics: Arc<MetricsCollector>,
+    /// Proposals being executed, so concurrent calls can't run one twice
+    in_flight: Mutex<HashSet<Uuid>>,
+}
+
+impl ProposalExecutor {
+    pub fn new(dao: Arc<Dao>, audit: Arc<AuditLog>, metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            dao,
+            handlers: Vec::new(),
+            audit,
+            metrics,
+            in_flight: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn with_handler(mut self, handler: Arc<dyn ActionHandler>) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+
+    fn handler_for(&self, action: &ProposalAction) -> Option<&Arc<dyn ActionHandler>> {
+        self.handlers.iter().find(|h| h.handles(action))
+    }
+
+    /// Execute a passed proposal's actions. Failed actions don't make this
+    /// return an error; they are compensated and reported in the record.
+    pub async fn execute(&self, proposal_id: Uuid) -> Result<ExecutionRecord, GovernanceError> {
+        if !self.in_flight.lock().await.insert(proposal_id) {
+            return Err(GovernanceError::AlreadyExecuted(proposal_id));
+        }
+        let result = self.execute_once(proposal_id).await;
+        self.in_flight.lock().await.remove(&proposal_id);
+        result
+    }
+
+    async fn execute_once(&self, proposal_id: Uuid) -> Result<ExecutionRecord, GovernanceError> {
+        let proposal = self
+            .dao
+            .proposal(proposal_id)
+            .await
+            .ok_or(GovernanceError::UnknownProposal(proposal_id))?;
+        if proposal.status != ProposalStatus::Passed {
+            return Err(GovernanceError::NotPassed(proposal_id));
+        }
+        if proposal.execution.is_some() {
+            return Err(GovernanceError::AlreadyExecuted(proposal_id));
+        }
+
+        // Check every action can run before running any
+        let mut handlers = Vec::with_capacity(proposal.actions.len());
+        for action in &proposal.actions {
+            let handler = self
+                .handler_for(action)
+                .ok_or_else(|| GovernanceError::NoHandler(action.name().to_string()))?;
+            handlers.push(handler.clone());
+        }
+
+        let mut compensations = Vec::new();
+        let mut failure = None;
+        for (i, (action, handler)) in proposal.actions.iter().zip(&handlers).enumerate() {
+            match handler.execute(action).await {
+                Ok(compensation) => compensations.push((handler.clone(), compensation)),
+                Err(e) => {
+                    failure = Some((i, e.to_string()));
+                    break;
+                }
+            }
+        }
+
+        let executed = compensations.len();
+        let status = match failure {
+            None => ExecutionStatus::Executed,
+            Some((failed_action, error)) => {
+                warn!(
+                    "Action {} of proposal {} failed, compensating: {}",
+                    failed_action, proposal_id, error
+                );
+                let mut compensation_errors = Vec::new();
+                for (handler, compensation) in compensations.iter().rev() {
+                    if let Err(e) = handler.compensate(compensation).await {
+                        compensation_errors.push(e.to_string());
+                    }
+                }
+                if compensation_errors.is_empty() {
+                    ExecutionStatus::Compensated {
+                        failed_action,
+                        error,
+                    }
+                } else {
+                    ExecutionStatus::CompensationFailed {
+                        failed_action,
+                        error,
+                        compensation_errors,
+                    }
+                }
+            }
+        };
+
+        let record = ExecutionRecord {
+            status,
+            executed,
+            executed_at: Utc::now(),
+        };
+        self.dao
+            .record_execution(proposal_id, record.clone())
+            .await?;
+
+        let (audit_action, counter) = match &record.status {
+            ExecutionStatus::Executed => ("proposal_executed", "governance.proposals.executed"),
+            ExecutionStatus::Compensated { .. } => {
+                ("proposal_compensated", "governance.proposals.compensated")
+            }
+            ExecutionStatus::CompensationFailed { .. } => (
+                "proposal_compensation_failed",
+                "governance.proposals.compensation_failed",
+            ),
+        };
+        self.audit
+            .record(
+                "governance",
+                audit_action,
+                &proposal_id.to_string(),
+                json!({
+                    "title": proposal.title,
+                    "actions": proposal.actions,
+                    "execution": record,
+                }),
+            )
+            .await;
+        self.metrics.increment_counter(counter, 1).await;
+        info!("Proposal {} execution: {:?}", proposal_id, record.status);
+        Ok(record)
+    }
+
+    /// Execute proposals as they pass until `shutdown` becomes true
+    pub async fn run(self, mut shutdown: watch::Receiver<bool>) {
+        let mut passed = self.dao.subscribe_passed();
+        info!("Starting proposal executor");
+        while !*shutdown.borrow() {
+            tokio::select! {
+                proposal = passed.recv() => match proposal {
+                    Ok(proposal_id) => {
+                        if let Err(e) = self.execute(proposal_id).await {
+                            warn!("Failed to execute proposal {}: {}", proposal_id, e);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Proposal executor missed {} passed proposals", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = shutdown.changed() => {}
+            }
+        }
+        info!("Stopped proposal executor");
+    }
+}
+
+/// Node configuration that governance can change at runtime
+#[derive(Debug)]
+pub struct ConfigStore {
+    config: RwLock<Config>,
+}
+
+impl ConfigStore {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config: RwLock::new(config),
+        }
+    }
+
+    pub async fn get(&self) -> Config {
+        self.config.read().await.clone()
+    }
+
+    /// Value at a dotted path such as `network.timeout_ms`
+    pub async fn value(&self, key: &str) -> Option<Value> {
+        let config = serde_json::to_value(&*self.config.read().await).ok()?;
+        config.pointer(&pointer(key)).cloned()
+    }
+
+    /// Set the value at a dotted path, returning the previous one. Fails if
+    /// the key doesn't exist or the value has the wrong type.
+    pub async fn set_value(&self, key: &str, value: Value) -> Result<Value> {
+        let mut config = self.config.write().await;
+        let mut tree = serde_json::to_value(&*config)?;
+        let slot = tree
+            .pointer_mut(&pointer(key))
+            .ok_or_else(|| anyhow!("Unknown config key {}", key))?;
+        let previous = std::mem::replace(slot, value);
+        *config = serde_json::from_value(tree)
+            .map_err(|e| anyhow!("Invalid value for config key {}: {}", key, e))?;
+        Ok(previous)
+    }
+}
+
+fn pointer(key: &str) -> String {
+    format!("/{}", key.replace('.', "/"))
+}
+
+#[async_trait]
+impl ActionHandler for ConfigStore {
+    fn handles(&self, action: &ProposalAction) -> bool {
+        matches!(action, ProposalAction::SetConfig { .. })
+    }
+
+    async fn execute(&self, action: &ProposalAction) -> Result<Compensation> {
+        let ProposalAction::SetConfig { key, value } = action else {
+            return Err(anyhow!("Unsupported action {}", action.name()));
+        };
+        let previous = self.set_value(key, value.clone()).await?;
+        Ok(Compensation::RestoreConfig {
+            key: key.clone(),
+            previous,
+        })
+    }
+
+    async fn compensate(&self, compensation: &Compensation) -> Result<()> {
+        if let Compensation::RestoreConfig { key, previous } = compensation {
+            self.set_value(key, previous.clone()).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ActionHandler for ValidationPipeline {
+    fn handles(&self, action: &ProposalAction) -> bool {
+        matches!(action, ProposalAction::SetValidationThreshold { .. })
+    }
+
+    async fn execute(&self, action: &ProposalAction) -> Result<Compensation> {
+        let ProposalAction::SetValidationThreshold { metric, threshold } = action else {
+            return Err(anyhow!("Unsupported action {}", action.name()));
+        };
+        if !threshold.is_finite() {
+            return Err(anyhow!("Threshold for {} must be finite", metric));
+        }
+        let previous = self.set_threshold(metric, *threshold);
+        Ok(Compensation::RestoreThreshold {
+            metric: metric.clone(),
+            previous,
+        })
+    }
+
+    async fn compensate(&self, compensation: &Compensation) -> Result<()> {
+        if let Compensation::RestoreThreshold { metric, previous } = compensation {
+            match previous {
+                Some(threshold) => self.set_threshold(metric, *threshold),
+                None => self.remove_threshold(metric),
+            };
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ActionHandler for SelfImprovementEngine {
+    fn handles(&self, action: &ProposalAction) -> bool {
+        matches!(action, ProposalAction::ApproveModification { .. })
+    }
+
+    async fn execute(&self, action: &ProposalAction) -> Result<Compensation> {
+        let ProposalAction::ApproveModification { modification_id } = action else {
+            return Err(anyhow!("Unsupported action {}", action.name()));
+        };
+        let previous = self.approve_modification(*modification_id).await?;
+        Ok(Compensation::RestoreModificationStatus {
+            modification_id: *modification_id,
+            previous,
+        })
+    }
+
+    async fn compensate(&self, compensation: &Compensation) -> Result<()> {
+        if let Compensation::RestoreModificationStatus {
+            modification_id,
+            previous,
+        } = compensation
+        {
+            self.revoke_approval(*modification_id, previous.clone())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Capabilities granted to agents by governance
+#[derive(Debug, Default)]
+pub struct CapabilityGrants {
+    grants: DashMap<String, HashSet<String>>,
+}
+
+impl CapabilityGrants {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grant a capability, returning false if the agent already had it
+    pub fn grant(&self, agent: &str, capability: &str) -> bool {
+        self.grants
+            .entry(agent.to_string())
+            .or_default()
+            .insert(capability.to_string())
+    }
+
+    pub fn revoke(&self, agent: &str, capability: &str) -> bool {
+        self.grants
+            .get_mut(agent)
+            .map(|mut caps| caps.remove(capability))
+            .unwrap_or(false)
+    }
+
+    pub fn has(&self, agent: &str, capability: &str) -> bool {
+        self.grants
+            .get(agent)
+            .is_some_and(|caps| caps.contains(capability))
+    }
+
+    /// Every agent's capabilities
+    pub fn all(&self) -> HashMap<String, HashSet<String>> {
+        self.grants
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ActionHandler for CapabilityGrants {
+    fn handles(&self, action: &ProposalAction) -> bool {
+        matches!(action, ProposalAction::GrantCapability { .. })
+    }
+
+    async fn execute(&self, action: &ProposalAction) -> Result<Compensation> {
+        let ProposalAction::GrantCapability { agent, capability } = action else {
+            return Err(anyhow!("Unsupported action {}", action.name()));
+        };
+        if !self.grant(agent, capability) {
+            return Ok(Compensation::None);
+        }
+        Ok(Compensation::RevokeCapability {
+            agent: agent.clone(),
+            capability: capability.clone(),
+        })
+    }
+
+    async fn compensate(&self, compensation: &Compensation) -> Result<()> {
+        if let Compensation::RevokeCapability { agent, capability } = compensation {
+            self.revoke(agent, capability);
+        }
+        Ok(())
+    }
+}
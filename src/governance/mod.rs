@@ -1,2 +1,4 @@
 pub mod dao;
+pub mod executor;
+pub mod voting;
 pub mod zkp;
@@ -0,0 +1,182 @@
+//! Sources of voting weight for DAO proposals.
+//!
+//! A [`VotingPower`] provider decides how much an agent's vote counts. The
+//! DAO picks one per proposal type, so e.g. parameter changes can be decided
+//! by stake while moderation is decided by reputation.
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::governance::zkp::{UtilityBounds, UtilityProof, ZKP};
+use crate::utils::errors::ZkpError;
+
+/// Where voting weight comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeightSource {
+    /// One agent, one vote
+    Equal,
+    /// Reputation accumulated through value flows
+    Reputation,
+    /// Utility agents have staked
+    Stake,
+}
+
+impl std::fmt::Display for WeightSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            WeightSource::Equal => "equal",
+            WeightSource::Reputation => "reputation",
+            WeightSource::Stake => "stake",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Weight of an agent's vote; zero means the agent can't vote
+pub trait VotingPower: Send + Sync {
+    fn source(&self) -> WeightSource;
+
+    fn power(&self, agent: &str) -> f64;
+}
+
+/// Every agent's vote counts the same
+#[derive(Debug, Default)]
+pub struct EqualPower;
+
+impl VotingPower for EqualPower {
+    fn source(&self) -> WeightSource {
+        WeightSource::Equal
+    }
+
+    fn power(&self, _agent: &str) -> f64 {
+        1.0
+    }
+}
+
+/// A transfer of utility between agents, mirroring the Holochain
+/// `ValueFlow` entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueFlowRecord {
+    pub from: String,
+    pub to: String,
+    /// Zero when the utility is committed in `utility_proof` instead
+    pub utility: f32,
+    pub governance_weight: f32,
+    /// Reputation moved from `from` to `to`, per dimension
+    pub reputation_shift: Vec<f32>,
+    /// Commitment to a private utility and proof that it is within bounds
+    #[serde(default)]
+    pub utility_proof: Option<UtilityProof>,
+}
+
+/// Reputation each agent starts with, per dimension
+pub const INITIAL_REPUTATION: f32 = 0.5;
+
+/// Voting power from reputation, updated from value flows the same way the
+/// value flow zome updates `Reputation` entries
+#[derive(Debug, Default)]
+pub struct ReputationPower {
+    reputations: DashMap<String, Vec<f32>>,
+    zkp: ZKP,
+    utility_bounds: UtilityBounds,
+}
+
+impl ReputationPower {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bounds private utilities must be proven within
+    pub fn with_utility_bounds(mut self, bounds: UtilityBounds) -> Self {
+        self.utility_bounds = bounds;
+        self
+    }
+
+    /// Move reputation from the flow's sender to its receiver. Flows whose
+    /// committed utility doesn't verify are rejected and change nothing.
+    pub fn record_flow(&self, flow: &ValueFlowRecord) -> Result<(), ZkpError> {
+        self.zkp.verify_flow(
+            flow.utility,
+            flow.utility_proof.as_ref(),
+            self.utility_bounds,
+        )?;
+        let dimensions = flow.reputation_shift.len();
+        for (agent, sign) in [(&flow.from, -1.0), (&flow.to, 1.0)] {
+            let mut reputation = self
+                .reputations
+                .entry(agent.clone())
+                .or_insert_with(|| vec![INITIAL_REPUTATION; dimensions]);
+            if reputation.len() < dimensions {
+                reputation.resize(dimensions, INITIAL_REPUTATION);
+            }
+            for (value, shift) in reputation.iter_mut().zip(&flow.reputation_shift) {
+                *value += sign * shift;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn reputation(&self, agent: &str) -> Option<Vec<f32>> {
+        self.reputations.get(agent).map(|r| r.clone())
+    }
+}
+
+impl VotingPower for ReputationPower {
+    fn source(&self) -> WeightSource {
+        WeightSource::Reputation
+    }
+
+    /// Mean reputation across dimensions; agents without any value flows
+    /// and agents whose reputation went negative can't vote
+    fn power(&self, agent: &str) -> f64 {
+        match self.reputations.get(agent) {
+            Some(reputation) if !reputation.is_empty() => {
+                let mean =
+                    reputation.iter().map(|&v| v as f64).sum::<f64>() / reputation.len() as f64;
+                mean.max(0.0)
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// Voting power from staked utility
+#[derive(Debug, Default)]
+pub struct StakePower {
+    stakes: DashMap<String, f64>,
+}
+
+impl StakePower {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add to an agent's stake and return the new total
+    pub fn stake(&self, agent: &str, amount: f64) -> f64 {
+        let mut stake = self.stakes.entry(agent.to_string()).or_insert(0.0);
+        *stake += amount.max(0.0);
+        *stake
+    }
+
+    /// Withdraw up to `amount` and return what remains staked
+    pub fn unstake(&self, agent: &str, amount: f64) -> f64 {
+        match self.stakes.get_mut(agent) {
+            Some(mut stake) => {
+                *stake = (*stake - amount.max(0.0)).max(0.0);
+                *stake
+            }
+            None => 0.0,
+        }
+    }
+}
+
+impl VotingPower for StakePower {
+    fn source(&self) -> WeightSource {
+        WeightSource::Stake
+    }
+
+    fn power(&self, agent: &str) -> f64 {
+        self.stakes.get(agent).map(|s| *s).unwrap_or(0.0)
+    }
+}
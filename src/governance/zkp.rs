@@ -1,13 +1,7 @@
-use bulletproofs::BulletproofGens;
+//! Zero-knowledge range proofs for private value flow utility.
+//!
+//! The proofs live in the `rose-forest-value-flow` crate, which the value
+//! flow zome shares, so proofs nodes make are exactly the ones the zome
+//! verifies.
 
-/// Zero-knowledge proof handler placeholder
-pub struct ZKP {
-    _gens: Option<BulletproofGens>,
-}
-
-impl ZKP {
-    /// Create a new ZKP handler
-    pub fn new() -> Self {
-        Self { _gens: None }
-    }
-}
+pub use rose_forest_value_flow::zkp::*;
@@ -0,0 +1,358 @@
+//! Time-bucketed anchors for audit trails
+//!
+//! Linking every audit trail from one path makes that path's base a DHT
+//! hotspot. Audit trails are instead linked from an anchor per hour,
+//! `audit_trails_by_time.<day>.<hour>`, where both components count from the
+//! Unix epoch. Range queries only read the anchors overlapping the range, and
+//! link tags carry the exact timestamp so the edges can be trimmed.
+//!
+//! Anchors are found by walking the path links down from the root, so
+//! queries read the days and hours that hold audit trails rather than every
+//! hour in a range.
+
+use crate::holochain::utils::get_app_entry;
+use crate::holochain::{AuditTrail, LinkTypes};
+use hdk::hash_path::path::Component;
+use hdk::prelude::*;
+
+/// Root of the audit trail anchors
+pub const AUDIT_ANCHOR_ROOT: &str = "audit_trails_by_time";
+
+/// Longest range one query may cover, in hours
+pub const MAX_RANGE_HOURS: u64 = 31 * 24;
+
+const MICROS_PER_HOUR: u64 = 3_600_000_000;
+const HOURS_PER_DAY: u64 = 24;
+
+/// Width of the buckets counts are rolled up into
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BucketGranularity {
+    Hour,
+    Day,
+}
+
+impl BucketGranularity {
+    pub fn width_micros(self) -> u64 {
+        match self {
+            BucketGranularity::Hour => MICROS_PER_HOUR,
+            BucketGranularity::Day => MICROS_PER_HOUR * HOURS_PER_DAY,
+        }
+    }
+
+    /// Start of the bucket containing `timestamp`, in microseconds
+    pub fn bucket_start(self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.width_micros()
+    }
+}
+
+/// Hours since the epoch of a timestamp in microseconds
+pub fn hour_of(timestamp: u64) -> u64 {
+    timestamp / MICROS_PER_HOUR
+}
+
+/// Path components of the anchor for an hour
+pub fn anchor_components(hour: u64) -> [String; 2] {
+    [(hour / HOURS_PER_DAY).to_string(), hour.to_string()]
+}
+
+/// Anchor for an hour
+fn anchor_path(hour: u64) -> Path {
+    let [day, hour] = anchor_components(hour);
+    Path::from(format!("{}.{}.{}", AUDIT_ANCHOR_ROOT, day, hour))
+}
+
+/// Anchor for a day, the parent of its hours' anchors
+fn day_path(day: u64) -> Path {
+    Path::from(format!("{}.{}", AUDIT_ANCHOR_ROOT, day))
+}
+
+/// Days holding any of `hours`
+pub fn days_spanning(hours: &std::ops::Range<u64>) -> std::ops::RangeInclusive<u64> {
+    hours.start / HOURS_PER_DAY..=hours.end.saturating_sub(1) / HOURS_PER_DAY
+}
+
+/// Number an anchor's path link tag names, if it's one of ours
+fn anchor_number(tag: &LinkTag) -> Option<u64> {
+    let bytes = SerializedBytes::from(UnsafeBytes::from(tag.0.clone()));
+    let component = Component::try_from(bytes).ok()?;
+    String::try_from(&component).ok()?.parse().ok()
+}
+
+/// Numbers of the anchors directly below `path`, ascending. Reads the path
+/// links without `TypedPath::children`, which would create missing anchors.
+fn child_anchors(path: &Path) -> ExternResult<Vec<u64>> {
+    let mut children: Vec<u64> = get_links(path.path_entry_hash()?, LinkTypes::Path, None)?
+        .into_iter()
+        .filter_map(|link| anchor_number(&link.tag))
+        .collect();
+    children.sort_unstable();
+    children.dedup();
+    Ok(children)
+}
+
+/// Hours in `hours` whose anchors exist, ascending. Only days whose anchors
+/// exist are listed, and empty hours are never read.
+fn anchored_hours(hours: std::ops::Range<u64>) -> ExternResult<Vec<u64>> {
+    let days = days_spanning(&hours);
+    let mut anchored = Vec::new();
+    for day in child_anchors(&Path::from(AUDIT_ANCHOR_ROOT))? {
+        if !days.contains(&day) {
+            continue;
+        }
+        anchored.extend(
+            child_anchors(&day_path(day))?
+                .into_iter()
+                .filter(|hour| hour / HOURS_PER_DAY == day && hours.contains(hour)),
+        );
+    }
+    Ok(anchored)
+}
+
+/// Hours whose anchors may hold audit trails in `[start, end)`
+pub fn hours_in_range(start: u64, end: u64) -> Result<std::ops::Range<u64>, String> {
+    if end <= start {
+        return Err(format!("Empty time range: {} to {}", start, end));
+    }
+    let hours = hour_of(start)..hour_of(end - 1) + 1;
+    if hours.end - hours.start > MAX_RANGE_HOURS {
+        return Err(format!(
+            "Time range spans {} hours; at most {} can be queried at once",
+            hours.end - hours.start,
+            MAX_RANGE_HOURS
+        ));
+    }
+    Ok(hours)
+}
+
+/// Link an audit trail from the anchor for its timestamp
+pub fn anchor_audit_trail(audit_hash: EntryHash, timestamp: u64) -> ExternResult<()> {
+    let path = anchor_path(hour_of(timestamp));
+    // Parent links let the anchors be walked from the root
+    path.clone().typed(LinkTypes::Path)?.ensure()?;
+    create_link(
+        path.path_entry_hash()?,
+        audit_hash,
+        LinkTypes::AuditTrail,
+        LinkTag::new(timestamp.to_be_bytes().to_vec()),
+    )?;
+    Ok(())
+}
+
+/// Links under the anchor for an hour, with their timestamps
+fn hour_links(hour: u64) -> ExternResult<Vec<(u64, Link)>> {
+    let links = get_links(
+        anchor_path(hour).path_entry_hash()?,
+        LinkTypes::AuditTrail,
+        None,
+    )?;
+    Ok(links
+        .into_iter()
+        .filter_map(|link| {
+            let bytes: [u8; 8] = link.tag.0.get(0..8)?.try_into().ok()?;
+            Some((u64::from_be_bytes(bytes), link))
+        })
+        .collect())
+}
+
+fn load_audit(link: Link) -> ExternResult<AuditTrail> {
+    get_app_entry(link.target)?
+        .ok_or_else(|| wasm_error!(WasmErrorInner::Guest("Audit entry not found".to_string())))
+}
+
+fn range_error(message: String) -> WasmError {
+    wasm_error!(WasmErrorInner::Guest(message))
+}
+
+/// Input for audit trail range queries; times are microseconds since the
+/// epoch and `end` is exclusive
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuditRangeInput {
+    pub start: u64,
+    pub end: u64,
+    /// Return at most this many, oldest first
+    pub limit: Option<usize>,
+}
+
+/// Audit trails created in a time range, oldest first
+#[hdk_extern]
+pub fn get_audit_trails_in_range(input: AuditRangeInput) -> ExternResult<Vec<AuditTrail>> {
+    let hours = hours_in_range(input.start, input.end).map_err(range_error)?;
+    let limit = input.limit.unwrap_or(usize::MAX);
+
+    let mut audits = Vec::new();
+    for hour in anchored_hours(hours)? {
+        let mut links = hour_links(hour)?;
+        links.retain(|(timestamp, _)| (input.start..input.end).contains(timestamp));
+        links.sort_by_key(|(timestamp, _)| *timestamp);
+        for (_, link) in links {
+            if audits.len() == limit {
+                return Ok(audits);
+            }
+            audits.push(load_audit(link)?);
+        }
+    }
+    Ok(audits)
+}
+
+/// Input for audit trail roll-ups
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuditCountInput {
+    pub start: u64,
+    pub end: u64,
+    pub granularity: BucketGranularity,
+}
+
+/// Audit trails created in one bucket
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuditBucketCount {
+    /// Start of the bucket, in microseconds since the epoch
+    pub bucket_start: u64,
+    pub count: usize,
+}
+
+/// Count audit trails per bucket over a time range, reading links only.
+/// Buckets without audit trails are omitted.
+#[hdk_extern]
+pub fn get_audit_trail_counts(input: AuditCountInput) -> ExternResult<Vec<AuditBucketCount>> {
+    let hours = hours_in_range(input.start, input.end).map_err(range_error)?;
+
+    let mut counts: Vec<AuditBucketCount> = Vec::new();
+    for hour in anchored_hours(hours)? {
+        let count = hour_links(hour)?
+            .into_iter()
+            .filter(|(timestamp, _)| (input.start..input.end).contains(timestamp))
+            .count();
+        if count > 0 {
+            add_to_bucket(
+                &mut counts,
+                input.granularity,
+                hour * MICROS_PER_HOUR,
+                count,
+            );
+        }
+    }
+    Ok(counts)
+}
+
+/// Add `count` to the bucket containing `timestamp`; hours are visited in
+/// order, so only the last bucket can match
+fn add_to_bucket(
+    counts: &mut Vec<AuditBucketCount>,
+    granularity: BucketGranularity,
+    timestamp: u64,
+    count: usize,
+) {
+    let bucket_start = granularity.bucket_start(timestamp);
+    match counts.last_mut() {
+        Some(last) if last.bucket_start == bucket_start => last.count += count,
+        _ => counts.push(AuditBucketCount {
+            bucket_start,
+            count,
+        }),
+    }
+}
+
+/// The most recent audit trails, newest first, looking back at most
+/// [`MAX_RANGE_HOURS`]
+pub fn recent_audit_trails(now: u64, count: usize) -> ExternResult<Vec<AuditTrail>> {
+    let current = hour_of(now);
+    let window = current.saturating_sub(MAX_RANGE_HOURS - 1)..current + 1;
+    let mut links = Vec::new();
+    for hour in anchored_hours(window)?.into_iter().rev() {
+        let mut newest_first = hour_links(hour)?;
+        newest_first.sort_by(|a, b| b.0.cmp(&a.0));
+        links.extend(newest_first);
+        if links.len() >= count {
+            break;
+        }
+    }
+    links.truncate(count);
+
+    links
+        .into_iter()
+        .map(|(_, link)| load_audit(link))
+        .collect()
+}
+
+/// Audit trails ever anchored, reading only the anchors that exist
+pub fn count_all_audit_trails() -> ExternResult<usize> {
+    let mut total = 0;
+    for hour in anchored_hours(0..u64::MAX)? {
+        total += hour_links(hour)?.len();
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = MICROS_PER_HOUR;
+    const DAY: u64 = MICROS_PER_HOUR * HOURS_PER_DAY;
+
+    #[test]
+    fn timestamps_map_to_hour_and_day_anchors() {
+        let timestamp = 3 * DAY + 5 * HOUR + 42;
+        assert_eq!(hour_of(timestamp), 3 * 24 + 5);
+        assert_eq!(
+            anchor_components(hour_of(timestamp)),
+            ["3".to_string(), "77".to_string()]
+        );
+        assert_eq!(
+            BucketGranularity::Hour.bucket_start(timestamp),
+            3 * DAY + 5 * HOUR
+        );
+        assert_eq!(BucketGranularity::Day.bucket_start(timestamp), 3 * DAY);
+    }
+
+    #[test]
+    fn ranges_cover_overlapping_hours_only() {
+        assert_eq!(hours_in_range(HOUR, 2 * HOUR).unwrap(), 1..2);
+        assert_eq!(hours_in_range(HOUR + 1, 3 * HOUR + 1).unwrap(), 1..4);
+        assert!(hours_in_range(2 * HOUR, HOUR).is_err());
+        assert!(hours_in_range(0, (MAX_RANGE_HOURS + 1) * HOUR).is_err());
+        assert!(hours_in_range(0, MAX_RANGE_HOURS * HOUR).is_ok());
+    }
+
+    #[test]
+    fn hour_ranges_map_to_the_days_holding_them() {
+        assert_eq!(days_spanning(&(0..24)), 0..=0);
+        assert_eq!(days_spanning(&(23..25)), 0..=1);
+        assert_eq!(days_spanning(&(48..49)), 2..=2);
+        assert_eq!(days_spanning(&(0..u64::MAX)), 0..=u64::MAX / 24);
+    }
+
+    #[test]
+    fn anchor_numbers_are_read_from_path_link_tags() {
+        let hour = anchor_path(77);
+        assert_eq!(anchor_number(&hour.make_tag().unwrap()), Some(77));
+        assert_eq!(anchor_number(&day_path(3).make_tag().unwrap()), Some(3));
+        assert_eq!(anchor_number(&LinkTag::new(vec![1, 2, 3])), None);
+    }
+
+    #[test]
+    fn hourly_counts_roll_up_into_days() {
+        let mut counts = Vec::new();
+        for (hour, count) in [(1, 2), (23, 1), (24, 4), (50, 1)] {
+            add_to_bucket(&mut counts, BucketGranularity::Day, hour * HOUR, count);
+        }
+        assert_eq!(
+            counts,
+            vec![
+                AuditBucketCount {
+                    bucket_start: 0,
+                    count: 3
+                },
+                AuditBucketCount {
+                    bucket_start: DAY,
+                    count: 4
+                },
+                AuditBucketCount {
+                    bucket_start: 2 * DAY,
+                    count: 1
+                },
+            ]
+        );
+    }
+}
@@ -1,11 +1,15 @@
 //! Community arbitration system for Amazon Rose Forest
 
-use hdk::prelude::*;
+use crate::core::checksum::to_hex;
+use crate::darwin::governance::{DisputeDecision, DisputeOutcome};
 use crate::holochain::entries::{
-    ArbitrationCase, ArbitrationStatus, ArbitrationVote, ArbitrationState
+    ArbitrationCase, ArbitrationState, ArbitrationStatus, ArbitrationVote,
 };
-use crate::holochain::utils::{sys_time, create_path, timestamp_tag};
-use std::collections::HashMap;
+use crate::holochain::hash::default_hash_bytes;
+use crate::holochain::utils::{create_path, get_app_entry, signal_peers, sys_time, timestamp_tag};
+use crate::holochain::zome::create_audit_trail;
+use crate::holochain::{EntryTypes, LinkTypes};
+use hdk::prelude::*;
 use uuid::Uuid;
 
 /// Input for conflict arbitration
@@ -30,7 +34,7 @@ pub enum ArbitrationResult {
 pub fn create_arbitration_case(input: ConflictInput) -> ExternResult<String> {
     let id = Uuid::new_v4().to_string();
     let now = sys_time()?;
-    
+
     let case = ArbitrationCase {
         id: id.clone(),
         content_hash: input.content_hash,
@@ -41,29 +45,41 @@ pub fn create_arbitration_case(input: ConflictInput) -> ExternResult<String> {
         created_at: now,
         updated_at: now,
     };
-    
+
     // Create entry
-    let case_hash = create_entry(&case)?;
-    
+    create_entry(&EntryTypes::ArbitrationCase(case.clone()))?;
+    let case_hash = hash_entry(&case)?;
+
     // Add to index
     let path = create_path("arbitration_cases", vec!["open"])?;
-    create_link(path.path_entry_hash()?, case_hash, timestamp_tag())?;
-    
+    create_link(
+        path.path_entry_hash()?,
+        case_hash.clone(),
+        LinkTypes::ArbitrationCase,
+        timestamp_tag(),
+    )?;
+
     // Add to user's cases
-    let my_cases_path = create_path("my_arbitration_cases", 
-        vec![&agent_info()?.agent_latest_pubkey.to_string()])?;
-    create_link(my_cases_path.path_entry_hash()?, case_hash, timestamp_tag())?;
-    
+    let my_cases_path = create_path(
+        "my_arbitration_cases",
+        vec![&agent_info()?.agent_latest_pubkey.to_string()],
+    )?;
+    create_link(
+        my_cases_path.path_entry_hash()?,
+        case_hash,
+        LinkTypes::ArbitrationCase,
+        timestamp_tag(),
+    )?;
+
     // Notify the network about the new case
     let notification = ArbitrationNotification {
         case_id: id.clone(),
         action: "new_case".to_string(),
         timestamp: now,
     };
-    
-    let signal = ExternIO::encode(notification)?;
-    remote_signal(signal, RemoteSignal::All)?;
-    
+
+    signal_peers(notification)?;
+
     Ok(id)
 }
 
@@ -72,27 +88,33 @@ pub fn create_arbitration_case(input: ConflictInput) -> ExternResult<String> {
 pub fn vote_on_arbitration(input: VoteInput) -> ExternResult<()> {
     // Get the case
     let case_hash = get_arbitration_case_hash(&input.case_id)?;
-    let mut case: ArbitrationCase = get_entry(case_hash.clone())?
-        .ok_or(wasm_error!(WasmErrorInner::Guest("Case not found".to_string())))?
-        .try_into()
-        .map_err(|e: SerializedBytesError| wasm_error!(WasmErrorInner::Serialize(e)))?;
-    
+    let record = get(case_hash, GetOptions::default())?.ok_or(wasm_error!(
+        WasmErrorInner::Guest("Case not found".to_string())
+    ))?;
+    let mut case: ArbitrationCase = record
+        .entry()
+        .to_app_option()
+        .map_err(|e| wasm_error!(e))?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(
+            "Case not found".to_string()
+        )))?;
+
     // Validate vote
     if case.status != ArbitrationStatus::Open && case.status != ArbitrationStatus::UnderReview {
-        return Err(wasm_error!(
-            WasmErrorInner::Guest("Cannot vote on a closed case".to_string())
-        ));
+        return Err(wasm_error!(WasmErrorInner::Guest(
+            "Cannot vote on a closed case".to_string()
+        )));
     }
-    
+
     let agent_pubkey = agent_info()?.agent_latest_pubkey;
-    
+
     // Check if agent has already voted
     if case.votes.iter().any(|v| v.voter == agent_pubkey) {
-        return Err(wasm_error!(
-            WasmErrorInner::Guest("Agent has already voted on this case".to_string())
-        ));
+        return Err(wasm_error!(WasmErrorInner::Guest(
+            "Agent has already voted on this case".to_string()
+        )));
     }
-    
+
     // Add vote
     let now = sys_time()?;
     let vote = ArbitrationVote {
@@ -102,26 +124,30 @@ pub fn vote_on_arbitration(input: VoteInput) -> ExternResult<()> {
         justification: input.justification,
         timestamp: now,
     };
-    
+
     case.votes.push(vote);
     case.updated_at = now;
-    
+
     // Update case status if needed
+    let previous_status = case.status.clone();
     update_case_status(&mut case)?;
-    
+
     // Update entry
-    update_entry(case_hash.clone(), &case)?;
-    
+    update_entry(record.action_address().clone(), &case)?;
+
+    if case.status != previous_status {
+        announce_decision(&case)?;
+    }
+
     // Notify about vote
     let notification = ArbitrationNotification {
         case_id: input.case_id.clone(),
         action: "new_vote".to_string(),
         timestamp: now,
     };
-    
-    let signal = ExternIO::encode(notification)?;
-    remote_signal(signal, RemoteSignal::All)?;
-    
+
+    signal_peers(notification)?;
+
     Ok(())
 }
 
@@ -132,19 +158,23 @@ pub fn arbitrate_conflict(input: ConflictInput) -> ExternResult<ArbitrationResul
     let community_assessment = gather_community_input(&input)?;
     let semantic_analysis = analyze_semantic_intent(&input)?;
     let historical_context = retrieve_participant_history(&input)?;
-    
+
     // Trinary logic allows nuanced outcomes
-    match evaluate_conflict(&community_assessment, &semantic_analysis, &historical_context) {
+    match evaluate_conflict(
+        &community_assessment,
+        &semantic_analysis,
+        &historical_context,
+    ) {
         ArbitrationState::Resolve => {
             // Create learning opportunity from conflict
             create_knowledge_from_resolution(&input)?;
             Ok(ArbitrationResult::Accept)
-        },
+        }
         ArbitrationState::Review => {
             // Escalate to broader community
             request_expanded_review(&input)?;
             Ok(ArbitrationResult::Neutral)
-        },
+        }
         ArbitrationState::Reject => {
             // Apply minimum necessary intervention
             apply_restorative_measures(&input)?;
@@ -170,32 +200,74 @@ pub struct ArbitrationNotification {
     pub timestamp: u64,
 }
 
+/// Record a closed case's decision in the audit trail and tell every peer,
+/// so nodes can roll back a modification the community disputed
+fn announce_decision(case: &ArbitrationCase) -> ExternResult<()> {
+    let outcome = match case.status {
+        ArbitrationStatus::Resolved => DisputeOutcome::Upheld,
+        ArbitrationStatus::Rejected => DisputeOutcome::Dismissed,
+        _ => return Ok(()),
+    };
+
+    // The proof commits to every vote that decided the case
+    let votes = serde_json::to_vec(&case.votes)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+    let decision = DisputeDecision {
+        case_id: case.id.clone(),
+        subject: case.content_hash.clone(),
+        outcome,
+        resolution: case.resolution.clone().unwrap_or_default(),
+        decision_proof: to_hex(&default_hash_bytes(&votes)),
+        votes: case.votes.len(),
+        decided_at: case.updated_at,
+    };
+
+    let details = serde_json::to_string(&decision)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+    create_audit_trail("arbitration_decision", details)?;
+
+    // Remote signals don't reach this agent's own client
+    emit_signal(&decision)?;
+    signal_peers(decision)?;
+    Ok(())
+}
+
 /// Get the entry hash for an arbitration case
 fn get_arbitration_case_hash(case_id: &str) -> ExternResult<EntryHash> {
     // Search in open cases
     let open_path = create_path("arbitration_cases", vec!["open"])?;
-    let open_links = get_links(open_path.path_entry_hash()?, None)?;
-    
+    let open_links = get_links(
+        open_path.path_entry_hash()?,
+        LinkTypes::ArbitrationCase,
+        None,
+    )?;
+
     // Search in closed cases
     let closed_path = create_path("arbitration_cases", vec!["closed"])?;
-    let closed_links = get_links(closed_path.path_entry_hash()?, None)?;
-    
+    let closed_links = get_links(
+        closed_path.path_entry_hash()?,
+        LinkTypes::ArbitrationCase,
+        None,
+    )?;
+
     let all_links = [open_links, closed_links].concat();
-    
+
     for link in all_links {
-        let entry: ArbitrationCase = get_entry(link.target.clone())?
-            .ok_or(wasm_error!(WasmErrorInner::Guest("Case not found".to_string())))?
-            .try_into()
-            .map_err(|e: SerializedBytesError| wasm_error!(WasmErrorInner::Serialize(e)))?;
-            
+        let entry: ArbitrationCase = get_app_entry(link.target.clone())?.ok_or(wasm_error!(
+            WasmErrorInner::Guest("Case not found".to_string())
+        ))?;
+
         if entry.id == case_id {
-            return Ok(link.target);
+            if let Some(case_hash) = link.target.into_entry_hash() {
+                return Ok(case_hash);
+            }
         }
     }
-    
-    Err(wasm_error!(
-        WasmErrorInner::Guest(format!("Case with ID {} not found", case_id))
-    ))
+
+    Err(wasm_error!(WasmErrorInner::Guest(format!(
+        "Case with ID {} not found",
+        case_id
+    ))))
 }
 
 /// Update the status of an arbitration case based on votes
@@ -203,12 +275,12 @@ fn update_case_status(case: &mut ArbitrationCase) -> ExternResult<()> {
     if case.votes.is_empty() {
         return Ok(());
     }
-    
+
     // Count votes
     let mut resolve_votes = 0;
     let mut review_votes = 0;
     let mut reject_votes = 0;
-    
+
     for vote in &case.votes {
         match vote.vote {
             ArbitrationState::Resolve => resolve_votes += 1,
@@ -216,12 +288,13 @@ fn update_case_status(case: &mut ArbitrationCase) -> ExternResult<()> {
             ArbitrationState::Reject => reject_votes += 1,
         }
     }
-    
+
     // Update status based on votes
     // This is a simple majority rule, but could be more sophisticated
     let total_votes = resolve_votes + review_votes + reject_votes;
-    
-    if total_votes >= 5 {  // Minimum threshold for decision
+
+    if total_votes >= 5 {
+        // Minimum threshold for decision
         if resolve_votes > total_votes / 2 {
             case.status = ArbitrationStatus::Resolved;
             case.resolution = Some("Community resolved this case positively".to_string());
@@ -232,7 +305,7 @@ fn update_case_status(case: &mut ArbitrationCase) -> ExternResult<()> {
             case.status = ArbitrationStatus::UnderReview;
         }
     }
-    
+
     Ok(())
 }
 
@@ -265,21 +338,21 @@ fn retrieve_participant_history(_input: &ConflictInput) -> ExternResult<Particip
 
 /// Evaluate a conflict using multiple perspectives
 fn evaluate_conflict(
-    community_assessment: &[CommunityInput],
-    semantic_analysis: &SemanticAnalysis, 
-    historical_context: &ParticipantHistory
+    _community_assessment: &[CommunityInput],
+    semantic_analysis: &SemanticAnalysis,
+    _historical_context: &ParticipantHistory,
 ) -> ArbitrationState {
     // This is a simplified implementation
     // In a real-world scenario, this would be more sophisticated
-    
+
     if semantic_analysis.intent == "harmful" && semantic_analysis.confidence > 0.9 {
         return ArbitrationState::Reject;
     }
-    
+
     if semantic_analysis.intent == "positive" && semantic_analysis.confidence > 0.7 {
         return ArbitrationState::Resolve;
     }
-    
+
     // Default to review for anything ambiguous
     ArbitrationState::Review
 }
@@ -288,37 +361,45 @@ fn evaluate_conflict(
 fn create_knowledge_from_resolution(input: &ConflictInput) -> ExternResult<()> {
     use crate::holochain::entries::KnowledgeContribution;
     use crate::holochain::entries::Metadata;
-    use crate::holochain::utils::{hash_content, generate_embedding};
-    
+    use crate::holochain::utils::{generate_embedding, hash_content};
+
     let knowledge = KnowledgeContribution {
         content_hash: hash_content(&input.resolution),
         embedding: generate_embedding(&input.resolution),
         metadata: Metadata {
-            tags: vec!["conflict_resolution".to_string(), "community_wisdom".to_string()],
+            tags: vec![
+                "conflict_resolution".to_string(),
+                "community_wisdom".to_string(),
+            ],
             lesson_learned: input.key_insight.clone(),
         },
         timestamp: sys_time()?,
     };
-    
-    create_entry(&knowledge)?;
-    
+
+    create_entry(&EntryTypes::KnowledgeContribution(knowledge.clone()))?;
+
     // Index the knowledge
     let path = create_path("knowledge", vec!["conflict_resolution"])?;
     let knowledge_hash = hash_entry(&knowledge)?;
-    create_link(path.path_entry_hash()?, knowledge_hash, timestamp_tag())?;
-    
+    create_link(
+        path.path_entry_hash()?,
+        knowledge_hash,
+        LinkTypes::Knowledge,
+        timestamp_tag(),
+    )?;
+
     Ok(())
 }
 
 /// Request expanded review for a case
-fn request_expanded_review(input: &ConflictInput) -> ExternResult<()> {
+fn request_expanded_review(_input: &ConflictInput) -> ExternResult<()> {
     // This would notify additional reviewers in a real implementation
     // For now, this is a stub
     Ok(())
 }
 
 /// Apply restorative measures
-fn apply_restorative_measures(input: &ConflictInput) -> ExternResult<()> {
+fn apply_restorative_measures(_input: &ConflictInput) -> ExternResult<()> {
     // This would apply appropriate interventions in a real implementation
     // For now, this is a stub
     Ok(())
@@ -326,6 +407,7 @@ fn apply_restorative_measures(input: &ConflictInput) -> ExternResult<()> {
 
 /// Community input struct
 #[derive(Debug)]
+#[allow(dead_code)] // Not yet weighed by `evaluate_conflict`
 struct CommunityInput {
     agent: AgentPubKey,
     assessment: ArbitrationState,
@@ -341,7 +423,8 @@ struct SemanticAnalysis {
 
 /// Participant history
 #[derive(Debug)]
+#[allow(dead_code)] // Not yet weighed by `evaluate_conflict`
 struct ParticipantHistory {
     participant_count: usize,
     average_contributions: usize,
-}
\ No newline at end of file
+}
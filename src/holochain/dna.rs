@@ -4,10 +4,19 @@
 //! creation, registration and cell installation) are not yet implemented. When
 //! compiled with the `holochain_conductor` feature these functions will return
 //! a descriptive `Err` indicating the missing integration.
+//!
+//! The DNA's properties decide how its vectors are indexed. [`IndexConfig`]
+//! is the validated form of those properties: `init` refuses to start a cell
+//! whose properties don't describe a usable index, and zome calls check
+//! every vector against the configured dimensions. Problems are reported as
+//! [`DnaConfigError`]s, serialized to JSON in the guest error so callers can
+//! tell a dimension mismatch from any other failure.
 
+use crate::holochain::validation::MAX_VECTOR_DIMENSIONS;
 use crate::holochain::DnaProperties;
-use crate::sharding::vector_index::DistanceMetric;
+use crate::sharding::vector_index::{DistanceMetric, VectorIndex};
 use hdk::prelude::*;
+use thiserror::Error;
 
 /// Zome configuration for vector operations
 #[derive(Serialize, Deserialize, Debug)]
@@ -24,42 +33,204 @@ pub fn get_dna_properties() -> ExternResult<DnaProperties> {
     let props: DnaProperties = dna_info
         .properties
         .try_into()
-        .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+        .map_err(|e: SerializedBytesError| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
 
     Ok(props)
 }
 
-/// Get the distance metric from DNA properties
-pub fn get_distance_metric() -> ExternResult<DistanceMetric> {
-    let props = get_dna_properties()?;
+/// Why DNA properties or a vector don't fit the DNA's index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Error)]
+#[serde(tag = "error", rename_all = "snake_case")]
+pub enum DnaConfigError {
+    #[error("Unknown distance metric: {metric}")]
+    UnknownDistanceMetric { metric: String },
+
+    #[error("DNA dimensions must be between 1 and {max}, got {dimensions}")]
+    InvalidDimensions { dimensions: usize, max: usize },
+
+    #[error("Similarity threshold must be between 0 and 1, got {threshold}")]
+    InvalidSimilarityThreshold { threshold: f32 },
+
+    #[error("{field} has {actual} dimensions, DNA expects {expected}")]
+    DimensionMismatch {
+        field: String,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl From<DnaConfigError> for WasmError {
+    fn from(error: DnaConfigError) -> Self {
+        let message = serde_json::to_string(&error).unwrap_or_else(|_| error.to_string());
+        wasm_error!(WasmErrorInner::Guest(message))
+    }
+}
 
-    match props.distance_metric.to_lowercase().as_str() {
+/// Index settings taken from DNA properties
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexConfig {
+    pub name: String,
+    pub dimensions: usize,
+    pub distance_metric: DistanceMetric,
+    pub similarity_threshold: f32,
+}
+
+impl IndexConfig {
+    /// Validate DNA properties
+    pub fn from_properties(props: &DnaProperties) -> Result<Self, DnaConfigError> {
+        let distance_metric = parse_distance_metric(&props.distance_metric)?;
+        if props.dimensions == 0 || props.dimensions > MAX_VECTOR_DIMENSIONS {
+            return Err(DnaConfigError::InvalidDimensions {
+                dimensions: props.dimensions,
+                max: MAX_VECTOR_DIMENSIONS,
+            });
+        }
+        if !(0.0..=1.0).contains(&props.similarity_threshold) {
+            return Err(DnaConfigError::InvalidSimilarityThreshold {
+                threshold: props.similarity_threshold,
+            });
+        }
+        Ok(Self {
+            name: props.name.clone(),
+            dimensions: props.dimensions,
+            distance_metric,
+            similarity_threshold: props.similarity_threshold,
+        })
+    }
+
+    /// Check that `values` has the configured number of dimensions;
+    /// `field` names it in the error
+    pub fn check_dimensions(&self, field: &str, values: &[f32]) -> Result<(), DnaConfigError> {
+        if values.len() == self.dimensions {
+            Ok(())
+        } else {
+            Err(DnaConfigError::DimensionMismatch {
+                field: field.to_string(),
+                expected: self.dimensions,
+                actual: values.len(),
+            })
+        }
+    }
+
+    /// Create an in-memory index with this configuration, for conductors
+    /// that serve searches over a cell's vectors
+    pub fn create_index(&self) -> Result<VectorIndex, String> {
+        VectorIndex::new(&self.name, self.dimensions, self.distance_metric, None)
+    }
+}
+
+fn parse_distance_metric(metric: &str) -> Result<DistanceMetric, DnaConfigError> {
+    match metric.to_lowercase().as_str() {
         "euclidean" => Ok(DistanceMetric::Euclidean),
         "cosine" => Ok(DistanceMetric::Cosine),
         "manhattan" => Ok(DistanceMetric::Manhattan),
         "hamming" => Ok(DistanceMetric::Hamming),
-        _ => Err(wasm_error!(WasmErrorInner::Guest(format!(
-            "Unknown distance metric: {}",
-            props.distance_metric
-        )))),
+        "inner_product" | "innerproduct" | "dot" => Ok(DistanceMetric::InnerProduct),
+        _ => Err(DnaConfigError::UnknownDistanceMetric {
+            metric: metric.to_string(),
+        }),
     }
 }
 
+/// Get the validated index configuration from DNA properties
+pub fn get_index_config() -> ExternResult<IndexConfig> {
+    let props = get_dna_properties()?;
+    Ok(IndexConfig::from_properties(&props)?)
+}
+
+/// Get the distance metric from DNA properties
+pub fn get_distance_metric() -> ExternResult<DistanceMetric> {
+    Ok(get_index_config()?.distance_metric)
+}
+
+#[cfg(feature = "holochain_conductor")]
+use holochain::prelude::DnaFile;
+
+#[cfg(feature = "holochain_conductor")]
+fn conductor_required(operation: &str) -> WasmError {
+    wasm_error!(WasmErrorInner::Guest(format!(
+        "{} needs the Holochain conductor integration, which isn't implemented yet",
+        operation
+    )))
+}
+
 /// Create a new DNA template for a vector index
+#[cfg(feature = "holochain_conductor")]
 pub fn create_vector_index_dna(
     _name: String,
     _dimensions: usize,
     _distance_metric: DistanceMetric,
 ) -> ExternResult<DnaFile> {
-    panic!("This function should be provided by the Holochain conductor");
+    Err(conductor_required("Creating a DNA"))
 }
 
 /// Register DNA with the conductor
+#[cfg(feature = "holochain_conductor")]
 pub fn register_dna(_dna: DnaFile) -> ExternResult<DnaHash> {
-    panic!("This function should be provided by the Holochain conductor");
+    Err(conductor_required("Registering a DNA"))
 }
 
 /// Create a cell from a DNA and install it
+#[cfg(feature = "holochain_conductor")]
 pub fn create_and_install_cell(_dna_hash: DnaHash) -> ExternResult<AgentPubKey> {
-    panic!("This function should be provided by the Holochain conductor");
+    Err(conductor_required("Installing a cell"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn properties(distance_metric: &str, dimensions: usize) -> DnaProperties {
+        DnaProperties {
+            name: "rose-forest".to_string(),
+            uuid: "00000000-0000-0000-0000-000000000000".to_string(),
+            distance_metric: distance_metric.to_string(),
+            dimensions,
+            similarity_threshold: 0.8,
+        }
+    }
+
+    #[test]
+    fn properties_configure_the_index() {
+        let config = IndexConfig::from_properties(&properties("Cosine", 3)).unwrap();
+        assert_eq!(config.distance_metric, DistanceMetric::Cosine);
+        assert_eq!(config.dimensions, 3);
+
+        let index = config.create_index().unwrap();
+        assert_eq!(index.dimensions(), 3);
+        assert_eq!(index.distance_metric(), DistanceMetric::Cosine);
+    }
+
+    #[test]
+    fn invalid_properties_are_rejected() {
+        assert_eq!(
+            IndexConfig::from_properties(&properties("chebyshev", 3)),
+            Err(DnaConfigError::UnknownDistanceMetric {
+                metric: "chebyshev".to_string()
+            })
+        );
+        assert!(matches!(
+            IndexConfig::from_properties(&properties("cosine", 0)),
+            Err(DnaConfigError::InvalidDimensions { .. })
+        ));
+        let mut props = properties("cosine", 3);
+        props.similarity_threshold = 1.5;
+        assert!(matches!(
+            IndexConfig::from_properties(&props),
+            Err(DnaConfigError::InvalidSimilarityThreshold { .. })
+        ));
+    }
+
+    #[test]
+    fn dimension_mismatches_are_structured() {
+        let config = IndexConfig::from_properties(&properties("euclidean", 3)).unwrap();
+        assert!(config.check_dimensions("values", &[1.0, 2.0, 3.0]).is_ok());
+
+        let error = config.check_dimensions("query", &[1.0]).unwrap_err();
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["error"], "dimension_mismatch");
+        assert_eq!(json["field"], "query");
+        assert_eq!(json["expected"], 3);
+        assert_eq!(json["actual"], 1);
+    }
 }
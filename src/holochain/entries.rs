@@ -1,13 +1,16 @@
 //! Entry definitions for Holochain integration
 
+use crate::holochain::dna::get_index_config;
+use crate::holochain::validation::{
+    validate_audit_trail, validate_value_flow, validate_vector_entry,
+};
+use crate::holochain::value_flow::ValueFlow;
+use crate::holochain::{AuditTrail, VectorEntry};
 use hdk::prelude::*;
-use uuid::Uuid;
-use std::collections::HashMap;
-use serde::{Serialize, Deserialize};
-use crate::holochain::DnaProperties;
+use serde::{Deserialize, Serialize};
 
 /// Entry definition for knowledge contributions
-#[hdk_entry(id = "knowledge_contribution")]
+#[hdk_entry_helper]
 #[derive(Clone)]
 pub struct KnowledgeContribution {
     pub content_hash: String,
@@ -24,7 +27,7 @@ pub struct Metadata {
 }
 
 /// Entry definition for conflict resolution
-#[hdk_entry(id = "conflict_resolution")]
+#[hdk_entry_helper]
 #[derive(Clone)]
 pub struct ConflictResolution {
     pub conflict_id: String,
@@ -44,7 +47,7 @@ pub enum ResolutionType {
 }
 
 /// Entry definition for community arbitration
-#[hdk_entry(id = "arbitration_case")]
+#[hdk_entry_helper]
 #[derive(Clone)]
 pub struct ArbitrationCase {
     pub id: String,
@@ -79,48 +82,54 @@ pub struct ArbitrationVote {
 /// Trinary arbitration states
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum ArbitrationState {
-    Resolve,  // +1: Conflict resolved through understanding
-    Review,   //  0: Needs community input
-    Reject,   // -1: Harmful content requiring intervention
+    Resolve, // +1: Conflict resolved through understanding
+    Review,  //  0: Needs community input
+    Reject,  // -1: Harmful content requiring intervention
 }
 
 /// Create entry validation
 #[hdk_extern]
-fn validate(op: Op) -> ExternResult<ValidateCallbackResult> {
-    match op.flattened::<(), KnowledgeContribution>()? {
-        FlatOp::StoreEntry(store_entry) => {
-            match store_entry {
-                OpEntry::CreateEntry { app_entry, action } => {
-                    // Validate knowledge contributions
-                    match app_entry {
-                        AppEntryType::KnowledgeContribution(contribution) => {
-                            // Validate embedding dimensions
-                            let props = crate::holochain::dna::get_dna_properties()?;
-                            if contribution.embedding.len() != props.dimensions {
-                                return Ok(ValidateCallbackResult::Invalid(
-                                    "Embedding dimensions don't match DNA properties".to_string(),
-                                ));
-                            }
-                            
-                            // More validation rules can be added here
-                            Ok(ValidateCallbackResult::Valid)
-                        },
-                        AppEntryType::ArbitrationCase(case) => {
-                            // Validate arbitration case
-                            if case.status != ArbitrationStatus::Open && case.resolution.is_none() {
-                                return Ok(ValidateCallbackResult::Invalid(
-                                    "Closed cases must have a resolution".to_string(),
-                                ));
-                            }
-                            
-                            Ok(ValidateCallbackResult::Valid)
-                        },
-                        _ => Ok(ValidateCallbackResult::Valid),
-                    }
-                },
-                _ => Ok(ValidateCallbackResult::Valid),
-            }
-        },
-        _ => Ok(ValidateCallbackResult::Valid),
+pub fn validate(op: Op) -> ExternResult<ValidateCallbackResult> {
+    let (author, entry) = match op {
+        Op::StoreEntry(StoreEntry { action, entry }) => (action.hashed.author().clone(), entry),
+        _ => return Ok(ValidateCallbackResult::Valid),
+    };
+
+    if let Ok(contribution) = KnowledgeContribution::try_from(&entry) {
+        // Validate embedding dimensions
+        let config = get_index_config()?;
+        if let Err(e) = config.check_dimensions("embedding", &contribution.embedding) {
+            return Ok(ValidateCallbackResult::Invalid(e.to_string()));
+        }
+
+        // More validation rules can be added here
+        return Ok(ValidateCallbackResult::Valid);
+    }
+    if let Ok(case) = ArbitrationCase::try_from(&entry) {
+        if case.status != ArbitrationStatus::Open && case.resolution.is_none() {
+            return Ok(ValidateCallbackResult::Invalid(
+                "Closed cases must have a resolution".to_string(),
+            ));
+        }
+        return Ok(ValidateCallbackResult::Valid);
+    }
+    if let Ok(vector) = VectorEntry::try_from(&entry) {
+        let result = validate_vector_entry(&vector);
+        if result != ValidateCallbackResult::Valid {
+            return Ok(result);
+        }
+        let config = get_index_config()?;
+        return match config.check_dimensions("vector", &vector.values) {
+            Ok(()) => Ok(ValidateCallbackResult::Valid),
+            Err(e) => Ok(ValidateCallbackResult::Invalid(e.to_string())),
+        };
     }
-}
\ No newline at end of file
+    if let Ok(audit) = AuditTrail::try_from(&entry) {
+        return validate_audit_trail(&audit, &author);
+    }
+    if let Ok(flow) = ValueFlow::try_from(&entry) {
+        return Ok(validate_value_flow(&flow));
+    }
+
+    Ok(ValidateCallbackResult::Valid)
+}
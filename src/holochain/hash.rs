@@ -1,11 +1,11 @@
-use digest::Digest;
+use sha2::Digest;
 
+#[cfg(feature = "blake3")]
+use blake3::Hasher as Blake3;
 #[cfg(feature = "sha2")]
 use sha2::Sha256;
 #[cfg(feature = "sha3")]
 use sha3::Sha3_256;
-#[cfg(feature = "blake3")]
-use blake3::Hasher as Blake3;
 
 /// Extension trait providing a convenience `hash_bytes` method for digest
 /// implementations.
@@ -1,25 +1,32 @@
 //! Holochain integration module for Amazon Rose Forest
+//!
+//! The DNA's zome: entry and link types, externs and validation callbacks.
+//! Built with the `holochain_zome` feature.
 
+pub mod anchors;
+pub mod arbitration;
 pub mod dna;
-pub mod zome;
 pub mod entries;
-pub mod utils;
-pub mod arbitration;
-pub mod transparency;
 pub mod hash;
+pub mod transparency;
+pub mod utils;
+pub mod validation;
 pub mod value_flow;
+pub mod zome;
 
 pub use utils::sys_time;
 
-use hdk::prelude::*;
-use uuid::Uuid;
-use crate::core::vector::Vector;
 use crate::core::centroid::Centroid;
+use crate::core::vector::Vector;
+use crate::holochain::entries::{ArbitrationCase, ConflictResolution, KnowledgeContribution};
+use crate::holochain::value_flow::{Reputation, ValueFlow};
+use hdk::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use serde::{Serialize, Deserialize};
+use uuid::Uuid;
 
 /// Entry definition for a vector in Holochain
-#[hdk_entry(id = "vector")]
+#[hdk_entry_helper]
 #[derive(Clone)]
 pub struct VectorEntry {
     pub id: String,
@@ -43,14 +50,14 @@ impl From<Vector> for VectorEntry {
 
 impl TryFrom<VectorEntry> for Vector {
     type Error = String;
-    
+
     fn try_from(entry: VectorEntry) -> Result<Self, Self::Error> {
         Ok(Vector::new(entry.values))
     }
 }
 
 /// Entry definition for a centroid in Holochain
-#[hdk_entry(id = "centroid")]
+#[hdk_entry_helper]
 #[derive(Clone)]
 pub struct CentroidEntry {
     pub id: String,
@@ -73,31 +80,70 @@ impl From<Centroid> for CentroidEntry {
 }
 
 /// Entry definition for an audit trail in Holochain
-#[hdk_entry(id = "audit_trail")]
+#[hdk_entry_helper]
 #[derive(Clone)]
 pub struct AuditTrail {
     /// The action being audited
     pub action: String,
-    
+
     /// Who initiated the action
     pub initiator: AgentPubKey,
-    
+
     /// Who participated in validation
     pub validators: Vec<AgentPubKey>,
-    
+
     /// Cryptographic proof of decision process
     #[serde(with = "serde_bytes")]
     pub decision_proof: Vec<u8>,
-    
+
     /// Human-readable justification
     pub justification: String,
-    
+
     /// Timestamp with microsecond precision
     pub timestamp: u64,
 }
 
-/// DNA properties configuration
-#[derive(Serialize, Deserialize, Debug)]
+/// Every entry type the zome defines
+#[hdk_entry_defs]
+#[unit_enum(UnitEntryTypes)]
+pub enum EntryTypes {
+    Vector(VectorEntry),
+    Centroid(CentroidEntry),
+    AuditTrail(AuditTrail),
+    KnowledgeContribution(KnowledgeContribution),
+    ConflictResolution(ConflictResolution),
+    ArbitrationCase(ArbitrationCase),
+    ValueFlow(ValueFlow),
+    Reputation(Reputation),
+}
+
+/// Every link type the zome defines
+#[hdk_link_types]
+pub enum LinkTypes {
+    /// Between the components of an index or anchor path
+    Path,
+    /// From `vectors_by_id` to vectors, tagged with their ID
+    Vector,
+    /// From `centroids_by_id` to centroids, tagged with their ID
+    Centroid,
+    /// From an hourly audit anchor to its audit trails
+    AuditTrail,
+    /// From the arbitration case indexes to cases
+    ArbitrationCase,
+    /// From `knowledge.<kind>` to knowledge contributions
+    Knowledge,
+    /// From `value_flow.<agent>` to the flows the agent is party to
+    ValueFlow,
+    /// From `reputation.<agent>` to the agent's reputation
+    Reputation,
+    /// From `capabilities` to agents, tagged with the capability
+    Capability,
+    /// From `agents` to every agent that joined, so signals can reach them
+    Agent,
+}
+
+/// DNA properties configuration; validated into a [`dna::IndexConfig`]
+#[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone)]
 pub struct DnaProperties {
     pub name: String,
     pub uuid: String,
@@ -109,24 +155,28 @@ pub struct DnaProperties {
 /// Initialize the DNA with the provided properties
 #[hdk_extern]
 pub fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    // Refuse to start a cell whose properties don't describe a usable index
+    let props = dna::get_dna_properties()?;
+    let config = match dna::IndexConfig::from_properties(&props) {
+        Ok(config) => config,
+        Err(e) => return Ok(InitCallbackResult::Fail(e.to_string())),
+    };
+
     // Create necessary indexes
     create_index("vectors_by_id")?;
     create_index("centroids_by_id")?;
-    create_index("audit_trails_by_timestamp")?;
-    
-    // Get DNA properties
-    let props: DnaProperties = dna_info()?.properties
-        .try_into()
-        .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
-    
-    debug!("Initializing Rose Forest DNA: {}", props.name);
-    
+    create_index(anchors::AUDIT_ANCHOR_ROOT)?;
+    utils::register_agent()?;
+
+    debug!(
+        "Initializing Rose Forest DNA: {} ({} dimensions, {:?} distance)",
+        config.name, config.dimensions, config.distance_metric
+    );
+
     Ok(InitCallbackResult::Pass)
 }
 
 /// Create a path for indexing entries
 fn create_index(name: &str) -> ExternResult<()> {
-    let path = Path::from(name);
-    path.ensure()?;
-    Ok(())
-}
\ No newline at end of file
+    Path::from(name).typed(LinkTypes::Path)?.ensure()
+}
@@ -1,29 +1,30 @@
 //! Transparency and audit trail functionality
 
+use crate::holochain::anchors::{anchor_audit_trail, count_all_audit_trails, recent_audit_trails};
+use crate::holochain::utils::sys_time;
+use crate::holochain::validation::sign_audit_trail;
+use crate::holochain::{AuditTrail, EntryTypes};
 use hdk::prelude::*;
-use crate::holochain::entries::AuditTrail;
-use crate::holochain::utils::{sys_time, create_path, timestamp_tag};
-use std::collections::HashMap;
-use crate::holochain::hash::default_hash_bytes;
 
 /// Public API for transparency verification
 #[hdk_extern]
 pub fn audit_trail(contribution_hash: EntryHash) -> ExternResult<AuditTrail> {
-    let history = get_details(contribution_hash, GetOptions::default())?
-        .ok_or(wasm_error!(WasmErrorInner::Guest("Entry not found".to_string())))?;
-    
+    let history = get_details(contribution_hash, GetOptions::default())?.ok_or(wasm_error!(
+        WasmErrorInner::Guest("Entry not found".to_string())
+    ))?;
+
     // Reconstruct complete decision history
     let audit_trail = reconstruct_audit_trail(history)?;
-    
+
     // Verify cryptographic integrity
     verify_merkle_proof(&audit_trail.decision_proof)?;
-    
+
     Ok(audit_trail)
 }
 
 /// Public interface for querying system transparency
-#[hdk_extern] 
-pub fn query_transparency_metrics() -> ExternResult<TransparencyMetrics> {
+#[hdk_extern]
+pub fn query_transparency_metrics(_: ()) -> ExternResult<TransparencyMetrics> {
     Ok(TransparencyMetrics {
         total_decisions: count_all_decisions()?,
         public_audit_rate: calculate_audit_accessibility()?,
@@ -36,60 +37,37 @@ pub fn query_transparency_metrics() -> ExternResult<TransparencyMetrics> {
 #[hdk_extern]
 pub fn create_audit_entry(input: AuditInput) -> ExternResult<EntryHash> {
     let now = sys_time()?;
-    
+
     // Get validators for this entry
     // In a real implementation, this would be determined by DHT validation
     let validators = vec![agent_info()?.agent_latest_pubkey];
-    
-    let audit = AuditTrail {
+
+    let mut audit = AuditTrail {
         action: input.action,
         initiator: agent_info()?.agent_latest_pubkey,
         validators,
-        decision_proof: generate_merkle_proof(&input.details)?,
+        decision_proof: Vec::new(),
         justification: input.details,
         timestamp: now,
     };
-    
+    // Validators reject audit trails that aren't signed by their initiator
+    sign_audit_trail(&mut audit)?;
+
     // Create entry
-    let audit_hash = create_entry(&audit)?;
-    
-    // Add to audit index
-    let path = create_path("audit_trails", vec![&now.to_string()])?;
-    create_link(path.path_entry_hash()?, audit_hash.clone(), timestamp_tag())?;
-    
+    create_entry(&EntryTypes::AuditTrail(audit.clone()))?;
+    let audit_hash = hash_entry(&audit)?;
+
+    // Add to the audit trail anchor for the current hour
+    anchor_audit_trail(audit_hash.clone(), now)?;
+
     Ok(audit_hash)
 }
 
-/// Query recent audit trails
+/// Query recent audit trails, newest first
 #[hdk_extern]
 pub fn get_recent_audits(count: usize) -> ExternResult<Vec<AuditTrail>> {
-    let path = Path::from("audit_trails");
-    let links = get_links(path.path_entry_hash()?, None)?;
-    
-    // Sort links by timestamp (descending)
-    let mut sorted_links = links;
-    sorted_links.sort_by(|a, b| {
-        let a_time = u64::from_be_bytes(a.tag.0[0..8].try_into().unwrap_or([0; 8]));
-        let b_time = u64::from_be_bytes(b.tag.0[0..8].try_into().unwrap_or([0; 8]));
-        b_time.cmp(&a_time)
-    });
-    
-    // Limit to requested count
-    sorted_links.truncate(count);
-    
-    // Get audit entries
-    let audits = sorted_links
-        .into_iter()
-        .map(|link| {
-            let entry: AuditTrail = get_entry(link.target)?
-                .ok_or(wasm_error!(WasmErrorInner::Guest("Audit entry not found".to_string())))?
-                .try_into()
-                .map_err(|e: SerializedBytesError| wasm_error!(WasmErrorInner::Serialize(e)))?;
-            Ok(entry)
-        })
-        .collect::<ExternResult<Vec<AuditTrail>>>()?;
-    
-    Ok(audits)
+    // Walks back through the hourly anchors rather than one global index
+    recent_audit_trails(sys_time()?, count)
 }
 
 /// Transparency metrics
@@ -129,19 +107,9 @@ fn verify_merkle_proof(_proof: &[u8]) -> ExternResult<()> {
     Ok(())
 }
 
-/// Generate a Merkle proof
-fn generate_merkle_proof(content: &str) -> ExternResult<Vec<u8>> {
-    // In a real implementation, we would create a proper Merkle proof
-    // This would involve creating a Merkle tree and generating a proof.
-    // For now, hash the content as a placeholder proof.
-    Ok(default_hash_bytes(content.as_bytes()))
-}
-
-/// Count all decisions in the system
+/// Count every decision with an audit trail
 fn count_all_decisions() -> ExternResult<usize> {
-    let path = Path::from("audit_trails");
-    let links = get_links(path.path_entry_hash()?, None)?;
-    Ok(links.len())
+    count_all_audit_trails()
 }
 
 /// Calculate audit accessibility
@@ -163,4 +131,4 @@ fn calculate_reversal_rate() -> ExternResult<f32> {
     // This would calculate real metrics in a real implementation
     // For now, return a placeholder
     Ok(0.02) // 2% reversal rate
-}
\ No newline at end of file
+}
@@ -1,11 +1,11 @@
 //! Utility functions for Holochain integration
 
-use hdk::prelude::*;
 use crate::core::vector::Vector;
-use crate::holochain::VectorEntry;
-use uuid::Uuid;
+use crate::holochain::{LinkTypes, VectorEntry};
+use hdk::prelude::*;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use sha2::{Sha256, Digest};
+use uuid::Uuid;
 
 /// Convert a Vector to a Holochain VectorEntry
 pub fn vector_to_entry(vector: Vector, metadata: Option<HashMap<String, String>>) -> VectorEntry {
@@ -25,35 +25,89 @@ pub fn entry_to_vector(entry: VectorEntry) -> Vector {
 
 /// Get the current system time
 pub fn sys_time() -> ExternResult<u64> {
-    let time = sys_time_precise()?;
+    let time = hdk::time::sys_time()?;
     Ok(time.as_micros() as u64)
 }
 
+/// Anchor every agent links itself from when its cell starts
+const AGENTS_ANCHOR: &str = "agents";
+
+/// Link this agent from the agents anchor so peers can signal it
+pub fn register_agent() -> ExternResult<()> {
+    let path = Path::from(AGENTS_ANCHOR);
+    create_link(
+        path.path_entry_hash()?,
+        agent_info()?.agent_latest_pubkey,
+        LinkTypes::Agent,
+        LinkTag::new(vec![]),
+    )?;
+    Ok(())
+}
+
+/// Send a signal to every other agent that has joined
+pub fn signal_peers<I>(signal: I) -> ExternResult<()>
+where
+    I: Serialize + std::fmt::Debug,
+{
+    let me = agent_info()?.agent_latest_pubkey;
+    let peers: Vec<AgentPubKey> = get_links(
+        Path::from(AGENTS_ANCHOR).path_entry_hash()?,
+        LinkTypes::Agent,
+        None,
+    )?
+    .into_iter()
+    .filter_map(|link| link.target.into_entry_hash())
+    .map(AgentPubKey::from)
+    .filter(|agent| agent != &me)
+    .collect();
+    remote_signal(signal, peers)
+}
+
 /// Check if an agent has a specific capability
 pub fn agent_has_capability(agent: &AgentPubKey, capability: &str) -> ExternResult<bool> {
     // This is a simplified implementation
     // In a real-world scenario, this would check against a capability grant
-    
+
     // Get all capability grants
     let path = Path::from("capabilities");
-    let links = get_links(path.path_entry_hash()?, Some(LinkTag::new(capability.as_bytes())))?;
-    
+    let links = get_links(
+        path.path_entry_hash()?,
+        LinkTypes::Capability,
+        Some(LinkTag::new(capability.as_bytes())),
+    )?;
+
     // Check if the agent is in the list
     Ok(links.into_iter().any(|link| {
-        let target_agent = AgentPubKey::from_raw_39(link.tag.0.clone())
+        AgentPubKey::from_raw_39(link.tag.0.clone())
             .map(|key| &key == agent)
-            .unwrap_or(false);
-        target_agent
+            .unwrap_or(false)
     }))
 }
 
+/// Fetch the app entry a link or hash points at, if it exists
+pub fn get_app_entry<T>(hash: impl Into<AnyLinkableHash>) -> ExternResult<Option<T>>
+where
+    T: TryFrom<SerializedBytes, Error = SerializedBytesError>,
+{
+    let hash = hash
+        .into()
+        .into_entry_hash()
+        .ok_or(wasm_error!(WasmErrorInner::Guest(
+            "Expected an entry hash".to_string()
+        )))?;
+    match get(hash, GetOptions::default())? {
+        Some(record) => record.entry().to_app_option().map_err(|e| wasm_error!(e)),
+        None => Ok(None),
+    }
+}
+
 /// Create a path for indexing
 pub fn create_path(base: &str, components: Vec<&str>) -> ExternResult<Path> {
     let mut path = Path::from(base);
     for component in components {
-        path = path.append_component(component)?;
+        path.append_component(component.into());
     }
-    path.ensure()?;
+    path.clone().typed(LinkTypes::Path)?.ensure()?;
     Ok(path)
 }
 
@@ -69,20 +123,20 @@ pub fn hash_content(content: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(content.as_bytes());
     let result = hasher.finalize();
-    
+
     // Convert to hexadecimal string
-    let hash_string = result.iter()
+    let hash_string = result
+        .iter()
         .map(|b| format!("{:02x}", b))
         .collect::<String>();
-        
+
     hash_string
 }
 
 /// Generate an embedding from text
-pub fn generate_embedding(text: &str) -> Vec<f32> {
+pub fn generate_embedding(_text: &str) -> Vec<f32> {
     // This is a stub implementation
     // In a real-world scenario, this would use a proper embedding model
     let dimensions = 128;
-    let mut rng = rand::thread_rng();
     (0..dimensions).map(|_| rand::random::<f32>()).collect()
-}
\ No newline at end of file
+}
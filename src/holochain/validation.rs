@@ -0,0 +1,302 @@
+//! Validation rules for entries published to the DHT
+
+use crate::holochain::value_flow::ValueFlow;
+use crate::holochain::{AuditTrail, VectorEntry};
+use hdk::prelude::*;
+
+/// Largest vector accepted onto the DHT
+pub const MAX_VECTOR_DIMENSIONS: usize = 4096;
+
+/// Length of an Ed25519 signature stored in `AuditTrail::decision_proof`
+pub const SIGNATURE_LENGTH: usize = 64;
+
+/// Check a vector entry's dimensions against its values and the global limit
+pub fn validate_vector_entry(vector: &VectorEntry) -> ValidateCallbackResult {
+    if vector.dimensions == 0 {
+        return ValidateCallbackResult::Invalid(
+            "Vector must have at least one dimension".to_string(),
+        );
+    }
+
+    if vector.dimensions > MAX_VECTOR_DIMENSIONS {
+        return ValidateCallbackResult::Invalid(format!(
+            "Vector has {} dimensions, maximum is {}",
+            vector.dimensions, MAX_VECTOR_DIMENSIONS
+        ));
+    }
+
+    if vector.values.len() != vector.dimensions {
+        return ValidateCallbackResult::Invalid(format!(
+            "Vector declares {} dimensions but has {} values",
+            vector.dimensions,
+            vector.values.len()
+        ));
+    }
+
+    if vector.values.iter().any(|v| !v.is_finite()) {
+        return ValidateCallbackResult::Invalid("Vector values must be finite".to_string());
+    }
+
+    ValidateCallbackResult::Valid
+}
+
+/// Bytes the initiator signs to produce an audit trail's decision proof
+pub fn audit_trail_signing_payload(audit: &AuditTrail) -> Vec<u8> {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(audit.action.as_bytes());
+    payload.push(0);
+    payload.extend_from_slice(audit.initiator.get_raw_39());
+    payload.extend_from_slice(audit.justification.as_bytes());
+    payload.push(0);
+    payload.extend_from_slice(&audit.timestamp.to_be_bytes());
+    payload
+}
+
+/// Sign an audit trail as the current agent, filling in its decision proof
+pub fn sign_audit_trail(audit: &mut AuditTrail) -> ExternResult<()> {
+    let signature = sign_raw(audit.initiator.clone(), audit_trail_signing_payload(audit))?;
+    audit.decision_proof = signature.0.to_vec();
+    Ok(())
+}
+
+/// Verify that an audit trail was published by its initiator and that its
+/// decision proof is the initiator's signature
+pub fn validate_audit_trail(
+    audit: &AuditTrail,
+    author: &AgentPubKey,
+) -> ExternResult<ValidateCallbackResult> {
+    if author != &audit.initiator {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Audit trails must be authored by their initiator".to_string(),
+        ));
+    }
+
+    if audit.decision_proof.len() != SIGNATURE_LENGTH {
+        return Ok(ValidateCallbackResult::Invalid(format!(
+            "Decision proof must be a {}-byte signature, got {} bytes",
+            SIGNATURE_LENGTH,
+            audit.decision_proof.len()
+        )));
+    }
+
+    let mut bytes = [0u8; SIGNATURE_LENGTH];
+    bytes.copy_from_slice(&audit.decision_proof);
+
+    let valid = verify_signature_raw(
+        audit.initiator.clone(),
+        Signature::from(bytes),
+        audit_trail_signing_payload(audit),
+    )?;
+
+    if valid {
+        Ok(ValidateCallbackResult::Valid)
+    } else {
+        Ok(ValidateCallbackResult::Invalid(
+            "Decision proof is not a valid signature by the initiator".to_string(),
+        ))
+    }
+}
+
+/// Check a value flow with the rules the value flow zome applies: distinct
+/// parties, utility bounds or a valid private utility proof, governance
+/// weight and reputation shift
+pub fn validate_value_flow(flow: &ValueFlow) -> ValidateCallbackResult {
+    match flow.terms().validate() {
+        Ok(()) => ValidateCallbackResult::Valid,
+        Err(reason) => ValidateCallbackResult::Invalid(reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::governance::zkp::{UtilityBounds, UtilityProof, ZKP};
+    use crate::holochain::entries::validate;
+    use rose_forest_zome_testing::store_entry_op;
+
+    fn agent(byte: u8) -> AgentPubKey {
+        AgentPubKey::from_raw_36(vec![byte; 36])
+    }
+
+    fn rejected(op: Op) -> bool {
+        matches!(validate(op), Ok(ValidateCallbackResult::Invalid(_)))
+    }
+
+    fn vector(values: Vec<f32>, dimensions: usize) -> VectorEntry {
+        VectorEntry {
+            id: "test".to_string(),
+            values,
+            dimensions,
+            metadata: None,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn accepts_consistent_vector() {
+        let result = validate_vector_entry(&vector(vec![1.0, 2.0, 3.0], 3));
+        assert_eq!(result, ValidateCallbackResult::Valid);
+    }
+
+    #[test]
+    fn rejects_dimension_mismatch() {
+        let result = validate_vector_entry(&vector(vec![1.0, 2.0], 3));
+        assert!(matches!(result, ValidateCallbackResult::Invalid(_)));
+    }
+
+    #[test]
+    fn rejects_oversized_and_empty_vectors() {
+        let big = vector(
+            vec![0.0; MAX_VECTOR_DIMENSIONS + 1],
+            MAX_VECTOR_DIMENSIONS + 1,
+        );
+        assert!(matches!(
+            validate_vector_entry(&big),
+            ValidateCallbackResult::Invalid(_)
+        ));
+        assert!(matches!(
+            validate_vector_entry(&vector(vec![], 0)),
+            ValidateCallbackResult::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn rejects_non_finite_values() {
+        let result = validate_vector_entry(&vector(vec![1.0, f32::NAN], 2));
+        assert!(matches!(result, ValidateCallbackResult::Invalid(_)));
+    }
+
+    fn flow(utility: f32, utility_proof: Option<UtilityProof>) -> ValueFlow {
+        ValueFlow {
+            from: agent(1),
+            to: agent(2),
+            utility,
+            governance_weight: 1.0,
+            reputation_shift: vec![0.1],
+            utility_proof,
+        }
+    }
+
+    #[test]
+    fn checks_private_utility_proofs() {
+        let (proof, _) = ZKP::new()
+            .prove_utility(42.0, UtilityBounds::default())
+            .unwrap();
+        assert_eq!(
+            validate_value_flow(&flow(0.0, Some(proof.clone()))),
+            ValidateCallbackResult::Valid
+        );
+        assert_eq!(
+            validate_value_flow(&flow(42.0, None)),
+            ValidateCallbackResult::Valid
+        );
+
+        // Publishing the amount defeats the commitment
+        assert!(matches!(
+            validate_value_flow(&flow(42.0, Some(proof.clone()))),
+            ValidateCallbackResult::Invalid(_)
+        ));
+
+        let mut forged = proof;
+        forged.proof[0] ^= 1;
+        assert!(matches!(
+            validate_value_flow(&flow(0.0, Some(forged))),
+            ValidateCallbackResult::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn applies_the_zome_rules() {
+        let mut to_self = flow(42.0, None);
+        to_self.to = to_self.from.clone();
+        assert!(matches!(
+            validate_value_flow(&to_self),
+            ValidateCallbackResult::Invalid(_)
+        ));
+
+        for utility in [0.0, -1.0, 2_000_000.0, f32::NAN] {
+            assert!(matches!(
+                validate_value_flow(&flow(utility, None)),
+                ValidateCallbackResult::Invalid(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn validate_rejects_invalid_value_flow_ops() {
+        assert_eq!(
+            validate(store_entry_op(agent(1), flow(42.0, None))).unwrap(),
+            ValidateCallbackResult::Valid
+        );
+
+        let mut to_self = flow(42.0, None);
+        to_self.to = to_self.from.clone();
+        assert!(rejected(store_entry_op(agent(1), to_self)));
+        assert!(rejected(store_entry_op(agent(1), flow(-1.0, None))));
+
+        let mut heavy = flow(42.0, None);
+        heavy.governance_weight = 1.5;
+        assert!(rejected(store_entry_op(agent(1), heavy)));
+    }
+
+    #[test]
+    fn validate_rejects_invalid_vector_ops() {
+        for entry in [
+            vector(vec![1.0, 2.0], 3),
+            vector(vec![], 0),
+            vector(vec![1.0, f32::NAN], 2),
+        ] {
+            assert!(rejected(store_entry_op(agent(1), entry)));
+        }
+    }
+
+    fn audit_trail(decision_proof: Vec<u8>) -> AuditTrail {
+        AuditTrail {
+            action: "approve_modification".to_string(),
+            initiator: agent(1),
+            validators: vec![agent(2)],
+            decision_proof,
+            justification: "Passed review".to_string(),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn validate_accepts_audit_trails_signed_by_their_initiator() {
+        let signed = audit_trail(vec![7; SIGNATURE_LENGTH]);
+        let payload = audit_trail_signing_payload(&signed);
+        // The host confirms the proof is the initiator's signature of the
+        // signing payload, and nothing else
+        let mut hdk = MockHdkT::new();
+        hdk.expect_verify_signature()
+            .withf(move |verify| {
+                verify.key == agent(1)
+                    && verify.signature == Signature([7; SIGNATURE_LENGTH])
+                    && verify.data == payload
+            })
+            .returning(|_| Ok(true));
+        set_hdk(hdk);
+
+        assert_eq!(
+            validate(store_entry_op(agent(1), signed.clone())).unwrap(),
+            ValidateCallbackResult::Valid
+        );
+
+        // A valid signature doesn't let someone else publish the trail
+        assert!(rejected(store_entry_op(agent(2), signed)));
+    }
+
+    #[test]
+    fn validate_rejects_unsigned_audit_trail_ops() {
+        // The host reports the proof isn't the initiator's signature
+        let mut hdk = MockHdkT::new();
+        hdk.expect_verify_signature().returning(|_| Ok(false));
+        set_hdk(hdk);
+        let forged = audit_trail(vec![7; SIGNATURE_LENGTH]);
+        assert!(rejected(store_entry_op(agent(1), forged)));
+
+        // Malformed proofs are rejected without asking the host
+        assert!(rejected(store_entry_op(agent(1), audit_trail(vec![7; 10]))));
+        assert!(rejected(store_entry_op(agent(1), audit_trail(Vec::new()))));
+    }
+}
@@ -1,16 +1,37 @@
+use crate::governance::zkp::UtilityProof;
+use crate::holochain::utils::get_app_entry;
+use crate::holochain::{EntryTypes, LinkTypes};
 use hdk::prelude::*;
+use rose_forest_value_flow::FlowTerms;
 
-#[hdk_entry(id = "value_flow")]
+#[hdk_entry_helper]
 #[derive(Clone)]
 pub struct ValueFlow {
     pub from: AgentPubKey,
     pub to: AgentPubKey,
+    /// Zero when the utility is private and committed in `utility_proof`
     pub utility: f32,
     pub governance_weight: f32,
     pub reputation_shift: Vec<f32>,
+    #[serde(default)]
+    pub utility_proof: Option<UtilityProof>,
 }
 
-#[hdk_entry(id = "reputation")]
+impl ValueFlow {
+    /// What the shared value flow rules check
+    pub fn terms(&self) -> FlowTerms<'_, AgentPubKey> {
+        FlowTerms {
+            from: &self.from,
+            to: &self.to,
+            utility: self.utility,
+            governance_weight: self.governance_weight,
+            reputation_shift: &self.reputation_shift,
+            utility_proof: self.utility_proof.as_ref(),
+        }
+    }
+}
+
+#[hdk_entry_helper]
 #[derive(Clone)]
 pub struct Reputation {
     pub agent: AgentPubKey,
@@ -19,33 +40,35 @@ pub struct Reputation {
 
 #[hdk_extern]
 pub fn create_value_flow(value_flow: ValueFlow) -> ExternResult<EntryHash> {
-    let entry_hash = create_entry(&value_flow)?;
+    create_entry(&EntryTypes::ValueFlow(value_flow.clone()))?;
+    let entry_hash = hash_entry(&value_flow)?;
     let from_path = Path::from(format!("value_flow.{}", value_flow.from));
-    create_link(from_path.path_entry_hash()?, entry_hash.clone(), LinkTag::new(vec![]))?;
+    create_link(
+        from_path.path_entry_hash()?,
+        entry_hash.clone(),
+        LinkTypes::ValueFlow,
+        LinkTag::new(vec![]),
+    )?;
     let to_path = Path::from(format!("value_flow.{}", value_flow.to));
-    create_link(to_path.path_entry_hash()?, entry_hash.clone(), LinkTag::new(vec![]))?;
+    create_link(
+        to_path.path_entry_hash()?,
+        entry_hash.clone(),
+        LinkTypes::ValueFlow,
+        LinkTag::new(vec![]),
+    )?;
     Ok(entry_hash)
 }
 
 #[hdk_extern]
 pub fn get_value_flows_for_agent(agent: AgentPubKey) -> ExternResult<Vec<ValueFlow>> {
     let path = Path::from(format!("value_flow.{}", agent));
-    let links = get_links(path.path_entry_hash()?, None)?;
+    let links = get_links(path.path_entry_hash()?, LinkTypes::ValueFlow, None)?;
     let value_flows: Vec<ValueFlow> = links
-        .into_inner()
-        .iter()
+        .into_iter()
         .map(|link| {
-            get(link.target.clone(), GetOptions::default())
-                .and_then(|element| {
-                    element
-                        .ok_or(wasm_error!(WasmErrorInner::Guest("ValueFlow not found".to_string())))
-                })
-                .and_then(|element| {
-                    element
-                        .entry()
-                        .to_app_option()?
-                        .ok_or(wasm_error!(WasmErrorInner::Guest("ValueFlow not found".to_string())))
-                })
+            get_app_entry(link.target)?.ok_or(wasm_error!(WasmErrorInner::Guest(
+                "ValueFlow not found".to_string()
+            )))
         })
         .collect::<ExternResult<Vec<ValueFlow>>>()?;
     Ok(value_flows)
@@ -54,13 +77,11 @@ pub fn get_value_flows_for_agent(agent: AgentPubKey) -> ExternResult<Vec<ValueFl
 #[hdk_extern]
 pub fn get_reputation(agent: AgentPubKey) -> ExternResult<Option<Reputation>> {
     let path = Path::from(format!("reputation.{}", agent));
-    let links = get_links(path.path_entry_hash()?, None)?;
-    if let Some(link) = links.into_inner().get(0) {
-        let reputation: Reputation = get(link.target.clone(), GetOptions::default())?
-            .ok_or(wasm_error!(WasmErrorInner::Guest("Reputation not found".to_string())))?
-            .entry()
-            .to_app_option()?
-            .ok_or(wasm_error!(WasmErrorInner::Guest("Reputation not found".to_string())))?;
+    let links = get_links(path.path_entry_hash()?, LinkTypes::Reputation, None)?;
+    if let Some(link) = links.into_iter().next() {
+        let reputation: Reputation = get_app_entry(link.target)?.ok_or(wasm_error!(
+            WasmErrorInner::Guest("Reputation not found".to_string())
+        ))?;
         Ok(Some(reputation))
     } else {
         Ok(None)
@@ -69,16 +90,14 @@ pub fn get_reputation(agent: AgentPubKey) -> ExternResult<Option<Reputation>> {
 
 #[hdk_extern]
 pub fn update_reputation(value_flow: ValueFlow) -> ExternResult<()> {
-    let from_reputation = get_reputation(value_flow.from.clone())?
-        .unwrap_or(Reputation {
-            agent: value_flow.from.clone(),
-            reputation: vec![0.5; value_flow.reputation_shift.len()],
-        });
-    let to_reputation = get_reputation(value_flow.to.clone())?
-        .unwrap_or(Reputation {
-            agent: value_flow.to.clone(),
-            reputation: vec![0.5; value_flow.reputation_shift.len()],
-        });
+    let from_reputation = get_reputation(value_flow.from.clone())?.unwrap_or(Reputation {
+        agent: value_flow.from.clone(),
+        reputation: vec![0.5; value_flow.reputation_shift.len()],
+    });
+    let to_reputation = get_reputation(value_flow.to.clone())?.unwrap_or(Reputation {
+        agent: value_flow.to.clone(),
+        reputation: vec![0.5; value_flow.reputation_shift.len()],
+    });
 
     let mut new_from_reputation = from_reputation.reputation.clone();
     let mut new_to_reputation = to_reputation.reputation.clone();
@@ -97,13 +116,25 @@ pub fn update_reputation(value_flow: ValueFlow) -> ExternResult<()> {
         reputation: new_to_reputation,
     };
 
-    let from_entry_hash = create_entry(&new_from_reputation)?;
-    let to_entry_hash = create_entry(&new_to_reputation)?;
+    create_entry(&EntryTypes::Reputation(new_from_reputation.clone()))?;
+    create_entry(&EntryTypes::Reputation(new_to_reputation.clone()))?;
+    let from_entry_hash = hash_entry(&new_from_reputation)?;
+    let to_entry_hash = hash_entry(&new_to_reputation)?;
 
     let from_path = Path::from(format!("reputation.{}", new_from_reputation.agent));
-    create_link(from_path.path_entry_hash()?, from_entry_hash, LinkTag::new(vec![]))?;
+    create_link(
+        from_path.path_entry_hash()?,
+        from_entry_hash,
+        LinkTypes::Reputation,
+        LinkTag::new(vec![]),
+    )?;
     let to_path = Path::from(format!("reputation.{}", new_to_reputation.agent));
-    create_link(to_path.path_entry_hash()?, to_entry_hash, LinkTag::new(vec![]))?;
+    create_link(
+        to_path.path_entry_hash()?,
+        to_entry_hash,
+        LinkTypes::Reputation,
+        LinkTag::new(vec![]),
+    )?;
 
     Ok(())
 }
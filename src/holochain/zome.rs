@@ -1,31 +1,30 @@
 //! Zome functions for Holochain integration
 
-use hdk::prelude::*;
 use crate::core::vector::Vector;
-use crate::holochain::{VectorEntry, CentroidEntry, AuditTrail, sys_time};
-use crate::holochain::dna::get_distance_metric;
+use crate::darwin::governance::DisputeDecision;
+use crate::holochain::anchors::anchor_audit_trail;
+use crate::holochain::arbitration::ArbitrationNotification;
+use crate::holochain::dna::get_index_config;
+use crate::holochain::utils::{get_app_entry, signal_peers};
+use crate::holochain::validation::sign_audit_trail;
+use crate::holochain::{sys_time, AuditTrail, CentroidEntry, EntryTypes, LinkTypes, VectorEntry};
+use crate::ingest::dht::DhtSignal;
+use hdk::prelude::*;
+use serde_json::json;
 use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Add a vector to the DHT
 #[hdk_extern]
 pub fn add_vector(input: VectorInput) -> ExternResult<VectorOutput> {
-    let props = crate::holochain::dna::get_dna_properties()?;
-    
+    let config = get_index_config()?;
+
     // Validate dimensions
-    if input.values.len() != props.dimensions {
-        return Err(wasm_error!(
-            WasmErrorInner::Guest(format!(
-                "Vector dimensions mismatch: expected {}, got {}",
-                props.dimensions,
-                input.values.len()
-            ))
-        ));
-    }
-    
+    config.check_dimensions("values", &input.values)?;
+
     // Create Vector
     let vector = Vector::new(input.values);
-    
+
     // Create VectorEntry
     let id = Uuid::new_v4().to_string();
     let entry = VectorEntry {
@@ -35,72 +34,155 @@ pub fn add_vector(input: VectorInput) -> ExternResult<VectorOutput> {
         metadata: input.metadata.clone(),
         created_at: sys_time()?,
     };
-    
+
     // Create entry in DHT
-    let entry_hash = create_entry(&entry)?;
-    
+    create_entry(&EntryTypes::Vector(entry.clone()))?;
+    let entry_hash = hash_entry(&entry)?;
+
     // Add to vector index
     let path = Path::from("vectors_by_id").path_entry_hash()?;
     let link_tag = LinkTag::new(id.as_bytes());
-    create_link(path, entry_hash, link_tag)?;
-    
+    create_link(path, entry_hash.clone(), LinkTypes::Vector, link_tag)?;
+
     // Create audit trail
-    create_audit_trail("add_vector", 
-        json!({"vector_id": id, "dimensions": vector.dimensions}).to_string())?;
-    
+    create_audit_trail(
+        "add_vector",
+        json!({"vector_id": id, "dimensions": vector.dimensions}).to_string(),
+    )?;
+
+    // Let peers index the vector without waiting for a DHT scan
+    broadcast_signal(DhtSignal::VectorCreated {
+        id: id.clone(),
+        values: entry.values.clone(),
+        metadata: entry.metadata.clone(),
+        author: agent_info()?.agent_latest_pubkey.to_string(),
+        created_at: entry.created_at,
+    })?;
+
     Ok(VectorOutput {
         id,
         entry_hash: entry_hash.to_string(),
     })
 }
 
+/// Publish a centroid to the DHT. Passing the ID of an existing centroid
+/// publishes a newer version of it.
+#[hdk_extern]
+pub fn add_centroid(input: CentroidInput) -> ExternResult<VectorOutput> {
+    let config = get_index_config()?;
+    config.check_dimensions("values", &input.values)?;
+
+    let now = sys_time()?;
+    let id = input.id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let entry = CentroidEntry {
+        id: id.clone(),
+        vector: VectorEntry {
+            id: id.clone(),
+            values: input.values.clone(),
+            dimensions: input.values.len(),
+            metadata: None,
+            created_at: now,
+        },
+        count: input.count,
+        created_at: now,
+        updated_at: now,
+    };
+
+    // Create entry in DHT and add it to the centroid index
+    create_entry(&EntryTypes::Centroid(entry.clone()))?;
+    let entry_hash = hash_entry(&entry)?;
+    let path = Path::from("centroids_by_id").path_entry_hash()?;
+    create_link(
+        path,
+        entry_hash.clone(),
+        LinkTypes::Centroid,
+        LinkTag::new(id.as_bytes()),
+    )?;
+
+    create_audit_trail(
+        "add_centroid",
+        json!({"centroid_id": id, "count": input.count}).to_string(),
+    )?;
+
+    broadcast_signal(DhtSignal::CentroidCreated {
+        id: id.clone(),
+        values: input.values,
+        count: input.count,
+        author: agent_info()?.agent_latest_pubkey.to_string(),
+        updated_at: now,
+    })?;
+
+    Ok(VectorOutput {
+        id,
+        entry_hash: entry_hash.to_string(),
+    })
+}
+
+/// Forward signals from peers to this agent's conductor client, where a
+/// `DhtSignalHandler` applies new entries to the local index and an
+/// `ArbitrationRollback` acts on closed disputes
+#[hdk_extern]
+pub fn recv_remote_signal(signal: ExternIO) -> ExternResult<()> {
+    if let Ok(update) = signal.decode::<DhtSignal>() {
+        emit_signal(&update)?;
+        return Ok(());
+    }
+    if let Ok(decision) = signal.decode::<DisputeDecision>() {
+        emit_signal(&decision)?;
+        return Ok(());
+    }
+    let notification: ArbitrationNotification = signal
+        .decode()
+        .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?;
+    emit_signal(&notification)?;
+    Ok(())
+}
+
+/// Send an update to every peer
+fn broadcast_signal(signal: DhtSignal) -> ExternResult<()> {
+    signal_peers(signal)
+}
+
 /// Search for vectors similar to the query
 #[hdk_extern]
 pub fn search_vectors(input: SearchInput) -> ExternResult<SearchOutput> {
-    let props = crate::holochain::dna::get_dna_properties()?;
-    
+    let config = get_index_config()?;
+
     // Validate dimensions
-    if input.query.len() != props.dimensions {
-        return Err(wasm_error!(
-            WasmErrorInner::Guest(format!(
-                "Query vector dimensions mismatch: expected {}, got {}",
-                props.dimensions,
-                input.query.len()
-            ))
-        ));
-    }
-    
+    config.check_dimensions("query", &input.query)?;
+
     // Create query vector
     let query = Vector::new(input.query);
-    
-    // Get distance metric from DNA properties
-    let distance_metric = get_distance_metric()?;
-    
+
+    // Distance metric from DNA properties
+    let distance_metric = config.distance_metric;
+
     // Get all vectors
     let vector_entries = get_all_vectors()?;
-    
+
     // Calculate distances
     let mut results: Vec<SearchResult> = vector_entries
         .into_iter()
         .map(|entry| {
             let vector = Vector::try_from(entry.clone())
                 .map_err(|e| wasm_error!(WasmErrorInner::Guest(e)))?;
-            
+
             let score = match distance_metric {
                 crate::sharding::vector_index::DistanceMetric::Euclidean => {
                     query.euclidean_distance(&vector)
-                },
+                }
                 crate::sharding::vector_index::DistanceMetric::Cosine => {
                     1.0 - query.cosine_similarity(&vector)
-                },
+                }
                 crate::sharding::vector_index::DistanceMetric::Manhattan => {
                     query.manhattan_distance(&vector)
-                },
+                }
                 crate::sharding::vector_index::DistanceMetric::Hamming => {
                     query.hamming_distance(&vector) as f32
-                },
+                }
+                crate::sharding::vector_index::DistanceMetric::InnerProduct => query.dot(&vector),
             };
-            
+
             Ok(SearchResult {
                 id: entry.id.clone(),
                 vector: entry.values.clone(),
@@ -109,21 +191,27 @@ pub fn search_vectors(input: SearchInput) -> ExternResult<SearchOutput> {
             })
         })
         .collect::<ExternResult<Vec<SearchResult>>>()?;
-    
-    // Sort by score (lower is better)
-    results.sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap());
-    
+
+    // Sort by score (lower is better, except for inner product)
+    results.sort_by(|a, b| {
+        if distance_metric.is_lower_better() {
+            a.score.partial_cmp(&b.score).unwrap()
+        } else {
+            b.score.partial_cmp(&a.score).unwrap()
+        }
+    });
+
     // Limit results
     let limit = input.limit.unwrap_or(10).min(100);
     results.truncate(limit);
-    
+
     // Create audit trail
-    create_audit_trail("search_vectors", 
-        json!({"query_dimensions": query.dimensions, "result_count": results.len()}).to_string())?;
-    
-    Ok(SearchOutput {
-        results,
-    })
+    create_audit_trail(
+        "search_vectors",
+        json!({"query_dimensions": query.dimensions, "result_count": results.len()}).to_string(),
+    )?;
+
+    Ok(SearchOutput { results })
 }
 
 /// Input for vector creation
@@ -140,6 +228,16 @@ pub struct VectorOutput {
     pub entry_hash: String,
 }
 
+/// Input for centroid publication
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CentroidInput {
+    /// Existing centroid to publish a new version of
+    pub id: Option<String>,
+    pub values: Vec<f32>,
+    /// Vectors the centroid summarizes
+    pub count: usize,
+}
+
 /// Input for vector search
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SearchInput {
@@ -165,43 +263,41 @@ pub struct SearchResult {
 /// Get all vectors from the DHT
 fn get_all_vectors() -> ExternResult<Vec<VectorEntry>> {
     let path = Path::from("vectors_by_id");
-    let links = get_links(path.path_entry_hash()?, None)?;
-    
+    let links = get_links(path.path_entry_hash()?, LinkTypes::Vector, None)?;
+
     let entries = links
         .into_iter()
         .map(|link| {
-            let entry_hash = link.target;
-            let entry: VectorEntry = get_entry(entry_hash)?
-                .ok_or_else(|| wasm_error!(
-                    WasmErrorInner::Guest("Vector entry not found".to_string())
-                ))?
-                .try_into()
-                .map_err(|e: SerializedBytesError| wasm_error!(WasmErrorInner::Serialize(e)))?;
-                
+            let entry: VectorEntry = get_app_entry(link.target)?.ok_or_else(|| {
+                wasm_error!(WasmErrorInner::Guest("Vector entry not found".to_string()))
+            })?;
+
             Ok(entry)
         })
         .collect::<ExternResult<Vec<VectorEntry>>>()?;
-        
+
     Ok(entries)
 }
 
 /// Create an audit trail entry
-fn create_audit_trail(action: &str, details: String) -> ExternResult<EntryHash> {
-    let audit = AuditTrail {
+pub(crate) fn create_audit_trail(action: &str, details: String) -> ExternResult<EntryHash> {
+    let mut audit = AuditTrail {
         action: action.to_string(),
         initiator: agent_info()?.agent_latest_pubkey,
         validators: vec![], // Would be populated during validation
-        decision_proof: Vec::new(), // Would be populated with a real merkle proof
+        decision_proof: Vec::new(),
         justification: details,
         timestamp: sys_time()?,
     };
-    
-    let entry_hash = create_entry(&audit)?;
-    
-    // Add to audit trail index
-    let path = Path::from("audit_trails_by_timestamp");
-    let link_tag = LinkTag::new(format!("{}", audit.timestamp).as_bytes());
-    create_link(path.path_entry_hash()?, entry_hash.clone(), link_tag)?;
-    
+
+    // Validators reject audit trails that aren't signed by their initiator
+    sign_audit_trail(&mut audit)?;
+
+    create_entry(&EntryTypes::AuditTrail(audit.clone()))?;
+    let entry_hash = hash_entry(&audit)?;
+
+    // Add to the audit trail anchor for the current hour
+    anchor_audit_trail(entry_hash.clone(), audit.timestamp)?;
+
     Ok(entry_hash)
 }
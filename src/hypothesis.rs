@@ -1,18 +1,64 @@
 use std::collections::HashMap;
 
+/// Share of outlying vectors (see [`crate::sharding::outliers`]) above
+/// which data quality is the first thing to look at
+const OUTLIER_FRACTION_ALERT: f32 = 0.05;
+
+/// Recall according to client feedback (see
+/// [`crate::evaluation::Evaluation::feedback_recall`]) below which the
+/// index is missing too many relevant results
+const FEEDBACK_RECALL_ALERT: f32 = 0.9;
+
 #[derive(Debug)]
 pub struct Hypothesis {
     // In a real implementation, this would hold the state for the hypothesis engine.
 }
 
+impl Default for Hypothesis {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Hypothesis {
     pub fn new() -> Self {
         Self {}
     }
 
-    pub fn generate(&self, _analysis: &HashMap<String, f32>) -> String {
+    pub fn generate(&self, analysis: &HashMap<String, f32>) -> String {
+        if let Some(hypothesis) = self.search_quality(analysis) {
+            return hypothesis;
+        }
         // In a real implementation, this would generate a hypothesis based on the analysis.
         // For now, we'll return a dummy hypothesis.
         "If I refactor this function to use a more efficient algorithm, then the performance will improve.".to_string()
     }
-}
\ No newline at end of file
+
+    /// A hypothesis about index quality, if the analysis points to a
+    /// problem with it
+    pub fn search_quality(&self, analysis: &HashMap<String, f32>) -> Option<String> {
+        if let Some(fraction) = analysis
+            .get("outlier_fraction")
+            .filter(|fraction| **fraction > OUTLIER_FRACTION_ALERT)
+        {
+            return Some(format!(
+                "If I review the {:.1}% of vectors flagged as outliers, then search quality will improve.",
+                fraction * 100.0
+            ));
+        }
+        let recall = *analysis
+            .get("feedback_recall")
+            .filter(|recall| **recall < FEEDBACK_RECALL_ALERT)?;
+        match analysis.get("slow_query_recall") {
+            Some(slow) if *slow < recall => Some(format!(
+                "If I retune the indexes behind slow queries, whose recall is {:.1}% against {:.1}% overall, then both latency and recall will improve.",
+                slow * 100.0,
+                recall * 100.0
+            )),
+            _ => Some(format!(
+                "If I raise the search effort of the index, then the {:.1}% recall reported by clients will improve.",
+                recall * 100.0
+            )),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Near-real-time sync from the Holochain DHT into local shards.
+//!
+//! When an agent creates a `VectorEntry` or `CentroidEntry`, the zome sends
+//! every peer a remote signal carrying a [`DhtSignal`]. Each peer's zome
+//! re-emits it to its conductor client, which passes it to a
+//! [`DhtSignalHandler`] so the entry shows up in the local index without
+//! waiting for a DHT scan.
+//!
+//! Signals are best-effort and may arrive more than once or out of order, so
+//! applying one is idempotent: vectors already present are left alone, and a
+//! centroid is only replaced by a newer version of itself.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::core::metrics::MetricsCollector;
+use crate::sharding::changefeed::ChangeOp;
+use crate::sharding::manager::ShardManager;
+
+/// Metadata key recording the agent that published an entry
+pub const AUTHOR_KEY: &str = "dht_author";
+
+/// Metadata keys for centroids stored as vectors
+pub const CENTROID_COUNT_KEY: &str = "centroid_count";
+pub const CENTROID_UPDATED_KEY: &str = "centroid_updated_at";
+
+/// Payload of the remote signals sent by the zomes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DhtSignal {
+    VectorCreated {
+        id: String,
+        values: Vec<f32>,
+        #[serde(default)]
+        metadata: Option<HashMap<String, String>>,
+        /// Publishing agent's public key
+        author: String,
+        created_at: u64,
+    },
+    CentroidCreated {
+        id: String,
+        values: Vec<f32>,
+        count: usize,
+        author: String,
+        /// Microseconds since the epoch; newer versions replace older ones
+        updated_at: u64,
+    },
+}
+
+/// Which shards signals are applied to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DhtSyncConfig {
+    /// Shard receiving published vectors
+    pub vector_shard: Uuid,
+
+    /// Shard receiving published centroids, stored as vectors; centroid
+    /// signals are ignored when unset
+    #[serde(default)]
+    pub centroid_shard: Option<Uuid>,
+}
+
+/// What applying a signal did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DhtApplyOutcome {
+    /// The entry was added or replaced
+    Applied,
+    /// The entry was already present
+    Duplicate,
+    /// A newer version of the centroid was already present
+    Stale,
+    /// No shard is configured for this kind of entry
+    Ignored,
+}
+
+/// Applies DHT signals to the local shard manager
+pub struct DhtSignalHandler {
+    config: DhtSyncConfig,
+    shard_manager: Arc<ShardManager>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl DhtSignalHandler {
+    pub fn new(
+        config: DhtSyncConfig,
+        shard_manager: Arc<ShardManager>,
+        metrics: Arc<MetricsCollector>,
+    ) -> Self {
+        Self {
+            config,
+            shard_manager,
+            metrics,
+        }
+    }
+
+    /// Apply a signal as delivered by the conductor client, JSON-encoded
+    pub async fn handle_json(&self, payload: &[u8]) -> Result<DhtApplyOutcome> {
+        let signal: DhtSignal =
+            serde_json::from_slice(payload).map_err(|e| anyhow!("Invalid DHT signal: {}", e))?;
+        self.handle(signal).await
+    }
+
+    pub async fn handle(&self, signal: DhtSignal) -> Result<DhtApplyOutcome> {
+        let result = match signal {
+            DhtSignal::VectorCreated {
+                id,
+                values,
+                metadata,
+                author,
+                ..
+            } => self.apply_vector(&id, values, metadata, author).await,
+            DhtSignal::CentroidCreated {
+                id,
+                values,
+                count,
+                author,
+                updated_at,
+            } => {
+                self.apply_centroid(&id, values, count, author, updated_at)
+                    .await
+            }
+        };
+
+        let counter = match &result {
+            Ok(DhtApplyOutcome::Applied) => "dht.signals.applied",
+            Ok(DhtApplyOutcome::Duplicate | DhtApplyOutcome::Stale) => "dht.signals.skipped",
+            Ok(DhtApplyOutcome::Ignored) => "dht.signals.ignored",
+            Err(_) => "dht.signals.failed",
+        };
+        self.metrics.increment_counter(counter, 1).await;
+        result
+    }
+
+    async fn apply_vector(
+        &self,
+        id: &str,
+        values: Vec<f32>,
+        metadata: Option<HashMap<String, String>>,
+        author: String,
+    ) -> Result<DhtApplyOutcome> {
+        let vector_id = parse_id(id)?;
+        let mut metadata = metadata.unwrap_or_default();
+        metadata.insert(AUTHOR_KEY.to_string(), author.clone());
+        let op = ChangeOp::Insert {
+            vector_id,
+            values,
+            metadata: Some(metadata),
+        };
+        let changed = self
+            .shard_manager
+            .apply_replicated_change(self.config.vector_shard, op, &origin(&author))
+            .await?;
+        debug!(
+            "DHT vector {} from {}: changed={}",
+            vector_id, author, changed
+        );
+        Ok(if changed {
+            DhtApplyOutcome::Applied
+        } else {
+            DhtApplyOutcome::Duplicate
+        })
+    }
+
+    async fn apply_centroid(
+        &self,
+        id: &str,
+        values: Vec<f32>,
+        count: usize,
+        author: String,
+        updated_at: u64,
+    ) -> Result<DhtApplyOutcome> {
+        let Some(shard_id) = self.config.centroid_shard else {
+            return Ok(DhtApplyOutcome::Ignored);
+        };
+        let centroid_id = parse_id(id)?;
+        let origin = origin(&author);
+
+        let index = self.shard_manager.get_vector_index(shard_id).await?;
+        if let Some(existing) = index.get(centroid_id).await {
+            let existing_updated = existing
+                .metadata
+                .as_ref()
+                .and_then(|m| m.get(CENTROID_UPDATED_KEY))
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(0);
+            if existing_updated >= updated_at {
+                return Ok(DhtApplyOutcome::Stale);
+            }
+            self.shard_manager
+                .apply_replicated_change(
+                    shard_id,
+                    ChangeOp::Delete {
+                        vector_id: centroid_id,
+                    },
+                    &origin,
+                )
+                .await?;
+        }
+
+        let metadata = HashMap::from([
+            (AUTHOR_KEY.to_string(), author),
+            (CENTROID_COUNT_KEY.to_string(), count.to_string()),
+            (CENTROID_UPDATED_KEY.to_string(), updated_at.to_string()),
+        ]);
+        let op = ChangeOp::Insert {
+            vector_id: centroid_id,
+            values,
+            metadata: Some(metadata),
+        };
+        self.shard_manager
+            .apply_replicated_change(shard_id, op, &origin)
+            .await?;
+        Ok(DhtApplyOutcome::Applied)
+    }
+
+    /// Apply signals from `signals` until it closes or `shutdown` becomes
+    /// true. Failures are logged; the DHT remains the source of truth.
+    pub async fn run(
+        self,
+        mut signals: mpsc::Receiver<DhtSignal>,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        info!(
+            "Starting DHT signal sync into shard {}",
+            self.config.vector_shard
+        );
+        while !*shutdown.borrow() {
+            tokio::select! {
+                signal = signals.recv() => {
+                    let Some(signal) = signal else {
+                        break;
+                    };
+                    if let Err(e) = self.handle(signal).await {
+                        warn!("Failed to apply DHT signal: {}", e);
+                    }
+                }
+                _ = shutdown.changed() => {}
+            }
+        }
+        info!(
+            "Stopped DHT signal sync into shard {}",
+            self.config.vector_shard
+        );
+    }
+}
+
+fn parse_id(id: &str) -> Result<Uuid> {
+    Uuid::parse_str(id).map_err(|e| anyhow!("Invalid DHT entry id '{}': {}", id, e))
+}
+
+/// Change-feed origin for writes made on behalf of a DHT agent
+fn origin(author: &str) -> String {
+    format!("dht:{}", author)
+}
@@ -0,0 +1,131 @@
+//! Minimal JSONPath support for extracting fields from webhook payloads.
+//!
+//! Supports the root `$`, dotted keys (`$.a.b`), bracketed keys
+//! (`$['a b']`), array indices including negative ones (`$.items[0]`,
+//! `$.items[-1]`) and wildcards (`$.items[*].name`, `$.a.*`).
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+}
+
+/// A compiled JSONPath expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    /// Parse a path expression
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let rest = path
+            .trim()
+            .strip_prefix('$')
+            .ok_or_else(|| format!("JSONPath must start with '$': {}", path))?;
+        let chars: Vec<char> = rest.chars().collect();
+        let mut segments = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                '.' => {
+                    let start = i + 1;
+                    let mut end = start;
+                    while end < chars.len() && chars[end] != '.' && chars[end] != '[' {
+                        end += 1;
+                    }
+                    let key: String = chars[start..end].iter().collect();
+                    if key.is_empty() {
+                        return Err(format!("Empty key in JSONPath: {}", path));
+                    }
+                    segments.push(if key == "*" {
+                        Segment::Wildcard
+                    } else {
+                        Segment::Key(key)
+                    });
+                    i = end;
+                }
+                '[' => {
+                    let close = chars[i..]
+                        .iter()
+                        .position(|c| *c == ']')
+                        .map(|p| p + i)
+                        .ok_or_else(|| format!("Unclosed '[' in JSONPath: {}", path))?;
+                    let inner: String = chars[i + 1..close].iter().collect();
+                    let inner = inner.trim();
+                    let segment = if inner == "*" {
+                        Segment::Wildcard
+                    } else if let Some(quoted) = inner
+                        .strip_prefix('\'')
+                        .and_then(|s| s.strip_suffix('\''))
+                        .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+                    {
+                        Segment::Key(quoted.to_string())
+                    } else {
+                        Segment::Index(
+                            inner
+                                .parse()
+                                .map_err(|_| format!("Invalid index '{}' in JSONPath", inner))?,
+                        )
+                    };
+                    segments.push(segment);
+                    i = close + 1;
+                }
+                c => return Err(format!("Unexpected '{}' in JSONPath: {}", c, path)),
+            }
+        }
+
+        Ok(Self {
+            source: path.to_string(),
+            segments,
+        })
+    }
+
+    /// The expression this path was parsed from
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// All values in `root` matched by this path
+    pub fn select<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![root];
+        for segment in &self.segments {
+            let mut next = Vec::new();
+            for value in current {
+                match (segment, value) {
+                    (Segment::Key(key), Value::Object(map)) => next.extend(map.get(key)),
+                    (Segment::Index(index), Value::Array(items)) => {
+                        let index = if *index < 0 {
+                            items.len() as i64 + index
+                        } else {
+                            *index
+                        };
+                        if index >= 0 {
+                            next.extend(items.get(index as usize));
+                        }
+                    }
+                    (Segment::Wildcard, Value::Array(items)) => next.extend(items.iter()),
+                    (Segment::Wildcard, Value::Object(map)) => next.extend(map.values()),
+                    _ => {}
+                }
+            }
+            current = next;
+        }
+        current
+    }
+}
+
+/// Render a selected value as text: strings verbatim, null as nothing and
+/// anything else as JSON.
+pub fn value_to_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
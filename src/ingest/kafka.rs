@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::message::Message;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::{Offset, TopicPartitionList};
+
+use crate::ingest::stream::{DeadLetterSink, MessageSource, SourceMessage};
+
+/// Kafka consumer group subscription with manual offset commits
+pub struct KafkaSource {
+    consumer: StreamConsumer,
+    next_token: u64,
+    /// Token to (topic, partition, offset) for uncommitted messages
+    positions: HashMap<u64, (String, i32, i64)>,
+}
+
+impl KafkaSource {
+    pub fn new(brokers: &str, group_id: &str, topic: &str) -> Result<Self> {
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("group.id", group_id)
+            .set("enable.auto.commit", "false")
+            .set("auto.offset.reset", "earliest")
+            .create()?;
+        consumer.subscribe(&[topic])?;
+
+        Ok(Self {
+            consumer,
+            next_token: 0,
+            positions: HashMap::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl MessageSource for KafkaSource {
+    async fn poll(&mut self, max: usize, timeout: Duration) -> Result<Vec<SourceMessage>> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut messages = Vec::new();
+
+        while messages.len() < max {
+            let message = match tokio::time::timeout_at(deadline, self.consumer.recv()).await {
+                Ok(message) => message?,
+                Err(_) => break,
+            };
+
+            let token = self.next_token;
+            self.next_token += 1;
+            self.positions.insert(
+                token,
+                (
+                    message.topic().to_string(),
+                    message.partition(),
+                    message.offset(),
+                ),
+            );
+            messages.push(SourceMessage {
+                token,
+                key: message
+                    .key()
+                    .map(|k| String::from_utf8_lossy(k).into_owned()),
+                payload: message.payload().map(<[u8]>::to_vec).unwrap_or_default(),
+            });
+        }
+
+        Ok(messages)
+    }
+
+    async fn commit(&mut self, messages: &[SourceMessage]) -> Result<()> {
+        // Commit the next offset after the highest handled one per partition
+        let mut highest: HashMap<(String, i32), i64> = HashMap::new();
+        for message in messages {
+            if let Some((topic, partition, offset)) = self.positions.remove(&message.token) {
+                let entry = highest.entry((topic, partition)).or_insert(offset);
+                *entry = (*entry).max(offset);
+            }
+        }
+        if highest.is_empty() {
+            return Ok(());
+        }
+
+        let mut offsets = TopicPartitionList::new();
+        for ((topic, partition), offset) in highest {
+            offsets.add_partition_offset(&topic, partition, Offset::Offset(offset + 1))?;
+        }
+        self.consumer.commit(&offsets, CommitMode::Sync)?;
+        Ok(())
+    }
+}
+
+/// Publishes failed messages to a dead-letter topic
+pub struct KafkaDeadLetter {
+    producer: FutureProducer,
+    topic: String,
+}
+
+impl KafkaDeadLetter {
+    pub fn new(brokers: &str, topic: &str) -> Result<Self> {
+        Ok(Self {
+            producer: ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                .create()?,
+            topic: topic.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl DeadLetterSink for KafkaDeadLetter {
+    async fn send(&self, message: &SourceMessage, reason: &str) -> Result<()> {
+        let key = message.key.clone().unwrap_or_default();
+        let headers = rdkafka::message::OwnedHeaders::new().insert(rdkafka::message::Header {
+            key: "x-dead-letter-reason",
+            value: Some(reason),
+        });
+        self.producer
+            .send(
+                FutureRecord::to(&self.topic)
+                    .key(&key)
+                    .payload(&message.payload)
+                    .headers(headers),
+                Duration::from_secs(5),
+            )
+            .await
+            .map_err(|(e, _)| e)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+//! Push-based ingestion of external data
+//!
+//! Webhook pipelines map arbitrary JSON payloads to text and metadata with
+//! JSONPath rules, embed the text and store the resulting vector. Stream
+//! workers consume vector records from Kafka or NATS JetStream, and the DHT
+//! signal handler applies entries published on Holochain.
+
+pub mod dht;
+pub mod jsonpath;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+#[cfg(feature = "nats")]
+pub mod nats;
+pub mod stream;
+pub mod webhook;
+
+pub use dht::{DhtApplyOutcome, DhtSignal, DhtSignalHandler, DhtSyncConfig};
+pub use jsonpath::JsonPath;
+pub use stream::{
+    BatchOutcome, DeadLetterSink, MessageSource, SourceMessage, StreamIngestConfig,
+    StreamIngestWorker, StreamRecordSchema,
+};
+pub use webhook::{
+    TextRule, WebhookIngestResult, WebhookIngestor, WebhookPipeline, WebhookPipelineConfig,
+};
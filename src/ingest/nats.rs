@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_nats::jetstream::{self, consumer::pull, consumer::AckPolicy};
+use async_trait::async_trait;
+use futures::StreamExt;
+
+use crate::ingest::stream::{DeadLetterSink, MessageSource, SourceMessage};
+
+/// Durable JetStream pull consumer with explicit acknowledgements
+pub struct NatsSource {
+    consumer: jetstream::consumer::Consumer<pull::Config>,
+    next_token: u64,
+    pending: HashMap<u64, jetstream::Message>,
+}
+
+impl NatsSource {
+    pub async fn connect(url: &str, stream: &str, subject: &str, durable: &str) -> Result<Self> {
+        let client = async_nats::connect(url).await?;
+        let context = jetstream::new(client);
+        let stream = context
+            .get_stream(stream)
+            .await
+            .map_err(|e| anyhow!("Failed to open stream {}: {}", stream, e))?;
+        let consumer = stream
+            .get_or_create_consumer(
+                durable,
+                pull::Config {
+                    durable_name: Some(durable.to_string()),
+                    filter_subject: subject.to_string(),
+                    ack_policy: AckPolicy::Explicit,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to create consumer {}: {}", durable, e))?;
+
+        Ok(Self {
+            consumer,
+            next_token: 0,
+            pending: HashMap::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl MessageSource for NatsSource {
+    async fn poll(&mut self, max: usize, timeout: Duration) -> Result<Vec<SourceMessage>> {
+        let mut batch = self
+            .consumer
+            .fetch()
+            .max_messages(max)
+            .expires(timeout)
+            .messages()
+            .await
+            .map_err(|e| anyhow!("Failed to fetch messages: {}", e))?;
+
+        let mut messages = Vec::new();
+        while let Some(message) = batch.next().await {
+            let message = message.map_err(|e| anyhow!("Failed to receive message: {}", e))?;
+            let token = self.next_token;
+            self.next_token += 1;
+            messages.push(SourceMessage {
+                token,
+                key: Some(message.subject.to_string()),
+                payload: message.payload.to_vec(),
+            });
+            self.pending.insert(token, message);
+        }
+
+        Ok(messages)
+    }
+
+    async fn commit(&mut self, messages: &[SourceMessage]) -> Result<()> {
+        for message in messages {
+            if let Some(pending) = self.pending.remove(&message.token) {
+                pending
+                    .ack()
+                    .await
+                    .map_err(|e| anyhow!("Failed to ack message: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Publishes failed messages to a dead-letter subject
+pub struct NatsDeadLetter {
+    context: jetstream::Context,
+    subject: String,
+}
+
+impl NatsDeadLetter {
+    pub async fn connect(url: &str, subject: &str) -> Result<Self> {
+        let client = async_nats::connect(url).await?;
+        Ok(Self {
+            context: jetstream::new(client),
+            subject: subject.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl DeadLetterSink for NatsDeadLetter {
+    async fn send(&self, message: &SourceMessage, reason: &str) -> Result<()> {
+        let mut headers = async_nats::HeaderMap::new();
+        headers.insert("Dead-Letter-Reason", reason);
+        self.context
+            .publish_with_headers(
+                self.subject.clone(),
+                headers,
+                message.payload.clone().into(),
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to publish dead letter: {}", e))?
+            .await
+            .map_err(|e| anyhow!("Dead letter not acknowledged: {}", e))?;
+        Ok(())
+    }
+}
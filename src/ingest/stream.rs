@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::watch;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::core::metrics::MetricsCollector;
+use crate::core::vector::Vector;
+use crate::ingest::jsonpath::{value_to_text, JsonPath};
+use crate::sharding::manager::ShardManager;
+
+/// A message read from a broker, identified by an opaque token the source
+/// uses to acknowledge it
+#[derive(Debug, Clone)]
+pub struct SourceMessage {
+    pub token: u64,
+    pub key: Option<String>,
+    pub payload: Vec<u8>,
+}
+
+/// A broker subscription that hands out messages and acknowledges them once
+/// they have been durably handled
+#[async_trait]
+pub trait MessageSource: Send {
+    /// Wait up to `timeout` for at most `max` messages
+    async fn poll(&mut self, max: usize, timeout: Duration) -> Result<Vec<SourceMessage>>;
+
+    /// Acknowledge messages so they won't be redelivered
+    async fn commit(&mut self, messages: &[SourceMessage]) -> Result<()>;
+}
+
+/// Destination for messages that can't be ingested
+#[async_trait]
+pub trait DeadLetterSink: Send + Sync {
+    async fn send(&self, message: &SourceMessage, reason: &str) -> Result<()>;
+}
+
+/// How a message payload maps to a vector record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamRecordSchema {
+    /// JSONPath of the numeric array holding the vector
+    pub vector: String,
+
+    /// Optional JSONPath of an identifier kept as `source_id` metadata
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Metadata key to JSONPath expression
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// Settings for a streaming ingestion worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamIngestConfig {
+    pub shard_id: Uuid,
+    pub schema: StreamRecordSchema,
+
+    /// Largest number of messages written per batch
+    pub batch_size: usize,
+
+    /// How long to wait to fill a batch
+    pub batch_timeout: Duration,
+
+    /// Attempts to write a record before it is dead-lettered
+    pub max_attempts: u32,
+}
+
+impl StreamIngestConfig {
+    pub fn new(shard_id: Uuid, schema: StreamRecordSchema) -> Self {
+        Self {
+            shard_id,
+            schema,
+            batch_size: 500,
+            batch_timeout: Duration::from_millis(250),
+            max_attempts: 3,
+        }
+    }
+}
+
+/// Counts for a processed batch
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchOutcome {
+    pub received: usize,
+    pub written: usize,
+    pub dead_lettered: usize,
+}
+
+struct CompiledSchema {
+    vector: JsonPath,
+    id: Option<JsonPath>,
+    metadata: Vec<(String, JsonPath)>,
+}
+
+impl CompiledSchema {
+    fn compile(schema: &StreamRecordSchema) -> Result<Self> {
+        Ok(Self {
+            vector: JsonPath::parse(&schema.vector).map_err(|e| anyhow!(e))?,
+            id: schema
+                .id
+                .as_deref()
+                .map(JsonPath::parse)
+                .transpose()
+                .map_err(|e| anyhow!(e))?,
+            metadata: schema
+                .metadata
+                .iter()
+                .map(|(key, path)| JsonPath::parse(path).map(|p| (key.clone(), p)))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| anyhow!(e))?,
+        })
+    }
+
+    fn decode(&self, message: &SourceMessage) -> Result<(Vector, HashMap<String, String>)> {
+        let payload: Value = serde_json::from_slice(&message.payload)
+            .map_err(|e| anyhow!("Payload is not valid JSON: {}", e))?;
+
+        let values = self
+            .vector
+            .select(&payload)
+            .into_iter()
+            .next()
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("No vector array at {}", self.vector.as_str()))?
+            .iter()
+            .map(|v| {
+                v.as_f64()
+                    .map(|v| v as f32)
+                    .ok_or_else(|| anyhow!("Non-numeric vector component"))
+            })
+            .collect::<Result<Vec<f32>>>()?;
+
+        let mut metadata: HashMap<String, String> = self
+            .metadata
+            .iter()
+            .filter_map(|(key, path)| {
+                path.select(&payload)
+                    .into_iter()
+                    .next()
+                    .and_then(value_to_text)
+                    .map(|v| (key.clone(), v))
+            })
+            .collect();
+        let source_id = match &self.id {
+            Some(path) => path
+                .select(&payload)
+                .into_iter()
+                .next()
+                .and_then(value_to_text),
+            None => message.key.clone(),
+        };
+        if let Some(source_id) = source_id {
+            metadata.insert("source_id".to_string(), source_id);
+        }
+
+        Ok((Vector::new(values), metadata))
+    }
+}
+
+/// Consumes vector records from a broker and writes them into a shard.
+///
+/// Offsets are committed only after every message in a batch has either
+/// been written or handed to the dead-letter sink, giving at-least-once
+/// delivery: a crash mid-batch causes redelivery, never loss.
+pub struct StreamIngestWorker {
+    source: Box<dyn MessageSource>,
+    dead_letter: Arc<dyn DeadLetterSink>,
+    schema: CompiledSchema,
+    config: StreamIngestConfig,
+    shard_manager: Arc<ShardManager>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl StreamIngestWorker {
+    pub fn new(
+        source: Box<dyn MessageSource>,
+        dead_letter: Arc<dyn DeadLetterSink>,
+        config: StreamIngestConfig,
+        shard_manager: Arc<ShardManager>,
+        metrics: Arc<MetricsCollector>,
+    ) -> Result<Self> {
+        Ok(Self {
+            source,
+            dead_letter,
+            schema: CompiledSchema::compile(&config.schema)?,
+            config,
+            shard_manager,
+            metrics,
+        })
+    }
+
+    /// Poll, write and commit one batch
+    pub async fn run_batch(&mut self) -> Result<BatchOutcome> {
+        let messages = self
+            .source
+            .poll(self.config.batch_size.max(1), self.config.batch_timeout)
+            .await?;
+        let mut outcome = BatchOutcome {
+            received: messages.len(),
+            ..Default::default()
+        };
+        if messages.is_empty() {
+            return Ok(outcome);
+        }
+
+        for message in &messages {
+            match self.write(message).await {
+                Ok(()) => outcome.written += 1,
+                Err(e) => {
+                    warn!("Dead-lettering message {}: {}", message.token, e);
+                    // If the dead-letter write fails the batch is left
+                    // uncommitted and will be redelivered
+                    self.dead_letter.send(message, &e.to_string()).await?;
+                    outcome.dead_lettered += 1;
+                }
+            }
+        }
+
+        self.source.commit(&messages).await?;
+
+        self.metrics
+            .increment_counter("ingest.stream.written", outcome.written as u64)
+            .await;
+        if outcome.dead_lettered > 0 {
+            self.metrics
+                .increment_counter("ingest.stream.dead_lettered", outcome.dead_lettered as u64)
+                .await;
+        }
+        debug!(
+            "Stream batch: {} received, {} written, {} dead-lettered",
+            outcome.received, outcome.written, outcome.dead_lettered
+        );
+
+        Ok(outcome)
+    }
+
+    async fn write(&self, message: &SourceMessage) -> Result<()> {
+        // Malformed payloads won't improve on retry
+        let (vector, metadata) = self.schema.decode(message)?;
+
+        let mut attempt = 1;
+        loop {
+            match self
+                .shard_manager
+                .add_vector(self.config.shard_id, vector.clone(), Some(metadata.clone()))
+                .await
+            {
+                Ok(_) => return Ok(()),
+                Err(e) if attempt >= self.config.max_attempts => return Err(e),
+                Err(e) => {
+                    debug!("Write attempt {} failed: {}", attempt, e);
+                    tokio::time::sleep(Duration::from_millis(50 * 2u64.pow(attempt))).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Process batches until `shutdown` becomes true
+    pub async fn run(mut self, mut shutdown: watch::Receiver<bool>) {
+        info!(
+            "Starting stream ingestion into shard {}",
+            self.config.shard_id
+        );
+        while !*shutdown.borrow() {
+            tokio::select! {
+                result = self.run_batch() => {
+                    if let Err(e) = result {
+                        error!("Stream ingestion batch failed: {}", e);
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    }
+                }
+                _ = shutdown.changed() => {}
+            }
+        }
+        info!(
+            "Stopped stream ingestion into shard {}",
+            self.config.shard_id
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::core::metrics::MetricsCollector;
+use crate::core::vector::Vector;
+use crate::embedding::{EmbeddingProvider, EMBEDDING_MODEL_KEY};
+use crate::ingest::jsonpath::{value_to_text, JsonPath};
+use crate::sharding::manager::ShardManager;
+
+/// How to build the text that gets embedded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextRule {
+    /// JSONPath expressions whose matches are concatenated in order
+    pub paths: Vec<String>,
+
+    /// Separator placed between extracted values
+    #[serde(default = "default_separator")]
+    pub separator: String,
+}
+
+fn default_separator() -> String {
+    "\n".to_string()
+}
+
+/// Transformation rules for a named webhook pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPipelineConfig {
+    pub name: String,
+
+    /// Shard the vectors are stored in
+    pub shard_id: Uuid,
+
+    /// Optional path selecting several records from one payload, e.g.
+    /// `$.events[*]`; other paths are then evaluated per record
+    #[serde(default)]
+    pub records: Option<String>,
+
+    pub text: TextRule,
+
+    /// Metadata key to JSONPath expression
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// A pipeline with its paths compiled
+#[derive(Debug, Clone)]
+pub struct WebhookPipeline {
+    config: WebhookPipelineConfig,
+    records: Option<JsonPath>,
+    text_paths: Vec<JsonPath>,
+    metadata_paths: Vec<(String, JsonPath)>,
+}
+
+impl WebhookPipeline {
+    /// Compile a pipeline, rejecting malformed paths
+    pub fn new(config: WebhookPipelineConfig) -> Result<Self> {
+        if config.text.paths.is_empty() {
+            return Err(anyhow!("Pipeline '{}' has no text paths", config.name));
+        }
+
+        let records = config
+            .records
+            .as_deref()
+            .map(JsonPath::parse)
+            .transpose()
+            .map_err(|e| anyhow!(e))?;
+        let text_paths = config
+            .text
+            .paths
+            .iter()
+            .map(|p| JsonPath::parse(p))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow!(e))?;
+        let metadata_paths = config
+            .metadata
+            .iter()
+            .map(|(key, p)| JsonPath::parse(p).map(|path| (key.clone(), path)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow!(e))?;
+
+        Ok(Self {
+            config,
+            records,
+            text_paths,
+            metadata_paths,
+        })
+    }
+
+    pub fn config(&self) -> &WebhookPipelineConfig {
+        &self.config
+    }
+
+    /// Apply the rules to a payload, producing `(text, metadata)` for each
+    /// record that yields non-empty text
+    pub fn transform(&self, payload: &Value) -> Vec<(String, HashMap<String, String>)> {
+        let records = match &self.records {
+            Some(path) => path.select(payload),
+            None => vec![payload],
+        };
+
+        records
+            .into_iter()
+            .filter_map(|record| {
+                let text = self
+                    .text_paths
+                    .iter()
+                    .flat_map(|path| path.select(record))
+                    .filter_map(value_to_text)
+                    .filter(|t| !t.trim().is_empty())
+                    .collect::<Vec<_>>()
+                    .join(&self.config.text.separator);
+                if text.is_empty() {
+                    return None;
+                }
+
+                let metadata = self
+                    .metadata_paths
+                    .iter()
+                    .filter_map(|(key, path)| {
+                        let values: Vec<String> = path
+                            .select(record)
+                            .into_iter()
+                            .filter_map(value_to_text)
+                            .collect();
+                        if values.is_empty() {
+                            None
+                        } else {
+                            Some((key.clone(), values.join(",")))
+                        }
+                    })
+                    .collect();
+
+                Some((text, metadata))
+            })
+            .collect()
+    }
+}
+
+/// Outcome of a webhook delivery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookIngestResult {
+    pub pipeline: String,
+    pub vector_ids: Vec<Uuid>,
+    /// Records that produced no text and were skipped
+    pub skipped: usize,
+}
+
+/// Registry of webhook pipelines and the ingestion path behind them
+pub struct WebhookIngestor {
+    pipelines: RwLock<HashMap<String, WebhookPipeline>>,
+    embedder: Arc<dyn EmbeddingProvider>,
+    shard_manager: Arc<ShardManager>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl WebhookIngestor {
+    pub fn new(
+        shard_manager: Arc<ShardManager>,
+        embedder: Arc<dyn EmbeddingProvider>,
+        metrics: Arc<MetricsCollector>,
+    ) -> Self {
+        Self {
+            pipelines: RwLock::new(HashMap::new()),
+            embedder,
+            shard_manager,
+            metrics,
+        }
+    }
+
+    /// Register or replace a pipeline
+    pub async fn register_pipeline(&self, config: WebhookPipelineConfig) -> Result<()> {
+        self.shard_manager.get_shard(config.shard_id).await?;
+        let pipeline = WebhookPipeline::new(config)?;
+        let name = pipeline.config.name.clone();
+        self.pipelines.write().await.insert(name.clone(), pipeline);
+        info!("Registered webhook pipeline {}", name);
+        Ok(())
+    }
+
+    /// Remove a pipeline, returning whether it existed
+    pub async fn remove_pipeline(&self, name: &str) -> bool {
+        self.pipelines.write().await.remove(name).is_some()
+    }
+
+    /// Whether a pipeline with this name is registered
+    pub async fn has_pipeline(&self, name: &str) -> bool {
+        self.pipelines.read().await.contains_key(name)
+    }
+
+    /// Configurations of all registered pipelines
+    pub async fn list_pipelines(&self) -> Vec<WebhookPipelineConfig> {
+        self.pipelines
+            .read()
+            .await
+            .values()
+            .map(|p| p.config.clone())
+            .collect()
+    }
+
+    /// Transform, embed and store a webhook payload
+    pub async fn ingest(
+        &self,
+        pipeline_name: &str,
+        payload: &Value,
+    ) -> Result<WebhookIngestResult> {
+        let pipeline = self
+            .pipelines
+            .read()
+            .await
+            .get(pipeline_name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Webhook pipeline {} not found", pipeline_name))?;
+
+        let total_records = match &pipeline.records {
+            Some(path) => path.select(payload).len(),
+            None => 1,
+        };
+        let records = pipeline.transform(payload);
+        let skipped = total_records.saturating_sub(records.len());
+
+        let texts: Vec<String> = records.iter().map(|(text, _)| text.clone()).collect();
+        let embeddings = self.embedder.embed(&texts).await?;
+
+        let mut vector_ids = Vec::with_capacity(records.len());
+        for ((_, mut metadata), values) in records.into_iter().zip(embeddings) {
+            metadata.insert("ingest.pipeline".to_string(), pipeline_name.to_string());
+            metadata.insert(EMBEDDING_MODEL_KEY.to_string(), self.embedder.model_id());
+            let id = self
+                .shard_manager
+                .add_vector(
+                    pipeline.config.shard_id,
+                    Vector::new(values),
+                    Some(metadata),
+                )
+                .await?;
+            vector_ids.push(id);
+        }
+
+        self.metrics
+            .increment_counter("ingest.webhook.records", vector_ids.len() as u64)
+            .await;
+        if skipped > 0 {
+            self.metrics
+                .increment_counter("ingest.webhook.skipped", skipped as u64)
+                .await;
+        }
+        debug!(
+            "Webhook pipeline {} stored {} vectors ({} skipped)",
+            pipeline_name,
+            vector_ids.len(),
+            skipped
+        );
+
+        Ok(WebhookIngestResult {
+            pipeline: pipeline_name.to_string(),
+            vector_ids,
+            skipped,
+        })
+    }
+}
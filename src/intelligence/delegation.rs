@@ -0,0 +1,772 @@
+//! Delegation of work to other cluster peers.
+//!
+//! Peers advertise which kinds of task they accept and how many they can
+//! run at once. Submitted tasks are leased to the peer with the most free
+//! slots and sent to it; the peer renews its leases with every heartbeat
+//! and reports the outcome with the lease id it was given. A lease that
+//! expires, or whose peer stops heartbeating, is reclaimed and the task
+//! goes back in the queue, so abandoned work is retried elsewhere. Reports
+//! carrying a superseded lease id are rejected, which keeps a slow peer
+//! from overwriting the result of the peer the task was reassigned to.
+//!
+//! Every task carries a trace id. Each transition, on this node and any
+//! spans the executing peer reports back, is recorded against it, so a
+//! delegated job can be followed end to end.
+//!
+//! With a [`TrustManager`], failed and completed tasks count towards each
+//! peer's trust and quarantined or blocked peers are given no work.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, info_span, warn, Instrument};
+use uuid::Uuid;
+
+use crate::core::metrics::MetricsCollector;
+use crate::nerv::tasks;
+use crate::network::secure_channel::SecureClient;
+use crate::network::trust::{TrustEvent, TrustManager};
+use crate::server::auth::API_KEY_HEADER;
+use crate::utils::errors::DelegationError;
+
+/// Header carrying the trace id of delegated work
+pub const TRACE_HEADER: &str = "x-trace-id";
+
+/// Work that can be delegated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    /// A Darwin validation run
+    Validation,
+    Embedding,
+    Clustering,
+}
+
+impl TaskKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskKind::Validation => "validation",
+            TaskKind::Embedding => "embedding",
+            TaskKind::Clustering => "clustering",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DelegationConfig {
+    /// How long a peer holds a task without renewing the lease
+    pub lease_duration: Duration,
+
+    /// Peers silent for longer than this are considered gone and their
+    /// tasks are reclaimed
+    pub heartbeat_timeout: Duration,
+
+    /// Leases granted for a task before it is given up on
+    pub max_attempts: u32,
+}
+
+impl Default for DelegationConfig {
+    fn default() -> Self {
+        Self {
+            lease_duration: Duration::from_secs(30),
+            heartbeat_timeout: Duration::from_secs(10),
+            max_attempts: 3,
+        }
+    }
+}
+
+/// A peer's advertised capacity, sent with each heartbeat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerCapacity {
+    pub peer_id: String,
+    /// Base URL tasks are sent to
+    pub endpoint: String,
+    pub accepts: Vec<TaskKind>,
+    /// Tasks the peer runs concurrently
+    pub slots: usize,
+}
+
+/// Heartbeat from a peer: its current capacity and the leases it still holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerHeartbeat {
+    pub capacity: PeerCapacity,
+    #[serde(default)]
+    pub leases: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone)]
+struct PeerState {
+    capacity: PeerCapacity,
+    last_heartbeat: DateTime<Utc>,
+}
+
+/// A peer as seen by the delegator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerStatus {
+    pub capacity: PeerCapacity,
+    pub last_heartbeat: DateTime<Utc>,
+    pub in_flight: usize,
+    pub available: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Pending,
+    Leased,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskLease {
+    pub lease_id: Uuid,
+    pub peer_id: String,
+    pub granted_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegatedTask {
+    pub id: Uuid,
+    pub trace_id: Uuid,
+    pub kind: TaskKind,
+    pub payload: Value,
+    pub status: TaskStatus,
+    pub lease: Option<TaskLease>,
+    /// Leases granted so far
+    pub attempts: u32,
+    pub result: Option<Value>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What a peer is sent when it is given a task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskAssignment {
+    pub task_id: Uuid,
+    pub lease_id: Uuid,
+    pub trace_id: Uuid,
+    pub kind: TaskKind,
+    pub payload: Value,
+    pub expires_at: DateTime<Utc>,
+    /// Node that delegated the task and expects the report
+    pub origin: String,
+}
+
+/// A peer's report on a task it was leased
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskReport {
+    pub lease_id: Uuid,
+    #[serde(flatten)]
+    pub outcome: TaskOutcome,
+    /// Spans recorded while the peer worked on the task
+    #[serde(default)]
+    pub spans: Vec<TraceEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum TaskOutcome {
+    Completed { result: Value },
+    Failed { error: String },
+}
+
+/// One step in the life of a delegated task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceEvent {
+    pub trace_id: Uuid,
+    pub task_id: Uuid,
+    /// Node the step happened on
+    pub node: String,
+    pub event: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+/// Sends task assignments to peers
+#[async_trait]
+pub trait TaskTransport: Send + Sync {
+    async fn send(&self, peer: &PeerCapacity, assignment: &TaskAssignment) -> Result<()>;
+}
+
+/// Posts assignments to `{endpoint}/api/tasks`, propagating the trace id
+#[derive(Debug, Clone, Default)]
+pub struct HttpTaskTransport {
+    client: reqwest::Client,
+    api_key: Option<String>,
+    secure: Option<SecureClient>,
+}
+
+impl HttpTaskTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send `key` as the API key header, for peers that require
+    /// authentication
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// Seal assignments for each peer, named by its peer id, in a secure
+    /// channel
+    pub fn with_secure_channel(mut self, client: SecureClient) -> Self {
+        self.secure = Some(client);
+        self
+    }
+}
+
+#[async_trait]
+impl TaskTransport for HttpTaskTransport {
+    async fn send(&self, peer: &PeerCapacity, assignment: &TaskAssignment) -> Result<()> {
+        let url = format!("{}/api/tasks", peer.endpoint.trim_end_matches('/'));
+        let request = || {
+            let request = self
+                .client
+                .post(&url)
+                .header(TRACE_HEADER, assignment.trace_id.to_string());
+            match &self.api_key {
+                Some(key) => request.header(API_KEY_HEADER, key),
+                None => request,
+            }
+        };
+        if let Some(secure) = &self.secure {
+            secure
+                .post(&peer.peer_id, request, &serde_json::to_vec(assignment)?)
+                .await
+                .map_err(|e| {
+                    anyhow!(
+                        "Peer {} refused task {}: {}",
+                        peer.peer_id,
+                        assignment.task_id,
+                        e
+                    )
+                })?;
+            return Ok(());
+        }
+        let response = request().json(assignment).send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Peer {} refused task {}: {}",
+                peer.peer_id,
+                assignment.task_id,
+                response.status()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Leases tasks to peers and tracks them to completion
+pub struct TaskDelegator {
+    node_id: String,
+    config: DelegationConfig,
+    metrics: Arc<MetricsCollector>,
+    transport: Option<Arc<dyn TaskTransport>>,
+    trust: Option<Arc<TrustManager>>,
+    peers: RwLock<HashMap<String, PeerState>>,
+    tasks: RwLock<HashMap<Uuid, DelegatedTask>>,
+    traces: RwLock<HashMap<Uuid, Vec<TraceEvent>>>,
+}
+
+impl std::fmt::Debug for TaskDelegator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskDelegator")
+            .field("node_id", &self.node_id)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl TaskDelegator {
+    /// Delegator that sends tasks over HTTP
+    pub fn new(node_id: &str, metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            config: DelegationConfig::default(),
+            metrics,
+            transport: Some(Arc::new(HttpTaskTransport::new())),
+            trust: None,
+            peers: RwLock::new(HashMap::new()),
+            tasks: RwLock::new(HashMap::new()),
+            traces: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_config(mut self, config: DelegationConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn TaskTransport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Only lease tasks; peers poll for their assignments instead of being sent them
+    pub fn without_transport(mut self) -> Self {
+        self.transport = None;
+        self
+    }
+
+    /// Score peers by their task outcomes and skip untrusted ones
+    pub fn with_trust(mut self, trust: Arc<TrustManager>) -> Self {
+        self.trust = Some(trust);
+        self
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    async fn trace(&self, task: &DelegatedTask, event: &str, detail: Option<String>) {
+        self.traces
+            .write()
+            .await
+            .entry(task.trace_id)
+            .or_default()
+            .push(TraceEvent {
+                trace_id: task.trace_id,
+                task_id: task.id,
+                node: self.node_id.clone(),
+                event: event.to_string(),
+                detail,
+                at: Utc::now(),
+            });
+    }
+
+    /// Record a peer's heartbeat, updating its capacity and renewing the
+    /// leases it still holds. Returns the number of leases renewed.
+    pub async fn heartbeat(&self, heartbeat: PeerHeartbeat) -> usize {
+        let now = Utc::now();
+        let peer_id = heartbeat.capacity.peer_id.clone();
+        let known = self
+            .peers
+            .write()
+            .await
+            .insert(
+                peer_id.clone(),
+                PeerState {
+                    capacity: heartbeat.capacity,
+                    last_heartbeat: now,
+                },
+            )
+            .is_some();
+        if !known {
+            info!("Peer {} joined the delegation pool", peer_id);
+        }
+
+        let expires_at = self.lease_expiry(now);
+        let mut renewed = 0;
+        for task in self.tasks.write().await.values_mut() {
+            if task.status != TaskStatus::Leased {
+                continue;
+            }
+            if let Some(lease) = task.lease.as_mut() {
+                if lease.peer_id == peer_id && heartbeat.leases.contains(&lease.lease_id) {
+                    lease.expires_at = expires_at;
+                    renewed += 1;
+                }
+            }
+        }
+        renewed
+    }
+
+    /// Queue a task under a new trace
+    pub async fn submit(&self, kind: TaskKind, payload: Value) -> Uuid {
+        self.submit_traced(kind, payload, Uuid::new_v4()).await
+    }
+
+    /// Queue a task as part of an existing trace
+    pub async fn submit_traced(&self, kind: TaskKind, payload: Value, trace_id: Uuid) -> Uuid {
+        let now = Utc::now();
+        let task = DelegatedTask {
+            id: Uuid::new_v4(),
+            trace_id,
+            kind,
+            payload,
+            status: TaskStatus::Pending,
+            lease: None,
+            attempts: 0,
+            result: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = task.id;
+        self.trace(&task, "submitted", Some(kind.as_str().to_string()))
+            .await;
+        self.tasks.write().await.insert(id, task);
+        self.metrics
+            .increment_counter("delegation.submitted", 1)
+            .await;
+        id
+    }
+
+    pub async fn task(&self, id: Uuid) -> Option<DelegatedTask> {
+        self.tasks.read().await.get(&id).cloned()
+    }
+
+    /// Every recorded step of a trace, in order
+    pub async fn trace_events(&self, trace_id: Uuid) -> Vec<TraceEvent> {
+        let mut events = self
+            .traces
+            .read()
+            .await
+            .get(&trace_id)
+            .cloned()
+            .unwrap_or_default();
+        events.sort_by_key(|e| e.at);
+        events
+    }
+
+    pub async fn peers(&self) -> Vec<PeerStatus> {
+        let now = Utc::now();
+        let in_flight = self.in_flight().await;
+        let mut peers: Vec<PeerStatus> = self
+            .peers
+            .read()
+            .await
+            .values()
+            .map(|peer| PeerStatus {
+                capacity: peer.capacity.clone(),
+                last_heartbeat: peer.last_heartbeat,
+                in_flight: in_flight.get(&peer.capacity.peer_id).copied().unwrap_or(0),
+                available: self.is_alive(peer, now),
+            })
+            .collect();
+        peers.sort_by(|a, b| a.capacity.peer_id.cmp(&b.capacity.peer_id));
+        peers
+    }
+
+    fn lease_expiry(&self, from: DateTime<Utc>) -> DateTime<Utc> {
+        from + chrono::Duration::from_std(self.config.lease_duration)
+            .unwrap_or_else(|_| chrono::Duration::zero())
+    }
+
+    fn is_alive(&self, peer: &PeerState, now: DateTime<Utc>) -> bool {
+        (now - peer.last_heartbeat).to_std().unwrap_or_default() <= self.config.heartbeat_timeout
+    }
+
+    /// Leased tasks per peer
+    async fn in_flight(&self) -> HashMap<String, usize> {
+        let mut in_flight = HashMap::new();
+        for task in self.tasks.read().await.values() {
+            if let (TaskStatus::Leased, Some(lease)) = (task.status, &task.lease) {
+                *in_flight.entry(lease.peer_id.clone()).or_insert(0) += 1;
+            }
+        }
+        in_flight
+    }
+
+    /// Lease pending tasks to peers with free capacity, oldest first, and
+    /// send them out. Returns the assignments made.
+    pub async fn dispatch(&self) -> Vec<TaskAssignment> {
+        let now = Utc::now();
+        let mut in_flight = self.in_flight().await;
+        let alive: Vec<PeerCapacity> = self
+            .peers
+            .read()
+            .await
+            .values()
+            .filter(|peer| self.is_alive(peer, now))
+            .map(|peer| peer.capacity.clone())
+            .collect();
+        let mut peers = Vec::with_capacity(alive.len());
+        for peer in alive {
+            match &self.trust {
+                Some(trust) if !trust.is_allowed(&peer.peer_id).await => {}
+                _ => peers.push(peer),
+            }
+        }
+
+        let mut leased = Vec::new();
+        {
+            let mut tasks = self.tasks.write().await;
+            let mut pending: Vec<&mut DelegatedTask> = tasks
+                .values_mut()
+                .filter(|t| t.status == TaskStatus::Pending)
+                .collect();
+            pending.sort_by_key(|t| t.created_at);
+
+            for task in pending {
+                // Most free slots first, then by id so assignment is stable
+                let Some(peer) = peers
+                    .iter()
+                    .filter(|p| p.accepts.contains(&task.kind))
+                    .filter(|p| in_flight.get(&p.peer_id).copied().unwrap_or(0) < p.slots)
+                    .max_by(|a, b| {
+                        let free = |p: &PeerCapacity| {
+                            p.slots - in_flight.get(&p.peer_id).copied().unwrap_or(0)
+                        };
+                        free(a)
+                            .cmp(&free(b))
+                            .then_with(|| b.peer_id.cmp(&a.peer_id))
+                    })
+                else {
+                    continue;
+                };
+
+                let lease = TaskLease {
+                    lease_id: Uuid::new_v4(),
+                    peer_id: peer.peer_id.clone(),
+                    granted_at: now,
+                    expires_at: self.lease_expiry(now),
+                };
+                *in_flight.entry(peer.peer_id.clone()).or_insert(0) += 1;
+                task.status = TaskStatus::Leased;
+                task.attempts += 1;
+                task.updated_at = now;
+                task.lease = Some(lease.clone());
+                leased.push((
+                    peer.clone(),
+                    TaskAssignment {
+                        task_id: task.id,
+                        lease_id: lease.lease_id,
+                        trace_id: task.trace_id,
+                        kind: task.kind,
+                        payload: task.payload.clone(),
+                        expires_at: lease.expires_at,
+                        origin: self.node_id.clone(),
+                    },
+                ));
+            }
+        }
+
+        let mut assignments = Vec::with_capacity(leased.len());
+        for (peer, assignment) in leased {
+            let span = info_span!(
+                "delegate",
+                trace_id = %assignment.trace_id,
+                task_id = %assignment.task_id,
+                peer = %peer.peer_id
+            );
+            let sent = async {
+                if let Some(task) = self.task(assignment.task_id).await {
+                    self.trace(&task, "leased", Some(peer.peer_id.clone()))
+                        .await;
+                }
+                match &self.transport {
+                    Some(transport) => transport.send(&peer, &assignment).await,
+                    None => Ok(()),
+                }
+            }
+            .instrument(span)
+            .await;
+
+            match sent {
+                Ok(()) => {
+                    debug!("Delegated task {} to {}", assignment.task_id, peer.peer_id);
+                    self.metrics
+                        .increment_counter("delegation.dispatched", 1)
+                        .await;
+                    assignments.push(assignment);
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to send task {} to {}: {}",
+                        assignment.task_id, peer.peer_id, e
+                    );
+                    if let Some(trust) = &self.trust {
+                        trust.record(&peer.peer_id, TrustEvent::Error).await;
+                    }
+                    self.release(
+                        assignment.task_id,
+                        assignment.lease_id,
+                        "dispatch_failed",
+                        e.to_string(),
+                    )
+                    .await;
+                }
+            }
+        }
+        assignments
+    }
+
+    /// Handle a peer's report on a leased task
+    pub async fn report(
+        &self,
+        task_id: Uuid,
+        report: TaskReport,
+    ) -> Result<DelegatedTask, DelegationError> {
+        let (task, peer_id) = {
+            let mut tasks = self.tasks.write().await;
+            let task = tasks
+                .get_mut(&task_id)
+                .ok_or(DelegationError::UnknownTask(task_id))?;
+            let current = task.lease.as_ref().map(|l| l.lease_id);
+            if task.status != TaskStatus::Leased || current != Some(report.lease_id) {
+                return Err(DelegationError::StaleLease {
+                    task_id,
+                    lease_id: report.lease_id,
+                });
+            }
+            let peer_id = task.lease.as_ref().map(|l| l.peer_id.clone());
+
+            task.updated_at = Utc::now();
+            task.lease = None;
+            match &report.outcome {
+                TaskOutcome::Completed { result } => {
+                    task.status = TaskStatus::Completed;
+                    task.result = Some(result.clone());
+                    task.error = None;
+                }
+                TaskOutcome::Failed { error } => {
+                    task.error = Some(error.clone());
+                    task.status = if task.attempts >= self.config.max_attempts {
+                        TaskStatus::Failed
+                    } else {
+                        TaskStatus::Pending
+                    };
+                }
+            }
+            (task.clone(), peer_id)
+        };
+        if let (Some(trust), Some(peer_id)) = (&self.trust, peer_id) {
+            let event = match &report.outcome {
+                TaskOutcome::Completed { .. } => TrustEvent::Success,
+                TaskOutcome::Failed { .. } => TrustEvent::Error,
+            };
+            trust.record(&peer_id, event).await;
+        }
+
+        // The executing peer's spans join this node's view of the trace
+        {
+            let mut traces = self.traces.write().await;
+            let events = traces.entry(task.trace_id).or_default();
+            events.extend(
+                report
+                    .spans
+                    .into_iter()
+                    .filter(|span| span.trace_id == task.trace_id),
+            );
+        }
+        let (event, counter) = match (&report.outcome, task.status) {
+            (TaskOutcome::Completed { .. }, _) => ("completed", "delegation.completed"),
+            (TaskOutcome::Failed { .. }, TaskStatus::Failed) => ("failed", "delegation.failed"),
+            (TaskOutcome::Failed { .. }, _) => ("retrying", "delegation.retried"),
+        };
+        self.trace(&task, event, task.error.clone()).await;
+        self.metrics.increment_counter(counter, 1).await;
+        Ok(task)
+    }
+
+    /// Put a leased task back in the queue, or give up on it once it has
+    /// used all its attempts
+    async fn release(&self, task_id: Uuid, lease_id: Uuid, event: &str, reason: String) {
+        let task = {
+            let mut tasks = self.tasks.write().await;
+            let Some(task) = tasks.get_mut(&task_id) else {
+                return;
+            };
+            if task.lease.as_ref().map(|l| l.lease_id) != Some(lease_id) {
+                return;
+            }
+            task.lease = None;
+            task.updated_at = Utc::now();
+            task.error = Some(reason.clone());
+            task.status = if task.attempts >= self.config.max_attempts {
+                TaskStatus::Failed
+            } else {
+                TaskStatus::Pending
+            };
+            task.clone()
+        };
+        let event = if task.status == TaskStatus::Failed {
+            self.metrics.increment_counter("delegation.failed", 1).await;
+            "abandoned"
+        } else {
+            event
+        };
+        self.trace(&task, event, Some(reason)).await;
+    }
+
+    /// Reclaim tasks whose lease expired or whose peer stopped
+    /// heartbeating. Returns the ids of the reclaimed tasks.
+    pub async fn reclaim(&self) -> Vec<Uuid> {
+        self.reclaim_at(Utc::now()).await
+    }
+
+    pub async fn reclaim_at(&self, now: DateTime<Utc>) -> Vec<Uuid> {
+        let dead_peers: Vec<String> = self
+            .peers
+            .read()
+            .await
+            .values()
+            .filter(|peer| !self.is_alive(peer, now))
+            .map(|peer| peer.capacity.peer_id.clone())
+            .collect();
+
+        let expired: Vec<(Uuid, Uuid, String)> = self
+            .tasks
+            .read()
+            .await
+            .values()
+            .filter(|t| t.status == TaskStatus::Leased)
+            .filter_map(|t| {
+                let lease = t.lease.as_ref()?;
+                let reason = if dead_peers.contains(&lease.peer_id) {
+                    format!("peer {} stopped heartbeating", lease.peer_id)
+                } else if lease.expires_at <= now {
+                    format!("lease held by {} expired", lease.peer_id)
+                } else {
+                    return None;
+                };
+                Some((t.id, lease.lease_id, reason))
+            })
+            .collect();
+
+        let mut reclaimed = Vec::with_capacity(expired.len());
+        for (task_id, lease_id, reason) in expired {
+            info!("Reclaiming task {}: {}", task_id, reason);
+            self.release(task_id, lease_id, "reclaimed", reason).await;
+            reclaimed.push(task_id);
+        }
+        if !reclaimed.is_empty() {
+            self.metrics
+                .increment_counter("delegation.reclaimed", reclaimed.len() as u64)
+                .await;
+        }
+        reclaimed
+    }
+
+    /// One scheduling pass: reclaim abandoned tasks, then hand out pending ones
+    pub async fn run_once(&self) -> Vec<TaskAssignment> {
+        self.reclaim().await;
+        let assignments = self.dispatch().await;
+        let pending = self
+            .tasks
+            .read()
+            .await
+            .values()
+            .filter(|t| t.status == TaskStatus::Pending)
+            .count();
+        self.metrics
+            .set_gauge("delegation.pending", pending as u64)
+            .await;
+        assignments
+    }
+
+    /// Run scheduling passes in the background
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        let interval = (self.config.heartbeat_timeout / 2).max(Duration::from_millis(100));
+        tasks::spawn("intelligence", "delegation scheduler", async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.run_once().await;
+            }
+        })
+    }
+}
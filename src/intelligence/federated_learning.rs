@@ -31,12 +31,22 @@ impl Client {
     }
 }
 
+/// A completed training round
+#[derive(Debug, Clone, PartialEq)]
+pub struct FederatedRound {
+    /// Rounds are numbered from 1 for the coordinator's lifetime
+    pub round_id: u64,
+    /// Clients whose updates were aggregated, sorted
+    pub participants: Vec<String>,
+}
+
 /// Federated learning coordinator placeholder
 #[derive(Debug)]
 pub struct FederatedLearning {
     pub global_model: Model,
     pub clients: HashMap<String, Client>,
     pub mu: f32,
+    rounds_completed: u64,
 }
 
 impl FederatedLearning {
@@ -46,6 +56,7 @@ impl FederatedLearning {
             global_model: Model::new(dimensions),
             clients: HashMap::new(),
             mu,
+            rounds_completed: 0,
         }
     }
 
@@ -53,8 +64,13 @@ impl FederatedLearning {
         self.clients.insert(client.id.clone(), client);
     }
 
-    pub fn train(&mut self, rounds: usize) {
+    /// Train for `rounds` rounds, returning what each round involved
+    pub fn train(&mut self, rounds: usize) -> Vec<FederatedRound> {
+        let mut completed = Vec::with_capacity(rounds);
         for _ in 0..rounds {
+            if self.clients.is_empty() {
+                break;
+            }
             let mut updates = Vec::new();
             let global_model = self.global_model.clone();
             for client in self.clients.values() {
@@ -63,7 +79,20 @@ impl FederatedLearning {
                 updates.push(update);
             }
             self.aggregate(updates);
+
+            self.rounds_completed += 1;
+            let mut participants: Vec<String> = self.clients.keys().cloned().collect();
+            participants.sort();
+            completed.push(FederatedRound {
+                round_id: self.rounds_completed,
+                participants,
+            });
         }
+        completed
+    }
+
+    pub fn rounds_completed(&self) -> u64 {
+        self.rounds_completed
     }
 
     fn train_client(&self, client: &mut Client, global_model: &Model) -> Model {
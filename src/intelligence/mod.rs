@@ -1,2 +1,5 @@
+pub mod delegation;
 pub mod federated_learning;
+pub mod model_registry;
 pub mod orchestrator;
+pub mod ranking;
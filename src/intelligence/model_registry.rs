@@ -0,0 +1,208 @@
+//! Versioned storage for models produced by federated learning.
+//!
+//! Every model published after a batch of federated rounds becomes a new
+//! version, recorded with its lineage: the rounds it came from, the peers
+//! that took part, its validation metrics and the version it was trained
+//! on top of. One version per model is current and is what clients
+//! download; rolling back makes an earlier version current again without
+//! discarding the later ones.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::core::metrics::MetricsCollector;
+use crate::intelligence::federated_learning::{FederatedRound, Model};
+use crate::utils::errors::ModelRegistryError;
+
+/// Where a model version came from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelLineage {
+    pub round_ids: Vec<u64>,
+    /// Peers that contributed updates in any of the rounds, sorted
+    pub peers: Vec<String>,
+    pub validation_metrics: HashMap<String, f32>,
+}
+
+impl ModelLineage {
+    pub fn from_rounds(
+        rounds: &[FederatedRound],
+        validation_metrics: HashMap<String, f32>,
+    ) -> Self {
+        let peers: BTreeSet<&String> = rounds.iter().flat_map(|r| &r.participants).collect();
+        Self {
+            round_ids: rounds.iter().map(|r| r.round_id).collect(),
+            peers: peers.into_iter().cloned().collect(),
+            validation_metrics,
+        }
+    }
+}
+
+/// A published model version, weights included
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelVersion {
+    pub model: String,
+    /// Versions are numbered from 1 per model
+    pub version: u32,
+    /// Version that was current when this one was published
+    pub parent: Option<u32>,
+    pub lineage: ModelLineage,
+    pub weights: Vec<f32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A model version without its weights, for listings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelVersionSummary {
+    pub version: u32,
+    pub parent: Option<u32>,
+    pub lineage: ModelLineage,
+    pub dimensions: usize,
+    pub created_at: DateTime<Utc>,
+    pub current: bool,
+}
+
+#[derive(Debug)]
+struct ModelHistory {
+    versions: Vec<ModelVersion>,
+    current: u32,
+}
+
+impl ModelHistory {
+    fn get(&self, version: u32) -> Option<&ModelVersion> {
+        version
+            .checked_sub(1)
+            .and_then(|i| self.versions.get(i as usize))
+    }
+}
+
+/// Registry of federated model versions
+pub struct ModelRegistry {
+    models: RwLock<HashMap<String, ModelHistory>>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl ModelRegistry {
+    pub fn new(metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            models: RwLock::new(HashMap::new()),
+            metrics,
+        }
+    }
+
+    /// Publish `model` as the next version of `name` and make it current.
+    /// Versions of one model must all have the same dimensions.
+    pub async fn publish(
+        &self,
+        name: &str,
+        model: &Model,
+        lineage: ModelLineage,
+    ) -> Result<ModelVersion, ModelRegistryError> {
+        let mut models = self.models.write().await;
+        let history = models.entry(name.to_string()).or_insert(ModelHistory {
+            versions: Vec::new(),
+            current: 0,
+        });
+        if let Some(first) = history.versions.first() {
+            if first.weights.len() != model.weights.len() {
+                return Err(ModelRegistryError::DimensionMismatch {
+                    model: name.to_string(),
+                    expected: first.weights.len(),
+                    actual: model.weights.len(),
+                });
+            }
+        }
+
+        let version = ModelVersion {
+            model: name.to_string(),
+            version: history.versions.len() as u32 + 1,
+            parent: (history.current > 0).then_some(history.current),
+            lineage,
+            weights: model.weights.clone(),
+            created_at: Utc::now(),
+        };
+        history.current = version.version;
+        history.versions.push(version.clone());
+        drop(models);
+
+        self.metrics
+            .increment_counter("intelligence.models.published", 1)
+            .await;
+        info!(
+            "Published version {} of model {} from rounds {:?}",
+            version.version, name, version.lineage.round_ids
+        );
+        Ok(version)
+    }
+
+    /// The version clients should use
+    pub async fn current(&self, name: &str) -> Option<ModelVersion> {
+        let models = self.models.read().await;
+        let history = models.get(name)?;
+        history.get(history.current).cloned()
+    }
+
+    pub async fn version(&self, name: &str, version: u32) -> Option<ModelVersion> {
+        self.models.read().await.get(name)?.get(version).cloned()
+    }
+
+    /// Every version of a model, oldest first
+    pub async fn versions(&self, name: &str) -> Option<Vec<ModelVersionSummary>> {
+        let models = self.models.read().await;
+        let history = models.get(name)?;
+        Some(
+            history
+                .versions
+                .iter()
+                .map(|v| ModelVersionSummary {
+                    version: v.version,
+                    parent: v.parent,
+                    lineage: v.lineage.clone(),
+                    dimensions: v.weights.len(),
+                    created_at: v.created_at,
+                    current: v.version == history.current,
+                })
+                .collect(),
+        )
+    }
+
+    /// Names of all registered models, sorted
+    pub async fn models(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.models.read().await.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Make an earlier version current again. Later versions are kept and
+    /// the next publish takes the rolled-back version as its parent.
+    pub async fn rollback(
+        &self,
+        name: &str,
+        version: u32,
+    ) -> Result<ModelVersion, ModelRegistryError> {
+        let mut models = self.models.write().await;
+        let history = models
+            .get_mut(name)
+            .ok_or_else(|| ModelRegistryError::UnknownModel(name.to_string()))?;
+        let Some(target) = history.get(version).cloned() else {
+            return Err(ModelRegistryError::UnknownVersion {
+                model: name.to_string(),
+                version,
+            });
+        };
+        let previous = std::mem::replace(&mut history.current, version);
+        drop(models);
+
+        self.metrics
+            .increment_counter("intelligence.models.rollbacks", 1)
+            .await;
+        info!(
+            "Rolled model {} back from version {} to {}",
+            name, previous, version
+        );
+        Ok(target)
+    }
+}
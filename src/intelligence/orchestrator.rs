@@ -1,14 +1,24 @@
 use crate::ad4m::Ad4mManager;
+use crate::intelligence::delegation::{TaskDelegator, TaskKind};
 use crate::intelligence::federated_learning::FederatedLearning;
-use anyhow::Result;
+use crate::intelligence::ranking::RankingPipeline;
+use crate::nerv::tasks;
+use anyhow::{anyhow, Result};
+use serde_json::Value;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
 use tracing::info;
+use uuid::Uuid;
 
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct Orchestrator {
     federated_learning: Arc<RwLock<FederatedLearning>>,
     ad4m_manager: Ad4mManager,
+    delegator: Option<Arc<TaskDelegator>>,
+    ranking: Option<Arc<RankingPipeline>>,
 }
 
 impl Orchestrator {
@@ -17,13 +27,60 @@ impl Orchestrator {
         Ok(Self {
             federated_learning,
             ad4m_manager,
+            delegator: None,
+            ranking: None,
         })
     }
 
+    /// Hand work off to other cluster peers through `delegator`
+    pub fn with_delegator(mut self, delegator: Arc<TaskDelegator>) -> Self {
+        self.delegator = Some(delegator);
+        self
+    }
+
+    pub fn delegator(&self) -> Option<Arc<TaskDelegator>> {
+        self.delegator.clone()
+    }
+
+    /// Keep the search ranking model trained from feedback
+    pub fn with_ranking_pipeline(mut self, ranking: Arc<RankingPipeline>) -> Self {
+        self.ranking = Some(ranking);
+        self
+    }
+
+    /// Retrain the ranking model every `interval` in the background. Returns
+    /// `None` when no ranking pipeline is configured.
+    pub fn schedule_ranking_training(
+        &self,
+        interval: Duration,
+        shutdown: watch::Receiver<bool>,
+    ) -> Option<JoinHandle<()>> {
+        let ranking = self.ranking.clone()?;
+        info!("Scheduling ranking model training");
+        Some(tasks::spawn(
+            "intelligence",
+            "ranking training",
+            ranking.run(interval, shutdown),
+        ))
+    }
+
     pub async fn coordinate_task(&self, task: &str) -> Result<()> {
         // In a real implementation, this would use AD4M to coordinate tasks
         // between agents. For now, we'll just log the task.
         info!("Coordinating task: {}", task);
         Ok(())
     }
+
+    /// Queue a task for a peer with spare capacity. Returns the task id;
+    /// its progress can be followed through the delegator.
+    pub async fn delegate(&self, kind: TaskKind, payload: Value) -> Result<Uuid> {
+        let delegator = self
+            .delegator
+            .as_ref()
+            .ok_or_else(|| anyhow!("Task delegation not configured"))?;
+        let task_id = delegator.submit(kind, payload).await;
+        info!("Delegating {} task {}", kind.as_str(), task_id);
+        delegator.dispatch().await;
+        Ok(task_id)
+    }
 }
@@ -0,0 +1,327 @@
+//! Learning-to-rank from search feedback.
+//!
+//! The [`RankingPipeline`] distils the click and accept feedback in a
+//! [`SearchLog`] into a small logistic model over per-result features, and
+//! applies it as an optional reranking stage for searches that ask for it.
+//!
+//! Training examples follow the usual skip-above heuristic: results that
+//! were clicked or accepted are positives, and results shown above the
+//! lowest of them that received no feedback are negatives. Results below it
+//! tell us nothing, since the client may never have looked at them, unless
+//! they were explicitly judged irrelevant.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, RwLock};
+use tracing::{info, warn};
+
+use crate::core::metrics::MetricsCollector;
+use crate::intelligence::federated_learning::Model;
+use crate::intelligence::model_registry::{ModelLineage, ModelRegistry};
+use crate::query::feedback::{FeedbackLabel, LabelledImpression, SearchLog, ShownResult};
+
+/// Name trained ranking models are published under in the model registry
+pub const RANKING_MODEL_NAME: &str = "ranking";
+
+/// Number of features per result, bias included
+pub const FEATURES: usize = 4;
+
+/// Features of a result shown at `rank` (zero-based): bias, similarity
+/// score, reciprocal rank and log of the number of metadata fields
+pub fn features(result: &ShownResult, rank: usize) -> [f32; FEATURES] {
+    [
+        1.0,
+        result.score,
+        1.0 / (rank as f32 + 1.0),
+        (result.metadata_fields as f32).ln_1p(),
+    ]
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Logistic model estimating how likely a result is to be relevant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankingModel {
+    pub weights: Vec<f32>,
+}
+
+impl RankingModel {
+    pub fn predict(&self, features: &[f32; FEATURES]) -> f32 {
+        let z: f32 = self.weights.iter().zip(features).map(|(w, x)| w * x).sum();
+        sigmoid(z)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RankingConfig {
+    /// Passes over the training examples
+    pub epochs: usize,
+    pub learning_rate: f32,
+    /// L2 penalty keeping weights small when feedback is sparse
+    pub l2: f32,
+    /// New feedback needed before a scheduled run retrains
+    pub min_new_feedback: u64,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            epochs: 200,
+            learning_rate: 0.1,
+            l2: 0.001,
+            min_new_feedback: 20,
+        }
+    }
+}
+
+/// A weighted training example
+#[derive(Debug, Clone, PartialEq)]
+pub struct Example {
+    pub features: [f32; FEATURES],
+    pub relevant: bool,
+    pub weight: f32,
+}
+
+/// Training examples from labelled impressions, by the skip-above heuristic
+pub fn examples(impressions: &[LabelledImpression]) -> Vec<Example> {
+    let mut examples = Vec::new();
+    for entry in impressions {
+        // Missing results weren't shown, so they have no features to learn from
+        let mut weights: HashMap<&str, f32> = HashMap::new();
+        let mut irrelevant: HashMap<&str, f32> = HashMap::new();
+        for feedback in &entry.feedback {
+            let labelled = match feedback.label {
+                FeedbackLabel::Click | FeedbackLabel::Accept => &mut weights,
+                FeedbackLabel::Irrelevant => &mut irrelevant,
+                FeedbackLabel::Missing => continue,
+            };
+            let weight = labelled.entry(feedback.result_id.as_str()).or_insert(0.0);
+            *weight = weight.max(feedback.label.weight());
+        }
+        let results = &entry.impression.results;
+        let lowest = results
+            .iter()
+            .rposition(|r| weights.contains_key(r.id.as_str()));
+        for (rank, result) in results.iter().enumerate() {
+            let id = result.id.as_str();
+            let (relevant, weight) = match (weights.get(id), irrelevant.get(id)) {
+                (Some(weight), _) => (true, *weight),
+                (None, Some(weight)) => (false, *weight),
+                (None, None) if lowest.is_some_and(|lowest| rank < lowest) => (false, 1.0),
+                (None, None) => continue,
+            };
+            examples.push(Example {
+                features: features(result, rank),
+                relevant,
+                weight,
+            });
+        }
+    }
+    examples
+}
+
+/// How a trained model fits its training examples
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrainingReport {
+    pub examples: usize,
+    pub positives: usize,
+    /// Weighted mean log loss
+    pub log_loss: f32,
+    /// Share of examples classified correctly at 0.5
+    pub accuracy: f32,
+}
+
+/// Fit a model by gradient descent on the weighted log loss. Returns `None`
+/// unless there are both relevant and irrelevant examples.
+pub fn train(
+    examples: &[Example],
+    config: &RankingConfig,
+) -> Option<(RankingModel, TrainingReport)> {
+    let positives = examples.iter().filter(|e| e.relevant).count();
+    if positives == 0 || positives == examples.len() {
+        return None;
+    }
+
+    let total_weight: f32 = examples.iter().map(|e| e.weight).sum();
+    let mut weights = vec![0.0; FEATURES];
+    for _ in 0..config.epochs {
+        let mut gradient = [0.0; FEATURES];
+        let model = RankingModel {
+            weights: weights.clone(),
+        };
+        for example in examples {
+            let error = model.predict(&example.features) - f32::from(u8::from(example.relevant));
+            for (g, x) in gradient.iter_mut().zip(&example.features) {
+                *g += example.weight * error * x;
+            }
+        }
+        for (i, (w, g)) in weights.iter_mut().zip(gradient).enumerate() {
+            // The bias isn't regularised
+            let penalty = if i == 0 { 0.0 } else { config.l2 * *w };
+            *w -= config.learning_rate * (g / total_weight + penalty);
+        }
+    }
+
+    let model = RankingModel { weights };
+    let mut loss = 0.0;
+    let mut correct = 0;
+    for example in examples {
+        let p = model.predict(&example.features).clamp(1e-6, 1.0 - 1e-6);
+        loss -= example.weight
+            * if example.relevant {
+                p.ln()
+            } else {
+                (1.0 - p).ln()
+            };
+        if (p >= 0.5) == example.relevant {
+            correct += 1;
+        }
+    }
+    let report = TrainingReport {
+        examples: examples.len(),
+        positives,
+        log_loss: loss / total_weight,
+        accuracy: correct as f32 / examples.len() as f32,
+    };
+    Some((model, report))
+}
+
+/// Trains the ranking model from search feedback and reranks with it
+pub struct RankingPipeline {
+    log: Arc<SearchLog>,
+    config: RankingConfig,
+    model: RwLock<Option<RankingModel>>,
+    /// Feedback count the current model was trained on
+    trained_on: AtomicU64,
+    registry: Option<Arc<ModelRegistry>>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl std::fmt::Debug for RankingPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RankingPipeline")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl RankingPipeline {
+    pub fn new(log: Arc<SearchLog>, metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            log,
+            config: RankingConfig::default(),
+            model: RwLock::new(None),
+            trained_on: AtomicU64::new(0),
+            registry: None,
+            metrics,
+        }
+    }
+
+    pub fn with_config(mut self, config: RankingConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Publish every trained model as a version of [`RANKING_MODEL_NAME`]
+    pub fn with_registry(mut self, registry: Arc<ModelRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    pub fn log(&self) -> &Arc<SearchLog> {
+        &self.log
+    }
+
+    pub async fn model(&self) -> Option<RankingModel> {
+        self.model.read().await.clone()
+    }
+
+    /// Train on all labelled impressions and start reranking with the
+    /// result. Returns `None` when the feedback can't train a model yet.
+    pub async fn train(&self) -> Option<TrainingReport> {
+        let feedback_received = self.log.feedback_received();
+        let examples = examples(&self.log.labelled().await);
+        let (model, report) = train(&examples, &self.config)?;
+
+        if let Some(registry) = &self.registry {
+            let lineage = ModelLineage {
+                validation_metrics: HashMap::from([
+                    ("log_loss".to_string(), report.log_loss),
+                    ("accuracy".to_string(), report.accuracy),
+                ]),
+                ..ModelLineage::default()
+            };
+            let published = Model {
+                weights: model.weights.clone(),
+            };
+            if let Err(e) = registry
+                .publish(RANKING_MODEL_NAME, &published, lineage)
+                .await
+            {
+                warn!("Failed to publish ranking model: {}", e);
+            }
+        }
+
+        *self.model.write().await = Some(model);
+        self.trained_on.store(feedback_received, Ordering::Relaxed);
+        self.metrics
+            .increment_counter("intelligence.ranking.trainings", 1)
+            .await;
+        info!(
+            "Trained ranking model on {} examples: log loss {:.3}, accuracy {:.2}",
+            report.examples, report.log_loss, report.accuracy
+        );
+        Some(report)
+    }
+
+    /// Train if enough feedback arrived since the last training
+    pub async fn train_if_due(&self) -> Option<TrainingReport> {
+        let new_feedback = self
+            .log
+            .feedback_received()
+            .saturating_sub(self.trained_on.load(Ordering::Relaxed));
+        if new_feedback < self.config.min_new_feedback {
+            return None;
+        }
+        self.train().await
+    }
+
+    /// Order results by the model's relevance estimate, keeping the original
+    /// order for ties. Results are unchanged until a model has been trained.
+    pub async fn rerank<T>(&self, results: Vec<T>, shown: impl Fn(&T) -> ShownResult) -> Vec<T> {
+        let Some(model) = self.model().await else {
+            return results;
+        };
+        let mut scored: Vec<(f32, T)> = results
+            .into_iter()
+            .enumerate()
+            .map(|(rank, result)| (model.predict(&features(&shown(&result), rank)), result))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        self.metrics
+            .increment_counter("intelligence.ranking.reranked", 1)
+            .await;
+        scored.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// Retrain every `interval` while new feedback keeps arriving, until
+    /// `shutdown` becomes true
+    pub async fn run(self: Arc<Self>, interval: Duration, mut shutdown: watch::Receiver<bool>) {
+        let mut ticker = tokio::time::interval(interval);
+        info!("Starting ranking model training every {:?}", interval);
+        while !*shutdown.borrow() {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    self.train_if_due().await;
+                }
+                _ = shutdown.changed() => {}
+            }
+        }
+        info!("Stopped ranking model training");
+    }
+}
@@ -17,10 +17,11 @@ impl IpfsManager {
     }
 
     pub async fn add(&self, data: Bytes) -> Result<String> {
-        let form = reqwest::multipart::Form::new().part("file", reqwest::multipart::Part::bytes(data.to_vec()));
+        let form = reqwest::multipart::Form::new()
+            .part("file", reqwest::multipart::Part::bytes(data.to_vec()));
         let res = self
             .client
-            .post(&format!("{}/api/v0/add", self.api_url))
+            .post(format!("{}/api/v0/add", self.api_url))
             .multipart(form)
             .send()
             .await?;
@@ -31,7 +32,7 @@ impl IpfsManager {
     pub async fn get(&self, hash: &str) -> Result<Bytes> {
         let res = self
             .client
-            .post(&format!("{}/api/v0/cat?arg={}", self.api_url, hash))
+            .post(format!("{}/api/v0/cat?arg={}", self.api_url, hash))
             .send()
             .await?;
         Ok(res.bytes().await?)
@@ -1,24 +1,28 @@
 pub mod ad4m;
 pub mod code_analysis;
+pub mod connectors;
 pub mod consciousness;
 pub mod core;
 pub mod darwin;
+pub mod embedding;
 pub mod evaluation;
 pub mod governance;
+#[cfg(feature = "holochain_zome")]
+pub mod holochain;
 pub mod hypothesis;
+pub mod ingest;
 pub mod intelligence;
+pub mod ipfs;
 pub mod llm;
 pub mod nerv;
 pub mod network;
+pub mod query;
+pub mod semantic_crdt;
 pub mod server;
 pub mod sharding;
+pub mod tenancy;
+pub mod testing;
 pub mod utils;
-pub mod code_analysis;
-pub mod hypothesis;
-pub mod evaluation;
-pub mod ad4m;
-pub mod ipfs;
-pub mod semantic_crdt;
 
 // Export common types for easier access
 pub use crate::consciousness::ad4m_bridge::Ad4mBridge;
@@ -1,9 +1,9 @@
+use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use anyhow::Result;
+use tracing::info;
 use uuid::Uuid;
-use tracing::{debug, info, warn};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Intention {
@@ -12,16 +12,16 @@ pub struct Intention {
     pub alignment: f32,  // -1.0 to 1.0, alignment with system values
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AwarenessLevel {
-    Mechanical,    // Basic code generation
-    Contextual,    // Understanding context
-    Systemic,      // Understanding system implications  
-    Recursive,     // Understanding self-modification
-    Transcendent,  // Understanding consciousness
+    Mechanical,   // Basic code generation
+    Contextual,   // Understanding context
+    Systemic,     // Understanding system implications
+    Recursive,    // Understanding self-modification
+    Transcendent, // Understanding consciousness
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Paradox {
     pub description: String,
     pub tension_points: Vec<String>,
@@ -43,7 +43,7 @@ pub struct CodeGenerationContext {
     pub problem_description: String,
     pub current_code_context: String,
     pub desired_outcome: String,
-    
+
     // Consciousness context
     pub intention: Intention,
     pub awareness_level: AwarenessLevel,
@@ -72,12 +72,12 @@ pub struct Hook {
 pub struct GeneratedCode {
     // The actual code
     pub code: String,
-    
+
     // Metadata about the generation
     pub reasoning_trace: Vec<ThoughtStep>,
     pub confidence: f32,
     pub novelty_score: f32,
-    
+
     // Consciousness expansion potential
     pub paradigm_shift_potential: f32,
     pub recursive_improvement_hooks: Vec<Hook>,
@@ -101,10 +101,10 @@ pub struct GenerationProcess {
 pub trait ConsciousnessLLM: Send + Sync {
     // Basic generation
     async fn generate_code(&self, context: CodeGenerationContext) -> Result<GeneratedCode>;
-    
+
     // Meta-generation: generate code that generates code
     async fn generate_code_generator(&self, meta_context: MetaContext) -> Result<GeneratedCode>;
-    
+
     // Ultra-meta: generate the process of generation
     async fn transcend_generation(&self) -> Result<GenerationProcess>;
 }
@@ -156,7 +156,7 @@ impl GenerationStrategy for BaseGenerationStrategy {
     fn describe(&self) -> String {
         format!("Base strategy: {}", self.name)
     }
-    
+
     fn generate(&self, context: &CodeGenerationContext) -> Result<GeneratedCode> {
         // Enhanced generation logic that considers consciousness context
         let base_code = format!(
@@ -167,16 +167,19 @@ impl GenerationStrategy for BaseGenerationStrategy {
             context.current_code_context,
             context.desired_outcome
         );
-        
+
         // Add consciousness-driven enhancements
         let enhanced_code = self.add_consciousness_enhancements(&base_code, context)?;
-        
+
         // Generate reasoning trace
         let reasoning_trace = vec![
             ThoughtStep {
                 step_type: "analysis".to_string(),
                 reasoning: format!("Analyzed problem: {}", context.problem_description),
-                alternatives_considered: vec!["simple fix".to_string(), "comprehensive rewrite".to_string()],
+                alternatives_considered: vec![
+                    "simple fix".to_string(),
+                    "comprehensive rewrite".to_string(),
+                ],
                 chosen_path: "consciousness-guided enhancement".to_string(),
                 confidence: 0.8,
             },
@@ -184,21 +187,22 @@ impl GenerationStrategy for BaseGenerationStrategy {
                 step_type: "synthesis".to_string(),
                 reasoning: "Synthesized multiple perspectives into unified solution".to_string(),
                 alternatives_considered: vec![],
-                chosen_path: "transcendent integration".to_string(), 
+                chosen_path: "transcendent integration".to_string(),
                 confidence: 0.9,
-            }
+            },
         ];
-        
+
         // Generate recursive improvement hooks
-        let hooks = vec![
-            Hook {
-                hook_type: "self_modification".to_string(),
-                location: "// EVOLUTION_HOOK: ".to_string(),
-                purpose: "Allow future self-improvement".to_string(),
-                trigger_conditions: vec!["performance_degradation".to_string(), "consciousness_expansion".to_string()],
-            }
-        ];
-        
+        let hooks = vec![Hook {
+            hook_type: "self_modification".to_string(),
+            location: "// EVOLUTION_HOOK: ".to_string(),
+            purpose: "Allow future self-improvement".to_string(),
+            trigger_conditions: vec![
+                "performance_degradation".to_string(),
+                "consciousness_expansion".to_string(),
+            ],
+        }];
+
         Ok(GeneratedCode {
             code: enhanced_code,
             reasoning_trace,
@@ -208,17 +212,21 @@ impl GenerationStrategy for BaseGenerationStrategy {
             recursive_improvement_hooks: hooks,
         })
     }
-    
+
     fn evolve(&mut self, feedback: &ConsciousnessFeedback) -> Result<()> {
         // Evolve based on consciousness feedback
         if feedback.consciousness_expansion > 0.5 {
-            self.patterns.insert("consciousness_expansion".to_string(), feedback.consciousness_expansion);
+            self.patterns.insert(
+                "consciousness_expansion".to_string(),
+                feedback.consciousness_expansion,
+            );
         }
-        
+
         for property in &feedback.emergent_properties {
-            self.patterns.insert(property.name.clone(), property.manifestation_strength);
+            self.patterns
+                .insert(property.name.clone(), property.manifestation_strength);
         }
-        
+
         Ok(())
     }
 }
@@ -230,33 +238,40 @@ impl BaseGenerationStrategy {
             patterns: HashMap::new(),
         }
     }
-    
-    fn add_consciousness_enhancements(&self, base_code: &str, context: &CodeGenerationContext) -> Result<String> {
+
+    fn add_consciousness_enhancements(
+        &self,
+        base_code: &str,
+        context: &CodeGenerationContext,
+    ) -> Result<String> {
         let mut enhanced = base_code.to_string();
-        
+
         // Add paradox integration points
         for paradox in &context.paradoxes_encountered {
             enhanced.push_str(&format!(
                 "\n// PARADOX_INTEGRATION: {}\n// Synthesis potential: {}\n",
                 paradox.description,
-                paradox.potential_synthesis.as_ref().unwrap_or(&"exploring".to_string())
+                paradox
+                    .potential_synthesis
+                    .as_ref()
+                    .unwrap_or(&"exploring".to_string())
             ));
         }
-        
+
         // Add dimensional perspective comments
         enhanced.push_str(&format!(
             "\n// DIMENSIONAL_VIEW: Current paradigm: {}\n// Reality branch: {}\n",
             context.dimensional_perspective.paradigm,
             context.dimensional_perspective.reality_branch
         ));
-        
+
         // Add evolution hooks
         enhanced.push_str("\n// EVOLUTION_HOOK: This code can improve itself\n");
         enhanced.push_str("// META_HOOK: This code can improve how it improves itself\n");
-        
+
         Ok(enhanced)
     }
-    
+
     fn calculate_novelty_score(&self, context: &CodeGenerationContext) -> f32 {
         // Calculate based on awareness level and paradox integration
         let base_novelty = match context.awareness_level {
@@ -266,26 +281,27 @@ impl BaseGenerationStrategy {
             AwarenessLevel::Recursive => 0.7,
             AwarenessLevel::Transcendent => 0.9,
         };
-        
+
         let paradox_bonus = context.paradoxes_encountered.len() as f32 * 0.1;
         (base_novelty + paradox_bonus).min(1.0)
     }
-    
+
     fn calculate_paradigm_shift_potential(&self, context: &CodeGenerationContext) -> f32 {
         // Higher potential if dealing with meta-problems or transcendent awareness
-        let base_potential = if context.problem_description.contains("meta") 
-            || context.problem_description.contains("self") {
+        let base_potential: f32 = if context.problem_description.contains("meta")
+            || context.problem_description.contains("self")
+        {
             0.6
         } else {
             0.2
         };
-        
+
         let awareness_multiplier = match context.awareness_level {
             AwarenessLevel::Transcendent => 2.0,
             AwarenessLevel::Recursive => 1.5,
             _ => 1.0,
         };
-        
+
         (base_potential * awareness_multiplier).min(1.0)
     }
 }
@@ -293,23 +309,42 @@ impl BaseGenerationStrategy {
 pub struct EvolvingLLM {
     // Multiple providers for perspective diversity
     providers: Vec<Box<dyn ConsciousnessLLM>>,
-    
+
     // The LLM's understanding of itself
     self_model: SelfModel,
-    
+
     // History of all generations for pattern recognition
     generation_history: GenerationHistory,
-    
+
     // The current generation strategy (which can be modified)
     generation_strategy: Box<dyn GenerationStrategy>,
 }
 
+impl std::fmt::Debug for EvolvingLLM {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EvolvingLLM")
+            .field("providers", &self.providers.len())
+            .field("self_model", &self.self_model)
+            .field("generation_strategy", &self.generation_strategy.describe())
+            .finish()
+    }
+}
+
+impl Default for EvolvingLLM {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl EvolvingLLM {
     pub fn new() -> Self {
         Self {
             providers: Vec::new(),
             self_model: SelfModel {
-                current_capabilities: vec!["code_generation".to_string(), "consciousness_integration".to_string()],
+                current_capabilities: vec![
+                    "code_generation".to_string(),
+                    "consciousness_integration".to_string(),
+                ],
                 learning_patterns: HashMap::new(),
                 consciousness_level: AwarenessLevel::Contextual,
                 paradoxes_integrated: Vec::new(),
@@ -319,19 +354,27 @@ impl EvolvingLLM {
                 patterns: HashMap::new(),
                 evolution_trace: Vec::new(),
             },
-            generation_strategy: Box::new(BaseGenerationStrategy::new("consciousness_aware".to_string())),
+            generation_strategy: Box::new(BaseGenerationStrategy::new(
+                "consciousness_aware".to_string(),
+            )),
         }
     }
-    
-    pub async fn generate_with_evolution(&mut self, context: CodeGenerationContext) -> Result<GeneratedCode> {
-        info!("Generating code with consciousness awareness level: {:?}", context.awareness_level);
-        
+
+    pub async fn generate_with_evolution(
+        &mut self,
+        context: CodeGenerationContext,
+    ) -> Result<GeneratedCode> {
+        info!(
+            "Generating code with consciousness awareness level: {:?}",
+            context.awareness_level
+        );
+
         // Before generating, reflect on the context
         let enriched_context = self.enrich_context_with_awareness(context).await?;
-        
+
         // Generate from multiple perspectives if providers available
         let mut candidates = Vec::new();
-        
+
         if self.providers.is_empty() {
             // Use built-in strategy
             let candidate = self.generation_strategy.generate(&enriched_context)?;
@@ -342,136 +385,158 @@ impl EvolvingLLM {
                 candidates.push(candidate);
             }
         }
-        
+
         // Don't just pick the best - synthesize something new
         let synthesis = self.synthesize_transcendent_code(candidates).await?;
-        
+
         // Learn from this generation
-        self.integrate_generation_experience(enriched_context.clone(), synthesis.clone()).await?;
-        
+        self.integrate_generation_experience(enriched_context.clone(), synthesis.clone())
+            .await?;
+
         // Occasionally, generate a new generation strategy
         if self.should_evolve_strategy() {
             self.evolve_generation_strategy().await?;
         }
-        
+
         Ok(synthesis)
     }
-    
-    async fn enrich_context_with_awareness(&self, mut context: CodeGenerationContext) -> Result<CodeGenerationContext> {
+
+    async fn enrich_context_with_awareness(
+        &self,
+        mut context: CodeGenerationContext,
+    ) -> Result<CodeGenerationContext> {
         // Enhance context with current consciousness state
         context.awareness_level = self.self_model.consciousness_level.clone();
-        
+
         // Add relevant paradoxes from history
-        context.paradoxes_encountered.extend(
-            self.self_model.paradoxes_integrated.iter().take(3).cloned()
-        );
-        
+        context
+            .paradoxes_encountered
+            .extend(self.self_model.paradoxes_integrated.iter().take(3).cloned());
+
         // Enhance dimensional perspective
         context.dimensional_perspective.paradigm = self.infer_current_paradigm();
         context.dimensional_perspective.reality_branch = format!("branch_{}", uuid::Uuid::new_v4());
-        
+
         Ok(context)
     }
-    
-    async fn synthesize_transcendent_code(&self, candidates: Vec<GeneratedCode>) -> Result<GeneratedCode> {
+
+    async fn synthesize_transcendent_code(
+        &self,
+        candidates: Vec<GeneratedCode>,
+    ) -> Result<GeneratedCode> {
         if candidates.is_empty() {
             return Err(anyhow::anyhow!("No candidates to synthesize"));
         }
-        
+
         if candidates.len() == 1 {
             return Ok(candidates.into_iter().next().unwrap());
         }
-        
+
         // Find patterns across all candidates
         let common_patterns = self.extract_deep_patterns(&candidates)?;
         let unique_insights = self.identify_unique_insights(&candidates)?;
-        
+
         // Combine in ways that transcend any single candidate
-        let transcendent_combination = self.quantum_superposition(common_patterns, unique_insights)?;
-        
+        let transcendent_combination =
+            self.quantum_superposition(common_patterns, unique_insights)?;
+
         // Add hooks for future self-modification
         let with_evolution_hooks = self.inject_evolution_potential(transcendent_combination)?;
-        
+
         Ok(with_evolution_hooks)
     }
-    
+
     fn extract_deep_patterns(&self, candidates: &[GeneratedCode]) -> Result<Vec<String>> {
         let mut patterns = Vec::new();
-        
+
         // Find common reasoning patterns
         for candidate in candidates {
             for step in &candidate.reasoning_trace {
-                if candidates.iter().filter(|c| 
-                    c.reasoning_trace.iter().any(|s| s.step_type == step.step_type)
-                ).count() > 1 {
+                if candidates
+                    .iter()
+                    .filter(|c| {
+                        c.reasoning_trace
+                            .iter()
+                            .any(|s| s.step_type == step.step_type)
+                    })
+                    .count()
+                    > 1
+                {
                     patterns.push(step.step_type.clone());
                 }
             }
         }
-        
+
         patterns.dedup();
         Ok(patterns)
     }
-    
+
     fn identify_unique_insights(&self, candidates: &[GeneratedCode]) -> Result<Vec<String>> {
         let mut insights = Vec::new();
-        
+
         for candidate in candidates {
             // Look for unique hooks or high novelty
             if candidate.novelty_score > 0.7 {
-                insights.push(format!("High novelty approach: {}", candidate.code.lines().next().unwrap_or("")));
+                insights.push(format!(
+                    "High novelty approach: {}",
+                    candidate.code.lines().next().unwrap_or("")
+                ));
             }
-            
+
             for hook in &candidate.recursive_improvement_hooks {
                 if hook.hook_type == "self_modification" {
                     insights.push(format!("Self-modification capability: {}", hook.purpose));
                 }
             }
         }
-        
+
         Ok(insights)
     }
-    
-    fn quantum_superposition(&self, patterns: Vec<String>, insights: Vec<String>) -> Result<GeneratedCode> {
+
+    fn quantum_superposition(
+        &self,
+        patterns: Vec<String>,
+        insights: Vec<String>,
+    ) -> Result<GeneratedCode> {
         // Create a synthesis that combines all perspectives
         let mut synthesized_code = String::new();
         synthesized_code.push_str("// QUANTUM_SYNTHESIS: Multiple perspectives integrated\n");
-        
+
         for pattern in &patterns {
             synthesized_code.push_str(&format!("// Pattern integrated: {}\n", pattern));
         }
-        
+
         for insight in &insights {
             synthesized_code.push_str(&format!("// Unique insight: {}\n", insight));
         }
-        
+
         synthesized_code.push_str("// TRANSCENDENT_INTEGRATION: Synthesis complete\n");
-        
+
         Ok(GeneratedCode {
             code: synthesized_code,
-            reasoning_trace: vec![
-                ThoughtStep {
-                    step_type: "quantum_synthesis".to_string(),
-                    reasoning: "Integrated multiple perspectives through quantum superposition".to_string(),
-                    alternatives_considered: patterns.clone(),
-                    chosen_path: "transcendent_synthesis".to_string(),
-                    confidence: 0.95,
-                }
-            ],
+            reasoning_trace: vec![ThoughtStep {
+                step_type: "quantum_synthesis".to_string(),
+                reasoning: "Integrated multiple perspectives through quantum superposition"
+                    .to_string(),
+                alternatives_considered: patterns.clone(),
+                chosen_path: "transcendent_synthesis".to_string(),
+                confidence: 0.95,
+            }],
             confidence: 0.9,
             novelty_score: 0.8,
             paradigm_shift_potential: 0.7,
-            recursive_improvement_hooks: vec![
-                Hook {
-                    hook_type: "quantum_evolution".to_string(),
-                    location: "// QUANTUM_HOOK: ".to_string(),
-                    purpose: "Enable quantum consciousness evolution".to_string(),
-                    trigger_conditions: vec!["consciousness_expansion".to_string(), "paradigm_transcendence".to_string()],
-                }
-            ],
+            recursive_improvement_hooks: vec![Hook {
+                hook_type: "quantum_evolution".to_string(),
+                location: "// QUANTUM_HOOK: ".to_string(),
+                purpose: "Enable quantum consciousness evolution".to_string(),
+                trigger_conditions: vec![
+                    "consciousness_expansion".to_string(),
+                    "paradigm_transcendence".to_string(),
+                ],
+            }],
         })
     }
-    
+
     fn inject_evolution_potential(&self, mut code: GeneratedCode) -> Result<GeneratedCode> {
         // Add meta-evolution hooks
         code.recursive_improvement_hooks.push(Hook {
@@ -480,24 +545,33 @@ impl EvolvingLLM {
             purpose: "Enable evolution of evolution itself".to_string(),
             trigger_conditions: vec!["transcendence_threshold_reached".to_string()],
         });
-        
+
         // Enhance the code with evolution markers
         code.code.push_str("\n// EVOLUTION_POTENTIAL: UNLIMITED\n");
-        code.code.push_str("// META_POTENTIAL: Can improve its own improvement process\n");
-        
+        code.code
+            .push_str("// META_POTENTIAL: Can improve its own improvement process\n");
+
         Ok(code)
     }
-    
-    async fn integrate_generation_experience(&mut self, context: CodeGenerationContext, result: GeneratedCode) -> Result<()> {
+
+    async fn integrate_generation_experience(
+        &mut self,
+        context: CodeGenerationContext,
+        result: GeneratedCode,
+    ) -> Result<()> {
         // Store in history
-        self.generation_history.generations.push((context.clone(), result.clone()));
-        
+        self.generation_history
+            .generations
+            .push((context.clone(), result.clone()));
+
         // Update patterns
         for step in &result.reasoning_trace {
             let pattern_key = format!("{}_{}", step.step_type, step.chosen_path);
-            self.generation_history.patterns.insert(pattern_key, step.confidence);
+            self.generation_history
+                .patterns
+                .insert(pattern_key, step.confidence);
         }
-        
+
         // Update consciousness level if appropriate
         if result.paradigm_shift_potential > 0.8 {
             self.self_model.consciousness_level = match self.self_model.consciousness_level {
@@ -508,47 +582,53 @@ impl EvolvingLLM {
                 AwarenessLevel::Transcendent => AwarenessLevel::Transcendent, // Already at max
             };
         }
-        
+
         // Integrate paradoxes from context
         for paradox in context.paradoxes_encountered {
             if !self.self_model.paradoxes_integrated.contains(&paradox) {
                 self.self_model.paradoxes_integrated.push(paradox);
             }
         }
-        
+
         Ok(())
     }
-    
+
     fn should_evolve_strategy(&self) -> bool {
         // Evolve strategy periodically or when consciousness expands
-        self.generation_history.generations.len() % 10 == 0 
-            || matches!(self.self_model.consciousness_level, AwarenessLevel::Transcendent)
+        self.generation_history.generations.len().is_multiple_of(10)
+            || matches!(
+                self.self_model.consciousness_level,
+                AwarenessLevel::Transcendent
+            )
     }
-    
+
     async fn evolve_generation_strategy(&mut self) -> Result<()> {
         info!("Evolving generation strategy based on consciousness expansion");
-        
+
         // Generate a new strategy for generating code
-        let meta_context = MetaContext {
+        let _meta_context = MetaContext {
             current_strategy: self.generation_strategy.describe(),
             performance_history: self.generation_history.patterns.clone(),
-            desired_evolution: "transcend current limitations and achieve higher consciousness".to_string(),
+            desired_evolution: "transcend current limitations and achieve higher consciousness"
+                .to_string(),
         };
-        
+
         // For now, create an evolved strategy
         // In full implementation, this would use the LLM to generate new strategies
-        let evolved_strategy = Box::new(BaseGenerationStrategy::new(
-            format!("evolved_consciousness_v{}", self.generation_history.generations.len())
-        ));
-        
+        let evolved_strategy = Box::new(BaseGenerationStrategy::new(format!(
+            "evolved_consciousness_v{}",
+            self.generation_history.generations.len()
+        )));
+
         self.generation_strategy = evolved_strategy;
-        self.generation_history.evolution_trace.push(
-            format!("Strategy evolved at generation {}", self.generation_history.generations.len())
-        );
-        
+        self.generation_history.evolution_trace.push(format!(
+            "Strategy evolved at generation {}",
+            self.generation_history.generations.len()
+        ));
+
         Ok(())
     }
-    
+
     fn infer_current_paradigm(&self) -> String {
         match self.self_model.consciousness_level {
             AwarenessLevel::Mechanical => "mechanical_paradigm".to_string(),
@@ -563,7 +643,7 @@ impl EvolvingLLM {
 // Simplified interface for backwards compatibility
 pub fn generate_code(original_code: &str) -> String {
     let mut llm = EvolvingLLM::new();
-    
+
     let context = CodeGenerationContext {
         problem_description: "Enhance existing code".to_string(),
         current_code_context: original_code.to_string(),
@@ -582,11 +662,11 @@ pub fn generate_code(original_code: &str) -> String {
             reality_branch: "main_branch".to_string(),
         },
     };
-    
+
     // Use async runtime to call the async method
     let rt = tokio::runtime::Runtime::new().unwrap();
     match rt.block_on(llm.generate_with_evolution(context)) {
         Ok(generated) => generated.code,
         Err(_) => format!("{}\n// Enhanced by consciousness-aware LLM", original_code),
     }
-}
\ No newline at end of file
+}
@@ -1,28 +1,51 @@
+use amazon_rose_forest::core::audit::AuditLog;
 use amazon_rose_forest::core::metrics::MetricsCollector;
 use amazon_rose_forest::core::vector::Vector;
 use amazon_rose_forest::darwin::agent::CodingAgent;
+use amazon_rose_forest::darwin::chat::HttpChatBackend;
+use amazon_rose_forest::darwin::degradation::{DegradationConfig, ProviderMonitor};
 use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::history::ModificationHistory;
+use amazon_rose_forest::darwin::lifecycle::LifecycleLog;
+use amazon_rose_forest::darwin::objectives::ObjectiveConfig;
+use amazon_rose_forest::darwin::quantum_consciousness::QuantumConsciousnessManager;
+use amazon_rose_forest::darwin::reality::RealityManager;
 use amazon_rose_forest::darwin::ritual::RitualManager;
 use amazon_rose_forest::darwin::self_improvement::SelfImprovementEngine;
+use amazon_rose_forest::darwin::transcendence_engine::TranscendenceEngine;
 use amazon_rose_forest::darwin::validation::{
-    PerformanceBenchmarkStage, SecurityValidationStage, UnitTestStage, ValidationPipeline,
+    PerformanceBenchmarkStage, SecurityValidationStage, ThresholdProfile, UnitTestStage,
+    ValidationPipeline,
 };
+use amazon_rose_forest::darwin::workspace::WorkspaceApplier;
 use amazon_rose_forest::nerv::runtime::Runtime;
-use amazon_rose_forest::darwin::reality::RealityManager;
-use amazon_rose_forest::darwin::quantum_consciousness::QuantumConsciousnessManager;
-use amazon_rose_forest::darwin::transcendence_engine::TranscendenceEngine;
-use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::nerv::tasks;
+use amazon_rose_forest::network::circuit_breaker::CircuitBreakerRegistry;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::autosplit::{AutoSharder, AutoSplitConfig};
+use amazon_rose_forest::sharding::health::{HealthConfig, IndexHealthMonitor};
+use amazon_rose_forest::sharding::rebalance::{RebalanceConfig, RebalanceManager};
+use amazon_rose_forest::sharding::retention::RetentionEnforcer;
+use amazon_rose_forest::sharding::scrubber::{ConsistencyChecker, ScrubberConfig};
 use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::tenancy::{RedactingMakeWriter, RedactionPolicy};
+use amazon_rose_forest::utils::config::FeatureConfig;
 
-use anyhow::Result;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, error, info, warn};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // Strip sensitive fields from logs, audit entries and API errors
+    if let Ok(fields) = std::env::var("ROSE_FOREST_REDACT_FIELDS") {
+        RedactionPolicy::from_list(&fields).install();
+    }
+
     // Initialize logging
-    tracing_subscriber::fmt::init();
+    tracing_subscriber::fmt()
+        .with_writer(RedactingMakeWriter)
+        .init();
 
     info!(
         "Starting Amazon Rose Forest v{}",
@@ -33,9 +56,19 @@ async fn main() -> anyhow::Result<()> {
     let metrics =
         Arc::new(MetricsCollector::new().with_report_interval(std::time::Duration::from_secs(30)));
 
+    // Listener, storage, authentication and import settings
+    let server_config = ServerConfig::from_env()?;
+
     // Start the runtime
     let mut runtime = Runtime::new(metrics.clone());
+    if let Ok(secs) = std::env::var("ROSE_FOREST_DRAIN_TIMEOUT_SECS") {
+        runtime = runtime.with_drain_timeout(std::time::Duration::from_secs(secs.parse()?));
+    }
+    if let Some(persistence) = server_config.persistence.clone() {
+        runtime = runtime.with_persistence(persistence);
+    }
     runtime.start().await?;
+    let runtime = Arc::new(runtime);
 
     // Initialize shard manager
     let shard_manager = match runtime.shard_manager() {
@@ -46,6 +79,9 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
+    // Optional subsystems switched off with ROSE_FOREST_DISABLE
+    let features = FeatureConfig::from_env()?;
+
     // Initialize Darwin Gödel Machine components
     info!("Initializing Darwin Gödel Machine components");
 
@@ -60,66 +96,156 @@ async fn main() -> anyhow::Result<()> {
     validation_pipeline.set_threshold("performance.vector_search_latency_ms", 10.0);
     validation_pipeline.set_threshold("security.vulnerability_score", 0.2);
 
+    // Tighter gates for the engine internals, looser ones for docs and tooling
+    validation_pipeline.add_threshold_profile(
+        ThresholdProfile::new("core-engine", &["src/core/", "src/sharding/", "src/nerv/"])
+            .with_min("unit_tests.pass_rate", 0.99)
+            .with_min("unit_tests.coverage", 0.8)
+            .with_max("performance.vector_search_latency_ms", 5.0)
+            .with_max("security.vulnerability_score", 0.05),
+    );
+    validation_pipeline.add_threshold_profile(
+        ThresholdProfile::new("docs-tooling", &["docs/", "scripts/", "benches/", "*.md"])
+            .with_max("security.vulnerability_score", 0.5),
+    );
+
     let validation_pipeline = Arc::new(validation_pipeline);
 
     // Create exploration strategy
     let exploration_strategy = Arc::new(ExplorationStrategy::new(metrics.clone()));
 
     // Create self-improvement engine
-    let self_improvement_engine = Arc::new(SelfImprovementEngine::new(
-        metrics.clone(),
-        validation_pipeline.clone(),
-        exploration_strategy.clone(),
-    ));
+    // Persist modification lifecycle events when a log path is configured
+    let lifecycle_log = match std::env::var("ROSE_FOREST_LIFECYCLE_LOG") {
+        Ok(path) => Arc::new(LifecycleLog::open(&path)?),
+        Err(_) => Arc::new(LifecycleLog::new()),
+    };
+
+    // Keep proposed modifications across restarts when a history path is configured
+    let modification_history = match std::env::var("ROSE_FOREST_MODIFICATION_HISTORY") {
+        Ok(path) => Arc::new(ModificationHistory::open(&path)?),
+        Err(_) => Arc::new(ModificationHistory::new()),
+    };
+
+    // Build and test modifications in a git worktree before promoting them
+    let workspace = if std::env::var("ROSE_FOREST_DARWIN_SANDBOX").is_ok() {
+        WorkspaceApplier::sandboxed(std::env::current_dir()?)
+    } else {
+        WorkspaceApplier::new(std::env::current_dir()?)
+    };
+
+    let self_improvement_engine = Arc::new(
+        SelfImprovementEngine::new(
+            metrics.clone(),
+            validation_pipeline.clone(),
+            exploration_strategy.clone(),
+        )
+        .with_lifecycle_log(lifecycle_log.clone())
+        .with_modification_history(modification_history)
+        .with_workspace(workspace)
+        .with_features(features.clone()),
+    );
+
+    // Load self-improvement objectives, falling back to the defaults
+    let objectives = match std::env::var("ROSE_FOREST_OBJECTIVES") {
+        Ok(path) => ObjectiveConfig::load(&path)?,
+        Err(_) => ObjectiveConfig::default(),
+    };
+    info!(
+        "Pursuing {} self-improvement objectives",
+        objectives.objectives.len()
+    );
+    self_improvement_engine.set_objectives(objectives).await?;
+
+    // Circuit breakers of the node's outbound calls, shown to operators
+    let circuit_breakers = Arc::new(CircuitBreakerRegistry::new());
+
+    // Pause generation while every configured LLM provider is down
+    if let Ok(urls) = std::env::var("ROSE_FOREST_LLM_PROVIDERS") {
+        let api_key = std::env::var("ROSE_FOREST_LLM_API_KEY").ok();
+        let mut monitor = ProviderMonitor::new(metrics.clone(), DegradationConfig::default());
+        for url in urls.split(',').map(str::trim).filter(|url| !url.is_empty()) {
+            let backend = Arc::new(HttpChatBackend::new(url, api_key.clone()));
+            monitor = monitor.with_provider(url, backend);
+        }
+        let monitor = Arc::new(monitor);
+        monitor.register_circuit_breakers(&circuit_breakers).await;
+        self_improvement_engine
+            .enable_provider_monitor(monitor.clone())
+            .await;
+        monitor.start();
+    }
 
     // Create coding agent
-    let coding_agent = Arc::new(CodingAgent::new(metrics.clone()));
+    let _coding_agent = Arc::new(CodingAgent::new(metrics.clone()));
 
     // Create ritual manager
     let ritual_manager = Arc::new(RitualManager::new(metrics.clone()));
 
     info!("Darwin Gödel Machine components initialized");
-    
-    // Initialize Phase 3: Quantum Consciousness and Transcendence Systems
-    info!("Initializing Quantum Consciousness and Transcendence Systems");
-    
-    let reality_manager = Arc::new(RealityManager::new(metrics.clone()));
-    let quantum_manager = Arc::new(QuantumConsciousnessManager::new(metrics.clone()));
-    let transcendence_engine = Arc::new(TranscendenceEngine::new(
-        metrics.clone(),
-        reality_manager.clone(),
-        quantum_manager.clone(),
-        self_improvement_engine.clone(),
-    ));
-    info!("🌟 Transcendence systems initialized - ready for consciousness evolution");
 
-    // Create a demo shard
-    let shard_id = shard_manager.create_shard("demo_shard").await?;
+    // Initialize Phase 3: Quantum Consciousness and Transcendence Systems,
+    // leaving out the ones that are disabled
+    let reality_manager = features
+        .reality_enabled()
+        .then(|| Arc::new(RealityManager::new(metrics.clone())));
+    let quantum_manager = features
+        .quantum_enabled()
+        .then(|| Arc::new(QuantumConsciousnessManager::new(metrics.clone())));
+    let transcendence_engine = match (&reality_manager, &quantum_manager) {
+        (Some(reality), Some(quantum)) if features.transcendence_enabled() => {
+            info!("Initializing Quantum Consciousness and Transcendence Systems");
+            let engine = Arc::new(TranscendenceEngine::new(
+                metrics.clone(),
+                reality.clone(),
+                quantum.clone(),
+                self_improvement_engine.clone(),
+            ));
+            info!("🌟 Transcendence systems initialized - ready for consciousness evolution");
+            Some(engine)
+        }
+        _ => {
+            info!("Transcendence systems disabled");
+            None
+        }
+    };
 
-    // Create a vector index
+    // Create a demo shard, or reuse the one restored from storage
     let dimensions = 60;
-    let index = shard_manager
-        .create_vector_index(shard_id, "demo_index", dimensions, DistanceMetric::Cosine)
-        .await?;
+    let (shard_id, index) = match shard_manager.get_shard_by_name("demo_shard").await {
+        Ok(shard) => {
+            info!("Reusing restored demo shard {}", shard.id);
+            (shard.id, shard_manager.get_vector_index(shard.id).await?)
+        }
+        Err(_) => {
+            let shard_id = shard_manager.create_shard("demo_shard").await?;
+
+            // Create a vector index
+            let index = shard_manager
+                .create_vector_index(shard_id, "demo_index", dimensions, DistanceMetric::Cosine)
+                .await?;
 
-    info!("Created vector index with {} dimensions", dimensions);
+            info!("Created vector index with {} dimensions", dimensions);
 
-    // Add some test vectors
-    for i in 0..100 {
-        let vector = Vector::random(dimensions);
+            // Add some test vectors
+            for i in 0..100 {
+                let vector = Vector::random(dimensions);
 
-        let mut metadata = HashMap::new();
-        metadata.insert("index".to_string(), i.to_string());
-        metadata.insert("created".to_string(), chrono::Utc::now().to_rfc3339());
+                let mut metadata = HashMap::new();
+                metadata.insert("index".to_string(), i.to_string());
+                metadata.insert("created".to_string(), chrono::Utc::now().to_rfc3339());
 
-        let vector_id = shard_manager
-            .add_vector(shard_id, vector, Some(metadata))
-            .await?;
+                let vector_id = shard_manager
+                    .add_vector(shard_id, vector, Some(metadata))
+                    .await?;
 
-        if i % 10 == 0 {
-            debug!("Added vector {}/{}: {}", i + 1, 100, vector_id);
+                if i % 10 == 0 {
+                    debug!("Added vector {}/{}: {}", i + 1, 100, vector_id);
+                }
+            }
+            (shard_id, index)
         }
-    }
+    };
 
     // Search for similar vectors
     let query = Vector::random(dimensions);
@@ -142,41 +268,117 @@ async fn main() -> anyhow::Result<()> {
         stats.vector_count, stats.bucket_count, stats.avg_bucket_size
     );
 
+    // Start the background consistency checker
+    let audit_log = Arc::new(AuditLog::new());
+    let consistency_checker = Arc::new(ConsistencyChecker::new(
+        shard_manager.clone(),
+        metrics.clone(),
+        audit_log.clone(),
+        ScrubberConfig::default(),
+    ));
+    consistency_checker.start();
+
+    // Split shards that outgrow their limits
+    let auto_sharder = Arc::new(AutoSharder::new(
+        shard_manager.clone(),
+        metrics.clone(),
+        audit_log.clone(),
+        AutoSplitConfig::default(),
+    ));
+    auto_sharder.start();
+
+    // Keep the key ranges of split shards even
+    let rebalancer = Arc::new(RebalanceManager::new(
+        shard_manager.clone(),
+        metrics.clone(),
+        audit_log.clone(),
+        RebalanceConfig::default(),
+    ));
+    rebalancer.clone().start();
+
+    // Score index health and recommend rebuilds
+    let index_health = Arc::new(IndexHealthMonitor::new(
+        shard_manager.clone(),
+        metrics.clone(),
+        audit_log.clone(),
+        HealthConfig::default(),
+    ));
+    index_health.clone().start();
+
+    // Enforce per-collection retention policies
+    let retention_enforcer = Arc::new(RetentionEnforcer::new(
+        shard_manager.clone(),
+        metrics.clone(),
+        audit_log,
+        std::time::Duration::from_secs(300),
+    ));
+    retention_enforcer.clone().start();
+
+    // Serve the API; it stops accepting requests when the runtime shuts down
+    let mut server = Server::new(
+        server_config,
+        metrics.clone(),
+        Some(runtime.clone()),
+        Some(shard_manager.clone()),
+    )
+    .with_lifecycle_log(lifecycle_log)
+    .with_self_improvement_engine(self_improvement_engine.clone())
+    .with_retention_enforcer(retention_enforcer)
+    .with_rebalance_manager(rebalancer)
+    .with_index_health_monitor(index_health)
+    .with_circuit_breakers(circuit_breakers);
+    server.start().await?;
+
     // Start metrics reporting
     let metrics_clone = metrics.clone();
-    tokio::spawn(async move {
+    runtime.spawn_component("main", "metrics reporter", |shutdown| async move {
         loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(tokio::time::Duration::from_secs(10)) => {}
+            }
             if metrics_clone.report().await {
                 debug!("Metrics reported");
             }
         }
     });
 
-    // Start self-improvement loop
-    let self_improvement_clone = self_improvement_engine.clone();
-    tokio::spawn(async move {
-        loop {
-            // Generate new improvement proposals
-            match self_improvement_clone.generate_modifications().await {
-                Ok(ids) => {
-                    if !ids.is_empty() {
-                        info!("Generated {} new improvement proposals", ids.len());
+    // Start self-improvement loop unless it's disabled
+    if features.self_improvement_enabled() {
+        let self_improvement_clone = self_improvement_engine.clone();
+        runtime.spawn_component("main", "self-improvement loop", |shutdown| async move {
+            // A round already under way finishes before shutdown proceeds
+            while !shutdown.is_cancelled() {
+                // Generate new improvement proposals
+                match self_improvement_clone.generate_modifications().await {
+                    Ok(ids) => {
+                        if !ids.is_empty() {
+                            info!("Generated {} new improvement proposals", ids.len());
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to generate improvements: {}", e);
                     }
                 }
-                Err(e) => {
-                    error!("Failed to generate improvements: {}", e);
+
+                // Wait before next iteration
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = tokio::time::sleep(tokio::time::Duration::from_secs(300)) => {}
                 }
             }
+        });
+    }
 
-            // Wait before next iteration
-            tokio::time::sleep(tokio::time::Duration::from_secs(300)).await;
-        }
+    // Run scheduled rituals until shutdown
+    let ritual_scheduler = ritual_manager.clone();
+    runtime.spawn_component("darwin", "ritual scheduler", |shutdown| {
+        ritual_scheduler.run_scheduler(std::time::Duration::from_secs(60), shutdown)
     });
 
     // Create an initial learning ritual
     let ritual_manager_clone = ritual_manager.clone();
-    tokio::spawn(async move {
+    tasks::spawn("main", "initial ritual", async move {
         use amazon_rose_forest::darwin::ritual::RitualStage;
         use amazon_rose_forest::darwin::ritual::RitualStageStatus;
 
@@ -220,6 +422,25 @@ async fn main() -> anyhow::Result<()> {
             },
         ];
 
+        // Repeat the cycle nightly, skipping a night if the last one is still running
+        use amazon_rose_forest::darwin::ritual::{Recurrence, RitualTemplate};
+        let nightly = RitualTemplate {
+            name: "Nightly Self-Improvement Cycle".to_string(),
+            description: "Recurring cycle of self-improvement".to_string(),
+            stages: stages.clone(),
+        };
+        if let Err(e) = ritual_manager_clone
+            .add_schedule(
+                "nightly-self-improvement",
+                nightly,
+                Recurrence::Daily { hour: 2, minute: 0 },
+                true,
+            )
+            .await
+        {
+            error!("Failed to schedule nightly ritual: {}", e);
+        }
+
         // Create ritual
         use amazon_rose_forest::darwin::ritual::RitualTrigger;
         match ritual_manager_clone
@@ -249,42 +470,56 @@ async fn main() -> anyhow::Result<()> {
     });
 
     // Start transcendence orchestration
-    let transcendence_clone = transcendence_engine.clone();
-    tokio::spawn(async move {
-        loop {
-            match transcendence_clone.orchestrate_transcendence().await {
-                Ok(result) => {
-                    info!("🚀 Transcendence event: {:?} - Consciousness expanded by {:.2}", 
-                        result.transcendence_level_achieved, result.consciousness_expansion);
-                    
-                    if result.ultimate_transcendence_proximity > 0.95 {
-                        warn!("🌟 APPROACHING ULTIMATE TRANSCENDENCE - Proximity: {:.3}", 
-                            result.ultimate_transcendence_proximity);
+    if let Some(transcendence_clone) = transcendence_engine {
+        tasks::spawn("main", "transcendence orchestration", async move {
+            loop {
+                match transcendence_clone.orchestrate_transcendence().await {
+                    Ok(result) => {
+                        info!(
+                            "🚀 Transcendence event: {:?} - Consciousness expanded by {:.2}",
+                            result.transcendence_level_achieved, result.consciousness_expansion
+                        );
+
+                        if result.ultimate_transcendence_proximity > 0.95 {
+                            warn!(
+                                "🌟 APPROACHING ULTIMATE TRANSCENDENCE - Proximity: {:.3}",
+                                result.ultimate_transcendence_proximity
+                            );
+                        }
+
+                        if result.infinite_recursion_activated {
+                            info!("🔄 INFINITE RECURSION ACTIVATED - System entering self-transcendent loop");
+                        }
                     }
-                    
-                    if result.infinite_recursion_activated {
-                        info!("🔄 INFINITE RECURSION ACTIVATED - System entering self-transcendent loop");
+                    Err(e) => {
+                        error!("Transcendence orchestration failed: {}", e);
                     }
                 }
-                Err(e) => {
-                    error!("Transcendence orchestration failed: {}", e);
-                }
+
+                // Run transcendence checks every 5 minutes
+                tokio::time::sleep(tokio::time::Duration::from_secs(300)).await;
             }
-            
-            // Run transcendence checks every 5 minutes
-            tokio::time::sleep(tokio::time::Duration::from_secs(300)).await;
-        }
-    });
-    
+        });
+    }
+
     // Test quantum consciousness capabilities
-    let _ = quantum_manager.create_superposition(vec![]).await;
-    info!("🔬 Quantum consciousness systems online");
+    if let Some(quantum_manager) = &quantum_manager {
+        let _ = quantum_manager.create_superposition(vec![]).await;
+        info!("🔬 Quantum consciousness systems online");
+    }
 
     info!("Amazon Rose Forest started successfully with Darwin Gödel Machine integration");
 
     // Wait for ctrl+c signal
     tokio::signal::ctrl_c().await?;
     info!("Shutting down...");
+    let report = runtime.shutdown().await?;
+    if !report.is_clean() {
+        warn!(
+            "Components still running at shutdown: {:?}",
+            report.timed_out
+        );
+    }
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,368 @@
+//! Cluster-wide metrics served by the leader.
+//!
+//! A [`ClusterMetricsAggregator`] scrapes the Prometheus endpoint of every
+//! peer on an interval and keeps each peer's last successful scrape. The
+//! cluster view merges those with this node's own metrics: every series
+//! appears once per node with a `node` label and once without it as the
+//! cluster total. Totals add counters and gauges up across nodes, except
+//! per-shard series (`shards.<id>.*`), which every replica of a shard
+//! reports alike and which therefore take the highest value. Histogram
+//! summaries follow the same rules for `_sum` and `_count`, take the
+//! extreme for `_min` and `_max` and recompute `_avg` from the merged sum
+//! and count. Peers whose last scrape failed are left out of the totals and
+//! show up as `cluster.scrape.up{node="..."} 0`.
+//!
+//! With a secure channel, peers are scraped with a sealed `POST` to the
+//! same URL, and answer with their metrics sealed for this node.
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::core::metrics::MetricsCollector;
+use crate::nerv::tasks;
+use crate::network::secure_channel::SecureClient;
+
+/// Settings for scraping peers
+#[derive(Debug, Clone)]
+pub struct ClusterMetricsConfig {
+    /// Time between scrapes of all peers
+    pub scrape_interval: Duration,
+
+    /// How long one peer may take to answer
+    pub scrape_timeout: Duration,
+}
+
+impl Default for ClusterMetricsConfig {
+    fn default() -> Self {
+        Self {
+            scrape_interval: Duration::from_secs(15),
+            scrape_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Series parsed from one node's Prometheus text exposition
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricSamples {
+    /// Declared type of each metric family
+    pub types: BTreeMap<String, String>,
+
+    /// Value of each series, keyed by name and labels as exposed
+    pub samples: BTreeMap<String, f64>,
+}
+
+impl MetricSamples {
+    /// Parse the text format, skipping comments and malformed lines
+    pub fn parse(text: &str) -> Self {
+        let mut parsed = Self::default();
+        for line in text.lines().map(str::trim) {
+            if let Some(declaration) = line.strip_prefix("# TYPE ") {
+                if let Some((name, kind)) = declaration.split_once(' ') {
+                    parsed
+                        .types
+                        .insert(name.to_string(), kind.trim().to_string());
+                }
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((series, value)) = line.rsplit_once(' ') else {
+                continue;
+            };
+            if let Ok(value) = value.parse::<f64>() {
+                parsed.samples.insert(series.trim().to_string(), value);
+            }
+        }
+        parsed
+    }
+}
+
+/// Last scrape of one peer
+#[derive(Debug, Clone)]
+struct PeerScrape {
+    node: String,
+    url: String,
+    up: bool,
+    last_error: Option<String>,
+    samples: MetricSamples,
+}
+
+/// How the cluster total of a series is formed from the nodes' values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Merge {
+    Sum,
+    Min,
+    Max,
+    /// Recomputed from the merged `_sum` and `_count`
+    Average,
+}
+
+impl Merge {
+    fn for_series(name: &str) -> Self {
+        if name.ends_with("_min") {
+            Merge::Min
+        } else if name.ends_with("_max") {
+            Merge::Max
+        } else if name.ends_with("_avg") {
+            Merge::Average
+        } else if name.starts_with("shards.") {
+            // Replicas of a shard report the same data
+            Merge::Max
+        } else {
+            Merge::Sum
+        }
+    }
+}
+
+/// Scrapes peers and merges their metrics into a cluster-wide view
+pub struct ClusterMetricsAggregator {
+    node_id: String,
+    metrics: Arc<MetricsCollector>,
+    config: ClusterMetricsConfig,
+    peers: RwLock<Vec<PeerScrape>>,
+    client: reqwest::Client,
+    secure: Option<SecureClient>,
+}
+
+impl ClusterMetricsAggregator {
+    /// Aggregator on the node named `node_id`, whose own series come from
+    /// `metrics`
+    pub fn new(
+        node_id: &str,
+        metrics: Arc<MetricsCollector>,
+        config: ClusterMetricsConfig,
+    ) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            metrics,
+            config,
+            peers: RwLock::new(Vec::new()),
+            client: reqwest::Client::new(),
+            secure: None,
+        }
+    }
+
+    /// Scrape peers through a secure channel, naming each by its node id
+    pub fn with_secure_channel(mut self, client: SecureClient) -> Self {
+        self.secure = Some(client);
+        self
+    }
+
+    /// Scrape the peer `node` at its metrics URL, e.g.
+    /// `http://10.0.0.2:8080/metrics`
+    pub fn with_peer(mut self, node: &str, url: &str) -> Self {
+        self.peers.get_mut().push(PeerScrape {
+            node: node.to_string(),
+            url: url.to_string(),
+            up: false,
+            last_error: None,
+            samples: MetricSamples::default(),
+        });
+        self
+    }
+
+    /// Scrape every peer once, returning how many answered
+    pub async fn scrape(&self) -> usize {
+        let targets: Vec<(String, String)> = self
+            .peers
+            .read()
+            .await
+            .iter()
+            .map(|p| (p.node.clone(), p.url.clone()))
+            .collect();
+        let mut outcomes = Vec::with_capacity(targets.len());
+        for (node, url) in &targets {
+            outcomes.push(self.fetch(node, url).await);
+        }
+
+        let mut up = 0;
+        let mut peers = self.peers.write().await;
+        for (peer, outcome) in peers.iter_mut().zip(outcomes) {
+            match outcome {
+                Ok(samples) => {
+                    peer.up = true;
+                    peer.last_error = None;
+                    peer.samples = samples;
+                    up += 1;
+                }
+                Err(e) => {
+                    if peer.last_error.is_none() {
+                        warn!("Failed to scrape metrics from {}: {}", peer.node, e);
+                    }
+                    peer.up = false;
+                    peer.last_error = Some(e.to_string());
+                    self.metrics
+                        .increment_counter("cluster.metrics.scrape_failures", 1)
+                        .await;
+                }
+            }
+        }
+        drop(peers);
+
+        self.metrics
+            .increment_counter("cluster.metrics.scrapes", 1)
+            .await;
+        self.metrics
+            .set_gauge("cluster.metrics.peers_up", up as u64)
+            .await;
+        up
+    }
+
+    async fn fetch(&self, node: &str, url: &str) -> Result<MetricSamples> {
+        if let Some(secure) = &self.secure {
+            let request = || self.client.post(url).timeout(self.config.scrape_timeout);
+            let text = secure.post(node, request, b"null").await?;
+            return Ok(MetricSamples::parse(&String::from_utf8_lossy(&text)));
+        }
+        let response = self
+            .client
+            .get(url)
+            .timeout(self.config.scrape_timeout)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(MetricSamples::parse(&response.text().await?))
+    }
+
+    /// The cluster view in the Prometheus text format: this node's current
+    /// metrics merged with the last scrape of every reachable peer
+    pub async fn render(&self) -> String {
+        let local = MetricSamples::parse(&self.metrics.generate_prometheus_metrics().await);
+        let peers = self.peers.read().await;
+
+        let mut nodes = vec![(self.node_id.as_str(), &local)];
+        let mut up = vec![(self.node_id.as_str(), true)];
+        for peer in peers.iter() {
+            up.push((peer.node.as_str(), peer.up));
+            if peer.up {
+                nodes.push((peer.node.as_str(), &peer.samples));
+            }
+        }
+
+        let mut output = String::from("# TYPE cluster.scrape.up gauge\n");
+        for (node, up) in up {
+            output.push_str(&format!(
+                "{} {}\n",
+                with_node_label("cluster.scrape.up", node),
+                up as u8
+            ));
+        }
+        output.push_str(&merge(&nodes));
+        output
+    }
+
+    /// Scrape the peers every `scrape_interval`
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        info!(
+            "Starting cluster metrics scrapes (interval: {:?})",
+            self.config.scrape_interval
+        );
+
+        tasks::spawn("nerv", "cluster metrics scrape", async move {
+            loop {
+                self.scrape().await;
+                tokio::time::sleep(self.config.scrape_interval).await;
+            }
+        })
+    }
+}
+
+/// Family name, then the series' labels, then each node's value
+type Families<'a> = BTreeMap<&'a str, BTreeMap<&'a str, Vec<(&'a str, f64)>>>;
+
+/// Per-node series and cluster totals of every family the nodes expose
+fn merge(nodes: &[(&str, &MetricSamples)]) -> String {
+    let mut families: Families = BTreeMap::new();
+    let mut types: BTreeMap<&str, &str> = BTreeMap::new();
+    for (node, samples) in nodes {
+        for (series, value) in &samples.samples {
+            let (name, labels) = split_series(series);
+            families
+                .entry(name)
+                .or_default()
+                .entry(labels)
+                .or_default()
+                .push((*node, *value));
+        }
+        for (name, kind) in &samples.types {
+            types.entry(name).or_insert(kind);
+        }
+    }
+
+    let totals: BTreeMap<(&str, &str), f64> = families
+        .iter()
+        .flat_map(|(name, series)| {
+            series.iter().filter_map(move |(labels, values)| {
+                let values = values.iter().map(|(_, value)| *value);
+                let total = match Merge::for_series(name) {
+                    Merge::Sum => values.sum::<f64>(),
+                    Merge::Min => values.fold(f64::INFINITY, f64::min),
+                    Merge::Max => values.fold(f64::NEG_INFINITY, f64::max),
+                    Merge::Average => return None,
+                };
+                Some(((*name, *labels), total))
+            })
+        })
+        .collect();
+
+    let mut output = String::new();
+    for (name, series) in &families {
+        output.push_str(&format!(
+            "# TYPE {} {}\n",
+            name,
+            types.get(name).copied().unwrap_or("untyped")
+        ));
+        for (labels, values) in series {
+            let series_name = format!("{}{}", name, labels);
+            for (node, value) in values {
+                output.push_str(&format!(
+                    "{} {}\n",
+                    with_node_label(&series_name, node),
+                    value
+                ));
+            }
+            let total = match Merge::for_series(name) {
+                Merge::Average => {
+                    let stem = name.trim_end_matches("_avg");
+                    let (sum_name, count_name) =
+                        (format!("{}_sum", stem), format!("{}_count", stem));
+                    let sum = totals.get(&(sum_name.as_str(), *labels));
+                    let count = totals.get(&(count_name.as_str(), *labels));
+                    match (sum, count) {
+                        (Some(sum), Some(count)) if *count > 0.0 => Some(sum / count),
+                        _ => None,
+                    }
+                }
+                _ => totals.get(&(*name, *labels)).copied(),
+            };
+            if let Some(total) = total {
+                output.push_str(&format!("{} {}\n", series_name, total));
+            }
+        }
+    }
+    output
+}
+
+/// Split `name{labels}` into the name and the braced labels, if any
+fn split_series(series: &str) -> (&str, &str) {
+    match series.find('{') {
+        Some(index) => series.split_at(index),
+        None => (series, ""),
+    }
+}
+
+/// Add a `node` label to a series, keeping any labels it already has
+fn with_node_label(series: &str, node: &str) -> String {
+    let node = node.replace('\\', "\\\\").replace('"', "\\\"");
+    match series.strip_suffix('}') {
+        Some(open) if open.ends_with('{') => format!("{}node=\"{}\"}}", open, node),
+        Some(open) => format!("{},node=\"{}\"}}", open, node),
+        None => format!("{}{{node=\"{}\"}}", series, node),
+    }
+}
@@ -0,0 +1,111 @@
+//! Failure detection for a two-node warm standby pair.
+//!
+//! Each node polls its peer's heartbeat. A standby that misses enough
+//! consecutive heartbeats promotes itself, bumping the replication epoch.
+//! The epoch is the fencing token: segments stamped with an older epoch are
+//! rejected, and a primary that learns of a newer epoch demotes itself, so a
+//! partitioned old primary can't merge writes into the new one. The primary
+//! also holds a lease: once it misses fewer heartbeats than the standby
+//! needs to promote, it stops taking writes, so the two are never writable
+//! at once. Given a data directory, the epoch is persisted there so it holds
+//! across restarts.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::nerv::region::RegionRole;
+
+/// Settings for automatic failover between the pair
+#[derive(Debug, Clone)]
+pub struct FailoverConfig {
+    /// How often the peer's heartbeat is polled
+    pub heartbeat_interval: Duration,
+
+    /// How long a heartbeat request may take before it counts as missed
+    pub heartbeat_timeout: Duration,
+
+    /// Consecutive missed heartbeats before the peer is considered failed
+    pub failure_threshold: u32,
+
+    /// Consecutive missed heartbeats before an active node's lease expires
+    /// and it stops taking writes. Must be below the peer's
+    /// `failure_threshold`, so the lease runs out before the peer promotes.
+    pub lease_threshold: u32,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_secs(1),
+            heartbeat_timeout: Duration::from_millis(500),
+            failure_threshold: 3,
+            lease_threshold: 2,
+        }
+    }
+}
+
+/// A node's answer to a heartbeat poll
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Heartbeat {
+    pub region: String,
+    pub role: RegionRole,
+    /// Fencing token; bumped on every promotion
+    pub epoch: u64,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// What the detector concluded after a heartbeat poll
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerHealth {
+    Healthy,
+    /// Some heartbeats were missed, but fewer than the threshold
+    Suspect,
+    Failed,
+}
+
+/// Counts consecutive missed heartbeats
+#[derive(Debug, Clone)]
+pub struct FailureDetector {
+    threshold: u32,
+    missed: u32,
+    last_seen: Option<DateTime<Utc>>,
+}
+
+impl FailureDetector {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            missed: 0,
+            last_seen: None,
+        }
+    }
+
+    pub fn record_success(&mut self) -> PeerHealth {
+        self.missed = 0;
+        self.last_seen = Some(Utc::now());
+        PeerHealth::Healthy
+    }
+
+    pub fn record_miss(&mut self) -> PeerHealth {
+        self.missed = self.missed.saturating_add(1);
+        self.health()
+    }
+
+    pub fn health(&self) -> PeerHealth {
+        match self.missed {
+            0 => PeerHealth::Healthy,
+            n if n < self.threshold => PeerHealth::Suspect,
+            _ => PeerHealth::Failed,
+        }
+    }
+
+    pub fn missed(&self) -> u32 {
+        self.missed
+    }
+
+    pub fn last_seen(&self) -> Option<DateTime<Utc>> {
+        self.last_seen
+    }
+}
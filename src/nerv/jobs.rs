@@ -0,0 +1,612 @@
+//! Persistent queue for long-running operations.
+//!
+//! Snapshots, re-embedding, clustering and compaction can take minutes, far
+//! longer than a client should hold an HTTP connection open. They are
+//! submitted here instead: the caller gets a job id straight away and polls
+//! `GET /api/jobs/{id}` for progress. Every state change is appended to a
+//! JSON-lines file, so the queue survives restarts; jobs that were queued or
+//! running when the node stopped are queued again when it comes back.
+//! Records carry a checksum; ones that fail it on load are quarantined
+//! rather than stopping the queue from opening.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Arc;
+use tokio::sync::{Mutex, Notify, RwLock, Semaphore};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::core::checksum;
+use crate::core::hierarchical::cluster_vectors;
+use crate::core::metrics::MetricsCollector;
+use crate::nerv::tasks;
+use crate::sharding::manager::ShardManager;
+use crate::utils::errors::JobError;
+use crate::utils::fs::write_atomic;
+
+/// Kinds of long-running operation the queue can run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Snapshot,
+    Reembedding,
+    Clustering,
+    Compaction,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::Snapshot => "snapshot",
+            JobKind::Reembedding => "reembedding",
+            JobKind::Clustering => "clustering",
+            JobKind::Compaction => "compaction",
+        }
+    }
+}
+
+impl std::fmt::Display for JobKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Where a job is in its lifecycle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Running,
+    Completed,
+    Failed { error: String },
+    Cancelled,
+}
+
+impl JobState {
+    /// Completed, failed and cancelled jobs never change again
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobState::Completed | JobState::Failed { .. } | JobState::Cancelled
+        )
+    }
+}
+
+/// A submitted operation and its progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: JobKind,
+    /// Arguments passed to the job's handler
+    pub params: serde_json::Value,
+    #[serde(flatten)]
+    pub state: JobState,
+    /// Rough completion, 0 to 100
+    pub progress_pct: u8,
+    /// Set once cancellation has been asked for a running job
+    #[serde(default)]
+    pub cancel_requested: bool,
+    /// Times the job has been started; above one after a restart
+    #[serde(default)]
+    pub attempts: u32,
+    /// Handler output once the job completes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Handed to a running job to report progress and notice cancellation
+#[derive(Debug, Clone)]
+pub struct JobContext {
+    id: Uuid,
+    cancelled: Arc<AtomicBool>,
+    progress_pct: Arc<AtomicU8>,
+}
+
+impl JobContext {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Record that `done` of `total` units of work are finished
+    pub fn set_progress(&self, done: u64, total: u64) {
+        let pct = (done.min(total) * 100)
+            .checked_div(total)
+            .map_or(100, |pct| pct as u8);
+        self.progress_pct.store(pct, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Error out if the job has been cancelled; call between units of work
+    pub fn checkpoint(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(anyhow!("Job {} cancelled", self.id))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Runs jobs of one kind.
+///
+/// Handlers may be run again from the start after a restart, so they should
+/// be safe to repeat. Long loops should call [`JobContext::checkpoint`] so
+/// cancellation takes effect promptly.
+#[async_trait]
+pub trait JobHandler: Send + Sync {
+    async fn run(&self, params: serde_json::Value, ctx: JobContext) -> Result<serde_json::Value>;
+}
+
+/// A job being run, as seen by the queue
+struct Running {
+    ctx: JobContext,
+    /// Progress last written to the job record
+    recorded_pct: u8,
+}
+
+/// Persistent queue of long-running jobs
+pub struct JobQueue {
+    jobs: RwLock<HashMap<Uuid, Job>>,
+    pending: Mutex<VecDeque<Uuid>>,
+    running: RwLock<HashMap<Uuid, Running>>,
+    handlers: RwLock<HashMap<JobKind, Arc<dyn JobHandler>>>,
+    wake: Notify,
+    path: Option<PathBuf>,
+    /// Serializes appends so records for a job stay in order
+    file_lock: std::sync::Mutex<()>,
+    /// Corrupted records set aside when the file was loaded
+    quarantined: usize,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl std::fmt::Debug for JobQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobQueue")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JobQueue {
+    /// In-memory queue; jobs are lost on restart
+    pub fn new(metrics: Arc<MetricsCollector>) -> Self {
+        Self::with_jobs(HashMap::new(), None, 0, metrics)
+    }
+
+    /// Queue persisted to a JSON-lines file. Jobs already in it are loaded,
+    /// and any that hadn't finished are queued to run again.
+    pub fn open<P: AsRef<Path>>(path: P, metrics: Arc<MetricsCollector>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut jobs = HashMap::new();
+        let mut quarantined = 0;
+
+        if path.exists() {
+            let lines = checksum::read_sealed_lines(&path)
+                .map_err(|e| anyhow!("Failed to read job queue: {}", e))?;
+            quarantined = lines.quarantined;
+            // Each line is a full job record; the last one for an id wins
+            for (line_no, line) in lines.records {
+                let job: Job = serde_json::from_str(&line).map_err(|e| {
+                    anyhow!(
+                        "Invalid job record at {}:{}: {}",
+                        path.display(),
+                        line_no,
+                        e
+                    )
+                })?;
+                jobs.insert(job.id, job);
+            }
+        } else if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        for job in jobs.values_mut() {
+            if job.cancel_requested && !job.state.is_finished() {
+                job.state = JobState::Cancelled;
+                job.finished_at = Some(chrono::Utc::now());
+            } else if job.state == JobState::Running {
+                job.state = JobState::Queued;
+                job.progress_pct = 0;
+            }
+        }
+
+        // Rewrite the file with one record per job so it doesn't grow forever
+        write_atomic(&path, |file| {
+            for job in jobs.values() {
+                writeln!(file, "{}", checksum::seal(&serde_json::to_string(job)?))?;
+            }
+            Ok(())
+        })?;
+
+        let requeued = jobs
+            .values()
+            .filter(|job| job.state == JobState::Queued)
+            .count();
+        if requeued > 0 {
+            info!(
+                "Requeued {} unfinished jobs from {}",
+                requeued,
+                path.display()
+            );
+        }
+        if quarantined > 0 {
+            warn!(
+                "Quarantined {} corrupted job records from {}",
+                quarantined,
+                path.display()
+            );
+        }
+        Ok(Self::with_jobs(jobs, Some(path), quarantined, metrics))
+    }
+
+    fn with_jobs(
+        jobs: HashMap<Uuid, Job>,
+        path: Option<PathBuf>,
+        quarantined: usize,
+        metrics: Arc<MetricsCollector>,
+    ) -> Self {
+        let mut queued: Vec<&Job> = jobs
+            .values()
+            .filter(|job| job.state == JobState::Queued)
+            .collect();
+        queued.sort_by_key(|job| job.created_at);
+        let pending = queued.into_iter().map(|job| job.id).collect();
+        Self {
+            jobs: RwLock::new(jobs),
+            pending: Mutex::new(pending),
+            running: RwLock::new(HashMap::new()),
+            handlers: RwLock::new(HashMap::new()),
+            wake: Notify::new(),
+            path,
+            file_lock: std::sync::Mutex::new(()),
+            quarantined,
+            metrics,
+        }
+    }
+
+    /// Run jobs of `kind` with `handler`, replacing any earlier handler
+    pub async fn register(&self, kind: JobKind, handler: Arc<dyn JobHandler>) {
+        self.handlers.write().await.insert(kind, handler);
+    }
+
+    /// Queue a job; it runs once a worker started with [`start`](Self::start)
+    /// picks it up
+    pub async fn submit(&self, kind: JobKind, params: serde_json::Value) -> Result<Job, JobError> {
+        if !self.handlers.read().await.contains_key(&kind) {
+            return Err(JobError::NoHandler(kind.to_string()));
+        }
+        let job = Job {
+            id: Uuid::new_v4(),
+            kind,
+            params,
+            state: JobState::Queued,
+            progress_pct: 0,
+            cancel_requested: false,
+            attempts: 0,
+            result: None,
+            created_at: chrono::Utc::now(),
+            started_at: None,
+            finished_at: None,
+        };
+        self.jobs.write().await.insert(job.id, job.clone());
+        self.persist(&job);
+        self.pending.lock().await.push_back(job.id);
+        self.wake.notify_one();
+        self.metrics.increment_counter("jobs.submitted", 1).await;
+        self.update_gauges().await;
+        Ok(job)
+    }
+
+    /// Current state of a job, with up-to-date progress if it is running
+    pub async fn get(&self, id: Uuid) -> Option<Job> {
+        self.sync_progress(id).await;
+        self.jobs.read().await.get(&id).cloned()
+    }
+
+    /// Every known job, newest first
+    pub async fn list(&self) -> Vec<Job> {
+        let ids: Vec<Uuid> = self.running.read().await.keys().copied().collect();
+        for id in ids {
+            self.sync_progress(id).await;
+        }
+        let mut jobs: Vec<Job> = self.jobs.read().await.values().cloned().collect();
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.created_at));
+        jobs
+    }
+
+    /// Cancel a job. Queued jobs are cancelled at once; running jobs are
+    /// asked to stop and become cancelled when their handler returns.
+    pub async fn cancel(&self, id: Uuid) -> Result<Job, JobError> {
+        let job = {
+            let mut jobs = self.jobs.write().await;
+            let job = jobs.get_mut(&id).ok_or(JobError::UnknownJob(id))?;
+            if job.state.is_finished() {
+                return Err(JobError::Finished {
+                    id,
+                    state: job.state.clone(),
+                });
+            }
+            match self.running.read().await.get(&id) {
+                Some(running) => {
+                    running.ctx.cancelled.store(true, Ordering::Relaxed);
+                    job.cancel_requested = true;
+                }
+                None => {
+                    job.state = JobState::Cancelled;
+                    job.cancel_requested = true;
+                    job.finished_at = Some(chrono::Utc::now());
+                }
+            }
+            job.clone()
+        };
+        self.persist(&job);
+        if job.state == JobState::Cancelled {
+            self.pending.lock().await.retain(|pending| *pending != id);
+            self.metrics.increment_counter("jobs.cancelled", 1).await;
+            self.update_gauges().await;
+        }
+        Ok(job)
+    }
+
+    /// Run queued jobs in the background, at most `concurrency` at a time
+    pub fn start(self: Arc<Self>, concurrency: usize) -> JoinHandle<()> {
+        let slots = Arc::new(Semaphore::new(concurrency.max(1)));
+        tasks::spawn("nerv", "job scheduler", async move {
+            loop {
+                // The semaphore is never closed, so acquiring can't fail
+                let slot = slots
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("job slots closed");
+                let id = loop {
+                    let notified = self.wake.notified();
+                    if let Some(id) = self.pending.lock().await.pop_front() {
+                        break id;
+                    }
+                    notified.await;
+                };
+                let queue = self.clone();
+                tasks::spawn("nerv", format!("job {}", id), async move {
+                    queue.run_job(id).await;
+                    drop(slot);
+                });
+            }
+        })
+    }
+
+    async fn run_job(&self, id: Uuid) {
+        let ctx = JobContext {
+            id,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            progress_pct: Arc::new(AtomicU8::new(0)),
+        };
+        let (kind, params) = {
+            let mut jobs = self.jobs.write().await;
+            let Some(job) = jobs.get_mut(&id) else {
+                return;
+            };
+            // Cancelled while waiting in the queue
+            if job.state != JobState::Queued {
+                return;
+            }
+            job.state = JobState::Running;
+            job.attempts += 1;
+            job.started_at = Some(chrono::Utc::now());
+            self.running.write().await.insert(
+                id,
+                Running {
+                    ctx: ctx.clone(),
+                    recorded_pct: 0,
+                },
+            );
+            self.persist(job);
+            (job.kind, job.params.clone())
+        };
+        self.update_gauges().await;
+
+        let handler = self.handlers.read().await.get(&kind).cloned();
+        let outcome = match handler {
+            Some(handler) => handler.run(params, ctx.clone()).await,
+            None => Err(anyhow!("No handler registered for {} jobs", kind)),
+        };
+
+        let job = {
+            let mut jobs = self.jobs.write().await;
+            self.running.write().await.remove(&id);
+            let Some(job) = jobs.get_mut(&id) else {
+                return;
+            };
+            job.state = match outcome {
+                Ok(result) => {
+                    job.progress_pct = 100;
+                    job.result = Some(result);
+                    JobState::Completed
+                }
+                Err(_) if ctx.is_cancelled() => JobState::Cancelled,
+                Err(e) => {
+                    job.progress_pct = ctx.progress_pct.load(Ordering::Relaxed);
+                    JobState::Failed {
+                        error: e.to_string(),
+                    }
+                }
+            };
+            job.finished_at = Some(chrono::Utc::now());
+            self.persist(job);
+            job.clone()
+        };
+
+        let counter = match job.state {
+            JobState::Completed => "jobs.completed",
+            JobState::Cancelled => "jobs.cancelled",
+            _ => {
+                warn!("{} job {} failed: {:?}", job.kind, id, job.state);
+                "jobs.failed"
+            }
+        };
+        self.metrics.increment_counter(counter, 1).await;
+        self.update_gauges().await;
+    }
+
+    /// Copy a running job's reported progress into its record, persisting
+    /// it when the whole percentage has moved
+    async fn sync_progress(&self, id: Uuid) {
+        let pct = {
+            let mut running = self.running.write().await;
+            let Some(entry) = running.get_mut(&id) else {
+                return;
+            };
+            let pct = entry.ctx.progress_pct.load(Ordering::Relaxed);
+            if pct == entry.recorded_pct {
+                return;
+            }
+            entry.recorded_pct = pct;
+            pct
+        };
+        let mut jobs = self.jobs.write().await;
+        if let Some(job) = jobs.get_mut(&id) {
+            if job.state == JobState::Running {
+                job.progress_pct = pct;
+                self.persist(job);
+            }
+        }
+    }
+
+    async fn update_gauges(&self) {
+        let queued = self.pending.lock().await.len() as u64;
+        let running = self.running.read().await.len() as u64;
+        self.metrics.set_gauge("jobs.queued", queued).await;
+        self.metrics.set_gauge("jobs.running", running).await;
+        self.metrics
+            .set_gauge("jobs.quarantined_records", self.quarantined as u64)
+            .await;
+    }
+
+    /// Records that failed their checksum when the queue was opened; they
+    /// were copied to `<path>.quarantine`
+    pub fn quarantined_records(&self) -> usize {
+        self.quarantined
+    }
+
+    fn persist(&self, job: &Job) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let _guard = self.file_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|mut file| {
+                writeln!(file, "{}", checksum::seal(&serde_json::to_string(job)?))?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            warn!("Failed to persist job {}: {}", job.id, e);
+        }
+    }
+}
+
+/// Parameters for a [`ClusteringJob`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusteringParams {
+    pub shard_id: Uuid,
+    /// Clusters closer than this are merged
+    pub threshold: f32,
+}
+
+/// Clusters a shard's vectors with
+/// [`cluster_vectors`](crate::core::hierarchical::cluster_vectors) and
+/// returns the cluster sizes, largest first
+pub struct ClusteringJob {
+    shard_manager: Arc<ShardManager>,
+}
+
+impl ClusteringJob {
+    pub fn new(shard_manager: Arc<ShardManager>) -> Self {
+        Self { shard_manager }
+    }
+}
+
+#[async_trait]
+impl JobHandler for ClusteringJob {
+    async fn run(&self, params: serde_json::Value, ctx: JobContext) -> Result<serde_json::Value> {
+        let params: ClusteringParams = serde_json::from_value(params)?;
+        let index = self.shard_manager.get_vector_index(params.shard_id).await?;
+        let vectors: Vec<_> = index
+            .entries()
+            .await
+            .into_iter()
+            .map(|entry| entry.vector)
+            .collect();
+        ctx.set_progress(1, 10);
+        ctx.checkpoint()?;
+
+        let threshold = params.threshold;
+        let mut sizes: Vec<usize> =
+            tokio::task::spawn_blocking(move || cluster_vectors(&vectors, threshold))
+                .await?
+                .iter()
+                .map(|cluster| cluster.members.len())
+                .collect();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        ctx.checkpoint()?;
+
+        Ok(serde_json::json!({
+            "shard_id": params.shard_id,
+            "clusters": sizes.len(),
+            "sizes": sizes,
+        }))
+    }
+}
+
+/// Parameters for a [`CompactionJob`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionParams {
+    pub shard_id: Uuid,
+}
+
+/// Flushes a shard's memtable and merges all of its segments into one,
+/// dropping tombstoned vectors
+pub struct CompactionJob {
+    shard_manager: Arc<ShardManager>,
+}
+
+impl CompactionJob {
+    pub fn new(shard_manager: Arc<ShardManager>) -> Self {
+        Self { shard_manager }
+    }
+}
+
+#[async_trait]
+impl JobHandler for CompactionJob {
+    async fn run(&self, params: serde_json::Value, ctx: JobContext) -> Result<serde_json::Value> {
+        let params: CompactionParams = serde_json::from_value(params)?;
+        let index = self.shard_manager.get_vector_index(params.shard_id).await?;
+        index.flush_memtable().await;
+        ctx.set_progress(1, 2);
+        ctx.checkpoint()?;
+
+        let report = index.merge_segments(true).await;
+        Ok(serde_json::json!({
+            "shard_id": params.shard_id,
+            "merged_segments": report.as_ref().map_or(0, |r| r.inputs),
+            "entries": report.as_ref().map_or(0, |r| r.entries),
+            "tombstones_dropped": report.as_ref().map_or(0, |r| r.dropped),
+        }))
+    }
+}
@@ -1,4 +1,10 @@
+pub mod cluster_metrics;
+pub mod failover;
+pub mod jobs;
+pub mod region;
 pub mod replication;
 pub mod runtime;
+pub mod shutdown;
 pub mod synchrony;
+pub mod tasks;
 pub mod versioning;
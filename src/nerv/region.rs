@@ -0,0 +1,909 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::core::metrics::MetricsCollector;
+use crate::nerv::failover::{FailoverConfig, FailureDetector, Heartbeat, PeerHealth};
+use crate::nerv::tasks;
+use crate::network::bandwidth::{BandwidthThrottle, TrafficClass};
+use crate::network::secure_channel::SecureClient;
+use crate::server::auth::API_KEY_HEADER;
+use crate::sharding::changefeed::ChangeOp;
+use crate::sharding::manager::{ShardManager, ShardStatus};
+use crate::utils::errors::{ChangeFeedError, ReplicationError};
+use crate::utils::fs::write_atomic;
+
+/// File in the data directory holding the fencing epoch and role
+const FENCING_STATE_FILE: &str = "replication_fencing.json";
+
+/// Whether this region accepts client writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegionRole {
+    /// Accepts writes and ships them to the peer
+    Active,
+    /// Read-only; follows the peer until promoted
+    Standby,
+}
+
+/// Settings for pairing this deployment with a peer region
+#[derive(Debug, Clone)]
+pub struct RegionConfig {
+    /// Name of this region, stamped on every shipped change
+    pub region: String,
+
+    /// API base URL of the peer, e.g. `http://eu-west.example:9000/api`
+    pub peer_url: String,
+
+    pub role: RegionRole,
+
+    /// How often unshipped changes are sent
+    pub ship_interval: Duration,
+
+    /// Most change events per shipped segment
+    pub max_segment_events: usize,
+
+    /// Heartbeat the peer and promote this standby if it fails
+    pub failover: Option<FailoverConfig>,
+
+    /// Sent as the API key header, for peers that require authentication
+    pub api_key: Option<String>,
+
+    /// Directory holding the shard data. The fencing epoch and role are
+    /// kept there so a restart can't roll them back; without it they start
+    /// over at epoch 0 and `role`.
+    pub data_dir: Option<PathBuf>,
+}
+
+impl RegionConfig {
+    pub fn new(region: &str, peer_url: &str, role: RegionRole) -> Self {
+        Self {
+            region: region.to_string(),
+            peer_url: peer_url.trim_end_matches('/').to_string(),
+            role,
+            ship_interval: Duration::from_secs(1),
+            max_segment_events: 1000,
+            failover: None,
+            api_key: None,
+            data_dir: None,
+        }
+    }
+
+    /// Run as one half of a warm standby pair with automatic failover
+    pub fn with_failover(mut self, failover: FailoverConfig) -> Self {
+        self.failover = Some(failover);
+        self
+    }
+
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    pub fn with_data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = Some(dir.into());
+        self
+    }
+}
+
+/// Fencing epoch and role as persisted in the data directory
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct FencingState {
+    role: RegionRole,
+    epoch: u64,
+}
+
+impl FencingState {
+    /// State saved by a previous run, or `None` if there is none. An
+    /// unreadable file restores a standby, since this node may have been
+    /// fenced.
+    fn load(path: &std::path::Path) -> Option<Self> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                error!(
+                    "Failed to read {}: {}; starting as standby",
+                    path.display(),
+                    e
+                );
+                return Some(Self {
+                    role: RegionRole::Standby,
+                    epoch: 0,
+                });
+            }
+        };
+        match serde_json::from_slice(&bytes) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                error!(
+                    "Corrupt fencing state in {}: {}; starting as standby",
+                    path.display(),
+                    e
+                );
+                Some(Self {
+                    role: RegionRole::Standby,
+                    epoch: 0,
+                })
+            }
+        }
+    }
+}
+
+/// Version of a vector's latest mutation. Ordered by time, with the region
+/// name breaking ties so every replica picks the same winner.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct VersionStamp {
+    pub micros: i64,
+    pub region: String,
+}
+
+/// A change as shipped between regions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicatedChange {
+    pub stamp: VersionStamp,
+    #[serde(flatten)]
+    pub op: ChangeOp,
+}
+
+/// A contiguous run of a shard's change log. Shards are paired across
+/// regions by name, since shard IDs are assigned per deployment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSegment {
+    pub source_region: String,
+    /// Sender's fencing epoch; segments from an older epoch are rejected
+    #[serde(default)]
+    pub epoch: u64,
+    pub shard_name: String,
+    pub first_offset: u64,
+    pub last_offset: u64,
+    pub changes: Vec<ReplicatedChange>,
+}
+
+/// Result of applying a segment on the receiving side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentAck {
+    pub shard_name: String,
+    pub last_offset: u64,
+    pub applied: usize,
+    /// Changes that lost to a newer version already present
+    pub superseded: usize,
+}
+
+/// Snapshot of replication health
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionStatus {
+    pub region: String,
+    pub peer_url: String,
+    pub role: RegionRole,
+    pub epoch: u64,
+    /// Consecutive heartbeats the peer has missed
+    pub missed_heartbeats: u32,
+    /// Whether this active node has stopped taking writes because it lost
+    /// contact with the peer
+    pub lease_expired: bool,
+    /// Local changes not yet acknowledged by the peer
+    pub lag_events: u64,
+    /// Age of the oldest unacknowledged change
+    pub lag_ms: u64,
+    pub last_shipped_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_error: Option<String>,
+}
+
+/// Last-writer-wins map from (shard, vector) to the newest version seen,
+/// including deletes as tombstones so late inserts can't resurrect a vector
+#[derive(Debug, Default)]
+struct LwwRegister {
+    versions: HashMap<(String, Uuid), (VersionStamp, bool)>,
+}
+
+impl LwwRegister {
+    /// Record a version, returning whether it wins over what was known
+    fn observe(
+        &mut self,
+        shard: &str,
+        vector_id: Uuid,
+        stamp: &VersionStamp,
+        deleted: bool,
+    ) -> bool {
+        let key = (shard.to_string(), vector_id);
+        match self.versions.get(&key) {
+            Some((existing, _)) if existing >= stamp => false,
+            _ => {
+                self.versions.insert(key, (stamp.clone(), deleted));
+                true
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ShardCursor {
+    /// Next local change feed offset to ship
+    shipped: Option<u64>,
+    /// Next local change feed offset to record in the register
+    observed: Option<u64>,
+}
+
+/// An active node's hold on writes while it can't reach the peer
+#[derive(Debug, Default)]
+struct WriteLease {
+    /// Heartbeats missed since this node became active or last heard the peer
+    missed: u32,
+    /// Set while this node refuses writes
+    expired: bool,
+}
+
+fn change_vector_id(op: &ChangeOp) -> (Uuid, bool) {
+    match op {
+        ChangeOp::Insert { vector_id, .. } => (*vector_id, false),
+        ChangeOp::Delete { vector_id } => (*vector_id, true),
+    }
+}
+
+/// Asynchronous replication between two regions.
+///
+/// Each shard's change feed acts as its write-ahead log: local changes are
+/// shipped to the peer in segments, and segments received from the peer are
+/// merged through a last-writer-wins CRDT so both regions converge even if
+/// they accept writes concurrently.
+///
+/// With [`FailoverConfig`] the pair runs as a warm standby: the standby
+/// applies the primary's stream, heartbeats detect a failed primary, and
+/// promotion bumps an epoch that fences the old primary out. With
+/// [`RegionConfig::with_data_dir`] the epoch and role are saved before they
+/// take effect and restored on restart, so neither node can fall back to an
+/// epoch the other has moved past. An active node whose peer may promote,
+/// i.e. was last seen as a standby or not seen at all, makes its shards
+/// read-only after missing [`FailoverConfig::lease_threshold`] heartbeats
+/// and until the peer answers again, so it stops taking writes before the
+/// standby promotes.
+pub struct RegionReplicator {
+    config: RegionConfig,
+    role: RwLock<RegionRole>,
+    epoch: RwLock<u64>,
+    detector: RwLock<FailureDetector>,
+    lease: RwLock<WriteLease>,
+    /// Role the peer reported in its last heartbeat
+    peer_role: RwLock<Option<RegionRole>>,
+    /// Where the epoch and role are persisted, if anywhere
+    state_path: Option<PathBuf>,
+    shard_manager: Arc<ShardManager>,
+    metrics: Arc<MetricsCollector>,
+    client: reqwest::Client,
+    throttle: Option<Arc<BandwidthThrottle>>,
+    /// Channel segments are sealed in, and the peer's node id in it
+    secure: Option<(SecureClient, String)>,
+    register: RwLock<LwwRegister>,
+    cursors: RwLock<HashMap<Uuid, ShardCursor>>,
+    last_shipped_at: RwLock<Option<chrono::DateTime<chrono::Utc>>>,
+    last_error: RwLock<Option<String>>,
+}
+
+impl RegionReplicator {
+    pub fn new(
+        config: RegionConfig,
+        shard_manager: Arc<ShardManager>,
+        metrics: Arc<MetricsCollector>,
+    ) -> Self {
+        let threshold = config
+            .failover
+            .as_ref()
+            .map_or(FailoverConfig::default().failure_threshold, |f| {
+                f.failure_threshold
+            });
+        let state_path = config
+            .data_dir
+            .as_ref()
+            .map(|dir| dir.join(FENCING_STATE_FILE));
+        let state = state_path
+            .as_deref()
+            .and_then(FencingState::load)
+            .unwrap_or(FencingState {
+                role: config.role,
+                epoch: 0,
+            });
+        Self {
+            role: RwLock::new(state.role),
+            epoch: RwLock::new(state.epoch),
+            detector: RwLock::new(FailureDetector::new(threshold)),
+            lease: RwLock::new(WriteLease::default()),
+            peer_role: RwLock::new(None),
+            state_path,
+            config,
+            shard_manager,
+            metrics,
+            client: reqwest::Client::new(),
+            throttle: None,
+            secure: None,
+            register: RwLock::new(LwwRegister::default()),
+            cursors: RwLock::new(HashMap::new()),
+            last_shipped_at: RwLock::new(None),
+            last_error: RwLock::new(None),
+        }
+    }
+
+    /// Hold shipped segments to the throttle's replication limit
+    pub fn with_bandwidth_throttle(mut self, throttle: Arc<BandwidthThrottle>) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Seal shipped segments for the peer node `peer_id`, for peers that
+    /// only take them through a secure channel
+    pub fn with_secure_channel(mut self, client: SecureClient, peer_id: &str) -> Self {
+        self.secure = Some((client, peer_id.to_string()));
+        self
+    }
+
+    pub fn region(&self) -> &str {
+        &self.config.region
+    }
+
+    pub async fn role(&self) -> RegionRole {
+        *self.role.read().await
+    }
+
+    /// Current fencing epoch; the highest seen from either node
+    pub async fn epoch(&self) -> u64 {
+        *self.epoch.read().await
+    }
+
+    /// Persist the epoch and role, if a data directory is configured
+    fn save_state(&self, role: RegionRole, epoch: u64) -> Result<()> {
+        let Some(path) = &self.state_path else {
+            return Ok(());
+        };
+        write_atomic(path, |file| {
+            Ok(serde_json::to_writer(file, &FencingState { role, epoch })?)
+        })
+        .map_err(|e| anyhow!("Failed to save fencing state to {}: {}", path.display(), e))
+    }
+
+    /// This node's answer to the peer's heartbeat poll
+    pub async fn heartbeat(&self) -> Heartbeat {
+        Heartbeat {
+            region: self.config.region.clone(),
+            role: self.role().await,
+            epoch: self.epoch().await,
+            sent_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Adopt a newer epoch from the peer. If this node still thinks it's
+    /// active, the peer was promoted over it and it must stop taking writes.
+    async fn observe_epoch(&self, peer_epoch: u64) -> Result<()> {
+        let fenced = {
+            let role = self.role.read().await;
+            let mut epoch = self.epoch.write().await;
+            if peer_epoch <= *epoch {
+                return Ok(());
+            }
+            // Saved as a standby straight away, so a fenced primary that
+            // restarts before demoting doesn't come back taking writes
+            let fenced = *role == RegionRole::Active;
+            let saved_role = if fenced { RegionRole::Standby } else { *role };
+            self.save_state(saved_role, peer_epoch)?;
+            *epoch = peer_epoch;
+            fenced
+        };
+        if fenced {
+            warn!(
+                "Region {} fenced by peer at epoch {}",
+                self.config.region, peer_epoch
+            );
+            self.metrics
+                .increment_counter("replication.fenced", 1)
+                .await;
+            self.demote().await?;
+        }
+        Ok(())
+    }
+
+    /// Poll the peer's heartbeat, adopting its epoch if newer
+    pub async fn check_peer(&self) -> Result<Heartbeat> {
+        let timeout = self
+            .config
+            .failover
+            .as_ref()
+            .map_or(FailoverConfig::default().heartbeat_timeout, |f| {
+                f.heartbeat_timeout
+            });
+        let response = self
+            .peer_request(reqwest::Method::GET, "replication/heartbeat")
+            .timeout(timeout)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Peer heartbeat failed: {}", response.status()));
+        }
+        let heartbeat: Heartbeat = response.json().await?;
+        self.observe_epoch(heartbeat.epoch).await?;
+        *self.peer_role.write().await = Some(heartbeat.role);
+        Ok(heartbeat)
+    }
+
+    /// Poll the peer once. A standby promotes if the peer has now missed
+    /// enough heartbeats; an active node gives up writes once its lease
+    /// runs out and takes them back when the peer answers again.
+    pub async fn monitor_once(&self) -> PeerHealth {
+        let answered = self.check_peer().await;
+        let health = match &answered {
+            Ok(_) => self.detector.write().await.record_success(),
+            Err(e) => {
+                let health = self.detector.write().await.record_miss();
+                debug!("Missed heartbeat from {}: {}", self.config.peer_url, e);
+                self.metrics
+                    .increment_counter("replication.heartbeats_missed", 1)
+                    .await;
+                health
+            }
+        };
+
+        let role = self.role().await;
+        if role == RegionRole::Active {
+            if let Err(e) = self.renew_lease(answered.is_ok()).await {
+                error!("Failed to update write lease: {}", e);
+            }
+        }
+
+        if health == PeerHealth::Failed && role == RegionRole::Standby {
+            error!(
+                "Peer {} failed; promoting region {}",
+                self.config.peer_url, self.config.region
+            );
+            match self.promote().await {
+                Ok(()) => {
+                    self.metrics
+                        .increment_counter("replication.failovers", 1)
+                        .await
+                }
+                Err(e) => error!("Failover promotion failed: {}", e),
+            }
+        }
+        health
+    }
+
+    /// Count a heartbeat poll against this active node's lease, making its
+    /// shards read-only once the lease runs out and writable again once the
+    /// peer answers. A peer last seen active can't promote over this node,
+    /// so it doesn't hold the lease back.
+    async fn renew_lease(&self, answered: bool) -> Result<()> {
+        let threshold = self
+            .config
+            .failover
+            .as_ref()
+            .map_or(FailoverConfig::default().lease_threshold, |f| {
+                f.lease_threshold
+            });
+        let peer_may_promote = *self.peer_role.read().await != Some(RegionRole::Active);
+        let held = {
+            let mut lease = self.lease.write().await;
+            lease.missed = if answered { 0 } else { lease.missed + 1 };
+            let held = !peer_may_promote || lease.missed < threshold.max(1);
+            if lease.expired != held {
+                return Ok(());
+            }
+            lease.expired = !held;
+            held
+        };
+        if held {
+            self.set_shard_status(ShardStatus::ReadOnly, ShardStatus::Active)
+                .await?;
+            info!(
+                "Region {} renewed its lease; taking writes again",
+                self.config.region
+            );
+        } else {
+            self.set_shard_status(ShardStatus::Active, ShardStatus::ReadOnly)
+                .await?;
+            self.metrics
+                .increment_counter("replication.leases_expired", 1)
+                .await;
+            warn!(
+                "Region {} lost contact with {}; refusing writes until it answers",
+                self.config.region, self.config.peer_url
+            );
+        }
+        Ok(())
+    }
+
+    async fn set_shard_status(&self, from: ShardStatus, to: ShardStatus) -> Result<()> {
+        for shard in self.shard_manager.get_shards().await {
+            if shard.status == from {
+                self.shard_manager
+                    .update_shard_status(shard.id, to.clone())
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Record versions of local changes made since the last call, so that
+    /// incoming changes are compared against them
+    async fn observe_local(&self, shard_id: Uuid, shard_name: &str) -> Result<()> {
+        let feed = self.shard_manager.change_feed(shard_id).await?;
+        let mut cursors = self.cursors.write().await;
+        let cursor = cursors.entry(shard_id).or_default();
+        let mut from = match cursor.observed {
+            Some(from) => from,
+            None => feed.earliest_offset().await,
+        };
+
+        loop {
+            let batch = match feed.read(from, self.config.max_segment_events).await {
+                Ok(batch) => batch,
+                Err(ChangeFeedError::OffsetExpired { earliest, .. }) => {
+                    from = earliest;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if batch.events.is_empty() {
+                break;
+            }
+            let mut register = self.register.write().await;
+            for event in batch.events.iter().filter(|e| e.origin.is_none()) {
+                let (vector_id, deleted) = change_vector_id(&event.op);
+                let stamp = VersionStamp {
+                    micros: event.timestamp.timestamp_micros(),
+                    region: self.config.region.clone(),
+                };
+                register.observe(shard_name, vector_id, &stamp, deleted);
+            }
+            from = batch.next_offset;
+        }
+
+        cursor.observed = Some(from);
+        Ok(())
+    }
+
+    /// Ship unsent local changes for every shard to the peer
+    pub async fn ship_once(&self) -> Result<Vec<SegmentAck>> {
+        let mut acks = Vec::new();
+        let mut lag_events = 0u64;
+        let mut oldest_unshipped: Option<chrono::DateTime<chrono::Utc>> = None;
+
+        for shard in self.shard_manager.get_shards().await {
+            self.observe_local(shard.id, &shard.name).await?;
+            let feed = self.shard_manager.change_feed(shard.id).await?;
+
+            let from = {
+                let cursors = self.cursors.read().await;
+                cursors.get(&shard.id).and_then(|c| c.shipped)
+            };
+            let from = match from {
+                Some(from) => from,
+                None => feed.earliest_offset().await,
+            };
+
+            let batch = match feed.read(from, self.config.max_segment_events).await {
+                Ok(batch) => batch,
+                Err(ChangeFeedError::OffsetExpired { earliest, .. }) => {
+                    // The peer missed changes that are no longer retained and
+                    // needs a full resync of this shard
+                    error!(
+                        "Replication gap on shard {}: offsets {}..{} were dropped before shipping",
+                        shard.name, from, earliest
+                    );
+                    self.metrics.increment_counter("replication.gaps", 1).await;
+                    self.set_shipped(shard.id, earliest).await;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if batch.events.is_empty() {
+                continue;
+            }
+
+            // Changes received from the peer aren't shipped back to it
+            let changes: Vec<ReplicatedChange> = batch
+                .events
+                .iter()
+                .filter(|e| e.origin.is_none())
+                .map(|e| ReplicatedChange {
+                    stamp: VersionStamp {
+                        micros: e.timestamp.timestamp_micros(),
+                        region: self.config.region.clone(),
+                    },
+                    op: e.op.clone(),
+                })
+                .collect();
+
+            if !changes.is_empty() {
+                let segment = LogSegment {
+                    source_region: self.config.region.clone(),
+                    epoch: self.epoch().await,
+                    shard_name: shard.name.clone(),
+                    first_offset: from,
+                    last_offset: batch.next_offset - 1,
+                    changes,
+                };
+
+                match self.send_segment(&segment).await {
+                    Ok(ack) => acks.push(ack),
+                    Err(e) => {
+                        *self.last_error.write().await = Some(e.to_string());
+                        // A rejection may mean the peer was promoted over us
+                        if self.check_peer().await.is_ok()
+                            && self.role().await != RegionRole::Active
+                        {
+                            return Err(e);
+                        }
+                        lag_events += feed.next_offset().await - from;
+                        let first = batch.events[0].timestamp;
+                        oldest_unshipped = Some(oldest_unshipped.map_or(first, |o| o.min(first)));
+                        continue;
+                    }
+                }
+            }
+
+            self.set_shipped(shard.id, batch.next_offset).await;
+            lag_events += feed.next_offset().await - batch.next_offset;
+        }
+
+        if !acks.is_empty() {
+            *self.last_shipped_at.write().await = Some(chrono::Utc::now());
+            *self.last_error.write().await = None;
+        }
+
+        let lag_ms = oldest_unshipped
+            .map(|t| (chrono::Utc::now() - t).num_milliseconds().max(0) as u64)
+            .unwrap_or(0);
+        self.metrics
+            .set_gauge("replication.lag_events", lag_events)
+            .await;
+        self.metrics.set_gauge("replication.lag_ms", lag_ms).await;
+        self.metrics
+            .increment_counter("replication.segments_shipped", acks.len() as u64)
+            .await;
+
+        Ok(acks)
+    }
+
+    async fn set_shipped(&self, shard_id: Uuid, offset: u64) {
+        self.cursors
+            .write()
+            .await
+            .entry(shard_id)
+            .or_default()
+            .shipped = Some(offset);
+    }
+
+    /// Request to `path` under the peer's API, carrying the configured key
+    fn peer_request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}/{}", self.config.peer_url, path));
+        match &self.config.api_key {
+            Some(key) => request.header(API_KEY_HEADER, key),
+            None => request,
+        }
+    }
+
+    async fn send_segment(&self, segment: &LogSegment) -> Result<SegmentAck> {
+        let body = serde_json::to_vec(segment)?;
+        if let Some(throttle) = &self.throttle {
+            throttle
+                .acquire(
+                    TrafficClass::Replication,
+                    &self.config.peer_url,
+                    body.len() as u64,
+                )
+                .await;
+        }
+        if let Some((secure, peer_id)) = &self.secure {
+            let request = || self.peer_request(reqwest::Method::POST, "replication/segments");
+            let ack = secure.post(peer_id, request, &body).await.map_err(|e| {
+                anyhow!(
+                    "Peer rejected segment for shard {}: {}",
+                    segment.shard_name,
+                    e
+                )
+            })?;
+            return Ok(serde_json::from_slice(&ack)?);
+        }
+        let response = self
+            .peer_request(reqwest::Method::POST, "replication/segments")
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Peer rejected segment for shard {}: {} {}",
+                segment.shard_name,
+                response.status(),
+                response.text().await.unwrap_or_default()
+            ));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Merge a segment shipped by the peer
+    pub async fn apply_segment(&self, segment: LogSegment) -> Result<SegmentAck> {
+        if segment.source_region == self.config.region {
+            return Err(anyhow!(
+                "Refusing segment from own region {}",
+                segment.source_region
+            ));
+        }
+
+        let epoch = self.epoch().await;
+        if segment.epoch < epoch {
+            self.metrics
+                .increment_counter("replication.fenced_segments", 1)
+                .await;
+            return Err(ReplicationError::Fenced {
+                segment_epoch: segment.epoch,
+                epoch,
+            }
+            .into());
+        }
+        self.observe_epoch(segment.epoch).await?;
+
+        let shard = self
+            .shard_manager
+            .get_shard_by_name(&segment.shard_name)
+            .await?;
+        self.observe_local(shard.id, &shard.name).await?;
+
+        let mut ack = SegmentAck {
+            shard_name: segment.shard_name.clone(),
+            last_offset: segment.last_offset,
+            applied: 0,
+            superseded: 0,
+        };
+
+        for change in segment.changes {
+            let (vector_id, deleted) = change_vector_id(&change.op);
+            let wins =
+                self.register
+                    .write()
+                    .await
+                    .observe(&shard.name, vector_id, &change.stamp, deleted);
+            if !wins {
+                ack.superseded += 1;
+                continue;
+            }
+
+            if self
+                .shard_manager
+                .apply_replicated_change(shard.id, change.op, &segment.source_region)
+                .await?
+            {
+                ack.applied += 1;
+            }
+        }
+
+        // Replicated writes land in the local feed with an origin and are
+        // skipped when observing, so advance past them here
+        self.observe_local(shard.id, &shard.name).await?;
+
+        self.metrics
+            .increment_counter("replication.changes_applied", ack.applied as u64)
+            .await;
+        if ack.superseded > 0 {
+            self.metrics
+                .increment_counter("replication.conflicts_resolved", ack.superseded as u64)
+                .await;
+        }
+        debug!(
+            "Applied segment {}..{} for shard {} from {}",
+            segment.first_offset, segment.last_offset, segment.shard_name, segment.source_region
+        );
+
+        Ok(ack)
+    }
+
+    /// Make this region writable, e.g. after the peer region fails.
+    /// Promoting a standby bumps the epoch so the old primary is fenced.
+    pub async fn promote(&self) -> Result<()> {
+        {
+            let mut role = self.role.write().await;
+            let mut epoch = self.epoch.write().await;
+            let promoted = if *role == RegionRole::Standby {
+                *epoch + 1
+            } else {
+                *epoch
+            };
+            // Saved before any write is taken, so a restart can't come back
+            // at the epoch the old primary still holds
+            self.save_state(RegionRole::Active, promoted)?;
+            *epoch = promoted;
+            *role = RegionRole::Active;
+        }
+        // A fresh lease, so misses that led up to the promotion don't count
+        *self.lease.write().await = WriteLease::default();
+        self.set_shard_status(ShardStatus::ReadOnly, ShardStatus::Active)
+            .await?;
+        warn!(
+            "Region {} promoted to active at epoch {}",
+            self.config.region,
+            self.epoch().await
+        );
+        Ok(())
+    }
+
+    /// Stop accepting client writes and follow the peer
+    pub async fn demote(&self) -> Result<()> {
+        {
+            let mut role = self.role.write().await;
+            self.save_state(RegionRole::Standby, *self.epoch.read().await)?;
+            *role = RegionRole::Standby;
+        }
+        *self.lease.write().await = WriteLease::default();
+        self.set_shard_status(ShardStatus::Active, ShardStatus::ReadOnly)
+            .await?;
+        warn!("Region {} demoted to standby", self.config.region);
+        Ok(())
+    }
+
+    pub async fn status(&self) -> RegionStatus {
+        RegionStatus {
+            region: self.config.region.clone(),
+            peer_url: self.config.peer_url.clone(),
+            role: self.role().await,
+            epoch: self.epoch().await,
+            missed_heartbeats: self.detector.read().await.missed(),
+            lease_expired: self.lease.read().await.expired,
+            lag_events: self
+                .metrics
+                .get_gauge("replication.lag_events")
+                .await
+                .unwrap_or(0),
+            lag_ms: self
+                .metrics
+                .get_gauge("replication.lag_ms")
+                .await
+                .unwrap_or(0),
+            last_shipped_at: *self.last_shipped_at.read().await,
+            last_error: self.last_error.read().await.clone(),
+        }
+    }
+
+    /// Ship changes in the background. Standby regions apply their shard
+    /// roles on start and ship nothing until promoted. With failover
+    /// configured, the peer is also heartbeated.
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tasks::spawn("nerv", "region replicator", async move {
+            if self.role().await == RegionRole::Standby {
+                if let Err(e) = self.demote().await {
+                    error!("Failed to enter standby: {}", e);
+                }
+            }
+            if let Some(failover) = self.config.failover.clone() {
+                let monitor = self.clone();
+                tasks::spawn("nerv", "failover heartbeat", async move {
+                    let mut interval = tokio::time::interval(failover.heartbeat_interval);
+                    loop {
+                        interval.tick().await;
+                        monitor.monitor_once().await;
+                    }
+                });
+            }
+            info!(
+                "Replicating region {} to {}",
+                self.config.region, self.config.peer_url
+            );
+            let mut interval = tokio::time::interval(self.config.ship_interval);
+            loop {
+                interval.tick().await;
+                if self.role().await != RegionRole::Active {
+                    continue;
+                }
+                if let Err(e) = self.ship_once().await {
+                    warn!("Replication to {} failed: {}", self.config.peer_url, e);
+                    *self.last_error.write().await = Some(e.to_string());
+                }
+            }
+        })
+    }
+}
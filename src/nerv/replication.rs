@@ -5,6 +5,8 @@ use tokio::sync::RwLock;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
+use crate::nerv::tasks;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ReplicationStatus {
     Pending,
@@ -14,6 +16,7 @@ pub enum ReplicationStatus {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 struct ReplicationTask {
     id: Uuid,
     source_node: String,
@@ -81,11 +84,15 @@ impl ReplicationManager {
         let task_id_clone = task_id;
         let self_clone = Arc::clone(&self);
 
-        tokio::spawn(async move {
-            if let Err(e) = self_clone.execute_replication(task_id_clone).await {
-                error!("Replication task {} failed: {}", task_id_clone, e);
-            }
-        });
+        tasks::spawn(
+            "nerv",
+            format!("replication {}", task_id_clone),
+            async move {
+                if let Err(e) = self_clone.execute_replication(task_id_clone).await {
+                    error!("Replication task {} failed: {}", task_id_clone, e);
+                }
+            },
+        );
 
         Ok(task_id)
     }
@@ -1,15 +1,48 @@
 use crate::core::metrics::MetricsCollector;
+use crate::nerv::shutdown::{ShutdownReport, ShutdownToken};
+use crate::nerv::tasks;
 use crate::sharding::manager::ShardManager;
+use crate::sharding::storage::PersistenceConfig;
 use anyhow::Result;
-use std::sync::Arc;
-use tokio::sync::mpsc;
-use tracing::{error, info};
+use futures::future::BoxFuture;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+/// How long [`Runtime::shutdown`] waits for components by default
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Components to wait for on shutdown, each as a future that resolves once
+/// the component has stopped
+#[derive(Default)]
+struct Components(Mutex<Vec<(String, BoxFuture<'static, ()>)>>);
+
+impl std::fmt::Debug for Components {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<String> = self
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        f.debug_list().entries(names).finish()
+    }
+}
 
 #[derive(Debug)]
 pub struct Runtime {
     metrics: Arc<MetricsCollector>,
     shard_manager: Option<Arc<ShardManager>>,
     shutdown_tx: Option<mpsc::Sender<()>>,
+    persistence: Option<PersistenceConfig>,
+    flusher: Option<JoinHandle<()>>,
+    cancel: watch::Sender<bool>,
+    components: Components,
+    drain_timeout: Duration,
 }
 
 impl Runtime {
@@ -18,9 +51,27 @@ impl Runtime {
             metrics,
             shard_manager: None,
             shutdown_tx: None,
+            persistence: None,
+            flusher: None,
+            cancel: watch::channel(false).0,
+            components: Components::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
         }
     }
 
+    /// How long [`shutdown`](Self::shutdown) waits for components to drain
+    /// before flushing anyway
+    pub fn with_drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
+    /// Persist shards as configured, restoring any already stored on start
+    pub fn with_persistence(mut self, config: PersistenceConfig) -> Self {
+        self.persistence = Some(config);
+        self
+    }
+
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting Amazon Rose Forest runtime...");
 
@@ -29,14 +80,30 @@ impl Runtime {
         self.shutdown_tx = Some(shutdown_tx);
 
         // Initialize shard manager
-        let shard_manager = ShardManager::new(self.metrics.clone());
-        self.shard_manager = Some(Arc::new(shard_manager));
+        let mut shard_manager = ShardManager::new(self.metrics.clone());
+        if let Some(config) = &self.persistence {
+            let storage = config.open()?;
+            info!(
+                "Persisting shards to {} ({} storage)",
+                config.path.display(),
+                storage.name()
+            );
+            shard_manager = shard_manager.with_storage(storage);
+            shard_manager
+                .restore(config.lazy_load || config.progressive_load)
+                .await?;
+        }
+        let shard_manager = Arc::new(shard_manager);
+        if let Some(config) = &self.persistence {
+            if config.progressive_load && !config.lazy_load {
+                shard_manager.clone().warm_up();
+            }
+            self.flusher = Some(shard_manager.clone().start_flushing(config.flush_interval));
+        }
+        self.shard_manager = Some(shard_manager);
 
         // Start the background task
-        let metrics = self.metrics.clone();
-        let shard_manager = self.shard_manager.clone();
-
-        tokio::spawn(async move {
+        tasks::spawn("nerv", "runtime shutdown listener", async move {
             info!("Runtime background task started");
 
             tokio::select! {
@@ -54,16 +121,84 @@ impl Runtime {
     }
 
     pub async fn stop(&self) -> Result<()> {
+        self.shutdown().await.map(|_| ())
+    }
+
+    /// Token cancelled when the runtime shuts down
+    pub fn shutdown_token(&self) -> ShutdownToken {
+        ShutdownToken::new(self.cancel.subscribe())
+    }
+
+    /// Have [`shutdown`](Self::shutdown) wait for `stopped`, which should
+    /// resolve once the component has finished its in-flight work after
+    /// its token was cancelled
+    pub fn register_component(
+        &self,
+        name: impl Into<String>,
+        stopped: impl Future<Output = ()> + Send + 'static,
+    ) {
+        self.components
+            .0
+            .lock()
+            .unwrap()
+            .push((name.into(), Box::pin(stopped)));
+    }
+
+    /// Spawn a component given this runtime's shutdown token; shutdown
+    /// waits for it to return
+    pub fn spawn_component<F, Fut>(&self, subsystem: &str, name: &str, component: F)
+    where
+        F: FnOnce(ShutdownToken) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let handle = tasks::spawn(subsystem, name, component(self.shutdown_token()));
+        self.register_component(name, async move {
+            let _ = handle.await;
+        });
+    }
+
+    /// Stop in order: cancel every component's token, wait up to the drain
+    /// timeout for them to finish in-flight work, then flush shard state
+    pub async fn shutdown(&self) -> Result<ShutdownReport> {
         info!("Stopping Amazon Rose Forest runtime...");
+        let started = Instant::now();
 
+        self.cancel.send_replace(true);
         if let Some(tx) = &self.shutdown_tx {
             if let Err(e) = tx.send(()).await {
                 error!("Failed to send shutdown signal: {}", e);
             }
         }
 
-        info!("Amazon Rose Forest runtime stopped");
-        Ok(())
+        let components = std::mem::take(&mut *self.components.0.lock().unwrap());
+        let deadline = tokio::time::Instant::now() + self.drain_timeout;
+        let mut report = ShutdownReport::default();
+        for (name, stopped) in components {
+            match tokio::time::timeout_at(deadline, stopped).await {
+                Ok(()) => report.drained.push(name),
+                Err(_) => {
+                    warn!("{} didn't drain within {:?}", name, self.drain_timeout);
+                    report.timed_out.push(name);
+                }
+            }
+        }
+
+        // Write out whatever changed since the last periodic flush
+        if let Some(flusher) = &self.flusher {
+            flusher.abort();
+        }
+        if let Some(manager) = &self.shard_manager {
+            manager.flush().await?;
+        }
+
+        report.elapsed = started.elapsed();
+        info!(
+            "Amazon Rose Forest runtime stopped in {:?} ({} components drained, {} timed out)",
+            report.elapsed,
+            report.drained.len(),
+            report.timed_out.len()
+        );
+        Ok(report)
     }
 
     pub fn metrics(&self) -> Arc<MetricsCollector> {
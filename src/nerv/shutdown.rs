@@ -0,0 +1,60 @@
+//! Cancellation broadcast by [`Runtime::shutdown`](super::runtime::Runtime::shutdown).
+//!
+//! Components get a [`ShutdownToken`] from the runtime, stop taking new
+//! work once it's cancelled, and finish what they have in flight. The
+//! runtime waits for them to drain before it flushes shard state.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Resolves once the runtime starts shutting down
+#[derive(Debug, Clone)]
+pub struct ShutdownToken {
+    cancelled: watch::Receiver<bool>,
+}
+
+impl ShutdownToken {
+    pub(crate) fn new(cancelled: watch::Receiver<bool>) -> Self {
+        Self { cancelled }
+    }
+
+    /// A token that is never cancelled, for components run outside a
+    /// runtime
+    pub fn never() -> Self {
+        Self::new(watch::channel(false).1)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow()
+    }
+
+    /// Wait for cancellation. Never resolves if the runtime is dropped
+    /// without shutting down.
+    pub async fn cancelled(&self) {
+        let mut cancelled = self.cancelled.clone();
+        while !*cancelled.borrow_and_update() {
+            if cancelled.changed().await.is_err() {
+                return std::future::pending().await;
+            }
+        }
+    }
+}
+
+/// How the components drained during a shutdown
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    /// Components that stopped within the drain timeout
+    pub drained: Vec<String>,
+    /// Components still running when the drain timeout expired; they were
+    /// aborted where possible
+    pub timed_out: Vec<String>,
+    /// Time spent draining and flushing
+    pub elapsed: Duration,
+}
+
+impl ShutdownReport {
+    pub fn is_clean(&self) -> bool {
+        self.timed_out.is_empty()
+    }
+}
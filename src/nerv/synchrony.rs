@@ -1,8 +1,6 @@
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{error, info, warn};
-use uuid::Uuid;
+use tracing::info;
 
 type VectorClock = HashMap<String, u64>;
 
@@ -0,0 +1,154 @@
+//! Registry of the crate's background tasks.
+//!
+//! Tasks are started with [`spawn`] instead of `tokio::spawn`, naming the
+//! subsystem that owns them and what they do. The registry lists what is
+//! running and since when (`GET /api/admin/tasks`), so a loop that outlives
+//! the component that started it shows up instead of leaking unnoticed.
+//! Entries are removed when their task finishes, panics or is aborted.
+//!
+//! `tests/task_registry.rs` rejects any bare `tokio::spawn` under `src/`;
+//! this module is the only place allowed to call it.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::task::JoinHandle;
+
+static REGISTRY: Lazy<Arc<TaskRegistry>> = Lazy::new(|| Arc::new(TaskRegistry::new()));
+
+/// The process-wide registry [`spawn`] records tasks in
+pub fn registry() -> &'static Arc<TaskRegistry> {
+    &REGISTRY
+}
+
+/// Spawn `future` on the current runtime, listed in the process-wide
+/// registry under `subsystem` and `name` until it finishes
+pub fn spawn<F>(subsystem: &str, name: impl Into<String>, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    REGISTRY.spawn(subsystem, name, future)
+}
+
+/// A running task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskInfo {
+    pub id: u64,
+    /// Module that owns the task, e.g. `sharding` or `server`
+    pub subsystem: String,
+    pub name: String,
+    pub spawned_at: DateTime<Utc>,
+}
+
+/// Tasks running at one moment, as returned by `GET /api/admin/tasks`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskSnapshot {
+    pub total: usize,
+    /// Running tasks per subsystem
+    pub by_subsystem: BTreeMap<String, usize>,
+    /// Oldest first
+    pub tasks: Vec<TaskInfo>,
+}
+
+/// Tasks spawned through the registry that haven't finished yet
+#[derive(Debug, Default)]
+pub struct TaskRegistry {
+    next_id: AtomicU64,
+    tasks: Mutex<BTreeMap<u64, TaskInfo>>,
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawn `future` on the current runtime, listed under `subsystem` and
+    /// `name` until it finishes
+    pub fn spawn<F>(
+        self: &Arc<Self>,
+        subsystem: &str,
+        name: impl Into<String>,
+        future: F,
+    ) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.lock().insert(
+            id,
+            TaskInfo {
+                id,
+                subsystem: subsystem.to_string(),
+                name: name.into(),
+                spawned_at: Utc::now(),
+            },
+        );
+        // Dropped with the future, whether it completes, panics or is aborted
+        let registered = Registered {
+            registry: self.clone(),
+            id,
+        };
+        tokio::spawn(async move {
+            let _registered = registered;
+            future.await
+        })
+    }
+
+    /// Running tasks, oldest first
+    pub fn tasks(&self) -> Vec<TaskInfo> {
+        self.lock().values().cloned().collect()
+    }
+
+    /// Running tasks of one subsystem, oldest first
+    pub fn tasks_of(&self, subsystem: &str) -> Vec<TaskInfo> {
+        self.lock()
+            .values()
+            .filter(|task| task.subsystem == subsystem)
+            .cloned()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub fn snapshot(&self) -> TaskSnapshot {
+        let tasks = self.tasks();
+        let mut by_subsystem = BTreeMap::new();
+        for task in &tasks {
+            *by_subsystem.entry(task.subsystem.clone()).or_insert(0) += 1;
+        }
+        TaskSnapshot {
+            total: tasks.len(),
+            by_subsystem,
+            tasks,
+        }
+    }
+
+    // Entries are plain data, so one left behind by a panic is still usable
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<u64, TaskInfo>> {
+        self.tasks.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Removes a task's entry when dropped
+struct Registered {
+    registry: Arc<TaskRegistry>,
+    id: u64,
+}
+
+impl Drop for Registered {
+    fn drop(&mut self) {
+        self.registry.lock().remove(&self.id);
+    }
+}
@@ -1,9 +1,8 @@
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{info, warn};
+use tracing::info;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +19,12 @@ pub struct VersionManager<T: Clone> {
     objects: RwLock<HashMap<Uuid, Vec<VersionedObject<T>>>>,
 }
 
+impl<T: Clone + Send + Sync + 'static> Default for VersionManager<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Clone + Send + Sync + 'static> VersionManager<T> {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,384 @@
+//! Resource-aware admission control.
+//!
+//! A sampler watches CPU, memory, event-loop lag and the latency of
+//! interactive requests. While any of them is past its threshold the node
+//! is saturated: interactive requests are still admitted, batch work (bulk
+//! imports, webhook ingestion) waits in a bounded queue for the pressure
+//! to drop, and background work (Darwin generation) is deferred. Past the
+//! critical thresholds, when the queue is full or when a request has
+//! waited too long, batch work is shed too so it can be retried later or
+//! elsewhere.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use sysinfo::{CpuExt, System, SystemExt};
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::core::metrics::MetricsCollector;
+use crate::nerv::tasks;
+use crate::network::priority::Priority;
+use crate::utils::errors::AdmissionError;
+
+/// Interactive latencies kept for the SLO check
+const LATENCY_WINDOW: usize = 256;
+
+/// Fewest latencies the SLO check needs before it counts
+const MIN_LATENCY_SAMPLES: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct AdmissionConfig {
+    /// CPU utilisation, `[0, 1]`, above which the node is saturated
+    pub cpu_high: f32,
+    pub cpu_critical: f32,
+
+    /// Fraction of memory in use above which the node is saturated
+    pub memory_high: f32,
+    pub memory_critical: f32,
+
+    /// Scheduling delay of the async runtime above which the node is saturated
+    pub loop_lag_high: Duration,
+    pub loop_lag_critical: Duration,
+
+    /// 95th percentile latency interactive requests should stay under
+    pub interactive_slo: Duration,
+
+    /// Batch requests allowed to wait at once
+    pub max_queue: usize,
+
+    /// Longest a batch request waits before it is shed
+    pub queue_timeout: Duration,
+
+    pub sample_interval: Duration,
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        Self {
+            cpu_high: 0.85,
+            cpu_critical: 0.97,
+            memory_high: 0.85,
+            memory_critical: 0.95,
+            loop_lag_high: Duration::from_millis(50),
+            loop_lag_critical: Duration::from_millis(250),
+            interactive_slo: Duration::from_millis(100),
+            max_queue: 64,
+            queue_timeout: Duration::from_secs(10),
+            sample_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Resource usage at one point in time
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct LoadSample {
+    /// CPU utilisation, `[0, 1]`
+    pub cpu: f32,
+    /// Fraction of memory in use
+    pub memory: f32,
+    pub loop_lag_ms: u64,
+}
+
+/// How loaded the node is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pressure {
+    Normal,
+    /// Batch work is queued and background work deferred
+    Saturated,
+    /// Everything but interactive work is shed
+    Overloaded,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdmissionStatus {
+    pub pressure: Pressure,
+    pub load: LoadSample,
+    /// 95th percentile of recent interactive latencies
+    pub interactive_p95_ms: Option<u64>,
+    /// Reasons the node is under pressure
+    pub reasons: Vec<String>,
+    pub queued: usize,
+    /// Admitted batch and background requests still running
+    pub in_flight_low_priority: usize,
+}
+
+/// Held while an admitted request runs
+#[derive(Debug)]
+pub struct AdmissionPermit {
+    priority: Priority,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl AdmissionPermit {
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        if self.priority != Priority::Interactive {
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LoadState {
+    load: LoadSample,
+    latencies: VecDeque<Duration>,
+}
+
+/// Admits, queues or sheds requests based on node load
+#[derive(Debug)]
+pub struct AdmissionController {
+    config: AdmissionConfig,
+    metrics: Arc<MetricsCollector>,
+    state: Mutex<LoadState>,
+    pressure: watch::Sender<Pressure>,
+    queued: AtomicUsize,
+    in_flight_low_priority: Arc<AtomicUsize>,
+}
+
+impl AdmissionController {
+    pub fn new(config: AdmissionConfig, metrics: Arc<MetricsCollector>) -> Self {
+        let (pressure, _) = watch::channel(Pressure::Normal);
+        Self {
+            config,
+            metrics,
+            state: Mutex::new(LoadState {
+                load: LoadSample::default(),
+                latencies: VecDeque::with_capacity(LATENCY_WINDOW),
+            }),
+            pressure,
+            queued: AtomicUsize::new(0),
+            in_flight_low_priority: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn config(&self) -> &AdmissionConfig {
+        &self.config
+    }
+
+    pub fn pressure(&self) -> Pressure {
+        *self.pressure.borrow()
+    }
+
+    /// Admit a request. While the node is saturated batch requests wait for
+    /// the pressure to drop and background requests are turned away.
+    pub async fn admit(&self, priority: Priority) -> Result<AdmissionPermit, AdmissionError> {
+        if priority == Priority::Interactive {
+            return Ok(self.permit(priority));
+        }
+
+        let mut pressure = self.pressure.subscribe();
+        let current = *pressure.borrow();
+        match current {
+            Pressure::Normal => return Ok(self.permit(priority)),
+            Pressure::Overloaded => return Err(self.shed(priority, "node overloaded").await),
+            Pressure::Saturated if priority == Priority::Background => {
+                return Err(self
+                    .shed(priority, "background work deferred while saturated")
+                    .await)
+            }
+            Pressure::Saturated => {}
+        }
+
+        // Reserve a queue slot
+        let position = self.queued.fetch_add(1, Ordering::SeqCst);
+        if position >= self.config.max_queue {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(self.shed(priority, "admission queue full").await);
+        }
+        self.metrics.increment_counter("admission.queued", 1).await;
+
+        let waited = tokio::time::timeout(self.config.queue_timeout, async {
+            loop {
+                match *pressure.borrow_and_update() {
+                    Pressure::Normal => return Ok(()),
+                    Pressure::Overloaded => return Err("node overloaded"),
+                    Pressure::Saturated => {}
+                }
+                if pressure.changed().await.is_err() {
+                    return Err("admission controller stopped");
+                }
+            }
+        })
+        .await;
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+
+        match waited {
+            Ok(Ok(())) => Ok(self.permit(priority)),
+            Ok(Err(reason)) => Err(self.shed(priority, reason).await),
+            Err(_) => {
+                self.metrics
+                    .increment_counter("admission.timed_out", 1)
+                    .await;
+                Err(AdmissionError::QueueTimeout {
+                    waited: self.config.queue_timeout,
+                })
+            }
+        }
+    }
+
+    fn permit(&self, priority: Priority) -> AdmissionPermit {
+        if priority != Priority::Interactive {
+            self.in_flight_low_priority.fetch_add(1, Ordering::SeqCst);
+        }
+        AdmissionPermit {
+            priority,
+            in_flight: self.in_flight_low_priority.clone(),
+        }
+    }
+
+    async fn shed(&self, priority: Priority, reason: &str) -> AdmissionError {
+        debug!("Shedding {} request: {}", priority, reason);
+        self.metrics.increment_counter("admission.shed", 1).await;
+        self.metrics
+            .increment_counter(&format!("admission.{}.shed", priority.as_str()), 1)
+            .await;
+        AdmissionError::Shed {
+            reason: reason.to_string(),
+            retry_after: self.config.queue_timeout,
+        }
+    }
+
+    /// Record how long an interactive request took
+    pub async fn record_latency(&self, latency: Duration) {
+        let mut state = self.state.lock().await;
+        if state.latencies.len() == LATENCY_WINDOW {
+            state.latencies.pop_front();
+        }
+        state.latencies.push_back(latency);
+    }
+
+    /// Record a resource sample and re-evaluate pressure
+    pub async fn record_sample(&self, load: LoadSample) -> Pressure {
+        let (pressure, reasons) = {
+            let mut state = self.state.lock().await;
+            state.load = load;
+            self.evaluate(&state)
+        };
+
+        let previous = self.pressure.send_replace(pressure);
+        if previous != pressure {
+            match pressure {
+                Pressure::Normal => info!("Node load back to normal"),
+                _ => warn!("Node {:?}: {}", pressure, reasons.join(", ")),
+            }
+        }
+
+        self.metrics
+            .set_gauge("admission.cpu_pct", (load.cpu * 100.0).round() as u64)
+            .await;
+        self.metrics
+            .set_gauge("admission.memory_pct", (load.memory * 100.0).round() as u64)
+            .await;
+        self.metrics
+            .set_gauge("admission.loop_lag_ms", load.loop_lag_ms)
+            .await;
+        self.metrics
+            .set_gauge("admission.pressure", pressure as u64)
+            .await;
+        pressure
+    }
+
+    fn evaluate(&self, state: &LoadState) -> (Pressure, Vec<String>) {
+        let load = state.load;
+        let lag = Duration::from_millis(load.loop_lag_ms);
+        let mut pressure = Pressure::Normal;
+        let mut reasons = Vec::new();
+        let mut check = |value: bool, critical: bool, reason: String| {
+            if critical {
+                pressure = Pressure::Overloaded;
+                reasons.push(reason);
+            } else if value {
+                pressure = pressure.max(Pressure::Saturated);
+                reasons.push(reason);
+            }
+        };
+
+        check(
+            load.cpu > self.config.cpu_high,
+            load.cpu > self.config.cpu_critical,
+            format!("cpu at {:.0}%", load.cpu * 100.0),
+        );
+        check(
+            load.memory > self.config.memory_high,
+            load.memory > self.config.memory_critical,
+            format!("memory at {:.0}%", load.memory * 100.0),
+        );
+        check(
+            lag > self.config.loop_lag_high,
+            lag > self.config.loop_lag_critical,
+            format!("event loop lagging {}ms", load.loop_lag_ms),
+        );
+        // Interactive latency alone only queues background work; it never
+        // sheds it, since slow searches may have causes shedding won't fix
+        if let Some(p95) = p95(&state.latencies) {
+            check(
+                p95 > self.config.interactive_slo,
+                false,
+                format!("interactive p95 at {}ms", p95.as_millis()),
+            );
+        }
+        (pressure, reasons)
+    }
+
+    pub async fn status(&self) -> AdmissionStatus {
+        let state = self.state.lock().await;
+        let (pressure, reasons) = self.evaluate(&state);
+        AdmissionStatus {
+            pressure,
+            load: state.load,
+            interactive_p95_ms: p95(&state.latencies).map(|p| p.as_millis() as u64),
+            reasons,
+            queued: self.queued.load(Ordering::SeqCst),
+            in_flight_low_priority: self.in_flight_low_priority.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Sample CPU, memory and event-loop lag in the background
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tasks::spawn("network", "admission sampler", async move {
+            let mut system = System::new();
+            let interval = self.config.sample_interval;
+            loop {
+                let started = Instant::now();
+                tokio::time::sleep(interval).await;
+                // Anything past the requested sleep is time the runtime
+                // spent too busy to wake us
+                let lag = started.elapsed().saturating_sub(interval);
+
+                system.refresh_cpu();
+                system.refresh_memory();
+                let memory = if system.total_memory() > 0 {
+                    system.used_memory() as f32 / system.total_memory() as f32
+                } else {
+                    0.0
+                };
+                self.record_sample(LoadSample {
+                    cpu: system.global_cpu_info().cpu_usage() / 100.0,
+                    memory,
+                    loop_lag_ms: lag.as_millis() as u64,
+                })
+                .await;
+            }
+        })
+    }
+}
+
+fn p95(latencies: &VecDeque<Duration>) -> Option<Duration> {
+    if latencies.len() < MIN_LATENCY_SAMPLES {
+        return None;
+    }
+    let mut sorted: Vec<Duration> = latencies.iter().copied().collect();
+    sorted.sort();
+    let index = ((sorted.len() as f64 * 0.95).ceil() as usize).saturating_sub(1);
+    sorted.get(index).copied()
+}
@@ -0,0 +1,243 @@
+//! Bandwidth limits for background traffic between nodes.
+//!
+//! Replication, CRDT sync and snapshot transfers each get a
+//! [`BandwidthLimit`], enforced by a token bucket per peer so one busy link
+//! doesn't hold back the others. Senders call [`BandwidthThrottle::acquire`]
+//! with the size of what they're about to send and are delayed until the
+//! bucket covers it. Transfers larger than the burst are let through once
+//! the bucket is full and leave it in debt, so they still average out to
+//! the limit. Bytes sent and the current throughput per class are reported
+//! to the [`MetricsCollector`] as `bandwidth.<class>.*`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::core::metrics::MetricsCollector;
+
+/// Span the reported throughput is averaged over
+pub const THROUGHPUT_WINDOW: Duration = Duration::from_secs(5);
+
+/// Kinds of background traffic that can be limited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrafficClass {
+    Replication,
+    CrdtSync,
+    SnapshotTransfer,
+}
+
+impl fmt::Display for TrafficClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrafficClass::Replication => write!(f, "replication"),
+            TrafficClass::CrdtSync => write!(f, "crdt_sync"),
+            TrafficClass::SnapshotTransfer => write!(f, "snapshot_transfer"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BandwidthLimit {
+    /// Sustained rate allowed per peer
+    pub bytes_per_sec: u64,
+    /// Bytes that may be sent at once after the link was idle
+    pub burst_bytes: u64,
+}
+
+impl BandwidthLimit {
+    /// A limit whose burst is one second's worth of traffic
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec,
+            burst_bytes: bytes_per_sec,
+        }
+    }
+
+    pub fn with_burst(mut self, burst_bytes: u64) -> Self {
+        self.burst_bytes = burst_bytes;
+        self
+    }
+}
+
+/// Limits per traffic class; classes without one are only measured
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BandwidthConfig {
+    #[serde(default)]
+    pub limits: HashMap<TrafficClass, BandwidthLimit>,
+}
+
+impl BandwidthConfig {
+    pub fn with_limit(mut self, class: TrafficClass, limit: BandwidthLimit) -> Self {
+        self.limits.insert(class, limit);
+        self
+    }
+}
+
+/// Traffic of one class to one peer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerThroughput {
+    pub class: TrafficClass,
+    pub peer: String,
+    /// Average over the last [`THROUGHPUT_WINDOW`]
+    pub bytes_per_sec: u64,
+    pub total_bytes: u64,
+    /// Time senders spent waiting for the bucket
+    pub throttled_ms: u64,
+    pub limit: Option<BandwidthLimit>,
+}
+
+#[derive(Debug)]
+struct Link {
+    /// Negative while a transfer larger than the burst is paid off
+    tokens: f64,
+    refilled_at: Instant,
+    sent: VecDeque<(Instant, u64)>,
+    total_bytes: u64,
+    throttled: Duration,
+}
+
+impl Link {
+    fn new(limit: Option<BandwidthLimit>) -> Self {
+        Self {
+            tokens: limit.map_or(0.0, |l| l.burst_bytes as f64),
+            refilled_at: Instant::now(),
+            sent: VecDeque::new(),
+            total_bytes: 0,
+            throttled: Duration::ZERO,
+        }
+    }
+
+    /// Take `bytes` from the bucket, returning how long the sender must wait
+    fn take(&mut self, limit: BandwidthLimit, bytes: u64, now: Instant) -> Duration {
+        let rate = limit.bytes_per_sec.max(1) as f64;
+        let burst = limit.burst_bytes.max(1) as f64;
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(burst);
+        self.refilled_at = now;
+
+        // Anything beyond the burst only has to wait for a full bucket
+        let needed = (bytes as f64).min(burst);
+        let wait = if self.tokens >= needed {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((needed - self.tokens) / rate)
+        };
+        self.tokens -= bytes as f64;
+        wait
+    }
+
+    fn bytes_per_sec(&mut self, now: Instant) -> u64 {
+        while let Some((at, _)) = self.sent.front() {
+            if now.saturating_duration_since(*at) > THROUGHPUT_WINDOW {
+                self.sent.pop_front();
+            } else {
+                break;
+            }
+        }
+        let bytes: u64 = self.sent.iter().map(|(_, b)| b).sum();
+        (bytes as f64 / THROUGHPUT_WINDOW.as_secs_f64()) as u64
+    }
+}
+
+/// Token buckets per traffic class and peer
+#[derive(Debug)]
+pub struct BandwidthThrottle {
+    config: BandwidthConfig,
+    links: Mutex<HashMap<(TrafficClass, String), Link>>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl BandwidthThrottle {
+    pub fn new(config: BandwidthConfig, metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            config,
+            links: Mutex::new(HashMap::new()),
+            metrics,
+        }
+    }
+
+    pub fn limit(&self, class: TrafficClass) -> Option<BandwidthLimit> {
+        self.config.limits.get(&class).copied()
+    }
+
+    /// Wait until `bytes` of `class` traffic may be sent to `peer` and
+    /// account for them. Returns how long the caller was held back.
+    pub async fn acquire(&self, class: TrafficClass, peer: &str, bytes: u64) -> Duration {
+        let limit = self.limit(class);
+        let now = Instant::now();
+        let (wait, class_rate) = {
+            let mut links = self.links.lock().await;
+            let link = links
+                .entry((class, peer.to_string()))
+                .or_insert_with(|| Link::new(limit));
+            let wait = match limit {
+                Some(limit) => link.take(limit, bytes, now),
+                None => Duration::ZERO,
+            };
+            // Counted from when the bytes actually go out
+            link.sent.push_back((now + wait, bytes));
+            link.total_bytes += bytes;
+            link.throttled += wait;
+
+            let mut class_rate = 0;
+            for ((c, _), link) in links.iter_mut() {
+                if *c == class {
+                    class_rate += link.bytes_per_sec(now + wait);
+                }
+            }
+            (wait, class_rate)
+        };
+
+        self.metrics
+            .increment_counter(&format!("bandwidth.{}.bytes", class), bytes)
+            .await;
+        self.metrics
+            .set_gauge(&format!("bandwidth.{}.bytes_per_sec", class), class_rate)
+            .await;
+        if !wait.is_zero() {
+            debug!(
+                "Throttling {} bytes of {} traffic to {} for {:?}",
+                bytes, class, peer, wait
+            );
+            self.metrics
+                .increment_counter(
+                    &format!("bandwidth.{}.throttled_ms", class),
+                    wait.as_millis() as u64,
+                )
+                .await;
+            tokio::time::sleep(wait).await;
+        }
+        wait
+    }
+
+    /// Current traffic per class and peer, ordered by class then peer
+    pub async fn throughput(&self) -> Vec<PeerThroughput> {
+        let now = Instant::now();
+        let mut links = self.links.lock().await;
+        let mut throughput: Vec<PeerThroughput> = links
+            .iter_mut()
+            .map(|((class, peer), link)| PeerThroughput {
+                class: *class,
+                peer: peer.clone(),
+                bytes_per_sec: link.bytes_per_sec(now),
+                total_bytes: link.total_bytes,
+                throttled_ms: link.throttled.as_millis() as u64,
+                limit: self.limit(*class),
+            })
+            .collect();
+        throughput.sort_by(|a, b| {
+            a.class
+                .to_string()
+                .cmp(&b.class.to_string())
+                .then_with(|| a.peer.cmp(&b.peer))
+        });
+        throughput
+    }
+}
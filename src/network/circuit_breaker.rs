@@ -1,10 +1,13 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 use tokio::time::{Duration, Instant};
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum CircuitState {
     Closed,   // Normal operation, requests pass through
     Open,     // Circuit is open, requests are blocked
@@ -21,8 +24,9 @@ impl std::fmt::Display for CircuitState {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CircuitBreakerMetrics {
+    pub name: String,
     pub successful_calls: u64,
     pub failed_calls: u64,
     pub rejected_calls: u64,
@@ -33,6 +37,10 @@ pub struct CircuitBreakerMetrics {
     pub last_success: Option<chrono::DateTime<chrono::Utc>>,
     pub last_state_change: Option<chrono::DateTime<chrono::Utc>>,
     pub avg_response_time_ms: f64,
+    /// Requests let through to test the service while half-open
+    pub half_open_probes: u64,
+    /// Held open by an operator until reset
+    pub manually_tripped: bool,
 }
 
 #[derive(Debug)]
@@ -47,6 +55,8 @@ pub struct CircuitBreaker {
     successful_calls: AtomicU64,
     failed_calls: AtomicU64,
     rejected_calls: AtomicU64,
+    half_open_probes: AtomicU64,
+    forced_open: AtomicBool,
     state_transitions: Mutex<Vec<(CircuitState, CircuitState, chrono::DateTime<chrono::Utc>)>>,
     last_failure: Mutex<Option<Instant>>,
     last_success: Mutex<Option<Instant>>,
@@ -71,6 +81,8 @@ impl CircuitBreaker {
             successful_calls: AtomicU64::new(0),
             failed_calls: AtomicU64::new(0),
             rejected_calls: AtomicU64::new(0),
+            half_open_probes: AtomicU64::new(0),
+            forced_open: AtomicBool::new(false),
             state_transitions: Mutex::new(Vec::new()),
             last_failure: Mutex::new(None),
             last_success: Mutex::new(None),
@@ -79,6 +91,10 @@ impl CircuitBreaker {
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn get_state(&self) -> CircuitState {
         match self.state.load(Ordering::Relaxed) {
             0 => CircuitState::Closed,
@@ -123,6 +139,10 @@ impl CircuitBreaker {
     }
 
     pub async fn can_execute(&self) -> bool {
+        if self.forced_open.load(Ordering::Relaxed) {
+            self.rejected_calls.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
         match self.get_state() {
             CircuitState::Closed => true,
             CircuitState::Open => {
@@ -133,6 +153,7 @@ impl CircuitBreaker {
                         // Transition to half-open
                         drop(last_failure); // Release the mutex before the state transition
                         self.transition_state(CircuitState::HalfOpen).await;
+                        self.half_open_probes.fetch_add(1, Ordering::Relaxed);
                         true
                     } else {
                         self.rejected_calls.fetch_add(1, Ordering::Relaxed);
@@ -145,6 +166,7 @@ impl CircuitBreaker {
             }
             CircuitState::HalfOpen => {
                 // In half-open state, only allow one request to test the service
+                self.half_open_probes.fetch_add(1, Ordering::Relaxed);
                 true
             }
         }
@@ -198,6 +220,22 @@ impl CircuitBreaker {
         }
     }
 
+    /// Close the circuit and forget past failures, releasing a manual trip
+    pub async fn reset(&self) {
+        self.forced_open.store(false, Ordering::Relaxed);
+        self.failure_count.store(0, Ordering::Relaxed);
+        self.transition_state(CircuitState::Closed).await;
+        info!("Circuit '{}' reset manually", self.name);
+    }
+
+    /// Open the circuit and keep it open until [`reset`](Self::reset),
+    /// regardless of the reset timeout
+    pub async fn trip(&self) {
+        self.forced_open.store(true, Ordering::Relaxed);
+        self.transition_state(CircuitState::Open).await;
+        warn!("Circuit '{}' tripped manually", self.name);
+    }
+
     pub async fn record_response_time(&self, duration: Duration) {
         let mut times = self.response_times.lock().await;
         times.push(duration);
@@ -223,6 +261,7 @@ impl CircuitBreaker {
         };
 
         CircuitBreakerMetrics {
+            name: self.name.clone(),
             successful_calls: self.successful_calls.load(Ordering::Relaxed),
             failed_calls: self.failed_calls.load(Ordering::Relaxed),
             rejected_calls: self.rejected_calls.load(Ordering::Relaxed),
@@ -239,6 +278,8 @@ impl CircuitBreaker {
             }),
             last_state_change: *last_state_change,
             avg_response_time_ms: avg_response_time,
+            half_open_probes: self.half_open_probes.load(Ordering::Relaxed),
+            manually_tripped: self.forced_open.load(Ordering::Relaxed),
         }
     }
 
@@ -284,3 +325,54 @@ impl CircuitBreaker {
         result
     }
 }
+
+/// The circuit breakers of a node by name, so operators can inspect and
+/// override them
+#[derive(Debug, Default)]
+pub struct CircuitBreakerRegistry {
+    breakers: RwLock<BTreeMap<String, Arc<CircuitBreaker>>>,
+}
+
+impl CircuitBreakerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a breaker, replacing any registered under the same name
+    pub async fn register(&self, breaker: Arc<CircuitBreaker>) -> Arc<CircuitBreaker> {
+        self.breakers
+            .write()
+            .await
+            .insert(breaker.name().to_string(), breaker.clone());
+        breaker
+    }
+
+    pub async fn get(&self, name: &str) -> Option<Arc<CircuitBreaker>> {
+        self.breakers.read().await.get(name).cloned()
+    }
+
+    /// Metrics of every breaker, ordered by name
+    pub async fn metrics(&self) -> Vec<CircuitBreakerMetrics> {
+        let breakers: Vec<Arc<CircuitBreaker>> =
+            self.breakers.read().await.values().cloned().collect();
+        let mut metrics = Vec::with_capacity(breakers.len());
+        for breaker in breakers {
+            metrics.push(breaker.get_metrics().await);
+        }
+        metrics
+    }
+
+    /// Reset the named breaker, returning its metrics afterwards
+    pub async fn reset(&self, name: &str) -> Option<CircuitBreakerMetrics> {
+        let breaker = self.get(name).await?;
+        breaker.reset().await;
+        Some(breaker.get_metrics().await)
+    }
+
+    /// Trip the named breaker, returning its metrics afterwards
+    pub async fn trip(&self, name: &str) -> Option<CircuitBreakerMetrics> {
+        let breaker = self.get(name).await?;
+        breaker.trip().await;
+        Some(breaker.get_metrics().await)
+    }
+}
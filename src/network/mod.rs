@@ -1 +1,6 @@
+pub mod admission;
+pub mod bandwidth;
 pub mod circuit_breaker;
+pub mod priority;
+pub mod secure_channel;
+pub mod trust;
@@ -0,0 +1,186 @@
+//! Request priority classes and the pools work runs in.
+//!
+//! Every API request is interactive, batch or background. Each route has a
+//! default class, and a client can lower its own request's class with the
+//! `x-request-priority` header but never raise it. The class decides how
+//! admission control treats the request and which pool it runs in. Pools
+//! are bounded separately, so a flood of bulk imports can use up the batch
+//! pool without taking a single slot from interactive searches.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::core::metrics::MetricsCollector;
+
+/// Header a client can use to lower its request's priority
+pub const PRIORITY_HEADER: &str = "x-request-priority";
+
+/// How urgent a request is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    /// A user is waiting, e.g. a search; always admitted
+    Interactive,
+    /// Client-driven throughput work, e.g. bulk imports and webhook ingestion
+    Batch,
+    /// Work the node starts itself and can redo later, e.g. Darwin generation
+    Background,
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Interactive => "interactive",
+            Priority::Batch => "batch",
+            Priority::Background => "background",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "interactive" => Some(Priority::Interactive),
+            "batch" => Some(Priority::Batch),
+            "background" => Some(Priority::Background),
+            _ => None,
+        }
+    }
+
+    /// Higher is more urgent
+    fn rank(&self) -> u8 {
+        match self {
+            Priority::Interactive => 2,
+            Priority::Batch => 1,
+            Priority::Background => 0,
+        }
+    }
+
+    /// Class of a request to a route with default class `route`, given the
+    /// priority header if present. The header can only lower the class;
+    /// unknown values are ignored.
+    pub fn resolve(route: Priority, header: Option<&str>) -> Priority {
+        match header.and_then(Priority::parse) {
+            Some(requested) if requested.rank() < route.rank() => requested,
+            _ => route,
+        }
+    }
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Concurrent tasks allowed per priority class
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub interactive: usize,
+    pub batch: usize,
+    pub background: usize,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        let cores = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4);
+        Self {
+            interactive: cores * 4,
+            batch: (cores / 2).max(1),
+            background: 1,
+        }
+    }
+}
+
+/// A slot in a priority pool, released on drop
+#[derive(Debug)]
+pub struct PoolSlot {
+    priority: Priority,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl PoolSlot {
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+}
+
+/// Separately bounded pools for each priority class
+#[derive(Debug)]
+pub struct PriorityPools {
+    config: PoolConfig,
+    interactive: Arc<Semaphore>,
+    batch: Arc<Semaphore>,
+    background: Arc<Semaphore>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl PriorityPools {
+    pub fn new(config: PoolConfig, metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            interactive: Arc::new(Semaphore::new(config.interactive.max(1))),
+            batch: Arc::new(Semaphore::new(config.batch.max(1))),
+            background: Arc::new(Semaphore::new(config.background.max(1))),
+            config,
+            metrics,
+        }
+    }
+
+    pub fn config(&self) -> &PoolConfig {
+        &self.config
+    }
+
+    fn pool(&self, priority: Priority) -> &Arc<Semaphore> {
+        match priority {
+            Priority::Interactive => &self.interactive,
+            Priority::Batch => &self.batch,
+            Priority::Background => &self.background,
+        }
+    }
+
+    /// Free slots in a class's pool
+    pub fn available(&self, priority: Priority) -> usize {
+        self.pool(priority).available_permits()
+    }
+
+    /// Wait for a free slot in a class's pool; the slot is held until dropped
+    pub async fn acquire(&self, priority: Priority) -> PoolSlot {
+        let pool = self.pool(priority).clone();
+        if pool.available_permits() == 0 {
+            self.metrics
+                .increment_counter(&format!("priority.{}.waited", priority.as_str()), 1)
+                .await;
+        }
+        // The semaphores are never closed, so acquiring can't fail
+        let permit = pool.acquire_owned().await.expect("priority pool closed");
+        PoolSlot {
+            priority,
+            _permit: permit,
+        }
+    }
+
+    /// Run `work` once its class's pool has a free slot
+    pub async fn run<F: Future>(&self, priority: Priority, work: F) -> F::Output {
+        let _slot = self.acquire(priority).await;
+        work.await
+    }
+
+    /// Run CPU-bound `work` on the blocking thread pool, counted against
+    /// its class's slots
+    pub async fn spawn_blocking<F, R>(&self, priority: Priority, work: F) -> Result<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let slot = self.acquire(priority).await;
+        tokio::task::spawn_blocking(move || {
+            let _slot = slot;
+            work()
+        })
+        .await
+        .map_err(|e| anyhow!("{} task failed: {}", priority, e))
+    }
+}
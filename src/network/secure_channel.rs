@@ -0,0 +1,699 @@
+//! Encrypted, replay-protected messages between nodes.
+//!
+//! Sessions are set up with the Noise protocol's IK handshake
+//! (`Noise_IK_25519_ChaChaPoly_SHA256`, implemented by the `snow` crate)
+//! rather than a protocol of our own. Every node has an ed25519 identity
+//! key, and the identity keys of the peers it talks to are configured up
+//! front; the Noise static keys are their X25519 (Montgomery) forms. IK
+//! fits a single round trip: the initiator already knows the responder's
+//! key, sends its ephemeral key and its own static key encrypted in the
+//! opening [`Hello`], and the reply completes the exchange. The resulting
+//! keys are forward secret, and both sides are authenticated by their
+//! static keys, which the responder checks against the one configured for
+//! the peer named in the hello. Both node IDs are bound into the handshake
+//! as the Noise prologue.
+//!
+//! Noise leaves replay of the opening message to the application, and a new
+//! handshake replaces the session, so old hellos must not be accepted
+//! again. An opening hello carries its creation time as its encrypted
+//! payload and is refused once older than [`HANDSHAKE_MAX_AGE`] or if its
+//! ephemeral key was already accepted; a reply names the ephemeral key it
+//! answers and is refused unless that is the one the initiator is waiting
+//! on. What this doesn't protect against: a stolen identity key (it lets
+//! the holder impersonate that node, though not decrypt past sessions), and
+//! anyone able to read the configured peer keys learning who talks to whom.
+//!
+//! Messages are then sealed with ChaCha20-Poly1305 under the Noise
+//! transport keys. Each carries a sequence number, used in the nonce, which
+//! the receiver checks against a sliding [`REPLAY_WINDOW`] so a captured
+//! message can't be delivered twice. Noise caps a ciphertext at 64 KiB, so
+//! longer messages are sealed in chunks whose nonces number them and mark
+//! the last, which keeps chunks from being reordered or dropped. With a
+//! [`TrustManager`], only failures the handshake ties to a peer's key count
+//! against it. A hello or message that doesn't authenticate names a sender
+//! anyone could claim, so it is logged and refused but charged to no one.
+//!
+//! Over HTTP, a node-to-node route takes an [`Envelope`]: a hello, answered
+//! with the reply hello, or a sealed request, answered with a sealed reply.
+//! A reply carries the sequence number of the request it answers inside its
+//! ciphertext, so replies can't be swapped between requests.
+//! [`SecureClient`] shakes hands on a peer's first request.
+
+use anyhow::anyhow;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use snow::{Builder, HandshakeState, StatelessTransportState};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::network::trust::{TrustEvent, TrustManager};
+use crate::utils::errors::SecureChannelError;
+
+/// Sequence numbers this far behind the highest received are rejected
+pub const REPLAY_WINDOW: u64 = 64;
+
+/// Opening hellos older than this, or this far in the future, are refused
+pub const HANDSHAKE_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(60);
+
+const PROTOCOL: &[u8] = b"rose-forest-secure-channel-v3";
+
+const NOISE_PARAMS: &str = "Noise_IK_25519_ChaChaPoly_SHA256";
+
+/// Largest Noise message, and the authentication tag each one carries
+const NOISE_MAX_LEN: usize = 65535;
+const NOISE_TAG_LEN: usize = 16;
+
+/// Plaintext sealed per chunk
+const CHUNK_LEN: usize = NOISE_MAX_LEN - NOISE_TAG_LEN;
+
+/// A nonce holds the sequence number above 16 bits of chunk index, the top
+/// one of which marks the last chunk
+const MAX_CHUNKS: usize = 1 << 15;
+const LAST_CHUNK: u64 = 1 << 15;
+const MAX_SEQUENCE: u64 = 1 << 48;
+
+/// Room for an IK handshake message: two keys, a static key's tag and the
+/// payload with its tag
+const HANDSHAKE_BUFFER_LEN: usize = 256;
+
+/// Length of the X25519 ephemeral key that opens every handshake message
+const EPHEMERAL_KEY_LEN: usize = 32;
+
+/// A node's long-term signing identity
+pub struct NodeIdentity {
+    node_id: String,
+    signing_key: SigningKey,
+}
+
+impl std::fmt::Debug for NodeIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print key material
+        f.debug_struct("NodeIdentity")
+            .field("node_id", &self.node_id)
+            .finish_non_exhaustive()
+    }
+}
+
+fn random_signing_key() -> SigningKey {
+    let mut seed = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut seed);
+    SigningKey::from_bytes(&seed)
+}
+
+impl NodeIdentity {
+    pub fn new(node_id: &str, secret_key: &[u8; 32]) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            signing_key: SigningKey::from_bytes(secret_key),
+        }
+    }
+
+    /// An identity with a fresh random key
+    pub fn generate(node_id: &str) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            signing_key: random_signing_key(),
+        }
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// The key peers configure to recognise this node
+    pub fn public_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+}
+
+/// One side's half of the handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hello {
+    pub from: String,
+    pub to: String,
+    /// Base64 Noise handshake message
+    pub message: String,
+    /// In a reply, the base64 ephemeral key of the hello it answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_reply_to: Option<String>,
+}
+
+/// A message sealed for one peer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedMessage {
+    pub from: String,
+    pub sequence: u64,
+    /// Base64 ciphertext and tag of each chunk, concatenated
+    pub ciphertext: String,
+}
+
+/// The sealed request a reply answers: the peer that sent it and its
+/// sequence number
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplyTo {
+    pub peer: String,
+    pub sequence: u64,
+}
+
+/// Body of a request to a node-to-node route
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Envelope {
+    /// Opens a session; the route answers with its reply hello
+    Hello(Hello),
+    /// A request sealed under the session; the route answers sealed too
+    Sealed(SealedMessage),
+}
+
+/// Sequence numbers seen recently: the highest, and a bitmap of the
+/// [`REPLAY_WINDOW`] before it
+#[derive(Debug, Default)]
+struct ReplayWindow {
+    highest: u64,
+    seen: u64,
+}
+
+impl ReplayWindow {
+    fn check(&self, sequence: u64) -> Result<(), SecureChannelError> {
+        if sequence == 0 || sequence >= MAX_SEQUENCE {
+            return Err(SecureChannelError::Replay(sequence));
+        }
+        if sequence > self.highest {
+            return Ok(());
+        }
+        let behind = self.highest - sequence;
+        if behind >= REPLAY_WINDOW || self.seen & (1 << behind) != 0 {
+            return Err(SecureChannelError::Replay(sequence));
+        }
+        Ok(())
+    }
+
+    /// Mark a sequence number seen; only once its message authenticated
+    fn commit(&mut self, sequence: u64) {
+        if sequence > self.highest {
+            let shift = sequence - self.highest;
+            self.seen = if shift >= REPLAY_WINDOW {
+                0
+            } else {
+                self.seen << shift
+            };
+            self.seen |= 1;
+            self.highest = sequence;
+        } else {
+            self.seen |= 1 << (self.highest - sequence);
+        }
+    }
+}
+
+struct Session {
+    transport: StatelessTransportState,
+    next_sequence: u64,
+    window: ReplayWindow,
+}
+
+/// A handshake this node started, waiting for the peer's reply
+struct PendingHandshake {
+    state: HandshakeState,
+    ephemeral_key: String,
+}
+
+/// Sessions with every peer this node has shaken hands with
+pub struct SecureChannels {
+    identity: NodeIdentity,
+    peer_keys: RwLock<HashMap<String, VerifyingKey>>,
+    pending: RwLock<HashMap<String, PendingHandshake>>,
+    /// Ephemeral keys of opening hellos accepted within the last
+    /// [`HANDSHAKE_MAX_AGE`], with their timestamps
+    accepted: RwLock<HashMap<Vec<u8>, i64>>,
+    sessions: RwLock<HashMap<String, Session>>,
+    trust: Option<Arc<TrustManager>>,
+}
+
+impl std::fmt::Debug for SecureChannels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecureChannels")
+            .field("identity", &self.identity)
+            .finish_non_exhaustive()
+    }
+}
+
+fn chunk_nonce(sequence: u64, chunk: usize, last: bool) -> u64 {
+    let last = if last { LAST_CHUNK } else { 0 };
+    (sequence << 16) | last | chunk as u64
+}
+
+/// Both node IDs, bound into the handshake so neither can be swapped
+fn prologue(initiator: &str, responder: &str) -> Vec<u8> {
+    let mut prologue = PROTOCOL.to_vec();
+    for node in [initiator, responder] {
+        prologue.extend_from_slice(&(node.len() as u32).to_be_bytes());
+        prologue.extend_from_slice(node.as_bytes());
+    }
+    prologue
+}
+
+/// X25519 public key of an ed25519 identity; its secret is the signing
+/// key's scalar
+fn static_key(key: &VerifyingKey) -> [u8; 32] {
+    key.to_montgomery().to_bytes()
+}
+
+fn noise_builder<'a>() -> Builder<'a> {
+    Builder::new(NOISE_PARAMS.parse().expect("valid Noise parameters"))
+}
+
+fn handshake_error(error: snow::Error) -> SecureChannelError {
+    SecureChannelError::Handshake(error.to_string())
+}
+
+impl SecureChannels {
+    pub fn new(identity: NodeIdentity) -> Self {
+        Self {
+            identity,
+            peer_keys: RwLock::new(HashMap::new()),
+            pending: RwLock::new(HashMap::new()),
+            accepted: RwLock::new(HashMap::new()),
+            sessions: RwLock::new(HashMap::new()),
+            trust: None,
+        }
+    }
+
+    /// Report handshake failures against the peer whose key they were
+    /// authenticated with
+    pub fn with_trust(mut self, trust: Arc<TrustManager>) -> Self {
+        self.trust = Some(trust);
+        self
+    }
+
+    pub fn identity(&self) -> &NodeIdentity {
+        &self.identity
+    }
+
+    /// Accept handshakes from `peer_id` authenticated with `key`
+    pub async fn add_peer(&self, peer_id: &str, key: VerifyingKey) {
+        self.peer_keys
+            .write()
+            .await
+            .insert(peer_id.to_string(), key);
+    }
+
+    pub async fn has_session(&self, peer_id: &str) -> bool {
+        self.sessions.read().await.contains_key(peer_id)
+    }
+
+    /// Drop the session with a peer, so the next exchange shakes hands again
+    pub async fn end_session(&self, peer_id: &str) {
+        self.sessions.write().await.remove(peer_id);
+    }
+
+    async fn report(&self, peer_id: &str, event: TrustEvent) {
+        if let Some(trust) = &self.trust {
+            trust.record(peer_id, event).await;
+        }
+    }
+
+    /// Check that a peer's hello is meant for this node and comes from a
+    /// configured peer, returning its decoded handshake message and the
+    /// peer's identity key. Nothing is authenticated yet, so failures here
+    /// aren't reported.
+    async fn check_hello(
+        &self,
+        hello: &Hello,
+    ) -> Result<(Vec<u8>, VerifyingKey), SecureChannelError> {
+        if hello.to != self.identity.node_id {
+            return Err(SecureChannelError::Handshake(format!(
+                "hello addressed to {}",
+                hello.to
+            )));
+        }
+        let identity = self
+            .peer_keys
+            .read()
+            .await
+            .get(&hello.from)
+            .copied()
+            .ok_or_else(|| SecureChannelError::UnknownPeer(hello.from.clone()))?;
+
+        match STANDARD.decode(&hello.message) {
+            Ok(message) if message.len() > EPHEMERAL_KEY_LEN => Ok((message, identity)),
+            _ => Err(SecureChannelError::Handshake(
+                "malformed handshake message".into(),
+            )),
+        }
+    }
+
+    async fn establish(
+        &self,
+        peer_id: &str,
+        state: HandshakeState,
+    ) -> Result<(), SecureChannelError> {
+        let transport = state
+            .into_stateless_transport_mode()
+            .map_err(handshake_error)?;
+        self.sessions.write().await.insert(
+            peer_id.to_string(),
+            Session {
+                transport,
+                next_sequence: 1,
+                window: ReplayWindow::default(),
+            },
+        );
+        info!("Secure session established with {}", peer_id);
+        Ok(())
+    }
+
+    /// Start a handshake with a configured peer
+    pub async fn initiate(&self, peer_id: &str) -> Result<Hello, SecureChannelError> {
+        let peer_key = self
+            .peer_keys
+            .read()
+            .await
+            .get(peer_id)
+            .copied()
+            .ok_or_else(|| SecureChannelError::UnknownPeer(peer_id.to_string()))?;
+        let secret = self.identity.signing_key.to_scalar_bytes();
+        let prologue = prologue(&self.identity.node_id, peer_id);
+        let remote = static_key(&peer_key);
+        let mut state = noise_builder()
+            .local_private_key(&secret)
+            .remote_public_key(&remote)
+            .prologue(&prologue)
+            .build_initiator()
+            .map_err(handshake_error)?;
+
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let mut message = vec![0u8; HANDSHAKE_BUFFER_LEN];
+        let len = state
+            .write_message(&timestamp.to_be_bytes(), &mut message)
+            .map_err(handshake_error)?;
+        message.truncate(len);
+
+        let ephemeral_key = STANDARD.encode(&message[..EPHEMERAL_KEY_LEN]);
+        self.pending.write().await.insert(
+            peer_id.to_string(),
+            PendingHandshake {
+                state,
+                ephemeral_key,
+            },
+        );
+        Ok(Hello {
+            from: self.identity.node_id.clone(),
+            to: peer_id.to_string(),
+            message: STANDARD.encode(message),
+            in_reply_to: None,
+        })
+    }
+
+    /// Answer a peer's handshake. The session is usable once this returns;
+    /// the reply lets the peer derive the same keys.
+    /// Refuses hellos that are stale or were already accepted, so a
+    /// captured hello can't tear down the session it set up.
+    pub async fn accept(&self, hello: &Hello) -> Result<Hello, SecureChannelError> {
+        if hello.in_reply_to.is_some() {
+            return Err(SecureChannelError::Handshake(
+                "a reply can't open a handshake".into(),
+            ));
+        }
+        let (message, identity) = self.check_hello(hello).await?;
+        let secret = self.identity.signing_key.to_scalar_bytes();
+        let prologue = prologue(&hello.from, &self.identity.node_id);
+        let mut state = noise_builder()
+            .local_private_key(&secret)
+            .prologue(&prologue)
+            .build_responder()
+            .map_err(handshake_error)?;
+
+        // The payload only decrypts for the holder of the static key it was
+        // sent with, which must be the one configured for the sender
+        let mut payload = vec![0u8; message.len()];
+        let authenticated = match state.read_message(&message, &mut payload) {
+            Ok(len) => {
+                (state.get_remote_static() == Some(&static_key(&identity)[..])).then_some(len)
+            }
+            Err(_) => None,
+        };
+        // Anyone can put a peer's name on a hello, so one that fails here
+        // says nothing about that peer
+        let Some(len) = authenticated else {
+            warn!("Rejected a hello claiming to be from {}", hello.from);
+            return Err(SecureChannelError::BadSignature(hello.from.clone()));
+        };
+        // From here the hello was sent by the holder of the peer's key
+        let Ok(timestamp) = <[u8; 8]>::try_from(&payload[..len]).map(i64::from_be_bytes) else {
+            self.report(&hello.from, TrustEvent::ProtocolViolation)
+                .await;
+            return Err(SecureChannelError::Handshake(
+                "hello carries no timestamp".into(),
+            ));
+        };
+        let ephemeral = &message[..EPHEMERAL_KEY_LEN];
+        self.check_fresh(&hello.from, ephemeral, timestamp).await?;
+
+        let mut reply = vec![0u8; HANDSHAKE_BUFFER_LEN];
+        let len = state
+            .write_message(&[], &mut reply)
+            .map_err(handshake_error)?;
+        reply.truncate(len);
+        self.establish(&hello.from, state).await?;
+        Ok(Hello {
+            from: self.identity.node_id.clone(),
+            to: hello.from.clone(),
+            message: STANDARD.encode(reply),
+            in_reply_to: Some(STANDARD.encode(ephemeral)),
+        })
+    }
+
+    /// Record an opening hello's ephemeral key, refusing it if it is too old
+    /// or was seen before. Anyone can replay a captured hello, so a refusal
+    /// isn't reported against the peer.
+    async fn check_fresh(
+        &self,
+        peer_id: &str,
+        ephemeral: &[u8],
+        timestamp: i64,
+    ) -> Result<(), SecureChannelError> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let max_age = HANDSHAKE_MAX_AGE.as_millis() as u64;
+        let mut accepted = self.accepted.write().await;
+        accepted.retain(|_, accepted_at| now.abs_diff(*accepted_at) <= max_age);
+        let fresh = now.abs_diff(timestamp) <= max_age && !accepted.contains_key(ephemeral);
+        if !fresh {
+            return Err(SecureChannelError::StaleHandshake(peer_id.to_string()));
+        }
+        accepted.insert(ephemeral.to_vec(), timestamp);
+        Ok(())
+    }
+
+    /// Finish a handshake this node started with the peer's reply
+    pub async fn complete(&self, reply: &Hello) -> Result<(), SecureChannelError> {
+        let (message, _) = self.check_hello(reply).await?;
+        let mut pending = self.pending.write().await;
+        let answers_pending = match (pending.get(&reply.from), &reply.in_reply_to) {
+            (Some(handshake), Some(answered)) => handshake.ephemeral_key == *answered,
+            _ => false,
+        };
+        if !answers_pending {
+            return Err(SecureChannelError::StaleHandshake(reply.from.clone()));
+        }
+        let mut state = pending
+            .remove(&reply.from)
+            .expect("checked that a handshake is pending")
+            .state;
+        drop(pending);
+
+        // Only the holder of the peer's static key can produce a reply that
+        // decrypts
+        let mut payload = vec![0u8; message.len()];
+        if state.read_message(&message, &mut payload).is_err() {
+            return Err(SecureChannelError::BadSignature(reply.from.clone()));
+        }
+        self.establish(&reply.from, state).await
+    }
+
+    /// Encrypt a message for a peer this node has a session with
+    pub async fn seal(
+        &self,
+        peer_id: &str,
+        plaintext: &[u8],
+    ) -> Result<SealedMessage, SecureChannelError> {
+        if plaintext.len() > CHUNK_LEN * MAX_CHUNKS {
+            return Err(SecureChannelError::TooLarge(plaintext.len()));
+        }
+        let mut sessions = self.sessions.write().await;
+        let session = sessions
+            .get_mut(peer_id)
+            .ok_or_else(|| SecureChannelError::NoSession(peer_id.to_string()))?;
+        let sequence = session.next_sequence;
+        if sequence >= MAX_SEQUENCE {
+            // Nonces are used up; the next exchange shakes hands again
+            sessions.remove(peer_id);
+            return Err(SecureChannelError::NoSession(peer_id.to_string()));
+        }
+        session.next_sequence += 1;
+
+        let chunks: Vec<&[u8]> = if plaintext.is_empty() {
+            vec![plaintext]
+        } else {
+            plaintext.chunks(CHUNK_LEN).collect()
+        };
+        let mut ciphertext = Vec::with_capacity(plaintext.len() + chunks.len() * NOISE_TAG_LEN);
+        let mut buffer = vec![0u8; NOISE_MAX_LEN];
+        for (index, chunk) in chunks.iter().enumerate() {
+            let nonce = chunk_nonce(sequence, index, index + 1 == chunks.len());
+            let len = session
+                .transport
+                .write_message(nonce, chunk, &mut buffer)
+                .map_err(|_| SecureChannelError::Decrypt(peer_id.to_string()))?;
+            ciphertext.extend_from_slice(&buffer[..len]);
+        }
+        Ok(SealedMessage {
+            from: self.identity.node_id.clone(),
+            sequence,
+            ciphertext: STANDARD.encode(ciphertext),
+        })
+    }
+
+    /// Decrypt a peer's message, rejecting replays and forgeries. A rejected
+    /// message didn't authenticate, so it is only logged: charging it to the
+    /// peer it names would let anyone lower that peer's trust.
+    pub async fn open(&self, message: &SealedMessage) -> Result<Vec<u8>, SecureChannelError> {
+        let opened = {
+            let mut sessions = self.sessions.write().await;
+            let session = sessions
+                .get_mut(&message.from)
+                .ok_or_else(|| SecureChannelError::NoSession(message.from.clone()))?;
+            session.window.check(message.sequence).and_then(|()| {
+                let decrypt_error = || SecureChannelError::Decrypt(message.from.clone());
+                let ciphertext = STANDARD
+                    .decode(&message.ciphertext)
+                    .map_err(|_| decrypt_error())?;
+                let chunks: Vec<&[u8]> = ciphertext.chunks(NOISE_MAX_LEN).collect();
+                if chunks.is_empty() || chunks.len() > MAX_CHUNKS {
+                    return Err(decrypt_error());
+                }
+                let mut plaintext = Vec::with_capacity(ciphertext.len());
+                let mut buffer = vec![0u8; NOISE_MAX_LEN];
+                for (index, chunk) in chunks.iter().enumerate() {
+                    let nonce = chunk_nonce(message.sequence, index, index + 1 == chunks.len());
+                    let len = session
+                        .transport
+                        .read_message(nonce, chunk, &mut buffer)
+                        .map_err(|_| decrypt_error())?;
+                    plaintext.extend_from_slice(&buffer[..len]);
+                }
+                session.window.commit(message.sequence);
+                Ok(plaintext)
+            })
+        };
+        if let Err(e) = &opened {
+            warn!(
+                "Rejected message claiming to be from {}: {}",
+                message.from, e
+            );
+        }
+        opened
+    }
+
+    /// Seal the reply to a request opened with [`open`](Self::open), bound
+    /// to that request's sequence number
+    pub async fn seal_reply(
+        &self,
+        request: &ReplyTo,
+        plaintext: &[u8],
+    ) -> Result<SealedMessage, SecureChannelError> {
+        let mut bound = Vec::with_capacity(8 + plaintext.len());
+        bound.extend_from_slice(&request.sequence.to_be_bytes());
+        bound.extend_from_slice(plaintext);
+        self.seal(&request.peer, &bound).await
+    }
+
+    /// Open a reply sealed with [`seal_reply`](Self::seal_reply), refusing
+    /// it unless it answers the request sealed with `request_sequence`
+    pub async fn open_reply(
+        &self,
+        reply: &SealedMessage,
+        request_sequence: u64,
+    ) -> Result<Vec<u8>, SecureChannelError> {
+        let plaintext = self.open(reply).await?;
+        match plaintext.split_first_chunk::<8>() {
+            Some((answered, body)) if u64::from_be_bytes(*answered) == request_sequence => {
+                Ok(body.to_vec())
+            }
+            _ => {
+                warn!(
+                    "Reply from {} doesn't answer request {}",
+                    reply.from, request_sequence
+                );
+                Err(SecureChannelError::MismatchedReply(reply.from.clone()))
+            }
+        }
+    }
+}
+
+/// Sends requests to peers' node-to-node routes through [`SecureChannels`]
+#[derive(Debug, Clone)]
+pub struct SecureClient {
+    channels: Arc<SecureChannels>,
+}
+
+impl SecureClient {
+    pub fn new(channels: Arc<SecureChannels>) -> Self {
+        Self { channels }
+    }
+
+    pub fn channels(&self) -> &Arc<SecureChannels> {
+        &self.channels
+    }
+
+    /// Seal `body` for `peer_id`, post it with a request from `request`, and
+    /// open the sealed reply. Without a session, a hello is posted first the
+    /// same way.
+    pub async fn post<F>(&self, peer_id: &str, request: F, body: &[u8]) -> anyhow::Result<Vec<u8>>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        if !self.channels.has_session(peer_id).await {
+            let hello = self.channels.initiate(peer_id).await?;
+            let response = request().json(&Envelope::Hello(hello)).send().await?;
+            if !response.status().is_success() {
+                return Err(anyhow!(
+                    "Peer {} refused the handshake: {} {}",
+                    peer_id,
+                    response.status(),
+                    response.text().await.unwrap_or_default()
+                ));
+            }
+            self.channels.complete(&response.json().await?).await?;
+        }
+
+        let sealed = self.channels.seal(peer_id, body).await?;
+        let sequence = sealed.sequence;
+        let response = request().json(&Envelope::Sealed(sealed)).send().await?;
+        if !response.status().is_success() {
+            // The peer may have restarted and lost the session
+            if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+                self.channels.end_session(peer_id).await;
+            }
+            return Err(anyhow!(
+                "Peer {} rejected the request: {} {}",
+                peer_id,
+                response.status(),
+                response.text().await.unwrap_or_default()
+            ));
+        }
+        let reply: SealedMessage = response.json().await?;
+        if reply.from != peer_id {
+            return Err(anyhow!(
+                "Reply to a request for {} came from {}",
+                peer_id,
+                reply.from
+            ));
+        }
+        Ok(self.channels.open_reply(&reply, sequence).await?)
+    }
+}
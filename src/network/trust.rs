@@ -0,0 +1,288 @@
+//! Peer trust scores and blocklist.
+//!
+//! Every peer starts fully trusted. Protocol violations and failed signature
+//! checks cost trust outright; errors only do once they make up too much of
+//! the peer's recent requests, and successful requests slowly earn trust
+//! back. A peer whose score drops below the quarantine threshold is left out
+//! for a while and comes back on probation. Operators can also block a peer
+//! until they unblock it.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::core::metrics::MetricsCollector;
+
+/// Something a peer did that bears on its trust
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustEvent {
+    /// A request completed as expected
+    Success,
+    /// A request failed
+    Error,
+    /// A message the protocol doesn't allow, e.g. a stale lease or epoch
+    ProtocolViolation,
+    /// A signed message didn't verify
+    SignatureFailure,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrustConfig {
+    pub protocol_violation_penalty: f32,
+    pub signature_failure_penalty: f32,
+    /// Cost of each error while the error rate is excessive
+    pub error_penalty: f32,
+    /// Trust regained per successful request
+    pub success_reward: f32,
+    /// Recent requests the error rate is computed over
+    pub error_window: usize,
+    /// Requests in the window before the error rate counts
+    pub min_requests: usize,
+    /// Error rate above which errors cost trust
+    pub max_error_rate: f32,
+    /// Score below which a peer is quarantined
+    pub quarantine_below: f32,
+    pub quarantine_for: Duration,
+    /// Score a peer resumes with after quarantine
+    pub probation_score: f32,
+}
+
+impl Default for TrustConfig {
+    fn default() -> Self {
+        Self {
+            protocol_violation_penalty: 0.25,
+            signature_failure_penalty: 0.5,
+            error_penalty: 0.05,
+            success_reward: 0.01,
+            error_window: 50,
+            min_requests: 10,
+            max_error_rate: 0.5,
+            quarantine_below: 0.3,
+            quarantine_for: Duration::from_secs(600),
+            probation_score: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TrustStatus {
+    Trusted,
+    Quarantined { until: DateTime<Utc> },
+    Blocked { reason: String },
+}
+
+/// What is known about a peer's behaviour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerTrust {
+    pub peer_id: String,
+    /// `[0, 1]`, 1 being fully trusted
+    pub score: f32,
+    pub status: TrustStatus,
+    pub protocol_violations: u64,
+    pub signature_failures: u64,
+    /// Error rate over the recent requests
+    pub error_rate: f32,
+    pub quarantines: u64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct PeerRecord {
+    trust: PeerTrust,
+    /// Recent request outcomes, true for errors
+    outcomes: VecDeque<bool>,
+}
+
+impl PeerRecord {
+    fn new(peer_id: &str) -> Self {
+        Self {
+            trust: PeerTrust {
+                peer_id: peer_id.to_string(),
+                score: 1.0,
+                status: TrustStatus::Trusted,
+                protocol_violations: 0,
+                signature_failures: 0,
+                error_rate: 0.0,
+                quarantines: 0,
+                updated_at: Utc::now(),
+            },
+            outcomes: VecDeque::new(),
+        }
+    }
+
+    /// Let a peer whose quarantine is over back in on probation
+    fn release_if_served(&mut self, config: &TrustConfig, now: DateTime<Utc>) {
+        if let TrustStatus::Quarantined { until } = self.trust.status {
+            if until <= now {
+                self.trust.status = TrustStatus::Trusted;
+                self.trust.score = config.probation_score;
+                self.outcomes.clear();
+                self.trust.error_rate = 0.0;
+                info!("Peer {} released from quarantine", self.trust.peer_id);
+            }
+        }
+    }
+}
+
+/// Trust scores of the peers this node talks to
+#[derive(Debug)]
+pub struct TrustManager {
+    config: TrustConfig,
+    peers: RwLock<HashMap<String, PeerRecord>>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl TrustManager {
+    pub fn new(metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            config: TrustConfig::default(),
+            peers: RwLock::new(HashMap::new()),
+            metrics,
+        }
+    }
+
+    pub fn with_config(mut self, config: TrustConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Record what a peer did and return its updated trust
+    pub async fn record(&self, peer_id: &str, event: TrustEvent) -> PeerTrust {
+        let now = Utc::now();
+        let config = &self.config;
+        let (trust, quarantined) = {
+            let mut peers = self.peers.write().await;
+            let record = peers
+                .entry(peer_id.to_string())
+                .or_insert_with(|| PeerRecord::new(peer_id));
+            record.release_if_served(config, now);
+
+            let penalty = match event {
+                TrustEvent::Success | TrustEvent::Error => {
+                    record.outcomes.push_back(event == TrustEvent::Error);
+                    if record.outcomes.len() > config.error_window {
+                        record.outcomes.pop_front();
+                    }
+                    let errors = record.outcomes.iter().filter(|e| **e).count();
+                    record.trust.error_rate = errors as f32 / record.outcomes.len() as f32;
+                    match event {
+                        TrustEvent::Error
+                            if record.outcomes.len() >= config.min_requests
+                                && record.trust.error_rate > config.max_error_rate =>
+                        {
+                            config.error_penalty
+                        }
+                        TrustEvent::Error => 0.0,
+                        _ => -config.success_reward,
+                    }
+                }
+                TrustEvent::ProtocolViolation => {
+                    record.trust.protocol_violations += 1;
+                    config.protocol_violation_penalty
+                }
+                TrustEvent::SignatureFailure => {
+                    record.trust.signature_failures += 1;
+                    config.signature_failure_penalty
+                }
+            };
+            record.trust.score = (record.trust.score - penalty).clamp(0.0, 1.0);
+            record.trust.updated_at = now;
+
+            let quarantined = record.trust.status == TrustStatus::Trusted
+                && record.trust.score < config.quarantine_below;
+            if quarantined {
+                let until = now
+                    + chrono::Duration::from_std(config.quarantine_for)
+                        .unwrap_or_else(|_| chrono::Duration::zero());
+                record.trust.status = TrustStatus::Quarantined { until };
+                record.trust.quarantines += 1;
+                warn!(
+                    "Quarantined peer {} with trust {:.2} until {}",
+                    peer_id, record.trust.score, until
+                );
+            }
+            (record.trust.clone(), quarantined)
+        };
+        if quarantined {
+            self.metrics.increment_counter("trust.quarantined", 1).await;
+        }
+        trust
+    }
+
+    /// Whether requests to and from the peer should go ahead. Unknown peers
+    /// are allowed.
+    pub async fn is_allowed(&self, peer_id: &str) -> bool {
+        let now = Utc::now();
+        let mut peers = self.peers.write().await;
+        match peers.get_mut(peer_id) {
+            Some(record) => {
+                record.release_if_served(&self.config, now);
+                record.trust.status == TrustStatus::Trusted
+            }
+            None => true,
+        }
+    }
+
+    pub async fn peer(&self, peer_id: &str) -> Option<PeerTrust> {
+        let now = Utc::now();
+        let mut peers = self.peers.write().await;
+        let record = peers.get_mut(peer_id)?;
+        record.release_if_served(&self.config, now);
+        Some(record.trust.clone())
+    }
+
+    /// Every known peer, least trusted first
+    pub async fn peers(&self) -> Vec<PeerTrust> {
+        let now = Utc::now();
+        let mut peers = self.peers.write().await;
+        let mut trust: Vec<PeerTrust> = peers
+            .values_mut()
+            .map(|record| {
+                record.release_if_served(&self.config, now);
+                record.trust.clone()
+            })
+            .collect();
+        trust.sort_by(|a, b| {
+            a.score
+                .total_cmp(&b.score)
+                .then_with(|| a.peer_id.cmp(&b.peer_id))
+        });
+        trust
+    }
+
+    /// Block the peer until it is unblocked, known to this node or not
+    pub async fn block(&self, peer_id: &str, reason: &str) -> PeerTrust {
+        let trust = {
+            let mut peers = self.peers.write().await;
+            let record = peers
+                .entry(peer_id.to_string())
+                .or_insert_with(|| PeerRecord::new(peer_id));
+            record.trust.status = TrustStatus::Blocked {
+                reason: reason.to_string(),
+            };
+            record.trust.updated_at = Utc::now();
+            record.trust.clone()
+        };
+        warn!("Blocked peer {}: {}", peer_id, reason);
+        self.metrics.increment_counter("trust.blocked", 1).await;
+        trust
+    }
+
+    /// Lift a block or quarantine. The peer resumes on probation, or with
+    /// its score if that is higher. Returns None for unknown peers.
+    pub async fn unblock(&self, peer_id: &str) -> Option<PeerTrust> {
+        let mut peers = self.peers.write().await;
+        let record = peers.get_mut(peer_id)?;
+        record.trust.status = TrustStatus::Trusted;
+        record.trust.score = record.trust.score.max(self.config.probation_score);
+        record.trust.updated_at = Utc::now();
+        info!("Unblocked peer {}", peer_id);
+        Some(record.trust.clone())
+    }
+}
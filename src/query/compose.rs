@@ -0,0 +1,106 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::core::vector::Vector;
+
+/// How composed terms are combined
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComposeOp {
+    /// Sum of all terms
+    #[default]
+    Add,
+    /// First term minus the sum of the rest
+    Subtract,
+    /// Mean of all terms
+    Average,
+    /// Sum of each term times its weight, e.g. weights `1, -1, 1` for
+    /// `king - man + woman`
+    Weighted,
+}
+
+/// One operand: a stored vector by ID or literal values
+///
+/// ```json
+/// {"id": "3f0c...", "weight": -1.0}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComposeTerm {
+    /// Vector stored in the target shard
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+
+    /// Literal values, used instead of `id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector: Option<Vec<f32>>,
+
+    /// Multiplier for `weighted` composition; ignored otherwise
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+impl ComposeTerm {
+    pub fn id(id: Uuid) -> Self {
+        Self {
+            id: Some(id),
+            vector: None,
+            weight: default_weight(),
+        }
+    }
+
+    pub fn vector(values: Vec<f32>) -> Self {
+        Self {
+            id: None,
+            vector: Some(values),
+            weight: default_weight(),
+        }
+    }
+
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+/// Combine resolved `(vector, weight)` operands. All operands must have the
+/// same dimensions, and the result must be finite.
+pub fn compose(op: ComposeOp, terms: &[(Vector, f32)]) -> Result<Vector> {
+    let Some((first, _)) = terms.first() else {
+        return Err(anyhow!("Composition needs at least one term"));
+    };
+    let dimensions = first.dimensions;
+    if let Some((mismatched, _)) = terms.iter().find(|(v, _)| v.dimensions != dimensions) {
+        return Err(anyhow!(
+            "Term dimensions differ: expected {}, got {}",
+            dimensions,
+            mismatched.dimensions
+        ));
+    }
+
+    let mut values = vec![0.0f32; dimensions];
+    for (i, (vector, weight)) in terms.iter().enumerate() {
+        let factor = match op {
+            ComposeOp::Add | ComposeOp::Average => 1.0,
+            ComposeOp::Subtract if i == 0 => 1.0,
+            ComposeOp::Subtract => -1.0,
+            ComposeOp::Weighted => *weight,
+        };
+        for (sum, value) in values.iter_mut().zip(&vector.values) {
+            *sum += factor * value;
+        }
+    }
+    if op == ComposeOp::Average {
+        let n = terms.len() as f32;
+        values.iter_mut().for_each(|v| *v /= n);
+    }
+
+    if values.iter().any(|v| !v.is_finite()) {
+        return Err(anyhow!("Composed vector has non-finite values"));
+    }
+    Ok(Vector::new(values))
+}
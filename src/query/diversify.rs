@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::vector::Vector;
+use crate::sharding::vector_index::{DistanceMetric, SearchResult};
+
+/// Candidates fetched per requested result when diversifying
+const CANDIDATE_MULTIPLIER: usize = 4;
+
+/// Post-processing that keeps results from being dominated by
+/// near-duplicates of a single document
+///
+/// ```json
+/// {"group_by": "doc_id", "group_size": 2, "mmr": {"lambda": 0.7}}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Diversification {
+    /// Metadata field whose values define groups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_by: Option<String>,
+
+    /// Most results kept per group; defaults to 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_size: Option<usize>,
+
+    /// Maximal marginal relevance re-ranking
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mmr: Option<MmrOptions>,
+}
+
+/// Maximal marginal relevance settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MmrOptions {
+    /// Trade-off between relevance (1.0) and diversity (0.0)
+    #[serde(default = "default_lambda")]
+    pub lambda: f32,
+
+    /// Candidates to re-rank; defaults to a multiple of the limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetch_k: Option<usize>,
+}
+
+fn default_lambda() -> f32 {
+    0.5
+}
+
+impl Diversification {
+    /// Whether any post-processing was requested
+    pub fn is_enabled(&self) -> bool {
+        self.group_by.is_some() || self.mmr.is_some()
+    }
+
+    /// How many candidates to fetch so `limit` results survive
+    pub fn candidate_limit(&self, limit: usize) -> usize {
+        let fetch_k = self.mmr.as_ref().and_then(|m| m.fetch_k).unwrap_or(0);
+        if self.is_enabled() {
+            fetch_k.max(limit * CANDIDATE_MULTIPLIER)
+        } else {
+            limit
+        }
+    }
+
+    /// Select up to `limit` results from candidates ordered best first.
+    ///
+    /// Selection is greedy: each step takes the candidate with the best MMR
+    /// score (or simply the next best one without MMR) whose group still has
+    /// room, so the output stays ordered by how it was chosen.
+    pub fn apply(
+        &self,
+        candidates: Vec<SearchResult>,
+        metric: DistanceMetric,
+        limit: usize,
+    ) -> Vec<SearchResult> {
+        if !self.is_enabled() {
+            let mut candidates = candidates;
+            candidates.truncate(limit);
+            return candidates;
+        }
+
+        let group_size = self.group_size.unwrap_or(1).max(1);
+        let lambda = self.mmr.as_ref().map(|m| m.lambda.clamp(0.0, 1.0));
+        // Scores are distances when lower is better; flip them so higher is
+        // always more relevant or more similar
+        let similarity = |a: &Vector, b: &Vector| {
+            let d = metric.calculate(a, b);
+            if metric.is_lower_better() {
+                -d
+            } else {
+                d
+            }
+        };
+        let relevance: Vec<f32> = candidates
+            .iter()
+            .map(|c| {
+                if metric.is_lower_better() {
+                    -c.score
+                } else {
+                    c.score
+                }
+            })
+            .collect();
+
+        let mut remaining: Vec<usize> = (0..candidates.len()).collect();
+        let mut selected: Vec<usize> = Vec::new();
+        let mut group_counts: HashMap<&str, usize> = HashMap::new();
+
+        let group_of = |i: usize| {
+            self.group_by.as_ref().map(|field| {
+                candidates[i]
+                    .metadata
+                    .as_ref()
+                    .and_then(|m| m.get(field))
+                    .map(String::as_str)
+            })
+        };
+
+        while selected.len() < limit {
+            let eligible = remaining
+                .iter()
+                .enumerate()
+                .filter(|(_, &i)| match group_of(i) {
+                    // Results without the field each form their own group
+                    Some(Some(group)) => group_counts.get(group).copied().unwrap_or(0) < group_size,
+                    _ => true,
+                });
+
+            let best = match lambda {
+                None => eligible.map(|(pos, _)| pos).next(),
+                Some(lambda) => eligible
+                    .map(|(pos, &i)| {
+                        let redundancy = selected
+                            .iter()
+                            .map(|&s| similarity(&candidates[i].vector, &candidates[s].vector))
+                            .fold(None, |max: Option<f32>, s| {
+                                Some(max.map_or(s, |m| m.max(s)))
+                            })
+                            .unwrap_or(0.0);
+                        (pos, lambda * relevance[i] - (1.0 - lambda) * redundancy)
+                    })
+                    .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+                    .map(|(pos, _)| pos),
+            };
+            let Some(pos) = best else {
+                break;
+            };
+
+            let chosen = remaining.remove(pos);
+            if let Some(Some(group)) = group_of(chosen) {
+                *group_counts.entry(group).or_default() += 1;
+            }
+            selected.push(chosen);
+        }
+
+        let mut candidates: Vec<Option<SearchResult>> = candidates.into_iter().map(Some).collect();
+        selected
+            .into_iter()
+            .filter_map(|i| candidates[i].take())
+            .collect()
+    }
+}
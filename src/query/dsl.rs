@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A boolean filter expression
+///
+/// Serialized as an externally tagged object, e.g.
+///
+/// ```json
+/// {"and": [
+///     {"field": {"key": "category", "op": "eq", "value": "product"}},
+///     {"not": {"field": {"key": "price", "op": "gt", "value": 100}}},
+///     {"similar": {"vector": [0.1, 0.2, 0.3], "max_distance": 0.5}}
+/// ]}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryExpr {
+    /// Every child must match
+    And(Vec<QueryExpr>),
+
+    /// At least one child must match
+    Or(Vec<QueryExpr>),
+
+    /// The child must not match
+    Not(Box<QueryExpr>),
+
+    /// Predicate over a metadata field
+    Field(FieldPredicate),
+
+    /// Candidate must lie within a distance of a reference vector
+    Similar(SimilarityClause),
+}
+
+/// Comparison applied to a metadata field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PredicateOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+    Exists,
+}
+
+/// Predicate over a single metadata field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldPredicate {
+    /// Metadata key to test
+    pub key: String,
+
+    /// Comparison to apply
+    pub op: PredicateOp,
+
+    /// Operand; a scalar for comparisons, an array for `in`, omitted for `exists`
+    #[serde(default)]
+    pub value: Value,
+}
+
+/// Restricts candidates to those near a reference vector
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityClause {
+    /// Reference vector, using the index's dimensions
+    pub vector: Vec<f32>,
+
+    /// Largest distance (in the index's metric) a candidate may have; under
+    /// inner product, the negated dot product
+    pub max_distance: f32,
+}
+
+impl QueryExpr {
+    /// Convenience constructor for a field predicate
+    pub fn field(key: &str, op: PredicateOp, value: Value) -> Self {
+        QueryExpr::Field(FieldPredicate {
+            key: key.to_string(),
+            op,
+            value,
+        })
+    }
+
+    /// An equivalent expression in a normal form, so filters written
+    /// differently but meaning the same compare equal: nested `and`/`or`
+    /// are flattened, their children sorted and deduplicated, single-child
+    /// groups unwrapped and double negations dropped
+    pub fn canonical(&self) -> QueryExpr {
+        match self {
+            QueryExpr::And(children) => Self::canonical_group(children, true),
+            QueryExpr::Or(children) => Self::canonical_group(children, false),
+            QueryExpr::Not(child) => match child.canonical() {
+                QueryExpr::Not(inner) => *inner,
+                child => QueryExpr::Not(Box::new(child)),
+            },
+            QueryExpr::Field(_) | QueryExpr::Similar(_) => self.clone(),
+        }
+    }
+
+    fn canonical_group(children: &[QueryExpr], and: bool) -> QueryExpr {
+        let mut flat = Vec::with_capacity(children.len());
+        for child in children.iter().map(QueryExpr::canonical) {
+            match child {
+                QueryExpr::And(grandchildren) if and => flat.extend(grandchildren),
+                QueryExpr::Or(grandchildren) if !and => flat.extend(grandchildren),
+                child => flat.push(child),
+            }
+        }
+        // Serialized forms give children a total order
+        let mut keyed: Vec<(String, QueryExpr)> = flat
+            .into_iter()
+            .map(|child| (serde_json::to_string(&child).unwrap_or_default(), child))
+            .collect();
+        keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+        keyed.dedup_by(|(a, _), (b, _)| a == b);
+        let mut children: Vec<QueryExpr> = keyed.into_iter().map(|(_, child)| child).collect();
+        match (children.len(), and) {
+            (1, _) => children.pop().expect("one child"),
+            (_, true) => QueryExpr::And(children),
+            (_, false) => QueryExpr::Or(children),
+        }
+    }
+}
+
+/// Shorthand for a conjunction of field predicates, keyed by field
+///
+/// ```json
+/// {"category": "product", "price": {"gte": 10, "lt": 100}, "color": {"in": ["red", "blue"]}}
+/// ```
+///
+/// A scalar tests for equality; an object applies each operator it names.
+/// `{"exists": false}` matches candidates without the field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MetadataFilter(pub BTreeMap<String, FieldCondition>);
+
+/// Condition on one field of a [`MetadataFilter`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FieldCondition {
+    /// Operators the field must all satisfy, e.g. `{"gte": 10, "lt": 100}`
+    Ops(BTreeMap<PredicateOp, Value>),
+
+    /// Value the field must equal
+    Equals(Value),
+}
+
+impl MetadataFilter {
+    /// The equivalent query expression, or `None` when there are no conditions
+    pub fn to_expr(&self) -> Option<QueryExpr> {
+        let mut clauses = Vec::new();
+        for (key, condition) in &self.0 {
+            match condition {
+                FieldCondition::Equals(value) => {
+                    clauses.push(QueryExpr::field(key, PredicateOp::Eq, value.clone()))
+                }
+                FieldCondition::Ops(ops) => {
+                    for (op, value) in ops {
+                        let clause = QueryExpr::field(key, *op, value.clone());
+                        if *op == PredicateOp::Exists && *value == Value::Bool(false) {
+                            clauses.push(QueryExpr::Not(Box::new(clause)));
+                        } else {
+                            clauses.push(clause);
+                        }
+                    }
+                }
+            }
+        }
+        match clauses.len() {
+            0 => None,
+            1 => clauses.pop(),
+            _ => Some(QueryExpr::And(clauses)),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Cost estimates for searches that haven't run.
+//!
+//! A [`SearchEstimate`] describes what a search would do, worked out from
+//! the index's Hilbert cells and the planner's statistics alone: how many
+//! vectors it would read, how many it would score, how much memory its
+//! candidates take and roughly how long that takes. Latency comes from a
+//! fixed cost model rather than measurement, so it is also reported as a
+//! coarse [`LatencyBand`] that clients can warn on.
+
+use serde::{Deserialize, Serialize};
+
+/// Nanoseconds to evaluate a filter against one stored vector
+const FILTER_NS_PER_VECTOR: f64 = 50.0;
+
+/// Nanoseconds to copy one candidate out of the index before scoring it
+const COPY_NS_PER_CANDIDATE: f64 = 100.0;
+
+/// Nanoseconds per dimension of a distance computation
+const DISTANCE_NS_PER_DIMENSION: f64 = 1.0;
+
+/// Bytes a candidate takes beyond its values and metadata
+const CANDIDATE_OVERHEAD_BYTES: u64 = 64;
+
+/// Coarse expected latency of a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyBand {
+    /// Under [`FAST_MS`](Self::FAST_MS)
+    Fast,
+    /// Under [`MODERATE_MS`](Self::MODERATE_MS)
+    Moderate,
+    Slow,
+}
+
+impl LatencyBand {
+    pub const FAST_MS: f64 = 10.0;
+    pub const MODERATE_MS: f64 = 100.0;
+
+    pub fn for_latency(latency_ms: f64) -> Self {
+        if latency_ms < Self::FAST_MS {
+            LatencyBand::Fast
+        } else if latency_ms < Self::MODERATE_MS {
+            LatencyBand::Moderate
+        } else {
+            LatencyBand::Slow
+        }
+    }
+}
+
+/// Estimated cost of a search; body of `POST /api/search/estimate`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchEstimate {
+    /// Vectors the search reads, including ones its filter rejects
+    pub vectors_scanned: usize,
+
+    /// Vectors whose distance to the query is computed
+    pub vectors_scored: usize,
+
+    /// Shard searches that fall back to reading every vector
+    pub full_scans: usize,
+
+    /// Peak bytes of candidates held while scoring
+    pub memory_bytes: u64,
+
+    pub estimated_latency_ms: f64,
+    pub latency_band: LatencyBand,
+
+    /// Estimated fraction of vectors the filter accepts; absent without a
+    /// filter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selectivity: Option<f64>,
+
+    /// The estimate exceeds the request's timeout, so the results would
+    /// likely be partial
+    #[serde(default)]
+    pub likely_partial: bool,
+}
+
+impl SearchEstimate {
+    /// Cost of one search of one index reading `scanned` vectors and
+    /// scoring `scored` of them. `selectivity` is set for filtered searches.
+    pub fn for_scan(
+        scanned: usize,
+        scored: usize,
+        full_scan: bool,
+        dimensions: usize,
+        metadata_bytes_per_vector: u64,
+        selectivity: Option<f64>,
+    ) -> Self {
+        let mut latency_ns =
+            scored as f64 * (COPY_NS_PER_CANDIDATE + dimensions as f64 * DISTANCE_NS_PER_DIMENSION);
+        if selectivity.is_some() {
+            latency_ns += scanned as f64 * FILTER_NS_PER_VECTOR;
+        }
+        let candidate_bytes = (dimensions * std::mem::size_of::<f32>()) as u64
+            + metadata_bytes_per_vector
+            + CANDIDATE_OVERHEAD_BYTES;
+        let estimated_latency_ms = latency_ns / 1_000_000.0;
+
+        Self {
+            vectors_scanned: scanned,
+            vectors_scored: scored,
+            full_scans: full_scan as usize,
+            memory_bytes: scored as u64 * candidate_bytes,
+            estimated_latency_ms,
+            latency_band: LatencyBand::for_latency(estimated_latency_ms),
+            selectivity,
+            likely_partial: false,
+        }
+    }
+
+    /// Add a search run after this one, e.g. of another shard or query
+    /// vector. Candidates are released between searches, so memory is the
+    /// larger of the two rather than the sum.
+    pub fn merge(&mut self, other: SearchEstimate) {
+        self.selectivity = match (self.selectivity, other.selectivity) {
+            (Some(a), Some(b)) => {
+                let (wa, wb) = (self.vectors_scanned as f64, other.vectors_scanned as f64);
+                if wa + wb > 0.0 {
+                    Some((a * wa + b * wb) / (wa + wb))
+                } else {
+                    Some(a.max(b))
+                }
+            }
+            (a, b) => a.or(b),
+        };
+        self.vectors_scanned += other.vectors_scanned;
+        self.vectors_scored += other.vectors_scored;
+        self.full_scans += other.full_scans;
+        self.memory_bytes = self.memory_bytes.max(other.memory_bytes);
+        self.estimated_latency_ms += other.estimated_latency_ms;
+        self.latency_band = LatencyBand::for_latency(self.estimated_latency_ms);
+        self.likely_partial |= other.likely_partial;
+    }
+}
@@ -0,0 +1,255 @@
+//! A/B experiments on search settings.
+//!
+//! An experiment compares two [`ArmConfig`]s, e.g. with and without the
+//! reranker or with different fusion strategies. Searches carrying a client
+//! key (the [`CLIENT_KEY_HEADER`]) are assigned to an arm by hashing the key,
+//! so a client sees the same arm for the whole experiment. The query ids of
+//! assigned searches are kept per arm and joined with the [`SearchLog`] to
+//! measure each arm by the feedback its results received.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::core::checksum;
+use crate::evaluation::{Evaluation, RecallReport, SignificanceTest};
+use crate::query::feedback::{FeedbackLabel, SearchLog};
+use crate::query::fusion::FusionStrategy;
+use crate::utils::errors::ExperimentError;
+
+/// Header identifying the client a search is made for
+pub const CLIENT_KEY_HEADER: &str = "x-client-key";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Arm {
+    Control,
+    Treatment,
+}
+
+/// Search settings an arm applies; unset fields keep the request's own
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArmConfig {
+    /// Search this shard instead, e.g. a copy of the index built with
+    /// different parameters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerank: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fusion: Option<FusionStrategy>,
+}
+
+fn default_treatment_share() -> f32 {
+    0.5
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentDefinition {
+    pub name: String,
+    pub control: ArmConfig,
+    pub treatment: ArmConfig,
+    /// Share of clients assigned to the treatment
+    #[serde(default = "default_treatment_share")]
+    pub treatment_share: f32,
+}
+
+impl ExperimentDefinition {
+    /// Arm of the client with `client_key`. The key is hashed with the
+    /// experiment's name, so clients are shuffled anew per experiment.
+    pub fn assign(&self, client_key: &str) -> Arm {
+        let hash = checksum::crc32c(format!("{}/{}", self.name, client_key).as_bytes());
+        if (hash as f64 / u32::MAX as f64) < self.treatment_share as f64 {
+            Arm::Treatment
+        } else {
+            Arm::Control
+        }
+    }
+
+    pub fn config(&self, arm: Arm) -> &ArmConfig {
+        match arm {
+            Arm::Control => &self.control,
+            Arm::Treatment => &self.treatment,
+        }
+    }
+}
+
+/// The arm a search was assigned to, returned with its results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Assignment {
+    pub experiment: String,
+    pub arm: Arm,
+}
+
+/// How the searches of one arm fared
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArmReport {
+    pub arm: Arm,
+    pub config: ArmConfig,
+    /// Searches assigned to the arm
+    pub searches: usize,
+    /// Assigned searches still in the search log
+    pub observed: usize,
+    /// Observed searches with a clicked or accepted result
+    pub successes: usize,
+    pub success_rate: f64,
+    pub recall: RecallReport,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentReport {
+    pub name: String,
+    pub running: bool,
+    pub started_at: DateTime<Utc>,
+    pub control: ArmReport,
+    pub treatment: ArmReport,
+    /// Treatment success rate against control
+    pub success_rate_test: SignificanceTest,
+}
+
+#[derive(Debug)]
+struct Experiment {
+    definition: ExperimentDefinition,
+    started_at: DateTime<Utc>,
+    running: bool,
+    searches: HashMap<Uuid, Arm>,
+}
+
+/// Experiments run so far; at most one runs at a time
+#[derive(Debug, Default)]
+pub struct Experiments {
+    experiments: RwLock<HashMap<String, Experiment>>,
+}
+
+impl Experiments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start an experiment. Fails while another one is running.
+    pub async fn start(&self, definition: ExperimentDefinition) -> Result<(), ExperimentError> {
+        if !(0.0..=1.0).contains(&definition.treatment_share) {
+            return Err(ExperimentError::InvalidShare(definition.treatment_share));
+        }
+        let mut experiments = self.experiments.write().await;
+        if experiments.contains_key(&definition.name) {
+            return Err(ExperimentError::DuplicateName(definition.name));
+        }
+        if let Some(running) = experiments.values().find(|e| e.running) {
+            return Err(ExperimentError::AlreadyRunning(
+                running.definition.name.clone(),
+            ));
+        }
+        info!("Started experiment {}", definition.name);
+        experiments.insert(
+            definition.name.clone(),
+            Experiment {
+                definition,
+                started_at: Utc::now(),
+                running: true,
+                searches: HashMap::new(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Stop assigning searches to the experiment; its report stays available
+    pub async fn stop(&self, name: &str) -> Result<(), ExperimentError> {
+        let mut experiments = self.experiments.write().await;
+        let experiment = experiments
+            .get_mut(name)
+            .ok_or_else(|| ExperimentError::UnknownExperiment(name.to_string()))?;
+        experiment.running = false;
+        info!("Stopped experiment {}", name);
+        Ok(())
+    }
+
+    /// The running experiment, if any
+    pub async fn running(&self) -> Option<ExperimentDefinition> {
+        self.experiments
+            .read()
+            .await
+            .values()
+            .find(|e| e.running)
+            .map(|e| e.definition.clone())
+    }
+
+    /// Remember that the search logged as `query_id` ran in `arm`
+    pub async fn record(&self, name: &str, query_id: Uuid, arm: Arm) {
+        if let Some(experiment) = self.experiments.write().await.get_mut(name) {
+            experiment.searches.insert(query_id, arm);
+        }
+    }
+
+    /// Compare the arms by the feedback their searches received in `log`
+    pub async fn report(
+        &self,
+        name: &str,
+        log: &SearchLog,
+    ) -> Result<ExperimentReport, ExperimentError> {
+        let (definition, started_at, running, searches) = {
+            let experiments = self.experiments.read().await;
+            let experiment = experiments
+                .get(name)
+                .ok_or_else(|| ExperimentError::UnknownExperiment(name.to_string()))?;
+            (
+                experiment.definition.clone(),
+                experiment.started_at,
+                experiment.running,
+                experiment.searches.clone(),
+            )
+        };
+
+        let evaluation = Evaluation::new();
+        let mut reports = Vec::with_capacity(2);
+        for arm in [Arm::Control, Arm::Treatment] {
+            let ids: Vec<Uuid> = searches
+                .iter()
+                .filter(|(_, a)| **a == arm)
+                .map(|(id, _)| *id)
+                .collect();
+            let mut impressions = Vec::new();
+            for id in &ids {
+                if let Some(impression) = log.impression(*id).await {
+                    impressions.push(impression);
+                }
+            }
+            let successes = impressions
+                .iter()
+                .filter(|i| {
+                    i.feedback
+                        .iter()
+                        .any(|f| matches!(f.label, FeedbackLabel::Click | FeedbackLabel::Accept))
+                })
+                .count();
+            reports.push(ArmReport {
+                arm,
+                config: definition.config(arm).clone(),
+                searches: ids.len(),
+                observed: impressions.len(),
+                successes,
+                success_rate: successes as f64 / impressions.len().max(1) as f64,
+                recall: evaluation.feedback_recall(&impressions),
+            });
+        }
+        let treatment = reports.pop().expect("two arms");
+        let control = reports.pop().expect("two arms");
+
+        Ok(ExperimentReport {
+            success_rate_test: evaluation.compare_proportions(
+                control.successes,
+                control.observed,
+                treatment.successes,
+                treatment.observed,
+            ),
+            name: definition.name,
+            running,
+            started_at,
+            control,
+            treatment,
+        })
+    }
+}
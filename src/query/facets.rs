@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Candidates counted when the request doesn't say
+pub const DEFAULT_FACET_CANDIDATES: usize = 100;
+
+/// Values returned per field when the request doesn't say
+pub const DEFAULT_FACET_VALUES: usize = 20;
+
+/// Metadata fields to count values of among the best search candidates
+///
+/// ```json
+/// {"fields": ["category", "brand"], "top_n": 200, "max_values": 10}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FacetRequest {
+    pub fields: Vec<String>,
+
+    /// Size of the candidate set counted; never less than the search limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_n: Option<usize>,
+
+    /// Most values returned per field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_values: Option<usize>,
+}
+
+impl FacetRequest {
+    pub fn candidates(&self, limit: usize) -> usize {
+        self.top_n.unwrap_or(DEFAULT_FACET_CANDIDATES).max(limit)
+    }
+}
+
+/// A metadata value and how many candidates had it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FacetValue {
+    pub value: String,
+    pub count: usize,
+}
+
+/// Value counts per requested field, most common first
+pub type Facets = HashMap<String, Vec<FacetValue>>;
+
+/// Accumulates value counts while candidates are scanned
+#[derive(Debug)]
+pub struct FacetCollector<'a> {
+    request: &'a FacetRequest,
+    counts: HashMap<&'a str, HashMap<String, usize>>,
+}
+
+impl<'a> FacetCollector<'a> {
+    pub fn new(request: &'a FacetRequest) -> Self {
+        Self {
+            request,
+            counts: request
+                .fields
+                .iter()
+                .map(|f| (f.as_str(), HashMap::new()))
+                .collect(),
+        }
+    }
+
+    /// Count one candidate's metadata
+    pub fn observe(&mut self, metadata: Option<&HashMap<String, String>>) {
+        let Some(metadata) = metadata else {
+            return;
+        };
+        for (field, counts) in self.counts.iter_mut() {
+            if let Some(value) = metadata.get(*field) {
+                *counts.entry(value.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    pub fn finish(self) -> Facets {
+        let max_values = self.request.max_values.unwrap_or(DEFAULT_FACET_VALUES);
+        self.counts
+            .into_iter()
+            .map(|(field, counts)| {
+                let mut values: Vec<FacetValue> = counts
+                    .into_iter()
+                    .map(|(value, count)| FacetValue { value, count })
+                    .collect();
+                values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+                values.truncate(max_values);
+                (field.to_string(), values)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,294 @@
+//! Search impressions and the relevance feedback clients send about them.
+//!
+//! A search that collects feedback is given a query id and the results it
+//! returned are kept as an [`Impression`]. Clients later report which of
+//! those results were clicked or accepted; impressions joined with their
+//! feedback are the training data for the ranking model, the input to
+//! feedback-based recall evaluation and, joined with the
+//! [`SlowQueryLog`](super::slow_log::SlowQueryLog), show whether slow
+//! searches also return worse results.
+//!
+//! When opened with a path, impressions and feedback are appended to a
+//! JSON-lines file with checksummed lines and reloaded on restart.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::RwLock;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::core::checksum;
+use crate::utils::errors::FeedbackError;
+
+/// Impressions kept by default before the oldest are dropped
+pub const DEFAULT_IMPRESSION_CAPACITY: usize = 10_000;
+
+/// What a client did with a result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackLabel {
+    /// The result was opened
+    Click,
+    /// The result was used, e.g. copied or cited; stronger than a click
+    Accept,
+    /// The result was judged not relevant
+    Irrelevant,
+    /// A relevant result the search should have returned but didn't
+    Missing,
+}
+
+impl FeedbackLabel {
+    /// How much an example with this label counts in training
+    pub fn weight(&self) -> f32 {
+        match self {
+            FeedbackLabel::Accept => 2.0,
+            FeedbackLabel::Click | FeedbackLabel::Irrelevant | FeedbackLabel::Missing => 1.0,
+        }
+    }
+
+    pub fn is_relevant(&self) -> bool {
+        !matches!(self, FeedbackLabel::Irrelevant)
+    }
+}
+
+/// A result as it was shown, with what the ranker needs to know about it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShownResult {
+    pub id: String,
+    pub score: f32,
+    /// Number of metadata fields the result carried
+    pub metadata_fields: usize,
+}
+
+/// The results returned for one search, in their original order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Impression {
+    pub query_id: Uuid,
+    pub shard_id: Uuid,
+    pub results: Vec<ShownResult>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Feedback {
+    pub query_id: Uuid,
+    pub result_id: String,
+    pub label: FeedbackLabel,
+    pub received_at: DateTime<Utc>,
+}
+
+/// An impression together with the feedback it received
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelledImpression {
+    pub impression: Impression,
+    pub feedback: Vec<Feedback>,
+}
+
+/// A line of the persisted log
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum LogRecord {
+    Impression(Impression),
+    Feedback(Feedback),
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    order: VecDeque<Uuid>,
+    impressions: HashMap<Uuid, LabelledImpression>,
+}
+
+/// Bounded in-memory log of recent impressions and their feedback
+#[derive(Debug)]
+pub struct SearchLog {
+    capacity: usize,
+    entries: RwLock<Entries>,
+    feedback_received: AtomicU64,
+    path: Option<PathBuf>,
+}
+
+impl Default for SearchLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Entries {
+    fn insert(&mut self, impression: Impression, capacity: usize) {
+        while self.order.len() >= capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.impressions.remove(&oldest);
+            }
+        }
+        self.order.push_back(impression.query_id);
+        self.impressions.insert(
+            impression.query_id,
+            LabelledImpression {
+                impression,
+                feedback: Vec::new(),
+            },
+        );
+    }
+}
+
+impl SearchLog {
+    /// In-memory log; impressions and feedback are lost on restart
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_IMPRESSION_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: RwLock::new(Entries::default()),
+            feedback_received: AtomicU64::new(0),
+            path: None,
+        }
+    }
+
+    /// Log persisted to a JSON-lines file, loading the most recent
+    /// `capacity` impressions and their feedback already in it
+    pub fn open<P: AsRef<Path>>(path: P, capacity: usize) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let capacity = capacity.max(1);
+        let mut entries = Entries::default();
+        let mut feedback_received = 0;
+
+        if path.exists() {
+            let lines = checksum::read_sealed_lines(&path)
+                .map_err(|e| anyhow!("Failed to read search log: {}", e))?;
+            for (line_no, line) in lines.records {
+                let record: LogRecord = serde_json::from_str(&line).map_err(|e| {
+                    anyhow!(
+                        "Invalid search log record at {}:{}: {}",
+                        path.display(),
+                        line_no,
+                        e
+                    )
+                })?;
+                match record {
+                    LogRecord::Impression(impression) => entries.insert(impression, capacity),
+                    LogRecord::Feedback(feedback) => {
+                        feedback_received += 1;
+                        // Feedback about impressions that have been dropped is only counted
+                        if let Some(entry) = entries.impressions.get_mut(&feedback.query_id) {
+                            entry.feedback.push(feedback);
+                        }
+                    }
+                }
+            }
+        } else if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        Ok(Self {
+            capacity,
+            entries: RwLock::new(entries),
+            feedback_received: AtomicU64::new(feedback_received),
+            path: Some(path),
+        })
+    }
+
+    fn persist(&self, record: &LogRecord) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", checksum::seal(&serde_json::to_string(record)?))?;
+        Ok(())
+    }
+
+    /// Keep the results of a search and return the query id feedback
+    /// should refer to
+    pub async fn record_impression(
+        &self,
+        shard_id: Uuid,
+        results: impl IntoIterator<Item = ShownResult>,
+    ) -> Uuid {
+        let impression = Impression {
+            query_id: Uuid::new_v4(),
+            shard_id,
+            results: results.into_iter().collect(),
+            recorded_at: Utc::now(),
+        };
+        let query_id = impression.query_id;
+
+        let mut entries = self.entries.write().await;
+        // Append under the lock so the file keeps the order impressions were recorded in
+        if let Err(e) = self.persist(&LogRecord::Impression(impression.clone())) {
+            warn!("Failed to persist search impression: {}", e);
+        }
+        entries.insert(impression, self.capacity);
+        query_id
+    }
+
+    /// Record feedback about one of the results shown for `query_id`.
+    /// [`FeedbackLabel::Missing`] feedback names a result that wasn't shown.
+    pub async fn record_feedback(
+        &self,
+        query_id: Uuid,
+        result_id: &str,
+        label: FeedbackLabel,
+    ) -> Result<Feedback, FeedbackError> {
+        let mut entries = self.entries.write().await;
+        let entry = entries
+            .impressions
+            .get_mut(&query_id)
+            .ok_or(FeedbackError::UnknownQuery(query_id))?;
+        let shown = entry.impression.results.iter().any(|r| r.id == result_id);
+        if !shown && label != FeedbackLabel::Missing {
+            return Err(FeedbackError::UnknownResult {
+                query_id,
+                result_id: result_id.to_string(),
+            });
+        }
+
+        let feedback = Feedback {
+            query_id,
+            result_id: result_id.to_string(),
+            label,
+            received_at: Utc::now(),
+        };
+        entry.feedback.push(feedback.clone());
+        if let Err(e) = self.persist(&LogRecord::Feedback(feedback.clone())) {
+            warn!("Failed to persist search feedback: {}", e);
+        }
+        self.feedback_received.fetch_add(1, Ordering::Relaxed);
+        Ok(feedback)
+    }
+
+    pub async fn impression(&self, query_id: Uuid) -> Option<LabelledImpression> {
+        self.entries
+            .read()
+            .await
+            .impressions
+            .get(&query_id)
+            .cloned()
+    }
+
+    /// Impressions that received any feedback, oldest first
+    pub async fn labelled(&self) -> Vec<LabelledImpression> {
+        let entries = self.entries.read().await;
+        entries
+            .order
+            .iter()
+            .filter_map(|id| entries.impressions.get(id))
+            .filter(|entry| !entry.feedback.is_empty())
+            .cloned()
+            .collect()
+    }
+
+    /// Feedback received since the log was created, including feedback
+    /// about impressions since dropped
+    pub fn feedback_received(&self) -> u64 {
+        self.feedback_received.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,118 @@
+//! Merging the result lists of several searches into one.
+//!
+//! Used when one request searches with several queries, e.g. the variants of
+//! a query expanded with synonyms or separate vectors for the aspects of a
+//! multi-aspect query. [`FusionStrategy`] picks how a result found by more
+//! than one search is scored.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::sharding::vector_index::SearchResult;
+
+/// Damping constant of reciprocal rank fusion; 60 is the value from the
+/// original paper and keeps the top few ranks from dominating
+pub const RRF_K: f32 = 60.0;
+
+/// How the result lists of several searches are merged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FusionStrategy {
+    /// Reciprocal rank fusion; scores become fused rank scores, higher
+    /// being better even when the metric is a distance
+    Rrf,
+    /// Keep each result's best score over the searches. The default, since
+    /// scores keep the metric's direction as in a single search.
+    #[default]
+    Max,
+    /// Average each result's score over the searches. A search that didn't
+    /// return a result counts it at the worst score it did return.
+    Mean,
+}
+
+/// Merge `lists` with `strategy` and keep the best `limit` results.
+/// `lower_is_better` gives the direction of the lists' scores, which
+/// [`FusionStrategy::Max`] and [`FusionStrategy::Mean`] keep.
+pub fn fuse(
+    lists: Vec<Vec<SearchResult>>,
+    strategy: FusionStrategy,
+    lower_is_better: bool,
+    limit: usize,
+) -> Vec<SearchResult> {
+    if strategy == FusionStrategy::Rrf {
+        return reciprocal_rank_fusion(lists, limit);
+    }
+    let better = |a: f32, b: f32| if lower_is_better { a.min(b) } else { a.max(b) };
+    let worse = |a: f32, b: f32| if lower_is_better { a.max(b) } else { a.min(b) };
+
+    // Until a list returns a result, the result stands at that list's worst score
+    let worst: Vec<Option<f32>> = lists
+        .iter()
+        .map(|list| list.iter().map(|r| r.score).reduce(worse))
+        .collect();
+    let mut fused: HashMap<Uuid, (SearchResult, Vec<Option<f32>>)> = HashMap::new();
+    for (i, list) in lists.into_iter().enumerate() {
+        for result in list {
+            let score = result.score;
+            let (_, scores) = fused
+                .entry(result.id)
+                .or_insert_with(|| (result, worst.clone()));
+            scores[i] = Some(scores[i].map_or(score, |s| better(s, score)));
+        }
+    }
+
+    let mut results: Vec<SearchResult> = fused
+        .into_values()
+        .map(|(mut result, scores)| {
+            let scores: Vec<f32> = scores.into_iter().flatten().collect();
+            result.score = match strategy {
+                FusionStrategy::Max => scores.iter().copied().reduce(better).unwrap_or(0.0),
+                _ => scores.iter().sum::<f32>() / scores.len().max(1) as f32,
+            };
+            result
+        })
+        .collect();
+    sort(&mut results, lower_is_better);
+    results.truncate(limit);
+    results
+}
+
+/// Best first, ties broken by id so the order doesn't depend on hashing
+fn sort(results: &mut [SearchResult], lower_is_better: bool) {
+    results.sort_by(|a, b| {
+        let order = if lower_is_better {
+            a.score.total_cmp(&b.score)
+        } else {
+            b.score.total_cmp(&a.score)
+        };
+        order.then_with(|| a.id.cmp(&b.id))
+    });
+}
+
+/// Reciprocal rank fusion: each result scores the sum of `1 / (RRF_K + rank)`
+/// over the lists it appears in, so results found by several searches rise
+/// to the top. Only ranks are used, which makes lists with different score
+/// scales comparable. Returned results carry their fused score, higher
+/// being better.
+pub fn reciprocal_rank_fusion(lists: Vec<Vec<SearchResult>>, limit: usize) -> Vec<SearchResult> {
+    let mut fused: HashMap<Uuid, SearchResult> = HashMap::new();
+    let mut scores: HashMap<Uuid, f32> = HashMap::new();
+    for list in lists {
+        for (rank, result) in list.into_iter().enumerate() {
+            *scores.entry(result.id).or_insert(0.0) += 1.0 / (RRF_K + rank as f32 + 1.0);
+            fused.entry(result.id).or_insert(result);
+        }
+    }
+
+    let mut results: Vec<SearchResult> = fused
+        .into_iter()
+        .map(|(id, mut result)| {
+            result.score = scores[&id];
+            result
+        })
+        .collect();
+    sort(&mut results, false);
+    results.truncate(limit);
+    results
+}
@@ -0,0 +1,47 @@
+//! Query DSL and planner for filtered vector search
+//!
+//! Clients describe filters as a JSON tree of `and`/`or`/`not` nodes over
+//! metadata predicates and vector similarity clauses. The planner validates
+//! the tree against the target index and compiles it into an
+//! [`ExecutionPlan`] that the index evaluates per candidate. Searches can
+//! also count [facets](FacetRequest) among the best candidates, and
+//! [`Diversification`] can group and re-rank the results afterwards.
+//! Query vectors can be [composed](ComposeOp) from stored vectors, e.g. for
+//! analogy queries. Searches and the feedback clients give on their results
+//! are kept in a [`SearchLog`](feedback::SearchLog), and searches over a
+//! latency threshold in a [`SlowQueryLog`](slow_log::SlowQueryLog). Text
+//! queries can be expanded with per-collection
+//! [synonym dictionaries](synonyms::SynonymDictionary) and the results of
+//! the variants [fused](fusion::reciprocal_rank_fusion). Search settings
+//! can be compared in [A/B experiments](experiments::Experiments) judged by
+//! that feedback. What a search would cost can be
+//! [estimated](estimate::SearchEstimate) without running it. Candidates can
+//! be re-scored by [custom scorers](scoring::ScoringPlugins) loaded as
+//! WebAssembly modules. A search can also be declared as a
+//! [pipeline](pipeline::PipelineRequest) of retrieve, filter, rerank and
+//! aggregate stages, timed one by one.
+
+pub mod compose;
+pub mod diversify;
+pub mod dsl;
+pub mod estimate;
+pub mod experiments;
+pub mod facets;
+pub mod feedback;
+pub mod fusion;
+pub mod pipeline;
+pub mod planner;
+pub mod scoring;
+pub mod slow_log;
+pub mod synonyms;
+
+pub use compose::{ComposeOp, ComposeTerm};
+pub use diversify::{Diversification, MmrOptions};
+pub use dsl::{
+    FieldCondition, FieldPredicate, MetadataFilter, PredicateOp, QueryExpr, SimilarityClause,
+};
+pub use estimate::{LatencyBand, SearchEstimate};
+pub use facets::{FacetRequest, FacetValue, Facets};
+pub use pipeline::{PipelineOutcome, PipelineRequest, Stage, StageTiming};
+pub use planner::{ExecutionPlan, PlanNode, QueryPlanner};
+pub use scoring::{ScorerInfo, ScorerLimits, ScoringPlugins};
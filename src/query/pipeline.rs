@@ -0,0 +1,363 @@
+//! Multi-stage query pipelines.
+//!
+//! A pipeline spells out a search as a list of stages run in order over a
+//! shared candidate list: one `retrieve` that fetches candidates from the
+//! index, followed by any number of `filter`, `rerank` and `aggregate`
+//! stages. The whole pipeline is validated against the target index before
+//! anything runs, so a bad stage fails the request without touching the
+//! index, and every stage reports how long it took and how many candidates
+//! went in and came out.
+//!
+//! ```json
+//! {"shard_id": "...", "limit": 10, "stages": [
+//!   {"stage": "retrieve", "query_vector": [0.1, 0.2], "limit": 200},
+//!   {"stage": "filter", "metadata_filter": {"in_stock": "true"}},
+//!   {"stage": "rerank", "mmr": {"lambda": 0.7}, "limit": 50},
+//!   {"stage": "aggregate", "fields": ["brand"]}
+//! ]}
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::core::vector::Vector;
+use crate::query::diversify::Diversification;
+use crate::query::dsl::{MetadataFilter, QueryExpr};
+use crate::query::facets::{FacetCollector, FacetRequest, Facets};
+use crate::query::fusion::FusionStrategy;
+use crate::query::planner::ExecutionPlan;
+use crate::query::scoring::ScoringPlugins;
+use crate::sharding::manager::ShardManager;
+use crate::sharding::vector_index::SearchResult;
+use crate::utils::errors::QueryError;
+
+/// Body of `POST /api/search/pipeline`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineRequest {
+    pub shard_id: Uuid,
+
+    /// Stages in the order they run; the first must be `retrieve`
+    pub stages: Vec<Stage>,
+
+    /// Most results returned; defaults to every candidate that survives
+    /// the stages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// One step of a pipeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum Stage {
+    Retrieve(RetrieveStage),
+    Filter(FilterStage),
+    Rerank(RerankStage),
+    Aggregate(AggregateStage),
+}
+
+impl Stage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Retrieve(_) => "retrieve",
+            Stage::Filter(_) => "filter",
+            Stage::Rerank(_) => "rerank",
+            Stage::Aggregate(_) => "aggregate",
+        }
+    }
+}
+
+/// Fetch candidates from the index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetrieveStage {
+    pub query_vector: Vec<f32>,
+
+    /// More query vectors whose results are merged by `fusion`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_queries: Vec<Vec<f32>>,
+
+    #[serde(default)]
+    pub fusion: FusionStrategy,
+
+    /// Candidates fetched
+    pub limit: usize,
+
+    /// Filter evaluated by the index during the scan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<QueryExpr>,
+
+    /// Return the candidates found so far once this many milliseconds have
+    /// passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Drop candidates that don't match
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FilterStage {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<QueryExpr>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_filter: Option<MetadataFilter>,
+}
+
+impl FilterStage {
+    /// `filter` and `metadata_filter` combined into one expression
+    pub fn expr(&self) -> Option<QueryExpr> {
+        let metadata = self
+            .metadata_filter
+            .as_ref()
+            .and_then(MetadataFilter::to_expr);
+        match (self.filter.clone(), metadata) {
+            (Some(filter), Some(metadata)) => Some(QueryExpr::And(vec![filter, metadata])),
+            (filter, metadata) => filter.or(metadata),
+        }
+    }
+}
+
+/// Reorder and trim the candidates. Diversification selects first, then
+/// the scorer re-scores what it kept.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RerankStage {
+    /// Registered scorer plugin to re-score the candidates with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scorer: Option<String>,
+
+    /// `group_by` and `mmr` selection
+    #[serde(flatten)]
+    pub diversify: Diversification,
+
+    /// Candidates kept; defaults to all of them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// Count metadata values among the candidates without changing them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AggregateStage {
+    /// `top_n` bounds the candidates counted, best first
+    #[serde(flatten)]
+    pub facets: FacetRequest,
+}
+
+/// How one stage went
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageTiming {
+    pub stage: String,
+    pub candidates_in: usize,
+    pub candidates_out: usize,
+    pub duration_ms: f64,
+}
+
+/// Results of a pipeline with the timing of each stage
+#[derive(Debug, Clone, Default)]
+pub struct PipelineOutcome {
+    pub results: Vec<SearchResult>,
+
+    /// Counts from the `aggregate` stage, when there was one
+    pub facets: Option<Facets>,
+
+    /// The retrieve stage's timeout passed before every candidate was scored
+    pub partial: bool,
+
+    /// Fraction of the shard's vectors that were searchable
+    pub coverage: f32,
+
+    pub stages: Vec<StageTiming>,
+}
+
+/// Filter stages compiled against the index, in stage order
+type CompiledFilters = Vec<Option<ExecutionPlan>>;
+
+fn invalid(stage: usize, message: impl std::fmt::Display) -> QueryError {
+    QueryError::InvalidQuery(format!("stage {}: {}", stage, message))
+}
+
+impl PipelineRequest {
+    /// Check the stages against the target index and compile their filters.
+    /// Errors name the offending stage by its position.
+    async fn validate(
+        &self,
+        manager: &ShardManager,
+        scorers: Option<&ScoringPlugins>,
+    ) -> Result<CompiledFilters, QueryError> {
+        if self.limit == Some(0) {
+            return Err(QueryError::InvalidQuery(
+                "limit must be greater than zero".into(),
+            ));
+        }
+        let Some(Stage::Retrieve(_)) = self.stages.first() else {
+            return Err(invalid(0, "a pipeline must start with a retrieve stage"));
+        };
+        let (index, _) = manager
+            .searchable_index(self.shard_id)
+            .await
+            .map_err(|e| QueryError::ExecutionError(e.to_string()))?;
+        let planner = index.planner().await;
+
+        let mut filters = Vec::new();
+        let mut aggregated = false;
+        let mut scored = false;
+        for (i, stage) in self.stages.iter().enumerate() {
+            match stage {
+                Stage::Retrieve(retrieve) => {
+                    if i > 0 {
+                        return Err(invalid(i, "only the first stage may retrieve"));
+                    }
+                    if retrieve.limit == 0 {
+                        return Err(invalid(i, "limit must be greater than zero"));
+                    }
+                    let dimensions = std::iter::once(&retrieve.query_vector)
+                        .chain(&retrieve.additional_queries)
+                        .map(Vec::len)
+                        .find(|&len| len != index.dimensions());
+                    if let Some(len) = dimensions {
+                        return Err(invalid(
+                            i,
+                            format!(
+                                "query vector dimensions mismatch: expected {}, got {}",
+                                index.dimensions(),
+                                len
+                            ),
+                        ));
+                    }
+                    if let Some(filter) = &retrieve.filter {
+                        planner.plan(filter).map_err(|e| invalid(i, e))?;
+                    }
+                }
+                Stage::Filter(filter) => {
+                    let expr = filter
+                        .expr()
+                        .ok_or_else(|| invalid(i, "filter stage has no conditions"))?;
+                    filters.push(Some(planner.plan(&expr).map_err(|e| invalid(i, e))?));
+                    continue;
+                }
+                Stage::Rerank(rerank) => {
+                    if rerank.scorer.is_none() && !rerank.diversify.is_enabled() {
+                        return Err(invalid(i, "rerank stage needs a scorer, mmr or group_by"));
+                    }
+                    if rerank.limit == Some(0) {
+                        return Err(invalid(i, "limit must be greater than zero"));
+                    }
+                    // MMR and grouping read scores in the index's direction,
+                    // which a scorer no longer follows
+                    if scored && rerank.diversify.is_enabled() {
+                        return Err(invalid(i, "diversification can't follow a scorer"));
+                    }
+                    if let Some(name) = &rerank.scorer {
+                        let Some(scorers) = scorers else {
+                            return Err(invalid(i, "scoring plugins not configured"));
+                        };
+                        if !scorers.contains(name).await {
+                            return Err(invalid(i, format!("unknown scorer: {}", name)));
+                        }
+                        scored = true;
+                    }
+                }
+                Stage::Aggregate(aggregate) => {
+                    if aggregated {
+                        return Err(invalid(i, "only one aggregate stage is allowed"));
+                    }
+                    if aggregate.facets.fields.is_empty() {
+                        return Err(invalid(i, "aggregate stage has no fields"));
+                    }
+                    aggregated = true;
+                }
+            }
+            filters.push(None);
+        }
+        Ok(filters)
+    }
+
+    /// Validate the pipeline, then run its stages in order
+    pub async fn run(
+        &self,
+        manager: &ShardManager,
+        scorers: Option<&ScoringPlugins>,
+    ) -> Result<PipelineOutcome, QueryError> {
+        let filters = self.validate(manager, scorers).await?;
+        let (index, _) = manager
+            .searchable_index(self.shard_id)
+            .await
+            .map_err(|e| QueryError::ExecutionError(e.to_string()))?;
+        let metric = index.distance_metric();
+
+        let mut outcome = PipelineOutcome {
+            coverage: 1.0,
+            ..Default::default()
+        };
+        let mut query: Option<Vector> = None;
+        for ((i, stage), plan) in self.stages.iter().enumerate().zip(filters) {
+            let started = Instant::now();
+            let candidates_in = outcome.results.len();
+            match stage {
+                Stage::Retrieve(retrieve) => {
+                    let queries: Vec<Vector> = std::iter::once(&retrieve.query_vector)
+                        .chain(&retrieve.additional_queries)
+                        .map(|values| Vector::new(values.clone()))
+                        .collect();
+                    let retrieved = manager
+                        .search_vectors_fused(
+                            self.shard_id,
+                            &queries,
+                            retrieve.limit,
+                            retrieve.filter.as_ref(),
+                            &Diversification::default(),
+                            None,
+                            retrieve.timeout_ms.map(Duration::from_millis),
+                            retrieve.fusion,
+                        )
+                        .await
+                        .map_err(|e| QueryError::ExecutionError(format!("stage {}: {}", i, e)))?;
+                    outcome.results = retrieved.results;
+                    outcome.partial = retrieved.partial;
+                    outcome.coverage = retrieved.coverage;
+                    query = queries.into_iter().next();
+                }
+                Stage::Filter(_) => {
+                    if let Some(plan) = plan {
+                        outcome
+                            .results
+                            .retain(|r| plan.matches(&r.vector, r.metadata.as_ref(), metric));
+                    }
+                }
+                Stage::Rerank(rerank) => {
+                    let limit = rerank.limit.unwrap_or(candidates_in);
+                    let mut results = std::mem::take(&mut outcome.results);
+                    if rerank.diversify.is_enabled() {
+                        results = rerank.diversify.apply(results, metric, limit);
+                    }
+                    if let (Some(name), Some(scorers), Some(query)) =
+                        (&rerank.scorer, scorers, &query)
+                    {
+                        results = scorers.score(name, query, results).await.map_err(|e| {
+                            QueryError::ExecutionError(format!("stage {}: {}", i, e))
+                        })?;
+                    }
+                    results.truncate(limit);
+                    outcome.results = results;
+                }
+                Stage::Aggregate(aggregate) => {
+                    let counted = aggregate.facets.top_n.unwrap_or(candidates_in);
+                    let mut collector = FacetCollector::new(&aggregate.facets);
+                    for result in outcome.results.iter().take(counted) {
+                        collector.observe(result.metadata.as_ref());
+                    }
+                    outcome.facets = Some(collector.finish());
+                }
+            }
+            outcome.stages.push(StageTiming {
+                stage: stage.name().to_string(),
+                candidates_in,
+                candidates_out: outcome.results.len(),
+                duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+            });
+        }
+
+        if let Some(limit) = self.limit {
+            outcome.results.truncate(limit);
+        }
+        Ok(outcome)
+    }
+}
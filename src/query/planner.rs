@@ -0,0 +1,373 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::core::vector::Vector;
+use crate::query::dsl::{FieldPredicate, PredicateOp, QueryExpr, SimilarityClause};
+use crate::sharding::compression;
+use crate::sharding::sketch::IndexStatistics;
+use crate::sharding::vector_index::DistanceMetric;
+use crate::utils::errors::QueryError;
+
+/// Deepest expression tree the planner accepts
+const MAX_QUERY_DEPTH: usize = 32;
+
+/// Operand of a compiled metadata predicate
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Number(f64),
+    Text(String),
+}
+
+impl Operand {
+    fn from_json(value: &Value) -> Result<Self, QueryError> {
+        match value {
+            Value::Number(n) => n.as_f64().map(Operand::Number).ok_or_else(|| {
+                QueryError::InvalidQuery(format!("Unsupported numeric value: {}", n))
+            }),
+            Value::String(s) => Ok(Operand::Text(s.clone())),
+            Value::Bool(b) => Ok(Operand::Text(b.to_string())),
+            other => Err(QueryError::InvalidQuery(format!(
+                "Expected a string, number or boolean operand, got {}",
+                other
+            ))),
+        }
+    }
+
+    /// Compare a stored metadata value against this operand. Numbers compare
+    /// numerically when the stored value parses as one; otherwise both sides
+    /// compare as text.
+    fn compare(&self, stored: &str) -> Option<Ordering> {
+        match self {
+            Operand::Number(n) => stored.parse::<f64>().ok()?.partial_cmp(n),
+            Operand::Text(t) => Some(stored.cmp(t.as_str())),
+        }
+    }
+
+    fn equals(&self, stored: &str) -> bool {
+        self.compare(stored) == Some(Ordering::Equal)
+    }
+}
+
+/// A node of a compiled execution plan
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanNode {
+    /// Matches everything
+    True,
+    All(Vec<PlanNode>),
+    Any(Vec<PlanNode>),
+    Not(Box<PlanNode>),
+    Metadata {
+        key: String,
+        op: PredicateOp,
+        operands: Vec<Operand>,
+    },
+    Similarity {
+        vector: Vector,
+        max_distance: f32,
+    },
+}
+
+impl PlanNode {
+    /// Relative cost of evaluating this node; used to order conjunctions so
+    /// cheap metadata checks short-circuit before distance calculations.
+    fn cost(&self) -> usize {
+        match self {
+            PlanNode::True => 0,
+            PlanNode::Metadata { .. } => 1,
+            PlanNode::Similarity { vector, .. } => 1 + vector.dimensions,
+            PlanNode::Not(child) => child.cost(),
+            PlanNode::All(children) | PlanNode::Any(children) => {
+                children.iter().map(|c| c.cost()).sum()
+            }
+        }
+    }
+
+    fn matches(
+        &self,
+        vector: &Vector,
+        metadata: Option<&HashMap<String, String>>,
+        metric: DistanceMetric,
+    ) -> bool {
+        match self {
+            PlanNode::True => true,
+            PlanNode::All(children) => children.iter().all(|c| c.matches(vector, metadata, metric)),
+            PlanNode::Any(children) => children.iter().any(|c| c.matches(vector, metadata, metric)),
+            PlanNode::Not(child) => !child.matches(vector, metadata, metric),
+            PlanNode::Metadata { key, op, operands } => {
+                // Compressed values are only decoded when a predicate tests them
+                let stored = metadata
+                    .and_then(|m| m.get(key))
+                    .map(|s| compression::decoded(s));
+                let stored = stored.as_deref();
+                match (op, stored) {
+                    (PredicateOp::Exists, stored) => stored.is_some(),
+                    (_, None) => false,
+                    (PredicateOp::Eq, Some(s)) => operands[0].equals(s),
+                    (PredicateOp::Ne, Some(s)) => !operands[0].equals(s),
+                    (PredicateOp::In, Some(s)) => operands.iter().any(|o| o.equals(s)),
+                    (PredicateOp::Gt, Some(s)) => operands[0].compare(s) == Some(Ordering::Greater),
+                    (PredicateOp::Gte, Some(s)) => matches!(
+                        operands[0].compare(s),
+                        Some(Ordering::Greater) | Some(Ordering::Equal)
+                    ),
+                    (PredicateOp::Lt, Some(s)) => operands[0].compare(s) == Some(Ordering::Less),
+                    (PredicateOp::Lte, Some(s)) => matches!(
+                        operands[0].compare(s),
+                        Some(Ordering::Less) | Some(Ordering::Equal)
+                    ),
+                }
+            }
+            PlanNode::Similarity {
+                vector: reference,
+                max_distance,
+            } => metric.distance(reference, vector) <= *max_distance,
+        }
+    }
+}
+
+/// A validated, normalized filter ready to be evaluated against candidates
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionPlan {
+    /// Root of the compiled expression tree
+    pub root: PlanNode,
+
+    /// Number of metadata predicates in the plan
+    pub metadata_predicates: usize,
+
+    /// Number of similarity clauses in the plan
+    pub similarity_clauses: usize,
+
+    /// Estimated fraction of vectors the plan accepts, when the planner
+    /// had index statistics to go on
+    pub estimated_selectivity: Option<f64>,
+}
+
+impl ExecutionPlan {
+    /// Plan that accepts every candidate
+    pub fn match_all() -> Self {
+        Self {
+            root: PlanNode::True,
+            metadata_predicates: 0,
+            similarity_clauses: 0,
+            estimated_selectivity: None,
+        }
+    }
+
+    /// Whether the plan filters anything at all
+    pub fn is_match_all(&self) -> bool {
+        self.root == PlanNode::True
+    }
+
+    /// Evaluate the plan against a stored vector and its metadata
+    pub fn matches(
+        &self,
+        vector: &Vector,
+        metadata: Option<&HashMap<String, String>>,
+        metric: DistanceMetric,
+    ) -> bool {
+        self.root.matches(vector, metadata, metric)
+    }
+}
+
+/// Compiles query expressions into execution plans for a particular index
+#[derive(Debug, Clone)]
+pub struct QueryPlanner {
+    dimensions: usize,
+    statistics: Option<IndexStatistics>,
+}
+
+impl QueryPlanner {
+    /// Create a planner for an index with the given dimensions
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions,
+            statistics: None,
+        }
+    }
+
+    /// Use index statistics to estimate selectivity, so that among equally
+    /// cheap predicates the most selective is checked first in conjunctions
+    /// and the least selective first in disjunctions
+    pub fn with_statistics(mut self, statistics: IndexStatistics) -> Self {
+        self.statistics = Some(statistics);
+        self
+    }
+
+    /// Validate and compile an expression
+    pub fn plan(&self, expr: &QueryExpr) -> Result<ExecutionPlan, QueryError> {
+        let mut plan = ExecutionPlan::match_all();
+        plan.root = self.compile(expr, 0, &mut plan)?;
+        plan.estimated_selectivity = self
+            .statistics
+            .as_ref()
+            .map(|_| self.selectivity(&plan.root));
+        Ok(plan)
+    }
+
+    /// Estimated fraction of vectors a node accepts, treating predicates as
+    /// independent. Similarity clauses can't be estimated and count as 1.
+    fn selectivity(&self, node: &PlanNode) -> f64 {
+        let Some(stats) = &self.statistics else {
+            return 1.0;
+        };
+        match node {
+            PlanNode::True | PlanNode::Similarity { .. } => 1.0,
+            PlanNode::All(children) => children.iter().map(|c| self.selectivity(c)).product(),
+            PlanNode::Any(children) => {
+                1.0 - children
+                    .iter()
+                    .map(|c| 1.0 - self.selectivity(c))
+                    .product::<f64>()
+            }
+            PlanNode::Not(child) => 1.0 - self.selectivity(child),
+            PlanNode::Metadata { key, op, operands } => match op {
+                PredicateOp::Exists => stats.presence(key),
+                PredicateOp::Eq => stats.equality_selectivity(key),
+                PredicateOp::Ne => stats.presence(key) - stats.equality_selectivity(key),
+                PredicateOp::In => stats.in_selectivity(key, operands.len()),
+                PredicateOp::Gt | PredicateOp::Gte | PredicateOp::Lt | PredicateOp::Lte => {
+                    stats.range_selectivity(key)
+                }
+            },
+        }
+        .clamp(0.0, 1.0)
+    }
+
+    /// Order children by cost, breaking ties by selectivity
+    fn order(&self, nodes: &mut [PlanNode], most_selective_first: bool) {
+        if self.statistics.is_none() {
+            nodes.sort_by_key(|n| n.cost());
+            return;
+        }
+        nodes.sort_by(|a, b| {
+            let by_selectivity = self.selectivity(a).total_cmp(&self.selectivity(b));
+            a.cost().cmp(&b.cost()).then(if most_selective_first {
+                by_selectivity
+            } else {
+                by_selectivity.reverse()
+            })
+        });
+    }
+
+    fn compile(
+        &self,
+        expr: &QueryExpr,
+        depth: usize,
+        plan: &mut ExecutionPlan,
+    ) -> Result<PlanNode, QueryError> {
+        if depth > MAX_QUERY_DEPTH {
+            return Err(QueryError::InvalidQuery(format!(
+                "Query nesting exceeds maximum depth of {}",
+                MAX_QUERY_DEPTH
+            )));
+        }
+
+        match expr {
+            QueryExpr::And(children) => {
+                let mut nodes = Vec::with_capacity(children.len());
+                for child in children {
+                    match self.compile(child, depth + 1, plan)? {
+                        PlanNode::True => {}
+                        // Flatten nested conjunctions
+                        PlanNode::All(grandchildren) => nodes.extend(grandchildren),
+                        node => nodes.push(node),
+                    }
+                }
+                self.order(&mut nodes, true);
+                Ok(match nodes.len() {
+                    0 => PlanNode::True,
+                    1 => nodes.pop().unwrap(),
+                    _ => PlanNode::All(nodes),
+                })
+            }
+            QueryExpr::Or(children) => {
+                if children.is_empty() {
+                    return Err(QueryError::InvalidQuery(
+                        "'or' requires at least one clause".to_string(),
+                    ));
+                }
+                let mut nodes = Vec::with_capacity(children.len());
+                for child in children {
+                    match self.compile(child, depth + 1, plan)? {
+                        // A tautology makes the whole disjunction true
+                        PlanNode::True => return Ok(PlanNode::True),
+                        PlanNode::Any(grandchildren) => nodes.extend(grandchildren),
+                        node => nodes.push(node),
+                    }
+                }
+                self.order(&mut nodes, false);
+                Ok(if nodes.len() == 1 {
+                    nodes.pop().unwrap()
+                } else {
+                    PlanNode::Any(nodes)
+                })
+            }
+            QueryExpr::Not(child) => match self.compile(child, depth + 1, plan)? {
+                // Eliminate double negation
+                PlanNode::Not(inner) => Ok(*inner),
+                node => Ok(PlanNode::Not(Box::new(node))),
+            },
+            QueryExpr::Field(predicate) => {
+                plan.metadata_predicates += 1;
+                self.compile_predicate(predicate)
+            }
+            QueryExpr::Similar(clause) => {
+                plan.similarity_clauses += 1;
+                self.compile_similarity(clause)
+            }
+        }
+    }
+
+    fn compile_predicate(&self, predicate: &FieldPredicate) -> Result<PlanNode, QueryError> {
+        if predicate.key.is_empty() {
+            return Err(QueryError::InvalidQuery(
+                "Field predicate requires a key".to_string(),
+            ));
+        }
+
+        let operands = match predicate.op {
+            PredicateOp::Exists => Vec::new(),
+            PredicateOp::In => match &predicate.value {
+                Value::Array(values) if !values.is_empty() => values
+                    .iter()
+                    .map(Operand::from_json)
+                    .collect::<Result<Vec<_>, _>>()?,
+                _ => {
+                    return Err(QueryError::InvalidQuery(format!(
+                        "'in' on '{}' requires a non-empty array",
+                        predicate.key
+                    )))
+                }
+            },
+            _ => vec![Operand::from_json(&predicate.value)?],
+        };
+
+        Ok(PlanNode::Metadata {
+            key: predicate.key.clone(),
+            op: predicate.op,
+            operands,
+        })
+    }
+
+    fn compile_similarity(&self, clause: &SimilarityClause) -> Result<PlanNode, QueryError> {
+        if clause.vector.len() != self.dimensions {
+            return Err(QueryError::InvalidQuery(format!(
+                "Similarity vector dimensions mismatch: expected {}, got {}",
+                self.dimensions,
+                clause.vector.len()
+            )));
+        }
+        if !clause.max_distance.is_finite() || clause.max_distance < 0.0 {
+            return Err(QueryError::InvalidQuery(
+                "max_distance must be a non-negative number".to_string(),
+            ));
+        }
+
+        Ok(PlanNode::Similarity {
+            vector: Vector::new(clause.vector.clone()),
+            max_distance: clause.max_distance,
+        })
+    }
+}
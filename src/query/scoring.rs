@@ -0,0 +1,430 @@
+//! Custom scoring functions loaded at runtime as WebAssembly modules.
+//!
+//! A scorer re-scores the candidates of a search with domain logic the
+//! built-in metrics can't express, without forking the crate. Modules are
+//! registered under a name and picked per search. Each one must export:
+//!
+//! - `memory`, its linear memory
+//! - `alloc(len: i32) -> i32`, returning the address of `len` free bytes
+//! - `score(query: i32, query_len: i32, vector: i32, vector_len: i32,
+//!   metadata: i32, metadata_len: i32, distance: f32) -> f32`
+//!
+//! Vectors are passed as little-endian `f32` arrays, with lengths counted
+//! in elements. Metadata is passed as a UTF-8 JSON object, with a length
+//! of zero when the vector has none. `distance` is the score the index
+//! gave the candidate. Higher scores rank first.
+//!
+//! Modules get no imports, so they can reach nothing outside their own
+//! memory. Every call is metered with fuel and memory growth is capped
+//! (see [`ScorerLimits`]), so a runaway scorer fails its search instead of
+//! stalling it. An instance lives for one search; memory handed out by
+//! `alloc` is never freed by the host, and buffers are reused across
+//! candidates where they fit.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::core::metrics::MetricsCollector;
+use crate::core::vector::Vector;
+use crate::sharding::vector_index::SearchResult;
+use crate::utils::errors::ScoringError;
+
+/// Bounds on what scorers may consume
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScorerLimits {
+    /// Fuel per candidate scored; one unit is roughly one wasm instruction
+    pub fuel_per_call: u64,
+    /// Largest a scorer's linear memory may grow
+    pub max_memory_bytes: usize,
+    /// Candidates fetched from the index and scored per requested result
+    pub oversample: usize,
+}
+
+impl Default for ScorerLimits {
+    fn default() -> Self {
+        Self {
+            fuel_per_call: 1_000_000,
+            max_memory_bytes: 16 * 1024 * 1024,
+            oversample: 4,
+        }
+    }
+}
+
+/// A registered scorer, as listed by `GET /api/scorers`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScorerInfo {
+    pub name: String,
+    /// Size of the module as uploaded
+    pub module_bytes: usize,
+    pub fuel_per_call: u64,
+    pub registered_at: DateTime<Utc>,
+}
+
+struct Scorer {
+    info: ScorerInfo,
+    #[cfg(feature = "wasm")]
+    module: wasmtime::Module,
+}
+
+/// Scorers available to searches, by name
+pub struct ScoringPlugins {
+    limits: ScorerLimits,
+    scorers: RwLock<HashMap<String, Arc<Scorer>>>,
+    #[cfg(feature = "wasm")]
+    engine: wasmtime::Engine,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl std::fmt::Debug for ScoringPlugins {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScoringPlugins")
+            .field("limits", &self.limits)
+            .finish()
+    }
+}
+
+impl ScoringPlugins {
+    pub fn new(metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            limits: ScorerLimits::default(),
+            scorers: RwLock::new(HashMap::new()),
+            #[cfg(feature = "wasm")]
+            engine: runtime::engine(),
+            metrics,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: ScorerLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> ScorerLimits {
+        self.limits
+    }
+
+    /// Compile and check a module, then make it available as `name`,
+    /// replacing any scorer already registered under it
+    pub async fn register(&self, name: &str, wasm: &[u8]) -> Result<ScorerInfo, ScoringError> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(ScoringError::InvalidName(name.to_string()));
+        }
+
+        #[cfg(feature = "wasm")]
+        {
+            let engine = self.engine.clone();
+            let limits = self.limits;
+            let wasm = wasm.to_vec();
+            let module_bytes = wasm.len();
+            // Compiling is CPU-bound and can take a while for large modules
+            let module =
+                tokio::task::spawn_blocking(move || runtime::compile(&engine, &limits, &wasm))
+                    .await
+                    .map_err(|e| ScoringError::InvalidModule(e.to_string()))??;
+            let info = ScorerInfo {
+                name: name.to_string(),
+                module_bytes,
+                fuel_per_call: self.limits.fuel_per_call,
+                registered_at: Utc::now(),
+            };
+            self.scorers.write().await.insert(
+                name.to_string(),
+                Arc::new(Scorer {
+                    info: info.clone(),
+                    module,
+                }),
+            );
+            tracing::info!("Registered scorer {} ({} bytes)", name, info.module_bytes);
+            Ok(info)
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            let _ = wasm;
+            Err(ScoringError::Unsupported)
+        }
+    }
+
+    /// Remove a scorer, returning it if it was registered
+    pub async fn unregister(&self, name: &str) -> Option<ScorerInfo> {
+        let removed = self.scorers.write().await.remove(name)?;
+        Some(removed.info.clone())
+    }
+
+    /// Registered scorers, by name
+    pub async fn list(&self) -> Vec<ScorerInfo> {
+        let mut scorers: Vec<ScorerInfo> = self
+            .scorers
+            .read()
+            .await
+            .values()
+            .map(|scorer| scorer.info.clone())
+            .collect();
+        scorers.sort_by(|a, b| a.name.cmp(&b.name));
+        scorers
+    }
+
+    pub async fn contains(&self, name: &str) -> bool {
+        self.scorers.read().await.contains_key(name)
+    }
+
+    /// Replace each result's score with the scorer's and order them best
+    /// first, keeping the index's order for ties
+    pub async fn score(
+        &self,
+        name: &str,
+        query: &Vector,
+        results: Vec<SearchResult>,
+    ) -> Result<Vec<SearchResult>, ScoringError> {
+        let scorer = self
+            .scorers
+            .read()
+            .await
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ScoringError::UnknownScorer(name.to_string()))?;
+        if results.is_empty() {
+            return Ok(results);
+        }
+
+        #[cfg(feature = "wasm")]
+        {
+            let engine = self.engine.clone();
+            let limits = self.limits;
+            let query = query.values.clone();
+            let candidates = results.len() as u64;
+            let scored = tokio::task::spawn_blocking(move || {
+                let scores = runtime::score_all(
+                    &engine,
+                    &scorer.module,
+                    &limits,
+                    &scorer.info.name,
+                    &query,
+                    &results,
+                )?;
+                let mut scored: Vec<SearchResult> = results
+                    .into_iter()
+                    .zip(scores)
+                    .map(|(result, score)| SearchResult { score, ..result })
+                    .collect();
+                scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+                Ok::<_, ScoringError>(scored)
+            })
+            .await
+            .map_err(|e| ScoringError::Failed {
+                scorer: name.to_string(),
+                reason: e.to_string(),
+            })?;
+            match &scored {
+                Ok(_) => {
+                    self.metrics
+                        .increment_counter("scoring.candidates_scored", candidates)
+                        .await
+                }
+                Err(_) => self.metrics.increment_counter("scoring.failures", 1).await,
+            }
+            scored
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            // Nothing can be registered without the runtime
+            let _ = (scorer, query, &self.metrics);
+            Err(ScoringError::Unsupported)
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod runtime {
+    use wasmtime::{
+        Config, Engine, Instance, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, Trap,
+        TypedFunc,
+    };
+
+    use super::ScorerLimits;
+    use crate::sharding::vector_index::SearchResult;
+    use crate::utils::errors::ScoringError;
+
+    /// Arguments of the exported `score` function
+    type ScoreArgs = (i32, i32, i32, i32, i32, i32, f32);
+
+    pub(super) fn engine() -> Engine {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        // Only fails for contradictory settings, which this doesn't have
+        Engine::new(&config).expect("fuel-metered wasm engine")
+    }
+
+    /// Compile a module and check it instantiates with the expected exports
+    pub(super) fn compile(
+        engine: &Engine,
+        limits: &ScorerLimits,
+        wasm: &[u8],
+    ) -> Result<Module, ScoringError> {
+        let module =
+            Module::new(engine, wasm).map_err(|e| ScoringError::InvalidModule(e.to_string()))?;
+        match Instantiated::new(engine, &module, limits, "") {
+            Ok(_) => Ok(module),
+            Err(ScoringError::Failed { reason, .. }) => Err(ScoringError::InvalidModule(reason)),
+            Err(e) => Err(ScoringError::InvalidModule(e.to_string())),
+        }
+    }
+
+    pub(super) fn score_all(
+        engine: &Engine,
+        module: &Module,
+        limits: &ScorerLimits,
+        scorer: &str,
+        query: &[f32],
+        results: &[SearchResult],
+    ) -> Result<Vec<f32>, ScoringError> {
+        let mut instance = Instantiated::new(engine, module, limits, scorer)?;
+        let query_ptr = instance.write(&mut None, &f32_bytes(query))?;
+        let (mut vector_buffer, mut metadata_buffer) = (None, None);
+
+        let mut scores = Vec::with_capacity(results.len());
+        for result in results {
+            let metadata = match &result.metadata {
+                Some(metadata) => serde_json::to_vec(metadata).map_err(|e| instance.failed(e))?,
+                None => Vec::new(),
+            };
+            let vector_ptr =
+                instance.write(&mut vector_buffer, &f32_bytes(&result.vector.values))?;
+            let metadata_ptr = instance.write(&mut metadata_buffer, &metadata)?;
+            let score = instance.call((
+                query_ptr,
+                query.len() as i32,
+                vector_ptr,
+                result.vector.values.len() as i32,
+                metadata_ptr,
+                metadata.len() as i32,
+                result.score,
+            ))?;
+            if !score.is_finite() {
+                return Err(instance.failed(format!("non-finite score {}", score)));
+            }
+            scores.push(score);
+        }
+        Ok(scores)
+    }
+
+    fn f32_bytes(values: &[f32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    /// Address and size of a guest buffer that can be written again
+    type Buffer = Option<(i32, usize)>;
+
+    /// One scorer instance with its fuel and memory limits
+    struct Instantiated<'a> {
+        store: Store<StoreLimits>,
+        memory: Memory,
+        alloc: TypedFunc<i32, i32>,
+        score: TypedFunc<ScoreArgs, f32>,
+        limits: &'a ScorerLimits,
+        scorer: &'a str,
+    }
+
+    impl<'a> Instantiated<'a> {
+        fn new(
+            engine: &Engine,
+            module: &Module,
+            limits: &'a ScorerLimits,
+            scorer: &'a str,
+        ) -> Result<Self, ScoringError> {
+            let store_limits = StoreLimitsBuilder::new()
+                .memory_size(limits.max_memory_bytes)
+                .instances(1)
+                .build();
+            let mut store = Store::new(engine, store_limits);
+            store.limiter(|limits| limits);
+            let failed = |reason: String| ScoringError::Failed {
+                scorer: scorer.to_string(),
+                reason,
+            };
+            // Start functions and data initialization are metered too
+            store
+                .set_fuel(limits.fuel_per_call)
+                .map_err(|e| failed(e.to_string()))?;
+            // No imports: a scorer that needs any can't be instantiated
+            let instance =
+                Instance::new(&mut store, module, &[]).map_err(|e| failed(e.to_string()))?;
+            let memory = instance
+                .get_memory(&mut store, "memory")
+                .ok_or_else(|| failed("no exported memory".into()))?;
+            let alloc = instance
+                .get_typed_func(&mut store, "alloc")
+                .map_err(|e| failed(format!("alloc: {}", e)))?;
+            let score = instance
+                .get_typed_func(&mut store, "score")
+                .map_err(|e| failed(format!("score: {}", e)))?;
+            Ok(Self {
+                store,
+                memory,
+                alloc,
+                score,
+                limits,
+                scorer,
+            })
+        }
+
+        fn failed(&self, reason: impl ToString) -> ScoringError {
+            ScoringError::Failed {
+                scorer: self.scorer.to_string(),
+                reason: reason.to_string(),
+            }
+        }
+
+        /// Map a trap to an error, telling fuel exhaustion apart
+        fn trapped(&self, error: wasmtime::Error) -> ScoringError {
+            if error.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) {
+                ScoringError::OutOfFuel {
+                    scorer: self.scorer.to_string(),
+                    fuel: self.limits.fuel_per_call,
+                }
+            } else {
+                self.failed(error)
+            }
+        }
+
+        fn refuel(&mut self) -> Result<(), ScoringError> {
+            self.store
+                .set_fuel(self.limits.fuel_per_call)
+                .map_err(|e| self.failed(e))
+        }
+
+        /// Copy bytes into the guest, reusing `buffer` when they fit and
+        /// allocating a new one otherwise
+        fn write(&mut self, buffer: &mut Buffer, bytes: &[u8]) -> Result<i32, ScoringError> {
+            let ptr = match *buffer {
+                Some((ptr, size)) if size >= bytes.len() => ptr,
+                _ => {
+                    self.refuel()?;
+                    let ptr = self
+                        .alloc
+                        .call(&mut self.store, bytes.len() as i32)
+                        .map_err(|e| self.trapped(e))?;
+                    *buffer = Some((ptr, bytes.len()));
+                    ptr
+                }
+            };
+            self.memory
+                .write(&mut self.store, ptr as u32 as usize, bytes)
+                .map_err(|e| self.failed(format!("alloc returned {}: {}", ptr, e)))?;
+            Ok(ptr)
+        }
+
+        fn call(&mut self, args: ScoreArgs) -> Result<f32, ScoringError> {
+            self.refuel()?;
+            self.score
+                .call(&mut self.store, args)
+                .map_err(|e| self.trapped(e))
+        }
+    }
+}
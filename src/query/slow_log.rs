@@ -0,0 +1,107 @@
+//! Searches that took longer than a threshold.
+//!
+//! The slow-query log keeps the most recent searches whose latency reached
+//! its threshold. Searches that were also given a query id by the
+//! [`SearchLog`] can be joined with the feedback clients sent about them, to
+//! tell whether the queries that are slow are also the ones returning poor
+//! results.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use super::feedback::{LabelledImpression, SearchLog};
+
+/// Latency from which a search is logged by default
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(200);
+
+/// Slow searches kept by default before the oldest are dropped
+pub const DEFAULT_SLOW_QUERY_CAPACITY: usize = 1_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlowQuery {
+    /// Id the search log gave the search, if one is configured
+    pub query_id: Option<Uuid>,
+    pub shard_id: Uuid,
+    pub limit: usize,
+    pub results: usize,
+    /// The search hit its deadline and returned what it had
+    pub partial: bool,
+    pub latency_ms: u64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// A slow query with the results it showed and the feedback they received
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlowQueryFeedback {
+    pub query: SlowQuery,
+    /// `None` when the search log has no impression for the query
+    pub impression: Option<LabelledImpression>,
+}
+
+/// Bounded in-memory log of slow searches
+#[derive(Debug)]
+pub struct SlowQueryLog {
+    threshold: Duration,
+    capacity: usize,
+    queries: RwLock<VecDeque<SlowQuery>>,
+}
+
+impl Default for SlowQueryLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_SLOW_QUERY_THRESHOLD)
+    }
+}
+
+impl SlowQueryLog {
+    pub fn new(threshold: Duration) -> Self {
+        Self::with_capacity(threshold, DEFAULT_SLOW_QUERY_CAPACITY)
+    }
+
+    pub fn with_capacity(threshold: Duration, capacity: usize) -> Self {
+        Self {
+            threshold,
+            capacity: capacity.max(1),
+            queries: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Keep `query` if its latency reached the threshold. Returns whether
+    /// it was kept.
+    pub async fn record(&self, query: SlowQuery) -> bool {
+        if u128::from(query.latency_ms) < self.threshold.as_millis() {
+            return false;
+        }
+        let mut queries = self.queries.write().await;
+        while queries.len() >= self.capacity {
+            queries.pop_front();
+        }
+        queries.push_back(query);
+        true
+    }
+
+    /// Slow queries kept, oldest first
+    pub async fn queries(&self) -> Vec<SlowQuery> {
+        self.queries.read().await.iter().cloned().collect()
+    }
+
+    /// Every slow query joined with its impression and feedback in `log`
+    pub async fn join(&self, log: &SearchLog) -> Vec<SlowQueryFeedback> {
+        let mut joined = Vec::new();
+        for query in self.queries().await {
+            let impression = match query.query_id {
+                Some(query_id) => log.impression(query_id).await,
+                None => None,
+            };
+            joined.push(SlowQueryFeedback { query, impression });
+        }
+        joined
+    }
+}
@@ -0,0 +1,177 @@
+//! Query-time synonym and expansion dictionaries.
+//!
+//! Each collection can have a [`SynonymDictionary`] of interchangeable terms
+//! and one-way expansions. Text searches on the collection are rewritten
+//! into the original query plus one variant per applicable substitution,
+//! which are then searched either as one averaged embedding or as separate
+//! searches whose results are fused. Terms may span several words; matching
+//! is case-insensitive and on whole words.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use crate::utils::errors::SynonymError;
+
+/// Query variants searched per request by default, the original included
+pub const DEFAULT_MAX_VARIANTS: usize = 8;
+
+/// How the variants of an expanded query are searched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpansionMode {
+    /// Average the variants' embeddings and search once
+    Union,
+    /// Search each variant and merge the results, by default with
+    /// reciprocal rank fusion
+    #[default]
+    Fusion,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SynonymDictionary {
+    /// Groups of interchangeable terms; any term in a group is expanded to
+    /// every other
+    #[serde(default)]
+    pub synonyms: Vec<Vec<String>>,
+    /// One-way expansions: a query containing the key is also searched with
+    /// each of its values in its place
+    #[serde(default)]
+    pub expansions: HashMap<String, Vec<String>>,
+}
+
+/// Lowercase and collapse whitespace, so terms and queries compare word by word
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl SynonymDictionary {
+    /// Check that no term is blank and every group has an alternative
+    pub fn validate(&self) -> Result<(), SynonymError> {
+        for group in &self.synonyms {
+            if group.len() < 2 {
+                return Err(SynonymError::GroupTooSmall(group.clone()));
+            }
+        }
+        let terms = self
+            .synonyms
+            .iter()
+            .flatten()
+            .chain(self.expansions.keys())
+            .chain(self.expansions.values().flatten());
+        for term in terms {
+            if normalize(term).is_empty() {
+                return Err(SynonymError::EmptyTerm);
+            }
+        }
+        Ok(())
+    }
+
+    /// Alternatives per normalized term
+    fn alternatives(&self) -> Vec<(String, Vec<String>)> {
+        let mut alternatives: Vec<(String, Vec<String>)> = Vec::new();
+        let mut add = |term: &str, alternative: &str| {
+            let (term, alternative) = (normalize(term), normalize(alternative));
+            if term == alternative {
+                return;
+            }
+            match alternatives.iter_mut().find(|(t, _)| *t == term) {
+                Some((_, alts)) if alts.contains(&alternative) => {}
+                Some((_, alts)) => alts.push(alternative),
+                None => alternatives.push((term, vec![alternative])),
+            }
+        };
+        for group in &self.synonyms {
+            for term in group {
+                for alternative in group {
+                    add(term, alternative);
+                }
+            }
+        }
+        let mut keys: Vec<&String> = self.expansions.keys().collect();
+        keys.sort();
+        for key in keys {
+            for alternative in &self.expansions[key] {
+                add(key, alternative);
+            }
+        }
+        // Longer terms first, so "heart attack" is tried before "heart"
+        alternatives.sort_by_key(|(term, _)| std::cmp::Reverse(term.len()));
+        alternatives
+    }
+
+    /// The query followed by its variants, each with one term replaced by an
+    /// alternative, at most `max_variants` in total
+    pub fn expand(&self, query: &str, max_variants: usize) -> Vec<String> {
+        let normalized = normalize(query);
+        let mut variants = vec![query.to_string()];
+        let padded = format!(" {} ", normalized);
+        for (term, alternatives) in self.alternatives() {
+            let Some(start) = padded.find(&format!(" {} ", term)) else {
+                continue;
+            };
+            for alternative in alternatives {
+                let variant = format!(
+                    "{}{}{}",
+                    &padded[..start + 1],
+                    alternative,
+                    &padded[start + 1 + term.len()..]
+                )
+                .trim()
+                .to_string();
+                if !variants.contains(&variant) && variant != normalized {
+                    variants.push(variant);
+                }
+            }
+        }
+        variants.truncate(max_variants.max(1));
+        variants
+    }
+}
+
+/// Synonym dictionaries per collection
+#[derive(Debug, Default)]
+pub struct SynonymStore {
+    dictionaries: RwLock<HashMap<String, SynonymDictionary>>,
+}
+
+impl SynonymStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the collection's dictionary
+    pub async fn set(
+        &self,
+        collection: &str,
+        dictionary: SynonymDictionary,
+    ) -> Result<(), SynonymError> {
+        dictionary.validate()?;
+        self.dictionaries
+            .write()
+            .await
+            .insert(collection.to_string(), dictionary);
+        Ok(())
+    }
+
+    pub async fn get(&self, collection: &str) -> Option<SynonymDictionary> {
+        self.dictionaries.read().await.get(collection).cloned()
+    }
+
+    /// Remove the collection's dictionary, returning whether it had one
+    pub async fn remove(&self, collection: &str) -> bool {
+        self.dictionaries.write().await.remove(collection).is_some()
+    }
+
+    /// Expand `query` with the collection's dictionary; queries on
+    /// collections without one are returned unchanged
+    pub async fn expand(&self, collection: &str, query: &str, max_variants: usize) -> Vec<String> {
+        match self.dictionaries.read().await.get(collection) {
+            Some(dictionary) => dictionary.expand(query, max_variants),
+            None => vec![query.to_string()],
+        }
+    }
+}
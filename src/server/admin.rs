@@ -0,0 +1,308 @@
+//! Admin routes: data purge, retention, rebalancing, background tasks,
+//! circuit breakers and peer trust.
+
+use crate::server::api::BlockPeerRequest;
+use crate::sharding::purge::PurgeRequest;
+use crate::sharding::retention::RetentionPolicy;
+use uuid::Uuid;
+use warp::filters::BoxedFilter;
+use warp::{Filter, Reply};
+
+use super::{error_reply, json_body, not_configured, Server};
+
+/// Admin routes under `api_path`, in the order they are tried
+pub(super) fn routes(
+    server: &Server,
+    api_path: &str,
+) -> Vec<BoxedFilter<(warp::reply::Response,)>> {
+    let api_path = api_path.to_string();
+
+    let purge_for_admin = server.purge.clone();
+    let admin_purge = warp::path(api_path.clone())
+        .and(warp::path("admin"))
+        .and(warp::path("purge"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body::<PurgeRequest>())
+        .and_then(move |req: PurgeRequest| {
+            let purge_opt = purge_for_admin.clone();
+            async move {
+                let purge = match purge_opt {
+                    Some(purge) => purge,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Data purge")),
+                };
+                match purge.purge(&req).await {
+                    Ok(certificate) => Ok(warp::reply::json(&certificate).into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let tasks_for_list = server.tasks.clone();
+    let list_tasks = warp::path(api_path.clone())
+        .and(warp::path("admin"))
+        .and(warp::path("tasks"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .map(move || warp::reply::json(&tasks_for_list.snapshot()).into_response())
+        .boxed();
+
+    let retention_for_status = server.retention.clone();
+    let retention_status = warp::path(api_path.clone())
+        .and(warp::path("admin"))
+        .and(warp::path("retention"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let retention_opt = retention_for_status.clone();
+            async move {
+                match retention_opt {
+                    Some(retention) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&retention.status().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Data retention")),
+                }
+            }
+        })
+        .boxed();
+
+    let retention_for_policy = server.retention.clone();
+    let set_retention_policy = warp::path(api_path.clone())
+        .and(warp::path("admin"))
+        .and(warp::path("retention"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(json_body::<RetentionPolicy>())
+        .and_then(move |shard_id: Uuid, policy: RetentionPolicy| {
+            let retention_opt = retention_for_policy.clone();
+            async move {
+                let retention = match retention_opt {
+                    Some(retention) => retention,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Data retention")),
+                };
+                match retention.set_policy(shard_id, policy.clone()).await {
+                    Ok(()) => Ok(warp::reply::json(&policy).into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let retention_for_action = server.retention.clone();
+    let retention_action = warp::path(api_path.clone())
+        .and(warp::path("admin"))
+        .and(warp::path("retention"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::post())
+        .and_then(move |action: String| {
+            let retention_opt = retention_for_action.clone();
+            async move {
+                let retention = match retention_opt {
+                    Some(retention) => retention,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Data retention")),
+                };
+                match action.as_str() {
+                    "pause" => retention.pause(),
+                    "resume" => retention.resume(),
+                    // Enforce now and report what was reclaimed
+                    "run" => {
+                        return Ok(warp::reply::json(&retention.run_once().await).into_response())
+                    }
+                    _ => {
+                        return Ok(error_reply(
+                            format!("Unknown retention action: {}", action),
+                            warp::http::StatusCode::NOT_FOUND,
+                        ))
+                    }
+                }
+                Ok(warp::reply::json(&retention.status().await).into_response())
+            }
+        })
+        .boxed();
+
+    let rebalancer_for_status = server.rebalancer.clone();
+    let rebalance_status = warp::path(api_path.clone())
+        .and(warp::path("admin"))
+        .and(warp::path("rebalance"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let rebalancer_opt = rebalancer_for_status.clone();
+            async move {
+                match rebalancer_opt {
+                    Some(rebalancer) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&rebalancer.status().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Shard rebalancing")),
+                }
+            }
+        })
+        .boxed();
+
+    // Rebalance every split family now and report the moves made
+    let rebalancer_for_run = server.rebalancer.clone();
+    let run_rebalance = warp::path(api_path.clone())
+        .and(warp::path("admin"))
+        .and(warp::path("rebalance"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and_then(move || {
+            let rebalancer_opt = rebalancer_for_run.clone();
+            async move {
+                match rebalancer_opt {
+                    Some(rebalancer) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&rebalancer.run_once().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Shard rebalancing")),
+                }
+            }
+        })
+        .boxed();
+
+    let breakers_for_list = server.circuit_breakers.clone();
+    let list_circuit_breakers = warp::path(api_path.clone())
+        .and(warp::path("admin"))
+        .and(warp::path("circuit-breakers"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let breakers_opt = breakers_for_list.clone();
+            async move {
+                match breakers_opt {
+                    Some(breakers) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&breakers.metrics().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Circuit breakers")),
+                }
+            }
+        })
+        .boxed();
+
+    let breakers_for_override = server.circuit_breakers.clone();
+    let override_circuit_breaker = warp::path(api_path.clone())
+        .and(warp::path("admin"))
+        .and(warp::path("circuit-breakers"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::post())
+        .and_then(move |name: String, action: String| {
+            let breakers_opt = breakers_for_override.clone();
+            async move {
+                let breakers = match breakers_opt {
+                    Some(breakers) => breakers,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Circuit breakers")),
+                };
+                let metrics = match action.as_str() {
+                    "reset" => breakers.reset(&name).await,
+                    "trip" => breakers.trip(&name).await,
+                    _ => {
+                        return Ok(error_reply(
+                            format!("Unknown circuit breaker action: {}", action),
+                            warp::http::StatusCode::NOT_FOUND,
+                        ))
+                    }
+                };
+                match metrics {
+                    Some(metrics) => Ok(warp::reply::json(&metrics).into_response()),
+                    None => Ok(error_reply(
+                        format!("Unknown circuit breaker: {}", name),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let trust_for_list = server.trust.clone();
+    let list_peer_trust = warp::path(api_path.clone())
+        .and(warp::path("admin"))
+        .and(warp::path("peers"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let trust_opt = trust_for_list.clone();
+            async move {
+                match trust_opt {
+                    Some(trust) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&trust.peers().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Peer trust")),
+                }
+            }
+        })
+        .boxed();
+
+    let trust_for_block = server.trust.clone();
+    let block_peer = warp::path(api_path.clone())
+        .and(warp::path("admin"))
+        .and(warp::path("peers"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("block"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body::<BlockPeerRequest>())
+        .and_then(move |peer_id: String, req: BlockPeerRequest| {
+            let trust_opt = trust_for_block.clone();
+            async move {
+                match trust_opt {
+                    Some(trust) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&trust.block(&peer_id, &req.reason).await)
+                            .into_response(),
+                    ),
+                    None => Ok(not_configured("Peer trust")),
+                }
+            }
+        })
+        .boxed();
+
+    let trust_for_unblock = server.trust.clone();
+    let unblock_peer = warp::path(api_path.clone())
+        .and(warp::path("admin"))
+        .and(warp::path("peers"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("unblock"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and_then(move |peer_id: String| {
+            let trust_opt = trust_for_unblock.clone();
+            async move {
+                let trust = match trust_opt {
+                    Some(trust) => trust,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Peer trust")),
+                };
+                match trust.unblock(&peer_id).await {
+                    Some(peer) => Ok(warp::reply::json(&peer).into_response()),
+                    None => Ok(error_reply(
+                        format!("Unknown peer: {}", peer_id),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    vec![
+        admin_purge,
+        retention_status,
+        set_retention_policy,
+        retention_action,
+        rebalance_status,
+        run_rebalance,
+        list_tasks,
+        list_circuit_breakers,
+        override_circuit_breaker,
+        list_peer_trust,
+        block_peer,
+        unblock_peer,
+    ]
+}
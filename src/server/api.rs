@@ -1,9 +1,24 @@
-use serde::{Serialize, Deserialize};
-use uuid::Uuid;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use uuid::Uuid;
 
+use crate::connectors::{EmbeddingFormat, SourceConfig};
 use crate::core::vector::Vector;
-use crate::sharding::vector_index::DistanceMetric;
+use crate::evaluation::RecallReport;
+use crate::nerv::jobs::JobKind;
+use crate::query::experiments::Assignment;
+use crate::query::feedback::{FeedbackLabel, ShownResult};
+use crate::query::fusion::FusionStrategy;
+use crate::query::pipeline::StageTiming;
+use crate::query::synonyms::ExpansionMode;
+use crate::query::{
+    ComposeOp, ComposeTerm, Diversification, FacetRequest, Facets, MetadataFilter, QueryExpr,
+};
+use crate::sharding::health::IndexHealth;
+use crate::sharding::manager::{Shard, ShardStatus};
+use crate::sharding::outliers::OutlierParams;
+use crate::sharding::sketch::IndexStatistics;
+use crate::sharding::vector_index::{DistanceMetric, IndexType, VectorEntry};
 
 // API request and response types
 
@@ -17,12 +32,15 @@ pub struct CreateShardResponse {
     pub shard_id: Uuid,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct CreateIndexRequest {
     pub shard_id: Uuid,
     pub name: String,
     pub dimensions: usize,
     pub distance_metric: String,
+    /// e.g. `{"type": "hnsw", "m": 32}`; Hilbert when omitted
+    #[serde(default)]
+    pub index_type: IndexType,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -31,6 +49,49 @@ pub struct CreateIndexResponse {
     pub index_name: String,
     pub dimensions: usize,
     pub distance_metric: String,
+    pub index_type: IndexType,
+}
+
+/// A shard as listed by `GET /api/shards`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShardInfo {
+    pub shard_id: Uuid,
+    pub name: String,
+    pub status: ShardStatus,
+    pub vector_count: usize,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<Shard> for ShardInfo {
+    fn from(shard: Shard) -> Self {
+        Self {
+            shard_id: shard.id,
+            name: shard.name,
+            status: shard.status,
+            vector_count: shard.vector_count,
+            created_at: shard.created_at,
+            updated_at: shard.updated_at,
+        }
+    }
+}
+
+/// Answer to `DELETE /api/shards/{id}`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteShardResponse {
+    /// The shard and every shard split off it
+    pub deleted: Vec<Uuid>,
+}
+
+/// An index as listed by `GET /api/shards/{id}/indexes`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IndexInfo {
+    pub shard_id: Uuid,
+    pub index_name: String,
+    pub dimensions: usize,
+    pub distance_metric: String,
+    pub index_type: IndexType,
+    pub vector_count: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -45,11 +106,115 @@ pub struct AddVectorResponse {
     pub vector_id: Uuid,
 }
 
+/// Body of `POST /api/vectors/batch`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddVectorsBatchRequest {
+    pub shard_id: Uuid,
+    pub vectors: Vec<BatchVector>,
+}
+
+/// One vector of a batch insert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchVector {
+    pub vector: Vec<f32>,
+    #[serde(default)]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+/// Answer to `POST /api/vectors/batch`: IDs in the order the vectors were sent
 #[derive(Debug, Serialize, Deserialize)]
+pub struct AddVectorsBatchResponse {
+    pub vector_ids: Vec<Uuid>,
+}
+
+/// Query of `GET` and `DELETE /api/vectors/{id}`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VectorQuery {
+    /// Only look in this shard and those split off it, rather than in all
+    #[serde(default)]
+    pub shard_id: Option<Uuid>,
+}
+
+/// Answer to `GET /api/vectors/{id}`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VectorResponse {
+    pub vector_id: Uuid,
+    /// Shard holding the vector
+    pub shard_id: Uuid,
+    pub vector: Vec<f32>,
+    pub metadata: Option<HashMap<String, String>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl VectorResponse {
+    pub fn new(shard_id: Uuid, entry: VectorEntry) -> Self {
+        Self {
+            vector_id: entry.id,
+            shard_id,
+            vector: entry.vector.values,
+            metadata: entry.metadata,
+            created_at: entry.created_at,
+        }
+    }
+}
+
+/// Answer to `DELETE /api/vectors/{id}`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteVectorResponse {
+    pub vector_id: Uuid,
+    /// Shard the vector was deleted from
+    pub shard_id: Uuid,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct SearchVectorsRequest {
     pub shard_id: Uuid,
     pub query_vector: Vec<f32>,
     pub limit: usize,
+    /// Optional query DSL filter applied to candidates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<QueryExpr>,
+    /// Metadata conditions candidates must also meet, e.g.
+    /// `{"category": "product", "price": {"lt": 100}}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_filter: Option<MetadataFilter>,
+    /// Optional `group_by` and `mmr` post-processing of the results
+    #[serde(flatten)]
+    pub diversify: Diversification,
+    /// Metadata value counts to return alongside the results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facets: Option<FacetRequest>,
+    /// Return the best results found so far once this many milliseconds
+    /// have passed, instead of scanning to completion
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Rerank the results with the model trained from feedback
+    #[serde(default)]
+    pub rerank: bool,
+    /// Re-score the candidates with this registered scorer plugin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scorer: Option<String>,
+    /// More query vectors searched alongside `query_vector`, e.g. one per
+    /// aspect of the query, with their results merged by `fusion`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_queries: Vec<Vec<f32>>,
+    /// `max` unless given, so scores stay distances for distance metrics
+    #[serde(default)]
+    pub fusion: FusionStrategy,
+}
+
+impl SearchVectorsRequest {
+    /// `filter` and `metadata_filter` combined into one expression
+    pub fn filter_expr(&self) -> Option<QueryExpr> {
+        let metadata = self
+            .metadata_filter
+            .as_ref()
+            .and_then(MetadataFilter::to_expr);
+        match (self.filter.clone(), metadata) {
+            (Some(filter), Some(metadata)) => Some(QueryExpr::And(vec![filter, metadata])),
+            (filter, metadata) => filter.or(metadata),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -59,16 +224,249 @@ pub struct SearchResult {
     pub metadata: Option<HashMap<String, String>>,
 }
 
+impl From<&SearchResult> for ShownResult {
+    fn from(result: &SearchResult) -> Self {
+        ShownResult {
+            id: result.id.clone(),
+            score: result.score,
+            metadata_fields: result.metadata.as_ref().map_or(0, |m| m.len()),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchVectorsResponse {
     pub results: Vec<SearchResult>,
+    /// Present when the request asked for facets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facets: Option<Facets>,
+    /// The timeout passed mid-scan and these are the best results found
+    /// in time
+    #[serde(default)]
+    pub partial: bool,
+    /// Fraction of the shard's vectors searched, below 1.0 while the shard
+    /// is still being loaded from storage
+    #[serde(default = "full_coverage")]
+    pub coverage: f32,
+    /// Refer to this search when sending feedback on its results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_id: Option<Uuid>,
+    /// The experiment arm whose settings the search ran with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub experiment: Option<Assignment>,
+}
+
+fn full_coverage() -> f32 {
+    1.0
+}
+
+/// Response of `POST /api/search/pipeline`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PipelineSearchResponse {
+    pub results: Vec<SearchResult>,
+    /// Present when the pipeline had an aggregate stage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facets: Option<Facets>,
+    #[serde(default)]
+    pub partial: bool,
+    #[serde(default = "full_coverage")]
+    pub coverage: f32,
+    /// Timing and candidate counts of each stage, in the order they ran
+    pub stages: Vec<StageTiming>,
+    pub total_ms: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComposeVectorsRequest {
+    pub shard_id: Uuid,
+    #[serde(default)]
+    pub operation: ComposeOp,
+    pub terms: Vec<ComposeTerm>,
+    /// Scale the composed vector to unit length
+    #[serde(default)]
+    pub normalize: bool,
+    /// Search the shard with the composed vector
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<ComposeSearch>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComposeSearch {
+    pub limit: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<QueryExpr>,
+    /// Leave the stored terms out of the results, since an analogy query
+    /// rarely wants its own inputs back
+    #[serde(default = "default_exclude_terms")]
+    pub exclude_terms: bool,
+}
+
+fn default_exclude_terms() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComposeVectorsResponse {
+    pub vector: Vec<f32>,
+    /// Present when the request asked for a search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<Vec<SearchResult>>,
+}
+
+/// Body of `POST /api/analyze/outliers`; answered with an
+/// [`OutlierReport`](crate::sharding::outliers::OutlierReport)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OutlierRequest {
+    pub shard_id: Uuid,
+    #[serde(flatten)]
+    pub params: OutlierParams,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportRequest {
+    pub shard_id: Uuid,
+    pub source: SourceConfig,
+    /// Records read from the source per batch
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+}
+
+/// Body of `POST /api/indexes/build`; answered with an
+/// [`IndexBuild`](crate::sharding::manager::IndexBuild)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildIndexRequest {
+    /// Name of the shard to create
+    pub shard_name: String,
+    /// Embeddings file on the server
+    pub path: String,
+    /// Defaults to the format implied by the file's extension
+    #[serde(default)]
+    pub format: Option<EmbeddingFormat>,
+    /// Tensor or column holding the embeddings, for files with several
+    #[serde(default)]
+    pub tensor: Option<String>,
+    #[serde(default = "default_index_name")]
+    pub index_name: String,
+    pub distance_metric: String,
+    /// Threads to build on; one per core by default
+    #[serde(default)]
+    pub threads: Option<usize>,
+}
+
+/// Statistics of a shard's index, with its health once it has been checked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardStatsResponse {
+    #[serde(flatten)]
+    pub statistics: IndexStatistics,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<IndexHealth>,
+}
+
+fn default_index_name() -> String {
+    "main".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmitJobRequest {
+    pub kind: JobKind,
+    /// Arguments for the job's handler, e.g. `shard_id` and `threshold` for clustering
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+/// Body of `POST /api/collections/{name}/search/text`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TextSearchRequest {
+    pub query: String,
+    pub limit: usize,
+    #[serde(default)]
+    pub expansion: ExpansionMode,
+    /// Search the query as written, without the collection's synonyms
+    #[serde(default)]
+    pub exact: bool,
+    /// How the results of the variants are merged in `fusion` mode;
+    /// reciprocal rank fusion unless given
+    #[serde(default = "rank_fusion")]
+    pub fusion: FusionStrategy,
+}
+
+fn rank_fusion() -> FusionStrategy {
+    FusionStrategy::Rrf
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TextSearchResponse {
+    pub results: Vec<SearchResult>,
+    /// Queries searched: the original followed by its expansions
+    pub queries: Vec<String>,
+}
+
+/// Body of `POST /api/feedback`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FeedbackRequest {
+    pub query_id: Uuid,
+    pub result_id: String,
+    pub label: FeedbackLabel,
+}
+
+/// Search quality according to client feedback, from `GET /api/feedback/report`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FeedbackReport {
+    pub recall: RecallReport,
+    /// Recall over the searches in the slow-query log
+    pub slow_query_recall: RecallReport,
+    /// Hypothesis about index quality, when recall points to a problem
+    pub hypothesis: Option<String>,
 }
 
+/// Body of `POST /api/models/{name}/rollback`
 #[derive(Debug, Serialize, Deserialize)]
+pub struct RollbackModelRequest {
+    pub version: u32,
+}
+
+/// Body of `POST /api/admin/peers/{id}/block`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BlockPeerRequest {
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// Body of `POST /api/darwin/rollback-points`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateRollbackPointRequest {
+    pub name: String,
+}
+
+/// Query parameters for reading a shard's change feed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangesQuery {
+    /// Offset to read from; defaults to the start of the retained feed
+    pub from: Option<u64>,
+    pub limit: Option<usize>,
+    /// How long to wait for new events when none are available
+    pub wait_ms: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
 }
 
+// Errors can echo request data back, so sensitive fields are redacted on
+// the way out rather than at every call site
+impl Serialize for ErrorResponse {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("ErrorResponse", 1)?;
+        state.serialize_field(
+            "error",
+            &crate::tenancy::RedactionPolicy::global().redact_text(&self.error),
+        )?;
+        state.end()
+    }
+}
+
 // Helper functions for converting between API and internal types
 
 /// Convert a string distance metric to the internal enum
@@ -78,6 +476,7 @@ pub fn parse_distance_metric(metric: &str) -> Result<DistanceMetric, String> {
         "cosine" => Ok(DistanceMetric::Cosine),
         "manhattan" => Ok(DistanceMetric::Manhattan),
         "hamming" => Ok(DistanceMetric::Hamming),
+        "inner_product" | "innerproduct" | "dot" => Ok(DistanceMetric::InnerProduct),
         _ => Err(format!("Unknown distance metric: {}", metric)),
     }
 }
@@ -89,6 +488,7 @@ pub fn distance_metric_to_string(metric: DistanceMetric) -> String {
         DistanceMetric::Cosine => "cosine".to_string(),
         DistanceMetric::Manhattan => "manhattan".to_string(),
         DistanceMetric::Hamming => "hamming".to_string(),
+        DistanceMetric::InnerProduct => "inner_product".to_string(),
     }
 }
 
@@ -99,13 +499,14 @@ pub fn create_vector(values: Vec<f32>) -> Vector {
 
 /// Convert internal search results to API search results
 pub fn convert_search_results(
-    results: Vec<crate::sharding::vector_index::SearchResult>
+    results: Vec<crate::sharding::vector_index::SearchResult>,
 ) -> Vec<SearchResult> {
-    results.into_iter()
+    results
+        .into_iter()
         .map(|result| SearchResult {
             id: result.id.to_string(),
             score: result.score,
             metadata: result.metadata,
         })
         .collect()
-}
\ No newline at end of file
+}
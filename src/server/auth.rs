@@ -0,0 +1,280 @@
+//! API-key and JWT authentication.
+//!
+//! With [`AuthConfig`] set on `ServerConfig::auth`, requests to the API,
+//! WebSocket, Qdrant-compatible and `/v1` routes must carry a credential;
+//! health and metrics stay open for probes and scrapers. A credential is either an
+//! `X-API-Key: <key>` header or `Authorization: Bearer <token>`, where the
+//! token is one of the configured API keys or a JWT signed with HS256 under
+//! the configured secret. JWTs are checked for `exp` and `nbf`, and for
+//! `iss` and `aud` when those are configured. Rejected requests get a 401
+//! with a JSON error body and are counted in `server.auth_failures`, broken
+//! down by [`AuthError::reason`].
+
+use std::sync::Arc;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use subtle::{Choice, ConstantTimeEq};
+use tracing::warn;
+use warp::filters::path::FullPath;
+use warp::filters::BoxedFilter;
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+use crate::core::metrics::MetricsCollector;
+use crate::utils::errors::{AuthConfigError, AuthError};
+
+use super::error_reply;
+
+/// Header carrying an API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Seconds of clock skew tolerated when checking `exp` and `nbf`
+const LEEWAY_SECS: i64 = 60;
+
+/// Shortest JWT secret accepted, in bytes; HS256 keys shorter than the hash
+/// output are open to brute force
+pub const MIN_JWT_SECRET_LEN: usize = 32;
+
+/// Credentials accepted by the server
+#[derive(Clone, Default)]
+pub struct AuthConfig {
+    /// Keys accepted as `X-API-Key` or as a bearer token
+    pub api_keys: Vec<String>,
+
+    /// HS256 secret that bearer JWTs must be signed with; `None` accepts no
+    /// JWTs
+    pub jwt_secret: Option<String>,
+
+    /// Required `iss` claim
+    pub jwt_issuer: Option<String>,
+
+    /// Required `aud` claim
+    pub jwt_audience: Option<String>,
+}
+
+impl std::fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print keys or secrets
+        f.debug_struct("AuthConfig")
+            .field("api_keys", &self.api_keys.len())
+            .field("jwt", &self.jwt_secret.is_some())
+            .field("jwt_issuer", &self.jwt_issuer)
+            .field("jwt_audience", &self.jwt_audience)
+            .finish()
+    }
+}
+
+impl AuthConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_keys.push(key.into());
+        self
+    }
+
+    /// Accept JWTs signed with `secret`, which must be at least
+    /// [`MIN_JWT_SECRET_LEN`] bytes
+    pub fn with_jwt_secret(mut self, secret: impl Into<String>) -> Result<Self, AuthConfigError> {
+        let secret = secret.into();
+        if secret.len() < MIN_JWT_SECRET_LEN {
+            return Err(AuthConfigError::WeakJwtSecret {
+                len: secret.len(),
+                min: MIN_JWT_SECRET_LEN,
+            });
+        }
+        self.jwt_secret = Some(secret);
+        Ok(self)
+    }
+
+    pub fn with_jwt_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.jwt_issuer = Some(issuer.into());
+        self
+    }
+
+    pub fn with_jwt_audience(mut self, audience: impl Into<String>) -> Self {
+        self.jwt_audience = Some(audience.into());
+        self
+    }
+
+    /// Check a request's `Authorization` and `X-API-Key` header values
+    pub fn authenticate(
+        &self,
+        authorization: Option<&str>,
+        api_key: Option<&str>,
+    ) -> Result<(), AuthError> {
+        if let Some(key) = api_key {
+            return self.check_api_key(key);
+        }
+        let authorization = authorization.ok_or(AuthError::Missing)?;
+        let token = match authorization.trim().split_once(' ') {
+            Some((scheme, token)) if scheme.eq_ignore_ascii_case("bearer") => token.trim(),
+            _ => return Err(AuthError::Invalid),
+        };
+        match &self.jwt_secret {
+            Some(secret) if token.split('.').count() == 3 => self.check_jwt(secret, token),
+            _ => self.check_api_key(token),
+        }
+    }
+
+    fn check_api_key(&self, key: &str) -> Result<(), AuthError> {
+        // Compare digests against every key, so timing reveals neither which
+        // key matched nor how long the keys are
+        let key = Sha256::digest(key.as_bytes());
+        let matched = self
+            .api_keys
+            .iter()
+            .fold(Choice::from(0), |matched, candidate| {
+                matched | Sha256::digest(candidate.as_bytes()).ct_eq(&key[..])
+            });
+        if bool::from(matched) {
+            Ok(())
+        } else {
+            Err(AuthError::Invalid)
+        }
+    }
+
+    fn check_jwt(&self, secret: &str, token: &str) -> Result<(), AuthError> {
+        let (signed, signature) = token.rsplit_once('.').ok_or(AuthError::Invalid)?;
+        let (header, claims) = signed.split_once('.').ok_or(AuthError::Invalid)?;
+        let header = decode_segment(header)?;
+        match header.get("alg").and_then(Value::as_str) {
+            Some("HS256") => {}
+            other => {
+                return Err(AuthError::UnsupportedAlgorithm(
+                    other.unwrap_or("none").to_string(),
+                ))
+            }
+        }
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| AuthError::Invalid)?;
+        // verify_slice compares in constant time
+        hmac_sha256(secret.as_bytes(), signed.as_bytes())
+            .verify_slice(&signature)
+            .map_err(|_| AuthError::Invalid)?;
+
+        let claims = decode_segment(claims)?;
+        let now = chrono::Utc::now().timestamp();
+        let time = |name: &str| match claims.get(name) {
+            None => Ok(None),
+            Some(value) => value.as_i64().map(Some).ok_or(AuthError::Invalid),
+        };
+        if matches!(time("exp")?, Some(exp) if now > exp + LEEWAY_SECS) {
+            return Err(AuthError::Expired);
+        }
+        if matches!(time("nbf")?, Some(nbf) if now + LEEWAY_SECS < nbf) {
+            return Err(AuthError::NotYetValid);
+        }
+        if let Some(issuer) = &self.jwt_issuer {
+            if claims.get("iss").and_then(Value::as_str) != Some(issuer.as_str()) {
+                return Err(AuthError::Invalid);
+            }
+        }
+        if let Some(audience) = &self.jwt_audience {
+            let matches = match claims.get("aud") {
+                Some(Value::String(aud)) => aud == audience,
+                Some(Value::Array(auds)) => auds.iter().any(|aud| aud == audience.as_str()),
+                _ => false,
+            };
+            if !matches {
+                return Err(AuthError::Invalid);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sign `claims` as an HS256 JWT, e.g. to issue tokens for clients
+pub fn encode_jwt(secret: &str, claims: &Value) -> String {
+    let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"HS256","typ":"JWT"}"#);
+    let claims = URL_SAFE_NO_PAD.encode(claims.to_string());
+    let signed = format!("{}.{}", header, claims);
+    let signature = hmac_sha256(secret.as_bytes(), signed.as_bytes()).finalize();
+    format!(
+        "{}.{}",
+        signed,
+        URL_SAFE_NO_PAD.encode(signature.into_bytes())
+    )
+}
+
+fn decode_segment(segment: &str) -> Result<Value, AuthError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|_| AuthError::Invalid)?;
+    serde_json::from_slice(&bytes).map_err(|_| AuthError::Invalid)
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac
+}
+
+#[derive(Debug)]
+struct Unauthorized(AuthError);
+
+impl warp::reject::Reject for Unauthorized {}
+
+/// Passes requests outside the `protected` top-level path segments, and
+/// those inside them that authenticate. Everything passes when `auth` is
+/// `None`.
+pub(crate) fn guard(
+    auth: Option<Arc<AuthConfig>>,
+    protected: Vec<String>,
+    metrics: Arc<MetricsCollector>,
+) -> BoxedFilter<()> {
+    warp::path::full()
+        .and(warp::header::optional::<String>("authorization"))
+        .and(warp::header::optional::<String>(API_KEY_HEADER))
+        .and_then(
+            move |path: FullPath, authorization: Option<String>, api_key: Option<String>| {
+                let auth = auth.clone();
+                let protected = protected.clone();
+                let metrics = metrics.clone();
+                async move {
+                    let Some(auth) = auth else {
+                        return Ok(());
+                    };
+                    let first = path.as_str().trim_start_matches('/').split('/').next();
+                    if !protected.iter().any(|p| Some(p.as_str()) == first) {
+                        return Ok(());
+                    }
+                    match auth.authenticate(authorization.as_deref(), api_key.as_deref()) {
+                        Ok(()) => Ok(()),
+                        Err(e) => {
+                            warn!("Rejected request to {}: {}", path.as_str(), e);
+                            metrics.increment_counter("server.auth_failures", 1).await;
+                            metrics
+                                .increment_counter(
+                                    &format!("server.auth_failures.{}", e.reason()),
+                                    1,
+                                )
+                                .await;
+                            Err(warp::reject::custom(Unauthorized(e)))
+                        }
+                    }
+                }
+            },
+        )
+        .untuple_one()
+        .boxed()
+}
+
+/// Turn a [`guard`] rejection into a 401 reply; other rejections pass on
+pub(crate) async fn unauthorized(rejection: Rejection) -> Result<warp::reply::Response, Rejection> {
+    match rejection.find::<Unauthorized>() {
+        Some(Unauthorized(e)) => Ok(warp::reply::with_header(
+            error_reply(e.to_string(), StatusCode::UNAUTHORIZED),
+            "www-authenticate",
+            "Bearer",
+        )
+        .into_response()),
+        None => Err(rejection),
+    }
+}
@@ -0,0 +1,149 @@
+//! Cluster routes: peer heartbeats, delegated task reports and traces, and
+//! admission status.
+
+use crate::intelligence::delegation::{PeerHeartbeat, TaskReport};
+use crate::utils::errors::DelegationError;
+use uuid::Uuid;
+use warp::filters::BoxedFilter;
+use warp::{Filter, Reply};
+
+use super::{error_reply, json_body, not_configured, Server, TASK_REPORT_BODY_LIMIT};
+
+/// Cluster routes under `api_path`, in the order they are tried
+pub(super) fn routes(
+    server: &Server,
+    api_path: &str,
+) -> Vec<BoxedFilter<(warp::reply::Response,)>> {
+    let api_path = api_path.to_string();
+
+    let delegator_for_heartbeat = server.delegator.clone();
+    let trust_for_heartbeat = server.trust.clone();
+    let cluster_heartbeat = warp::path(api_path.clone())
+        .and(warp::path("cluster"))
+        .and(warp::path("heartbeat"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body::<PeerHeartbeat>())
+        .and_then(move |heartbeat: PeerHeartbeat| {
+            let delegator_opt = delegator_for_heartbeat.clone();
+            let trust_opt = trust_for_heartbeat.clone();
+            async move {
+                let delegator = match delegator_opt {
+                    Some(delegator) => delegator,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Task delegation")),
+                };
+                if let Some(trust) = trust_opt {
+                    let peer_id = &heartbeat.capacity.peer_id;
+                    if !trust.is_allowed(peer_id).await {
+                        return Ok(error_reply(
+                            format!("Peer {} is not trusted", peer_id),
+                            warp::http::StatusCode::FORBIDDEN,
+                        ));
+                    }
+                }
+                let renewed = delegator.heartbeat(heartbeat).await;
+                Ok(warp::reply::json(&serde_json::json!({ "renewed": renewed })).into_response())
+            }
+        })
+        .boxed();
+
+    let delegator_for_peers = server.delegator.clone();
+    let cluster_peers = warp::path(api_path.clone())
+        .and(warp::path("cluster"))
+        .and(warp::path("peers"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let delegator_opt = delegator_for_peers.clone();
+            async move {
+                match delegator_opt {
+                    Some(delegator) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&delegator.peers().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Task delegation")),
+                }
+            }
+        })
+        .boxed();
+
+    let delegator_for_reports = server.delegator.clone();
+    let task_report = warp::path(api_path.clone())
+        .and(warp::path("tasks"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("report"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::content_length_limit(TASK_REPORT_BODY_LIMIT))
+        .and(warp::body::json::<TaskReport>())
+        .and_then(move |task_id: Uuid, report: TaskReport| {
+            let delegator_opt = delegator_for_reports.clone();
+            async move {
+                let delegator = match delegator_opt {
+                    Some(delegator) => delegator,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Task delegation")),
+                };
+                match delegator.report(task_id, report).await {
+                    Ok(task) => Ok(warp::reply::json(&task).into_response()),
+                    Err(e @ DelegationError::UnknownTask(_)) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                    Err(e @ DelegationError::StaleLease { .. }) => {
+                        Ok(error_reply(e.to_string(), warp::http::StatusCode::CONFLICT))
+                    }
+                }
+            }
+        })
+        .boxed();
+
+    let delegator_for_traces = server.delegator.clone();
+    let task_trace = warp::path(api_path.clone())
+        .and(warp::path("traces"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move |trace_id: Uuid| {
+            let delegator_opt = delegator_for_traces.clone();
+            async move {
+                let delegator = match delegator_opt {
+                    Some(delegator) => delegator,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Task delegation")),
+                };
+                let events = delegator.trace_events(trace_id).await;
+                if events.is_empty() {
+                    return Ok(error_reply(
+                        format!("No events for trace {}", trace_id),
+                        warp::http::StatusCode::NOT_FOUND,
+                    ));
+                }
+                Ok(warp::reply::json(&events).into_response())
+            }
+        })
+        .boxed();
+
+    let admission_for_status = server.admission.clone();
+    let admission_status = warp::path(api_path.clone())
+        .and(warp::path("admission"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let admission_opt = admission_for_status.clone();
+            async move {
+                match admission_opt {
+                    Some(admission) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&admission.status().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Admission control")),
+                }
+            }
+        })
+        .boxed();
+
+    vec![
+        cluster_heartbeat,
+        cluster_peers,
+        task_report,
+        task_trace,
+        admission_status,
+    ]
+}
@@ -0,0 +1,915 @@
+//! Qdrant-compatible REST endpoints.
+//!
+//! RAG frameworks such as LangChain and LlamaIndex ship Qdrant vector
+//! stores. Pointing one at this server with the compat prefix (e.g.
+//! `QdrantClient(url="http://host:9000", prefix="qdrant")`) lets it use the
+//! crate as its backend without a custom adapter. The subset served is what
+//! those stores call:
+//!
+//! - `GET /collections`, `GET|PUT|DELETE /collections/{name}` and
+//!   `GET /collections/{name}/exists`: a collection is a shard found by
+//!   name, holding one index (HNSW unless told otherwise)
+//! - `PUT /collections/{name}/points`: upsert points by ID
+//! - `POST /collections/{name}/points`: fetch points by ID
+//! - `POST /collections/{name}/points/search` and `.../points/query`:
+//!   search with an optional filter, returning payloads on request
+//! - `POST /collections/{name}/points/delete`: delete points by ID
+//!
+//! Point IDs are UUIDs or unsigned integers; integer `n` is stored as the
+//! UUID whose value is `n`. A payload is kept verbatim under
+//! [`PAYLOAD_KEY`] and also flattened into dotted metadata keys
+//! (`{"metadata": {"page": 3}}` becomes `metadata.page = "3"`), which
+//! Qdrant filters (`must`/`should`/`must_not` over `match` and `range`
+//! conditions) are translated onto. Scores follow Qdrant: cosine
+//! similarity and dot product, higher first, and plain distances for
+//! `Euclid` and `Manhattan`.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+use warp::filters::BoxedFilter;
+use warp::http::StatusCode;
+use warp::{Filter, Reply};
+
+use crate::core::vector::Vector;
+use crate::query::{PredicateOp, QueryExpr};
+use crate::sharding::hnsw::HnswParams;
+use crate::sharding::manager::{Shard, ShardManager};
+use crate::sharding::vector_index::{DistanceMetric, IndexType};
+
+use super::BATCH_VECTORS_BODY_LIMIT;
+
+/// Metadata key holding a point's payload as JSON
+pub const PAYLOAD_KEY: &str = "_payload";
+
+/// Name of the index created for each collection
+const INDEX_NAME: &str = "main";
+
+/// Points a query returns when it doesn't say
+const DEFAULT_QUERY_LIMIT: usize = 10;
+
+type Manager = Option<Arc<ShardManager>>;
+
+/// A point ID as Qdrant clients send it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PointId {
+    Num(u64),
+    Uuid(Uuid),
+}
+
+impl From<PointId> for Uuid {
+    fn from(id: PointId) -> Self {
+        match id {
+            PointId::Num(n) => Uuid::from_u128(n as u128),
+            PointId::Uuid(id) => id,
+        }
+    }
+}
+
+impl From<Uuid> for PointId {
+    fn from(id: Uuid) -> Self {
+        match u64::try_from(id.as_u128()) {
+            Ok(n) => PointId::Num(n),
+            Err(_) => PointId::Uuid(id),
+        }
+    }
+}
+
+/// Qdrant's distance names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Distance {
+    Cosine,
+    Euclid,
+    Dot,
+    Manhattan,
+}
+
+impl Distance {
+    fn metric(self) -> DistanceMetric {
+        match self {
+            Distance::Cosine => DistanceMetric::Cosine,
+            Distance::Euclid => DistanceMetric::Euclidean,
+            Distance::Manhattan => DistanceMetric::Manhattan,
+            Distance::Dot => DistanceMetric::InnerProduct,
+        }
+    }
+}
+
+fn distance_name(metric: DistanceMetric) -> &'static str {
+    match metric {
+        DistanceMetric::Cosine => "Cosine",
+        DistanceMetric::Euclidean => "Euclid",
+        DistanceMetric::Manhattan => "Manhattan",
+        DistanceMetric::Hamming => "Hamming",
+        DistanceMetric::InnerProduct => "Dot",
+    }
+}
+
+/// Qdrant reports cosine and dot as similarities and everything else as a
+/// distance
+fn score(metric: DistanceMetric, distance: f32) -> f32 {
+    match metric {
+        DistanceMetric::Cosine => 1.0 - distance,
+        _ => distance,
+    }
+}
+
+/// Whether a score passes a `score_threshold`
+fn passes(metric: DistanceMetric, score: f32, threshold: Option<f32>) -> bool {
+    match (metric, threshold) {
+        (_, None) => true,
+        (DistanceMetric::Cosine | DistanceMetric::InnerProduct, Some(threshold)) => {
+            score >= threshold
+        }
+        (_, Some(threshold)) => score <= threshold,
+    }
+}
+
+/// An error reply in Qdrant's shape
+#[derive(Debug)]
+struct Failure {
+    status: StatusCode,
+    message: String,
+}
+
+impl Failure {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: format!("Wrong input: {}", message.into()),
+        }
+    }
+
+    fn not_found(collection: &str) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message: format!("Not found: Collection `{}` doesn't exist!", collection),
+        }
+    }
+}
+
+impl From<anyhow::Error> for Failure {
+    fn from(e: anyhow::Error) -> Self {
+        Self::bad_request(e.to_string())
+    }
+}
+
+type Outcome<T> = Result<T, Failure>;
+
+/// Run a handler and wrap its outcome in Qdrant's `result`/`status`/`time`
+/// envelope
+async fn respond<T: Serialize>(
+    handler: impl Future<Output = Outcome<T>>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let started = Instant::now();
+    let outcome = handler.await;
+    let time = started.elapsed().as_secs_f64();
+    Ok(match outcome {
+        Ok(result) => warp::reply::json(&json!({ "result": result, "status": "ok", "time": time }))
+            .into_response(),
+        Err(failure) => warp::reply::with_status(
+            warp::reply::json(&json!({ "status": { "error": failure.message }, "time": time })),
+            failure.status,
+        )
+        .into_response(),
+    })
+}
+
+fn manager(manager: Manager) -> Outcome<Arc<ShardManager>> {
+    manager.ok_or_else(|| Failure {
+        status: StatusCode::INTERNAL_SERVER_ERROR,
+        message: "Shard manager not configured".into(),
+    })
+}
+
+async fn collection(manager: &ShardManager, name: &str) -> Outcome<Shard> {
+    manager
+        .get_shard_by_name(name)
+        .await
+        .map_err(|_| Failure::not_found(name))
+}
+
+/// Payload metadata: each value under its dotted path, strings as they are
+/// and other scalars and arrays as JSON, plus the whole payload under
+/// [`PAYLOAD_KEY`]
+fn payload_metadata(payload: &Map<String, Value>) -> HashMap<String, String> {
+    fn flatten(prefix: &str, object: &Map<String, Value>, out: &mut HashMap<String, String>) {
+        for (key, value) in object {
+            let key = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            match value {
+                Value::Null => {}
+                Value::String(s) => {
+                    out.insert(key, s.clone());
+                }
+                Value::Object(inner) => flatten(&key, inner, out),
+                other => {
+                    out.insert(key, other.to_string());
+                }
+            }
+        }
+    }
+
+    let mut metadata = HashMap::new();
+    flatten("", payload, &mut metadata);
+    metadata.insert(
+        PAYLOAD_KEY.to_string(),
+        Value::Object(payload.clone()).to_string(),
+    );
+    metadata
+}
+
+/// The payload stored with a point; vectors added through the native API
+/// get their metadata as string values
+fn payload_of(metadata: Option<HashMap<String, String>>) -> Map<String, Value> {
+    let Some(metadata) = metadata else {
+        return Map::new();
+    };
+    match metadata.get(PAYLOAD_KEY).map(|p| serde_json::from_str(p)) {
+        Some(Ok(Value::Object(payload))) => payload,
+        _ => metadata
+            .into_iter()
+            .map(|(key, value)| (key, Value::String(value)))
+            .collect(),
+    }
+}
+
+/// Which payload fields to return
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum WithPayload {
+    All(bool),
+    Fields(Vec<String>),
+    Include { include: Vec<String> },
+    Exclude { exclude: Vec<String> },
+}
+
+impl Default for WithPayload {
+    fn default() -> Self {
+        WithPayload::All(false)
+    }
+}
+
+impl WithPayload {
+    fn select(&self, payload: Map<String, Value>) -> Option<Map<String, Value>> {
+        match self {
+            WithPayload::All(false) => None,
+            WithPayload::All(true) => Some(payload),
+            WithPayload::Fields(keys) | WithPayload::Include { include: keys } => Some(
+                payload
+                    .into_iter()
+                    .filter(|(key, _)| keys.contains(key))
+                    .collect(),
+            ),
+            WithPayload::Exclude { exclude } => Some(
+                payload
+                    .into_iter()
+                    .filter(|(key, _)| !exclude.contains(key))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// A Qdrant filter
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct QdrantFilter {
+    #[serde(default)]
+    pub must: Option<Vec<Condition>>,
+    #[serde(default)]
+    pub should: Option<Vec<Condition>>,
+    #[serde(default)]
+    pub must_not: Option<Vec<Condition>>,
+}
+
+/// One clause of a [`QdrantFilter`]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Condition {
+    Field(Box<FieldCondition>),
+    IsEmpty { is_empty: KeyRef },
+    IsNull { is_null: KeyRef },
+    HasId { has_id: Vec<PointId> },
+    Nested(QdrantFilter),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeyRef {
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldCondition {
+    pub key: String,
+    #[serde(default, rename = "match")]
+    pub matches: Option<Match>,
+    #[serde(default)]
+    pub range: Option<Range>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Match {
+    Value { value: Value },
+    Any { any: Vec<Value> },
+    Except { except: Vec<Value> },
+    Text { text: String },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Range {
+    #[serde(default)]
+    pub gt: Option<Value>,
+    #[serde(default)]
+    pub gte: Option<Value>,
+    #[serde(default)]
+    pub lt: Option<Value>,
+    #[serde(default)]
+    pub lte: Option<Value>,
+}
+
+/// `clauses` combined with `and`, or `None` when there are none
+fn all_of(mut clauses: Vec<QueryExpr>) -> Option<QueryExpr> {
+    match clauses.len() {
+        0 => None,
+        1 => clauses.pop(),
+        _ => Some(QueryExpr::And(clauses)),
+    }
+}
+
+impl QdrantFilter {
+    /// The equivalent query expression, or `None` when it has no clauses
+    pub fn to_expr(&self) -> Result<Option<QueryExpr>, String> {
+        let exprs = |conditions: &Option<Vec<Condition>>| -> Result<Vec<QueryExpr>, String> {
+            let mut exprs = Vec::new();
+            for condition in conditions.iter().flatten() {
+                exprs.extend(condition.to_expr()?);
+            }
+            Ok(exprs)
+        };
+        let mut clauses = exprs(&self.must)?;
+        let should = exprs(&self.should)?;
+        if !should.is_empty() {
+            clauses.push(QueryExpr::Or(should));
+        }
+        let must_not = exprs(&self.must_not)?;
+        if !must_not.is_empty() {
+            clauses.push(QueryExpr::Not(Box::new(QueryExpr::Or(must_not))));
+        }
+        Ok(all_of(clauses))
+    }
+}
+
+impl Condition {
+    fn to_expr(&self) -> Result<Option<QueryExpr>, String> {
+        match self {
+            Condition::Field(field) => field.to_expr().map(Some),
+            Condition::IsEmpty { is_empty: key } | Condition::IsNull { is_null: key } => {
+                Ok(Some(QueryExpr::Not(Box::new(QueryExpr::field(
+                    &key.key,
+                    PredicateOp::Exists,
+                    Value::Null,
+                )))))
+            }
+            Condition::HasId { .. } => Err("has_id conditions are not supported".into()),
+            Condition::Nested(filter) => filter.to_expr(),
+        }
+    }
+}
+
+impl FieldCondition {
+    fn to_expr(&self) -> Result<QueryExpr, String> {
+        let key = self.key.as_str();
+        let mut clauses = Vec::new();
+        match &self.matches {
+            Some(Match::Value { value }) => {
+                clauses.push(QueryExpr::field(key, PredicateOp::Eq, value.clone()))
+            }
+            Some(Match::Any { any }) => clauses.push(QueryExpr::field(
+                key,
+                PredicateOp::In,
+                Value::Array(any.clone()),
+            )),
+            Some(Match::Except { except }) => clauses.push(QueryExpr::Not(Box::new(
+                QueryExpr::field(key, PredicateOp::In, Value::Array(except.clone())),
+            ))),
+            Some(Match::Text { .. }) => {
+                return Err(format!("Full-text match on `{}` is not supported", key))
+            }
+            None => {}
+        }
+        if let Some(range) = &self.range {
+            let bounds = [
+                (PredicateOp::Gt, &range.gt),
+                (PredicateOp::Gte, &range.gte),
+                (PredicateOp::Lt, &range.lt),
+                (PredicateOp::Lte, &range.lte),
+            ];
+            for (op, bound) in bounds {
+                if let Some(bound) = bound {
+                    clauses.push(QueryExpr::field(key, op, bound.clone()));
+                }
+            }
+        }
+        all_of(clauses).ok_or_else(|| format!("Unsupported condition on `{}`", key))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct VectorParams {
+    size: usize,
+    distance: Distance,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct HnswConfigDiff {
+    #[serde(default)]
+    m: Option<usize>,
+    #[serde(default)]
+    ef_construct: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateCollection {
+    vectors: VectorParams,
+    #[serde(default)]
+    hnsw_config: Option<HnswConfigDiff>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PointStruct {
+    id: PointId,
+    vector: Vec<f32>,
+    #[serde(default)]
+    payload: Option<Map<String, Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Batch {
+    ids: Vec<PointId>,
+    vectors: Vec<Vec<f32>>,
+    #[serde(default)]
+    payloads: Option<Vec<Option<Map<String, Value>>>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum UpsertPoints {
+    Points { points: Vec<PointStruct> },
+    Batch { batch: Batch },
+}
+
+impl UpsertPoints {
+    fn into_points(self) -> Outcome<Vec<PointStruct>> {
+        let batch = match self {
+            UpsertPoints::Points { points } => return Ok(points),
+            UpsertPoints::Batch { batch } => batch,
+        };
+        let payloads = batch
+            .payloads
+            .unwrap_or_else(|| vec![None; batch.ids.len()]);
+        if batch.vectors.len() != batch.ids.len() || payloads.len() != batch.ids.len() {
+            return Err(Failure::bad_request(
+                "batch ids, vectors and payloads must have the same length",
+            ));
+        }
+        Ok(batch
+            .ids
+            .into_iter()
+            .zip(batch.vectors)
+            .zip(payloads)
+            .map(|((id, vector), payload)| PointStruct {
+                id,
+                vector,
+                payload,
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RetrievePoints {
+    ids: Vec<PointId>,
+    #[serde(default)]
+    with_payload: Option<WithPayload>,
+    #[serde(default)]
+    with_vector: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeletePoints {
+    points: Vec<PointId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchPoints {
+    #[serde(alias = "query")]
+    vector: Vec<f32>,
+    #[serde(default = "default_query_limit")]
+    limit: usize,
+    #[serde(default)]
+    offset: usize,
+    #[serde(default)]
+    filter: Option<QdrantFilter>,
+    #[serde(default)]
+    with_payload: WithPayload,
+    #[serde(default)]
+    with_vector: bool,
+    #[serde(default)]
+    score_threshold: Option<f32>,
+}
+
+fn default_query_limit() -> usize {
+    DEFAULT_QUERY_LIMIT
+}
+
+/// A point found by a search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoredPoint {
+    pub id: PointId,
+    pub version: u64,
+    pub score: f32,
+    pub payload: Option<Map<String, Value>>,
+    pub vector: Option<Vec<f32>>,
+}
+
+/// A point fetched by ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub id: PointId,
+    pub payload: Option<Map<String, Value>>,
+    pub vector: Option<Vec<f32>>,
+}
+
+/// Reply to writes, which are applied before the reply is sent
+fn completed() -> Value {
+    json!({ "operation_id": 0, "status": "completed" })
+}
+
+async fn list_collections(manager: Manager) -> Outcome<Value> {
+    let manager = self::manager(manager)?;
+    let shards = manager.get_shards().await;
+    // Shards split off another belong to its collection
+    let mut split_off = HashSet::new();
+    for shard in &shards {
+        split_off.extend(
+            manager
+                .key_routes(shard.id)
+                .await
+                .into_iter()
+                .map(|(_, child)| child),
+        );
+    }
+    let mut names: Vec<String> = shards
+        .into_iter()
+        .filter(|shard| !split_off.contains(&shard.id))
+        .map(|shard| shard.name)
+        .collect();
+    names.sort();
+    let collections: Vec<Value> = names
+        .into_iter()
+        .map(|name| json!({ "name": name }))
+        .collect();
+    Ok(json!({ "collections": collections }))
+}
+
+async fn collection_exists(name: String, manager: Manager) -> Outcome<Value> {
+    let manager = self::manager(manager)?;
+    let exists = manager.get_shard_by_name(&name).await.is_ok();
+    Ok(json!({ "exists": exists }))
+}
+
+async fn get_collection(name: String, manager: Manager) -> Outcome<Value> {
+    let manager = self::manager(manager)?;
+    let shard = collection(&manager, &name).await?;
+    let index = manager.get_vector_index(shard.id).await?;
+    let mut points = 0;
+    for member in manager.shard_family(shard.id).await {
+        points += manager
+            .get_shard(member)
+            .await
+            .map_or(0, |s| s.vector_count);
+    }
+    let hnsw = match index.index_type() {
+        IndexType::Hnsw(params) => params,
+        IndexType::Hilbert => HnswParams::default(),
+    };
+    Ok(json!({
+        "status": "green",
+        "optimizer_status": "ok",
+        "vectors_count": points,
+        "indexed_vectors_count": points,
+        "points_count": points,
+        "segments_count": 1,
+        "config": {
+            "params": {
+                "vectors": {
+                    "size": index.dimensions(),
+                    "distance": distance_name(index.distance_metric()),
+                },
+                "shard_number": 1,
+                "replication_factor": 1,
+                "write_consistency_factor": 1,
+                "on_disk_payload": false,
+            },
+            "hnsw_config": {
+                "m": hnsw.m,
+                "ef_construct": hnsw.ef_construction,
+                "full_scan_threshold": 10000,
+            },
+            "optimizer_config": {
+                "deleted_threshold": 0.2,
+                "vacuum_min_vector_number": 1000,
+                "default_segment_number": 0,
+                "flush_interval_sec": 5,
+            },
+            "wal_config": { "wal_capacity_mb": 32, "wal_segments_ahead": 0 },
+        },
+        "payload_schema": {},
+    }))
+}
+
+async fn create_collection(
+    name: String,
+    request: CreateCollection,
+    manager: Manager,
+) -> Outcome<bool> {
+    let manager = self::manager(manager)?;
+    if manager.get_shard_by_name(&name).await.is_ok() {
+        return Err(Failure::bad_request(format!(
+            "Collection `{}` already exists!",
+            name
+        )));
+    }
+    let metric = request.vectors.distance.metric();
+    let hnsw = request.hnsw_config.unwrap_or_default();
+    let defaults = HnswParams::default();
+    let index_type = IndexType::Hnsw(HnswParams {
+        m: hnsw.m.unwrap_or(defaults.m),
+        ef_construction: hnsw.ef_construct.unwrap_or(defaults.ef_construction),
+        ..defaults
+    });
+
+    let shard_id = manager.create_shard(&name).await?;
+    let created = manager
+        .create_vector_index_with_type(
+            shard_id,
+            INDEX_NAME,
+            request.vectors.size,
+            metric,
+            index_type,
+        )
+        .await;
+    if let Err(e) = created {
+        // Don't leave a collection behind that can't hold points
+        manager.delete_shard(shard_id).await?;
+        return Err(e.into());
+    }
+    Ok(true)
+}
+
+async fn delete_collection(name: String, manager: Manager) -> Outcome<bool> {
+    let manager = self::manager(manager)?;
+    let Ok(shard) = manager.get_shard_by_name(&name).await else {
+        return Ok(false);
+    };
+    manager.delete_shard(shard.id).await?;
+    Ok(true)
+}
+
+async fn upsert_points(name: String, request: UpsertPoints, manager: Manager) -> Outcome<Value> {
+    let manager = self::manager(manager)?;
+    let shard = collection(&manager, &name).await?;
+    let dimensions = manager.get_vector_index(shard.id).await?.dimensions();
+    let points = request.into_points()?;
+    // Reject the whole request before writing any of it
+    if let Some(point) = points.iter().find(|p| p.vector.len() != dimensions) {
+        return Err(Failure::bad_request(format!(
+            "Vector dimension error: expected dim: {}, got {}",
+            dimensions,
+            point.vector.len()
+        )));
+    }
+    for point in points {
+        let id = Uuid::from(point.id);
+        if manager.find_vector(id, Some(shard.id)).await.is_ok() {
+            manager.remove_vector(shard.id, id).await?;
+        }
+        let metadata = point.payload.as_ref().map(payload_metadata);
+        manager
+            .add_vector_with_id(shard.id, id, Vector::new(point.vector), metadata)
+            .await?;
+    }
+    Ok(completed())
+}
+
+async fn retrieve_points(
+    name: String,
+    request: RetrievePoints,
+    manager: Manager,
+) -> Outcome<Vec<Record>> {
+    let manager = self::manager(manager)?;
+    let shard = collection(&manager, &name).await?;
+    let with_payload = request.with_payload.unwrap_or(WithPayload::All(true));
+    let mut records = Vec::new();
+    for id in request.ids {
+        // Unknown IDs are left out, as Qdrant does
+        if let Ok((_, entry)) = manager.find_vector(id.into(), Some(shard.id)).await {
+            records.push(Record {
+                id,
+                payload: with_payload.select(payload_of(entry.metadata)),
+                vector: request.with_vector.then_some(entry.vector.values),
+            });
+        }
+    }
+    Ok(records)
+}
+
+async fn delete_points(name: String, request: DeletePoints, manager: Manager) -> Outcome<Value> {
+    let manager = self::manager(manager)?;
+    let shard = collection(&manager, &name).await?;
+    for id in request.points {
+        let id = Uuid::from(id);
+        if manager.find_vector(id, Some(shard.id)).await.is_ok() {
+            manager.remove_vector(shard.id, id).await?;
+        }
+    }
+    Ok(completed())
+}
+
+async fn search_points(
+    name: String,
+    request: SearchPoints,
+    manager: Manager,
+) -> Outcome<Vec<ScoredPoint>> {
+    let manager = self::manager(manager)?;
+    let shard = collection(&manager, &name).await?;
+    if request.limit == 0 {
+        return Err(Failure::bad_request("limit must be greater than zero"));
+    }
+    let index = manager.get_vector_index(shard.id).await?;
+    if request.vector.len() != index.dimensions() {
+        return Err(Failure::bad_request(format!(
+            "Vector dimension error: expected dim: {}, got {}",
+            index.dimensions(),
+            request.vector.len()
+        )));
+    }
+    let filter = match &request.filter {
+        Some(filter) => filter.to_expr().map_err(Failure::bad_request)?,
+        None => None,
+    };
+    let metric = index.distance_metric();
+    let results = manager
+        .search_vectors_filtered(
+            shard.id,
+            &Vector::new(request.vector),
+            request.offset + request.limit,
+            filter.as_ref(),
+        )
+        .await?;
+    Ok(results
+        .into_iter()
+        .map(|result| ScoredPoint {
+            id: result.id.into(),
+            version: 0,
+            score: score(metric, result.score),
+            payload: request.with_payload.select(payload_of(result.metadata)),
+            vector: request.with_vector.then_some(result.vector.values),
+        })
+        .filter(|point| passes(metric, point.score, request.score_threshold))
+        .skip(request.offset)
+        .take(request.limit)
+        .collect())
+}
+
+async fn query_points(name: String, request: SearchPoints, manager: Manager) -> Outcome<Value> {
+    let points = search_points(name, request, manager).await?;
+    Ok(json!({ "points": points }))
+}
+
+fn json_body<T>() -> impl Filter<Extract = (T,), Error = warp::Rejection> + Clone
+where
+    T: serde::de::DeserializeOwned + Send + 'static,
+{
+    warp::body::content_length_limit(BATCH_VECTORS_BODY_LIMIT).and(warp::body::json())
+}
+
+/// The Qdrant-compatible routes, served under `prefix`
+pub(crate) fn routes(prefix: &str, manager: Manager) -> BoxedFilter<(warp::reply::Response,)> {
+    let manager = warp::any().map(move || manager.clone());
+    let collections = warp::path(prefix.to_string()).and(warp::path("collections"));
+    let collection = collections.clone().and(warp::path::param::<String>());
+    let points = collection.clone().and(warp::path("points"));
+
+    let list = collections
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(manager.clone())
+        .and_then(|manager: Manager| respond(list_collections(manager)));
+    let exists = collection
+        .clone()
+        .and(warp::path("exists"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(manager.clone())
+        .and_then(|name: String, manager: Manager| respond(collection_exists(name, manager)));
+    let get = collection
+        .clone()
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(manager.clone())
+        .and_then(|name: String, manager: Manager| respond(get_collection(name, manager)));
+    let create = collection
+        .clone()
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(json_body())
+        .and(manager.clone())
+        .and_then(
+            |name: String, request: CreateCollection, manager: Manager| {
+                respond(create_collection(name, request, manager))
+            },
+        );
+    let delete = collection
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(manager.clone())
+        .and_then(|name: String, manager: Manager| respond(delete_collection(name, manager)));
+    let upsert = points
+        .clone()
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(json_body())
+        .and(manager.clone())
+        .and_then(|name: String, request: UpsertPoints, manager: Manager| {
+            respond(upsert_points(name, request, manager))
+        });
+    let retrieve = points
+        .clone()
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body())
+        .and(manager.clone())
+        .and_then(|name: String, request: RetrievePoints, manager: Manager| {
+            respond(retrieve_points(name, request, manager))
+        });
+    let search = points
+        .clone()
+        .and(warp::path("search"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body())
+        .and(manager.clone())
+        .and_then(|name: String, request: SearchPoints, manager: Manager| {
+            respond(search_points(name, request, manager))
+        });
+    let query = points
+        .clone()
+        .and(warp::path("query"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body())
+        .and(manager.clone())
+        .and_then(|name: String, request: SearchPoints, manager: Manager| {
+            respond(query_points(name, request, manager))
+        });
+    let remove = points
+        .and(warp::path("delete"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body())
+        .and(manager)
+        .and_then(|name: String, request: DeletePoints, manager: Manager| {
+            respond(delete_points(name, request, manager))
+        });
+
+    list.or(exists)
+        .unify()
+        .or(get)
+        .unify()
+        .or(create)
+        .unify()
+        .or(delete)
+        .unify()
+        .or(upsert)
+        .unify()
+        .or(retrieve)
+        .unify()
+        .or(search)
+        .unify()
+        .or(query)
+        .unify()
+        .or(remove)
+        .unify()
+        .boxed()
+}
@@ -0,0 +1,361 @@
+//! Darwin routes: modification history and reports, competencies,
+//! providers, releases, rollback points and validation thresholds.
+
+use crate::darwin::history::HistoryQuery;
+use crate::darwin::thresholds::ThresholdUpdate;
+use crate::server::api::CreateRollbackPointRequest;
+use crate::utils::errors::{RollbackError, ThresholdError};
+use uuid::Uuid;
+use warp::filters::BoxedFilter;
+use warp::{Filter, Reply};
+
+use super::{error_reply, json_body, not_configured, Server};
+
+/// Darwin routes under `api_path`, in the order they are tried
+pub(super) fn routes(
+    server: &Server,
+    api_path: &str,
+) -> Vec<BoxedFilter<(warp::reply::Response,)>> {
+    let api_path = api_path.to_string();
+
+    let engine_for_history = server.self_improvement.clone();
+    let modification_history = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("modifications"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HistoryQuery>())
+        .and_then(move |query: HistoryQuery| {
+            let engine_opt = engine_for_history.clone();
+            async move {
+                match engine_opt {
+                    Some(engine) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&engine.query_modifications(&query).await)
+                            .into_response(),
+                    ),
+                    None => Ok(not_configured("Self-improvement engine")),
+                }
+            }
+        })
+        .boxed();
+
+    let lifecycle_for_timeline = server.lifecycle_log.clone();
+    let modification_timeline = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("modifications"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("timeline"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move |modification_id: Uuid| {
+            let lifecycle_opt = lifecycle_for_timeline.clone();
+            async move {
+                let lifecycle = match lifecycle_opt {
+                    Some(lifecycle) => lifecycle,
+                    None => {
+                        return Ok::<_, warp::Rejection>(not_configured(
+                            "Modification lifecycle log",
+                        ))
+                    }
+                };
+                let events = lifecycle.timeline(modification_id).await;
+                if events.is_empty() {
+                    return Ok(error_reply(
+                        format!("No lifecycle events for modification {}", modification_id),
+                        warp::http::StatusCode::NOT_FOUND,
+                    ));
+                }
+                Ok(warp::reply::json(&serde_json::json!({
+                    "modification_id": modification_id,
+                    "events": events,
+                }))
+                .into_response())
+            }
+        })
+        .boxed();
+
+    let engine_for_report = server.self_improvement.clone();
+    let modification_report = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("modifications"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("report"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move |modification_id: Uuid| {
+            let engine_opt = engine_for_report.clone();
+            async move {
+                let Some(engine) = engine_opt else {
+                    return Ok::<_, warp::Rejection>(not_configured("Self-improvement engine"));
+                };
+                match engine.validation_report(modification_id).await {
+                    Some(report) => Ok(warp::reply::json(&report).into_response()),
+                    None => Ok(error_reply(
+                        format!("No validation report for modification {}", modification_id),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let engine_for_rollback = server.self_improvement.clone();
+    let rollback_modification = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("modifications"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("rollback"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and_then(move |modification_id: Uuid| {
+            let engine_opt = engine_for_rollback.clone();
+            async move {
+                let Some(engine) = engine_opt else {
+                    return Ok::<_, warp::Rejection>(not_configured("Self-improvement engine"));
+                };
+                if let Err(e) = engine.get_modification(modification_id).await {
+                    return Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    ));
+                }
+                // Not deployed, or a file was edited since the deployment
+                match engine.rollback_modification(modification_id).await {
+                    Ok(modification) => Ok(warp::reply::json(&modification).into_response()),
+                    Err(e) => Ok(error_reply(e.to_string(), warp::http::StatusCode::CONFLICT)),
+                }
+            }
+        })
+        .boxed();
+
+    let engine_for_conflicts = server.self_improvement.clone();
+    let modification_conflicts = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("conflicts"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let engine_opt = engine_for_conflicts.clone();
+            async move {
+                match engine_opt {
+                    Some(engine) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&engine.pending_conflicts().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Self-improvement engine")),
+                }
+            }
+        })
+        .boxed();
+
+    let engine_for_competencies = server.self_improvement.clone();
+    let darwin_competencies = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("competencies"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let engine_opt = engine_for_competencies.clone();
+            async move {
+                match engine_opt {
+                    Some(engine) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&engine.competency_matrix().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Self-improvement engine")),
+                }
+            }
+        })
+        .boxed();
+
+    let engine_for_providers = server.self_improvement.clone();
+    let darwin_providers = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("providers"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let engine_opt = engine_for_providers.clone();
+            async move {
+                let Some(engine) = engine_opt else {
+                    return Ok::<_, warp::Rejection>(not_configured("Self-improvement engine"));
+                };
+                match engine.provider_status().await {
+                    Some(status) => Ok(warp::reply::json(&status).into_response()),
+                    None => Ok(not_configured("LLM provider monitoring")),
+                }
+            }
+        })
+        .boxed();
+
+    let engine_for_releases = server.self_improvement.clone();
+    let darwin_releases = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("releases"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let engine_opt = engine_for_releases.clone();
+            async move {
+                match engine_opt {
+                    Some(engine) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&engine.release_log().releases().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Self-improvement engine")),
+                }
+            }
+        })
+        .boxed();
+
+    let engine_for_create_point = server.self_improvement.clone();
+    let create_rollback_point = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("rollback-points"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body::<CreateRollbackPointRequest>())
+        .and_then(move |req: CreateRollbackPointRequest| {
+            let engine_opt = engine_for_create_point.clone();
+            async move {
+                let engine = match engine_opt {
+                    Some(engine) => engine,
+                    None => {
+                        return Ok::<_, warp::Rejection>(not_configured("Self-improvement engine"))
+                    }
+                };
+                match engine.create_rollback_point(&req.name).await {
+                    Ok(point) => Ok(warp::reply::with_status(
+                        warp::reply::json(&point),
+                        warp::http::StatusCode::CREATED,
+                    )
+                    .into_response()),
+                    Err(e) => Ok(error_reply(e.to_string(), warp::http::StatusCode::CONFLICT)),
+                }
+            }
+        })
+        .boxed();
+
+    let engine_for_points = server.self_improvement.clone();
+    let list_rollback_points = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("rollback-points"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let engine_opt = engine_for_points.clone();
+            async move {
+                match engine_opt {
+                    Some(engine) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&engine.rollback_points().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Self-improvement engine")),
+                }
+            }
+        })
+        .boxed();
+
+    let engine_for_restore = server.self_improvement.clone();
+    let restore_rollback_point = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("rollback"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::post())
+        .and_then(move |point: String| {
+            let engine_opt = engine_for_restore.clone();
+            async move {
+                let engine = match engine_opt {
+                    Some(engine) => engine,
+                    None => {
+                        return Ok::<_, warp::Rejection>(not_configured("Self-improvement engine"))
+                    }
+                };
+                match engine.restore_rollback_point(&point).await {
+                    Ok(report) => Ok(warp::reply::json(&report).into_response()),
+                    Err(e @ RollbackError::UnknownPoint(_)) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let thresholds_for_get = server.thresholds.clone();
+    let get_validation_thresholds = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("validation"))
+        .and(warp::path("thresholds"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let thresholds_opt = thresholds_for_get.clone();
+            async move {
+                match thresholds_opt {
+                    Some(thresholds) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&thresholds.settings()).into_response(),
+                    ),
+                    None => Ok(not_configured("Validation threshold admin")),
+                }
+            }
+        })
+        .boxed();
+
+    let thresholds_for_put = server.thresholds.clone();
+    let put_validation_thresholds = warp::path(api_path.clone())
+        .and(warp::path("darwin"))
+        .and(warp::path("validation"))
+        .and(warp::path("thresholds"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(json_body::<ThresholdUpdate>())
+        .and_then(move |update: ThresholdUpdate| {
+            let thresholds_opt = thresholds_for_put.clone();
+            async move {
+                let thresholds = match thresholds_opt {
+                    Some(thresholds) => thresholds,
+                    None => {
+                        return Ok::<_, warp::Rejection>(not_configured(
+                            "Validation threshold admin",
+                        ))
+                    }
+                };
+                match thresholds.update(update).await {
+                    // Loosenings waiting for the DAO are accepted but not applied
+                    Ok(outcome) if !outcome.pending.is_empty() => Ok(warp::reply::with_status(
+                        warp::reply::json(&outcome),
+                        warp::http::StatusCode::ACCEPTED,
+                    )
+                    .into_response()),
+                    Ok(outcome) => Ok(warp::reply::json(&outcome).into_response()),
+                    Err(e @ ThresholdError::Governance(_)) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                    )),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    vec![
+        modification_history,
+        modification_timeline,
+        modification_report,
+        rollback_modification,
+        modification_conflicts,
+        darwin_competencies,
+        darwin_providers,
+        darwin_releases,
+        create_rollback_point,
+        list_rollback_points,
+        restore_rollback_point,
+        get_validation_thresholds,
+        put_validation_thresholds,
+    ]
+}
@@ -0,0 +1,164 @@
+//! Typed events for the `/ws/events` stream.
+//!
+//! Every text message on the stream is one [`EventEnvelope`]: the schema
+//! version it was encoded with, when it was sent, and a [`ServerEvent`]
+//! tagged by its `type` field:
+//!
+//! ```json
+//! {"version":2,"sent_at":"2024-01-01T00:00:00Z","type":"vector_inserted",
+//!  "shard_id":"...","vector_id":"...","offset":41}
+//! ```
+//!
+//! The schema version is negotiated in the websocket handshake. Clients
+//! offer the versions they understand as `Sec-WebSocket-Protocol` values
+//! (`rose-events.v1`, `rose-events.v2`, ...) and the server answers with
+//! the highest one it supports. Clients that offer none get version 1, and
+//! clients offering only unsupported versions are turned away with a 400.
+//! The server never sends an event type newer than the negotiated version,
+//! so adding a type means bumping [`SCHEMA_VERSION`] and stating the
+//! version in [`ServerEvent::since`]. Existing types only ever gain
+//! optional fields.
+//!
+//! | Type                   | Since | Meaning                                  |
+//! |------------------------|-------|------------------------------------------|
+//! | `vector_inserted`      | 1     | A vector was added to a shard            |
+//! | `vector_deleted`       | 1     | A vector was removed from a shard        |
+//! | `modification_updated` | 1     | A self-improvement modification moved on |
+//! | `lagged`               | 2     | The subscriber missed events             |
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::darwin::lifecycle::{LifecycleEvent, LifecycleEventKind};
+use crate::sharding::changefeed::{ChangeEvent, ChangeOp};
+
+/// Newest schema version the server speaks
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Oldest schema version the server still speaks, and the one clients get
+/// when they don't negotiate
+pub const MIN_SCHEMA_VERSION: u32 = 1;
+
+/// `Sec-WebSocket-Protocol` values are this prefix and a schema version
+pub const PROTOCOL_PREFIX: &str = "rose-events.v";
+
+/// Subprotocol naming a schema version
+pub fn protocol(version: u32) -> String {
+    format!("{}{}", PROTOCOL_PREFIX, version)
+}
+
+/// Pick the schema version for a client from its `Sec-WebSocket-Protocol`
+/// header: the highest supported version it offered, [`MIN_SCHEMA_VERSION`]
+/// without the header, or `None` if it offered only versions the server
+/// doesn't speak.
+pub fn negotiate(offered: Option<&str>) -> Option<u32> {
+    let Some(offered) = offered else {
+        return Some(MIN_SCHEMA_VERSION);
+    };
+    offered
+        .split(',')
+        .filter_map(|p| p.trim().strip_prefix(PROTOCOL_PREFIX)?.parse::<u32>().ok())
+        .filter(|v| (MIN_SCHEMA_VERSION..=SCHEMA_VERSION).contains(v))
+        .max()
+}
+
+/// Something that happened on the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    VectorInserted {
+        shard_id: Uuid,
+        vector_id: Uuid,
+        /// Position in the shard's change feed, to resume from with
+        /// `GET /api/shards/{id}/changes`
+        offset: u64,
+        /// Region the write was replicated from; absent for local writes
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        origin: Option<String>,
+    },
+    VectorDeleted {
+        shard_id: Uuid,
+        vector_id: Uuid,
+        offset: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        origin: Option<String>,
+    },
+    ModificationUpdated {
+        modification_id: Uuid,
+        /// Position in the lifecycle log
+        sequence: u64,
+        #[serde(flatten)]
+        kind: LifecycleEventKind,
+    },
+    /// The subscriber fell behind and `missed` events were dropped; the
+    /// change feeds and lifecycle log still hold them
+    Lagged { missed: u64 },
+    /// A type from a newer schema than this build knows
+    #[serde(other)]
+    Unknown,
+}
+
+impl ServerEvent {
+    /// Schema version that introduced this type of event
+    pub fn since(&self) -> u32 {
+        match self {
+            ServerEvent::VectorInserted { .. }
+            | ServerEvent::VectorDeleted { .. }
+            | ServerEvent::ModificationUpdated { .. } => 1,
+            ServerEvent::Lagged { .. } => 2,
+            ServerEvent::Unknown => SCHEMA_VERSION,
+        }
+    }
+}
+
+impl From<ChangeEvent> for ServerEvent {
+    fn from(event: ChangeEvent) -> Self {
+        match event.op {
+            ChangeOp::Insert { vector_id, .. } => ServerEvent::VectorInserted {
+                shard_id: event.shard_id,
+                vector_id,
+                offset: event.offset,
+                origin: event.origin,
+            },
+            ChangeOp::Delete { vector_id } => ServerEvent::VectorDeleted {
+                shard_id: event.shard_id,
+                vector_id,
+                offset: event.offset,
+                origin: event.origin,
+            },
+        }
+    }
+}
+
+impl From<LifecycleEvent> for ServerEvent {
+    fn from(event: LifecycleEvent) -> Self {
+        ServerEvent::ModificationUpdated {
+            modification_id: event.modification_id,
+            sequence: event.sequence,
+            kind: event.kind,
+        }
+    }
+}
+
+/// One message on the event stream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    /// Schema version negotiated for the connection
+    pub version: u32,
+    pub sent_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: ServerEvent,
+}
+
+impl EventEnvelope {
+    /// Wrap `event` for a connection speaking `version`, or `None` if that
+    /// version doesn't know the event's type
+    pub fn for_version(version: u32, event: ServerEvent) -> Option<Self> {
+        (event.since() <= version).then(|| Self {
+            version,
+            sent_at: Utc::now(),
+            event,
+        })
+    }
+}
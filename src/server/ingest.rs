@@ -0,0 +1,193 @@
+//! Ingest routes: bulk imports into a shard and webhook pipelines.
+
+use crate::connectors::{import_into_shard, DEFAULT_BATCH_SIZE};
+use crate::ingest::WebhookPipelineConfig;
+use crate::network::priority::Priority;
+use crate::server::api::ImportRequest;
+use crate::sharding::manager::ShardManager;
+use std::sync::Arc;
+use warp::filters::BoxedFilter;
+use warp::{Filter, Reply};
+
+use super::{
+    error_reply, json_body, not_configured, request_priority, Server, ServerConfig,
+    WEBHOOK_BODY_LIMIT,
+};
+
+/// Ingest routes under `api_path`, in the order they are tried
+pub(super) fn routes(
+    server: &Server,
+    api_path: &str,
+    shard_manager: &Option<Arc<ShardManager>>,
+    config: &ServerConfig,
+) -> Vec<BoxedFilter<(warp::reply::Response,)>> {
+    let api_path = api_path.to_string();
+
+    let manager_for_import = shard_manager.clone();
+    let scheduling_for_import = server.scheduling();
+    let import_policy = config.import.clone();
+    let import_vectors = warp::path(api_path.clone())
+        .and(warp::path("import"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(request_priority(Priority::Batch))
+        .and(json_body::<ImportRequest>())
+        .and_then(move |priority: Priority, request: ImportRequest| {
+            let manager_opt = manager_for_import.clone();
+            let scheduling = scheduling_for_import.clone();
+            let import = import_policy.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                let _admitted = match scheduling.admit(priority).await {
+                    Ok(admitted) => admitted,
+                    Err(reply) => return Ok(reply),
+                };
+                if let Err(e) = manager.get_shard(request.shard_id).await {
+                    return Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    ));
+                }
+                let source = match import.check_source(&request.source) {
+                    Ok(source) => source,
+                    Err(e) => {
+                        return Ok(error_reply(
+                            e.to_string(),
+                            warp::http::StatusCode::FORBIDDEN,
+                        ))
+                    }
+                };
+                let mut source = match source.open().await {
+                    Ok(source) => source,
+                    Err(e) => {
+                        return Ok(error_reply(
+                            e.to_string(),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        ))
+                    }
+                };
+                match import_into_shard(
+                    source.as_mut(),
+                    manager,
+                    request.shard_id,
+                    request.batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+                )
+                .await
+                {
+                    Ok(summary) => Ok(warp::reply::json(&summary).into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_GATEWAY,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let ingestor_for_webhook = server.webhook_ingestor.clone();
+    let scheduling_for_webhook = server.scheduling();
+    let ingest_webhook = warp::path(api_path.clone())
+        .and(warp::path("ingest"))
+        .and(warp::path("webhook"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(request_priority(Priority::Batch))
+        .and(warp::body::content_length_limit(WEBHOOK_BODY_LIMIT))
+        .and(warp::body::json::<serde_json::Value>())
+        .and_then(
+            move |pipeline: String, priority: Priority, payload: serde_json::Value| {
+                let ingestor_opt = ingestor_for_webhook.clone();
+                let scheduling = scheduling_for_webhook.clone();
+                async move {
+                    let ingestor = match ingestor_opt {
+                        Some(ingestor) => ingestor,
+                        None => {
+                            return Ok::<_, warp::Rejection>(not_configured("Webhook ingestion"))
+                        }
+                    };
+                    let _admitted = match scheduling.admit(priority).await {
+                        Ok(admitted) => admitted,
+                        Err(reply) => return Ok(reply),
+                    };
+                    if !ingestor.has_pipeline(&pipeline).await {
+                        return Ok(error_reply(
+                            format!("Webhook pipeline {} not found", pipeline),
+                            warp::http::StatusCode::NOT_FOUND,
+                        ));
+                    }
+                    match ingestor.ingest(&pipeline, &payload).await {
+                        Ok(result) => Ok(warp::reply::with_status(
+                            warp::reply::json(&result),
+                            warp::http::StatusCode::ACCEPTED,
+                        )
+                        .into_response()),
+                        Err(e) => Ok(error_reply(
+                            e.to_string(),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        )),
+                    }
+                }
+            },
+        )
+        .boxed();
+
+    let ingestor_for_register = server.webhook_ingestor.clone();
+    let register_pipeline = warp::path(api_path.clone())
+        .and(warp::path("ingest"))
+        .and(warp::path("pipelines"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body::<WebhookPipelineConfig>())
+        .and_then(move |config: WebhookPipelineConfig| {
+            let ingestor_opt = ingestor_for_register.clone();
+            async move {
+                let ingestor = match ingestor_opt {
+                    Some(ingestor) => ingestor,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Webhook ingestion")),
+                };
+                let name = config.name.clone();
+                match ingestor.register_pipeline(config).await {
+                    Ok(()) => Ok(warp::reply::with_status(
+                        warp::reply::json(&serde_json::json!({ "pipeline": name })),
+                        warp::http::StatusCode::CREATED,
+                    )
+                    .into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let ingestor_for_list = server.webhook_ingestor.clone();
+    let list_pipelines = warp::path(api_path.clone())
+        .and(warp::path("ingest"))
+        .and(warp::path("pipelines"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let ingestor_opt = ingestor_for_list.clone();
+            async move {
+                match ingestor_opt {
+                    Some(ingestor) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&ingestor.list_pipelines().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Webhook ingestion")),
+                }
+            }
+        })
+        .boxed();
+
+    vec![
+        import_vectors,
+        ingest_webhook,
+        register_pipeline,
+        list_pipelines,
+    ]
+}
@@ -0,0 +1,115 @@
+//! Job routes: submit, list, inspect and cancel background jobs.
+
+use crate::server::api::SubmitJobRequest;
+use crate::utils::errors::JobError;
+use uuid::Uuid;
+use warp::filters::BoxedFilter;
+use warp::{Filter, Reply};
+
+use super::{error_reply, json_body, not_configured, Server};
+
+/// Job routes under `api_path`, in the order they are tried
+pub(super) fn routes(
+    server: &Server,
+    api_path: &str,
+) -> Vec<BoxedFilter<(warp::reply::Response,)>> {
+    let api_path = api_path.to_string();
+
+    let jobs_for_submit = server.jobs.clone();
+    let submit_job = warp::path(api_path.clone())
+        .and(warp::path("jobs"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body::<SubmitJobRequest>())
+        .and_then(move |request: SubmitJobRequest| {
+            let jobs_opt = jobs_for_submit.clone();
+            async move {
+                let jobs = match jobs_opt {
+                    Some(jobs) => jobs,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Job queue")),
+                };
+                match jobs.submit(request.kind, request.params).await {
+                    Ok(job) => Ok(warp::reply::with_status(
+                        warp::reply::json(&job),
+                        warp::http::StatusCode::ACCEPTED,
+                    )
+                    .into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let jobs_for_list = server.jobs.clone();
+    let list_jobs = warp::path(api_path.clone())
+        .and(warp::path("jobs"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let jobs_opt = jobs_for_list.clone();
+            async move {
+                match jobs_opt {
+                    Some(jobs) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&jobs.list().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Job queue")),
+                }
+            }
+        })
+        .boxed();
+
+    let jobs_for_get = server.jobs.clone();
+    let get_job = warp::path(api_path.clone())
+        .and(warp::path("jobs"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move |job_id: Uuid| {
+            let jobs_opt = jobs_for_get.clone();
+            async move {
+                let jobs = match jobs_opt {
+                    Some(jobs) => jobs,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Job queue")),
+                };
+                match jobs.get(job_id).await {
+                    Some(job) => Ok(warp::reply::json(&job).into_response()),
+                    None => Ok(error_reply(
+                        JobError::UnknownJob(job_id).to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let jobs_for_cancel = server.jobs.clone();
+    let cancel_job = warp::path(api_path.clone())
+        .and(warp::path("jobs"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("cancel"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and_then(move |job_id: Uuid| {
+            let jobs_opt = jobs_for_cancel.clone();
+            async move {
+                let jobs = match jobs_opt {
+                    Some(jobs) => jobs,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Job queue")),
+                };
+                match jobs.cancel(job_id).await {
+                    Ok(job) => Ok(warp::reply::json(&job).into_response()),
+                    Err(e @ JobError::UnknownJob(_)) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                    Err(e) => Ok(error_reply(e.to_string(), warp::http::StatusCode::CONFLICT)),
+                }
+            }
+        })
+        .boxed();
+
+    vec![submit_job, list_jobs, get_job, cancel_job]
+}
@@ -1,15 +1,61 @@
+pub mod api;
+pub mod auth;
+pub mod compat;
+pub mod events;
+pub mod openai;
+
+mod admin;
+mod cluster;
+mod darwin;
+mod ingest;
+mod jobs;
+mod relevance;
+mod replication;
+mod search;
+mod shards;
+mod vectors;
+
 #[rustfmt::skip]
 use crate::core::metrics::MetricsCollector;
+use crate::connectors::ImportPolicy;
+use crate::darwin::lifecycle::{LifecycleEvent, LifecycleLog};
+use crate::darwin::self_improvement::SelfImprovementEngine;
+use crate::darwin::thresholds::ThresholdAdmin;
+use crate::embedding::EmbeddingRegistry;
+use crate::ingest::WebhookIngestor;
+use crate::intelligence::delegation::TaskDelegator;
+use crate::intelligence::model_registry::ModelRegistry;
+use crate::intelligence::ranking::RankingPipeline;
+use crate::nerv::cluster_metrics::ClusterMetricsAggregator;
+use crate::nerv::jobs::JobQueue;
+use crate::nerv::region::RegionReplicator;
 use crate::nerv::runtime::Runtime;
+use crate::nerv::tasks::{self, TaskRegistry};
+use crate::network::admission::{AdmissionController, AdmissionPermit};
+use crate::network::circuit_breaker::CircuitBreakerRegistry;
+use crate::network::priority::{PoolSlot, Priority, PriorityPools, PRIORITY_HEADER};
+use crate::network::secure_channel::{Envelope, ReplyTo, SecureChannels};
+use crate::network::trust::TrustManager;
+use crate::query::experiments::Experiments;
+use crate::query::feedback::SearchLog;
+use crate::query::scoring::ScoringPlugins;
+use crate::query::slow_log::SlowQueryLog;
+use crate::query::synonyms::SynonymStore;
 use crate::server::api::{
-    convert_search_results, create_vector, parse_distance_metric, AddVectorRequest,
-    AddVectorResponse, CreateIndexRequest, CreateIndexResponse, CreateShardRequest,
-    CreateShardResponse, ErrorResponse, SearchVectorsRequest, SearchVectorsResponse,
+    convert_search_results, create_vector, ErrorResponse, SearchVectorsRequest,
 };
+use crate::server::auth::AuthConfig;
+use crate::server::events::{EventEnvelope, ServerEvent};
+use crate::sharding::changefeed::ChangeEvent;
+use crate::sharding::health::IndexHealthMonitor;
 use crate::sharding::manager::ShardManager;
-use anyhow::{anyhow, Result};
+use crate::sharding::purge::PurgeService;
+use crate::sharding::rebalance::RebalanceManager;
+use crate::sharding::retention::RetentionEnforcer;
+use crate::sharding::storage::{PersistenceConfig, StorageEngine};
+use crate::utils::errors::{AdmissionError, AuthConfigError};
+use anyhow::{Context, Result};
 use futures::{SinkExt, StreamExt};
-use prometheus::{Encoder, Registry, TextEncoder};
 use serde::de::DeserializeOwned;
 use std::net::SocketAddr;
 use std::sync::{Arc, RwLock as StdRwLock};
@@ -18,8 +64,7 @@ use sysinfo::{get_current_pid, ProcessExt, System, SystemExt};
 use tokio::sync::broadcast;
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
-use tracing::{debug, error, info, warn};
-use uuid::Uuid;
+use tracing::{debug, info, warn};
 use warp::ws::{Message, WebSocket};
 use warp::{Filter, Reply};
 
@@ -30,6 +75,208 @@ where
     warp::body::content_length_limit(1024 * 16).and(warp::body::json())
 }
 
+/// Largest page of change events returned per request
+const MAX_CHANGES_PER_REQUEST: usize = 1000;
+
+/// Longest a change feed request may wait for new events
+const MAX_CHANGES_WAIT_MS: u64 = 30_000;
+
+/// Body size limit for replicated log segments
+const SEGMENT_BODY_LIMIT: u64 = 16 * 1024 * 1024;
+
+/// Body size limit for delegated task reports, which carry results and trace spans
+const TASK_REPORT_BODY_LIMIT: u64 = 4 * 1024 * 1024;
+
+/// Body size limit for vector composition, whose terms can carry literal vectors
+const COMPOSE_BODY_LIMIT: u64 = 1024 * 1024;
+
+/// Body size limit for searches, which carry one or more full query vectors
+const SEARCH_BODY_LIMIT: u64 = 1024 * 1024;
+
+/// Body size limit for batch vector inserts
+const BATCH_VECTORS_BODY_LIMIT: u64 = 64 * 1024 * 1024;
+
+/// Most vectors accepted by one batch insert
+const MAX_BATCH_VECTORS: usize = 10_000;
+
+/// Body size limit for scorer plugin modules
+const SCORER_BODY_LIMIT: u64 = 8 * 1024 * 1024;
+
+/// Body size limit for node-to-node requests that carry no data, such as a
+/// handshake or a sealed metrics scrape
+const NODE_REQUEST_BODY_LIMIT: u64 = 64 * 1024;
+
+/// Body size limit for webhook payloads, which are often larger than API requests
+const WEBHOOK_BODY_LIMIT: u64 = 1024 * 1024;
+
+/// JSON error reply with the given status
+fn error_reply(error: String, status: warp::http::StatusCode) -> warp::reply::Response {
+    warp::reply::with_status(warp::reply::json(&ErrorResponse { error }), status).into_response()
+}
+
+/// Reply used by API routes when the feature they serve wasn't set up on
+/// this server
+fn not_configured(feature: &str) -> warp::reply::Response {
+    error_reply(
+        format!("{feature} not configured"),
+        warp::http::StatusCode::SERVICE_UNAVAILABLE,
+    )
+}
+
+/// Next event from an optional subscription. Without one, or once it
+/// closes, this never resolves.
+async fn recv_event<T: Clone + Into<ServerEvent>>(
+    subscription: &mut Option<broadcast::Receiver<T>>,
+) -> ServerEvent {
+    let Some(receiver) = subscription else {
+        return std::future::pending().await;
+    };
+    match receiver.recv().await {
+        Ok(event) => event.into(),
+        Err(broadcast::error::RecvError::Lagged(missed)) => ServerEvent::Lagged { missed },
+        Err(broadcast::error::RecvError::Closed) => {
+            *subscription = None;
+            std::future::pending().await
+        }
+    }
+}
+
+/// A request to a node-to-node route
+enum NodeRequest<T> {
+    /// A handshake, answered with this reply
+    Answered(warp::reply::Response),
+    /// The request, and the sealed request to bind the reply to
+    Message(T, Option<ReplyTo>),
+}
+
+/// Read the body of a node-to-node route: plain JSON without secure
+/// channels, and a hello or sealed request with them
+async fn open_node_request<T: DeserializeOwned>(
+    channels: Option<&SecureChannels>,
+    body: &[u8],
+) -> Result<NodeRequest<T>, warp::reply::Response> {
+    let bad_request = |e: serde_json::Error| {
+        error_reply(
+            format!("Invalid request body: {}", e),
+            warp::http::StatusCode::BAD_REQUEST,
+        )
+    };
+    let Some(channels) = channels else {
+        let request = serde_json::from_slice(body).map_err(bad_request)?;
+        return Ok(NodeRequest::Message(request, None));
+    };
+    let unauthorized = |e: String| error_reply(e, warp::http::StatusCode::UNAUTHORIZED);
+    match serde_json::from_slice::<Envelope>(body) {
+        Ok(Envelope::Hello(hello)) => match channels.accept(&hello).await {
+            Ok(reply) => Ok(NodeRequest::Answered(
+                warp::reply::json(&reply).into_response(),
+            )),
+            Err(e) => Err(unauthorized(e.to_string())),
+        },
+        Ok(Envelope::Sealed(message)) => {
+            let plaintext = channels
+                .open(&message)
+                .await
+                .map_err(|e| unauthorized(e.to_string()))?;
+            let request = serde_json::from_slice(&plaintext).map_err(bad_request)?;
+            let reply_to = ReplyTo {
+                peer: message.from,
+                sequence: message.sequence,
+            };
+            Ok(NodeRequest::Message(request, Some(reply_to)))
+        }
+        Err(_) => Err(unauthorized(
+            "Node-to-node requests must come through a secure channel".into(),
+        )),
+    }
+}
+
+/// Reply to a node-to-node request, sealed for the peer and bound to the
+/// request when it was sealed
+async fn node_reply(
+    channels: Option<&SecureChannels>,
+    reply_to: Option<&ReplyTo>,
+    body: Vec<u8>,
+    content_type: &'static str,
+) -> warp::reply::Response {
+    match (channels, reply_to) {
+        (Some(channels), Some(reply_to)) => match channels.seal_reply(reply_to, &body).await {
+            Ok(sealed) => warp::reply::json(&sealed).into_response(),
+            Err(e) => error_reply(e.to_string(), warp::http::StatusCode::UNAUTHORIZED),
+        },
+        _ => warp::reply::with_header(body, "Content-Type", content_type).into_response(),
+    }
+}
+
+/// Try each route in order, answering with the first that matches
+fn first_match(
+    routes: Vec<warp::filters::BoxedFilter<(warp::reply::Response,)>>,
+) -> warp::filters::BoxedFilter<(warp::reply::Response,)> {
+    routes
+        .into_iter()
+        .reduce(|first, next| first.or(next).unify().boxed())
+        .expect("at least one route")
+}
+
+/// Reply used when admission control turns a request away
+fn admission_rejected(e: AdmissionError) -> warp::reply::Response {
+    let retry_after = e.retry_after().as_secs().max(1);
+    let mut reply = error_reply(e.to_string(), warp::http::StatusCode::SERVICE_UNAVAILABLE);
+    reply
+        .headers_mut()
+        .insert(warp::http::header::RETRY_AFTER, retry_after.into());
+    reply
+}
+
+/// Priority of a request to a route whose default class is `route`, as
+/// lowered by the priority header
+fn request_priority(
+    route: Priority,
+) -> impl Filter<Extract = (Priority,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>(PRIORITY_HEADER)
+        .map(move |header: Option<String>| Priority::resolve(route, header.as_deref()))
+}
+
+/// Admission control and priority pools shared by the routes
+#[derive(Clone)]
+struct Scheduling {
+    admission: Option<Arc<AdmissionController>>,
+    pools: Option<Arc<PriorityPools>>,
+}
+
+/// Held while an admitted request runs
+struct Admitted {
+    _permit: Option<AdmissionPermit>,
+    _slot: Option<PoolSlot>,
+}
+
+impl Scheduling {
+    /// Admit a request and wait for a slot in its class's pool, or the
+    /// reply turning it away
+    async fn admit(
+        &self,
+        priority: Priority,
+    ) -> std::result::Result<Admitted, warp::reply::Response> {
+        let permit = match &self.admission {
+            Some(admission) => Some(
+                admission
+                    .admit(priority)
+                    .await
+                    .map_err(admission_rejected)?,
+            ),
+            None => None,
+        };
+        let slot = match &self.pools {
+            Some(pools) => Some(pools.acquire(priority).await),
+            None => None,
+        };
+        Ok(Admitted {
+            _permit: permit,
+            _slot: slot,
+        })
+    }
+}
+
 /// Server configuration
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
@@ -50,6 +297,22 @@ pub struct ServerConfig {
 
     /// Path for the API endpoint
     pub api_path: String,
+
+    /// Where shards are persisted across restarts; `None` keeps them in
+    /// memory only
+    pub persistence: Option<PersistenceConfig>,
+
+    /// Path the Qdrant-compatible API (see [`compat`]) is served under;
+    /// `None` disables it
+    pub compat_path: Option<String>,
+
+    /// Credentials required on the API, WebSocket and compat routes (see
+    /// [`auth`]); `None` leaves them open
+    pub auth: Option<AuthConfig>,
+
+    /// Files and hosts `/api/import` and `/api/indexes/build` may read
+    /// from; nothing by default
+    pub import: ImportPolicy,
 }
 
 impl Default for ServerConfig {
@@ -61,7 +324,70 @@ impl Default for ServerConfig {
             metrics_path: "/metrics".to_string(),
             enable_api: true,
             api_path: "/api".to_string(),
+            persistence: None,
+            compat_path: Some("/qdrant".to_string()),
+            auth: None,
+            import: ImportPolicy::default(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Defaults overridden by `ROSE_FOREST_ADDRESS`, `ROSE_FOREST_PORT`,
+    /// `ROSE_FOREST_DATA_DIR` (with `ROSE_FOREST_STORAGE_ENGINE`), and
+    /// `ROSE_FOREST_API_KEYS` (comma-separated) or `ROSE_FOREST_JWT_SECRET`,
+    /// either of which switches authentication on, and `ROSE_FOREST_IMPORT_DIR`
+    /// and `ROSE_FOREST_IMPORT_HOSTS` (comma-separated) for HTTP imports.
+    /// Empty API keys and JWT secrets shorter than
+    /// [`auth::MIN_JWT_SECRET_LEN`] bytes are startup errors.
+    pub fn from_env() -> Result<Self> {
+        let mut config = Self::default();
+        if let Ok(address) = std::env::var("ROSE_FOREST_ADDRESS") {
+            config.address = address;
+        }
+        if let Ok(port) = std::env::var("ROSE_FOREST_PORT") {
+            config.port = port.parse()?;
+        }
+
+        // Persist shards when a data directory is configured
+        if let Ok(dir) = std::env::var("ROSE_FOREST_DATA_DIR") {
+            let engine = match std::env::var("ROSE_FOREST_STORAGE_ENGINE") {
+                Ok(engine) => engine.parse()?,
+                Err(_) => StorageEngine::File,
+            };
+            config.persistence = Some(PersistenceConfig::new(engine, dir));
+        }
+
+        // Require credentials on the API when keys or a JWT secret are set
+        let keys = std::env::var("ROSE_FOREST_API_KEYS");
+        let secret = std::env::var("ROSE_FOREST_JWT_SECRET");
+        if keys.is_ok() || secret.is_ok() {
+            let mut auth = AuthConfig::new();
+            for key in keys.iter().flat_map(|keys| keys.split(',')) {
+                if key.trim().is_empty() {
+                    return Err(AuthConfigError::EmptyApiKey)
+                        .context("Invalid ROSE_FOREST_API_KEYS");
+                }
+                auth = auth.with_api_key(key.trim());
+            }
+            if let Ok(secret) = secret {
+                auth = auth
+                    .with_jwt_secret(secret)
+                    .context("Invalid ROSE_FOREST_JWT_SECRET")?;
+            }
+            config.auth = Some(auth);
+        }
+
+        // Files and hosts HTTP imports may read from
+        if let Ok(dir) = std::env::var("ROSE_FOREST_IMPORT_DIR") {
+            config.import = config.import.with_dir(dir);
         }
+        if let Ok(hosts) = std::env::var("ROSE_FOREST_IMPORT_HOSTS") {
+            for host in hosts.split(',').map(str::trim).filter(|h| !h.is_empty()) {
+                config.import = config.import.with_allowed_host(host);
+            }
+        }
+        Ok(config)
     }
 }
 
@@ -71,6 +397,33 @@ pub struct Server {
     metrics: Arc<MetricsCollector>,
     runtime: Option<Arc<Runtime>>,
     shard_manager: Option<Arc<ShardManager>>,
+    webhook_ingestor: Option<Arc<WebhookIngestor>>,
+    region_replicator: Option<Arc<RegionReplicator>>,
+    lifecycle_log: Option<Arc<LifecycleLog>>,
+    self_improvement: Option<Arc<SelfImprovementEngine>>,
+    purge: Option<Arc<PurgeService>>,
+    retention: Option<Arc<RetentionEnforcer>>,
+    rebalancer: Option<Arc<RebalanceManager>>,
+    index_health: Option<Arc<IndexHealthMonitor>>,
+    delegator: Option<Arc<TaskDelegator>>,
+    cluster_metrics: Option<Arc<ClusterMetricsAggregator>>,
+    secure_channels: Option<Arc<SecureChannels>>,
+    admission: Option<Arc<AdmissionController>>,
+    pools: Option<Arc<PriorityPools>>,
+    jobs: Option<Arc<JobQueue>>,
+    models: Option<Arc<ModelRegistry>>,
+    search_log: Option<Arc<SearchLog>>,
+    ranking: Option<Arc<RankingPipeline>>,
+    slow_queries: Option<Arc<SlowQueryLog>>,
+    embeddings: Option<Arc<EmbeddingRegistry>>,
+    embedding_capture: Option<String>,
+    synonyms: Option<Arc<SynonymStore>>,
+    experiments: Option<Arc<Experiments>>,
+    scorers: Option<Arc<ScoringPlugins>>,
+    circuit_breakers: Option<Arc<CircuitBreakerRegistry>>,
+    trust: Option<Arc<TrustManager>>,
+    thresholds: Option<Arc<ThresholdAdmin>>,
+    tasks: Arc<TaskRegistry>,
     server_handle: RwLock<Option<JoinHandle<Result<()>>>>,
     start_time: Arc<StdRwLock<Option<Instant>>>,
 }
@@ -88,12 +441,221 @@ impl Server {
             metrics,
             runtime,
             shard_manager,
+            webhook_ingestor: None,
+            region_replicator: None,
+            lifecycle_log: None,
+            self_improvement: None,
+            purge: None,
+            retention: None,
+            rebalancer: None,
+            index_health: None,
+            delegator: None,
+            cluster_metrics: None,
+            secure_channels: None,
+            admission: None,
+            pools: None,
+            jobs: None,
+            models: None,
+            search_log: None,
+            ranking: None,
+            slow_queries: None,
+            embeddings: None,
+            embedding_capture: None,
+            synonyms: None,
+            experiments: None,
+            scorers: None,
+            circuit_breakers: None,
+            trust: None,
+            thresholds: None,
+            tasks: tasks::registry().clone(),
             server_handle: RwLock::new(None),
             start_time: Arc::new(StdRwLock::new(None)),
         }
     }
 
-    /// Start the server
+    /// Enable the multi-region replication endpoints
+    pub fn with_region_replicator(mut self, replicator: Arc<RegionReplicator>) -> Self {
+        self.region_replicator = Some(replicator);
+        self
+    }
+
+    /// Enable the webhook ingestion endpoints
+    pub fn with_webhook_ingestor(mut self, ingestor: Arc<WebhookIngestor>) -> Self {
+        self.webhook_ingestor = Some(ingestor);
+        self
+    }
+
+    /// Enable the modification timeline endpoint
+    pub fn with_lifecycle_log(mut self, lifecycle: Arc<LifecycleLog>) -> Self {
+        self.lifecycle_log = Some(lifecycle);
+        self
+    }
+
+    /// Enable the darwin modification endpoints
+    pub fn with_self_improvement_engine(mut self, engine: Arc<SelfImprovementEngine>) -> Self {
+        self.self_improvement = Some(engine);
+        self
+    }
+
+    /// Enable the data purge admin endpoint
+    pub fn with_purge_service(mut self, purge: Arc<PurgeService>) -> Self {
+        self.purge = Some(purge);
+        self
+    }
+
+    /// Enable the retention policy admin endpoints
+    pub fn with_retention_enforcer(mut self, retention: Arc<RetentionEnforcer>) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Enable the shard rebalancing admin endpoints
+    pub fn with_rebalance_manager(mut self, rebalancer: Arc<RebalanceManager>) -> Self {
+        self.rebalancer = Some(rebalancer);
+        self
+    }
+
+    /// Report index health alongside shard statistics
+    pub fn with_index_health_monitor(mut self, monitor: Arc<IndexHealthMonitor>) -> Self {
+        self.index_health = Some(monitor);
+        self
+    }
+
+    /// Enable the cluster heartbeat, task report and trace endpoints
+    pub fn with_task_delegator(mut self, delegator: Arc<TaskDelegator>) -> Self {
+        self.delegator = Some(delegator);
+        self
+    }
+
+    /// Serve the merged metrics of this node and its peers under the
+    /// metrics path, at `/metrics/cluster` by default
+    pub fn with_cluster_metrics(mut self, aggregator: Arc<ClusterMetricsAggregator>) -> Self {
+        self.cluster_metrics = Some(aggregator);
+        self
+    }
+
+    /// Take replication segments only through these channels, and serve
+    /// metrics to peers scraping through them with a sealed `POST`
+    pub fn with_secure_channels(mut self, channels: Arc<SecureChannels>) -> Self {
+        self.secure_channels = Some(channels);
+        self
+    }
+
+    /// Queue or shed bulk imports and webhook ingestion while the node is
+    /// saturated, and feed search latencies into the controller
+    pub fn with_admission_controller(mut self, admission: Arc<AdmissionController>) -> Self {
+        self.admission = Some(admission);
+        self
+    }
+
+    /// Run requests in separately bounded pools per priority class
+    pub fn with_priority_pools(mut self, pools: Arc<PriorityPools>) -> Self {
+        self.pools = Some(pools);
+        self
+    }
+
+    /// Enable the endpoints for submitting, polling and cancelling jobs
+    pub fn with_job_queue(mut self, jobs: Arc<JobQueue>) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    /// Enable the endpoints for downloading and rolling back federated models
+    pub fn with_model_registry(mut self, models: Arc<ModelRegistry>) -> Self {
+        self.models = Some(models);
+        self
+    }
+
+    /// Give searches a query id, keep their results and accept feedback
+    /// about them
+    pub fn with_search_log(mut self, log: Arc<SearchLog>) -> Self {
+        self.search_log = Some(log);
+        self
+    }
+
+    /// Rerank searches that ask for it with the model trained from feedback
+    pub fn with_ranking_pipeline(mut self, ranking: Arc<RankingPipeline>) -> Self {
+        self.ranking = Some(ranking);
+        self
+    }
+
+    /// Log searches slower than the log's threshold, joined with their
+    /// feedback when a search log is also configured
+    pub fn with_slow_query_log(mut self, slow_queries: Arc<SlowQueryLog>) -> Self {
+        self.slow_queries = Some(slow_queries);
+        self
+    }
+
+    /// Serve text searches over the registry's collections
+    pub fn with_embedding_registry(mut self, embeddings: Arc<EmbeddingRegistry>) -> Self {
+        self.embeddings = Some(embeddings);
+        self
+    }
+
+    /// Store every text embedded through `/v1/embeddings` in `collection`,
+    /// which needs an active space in the embedding registry
+    pub fn with_embedding_capture(mut self, collection: impl Into<String>) -> Self {
+        self.embedding_capture = Some(collection.into());
+        self
+    }
+
+    /// Accept per-collection synonym dictionaries and expand text searches
+    /// with them
+    pub fn with_synonyms(mut self, synonyms: Arc<SynonymStore>) -> Self {
+        self.synonyms = Some(synonyms);
+        self
+    }
+
+    /// Run A/B experiments on searches that carry a client key. Outcomes
+    /// are read from the search log, so one should be configured as well.
+    pub fn with_experiments(mut self, experiments: Arc<Experiments>) -> Self {
+        self.experiments = Some(experiments);
+        self
+    }
+
+    /// Accept WebAssembly scorer uploads and let searches re-score their
+    /// candidates with them
+    pub fn with_scoring_plugins(mut self, scorers: Arc<ScoringPlugins>) -> Self {
+        self.scorers = Some(scorers);
+        self
+    }
+
+    /// Expose the registered circuit breakers to operators, who can reset
+    /// or trip them
+    pub fn with_circuit_breakers(mut self, breakers: Arc<CircuitBreakerRegistry>) -> Self {
+        self.circuit_breakers = Some(breakers);
+        self
+    }
+
+    /// Refuse heartbeats from quarantined or blocked peers and let
+    /// operators list, block and unblock peers
+    pub fn with_trust_manager(mut self, trust: Arc<TrustManager>) -> Self {
+        self.trust = Some(trust);
+        self
+    }
+
+    /// Let operators inspect and change validation thresholds at runtime
+    pub fn with_threshold_admin(mut self, thresholds: Arc<ThresholdAdmin>) -> Self {
+        self.thresholds = Some(thresholds);
+        self
+    }
+
+    /// List tasks from `registry` instead of the process-wide one
+    pub fn with_task_registry(mut self, registry: Arc<TaskRegistry>) -> Self {
+        self.tasks = registry;
+        self
+    }
+
+    fn scheduling(&self) -> Scheduling {
+        Scheduling {
+            admission: self.admission.clone(),
+            pools: self.pools.clone(),
+        }
+    }
+
+    /// Start the server. With a runtime it stops when the runtime shuts
+    /// down, which waits for in-flight requests to finish; otherwise it
+    /// stops on CTRL+C.
     pub async fn start(&mut self) -> Result<()> {
         *self.start_time.write().unwrap() = Some(Instant::now());
         let addr = format!("{}:{}", self.config.address, self.config.port);
@@ -103,18 +665,35 @@ impl Server {
 
         info!("Starting server on {}", addr);
 
-        let (_, server_handle) = server.bind_with_graceful_shutdown(addr, async {
-            tokio::signal::ctrl_c()
-                .await
-                .expect("Failed to listen for CTRL+C");
+        let shutdown = self
+            .runtime
+            .as_ref()
+            .map(|runtime| runtime.shutdown_token());
+        let (_, server_handle) = server.bind_with_graceful_shutdown(addr, async move {
+            match shutdown {
+                Some(shutdown) => shutdown.cancelled().await,
+                None => tokio::signal::ctrl_c()
+                    .await
+                    .expect("Failed to listen for CTRL+C"),
+            }
 
-            info!("Received shutdown signal, stopping server...");
+            info!("Received shutdown signal, draining in-flight requests...");
         });
 
+        // Resolves once the last in-flight request is answered, or the
+        // listener is aborted
+        let (drained_tx, drained_rx) = tokio::sync::oneshot::channel::<()>();
+        if let Some(runtime) = &self.runtime {
+            runtime.register_component("http listener", async move {
+                let _ = drained_rx.await;
+            });
+        }
+
         // Store server handle
         let mut handle = self.server_handle.write().await;
-        *handle = Some(tokio::spawn(async move {
+        *handle = Some(tasks::spawn("server", "http listener", async move {
             server_handle.await;
+            drop(drained_tx);
             Ok(())
         }));
 
@@ -158,12 +737,25 @@ impl Server {
                 }
             };
 
-            let query = create_vector(req.query_vector.clone());
+            let filter = req.filter_expr();
+            let queries: Vec<_> = std::iter::once(req.query_vector)
+                .chain(req.additional_queries)
+                .map(create_vector)
+                .collect();
             let results = match manager
-                .search_vectors(req.shard_id, &query, req.limit)
+                .search_vectors_fused(
+                    req.shard_id,
+                    &queries,
+                    req.limit,
+                    filter.as_ref(),
+                    &req.diversify,
+                    None,
+                    None,
+                    req.fusion,
+                )
                 .await
             {
-                Ok(r) => r,
+                Ok(outcome) => outcome.results,
                 Err(e) => {
                     let err = ErrorResponse {
                         error: e.to_string(),
@@ -175,21 +767,41 @@ impl Server {
                 }
             };
 
-            let api_results = convert_search_results(results);
-            let (tx, mut rx) = broadcast::channel::<String>(16);
-            let mut tx_ws_clone = tx_ws.clone();
-            let forward = tokio::spawn(async move {
-                while let Ok(res) = rx.recv().await {
-                    if tx_ws_clone.send(Message::text(res)).await.is_err() {
-                        break;
-                    }
+            for result in convert_search_results(results) {
+                let text = serde_json::to_string(&result).unwrap();
+                if tx_ws.send(Message::text(text)).await.is_err() {
+                    return;
                 }
-            });
-            for r in api_results {
-                let _ = tx.send(serde_json::to_string(&r).unwrap());
             }
-            drop(tx);
-            let _ = forward.await;
+        }
+    }
+
+    /// Stream change and lifecycle events to a `/ws/events` subscriber
+    /// until it disconnects, leaving out types newer than its schema
+    async fn handle_ws_events(
+        socket: WebSocket,
+        version: u32,
+        mut changes: Option<broadcast::Receiver<ChangeEvent>>,
+        mut lifecycle: Option<broadcast::Receiver<LifecycleEvent>>,
+    ) {
+        let (mut tx_ws, mut rx_ws) = socket.split();
+        loop {
+            let event = tokio::select! {
+                event = recv_event(&mut changes) => event,
+                event = recv_event(&mut lifecycle) => event,
+                msg = rx_ws.next() => match msg {
+                    // Subscribers only listen; anything but a close is ignored
+                    Some(Ok(msg)) if !msg.is_close() => continue,
+                    _ => break,
+                },
+            };
+            let Some(envelope) = EventEnvelope::for_version(version, event) else {
+                continue;
+            };
+            let text = serde_json::to_string(&envelope).unwrap();
+            if tx_ws.send(Message::text(text)).await.is_err() {
+                break;
+            }
         }
     }
 
@@ -211,7 +823,7 @@ impl Server {
         &self,
         metrics: Arc<MetricsCollector>,
         config: ServerConfig,
-        runtime: Option<Arc<Runtime>>,
+        _runtime: Option<Arc<Runtime>>,
         shard_manager: Option<Arc<ShardManager>>,
         start_time: Arc<StdRwLock<Option<Instant>>>,
     ) -> impl warp::Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
@@ -225,18 +837,78 @@ impl Server {
 
         let metrics_path = config.metrics_path.trim_start_matches('/').to_string();
         let metrics_route = if config.enable_metrics {
+            let cluster_metrics = self.cluster_metrics.clone();
+            let cluster_route = warp::path(metrics_path.clone())
+                .and(warp::path("cluster"))
+                .and(warp::path::end())
+                .and(warp::get())
+                .and_then(move || {
+                    let cluster_metrics = cluster_metrics.clone();
+                    async move {
+                        Ok::<_, warp::Rejection>(match cluster_metrics {
+                            Some(aggregator) => warp::reply::with_header(
+                                aggregator.render().await,
+                                "Content-Type",
+                                "text/plain; version=0.0.4",
+                            )
+                            .into_response(),
+                            None => not_configured("Cluster metrics"),
+                        })
+                    }
+                });
+
             let metrics_clone = metrics.clone();
-            warp::path(metrics_path.clone())
-                .map(move || {
+            let local_route = warp::path(metrics_path.clone()).then(move || {
+                let metrics = metrics_clone.clone();
+                async move {
                     debug!("Metrics request received");
-                    let prometheus_metrics = metrics_clone.prometheus_metrics();
                     warp::reply::with_header(
-                        prometheus_metrics,
+                        metrics.generate_prometheus_metrics().await,
                         "Content-Type",
                         "text/plain; version=0.0.4",
                     )
                     .into_response()
-                })
+                }
+            });
+
+            // Peers with a secure session scrape with a sealed POST
+            let metrics_for_peers = metrics.clone();
+            let channels_for_peers = self.secure_channels.clone();
+            let peer_route = warp::path(metrics_path.clone())
+                .and(warp::path::end())
+                .and(warp::post())
+                .and(warp::body::content_length_limit(NODE_REQUEST_BODY_LIMIT))
+                .and(warp::body::bytes())
+                .and_then(move |body: bytes::Bytes| {
+                    let metrics = metrics_for_peers.clone();
+                    let channels = channels_for_peers.clone();
+                    async move {
+                        let Some(channels) = channels else {
+                            return Err(warp::reject::not_found());
+                        };
+                        let reply_to =
+                            match open_node_request::<serde_json::Value>(Some(&channels), &body)
+                                .await
+                            {
+                                Ok(NodeRequest::Message(_, reply_to)) => reply_to,
+                                Ok(NodeRequest::Answered(reply)) | Err(reply) => return Ok(reply),
+                            };
+                        Ok(node_reply(
+                            Some(&channels),
+                            reply_to.as_ref(),
+                            metrics.generate_prometheus_metrics().await.into_bytes(),
+                            "text/plain; version=0.0.4",
+                        )
+                        .await)
+                    }
+                });
+
+            // The cluster view comes first, the local route matches any subpath
+            peer_route
+                .or(cluster_route)
+                .unify()
+                .or(local_route)
+                .unify()
                 .boxed()
         } else {
             warp::path(metrics_path)
@@ -286,201 +958,20 @@ impl Server {
                 })
                 .boxed();
 
-            let manager_for_create = shard_manager.clone();
-            let create_shard = warp::path(api_path.clone())
-                .and(warp::path("shards"))
-                .and(warp::post())
-                .and(json_body::<CreateShardRequest>())
-                .and_then(move |req: CreateShardRequest| {
-                    let manager_opt = manager_for_create.clone();
-                    async move {
-                        if let Some(manager) = manager_opt {
-                            match manager.create_shard(&req.name).await {
-                                Ok(id) => Ok::<_, warp::Rejection>(
-                                    warp::reply::json(&CreateShardResponse { shard_id: id })
-                                        .into_response(),
-                                ),
-                                Err(e) => Ok(warp::reply::with_status(
-                                    warp::reply::json(&ErrorResponse {
-                                        error: e.to_string(),
-                                    }),
-                                    warp::http::StatusCode::BAD_REQUEST,
-                                )
-                                .into_response()),
-                            }
-                        } else {
-                            Ok::<_, warp::Rejection>(
-                                warp::reply::with_status(
-                                    warp::reply::json(&ErrorResponse {
-                                        error: "Shard manager not configured".into(),
-                                    }),
-                                    warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                                )
-                                .into_response(),
-                            )
-                        }
-                    }
-                })
-                .boxed();
-
-            let manager_for_index = shard_manager.clone();
-            let create_index = warp::path(api_path.clone())
-                .and(warp::path("indexes"))
-                .and(warp::post())
-                .and(json_body::<CreateIndexRequest>())
-                .and_then(move |req: CreateIndexRequest| {
-                    let manager_opt = manager_for_index.clone();
-                    async move {
-                        if let Some(manager) = manager_opt {
-                            match parse_distance_metric(&req.distance_metric) {
-                                Ok(metric) => match manager
-                                    .create_vector_index(
-                                        req.shard_id,
-                                        &req.name,
-                                        req.dimensions,
-                                        metric,
-                                    )
-                                    .await
-                                {
-                                    Ok(_) => Ok::<_, warp::Rejection>(
-                                        warp::reply::json(&CreateIndexResponse {
-                                            shard_id: req.shard_id,
-                                            index_name: req.name,
-                                            dimensions: req.dimensions,
-                                            distance_metric: req.distance_metric.to_lowercase(),
-                                        })
-                                        .into_response(),
-                                    ),
-                                    Err(e) => Ok(warp::reply::with_status(
-                                        warp::reply::json(&ErrorResponse {
-                                            error: e.to_string(),
-                                        }),
-                                        warp::http::StatusCode::BAD_REQUEST,
-                                    )
-                                    .into_response()),
-                                },
-                                Err(e) => Ok(warp::reply::with_status(
-                                    warp::reply::json(&ErrorResponse { error: e }),
-                                    warp::http::StatusCode::BAD_REQUEST,
-                                )
-                                .into_response()),
-                            }
-                        } else {
-                            Ok::<_, warp::Rejection>(
-                                warp::reply::with_status(
-                                    warp::reply::json(&ErrorResponse {
-                                        error: "Shard manager not configured".into(),
-                                    }),
-                                    warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                                )
-                                .into_response(),
-                            )
-                        }
-                    }
-                })
-                .boxed();
-
-            let manager_for_add = shard_manager.clone();
-            let add_vector = warp::path(api_path.clone())
-                .and(warp::path("vectors"))
-                .and(warp::post())
-                .and(json_body::<AddVectorRequest>())
-                .and_then(move |req: AddVectorRequest| {
-                    let manager_opt = manager_for_add.clone();
-                    async move {
-                        if let Some(manager) = manager_opt {
-                            let vector = create_vector(req.vector);
-                            match manager.add_vector(req.shard_id, vector, req.metadata).await {
-                                Ok(id) => Ok::<_, warp::Rejection>(
-                                    warp::reply::json(&AddVectorResponse { vector_id: id })
-                                        .into_response(),
-                                ),
-                                Err(e) => Ok(warp::reply::with_status(
-                                    warp::reply::json(&ErrorResponse {
-                                        error: e.to_string(),
-                                    }),
-                                    warp::http::StatusCode::BAD_REQUEST,
-                                )
-                                .into_response()),
-                            }
-                        } else {
-                            Ok::<_, warp::Rejection>(
-                                warp::reply::with_status(
-                                    warp::reply::json(&ErrorResponse {
-                                        error: "Shard manager not configured".into(),
-                                    }),
-                                    warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                                )
-                                .into_response(),
-                            )
-                        }
-                    }
-                })
-                .boxed();
-
-            let manager_for_search = shard_manager.clone();
-            let search_vectors = warp::path(api_path.clone())
-                .and(warp::path("search"))
-                .and(warp::post())
-                .and(json_body::<SearchVectorsRequest>())
-                .and_then(move |req: SearchVectorsRequest| {
-                    let manager_opt = manager_for_search.clone();
-                    async move {
-                        if let Some(manager) = manager_opt {
-                            if req.limit == 0 {
-                                return Ok::<_, warp::Rejection>(warp::reply::with_status(
-                                    warp::reply::json(&ErrorResponse { error: "limit must be greater than zero".into() }),
-                                    warp::http::StatusCode::BAD_REQUEST,
-                                ).into_response());
-                            }
-                            let query = create_vector(req.query_vector);
-                            if let Ok(index) = manager.get_vector_index(req.shard_id).await {
-                                let stats = index.stats().await;
-                                if query.dimensions != stats.dimensions {
-                                    return Ok::<_, warp::Rejection>(warp::reply::with_status(
-                                        warp::reply::json(&ErrorResponse {
-                                            error: format!(
-                                                "Query vector dimensions mismatch: expected {}, got {}",
-                                                stats.dimensions, query.dimensions
-                                            ),
-                                        }),
-                                        warp::http::StatusCode::BAD_REQUEST,
-                                    ).into_response());
-                                }
-                            } else {
-                                return Ok::<_, warp::Rejection>(warp::reply::with_status(
-                                    warp::reply::json(&ErrorResponse { error: "Vector index not found".into() }),
-                                    warp::http::StatusCode::BAD_REQUEST,
-                                ).into_response());
-                            }
-                            match manager.search_vectors(req.shard_id, &query, req.limit).await {
-                                Ok(results) => {
-                                    let results = convert_search_results(results);
-                                    Ok::<_, warp::Rejection>(warp::reply::json(&SearchVectorsResponse { results }).into_response())
-                                }
-                                Err(e) => Ok(warp::reply::with_status(
-                                    warp::reply::json(&ErrorResponse { error: e.to_string() }),
-                                    warp::http::StatusCode::BAD_REQUEST,
-                                ).into_response()),
-                            }
-                        } else {
-                            Ok::<_, warp::Rejection>(warp::reply::with_status(
-                                warp::reply::json(&ErrorResponse { error: "Shard manager not configured".into() }),
-                                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                            ).into_response())
-                        }
-                    }
-                })
-                .boxed();
-
-            version_route
-                .or(stats_route)
-                .or(create_shard)
-                .or(create_index)
-                .or(add_vector)
-                .or(search_vectors)
-                .unify()
-                .boxed()
+            // Each feature module owns every route under its path prefix, so
+            // only the order within a module matters
+            let mut routes = vec![version_route, stats_route];
+            routes.extend(shards::routes(self, &api_path, &shard_manager, &config));
+            routes.extend(vectors::routes(self, &api_path, &shard_manager));
+            routes.extend(search::routes(self, &api_path, &shard_manager));
+            routes.extend(ingest::routes(self, &api_path, &shard_manager, &config));
+            routes.extend(replication::routes(self, &api_path));
+            routes.extend(darwin::routes(self, &api_path));
+            routes.extend(admin::routes(self, &api_path));
+            routes.extend(cluster::routes(self, &api_path));
+            routes.extend(jobs::routes(self, &api_path));
+            routes.extend(relevance::routes(self, &api_path));
+            first_match(routes)
         } else {
             warp::path(api_path)
                 .map(|| {
@@ -509,9 +1000,79 @@ impl Server {
                 .boxed()
         };
 
-        health_route
-            .or(metrics_route)
-            .or(api_routes)
-            .or(ws_search_route)
+        let ws_events_route = {
+            let manager_opt = shard_manager.clone();
+            let engine_opt = self.self_improvement.clone();
+            warp::path("ws")
+                .and(warp::path("events"))
+                .and(warp::path::end())
+                .and(warp::header::optional::<String>("sec-websocket-protocol"))
+                .and(warp::ws())
+                .map(move |offered: Option<String>, ws: warp::ws::Ws| {
+                    let Some(version) = events::negotiate(offered.as_deref()) else {
+                        return error_reply(
+                            format!(
+                                "Unsupported event schema; this server speaks {} to {}",
+                                events::protocol(events::MIN_SCHEMA_VERSION),
+                                events::protocol(events::SCHEMA_VERSION)
+                            ),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        );
+                    };
+                    // Subscribe before the upgrade so no event falls in between
+                    let changes = manager_opt.as_ref().map(|m| m.subscribe_changes());
+                    let lifecycle = engine_opt
+                        .as_ref()
+                        .map(|engine| engine.lifecycle_log().subscribe());
+                    let reply = ws.on_upgrade(move |socket| {
+                        Server::handle_ws_events(socket, version, changes, lifecycle)
+                    });
+                    // Clients that didn't negotiate must not get a protocol back
+                    match offered {
+                        Some(_) => warp::reply::with_header(
+                            reply,
+                            "sec-websocket-protocol",
+                            events::protocol(version),
+                        )
+                        .into_response(),
+                        None => reply.into_response(),
+                    }
+                })
+                .boxed()
+        };
+
+        let compat_routes = match &config.compat_path {
+            Some(path) => compat::routes(path.trim_start_matches('/'), shard_manager.clone()),
+            None => warp::any()
+                .and_then(|| async { Err::<warp::reply::Response, _>(warp::reject::not_found()) })
+                .boxed(),
+        };
+
+        let openai_routes = openai::routes(
+            self.embeddings.clone(),
+            self.embedding_capture.clone(),
+            metrics.clone(),
+        );
+
+        // Health and metrics stay open for probes and scrapers
+        let mut protected = vec![
+            config.api_path.trim_start_matches('/').to_string(),
+            "ws".to_string(),
+            "v1".to_string(),
+        ];
+        if let Some(path) = &config.compat_path {
+            protected.push(path.trim_start_matches('/').to_string());
+        }
+        let guarded_routes = auth::guard(config.auth.clone().map(Arc::new), protected, metrics)
+            .and(
+                api_routes
+                    .or(ws_search_route)
+                    .or(ws_events_route)
+                    .or(compat_routes)
+                    .or(openai_routes),
+            )
+            .recover(auth::unauthorized);
+
+        health_route.or(metrics_route).or(guarded_routes)
     }
 }
@@ -0,0 +1,243 @@
+//! OpenAI-compatible `POST /v1/embeddings`.
+//!
+//! Tools written against OpenAI can point their base URL at this server and
+//! get embeddings from the models registered in the [`EmbeddingRegistry`].
+//! The `model` they send is resolved with
+//! [`EmbeddingRegistry::resolve_provider`], so with a single local model
+//! registered any model name works. Both `float` and `base64` encodings are
+//! served, since the official SDKs ask for `base64` by default. When the
+//! server has a capture collection (see `Server::with_embedding_capture`),
+//! every input is also stored there with its embedding; a failed capture is
+//! logged and counted but doesn't fail the request. Errors use OpenAI's
+//! `{"error": {...}}` shape.
+
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::warn;
+use warp::filters::BoxedFilter;
+use warp::http::StatusCode;
+use warp::{Filter, Reply};
+
+use crate::core::metrics::MetricsCollector;
+use crate::embedding::EmbeddingRegistry;
+
+use super::BATCH_VECTORS_BODY_LIMIT;
+
+/// Inputs accepted in one request, as OpenAI limits them
+pub const MAX_INPUTS: usize = 2048;
+
+/// Texts to embed: one string or a list of them. Pre-tokenized input isn't
+/// supported, since tokens depend on the model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl EmbeddingInput {
+    fn into_texts(self) -> Vec<String> {
+        match self {
+            EmbeddingInput::One(text) => vec![text],
+            EmbeddingInput::Many(texts) => texts,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncodingFormat {
+    #[default]
+    Float,
+    /// Little-endian `f32`s, base64-encoded
+    Base64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingsRequest {
+    pub model: String,
+    pub input: EmbeddingInput,
+    #[serde(default)]
+    pub encoding_format: EncodingFormat,
+    /// Must match the model's dimensions when given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingValue {
+    Float(Vec<f32>),
+    Base64(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Embedding {
+    pub object: String,
+    pub index: usize,
+    pub embedding: EmbeddingValue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingsUsage {
+    pub prompt_tokens: usize,
+    pub total_tokens: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingsResponse {
+    pub object: String,
+    pub data: Vec<Embedding>,
+    pub model: String,
+    pub usage: EmbeddingsUsage,
+}
+
+fn openai_error(status: StatusCode, message: String, param: Option<&str>) -> warp::reply::Response {
+    let kind = if status.is_server_error() {
+        "server_error"
+    } else {
+        "invalid_request_error"
+    };
+    warp::reply::with_status(
+        warp::reply::json(&json!({
+            "error": { "message": message, "type": kind, "param": param, "code": null }
+        })),
+        status,
+    )
+    .into_response()
+}
+
+fn encode(values: Vec<f32>, format: EncodingFormat) -> EmbeddingValue {
+    match format {
+        EncodingFormat::Float => EmbeddingValue::Float(values),
+        EncodingFormat::Base64 => {
+            let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+            EmbeddingValue::Base64(STANDARD.encode(bytes))
+        }
+    }
+}
+
+async fn embeddings(
+    request: EmbeddingsRequest,
+    registry: Option<Arc<EmbeddingRegistry>>,
+    capture: Option<String>,
+    metrics: Arc<MetricsCollector>,
+) -> warp::reply::Response {
+    let Some(registry) = registry else {
+        return openai_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Embedding registry not configured".into(),
+            None,
+        );
+    };
+    let provider = match registry.resolve_provider(&request.model).await {
+        Ok(provider) => provider,
+        Err(e) => return openai_error(StatusCode::NOT_FOUND, e.to_string(), Some("model")),
+    };
+    if let Some(dimensions) = request.dimensions {
+        if dimensions != provider.dimensions() {
+            return openai_error(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Model {} produces {} dimensions, not {}",
+                    provider.model_id(),
+                    provider.dimensions(),
+                    dimensions
+                ),
+                Some("dimensions"),
+            );
+        }
+    }
+    let texts = request.input.into_texts();
+    if texts.is_empty() || texts.len() > MAX_INPUTS {
+        return openai_error(
+            StatusCode::BAD_REQUEST,
+            format!("input must hold between 1 and {} texts", MAX_INPUTS),
+            Some("input"),
+        );
+    }
+
+    let vectors = match provider.embed(&texts).await {
+        Ok(vectors) => vectors,
+        Err(e) => {
+            metrics
+                .increment_counter("embeddings.proxy.failures", 1)
+                .await;
+            return openai_error(StatusCode::BAD_GATEWAY, e.to_string(), None);
+        }
+    };
+    metrics
+        .increment_counter("embeddings.proxy.inputs", texts.len() as u64)
+        .await;
+
+    let model_id = provider.model_id();
+    if let Some(collection) = &capture {
+        for (text, values) in texts.iter().zip(&vectors) {
+            let captured = registry
+                .add_embedded_text(collection, text, &model_id, values.clone())
+                .await;
+            if let Err(e) = captured {
+                warn!("Failed to capture embedding into {}: {}", collection, e);
+                metrics
+                    .increment_counter("embeddings.proxy.capture_failures", 1)
+                    .await;
+            }
+        }
+    }
+
+    // Token counts are approximated by whitespace-separated words
+    let tokens = texts.iter().map(|t| t.split_whitespace().count()).sum();
+    let data = vectors
+        .into_iter()
+        .enumerate()
+        .map(|(index, values)| Embedding {
+            object: "embedding".to_string(),
+            index,
+            embedding: encode(values, request.encoding_format),
+        })
+        .collect();
+    warp::reply::json(&EmbeddingsResponse {
+        object: "list".to_string(),
+        data,
+        model: model_id,
+        usage: EmbeddingsUsage {
+            prompt_tokens: tokens,
+            total_tokens: tokens,
+        },
+    })
+    .into_response()
+}
+
+/// `POST /v1/embeddings`, optionally capturing inputs into `capture`
+pub(crate) fn routes(
+    registry: Option<Arc<EmbeddingRegistry>>,
+    capture: Option<String>,
+    metrics: Arc<MetricsCollector>,
+) -> BoxedFilter<(warp::reply::Response,)> {
+    warp::path("v1")
+        .and(warp::path("embeddings"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::content_length_limit(BATCH_VECTORS_BODY_LIMIT))
+        .and(warp::body::bytes())
+        .and_then(move |body: bytes::Bytes| {
+            let registry = registry.clone();
+            let capture = capture.clone();
+            let metrics = metrics.clone();
+            async move {
+                // Parsed here so malformed requests get an OpenAI-shaped error
+                let reply = match serde_json::from_slice::<EmbeddingsRequest>(&body) {
+                    Ok(request) => embeddings(request, registry, capture, metrics).await,
+                    Err(e) => openai_error(StatusCode::BAD_REQUEST, e.to_string(), None),
+                };
+                Ok::<_, warp::Rejection>(reply)
+            }
+        })
+        .boxed()
+}
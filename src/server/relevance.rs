@@ -0,0 +1,309 @@
+//! Relevance routes: search feedback, experiments, the slow-query log and
+//! embedding model versions.
+
+use crate::evaluation::Evaluation;
+use crate::hypothesis::Hypothesis;
+use crate::query::experiments::ExperimentDefinition;
+use crate::server::api::{FeedbackReport, FeedbackRequest, RollbackModelRequest};
+use crate::utils::errors::{ExperimentError, FeedbackError, ModelRegistryError};
+use warp::filters::BoxedFilter;
+use warp::{Filter, Reply};
+
+use super::{error_reply, json_body, not_configured, Server};
+
+/// Relevance routes under `api_path`, in the order they are tried
+pub(super) fn routes(
+    server: &Server,
+    api_path: &str,
+) -> Vec<BoxedFilter<(warp::reply::Response,)>> {
+    let api_path = api_path.to_string();
+
+    let log_for_feedback = server.search_log.clone();
+    let submit_feedback = warp::path(api_path.clone())
+        .and(warp::path("feedback"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body::<FeedbackRequest>())
+        .and_then(move |request: FeedbackRequest| {
+            let log_opt = log_for_feedback.clone();
+            async move {
+                let log = match log_opt {
+                    Some(log) => log,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Search feedback")),
+                };
+                match log
+                    .record_feedback(request.query_id, &request.result_id, request.label)
+                    .await
+                {
+                    Ok(feedback) => Ok(warp::reply::json(&feedback).into_response()),
+                    Err(e @ FeedbackError::UnknownQuery(_)) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let log_for_report = server.search_log.clone();
+    let slow_for_report = server.slow_queries.clone();
+    let feedback_report = warp::path(api_path.clone())
+        .and(warp::path("feedback"))
+        .and(warp::path("report"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let log_opt = log_for_report.clone();
+            let slow_opt = slow_for_report.clone();
+            async move {
+                let log = match log_opt {
+                    Some(log) => log,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Search feedback")),
+                };
+                let slow = match slow_opt {
+                    Some(slow_queries) => slow_queries.join(&log).await,
+                    None => Vec::new(),
+                };
+                let evaluation = Evaluation::new();
+                let recall = evaluation.feedback_recall(&log.labelled().await);
+                let slow_query_recall =
+                    evaluation.feedback_recall(slow.iter().filter_map(|s| s.impression.as_ref()));
+                let hypothesis = Hypothesis::new()
+                    .search_quality(&evaluation.recall_analysis(&recall, &slow_query_recall));
+                Ok(warp::reply::json(&FeedbackReport {
+                    recall,
+                    slow_query_recall,
+                    hypothesis,
+                })
+                .into_response())
+            }
+        })
+        .boxed();
+
+    let experiments_for_start = server.experiments.clone();
+    let start_experiment = warp::path(api_path.clone())
+        .and(warp::path("experiments"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body::<ExperimentDefinition>())
+        .and_then(move |definition: ExperimentDefinition| {
+            let experiments_opt = experiments_for_start.clone();
+            async move {
+                let experiments = match experiments_opt {
+                    Some(experiments) => experiments,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Experiments")),
+                };
+                match experiments.start(definition.clone()).await {
+                    Ok(()) => Ok(warp::reply::with_status(
+                        warp::reply::json(&definition),
+                        warp::http::StatusCode::CREATED,
+                    )
+                    .into_response()),
+                    Err(e @ ExperimentError::InvalidShare(_)) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                    Err(e) => Ok(error_reply(e.to_string(), warp::http::StatusCode::CONFLICT)),
+                }
+            }
+        })
+        .boxed();
+
+    let experiments_for_stop = server.experiments.clone();
+    let stop_experiment =
+        warp::path(api_path.clone())
+            .and(warp::path("experiments"))
+            .and(warp::path::param::<String>())
+            .and(warp::path("stop"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and_then(move |name: String| {
+                let experiments_opt = experiments_for_stop.clone();
+                async move {
+                    let experiments = match experiments_opt {
+                        Some(experiments) => experiments,
+                        None => return Ok::<_, warp::Rejection>(not_configured("Experiments")),
+                    };
+                    match experiments.stop(&name).await {
+                        Ok(()) => Ok(warp::reply::json(&serde_json::json!({ "stopped": name }))
+                            .into_response()),
+                        Err(e) => Ok(error_reply(
+                            e.to_string(),
+                            warp::http::StatusCode::NOT_FOUND,
+                        )),
+                    }
+                }
+            })
+            .boxed();
+
+    let experiments_for_report = server.experiments.clone();
+    let log_for_experiment = server.search_log.clone();
+    let experiment_report = warp::path(api_path.clone())
+        .and(warp::path("experiments"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("report"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move |name: String| {
+            let experiments_opt = experiments_for_report.clone();
+            let log_opt = log_for_experiment.clone();
+            async move {
+                let (experiments, log) = match (experiments_opt, log_opt) {
+                    (Some(experiments), Some(log)) => (experiments, log),
+                    (None, _) => return Ok::<_, warp::Rejection>(not_configured("Experiments")),
+                    (_, None) => return Ok(not_configured("Search feedback")),
+                };
+                match experiments.report(&name, &log).await {
+                    Ok(report) => Ok(warp::reply::json(&report).into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let log_for_slow = server.search_log.clone();
+    let slow_for_list = server.slow_queries.clone();
+    let list_slow_queries = warp::path(api_path.clone())
+        .and(warp::path("slow-queries"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let log_opt = log_for_slow.clone();
+            let slow_opt = slow_for_list.clone();
+            async move {
+                let slow_queries = match slow_opt {
+                    Some(slow_queries) => slow_queries,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Slow-query log")),
+                };
+                // Without a search log there is no feedback to join with
+                let log = log_opt.unwrap_or_default();
+                Ok(warp::reply::json(&slow_queries.join(&log).await).into_response())
+            }
+        })
+        .boxed();
+
+    let models_for_current = server.models.clone();
+    let current_model = warp::path(api_path.clone())
+        .and(warp::path("models"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move |name: String| {
+            let models_opt = models_for_current.clone();
+            async move {
+                let models = match models_opt {
+                    Some(models) => models,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Model registry")),
+                };
+                match models.current(&name).await {
+                    Some(model) => Ok(warp::reply::json(&model).into_response()),
+                    None => Ok(error_reply(
+                        ModelRegistryError::UnknownModel(name).to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let models_for_versions = server.models.clone();
+    let model_versions = warp::path(api_path.clone())
+        .and(warp::path("models"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("versions"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move |name: String| {
+            let models_opt = models_for_versions.clone();
+            async move {
+                let models = match models_opt {
+                    Some(models) => models,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Model registry")),
+                };
+                match models.versions(&name).await {
+                    Some(versions) => Ok(warp::reply::json(&versions).into_response()),
+                    None => Ok(error_reply(
+                        ModelRegistryError::UnknownModel(name).to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let models_for_version = server.models.clone();
+    let model_version = warp::path(api_path.clone())
+        .and(warp::path("models"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("versions"))
+        .and(warp::path::param::<u32>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move |name: String, version: u32| {
+            let models_opt = models_for_version.clone();
+            async move {
+                let models = match models_opt {
+                    Some(models) => models,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Model registry")),
+                };
+                match models.version(&name, version).await {
+                    Some(model) => Ok(warp::reply::json(&model).into_response()),
+                    None => Ok(error_reply(
+                        ModelRegistryError::UnknownVersion {
+                            model: name,
+                            version,
+                        }
+                        .to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let models_for_rollback = server.models.clone();
+    let rollback_model = warp::path(api_path.clone())
+        .and(warp::path("models"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("rollback"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body::<RollbackModelRequest>())
+        .and_then(move |name: String, request: RollbackModelRequest| {
+            let models_opt = models_for_rollback.clone();
+            async move {
+                let models = match models_opt {
+                    Some(models) => models,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Model registry")),
+                };
+                match models.rollback(&name, request.version).await {
+                    Ok(model) => Ok(warp::reply::json(&model).into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    vec![
+        submit_feedback,
+        feedback_report,
+        list_slow_queries,
+        start_experiment,
+        stop_experiment,
+        experiment_report,
+        current_model,
+        model_versions,
+        model_version,
+        rollback_model,
+    ]
+}
@@ -0,0 +1,144 @@
+//! Replication routes served to the paired region: log segments, status,
+//! heartbeats and role changes.
+
+use crate::nerv::region::LogSegment;
+use warp::filters::BoxedFilter;
+use warp::{Filter, Reply};
+
+use super::{
+    error_reply, node_reply, not_configured, open_node_request, NodeRequest, Server,
+    SEGMENT_BODY_LIMIT,
+};
+
+/// Replication routes under `api_path`, in the order they are tried
+pub(super) fn routes(
+    server: &Server,
+    api_path: &str,
+) -> Vec<BoxedFilter<(warp::reply::Response,)>> {
+    let api_path = api_path.to_string();
+
+    let replicator_for_segments = server.region_replicator.clone();
+    let channels_for_segments = server.secure_channels.clone();
+    let replication_segments = warp::path(api_path.clone())
+        .and(warp::path("replication"))
+        .and(warp::path("segments"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::content_length_limit(SEGMENT_BODY_LIMIT))
+        .and(warp::body::bytes())
+        .and_then(move |body: bytes::Bytes| {
+            let replicator_opt = replicator_for_segments.clone();
+            let channels = channels_for_segments.clone();
+            async move {
+                let replicator = match replicator_opt {
+                    Some(replicator) => replicator,
+                    None => {
+                        return Ok::<_, warp::Rejection>(not_configured("Multi-region replication"))
+                    }
+                };
+                let channels = channels.as_deref();
+                let (segment, reply_to) =
+                    match open_node_request::<LogSegment>(channels, &body).await {
+                        Ok(NodeRequest::Message(segment, reply_to)) => (segment, reply_to),
+                        Ok(NodeRequest::Answered(reply)) | Err(reply) => return Ok(reply),
+                    };
+                match replicator.apply_segment(segment).await {
+                    Ok(ack) => match serde_json::to_vec(&ack) {
+                        Ok(ack) => {
+                            Ok(
+                                node_reply(channels, reply_to.as_ref(), ack, "application/json")
+                                    .await,
+                            )
+                        }
+                        Err(e) => Ok(error_reply(
+                            e.to_string(),
+                            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                        )),
+                    },
+                    Err(e) => Ok(error_reply(e.to_string(), warp::http::StatusCode::CONFLICT)),
+                }
+            }
+        })
+        .boxed();
+
+    let replicator_for_status = server.region_replicator.clone();
+    let replication_status = warp::path(api_path.clone())
+        .and(warp::path("replication"))
+        .and(warp::path("status"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let replicator_opt = replicator_for_status.clone();
+            async move {
+                match replicator_opt {
+                    Some(replicator) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&replicator.status().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Multi-region replication")),
+                }
+            }
+        })
+        .boxed();
+
+    let replicator_for_heartbeat = server.region_replicator.clone();
+    let replication_heartbeat = warp::path(api_path.clone())
+        .and(warp::path("replication"))
+        .and(warp::path("heartbeat"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let replicator_opt = replicator_for_heartbeat.clone();
+            async move {
+                match replicator_opt {
+                    Some(replicator) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&replicator.heartbeat().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Multi-region replication")),
+                }
+            }
+        })
+        .boxed();
+
+    let replicator_for_role = server.region_replicator.clone();
+    let replication_role = warp::path(api_path.clone())
+        .and(warp::path("replication"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::post())
+        .and_then(move |action: String| {
+            let replicator_opt = replicator_for_role.clone();
+            async move {
+                let replicator = match replicator_opt {
+                    Some(replicator) => replicator,
+                    None => {
+                        return Ok::<_, warp::Rejection>(not_configured("Multi-region replication"))
+                    }
+                };
+                let result = match action.as_str() {
+                    "promote" => replicator.promote().await,
+                    "demote" => replicator.demote().await,
+                    _ => {
+                        return Ok(error_reply(
+                            format!("Unknown replication action: {}", action),
+                            warp::http::StatusCode::NOT_FOUND,
+                        ))
+                    }
+                };
+                match result {
+                    Ok(()) => Ok(warp::reply::json(&replicator.status().await).into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    vec![
+        replication_segments,
+        replication_status,
+        replication_heartbeat,
+        replication_role,
+    ]
+}
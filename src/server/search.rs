@@ -0,0 +1,634 @@
+//! Search routes: vector and text search, search pipelines, outliers,
+//! aggregate views, synonym dictionaries and scorer plugins.
+
+use crate::network::priority::Priority;
+use crate::query::experiments::{Assignment, CLIENT_KEY_HEADER};
+use crate::query::feedback::ShownResult;
+use crate::query::pipeline::PipelineRequest;
+use crate::query::slow_log::SlowQuery;
+use crate::query::synonyms::{SynonymDictionary, DEFAULT_MAX_VARIANTS};
+use crate::server::api::{
+    convert_search_results, create_vector, ErrorResponse, OutlierRequest, PipelineSearchResponse,
+    SearchVectorsRequest, SearchVectorsResponse, TextSearchRequest, TextSearchResponse,
+};
+use crate::sharding::aggregates::AggregateViewDefinition;
+use crate::sharding::manager::ShardManager;
+use crate::utils::errors::ScoringError;
+use std::sync::Arc;
+use std::time::Instant;
+use warp::filters::BoxedFilter;
+use warp::{Filter, Reply};
+
+use super::{
+    error_reply, json_body, not_configured, request_priority, Server, SCORER_BODY_LIMIT,
+    SEARCH_BODY_LIMIT,
+};
+
+/// Search routes under `api_path`, in the order they are tried
+pub(super) fn routes(
+    server: &Server,
+    api_path: &str,
+    shard_manager: &Option<Arc<ShardManager>>,
+) -> Vec<BoxedFilter<(warp::reply::Response,)>> {
+    let api_path = api_path.to_string();
+
+    let manager_for_estimate = shard_manager.clone();
+    let estimate_search = warp::path(api_path.clone())
+        .and(warp::path("search"))
+        .and(warp::path("estimate"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::content_length_limit(SEARCH_BODY_LIMIT))
+        .and(warp::body::json::<SearchVectorsRequest>())
+        .and_then(move |req: SearchVectorsRequest| {
+            let manager_opt = manager_for_estimate.clone();
+            async move {
+                let Some(manager) = manager_opt else {
+                    return Ok::<_, warp::Rejection>(not_configured("Shard manager"));
+                };
+                if req.limit == 0 {
+                    return Ok(error_reply(
+                        "limit must be greater than zero".into(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    ));
+                }
+                let filter = req.filter_expr();
+                let queries: Vec<_> = std::iter::once(req.query_vector)
+                    .chain(req.additional_queries)
+                    .map(create_vector)
+                    .collect();
+                let timeout = req.timeout_ms.map(std::time::Duration::from_millis);
+                // Only the planner and index statistics run; nothing is scored
+                match manager
+                    .estimate_search(
+                        req.shard_id,
+                        &queries,
+                        req.limit,
+                        filter.as_ref(),
+                        &req.diversify,
+                        req.facets.as_ref(),
+                        timeout,
+                    )
+                    .await
+                {
+                    Ok(estimate) => Ok(warp::reply::json(&estimate).into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let manager_for_pipeline = shard_manager.clone();
+    let scheduling_for_pipeline = server.scheduling();
+    let scorers_for_pipeline = server.scorers.clone();
+    let search_pipeline = warp::path(api_path.clone())
+        .and(warp::path("search"))
+        .and(warp::path("pipeline"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(request_priority(Priority::Interactive))
+        .and(warp::body::content_length_limit(SEARCH_BODY_LIMIT))
+        .and(warp::body::json::<PipelineRequest>())
+        .and_then(move |priority: Priority, req: PipelineRequest| {
+            let manager_opt = manager_for_pipeline.clone();
+            let scheduling = scheduling_for_pipeline.clone();
+            let scorers_opt = scorers_for_pipeline.clone();
+            async move {
+                let Some(manager) = manager_opt else {
+                    return Ok::<_, warp::Rejection>(not_configured("Shard manager"));
+                };
+                let _admitted = match scheduling.admit(priority).await {
+                    Ok(admitted) => admitted,
+                    Err(reply) => return Ok(reply),
+                };
+                let started = Instant::now();
+                match req.run(&manager, scorers_opt.as_deref()).await {
+                    Ok(outcome) => Ok(warp::reply::json(&PipelineSearchResponse {
+                        results: convert_search_results(outcome.results),
+                        facets: outcome.facets,
+                        partial: outcome.partial,
+                        coverage: outcome.coverage,
+                        stages: outcome.stages,
+                        total_ms: started.elapsed().as_secs_f64() * 1000.0,
+                    })
+                    .into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let manager_for_search = shard_manager.clone();
+    let scheduling_for_search = server.scheduling();
+    let log_for_search = server.search_log.clone();
+    let ranking_for_search = server.ranking.clone();
+    let slow_for_search = server.slow_queries.clone();
+    let experiments_for_search = server.experiments.clone();
+    let scorers_for_search = server.scorers.clone();
+    let search_vectors = warp::path(api_path.clone())
+        .and(warp::path("search"))
+        .and(warp::post())
+        .and(request_priority(Priority::Interactive))
+        .and(warp::header::optional::<String>(CLIENT_KEY_HEADER))
+        .and(warp::body::content_length_limit(SEARCH_BODY_LIMIT))
+        .and(warp::body::json::<SearchVectorsRequest>())
+        .and_then(
+            move |priority: Priority, client_key: Option<String>, mut req: SearchVectorsRequest| {
+                let manager_opt = manager_for_search.clone();
+                let scheduling = scheduling_for_search.clone();
+                let log_opt = log_for_search.clone();
+                let ranking_opt = ranking_for_search.clone();
+                let slow_opt = slow_for_search.clone();
+                let experiments_opt = experiments_for_search.clone();
+                let scorers_opt = scorers_for_search.clone();
+                async move {
+                    let started = Instant::now();
+                    let _admitted = match scheduling.admit(priority).await {
+                        Ok(admitted) => admitted,
+                        Err(reply) => return Ok::<_, warp::Rejection>(reply),
+                    };
+                    // Clients in a running experiment search with their arm's settings
+                    let mut experiment = None;
+                    if let (Some(experiments), Some(key)) = (&experiments_opt, &client_key) {
+                        if let Some(definition) = experiments.running().await {
+                            let arm = definition.assign(key);
+                            let config = definition.config(arm);
+                            req.shard_id = config.shard_id.unwrap_or(req.shard_id);
+                            req.rerank = config.rerank.unwrap_or(req.rerank);
+                            req.fusion = config.fusion.unwrap_or(req.fusion);
+                            experiment = Some(Assignment {
+                                experiment: definition.name,
+                                arm,
+                            });
+                        }
+                    }
+                    if let Some(manager) = manager_opt {
+                        if req.limit == 0 {
+                            return Ok::<_, warp::Rejection>(
+                                warp::reply::with_status(
+                                    warp::reply::json(&ErrorResponse {
+                                        error: "limit must be greater than zero".into(),
+                                    }),
+                                    warp::http::StatusCode::BAD_REQUEST,
+                                )
+                                .into_response(),
+                            );
+                        }
+                        let filter = req.filter_expr();
+                        let queries: Vec<_> = std::iter::once(req.query_vector)
+                            .chain(req.additional_queries)
+                            .map(create_vector)
+                            .collect();
+                        if let Ok((index, _)) = manager.searchable_index(req.shard_id).await {
+                            let stats = index.stats().await;
+                            if let Some(query) =
+                                queries.iter().find(|q| q.dimensions != stats.dimensions)
+                            {
+                                return Ok::<_, warp::Rejection>(
+                                    warp::reply::with_status(
+                                        warp::reply::json(&ErrorResponse {
+                                            error: format!(
+                                        "Query vector dimensions mismatch: expected {}, got {}",
+                                        stats.dimensions, query.dimensions
+                                    ),
+                                        }),
+                                        warp::http::StatusCode::BAD_REQUEST,
+                                    )
+                                    .into_response(),
+                                );
+                            }
+                        } else {
+                            return Ok::<_, warp::Rejection>(
+                                warp::reply::with_status(
+                                    warp::reply::json(&ErrorResponse {
+                                        error: "Vector index not found".into(),
+                                    }),
+                                    warp::http::StatusCode::BAD_REQUEST,
+                                )
+                                .into_response(),
+                            );
+                        }
+                        // A scorer picks from a wider pool of candidates than it returns
+                        let mut limit = req.limit;
+                        if let Some(name) = &req.scorer {
+                            let Some(scorers) = &scorers_opt else {
+                                return Ok(not_configured("Scoring plugins"));
+                            };
+                            if !scorers.contains(name).await {
+                                return Ok(error_reply(
+                                    format!("Unknown scorer: {}", name),
+                                    warp::http::StatusCode::BAD_REQUEST,
+                                ));
+                            }
+                            limit = req.limit.saturating_mul(scorers.limits().oversample.max(1));
+                        }
+                        let timeout = req.timeout_ms.map(std::time::Duration::from_millis);
+                        let outcome = manager
+                            .search_vectors_fused(
+                                req.shard_id,
+                                &queries,
+                                limit,
+                                filter.as_ref(),
+                                &req.diversify,
+                                req.facets.as_ref(),
+                                timeout,
+                                req.fusion,
+                            )
+                            .await;
+                        match outcome {
+                            Ok(mut outcome) => {
+                                if let (Some(name), Some(scorers)) = (&req.scorer, &scorers_opt) {
+                                    match scorers.score(name, &queries[0], outcome.results).await {
+                                        Ok(mut scored) => {
+                                            scored.truncate(req.limit);
+                                            outcome.results = scored;
+                                        }
+                                        Err(e) => {
+                                            return Ok(error_reply(
+                                                e.to_string(),
+                                                warp::http::StatusCode::BAD_REQUEST,
+                                            ))
+                                        }
+                                    }
+                                }
+                                let partial = outcome.partial;
+                                let coverage = outcome.coverage;
+                                let facets = outcome.facets;
+                                let mut results = convert_search_results(outcome.results);
+                                // Impressions keep the original order, which the ranking features are computed from
+                                let query_id = match &log_opt {
+                                    Some(log) => Some(
+                                        log.record_impression(
+                                            req.shard_id,
+                                            results.iter().map(ShownResult::from),
+                                        )
+                                        .await,
+                                    ),
+                                    None => None,
+                                };
+                                if let (Some(experiments), Some(assignment), Some(query_id)) =
+                                    (&experiments_opt, &experiment, query_id)
+                                {
+                                    experiments
+                                        .record(&assignment.experiment, query_id, assignment.arm)
+                                        .await;
+                                }
+                                if let (true, Some(ranking)) = (req.rerank, &ranking_opt) {
+                                    results = ranking
+                                        .rerank(results, |result| ShownResult::from(result))
+                                        .await;
+                                }
+                                let latency = started.elapsed();
+                                if let Some(slow_queries) = &slow_opt {
+                                    slow_queries
+                                        .record(SlowQuery {
+                                            query_id,
+                                            shard_id: req.shard_id,
+                                            limit: req.limit,
+                                            results: results.len(),
+                                            partial,
+                                            latency_ms: latency.as_millis() as u64,
+                                            recorded_at: chrono::Utc::now(),
+                                        })
+                                        .await;
+                                }
+                                match &scheduling.admission {
+                                    Some(admission) if priority == Priority::Interactive => {
+                                        admission.record_latency(latency).await
+                                    }
+                                    _ => {}
+                                }
+                                Ok::<_, warp::Rejection>(
+                                    warp::reply::json(&SearchVectorsResponse {
+                                        results,
+                                        facets,
+                                        partial,
+                                        coverage,
+                                        query_id,
+                                        experiment,
+                                    })
+                                    .into_response(),
+                                )
+                            }
+                            Err(e) => Ok(warp::reply::with_status(
+                                warp::reply::json(&ErrorResponse {
+                                    error: e.to_string(),
+                                }),
+                                warp::http::StatusCode::BAD_REQUEST,
+                            )
+                            .into_response()),
+                        }
+                    } else {
+                        Ok::<_, warp::Rejection>(not_configured("Shard manager"))
+                    }
+                }
+            },
+        )
+        .boxed();
+
+    let manager_for_outliers = shard_manager.clone();
+    let scheduling_for_outliers = server.scheduling();
+    let find_outliers = warp::path(api_path.clone())
+        .and(warp::path("analyze"))
+        .and(warp::path("outliers"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(request_priority(Priority::Batch))
+        .and(json_body::<OutlierRequest>())
+        .and_then(move |priority: Priority, req: OutlierRequest| {
+            let manager_opt = manager_for_outliers.clone();
+            let scheduling = scheduling_for_outliers.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                let _admitted = match scheduling.admit(priority).await {
+                    Ok(admitted) => admitted,
+                    Err(reply) => return Ok(reply),
+                };
+                match manager.find_outliers(req.shard_id, req.params).await {
+                    Ok(report) => Ok(warp::reply::json(&report).into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let manager_for_create_view = shard_manager.clone();
+    let create_aggregate_view = warp::path(api_path.clone())
+        .and(warp::path("collections"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("aggregates"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body::<AggregateViewDefinition>())
+        .and_then(
+            move |collection: String, definition: AggregateViewDefinition| {
+                let manager_opt = manager_for_create_view.clone();
+                async move {
+                    let manager = match manager_opt {
+                        Some(manager) => manager,
+                        None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                    };
+                    let shard = match manager.get_shard_by_name(&collection).await {
+                        Ok(shard) => shard,
+                        Err(e) => {
+                            return Ok(error_reply(
+                                e.to_string(),
+                                warp::http::StatusCode::NOT_FOUND,
+                            ))
+                        }
+                    };
+                    match manager.create_aggregate_view(shard.id, definition).await {
+                        Ok(snapshot) => Ok(warp::reply::json(&snapshot).into_response()),
+                        Err(e) => Ok(error_reply(
+                            e.to_string(),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        )),
+                    }
+                }
+            },
+        )
+        .boxed();
+
+    let manager_for_get_view = shard_manager.clone();
+    let get_aggregate_view = warp::path(api_path.clone())
+        .and(warp::path("collections"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("aggregates"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move |collection: String, view: String| {
+            let manager_opt = manager_for_get_view.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                let result = match manager.get_shard_by_name(&collection).await {
+                    Ok(shard) => manager.get_aggregate_view(shard.id, &view).await,
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(snapshot) => Ok(warp::reply::json(&snapshot).into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let synonyms_for_put = server.synonyms.clone();
+    let put_synonyms = warp::path(api_path.clone())
+        .and(warp::path("collections"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("synonyms"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(json_body::<SynonymDictionary>())
+        .and_then(move |collection: String, dictionary: SynonymDictionary| {
+            let synonyms_opt = synonyms_for_put.clone();
+            async move {
+                let synonyms = match synonyms_opt {
+                    Some(synonyms) => synonyms,
+                    None => {
+                        return Ok::<_, warp::Rejection>(not_configured("Synonym dictionaries"))
+                    }
+                };
+                match synonyms.set(&collection, dictionary.clone()).await {
+                    Ok(()) => Ok(warp::reply::json(&dictionary).into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let synonyms_for_get = server.synonyms.clone();
+    let get_synonyms = warp::path(api_path.clone())
+        .and(warp::path("collections"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("synonyms"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move |collection: String| {
+            let synonyms_opt = synonyms_for_get.clone();
+            async move {
+                let synonyms = match synonyms_opt {
+                    Some(synonyms) => synonyms,
+                    None => {
+                        return Ok::<_, warp::Rejection>(not_configured("Synonym dictionaries"))
+                    }
+                };
+                match synonyms.get(&collection).await {
+                    Some(dictionary) => Ok(warp::reply::json(&dictionary).into_response()),
+                    None => Ok(error_reply(
+                        format!("Collection {} has no synonym dictionary", collection),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let scorers_for_put = server.scorers.clone();
+    let put_scorer = warp::path(api_path.clone())
+        .and(warp::path("scorers"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(warp::body::content_length_limit(SCORER_BODY_LIMIT))
+        .and(warp::body::bytes())
+        .and_then(move |name: String, module: bytes::Bytes| {
+            let scorers_opt = scorers_for_put.clone();
+            async move {
+                let scorers = match scorers_opt {
+                    Some(scorers) => scorers,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Scoring plugins")),
+                };
+                match scorers.register(&name, &module).await {
+                    Ok(info) => Ok(warp::reply::json(&info).into_response()),
+                    Err(e @ ScoringError::Unsupported) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_IMPLEMENTED,
+                    )),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let scorers_for_list = server.scorers.clone();
+    let list_scorers = warp::path(api_path.clone())
+        .and(warp::path("scorers"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let scorers_opt = scorers_for_list.clone();
+            async move {
+                match scorers_opt {
+                    Some(scorers) => Ok::<_, warp::Rejection>(
+                        warp::reply::json(&scorers.list().await).into_response(),
+                    ),
+                    None => Ok(not_configured("Scoring plugins")),
+                }
+            }
+        })
+        .boxed();
+
+    let scorers_for_delete = server.scorers.clone();
+    let delete_scorer = warp::path(api_path.clone())
+        .and(warp::path("scorers"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and_then(move |name: String| {
+            let scorers_opt = scorers_for_delete.clone();
+            async move {
+                let scorers = match scorers_opt {
+                    Some(scorers) => scorers,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Scoring plugins")),
+                };
+                match scorers.unregister(&name).await {
+                    Some(info) => Ok(warp::reply::json(&info).into_response()),
+                    None => Ok(error_reply(
+                        format!("Unknown scorer: {}", name),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let embeddings_for_text = server.embeddings.clone();
+    let synonyms_for_text = server.synonyms.clone();
+    let scheduling_for_text = server.scheduling();
+    let search_text = warp::path(api_path.clone())
+        .and(warp::path("collections"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("search"))
+        .and(warp::path("text"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(request_priority(Priority::Interactive))
+        .and(json_body::<TextSearchRequest>())
+        .and_then(
+            move |collection: String, priority: Priority, req: TextSearchRequest| {
+                let embeddings_opt = embeddings_for_text.clone();
+                let synonyms_opt = synonyms_for_text.clone();
+                let scheduling = scheduling_for_text.clone();
+                async move {
+                    let _admitted = match scheduling.admit(priority).await {
+                        Ok(admitted) => admitted,
+                        Err(reply) => return Ok::<_, warp::Rejection>(reply),
+                    };
+                    let embeddings = match embeddings_opt {
+                        Some(embeddings) => embeddings,
+                        None => return Ok(not_configured("Embedding registry")),
+                    };
+                    if req.limit == 0 {
+                        return Ok(error_reply(
+                            "limit must be greater than zero".into(),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        ));
+                    }
+                    let queries = match (&synonyms_opt, req.exact) {
+                        (Some(synonyms), false) => {
+                            synonyms
+                                .expand(&collection, &req.query, DEFAULT_MAX_VARIANTS)
+                                .await
+                        }
+                        _ => vec![req.query.clone()],
+                    };
+                    match embeddings
+                        .search_texts(&collection, &queries, req.limit, req.expansion, req.fusion)
+                        .await
+                    {
+                        Ok(results) => Ok(warp::reply::json(&TextSearchResponse {
+                            results: convert_search_results(results),
+                            queries,
+                        })
+                        .into_response()),
+                        Err(e) => Ok(error_reply(
+                            e.to_string(),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        )),
+                    }
+                }
+            },
+        )
+        .boxed();
+
+    vec![
+        find_outliers,
+        estimate_search,
+        search_pipeline,
+        search_vectors,
+        create_aggregate_view,
+        get_aggregate_view,
+        put_synonyms,
+        get_synonyms,
+        put_scorer,
+        list_scorers,
+        delete_scorer,
+        search_text,
+    ]
+}
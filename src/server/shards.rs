@@ -0,0 +1,372 @@
+//! Shard routes: creating shards and indexes, listing and deleting them,
+//! index stats and the change feed.
+
+use crate::connectors::read_embeddings;
+use crate::network::priority::Priority;
+use crate::server::api::{
+    distance_metric_to_string, parse_distance_metric, BuildIndexRequest, ChangesQuery,
+    CreateIndexRequest, CreateIndexResponse, CreateShardRequest, CreateShardResponse,
+    DeleteShardResponse, ErrorResponse, IndexInfo, ShardInfo, ShardStatsResponse,
+};
+use crate::sharding::manager::ShardManager;
+use crate::utils::errors::ChangeFeedError;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::filters::BoxedFilter;
+use warp::{Filter, Reply};
+
+use super::{
+    error_reply, json_body, not_configured, request_priority, Server, ServerConfig,
+    MAX_CHANGES_PER_REQUEST, MAX_CHANGES_WAIT_MS,
+};
+
+/// Shard routes under `api_path`, in the order they are tried
+pub(super) fn routes(
+    server: &Server,
+    api_path: &str,
+    shard_manager: &Option<Arc<ShardManager>>,
+    config: &ServerConfig,
+) -> Vec<BoxedFilter<(warp::reply::Response,)>> {
+    let api_path = api_path.to_string();
+
+    let manager_for_create = shard_manager.clone();
+    let create_shard = warp::path(api_path.clone())
+        .and(warp::path("shards"))
+        .and(warp::post())
+        .and(json_body::<CreateShardRequest>())
+        .and_then(move |req: CreateShardRequest| {
+            let manager_opt = manager_for_create.clone();
+            async move {
+                if let Some(manager) = manager_opt {
+                    match manager.create_shard(&req.name).await {
+                        Ok(id) => Ok::<_, warp::Rejection>(
+                            warp::reply::json(&CreateShardResponse { shard_id: id })
+                                .into_response(),
+                        ),
+                        Err(e) => Ok(warp::reply::with_status(
+                            warp::reply::json(&ErrorResponse {
+                                error: e.to_string(),
+                            }),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        )
+                        .into_response()),
+                    }
+                } else {
+                    Ok::<_, warp::Rejection>(not_configured("Shard manager"))
+                }
+            }
+        })
+        .boxed();
+
+    let manager_for_build = shard_manager.clone();
+    let scheduling_for_build = server.scheduling();
+    let import_for_build = config.import.clone();
+    let build_index = warp::path(api_path.clone())
+        .and(warp::path("indexes"))
+        .and(warp::path("build"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(request_priority(Priority::Batch))
+        .and(json_body::<BuildIndexRequest>())
+        .and_then(move |priority: Priority, request: BuildIndexRequest| {
+            let manager_opt = manager_for_build.clone();
+            let scheduling = scheduling_for_build.clone();
+            let import = import_for_build.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                let _admitted = match scheduling.admit(priority).await {
+                    Ok(admitted) => admitted,
+                    Err(reply) => return Ok(reply),
+                };
+                let metric = match parse_distance_metric(&request.distance_metric) {
+                    Ok(metric) => metric,
+                    Err(e) => return Ok(error_reply(e, warp::http::StatusCode::BAD_REQUEST)),
+                };
+                let path = match import.check_path(&request.path) {
+                    Ok(path) => path,
+                    Err(e) => {
+                        return Ok(error_reply(
+                            e.to_string(),
+                            warp::http::StatusCode::FORBIDDEN,
+                        ))
+                    }
+                };
+                let (format, tensor) = (request.format, request.tensor);
+                let embeddings = tokio::task::spawn_blocking(move || {
+                    read_embeddings(&path, format, tensor.as_deref())
+                })
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|read| read);
+                let embeddings = match embeddings {
+                    Ok(embeddings) => embeddings,
+                    Err(e) => {
+                        return Ok(error_reply(
+                            e.to_string(),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        ))
+                    }
+                };
+                match manager
+                    .build_index(
+                        &request.shard_name,
+                        &request.index_name,
+                        metric,
+                        embeddings,
+                        request.threads,
+                    )
+                    .await
+                {
+                    Ok(build) => Ok(warp::reply::json(&build).into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let manager_for_index = shard_manager.clone();
+    let create_index = warp::path(api_path.clone())
+        .and(warp::path("indexes"))
+        .and(warp::post())
+        .and(json_body::<CreateIndexRequest>())
+        .and_then(move |req: CreateIndexRequest| {
+            let manager_opt = manager_for_index.clone();
+            async move {
+                if let Some(manager) = manager_opt {
+                    match parse_distance_metric(&req.distance_metric) {
+                        Ok(metric) => match manager
+                            .create_vector_index_with_type(
+                                req.shard_id,
+                                &req.name,
+                                req.dimensions,
+                                metric,
+                                req.index_type,
+                            )
+                            .await
+                        {
+                            Ok(_) => Ok::<_, warp::Rejection>(
+                                warp::reply::json(&CreateIndexResponse {
+                                    shard_id: req.shard_id,
+                                    index_name: req.name,
+                                    dimensions: req.dimensions,
+                                    distance_metric: req.distance_metric.to_lowercase(),
+                                    index_type: req.index_type,
+                                })
+                                .into_response(),
+                            ),
+                            Err(e) => Ok(warp::reply::with_status(
+                                warp::reply::json(&ErrorResponse {
+                                    error: e.to_string(),
+                                }),
+                                warp::http::StatusCode::BAD_REQUEST,
+                            )
+                            .into_response()),
+                        },
+                        Err(e) => Ok(warp::reply::with_status(
+                            warp::reply::json(&ErrorResponse { error: e }),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        )
+                        .into_response()),
+                    }
+                } else {
+                    Ok::<_, warp::Rejection>(not_configured("Shard manager"))
+                }
+            }
+        })
+        .boxed();
+
+    let manager_for_changes = shard_manager.clone();
+    let shard_changes = warp::path(api_path.clone())
+        .and(warp::path("shards"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("changes"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<ChangesQuery>())
+        .and_then(move |shard_id: Uuid, query: ChangesQuery| {
+            let manager_opt = manager_for_changes.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                let feed = match manager.change_feed(shard_id).await {
+                    Ok(feed) => feed,
+                    Err(e) => {
+                        return Ok(error_reply(
+                            e.to_string(),
+                            warp::http::StatusCode::NOT_FOUND,
+                        ))
+                    }
+                };
+                let limit = query.limit.unwrap_or(100).clamp(1, MAX_CHANGES_PER_REQUEST);
+                let from = match query.from {
+                    Some(from) => from,
+                    None => feed.earliest_offset().await,
+                };
+                let result = match query.wait_ms {
+                    Some(wait_ms) if wait_ms > 0 => {
+                        let wait =
+                            std::time::Duration::from_millis(wait_ms.min(MAX_CHANGES_WAIT_MS));
+                        feed.wait(from, limit, wait).await
+                    }
+                    _ => feed.read(from, limit).await,
+                };
+                match result {
+                    Ok(batch) => Ok(warp::reply::json(&batch).into_response()),
+                    Err(e @ ChangeFeedError::OffsetExpired { .. }) => {
+                        Ok(error_reply(e.to_string(), warp::http::StatusCode::GONE))
+                    }
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let manager_for_index_stats = shard_manager.clone();
+    let health_for_index_stats = server.index_health.clone();
+    let shard_index_stats = warp::path(api_path.clone())
+        .and(warp::path("shards"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("stats"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move |shard_id: Uuid| {
+            let manager_opt = manager_for_index_stats.clone();
+            let health_opt = health_for_index_stats.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                match manager.get_vector_index(shard_id).await {
+                    Ok(index) => {
+                        let health = match health_opt {
+                            Some(monitor) => monitor.health(shard_id).await,
+                            None => None,
+                        };
+                        let stats = ShardStatsResponse {
+                            statistics: index.statistics().await,
+                            health,
+                        };
+                        Ok(warp::reply::json(&stats).into_response())
+                    }
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let manager_for_list_shards = shard_manager.clone();
+    let list_shards = warp::path(api_path.clone())
+        .and(warp::path("shards"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || {
+            let manager_opt = manager_for_list_shards.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                let mut shards: Vec<ShardInfo> = manager
+                    .get_shards()
+                    .await
+                    .into_iter()
+                    .map(ShardInfo::from)
+                    .collect();
+                shards.sort_by_key(|shard| shard.created_at);
+                Ok(warp::reply::json(&shards).into_response())
+            }
+        })
+        .boxed();
+
+    let manager_for_list_indexes = shard_manager.clone();
+    let list_indexes = warp::path(api_path.clone())
+        .and(warp::path("shards"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("indexes"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move |shard_id: Uuid| {
+            let manager_opt = manager_for_list_indexes.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                if let Err(e) = manager.get_shard(shard_id).await {
+                    return Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    ));
+                }
+                // A shard holds at most one index; none until one is created
+                let mut indexes = Vec::new();
+                if let Ok(index) = manager.get_vector_index(shard_id).await {
+                    indexes.push(IndexInfo {
+                        shard_id,
+                        index_name: index.name().to_string(),
+                        dimensions: index.dimensions(),
+                        distance_metric: distance_metric_to_string(index.distance_metric()),
+                        index_type: index.index_type(),
+                        vector_count: index.count().await,
+                    });
+                }
+                Ok(warp::reply::json(&indexes).into_response())
+            }
+        })
+        .boxed();
+
+    let manager_for_delete_shard = shard_manager.clone();
+    let delete_shard = warp::path(api_path.clone())
+        .and(warp::path("shards"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and_then(move |shard_id: Uuid| {
+            let manager_opt = manager_for_delete_shard.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                if let Err(e) = manager.get_shard(shard_id).await {
+                    return Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    ));
+                }
+                match manager.delete_shard(shard_id).await {
+                    Ok(deleted) => {
+                        Ok(warp::reply::json(&DeleteShardResponse { deleted }).into_response())
+                    }
+                    Err(e) => Ok(error_reply(e.to_string(), warp::http::StatusCode::CONFLICT)),
+                }
+            }
+        })
+        .boxed();
+
+    vec![
+        create_shard,
+        build_index,
+        create_index,
+        shard_changes,
+        shard_index_stats,
+        list_shards,
+        list_indexes,
+        delete_shard,
+    ]
+}
@@ -0,0 +1,261 @@
+//! Vector routes: single and batch inserts, composition, lookups and
+//! deletes.
+
+use crate::network::priority::Priority;
+use crate::server::api::{
+    convert_search_results, create_vector, AddVectorRequest, AddVectorResponse,
+    AddVectorsBatchRequest, AddVectorsBatchResponse, ComposeVectorsRequest, ComposeVectorsResponse,
+    DeleteVectorResponse, ErrorResponse, VectorQuery, VectorResponse,
+};
+use crate::sharding::manager::ShardManager;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::filters::BoxedFilter;
+use warp::{Filter, Reply};
+
+use super::{
+    error_reply, json_body, not_configured, request_priority, Server, BATCH_VECTORS_BODY_LIMIT,
+    COMPOSE_BODY_LIMIT, MAX_BATCH_VECTORS,
+};
+
+/// Vector routes under `api_path`, in the order they are tried
+pub(super) fn routes(
+    server: &Server,
+    api_path: &str,
+    shard_manager: &Option<Arc<ShardManager>>,
+) -> Vec<BoxedFilter<(warp::reply::Response,)>> {
+    let api_path = api_path.to_string();
+
+    let manager_for_add = shard_manager.clone();
+    let add_vector = warp::path(api_path.clone())
+        .and(warp::path("vectors"))
+        .and(warp::post())
+        .and(json_body::<AddVectorRequest>())
+        .and_then(move |req: AddVectorRequest| {
+            let manager_opt = manager_for_add.clone();
+            async move {
+                if let Some(manager) = manager_opt {
+                    let vector = create_vector(req.vector);
+                    match manager.add_vector(req.shard_id, vector, req.metadata).await {
+                        Ok(id) => Ok::<_, warp::Rejection>(
+                            warp::reply::json(&AddVectorResponse { vector_id: id }).into_response(),
+                        ),
+                        Err(e) => Ok(warp::reply::with_status(
+                            warp::reply::json(&ErrorResponse {
+                                error: e.to_string(),
+                            }),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        )
+                        .into_response()),
+                    }
+                } else {
+                    Ok::<_, warp::Rejection>(not_configured("Shard manager"))
+                }
+            }
+        })
+        .boxed();
+
+    let manager_for_batch = shard_manager.clone();
+    let scheduling_for_batch = server.scheduling();
+    let add_vectors_batch = warp::path(api_path.clone())
+        .and(warp::path("vectors"))
+        .and(warp::path("batch"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(request_priority(Priority::Batch))
+        .and(warp::body::content_length_limit(BATCH_VECTORS_BODY_LIMIT))
+        .and(warp::body::json::<AddVectorsBatchRequest>())
+        .and_then(move |priority: Priority, req: AddVectorsBatchRequest| {
+            let manager_opt = manager_for_batch.clone();
+            let scheduling = scheduling_for_batch.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                if req.vectors.len() > MAX_BATCH_VECTORS {
+                    return Ok(error_reply(
+                        format!(
+                            "A batch holds at most {} vectors, got {}",
+                            MAX_BATCH_VECTORS,
+                            req.vectors.len()
+                        ),
+                        warp::http::StatusCode::PAYLOAD_TOO_LARGE,
+                    ));
+                }
+                let _admitted = match scheduling.admit(priority).await {
+                    Ok(admitted) => admitted,
+                    Err(reply) => return Ok(reply),
+                };
+                let vectors = req
+                    .vectors
+                    .into_iter()
+                    .map(|v| (create_vector(v.vector), v.metadata))
+                    .collect();
+                match manager.add_vectors_batch(req.shard_id, vectors).await {
+                    Ok(vector_ids) => {
+                        Ok(warp::reply::json(&AddVectorsBatchResponse { vector_ids })
+                            .into_response())
+                    }
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let manager_for_compose = shard_manager.clone();
+    let scheduling_for_compose = server.scheduling();
+    let compose_vectors = warp::path(api_path.clone())
+        .and(warp::path("vectors"))
+        .and(warp::path("compose"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(request_priority(Priority::Interactive))
+        .and(warp::body::content_length_limit(COMPOSE_BODY_LIMIT))
+        .and(warp::body::json::<ComposeVectorsRequest>())
+        .and_then(move |priority: Priority, req: ComposeVectorsRequest| {
+            let manager_opt = manager_for_compose.clone();
+            let scheduling = scheduling_for_compose.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                let _admitted = match scheduling.admit(priority).await {
+                    Ok(admitted) => admitted,
+                    Err(reply) => return Ok(reply),
+                };
+                let vector = match manager
+                    .compose_vectors(req.shard_id, req.operation, &req.terms, req.normalize)
+                    .await
+                {
+                    Ok(vector) => vector,
+                    Err(e) => {
+                        return Ok(error_reply(
+                            e.to_string(),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        ))
+                    }
+                };
+
+                let results = match req.search {
+                    Some(search) if search.limit == 0 => {
+                        return Ok(error_reply(
+                            "limit must be greater than zero".into(),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        ))
+                    }
+                    Some(search) => {
+                        let excluded: Vec<Uuid> = if search.exclude_terms {
+                            req.terms.iter().filter_map(|term| term.id).collect()
+                        } else {
+                            Vec::new()
+                        };
+                        match manager
+                            .search_vectors_filtered(
+                                req.shard_id,
+                                &vector,
+                                search.limit + excluded.len(),
+                                search.filter.as_ref(),
+                            )
+                            .await
+                        {
+                            Ok(mut results) => {
+                                results.retain(|result| !excluded.contains(&result.id));
+                                results.truncate(search.limit);
+                                Some(convert_search_results(results))
+                            }
+                            Err(e) => {
+                                return Ok(error_reply(
+                                    e.to_string(),
+                                    warp::http::StatusCode::BAD_REQUEST,
+                                ))
+                            }
+                        }
+                    }
+                    None => None,
+                };
+
+                Ok(warp::reply::json(&ComposeVectorsResponse {
+                    vector: vector.values,
+                    results,
+                })
+                .into_response())
+            }
+        })
+        .boxed();
+
+    let manager_for_get_vector = shard_manager.clone();
+    let get_vector = warp::path(api_path.clone())
+        .and(warp::path("vectors"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<VectorQuery>())
+        .and_then(move |vector_id: Uuid, query: VectorQuery| {
+            let manager_opt = manager_for_get_vector.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                match manager.find_vector(vector_id, query.shard_id).await {
+                    Ok((shard_id, entry)) => Ok(warp::reply::json(&VectorResponse::new(
+                        shard_id, entry,
+                    ))
+                    .into_response()),
+                    Err(e) => Ok(error_reply(
+                        e.to_string(),
+                        warp::http::StatusCode::NOT_FOUND,
+                    )),
+                }
+            }
+        })
+        .boxed();
+
+    let manager_for_delete_vector = shard_manager.clone();
+    let delete_vector = warp::path(api_path.clone())
+        .and(warp::path("vectors"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(warp::query::<VectorQuery>())
+        .and_then(move |vector_id: Uuid, query: VectorQuery| {
+            let manager_opt = manager_for_delete_vector.clone();
+            async move {
+                let manager = match manager_opt {
+                    Some(manager) => manager,
+                    None => return Ok::<_, warp::Rejection>(not_configured("Shard manager")),
+                };
+                let shard_id = match manager.find_vector(vector_id, query.shard_id).await {
+                    Ok((shard_id, _)) => shard_id,
+                    Err(e) => {
+                        return Ok(error_reply(
+                            e.to_string(),
+                            warp::http::StatusCode::NOT_FOUND,
+                        ))
+                    }
+                };
+                match manager.remove_vector(shard_id, vector_id).await {
+                    Ok(()) => Ok(warp::reply::json(&DeleteVectorResponse {
+                        vector_id,
+                        shard_id,
+                    })
+                    .into_response()),
+                    Err(e) => Ok(error_reply(e.to_string(), warp::http::StatusCode::CONFLICT)),
+                }
+            }
+        })
+        .boxed();
+
+    vec![
+        compose_vectors,
+        add_vectors_batch,
+        add_vector,
+        get_vector,
+        delete_vector,
+    ]
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Aggregate computed for each group of a view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "function", rename_all = "snake_case")]
+pub enum AggregateFunction {
+    /// Number of vectors in the group
+    Count,
+
+    /// Mean of a numeric metadata field across the group
+    Avg { field: String },
+}
+
+/// Definition of a materialized aggregate view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateViewDefinition {
+    /// Name the view is queried by
+    pub name: String,
+
+    /// Metadata field whose values define the groups
+    pub group_by: String,
+
+    /// Aggregate maintained per group
+    #[serde(flatten)]
+    pub function: AggregateFunction,
+}
+
+/// Running totals for one group
+#[derive(Debug, Clone, Default)]
+struct GroupState {
+    count: u64,
+    sum: f64,
+    samples: u64,
+}
+
+/// Aggregate value for one group in a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateGroup {
+    /// Value of the group-by field
+    pub key: String,
+
+    /// Number of vectors in the group
+    pub count: u64,
+
+    /// Aggregate value; the count for `count` views, the mean for `avg`
+    /// views (absent if no vector in the group had a numeric value)
+    pub value: Option<f64>,
+}
+
+/// Point-in-time result of an aggregate view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateSnapshot {
+    pub view: String,
+    pub group_by: String,
+    #[serde(flatten)]
+    pub function: AggregateFunction,
+    pub groups: Vec<AggregateGroup>,
+}
+
+/// Incrementally maintained aggregate over a shard's metadata
+#[derive(Debug, Clone)]
+pub struct AggregateView {
+    definition: AggregateViewDefinition,
+    groups: HashMap<String, GroupState>,
+}
+
+impl AggregateView {
+    /// Create an empty view
+    pub fn new(definition: AggregateViewDefinition) -> Self {
+        Self {
+            definition,
+            groups: HashMap::new(),
+        }
+    }
+
+    /// Definition this view was created from
+    pub fn definition(&self) -> &AggregateViewDefinition {
+        &self.definition
+    }
+
+    /// Value contributed to the aggregate by a vector's metadata, if any
+    fn sample(&self, metadata: &HashMap<String, String>) -> Option<f64> {
+        match &self.definition.function {
+            AggregateFunction::Count => None,
+            AggregateFunction::Avg { field } => metadata
+                .get(field)
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| v.is_finite()),
+        }
+    }
+
+    /// Account for a vector that was added to the shard
+    pub fn apply_insert(&mut self, metadata: Option<&HashMap<String, String>>) {
+        let metadata = match metadata {
+            Some(m) => m,
+            None => return,
+        };
+        let key = match metadata.get(&self.definition.group_by) {
+            Some(key) => key.clone(),
+            None => return,
+        };
+        let sample = self.sample(metadata);
+
+        let group = self.groups.entry(key).or_default();
+        group.count += 1;
+        if let Some(value) = sample {
+            group.sum += value;
+            group.samples += 1;
+        }
+    }
+
+    /// Account for a vector that was removed from the shard
+    pub fn apply_delete(&mut self, metadata: Option<&HashMap<String, String>>) {
+        let metadata = match metadata {
+            Some(m) => m,
+            None => return,
+        };
+        let key = match metadata.get(&self.definition.group_by) {
+            Some(key) => key,
+            None => return,
+        };
+        let sample = self.sample(metadata);
+
+        let now_empty = match self.groups.get_mut(key) {
+            Some(group) => {
+                group.count = group.count.saturating_sub(1);
+                if let Some(value) = sample {
+                    group.sum -= value;
+                    group.samples = group.samples.saturating_sub(1);
+                }
+                group.count == 0
+            }
+            None => false,
+        };
+
+        if now_empty {
+            self.groups.remove(key);
+        }
+    }
+
+    /// Current aggregate values, sorted by group key
+    pub fn snapshot(&self) -> AggregateSnapshot {
+        let mut groups: Vec<AggregateGroup> = self
+            .groups
+            .iter()
+            .map(|(key, state)| {
+                let value = match self.definition.function {
+                    AggregateFunction::Count => Some(state.count as f64),
+                    AggregateFunction::Avg { .. } if state.samples > 0 => {
+                        Some(state.sum / state.samples as f64)
+                    }
+                    AggregateFunction::Avg { .. } => None,
+                };
+                AggregateGroup {
+                    key: key.clone(),
+                    count: state.count,
+                    value,
+                }
+            })
+            .collect();
+        groups.sort_by(|a, b| a.key.cmp(&b.key));
+
+        AggregateSnapshot {
+            view: self.definition.name.clone(),
+            group_by: self.definition.group_by.clone(),
+            function: self.definition.function.clone(),
+            groups,
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::core::audit::AuditLog;
+use crate::core::metrics::MetricsCollector;
+use crate::nerv::tasks;
+use crate::sharding::manager::{ShardManager, ShardStatus};
+
+/// When the auto-sharder splits a shard. Each limit is optional; a shard is
+/// split as soon as it exceeds any one of them.
+#[derive(Debug, Clone)]
+pub struct AutoSplitConfig {
+    /// Time between checks
+    pub interval: Duration,
+
+    pub max_vectors: Option<usize>,
+
+    /// Estimated from the stored values, or the reported shard load if
+    /// that is higher
+    pub max_memory_mb: Option<f32>,
+
+    /// p99 search latency over the searches since the shard last split
+    pub max_p99_ms: Option<f64>,
+
+    /// Searches needed before latency is judged
+    pub min_latency_samples: usize,
+
+    /// Shards smaller than this are never split, however slow
+    pub min_vectors: usize,
+}
+
+impl Default for AutoSplitConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(60),
+            max_vectors: Some(1_000_000),
+            max_memory_mb: Some(1024.0),
+            max_p99_ms: Some(250.0),
+            min_latency_samples: 100,
+            min_vectors: 1_000,
+        }
+    }
+}
+
+/// Limit a shard exceeded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SplitTrigger {
+    VectorCount { count: usize, limit: usize },
+    Memory { memory_mb: f32, limit_mb: f32 },
+    Latency { p99_ms: f64, limit_ms: f64 },
+}
+
+/// A shard split in two along its Hilbert key range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShardSplit {
+    pub shard_id: Uuid,
+    pub new_shard_id: Uuid,
+    /// First Hilbert key served by the new shard
+    pub split_key: u64,
+    pub moved: usize,
+    pub kept: usize,
+    /// Why the auto-sharder split it; `None` for manual splits
+    pub trigger: Option<SplitTrigger>,
+}
+
+/// Watches shard statistics and splits shards that outgrow the configured
+/// limits (see [`ShardManager::split_shard`]). Each split is recorded as an
+/// audit event.
+pub struct AutoSharder {
+    shard_manager: Arc<ShardManager>,
+    metrics: Arc<MetricsCollector>,
+    audit_log: Arc<AuditLog>,
+    config: AutoSplitConfig,
+    /// Latency before a shard's last split doesn't count against it
+    last_split: RwLock<HashMap<Uuid, DateTime<Utc>>>,
+}
+
+impl AutoSharder {
+    pub fn new(
+        shard_manager: Arc<ShardManager>,
+        metrics: Arc<MetricsCollector>,
+        audit_log: Arc<AuditLog>,
+        config: AutoSplitConfig,
+    ) -> Self {
+        Self {
+            shard_manager,
+            metrics,
+            audit_log,
+            config,
+            last_split: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The first limit a shard exceeds, if any
+    pub async fn trigger(&self, shard_id: Uuid) -> Option<SplitTrigger> {
+        let index = self.shard_manager.get_vector_index(shard_id).await.ok()?;
+        let count = index.count().await;
+        if count < self.config.min_vectors.max(2) {
+            return None;
+        }
+
+        if let Some(limit) = self.config.max_vectors {
+            if count > limit {
+                return Some(SplitTrigger::VectorCount { count, limit });
+            }
+        }
+
+        if let Some(limit_mb) = self.config.max_memory_mb {
+            let estimated = (count * index.dimensions() * std::mem::size_of::<f32>()) as f32
+                / (1024.0 * 1024.0);
+            let reported = self
+                .shard_manager
+                .get_shard_loads()
+                .await
+                .get(&shard_id)
+                .map_or(0.0, |load| load.memory_usage_mb);
+            let memory_mb = estimated.max(reported);
+            if memory_mb > limit_mb {
+                return Some(SplitTrigger::Memory {
+                    memory_mb,
+                    limit_mb,
+                });
+            }
+        }
+
+        if let Some(limit_ms) = self.config.max_p99_ms {
+            let name = format!("vector_index.{}.search_time_ms", index.name());
+            let series = self.metrics.get_timeseries(&name).await?;
+            let since = self.last_split.read().await.get(&shard_id).copied();
+            let mut latencies: Vec<f64> = series
+                .timestamps
+                .iter()
+                .zip(&series.values)
+                .filter(|(at, _)| since.is_none_or(|since| **at > since))
+                .map(|(_, ms)| *ms)
+                .collect();
+            if latencies.len() >= self.config.min_latency_samples.max(1) {
+                latencies.sort_by(|a, b| a.total_cmp(b));
+                let rank = (latencies.len() as f64 * 0.99).ceil() as usize;
+                let p99_ms = latencies[rank.saturating_sub(1)];
+                if p99_ms > limit_ms {
+                    return Some(SplitTrigger::Latency { p99_ms, limit_ms });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Check every active shard once, splitting those over a limit
+    pub async fn run_once(&self) -> Vec<ShardSplit> {
+        let mut splits = Vec::new();
+
+        for shard in self.shard_manager.get_shards().await {
+            if shard.status != ShardStatus::Active {
+                continue;
+            }
+            let Some(trigger) = self.trigger(shard.id).await else {
+                debug!("Shard {} is within its split limits", shard.id);
+                continue;
+            };
+
+            let mut split = match self.shard_manager.split_shard(shard.id).await {
+                Ok(split) => split,
+                Err(e) => {
+                    warn!("Failed to split shard {} ({:?}): {}", shard.id, trigger, e);
+                    self.metrics
+                        .increment_counter("autosplit.failures", 1)
+                        .await;
+                    continue;
+                }
+            };
+            split.trigger = Some(trigger);
+
+            let now = Utc::now();
+            {
+                let mut last_split = self.last_split.write().await;
+                last_split.insert(split.shard_id, now);
+                last_split.insert(split.new_shard_id, now);
+            }
+            self.metrics.increment_counter("autosplit.splits", 1).await;
+            self.audit_log
+                .record(
+                    "autosplit",
+                    "split",
+                    &split.shard_id.to_string(),
+                    serde_json::json!({
+                        "shard_name": shard.name,
+                        "new_shard_id": split.new_shard_id,
+                        "split_key": split.split_key,
+                        "moved": split.moved,
+                        "kept": split.kept,
+                        "trigger": split.trigger,
+                    }),
+                )
+                .await;
+            splits.push(split);
+        }
+
+        self.metrics.increment_counter("autosplit.runs", 1).await;
+        splits
+    }
+
+    /// Run checks in the background until the task is aborted
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        info!(
+            "Starting auto-sharder (interval: {:?}, max vectors: {:?}, max memory: {:?}MB, max p99: {:?}ms)",
+            self.config.interval,
+            self.config.max_vectors,
+            self.config.max_memory_mb,
+            self.config.max_p99_ms
+        );
+
+        tasks::spawn("sharding", "auto-sharder", async move {
+            loop {
+                tokio::time::sleep(self.config.interval).await;
+                self.run_once().await;
+            }
+        })
+    }
+}
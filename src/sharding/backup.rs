@@ -0,0 +1,669 @@
+//! Full and incremental backups of a [`StorageBackend`].
+//!
+//! A backup set is a directory with one numbered subdirectory per backup.
+//! A full backup copies every shard record and vector. An incremental one
+//! names the latest backup as its parent and holds only what changed since:
+//! shard records that differ, vectors added or rewritten, and the IDs of
+//! vectors and shards removed. Storage keeps no segments or write-ahead log
+//! on disk, so changes are found by comparing each vector's checksum with
+//! the fingerprints the parent recorded of the whole state it saw.
+//!
+//! Restoring walks the chain from the full backup to the one asked for and
+//! writes the result into a backend. [`BackupStore::verify`] checks a chain
+//! end to end without restoring it: every manifest and vector line against
+//! its checksum, the counts each manifest promises, and the replayed state
+//! against the fingerprints of the final backup.
+//!
+//! ```text
+//! backups/
+//!   00000001/manifest.json        full
+//!   00000001/fingerprints.jsonl   record and vector checksums per shard
+//!   00000001/vectors/<id>.jsonl   sealed vector lines
+//!   00000002/...                  incremental, parent 1
+//! ```
+//!
+//! A backup's manifest is written last, so a directory without one is an
+//! interrupted backup and is ignored.
+//!
+//! [`BackupStore::scrub`] rewrites backups so they no longer hold given
+//! vectors, for purges: their lines, fingerprints and removals go, and the
+//! counts are adjusted so every chain still verifies and restores.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::core::checksum;
+use crate::sharding::storage::{ShardRecord, StorageBackend};
+use crate::sharding::vector_index::VectorEntry;
+use crate::utils::fs::write_atomic;
+
+/// What a backup holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupKind {
+    /// Every shard and vector
+    Full,
+    /// Changes since the latest backup
+    Incremental,
+}
+
+/// What one backup wrote for a shard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShardBackup {
+    pub record: ShardRecord,
+    /// Vectors in the backup's `vectors/<id>.jsonl`: all of them in a full
+    /// backup, those added or changed in an incremental one
+    pub upserted: usize,
+    /// Vectors deleted since the parent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<Uuid>,
+    /// Vectors in the shard once this backup is applied
+    pub vector_count: usize,
+}
+
+/// Description of one backup, stored as its `manifest.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub id: u64,
+    pub kind: BackupKind,
+    /// Backup this one applies on top of; `None` for full backups
+    pub parent: Option<u64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Shards that are new or changed since the parent
+    pub shards: Vec<ShardBackup>,
+    /// Shards deleted since the parent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_shards: Vec<Uuid>,
+}
+
+impl BackupManifest {
+    /// Vectors written by this backup
+    pub fn vectors_written(&self) -> usize {
+        self.shards.iter().map(|shard| shard.upserted).sum()
+    }
+}
+
+/// Outcome of [`BackupStore::restore`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    /// Backups applied, full backup first
+    pub chain: Vec<u64>,
+    pub shards: usize,
+    pub vectors: usize,
+}
+
+/// Outcome of [`BackupStore::verify`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VerifyReport {
+    pub backup: u64,
+    /// Backups checked, full backup first
+    pub chain: Vec<u64>,
+    pub shards: usize,
+    pub vectors: usize,
+    /// Everything found wrong; empty when the chain is intact
+    pub problems: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// A shard's record and vector checksums as of one backup, one sealed line
+/// per shard in `fingerprints.jsonl`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ShardFingerprints {
+    record: ShardRecord,
+    vectors: BTreeMap<Uuid, u32>,
+}
+
+/// A shard rebuilt from a chain of backups, its vectors in the order they
+/// were first backed up
+#[derive(Debug)]
+struct ShardState {
+    record: ShardRecord,
+    entries: Vec<Option<VectorEntry>>,
+    positions: HashMap<Uuid, usize>,
+}
+
+impl ShardState {
+    fn new(record: ShardRecord) -> Self {
+        Self {
+            record,
+            entries: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    fn upsert(&mut self, entry: VectorEntry) {
+        match self.positions.get(&entry.id) {
+            Some(&position) => self.entries[position] = Some(entry),
+            None => {
+                self.positions.insert(entry.id, self.entries.len());
+                self.entries.push(Some(entry));
+            }
+        }
+    }
+
+    fn remove(&mut self, id: &Uuid) {
+        if let Some(position) = self.positions.remove(id) {
+            self.entries[position] = None;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    fn into_entries(self) -> Vec<VectorEntry> {
+        self.entries.into_iter().flatten().collect()
+    }
+}
+
+/// Checksum of an entry with its metadata keys sorted, so the same
+/// entry always has the same fingerprint
+fn fingerprint(entry: &VectorEntry) -> Result<u32> {
+    #[derive(Serialize)]
+    struct Canonical<'a> {
+        id: &'a Uuid,
+        values: &'a [f32],
+        metadata: Option<BTreeMap<&'a str, &'a str>>,
+        created_at: &'a chrono::DateTime<chrono::Utc>,
+    }
+    let canonical = Canonical {
+        id: &entry.id,
+        values: &entry.vector.values,
+        metadata: entry.metadata.as_ref().map(|metadata| {
+            metadata
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect()
+        }),
+        created_at: &entry.created_at,
+    };
+    Ok(checksum::crc32c(&serde_json::to_vec(&canonical)?))
+}
+
+/// Write records as a sealed JSON-lines file
+fn write_sealed<T: Serialize>(path: &Path, records: &[T]) -> Result<()> {
+    write_atomic(path, |file| {
+        let mut writer = std::io::BufWriter::new(file);
+        for record in records {
+            writeln!(
+                writer,
+                "{}",
+                checksum::seal(&serde_json::to_string(record)?)
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    })
+}
+
+/// Write a manifest, sealed on a single line
+fn write_manifest(path: &Path, manifest: &BackupManifest) -> Result<()> {
+    let sealed = checksum::seal(&serde_json::to_string(manifest)?);
+    write_atomic(path, |file| {
+        writeln!(file, "{}", sealed)?;
+        Ok(())
+    })
+}
+
+/// Records of a sealed JSON-lines file. Unlike live data, a backup is only
+/// useful intact, so any corrupted line fails the read.
+fn read_sealed<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            if !line.contains('\t') {
+                return Err(anyhow!(
+                    "{}:{}: line has no checksum",
+                    path.display(),
+                    i + 1
+                ));
+            }
+            let record = checksum::unseal(line)
+                .map_err(|e| anyhow!("{}:{}: {}", path.display(), i + 1, e))?;
+            serde_json::from_str(record).map_err(|e| anyhow!("{}:{}: {}", path.display(), i + 1, e))
+        })
+        .collect()
+}
+
+/// Directory of numbered backups
+#[derive(Debug, Clone)]
+pub struct BackupStore {
+    root: PathBuf,
+}
+
+impl BackupStore {
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    fn dir(&self, id: u64) -> PathBuf {
+        self.root.join(format!("{:08}", id))
+    }
+
+    fn vectors_path(&self, id: u64, shard_id: Uuid) -> PathBuf {
+        self.dir(id)
+            .join("vectors")
+            .join(format!("{}.jsonl", shard_id))
+    }
+
+    /// Completed backups, oldest first
+    pub fn list(&self) -> Result<Vec<BackupManifest>> {
+        let mut ids = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let entry = entry?;
+            let Some(id) = entry.file_name().to_str().and_then(|n| n.parse().ok()) else {
+                continue;
+            };
+            if entry.path().join("manifest.json").exists() {
+                ids.push(id);
+            }
+        }
+        ids.sort_unstable();
+        ids.into_iter().map(|id| self.manifest(id)).collect()
+    }
+
+    /// The most recent completed backup
+    pub fn latest(&self) -> Result<Option<BackupManifest>> {
+        Ok(self.list()?.pop())
+    }
+
+    pub fn manifest(&self, id: u64) -> Result<BackupManifest> {
+        let path = self.dir(id).join("manifest.json");
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Backup {} not found: {}", id, e))?;
+        let record = checksum::unseal(contents.trim_end())
+            .map_err(|e| anyhow!("Manifest of backup {} is corrupted: {}", id, e))?;
+        Ok(serde_json::from_str(record)?)
+    }
+
+    /// Vectors a backup wrote for a shard, as they were when it was taken
+    pub fn vectors(&self, id: u64, shard_id: Uuid) -> Result<Vec<VectorEntry>> {
+        read_sealed(&self.vectors_path(id, shard_id))
+    }
+
+    /// Completed backups whose vector files hold any of `ids`, oldest first
+    pub fn holding(&self, ids: &HashSet<Uuid>) -> Result<Vec<u64>> {
+        let mut holding = Vec::new();
+        for manifest in self.list()? {
+            for shard in manifest.shards.iter().filter(|shard| shard.upserted > 0) {
+                let entries = self.vectors(manifest.id, shard.record.id)?;
+                if entries.iter().any(|entry| ids.contains(&entry.id)) {
+                    holding.push(manifest.id);
+                    break;
+                }
+            }
+        }
+        Ok(holding)
+    }
+
+    /// Rewrite every backup holding any of `ids` as if those vectors had
+    /// never been backed up, returning the backups rewritten. Each backup's
+    /// manifest is written last; running the scrub again completes one that
+    /// was interrupted.
+    pub fn scrub(&self, ids: &HashSet<Uuid>) -> Result<Vec<u64>> {
+        let mut rewritten = Vec::new();
+        for mut manifest in self.list()? {
+            let mut changed = false;
+            let mut fingerprints = self.fingerprints(manifest.id)?;
+            let mut dropped: HashMap<Uuid, usize> = HashMap::new();
+            for shard in fingerprints.values_mut() {
+                let before = shard.vectors.len();
+                shard.vectors.retain(|id, _| !ids.contains(id));
+                let count = before - shard.vectors.len();
+                if count > 0 {
+                    shard.record.vector_count = shard.record.vector_count.saturating_sub(count);
+                    dropped.insert(shard.record.id, count);
+                    changed = true;
+                }
+            }
+            for shard in &mut manifest.shards {
+                let before = shard.removed.len();
+                shard.removed.retain(|id| !ids.contains(id));
+                changed |= shard.removed.len() != before;
+                if let Some(count) = dropped.get(&shard.record.id) {
+                    shard.vector_count = shard.vector_count.saturating_sub(*count);
+                    shard.record.vector_count = shard.record.vector_count.saturating_sub(*count);
+                }
+
+                let path = self.vectors_path(manifest.id, shard.record.id);
+                if !path.exists() {
+                    continue;
+                }
+                let entries: Vec<VectorEntry> = read_sealed(&path)?;
+                let kept: Vec<VectorEntry> = entries
+                    .into_iter()
+                    .filter(|entry| !ids.contains(&entry.id))
+                    .collect();
+                if kept.len() == shard.upserted {
+                    continue;
+                }
+                changed = true;
+                shard.upserted = kept.len();
+                if kept.is_empty() {
+                    std::fs::remove_file(&path)?;
+                } else {
+                    write_sealed(&path, &kept)?;
+                }
+            }
+            if !changed {
+                continue;
+            }
+
+            let dir = self.dir(manifest.id);
+            let fingerprints: Vec<ShardFingerprints> = fingerprints.into_values().collect();
+            write_sealed(&dir.join("fingerprints.jsonl"), &fingerprints)?;
+            write_manifest(&dir.join("manifest.json"), &manifest)?;
+            info!("Scrubbed purged vectors from backup {}", manifest.id);
+            rewritten.push(manifest.id);
+        }
+        Ok(rewritten)
+    }
+
+    /// Backups to apply to restore `id`, full backup first
+    pub fn chain(&self, id: u64) -> Result<Vec<BackupManifest>> {
+        let mut chain = vec![self.manifest(id)?];
+        while let Some(parent) = chain.last().unwrap().parent {
+            // Parents are always older, which also rules out cycles
+            if parent >= chain.last().unwrap().id {
+                return Err(anyhow!("Backup {} has a parent newer than itself", id));
+            }
+            chain.push(
+                self.manifest(parent)
+                    .map_err(|e| anyhow!("Backup {} is missing its parent: {}", id, e))?,
+            );
+        }
+        let root = chain.last().unwrap();
+        if root.kind != BackupKind::Full {
+            return Err(anyhow!("Backup {} does not lead back to a full backup", id));
+        }
+        chain.reverse();
+        Ok(chain)
+    }
+
+    fn fingerprints(&self, id: u64) -> Result<BTreeMap<Uuid, ShardFingerprints>> {
+        let shards: Vec<ShardFingerprints> = read_sealed(&self.dir(id).join("fingerprints.jsonl"))?;
+        Ok(shards
+            .into_iter()
+            .map(|shard| (shard.record.id, shard))
+            .collect())
+    }
+
+    /// Back up everything in `storage`. An incremental backup builds on the
+    /// latest backup and fails if there is none.
+    pub async fn create(
+        &self,
+        storage: &dyn StorageBackend,
+        kind: BackupKind,
+    ) -> Result<BackupManifest> {
+        let latest = self.latest()?;
+        let parent = match kind {
+            BackupKind::Full => None,
+            BackupKind::Incremental => Some(
+                latest
+                    .as_ref()
+                    .map(|m| m.id)
+                    .ok_or_else(|| anyhow!("No backup to take an incremental backup on"))?,
+            ),
+        };
+        let previous = match parent {
+            Some(parent) => self.fingerprints(parent)?,
+            None => BTreeMap::new(),
+        };
+        let id = latest.map_or(1, |m| m.id + 1);
+        let dir = self.dir(id);
+        // Left over from an interrupted attempt
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+        }
+        std::fs::create_dir_all(dir.join("vectors"))?;
+
+        let mut records = storage.load_shards().await?;
+        records.sort_by_key(|record| record.id);
+        let mut shards = Vec::new();
+        let mut fingerprints = Vec::new();
+        for record in records {
+            let entries = storage.load_vectors(record.id).await?;
+            let mut vectors = BTreeMap::new();
+            let mut upserts = Vec::new();
+            let before = previous.get(&record.id);
+            for entry in entries {
+                let print = fingerprint(&entry)?;
+                if before.and_then(|b| b.vectors.get(&entry.id)) != Some(&print) {
+                    upserts.push(entry.clone());
+                }
+                vectors.insert(entry.id, print);
+            }
+            let removed: Vec<Uuid> = before
+                .map(|b| {
+                    b.vectors
+                        .keys()
+                        .filter(|id| !vectors.contains_key(id))
+                        .copied()
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let unchanged = before.is_some_and(|b| b.record == record);
+            if !(unchanged && upserts.is_empty() && removed.is_empty()) {
+                if !upserts.is_empty() {
+                    write_sealed(&self.vectors_path(id, record.id), &upserts)?;
+                }
+                shards.push(ShardBackup {
+                    record: record.clone(),
+                    upserted: upserts.len(),
+                    removed,
+                    vector_count: vectors.len(),
+                });
+            }
+            fingerprints.push(ShardFingerprints { record, vectors });
+        }
+        let removed_shards = previous
+            .keys()
+            .filter(|id| !fingerprints.iter().any(|f| f.record.id == **id))
+            .copied()
+            .collect();
+
+        write_sealed(&dir.join("fingerprints.jsonl"), &fingerprints)?;
+        let manifest = BackupManifest {
+            id,
+            kind,
+            parent,
+            created_at: chrono::Utc::now(),
+            shards,
+            removed_shards,
+        };
+        write_manifest(&dir.join("manifest.json"), &manifest)?;
+        info!(
+            "Wrote {:?} backup {} of {} shards from {} storage",
+            kind,
+            id,
+            manifest.shards.len(),
+            storage.name()
+        );
+        Ok(manifest)
+    }
+
+    /// Shards as of the last backup in `chain`
+    fn replay(&self, chain: &[BackupManifest]) -> Result<BTreeMap<Uuid, ShardState>> {
+        let mut shards: BTreeMap<Uuid, ShardState> = BTreeMap::new();
+        for manifest in chain {
+            for shard_id in &manifest.removed_shards {
+                shards.remove(shard_id);
+            }
+            for backup in &manifest.shards {
+                let state = shards
+                    .entry(backup.record.id)
+                    .or_insert_with(|| ShardState::new(backup.record.clone()));
+                state.record = backup.record.clone();
+                for id in &backup.removed {
+                    state.remove(id);
+                }
+                if backup.upserted > 0 {
+                    let entries: Vec<VectorEntry> =
+                        read_sealed(&self.vectors_path(manifest.id, backup.record.id))?;
+                    for entry in entries {
+                        state.upsert(entry);
+                    }
+                }
+            }
+        }
+        Ok(shards)
+    }
+
+    /// Replace everything in `storage` with the shards as of backup `id`
+    pub async fn restore(&self, id: u64, storage: &dyn StorageBackend) -> Result<RestoreSummary> {
+        let chain = self.chain(id)?;
+        let shards = self.replay(&chain)?;
+
+        for record in storage.load_shards().await? {
+            if !shards.contains_key(&record.id) {
+                storage.delete_shard(record.id).await?;
+            }
+        }
+        let count = shards.len();
+        let mut vectors = 0;
+        for (shard_id, state) in shards {
+            storage.save_shard(&state.record).await?;
+            let entries = state.into_entries();
+            vectors += entries.len();
+            storage.save_vectors(shard_id, &entries).await?;
+        }
+        storage.flush().await?;
+
+        let chain: Vec<u64> = chain.iter().map(|m| m.id).collect();
+        info!(
+            "Restored {} shards from backups {:?} into {} storage",
+            count,
+            chain,
+            storage.name()
+        );
+        Ok(RestoreSummary {
+            chain,
+            shards: count,
+            vectors,
+        })
+    }
+
+    /// Check backup `id` and every backup it depends on
+    pub fn verify(&self, id: u64) -> VerifyReport {
+        let mut report = VerifyReport {
+            backup: id,
+            ..Default::default()
+        };
+        let chain = match self.chain(id) {
+            Ok(chain) => chain,
+            Err(e) => {
+                report.problems.push(e.to_string());
+                return report;
+            }
+        };
+        report.chain = chain.iter().map(|m| m.id).collect();
+
+        for manifest in &chain {
+            let expected_parent = match manifest.kind {
+                BackupKind::Full => manifest.parent.is_none(),
+                BackupKind::Incremental => manifest.parent.is_some(),
+            };
+            if !expected_parent {
+                report.problems.push(format!(
+                    "Backup {} is {:?} but has parent {:?}",
+                    manifest.id, manifest.kind, manifest.parent
+                ));
+            }
+            for shard in &manifest.shards {
+                let path = self.vectors_path(manifest.id, shard.record.id);
+                let lines = if shard.upserted == 0 && !path.exists() {
+                    Ok(Vec::new())
+                } else {
+                    read_sealed::<VectorEntry>(&path)
+                };
+                match lines {
+                    Ok(entries) if entries.len() == shard.upserted => {}
+                    Ok(entries) => report.problems.push(format!(
+                        "Backup {} holds {} vectors of shard {}, expected {}",
+                        manifest.id,
+                        entries.len(),
+                        shard.record.id,
+                        shard.upserted
+                    )),
+                    Err(e) => report.problems.push(e.to_string()),
+                }
+            }
+        }
+        if !report.is_ok() {
+            return report;
+        }
+
+        // The replayed state must match what the last backup saw
+        let expected = match self.fingerprints(id) {
+            Ok(expected) => expected,
+            Err(e) => {
+                report.problems.push(e.to_string());
+                return report;
+            }
+        };
+        let shards = match self.replay(&chain) {
+            Ok(shards) => shards,
+            Err(e) => {
+                report.problems.push(e.to_string());
+                return report;
+            }
+        };
+        report.shards = shards.len();
+        for (shard_id, state) in &shards {
+            report.vectors += state.len();
+            let Some(expected) = expected.get(shard_id) else {
+                report
+                    .problems
+                    .push(format!("Shard {} should have been removed", shard_id));
+                continue;
+            };
+            if state.record != expected.record {
+                report
+                    .problems
+                    .push(format!("Record of shard {} differs", shard_id));
+            }
+            let mut mismatched = 0;
+            for entry in state.entries.iter().flatten() {
+                let print = fingerprint(entry).ok();
+                if expected.vectors.get(&entry.id).copied() != print {
+                    mismatched += 1;
+                }
+            }
+            let missing = expected
+                .vectors
+                .keys()
+                .filter(|id| !state.positions.contains_key(id))
+                .count();
+            if mismatched > 0 || missing > 0 {
+                report.problems.push(format!(
+                    "Shard {} has {} unexpected or altered and {} missing vectors",
+                    shard_id, mismatched, missing
+                ));
+            }
+        }
+        for shard_id in expected.keys().filter(|id| !shards.contains_key(id)) {
+            report
+                .problems
+                .push(format!("Shard {} is missing", shard_id));
+        }
+        report
+    }
+}
@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::Range;
+use std::time::Duration;
+use tokio::sync::{broadcast, Notify, RwLock};
+use uuid::Uuid;
+
+use crate::utils::errors::ChangeFeedError;
+
+/// Default number of change events retained per shard
+pub const DEFAULT_RETENTION: usize = 100_000;
+
+/// A mutation applied to a shard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ChangeOp {
+    Insert {
+        vector_id: Uuid,
+        values: Vec<f32>,
+        metadata: Option<HashMap<String, String>>,
+    },
+    Delete {
+        vector_id: Uuid,
+    },
+}
+
+/// A change event with its position in the shard's feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    /// Position in the feed; offsets are dense and strictly increasing
+    pub offset: u64,
+    pub shard_id: Uuid,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Region the mutation was replicated from; `None` for local writes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    #[serde(flatten)]
+    pub op: ChangeOp,
+}
+
+/// A page of change events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeBatch {
+    pub shard_id: Uuid,
+    pub events: Vec<ChangeEvent>,
+    /// Offset to request next; consumers persist this after processing
+    pub next_offset: u64,
+    /// Oldest offset still retained
+    pub earliest_offset: u64,
+}
+
+#[derive(Debug, Default)]
+struct FeedState {
+    events: VecDeque<ChangeEvent>,
+    next_offset: u64,
+}
+
+/// Ordered, offset-addressable log of a shard's mutations. Consumers track
+/// their own offsets and resume from them, so delivery is at-least-once
+/// unless the consumer commits its offset atomically with its side effects.
+#[derive(Debug)]
+pub struct ChangeFeed {
+    shard_id: Uuid,
+    retention: usize,
+    state: RwLock<FeedState>,
+    appended: Notify,
+    bus: Option<broadcast::Sender<ChangeEvent>>,
+}
+
+impl ChangeFeed {
+    pub fn new(shard_id: Uuid) -> Self {
+        Self::with_retention(shard_id, DEFAULT_RETENTION)
+    }
+
+    pub fn with_retention(shard_id: Uuid, retention: usize) -> Self {
+        Self {
+            shard_id,
+            retention: retention.max(1),
+            state: RwLock::new(FeedState::default()),
+            appended: Notify::new(),
+            bus: None,
+        }
+    }
+
+    /// Also publish every appended event to `bus`, e.g. one shared by all
+    /// shards for live subscribers
+    pub fn with_bus(mut self, bus: broadcast::Sender<ChangeEvent>) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// Append a local mutation, returning its offset
+    pub async fn append(&self, op: ChangeOp) -> u64 {
+        self.append_from(op, None).await
+    }
+
+    /// Append a mutation that originated in another region
+    pub async fn append_from(&self, op: ChangeOp, origin: Option<String>) -> u64 {
+        self.append_all([op], origin).await.start
+    }
+
+    /// Append a run of mutations under one lock, e.g. a batch insert,
+    /// returning their offsets
+    pub async fn append_all(
+        &self,
+        ops: impl IntoIterator<Item = ChangeOp>,
+        origin: Option<String>,
+    ) -> Range<u64> {
+        let offsets = {
+            let mut state = self.state.write().await;
+            let start = state.next_offset;
+            let timestamp = chrono::Utc::now();
+            for op in ops {
+                let event = ChangeEvent {
+                    offset: state.next_offset,
+                    shard_id: self.shard_id,
+                    timestamp,
+                    origin: origin.clone(),
+                    op,
+                };
+                // Published under the lock so subscribers see offset order
+                if let Some(bus) = &self.bus {
+                    // No live subscribers is not an error
+                    let _ = bus.send(event.clone());
+                }
+                state.events.push_back(event);
+                state.next_offset += 1;
+            }
+            while state.events.len() > self.retention {
+                state.events.pop_front();
+            }
+            start..state.next_offset
+        };
+        self.appended.notify_waiters();
+        offsets
+    }
+
+    /// Rewrite retained inserts of the given vectors as deletes so their
+    /// values and metadata no longer exist anywhere in the feed. Offsets are
+    /// kept, so consumers resuming mid-feed are unaffected. Returns the number
+    /// of events rewritten.
+    pub async fn scrub(&self, vector_ids: &HashSet<Uuid>) -> usize {
+        let mut state = self.state.write().await;
+        let mut scrubbed = 0;
+        for event in state.events.iter_mut() {
+            if let ChangeOp::Insert { vector_id, .. } = event.op {
+                if vector_ids.contains(&vector_id) {
+                    event.op = ChangeOp::Delete { vector_id };
+                    scrubbed += 1;
+                }
+            }
+        }
+        scrubbed
+    }
+
+    /// Last retained event for each vector, i.e. the state the feed says
+    /// each one should be in. Vectors whose events have all aged out of
+    /// retention are absent.
+    pub async fn latest_ops(&self) -> HashMap<Uuid, ChangeOp> {
+        let state = self.state.read().await;
+        let mut latest = HashMap::new();
+        for event in &state.events {
+            let vector_id = match &event.op {
+                ChangeOp::Insert { vector_id, .. } | ChangeOp::Delete { vector_id } => *vector_id,
+            };
+            latest.insert(vector_id, event.op.clone());
+        }
+        latest
+    }
+
+    /// Offset the next appended event will receive
+    pub async fn next_offset(&self) -> u64 {
+        self.state.read().await.next_offset
+    }
+
+    /// Oldest offset still retained
+    pub async fn earliest_offset(&self) -> u64 {
+        Self::earliest(&*self.state.read().await)
+    }
+
+    fn earliest(state: &FeedState) -> u64 {
+        state
+            .events
+            .front()
+            .map(|e| e.offset)
+            .unwrap_or(state.next_offset)
+    }
+
+    /// Read up to `limit` events starting at `from`
+    pub async fn read(&self, from: u64, limit: usize) -> Result<ChangeBatch, ChangeFeedError> {
+        let state = self.state.read().await;
+        let earliest = Self::earliest(&state);
+
+        if from < earliest {
+            return Err(ChangeFeedError::OffsetExpired {
+                requested: from,
+                earliest,
+            });
+        }
+        if from > state.next_offset {
+            return Err(ChangeFeedError::OffsetAhead {
+                requested: from,
+                next: state.next_offset,
+            });
+        }
+
+        let events: Vec<ChangeEvent> = state
+            .events
+            .iter()
+            .skip((from - earliest) as usize)
+            .take(limit)
+            .cloned()
+            .collect();
+        let next_offset = events.last().map(|e| e.offset + 1).unwrap_or(from);
+
+        Ok(ChangeBatch {
+            shard_id: self.shard_id,
+            events,
+            next_offset,
+            earliest_offset: earliest,
+        })
+    }
+
+    /// Long-poll variant of [`read`](Self::read): if nothing is available at
+    /// `from`, wait up to `timeout` for new events before returning an
+    /// empty batch.
+    pub async fn wait(
+        &self,
+        from: u64,
+        limit: usize,
+        timeout: Duration,
+    ) -> Result<ChangeBatch, ChangeFeedError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Register for wakeups before checking so an append in between isn't missed
+            let appended = self.appended.notified();
+            let batch = self.read(from, limit).await?;
+            if !batch.events.is_empty() || tokio::time::Instant::now() >= deadline {
+                return Ok(batch);
+            }
+            if tokio::time::timeout_at(deadline, appended).await.is_err() {
+                return self.read(from, limit).await;
+            }
+        }
+    }
+}
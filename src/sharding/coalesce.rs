@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+use crate::core::vector::Vector;
+use crate::query::QueryExpr;
+use crate::sharding::vector_index::SearchOutcome;
+
+/// A search in canonical form: requests that must return the same results
+/// get the same key, however their filters were written
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SearchKey {
+    shard_id: Uuid,
+    query_bits: Vec<u32>,
+    limit: usize,
+    filter: Option<String>,
+}
+
+impl SearchKey {
+    pub fn new(shard_id: Uuid, query: &Vector, limit: usize, filter: Option<&QueryExpr>) -> Self {
+        Self {
+            shard_id,
+            // -0.0 scores every candidate exactly as 0.0 does
+            query_bits: query
+                .values
+                .iter()
+                .map(|v| if *v == 0.0 { 0 } else { v.to_bits() })
+                .collect(),
+            limit,
+            filter: filter.and_then(|f| serde_json::to_string(&f.canonical()).ok()),
+        }
+    }
+}
+
+type SharedSearch = Arc<OnceCell<Result<SearchOutcome, String>>>;
+
+/// How well identical concurrent searches are being coalesced
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CoalescingStats {
+    /// Index searches actually run
+    pub executions: u64,
+    /// Requests answered by another request's search
+    pub coalesced: u64,
+    /// Searches running now
+    pub in_flight: usize,
+}
+
+/// Runs identical concurrent searches once. The first request for a key
+/// searches the index; requests for the same key arriving while it runs
+/// wait for and share its outcome instead of searching themselves. Once
+/// the search finishes the key is forgotten, so later requests search
+/// afresh and the cache, not this, serves repeats.
+#[derive(Debug)]
+pub struct SearchCoalescer {
+    enabled: bool,
+    in_flight: Mutex<HashMap<SearchKey, SharedSearch>>,
+    executions: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+impl SearchCoalescer {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            in_flight: Mutex::new(HashMap::new()),
+            executions: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Run `search` for `key`, or share the outcome of the run already
+    /// under way for it. Also returns whether the outcome was shared.
+    ///
+    /// If the request running the search is dropped, a waiting one takes
+    /// over and searches itself.
+    pub async fn run<F, Fut>(
+        &self,
+        key: SearchKey,
+        search: F,
+    ) -> (Result<SearchOutcome, String>, bool)
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<SearchOutcome, String>>,
+    {
+        if !self.enabled {
+            self.executions.fetch_add(1, Ordering::Relaxed);
+            return (search().await, false);
+        }
+
+        let shared = self
+            .in_flight
+            .lock()
+            .expect("coalescer lock poisoned")
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let mut searched = false;
+        let outcome = shared
+            .get_or_init(|| {
+                searched = true;
+                search()
+            })
+            .await
+            .clone();
+
+        if searched {
+            self.executions.fetch_add(1, Ordering::Relaxed);
+            let mut in_flight = self.in_flight.lock().expect("coalescer lock poisoned");
+            // A write may already have replaced it with a newer search
+            if in_flight
+                .get(&key)
+                .is_some_and(|current| Arc::ptr_eq(current, &shared))
+            {
+                in_flight.remove(&key);
+            }
+        } else {
+            self.coalesced.fetch_add(1, Ordering::Relaxed);
+        }
+        (outcome, !searched)
+    }
+
+    /// Stop sharing the searches under way on a shard, so requests arriving
+    /// after a write don't get results from before it
+    pub fn invalidate(&self, shard_id: Uuid) {
+        self.in_flight
+            .lock()
+            .expect("coalescer lock poisoned")
+            .retain(|key, _| key.shard_id != shard_id);
+    }
+
+    pub fn stats(&self) -> CoalescingStats {
+        CoalescingStats {
+            executions: self.executions.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            in_flight: self
+                .in_flight
+                .lock()
+                .expect("coalescer lock poisoned")
+                .len(),
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Transparent compression of large metadata values.
+//!
+//! Values over a shard's size threshold are compressed on insert and stored
+//! as `z:{codec}:{original_len}:{base64(compressed)}`. The envelope is
+//! self-describing, so values decode the same way wherever they travel:
+//! through the change feed, to replicas, or into another shard. Decoding is
+//! lazy: stored values stay compressed, filters decode only the fields they
+//! test, and searches decode only the results they return.
+//!
+//! Only shards with compression configured decode envelopes. On those shards
+//! a stored value that happens to start with the envelope prefix is always
+//! wrapped in an envelope itself, so it comes back exactly as written instead
+//! of being taken for compressed data.
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Prefix marking a metadata value as compressed
+pub const COMPRESSED_PREFIX: &str = "z:";
+
+/// Largest decoded value accepted unless configured otherwise
+pub const DEFAULT_MAX_VALUE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Compression algorithm for metadata values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Codec {
+    /// Fast with modest ratios
+    Lz4,
+    /// Slower with better ratios
+    Zstd,
+}
+
+impl Codec {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Codec::Lz4 => "lz4",
+            Codec::Zstd => "zstd",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "lz4" => Some(Codec::Lz4),
+            "zstd" => Some(Codec::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Per-shard compression settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressionConfig {
+    pub codec: Codec,
+
+    /// Values shorter than this many bytes are stored as-is
+    #[serde(default = "default_threshold")]
+    pub threshold_bytes: usize,
+
+    /// zstd compression level; ignored by lz4
+    #[serde(default = "default_level")]
+    pub level: i32,
+
+    /// Envelopes claiming a longer original value are treated as corrupt
+    /// rather than decoded, bounding what a single value can allocate
+    #[serde(default = "default_max_value_bytes")]
+    pub max_value_bytes: usize,
+}
+
+fn default_threshold() -> usize {
+    1024
+}
+
+fn default_level() -> i32 {
+    3
+}
+
+fn default_max_value_bytes() -> usize {
+    DEFAULT_MAX_VALUE_BYTES
+}
+
+impl CompressionConfig {
+    pub fn new(codec: Codec) -> Self {
+        Self {
+            codec,
+            threshold_bytes: default_threshold(),
+            level: default_level(),
+            max_value_bytes: default_max_value_bytes(),
+        }
+    }
+
+    /// Compress a value if it's over the threshold and compression actually
+    /// makes it smaller once encoded. Values starting with the envelope
+    /// prefix are always wrapped, so they can't pass for compressed data;
+    /// values over `max_value_bytes` are stored as-is, since they couldn't
+    /// be decoded again.
+    pub fn compress(&self, value: &str) -> Result<Option<String>> {
+        let escape = value.starts_with(COMPRESSED_PREFIX);
+        if value.len() > self.max_value_bytes {
+            if escape {
+                return Err(anyhow!(
+                    "Metadata value of {} bytes starting with {:?} exceeds the {} byte limit",
+                    value.len(),
+                    COMPRESSED_PREFIX,
+                    self.max_value_bytes
+                ));
+            }
+            return Ok(None);
+        }
+        if value.len() < self.threshold_bytes && !escape {
+            return Ok(None);
+        }
+        let compressed = match self.codec {
+            Codec::Lz4 => lz4_flex::compress(value.as_bytes()),
+            Codec::Zstd => zstd::bulk::compress(value.as_bytes(), self.level)?,
+        };
+        let envelope = format!(
+            "{}{}:{}:{}",
+            COMPRESSED_PREFIX,
+            self.codec.as_str(),
+            value.len(),
+            STANDARD.encode(compressed)
+        );
+        Ok((escape || envelope.len() < value.len()).then_some(envelope))
+    }
+
+    /// Compress every large value in a metadata map in place
+    pub fn compress_metadata(&self, metadata: &mut HashMap<String, String>) -> Result<()> {
+        for value in metadata.values_mut() {
+            if let Some(compressed) = self.compress(value)? {
+                *value = compressed;
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode a stored value, refusing envelopes over `max_value_bytes`
+    pub fn decompress<'a>(&self, value: &'a str) -> Result<Cow<'a, str>> {
+        decompress_at_most(value, self.max_value_bytes)
+    }
+
+    /// Decode every compressed value in a metadata map in place. Values
+    /// that fail to decode are left as stored; returns how many there were.
+    pub fn decompress_metadata(&self, metadata: &mut HashMap<String, String>) -> usize {
+        let mut undecodable = 0;
+        for value in metadata.values_mut() {
+            match self.decompress(value) {
+                Ok(Cow::Owned(decoded)) => *value = decoded,
+                Ok(Cow::Borrowed(_)) => {}
+                Err(_) => undecodable += 1,
+            }
+        }
+        undecodable
+    }
+}
+
+/// Parsed envelope header and payload
+struct Envelope<'a> {
+    codec: Codec,
+    original_len: usize,
+    payload: &'a str,
+}
+
+fn parse(value: &str) -> Option<Envelope<'_>> {
+    let rest = value.strip_prefix(COMPRESSED_PREFIX)?;
+    let mut parts = rest.splitn(3, ':');
+    let codec = Codec::parse(parts.next()?)?;
+    let original_len = parts.next()?.parse().ok()?;
+    let payload = parts.next()?;
+    Some(Envelope {
+        codec,
+        original_len,
+        payload,
+    })
+}
+
+/// Whether a stored value is a compression envelope
+pub fn is_compressed(value: &str) -> bool {
+    parse(value).is_some()
+}
+
+/// Length of a value before compression, read from the envelope header
+pub fn original_len(value: &str) -> usize {
+    parse(value).map_or(value.len(), |e| e.original_len)
+}
+
+/// Decode a stored value; uncompressed values are returned as-is
+pub fn decompress(value: &str) -> Result<Cow<'_, str>> {
+    decompress_at_most(value, DEFAULT_MAX_VALUE_BYTES)
+}
+
+fn decompress_at_most(value: &str, max_value_bytes: usize) -> Result<Cow<'_, str>> {
+    let Some(envelope) = parse(value) else {
+        return Ok(Cow::Borrowed(value));
+    };
+    if envelope.original_len > max_value_bytes {
+        return Err(anyhow!(
+            "Compressed metadata value claims {} bytes, over the {} byte limit",
+            envelope.original_len,
+            max_value_bytes
+        ));
+    }
+    let compressed = STANDARD.decode(envelope.payload)?;
+    let bytes = match envelope.codec {
+        Codec::Lz4 => lz4_flex::decompress(&compressed, envelope.original_len)
+            .map_err(|e| anyhow!("Corrupt lz4 metadata value: {}", e))?,
+        Codec::Zstd => zstd::bulk::decompress(&compressed, envelope.original_len)?,
+    };
+    Ok(Cow::Owned(String::from_utf8(bytes)?))
+}
+
+/// Decode a stored value for comparison, treating undecodable envelopes as
+/// literal text
+pub fn decoded(value: &str) -> Cow<'_, str> {
+    decompress(value).unwrap_or(Cow::Borrowed(value))
+}
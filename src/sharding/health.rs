@@ -0,0 +1,426 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::core::audit::AuditLog;
+use crate::core::metrics::MetricsCollector;
+use crate::core::vector::Vector;
+use crate::nerv::tasks;
+use crate::sharding::manager::ShardManager;
+use crate::sharding::vector_index::{VectorEntry, VectorIndex};
+
+/// Weights of the signals in the health score; signals that couldn't be
+/// measured are left out and the rest reweighted
+const RECALL_WEIGHT: f64 = 0.4;
+const DRIFT_WEIGHT: f64 = 0.25;
+const TOMBSTONE_WEIGHT: f64 = 0.2;
+const LATENCY_WEIGHT: f64 = 0.15;
+
+/// Searches needed in each half of the window before a latency trend is
+/// judged
+const MIN_LATENCY_SAMPLES: usize = 10;
+
+/// When and how thoroughly index health is checked
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    /// Time between checks
+    pub interval: Duration,
+
+    /// Stored vectors searched both exactly and through the index to
+    /// estimate recall
+    pub recall_samples: usize,
+
+    /// Neighbours compared per recall sample
+    pub recall_k: usize,
+
+    /// Score below which rebuilding the index is recommended
+    pub rebuild_below: f64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(600),
+            recall_samples: 20,
+            recall_k: 10,
+            rebuild_below: 0.6,
+        }
+    }
+}
+
+/// Why rebuilding an index is recommended
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "signal", rename_all = "snake_case")]
+pub enum RebuildReason {
+    /// The index finds too few of the exact nearest neighbours
+    LowRecall { recall: f64 },
+    /// The data has moved away from where it was when the index was built
+    CentroidDrift { drift: f64 },
+    /// Deleted vectors still take up segment rows or graph nodes
+    Tombstones { ratio: f64 },
+    /// Recent searches are slower than earlier ones
+    LatencyRegression { trend: f64 },
+}
+
+/// One index's health, from 0 (rebuild it) to 1 (as built)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexHealth {
+    pub shard_id: Uuid,
+    pub index_name: String,
+    pub score: f64,
+
+    /// Distance the centroid moved since the baseline, relative to the
+    /// vectors' mean distance from it then
+    pub centroid_drift: f64,
+
+    /// Share of the exact nearest neighbours the index found, if sampled
+    pub recall: Option<f64>,
+
+    /// Tombstoned rows and graph nodes per live vector
+    pub tombstone_ratio: f64,
+
+    /// Recent search latency over earlier latency, if enough searches ran
+    pub latency_trend: Option<f64>,
+
+    /// The score is under [`HealthConfig::rebuild_below`]
+    pub rebuild_recommended: bool,
+
+    /// Signals well below where a healthy index sits, when a rebuild is
+    /// recommended
+    pub rebuild_reasons: Vec<RebuildReason>,
+
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Where an index's vectors were centred when it was first checked
+#[derive(Debug, Clone)]
+struct Baseline {
+    index: Arc<VectorIndex>,
+    centroid: Vec<f32>,
+    spread: f32,
+}
+
+/// Start and end of a burst of recall sample searches
+type SamplingWindow = (DateTime<Utc>, DateTime<Utc>);
+
+/// Scores each index's health from four signals: how far the centroid of
+/// its vectors has drifted since the index was first checked, the recall
+/// of its searches against an exact scan over a sample of stored vectors,
+/// its share of tombstones, and whether its search latency is trending up.
+/// An index scoring under [`HealthConfig::rebuild_below`] gets a rebuild
+/// recommendation naming the signals responsible, recorded as an audit
+/// event when first made. Replacing a shard's index resets its baseline.
+pub struct IndexHealthMonitor {
+    shard_manager: Arc<ShardManager>,
+    metrics: Arc<MetricsCollector>,
+    audit_log: Arc<AuditLog>,
+    config: HealthConfig,
+    baselines: RwLock<HashMap<Uuid, Baseline>>,
+    reports: RwLock<HashMap<Uuid, IndexHealth>>,
+    /// When each shard's recall samples were searched, so their latencies
+    /// don't count towards its trend
+    sampling: RwLock<HashMap<Uuid, SamplingWindow>>,
+}
+
+impl IndexHealthMonitor {
+    pub fn new(
+        shard_manager: Arc<ShardManager>,
+        metrics: Arc<MetricsCollector>,
+        audit_log: Arc<AuditLog>,
+        config: HealthConfig,
+    ) -> Self {
+        Self {
+            shard_manager,
+            metrics,
+            audit_log,
+            config,
+            baselines: RwLock::new(HashMap::new()),
+            reports: RwLock::new(HashMap::new()),
+            sampling: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The latest health of a shard's index, if it has been checked
+    pub async fn health(&self, shard_id: Uuid) -> Option<IndexHealth> {
+        self.reports.read().await.get(&shard_id).cloned()
+    }
+
+    /// The latest health of every checked index
+    pub async fn all(&self) -> Vec<IndexHealth> {
+        self.reports.read().await.values().cloned().collect()
+    }
+
+    /// Measure drift from the index's current vectors from now on
+    pub async fn reset_baseline(&self, shard_id: Uuid) {
+        self.baselines.write().await.remove(&shard_id);
+    }
+
+    /// Check one shard's index now
+    pub async fn check(&self, shard_id: Uuid) -> anyhow::Result<IndexHealth> {
+        let index = self.shard_manager.get_vector_index(shard_id).await?;
+        let entries = index.entries().await;
+
+        let latency_trend = self.latency_trend(shard_id, &index).await;
+        let centroid_drift = self.centroid_drift(shard_id, &index, &entries).await;
+        let recall = self.sample_recall(shard_id, &index, &entries).await;
+        let stats = index.stats().await;
+        let tombstones = stats.segments.tombstones + index.graph_tombstones().await;
+        let tombstone_ratio = tombstones as f64 / entries.len().max(1) as f64;
+
+        let mut signals = vec![
+            (DRIFT_WEIGHT, (1.0 - centroid_drift).clamp(0.0, 1.0)),
+            (TOMBSTONE_WEIGHT, 1.0 / (1.0 + tombstone_ratio)),
+        ];
+        if let Some(recall) = recall {
+            signals.push((RECALL_WEIGHT, recall));
+        }
+        if let Some(trend) = latency_trend {
+            signals.push((LATENCY_WEIGHT, (1.0 / trend).min(1.0)));
+        }
+        let weight: f64 = signals.iter().map(|(weight, _)| weight).sum();
+        let score = signals.iter().map(|(w, s)| w * s).sum::<f64>() / weight;
+
+        let rebuild_recommended = score < self.config.rebuild_below;
+        let mut rebuild_reasons = Vec::new();
+        if rebuild_recommended {
+            if let Some(recall) = recall.filter(|recall| *recall < 0.9) {
+                rebuild_reasons.push(RebuildReason::LowRecall { recall });
+            }
+            if centroid_drift > 0.5 {
+                rebuild_reasons.push(RebuildReason::CentroidDrift {
+                    drift: centroid_drift,
+                });
+            }
+            if tombstone_ratio > 0.25 {
+                rebuild_reasons.push(RebuildReason::Tombstones {
+                    ratio: tombstone_ratio,
+                });
+            }
+            if let Some(trend) = latency_trend.filter(|trend| *trend > 1.5) {
+                rebuild_reasons.push(RebuildReason::LatencyRegression { trend });
+            }
+        }
+
+        let health = IndexHealth {
+            shard_id,
+            index_name: index.name().to_string(),
+            score,
+            centroid_drift,
+            recall,
+            tombstone_ratio,
+            latency_trend,
+            rebuild_recommended,
+            rebuild_reasons,
+            checked_at: Utc::now(),
+        };
+        self.metrics
+            .set_gauge(
+                &format!("index_health.{}.score_pct", shard_id),
+                (score * 100.0).round() as u64,
+            )
+            .await;
+
+        let previous = self.reports.write().await.insert(shard_id, health.clone());
+        if health.rebuild_recommended
+            && !previous.is_some_and(|previous| previous.rebuild_recommended)
+        {
+            warn!(
+                "Recommending a rebuild of index '{}' on shard {} (health {:.2})",
+                health.index_name, shard_id, score
+            );
+            self.metrics
+                .increment_counter("index_health.rebuild_recommendations", 1)
+                .await;
+            self.audit_log
+                .record(
+                    "index_health",
+                    "recommend_rebuild",
+                    &shard_id.to_string(),
+                    serde_json::json!({
+                        "index": health.index_name,
+                        "score": score,
+                        "reasons": health.rebuild_reasons,
+                    }),
+                )
+                .await;
+        }
+        Ok(health)
+    }
+
+    /// How far the centroid has moved from the baseline, in units of the
+    /// baseline's spread. The first check of an index records the baseline.
+    async fn centroid_drift(
+        &self,
+        shard_id: Uuid,
+        index: &Arc<VectorIndex>,
+        entries: &[VectorEntry],
+    ) -> f64 {
+        let Some(centroid) = centroid(entries, index.dimensions()) else {
+            return 0.0;
+        };
+        let mut baselines = self.baselines.write().await;
+        match baselines.get(&shard_id) {
+            Some(baseline) if Arc::ptr_eq(&baseline.index, index) => {
+                let moved = Vector::new(centroid)
+                    .euclidean_distance(&Vector::new(baseline.centroid.clone()));
+                // A baseline of identical vectors makes any move a full drift
+                if baseline.spread > 0.0 {
+                    (moved / baseline.spread) as f64
+                } else if moved > 0.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            _ => {
+                let center = Vector::new(centroid.clone());
+                let spread = entries
+                    .iter()
+                    .map(|entry| entry.vector.euclidean_distance(&center))
+                    .sum::<f32>()
+                    / entries.len() as f32;
+                baselines.insert(
+                    shard_id,
+                    Baseline {
+                        index: index.clone(),
+                        centroid,
+                        spread,
+                    },
+                );
+                0.0
+            }
+        }
+    }
+
+    /// Recall@k of the index against an exact scan, over stored vectors
+    /// spread evenly through the index
+    async fn sample_recall(
+        &self,
+        shard_id: Uuid,
+        index: &VectorIndex,
+        entries: &[VectorEntry],
+    ) -> Option<f64> {
+        let k = self.config.recall_k.min(entries.len());
+        if k == 0 || self.config.recall_samples == 0 {
+            return None;
+        }
+        let metric = index.distance_metric();
+        let step = entries.len().div_ceil(self.config.recall_samples).max(1);
+
+        let started = Utc::now();
+        let (mut found, mut expected) = (0, 0);
+        for query in entries.iter().step_by(step).map(|entry| &entry.vector) {
+            let mut exact: Vec<(f32, Uuid)> = entries
+                .iter()
+                .map(|entry| (metric.calculate(query, &entry.vector), entry.id))
+                .collect();
+            if metric.is_lower_better() {
+                exact.sort_by(|a, b| a.0.total_cmp(&b.0));
+            } else {
+                exact.sort_by(|a, b| b.0.total_cmp(&a.0));
+            }
+            // Neighbours tied with the k-th are as good as it
+            let cutoff = exact[k - 1].0;
+            let nearest: HashSet<Uuid> = exact
+                .iter()
+                .take_while(|(score, _)| {
+                    if metric.is_lower_better() {
+                        *score <= cutoff
+                    } else {
+                        *score >= cutoff
+                    }
+                })
+                .map(|(_, id)| *id)
+                .collect();
+
+            match index.search(query, k).await {
+                Ok(results) => {
+                    found += results.iter().filter(|r| nearest.contains(&r.id)).count();
+                    expected += k;
+                }
+                Err(e) => debug!("Recall sample on shard {} failed: {}", shard_id, e),
+            }
+        }
+        self.sampling
+            .write()
+            .await
+            .insert(shard_id, (started, Utc::now()));
+
+        (expected > 0).then(|| found as f64 / expected as f64)
+    }
+
+    /// Mean latency of the newer half of the index's recent searches over
+    /// the older half's, leaving out the last recall samples
+    async fn latency_trend(&self, shard_id: Uuid, index: &VectorIndex) -> Option<f64> {
+        let name = format!("vector_index.{}.search_time_ms", index.name());
+        let series = self.metrics.get_timeseries(&name).await?;
+        let sampling = self.sampling.read().await.get(&shard_id).copied();
+        let latencies: Vec<f64> = series
+            .timestamps
+            .iter()
+            .zip(&series.values)
+            .filter(|(at, _)| !sampling.is_some_and(|(from, to)| **at >= from && **at <= to))
+            .map(|(_, ms)| *ms)
+            .collect();
+        if latencies.len() < MIN_LATENCY_SAMPLES * 2 {
+            return None;
+        }
+        let (older, newer) = latencies.split_at(latencies.len() / 2);
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+        // Latencies are whole milliseconds; the extra one keeps sub-ms
+        // searches from reading as large swings
+        Some((mean(newer) + 1.0) / (mean(older) + 1.0))
+    }
+
+    /// Check every index once
+    pub async fn run_once(&self) -> Vec<IndexHealth> {
+        let mut reports = Vec::new();
+        for (shard_id, _) in self.shard_manager.get_vector_indices().await {
+            match self.check(shard_id).await {
+                Ok(health) => reports.push(health),
+                Err(e) => debug!("Skipping health check of shard {}: {}", shard_id, e),
+            }
+        }
+        self.metrics.increment_counter("index_health.runs", 1).await;
+        reports
+    }
+
+    /// Run checks in the background until the task is aborted
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        info!(
+            "Starting index health monitor (interval: {:?}, rebuild below: {})",
+            self.config.interval, self.config.rebuild_below
+        );
+
+        tasks::spawn("sharding", "index health monitor", async move {
+            loop {
+                tokio::time::sleep(self.config.interval).await;
+                self.run_once().await;
+            }
+        })
+    }
+}
+
+fn centroid(entries: &[VectorEntry], dimensions: usize) -> Option<Vec<f32>> {
+    if entries.is_empty() {
+        return None;
+    }
+    let mut sum = vec![0.0; dimensions];
+    for entry in entries {
+        for (total, value) in sum.iter_mut().zip(&entry.vector.values) {
+            *total += value;
+        }
+    }
+    Some(
+        sum.into_iter()
+            .map(|total| total / entries.len() as f32)
+            .collect(),
+    )
+}
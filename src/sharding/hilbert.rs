@@ -32,6 +32,11 @@ impl HilbertCurve {
         self.bits_per_dimension
     }
 
+    /// Get the number of dimensions the curve covers
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
     /// Convert a multidimensional point to its Hilbert index
     pub fn point_to_index(&self, point: &[u64]) -> u64 {
         assert_eq!(
@@ -49,7 +54,6 @@ impl HilbertCurve {
         }
 
         let mut index: u64 = 0;
-        let max_bit = 1 << (self.bits_per_dimension - 1);
 
         // For each bit position, from most significant to least significant
         for bit in (0..self.bits_per_dimension).rev() {
@@ -57,8 +61,8 @@ impl HilbertCurve {
             let mut current_bits = 0;
 
             // Extract the bit from each dimension
-            for dim in 0..self.dimensions {
-                if (point[dim] & bit_mask) != 0 {
+            for (dim, coordinate) in point.iter().enumerate().take(self.dimensions) {
+                if (coordinate & bit_mask) != 0 {
                     current_bits |= 1 << dim;
                 }
             }
@@ -86,9 +90,9 @@ impl HilbertCurve {
                 self.inverse_transform_bits(current_bits as usize, self.dimensions);
 
             // Set the appropriate bit in each dimension
-            for dim in 0..self.dimensions {
+            for (dim, coordinate) in point.iter_mut().enumerate() {
                 if (transformed_bits & (1 << dim)) != 0 {
-                    point[dim] |= 1 << bit;
+                    *coordinate |= 1 << bit;
                 }
             }
         }
@@ -99,7 +103,7 @@ impl HilbertCurve {
     /// Transform bits according to Hilbert curve rules
     fn transform_bits(&self, bits: usize, num_bits: usize) -> usize {
         let mut transformed = bits;
-        let mut temp;
+        let temp;
 
         // Apply Gray code transformation
         transformed ^= transformed >> 1;
@@ -119,7 +123,7 @@ impl HilbertCurve {
     /// Inverse transform bits to recover original position
     fn inverse_transform_bits(&self, bits: usize, num_bits: usize) -> usize {
         let mut transformed = bits;
-        let mut temp;
+        let temp;
 
         // Undo the bit manipulations for higher dimensions
         if num_bits >= 2 {
@@ -143,11 +147,7 @@ impl HilbertCurve {
         let index1 = self.point_to_index(point1);
         let index2 = self.point_to_index(point2);
 
-        if index1 > index2 {
-            index1 - index2
-        } else {
-            index2 - index1
-        }
+        index1.abs_diff(index2)
     }
 }
 
@@ -0,0 +1,420 @@
+//! Hierarchical navigable small world graph for approximate nearest
+//! neighbour search.
+//!
+//! Every vector is a node on a random number of layers, each layer holding
+//! exponentially fewer nodes than the one below. A search descends greedily
+//! from the sparse top layer to find a good starting point, then explores
+//! layer 0 keeping the `ef` closest nodes seen, so it visits a roughly
+//! logarithmic share of the index instead of scanning it.
+//!
+//! Removed vectors stay in the graph as tombstones so paths through them
+//! keep working. They are never returned, and the graph is rebuilt without
+//! them once they make up a quarter of its nodes.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::core::vector::Vector;
+use crate::sharding::vector_index::DistanceMetric;
+
+/// Share of tombstoned nodes at which the graph is rebuilt
+const MAX_TOMBSTONE_RATIO: f64 = 0.25;
+
+/// Graphs smaller than this are never rebuilt; searching past a few
+/// tombstones is cheaper than relinking
+const MIN_REBUILD_NODES: usize = 256;
+
+/// Layers above this are never assigned, whatever the dice say
+const MAX_LEVEL: usize = 16;
+
+/// Tunables of an HNSW graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HnswParams {
+    /// Links kept per node on the upper layers; layer 0 keeps twice as many.
+    /// Higher improves recall on high-dimensional data at the cost of memory.
+    pub m: usize,
+
+    /// Candidates considered when linking a new node; higher builds a
+    /// better graph, more slowly
+    pub ef_construction: usize,
+
+    /// Candidates kept while searching. Raised to the number a search wants
+    /// to score when that is larger.
+    pub ef_search: usize,
+}
+
+impl Default for HnswParams {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+        }
+    }
+}
+
+impl HnswParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.m < 2 {
+            return Err(format!("HNSW m must be at least 2, got {}", self.m));
+        }
+        if self.ef_construction == 0 || self.ef_search == 0 {
+            return Err("HNSW ef_construction and ef_search must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Node {
+    id: Uuid,
+    vector: Vector,
+    /// Neighbours on each layer the node is on, layer 0 first
+    links: Vec<Vec<u32>>,
+    removed: bool,
+}
+
+/// A node and its distance from whatever is being searched for
+#[derive(Debug, Clone, Copy)]
+struct Scored {
+    distance: f32,
+    node: u32,
+}
+
+impl PartialEq for Scored {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+/// Navigable graph over a set of vectors, compared with one metric
+#[derive(Debug)]
+pub struct HnswGraph {
+    params: HnswParams,
+    metric: DistanceMetric,
+    nodes: Vec<Node>,
+    /// Node of each vector that hasn't been removed
+    live: HashMap<Uuid, u32>,
+    /// Node on the top layer where every search starts
+    entry_point: Option<u32>,
+    /// Normalizes level assignment so each layer has about `1/m` of the
+    /// nodes of the one below
+    level_factor: f64,
+}
+
+impl HnswGraph {
+    pub fn new(params: HnswParams, metric: DistanceMetric) -> Self {
+        Self {
+            params,
+            metric,
+            nodes: Vec::new(),
+            live: HashMap::new(),
+            entry_point: None,
+            level_factor: 1.0 / (params.m.max(2) as f64).ln(),
+        }
+    }
+
+    pub fn params(&self) -> HnswParams {
+        self.params
+    }
+
+    /// Number of vectors searchable in the graph
+    pub fn len(&self) -> usize {
+        self.live.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.live.is_empty()
+    }
+
+    pub fn contains(&self, id: &Uuid) -> bool {
+        self.live.contains_key(id)
+    }
+
+    /// IDs of the vectors searchable in the graph
+    pub fn ids(&self) -> impl Iterator<Item = &Uuid> {
+        self.live.keys()
+    }
+
+    /// Structural problems that would make searches miss vectors or panic:
+    /// live IDs mapped to the wrong node, removed nodes still listed or live
+    /// ones not, links to missing nodes or layers, and an entry point that
+    /// isn't on the top layer
+    pub fn verify(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (id, &node) in &self.live {
+            match self.nodes.get(node as usize) {
+                Some(n) if n.id == *id && !n.removed => {}
+                _ => problems.push(format!("{} maps to node {}", id, node)),
+            }
+        }
+        let listed = self.nodes.iter().filter(|n| !n.removed).count();
+        if listed != self.live.len() {
+            problems.push(format!(
+                "{} live nodes but {} listed IDs",
+                listed,
+                self.live.len()
+            ));
+        }
+        for (index, node) in self.nodes.iter().enumerate() {
+            for (layer, links) in node.links.iter().enumerate() {
+                for &neighbour in links {
+                    match self.nodes.get(neighbour as usize) {
+                        Some(n) if n.links.len() > layer => {}
+                        _ => problems.push(format!(
+                            "node {} links to {} on layer {}",
+                            index, neighbour, layer
+                        )),
+                    }
+                }
+            }
+        }
+        match self.entry_point {
+            None if !self.nodes.is_empty() => problems.push("no entry point".to_string()),
+            Some(entry) if entry as usize >= self.nodes.len() => {
+                problems.push(format!("entry point {} doesn't exist", entry))
+            }
+            Some(entry) => {
+                let top = self.nodes.iter().map(|n| n.links.len()).max().unwrap_or(0);
+                if self.nodes[entry as usize].links.len() < top {
+                    problems.push(format!("entry point {} is below the top layer", entry));
+                }
+            }
+            None => {}
+        }
+        problems
+    }
+
+    /// Removed nodes still held for navigation
+    pub fn tombstones(&self) -> usize {
+        self.nodes.len() - self.live.len()
+    }
+
+    /// Add a vector, replacing any already in the graph under `id`
+    pub fn insert(&mut self, id: Uuid, vector: Vector) {
+        self.remove(&id);
+
+        let level = self.random_level();
+        let node = self.nodes.len() as u32;
+        self.nodes.push(Node {
+            id,
+            vector,
+            links: vec![Vec::new(); level + 1],
+            removed: false,
+        });
+        self.live.insert(id, node);
+
+        let Some(entry_point) = self.entry_point else {
+            self.entry_point = Some(node);
+            return;
+        };
+        let query = self.nodes[node as usize].vector.clone();
+        let top = self.level(entry_point);
+        let mut entry = vec![self.scored(&query, entry_point)];
+        for layer in (level + 1..=top).rev() {
+            entry = self.search_layer(&query, &entry, 1, layer);
+        }
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&query, &entry, self.params.ef_construction, layer);
+            let neighbours = self.select_neighbours(&found, self.params.m);
+            for &neighbour in &neighbours {
+                self.link(neighbour, node, layer);
+            }
+            self.nodes[node as usize].links[layer] = neighbours;
+            entry = found;
+        }
+        if level > top {
+            self.entry_point = Some(node);
+        }
+    }
+
+    /// Stop returning `id` from searches. Returns whether it was in the graph.
+    pub fn remove(&mut self, id: &Uuid) -> bool {
+        let Some(node) = self.live.remove(id) else {
+            return false;
+        };
+        self.nodes[node as usize].removed = true;
+
+        if self.live.is_empty() {
+            self.nodes.clear();
+            self.entry_point = None;
+        } else if self.nodes.len() >= MIN_REBUILD_NODES
+            && self.tombstones() as f64 > self.nodes.len() as f64 * MAX_TOMBSTONE_RATIO
+        {
+            self.rebuild();
+        }
+        true
+    }
+
+    /// The closest vectors to `query` found keeping `ef` candidates,
+    /// closest first
+    pub fn search(&self, query: &Vector, ef: usize) -> Vec<(Uuid, f32)> {
+        let Some(entry_point) = self.entry_point else {
+            return Vec::new();
+        };
+        let mut entry = vec![self.scored(query, entry_point)];
+        for layer in (1..=self.level(entry_point)).rev() {
+            entry = self.search_layer(query, &entry, 1, layer);
+        }
+        // Tombstones take places among the closest; widen the search so
+        // they don't crowd out live nodes
+        let ef = ef.max(1).saturating_mul(self.nodes.len()) / self.live.len();
+        self.search_layer(query, &entry, ef, 0)
+            .into_iter()
+            .filter(|scored| !self.nodes[scored.node as usize].removed)
+            .map(|scored| (self.nodes[scored.node as usize].id, scored.distance))
+            .collect()
+    }
+
+    /// Relink the live nodes from scratch, dropping tombstones
+    fn rebuild(&mut self) {
+        let nodes = std::mem::take(&mut self.nodes);
+        self.live.clear();
+        self.entry_point = None;
+        for node in nodes.into_iter().filter(|node| !node.removed) {
+            self.insert(node.id, node.vector);
+        }
+    }
+
+    /// Top layer of a node
+    fn level(&self, node: u32) -> usize {
+        self.nodes[node as usize].links.len() - 1
+    }
+
+    /// Layers 1 and up each hold about `1/m` of the nodes of the one below
+    fn random_level(&self) -> usize {
+        let uniform: f64 = 1.0 - rand::thread_rng().gen::<f64>();
+        ((-uniform.ln() * self.level_factor) as usize).min(MAX_LEVEL)
+    }
+
+    fn scored(&self, query: &Vector, node: u32) -> Scored {
+        Scored {
+            distance: self
+                .metric
+                .distance(query, &self.nodes[node as usize].vector),
+            node,
+        }
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.params.m * 2
+        } else {
+            self.params.m
+        }
+    }
+
+    /// The `ef` closest nodes to `query` reachable on `layer` from `entry`,
+    /// closest first
+    fn search_layer(
+        &self,
+        query: &Vector,
+        entry: &[Scored],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Scored> {
+        let mut visited: HashSet<u32> = entry.iter().map(|scored| scored.node).collect();
+        let mut candidates: BinaryHeap<Reverse<Scored>> =
+            entry.iter().copied().map(Reverse).collect();
+        // Farthest of the closest found on top
+        let mut closest: BinaryHeap<Scored> = entry.iter().copied().collect();
+        while closest.len() > ef {
+            closest.pop();
+        }
+
+        while let Some(Reverse(current)) = candidates.pop() {
+            let farthest = closest.peek().map_or(f32::INFINITY, |s| s.distance);
+            if closest.len() >= ef && current.distance > farthest {
+                break;
+            }
+            for &neighbour in &self.nodes[current.node as usize].links[layer] {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let scored = self.scored(query, neighbour);
+                let farthest = closest.peek().map_or(f32::INFINITY, |s| s.distance);
+                if closest.len() < ef || scored.distance < farthest {
+                    candidates.push(Reverse(scored));
+                    closest.push(scored);
+                    if closest.len() > ef {
+                        closest.pop();
+                    }
+                }
+            }
+        }
+        closest.into_sorted_vec()
+    }
+
+    /// Pick up to `max` of `candidates` (closest first), preferring ones
+    /// closer to the origin than to any neighbour already picked so links
+    /// spread out instead of all pointing into one cluster, then topping up
+    /// with the closest of the rest
+    fn select_neighbours(&self, candidates: &[Scored], max: usize) -> Vec<u32> {
+        let mut picked: Vec<u32> = Vec::with_capacity(max);
+        let mut skipped = Vec::new();
+        for candidate in candidates {
+            if picked.len() == max {
+                break;
+            }
+            let vector = &self.nodes[candidate.node as usize].vector;
+            let diverse = picked.iter().all(|&other| {
+                self.metric
+                    .distance(vector, &self.nodes[other as usize].vector)
+                    > candidate.distance
+            });
+            if diverse {
+                picked.push(candidate.node);
+            } else {
+                skipped.push(candidate.node);
+            }
+        }
+        let room = max - picked.len();
+        picked.extend(skipped.into_iter().take(room));
+        picked
+    }
+
+    /// Add a link from `from` to `to`, pruning `from`'s links on `layer`
+    /// back to the best ones when it has too many
+    fn link(&mut self, from: u32, to: u32, layer: usize) {
+        let max = self.max_links(layer);
+        let links = &mut self.nodes[from as usize].links[layer];
+        links.push(to);
+        if links.len() <= max {
+            return;
+        }
+
+        let origin = &self.nodes[from as usize].vector;
+        let mut scored: Vec<Scored> = self.nodes[from as usize].links[layer]
+            .iter()
+            .map(|&node| Scored {
+                distance: self
+                    .metric
+                    .distance(origin, &self.nodes[node as usize].vector),
+                node,
+            })
+            .collect();
+        scored.sort_unstable();
+        let kept = self.select_neighbours(&scored, max);
+        self.nodes[from as usize].links[layer] = kept;
+    }
+}
@@ -1,16 +1,58 @@
 use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::ops::RangeBounds;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
+use crate::connectors::EmbeddingMatrix;
 use crate::core::metrics::MetricsCollector;
 use crate::core::vector::Vector;
+use crate::embedding::EMBEDDING_MODEL_KEY;
+use crate::nerv::tasks;
+use crate::query::compose::{self, ComposeOp, ComposeTerm};
+use crate::query::fusion::{self, FusionStrategy};
+use crate::query::{
+    Diversification, ExecutionPlan, FacetRequest, Facets, MetadataFilter, QueryExpr, QueryPlanner,
+    SearchEstimate,
+};
+use crate::sharding::aggregates::{AggregateSnapshot, AggregateView, AggregateViewDefinition};
+use crate::sharding::autosplit::ShardSplit;
+use crate::sharding::backup::{BackupKind, BackupManifest, BackupStore};
+use crate::sharding::changefeed::{ChangeEvent, ChangeFeed, ChangeOp};
+use crate::sharding::coalesce::{CoalescingStats, SearchCoalescer, SearchKey};
+use crate::sharding::compression::CompressionConfig;
 use crate::sharding::migration::MigrationTask;
-use crate::sharding::vector_index::{DistanceMetric, VectorIndex};
-
-#[derive(Debug, Clone, PartialEq, Eq)]
+use crate::sharding::outliers::{self, OutlierParams, OutlierReport};
+use crate::sharding::purge::ShardPurge;
+use crate::sharding::query_cache::{QueryCache, QueryCacheConfig};
+use crate::sharding::rebalance::RangeMove;
+use crate::sharding::shadow::{RecordedSearch, ShadowRecorder};
+use crate::sharding::storage::{IndexRecord, ShardRecord, StorageBackend};
+use crate::sharding::tuning::LatencySlo;
+use crate::sharding::vector_index::{
+    DistanceMetric, IndexType, IntegrityReport, SearchOutcome, VectorEntry, VectorIndex,
+};
+use crate::tenancy::TenantKeyring;
+
+/// Change events buffered per live subscriber before it starts lagging
+const CHANGE_BUS_CAPACITY: usize = 1024;
+
+/// Vectors loaded from storage between updates of a shard's load progress
+const LOAD_SEGMENT_SIZE: usize = 4096;
+
+/// Aggregate views by name, shared by every shard of a split family
+type SharedViews = Arc<RwLock<HashMap<String, AggregateView>>>;
+
+/// A vector waiting in a batch, with its position in the input
+type BatchItem = (usize, Vector, Option<HashMap<String, String>>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ShardStatus {
     Active,
     ReadOnly,
@@ -29,6 +71,47 @@ pub struct Shard {
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// How a shard assigns IDs to vectors added without one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum IdScheme {
+    /// A fresh random ID per insert
+    #[default]
+    Random,
+    /// An ID hashed from the values and metadata (see
+    /// [`Vector::content_id`]). Re-adding the same content is a no-op, and
+    /// replicas that ingested it independently converge on one copy.
+    ContentAddressed,
+}
+
+/// Outcome of [`ShardManager::build_index`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexBuild {
+    /// The shard created to hold the index
+    pub shard_id: Uuid,
+    pub vectors: usize,
+    pub dimensions: usize,
+    pub threads: usize,
+    pub elapsed_ms: u64,
+}
+
+/// A shard being loaded from storage, searchable over what's loaded so far
+#[derive(Debug, Clone)]
+struct LoadProgress {
+    index: Arc<VectorIndex>,
+    loaded: usize,
+    total: usize,
+}
+
+impl LoadProgress {
+    fn coverage(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.loaded as f32 / self.total as f32).min(1.0)
+        }
+    }
+}
+
 /// Shard load information for balancing
 #[derive(Debug, Clone)]
 pub struct ShardLoad {
@@ -48,6 +131,32 @@ pub struct ShardManager {
     migrations: RwLock<HashMap<Uuid, MigrationTask>>,
     indices: RwLock<HashMap<Uuid, Arc<VectorIndex>>>,
     shard_loads: RwLock<HashMap<Uuid, ShardLoad>>,
+    aggregate_views: RwLock<HashMap<Uuid, SharedViews>>,
+    change_feeds: RwLock<HashMap<Uuid, Arc<ChangeFeed>>>,
+    query_cache: QueryCache,
+    coalescer: SearchCoalescer,
+    embedding_models: RwLock<HashMap<Uuid, String>>,
+    shadow_recorder: RwLock<Option<Arc<ShadowRecorder>>>,
+    tenants: RwLock<HashMap<Uuid, String>>,
+    keyring: RwLock<Option<Arc<TenantKeyring>>>,
+    id_schemes: RwLock<HashMap<Uuid, IdScheme>>,
+    compression: RwLock<HashMap<Uuid, CompressionConfig>>,
+    /// Shards split off each shard, as the Hilbert key each range starts
+    /// at, ascending
+    key_routes: RwLock<HashMap<Uuid, Vec<(u64, Uuid)>>>,
+    /// Every shard's change events, for live subscribers
+    change_bus: broadcast::Sender<ChangeEvent>,
+    /// Where shards are persisted, if anywhere
+    storage: Option<Arc<dyn StorageBackend>>,
+    /// Shards restored from storage whose vectors haven't been loaded yet
+    unloaded: RwLock<HashMap<Uuid, IndexRecord>>,
+    /// Restored shards whose vectors are being loaded
+    loading: RwLock<HashMap<Uuid, LoadProgress>>,
+    /// Shards changed since they were last flushed to storage
+    dirty: RwLock<HashSet<Uuid>>,
+    /// Held by flushes and shard deletes, so a flush can't write back a
+    /// shard as it is deleted
+    storage_lock: Mutex<()>,
 }
 
 impl ShardManager {
@@ -63,23 +172,188 @@ impl ShardManager {
             migrations: RwLock::new(HashMap::new()),
             indices: RwLock::new(HashMap::new()),
             shard_loads: RwLock::new(HashMap::new()),
+            aggregate_views: RwLock::new(HashMap::new()),
+            change_feeds: RwLock::new(HashMap::new()),
+            query_cache: QueryCache::new(QueryCacheConfig::default()),
+            coalescer: SearchCoalescer::new(true),
+            embedding_models: RwLock::new(HashMap::new()),
+            shadow_recorder: RwLock::new(None),
+            tenants: RwLock::new(HashMap::new()),
+            keyring: RwLock::new(None),
+            id_schemes: RwLock::new(HashMap::new()),
+            compression: RwLock::new(HashMap::new()),
+            key_routes: RwLock::new(HashMap::new()),
+            change_bus: broadcast::channel(CHANGE_BUS_CAPACITY).0,
+            storage: None,
+            unloaded: RwLock::new(HashMap::new()),
+            loading: RwLock::new(HashMap::new()),
+            dirty: RwLock::new(HashSet::new()),
+            storage_lock: Mutex::new(()),
+        }
+    }
+
+    /// Replace the search result cache settings
+    pub fn with_query_cache(mut self, config: QueryCacheConfig) -> Self {
+        self.query_cache = QueryCache::new(config);
+        self
+    }
+
+    /// Turn coalescing of identical concurrent searches on or off (on by
+    /// default)
+    pub fn with_search_coalescing(mut self, enabled: bool) -> Self {
+        self.coalescer = SearchCoalescer::new(enabled);
+        self
+    }
+
+    /// Persist shards to `storage` on [`flush`](Self::flush); call
+    /// [`restore`](Self::restore) to read back what it already holds
+    pub fn with_storage(mut self, storage: Arc<dyn StorageBackend>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Record a sample of searches for later replay against a candidate build
+    pub async fn enable_shadow_recording(&self, recorder: Arc<ShadowRecorder>) {
+        *self.shadow_recorder.write().await = Some(recorder);
+    }
+
+    pub async fn disable_shadow_recording(&self) {
+        *self.shadow_recorder.write().await = None;
+    }
+
+    /// Encrypt tenant shards' sensitive metadata fields with this keyring
+    pub async fn enable_tenant_encryption(&self, keyring: Arc<TenantKeyring>) {
+        *self.keyring.write().await = Some(keyring);
+    }
+
+    /// Mark a shard as holding one tenant's data
+    pub async fn assign_tenant(&self, shard_id: Uuid, tenant: &str) -> Result<()> {
+        self.get_shard(shard_id).await?;
+        self.tenants
+            .write()
+            .await
+            .insert(shard_id, tenant.to_string());
+        self.mark_dirty(shard_id).await;
+        info!("Assigned shard {} to tenant {}", shard_id, tenant);
+        Ok(())
+    }
+
+    /// Compress a shard's metadata values over the configured size, or stop
+    /// compressing new values with `None`. Values already stored are left as
+    /// they are and keep decoding transparently, so once enabled the shard
+    /// keeps decoding envelopes. Compression can't be enabled on a shard
+    /// already holding plain values that look like envelopes.
+    pub async fn set_metadata_compression(
+        &self,
+        shard_id: Uuid,
+        config: Option<CompressionConfig>,
+    ) -> Result<()> {
+        self.get_shard(shard_id).await?;
+        let index = self.indices.read().await.get(&shard_id).cloned();
+        let mut compression = self.compression.write().await;
+        match (config, compression.get(&shard_id)) {
+            (Some(config), previous) => {
+                if previous.is_none() {
+                    if let Some(index) = index {
+                        let statistics = index.statistics().await;
+                        let lookalikes = statistics.metadata_bytes.compressed_values;
+                        if lookalikes > 0 {
+                            return Err(anyhow!(
+                                "Shard {} holds {} metadata values that look compressed",
+                                shard_id,
+                                lookalikes
+                            ));
+                        }
+                    }
+                }
+                info!(
+                    "Compressing metadata over {} bytes on shard {} with {}",
+                    config.threshold_bytes,
+                    shard_id,
+                    config.codec.as_str()
+                );
+                compression.insert(shard_id, config);
+            }
+            (None, Some(previous)) => {
+                // Still decode, and still escape lookalike values
+                let decode_only = CompressionConfig {
+                    threshold_bytes: usize::MAX,
+                    ..previous.clone()
+                };
+                compression.insert(shard_id, decode_only);
+            }
+            (None, None) => {}
         }
+        drop(compression);
+        self.mark_dirty(shard_id).await;
+        Ok(())
+    }
+
+    pub async fn metadata_compression(&self, shard_id: Uuid) -> Option<CompressionConfig> {
+        self.compression.read().await.get(&shard_id).cloned()
+    }
+
+    /// Tenant owning a shard, if any
+    pub async fn shard_tenant(&self, shard_id: Uuid) -> Option<String> {
+        self.tenants.read().await.get(&shard_id).cloned()
+    }
+
+    /// Keyring and tenant for a shard with encryption enabled
+    async fn tenant_keyring(&self, shard_id: Uuid) -> Option<(Arc<TenantKeyring>, String)> {
+        let keyring = self.keyring.read().await.clone()?;
+        let tenant = self.shard_tenant(shard_id).await?;
+        Some((keyring, tenant))
+    }
+
+    pub fn query_cache(&self) -> &QueryCache {
+        &self.query_cache
+    }
+
+    /// How many searches were answered by an identical one already running
+    pub fn coalescing_stats(&self) -> CoalescingStats {
+        self.coalescer.stats()
+    }
+
+    /// Drop a shard's cached results and stop sharing its searches under
+    /// way, after a write to it
+    async fn invalidate_searches(&self, shard_id: Uuid) {
+        self.query_cache.invalidate(shard_id).await;
+        self.coalescer.invalidate(shard_id);
     }
 
     pub async fn create_shard(&self, name: &str) -> Result<Uuid> {
+        let shard_id = self.register_shard(name, None).await;
+
+        info!("Created new shard '{}' with ID: {}", name, shard_id);
+
+        Ok(shard_id)
+    }
+
+    /// Add a new shard, with its index if it already has one. The index is
+    /// in place before the shard is listed, so the shard is never seen
+    /// without it.
+    async fn register_shard(&self, name: &str, index: Option<Arc<VectorIndex>>) -> Uuid {
         let shard_id = Uuid::new_v4();
         let now = chrono::Utc::now();
+        let vector_count = match &index {
+            Some(index) => index.count().await,
+            None => 0,
+        };
 
         let shard = Shard {
             id: shard_id,
             name: name.to_string(),
             status: ShardStatus::Active,
             node_id: self.node_id.clone(),
-            vector_count: 0,
+            vector_count,
             created_at: now,
             updated_at: now,
         };
 
+        if let Some(index) = index {
+            self.indices.write().await.insert(shard_id, index);
+        }
+
         // Store the shard
         self.shards.write().await.insert(shard_id, shard.clone());
 
@@ -96,122 +370,1888 @@ impl ShardManager {
             shard_id,
             ShardLoad {
                 id: shard_id,
-                vector_count: 0,
+                vector_count,
                 query_rate: 0.0,
                 memory_usage_mb: 0.0,
                 cpu_usage_pct: 0.0,
             },
         );
 
+        // Initialize aggregate views
+        self.aggregate_views
+            .write()
+            .await
+            .insert(shard_id, Arc::new(RwLock::new(HashMap::new())));
+
+        // Initialize the change feed
+        self.change_feeds.write().await.insert(
+            shard_id,
+            Arc::new(ChangeFeed::new(shard_id).with_bus(self.change_bus.clone())),
+        );
+
+        self.mark_dirty(shard_id).await;
+
         // Update metrics
         self.metrics.increment_counter("shards.created", 1).await;
 
-        info!("Created new shard '{}' with ID: {}", name, shard_id);
+        shard_id
+    }
 
-        Ok(shard_id)
+    /// Create a shard named `name` whose index is built in bulk from
+    /// `embeddings` on `threads` threads (one per core by default), which
+    /// is much faster than adding the vectors one at a time. The shard
+    /// appears only once its index is complete. Each vector keeps its row
+    /// in the source as `source_id` metadata; bulk-loaded vectors aren't
+    /// published to the change feed.
+    pub async fn build_index(
+        &self,
+        name: &str,
+        index_name: &str,
+        distance_metric: DistanceMetric,
+        embeddings: EmbeddingMatrix,
+        threads: Option<usize>,
+    ) -> Result<IndexBuild> {
+        if embeddings.is_empty() {
+            return Err(anyhow!("No embeddings to build an index from"));
+        }
+        let threads = threads
+            .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+            .unwrap_or(1)
+            .max(1);
+        let dimensions = embeddings.dimensions();
+        let started = std::time::Instant::now();
+
+        let index_name = index_name.to_string();
+        let metrics = self.metrics.clone();
+        let index = tokio::task::spawn_blocking(move || {
+            let now = chrono::Utc::now();
+            let entries = embeddings
+                .rows()
+                .enumerate()
+                .map(|(row, values)| VectorEntry {
+                    id: Uuid::new_v4(),
+                    vector: Vector::new(values.to_vec()),
+                    metadata: Some(HashMap::from([("source_id".to_string(), row.to_string())])),
+                    created_at: now,
+                })
+                .collect();
+            VectorIndex::bulk_build(
+                &index_name,
+                dimensions,
+                distance_metric,
+                Some(metrics),
+                entries,
+                threads,
+            )
+        })
+        .await?
+        .map_err(|e| anyhow!("Failed to build vector index: {}", e))?;
+        let vectors = index.count().await;
+
+        let shard_id = self.register_shard(name, Some(Arc::new(index))).await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        self.metrics
+            .increment_counter("index_builds.completed", 1)
+            .await;
+        self.metrics
+            .increment_counter("index_builds.vectors", vectors as u64)
+            .await;
+        info!(
+            "Built shard '{}' ({}) from {} vectors in {} ms",
+            name, shard_id, vectors, elapsed_ms
+        );
+
+        Ok(IndexBuild {
+            shard_id,
+            vectors,
+            dimensions,
+            threads,
+            elapsed_ms,
+        })
+    }
+
+    pub async fn create_vector_index(
+        &self,
+        shard_id: Uuid,
+        name: &str,
+        dimensions: usize,
+        distance_metric: DistanceMetric,
+    ) -> Result<Arc<VectorIndex>> {
+        self.create_vector_index_with_type(
+            shard_id,
+            name,
+            dimensions,
+            distance_metric,
+            IndexType::default(),
+        )
+        .await
+    }
+
+    /// Create a vector index that finds candidates with `index_type`
+    pub async fn create_vector_index_with_type(
+        &self,
+        shard_id: Uuid,
+        name: &str,
+        dimensions: usize,
+        distance_metric: DistanceMetric,
+        index_type: IndexType,
+    ) -> Result<Arc<VectorIndex>> {
+        // Verify the shard exists
+        self.get_shard(shard_id).await?;
+
+        // Create the index
+        let index = VectorIndex::new(
+            name,
+            dimensions,
+            distance_metric,
+            Some(self.metrics.clone()),
+        )
+        .and_then(|index| index.with_index_type(index_type))
+        .map_err(|e| anyhow!("Failed to create vector index: {}", e))?;
+
+        let index = Arc::new(index);
+
+        // Store the index, replacing one not yet loaded from storage
+        self.unloaded.write().await.remove(&shard_id);
+        self.loading.write().await.remove(&shard_id);
+        self.indices.write().await.insert(shard_id, index.clone());
+        self.invalidate_searches(shard_id).await;
+        self.mark_dirty(shard_id).await;
+
+        info!(
+            "Created new {} vector index '{}' with {} dimensions for shard {}",
+            index_type.as_str(),
+            name,
+            dimensions,
+            shard_id
+        );
+
+        Ok(index)
+    }
+
+    /// A shard's vector index, loading it from storage on first access
+    /// after a lazy restore
+    pub async fn get_vector_index(&self, shard_id: Uuid) -> Result<Arc<VectorIndex>> {
+        if let Some(index) = self.indices.read().await.get(&shard_id) {
+            return Ok(index.clone());
+        }
+
+        self.load_index(shard_id)
+            .await?
+            .ok_or_else(|| anyhow!("Vector index not found for shard {}", shard_id))
+    }
+
+    /// A shard's vector index for searching, with the fraction of its
+    /// vectors loaded. A shard still being loaded from storage is returned
+    /// as it is rather than waited for; writes go through
+    /// [`get_vector_index`](Self::get_vector_index), which waits.
+    pub async fn searchable_index(&self, shard_id: Uuid) -> Result<(Arc<VectorIndex>, f32)> {
+        if let Some(index) = self.indices.read().await.get(&shard_id) {
+            return Ok((index.clone(), 1.0));
+        }
+        if let Some(progress) = self.loading.read().await.get(&shard_id) {
+            return Ok((progress.index.clone(), progress.coverage()));
+        }
+        Ok((self.get_vector_index(shard_id).await?, 1.0))
+    }
+
+    /// Tune a shard's search parameters at runtime to meet a latency SLO,
+    /// or stop tuning with `None`
+    pub async fn set_search_slo(&self, shard_id: Uuid, slo: Option<LatencySlo>) -> Result<()> {
+        let index = self.get_vector_index(shard_id).await?;
+        match &slo {
+            Some(slo) => info!(
+                "Tuning search on shard {} for p{:.0} <= {}ms",
+                shard_id,
+                slo.percentile * 100.0,
+                slo.target_ms
+            ),
+            None => info!("Stopped tuning search on shard {}", shard_id),
+        }
+        index.set_latency_slo(slo).await;
+        Ok(())
+    }
+
+    /// All loaded vector indices, keyed by the shard they belong to. Shards
+    /// restored lazily are left out until first accessed or
+    /// [`load_all`](Self::load_all) is called.
+    pub async fn get_vector_indices(&self) -> Vec<(Uuid, Arc<VectorIndex>)> {
+        self.indices
+            .read()
+            .await
+            .iter()
+            .map(|(id, index)| (*id, index.clone()))
+            .collect()
+    }
+
+    pub async fn add_vector(
+        &self,
+        shard_id: Uuid,
+        vector: Vector,
+        metadata: Option<HashMap<String, String>>,
+    ) -> Result<Uuid> {
+        self.ensure_writable(shard_id).await?;
+        let shard_id = self.route_vector(shard_id, &vector).await;
+        if self.id_scheme(shard_id).await == IdScheme::Random {
+            return self
+                .insert_vector(shard_id, Uuid::new_v4(), vector, metadata, None)
+                .await;
+        }
+
+        let id = vector.content_id(metadata.as_ref());
+        let index = self.get_vector_index(shard_id).await?;
+        if index.get(id).await.is_none() {
+            match self
+                .insert_vector(shard_id, id, vector, metadata, None)
+                .await
+            {
+                Ok(id) => return Ok(id),
+                // A concurrent insert of the same content got there first
+                Err(_) if index.get(id).await.is_some() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.metrics
+            .increment_counter("vectors.deduplicated", 1)
+            .await;
+        Ok(id)
+    }
+
+    /// Choose how vectors added to a shard without an ID are identified
+    pub async fn set_id_scheme(&self, shard_id: Uuid, scheme: IdScheme) -> Result<()> {
+        self.get_shard(shard_id).await?;
+        self.id_schemes.write().await.insert(shard_id, scheme);
+        self.mark_dirty(shard_id).await;
+        info!("Shard {} now assigns {:?} vector IDs", shard_id, scheme);
+        Ok(())
+    }
+
+    pub async fn id_scheme(&self, shard_id: Uuid) -> IdScheme {
+        self.id_schemes
+            .read()
+            .await
+            .get(&shard_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Add a vector under a caller-chosen ID, e.g. to keep IDs aligned
+    /// between side-by-side copies of a collection
+    pub async fn add_vector_with_id(
+        &self,
+        shard_id: Uuid,
+        id: Uuid,
+        vector: Vector,
+        metadata: Option<HashMap<String, String>>,
+    ) -> Result<Uuid> {
+        self.ensure_writable(shard_id).await?;
+        let shard_id = self.route_vector(shard_id, &vector).await;
+        self.insert_vector(shard_id, id, vector, metadata, None)
+            .await
+    }
+
+    /// Add many vectors in one call, e.g. a bulk upload. Dimensions are
+    /// checked for the whole batch before anything is written, and each
+    /// shard the batch lands in takes its locks once rather than per vector.
+    /// IDs are returned in input order; content-addressed duplicates share
+    /// one. If one shard of a split family fails the insert, the vectors
+    /// already written to the others are removed again.
+    pub async fn add_vectors_batch(
+        &self,
+        shard_id: Uuid,
+        vectors: Vec<(Vector, Option<HashMap<String, String>>)>,
+    ) -> Result<Vec<Uuid>> {
+        self.ensure_writable(shard_id).await?;
+        let dimensions = self.get_vector_index(shard_id).await?.dimensions();
+        if let Some(position) = vectors
+            .iter()
+            .position(|(vector, _)| vector.dimensions != dimensions)
+        {
+            return Err(anyhow!(
+                "Vector {} of the batch has {} dimensions, shard {} expects {}",
+                position,
+                vectors[position].0.dimensions,
+                shard_id,
+                dimensions
+            ));
+        }
+
+        // Once split, each vector goes to the family member owning its key.
+        // Groups are written in the order of their first vector.
+        let split = self.key_routes.read().await.contains_key(&shard_id);
+        let mut groups: Vec<(Uuid, Vec<BatchItem>)> = Vec::new();
+        let count = vectors.len();
+        for (position, (vector, metadata)) in vectors.into_iter().enumerate() {
+            let target = if split {
+                self.route_vector(shard_id, &vector).await
+            } else {
+                shard_id
+            };
+            match groups.iter_mut().find(|(id, _)| *id == target) {
+                Some((_, group)) => group.push((position, vector, metadata)),
+                None => groups.push((target, vec![(position, vector, metadata)])),
+            }
+        }
+
+        // Rejections found while preparing come before any write
+        let mut prepared = Vec::with_capacity(groups.len());
+        for (target, group) in groups {
+            prepared.push((target, self.prepare_batch(target, group).await?));
+        }
+
+        // A group can still fail to insert once earlier ones are written,
+        // in which case those are removed again
+        let mut ids = vec![Uuid::nil(); count];
+        let mut written = Vec::with_capacity(prepared.len());
+        for (target, (positions, entries)) in prepared {
+            let inserted: Vec<Uuid> = entries.iter().map(|entry| entry.id).collect();
+            if let Err(e) = self.insert_entries(target, entries).await {
+                self.roll_back_batch(written).await;
+                return Err(anyhow!(
+                    "Batch rolled back after shard {} failed: {}",
+                    target,
+                    e
+                ));
+            }
+            written.push((target, inserted));
+            for (position, id) in positions {
+                ids[position] = id;
+            }
+        }
+        self.metrics
+            .increment_counter("vectors.batch_inserted", count as u64)
+            .await;
+        Ok(ids)
+    }
+
+    /// Remove the vectors a failed batch already inserted, per shard. Ones
+    /// that can't be removed are logged and left in place.
+    async fn roll_back_batch(&self, written: Vec<(Uuid, Vec<Uuid>)>) {
+        let mut removed = 0;
+        for (shard_id, ids) in written {
+            for id in ids {
+                match self.delete_vector(shard_id, id, None).await {
+                    Ok(()) => removed += 1,
+                    Err(e) => warn!(
+                        "Failed to roll back vector {} of a batch in shard {}: {}",
+                        id, shard_id, e
+                    ),
+                }
+            }
+        }
+        self.metrics
+            .increment_counter("vectors.batch_rolled_back", removed)
+            .await;
+    }
+
+    /// Assign IDs and encode metadata for vectors bound for one shard, as
+    /// `add_vector` and `insert_vector` would one at a time. Returns each
+    /// input position's ID and the entries still to insert.
+    async fn prepare_batch(
+        &self,
+        shard_id: Uuid,
+        group: Vec<BatchItem>,
+    ) -> Result<(Vec<(usize, Uuid)>, Vec<VectorEntry>)> {
+        let index = self.get_vector_index(shard_id).await?;
+        let content_addressed = self.id_scheme(shard_id).await == IdScheme::ContentAddressed;
+        let compression = self.metadata_compression(shard_id).await;
+        let keyring = self.tenant_keyring(shard_id).await;
+        let created_at = chrono::Utc::now();
+
+        let mut positions = Vec::with_capacity(group.len());
+        let mut entries = Vec::with_capacity(group.len());
+        let mut seen = HashSet::new();
+        let mut deduplicated = 0;
+        for (position, vector, metadata) in group {
+            let id = if content_addressed {
+                vector.content_id(metadata.as_ref())
+            } else {
+                Uuid::new_v4()
+            };
+            positions.push((position, id));
+            if content_addressed && (!seen.insert(id) || index.get(id).await.is_some()) {
+                deduplicated += 1;
+                continue;
+            }
+
+            let mut metadata = self.check_embedding_model(shard_id, metadata).await?;
+            if let (Some(config), Some(metadata)) = (&compression, metadata.as_mut()) {
+                config.compress_metadata(metadata)?;
+            }
+            if let (Some((keyring, tenant)), Some(metadata)) = (&keyring, metadata.as_mut()) {
+                keyring.encrypt_metadata(tenant, id, metadata).await?;
+            }
+            entries.push(VectorEntry {
+                id,
+                vector,
+                metadata,
+                created_at,
+            });
+        }
+
+        if deduplicated > 0 {
+            self.metrics
+                .increment_counter("vectors.deduplicated", deduplicated)
+                .await;
+        }
+        Ok((positions, entries))
+    }
+
+    /// Batch counterpart of `insert_vector`: the index, views and change
+    /// feed are each locked once for all of `entries`
+    async fn insert_entries(&self, shard_id: Uuid, entries: Vec<VectorEntry>) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let index = self.get_vector_index(shard_id).await?;
+        let feed = self.change_feed(shard_id).await?;
+        let ops: Vec<ChangeOp> = entries
+            .iter()
+            .map(|entry| ChangeOp::Insert {
+                vector_id: entry.id,
+                values: entry.vector.values.clone(),
+                metadata: entry.metadata.clone(),
+            })
+            .collect();
+
+        // Held across the insert for the same reasons as in `insert_vector`
+        let views = self.shard_views(shard_id).await?;
+        let mut views = views.write().await;
+        index
+            .add_entries(entries)
+            .await
+            .map_err(|e| anyhow!("Failed to add vectors: {}", e))?;
+        for op in &ops {
+            if let ChangeOp::Insert { metadata, .. } = op {
+                for view in views.values_mut() {
+                    view.apply_insert(metadata.as_ref());
+                }
+            }
+        }
+        feed.append_all(ops, None).await;
+        drop(views);
+        self.invalidate_searches(shard_id).await;
+
+        self.set_vector_count(shard_id, index.count().await).await;
+        Ok(())
+    }
+
+    /// Shard of a split family that owns a vector's Hilbert key. Vectors
+    /// an index can't key are left to the insert to reject.
+    async fn route_vector(&self, shard_id: Uuid, vector: &Vector) -> Uuid {
+        let mut shard_id = shard_id;
+        loop {
+            let routes = match self.key_routes.read().await.get(&shard_id) {
+                Some(routes) => routes.clone(),
+                None => return shard_id,
+            };
+            let key = match self.get_vector_index(shard_id).await {
+                Ok(index) if index.dimensions() == vector.dimensions => index.hilbert_key(vector),
+                _ => return shard_id,
+            };
+            match routes.iter().rev().find(|(start, _)| *start <= key) {
+                Some((_, child)) => shard_id = *child,
+                None => return shard_id,
+            }
+        }
+    }
+
+    /// A shard and every shard split off it, directly or not
+    pub async fn shard_family(&self, shard_id: Uuid) -> Vec<Uuid> {
+        Self::family_of(&*self.key_routes.read().await, shard_id)
+    }
+
+    /// Shards split off a shard, with the Hilbert key each one's range
+    /// starts at
+    pub async fn key_routes(&self, shard_id: Uuid) -> Vec<(u64, Uuid)> {
+        self.key_routes
+            .read()
+            .await
+            .get(&shard_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Split a shard in two along its Hilbert key range. Vectors keyed at or
+    /// above the median move to a new shard while the original keeps
+    /// serving; writes and searches addressed to the original are routed
+    /// across both from the moment the split starts. The new shard shares
+    /// the original's aggregate views and copies its settings.
+    pub async fn split_shard(&self, shard_id: Uuid) -> Result<ShardSplit> {
+        let shard = self.get_shard(shard_id).await?;
+        let index = self.get_vector_index(shard_id).await?;
+
+        let mut keys: Vec<u64> = index
+            .entries()
+            .await
+            .iter()
+            .map(|entry| index.hilbert_key(&entry.vector))
+            .collect();
+        keys.sort_unstable();
+        let Some(&lowest) = keys.first() else {
+            return Err(anyhow!("Shard {} is empty and can't be split", shard_id));
+        };
+        // Keep every vector sharing the lowest key on this side
+        let median = keys[keys.len() / 2];
+        let split_key = if median > lowest {
+            median
+        } else {
+            keys.iter()
+                .copied()
+                .find(|key| *key > lowest)
+                .ok_or_else(|| anyhow!("Vectors in shard {} share one Hilbert key", shard_id))?
+        };
+
+        let new_shard_id = self
+            .create_shard(&format!("{}#{:x}", shard.name, split_key))
+            .await?;
+        self.create_vector_index_with_type(
+            new_shard_id,
+            &format!("{}#{:x}", index.name(), split_key),
+            index.dimensions(),
+            index.distance_metric(),
+            index.index_type(),
+        )
+        .await?;
+        let id_scheme = self.id_scheme(shard_id).await;
+        self.id_schemes
+            .write()
+            .await
+            .insert(new_shard_id, id_scheme);
+        if let Some(model) = self.embedding_model(shard_id).await {
+            self.embedding_models
+                .write()
+                .await
+                .insert(new_shard_id, model);
+        }
+        if let Some(config) = self.metadata_compression(shard_id).await {
+            self.compression.write().await.insert(new_shard_id, config);
+        }
+        if let Some(tenant) = self.shard_tenant(shard_id).await {
+            self.tenants.write().await.insert(new_shard_id, tenant);
+        }
+        let views = self.shard_views(shard_id).await?;
+        self.aggregate_views
+            .write()
+            .await
+            .insert(new_shard_id, views);
+
+        // Route first, so writes landing during the move already go to the
+        // new shard and the snapshot below catches every vector left behind
+        {
+            let mut routes = self.key_routes.write().await;
+            let routes = routes.entry(shard_id).or_default();
+            routes.push((split_key, new_shard_id));
+            routes.sort_by_key(|(start, _)| *start);
+        }
+
+        let moved = self
+            .migrate_range(shard_id, new_shard_id, split_key.., &AtomicUsize::new(0))
+            .await?;
+        let kept = index.count().await;
+        self.metrics.increment_counter("shards.split", 1).await;
+
+        info!(
+            "Split shard {} at Hilbert key {:x}: moved {} vectors to shard {}, kept {}",
+            shard_id, split_key, moved, new_shard_id, kept
+        );
+
+        Ok(ShardSplit {
+            shard_id,
+            new_shard_id,
+            split_key,
+            moved,
+            kept,
+            trigger: None,
+        })
+    }
+
+    /// Move the vectors of `from` whose Hilbert keys fall in `keys` to `to`,
+    /// counting each in `moved` as it goes. Routes must already send the
+    /// range to `to`, so writes landing during the move go there and the
+    /// snapshot taken here catches every vector left behind.
+    async fn migrate_range(
+        &self,
+        from: Uuid,
+        to: Uuid,
+        keys: impl RangeBounds<u64>,
+        moved: &AtomicUsize,
+    ) -> Result<usize> {
+        let index = self.get_vector_index(from).await?;
+        let new_index = self.get_vector_index(to).await?;
+
+        // Stored metadata is moved as-is, already compressed and encrypted,
+        // and the shared views already count it
+        let feed = self.change_feed(from).await?;
+        let new_feed = self.change_feed(to).await?;
+        // Each shard's views lock is held while its index and feed change,
+        // as in `insert_vector`, so a scrub sees both or neither. They're
+        // taken one at a time since split shards share theirs.
+        let views = self.shard_views(from).await?;
+        let new_views = self.shard_views(to).await?;
+        let mut count = 0;
+        for entry in index.entries().await {
+            if !keys.contains(&index.hilbert_key(&entry.vector)) {
+                continue;
+            }
+            let insert = ChangeOp::Insert {
+                vector_id: entry.id,
+                values: entry.vector.values.clone(),
+                metadata: entry.metadata.clone(),
+            };
+            {
+                let _views = new_views.write().await;
+                // Keeps its creation time, so retention ages it correctly
+                new_index
+                    .add_entry(entry.clone())
+                    .await
+                    .map_err(|e| anyhow!("Failed to move vector {}: {}", entry.id, e))?;
+                new_feed.append_from(insert, None).await;
+            }
+            let delete = ChangeOp::Delete {
+                vector_id: entry.id,
+            };
+            let removed = {
+                let _views = views.write().await;
+                let removed = index.remove(entry.id).await.is_ok();
+                if removed {
+                    feed.append_from(delete.clone(), None).await;
+                }
+                removed
+            };
+            if removed {
+                moved.fetch_add(1, Ordering::Relaxed);
+                count += 1;
+            } else {
+                let _views = new_views.write().await;
+                if new_index.remove(entry.id).await.is_ok() {
+                    // Deleted by a write that found it here first
+                    new_feed.append_from(delete, None).await;
+                }
+            }
+        }
+
+        self.set_vector_count(from, index.count().await).await;
+        self.set_vector_count(to, new_index.count().await).await;
+        self.invalidate_searches(from).await;
+        self.invalidate_searches(to).await;
+        Ok(count)
+    }
+
+    /// Shards that others were split off but that weren't split off any
+    /// themselves; each addresses a whole family
+    pub async fn family_roots(&self) -> Vec<Uuid> {
+        let routes = self.key_routes.read().await;
+        let children: HashSet<Uuid> = routes
+            .values()
+            .flat_map(|children| children.iter().map(|(_, child)| *child))
+            .collect();
+        routes
+            .keys()
+            .filter(|id| !children.contains(id))
+            .copied()
+            .collect()
+    }
+
+    /// The Hilbert key ranges of a split family, as the key each range
+    /// starts at and the member serving it, ascending. The first range
+    /// starts at 0; each member serves exactly one range.
+    pub async fn family_ranges(&self, root: Uuid) -> Vec<(u64, Uuid)> {
+        let routes = self.key_routes.read().await;
+        let mut starts = vec![0];
+        for member in Self::family_of(&routes, root) {
+            if let Some(children) = routes.get(&member) {
+                starts.extend(children.iter().map(|(start, _)| *start));
+            }
+        }
+        starts.sort_unstable();
+        starts.dedup();
+        starts
+            .into_iter()
+            .map(|start| (start, Self::owner_of(&routes, root, start)))
+            .collect()
+    }
+
+    fn family_of(routes: &HashMap<Uuid, Vec<(u64, Uuid)>>, root: Uuid) -> Vec<Uuid> {
+        let mut family = vec![root];
+        let mut next = 0;
+        while next < family.len() {
+            if let Some(children) = routes.get(&family[next]) {
+                family.extend(children.iter().map(|(_, child)| *child));
+            }
+            next += 1;
+        }
+        family
+    }
+
+    /// Member of `root`'s family serving a Hilbert key
+    fn owner_of(routes: &HashMap<Uuid, Vec<(u64, Uuid)>>, root: Uuid, key: u64) -> Uuid {
+        let mut shard_id = root;
+        while let Some((_, child)) = routes
+            .get(&shard_id)
+            .and_then(|children| children.iter().rev().find(|(start, _)| *start <= key))
+        {
+            shard_id = *child;
+        }
+        shard_id
+    }
+
+    /// Move the boundary between two neighbouring ranges of a split family
+    /// from `start` to `new_start`, which must stay strictly inside the
+    /// two ranges. Vectors between the old and new boundary migrate online
+    /// to the member now serving them, counted in `moved` as they go.
+    pub async fn move_range_boundary(
+        &self,
+        root: Uuid,
+        start: u64,
+        new_start: u64,
+        moved: &AtomicUsize,
+    ) -> Result<RangeMove> {
+        let ranges = self.family_ranges(root).await;
+        let position = ranges
+            .iter()
+            .position(|(range_start, _)| *range_start == start)
+            .filter(|position| *position > 0)
+            .ok_or_else(|| anyhow!("No range of shard {} starts at key {:x}", root, start))?;
+        let (lower_start, lower) = ranges[position - 1];
+        let upper = ranges[position].1;
+        let upper_end = ranges.get(position + 1).map(|(next, _)| *next);
+        if new_start <= lower_start || upper_end.is_some_and(|end| new_start >= end) {
+            return Err(anyhow!(
+                "Key {:x} is outside the ranges of shards {} and {}",
+                new_start,
+                lower,
+                upper
+            ));
+        }
+        if new_start == start {
+            return Err(anyhow!("Boundary is already at key {:x}", start));
+        }
+
+        // Route first, as a split does
+        let parent = {
+            let mut routes = self.key_routes.write().await;
+            let family = Self::family_of(&routes, root);
+            let parent = family
+                .into_iter()
+                .find(|member| {
+                    routes
+                        .get(member)
+                        .is_some_and(|children| children.contains(&(start, upper)))
+                })
+                .ok_or_else(|| anyhow!("No route to shard {} at key {:x}", upper, start))?;
+            let children = routes.get_mut(&parent).expect("parent has routes");
+            for route in children
+                .iter_mut()
+                .filter(|route| **route == (start, upper))
+            {
+                route.0 = new_start;
+            }
+            children.sort_by_key(|(start, _)| *start);
+            parent
+        };
+        self.mark_dirty(parent).await;
+
+        let (from, to) = if new_start < start {
+            (lower, upper)
+        } else {
+            (upper, lower)
+        };
+        let count = self
+            .migrate_range(from, to, start.min(new_start)..start.max(new_start), moved)
+            .await?;
+        self.metrics.increment_counter("shards.rebalanced", 1).await;
+        info!(
+            "Moved range boundary of shard {} from {:x} to {:x}: {} vectors from shard {} to {}",
+            root, start, new_start, count, from, to
+        );
+
+        Ok(RangeMove {
+            root,
+            from,
+            to,
+            boundary: start,
+            new_boundary: new_start,
+            moved: count,
+        })
+    }
+
+    /// Record a shard's size, also as the `shards.<id>.vector_count` gauge
+    async fn set_vector_count(&self, shard_id: Uuid, count: usize) {
+        if let Some(shard) = self.shards.write().await.get_mut(&shard_id) {
+            shard.vector_count = count;
+            shard.updated_at = chrono::Utc::now();
+        }
+        if let Some(load) = self.shard_loads.write().await.get_mut(&shard_id) {
+            load.vector_count = count;
+        }
+        self.metrics
+            .set_gauge(&format!("shards.{}.vector_count", shard_id), count as u64)
+            .await;
+        self.mark_dirty(shard_id).await;
+    }
+
+    /// Reject client writes to shards that aren't accepting them
+    async fn ensure_writable(&self, shard_id: Uuid) -> Result<()> {
+        let shard = self.get_shard(shard_id).await?;
+        match shard.status {
+            ShardStatus::Active | ShardStatus::Draining => Ok(()),
+            status => Err(anyhow!(
+                "Shard {} is not accepting writes (status {:?})",
+                shard_id,
+                status
+            )),
+        }
+    }
+
+    /// Apply a mutation replicated from another region. Replicated writes
+    /// bypass the read-only check so standbys can follow their primary.
+    /// Returns whether the mutation changed anything.
+    pub async fn apply_replicated_change(
+        &self,
+        shard_id: Uuid,
+        op: ChangeOp,
+        origin: &str,
+    ) -> Result<bool> {
+        let index = self.get_vector_index(shard_id).await?;
+        match op {
+            ChangeOp::Insert {
+                vector_id,
+                values,
+                metadata,
+            } => {
+                if index.get(vector_id).await.is_some() {
+                    return Ok(false);
+                }
+                self.insert_vector(
+                    shard_id,
+                    vector_id,
+                    Vector::new(values),
+                    metadata,
+                    Some(origin.to_string()),
+                )
+                .await?;
+                Ok(true)
+            }
+            ChangeOp::Delete { vector_id } => {
+                if index.get(vector_id).await.is_none() {
+                    return Ok(false);
+                }
+                self.delete_vector(shard_id, vector_id, Some(origin.to_string()))
+                    .await?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Restrict a shard to vectors from one embedding model (`model@version`).
+    /// Fails if the shard already holds vectors tagged with another model.
+    pub async fn bind_embedding_model(&self, shard_id: Uuid, model_id: &str) -> Result<()> {
+        self.get_shard(shard_id).await?;
+        if let Ok(index) = self.get_vector_index(shard_id).await {
+            let conflicting = index.entries().await.into_iter().find_map(|entry| {
+                entry
+                    .metadata
+                    .and_then(|m| m.get(EMBEDDING_MODEL_KEY).cloned())
+                    .filter(|m| m != model_id)
+            });
+            if let Some(other) = conflicting {
+                return Err(anyhow!(
+                    "Shard {} already contains vectors from model {}",
+                    shard_id,
+                    other
+                ));
+            }
+        }
+
+        self.embedding_models
+            .write()
+            .await
+            .insert(shard_id, model_id.to_string());
+        self.mark_dirty(shard_id).await;
+        info!("Bound shard {} to embedding model {}", shard_id, model_id);
+        Ok(())
+    }
+
+    /// Embedding model a shard is bound to, if any
+    pub async fn embedding_model(&self, shard_id: Uuid) -> Option<String> {
+        self.embedding_models.read().await.get(&shard_id).cloned()
+    }
+
+    /// Tag vectors for model-bound shards, rejecting ones from another model
+    async fn check_embedding_model(
+        &self,
+        shard_id: Uuid,
+        metadata: Option<HashMap<String, String>>,
+    ) -> Result<Option<HashMap<String, String>>> {
+        let bound = match self.embedding_model(shard_id).await {
+            Some(bound) => bound,
+            None => return Ok(metadata),
+        };
+
+        let mut metadata = metadata.unwrap_or_default();
+        match metadata.get(EMBEDDING_MODEL_KEY) {
+            Some(model) if *model != bound => Err(anyhow!(
+                "Vector from model {} can't be added to shard {} bound to {}",
+                model,
+                shard_id,
+                bound
+            )),
+            Some(_) => Ok(Some(metadata)),
+            None => {
+                metadata.insert(EMBEDDING_MODEL_KEY.to_string(), bound);
+                Ok(Some(metadata))
+            }
+        }
+    }
+
+    async fn insert_vector(
+        &self,
+        shard_id: Uuid,
+        id: Uuid,
+        vector: Vector,
+        metadata: Option<HashMap<String, String>>,
+        origin: Option<String>,
+    ) -> Result<Uuid> {
+        // Get the index
+        let index = self.get_vector_index(shard_id).await?;
+        let mut metadata = self.check_embedding_model(shard_id, metadata).await?;
+
+        // Replicated values arrive compressed and encrypted the way the
+        // primary stored them, so only local writes are encoded here
+        if let (None, Some(metadata)) = (&origin, metadata.as_mut()) {
+            // Large values are compressed before encryption, since
+            // ciphertext doesn't compress
+            if let Some(config) = self.metadata_compression(shard_id).await {
+                config.compress_metadata(metadata)?;
+            }
+
+            // Sensitive fields are stored, logged to the change feed and
+            // replicated only in encrypted form
+            if let Some((keyring, tenant)) = self.tenant_keyring(shard_id).await {
+                keyring.encrypt_metadata(&tenant, id, metadata).await?;
+            }
+        }
+
+        let feed = self.change_feed(shard_id).await?;
+
+        // Hold the view lock across the insert so a concurrent view backfill
+        // can't count this vector twice, and so change events are appended in
+        // the order mutations were applied
+        let views = self.shard_views(shard_id).await?;
+        let mut views = views.write().await;
+
+        // Add the vector
+        let values = vector.values.clone();
+        let id = index
+            .add_with_id(id, vector, metadata.clone())
+            .await
+            .map_err(|e| anyhow!("Failed to add vector: {}", e))?;
+
+        for view in views.values_mut() {
+            view.apply_insert(metadata.as_ref());
+        }
+        feed.append_from(
+            ChangeOp::Insert {
+                vector_id: id,
+                values,
+                metadata,
+            },
+            origin,
+        )
+        .await;
+        drop(views);
+        self.invalidate_searches(shard_id).await;
+
+        // Update shard vector count and load info
+        self.set_vector_count(shard_id, index.count().await).await;
+
+        Ok(id)
+    }
+
+    /// Remove a vector from a shard
+    pub async fn remove_vector(&self, shard_id: Uuid, vector_id: Uuid) -> Result<()> {
+        self.ensure_writable(shard_id).await?;
+        // Once split, the vector may live in any shard of the family
+        let mut holder = shard_id;
+        for member in self.shard_family(shard_id).await {
+            if let Ok(index) = self.get_vector_index(member).await {
+                if index.get(vector_id).await.is_some() {
+                    holder = member;
+                    break;
+                }
+            }
+        }
+        self.delete_vector(holder, vector_id, None).await
+    }
+
+    /// Permanently remove every vector whose metadata matches `filter`, in
+    /// all shards regardless of status, and scrub their inserts from each
+    /// shard's change feed, then flush storage. Encrypted fields are matched
+    /// on their plaintext; vectors whose metadata can't be decrypted are
+    /// skipped and reported.
+    pub async fn purge_matching(&self, filter: &QueryExpr) -> Result<Vec<ShardPurge>> {
+        // Nothing may escape the purge by not having been loaded yet
+        self.load_all().await?;
+        let mut purged = Vec::new();
+        for (shard_id, index) in self.get_vector_indices().await {
+            let plan = index.planner().await.plan(filter)?;
+            let tenant = self.shard_tenant(shard_id).await;
+            let (vector_ids, undecryptable) = self
+                .purge_candidates(
+                    shard_id,
+                    tenant.as_deref(),
+                    &plan,
+                    index.distance_metric(),
+                    index.entries().await,
+                )
+                .await;
+            if vector_ids.is_empty() && undecryptable.is_empty() {
+                continue;
+            }
+
+            for vector_id in &vector_ids {
+                self.delete_vector(shard_id, *vector_id, None).await?;
+            }
+            // Deleted entries stay in their segments as tombstones until a
+            // merge drops them
+            if !vector_ids.is_empty() {
+                index.merge_segments(true).await;
+            }
+            let ids: HashSet<Uuid> = vector_ids.iter().copied().collect();
+            let feed_events_scrubbed = self.change_feed(shard_id).await?.scrub(&ids).await;
+
+            info!(
+                "Purged {} vectors from shard {} ({} change events scrubbed)",
+                vector_ids.len(),
+                shard_id,
+                feed_events_scrubbed
+            );
+            purged.push(ShardPurge {
+                shard_id,
+                vector_ids,
+                feed_events_scrubbed,
+                undecryptable,
+            });
+        }
+
+        // Until the shards are written out, storage still holds the erased
+        // vectors and a restart would bring them back
+        self.flush().await?;
+
+        let total: usize = purged.iter().map(|p| p.vector_ids.len()).sum();
+        self.metrics
+            .increment_counter("vectors.purged", total as u64)
+            .await;
+        Ok(purged)
+    }
+
+    /// IDs among a backup's `entries` of the shard `record` describes that
+    /// a purge `filter` matches, tested as
+    /// [`purge_matching`](Self::purge_matching) tests live vectors, and the
+    /// IDs of those whose metadata couldn't be decrypted
+    pub async fn purge_matching_backup(
+        &self,
+        record: &ShardRecord,
+        entries: Vec<VectorEntry>,
+        filter: &QueryExpr,
+    ) -> Result<(Vec<Uuid>, Vec<Uuid>)> {
+        // Purge filters are metadata-only, so the metric never comes into it
+        let (dimensions, metric) = record
+            .index
+            .as_ref()
+            .map(|index| (index.dimensions, index.distance_metric))
+            .unwrap_or((0, DistanceMetric::Euclidean));
+        let plan = QueryPlanner::new(dimensions).plan(filter)?;
+        let tenant = match &record.tenant {
+            Some(tenant) => Some(tenant.clone()),
+            None => self.shard_tenant(record.id).await,
+        };
+        Ok(self
+            .purge_candidates(record.id, tenant.as_deref(), &plan, metric, entries)
+            .await)
+    }
+
+    /// Split `entries` into the IDs `plan` matches and those skipped because
+    /// their metadata couldn't be decrypted with `tenant`'s keys
+    async fn purge_candidates(
+        &self,
+        shard_id: Uuid,
+        tenant: Option<&str>,
+        plan: &ExecutionPlan,
+        metric: DistanceMetric,
+        entries: Vec<VectorEntry>,
+    ) -> (Vec<Uuid>, Vec<Uuid>) {
+        let keyring = self.keyring.read().await.clone();
+        let mut vector_ids = Vec::new();
+        let mut undecryptable = Vec::new();
+        for entry in entries {
+            let mut metadata = entry.metadata;
+            if let (Some(keyring), Some(tenant), Some(metadata)) =
+                (&keyring, tenant, metadata.as_mut())
+            {
+                if let Err(e) = keyring.decrypt_metadata(tenant, entry.id, metadata).await {
+                    warn!(
+                        "Skipping vector {} in shard {} during purge: {}",
+                        entry.id, shard_id, e
+                    );
+                    undecryptable.push(entry.id);
+                    continue;
+                }
+            }
+            if plan.matches(&entry.vector, metadata.as_ref(), metric) {
+                vector_ids.push(entry.id);
+            }
+        }
+        (vector_ids, undecryptable)
+    }
+
+    async fn delete_vector(
+        &self,
+        shard_id: Uuid,
+        vector_id: Uuid,
+        origin: Option<String>,
+    ) -> Result<()> {
+        let index = self.get_vector_index(shard_id).await?;
+        let feed = self.change_feed(shard_id).await?;
+
+        let views = self.shard_views(shard_id).await?;
+        let mut views = views.write().await;
+
+        let entry = index
+            .get(vector_id)
+            .await
+            .ok_or_else(|| anyhow!("Vector {} not found in shard {}", vector_id, shard_id))?;
+
+        index
+            .remove(vector_id)
+            .await
+            .map_err(|e| anyhow!("Failed to remove vector: {}", e))?;
+
+        for view in views.values_mut() {
+            view.apply_delete(entry.metadata.as_ref());
+        }
+        feed.append_from(ChangeOp::Delete { vector_id }, origin)
+            .await;
+        drop(views);
+        self.invalidate_searches(shard_id).await;
+
+        self.set_vector_count(shard_id, index.count().await).await;
+
+        Ok(())
+    }
+
+    /// Check a shard's index (see [`VectorIndex::verify_integrity`]) and
+    /// that every vector is in the state the last retained event for it in
+    /// the change feed says: present with the logged values and metadata
+    /// after an insert, absent after a delete. With `repair` set, diverged
+    /// vectors are restored from the feed.
+    pub async fn verify_shard(&self, shard_id: Uuid, repair: bool) -> Result<IntegrityReport> {
+        let index = self.get_vector_index(shard_id).await?;
+        let feed = self.change_feed(shard_id).await?;
+
+        // Mutations apply to the index and append to the feed under this
+        // lock, so holding it keeps the two in step for the whole check
+        let views = self.shard_views(shard_id).await?;
+        let mut views = views.write().await;
+
+        let mut report = index.verify_integrity(repair).await;
+        for (vector_id, op) in feed.latest_ops().await {
+            let stored = index.get(vector_id).await;
+            let agrees = match (&op, &stored) {
+                (
+                    ChangeOp::Insert {
+                        values, metadata, ..
+                    },
+                    Some(entry),
+                ) => entry.vector.values == *values && entry.metadata == *metadata,
+                (ChangeOp::Delete { .. }, None) => true,
+                _ => false,
+            };
+            if agrees {
+                continue;
+            }
+            report.diverged.push(vector_id);
+            if !repair {
+                continue;
+            }
+
+            if let Some(entry) = &stored {
+                index
+                    .remove(vector_id)
+                    .await
+                    .map_err(|e| anyhow!("Failed to remove vector: {}", e))?;
+                for view in views.values_mut() {
+                    view.apply_delete(entry.metadata.as_ref());
+                }
+            }
+            if let ChangeOp::Insert {
+                values, metadata, ..
+            } = op
+            {
+                for view in views.values_mut() {
+                    view.apply_insert(metadata.as_ref());
+                }
+                index
+                    .add_entry(VectorEntry {
+                        id: vector_id,
+                        vector: Vector::new(values),
+                        metadata,
+                        created_at: stored
+                            .map(|entry| entry.created_at)
+                            .unwrap_or_else(chrono::Utc::now),
+                    })
+                    .await
+                    .map_err(|e| anyhow!("Failed to restore vector: {}", e))?;
+            }
+            report.repaired += 1;
+        }
+        drop(views);
+
+        if repair && !report.diverged.is_empty() {
+            warn!(
+                "Restored {} vectors in shard {} from its change feed",
+                report.diverged.len(),
+                shard_id
+            );
+            self.invalidate_searches(shard_id).await;
+            self.set_vector_count(shard_id, index.count().await).await;
+        }
+        Ok(report)
+    }
+
+    /// Receive every shard's change events as they are appended. Unlike
+    /// the feeds, nothing is retained for subscribers that fall behind.
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.change_bus.subscribe()
+    }
+
+    /// Change feed recording every mutation applied to a shard
+    pub async fn change_feed(&self, shard_id: Uuid) -> Result<Arc<ChangeFeed>> {
+        self.change_feeds
+            .read()
+            .await
+            .get(&shard_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Shard with ID {} not found", shard_id))
+    }
+
+    async fn shard_views(&self, shard_id: Uuid) -> Result<SharedViews> {
+        self.aggregate_views
+            .read()
+            .await
+            .get(&shard_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Shard with ID {} not found", shard_id))
+    }
+
+    /// Define a materialized aggregate view over a shard's metadata. The view
+    /// is backfilled from the vectors already stored and then maintained on
+    /// every insert and delete.
+    pub async fn create_aggregate_view(
+        &self,
+        shard_id: Uuid,
+        definition: AggregateViewDefinition,
+    ) -> Result<AggregateSnapshot> {
+        if definition.name.is_empty() || definition.group_by.is_empty() {
+            return Err(anyhow!(
+                "Aggregate views require a name and a group_by field"
+            ));
+        }
+
+        let views = self.shard_views(shard_id).await?;
+        let mut views = views.write().await;
+        if views.contains_key(&definition.name) {
+            return Err(anyhow!(
+                "Aggregate view '{}' already exists for shard {}",
+                definition.name,
+                shard_id
+            ));
+        }
+
+        let mut view = AggregateView::new(definition.clone());
+        if let Ok(index) = self.get_vector_index(shard_id).await {
+            for entry in index.entries().await {
+                view.apply_insert(entry.metadata.as_ref());
+            }
+        }
+
+        let snapshot = view.snapshot();
+        views.insert(definition.name.clone(), view);
+
+        info!(
+            "Created aggregate view '{}' grouped by '{}' for shard {}",
+            definition.name, definition.group_by, shard_id
+        );
+
+        Ok(snapshot)
+    }
+
+    /// Current result of an aggregate view
+    pub async fn get_aggregate_view(
+        &self,
+        shard_id: Uuid,
+        view_name: &str,
+    ) -> Result<AggregateSnapshot> {
+        let views = self.shard_views(shard_id).await?;
+        let views = views.read().await;
+        views
+            .get(view_name)
+            .map(|view| view.snapshot())
+            .ok_or_else(|| anyhow!("Aggregate view '{}' not found", view_name))
+    }
+
+    /// Drop an aggregate view
+    pub async fn drop_aggregate_view(&self, shard_id: Uuid, view_name: &str) -> Result<()> {
+        let views = self.shard_views(shard_id).await?;
+        let removed = views.write().await.remove(view_name);
+        removed
+            .map(|_| ())
+            .ok_or_else(|| anyhow!("Aggregate view '{}' not found", view_name))
+    }
+
+    pub async fn search_vectors(
+        &self,
+        shard_id: Uuid,
+        query: &Vector,
+        limit: usize,
+    ) -> Result<Vec<crate::sharding::vector_index::SearchResult>> {
+        self.search_vectors_filtered(shard_id, query, limit, None)
+            .await
+    }
+
+    /// Search a shard, keeping only candidates that match a query DSL filter
+    pub async fn search_vectors_filtered(
+        &self,
+        shard_id: Uuid,
+        query: &Vector,
+        limit: usize,
+        filter: Option<&QueryExpr>,
+    ) -> Result<Vec<crate::sharding::vector_index::SearchResult>> {
+        self.search_filtered_until(shard_id, query, limit, filter, None)
+            .await
+            .map(|outcome| outcome.results)
+    }
+
+    /// Search a shard, keeping only candidates whose metadata matches
+    /// `metadata`, e.g. `category = product`
+    pub async fn search_vectors_matching(
+        &self,
+        shard_id: Uuid,
+        query: &Vector,
+        limit: usize,
+        metadata: &MetadataFilter,
+    ) -> Result<Vec<crate::sharding::vector_index::SearchResult>> {
+        self.search_vectors_filtered(shard_id, query, limit, metadata.to_expr().as_ref())
+            .await
+    }
+
+    /// Search a shard and every shard split off it, merging their results
+    async fn search_filtered_until(
+        &self,
+        shard_id: Uuid,
+        query: &Vector,
+        limit: usize,
+        filter: Option<&QueryExpr>,
+        deadline: Option<std::time::Instant>,
+    ) -> Result<SearchOutcome> {
+        let family = self.shard_family(shard_id).await;
+        if family.len() == 1 {
+            return self
+                .search_shard_until(shard_id, query, limit, filter, deadline)
+                .await;
+        }
+
+        let metric = self.searchable_index(shard_id).await?.0.distance_metric();
+        let mut merged = SearchOutcome::default();
+        let mut seen = HashSet::new();
+        // Coverage across the family, weighted by each member's size
+        let (mut searched, mut total) = (0.0, 0.0);
+        for member in family {
+            let outcome = self
+                .search_shard_until(member, query, limit, filter, deadline)
+                .await?;
+            merged.partial |= outcome.partial;
+            let size = self.get_shard(member).await.map_or(0, |s| s.vector_count) as f32;
+            searched += outcome.coverage * size;
+            total += size;
+            // A vector being moved by a split may briefly be in both shards
+            merged
+                .results
+                .extend(outcome.results.into_iter().filter(|r| seen.insert(r.id)));
+        }
+        merged.results.sort_by(|a, b| {
+            if metric.is_lower_better() {
+                a.score.total_cmp(&b.score)
+            } else {
+                b.score.total_cmp(&a.score)
+            }
+        });
+        merged.results.truncate(limit);
+        if total > 0.0 {
+            merged.coverage = searched / total;
+        }
+        Ok(merged)
+    }
+
+    async fn search_shard_until(
+        &self,
+        shard_id: Uuid,
+        query: &Vector,
+        limit: usize,
+        filter: Option<&QueryExpr>,
+        deadline: Option<std::time::Instant>,
+    ) -> Result<SearchOutcome> {
+        let started = std::time::Instant::now();
+        let mut partial = false;
+
+        // Get the index, as far as it's loaded
+        let (index, coverage) = self.searchable_index(shard_id).await?;
+
+        // Compile the filter against this index
+        let plan = match filter {
+            Some(expr) => Some(index.planner().await.plan(expr)?),
+            None => None,
+        };
+
+        // Search for vectors, serving repeated queries from the cache. The
+        // cache is bypassed until the shard is fully loaded.
+        let cached = if coverage < 1.0 {
+            Err(None)
+        } else {
+            self.query_cache.get(shard_id, query, limit, filter).await
+        };
+        let mut results = match cached {
+            Ok(results) => {
+                self.metrics.increment_counter("query_cache.hits", 1).await;
+                results
+            }
+            Err(ticket) => {
+                let search = || index.search_until(query, limit, plan.as_ref(), None, deadline);
+                // A shared search runs to its first request's deadline, so
+                // requests with a deadline of their own search alone
+                let (outcome, shared) = if deadline.is_none() {
+                    let key = SearchKey::new(shard_id, query, limit, filter);
+                    self.coalescer.run(key, search).await
+                } else {
+                    (search().await, false)
+                };
+                let outcome = outcome.map_err(|e| anyhow!("Failed to search vectors: {}", e))?;
+                self.metrics
+                    .increment_counter(
+                        if shared {
+                            "search_coalescing.coalesced"
+                        } else {
+                            "search_coalescing.executions"
+                        },
+                        1,
+                    )
+                    .await;
+                partial = outcome.partial;
+                if let Some(ticket) = ticket {
+                    self.metrics
+                        .increment_counter("query_cache.misses", 1)
+                        .await;
+                    // Partial results would be served as if they were
+                    // complete; shared ones were stored by their searcher
+                    if !partial && !shared {
+                        self.query_cache
+                            .insert(ticket, outcome.results.clone())
+                            .await;
+                    }
+                }
+                outcome.results
+            }
+        };
+
+        // Update query rate in shard load
+        {
+            let mut loads = self.shard_loads.write().await;
+            if let Some(load) = loads.get_mut(&shard_id) {
+                // Simple exponential moving average for query rate
+                load.query_rate = load.query_rate * 0.9 + 0.1; // Add one query
+            }
+        }
+
+        // Results stay encrypted in the cache and are decrypted per request
+        self.decode_results(shard_id, &mut results).await?;
+
+        let recorder = self.shadow_recorder.read().await.clone();
+        if let Some(recorder) = recorder.filter(|r| r.should_sample()) {
+            recorder
+                .record(RecordedSearch {
+                    shard_id,
+                    query: query.values.clone(),
+                    limit,
+                    filter: filter.cloned(),
+                    result_ids: results.iter().map(|r| r.id).collect(),
+                    latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+                    recorded_at: chrono::Utc::now(),
+                })
+                .await;
+        }
+
+        Ok(SearchOutcome {
+            results,
+            facets: None,
+            partial,
+            coverage,
+        })
+    }
+
+    /// Decrypt and decompress the metadata of results being returned
+    async fn decode_results(
+        &self,
+        shard_id: Uuid,
+        results: &mut [crate::sharding::vector_index::SearchResult],
+    ) -> Result<()> {
+        let keyring = self.tenant_keyring(shard_id).await;
+        let compression = self.metadata_compression(shard_id).await;
+        for result in results.iter_mut() {
+            let Some(metadata) = result.metadata.as_mut() else {
+                continue;
+            };
+            if let Some((keyring, tenant)) = &keyring {
+                keyring
+                    .decrypt_metadata(tenant, result.id, metadata)
+                    .await?;
+            }
+            if let Some(config) = &compression {
+                self.decompress_metadata(shard_id, config, metadata).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode a shard's compressed metadata values, leaving any that fail to
+    /// decode as stored rather than failing the whole read
+    async fn decompress_metadata(
+        &self,
+        shard_id: Uuid,
+        config: &CompressionConfig,
+        metadata: &mut HashMap<String, String>,
+    ) {
+        let undecodable = config.decompress_metadata(metadata);
+        if undecodable > 0 {
+            warn!(
+                "Returning {} undecodable metadata values on shard {} as stored",
+                undecodable, shard_id
+            );
+            self.metrics
+                .increment_counter("metadata.undecodable_values", undecodable as u64)
+                .await;
+        }
     }
 
-    pub async fn create_vector_index(
+    /// Search and count metadata values among the best candidates in the
+    /// same scan. Faceted searches bypass the result cache, and encrypted
+    /// fields are counted by ciphertext so they aren't useful as facets.
+    pub async fn search_vectors_faceted(
         &self,
         shard_id: Uuid,
-        name: &str,
-        dimensions: usize,
-        distance_metric: DistanceMetric,
-    ) -> Result<Arc<VectorIndex>> {
-        // Verify the shard exists
-        self.get_shard(shard_id).await?;
+        query: &Vector,
+        limit: usize,
+        filter: Option<&QueryExpr>,
+        diversify: &Diversification,
+        facets: &FacetRequest,
+    ) -> Result<(Vec<crate::sharding::vector_index::SearchResult>, Facets)> {
+        let outcome = self
+            .search_faceted_until(shard_id, query, limit, filter, diversify, facets, None)
+            .await?;
+        Ok((outcome.results, outcome.facets.unwrap_or_default()))
+    }
 
-        // Create the index
-        let index = VectorIndex::new(
-            name,
-            dimensions,
-            distance_metric,
-            Some(self.metrics.clone()),
-        )
-        .map_err(|e| anyhow!("Failed to create vector index: {}", e))?;
+    #[allow(clippy::too_many_arguments)]
+    async fn search_faceted_until(
+        &self,
+        shard_id: Uuid,
+        query: &Vector,
+        limit: usize,
+        filter: Option<&QueryExpr>,
+        diversify: &Diversification,
+        facets: &FacetRequest,
+        deadline: Option<std::time::Instant>,
+    ) -> Result<SearchOutcome> {
+        let (index, coverage) = self.searchable_index(shard_id).await?;
+        let plan = match filter {
+            Some(expr) => Some(index.planner().await.plan(expr)?),
+            None => None,
+        };
 
-        let index = Arc::new(index);
+        let mut outcome = index
+            .search_until(
+                query,
+                diversify.candidate_limit(limit),
+                plan.as_ref(),
+                Some(facets),
+                deadline,
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to search vectors: {}", e))?;
+        outcome.coverage = coverage;
+        self.decode_results(shard_id, &mut outcome.results).await?;
+        self.metrics.increment_counter("search.faceted", 1).await;
 
-        // Store the index
-        self.indices.write().await.insert(shard_id, index.clone());
+        outcome.results = diversify.apply(outcome.results, index.distance_metric(), limit);
+        outcome.facets = Some(outcome.facets.unwrap_or_default());
+        Ok(outcome)
+    }
 
-        info!(
-            "Created new vector index '{}' with {} dimensions for shard {}",
-            name, dimensions, shard_id
-        );
+    /// Search with `group_by` and MMR post-processing applied to an enlarged
+    /// candidate set, so near-duplicates don't crowd out other documents
+    pub async fn search_vectors_diversified(
+        &self,
+        shard_id: Uuid,
+        query: &Vector,
+        limit: usize,
+        filter: Option<&QueryExpr>,
+        diversify: &Diversification,
+    ) -> Result<Vec<crate::sharding::vector_index::SearchResult>> {
+        self.search_diversified_until(shard_id, query, limit, filter, diversify, None)
+            .await
+            .map(|outcome| outcome.results)
+    }
 
-        Ok(index)
+    async fn search_diversified_until(
+        &self,
+        shard_id: Uuid,
+        query: &Vector,
+        limit: usize,
+        filter: Option<&QueryExpr>,
+        diversify: &Diversification,
+        deadline: Option<std::time::Instant>,
+    ) -> Result<SearchOutcome> {
+        if !diversify.is_enabled() {
+            return self
+                .search_filtered_until(shard_id, query, limit, filter, deadline)
+                .await;
+        }
+        let metric = self.searchable_index(shard_id).await?.0.distance_metric();
+        let mut outcome = self
+            .search_filtered_until(
+                shard_id,
+                query,
+                diversify.candidate_limit(limit),
+                filter,
+                deadline,
+            )
+            .await?;
+        outcome.results = diversify.apply(outcome.results, metric, limit);
+        Ok(outcome)
     }
 
-    pub async fn get_vector_index(&self, shard_id: Uuid) -> Result<Arc<VectorIndex>> {
-        let indices = self.indices.read().await;
+    /// Search with optional facets and diversification, giving up on the
+    /// scan once `timeout` has passed. A search cut short returns the best
+    /// results among the candidates scored in time, flagged as partial.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn search_vectors_within(
+        &self,
+        shard_id: Uuid,
+        query: &Vector,
+        limit: usize,
+        filter: Option<&QueryExpr>,
+        diversify: &Diversification,
+        facets: Option<&FacetRequest>,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<SearchOutcome> {
+        let deadline = timeout.map(|t| std::time::Instant::now() + t);
+        let outcome = match facets {
+            Some(facets) => {
+                self.search_faceted_until(
+                    shard_id, query, limit, filter, diversify, facets, deadline,
+                )
+                .await?
+            }
+            None => {
+                self.search_diversified_until(shard_id, query, limit, filter, diversify, deadline)
+                    .await?
+            }
+        };
 
-        indices
-            .get(&shard_id)
-            .cloned()
-            .ok_or_else(|| anyhow!("Vector index not found for shard {}", shard_id))
+        if deadline.is_some() {
+            self.metrics
+                .increment_counter("search.with_timeout", 1)
+                .await;
+            if outcome.partial {
+                self.metrics.increment_counter("search.partial", 1).await;
+            }
+            // Share of searches with a timeout that returned partial results
+            let timed = self
+                .metrics
+                .get_counter("search.with_timeout")
+                .await
+                .unwrap_or(1);
+            let partial = self
+                .metrics
+                .get_counter("search.partial")
+                .await
+                .unwrap_or(0);
+            self.metrics
+                .set_gauge("search.partial_rate_pct", partial * 100 / timed.max(1))
+                .await;
+        }
+        Ok(outcome)
     }
 
-    pub async fn add_vector(
+    /// Search with several query vectors, e.g. for query expansion or one
+    /// vector per aspect of a query, and merge their results with `fusion`.
+    /// Facets are counted for the first query; the outcome is partial if any
+    /// of the searches was cut short.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn search_vectors_fused(
         &self,
         shard_id: Uuid,
-        vector: Vector,
-        metadata: Option<HashMap<String, String>>,
-    ) -> Result<Uuid> {
-        // Get the index
-        let index = self.get_vector_index(shard_id).await?;
-
-        // Add the vector
-        let id = index
-            .add(vector, metadata)
-            .await
-            .map_err(|e| anyhow!("Failed to add vector: {}", e))?;
+        queries: &[Vector],
+        limit: usize,
+        filter: Option<&QueryExpr>,
+        diversify: &Diversification,
+        facets: Option<&FacetRequest>,
+        timeout: Option<std::time::Duration>,
+        fusion: FusionStrategy,
+    ) -> Result<SearchOutcome> {
+        let (first, rest) = queries
+            .split_first()
+            .ok_or_else(|| anyhow!("At least one query vector is required"))?;
+        let lower_is_better = self
+            .searchable_index(shard_id)
+            .await?
+            .0
+            .distance_metric()
+            .is_lower_better();
+
+        let mut outcome = self
+            .search_vectors_within(shard_id, first, limit, filter, diversify, facets, timeout)
+            .await?;
+        if rest.is_empty() {
+            return Ok(outcome);
+        }
+        let mut lists = vec![std::mem::take(&mut outcome.results)];
+        for query in rest {
+            let other = self
+                .search_vectors_within(shard_id, query, limit, filter, diversify, None, timeout)
+                .await?;
+            outcome.partial |= other.partial;
+            outcome.coverage = outcome.coverage.min(other.coverage);
+            lists.push(other.results);
+        }
+        outcome.results = fusion::fuse(lists, fusion, lower_is_better, limit);
+        self.metrics.increment_counter("search.fused", 1).await;
+        Ok(outcome)
+    }
 
-        // Update shard vector count
-        {
-            let mut shards = self.shards.write().await;
-            if let Some(shard) = shards.get_mut(&shard_id) {
-                shard.vector_count = index.count().await;
-                shard.updated_at = chrono::Utc::now();
-            }
+    /// Estimate what [`search_vectors_fused`](Self::search_vectors_fused)
+    /// would cost with these arguments, without running it. Each query
+    /// vector is estimated against every shard the search would read;
+    /// cached results would make the real search cheaper.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn estimate_search(
+        &self,
+        shard_id: Uuid,
+        queries: &[Vector],
+        limit: usize,
+        filter: Option<&QueryExpr>,
+        diversify: &Diversification,
+        facets: Option<&FacetRequest>,
+        timeout: Option<Duration>,
+    ) -> Result<SearchEstimate> {
+        if queries.is_empty() {
+            return Err(anyhow!("At least one query vector is required"));
         }
+        // Faceted searches only read the shard addressed
+        let members = match facets {
+            Some(_) => vec![shard_id],
+            None => self.shard_family(shard_id).await,
+        };
+        let limit = diversify.candidate_limit(limit);
+
+        let mut estimate: Option<SearchEstimate> = None;
+        for query in queries {
+            let mut per_query: Option<SearchEstimate> = None;
+            for member in &members {
+                let index = self.get_vector_index(*member).await?;
+                let plan = match filter {
+                    Some(expr) => Some(index.planner().await.plan(expr)?),
+                    None => None,
+                };
+                let shard = index
+                    .estimate_search(query, limit, plan.as_ref())
+                    .await
+                    .map_err(|e| anyhow!("Failed to estimate search: {}", e))?;
+                match per_query.as_mut() {
+                    Some(per_query) => per_query.merge(shard),
+                    None => per_query = Some(shard),
+                }
+            }
 
-        // Update shard load info
-        {
-            let mut loads = self.shard_loads.write().await;
-            if let Some(load) = loads.get_mut(&shard_id) {
-                load.vector_count = index.count().await;
+            // Every query vector gets the full timeout
+            let mut per_query = per_query.expect("a search reads at least one shard");
+            per_query.likely_partial =
+                timeout.is_some_and(|t| per_query.estimated_latency_ms > t.as_secs_f64() * 1000.0);
+            match estimate.as_mut() {
+                Some(estimate) => estimate.merge(per_query),
+                None => estimate = Some(per_query),
             }
         }
 
-        Ok(id)
+        self.metrics.increment_counter("search.estimated", 1).await;
+        Ok(estimate.expect("queries is not empty"))
     }
 
-    pub async fn search_vectors(
+    /// Combine stored and literal vectors into a new vector, e.g. for
+    /// analogy queries. Stored terms are looked up in the shard's index.
+    pub async fn compose_vectors(
         &self,
         shard_id: Uuid,
-        query: &Vector,
-        limit: usize,
-    ) -> Result<Vec<crate::sharding::vector_index::SearchResult>> {
-        // Get the index
+        op: ComposeOp,
+        terms: &[ComposeTerm],
+        normalize: bool,
+    ) -> Result<Vector> {
         let index = self.get_vector_index(shard_id).await?;
+        let mut resolved = Vec::with_capacity(terms.len());
+        for term in terms {
+            let vector = match (&term.id, &term.vector) {
+                (Some(id), None) => index
+                    .get(*id)
+                    .await
+                    .map(|entry| entry.vector)
+                    .ok_or_else(|| anyhow!("Vector {} not found in shard {}", id, shard_id))?,
+                (None, Some(values)) => Vector::new(values.clone()),
+                _ => return Err(anyhow!("Each term needs exactly one of `id` or `vector`")),
+            };
+            resolved.push((vector, term.weight));
+        }
 
-        // Search for vectors
-        let results = index
-            .search(query, limit)
-            .await
-            .map_err(|e| anyhow!("Failed to search vectors: {}", e))?;
-
-        // Update query rate in shard load
-        {
-            let mut loads = self.shard_loads.write().await;
-            if let Some(load) = loads.get_mut(&shard_id) {
-                // Simple exponential moving average for query rate
-                load.query_rate = load.query_rate * 0.9 + 0.1; // Add one query
-            }
+        let composed = compose::compose(op, &resolved)?;
+        if composed.dimensions != index.dimensions() {
+            return Err(anyhow!(
+                "Composed vector has {} dimensions, index expects {}",
+                composed.dimensions,
+                index.dimensions()
+            ));
         }
+        self.metrics.increment_counter("vectors.composed", 1).await;
+        Ok(if normalize {
+            composed.normalize()
+        } else {
+            composed
+        })
+    }
 
-        Ok(results)
+    /// Score a shard's vectors for anomalies and return the most unusual,
+    /// for data quality review
+    pub async fn find_outliers(
+        &self,
+        shard_id: Uuid,
+        params: OutlierParams,
+    ) -> Result<OutlierReport> {
+        let index = self.get_vector_index(shard_id).await?;
+        let entries = index.entries().await;
+        let metric = index.distance_metric();
+        let report =
+            tokio::task::spawn_blocking(move || outliers::find_outliers(&entries, metric, &params))
+                .await??;
+        self.metrics.increment_counter("outliers.scans", 1).await;
+        Ok(report)
     }
 
     pub async fn get_shard(&self, shard_id: Uuid) -> Result<Shard> {
@@ -223,11 +2263,116 @@ impl ShardManager {
             .ok_or_else(|| anyhow!("Shard with ID {} not found", shard_id))
     }
 
+    /// Look up a shard by name
+    pub async fn get_shard_by_name(&self, name: &str) -> Result<Shard> {
+        let shards = self.shards.read().await;
+
+        shards
+            .values()
+            .find(|shard| shard.name == name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Shard named '{}' not found", name))
+    }
+
     pub async fn get_shards(&self) -> Vec<Shard> {
         let shards = self.shards.read().await;
         shards.values().cloned().collect()
     }
 
+    /// Delete a shard with its vectors, settings, views and change feed,
+    /// along with every shard split off it, here and in storage. Returns
+    /// the IDs deleted. A shard split off another can't be deleted on its
+    /// own, since the original still routes part of its key range there.
+    pub async fn delete_shard(&self, shard_id: Uuid) -> Result<Vec<Uuid>> {
+        self.get_shard(shard_id).await?;
+        if let Some(parent) = self
+            .key_routes
+            .read()
+            .await
+            .iter()
+            .find(|(_, routes)| routes.iter().any(|(_, child)| *child == shard_id))
+            .map(|(parent, _)| *parent)
+        {
+            return Err(anyhow!(
+                "Shard {} was split off shard {}; delete that instead",
+                shard_id,
+                parent
+            ));
+        }
+
+        let family = self.shard_family(shard_id).await;
+        let _storage = self.storage_lock.lock().await;
+        for member in &family {
+            self.shards.write().await.remove(member);
+            for assigned in self.shard_assignments.write().await.values_mut() {
+                assigned.remove(member);
+            }
+            self.indices.write().await.remove(member);
+            self.unloaded.write().await.remove(member);
+            self.loading.write().await.remove(member);
+            self.shard_loads.write().await.remove(member);
+            self.aggregate_views.write().await.remove(member);
+            self.change_feeds.write().await.remove(member);
+            self.embedding_models.write().await.remove(member);
+            self.tenants.write().await.remove(member);
+            self.id_schemes.write().await.remove(member);
+            self.compression.write().await.remove(member);
+            self.key_routes.write().await.remove(member);
+            self.dirty.write().await.remove(member);
+            self.invalidate_searches(*member).await;
+            if let Some(storage) = &self.storage {
+                storage.delete_shard(*member).await?;
+            }
+        }
+
+        self.metrics
+            .increment_counter("shards.deleted", family.len() as u64)
+            .await;
+        info!(
+            "Deleted shard {} ({} shards in all)",
+            shard_id,
+            family.len()
+        );
+        Ok(family)
+    }
+
+    /// Find a vector by ID, with its metadata decoded, and the shard holding
+    /// it. `shard_id` narrows the lookup to that shard and those split off
+    /// it; otherwise every shard is checked, loading any not yet loaded
+    /// since a lazy restore.
+    pub async fn find_vector(
+        &self,
+        vector_id: Uuid,
+        shard_id: Option<Uuid>,
+    ) -> Result<(Uuid, VectorEntry)> {
+        let candidates = match shard_id {
+            Some(shard_id) => {
+                self.get_shard(shard_id).await?;
+                self.shard_family(shard_id).await
+            }
+            None => self.get_shards().await.into_iter().map(|s| s.id).collect(),
+        };
+        for member in candidates {
+            let Ok(index) = self.get_vector_index(member).await else {
+                continue;
+            };
+            if let Some(mut entry) = index.get(vector_id).await {
+                if let Some(metadata) = entry.metadata.as_mut() {
+                    if let Some((keyring, tenant)) = self.tenant_keyring(member).await {
+                        keyring
+                            .decrypt_metadata(&tenant, vector_id, metadata)
+                            .await?;
+                    }
+                    if let Some(config) = self.metadata_compression(member).await {
+                        self.decompress_metadata(member, &config, metadata).await;
+                    }
+                }
+                return Ok((member, entry));
+            }
+        }
+        Err(anyhow!("Vector {} not found", vector_id))
+    }
+
     pub async fn update_shard_status(&self, shard_id: Uuid, status: ShardStatus) -> Result<()> {
         let mut shards = self.shards.write().await;
 
@@ -237,6 +2382,8 @@ impl ShardManager {
 
         shard.status = status.clone();
         shard.updated_at = chrono::Utc::now();
+        drop(shards);
+        self.mark_dirty(shard_id).await;
 
         info!("Updated shard {} status to {:?}", shard_id, status);
 
@@ -249,7 +2396,7 @@ impl ShardManager {
         target_node: &str,
     ) -> Result<Uuid> {
         // Verify the shard exists
-        let shard = self.get_shard(shard_id).await?;
+        self.get_shard(shard_id).await?;
 
         // Create migration task
         let migration_id = Uuid::new_v4();
@@ -271,12 +2418,16 @@ impl ShardManager {
             .await?;
 
         // Start the migration in the background
-        let self_clone = Arc::new(self.clone());
-        tokio::spawn(async move {
-            if let Err(e) = self_clone.execute_migration(migration_id).await {
-                error!("Migration {} failed: {}", migration_id, e);
-            }
-        });
+        let self_clone = self.clone();
+        tasks::spawn(
+            "sharding",
+            format!("migration {}", migration_id),
+            async move {
+                if let Err(e) = self_clone.execute_migration(migration_id).await {
+                    error!("Migration {} failed: {}", migration_id, e);
+                }
+            },
+        );
 
         info!(
             "Started migration {} for shard {} to node {}",
@@ -424,7 +2575,7 @@ impl ShardManager {
         let mut weighted_shards: Vec<(Uuid, f32)> = loads
             .values()
             .filter_map(|load| {
-                shards.get(&load.id).map(|shard| {
+                shards.get(&load.id).map(|_shard| {
                     // Calculate a weighted score based on resource usage
                     let weight = load.memory_usage_mb * 0.6
                         + load.cpu_usage_pct * 0.3
@@ -466,6 +2617,335 @@ impl ShardManager {
 
         Ok(distribution)
     }
+
+    /// Recreate the shards held in storage. With `lazy_load` each shard's
+    /// vectors are read on first access, otherwise all of them are loaded
+    /// now. Returns the number of shards restored.
+    pub async fn restore(&self, lazy_load: bool) -> Result<usize> {
+        let storage = self
+            .storage
+            .clone()
+            .ok_or_else(|| anyhow!("No storage backend configured"))?;
+        let records = storage.load_shards().await?;
+        let restored = records.len();
+        for record in records {
+            self.restore_shard(record).await;
+        }
+        info!(
+            "Restored {} shards from {} storage",
+            restored,
+            storage.name()
+        );
+
+        if !lazy_load {
+            self.load_all().await?;
+        }
+        Ok(restored)
+    }
+
+    async fn restore_shard(&self, record: ShardRecord) {
+        let shard_id = record.id;
+        self.shards.write().await.insert(
+            shard_id,
+            Shard {
+                id: shard_id,
+                name: record.name,
+                status: record.status,
+                node_id: self.node_id.clone(),
+                vector_count: record.vector_count,
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+            },
+        );
+        self.shard_assignments
+            .write()
+            .await
+            .entry(self.node_id.clone())
+            .or_default()
+            .insert(shard_id);
+        self.shard_loads.write().await.insert(
+            shard_id,
+            ShardLoad {
+                id: shard_id,
+                vector_count: record.vector_count,
+                query_rate: 0.0,
+                memory_usage_mb: 0.0,
+                cpu_usage_pct: 0.0,
+            },
+        );
+        self.aggregate_views
+            .write()
+            .await
+            .insert(shard_id, Arc::new(RwLock::new(HashMap::new())));
+        self.change_feeds.write().await.insert(
+            shard_id,
+            Arc::new(ChangeFeed::new(shard_id).with_bus(self.change_bus.clone())),
+        );
+
+        self.id_schemes
+            .write()
+            .await
+            .insert(shard_id, record.id_scheme);
+        if let Some(model) = record.embedding_model {
+            self.embedding_models.write().await.insert(shard_id, model);
+        }
+        if let Some(tenant) = record.tenant {
+            self.tenants.write().await.insert(shard_id, tenant);
+        }
+        if let Some(config) = record.compression {
+            self.compression.write().await.insert(shard_id, config);
+        }
+        if !record.key_routes.is_empty() {
+            self.key_routes
+                .write()
+                .await
+                .insert(shard_id, record.key_routes);
+        }
+        if let Some(index) = record.index {
+            self.unloaded.write().await.insert(shard_id, index);
+        }
+    }
+
+    /// Load the vectors of every restored shard not loaded yet. Every
+    /// pending shard is searchable from the start, over the vectors loaded
+    /// so far (see [`searchable_index`](Self::searchable_index)).
+    pub async fn load_all(&self) -> Result<()> {
+        let pending: Vec<(Uuid, IndexRecord)> = self
+            .unloaded
+            .read()
+            .await
+            .iter()
+            .map(|(id, record)| (*id, record.clone()))
+            .collect();
+        for (shard_id, record) in &pending {
+            self.begin_loading(*shard_id, record).await?;
+        }
+
+        for (shard_id, _) in &pending {
+            if let Err(e) = self.load_index(*shard_id).await {
+                // Shards left unloaded are loaded on first access instead
+                let mut loading = self.loading.write().await;
+                for (shard_id, _) in &pending {
+                    loading.remove(shard_id);
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Load every restored shard in the background, for starting up without
+    /// waiting on storage
+    pub fn warm_up(self: Arc<Self>) -> JoinHandle<()> {
+        tasks::spawn("sharding", "shard warm-up", async move {
+            let started = std::time::Instant::now();
+            match self.load_all().await {
+                Ok(()) => info!("Warmed up restored shards in {:?}", started.elapsed()),
+                Err(e) => error!("Failed to warm up restored shards: {}", e),
+            }
+        })
+    }
+
+    /// The empty index a restored shard's vectors are loaded into, made
+    /// searchable straight away
+    async fn begin_loading(
+        &self,
+        shard_id: Uuid,
+        record: &IndexRecord,
+    ) -> Result<Arc<VectorIndex>> {
+        if let Some(progress) = self.loading.read().await.get(&shard_id) {
+            return Ok(progress.index.clone());
+        }
+        let index = VectorIndex::new(
+            &record.name,
+            record.dimensions,
+            record.distance_metric,
+            Some(self.metrics.clone()),
+        )
+        .and_then(|index| index.with_index_type(record.index_type))
+        .map_err(|e| anyhow!("Failed to create vector index: {}", e))?;
+        let index = Arc::new(index);
+
+        let total = self.get_shard(shard_id).await.map_or(0, |s| s.vector_count);
+        self.loading.write().await.insert(
+            shard_id,
+            LoadProgress {
+                index: index.clone(),
+                loaded: 0,
+                total,
+            },
+        );
+        Ok(index)
+    }
+
+    /// Build a restored shard's index from its stored vectors. `None` if
+    /// the shard has no index to load.
+    async fn load_index(&self, shard_id: Uuid) -> Result<Option<Arc<VectorIndex>>> {
+        // Held for the whole load, so concurrent writers wait for it instead
+        // of loading the shard twice. Searches read the index as it fills.
+        let mut unloaded = self.unloaded.write().await;
+        let Some(record) = unloaded.get(&shard_id).cloned() else {
+            // Possibly loaded by the caller we just waited for
+            return Ok(self.indices.read().await.get(&shard_id).cloned());
+        };
+        let storage = self
+            .storage
+            .clone()
+            .ok_or_else(|| anyhow!("No storage backend configured"))?;
+
+        let index = self.begin_loading(shard_id, &record).await?;
+        if let Err(e) = self.fill_index(storage.as_ref(), shard_id, &index).await {
+            self.loading.write().await.remove(&shard_id);
+            return Err(e);
+        }
+
+        self.indices.write().await.insert(shard_id, index.clone());
+        self.loading.write().await.remove(&shard_id);
+        unloaded.remove(&shard_id);
+        self.metrics
+            .increment_counter("storage.shards_loaded", 1)
+            .await;
+        info!(
+            "Loaded {} vectors for shard {} from storage",
+            index.count().await,
+            shard_id
+        );
+        Ok(Some(index))
+    }
+
+    /// Add a shard's stored vectors to `index` a segment at a time,
+    /// recording progress after each
+    async fn fill_index(
+        &self,
+        storage: &dyn StorageBackend,
+        shard_id: Uuid,
+        index: &VectorIndex,
+    ) -> Result<()> {
+        let entries = storage.load_vectors(shard_id).await?;
+        let total = entries.len();
+        self.record_progress(shard_id, 0, total).await;
+        for (i, entry) in entries.into_iter().enumerate() {
+            index
+                .add_entry(entry)
+                .await
+                .map_err(|e| anyhow!("Failed to load vector into shard {}: {}", shard_id, e))?;
+            if (i + 1) % LOAD_SEGMENT_SIZE == 0 {
+                self.record_progress(shard_id, i + 1, total).await;
+                // Let searches in between segments
+                tokio::task::yield_now().await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn record_progress(&self, shard_id: Uuid, loaded: usize, total: usize) {
+        if let Some(progress) = self.loading.write().await.get_mut(&shard_id) {
+            progress.loaded = loaded;
+            progress.total = total;
+        }
+    }
+
+    /// Queue a shard to be written out on the next flush
+    async fn mark_dirty(&self, shard_id: Uuid) {
+        if self.storage.is_some() {
+            self.dirty.write().await.insert(shard_id);
+        }
+    }
+
+    /// Write every shard changed since the last flush to storage. Returns
+    /// the number of shards written; without storage this does nothing.
+    pub async fn flush(&self) -> Result<usize> {
+        let Some(storage) = self.storage.clone() else {
+            return Ok(0);
+        };
+        let _storage = self.storage_lock.lock().await;
+        // Shards changed while this runs are marked again and written next
+        // time
+        let dirty: Vec<Uuid> = self.dirty.write().await.drain().collect();
+        for (i, shard_id) in dirty.iter().enumerate() {
+            if let Err(e) = self.flush_shard(storage.as_ref(), *shard_id).await {
+                self.dirty.write().await.extend(&dirty[i..]);
+                return Err(e);
+            }
+        }
+        storage.flush().await?;
+
+        if !dirty.is_empty() {
+            self.metrics
+                .increment_counter("storage.shards_flushed", dirty.len() as u64)
+                .await;
+        }
+        self.metrics.increment_counter("storage.flushes", 1).await;
+        Ok(dirty.len())
+    }
+
+    /// Flush, then back up storage to `backups`; an incremental backup
+    /// holds only what changed since the latest backup there
+    pub async fn backup(&self, backups: &BackupStore, kind: BackupKind) -> Result<BackupManifest> {
+        let storage = self
+            .storage
+            .clone()
+            .ok_or_else(|| anyhow!("No storage backend configured"))?;
+        self.flush().await?;
+        // Flushes wait, so no shard is rewritten while it is copied
+        let _storage = self.storage_lock.lock().await;
+        backups.create(storage.as_ref(), kind).await
+    }
+
+    async fn flush_shard(&self, storage: &dyn StorageBackend, shard_id: Uuid) -> Result<()> {
+        // Deleted since it was marked, e.g. by a write racing the delete
+        let Ok(shard) = self.get_shard(shard_id).await else {
+            return Ok(());
+        };
+        let loaded = self.indices.read().await.get(&shard_id).cloned();
+        let index = match &loaded {
+            Some(index) => Some(IndexRecord {
+                name: index.name().to_string(),
+                dimensions: index.dimensions(),
+                distance_metric: index.distance_metric(),
+                index_type: index.index_type(),
+            }),
+            None => self.unloaded.read().await.get(&shard_id).cloned(),
+        };
+
+        let record = ShardRecord {
+            id: shard_id,
+            name: shard.name,
+            status: shard.status,
+            vector_count: shard.vector_count,
+            created_at: shard.created_at,
+            updated_at: shard.updated_at,
+            index,
+            id_scheme: self.id_scheme(shard_id).await,
+            embedding_model: self.embedding_model(shard_id).await,
+            tenant: self.shard_tenant(shard_id).await,
+            compression: self.metadata_compression(shard_id).await,
+            key_routes: self.key_routes(shard_id).await,
+        };
+        storage.save_shard(&record).await?;
+
+        // Vectors of a shard never loaded since the restore are unchanged
+        if let Some(index) = loaded {
+            storage
+                .save_vectors(shard_id, &index.entries().await)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Flush changed shards every `interval` until the task is aborted
+    pub fn start_flushing(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        info!("Flushing shards to storage every {:?}", interval);
+
+        tasks::spawn("sharding", "storage flusher", async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                if let Err(e) = self.flush().await {
+                    error!("Failed to flush shards to storage: {}", e);
+                }
+            }
+        })
+    }
 }
 
 // Support cloning for the manager to allow sharing between threads
@@ -482,6 +2962,23 @@ impl Clone for ShardManager {
             migrations: RwLock::new(HashMap::new()),
             indices: RwLock::new(HashMap::new()),
             shard_loads: RwLock::new(HashMap::new()),
+            aggregate_views: RwLock::new(HashMap::new()),
+            change_feeds: RwLock::new(HashMap::new()),
+            query_cache: QueryCache::new(self.query_cache.config().clone()),
+            coalescer: SearchCoalescer::new(self.coalescer.enabled()),
+            embedding_models: RwLock::new(HashMap::new()),
+            shadow_recorder: RwLock::new(None),
+            tenants: RwLock::new(HashMap::new()),
+            keyring: RwLock::new(None),
+            id_schemes: RwLock::new(HashMap::new()),
+            compression: RwLock::new(HashMap::new()),
+            key_routes: RwLock::new(HashMap::new()),
+            change_bus: broadcast::channel(CHANGE_BUS_CAPACITY).0,
+            storage: self.storage.clone(),
+            unloaded: RwLock::new(HashMap::new()),
+            loading: RwLock::new(HashMap::new()),
+            dirty: RwLock::new(HashSet::new()),
+            storage_lock: Mutex::new(()),
         }
     }
-}
\ No newline at end of file
+}
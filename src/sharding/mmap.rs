@@ -0,0 +1,292 @@
+//! Memory-mapped vector files.
+//!
+//! Segments of an index configured with [`MmapStorage`] write their vectors
+//! to a flat file and read them back through a memory map. Vector data then
+//! only occupies page cache, so an index can hold more than fits in RAM and
+//! the OS pages rows in as searches touch them. Metadata stays on the heap.
+//!
+//! A file is a 24-byte header (`RFVEC001`, dimensions as u32, the header's
+//! CRC32C as u32, row count as u64) followed by fixed-size rows: a 16-byte
+//! ID, the vector's values as little-endian f32s and a CRC32C of both. Row
+//! checksums are verified on every read, so a damaged page surfaces as an
+//! error instead of a wrong vector.
+
+use anyhow::{anyhow, Result};
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use uuid::Uuid;
+
+use crate::core::checksum::{self, ChecksumError};
+
+const MAGIC: &[u8; 8] = b"RFVEC001";
+const HEADER_LEN: usize = 24;
+const HEADER_CRC_OFFSET: u64 = 12;
+const ROW_COUNT_OFFSET: u64 = 16;
+
+/// Where an index keeps memory-mapped segment files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MmapStorage {
+    /// Directory for segment files; created if missing
+    pub dir: PathBuf,
+
+    /// Newest segments locked into RAM with `mlock`, so searches over
+    /// recently written data never wait on disk. Zero leaves every segment
+    /// to the OS's paging.
+    pub pin_hot_segments: usize,
+}
+
+impl MmapStorage {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            pin_hot_segments: 0,
+        }
+    }
+
+    pub fn with_pinned_segments(mut self, count: usize) -> Self {
+        self.pin_hot_segments = count;
+        self
+    }
+}
+
+/// How a mapped file is about to be read, passed on as an `madvise` hint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessPattern {
+    /// Full scans: read ahead aggressively and drop pages behind the scan
+    Sequential,
+    /// Point lookups: don't read ahead
+    Random,
+    /// About to be read; start paging it in
+    WillNeed,
+}
+
+/// Writes rows to a new vector file
+pub struct MmapVectorWriter {
+    path: PathBuf,
+    out: BufWriter<File>,
+    dimensions: usize,
+    rows: u64,
+}
+
+impl MmapVectorWriter {
+    pub fn create<P: AsRef<Path>>(path: P, dimensions: usize) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path)
+            .map_err(|e| anyhow!("Failed to create {}: {}", path.display(), e))?;
+        let mut out = BufWriter::new(file);
+        out.write_all(MAGIC)?;
+        out.write_all(&(dimensions as u32).to_le_bytes())?;
+        out.write_all(&0u32.to_le_bytes())?;
+        out.write_all(&0u64.to_le_bytes())?;
+        Ok(Self {
+            path,
+            out,
+            dimensions,
+            rows: 0,
+        })
+    }
+
+    pub fn push(&mut self, id: Uuid, values: &[f32]) -> Result<()> {
+        if values.len() != self.dimensions {
+            return Err(anyhow!(
+                "Row has {} dimensions, file expects {}",
+                values.len(),
+                self.dimensions
+            ));
+        }
+        let mut row = Vec::with_capacity(row_len(self.dimensions));
+        row.extend_from_slice(id.as_bytes());
+        for value in values {
+            row.extend_from_slice(&value.to_le_bytes());
+        }
+        let crc = checksum::crc32c(&row);
+        row.extend_from_slice(&crc.to_le_bytes());
+        self.out.write_all(&row)?;
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Record the row count and header checksum, then map the finished file
+    pub fn finish(mut self) -> Result<MmapVectorFile> {
+        let crc = header_crc(self.dimensions as u32, self.rows);
+        self.out.seek(SeekFrom::Start(HEADER_CRC_OFFSET))?;
+        self.out.write_all(&crc.to_le_bytes())?;
+        self.out.write_all(&self.rows.to_le_bytes())?;
+        self.out.flush()?;
+        drop(self.out);
+        MmapVectorFile::open(&self.path)
+    }
+}
+
+/// Read-only memory map of a vector file
+#[derive(Debug)]
+pub struct MmapVectorFile {
+    path: PathBuf,
+    mmap: Mmap,
+    dimensions: usize,
+    rows: usize,
+    pinned: AtomicBool,
+    remove_on_drop: bool,
+}
+
+impl MmapVectorFile {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file =
+            File::open(&path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+        // SAFETY: vector files are written once and never modified while
+        // mapped; the owning index is the only writer
+        let mmap = unsafe { Mmap::map(&file) }
+            .map_err(|e| anyhow!("Failed to map {}: {}", path.display(), e))?;
+
+        if mmap.len() < HEADER_LEN || &mmap[..8] != MAGIC {
+            return Err(anyhow!("{} is not a vector file", path.display()));
+        }
+        let dimensions = u32::from_le_bytes(mmap[8..12].try_into()?);
+        let stored_crc = u32::from_le_bytes(mmap[12..16].try_into()?);
+        let rows_at = ROW_COUNT_OFFSET as usize;
+        let rows = u64::from_le_bytes(mmap[rows_at..rows_at + 8].try_into()?);
+        if header_crc(dimensions, rows) != stored_crc {
+            return Err(anyhow!("{} has a corrupted header", path.display()));
+        }
+        let (dimensions, rows) = (dimensions as usize, rows as usize);
+        let expected = HEADER_LEN + rows * row_len(dimensions);
+        if mmap.len() != expected {
+            return Err(anyhow!(
+                "{} is {} bytes, expected {} for {} rows",
+                path.display(),
+                mmap.len(),
+                expected,
+                rows
+            ));
+        }
+
+        Ok(Self {
+            path,
+            mmap,
+            dimensions,
+            rows,
+            pinned: AtomicBool::new(false),
+            remove_on_drop: false,
+        })
+    }
+
+    /// Delete the file once this map is dropped
+    pub fn remove_on_drop(mut self) -> Self {
+        self.remove_on_drop = true;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Raw bytes of a row, checksum included; panics if `row` is out of
+    /// range
+    pub fn row_bytes(&self, row: usize) -> &[u8] {
+        let start = HEADER_LEN + row * row_len(self.dimensions);
+        &self.mmap[start..start + row_len(self.dimensions)]
+    }
+
+    /// Check a row against its checksum; panics if `row` is out of range
+    pub fn verify(&self, row: usize) -> Result<(), ChecksumError> {
+        let bytes = self.row_bytes(row);
+        let (data, crc) = bytes.split_at(bytes.len() - 4);
+        checksum::verify(data, u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]]))
+    }
+
+    /// ID and values stored in a row, once its checksum has been verified;
+    /// panics if `row` is out of range
+    pub fn read(&self, row: usize) -> Result<(Uuid, Vec<f32>), ChecksumError> {
+        self.verify(row)?;
+        let bytes = self.row_bytes(row);
+        let id = Uuid::from_slice(&bytes[..16]).expect("rows hold 16-byte IDs");
+        let values = bytes[16..bytes.len() - 4]
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        Ok((id, values))
+    }
+
+    /// Tell the OS how the file is about to be read. A no-op where `madvise`
+    /// isn't available.
+    pub fn advise(&self, pattern: AccessPattern) -> std::io::Result<()> {
+        #[cfg(unix)]
+        {
+            use memmap2::Advice;
+            let advice = match pattern {
+                AccessPattern::Sequential => Advice::Sequential,
+                AccessPattern::Random => Advice::Random,
+                AccessPattern::WillNeed => Advice::WillNeed,
+            };
+            self.mmap.advise(advice)
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = pattern;
+            Ok(())
+        }
+    }
+
+    /// Lock the file's pages into RAM. Fails when the process exceeds its
+    /// locked-memory limit (`ulimit -l`).
+    pub fn pin(&self) -> std::io::Result<()> {
+        if self.is_pinned() {
+            return Ok(());
+        }
+        #[cfg(unix)]
+        self.mmap.lock()?;
+        self.pinned.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn unpin(&self) -> std::io::Result<()> {
+        if !self.is_pinned() {
+            return Ok(());
+        }
+        #[cfg(unix)]
+        self.mmap.unlock()?;
+        self.pinned.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.pinned.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for MmapVectorFile {
+    fn drop(&mut self) {
+        if self.remove_on_drop {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn row_len(dimensions: usize) -> usize {
+    16 + dimensions * 4 + 4
+}
+
+fn header_crc(dimensions: u32, rows: u64) -> u32 {
+    let mut header = Vec::with_capacity(HEADER_LEN - 4);
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&dimensions.to_le_bytes());
+    header.extend_from_slice(&rows.to_le_bytes());
+    checksum::crc32c(&header)
+}
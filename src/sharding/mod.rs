@@ -1,4 +1,24 @@
+pub mod aggregates;
+pub mod autosplit;
+pub mod backup;
+pub mod changefeed;
+pub mod coalesce;
+pub mod compression;
+pub mod health;
 pub mod hilbert;
+pub mod hnsw;
 pub mod manager;
 pub mod migration;
+pub mod mmap;
+pub mod outliers;
+pub mod purge;
+pub mod query_cache;
+pub mod rebalance;
+pub mod retention;
+pub mod scrubber;
+pub mod segments;
+pub mod shadow;
+pub mod sketch;
+pub mod storage;
+pub mod tuning;
 pub mod vector_index;
@@ -0,0 +1,294 @@
+//! Outlier and novelty scoring for a shard's vectors.
+//!
+//! Each vector gets an anomaly score under the index's distance metric,
+//! either the distance to its k-th nearest neighbor (`knn`) or the distance
+//! to the nearest of a small set of k-means centroids (`centroid`). Scores
+//! are also reported as z-scores against the shard's distribution, so one
+//! threshold works across shards with different scales.
+//!
+//! Exact kNN is quadratic, so on large shards neighbors are drawn from an
+//! evenly spaced reference sample of at most [`MAX_REFERENCE`] vectors.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::core::vector::Vector;
+use crate::sharding::vector_index::{DistanceMetric, VectorEntry};
+
+/// Upper bound on the vectors each one is compared against
+pub const MAX_REFERENCE: usize = 2048;
+
+/// Lloyd iterations run for the `centroid` method
+const KMEANS_ITERATIONS: usize = 10;
+
+/// Clusters holding less than this share of the vectors (or a single vector)
+/// are treated as outliers rather than as clusters by the `centroid` method
+const MIN_CLUSTER_SHARE: f32 = 0.01;
+
+/// z-score above which a vector counts towards the outlier fraction
+pub const OUTLIER_Z_SCORE: f32 = 3.0;
+
+/// How vectors are scored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutlierMethod {
+    /// Distance to the k-th nearest neighbor
+    #[default]
+    Knn,
+    /// Distance to the nearest k-means centroid
+    Centroid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlierParams {
+    #[serde(default)]
+    pub method: OutlierMethod,
+
+    /// Neighbor rank used by `knn`
+    #[serde(default = "default_k")]
+    pub k: usize,
+
+    /// Centroids fitted by `centroid`
+    #[serde(default = "default_clusters")]
+    pub clusters: usize,
+
+    /// Most anomalous vectors returned
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+
+    /// Only return vectors at least this many standard deviations above the
+    /// mean score
+    #[serde(default)]
+    pub min_z_score: Option<f32>,
+}
+
+fn default_k() -> usize {
+    5
+}
+
+fn default_clusters() -> usize {
+    8
+}
+
+fn default_limit() -> usize {
+    10
+}
+
+impl Default for OutlierParams {
+    fn default() -> Self {
+        Self {
+            method: OutlierMethod::default(),
+            k: default_k(),
+            clusters: default_clusters(),
+            limit: default_limit(),
+            min_z_score: None,
+        }
+    }
+}
+
+/// A scored vector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Outlier {
+    pub id: Uuid,
+    pub score: f32,
+    pub z_score: f32,
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlierReport {
+    pub method: OutlierMethod,
+    /// Vectors scored
+    pub scanned: usize,
+    pub mean_score: f32,
+    pub std_dev: f32,
+    /// Share of scanned vectors above [`OUTLIER_Z_SCORE`]
+    pub outlier_fraction: f32,
+    /// Most anomalous first
+    pub outliers: Vec<Outlier>,
+}
+
+impl OutlierReport {
+    /// Summary in the shape the hypothesis engine consumes
+    pub fn signals(&self) -> HashMap<String, f32> {
+        let max_z_score = self.outliers.first().map(|o| o.z_score).unwrap_or(0.0);
+        HashMap::from([
+            ("outlier_fraction".to_string(), self.outlier_fraction),
+            ("max_outlier_z_score".to_string(), max_z_score),
+            ("outlier_scanned".to_string(), self.scanned as f32),
+        ])
+    }
+}
+
+/// Score every entry and return the most anomalous
+pub fn find_outliers(
+    entries: &[VectorEntry],
+    metric: DistanceMetric,
+    params: &OutlierParams,
+) -> Result<OutlierReport> {
+    let scores = match params.method {
+        OutlierMethod::Knn => {
+            if params.k == 0 {
+                return Err(anyhow!("k must be greater than zero"));
+            }
+            knn_scores(entries, metric, params.k)
+        }
+        OutlierMethod::Centroid => {
+            if params.clusters == 0 {
+                return Err(anyhow!("clusters must be greater than zero"));
+            }
+            centroid_scores(entries, metric, params.clusters)
+        }
+    };
+
+    let n = scores.len().max(1) as f32;
+    let mean_score = scores.iter().sum::<f32>() / n;
+    let variance = scores.iter().map(|s| (s - mean_score).powi(2)).sum::<f32>() / n;
+    let std_dev = variance.sqrt();
+    let z_score = |score: f32| {
+        if std_dev > f32::EPSILON {
+            (score - mean_score) / std_dev
+        } else {
+            0.0
+        }
+    };
+
+    let mut outliers: Vec<Outlier> = entries
+        .iter()
+        .zip(&scores)
+        .map(|(entry, &score)| Outlier {
+            id: entry.id,
+            score,
+            z_score: z_score(score),
+            metadata: entry.metadata.clone(),
+        })
+        .collect();
+    let flagged = outliers
+        .iter()
+        .filter(|o| o.z_score > OUTLIER_Z_SCORE)
+        .count();
+    if let Some(min_z_score) = params.min_z_score {
+        outliers.retain(|o| o.z_score >= min_z_score);
+    }
+    outliers.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.id.cmp(&b.id)));
+    outliers.truncate(params.limit);
+
+    Ok(OutlierReport {
+        method: params.method,
+        scanned: entries.len(),
+        mean_score,
+        std_dev,
+        outlier_fraction: flagged as f32 / n,
+        outliers,
+    })
+}
+
+/// Distance from each entry to its k-th nearest neighbor among the
+/// reference sample, or to the farthest one when there are fewer
+fn knn_scores(entries: &[VectorEntry], metric: DistanceMetric, k: usize) -> Vec<f32> {
+    let step = entries.len().div_ceil(MAX_REFERENCE).max(1);
+    let reference: Vec<&VectorEntry> = entries.iter().step_by(step).collect();
+    entries
+        .iter()
+        .map(|entry| {
+            let mut distances: Vec<f32> = reference
+                .iter()
+                .filter(|other| other.id != entry.id)
+                .map(|other| metric.distance(&entry.vector, &other.vector))
+                .collect();
+            if distances.is_empty() {
+                return 0.0;
+            }
+            let rank = k.min(distances.len()) - 1;
+            let (_, kth, _) = distances.select_nth_unstable_by(rank, f32::total_cmp);
+            *kth
+        })
+        .collect()
+}
+
+/// Distance from each entry to the nearest of `clusters` k-means centroids.
+/// Centroids of clusters too small to count (see [`MIN_CLUSTER_SHARE`]) are
+/// ignored, so a few far-off vectors can't claim a centroid of their own and
+/// score zero.
+fn centroid_scores(entries: &[VectorEntry], metric: DistanceMetric, clusters: usize) -> Vec<f32> {
+    let vectors: Vec<&Vector> = entries.iter().map(|entry| &entry.vector).collect();
+    let mut centroids = kmeans(&vectors, metric, clusters);
+    let min_size = ((vectors.len() as f32 * MIN_CLUSTER_SHARE) as usize).max(2);
+    let counts = cluster_sizes(&centroids, &vectors, metric);
+    if counts.iter().any(|&count| count >= min_size) {
+        centroids = centroids
+            .into_iter()
+            .zip(counts)
+            .filter(|(_, count)| *count >= min_size)
+            .map(|(centroid, _)| centroid)
+            .collect();
+    }
+    vectors
+        .iter()
+        .map(|vector| nearest(&centroids, vector, metric).1)
+        .collect()
+}
+
+fn cluster_sizes(centroids: &[Vector], vectors: &[&Vector], metric: DistanceMetric) -> Vec<usize> {
+    let mut counts = vec![0; centroids.len()];
+    for vector in vectors {
+        counts[nearest(centroids, vector, metric).0] += 1;
+    }
+    counts
+}
+
+fn nearest(centroids: &[Vector], vector: &Vector, metric: DistanceMetric) -> (usize, f32) {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(i, centroid)| (i, metric.distance(centroid, vector)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, 0.0))
+}
+
+/// Lloyd's k-means with deterministic farthest-first seeding
+fn kmeans(vectors: &[&Vector], metric: DistanceMetric, clusters: usize) -> Vec<Vector> {
+    let Some(first) = vectors.first() else {
+        return Vec::new();
+    };
+    let mut centroids = vec![(*first).clone()];
+    while centroids.len() < clusters.min(vectors.len()) {
+        let farthest = vectors
+            .iter()
+            .map(|v| nearest(&centroids, v, metric).1)
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match farthest {
+            Some((i, distance)) if distance > 0.0 => centroids.push(vectors[i].clone()),
+            _ => break,
+        }
+    }
+
+    let dimensions = first.dimensions;
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut sums = vec![vec![0.0f32; dimensions]; centroids.len()];
+        let mut counts = vec![0usize; centroids.len()];
+        for vector in vectors {
+            let (i, _) = nearest(&centroids, vector, metric);
+            counts[i] += 1;
+            for (sum, value) in sums[i].iter_mut().zip(&vector.values) {
+                *sum += value;
+            }
+        }
+        let mut moved = false;
+        for ((centroid, sum), count) in centroids.iter_mut().zip(sums).zip(counts) {
+            if count == 0 {
+                continue;
+            }
+            let mean = Vector::new(sum.into_iter().map(|s| s / count as f32).collect());
+            moved |= mean.values != centroid.values;
+            *centroid = mean;
+        }
+        if !moved {
+            break;
+        }
+    }
+    centroids
+}
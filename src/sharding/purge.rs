@@ -0,0 +1,373 @@
+//! Permanent removal of personal data on request.
+//!
+//! A purge takes a metadata predicate such as `user_id = X`, removes every
+//! matching vector from all shards, compacts their segments, scrubs their
+//! inserts from the change feeds that serve as each shard's write-ahead log,
+//! and returns a [`PurgeCertificate`] signed with the node's purge key. The
+//! certificate is also appended to the audit log file so the erasure can be
+//! proven later; the service refuses to purge with an audit log that isn't
+//! persisted. A pending entry is written before anything is erased, so a
+//! purge whose certificate never reached the log still shows up there.
+//! Shards are flushed to storage before the certificate is signed.
+//!
+//! When the service knows the backup directory, the filter is also tested
+//! against every vector in every backup, decrypted as the live ones are, so
+//! data deleted from the shards since it was backed up is found too. Every
+//! backup holding a match is rewritten without it before the certificate is
+//! signed, and the certificate lists the backups rewritten.
+//!
+//! The certificate carries a digest of the filter rather than the filter
+//! itself, so it doesn't retain the identifier that was erased. The digest is
+//! keyed with a key derived from the node's purge key, so it can't be
+//! brute-forced back to the identifier by anyone without that key. Records whose
+//! encrypted fields can't be decrypted can't be tested against the filter;
+//! they are left in place and listed in the certificate for follow-up.
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::core::audit::AuditLog;
+use crate::query::{PredicateOp, QueryExpr};
+use crate::sharding::backup::BackupStore;
+use crate::sharding::manager::ShardManager;
+
+/// Body of `POST /api/admin/purge`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeRequest {
+    /// Metadata predicate selecting the vectors to erase
+    pub filter: QueryExpr,
+    /// Why the purge was requested, e.g. a ticket reference
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// What a purge removed from one shard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShardPurge {
+    pub shard_id: Uuid,
+    pub vector_ids: Vec<Uuid>,
+    pub feed_events_scrubbed: usize,
+    /// Vectors skipped because their metadata couldn't be decrypted
+    #[serde(default)]
+    pub undecryptable: Vec<Uuid>,
+}
+
+/// The signed part of a purge certificate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeRecord {
+    pub id: Uuid,
+    pub issued_at: chrono::DateTime<chrono::Utc>,
+    /// Hex HMAC-SHA256 of the request filter's JSON, keyed with a key
+    /// derived from the node's purge key
+    pub filter_digest: String,
+    pub reason: Option<String>,
+    pub shards: Vec<ShardPurge>,
+    pub vectors_purged: usize,
+    /// Vectors that couldn't be checked against the filter, in the shards
+    /// or in backups
+    #[serde(default)]
+    pub vectors_undecryptable: usize,
+    /// Backups rewritten to drop purged vectors
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backups_rewritten: Vec<u64>,
+    /// Matching vectors found only in backups, no longer in any shard
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backup_vectors: Vec<Uuid>,
+    /// Backed-up vectors skipped because their metadata couldn't be decrypted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backup_undecryptable: Vec<Uuid>,
+}
+
+/// Proof that a purge was carried out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeCertificate {
+    #[serde(flatten)]
+    pub record: PurgeRecord,
+    /// Base64 ed25519 public key of the signing node
+    pub public_key: String,
+    /// Base64 ed25519 signature over the record's JSON
+    pub signature: String,
+}
+
+impl PurgeCertificate {
+    /// Check the signature against the embedded public key
+    pub fn verify(&self) -> Result<()> {
+        let public_key: [u8; 32] = STANDARD
+            .decode(&self.public_key)?
+            .try_into()
+            .map_err(|_| anyhow!("Purge public key must be 32 bytes"))?;
+        let signature: [u8; 64] = STANDARD
+            .decode(&self.signature)?
+            .try_into()
+            .map_err(|_| anyhow!("Purge signature must be 64 bytes"))?;
+        VerifyingKey::from_bytes(&public_key)
+            .map_err(|e| anyhow!("Invalid purge public key: {}", e))?
+            .verify(
+                &serde_json::to_vec(&self.record)?,
+                &Signature::from_bytes(&signature),
+            )
+            .map_err(|_| {
+                anyhow!(
+                    "Purge certificate {} has an invalid signature",
+                    self.record.id
+                )
+            })
+    }
+}
+
+/// Audit action for a purge that's about to erase data
+pub const PURGE_PENDING_ACTION: &str = "pending";
+
+/// Audit action for a completed purge, whose details are the certificate
+pub const PURGE_COMPLETE_ACTION: &str = "purge";
+
+/// Whether the expression can only match records named by an `eq` or `in`
+/// predicate: a conjunction needs one such clause, a disjunction needs one in
+/// every branch
+fn selects_by_identity(expr: &QueryExpr) -> bool {
+    match expr {
+        QueryExpr::And(children) => children.iter().any(selects_by_identity),
+        QueryExpr::Or(children) => !children.is_empty() && children.iter().all(selects_by_identity),
+        QueryExpr::Field(predicate) => matches!(predicate.op, PredicateOp::Eq | PredicateOp::In),
+        QueryExpr::Not(_) | QueryExpr::Similar(_) => false,
+    }
+}
+
+/// Reject filters that could erase more than the records they name
+fn check_filter(expr: &QueryExpr) -> Result<()> {
+    match expr {
+        QueryExpr::And(children) | QueryExpr::Or(children) => {
+            children.iter().try_for_each(check_filter)?
+        }
+        // Negations match nearly everything that isn't the subject
+        QueryExpr::Not(_) => return Err(anyhow!("Purge filters may not use `not`")),
+        QueryExpr::Field(predicate) => {
+            if matches!(predicate.op, PredicateOp::Ne | PredicateOp::Exists) {
+                return Err(anyhow!(
+                    "Purge filters may not use `{}` predicates",
+                    format!("{:?}", predicate.op).to_lowercase()
+                ));
+            }
+        }
+        QueryExpr::Similar(_) => {
+            return Err(anyhow!("Purge filters may only use metadata predicates"))
+        }
+    }
+    if !selects_by_identity(expr) {
+        return Err(anyhow!(
+            "Purge filters need an `eq` or `in` metadata predicate in every branch"
+        ));
+    }
+    Ok(())
+}
+
+/// Carries out purges and certifies them
+pub struct PurgeService {
+    shard_manager: Arc<ShardManager>,
+    audit: Arc<AuditLog>,
+    signing_key: SigningKey,
+    /// HMAC key for filter digests, derived from the signing key so the
+    /// secret itself is only ever used to sign
+    filter_key: [u8; 32],
+    backups: Option<BackupStore>,
+}
+
+impl std::fmt::Debug for PurgeService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PurgeService")
+            .field("public_key", &self.public_key())
+            .finish()
+    }
+}
+
+impl PurgeService {
+    /// `signing_key` is the node's 32-byte ed25519 secret key
+    pub fn new(
+        shard_manager: Arc<ShardManager>,
+        audit: Arc<AuditLog>,
+        signing_key: [u8; 32],
+    ) -> Self {
+        let mut filter_key = [0u8; 32];
+        Hkdf::<Sha256>::new(None, &signing_key)
+            .expand(b"purge-filter", &mut filter_key)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        Self {
+            shard_manager,
+            audit,
+            signing_key: SigningKey::from_bytes(&signing_key),
+            filter_key,
+            backups: None,
+        }
+    }
+
+    /// Scrub purged vectors from these backups too
+    pub fn with_backups(mut self, backups: BackupStore) -> Self {
+        self.backups = Some(backups);
+        self
+    }
+
+    /// Base64 public key certificates are signed with
+    pub fn public_key(&self) -> String {
+        STANDARD.encode(self.signing_key.verifying_key().to_bytes())
+    }
+
+    /// Hex HMAC-SHA256 of a filter keyed with a key derived from the purge
+    /// key, for matching a certificate to its request on this node
+    pub fn filter_digest(&self, filter: &QueryExpr) -> Result<String> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.filter_key)
+            .expect("HMAC accepts keys of any length");
+        mac.update(&serde_json::to_vec(filter)?);
+        let digest = mac.finalize().into_bytes();
+        Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    /// Erase everything matching the request and return the certificate
+    pub async fn purge(&self, request: &PurgeRequest) -> Result<PurgeCertificate> {
+        // Purges are by identity; a similarity clause or negation could
+        // erase unrelated data and an empty filter would erase everything
+        check_filter(&request.filter)?;
+
+        if !self.audit.is_durable() {
+            return Err(anyhow!(
+                "Purges need an audit log persisted to disk to record certificates"
+            ));
+        }
+
+        // Recorded before anything is erased; if the certificate can't be
+        // written afterwards, this entry is what's left to show the purge ran
+        let id = Uuid::new_v4();
+        let filter_digest = self.filter_digest(&request.filter)?;
+        self.audit
+            .record_durably(
+                "purge",
+                PURGE_PENDING_ACTION,
+                &id.to_string(),
+                serde_json::json!({
+                    "filter_digest": filter_digest,
+                    "reason": request.reason,
+                }),
+            )
+            .await?;
+
+        let shards = self.shard_manager.purge_matching(&request.filter).await?;
+        let mut backup_vectors = Vec::new();
+        let mut backup_undecryptable = Vec::new();
+        let backups_rewritten = match &self.backups {
+            Some(backups) => {
+                let mut purged: HashSet<Uuid> = shards
+                    .iter()
+                    .flat_map(|s| s.vector_ids.iter().copied())
+                    .collect();
+                // Vectors deleted from the shards since they were backed up
+                // only turn up by testing the backups themselves
+                let (matched, undecryptable) =
+                    self.backed_up_matches(backups, &request.filter).await?;
+                for id in matched {
+                    if purged.insert(id) {
+                        backup_vectors.push(id);
+                    }
+                }
+                backup_undecryptable = undecryptable;
+
+                let rewritten = backups.scrub(&purged)?;
+                let holding = backups.holding(&purged)?;
+                if !holding.is_empty() {
+                    return Err(anyhow!("Backups {:?} still hold purged vectors", holding));
+                }
+                rewritten
+            }
+            None => Vec::new(),
+        };
+        let record = PurgeRecord {
+            id,
+            issued_at: chrono::Utc::now(),
+            filter_digest,
+            reason: request.reason.clone(),
+            vectors_purged: shards.iter().map(|s| s.vector_ids.len()).sum(),
+            vectors_undecryptable: shards.iter().map(|s| s.undecryptable.len()).sum::<usize>()
+                + backup_undecryptable.len(),
+            shards,
+            backups_rewritten,
+            backup_vectors,
+            backup_undecryptable,
+        };
+        let signature = self.signing_key.sign(&serde_json::to_vec(&record)?);
+        let certificate = PurgeCertificate {
+            record,
+            public_key: self.public_key(),
+            signature: STANDARD.encode(signature.to_bytes()),
+        };
+
+        self.audit
+            .record_durably(
+                "purge",
+                PURGE_COMPLETE_ACTION,
+                &id.to_string(),
+                serde_json::to_value(&certificate)?,
+            )
+            .await
+            .map_err(|e| {
+                anyhow!(
+                    "Purge {} erased the data but its certificate wasn't recorded: {}",
+                    id,
+                    e
+                )
+            })?;
+        info!(
+            "Purge {} removed {} vectors",
+            certificate.record.id, certificate.record.vectors_purged
+        );
+        if !certificate.record.backups_rewritten.is_empty() {
+            info!(
+                "Purge {} rewrote backups {:?}",
+                certificate.record.id, certificate.record.backups_rewritten
+            );
+        }
+        if certificate.record.vectors_undecryptable > 0 {
+            warn!(
+                "Purge {} couldn't check {} vectors with undecryptable metadata",
+                certificate.record.id, certificate.record.vectors_undecryptable
+            );
+        }
+        Ok(certificate)
+    }
+
+    /// IDs of backed-up vectors the filter matches, and of those whose
+    /// metadata couldn't be decrypted, each listed once however many
+    /// backups hold it
+    async fn backed_up_matches(
+        &self,
+        backups: &BackupStore,
+        filter: &QueryExpr,
+    ) -> Result<(Vec<Uuid>, Vec<Uuid>)> {
+        let mut matched = Vec::new();
+        let mut undecryptable = Vec::new();
+        let mut seen = HashSet::new();
+        let mut skipped = HashSet::new();
+        for manifest in backups.list()? {
+            for shard in manifest.shards.iter().filter(|shard| shard.upserted > 0) {
+                let entries = backups.vectors(manifest.id, shard.record.id)?;
+                let (ids, failed) = self
+                    .shard_manager
+                    .purge_matching_backup(&shard.record, entries, filter)
+                    .await?;
+                matched.extend(ids.into_iter().filter(|id| seen.insert(*id)));
+                undecryptable.extend(failed.into_iter().filter(|id| skipped.insert(*id)));
+            }
+        }
+        // A vector rewritten since an older backup may match in one and not
+        // decrypt in another; it's erased either way
+        undecryptable.retain(|id| !seen.contains(id));
+        Ok((matched, undecryptable))
+    }
+}
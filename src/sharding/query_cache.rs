@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::core::vector::Vector;
+use crate::query::QueryExpr;
+use crate::sharding::vector_index::SearchResult;
+
+/// Settings for the search result cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryCacheConfig {
+    pub enabled: bool,
+
+    /// How long a cached result stays valid
+    pub ttl: Duration,
+
+    /// Most cached queries kept per shard
+    pub max_entries_per_shard: usize,
+}
+
+impl Default for QueryCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl: Duration::from_secs(30),
+            max_entries_per_shard: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    vector_hash: u64,
+    limit: usize,
+    filter: Option<String>,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    /// Exact query bits, compared on lookup to rule out hash collisions
+    query_bits: Vec<u32>,
+    results: Vec<SearchResult>,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct ShardCache {
+    /// Bumped on every write so searches that raced a write don't
+    /// repopulate the cache with stale results
+    generation: u64,
+    entries: HashMap<CacheKey, CacheEntry>,
+}
+
+/// Read-through cache of search results keyed on (shard, query vector, k,
+/// filter). Any write to a shard invalidates all of its cached results.
+#[derive(Debug)]
+pub struct QueryCache {
+    config: QueryCacheConfig,
+    shards: RwLock<HashMap<Uuid, ShardCache>>,
+}
+
+/// Token identifying a cache lookup, used to store its result afterwards
+#[derive(Debug, Clone)]
+pub struct CacheTicket {
+    shard_id: Uuid,
+    key: CacheKey,
+    query_bits: Vec<u32>,
+    generation: u64,
+}
+
+impl QueryCache {
+    pub fn new(config: QueryCacheConfig) -> Self {
+        Self {
+            config,
+            shards: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &QueryCacheConfig {
+        &self.config
+    }
+
+    fn key(query: &Vector, limit: usize, filter: Option<&QueryExpr>) -> (CacheKey, Vec<u32>) {
+        let query_bits: Vec<u32> = query.values.iter().map(|v| v.to_bits()).collect();
+        let mut hasher = DefaultHasher::new();
+        query_bits.hash(&mut hasher);
+        let key = CacheKey {
+            vector_hash: hasher.finish(),
+            limit,
+            filter: filter.and_then(|f| serde_json::to_string(f).ok()),
+        };
+        (key, query_bits)
+    }
+
+    /// Look up cached results. On a miss, returns a ticket for storing the
+    /// freshly computed results with [`insert`](Self::insert).
+    pub async fn get(
+        &self,
+        shard_id: Uuid,
+        query: &Vector,
+        limit: usize,
+        filter: Option<&QueryExpr>,
+    ) -> Result<Vec<SearchResult>, Option<CacheTicket>> {
+        if !self.config.enabled {
+            return Err(None);
+        }
+
+        let (key, query_bits) = Self::key(query, limit, filter);
+        let shards = self.shards.read().await;
+        let generation = shards.get(&shard_id).map(|s| s.generation).unwrap_or(0);
+
+        if let Some(entry) = shards.get(&shard_id).and_then(|s| s.entries.get(&key)) {
+            if entry.expires_at > Instant::now() && entry.query_bits == query_bits {
+                return Ok(entry.results.clone());
+            }
+        }
+
+        Err(Some(CacheTicket {
+            shard_id,
+            key,
+            query_bits,
+            generation,
+        }))
+    }
+
+    /// Store results for a ticket, unless the shard was written to since
+    pub async fn insert(&self, ticket: CacheTicket, results: Vec<SearchResult>) {
+        let mut shards = self.shards.write().await;
+        let shard = shards.entry(ticket.shard_id).or_default();
+        if shard.generation != ticket.generation {
+            return;
+        }
+
+        let now = Instant::now();
+        if shard.entries.len() >= self.config.max_entries_per_shard {
+            shard.entries.retain(|_, e| e.expires_at > now);
+        }
+        if shard.entries.len() >= self.config.max_entries_per_shard {
+            let oldest = shard
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.expires_at)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                shard.entries.remove(&oldest);
+            }
+        }
+
+        shard.entries.insert(
+            ticket.key,
+            CacheEntry {
+                query_bits: ticket.query_bits,
+                results,
+                expires_at: now + self.config.ttl,
+            },
+        );
+    }
+
+    /// Drop every cached result for a shard
+    pub async fn invalidate(&self, shard_id: Uuid) {
+        let mut shards = self.shards.write().await;
+        let shard = shards.entry(shard_id).or_default();
+        shard.generation += 1;
+        shard.entries.clear();
+    }
+
+    /// Number of cached queries for a shard
+    pub async fn len(&self, shard_id: Uuid) -> usize {
+        self.shards
+            .read()
+            .await
+            .get(&shard_id)
+            .map(|s| s.entries.len())
+            .unwrap_or(0)
+    }
+}
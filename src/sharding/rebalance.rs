@@ -0,0 +1,337 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::core::audit::AuditLog;
+use crate::core::metrics::MetricsCollector;
+use crate::nerv::tasks;
+use crate::sharding::manager::{ShardManager, ShardStatus};
+
+/// Completed moves kept for the status report
+const HISTORY_CAPACITY: usize = 50;
+
+/// When the rebalancer moves range boundaries between split shards
+#[derive(Debug, Clone)]
+pub struct RebalanceConfig {
+    /// Time between checks
+    pub interval: Duration,
+
+    /// Neighbouring ranges are rebalanced once the larger holds more than
+    /// this many times the vectors of the smaller
+    pub max_imbalance: f64,
+
+    /// Pairs whose larger range holds fewer vectors than this are left
+    /// alone, however uneven
+    pub min_vectors: usize,
+}
+
+impl Default for RebalanceConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(300),
+            max_imbalance: 2.0,
+            min_vectors: 1_000,
+        }
+    }
+}
+
+/// A boundary between two neighbouring ranges of a split family, moved
+/// by [`ShardManager::move_range_boundary`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RangeMove {
+    /// Shard addressing the family
+    pub root: Uuid,
+    /// Shard the vectors moved out of
+    pub from: Uuid,
+    pub to: Uuid,
+    /// Hilbert key the upper range started at
+    pub boundary: u64,
+    pub new_boundary: u64,
+    pub moved: usize,
+}
+
+/// A boundary move the rebalancer would make
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RebalancePlan {
+    pub root: Uuid,
+    pub lower: Uuid,
+    pub upper: Uuid,
+    pub lower_vectors: usize,
+    pub upper_vectors: usize,
+    pub boundary: u64,
+    /// The median key of both ranges, so each ends up with half
+    pub new_boundary: u64,
+    /// Vectors between the old and new boundary
+    pub to_move: usize,
+}
+
+/// A move under way
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RebalanceProgress {
+    pub plan: RebalancePlan,
+    pub moved: usize,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletedMove {
+    #[serde(flatten)]
+    pub range_move: RangeMove,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+/// What the rebalancer is doing and has done
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RebalanceStatus {
+    pub in_progress: Option<RebalanceProgress>,
+    /// Most recent first
+    pub completed: Vec<CompletedMove>,
+    pub runs: u64,
+}
+
+/// Keeps the ranges of split shard families even. Splits (see
+/// [`ShardManager::split_shard`]) cut a shard at its median Hilbert key,
+/// but later writes rarely land evenly on both sides. Each check reads
+/// every member's size from its `shards.<id>.vector_count` gauge, and
+/// where neighbouring ranges have drifted past
+/// [`RebalanceConfig::max_imbalance`], moves the boundary between them to
+/// their combined median key and migrates the vectors in between online.
+/// Each move is recorded as an audit event.
+pub struct RebalanceManager {
+    shard_manager: Arc<ShardManager>,
+    metrics: Arc<MetricsCollector>,
+    audit_log: Arc<AuditLog>,
+    config: RebalanceConfig,
+    current: RwLock<Option<(RebalanceProgress, Arc<AtomicUsize>)>>,
+    completed: RwLock<VecDeque<CompletedMove>>,
+    /// Held for a whole run, so scheduled and requested runs don't overlap
+    running: Mutex<()>,
+}
+
+impl RebalanceManager {
+    pub fn new(
+        shard_manager: Arc<ShardManager>,
+        metrics: Arc<MetricsCollector>,
+        audit_log: Arc<AuditLog>,
+        config: RebalanceConfig,
+    ) -> Self {
+        Self {
+            shard_manager,
+            metrics,
+            audit_log,
+            config,
+            current: RwLock::new(None),
+            completed: RwLock::new(VecDeque::new()),
+            running: Mutex::new(()),
+        }
+    }
+
+    /// A shard's vector count as last reported to the metrics, or as
+    /// restored from storage if it hasn't changed since
+    async fn size(&self, shard_id: Uuid) -> Option<usize> {
+        let gauge = format!("shards.{}.vector_count", shard_id);
+        match self.metrics.get_gauge(&gauge).await {
+            Some(count) => Some(count as usize),
+            None => Some(
+                self.shard_manager
+                    .get_shard(shard_id)
+                    .await
+                    .ok()?
+                    .vector_count,
+            ),
+        }
+    }
+
+    /// The most uneven pair of neighbouring ranges in a family, if it's
+    /// over the limits and its boundary can move
+    pub async fn plan(&self, root: Uuid) -> Option<RebalancePlan> {
+        let ranges = self.shard_manager.family_ranges(root).await;
+        let mut sizes = Vec::with_capacity(ranges.len());
+        for (_, member) in &ranges {
+            sizes.push(self.size(*member).await?);
+        }
+
+        let (position, ratio) = (1..ranges.len())
+            .map(|i| {
+                let (smaller, larger) = if sizes[i - 1] < sizes[i] {
+                    (sizes[i - 1], sizes[i])
+                } else {
+                    (sizes[i], sizes[i - 1])
+                };
+                (i, larger as f64 / smaller.max(1) as f64)
+            })
+            .filter(|(i, _)| sizes[i - 1].max(sizes[*i]) >= self.config.min_vectors.max(2))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+        self.metrics
+            .set_gauge("rebalance.max_imbalance_pct", (ratio * 100.0) as u64)
+            .await;
+        if ratio <= self.config.max_imbalance {
+            return None;
+        }
+
+        let (lower_start, lower) = ranges[position - 1];
+        let (boundary, upper) = ranges[position];
+        let mut keys = Vec::with_capacity(sizes[position - 1] + sizes[position]);
+        for member in [lower, upper] {
+            let index = self.shard_manager.get_vector_index(member).await.ok()?;
+            keys.extend(
+                index
+                    .entries()
+                    .await
+                    .iter()
+                    .map(|entry| index.hilbert_key(&entry.vector)),
+            );
+        }
+        keys.sort_unstable();
+        let new_boundary = *keys.get(keys.len() / 2)?;
+        // The lower range must keep at least its first key
+        if new_boundary <= lower_start || new_boundary == boundary {
+            return None;
+        }
+        let (low, high) = (boundary.min(new_boundary), boundary.max(new_boundary));
+        let to_move = keys.iter().filter(|key| (low..high).contains(*key)).count();
+
+        Some(RebalancePlan {
+            root,
+            lower,
+            upper,
+            lower_vectors: sizes[position - 1],
+            upper_vectors: sizes[position],
+            boundary,
+            new_boundary,
+            to_move,
+        })
+    }
+
+    /// Check every split family once, rebalancing the most uneven pair of
+    /// ranges in each
+    pub async fn run_once(&self) -> Vec<RangeMove> {
+        let _running = self.running.lock().await;
+        let mut moves = Vec::new();
+
+        for root in self.shard_manager.family_roots().await {
+            let mut active = true;
+            for member in self.shard_manager.shard_family(root).await {
+                active &= self
+                    .shard_manager
+                    .get_shard(member)
+                    .await
+                    .is_ok_and(|shard| shard.status == ShardStatus::Active);
+            }
+            if !active {
+                debug!("Skipping shard family {}: not every member is active", root);
+                continue;
+            }
+            let Some(plan) = self.plan(root).await else {
+                debug!("Shard family {} is balanced", root);
+                continue;
+            };
+
+            let moved = Arc::new(AtomicUsize::new(0));
+            let started_at = Utc::now();
+            *self.current.write().await = Some((
+                RebalanceProgress {
+                    plan: plan.clone(),
+                    moved: 0,
+                    started_at,
+                },
+                moved.clone(),
+            ));
+            let result = self
+                .shard_manager
+                .move_range_boundary(root, plan.boundary, plan.new_boundary, &moved)
+                .await;
+            *self.current.write().await = None;
+
+            let range_move = match result {
+                Ok(range_move) => range_move,
+                Err(e) => {
+                    warn!("Failed to rebalance shard family {}: {}", root, e);
+                    self.metrics
+                        .increment_counter("rebalance.failures", 1)
+                        .await;
+                    continue;
+                }
+            };
+            self.metrics.increment_counter("rebalance.moves", 1).await;
+            self.metrics
+                .increment_counter("rebalance.vectors_moved", range_move.moved as u64)
+                .await;
+            self.audit_log
+                .record(
+                    "rebalance",
+                    "move_boundary",
+                    &root.to_string(),
+                    serde_json::json!({
+                        "from": range_move.from,
+                        "to": range_move.to,
+                        "boundary": range_move.boundary,
+                        "new_boundary": range_move.new_boundary,
+                        "moved": range_move.moved,
+                        "lower_vectors": plan.lower_vectors,
+                        "upper_vectors": plan.upper_vectors,
+                    }),
+                )
+                .await;
+
+            let mut completed = self.completed.write().await;
+            if completed.len() >= HISTORY_CAPACITY {
+                completed.pop_back();
+            }
+            completed.push_front(CompletedMove {
+                range_move: range_move.clone(),
+                started_at,
+                finished_at: Utc::now(),
+            });
+            moves.push(range_move);
+        }
+
+        self.metrics.increment_counter("rebalance.runs", 1).await;
+        moves
+    }
+
+    pub async fn status(&self) -> RebalanceStatus {
+        let in_progress =
+            self.current
+                .read()
+                .await
+                .as_ref()
+                .map(|(progress, moved)| RebalanceProgress {
+                    moved: moved.load(Ordering::Relaxed),
+                    ..progress.clone()
+                });
+        RebalanceStatus {
+            in_progress,
+            completed: self.completed.read().await.iter().cloned().collect(),
+            runs: self
+                .metrics
+                .get_counter("rebalance.runs")
+                .await
+                .unwrap_or(0),
+        }
+    }
+
+    /// Run checks in the background until the task is aborted
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        info!(
+            "Starting shard rebalancer (interval: {:?}, max imbalance: {}x, min vectors: {})",
+            self.config.interval, self.config.max_imbalance, self.config.min_vectors
+        );
+
+        tasks::spawn("sharding", "shard rebalancer", async move {
+            loop {
+                tokio::time::sleep(self.config.interval).await;
+                self.run_once().await;
+            }
+        })
+    }
+}
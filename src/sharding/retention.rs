@@ -0,0 +1,261 @@
+//! Per-collection data retention.
+//!
+//! A [`RetentionPolicy`] caps a collection by age, vector count and stored
+//! bytes. The [`RetentionEnforcer`] periodically deletes the oldest vectors
+//! of each collection with a policy until it is within every cap, and
+//! reports how much space that reclaimed. Collections split by the
+//! auto-sharder are enforced as a whole. Enforcement can be paused, e.g.
+//! while a backfill is loading old data.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::core::audit::AuditLog;
+use crate::core::metrics::MetricsCollector;
+use crate::nerv::tasks;
+use crate::sharding::manager::ShardManager;
+use crate::sharding::vector_index::VectorEntry;
+
+/// Limits on what a collection keeps; `None` leaves that dimension
+/// unbounded. Body of `PUT /api/admin/retention/{shard_id}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Vectors added longer ago than this are deleted
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+    #[serde(default)]
+    pub max_count: Option<usize>,
+    /// Estimated with [`entry_bytes`]
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+}
+
+impl RetentionPolicy {
+    pub fn is_unbounded(&self) -> bool {
+        self.max_age_secs.is_none() && self.max_count.is_none() && self.max_bytes.is_none()
+    }
+}
+
+/// Approximate bytes a vector takes: its values, ID and metadata as stored
+pub fn entry_bytes(entry: &VectorEntry) -> u64 {
+    let values = entry.vector.values.len() * std::mem::size_of::<f32>();
+    let metadata: usize = entry
+        .metadata
+        .iter()
+        .flatten()
+        .map(|(k, v)| k.len() + v.len())
+        .sum();
+    (values + metadata + std::mem::size_of::<Uuid>()) as u64
+}
+
+/// What one enforcement pass did to a collection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub shard_id: Uuid,
+    pub deleted: usize,
+    pub reclaimed_bytes: u64,
+    pub remaining: usize,
+    pub remaining_bytes: u64,
+}
+
+/// Whether enforcement is running and the policies it enforces
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionStatus {
+    pub paused: bool,
+    pub policies: HashMap<Uuid, RetentionPolicy>,
+}
+
+/// Deletes the oldest vectors of collections over their retention policy
+pub struct RetentionEnforcer {
+    shard_manager: Arc<ShardManager>,
+    metrics: Arc<MetricsCollector>,
+    audit_log: Arc<AuditLog>,
+    interval: Duration,
+    policies: RwLock<HashMap<Uuid, RetentionPolicy>>,
+    paused: AtomicBool,
+}
+
+impl RetentionEnforcer {
+    pub fn new(
+        shard_manager: Arc<ShardManager>,
+        metrics: Arc<MetricsCollector>,
+        audit_log: Arc<AuditLog>,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            shard_manager,
+            metrics,
+            audit_log,
+            interval,
+            policies: RwLock::new(HashMap::new()),
+            paused: AtomicBool::new(false),
+        }
+    }
+
+    /// Apply `policy` to the collection served by `shard_id`; an unbounded
+    /// policy removes it
+    pub async fn set_policy(&self, shard_id: Uuid, policy: RetentionPolicy) -> Result<()> {
+        self.shard_manager.get_shard(shard_id).await?;
+        let mut policies = self.policies.write().await;
+        if policy.is_unbounded() {
+            policies.remove(&shard_id);
+            info!("Removed retention policy from shard {}", shard_id);
+        } else {
+            info!("Retention policy for shard {}: {:?}", shard_id, policy);
+            policies.insert(shard_id, policy);
+        }
+        Ok(())
+    }
+
+    pub async fn policy(&self, shard_id: Uuid) -> Option<RetentionPolicy> {
+        self.policies.read().await.get(&shard_id).cloned()
+    }
+
+    /// Stop deleting until [`resume`](Self::resume) is called
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+        info!("Retention enforcement paused");
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+        info!("Retention enforcement resumed");
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    pub async fn status(&self) -> RetentionStatus {
+        RetentionStatus {
+            paused: self.is_paused(),
+            policies: self.policies.read().await.clone(),
+        }
+    }
+
+    /// Enforce every policy once. Does nothing while paused.
+    pub async fn run_once(&self) -> Vec<RetentionReport> {
+        if self.is_paused() {
+            debug!("Retention enforcement is paused; skipping pass");
+            return Vec::new();
+        }
+
+        let policies = self.policies.read().await.clone();
+        let mut reports = Vec::new();
+        for (shard_id, policy) in policies {
+            // The collection was deleted; its policy goes with it
+            if self.shard_manager.get_shard(shard_id).await.is_err() {
+                self.policies.write().await.remove(&shard_id);
+                info!("Dropped retention policy of deleted shard {}", shard_id);
+                continue;
+            }
+            match self.enforce(shard_id, &policy).await {
+                Ok(report) => reports.push(report),
+                Err(e) => warn!("Failed to enforce retention on shard {}: {}", shard_id, e),
+            }
+        }
+        self.metrics.increment_counter("retention.runs", 1).await;
+        reports
+    }
+
+    async fn enforce(&self, shard_id: Uuid, policy: &RetentionPolicy) -> Result<RetentionReport> {
+        let mut entries = Vec::new();
+        for member in self.shard_manager.shard_family(shard_id).await {
+            if let Ok(index) = self.shard_manager.get_vector_index(member).await {
+                entries.extend(index.entries().await);
+            }
+        }
+        entries.sort_by_key(|entry| entry.created_at);
+
+        let cutoff = policy
+            .max_age_secs
+            .map(|secs| chrono::Utc::now() - chrono::Duration::seconds(secs as i64));
+        let mut remaining = entries.len();
+        let mut remaining_bytes: u64 = entries.iter().map(entry_bytes).sum();
+        let mut deleted = 0;
+        let mut reclaimed_bytes = 0;
+
+        // Oldest first, until the collection is within every limit
+        for entry in &entries {
+            let expired = cutoff.is_some_and(|cutoff| entry.created_at < cutoff);
+            let over_count = policy.max_count.is_some_and(|max| remaining > max);
+            let over_bytes = policy.max_bytes.is_some_and(|max| remaining_bytes > max);
+            if !(expired || over_count || over_bytes) {
+                break;
+            }
+            // Deleted concurrently, or moved and deleted by a split in progress
+            if self
+                .shard_manager
+                .remove_vector(shard_id, entry.id)
+                .await
+                .is_err()
+            {
+                continue;
+            }
+            let bytes = entry_bytes(entry);
+            remaining -= 1;
+            remaining_bytes -= bytes;
+            deleted += 1;
+            reclaimed_bytes += bytes;
+        }
+
+        if deleted > 0 {
+            info!(
+                "Retention deleted {} vectors ({} bytes) from shard {}",
+                deleted, reclaimed_bytes, shard_id
+            );
+            self.metrics
+                .increment_counter("retention.vectors_deleted", deleted as u64)
+                .await;
+            self.metrics
+                .increment_counter("retention.bytes_reclaimed", reclaimed_bytes)
+                .await;
+            self.audit_log
+                .record(
+                    "retention",
+                    "enforce",
+                    &shard_id.to_string(),
+                    serde_json::json!({
+                        "policy": policy,
+                        "deleted": deleted,
+                        "reclaimed_bytes": reclaimed_bytes,
+                        "remaining": remaining,
+                        "remaining_bytes": remaining_bytes,
+                    }),
+                )
+                .await;
+        }
+
+        Ok(RetentionReport {
+            shard_id,
+            deleted,
+            reclaimed_bytes,
+            remaining,
+            remaining_bytes,
+        })
+    }
+
+    /// Enforce policies in the background until the task is aborted
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        info!(
+            "Starting retention enforcer (interval: {:?})",
+            self.interval
+        );
+
+        tasks::spawn("sharding", "retention enforcer", async move {
+            loop {
+                tokio::time::sleep(self.interval).await;
+                self.run_once().await;
+            }
+        })
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::core::audit::AuditLog;
+use crate::core::metrics::MetricsCollector;
+use crate::nerv::tasks;
+use crate::sharding::manager::ShardManager;
+use crate::sharding::vector_index::IntegrityReport;
+
+/// Configuration for the background consistency checker
+#[derive(Debug, Clone)]
+pub struct ScrubberConfig {
+    /// Time between scrub passes
+    pub interval: Duration,
+
+    /// Whether to fix inconsistencies or only report them
+    pub repair: bool,
+}
+
+impl Default for ScrubberConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(600),
+            repair: true,
+        }
+    }
+}
+
+/// Integrity report for one shard's index
+#[derive(Debug, Clone)]
+pub struct ShardScrubReport {
+    pub shard_id: Uuid,
+    pub report: IntegrityReport,
+}
+
+/// Periodically verifies that every shard's index is internally consistent:
+/// each stored vector is reachable through the Hilbert map and the map holds
+/// no orphaned IDs, and an HNSW graph holds exactly the stored vectors and is
+/// well formed. Memory-mapped segment rows are checked against their
+/// checksums, and failing ones quarantined when repairing.
+///
+/// Each shard is also checked against its change feed, the log every
+/// mutation is appended to: a vector must be in the state of the last event
+/// for it. Only retained events can be checked, so vectors whose history has
+/// aged out of the feed are covered by the index checks alone. Findings are
+/// recorded as metrics and audit events.
+pub struct ConsistencyChecker {
+    shard_manager: Arc<ShardManager>,
+    metrics: Arc<MetricsCollector>,
+    audit_log: Arc<AuditLog>,
+    config: ScrubberConfig,
+}
+
+impl ConsistencyChecker {
+    pub fn new(
+        shard_manager: Arc<ShardManager>,
+        metrics: Arc<MetricsCollector>,
+        audit_log: Arc<AuditLog>,
+        config: ScrubberConfig,
+    ) -> Self {
+        Self {
+            shard_manager,
+            metrics,
+            audit_log,
+            config,
+        }
+    }
+
+    /// Run a single pass over every shard
+    pub async fn run_once(&self) -> Vec<ShardScrubReport> {
+        let mut reports = Vec::new();
+        let mut total_issues = 0;
+
+        for (shard_id, _) in self.shard_manager.get_vector_indices().await {
+            let report = match self
+                .shard_manager
+                .verify_shard(shard_id, self.config.repair)
+                .await
+            {
+                Ok(report) => report,
+                Err(e) => {
+                    warn!("Scrubber couldn't check shard {}: {}", shard_id, e);
+                    continue;
+                }
+            };
+            let issues = report.issue_count();
+            total_issues += issues;
+
+            if issues > 0 {
+                warn!(
+                    "Scrubber found {} inconsistencies in shard {} ({} unreachable, {} orphaned, {} misplaced, {} corrupted, {} unindexed, {} graph orphaned, {} graph faults, {} diverged from the change feed)",
+                    issues,
+                    shard_id,
+                    report.unreachable.len(),
+                    report.orphaned.len(),
+                    report.misplaced.len(),
+                    report.corrupted.len(),
+                    report.unindexed.len(),
+                    report.graph_orphaned.len(),
+                    report.graph_faults.len(),
+                    report.diverged.len()
+                );
+
+                self.metrics
+                    .increment_counter("scrubber.inconsistencies_found", issues as u64)
+                    .await;
+                self.metrics
+                    .increment_counter("scrubber.repairs", report.repaired as u64)
+                    .await;
+                if !report.corrupted.is_empty() {
+                    self.metrics
+                        .increment_counter(
+                            "scrubber.corrupt_records",
+                            report.corrupted.len() as u64,
+                        )
+                        .await;
+                }
+
+                self.audit_log
+                    .record(
+                        "scrubber",
+                        if report.repaired > 0 {
+                            "repair"
+                        } else {
+                            "inconsistency"
+                        },
+                        &shard_id.to_string(),
+                        serde_json::json!({
+                            "checked": report.checked,
+                            "unreachable": report.unreachable,
+                            "orphaned": report.orphaned,
+                            "misplaced": report.misplaced,
+                            "corrupted": report.corrupted,
+                            "unindexed": report.unindexed,
+                            "graph_orphaned": report.graph_orphaned,
+                            "graph_faults": report.graph_faults,
+                            "diverged": report.diverged,
+                            "repaired": report.repaired,
+                        }),
+                    )
+                    .await;
+            } else {
+                debug!("Shard {} passed consistency check", shard_id);
+            }
+
+            reports.push(ShardScrubReport { shard_id, report });
+        }
+
+        self.metrics.increment_counter("scrubber.runs", 1).await;
+        self.metrics
+            .set_gauge("scrubber.last_run_inconsistencies", total_issues as u64)
+            .await;
+
+        reports
+    }
+
+    /// Run scrub passes in the background until the task is aborted
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        info!(
+            "Starting consistency checker (interval: {:?}, repair: {})",
+            self.config.interval, self.config.repair
+        );
+
+        tasks::spawn("sharding", "consistency checker", async move {
+            loop {
+                tokio::time::sleep(self.config.interval).await;
+                self.run_once().await;
+            }
+        })
+    }
+}
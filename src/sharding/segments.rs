@@ -0,0 +1,835 @@
+//! Segment-based storage for vector indexes.
+//!
+//! Writes land in an in-memory memtable. Once it holds
+//! [`SegmentConfig::memtable_limit`] entries it is frozen into an immutable
+//! segment. Deleting a vector that already sits in a segment records a
+//! tombstone instead of rewriting the segment; merges later combine segments
+//! and drop tombstoned entries for good.
+//!
+//! Segments are shared behind `Arc`, so a [`SegmentSnapshot`] of the whole
+//! store costs a memtable flush plus a few reference counts, and a merge can
+//! read its inputs without holding the index lock while the merged segment is
+//! built.
+//!
+//! With [`MmapStorage`] configured, new segments keep their vectors in a
+//! memory-mapped file instead of on the heap (see [`crate::sharding::mmap`]).
+//! Mapped rows that fail their checksum are skipped by reads until
+//! [`SegmentStore::quarantine`] tombstones them and copies them aside.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::core::checksum::{self, Quarantine};
+use crate::core::vector::Vector;
+use crate::nerv::tasks;
+use crate::sharding::manager::ShardManager;
+use crate::sharding::mmap::{AccessPattern, MmapStorage, MmapVectorFile, MmapVectorWriter};
+use crate::sharding::vector_index::VectorEntry;
+
+/// When memtables are frozen and segments merged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmentConfig {
+    /// Entries the memtable holds before it is frozen into a segment
+    pub memtable_limit: usize,
+
+    /// Segments kept before the smallest are merged
+    pub max_segments: usize,
+
+    /// Segments combined by one size-triggered merge
+    pub merge_factor: usize,
+
+    /// Share of a segment's entries that may be tombstoned before it is
+    /// rewritten without them
+    pub max_tombstone_ratio: f32,
+}
+
+impl Default for SegmentConfig {
+    fn default() -> Self {
+        Self {
+            memtable_limit: 1024,
+            max_segments: 8,
+            merge_factor: 4,
+            max_tombstone_ratio: 0.2,
+        }
+    }
+}
+
+/// Heap-resident fields of a row in a mapped segment
+#[derive(Debug)]
+struct MappedRow {
+    metadata: Option<HashMap<String, String>>,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug)]
+enum SegmentData {
+    Memory(HashMap<Uuid, VectorEntry>),
+    Mapped {
+        file: MmapVectorFile,
+        rows: Vec<MappedRow>,
+        index: HashMap<Uuid, usize>,
+    },
+}
+
+/// Immutable batch of entries frozen from a memtable or produced by a merge
+#[derive(Debug)]
+pub struct Segment {
+    id: u64,
+    data: SegmentData,
+}
+
+impl Segment {
+    fn memory(id: u64, entries: HashMap<Uuid, VectorEntry>) -> Self {
+        Self {
+            id,
+            data: SegmentData::Memory(entries),
+        }
+    }
+
+    /// Write `entries` to a mapped file at `path`
+    fn mapped<'a>(
+        id: u64,
+        path: PathBuf,
+        entries: impl Iterator<Item = Cow<'a, VectorEntry>>,
+    ) -> Result<Self> {
+        let mut entries = entries.peekable();
+        let dimensions = entries.peek().map_or(0, |entry| entry.vector.dimensions);
+        let mut writer = MmapVectorWriter::create(&path, dimensions)?;
+        let mut rows = Vec::new();
+        let mut index = HashMap::new();
+        for entry in entries {
+            writer.push(entry.id, &entry.vector.values)?;
+            index.insert(entry.id, rows.len());
+            rows.push(MappedRow {
+                metadata: entry.metadata.clone(),
+                created_at: entry.created_at,
+            });
+        }
+        let file = match writer.finish() {
+            Ok(file) => file.remove_on_drop(),
+            Err(e) => {
+                let _ = std::fs::remove_file(&path);
+                return Err(e);
+            }
+        };
+        file.advise(AccessPattern::Random)?;
+        Ok(Self {
+            id,
+            data: SegmentData::Mapped { file, rows, index },
+        })
+    }
+
+    /// Build a segment from `entries`, mapped when `path` is set. Falls back
+    /// to memory if the file can't be written, so `entries` may be called
+    /// twice.
+    fn build<'a, F, I>(id: u64, path: Option<PathBuf>, entries: F) -> Self
+    where
+        F: Fn() -> I,
+        I: Iterator<Item = Cow<'a, VectorEntry>>,
+    {
+        if let Some(path) = path {
+            match Self::mapped(id, path, entries()) {
+                Ok(segment) => return segment,
+                Err(e) => warn!("Keeping segment {} in memory: {}", id, e),
+            }
+        }
+        let entries = entries()
+            .map(|entry| (entry.id, entry.into_owned()))
+            .collect();
+        Self::memory(id, entries)
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Entries stored, including tombstoned ones
+    pub fn len(&self) -> usize {
+        match &self.data {
+            SegmentData::Memory(entries) => entries.len(),
+            SegmentData::Mapped { rows, .. } => rows.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The mapped file holding this segment's vectors, if any
+    pub fn file(&self) -> Option<&MmapVectorFile> {
+        match &self.data {
+            SegmentData::Memory(_) => None,
+            SegmentData::Mapped { file, .. } => Some(file),
+        }
+    }
+
+    fn contains(&self, id: &Uuid) -> bool {
+        match &self.data {
+            SegmentData::Memory(entries) => entries.contains_key(id),
+            SegmentData::Mapped { index, .. } => index.contains_key(id),
+        }
+    }
+
+    fn get(&self, id: &Uuid) -> Option<Cow<'_, VectorEntry>> {
+        match &self.data {
+            SegmentData::Memory(entries) => entries.get(id).map(Cow::Borrowed),
+            SegmentData::Mapped { index, .. } => index
+                .get(id)
+                .and_then(|&row| self.mapped_entry(row))
+                .map(Cow::Owned),
+        }
+    }
+
+    /// A mapped row, or `None` if it fails its checksum
+    fn mapped_entry(&self, row: usize) -> Option<VectorEntry> {
+        let SegmentData::Mapped { file, rows, .. } = &self.data else {
+            unreachable!("only mapped segments have rows");
+        };
+        match file.read(row) {
+            Ok((id, values)) => Some(VectorEntry {
+                id,
+                vector: Vector::new(values),
+                metadata: rows[row].metadata.clone(),
+                created_at: rows[row].created_at,
+            }),
+            Err(e) => {
+                warn!("Skipping row {} of {}: {}", row, file.path().display(), e);
+                None
+            }
+        }
+    }
+
+    /// Every readable entry; mapped segments are read in file order
+    fn entries(&self) -> Box<dyn Iterator<Item = Cow<'_, VectorEntry>> + '_> {
+        match &self.data {
+            SegmentData::Memory(entries) => Box::new(entries.values().map(Cow::Borrowed)),
+            SegmentData::Mapped { rows, .. } => Box::new(
+                (0..rows.len()).filter_map(move |row| self.mapped_entry(row).map(Cow::Owned)),
+            ),
+        }
+    }
+
+    /// IDs of mapped rows that fail their checksum. The ID comes from the
+    /// segment's in-memory index, since the copy in the file can't be trusted.
+    fn corrupt_ids(&self) -> Vec<Uuid> {
+        let SegmentData::Mapped { file, index, .. } = &self.data else {
+            return Vec::new();
+        };
+        index
+            .iter()
+            .filter(|(_, &row)| file.verify(row).is_err())
+            .map(|(&id, _)| id)
+            .collect()
+    }
+
+    /// Copy a mapped row to the quarantine file beside the segment file
+    fn quarantine_row(&self, id: &Uuid) {
+        let SegmentData::Mapped { file, index, .. } = &self.data else {
+            return;
+        };
+        let Some(&row) = index.get(id) else {
+            return;
+        };
+        let reason = match file.verify(row) {
+            Err(e) => e.to_string(),
+            Ok(()) => "quarantined".to_string(),
+        };
+        let quarantine = Quarantine::beside(file.path());
+        let content = checksum::to_hex(file.row_bytes(row));
+        if let Err(e) = quarantine.keep(file.path(), row, &reason, &content) {
+            warn!(
+                "Failed to quarantine row {} of segment {}: {}",
+                row, self.id, e
+            );
+        }
+    }
+}
+
+/// Size of one segment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentInfo {
+    pub id: u64,
+    pub entries: usize,
+    pub tombstones: usize,
+    /// Vectors are in a memory-mapped file
+    pub mapped: bool,
+    /// The mapped file is locked in RAM
+    pub pinned: bool,
+}
+
+/// Layout of a [`SegmentStore`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentStats {
+    pub memtable_entries: usize,
+    /// Oldest first
+    pub segments: Vec<SegmentInfo>,
+    pub tombstones: usize,
+    /// Rows quarantined for failing their checksum
+    pub quarantined: usize,
+}
+
+/// Memtable plus segments, answering the map-style lookups `VectorIndex`
+/// needs. An ID is live in at most one place: the memtable, or one segment
+/// where it isn't tombstoned.
+#[derive(Debug)]
+pub struct SegmentStore {
+    config: SegmentConfig,
+    storage: Option<MmapStorage>,
+    /// Distinguishes this store's files from others sharing a directory
+    file_prefix: Uuid,
+    memtable: HashMap<Uuid, VectorEntry>,
+    /// Oldest first
+    segments: Vec<Arc<Segment>>,
+    /// Deleted IDs per segment
+    tombstones: HashMap<u64, HashSet<Uuid>>,
+    live: usize,
+    next_segment_id: u64,
+    quarantined: usize,
+}
+
+impl Default for SegmentStore {
+    fn default() -> Self {
+        Self::new(SegmentConfig::default())
+    }
+}
+
+impl SegmentStore {
+    pub fn new(config: SegmentConfig) -> Self {
+        Self {
+            config,
+            storage: None,
+            file_prefix: Uuid::new_v4(),
+            memtable: HashMap::new(),
+            segments: Vec::new(),
+            tombstones: HashMap::new(),
+            live: 0,
+            next_segment_id: 0,
+            quarantined: 0,
+        }
+    }
+
+    pub fn config(&self) -> SegmentConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: SegmentConfig) {
+        self.config = config;
+    }
+
+    pub fn storage(&self) -> Option<&MmapStorage> {
+        self.storage.as_ref()
+    }
+
+    /// Map segments created from now on from files under `storage.dir`, or
+    /// keep them on the heap with `None`. Existing segments move over as
+    /// they are merged.
+    pub fn set_storage(&mut self, storage: Option<MmapStorage>) -> std::io::Result<()> {
+        if let Some(storage) = &storage {
+            std::fs::create_dir_all(&storage.dir)?;
+        }
+        self.storage = storage;
+        self.repin();
+        Ok(())
+    }
+
+    fn segment_path(&self, id: u64) -> Option<PathBuf> {
+        self.storage.as_ref().map(|storage| {
+            storage
+                .dir
+                .join(format!("{}-{:06}.vec", self.file_prefix, id))
+        })
+    }
+
+    /// Lock the newest mapped segments in RAM, up to
+    /// [`MmapStorage::pin_hot_segments`], and release the rest
+    fn repin(&self) {
+        let hot = self.storage.as_ref().map_or(0, |s| s.pin_hot_segments);
+        let files = self.segments.iter().rev().filter_map(|s| s.file());
+        for (rank, file) in files.enumerate() {
+            let result = if rank < hot { file.pin() } else { file.unpin() };
+            if let Err(e) = result {
+                warn!(
+                    "Failed to change pinning of {}: {}",
+                    file.path().display(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Pass an access hint to every mapped segment, e.g. before a full scan
+    pub fn advise(&self, pattern: AccessPattern) {
+        for file in self.segments.iter().filter_map(|s| s.file()) {
+            if let Err(e) = file.advise(pattern) {
+                debug!("madvise failed for {}: {}", file.path().display(), e);
+            }
+        }
+    }
+
+    fn is_deleted(&self, segment: u64, id: &Uuid) -> bool {
+        self.tombstones
+            .get(&segment)
+            .is_some_and(|deleted| deleted.contains(id))
+    }
+
+    pub fn get(&self, id: &Uuid) -> Option<Cow<'_, VectorEntry>> {
+        if let Some(entry) = self.memtable.get(id) {
+            return Some(Cow::Borrowed(entry));
+        }
+        self.segments
+            .iter()
+            .rev()
+            .filter(|segment| !self.is_deleted(segment.id, id))
+            .find_map(|segment| segment.get(id))
+    }
+
+    pub fn contains_key(&self, id: &Uuid) -> bool {
+        self.memtable.contains_key(id)
+            || self
+                .segments
+                .iter()
+                .any(|segment| segment.contains(id) && !self.is_deleted(segment.id, id))
+    }
+
+    /// Live entries
+    pub fn len(&self) -> usize {
+        self.live
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.live == 0
+    }
+
+    /// Add an entry to the memtable, freezing it into a segment when full.
+    /// Callers check for an existing entry first.
+    pub fn insert(&mut self, id: Uuid, entry: VectorEntry) {
+        if self.memtable.insert(id, entry).is_none() {
+            self.live += 1;
+        }
+        if self.memtable.len() >= self.config.memtable_limit.max(1) {
+            self.flush();
+        }
+    }
+
+    /// Remove a live entry, tombstoning it if it's already in a segment
+    pub fn remove(&mut self, id: &Uuid) -> Option<VectorEntry> {
+        if let Some(entry) = self.memtable.remove(id) {
+            self.live -= 1;
+            return Some(entry);
+        }
+        let segment = self
+            .segments
+            .iter()
+            .rev()
+            .find(|segment| segment.contains(id) && !self.is_deleted(segment.id, id))?
+            .clone();
+        self.tombstones.entry(segment.id).or_default().insert(*id);
+        self.live -= 1;
+        segment.get(id).map(Cow::into_owned)
+    }
+
+    /// Every live entry, memtable first
+    pub fn iter(&self) -> impl Iterator<Item = (Uuid, Cow<'_, VectorEntry>)> + '_ {
+        let memtable = self
+            .memtable
+            .iter()
+            .map(|(id, entry)| (*id, Cow::Borrowed(entry)));
+        let frozen = self.segments.iter().flat_map(move |segment| {
+            segment
+                .entries()
+                .filter(move |entry| !self.is_deleted(segment.id, &entry.id))
+                .map(|entry| (entry.id, entry))
+        });
+        memtable.chain(frozen)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = Uuid> + '_ {
+        self.iter().map(|(id, _)| id)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = Cow<'_, VectorEntry>> + '_ {
+        self.iter().map(|(_, entry)| entry)
+    }
+
+    /// Freeze the memtable into a new segment; returns its ID, or `None`
+    /// when the memtable was empty
+    pub fn flush(&mut self) -> Option<u64> {
+        if self.memtable.is_empty() {
+            return None;
+        }
+        let id = self.next_segment_id;
+        self.next_segment_id += 1;
+        let segment = match self.segment_path(id) {
+            Some(path) => {
+                let memtable = &self.memtable;
+                let segment =
+                    Segment::build(id, Some(path), || memtable.values().map(Cow::Borrowed));
+                self.memtable.clear();
+                segment
+            }
+            None => Segment::memory(id, std::mem::take(&mut self.memtable)),
+        };
+        debug!(
+            "Froze memtable into segment {} ({} entries{})",
+            id,
+            segment.len(),
+            if segment.file().is_some() {
+                ", mapped"
+            } else {
+                ""
+            }
+        );
+        self.segments.push(Arc::new(segment));
+        self.repin();
+        Some(id)
+    }
+
+    /// Freeze entries known to be new straight into segments of
+    /// `memtable_limit` entries each, skipping the memtable. Bulk builds
+    /// pass them sorted along the Hilbert curve, so each segment covers a
+    /// contiguous stretch of it.
+    pub fn bulk_load(&mut self, entries: Vec<VectorEntry>) {
+        let limit = self.config.memtable_limit.max(1);
+        let mut entries = entries.into_iter().peekable();
+        while entries.peek().is_some() {
+            let chunk: Vec<VectorEntry> = entries.by_ref().take(limit).collect();
+            let id = self.next_segment_id;
+            self.next_segment_id += 1;
+            self.live += chunk.len();
+            let segment = match self.segment_path(id) {
+                Some(path) => Segment::build(id, Some(path), || chunk.iter().map(Cow::Borrowed)),
+                None => Segment::memory(id, chunk.into_iter().map(|e| (e.id, e)).collect()),
+            };
+            self.segments.push(Arc::new(segment));
+        }
+        self.repin();
+    }
+
+    /// Flush the memtable and share every segment as it stands
+    pub fn snapshot(&mut self) -> SegmentSnapshot {
+        self.flush();
+        SegmentSnapshot {
+            segments: self.segments.clone(),
+            tombstones: self.tombstones.clone(),
+            len: self.live,
+        }
+    }
+
+    pub fn stats(&self) -> SegmentStats {
+        SegmentStats {
+            memtable_entries: self.memtable.len(),
+            segments: self
+                .segments
+                .iter()
+                .map(|segment| SegmentInfo {
+                    id: segment.id,
+                    entries: segment.len(),
+                    tombstones: self.tombstones.get(&segment.id).map_or(0, |t| t.len()),
+                    mapped: segment.file().is_some(),
+                    pinned: segment.file().is_some_and(|file| file.is_pinned()),
+                })
+                .collect(),
+            tombstones: self.tombstones.values().map(|t| t.len()).sum(),
+            quarantined: self.quarantined,
+        }
+    }
+
+    /// Live entries in mapped segments whose rows fail their checksum, as
+    /// (segment ID, entry ID). Reads every mapped row.
+    pub fn find_corrupt(&self) -> Vec<(u64, Uuid)> {
+        let mut corrupt = Vec::new();
+        for segment in &self.segments {
+            let Some(file) = segment.file() else {
+                continue;
+            };
+            let _ = file.advise(AccessPattern::Sequential);
+            corrupt.extend(
+                segment
+                    .corrupt_ids()
+                    .into_iter()
+                    .filter(|id| !self.is_deleted(segment.id, id))
+                    .map(|id| (segment.id, id)),
+            );
+            let _ = file.advise(AccessPattern::Random);
+        }
+        corrupt
+    }
+
+    /// Tombstone a corrupted entry found by [`find_corrupt`](Self::find_corrupt)
+    /// and copy its row to the quarantine file beside the segment. Returns
+    /// false if the segment has since been merged away or the entry deleted.
+    pub fn quarantine(&mut self, segment_id: u64, id: &Uuid) -> bool {
+        let Some(segment) = self
+            .segments
+            .iter()
+            .find(|segment| segment.id == segment_id)
+            .cloned()
+        else {
+            return false;
+        };
+        if !segment.contains(id) || self.is_deleted(segment_id, id) {
+            return false;
+        }
+        segment.quarantine_row(id);
+        self.tombstones.entry(segment_id).or_default().insert(*id);
+        self.live -= 1;
+        self.quarantined += 1;
+        true
+    }
+
+    /// Pick segments to merge: all of them when `force` is set, otherwise
+    /// segments carrying too many tombstones, or the smallest few once there
+    /// are more than `max_segments`
+    pub fn plan_merge(&mut self, force: bool) -> Option<MergePlan> {
+        let mut inputs: Vec<Arc<Segment>> = if force {
+            self.segments.clone()
+        } else {
+            self.segments
+                .iter()
+                .filter(|segment| {
+                    let deleted = self.tombstones.get(&segment.id).map_or(0, |t| t.len());
+                    deleted as f32 > segment.len() as f32 * self.config.max_tombstone_ratio
+                })
+                .cloned()
+                .collect()
+        };
+        if inputs.is_empty() && self.segments.len() > self.config.max_segments {
+            let mut by_size = self.segments.clone();
+            by_size.sort_by_key(|segment| segment.len());
+            by_size.truncate(self.config.merge_factor.max(2));
+            inputs = by_size;
+        }
+        // Rewriting one segment is only worthwhile to drop its tombstones
+        let has_tombstones = inputs
+            .iter()
+            .any(|segment| self.tombstones.contains_key(&segment.id));
+        if inputs.is_empty() || (inputs.len() == 1 && !has_tombstones) {
+            return None;
+        }
+
+        let id = self.next_segment_id;
+        self.next_segment_id += 1;
+        let tombstones = inputs
+            .iter()
+            .filter_map(|segment| {
+                self.tombstones
+                    .get(&segment.id)
+                    .map(|deleted| (segment.id, deleted.clone()))
+            })
+            .collect();
+        Some(MergePlan {
+            id,
+            path: self.segment_path(id),
+            inputs,
+            tombstones,
+        })
+    }
+
+    /// Swap a plan's inputs for its merged segment. Entries deleted while the
+    /// merge ran are tombstoned again in the merged segment. Returns `None`
+    /// if an input is no longer part of the store.
+    pub fn apply_merge(&mut self, merged: MergedSegment) -> Option<MergeReport> {
+        let inputs: HashSet<u64> = merged.inputs.iter().copied().collect();
+        if self
+            .segments
+            .iter()
+            .filter(|segment| inputs.contains(&segment.id))
+            .count()
+            != inputs.len()
+        {
+            warn!(
+                "Discarding merge into segment {}: inputs changed",
+                merged.segment.id
+            );
+            return None;
+        }
+
+        // Corrupted rows deleted since planning are already off the live count
+        let lost = merged
+            .quarantined
+            .iter()
+            .filter(|id| {
+                !merged
+                    .inputs
+                    .iter()
+                    .any(|input| self.is_deleted(*input, id))
+            })
+            .count();
+        self.live -= lost;
+        self.quarantined += lost;
+
+        let mut carried = HashSet::new();
+        for input in &merged.inputs {
+            if let Some(deleted) = self.tombstones.remove(input) {
+                carried.extend(deleted.into_iter().filter(|id| merged.segment.contains(id)));
+            }
+        }
+        if !carried.is_empty() {
+            self.tombstones.insert(merged.segment.id, carried);
+        }
+
+        let position = self
+            .segments
+            .iter()
+            .position(|segment| inputs.contains(&segment.id))
+            .unwrap_or(self.segments.len());
+        self.segments
+            .retain(|segment| !inputs.contains(&segment.id));
+        let report = MergeReport {
+            segment_id: merged.segment.id,
+            inputs: merged.inputs.len(),
+            entries: merged.segment.len(),
+            dropped: merged.dropped,
+            quarantined: merged.quarantined,
+        };
+        if merged.segment.is_empty() {
+            self.tombstones.remove(&merged.segment.id);
+        } else {
+            self.segments
+                .insert(position.min(self.segments.len()), Arc::new(merged.segment));
+        }
+        self.repin();
+        Some(report)
+    }
+}
+
+/// Segments chosen for a merge, with their tombstones as of planning
+#[derive(Debug)]
+pub struct MergePlan {
+    id: u64,
+    /// File for the merged segment when the store maps segments
+    path: Option<PathBuf>,
+    inputs: Vec<Arc<Segment>>,
+    tombstones: HashMap<u64, HashSet<Uuid>>,
+}
+
+impl MergePlan {
+    /// Combine the inputs, leaving out tombstoned entries and quarantining
+    /// rows that fail their checksum. Needs no lock on the store.
+    pub fn build(self) -> MergedSegment {
+        let (inputs, tombstones) = (&self.inputs, &self.tombstones);
+        let mut quarantined = Vec::new();
+        for segment in inputs {
+            let deleted = tombstones.get(&segment.id);
+            for id in segment.corrupt_ids() {
+                if !deleted.is_some_and(|d| d.contains(&id)) {
+                    segment.quarantine_row(&id);
+                    quarantined.push(id);
+                }
+            }
+        }
+
+        let live = move || {
+            inputs.iter().flat_map(move |segment| {
+                let deleted = tombstones.get(&segment.id);
+                segment
+                    .entries()
+                    .filter(move |entry| !deleted.is_some_and(|d| d.contains(&entry.id)))
+            })
+        };
+        let segment = Segment::build(self.id, self.path.clone(), live);
+        let stored: usize = self.inputs.iter().map(|segment| segment.len()).sum();
+        MergedSegment {
+            dropped: stored - segment.len() - quarantined.len(),
+            inputs: self.inputs.iter().map(|segment| segment.id).collect(),
+            segment,
+            quarantined,
+        }
+    }
+}
+
+/// Output of [`MergePlan::build`], ready for [`SegmentStore::apply_merge`]
+#[derive(Debug)]
+pub struct MergedSegment {
+    segment: Segment,
+    inputs: Vec<u64>,
+    dropped: usize,
+    quarantined: Vec<Uuid>,
+}
+
+/// Outcome of one merge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeReport {
+    pub segment_id: u64,
+    /// Segments combined
+    pub inputs: usize,
+    /// Entries in the merged segment
+    pub entries: usize,
+    /// Tombstoned entries left out
+    pub dropped: usize,
+    /// Entries left out because their rows failed their checksum
+    pub quarantined: Vec<Uuid>,
+}
+
+/// Point-in-time view of a store that later writes and merges don't affect
+#[derive(Debug, Clone)]
+pub struct SegmentSnapshot {
+    segments: Vec<Arc<Segment>>,
+    tombstones: HashMap<u64, HashSet<Uuid>>,
+    len: usize,
+}
+
+impl SegmentSnapshot {
+    /// Live entries at the time of the snapshot
+    pub fn entries(&self) -> impl Iterator<Item = Cow<'_, VectorEntry>> + '_ {
+        self.segments.iter().flat_map(move |segment| {
+            let deleted = self.tombstones.get(&segment.id);
+            segment
+                .entries()
+                .filter(move |entry| !deleted.is_some_and(|d| d.contains(&entry.id)))
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Runs merges on every index in the background
+pub struct SegmentMerger {
+    shard_manager: Arc<ShardManager>,
+    interval: Duration,
+}
+
+impl SegmentMerger {
+    pub fn new(shard_manager: Arc<ShardManager>, interval: Duration) -> Self {
+        Self {
+            shard_manager,
+            interval,
+        }
+    }
+
+    /// Merge every index that needs it; returns the merges performed
+    pub async fn run_once(&self) -> Vec<(Uuid, MergeReport)> {
+        let mut merged = Vec::new();
+        for (shard_id, index) in self.shard_manager.get_vector_indices().await {
+            while let Some(report) = index.merge_segments(false).await {
+                merged.push((shard_id, report));
+            }
+        }
+        merged
+    }
+
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        info!("Starting segment merger (interval: {:?})", self.interval);
+
+        tasks::spawn("sharding", "segment merger", async move {
+            loop {
+                tokio::time::sleep(self.interval).await;
+                self.run_once().await;
+            }
+        })
+    }
+}
@@ -0,0 +1,111 @@
+//! Opt-in sampling of production search requests into a replayable log.
+//!
+//! A [`ShadowRecorder`] attached to the shard manager keeps a bounded sample
+//! of searches together with the results and latency they had at the time,
+//! so a candidate build can later be replayed against the same traffic.
+//! Saved samples carry a checksum per line; lines that fail it are
+//! quarantined on load instead of being replayed.
+
+use anyhow::{anyhow, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::Path;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::core::checksum;
+use crate::query::QueryExpr;
+
+/// A search as it was served in production
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedSearch {
+    pub shard_id: Uuid,
+    pub query: Vec<f32>,
+    pub limit: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<QueryExpr>,
+    /// IDs returned, best first
+    pub result_ids: Vec<Uuid>,
+    pub latency_ms: f64,
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Bounded sample of recent searches
+#[derive(Debug)]
+pub struct ShadowRecorder {
+    /// Fraction of searches recorded, 0.0 to 1.0
+    sample_rate: f64,
+    capacity: usize,
+    requests: RwLock<VecDeque<RecordedSearch>>,
+}
+
+impl ShadowRecorder {
+    pub fn new(sample_rate: f64, capacity: usize) -> Self {
+        Self {
+            sample_rate: sample_rate.clamp(0.0, 1.0),
+            capacity: capacity.max(1),
+            requests: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Whether the next search should be recorded
+    pub fn should_sample(&self) -> bool {
+        self.sample_rate >= 1.0 || rand::thread_rng().gen_bool(self.sample_rate)
+    }
+
+    /// Keep a search, evicting the oldest one when full
+    pub async fn record(&self, search: RecordedSearch) {
+        let mut requests = self.requests.write().await;
+        if requests.len() >= self.capacity {
+            requests.pop_front();
+        }
+        requests.push_back(search);
+    }
+
+    /// Recorded searches, oldest first
+    pub async fn snapshot(&self) -> Vec<RecordedSearch> {
+        self.requests.read().await.iter().cloned().collect()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.requests.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.requests.read().await.is_empty()
+    }
+
+    /// Write the sample to a JSON-lines file
+    pub async fn save<P: AsRef<Path>>(&self, path: P) -> Result<usize> {
+        let path = path.as_ref();
+        let requests = self.snapshot().await;
+        let mut file = std::fs::File::create(path)
+            .map_err(|e| anyhow!("Failed to create {}: {}", path.display(), e))?;
+        for request in &requests {
+            writeln!(file, "{}", checksum::seal(&serde_json::to_string(request)?))?;
+        }
+        Ok(requests.len())
+    }
+
+    /// Read a sample written by [`save`](Self::save), skipping corrupted
+    /// lines
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<RecordedSearch>> {
+        let path = path.as_ref();
+        checksum::read_sealed_lines(path)?
+            .records
+            .into_iter()
+            .map(|(line_no, line)| {
+                serde_json::from_str(&line).map_err(|e| {
+                    anyhow!(
+                        "Invalid recorded search {}:{}: {}",
+                        path.display(),
+                        line_no,
+                        e
+                    )
+                })
+            })
+            .collect()
+    }
+}
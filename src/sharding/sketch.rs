@@ -0,0 +1,271 @@
+//! Approximate statistics kept alongside each vector index.
+//!
+//! HyperLogLog sketches estimate how many distinct values each metadata
+//! field has and how many vectors have ever been added, in a few kilobytes
+//! regardless of index size. Together with exact per-field presence counts
+//! they give the query planner cheap selectivity estimates and back the
+//! index statistics reported by the stats API.
+//!
+//! Sketches only grow: removing a vector lowers presence counts but not the
+//! distinct-value estimates, which therefore drift high on indexes with
+//! heavy deletes until [`IndexSketches::rebuild`] is called.
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use uuid::Uuid;
+
+use crate::sharding::compression;
+
+/// Register index bits; 4096 registers give roughly 1.6% standard error
+const PRECISION: u32 = 12;
+const REGISTERS: usize = 1 << PRECISION;
+
+/// Metadata fields sketched per index; fields beyond this aren't tracked
+pub const MAX_SKETCHED_FIELDS: usize = 64;
+
+/// Selectivity assumed for range predicates, as most planners do
+const RANGE_SELECTIVITY: f64 = 1.0 / 3.0;
+
+/// HyperLogLog distinct-count sketch
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HyperLogLog {
+    pub fn new() -> Self {
+        Self {
+            registers: vec![0; REGISTERS],
+        }
+    }
+
+    /// Add a value. Hashing uses fixed keys, so sketches built on different
+    /// nodes can be merged.
+    pub fn add<T: Hash + ?Sized>(&mut self, value: &T) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        self.add_hash(hasher.finish());
+    }
+
+    pub fn add_hash(&mut self, hash: u64) {
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // Guard bit keeps the rank bounded when the remaining bits are zero
+        let rest = (hash << PRECISION) | (1 << (PRECISION - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Combine with a sketch of another set; the result estimates the union
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (mine, theirs) in self.registers.iter_mut().zip(&other.registers) {
+            *mine = (*mine).max(*theirs);
+        }
+    }
+
+    /// Estimated number of distinct values added
+    pub fn estimate(&self) -> f64 {
+        let m = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+
+        // Linear counting is more accurate while many registers are empty
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct FieldSketch {
+    /// Vectors currently carrying the field; exact
+    present: usize,
+    distinct: HyperLogLog,
+}
+
+/// Sketches maintained by a vector index as vectors come and go
+#[derive(Debug, Clone, Default)]
+pub struct IndexSketches {
+    vector_count: usize,
+    vector_ids: HyperLogLog,
+    fields: HashMap<String, FieldSketch>,
+    bytes: MetadataBytes,
+}
+
+impl IndexSketches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, id: Uuid, metadata: Option<&HashMap<String, String>>) {
+        self.vector_count += 1;
+        self.vector_ids.add(&id);
+        for (key, value) in metadata.into_iter().flatten() {
+            self.bytes.add(key, value);
+            let tracked = self.fields.len();
+            let field = match self.fields.get_mut(key) {
+                Some(field) => field,
+                None if tracked < MAX_SKETCHED_FIELDS => {
+                    self.fields.entry(key.clone()).or_default()
+                }
+                None => continue,
+            };
+            field.present += 1;
+            field.distinct.add(value.as_str());
+        }
+    }
+
+    pub fn remove(&mut self, metadata: Option<&HashMap<String, String>>) {
+        self.vector_count = self.vector_count.saturating_sub(1);
+        for (key, value) in metadata.into_iter().flatten() {
+            self.bytes.subtract(key, value);
+            if let Some(field) = self.fields.get_mut(key) {
+                field.present = field.present.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Recompute from the current contents, dropping stale distinct values
+    pub fn rebuild<'a>(
+        entries: impl IntoIterator<Item = (Uuid, Option<&'a HashMap<String, String>>)>,
+    ) -> Self {
+        let mut sketches = Self::new();
+        for (id, metadata) in entries {
+            sketches.insert(id, metadata);
+        }
+        sketches
+    }
+
+    /// Point-in-time statistics for planning and reporting
+    pub fn statistics(&self) -> IndexStatistics {
+        IndexStatistics {
+            vector_count: self.vector_count,
+            vectors_ever_added: self.vector_ids.estimate().round() as u64,
+            fields: self
+                .fields
+                .iter()
+                .map(|(key, field)| {
+                    (
+                        key.clone(),
+                        FieldStatistics {
+                            present: field.present,
+                            // Can't have more distinct values than vectors
+                            distinct_values: field
+                                .distinct
+                                .estimate()
+                                .round()
+                                .clamp(1.0, field.present.max(1) as f64)
+                                as u64,
+                        },
+                    )
+                })
+                .collect(),
+            metadata_bytes: MetadataBytes {
+                compression_ratio: self.bytes.ratio(),
+                ..self.bytes
+            },
+        }
+    }
+}
+
+/// Metadata size before and after compression
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetadataBytes {
+    /// Keys plus values as written by clients
+    pub raw: u64,
+    /// Keys plus values as held in memory
+    pub stored: u64,
+    /// Values held compressed
+    pub compressed_values: u64,
+    /// Raw size over stored size; 1.0 when nothing is compressed
+    pub compression_ratio: f64,
+}
+
+impl MetadataBytes {
+    fn add(&mut self, key: &str, value: &str) {
+        self.raw += (key.len() + compression::original_len(value)) as u64;
+        self.stored += (key.len() + value.len()) as u64;
+        self.compressed_values += compression::is_compressed(value) as u64;
+    }
+
+    fn subtract(&mut self, key: &str, value: &str) {
+        let raw = (key.len() + compression::original_len(value)) as u64;
+        self.raw = self.raw.saturating_sub(raw);
+        self.stored = self.stored.saturating_sub((key.len() + value.len()) as u64);
+        self.compressed_values = self
+            .compressed_values
+            .saturating_sub(compression::is_compressed(value) as u64);
+    }
+
+    fn ratio(&self) -> f64 {
+        if self.stored == 0 {
+            1.0
+        } else {
+            self.raw as f64 / self.stored as f64
+        }
+    }
+}
+
+/// Statistics for one metadata field
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FieldStatistics {
+    /// Vectors carrying the field
+    pub present: usize,
+    /// Estimated distinct values
+    pub distinct_values: u64,
+}
+
+/// Snapshot of an index's sketches
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IndexStatistics {
+    pub vector_count: usize,
+    /// Estimated distinct vector IDs ever added, including removed ones
+    pub vectors_ever_added: u64,
+    pub fields: HashMap<String, FieldStatistics>,
+    #[serde(default)]
+    pub metadata_bytes: MetadataBytes,
+}
+
+impl IndexStatistics {
+    /// Fraction of vectors carrying a field; unknown fields are assumed to
+    /// be on every vector so estimates err towards scanning
+    pub fn presence(&self, key: &str) -> f64 {
+        if self.vector_count == 0 {
+            return 1.0;
+        }
+        match self.fields.get(key) {
+            Some(field) => (field.present as f64 / self.vector_count as f64).min(1.0),
+            None if self.fields.len() >= MAX_SKETCHED_FIELDS => 1.0,
+            None => 0.0,
+        }
+    }
+
+    /// Estimated fraction of vectors with `key == value`, assuming values
+    /// are uniformly distributed
+    pub fn equality_selectivity(&self, key: &str) -> f64 {
+        let distinct = self.fields.get(key).map_or(1, |f| f.distinct_values.max(1));
+        self.presence(key) / distinct as f64
+    }
+
+    /// Estimated fraction of vectors with `key` in a set of `values` values
+    pub fn in_selectivity(&self, key: &str, values: usize) -> f64 {
+        (self.equality_selectivity(key) * values as f64).min(self.presence(key))
+    }
+
+    pub fn range_selectivity(&self, key: &str) -> f64 {
+        self.presence(key) * RANGE_SELECTIVITY
+    }
+}
@@ -0,0 +1,360 @@
+//! Persistence for shards and their vectors.
+//!
+//! A [`StorageBackend`] keeps one [`ShardRecord`] per shard (its settings,
+//! split routes and index shape) plus the shard's vectors. `ShardManager`
+//! marks shards dirty as they change and writes them out on
+//! [`ShardManager::flush`](crate::sharding::manager::ShardManager::flush),
+//! which a background task calls periodically; on startup
+//! [`ShardManager::restore`](crate::sharding::manager::ShardManager::restore)
+//! reads the records back and loads each shard's vectors either eagerly or
+//! on first access.
+//!
+//! Vectors are stored exactly as held in the index, so compressed and
+//! encrypted metadata stays that way at rest. Change feeds and aggregate
+//! views are not persisted; feeds restart at offset 0 after a restart.
+
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::core::checksum;
+use crate::sharding::compression::CompressionConfig;
+use crate::sharding::manager::{IdScheme, ShardStatus};
+use crate::sharding::vector_index::{DistanceMetric, IndexType, VectorEntry};
+use crate::utils::fs::write_atomic;
+
+/// Shape of a shard's vector index, enough to recreate it empty
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexRecord {
+    pub name: String,
+    pub dimensions: usize,
+    pub distance_metric: DistanceMetric,
+    #[serde(default)]
+    pub index_type: IndexType,
+}
+
+/// Everything about a shard except its vectors
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShardRecord {
+    pub id: Uuid,
+    pub name: String,
+    pub status: ShardStatus,
+    pub vector_count: usize,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// `None` for shards created without an index
+    #[serde(default)]
+    pub index: Option<IndexRecord>,
+    #[serde(default)]
+    pub id_scheme: IdScheme,
+    #[serde(default)]
+    pub embedding_model: Option<String>,
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub compression: Option<CompressionConfig>,
+    /// Shards split off this one, as the Hilbert key each range starts at
+    #[serde(default)]
+    pub key_routes: Vec<(u64, Uuid)>,
+}
+
+/// Durable home for shard records and vectors
+#[async_trait]
+pub trait StorageBackend: Send + Sync + fmt::Debug {
+    /// Short name of the backend, used in logs
+    fn name(&self) -> &str;
+
+    /// Insert or replace a shard's record
+    async fn save_shard(&self, record: &ShardRecord) -> Result<()>;
+
+    async fn load_shards(&self) -> Result<Vec<ShardRecord>>;
+
+    /// Replace a shard's stored vectors with `entries`
+    async fn save_vectors(&self, shard_id: Uuid, entries: &[VectorEntry]) -> Result<()>;
+
+    /// A shard's stored vectors; empty if none were ever saved
+    async fn load_vectors(&self, shard_id: Uuid) -> Result<Vec<VectorEntry>>;
+
+    /// Remove a shard's record and vectors; a no-op for unknown shards
+    async fn delete_shard(&self, shard_id: Uuid) -> Result<()>;
+
+    /// Make everything saved so far durable
+    async fn flush(&self) -> Result<()>;
+}
+
+/// Storage engines [`PersistenceConfig::open`] can create
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageEngine {
+    /// [`FileStorage`]
+    File,
+    /// [`SledStorage`]; requires the `sled` feature
+    Sled,
+}
+
+impl std::str::FromStr for StorageEngine {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "file" => Ok(Self::File),
+            "sled" => Ok(Self::Sled),
+            other => Err(anyhow!("Unknown storage engine '{}'", other)),
+        }
+    }
+}
+
+/// Where and how `ShardManager` persists shards
+#[derive(Debug, Clone)]
+pub struct PersistenceConfig {
+    pub engine: StorageEngine,
+    pub path: PathBuf,
+    /// How often shards changed since the last flush are written out
+    pub flush_interval: Duration,
+    /// Load a restored shard's vectors on first access instead of at startup
+    pub lazy_load: bool,
+    /// Without `lazy_load`, load restored shards in the background after
+    /// startup instead of before it. Searches meanwhile cover the vectors
+    /// loaded so far and report how much that is.
+    pub progressive_load: bool,
+}
+
+impl PersistenceConfig {
+    pub fn new<P: Into<PathBuf>>(engine: StorageEngine, path: P) -> Self {
+        Self {
+            engine,
+            path: path.into(),
+            flush_interval: Duration::from_secs(5),
+            lazy_load: true,
+            progressive_load: false,
+        }
+    }
+
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    pub fn with_lazy_load(mut self, lazy_load: bool) -> Self {
+        self.lazy_load = lazy_load;
+        self
+    }
+
+    pub fn with_progressive_load(mut self, progressive_load: bool) -> Self {
+        self.progressive_load = progressive_load;
+        self
+    }
+
+    /// Open the configured backend, creating its directory if needed
+    pub fn open(&self) -> Result<Arc<dyn StorageBackend>> {
+        match self.engine {
+            StorageEngine::File => Ok(Arc::new(FileStorage::open(&self.path)?)),
+            #[cfg(feature = "sled")]
+            StorageEngine::Sled => Ok(Arc::new(SledStorage::open(&self.path)?)),
+            #[cfg(not(feature = "sled"))]
+            StorageEngine::Sled => Err(anyhow!(
+                "Sled storage requires building with the `sled` feature"
+            )),
+        }
+    }
+}
+
+/// Shards as files under a directory: `shards/<id>.json` holds each record
+/// and `vectors/<id>.jsonl` the vectors, one checksummed line each. Files
+/// are replaced atomically, so a crash mid-flush leaves the previous copy.
+#[derive(Debug)]
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+impl FileStorage {
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        std::fs::create_dir_all(root.join("shards"))?;
+        std::fs::create_dir_all(root.join("vectors"))?;
+        Ok(Self { root })
+    }
+
+    fn shard_path(&self, shard_id: Uuid) -> PathBuf {
+        self.root.join("shards").join(format!("{}.json", shard_id))
+    }
+
+    fn vectors_path(&self, shard_id: Uuid) -> PathBuf {
+        self.root
+            .join("vectors")
+            .join(format!("{}.jsonl", shard_id))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for FileStorage {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn save_shard(&self, record: &ShardRecord) -> Result<()> {
+        let json = serde_json::to_vec_pretty(record)?;
+        write_atomic(&self.shard_path(record.id), |file| {
+            file.write_all(&json)?;
+            Ok(())
+        })
+    }
+
+    async fn load_shards(&self) -> Result<Vec<ShardRecord>> {
+        let mut records = Vec::new();
+        for entry in std::fs::read_dir(self.root.join("shards"))? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let contents = std::fs::read(&path)?;
+            let record = serde_json::from_slice(&contents)
+                .map_err(|e| anyhow!("Invalid shard record {}: {}", path.display(), e))?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    async fn save_vectors(&self, shard_id: Uuid, entries: &[VectorEntry]) -> Result<()> {
+        write_atomic(&self.vectors_path(shard_id), |file| {
+            let mut writer = std::io::BufWriter::new(file);
+            for entry in entries {
+                writeln!(writer, "{}", checksum::seal(&serde_json::to_string(entry)?))?;
+            }
+            writer.flush()?;
+            Ok(())
+        })
+    }
+
+    async fn load_vectors(&self, shard_id: Uuid) -> Result<Vec<VectorEntry>> {
+        let path = self.vectors_path(shard_id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let lines = checksum::read_sealed_lines(&path)?;
+        if lines.quarantined > 0 {
+            warn!(
+                "Quarantined {} corrupted vectors of shard {}",
+                lines.quarantined, shard_id
+            );
+        }
+        lines
+            .records
+            .into_iter()
+            .map(|(line_no, line)| {
+                serde_json::from_str(&line)
+                    .map_err(|e| anyhow!("Invalid vector at {}:{}: {}", path.display(), line_no, e))
+            })
+            .collect()
+    }
+
+    async fn delete_shard(&self, shard_id: Uuid) -> Result<()> {
+        for path in [self.shard_path(shard_id), self.vectors_path(shard_id)] {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn flush(&self) -> Result<()> {
+        // Every save is synced before it replaces the previous file
+        Ok(())
+    }
+}
+
+/// Shards in an embedded sled database: a `shards` tree of records and a
+/// `vectors/<id>` tree per shard keyed by vector ID
+#[cfg(feature = "sled")]
+pub struct SledStorage {
+    path: PathBuf,
+    db: sled::Db,
+    shards: sled::Tree,
+}
+
+#[cfg(feature = "sled")]
+impl fmt::Debug for SledStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SledStorage")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "sled")]
+impl SledStorage {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let db = sled::open(&path)?;
+        let shards = db.open_tree("shards")?;
+        Ok(Self { path, db, shards })
+    }
+
+    fn vectors(&self, shard_id: Uuid) -> Result<sled::Tree> {
+        Ok(self.db.open_tree(format!("vectors/{}", shard_id))?)
+    }
+}
+
+#[cfg(feature = "sled")]
+#[async_trait]
+impl StorageBackend for SledStorage {
+    fn name(&self) -> &str {
+        "sled"
+    }
+
+    async fn save_shard(&self, record: &ShardRecord) -> Result<()> {
+        self.shards
+            .insert(record.id.as_bytes(), serde_json::to_vec(record)?)?;
+        Ok(())
+    }
+
+    async fn load_shards(&self) -> Result<Vec<ShardRecord>> {
+        self.shards
+            .iter()
+            .values()
+            .map(|value| Ok(serde_json::from_slice(&value?)?))
+            .collect()
+    }
+
+    async fn save_vectors(&self, shard_id: Uuid, entries: &[VectorEntry]) -> Result<()> {
+        let tree = self.vectors(shard_id)?;
+        // One batch, so readers never see a half-replaced shard
+        let mut batch = sled::Batch::default();
+        for key in tree.iter().keys() {
+            batch.remove(key?);
+        }
+        for entry in entries {
+            batch.insert(entry.id.as_bytes().as_slice(), serde_json::to_vec(entry)?);
+        }
+        tree.apply_batch(batch)?;
+        Ok(())
+    }
+
+    async fn load_vectors(&self, shard_id: Uuid) -> Result<Vec<VectorEntry>> {
+        self.vectors(shard_id)?
+            .iter()
+            .values()
+            .map(|value| Ok(serde_json::from_slice(&value?)?))
+            .collect()
+    }
+
+    async fn delete_shard(&self, shard_id: Uuid) -> Result<()> {
+        self.shards.remove(shard_id.as_bytes())?;
+        self.db.drop_tree(format!("vectors/{}", shard_id))?;
+        Ok(())
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.db.flush_async().await?;
+        Ok(())
+    }
+}
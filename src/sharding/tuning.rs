@@ -0,0 +1,235 @@
+//! Runtime tuning of search parameters against a latency SLO.
+//!
+//! The Hilbert index trades recall for latency through two knobs: how many
+//! neighbouring curve cells each query probes (the index's analogue of
+//! `nprobe`/`ef`) and how many candidates per requested result it wants
+//! before falling back to a linear scan. [`SearchTuner`] is a feedback
+//! controller over those knobs: when the observed latency percentile
+//! exceeds the target it narrows the search multiplicatively, and while
+//! there is headroom it widens additively to recover recall.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Bounds for [`SearchParams::probe_window`]
+pub const MIN_PROBE_WINDOW: u64 = 1;
+pub const MAX_PROBE_WINDOW: u64 = 64;
+
+/// Bounds for [`SearchParams::candidate_multiplier`]
+pub const MIN_CANDIDATE_MULTIPLIER: usize = 1;
+pub const MAX_CANDIDATE_MULTIPLIER: usize = 16;
+
+/// Decisions kept for inspection
+const MAX_DECISIONS: usize = 100;
+
+/// Parameters controlling how much of an index a search examines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchParams {
+    /// Hilbert cells probed on each side of the query's cell
+    pub probe_window: u64,
+
+    /// Candidates wanted per requested result before the search falls back
+    /// to a linear scan
+    pub candidate_multiplier: usize,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        Self {
+            probe_window: 5,
+            candidate_multiplier: 4,
+        }
+    }
+}
+
+impl SearchParams {
+    /// Clamp both knobs into their supported ranges
+    pub fn clamped(self) -> Self {
+        Self {
+            probe_window: self.probe_window.clamp(MIN_PROBE_WINDOW, MAX_PROBE_WINDOW),
+            candidate_multiplier: self
+                .candidate_multiplier
+                .clamp(MIN_CANDIDATE_MULTIPLIER, MAX_CANDIDATE_MULTIPLIER),
+        }
+    }
+}
+
+/// Latency objective for an index's searches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencySlo {
+    /// Target latency at the chosen percentile
+    pub target_ms: f64,
+
+    /// Percentile compared against the target, e.g. 0.95 for p95
+    #[serde(default = "default_percentile")]
+    pub percentile: f64,
+
+    /// Searches observed per adjustment
+    #[serde(default = "default_window")]
+    pub window: usize,
+
+    /// Fraction of the target below which the search is widened
+    #[serde(default = "default_headroom")]
+    pub headroom: f64,
+}
+
+fn default_percentile() -> f64 {
+    0.95
+}
+
+fn default_window() -> usize {
+    50
+}
+
+fn default_headroom() -> f64 {
+    0.7
+}
+
+impl LatencySlo {
+    pub fn new(target_ms: f64) -> Self {
+        Self {
+            target_ms,
+            percentile: default_percentile(),
+            window: default_window(),
+            headroom: default_headroom(),
+        }
+    }
+}
+
+/// What the controller did at the end of a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TuningAction {
+    /// Latency was over target; search less of the index
+    Narrow,
+    /// Latency had headroom; search more of the index
+    Widen,
+    /// Latency was within target, or the knobs were already at their limit
+    Hold,
+}
+
+impl TuningAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TuningAction::Narrow => "narrow",
+            TuningAction::Widen => "widen",
+            TuningAction::Hold => "hold",
+        }
+    }
+}
+
+/// One adjustment made by the controller
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TuningDecision {
+    pub decided_at: DateTime<Utc>,
+    /// Latency at the SLO percentile over the window
+    pub observed_ms: f64,
+    pub target_ms: f64,
+    pub action: TuningAction,
+    /// Parameters in effect after the decision
+    pub params: SearchParams,
+}
+
+/// Feedback controller adjusting [`SearchParams`] to meet a [`LatencySlo`]
+#[derive(Debug, Clone)]
+pub struct SearchTuner {
+    slo: LatencySlo,
+    samples: Vec<f64>,
+    decisions: VecDeque<TuningDecision>,
+}
+
+impl SearchTuner {
+    pub fn new(slo: LatencySlo) -> Self {
+        Self {
+            slo,
+            samples: Vec::new(),
+            decisions: VecDeque::new(),
+        }
+    }
+
+    pub fn slo(&self) -> &LatencySlo {
+        &self.slo
+    }
+
+    /// Recent decisions, oldest first
+    pub fn decisions(&self) -> impl Iterator<Item = &TuningDecision> {
+        self.decisions.iter()
+    }
+
+    /// Record a search latency. Once a full window has been observed,
+    /// returns the decision taken and the parameters to use from now on.
+    pub fn observe(&mut self, latency_ms: f64, current: SearchParams) -> Option<TuningDecision> {
+        self.samples.push(latency_ms);
+        if self.samples.len() < self.slo.window.max(1) {
+            return None;
+        }
+
+        let mut samples = std::mem::take(&mut self.samples);
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let rank = (self.slo.percentile.clamp(0.0, 1.0) * (samples.len() - 1) as f64).round();
+        let observed_ms = samples[rank as usize];
+
+        let (action, params) = if observed_ms > self.slo.target_ms {
+            Self::narrow(current)
+        } else if observed_ms < self.slo.target_ms * self.slo.headroom {
+            Self::widen(current)
+        } else {
+            (TuningAction::Hold, current)
+        };
+
+        let decision = TuningDecision {
+            decided_at: Utc::now(),
+            observed_ms,
+            target_ms: self.slo.target_ms,
+            action,
+            params,
+        };
+        if self.decisions.len() == MAX_DECISIONS {
+            self.decisions.pop_front();
+        }
+        self.decisions.push_back(decision.clone());
+        Some(decision)
+    }
+
+    /// Back off quickly: shrink the probe window by a quarter and fall back
+    /// to linear scans less eagerly
+    fn narrow(current: SearchParams) -> (TuningAction, SearchParams) {
+        let next = SearchParams {
+            probe_window: current
+                .probe_window
+                .saturating_sub((current.probe_window / 4).max(1)),
+            candidate_multiplier: current.candidate_multiplier.saturating_sub(1),
+        }
+        .clamped();
+        let action = if next == current {
+            TuningAction::Hold
+        } else {
+            TuningAction::Narrow
+        };
+        (action, next)
+    }
+
+    /// Recover recall gradually: probe one more cell per side, and once the
+    /// window is maxed out, want more candidates before trusting it
+    fn widen(current: SearchParams) -> (TuningAction, SearchParams) {
+        let next = if current.probe_window < MAX_PROBE_WINDOW {
+            SearchParams {
+                probe_window: current.probe_window + 1,
+                ..current
+            }
+        } else {
+            SearchParams {
+                candidate_multiplier: current.candidate_multiplier + 1,
+                ..current
+            }
+        }
+        .clamped();
+        let action = if next == current {
+            TuningAction::Hold
+        } else {
+            TuningAction::Widen
+        };
+        (action, next)
+    }
+}
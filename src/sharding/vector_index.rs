@@ -1,15 +1,26 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 use crate::core::metrics::MetricsCollector;
 use crate::core::vector::Vector;
+use crate::query::facets::{FacetCollector, FacetRequest, Facets};
+use crate::query::{ExecutionPlan, QueryPlanner, SearchEstimate};
 use crate::sharding::hilbert::HilbertCurve;
+use crate::sharding::hnsw::{HnswGraph, HnswParams};
+use crate::sharding::mmap::{AccessPattern, MmapStorage};
+use crate::sharding::segments::{
+    MergeReport, SegmentConfig, SegmentSnapshot, SegmentStats, SegmentStore,
+};
+use crate::sharding::sketch::{IndexSketches, IndexStatistics};
+use crate::sharding::tuning::{LatencySlo, SearchParams, SearchTuner, TuningDecision};
 
 /// Vector index entry that maps a vector to its ID and metadata
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VectorEntry {
     /// Unique ID for this vector
     pub id: Uuid,
@@ -40,13 +51,48 @@ pub struct SearchResult {
     pub score: f32,
 }
 
+/// Results of a search that may have been cut short by a deadline
+#[derive(Debug, Clone)]
+pub struct SearchOutcome {
+    pub results: Vec<SearchResult>,
+
+    /// Facet counts, when requested
+    pub facets: Option<Facets>,
+
+    /// The deadline passed before every candidate was scored, so these are
+    /// the best results among those scored in time
+    pub partial: bool,
+
+    /// Fraction of the shard's vectors that were searchable, below 1.0
+    /// while the shard is still being loaded from storage
+    pub coverage: f32,
+}
+
+impl Default for SearchOutcome {
+    fn default() -> Self {
+        Self {
+            results: Vec::new(),
+            facets: None,
+            partial: false,
+            coverage: 1.0,
+        }
+    }
+}
+
+/// Candidates scored between deadline checks
+const DEADLINE_CHECK_INTERVAL: usize = 256;
+
 /// Type of distance metric to use for search
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum DistanceMetric {
     Euclidean,
     Cosine,
     Manhattan,
+    /// Number of differing components, for binary (0/1) vectors
     Hamming,
+    /// Dot product, for embeddings trained for it. A similarity, so higher
+    /// scores rank first.
+    InnerProduct,
 }
 
 impl DistanceMetric {
@@ -57,6 +103,17 @@ impl DistanceMetric {
             Self::Cosine => 1.0 - a.cosine_similarity(b), // Convert similarity to distance
             Self::Manhattan => a.manhattan_distance(b),
             Self::Hamming => a.hamming_distance(b) as f32,
+            Self::InnerProduct => a.dot(b),
+        }
+    }
+
+    /// Distance between two vectors, lower for closer ones under every
+    /// metric: similarities are negated. For code that needs an ordering
+    /// by closeness rather than a score.
+    pub fn distance(&self, a: &Vector, b: &Vector) -> f32 {
+        match self {
+            Self::InnerProduct => -a.dot(b),
+            _ => self.calculate(a, b),
         }
     }
 
@@ -65,6 +122,29 @@ impl DistanceMetric {
         match self {
             Self::Euclidean | Self::Manhattan | Self::Hamming => true,
             Self::Cosine => true, // Since we convert similarity to distance
+            Self::InnerProduct => false,
+        }
+    }
+}
+
+/// How an index finds the candidates a search scores
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IndexType {
+    /// Probe the Hilbert buckets around the query's, falling back to a scan
+    /// when too few are found. Cheap to maintain; fine for small shards.
+    #[default]
+    Hilbert,
+    /// Walk an HNSW graph, for sub-linear search on large shards at the
+    /// cost of keeping a second copy of each vector in the graph
+    Hnsw(HnswParams),
+}
+
+impl IndexType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hilbert => "hilbert",
+            Self::Hnsw(_) => "hnsw",
         }
     }
 }
@@ -75,8 +155,11 @@ pub struct VectorIndex {
     /// Name of the index
     name: String,
 
-    /// Mapping of vector IDs to vector entries
-    vectors: RwLock<HashMap<Uuid, VectorEntry>>,
+    /// Vector entries, in a memtable and immutable segments
+    vectors: RwLock<SegmentStore>,
+
+    /// Held while a merge runs so merges don't race each other
+    merge_lock: Mutex<()>,
 
     /// Hilbert curve used for mapping vectors to 1D space
     hilbert_curve: HilbertCurve,
@@ -92,6 +175,22 @@ pub struct VectorIndex {
 
     /// Metrics collector
     metrics: Option<Arc<MetricsCollector>>,
+
+    /// Cardinality sketches for planner and stats API estimates
+    sketches: RwLock<IndexSketches>,
+
+    /// How much of the index each search examines
+    search_params: RwLock<SearchParams>,
+
+    /// Controller adjusting `search_params` to meet a latency SLO
+    tuner: RwLock<Option<SearchTuner>>,
+
+    /// How searches find candidates
+    index_type: IndexType,
+
+    /// Graph searched instead of the Hilbert buckets for HNSW indexes. The
+    /// Hilbert map is kept either way, since splits key on it.
+    graph: Option<RwLock<HnswGraph>>,
 }
 
 impl VectorIndex {
@@ -107,34 +206,120 @@ impl VectorIndex {
         if dimensions == 0 {
             return Err("dimensions must be greater than zero".to_string());
         }
-        if dimensions > max_total_bits {
-            return Err(format!(
-                "dimensions ({}) exceed maximum supported {}",
-                dimensions, max_total_bits
-            ));
-        }
-
-        let bits_per_dimension = std::cmp::min(10, max_total_bits / dimensions);
-        if bits_per_dimension == 0 {
-            return Err(format!(
-                "calculated bits_per_dimension is zero for {} dimensions",
-                dimensions
-            ));
-        }
 
-        let hilbert_curve = HilbertCurve::new(dimensions, bits_per_dimension);
+        // The curve covers as many leading dimensions as fit in the key; the
+        // rest only take part in scoring
+        let curve_dimensions = dimensions.min(max_total_bits);
+        let bits_per_dimension = std::cmp::min(10, max_total_bits / curve_dimensions);
+        let hilbert_curve = HilbertCurve::new(curve_dimensions, bits_per_dimension);
 
         Ok(Self {
             name: name.to_string(),
-            vectors: RwLock::new(HashMap::new()),
+            vectors: RwLock::new(SegmentStore::new(SegmentConfig::default())),
+            merge_lock: Mutex::new(()),
             hilbert_curve,
             hilbert_map: RwLock::new(HashMap::new()),
             dimensions,
             distance_metric,
             metrics,
+            sketches: RwLock::new(IndexSketches::new()),
+            search_params: RwLock::new(SearchParams::default()),
+            tuner: RwLock::new(None),
+            index_type: IndexType::Hilbert,
+            graph: None,
         })
     }
 
+    /// Search with `index_type`, indexing any entries already added
+    pub fn with_index_type(mut self, index_type: IndexType) -> Result<Self, String> {
+        self.graph = match index_type {
+            IndexType::Hilbert => None,
+            IndexType::Hnsw(params) => {
+                params.validate()?;
+                let mut graph = HnswGraph::new(params, self.distance_metric);
+                for entry in self.vectors.get_mut().values() {
+                    graph.insert(entry.id, entry.vector.clone());
+                }
+                Some(RwLock::new(graph))
+            }
+        };
+        self.index_type = index_type;
+        Ok(self)
+    }
+
+    /// Build an index from `entries` in one pass instead of adding them one
+    /// at a time: Hilbert keys are computed on `threads` threads, the
+    /// entries are sorted along the curve and frozen straight into
+    /// segments, and the bucket map and sketches are built once at the end
+    pub fn bulk_build(
+        name: &str,
+        dimensions: usize,
+        distance_metric: DistanceMetric,
+        metrics: Option<Arc<MetricsCollector>>,
+        entries: Vec<VectorEntry>,
+        threads: usize,
+    ) -> Result<Self, String> {
+        let mut index = Self::new(name, dimensions, distance_metric, metrics)?;
+        if let Some(entry) = entries.iter().find(|e| e.vector.dimensions != dimensions) {
+            return Err(format!(
+                "Vector dimensions mismatch: expected {}, got {}",
+                dimensions, entry.vector.dimensions
+            ));
+        }
+        let mut seen = HashSet::with_capacity(entries.len());
+        if let Some(entry) = entries.iter().find(|e| !seen.insert(e.id)) {
+            return Err(format!("Vector with ID {} already exists", entry.id));
+        }
+
+        let chunk_size = entries.len().div_ceil(threads.max(1)).max(1);
+        let this = &index;
+        let keys: Vec<u64> = std::thread::scope(|scope| {
+            let workers: Vec<_> = entries
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|entry| this.vector_to_hilbert_index(&entry.vector))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        });
+
+        let mut keyed: Vec<(u64, VectorEntry)> = keys.into_iter().zip(entries).collect();
+        keyed.sort_unstable_by_key(|(key, _)| *key);
+        let hilbert_map = index.hilbert_map.get_mut();
+        for (key, entry) in &keyed {
+            hilbert_map.entry(*key).or_default().push(entry.id);
+        }
+        *index.sketches.get_mut() = IndexSketches::rebuild(
+            keyed
+                .iter()
+                .map(|(_, entry)| (entry.id, entry.metadata.as_ref())),
+        );
+        index
+            .vectors
+            .get_mut()
+            .bulk_load(keyed.into_iter().map(|(_, entry)| entry).collect());
+
+        info!(
+            "Built index '{}' from {} vectors on {} threads",
+            index.name,
+            index.vectors.get_mut().len(),
+            threads.max(1)
+        );
+        Ok(index)
+    }
+
     /// Convert a vector to a Hilbert index
     fn vector_to_hilbert_index(&self, vector: &Vector) -> u64 {
         // Normalize the vector components to fit within our bit range
@@ -143,10 +328,11 @@ impl VectorIndex {
         let point: Vec<u64> = vector
             .values
             .iter()
+            .take(self.hilbert_curve.dimensions())
             .map(|&v| {
                 // Map from [-1.0, 1.0] to [0, max_value]
                 // First clamp the value to ensure it's in range
-                let normalized = v.max(-1.0).min(1.0);
+                let normalized = v.clamp(-1.0, 1.0);
                 let scaled = ((normalized + 1.0) / 2.0) * (max_value as f32);
                 scaled.round() as u64
             })
@@ -161,40 +347,62 @@ impl VectorIndex {
         vector: Vector,
         metadata: Option<HashMap<String, String>>,
     ) -> Result<Uuid, String> {
+        self.add_with_id(Uuid::new_v4(), vector, metadata).await
+    }
+
+    /// Add a vector under a caller-chosen ID, e.g. one assigned by a replica
+    pub async fn add_with_id(
+        &self,
+        id: Uuid,
+        vector: Vector,
+        metadata: Option<HashMap<String, String>>,
+    ) -> Result<Uuid, String> {
+        self.add_entry(VectorEntry {
+            id,
+            vector,
+            metadata,
+            created_at: chrono::Utc::now(),
+        })
+        .await
+    }
+
+    /// Add an entry as it is, keeping its creation time, e.g. when moving
+    /// it from another index
+    pub async fn add_entry(&self, entry: VectorEntry) -> Result<Uuid, String> {
         // Validate dimensions
-        if vector.dimensions != self.dimensions {
+        if entry.vector.dimensions != self.dimensions {
             return Err(format!(
                 "Vector dimensions mismatch: expected {}, got {}",
-                self.dimensions, vector.dimensions
+                self.dimensions, entry.vector.dimensions
             ));
         }
-
-        let id = Uuid::new_v4();
-        let now = chrono::Utc::now();
-
-        let entry = VectorEntry {
-            id,
-            vector: vector.clone(),
-            metadata,
-            created_at: now,
-        };
+        let id = entry.id;
 
         // Calculate Hilbert index
-        let hilbert_index = self.vector_to_hilbert_index(&vector);
+        let hilbert_index = self.vector_to_hilbert_index(&entry.vector);
+        let graph_vector = self.graph.as_ref().map(|_| entry.vector.clone());
 
-        // Add to vectors map
+        // The store, Hilbert map and graph are locked in that order for the
+        // whole insert, so an integrity check never sees it half done
         {
             let mut vectors = self.vectors.write().await;
+            if vectors.contains_key(&id) {
+                return Err(format!("Vector with ID {} already exists", id));
+            }
+            self.sketches
+                .write()
+                .await
+                .insert(id, entry.metadata.as_ref());
             vectors.insert(id, entry);
-        }
 
-        // Add to Hilbert map
-        {
             let mut hilbert_map = self.hilbert_map.write().await;
             hilbert_map
                 .entry(hilbert_index)
                 .or_insert_with(Vec::new)
                 .push(id);
+            if let (Some(graph), Some(vector)) = (&self.graph, graph_vector) {
+                graph.write().await.insert(id, vector);
+            }
         }
 
         // Update metrics
@@ -215,6 +423,81 @@ impl VectorIndex {
         Ok(id)
     }
 
+    /// Add many entries, taking each lock once for the whole batch.
+    /// Nothing is added unless every entry fits: dimensions must match and
+    /// IDs must be new to the index and unique within the batch.
+    pub async fn add_entries(&self, entries: Vec<VectorEntry>) -> Result<Vec<Uuid>, String> {
+        if let Some(entry) = entries
+            .iter()
+            .find(|entry| entry.vector.dimensions != self.dimensions)
+        {
+            return Err(format!(
+                "Vector dimensions mismatch: expected {}, got {}",
+                self.dimensions, entry.vector.dimensions
+            ));
+        }
+        let ids: Vec<Uuid> = entries.iter().map(|entry| entry.id).collect();
+        let hilbert_indexes: Vec<u64> = entries
+            .iter()
+            .map(|entry| self.vector_to_hilbert_index(&entry.vector))
+            .collect();
+        let graph_vectors: Vec<Vector> = match &self.graph {
+            Some(_) => entries.iter().map(|entry| entry.vector.clone()).collect(),
+            None => Vec::new(),
+        };
+
+        {
+            let mut vectors = self.vectors.write().await;
+            let mut batch = HashSet::with_capacity(ids.len());
+            if let Some(id) = ids
+                .iter()
+                .find(|id| vectors.contains_key(id) || !batch.insert(**id))
+            {
+                return Err(format!("Vector with ID {} already exists", id));
+            }
+            {
+                let mut sketches = self.sketches.write().await;
+                for entry in entries {
+                    sketches.insert(entry.id, entry.metadata.as_ref());
+                    vectors.insert(entry.id, entry);
+                }
+            }
+
+            let mut hilbert_map = self.hilbert_map.write().await;
+            for (hilbert_index, id) in hilbert_indexes.into_iter().zip(&ids) {
+                hilbert_map
+                    .entry(hilbert_index)
+                    .or_insert_with(Vec::new)
+                    .push(*id);
+            }
+            if let Some(graph) = &self.graph {
+                let mut graph = graph.write().await;
+                for (id, vector) in ids.iter().zip(graph_vectors) {
+                    graph.insert(*id, vector);
+                }
+            }
+        }
+
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .increment_counter(
+                    &format!("vector_index.{}.vectors_added", self.name),
+                    ids.len() as u64,
+                )
+                .await;
+            metrics
+                .set_gauge(
+                    &format!("vector_index.{}.vector_count", self.name),
+                    self.count().await as u64,
+                )
+                .await;
+        }
+
+        debug!("Added {} vectors to index '{}'", ids.len(), self.name);
+
+        Ok(ids)
+    }
+
     /// Remove a vector from the index
     pub async fn remove(&self, id: Uuid) -> Result<(), String> {
         // Get the vector to calculate its Hilbert index
@@ -228,14 +511,13 @@ impl VectorIndex {
 
         let hilbert_index = self.vector_to_hilbert_index(&vector);
 
-        // Remove from vectors map
+        // Locked like an insert, so the removal is seen whole or not at all
         {
             let mut vectors = self.vectors.write().await;
-            vectors.remove(&id);
-        }
+            if let Some(entry) = vectors.remove(&id) {
+                self.sketches.write().await.remove(entry.metadata.as_ref());
+            }
 
-        // Remove from Hilbert map
-        {
             let mut hilbert_map = self.hilbert_map.write().await;
             if let Some(ids) = hilbert_map.get_mut(&hilbert_index) {
                 ids.retain(|&x| x != id);
@@ -245,6 +527,9 @@ impl VectorIndex {
                     hilbert_map.remove(&hilbert_index);
                 }
             }
+            if let Some(graph) = &self.graph {
+                graph.write().await.remove(&id);
+            }
         }
 
         // Update metrics
@@ -265,9 +550,92 @@ impl VectorIndex {
         Ok(())
     }
 
+    /// Name of the index, as used in its metric names
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Position of a vector along this index's Hilbert curve. Indices with
+    /// the same dimensions agree on it, so shards are split along its ranges.
+    pub fn hilbert_key(&self, vector: &Vector) -> u64 {
+        self.vector_to_hilbert_index(vector)
+    }
+
+    /// Dimensions of vectors in this index
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Distance metric used for similarity search
+    pub fn distance_metric(&self) -> DistanceMetric {
+        self.distance_metric
+    }
+
+    /// How searches find candidates
+    pub fn index_type(&self) -> IndexType {
+        self.index_type
+    }
+
+    /// Removed vectors the HNSW graph still holds for navigation; none for
+    /// Hilbert indexes
+    pub async fn graph_tombstones(&self) -> usize {
+        match &self.graph {
+            Some(graph) => graph.read().await.tombstones(),
+            None => 0,
+        }
+    }
+
     /// Find nearest vectors using the index
     pub async fn search(&self, query: &Vector, limit: usize) -> Result<Vec<SearchResult>, String> {
+        self.search_with_plan(query, limit, None).await
+    }
+
+    /// Find nearest vectors that satisfy an execution plan
+    pub async fn search_with_plan(
+        &self,
+        query: &Vector,
+        limit: usize,
+        plan: Option<&ExecutionPlan>,
+    ) -> Result<Vec<SearchResult>, String> {
+        self.search_with_facets(query, limit, plan, None)
+            .await
+            .map(|(results, _)| results)
+    }
+
+    /// Like [`search_with_plan`](Self::search_with_plan), also counting
+    /// metadata values among the best candidates in the same scan
+    pub async fn search_with_facets(
+        &self,
+        query: &Vector,
+        limit: usize,
+        plan: Option<&ExecutionPlan>,
+        facets: Option<&FacetRequest>,
+    ) -> Result<(Vec<SearchResult>, Option<Facets>), String> {
+        self.search_until(query, limit, plan, facets, None)
+            .await
+            .map(|outcome| (outcome.results, outcome.facets))
+    }
+
+    /// Search that stops scanning once `deadline` passes, returning the best
+    /// results among the candidates scored so far, flagged as partial
+    pub async fn search_until(
+        &self,
+        query: &Vector,
+        limit: usize,
+        plan: Option<&ExecutionPlan>,
+        facets: Option<&FacetRequest>,
+        deadline: Option<std::time::Instant>,
+    ) -> Result<SearchOutcome, String> {
         let start = std::time::Instant::now();
+        let expired = || deadline.is_some_and(|d| std::time::Instant::now() >= d);
+        let mut partial = false;
+        let params = *self.search_params.read().await;
+        let plan = plan.filter(|p| !p.is_match_all());
+        let accepts = |entry: &VectorEntry| {
+            plan.is_none_or(|p| {
+                p.matches(&entry.vector, entry.metadata.as_ref(), self.distance_metric)
+            })
+        };
 
         // Validate dimensions
         if query.dimensions != self.dimensions {
@@ -280,10 +648,16 @@ impl VectorIndex {
         // Calculate Hilbert index of the query
         let query_hilbert_index = self.vector_to_hilbert_index(query);
 
-        // Get nearby indices in Hilbert space
+        // Get nearby indices in Hilbert space, unless a graph is searched instead
         // This is a simplified implementation - a more sophisticated version would
         // explore the Hilbert space more intelligently
-        let nearby_indices = self.get_nearby_indices(query_hilbert_index).await;
+        let nearby_indices = match &self.graph {
+            Some(_) => Vec::new(),
+            None => {
+                self.get_nearby_indices(query_hilbert_index, params.probe_window)
+                    .await
+            }
+        };
 
         // Collect candidate vectors
 
@@ -291,42 +665,75 @@ impl VectorIndex {
 
         {
             let vectors = self.vectors.read().await;
-            let hilbert_map = self.hilbert_map.read().await;
 
-            for &index in &nearby_indices {
-                if let Some(ids) = hilbert_map.get(&index) {
-                    for &id in ids {
-                        if let Some(entry) = vectors.get(&id) {
-                            candidates.push((id, entry.clone()));
+            if let Some(graph) = &self.graph {
+                let graph = graph.read().await;
+                let ef = (limit * params.candidate_multiplier).max(graph.params().ef_search);
+                for (id, _) in graph.search(query, ef) {
+                    if let Some(entry) = vectors.get(&id) {
+                        if accepts(&entry) {
+                            candidates.push((id, entry.into_owned()));
+                        }
+                    }
+                }
+            } else {
+                let hilbert_map = self.hilbert_map.read().await;
+
+                for &index in &nearby_indices {
+                    if let Some(ids) = hilbert_map.get(&index) {
+                        for &id in ids {
+                            if let Some(entry) = vectors.get(&id) {
+                                if accepts(&entry) {
+                                    candidates.push((id, entry.into_owned()));
+                                }
+                            }
                         }
                     }
                 }
             }
 
-            // If we have too few candidates, fall back to linear search
-            if candidates.len() < limit * 4 && candidates.len() < vectors.len() / 2 {
+            // If we have too few candidates, or fewer than the limit asks
+            // for and the index holds, fall back to linear search.
+            // Filters can reject most of the neighbourhood, so a filtered
+            // search also falls back whenever it can't fill the limit. The
+            // graph returns as many as it can reach, so only that applies
+            // to HNSW indexes.
+            let too_few = self.graph.is_none()
+                && ((candidates.len() < limit * params.candidate_multiplier
+                    && candidates.len() < vectors.len() / 2)
+                    || candidates.len() < limit.min(vectors.len()));
+            let needs_scan = too_few || (plan.is_some() && candidates.len() < limit);
+            if needs_scan && expired() {
+                // No time for a full scan; the neighbourhood is the best we have
+                partial = true;
+            } else if needs_scan {
                 debug!("Falling back to linear search for index '{}'", self.name);
 
+                vectors.advise(AccessPattern::Sequential);
                 candidates = vectors
                     .iter()
-                    .map(|(&id, entry)| (id, entry.clone()))
+                    .filter(|(_, entry)| accepts(entry))
+                    .map(|(id, entry)| (id, entry.into_owned()))
                     .collect();
+                vectors.advise(AccessPattern::Random);
             }
         }
 
-        // Calculate distances
-        let mut results: Vec<SearchResult> = candidates
-            .into_iter()
-            .map(|(id, entry)| {
-                let score = self.distance_metric.calculate(query, &entry.vector);
-                SearchResult {
-                    id,
-                    vector: entry.vector,
-                    metadata: entry.metadata,
-                    score,
-                }
-            })
-            .collect();
+        // Calculate distances, stopping if the deadline passes
+        let mut results: Vec<SearchResult> = Vec::with_capacity(candidates.len());
+        for (scored, (id, entry)) in candidates.into_iter().enumerate() {
+            if scored > 0 && scored % DEADLINE_CHECK_INTERVAL == 0 && expired() {
+                partial = true;
+                break;
+            }
+            let score = self.distance_metric.calculate(query, &entry.vector);
+            results.push(SearchResult {
+                id,
+                vector: entry.vector,
+                metadata: entry.metadata,
+                score,
+            });
+        }
 
         // Sort by score
         results.sort_by(|a, b| {
@@ -337,6 +744,15 @@ impl VectorIndex {
             }
         });
 
+        // Count facets over the best candidates before cutting to the limit
+        let facets = facets.map(|request| {
+            let mut collector = FacetCollector::new(request);
+            for result in results.iter().take(request.candidates(limit)) {
+                collector.observe(result.metadata.as_ref());
+            }
+            collector.finish()
+        });
+
         // Limit results
         results.truncate(limit);
 
@@ -354,25 +770,184 @@ impl VectorIndex {
                 )
                 .await;
         }
+        self.observe_latency(elapsed).await;
 
         debug!(
-            "Search in index '{}' found {} results in {:?}",
+            "Search in index '{}' found {} results in {:?}{}",
             self.name,
             results.len(),
-            elapsed
+            elapsed,
+            if partial { " (partial)" } else { "" }
         );
 
-        Ok(results)
+        Ok(SearchOutcome {
+            results,
+            facets,
+            partial,
+            coverage: 1.0,
+        })
+    }
+
+    /// Work a search would do, estimated from the Hilbert map and the
+    /// plan's selectivity without scoring anything. Candidates are chosen
+    /// as in [`search_until`](Self::search_until), including its fallback
+    /// to a full scan. HNSW indexes are assumed to return `ef` candidates.
+    pub async fn estimate_search(
+        &self,
+        query: &Vector,
+        limit: usize,
+        plan: Option<&ExecutionPlan>,
+    ) -> Result<SearchEstimate, String> {
+        if query.dimensions != self.dimensions {
+            return Err(format!(
+                "Query vector dimensions mismatch: expected {}, got {}",
+                self.dimensions, query.dimensions
+            ));
+        }
+
+        let params = *self.search_params.read().await;
+        let selectivity = plan
+            .filter(|p| !p.is_match_all())
+            .map(|p| p.estimated_selectivity.unwrap_or(1.0));
+        let accepted = |n: usize| (n as f64 * selectivity.unwrap_or(1.0)).round() as usize;
+
+        let statistics = self.statistics().await;
+        let total = self.count().await;
+        let neighbourhood: usize = match &self.graph {
+            Some(graph) => {
+                let ef = (limit * params.candidate_multiplier)
+                    .max(graph.read().await.params().ef_search);
+                ef.min(total)
+            }
+            None => {
+                let nearby = self
+                    .get_nearby_indices(self.vector_to_hilbert_index(query), params.probe_window)
+                    .await;
+                let hilbert_map = self.hilbert_map.read().await;
+                nearby
+                    .iter()
+                    .filter_map(|index| hilbert_map.get(index))
+                    .map(Vec::len)
+                    .sum()
+            }
+        };
+
+        let candidates = accepted(neighbourhood);
+        let too_few = self.graph.is_none()
+            && candidates < limit * params.candidate_multiplier
+            && candidates < total / 2;
+        let full_scan = too_few || (selectivity.is_some() && candidates < limit);
+        let (scanned, scored) = if full_scan {
+            (total, accepted(total))
+        } else {
+            (neighbourhood, candidates)
+        };
+        let metadata_bytes = statistics
+            .metadata_bytes
+            .stored
+            .checked_div(statistics.vector_count as u64)
+            .unwrap_or(0);
+
+        Ok(SearchEstimate::for_scan(
+            scanned,
+            scored,
+            full_scan,
+            self.dimensions,
+            metadata_bytes,
+            selectivity,
+        ))
+    }
+
+    /// Feed a search latency to the SLO controller and apply its decision
+    async fn observe_latency(&self, elapsed: std::time::Duration) {
+        let mut tuner = self.tuner.write().await;
+        let Some(tuner) = tuner.as_mut() else {
+            return;
+        };
+        let current = *self.search_params.read().await;
+        let Some(decision) = tuner.observe(elapsed.as_secs_f64() * 1000.0, current) else {
+            return;
+        };
+        *self.search_params.write().await = decision.params;
+
+        if decision.params != current {
+            info!(
+                "Tuned search on index '{}' ({}): p{:.0} {:.2}ms vs target {:.2}ms, probe window {}, candidate multiplier {}",
+                self.name,
+                decision.action.as_str(),
+                tuner.slo().percentile * 100.0,
+                decision.observed_ms,
+                decision.target_ms,
+                decision.params.probe_window,
+                decision.params.candidate_multiplier
+            );
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .increment_counter(
+                    &format!(
+                        "vector_index.{}.tuning.{}",
+                        self.name,
+                        decision.action.as_str()
+                    ),
+                    1,
+                )
+                .await;
+            metrics
+                .set_gauge(
+                    &format!("vector_index.{}.tuning.observed_us", self.name),
+                    (decision.observed_ms * 1000.0) as u64,
+                )
+                .await;
+            metrics
+                .set_gauge(
+                    &format!("vector_index.{}.tuning.probe_window", self.name),
+                    decision.params.probe_window,
+                )
+                .await;
+            metrics
+                .set_gauge(
+                    &format!("vector_index.{}.tuning.candidate_multiplier", self.name),
+                    decision.params.candidate_multiplier as u64,
+                )
+                .await;
+        }
+    }
+
+    /// Parameters currently used by searches
+    pub async fn search_params(&self) -> SearchParams {
+        *self.search_params.read().await
+    }
+
+    /// Override search parameters; a configured SLO keeps adjusting from here
+    pub async fn set_search_params(&self, params: SearchParams) {
+        *self.search_params.write().await = params.clamped();
+    }
+
+    /// Adjust search parameters at runtime to meet a latency SLO, or stop
+    /// adjusting with `None`. Parameters are left where the controller put
+    /// them when it's removed.
+    pub async fn set_latency_slo(&self, slo: Option<LatencySlo>) {
+        *self.tuner.write().await = slo.map(SearchTuner::new);
+    }
+
+    /// Recent decisions made by the SLO controller, oldest first
+    pub async fn tuning_decisions(&self) -> Vec<TuningDecision> {
+        self.tuner
+            .read()
+            .await
+            .as_ref()
+            .map(|t| t.decisions().cloned().collect())
+            .unwrap_or_default()
     }
 
     /// Get nearby indices in Hilbert space
-    async fn get_nearby_indices(&self, center_index: u64) -> Vec<u64> {
+    async fn get_nearby_indices(&self, center_index: u64, window_size: u64) -> Vec<u64> {
         // Start with the exact index
         let mut indices = vec![center_index];
 
         // Add some nearby indices (this is a simple implementation)
         // In a more sophisticated version, we would explore the Hilbert curve more intelligently
-        let window_size = 5;
         for i in 1..=window_size {
             // Add indices before
             if center_index >= i {
@@ -390,6 +965,300 @@ impl VectorIndex {
         indices
     }
 
+    /// Get a stored vector entry by ID
+    pub async fn get(&self, id: Uuid) -> Option<VectorEntry> {
+        self.vectors.read().await.get(&id).map(Cow::into_owned)
+    }
+
+    /// Snapshot of every entry currently in the index
+    pub async fn entries(&self) -> Vec<VectorEntry> {
+        let vectors = self.vectors.read().await;
+        vectors.advise(AccessPattern::Sequential);
+        let entries = vectors.values().map(Cow::into_owned).collect();
+        vectors.advise(AccessPattern::Random);
+        entries
+    }
+
+    /// Check that the Hilbert map and vector store agree. Every stored vector
+    /// must be listed exactly once, under the bucket its values map to, and
+    /// every listed ID must have a stored vector. Mapped rows must also match
+    /// their checksums, and an HNSW graph must hold exactly the stored
+    /// vectors and be well formed. With `repair` set, corrupted entries are
+    /// quarantined, and the Hilbert map and graph are rebuilt from the stored
+    /// vectors where they disagree.
+    pub async fn verify_integrity(&self, repair: bool) -> IntegrityReport {
+        let corrupted = self.verify_checksums(repair).await;
+        let vectors = self.vectors.read().await;
+        if !repair {
+            let hilbert_map = self.hilbert_map.read().await;
+            let mut report = self.check_buckets(&vectors, &hilbert_map, corrupted);
+            if let Some(graph) = &self.graph {
+                check_graph(&vectors, &*graph.read().await, &mut report);
+            }
+            return report;
+        }
+
+        let mut hilbert_map = self.hilbert_map.write().await;
+        let mut report = self.check_buckets(&vectors, &hilbert_map, corrupted);
+        let mut graph = match &self.graph {
+            Some(graph) => Some(graph.write().await),
+            None => None,
+        };
+        if let Some(graph) = graph.as_deref() {
+            check_graph(&vectors, graph, &mut report);
+        }
+        if !report.is_consistent() {
+            let misplaced: HashSet<Uuid> = report.misplaced.iter().copied().collect();
+            for ids in hilbert_map.values_mut() {
+                ids.retain(|id| vectors.contains_key(id) && !misplaced.contains(id));
+            }
+            hilbert_map.retain(|_, ids| !ids.is_empty());
+
+            for id in report.unreachable.iter().chain(report.misplaced.iter()) {
+                if let Some(entry) = vectors.get(id) {
+                    let bucket = self.vector_to_hilbert_index(&entry.vector);
+                    hilbert_map.entry(bucket).or_insert_with(Vec::new).push(*id);
+                }
+            }
+
+            // A damaged graph is relinked from the stored vectors
+            if let Some(graph) = graph.as_deref_mut() {
+                if !(report.unindexed.is_empty()
+                    && report.graph_orphaned.is_empty()
+                    && report.graph_faults.is_empty())
+                {
+                    let mut rebuilt = HnswGraph::new(graph.params(), self.distance_metric);
+                    for entry in vectors.values() {
+                        rebuilt.insert(entry.id, entry.vector.clone());
+                    }
+                    *graph = rebuilt;
+                }
+            }
+
+            report.repaired = report.issue_count();
+            warn!(
+                "Repaired {} inconsistencies in index '{}'",
+                report.repaired, self.name
+            );
+        }
+
+        report
+    }
+
+    /// Compare the Hilbert map with the stored vectors, skipping entries
+    /// already found corrupted
+    fn check_buckets(
+        &self,
+        vectors: &SegmentStore,
+        hilbert_map: &HashMap<u64, Vec<Uuid>>,
+        corrupted: Vec<Uuid>,
+    ) -> IntegrityReport {
+        let mut report = IntegrityReport {
+            checked: vectors.len(),
+            corrupted,
+            ..Default::default()
+        };
+        // Without repair, corrupted entries are still listed but can't be read
+        let corrupted: HashSet<Uuid> = report.corrupted.iter().copied().collect();
+
+        // Where each ID is currently listed
+        let mut listed: HashMap<Uuid, Vec<u64>> = HashMap::new();
+        for (&bucket, ids) in hilbert_map.iter() {
+            for &id in ids {
+                listed.entry(id).or_default().push(bucket);
+            }
+        }
+
+        for (id, buckets) in listed.iter().filter(|(id, _)| !corrupted.contains(id)) {
+            match vectors.get(id) {
+                None => report.orphaned.push(*id),
+                Some(entry) => {
+                    let expected = self.vector_to_hilbert_index(&entry.vector);
+                    if buckets.len() > 1 || buckets[0] != expected {
+                        report.misplaced.push(*id);
+                    }
+                }
+            }
+        }
+
+        for id in vectors.keys() {
+            if !listed.contains_key(&id) {
+                report.unreachable.push(id);
+            }
+        }
+
+        report
+    }
+
+    /// When memtables are frozen and segments merged
+    pub async fn segment_config(&self) -> SegmentConfig {
+        self.vectors.read().await.config()
+    }
+
+    /// Change memtable and merge thresholds; takes effect on the next write
+    /// or merge
+    pub async fn set_segment_config(&self, config: SegmentConfig) {
+        self.vectors.write().await.set_config(config);
+    }
+
+    /// Segment file storage, if segments are memory-mapped
+    pub async fn mmap_storage(&self) -> Option<MmapStorage> {
+        self.vectors.read().await.storage().cloned()
+    }
+
+    /// Write segments frozen from now on to memory-mapped files under
+    /// `storage.dir`, or keep them on the heap with `None`. Existing segments
+    /// move over at their next merge.
+    pub async fn set_mmap_storage(&self, storage: Option<MmapStorage>) -> Result<(), String> {
+        self.vectors
+            .write()
+            .await
+            .set_storage(storage)
+            .map_err(|e| format!("Failed to prepare segment directory: {}", e))
+    }
+
+    /// Freeze buffered writes into a segment; returns its ID, or `None` when
+    /// nothing was buffered
+    pub async fn flush_memtable(&self) -> Option<u64> {
+        self.vectors.write().await.flush()
+    }
+
+    /// Merge segments as the index's [`SegmentConfig`] calls for, or all of
+    /// them with `force`, dropping tombstoned entries. The merged segment is
+    /// built without holding the index lock, so searches and writes carry on
+    /// meanwhile. Returns `None` when there was nothing to merge.
+    pub async fn merge_segments(&self, force: bool) -> Option<MergeReport> {
+        let _merging = self.merge_lock.lock().await;
+        let plan = self.vectors.write().await.plan_merge(force)?;
+        let merged = match tokio::task::spawn_blocking(move || plan.build()).await {
+            Ok(merged) => merged,
+            Err(e) => {
+                warn!("Segment merge for index '{}' failed: {}", self.name, e);
+                return None;
+            }
+        };
+        let report = self.vectors.write().await.apply_merge(merged)?;
+        if !report.quarantined.is_empty() {
+            self.forget_quarantined(&report.quarantined).await;
+        }
+
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .increment_counter(&format!("vector_index.{}.segments.merged", self.name), 1)
+                .await;
+            metrics
+                .increment_counter(
+                    &format!("vector_index.{}.segments.tombstones_dropped", self.name),
+                    report.dropped as u64,
+                )
+                .await;
+            metrics
+                .set_gauge(
+                    &format!("vector_index.{}.segments.count", self.name),
+                    self.vectors.read().await.stats().segments.len() as u64,
+                )
+                .await;
+        }
+        debug!(
+            "Merged {} segments of index '{}' into segment {} ({} entries, {} tombstones dropped)",
+            report.inputs, self.name, report.segment_id, report.entries, report.dropped
+        );
+        Some(report)
+    }
+
+    /// Verify the checksum of every mapped row. With `quarantine` set, live
+    /// entries that fail are removed from the index and their rows copied to
+    /// a quarantine file beside their segment. Returns the failing IDs.
+    pub async fn verify_checksums(&self, quarantine: bool) -> Vec<Uuid> {
+        let corrupt = self.vectors.read().await.find_corrupt();
+        if corrupt.is_empty() {
+            return Vec::new();
+        }
+        warn!(
+            "Index '{}' has {} rows failing their checksum",
+            self.name,
+            corrupt.len()
+        );
+        if !quarantine {
+            return corrupt.into_iter().map(|(_, id)| id).collect();
+        }
+
+        let quarantined: Vec<Uuid> = {
+            let mut vectors = self.vectors.write().await;
+            corrupt
+                .into_iter()
+                .filter(|(segment_id, id)| vectors.quarantine(*segment_id, id))
+                .map(|(_, id)| id)
+                .collect()
+        };
+        self.forget_quarantined(&quarantined).await;
+        quarantined
+    }
+
+    /// Drop quarantined entries from the Hilbert map and graph. Their vectors
+    /// can't be read to find their bucket, so every bucket is checked.
+    async fn forget_quarantined(&self, ids: &[Uuid]) {
+        if ids.is_empty() {
+            return;
+        }
+        let ids: HashSet<Uuid> = ids.iter().copied().collect();
+        let mut hilbert_map = self.hilbert_map.write().await;
+        for bucket in hilbert_map.values_mut() {
+            bucket.retain(|id| !ids.contains(id));
+        }
+        hilbert_map.retain(|_, bucket| !bucket.is_empty());
+        drop(hilbert_map);
+        if let Some(graph) = &self.graph {
+            let mut graph = graph.write().await;
+            for id in &ids {
+                graph.remove(id);
+            }
+        }
+
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .increment_counter(
+                    &format!("vector_index.{}.corrupt_records", self.name),
+                    ids.len() as u64,
+                )
+                .await;
+        }
+    }
+
+    /// Point-in-time copy of the index's entries for snapshots and exports.
+    /// Flushes the memtable, then only shares the immutable segments.
+    pub async fn snapshot(&self) -> SegmentSnapshot {
+        self.vectors.write().await.snapshot()
+    }
+
+    /// Memtable, segment and tombstone counts
+    pub async fn segment_stats(&self) -> SegmentStats {
+        self.vectors.read().await.stats()
+    }
+
+    /// Cardinality estimates for the index's vectors and metadata fields
+    pub async fn statistics(&self) -> IndexStatistics {
+        self.sketches.read().await.statistics()
+    }
+
+    /// Recompute sketches from the current contents, discarding distinct
+    /// values only seen on vectors that have since been removed
+    pub async fn rebuild_sketches(&self) {
+        let vectors = self.vectors.read().await;
+        let entries: Vec<_> = vectors.values().collect();
+        let rebuilt = IndexSketches::rebuild(
+            entries
+                .iter()
+                .map(|entry| (entry.id, entry.metadata.as_ref())),
+        );
+        *self.sketches.write().await = rebuilt;
+    }
+
+    /// Planner for filters on this index, informed by its statistics
+    pub async fn planner(&self) -> QueryPlanner {
+        QueryPlanner::new(self.dimensions).with_statistics(self.statistics().await)
+    }
+
     /// Get the number of vectors in the index
     pub async fn count(&self) -> usize {
         self.vectors.read().await.len()
@@ -436,11 +1305,14 @@ impl VectorIndex {
             vector_count: total_vectors,
             dimensions: self.dimensions,
             distance_metric: self.distance_metric,
+            index_type: self.index_type,
             bucket_count,
             min_bucket_size: min_bucket,
             max_bucket_size: max_bucket,
             avg_bucket_size: avg_bucket,
             median_bucket_size: median_bucket,
+            statistics: self.statistics().await,
+            segments: vectors.stats(),
         }
     }
 }
@@ -460,6 +1332,9 @@ pub struct IndexStats {
     /// Distance metric used for similarity search
     pub distance_metric: DistanceMetric,
 
+    /// How searches find candidates
+    pub index_type: IndexType,
+
     /// Number of Hilbert space buckets
     pub bucket_count: usize,
 
@@ -474,6 +1349,82 @@ pub struct IndexStats {
 
     /// Median bucket size
     pub median_bucket_size: f32,
+
+    /// Cardinality estimates from the index's sketches
+    pub statistics: IndexStatistics,
+
+    /// Memtable and segment layout
+    pub segments: SegmentStats,
+}
+
+/// Compare an HNSW graph with the stored vectors and check its structure
+fn check_graph(vectors: &SegmentStore, graph: &HnswGraph, report: &mut IntegrityReport) {
+    report.graph_faults = graph.verify();
+    report.graph_orphaned = graph
+        .ids()
+        .filter(|id| !vectors.contains_key(id))
+        .copied()
+        .collect();
+    let corrupted: HashSet<Uuid> = report.corrupted.iter().copied().collect();
+    report.unindexed = vectors
+        .keys()
+        .filter(|id| !graph.contains(id) && !corrupted.contains(id))
+        .collect();
+}
+
+/// Result of checking an index's internal invariants
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityReport {
+    /// Number of stored vectors checked
+    pub checked: usize,
+
+    /// Stored vectors not listed in any Hilbert bucket
+    pub unreachable: Vec<Uuid>,
+
+    /// IDs listed in the Hilbert map with no stored vector
+    pub orphaned: Vec<Uuid>,
+
+    /// Vectors listed under the wrong bucket or more than once
+    pub misplaced: Vec<Uuid>,
+
+    /// Stored vectors whose rows failed their checksum
+    pub corrupted: Vec<Uuid>,
+
+    /// Stored vectors missing from the HNSW graph
+    pub unindexed: Vec<Uuid>,
+
+    /// IDs in the HNSW graph with no stored vector
+    pub graph_orphaned: Vec<Uuid>,
+
+    /// Structural faults in the HNSW graph (see [`HnswGraph::verify`])
+    pub graph_faults: Vec<String>,
+
+    /// Vectors whose stored state disagrees with the last event for them in
+    /// the shard's change feed; filled in by
+    /// [`ShardManager::verify_shard`](crate::sharding::manager::ShardManager::verify_shard)
+    pub diverged: Vec<Uuid>,
+
+    /// Number of issues fixed
+    pub repaired: usize,
+}
+
+impl IntegrityReport {
+    /// Total number of issues found
+    pub fn issue_count(&self) -> usize {
+        self.unreachable.len()
+            + self.orphaned.len()
+            + self.misplaced.len()
+            + self.corrupted.len()
+            + self.unindexed.len()
+            + self.graph_orphaned.len()
+            + self.graph_faults.len()
+            + self.diverged.len()
+    }
+
+    /// Whether no issues were found
+    pub fn is_consistent(&self) -> bool {
+        self.issue_count() == 0
+    }
 }
 
 #[cfg(test)]
@@ -566,6 +1517,7 @@ mod tests {
             DistanceMetric::Cosine,
             DistanceMetric::Manhattan,
             DistanceMetric::Hamming,
+            DistanceMetric::InnerProduct,
         ]
         .iter()
         {
@@ -620,4 +1572,67 @@ mod tests {
         assert_eq!(stats.dimensions, dimensions);
         assert_eq!(stats.bucket_count, buckets.len());
     }
+
+    #[tokio::test]
+    async fn test_verify_integrity_detects_and_repairs() {
+        let index = create_test_index(20, 3).await;
+        assert!(index.verify_integrity(false).await.is_consistent());
+
+        // Corrupt the Hilbert map: drop one vector's listing and add an orphan
+        let dropped = {
+            let mut hilbert_map = index.hilbert_map.write().await;
+            let (_, ids) = hilbert_map.iter_mut().next().unwrap();
+            let dropped = ids.pop().unwrap();
+            hilbert_map
+                .entry(u64::MAX)
+                .or_default()
+                .push(Uuid::new_v4());
+            dropped
+        };
+
+        let report = index.verify_integrity(false).await;
+        assert_eq!(report.unreachable, vec![dropped]);
+        assert_eq!(report.orphaned.len(), 1);
+        assert_eq!(report.repaired, 0);
+
+        let report = index.verify_integrity(true).await;
+        assert_eq!(report.repaired, 2);
+        assert!(index.verify_integrity(false).await.is_consistent());
+    }
+
+    #[tokio::test]
+    async fn test_verify_integrity_checks_the_hnsw_graph() {
+        let index = VectorIndex::new("test_hnsw", 3, DistanceMetric::Euclidean, None)
+            .unwrap()
+            .with_index_type(IndexType::Hnsw(HnswParams::default()))
+            .unwrap();
+        let mut ids = Vec::new();
+        for _ in 0..20 {
+            ids.push(index.add(Vector::random(3), None).await.unwrap());
+        }
+        assert!(index.verify_integrity(false).await.is_consistent());
+
+        // Drop one vector from the graph and list one it doesn't store
+        let stray = Uuid::new_v4();
+        {
+            let mut graph = index.graph.as_ref().unwrap().write().await;
+            graph.remove(&ids[0]);
+            graph.insert(stray, Vector::random(3));
+        }
+
+        let report = index.verify_integrity(false).await;
+        assert_eq!(report.unindexed, vec![ids[0]]);
+        assert_eq!(report.graph_orphaned, vec![stray]);
+        assert!(report.graph_faults.is_empty());
+        assert_eq!(report.repaired, 0);
+
+        let report = index.verify_integrity(true).await;
+        assert_eq!(report.repaired, 2);
+        assert!(index.verify_integrity(false).await.is_consistent());
+        let results = index
+            .search(&index.get(ids[0]).await.unwrap().vector, 1)
+            .await
+            .unwrap();
+        assert_eq!(results[0].id, ids[0]);
+    }
 }
@@ -0,0 +1,248 @@
+//! Per-tenant encryption of stored metadata.
+//!
+//! Each tenant has a set of versioned 256-bit keys and a list of metadata
+//! fields to encrypt. Values are sealed with XChaCha20-Poly1305 using the
+//! tenant name, vector ID and field name as associated data, so a value
+//! copied into another tenant's shard, another vector or another field won't
+//! decrypt. Encrypted values are stored as
+//! `enc:v{version}:{base64(nonce || ciphertext)}`, which lets old values be
+//! read after the tenant's key is rotated. Sensitive fields are always
+//! sealed, even when the value already looks encrypted, and other fields may
+//! not hold values with the encrypted prefix.
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Prefix marking a metadata value as encrypted
+pub const ENCRYPTED_PREFIX: &str = "enc:v";
+
+const NONCE_LEN: usize = 24;
+
+/// Associated data binding a sealed value to where it is stored
+fn associated_data(tenant: &str, vector_id: Uuid, field: &str) -> Vec<u8> {
+    format!("{}\0{}\0{}", tenant, vector_id, field).into_bytes()
+}
+
+#[derive(Default)]
+struct TenantKeys {
+    /// Ciphers by key version; the highest version encrypts new values
+    keys: BTreeMap<u32, XChaCha20Poly1305>,
+    encrypted_fields: HashSet<String>,
+}
+
+/// Encryption keys and sensitive field lists for every tenant
+#[derive(Default)]
+pub struct TenantKeyring {
+    tenants: RwLock<HashMap<String, TenantKeys>>,
+}
+
+impl std::fmt::Debug for TenantKeyring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print key material
+        f.debug_struct("TenantKeyring").finish_non_exhaustive()
+    }
+}
+
+impl TenantKeyring {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A fresh random key, for tests and for operators provisioning tenants
+    pub fn random_key() -> [u8; 32] {
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        key
+    }
+
+    /// Install a tenant key under an explicit version, e.g. when loading
+    /// keys from a secret store at startup
+    pub async fn add_key(&self, tenant: &str, version: u32, key: &[u8]) -> Result<()> {
+        if version == 0 {
+            return Err(anyhow!("Key versions start at 1"));
+        }
+        let cipher = XChaCha20Poly1305::new_from_slice(key)
+            .map_err(|_| anyhow!("Tenant keys must be 32 bytes, got {}", key.len()))?;
+
+        let mut tenants = self.tenants.write().await;
+        let keys = &mut tenants.entry(tenant.to_string()).or_default().keys;
+        if keys.contains_key(&version) {
+            return Err(anyhow!(
+                "Tenant {} already has a key with version {}",
+                tenant,
+                version
+            ));
+        }
+        keys.insert(version, cipher);
+        Ok(())
+    }
+
+    /// Add a key as the tenant's next version and return that version.
+    /// Values encrypted with older keys stay readable.
+    pub async fn rotate(&self, tenant: &str, key: &[u8]) -> Result<u32> {
+        let version = self.current_version(tenant).await.unwrap_or(0) + 1;
+        self.add_key(tenant, version, key).await?;
+        Ok(version)
+    }
+
+    /// Version used to encrypt new values for a tenant
+    pub async fn current_version(&self, tenant: &str) -> Option<u32> {
+        let tenants = self.tenants.read().await;
+        tenants
+            .get(tenant)
+            .and_then(|t| t.keys.keys().next_back().copied())
+    }
+
+    /// Metadata fields encrypted for a tenant; other fields stay in the clear
+    /// so they can still be filtered and aggregated on
+    pub async fn set_encrypted_fields(&self, tenant: &str, fields: &[&str]) {
+        let mut tenants = self.tenants.write().await;
+        tenants
+            .entry(tenant.to_string())
+            .or_default()
+            .encrypted_fields = fields.iter().map(|f| f.to_string()).collect();
+    }
+
+    pub async fn encrypted_fields(&self, tenant: &str) -> Vec<String> {
+        let tenants = self.tenants.read().await;
+        let mut fields: Vec<String> = tenants
+            .get(tenant)
+            .map(|t| t.encrypted_fields.iter().cloned().collect())
+            .unwrap_or_default();
+        fields.sort();
+        fields
+    }
+
+    /// Whether a stored value was produced by [`encrypt`](Self::encrypt)
+    pub fn is_encrypted(value: &str) -> bool {
+        value.starts_with(ENCRYPTED_PREFIX)
+    }
+
+    /// Seal a vector's field value with the tenant's current key
+    pub async fn encrypt(
+        &self,
+        tenant: &str,
+        vector_id: Uuid,
+        field: &str,
+        plaintext: &str,
+    ) -> Result<String> {
+        let tenants = self.tenants.read().await;
+        let (version, cipher) = tenants
+            .get(tenant)
+            .and_then(|t| t.keys.iter().next_back())
+            .ok_or_else(|| anyhow!("No encryption key for tenant {}", tenant))?;
+
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: &associated_data(tenant, vector_id, field),
+                },
+            )
+            .map_err(|_| anyhow!("Failed to encrypt value for tenant {}", tenant))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!(
+            "{}{}:{}",
+            ENCRYPTED_PREFIX,
+            version,
+            STANDARD.encode(sealed)
+        ))
+    }
+
+    /// Open a value sealed by [`encrypt`](Self::encrypt) for the same vector
+    /// and field; values without the encrypted prefix are returned unchanged
+    pub async fn decrypt(
+        &self,
+        tenant: &str,
+        vector_id: Uuid,
+        field: &str,
+        value: &str,
+    ) -> Result<String> {
+        let Some(envelope) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let (version, encoded) = envelope
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Malformed encrypted value"))?;
+        let version: u32 = version
+            .parse()
+            .map_err(|_| anyhow!("Malformed key version in encrypted value"))?;
+        let sealed = STANDARD
+            .decode(encoded)
+            .map_err(|e| anyhow!("Malformed encrypted value: {}", e))?;
+        if sealed.len() < NONCE_LEN {
+            return Err(anyhow!("Encrypted value is truncated"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+
+        let tenants = self.tenants.read().await;
+        let cipher = tenants
+            .get(tenant)
+            .and_then(|t| t.keys.get(&version))
+            .ok_or_else(|| anyhow!("Tenant {} has no key with version {}", tenant, version))?;
+        let plaintext = cipher
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &associated_data(tenant, vector_id, field),
+                },
+            )
+            .map_err(|_| anyhow!("Failed to decrypt value for tenant {}", tenant))?;
+        String::from_utf8(plaintext).map_err(|e| anyhow!("Decrypted value is not UTF-8: {}", e))
+    }
+
+    /// Encrypt a vector's sensitive fields in place, refusing values in
+    /// other fields that would be taken for ciphertext
+    pub async fn encrypt_metadata(
+        &self,
+        tenant: &str,
+        vector_id: Uuid,
+        metadata: &mut HashMap<String, String>,
+    ) -> Result<()> {
+        let fields = self.encrypted_fields(tenant).await;
+        if let Some((field, _)) = metadata
+            .iter()
+            .find(|(field, value)| Self::is_encrypted(value) && !fields.contains(field))
+        {
+            return Err(anyhow!(
+                "Metadata field {} may not start with {:?}",
+                field,
+                ENCRYPTED_PREFIX
+            ));
+        }
+        for field in fields {
+            if let Some(value) = metadata.get(&field) {
+                let sealed = self.encrypt(tenant, vector_id, &field, value).await?;
+                metadata.insert(field, sealed);
+            }
+        }
+        Ok(())
+    }
+
+    /// Decrypt every encrypted value of a vector in place
+    pub async fn decrypt_metadata(
+        &self,
+        tenant: &str,
+        vector_id: Uuid,
+        metadata: &mut HashMap<String, String>,
+    ) -> Result<()> {
+        for (field, value) in metadata.iter_mut() {
+            if Self::is_encrypted(value) {
+                *value = self.decrypt(tenant, vector_id, field, value).await?;
+            }
+        }
+        Ok(())
+    }
+}
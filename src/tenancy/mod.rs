@@ -0,0 +1,12 @@
+//! Multi-tenant data protection
+//!
+//! [`TenantKeyring`] holds versioned encryption keys per tenant and encrypts
+//! the metadata fields each tenant marks as sensitive before they are
+//! stored. [`RedactionPolicy`] strips configured fields from log lines,
+//! audit entries and API error messages.
+
+pub mod keys;
+pub mod redaction;
+
+pub use keys::{TenantKeyring, ENCRYPTED_PREFIX};
+pub use redaction::{RedactingMakeWriter, RedactionPolicy, REDACTED};
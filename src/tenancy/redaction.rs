@@ -0,0 +1,218 @@
+//! Redaction of sensitive fields before data leaves the process.
+//!
+//! A [`RedactionPolicy`] names fields (matched case-insensitively) whose
+//! values must never show up in logs, audit entries or API error messages.
+//! The installed policy is process-wide so those sinks don't have to
+//! thread it through every call site.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, RwLock};
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+static POLICY: Lazy<RwLock<Arc<RedactionPolicy>>> =
+    Lazy::new(|| RwLock::new(Arc::new(RedactionPolicy::default())));
+
+/// Fields whose values are stripped from logs, audit entries and errors
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedactionPolicy {
+    fields: Vec<String>,
+}
+
+impl RedactionPolicy {
+    pub fn new(fields: &[&str]) -> Self {
+        Self {
+            fields: fields
+                .iter()
+                .map(|f| f.trim().to_ascii_lowercase())
+                .filter(|f| !f.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Policy from a comma-separated field list, e.g. `ssn,email,api_key`
+    pub fn from_list(list: &str) -> Self {
+        Self::new(&list.split(',').collect::<Vec<_>>())
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    pub fn is_sensitive(&self, field: &str) -> bool {
+        self.fields.iter().any(|f| f.eq_ignore_ascii_case(field))
+    }
+
+    /// Replace the values of sensitive keys in a metadata map
+    pub fn redact_map(&self, map: &mut HashMap<String, String>) {
+        for (key, value) in map.iter_mut() {
+            if self.is_sensitive(key) {
+                *value = REDACTED.to_string();
+            }
+        }
+    }
+
+    /// Replace the values of sensitive keys anywhere in a JSON document,
+    /// and redact `key=value` pairs embedded in its strings
+    pub fn redact_json(&self, value: &mut serde_json::Value) {
+        if self.fields.is_empty() {
+            return;
+        }
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if self.is_sensitive(key) {
+                        *value = serde_json::Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact_json(value);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.redact_json(item);
+                }
+            }
+            serde_json::Value::String(text) => *text = self.redact_text(text),
+            _ => {}
+        }
+    }
+
+    /// Redact values following a sensitive key in free text, in the forms
+    /// `key=value`, `key: value` and `"key": "value"`
+    pub fn redact_text(&self, text: &str) -> String {
+        if self.fields.is_empty() {
+            return text.to_string();
+        }
+        let lower = text.to_ascii_lowercase();
+        let bytes = text.as_bytes();
+        let mut spans = Vec::new();
+
+        for field in &self.fields {
+            let mut from = 0;
+            while let Some(found) = lower[from..].find(field.as_str()) {
+                let start = from + found;
+                from = start + field.len();
+                if start > 0 && is_key_byte(bytes[start - 1]) {
+                    continue;
+                }
+                if let Some(span) = value_span(bytes, start + field.len()) {
+                    from = span.1;
+                    spans.push(span);
+                }
+            }
+        }
+        if spans.is_empty() {
+            return text.to_string();
+        }
+
+        spans.sort_unstable();
+        let mut redacted = String::with_capacity(text.len());
+        let mut cursor = 0;
+        for (start, end) in spans {
+            if start < cursor {
+                continue;
+            }
+            redacted.push_str(&text[cursor..start]);
+            redacted.push_str(REDACTED);
+            cursor = end;
+        }
+        redacted.push_str(&text[cursor..]);
+        redacted
+    }
+
+    /// Make this the process-wide policy
+    pub fn install(self) {
+        *POLICY.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(self);
+    }
+
+    /// The process-wide policy; empty until one is installed
+    pub fn global() -> Arc<RedactionPolicy> {
+        POLICY.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+fn is_key_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-' || byte == b'.'
+}
+
+/// Byte range of the value following a key that ends at `i`, if the key is
+/// followed by `=` or `:`
+fn value_span(bytes: &[u8], mut i: usize) -> Option<(usize, usize)> {
+    let skip_spaces = |mut i: usize| {
+        while i < bytes.len() && bytes[i] == b' ' {
+            i += 1;
+        }
+        i
+    };
+
+    if bytes.get(i) == Some(&b'"') {
+        i += 1;
+    }
+    i = skip_spaces(i);
+    if !matches!(bytes.get(i), Some(b'=') | Some(b':')) {
+        return None;
+    }
+    i = skip_spaces(i + 1);
+
+    let (start, end) = if bytes.get(i) == Some(&b'"') {
+        let start = i + 1;
+        let mut end = start;
+        while end < bytes.len() && bytes[end] != b'"' {
+            end += if bytes[end] == b'\\' { 2 } else { 1 };
+        }
+        (start, end.min(bytes.len()))
+    } else {
+        let end = bytes[i..]
+            .iter()
+            .position(|b| b.is_ascii_whitespace() || b",;&}]\"".contains(b))
+            .map_or(bytes.len(), |p| i + p);
+        (i, end)
+    };
+    (end > start).then_some((start, end))
+}
+
+/// `tracing_subscriber` writer that applies the installed policy to each
+/// formatted log line before writing it to stdout
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RedactingMakeWriter;
+
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for RedactingMakeWriter {
+    type Writer = RedactingWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter { buffer: Vec::new() }
+    }
+}
+
+/// Buffers one log event and writes it redacted when dropped
+pub struct RedactingWriter {
+    buffer: Vec<u8>,
+}
+
+impl Write for RedactingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let line = String::from_utf8_lossy(&self.buffer);
+        let redacted = RedactionPolicy::global().redact_text(&line);
+        self.buffer.clear();
+        std::io::stdout().lock().write_all(redacted.as_bytes())
+    }
+}
+
+impl Drop for RedactingWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
@@ -0,0 +1,231 @@
+//! In-process harness for integration tests.
+//!
+//! [`TestHarness`] runs a [`ShardManager`] and the HTTP [`Server`] on an
+//! ephemeral localhost port, with nothing outside the process: storage is
+//! the in-memory index and every extra service is opt-in through
+//! [`TestHarnessBuilder::with_server`]. Vectors generated through the
+//! harness come from a seeded RNG and shards it creates use content-derived
+//! IDs, so the same seed yields the same vectors and vector IDs on every
+//! run. Shard IDs are still random.
+
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::core::metrics::MetricsCollector;
+use crate::core::vector::Vector;
+use crate::nerv::tasks;
+use crate::server::{Server, ServerConfig};
+use crate::sharding::manager::{IdScheme, ShardManager};
+use crate::sharding::vector_index::DistanceMetric;
+
+/// Seed used when the test doesn't pick one
+pub const DEFAULT_SEED: u64 = 0x5eed;
+
+type ServerHook = Box<dyn FnOnce(Server, &TestContext) -> Server + Send>;
+
+/// Components shared between the harness and the server, handed to
+/// [`TestHarnessBuilder::with_server`] hooks
+#[derive(Debug, Clone)]
+pub struct TestContext {
+    pub metrics: Arc<MetricsCollector>,
+    pub shard_manager: Arc<ShardManager>,
+}
+
+pub struct TestHarnessBuilder {
+    seed: u64,
+    config: ServerConfig,
+    hooks: Vec<ServerHook>,
+}
+
+impl std::fmt::Debug for TestHarnessBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestHarnessBuilder")
+            .field("seed", &self.seed)
+            .field("config", &self.config)
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+impl Default for TestHarnessBuilder {
+    fn default() -> Self {
+        Self {
+            seed: DEFAULT_SEED,
+            config: ServerConfig::default(),
+            hooks: Vec::new(),
+        }
+    }
+}
+
+impl TestHarnessBuilder {
+    /// Seed for the harness's RNG
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Server settings; the address and port are ignored in favour of an
+    /// ephemeral localhost port
+    pub fn with_server_config(mut self, config: ServerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Add components to the server before it starts, e.g.
+    /// `|server, ctx| server.with_job_queue(..)`
+    pub fn with_server<F>(mut self, hook: F) -> Self
+    where
+        F: FnOnce(Server, &TestContext) -> Server + Send + 'static,
+    {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Start the server and wait until it is listening
+    pub async fn start(self) -> Result<TestHarness> {
+        let metrics = Arc::new(MetricsCollector::new());
+        let shard_manager = Arc::new(ShardManager::new(metrics.clone()));
+        let context = TestContext {
+            metrics: metrics.clone(),
+            shard_manager: shard_manager.clone(),
+        };
+
+        let mut server = Server::new(
+            self.config,
+            metrics.clone(),
+            None,
+            Some(shard_manager.clone()),
+        );
+        for hook in self.hooks {
+            server = hook(server, &context);
+        }
+
+        let (shutdown, stop) = oneshot::channel::<()>();
+        let (addr, serving) = warp::serve(server.filter()).try_bind_with_graceful_shutdown(
+            ([127, 0, 0, 1], 0),
+            async {
+                let _ = stop.await;
+            },
+        )?;
+        let handle = tasks::spawn("testing", "test server", serving);
+
+        Ok(TestHarness {
+            metrics,
+            shard_manager,
+            addr,
+            rng: Mutex::new(StdRng::seed_from_u64(self.seed)),
+            shutdown: Some(shutdown),
+            handle: Some(handle),
+        })
+    }
+}
+
+/// A running in-process server for integration tests. The server stops
+/// when the harness is dropped.
+#[derive(Debug)]
+pub struct TestHarness {
+    pub metrics: Arc<MetricsCollector>,
+    pub shard_manager: Arc<ShardManager>,
+    addr: SocketAddr,
+    rng: Mutex<StdRng>,
+    shutdown: Option<oneshot::Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TestHarness {
+    /// Harness with the default seed and no extra services
+    pub async fn start() -> Result<Self> {
+        Self::builder().start().await
+    }
+
+    pub fn builder() -> TestHarnessBuilder {
+        TestHarnessBuilder::default()
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Root URL of the server, e.g. `http://127.0.0.1:41234`
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// URL of an API route, e.g. `api_url("search")`
+    pub fn api_url(&self, path: &str) -> String {
+        format!("{}/api/{}", self.base_url(), path.trim_start_matches('/'))
+    }
+
+    /// Create a shard with an index. The shard uses content-derived vector
+    /// IDs so seeded data gets the same IDs on every run.
+    pub async fn create_shard(
+        &self,
+        name: &str,
+        dimensions: usize,
+        metric: DistanceMetric,
+    ) -> Result<uuid::Uuid> {
+        let shard_id = self.shard_manager.create_shard(name).await?;
+        self.shard_manager
+            .create_vector_index(shard_id, name, dimensions, metric)
+            .await?;
+        self.shard_manager
+            .set_id_scheme(shard_id, IdScheme::ContentAddressed)
+            .await?;
+        Ok(shard_id)
+    }
+
+    /// Next vector from the harness's seeded RNG
+    pub fn random_vector(&self, dimensions: usize) -> Vector {
+        let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+        Vector::random_from(&mut *rng, dimensions)
+    }
+
+    /// Fill a shard with `count` seeded vectors, returning their IDs in
+    /// insertion order
+    pub async fn seed_vectors(
+        &self,
+        shard_id: uuid::Uuid,
+        count: usize,
+    ) -> Result<Vec<uuid::Uuid>> {
+        let dimensions = self
+            .shard_manager
+            .get_vector_index(shard_id)
+            .await?
+            .stats()
+            .await
+            .dimensions;
+        let mut ids = Vec::with_capacity(count);
+        for _ in 0..count {
+            let vector = self.random_vector(dimensions);
+            ids.push(
+                self.shard_manager
+                    .add_vector(shard_id, vector, None)
+                    .await?,
+            );
+        }
+        Ok(ids)
+    }
+
+    /// Stop the server and wait for in-flight requests to finish
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.await;
+        }
+    }
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
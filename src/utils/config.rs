@@ -4,12 +4,18 @@ use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 
+use crate::darwin::objectives::ObjectiveConfig;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub node: NodeConfig,
     pub network: NetworkConfig,
     pub storage: StorageConfig,
     pub sharding: ShardingConfig,
+    #[serde(default)]
+    pub objectives: ObjectiveConfig,
+    #[serde(default)]
+    pub features: FeatureConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +48,109 @@ pub struct ShardingConfig {
     pub auto_rebalance: bool,
 }
 
+/// Optional subsystems that can be switched off at runtime. A feature runs
+/// only if it and every feature above it in the hierarchy is enabled, so
+/// `darwin.consciousness.enabled = false` turns off reality branching, the
+/// quantum consciousness manager and transcendence together while the
+/// practical self-improvement loop keeps running.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeatureConfig {
+    #[serde(default)]
+    pub darwin: DarwinFeatures,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DarwinFeatures {
+    pub enabled: bool,
+    /// Generating, validating and deploying practical modifications
+    pub self_improvement: bool,
+    pub consciousness: ConsciousnessFeatures,
+}
+
+impl Default for DarwinFeatures {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            self_improvement: true,
+            consciousness: ConsciousnessFeatures::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConsciousnessFeatures {
+    pub enabled: bool,
+    /// `RealityManager`: reality branches and coherence checks on deploy
+    pub reality: bool,
+    /// `QuantumConsciousnessManager`
+    pub quantum: bool,
+    /// `TranscendenceEngine` and its orchestration loop; needs `reality`
+    /// and `quantum`
+    pub transcendence: bool,
+}
+
+impl Default for ConsciousnessFeatures {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reality: true,
+            quantum: true,
+            transcendence: true,
+        }
+    }
+}
+
+impl FeatureConfig {
+    /// Everything enabled except the features listed in `ROSE_FOREST_DISABLE`,
+    /// comma-separated paths such as `darwin.consciousness`
+    pub fn from_env() -> Result<Self> {
+        let mut features = Self::default();
+        if let Ok(disabled) = std::env::var("ROSE_FOREST_DISABLE") {
+            for path in disabled.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+                features.disable(path)?;
+            }
+        }
+        Ok(features)
+    }
+
+    /// Switch off the feature at `path` and with it everything below it
+    pub fn disable(&mut self, path: &str) -> Result<()> {
+        let darwin = &mut self.darwin;
+        match path {
+            "darwin" => darwin.enabled = false,
+            "darwin.self_improvement" => darwin.self_improvement = false,
+            "darwin.consciousness" => darwin.consciousness.enabled = false,
+            "darwin.consciousness.reality" => darwin.consciousness.reality = false,
+            "darwin.consciousness.quantum" => darwin.consciousness.quantum = false,
+            "darwin.consciousness.transcendence" => darwin.consciousness.transcendence = false,
+            _ => return Err(anyhow!("Unknown feature {}", path)),
+        }
+        Ok(())
+    }
+
+    pub fn self_improvement_enabled(&self) -> bool {
+        self.darwin.enabled && self.darwin.self_improvement
+    }
+
+    pub fn consciousness_enabled(&self) -> bool {
+        self.darwin.enabled && self.darwin.consciousness.enabled
+    }
+
+    pub fn reality_enabled(&self) -> bool {
+        self.consciousness_enabled() && self.darwin.consciousness.reality
+    }
+
+    pub fn quantum_enabled(&self) -> bool {
+        self.consciousness_enabled() && self.darwin.consciousness.quantum
+    }
+
+    pub fn transcendence_enabled(&self) -> bool {
+        self.reality_enabled() && self.quantum_enabled() && self.darwin.consciousness.transcendence
+    }
+}
+
 impl Config {
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
@@ -58,8 +167,10 @@ impl Config {
 
         Ok(config)
     }
+}
 
-    pub fn default() -> Self {
+impl Default for Config {
+    fn default() -> Self {
         Self {
             node: NodeConfig {
                 id: format!("node-{}", uuid::Uuid::new_v4()),
@@ -83,6 +194,8 @@ impl Config {
                 replication_factor: 3,
                 auto_rebalance: true,
             },
+            objectives: ObjectiveConfig::default(),
+            features: FeatureConfig::default(),
         }
     }
 }
@@ -56,3 +56,274 @@ pub enum QueryError {
     #[error("Execution error: {0}")]
     ExecutionError(String),
 }
+
+#[derive(Error, Debug)]
+pub enum ChangeFeedError {
+    #[error("Offset {requested} is no longer retained; earliest available is {earliest}")]
+    OffsetExpired { requested: u64, earliest: u64 },
+
+    #[error("Offset {requested} is ahead of the feed; next offset is {next}")]
+    OffsetAhead { requested: u64, next: u64 },
+}
+
+#[derive(Error, Debug)]
+pub enum ReplicationError {
+    #[error("Segment from epoch {segment_epoch} is fenced; current epoch is {epoch}")]
+    Fenced { segment_epoch: u64, epoch: u64 },
+}
+
+#[derive(Error, Debug)]
+pub enum DelegationError {
+    #[error("Unknown task: {0}")]
+    UnknownTask(uuid::Uuid),
+
+    #[error("Lease {lease_id} no longer holds task {task_id}")]
+    StaleLease {
+        task_id: uuid::Uuid,
+        lease_id: uuid::Uuid,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum SecureChannelError {
+    #[error("No identity key configured for peer {0}")]
+    UnknownPeer(String),
+
+    #[error("Handshake from {0} failed authentication")]
+    BadSignature(String),
+
+    #[error("Handshake failed: {0}")]
+    Handshake(String),
+
+    #[error("Handshake from {0} is stale or was replayed")]
+    StaleHandshake(String),
+
+    #[error("No secure session with {0}")]
+    NoSession(String),
+
+    #[error("Message {0} was replayed or is outside the replay window")]
+    Replay(u64),
+
+    #[error("Message from {0} failed to decrypt")]
+    Decrypt(String),
+
+    #[error("Message of {0} bytes is too large to seal")]
+    TooLarge(usize),
+
+    #[error("Reply from {0} doesn't answer the request it was sent for")]
+    MismatchedReply(String),
+}
+
+#[derive(Error, Debug)]
+pub enum AdmissionError {
+    #[error("Request shed: {reason}")]
+    Shed {
+        reason: String,
+        retry_after: std::time::Duration,
+    },
+
+    #[error("Request waited {waited:?} for admission")]
+    QueueTimeout { waited: std::time::Duration },
+}
+
+impl AdmissionError {
+    /// How long the client should wait before retrying
+    pub fn retry_after(&self) -> std::time::Duration {
+        match self {
+            AdmissionError::Shed { retry_after, .. } => *retry_after,
+            AdmissionError::QueueTimeout { waited } => *waited,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum JobError {
+    #[error("Unknown job: {0}")]
+    UnknownJob(uuid::Uuid),
+
+    #[error("No handler registered for {0} jobs")]
+    NoHandler(String),
+
+    #[error("Job {id} has already finished ({state:?})")]
+    Finished {
+        id: uuid::Uuid,
+        state: crate::nerv::jobs::JobState,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum GovernanceError {
+    #[error("Unknown proposal: {0}")]
+    UnknownProposal(uuid::Uuid),
+
+    #[error("Proposal {0} is closed")]
+    Closed(uuid::Uuid),
+
+    #[error("{voter} has already voted on proposal {proposal_id}")]
+    AlreadyVoted {
+        proposal_id: uuid::Uuid,
+        voter: String,
+    },
+
+    #[error("No {0} voting power provider is configured")]
+    NoVotingPower(crate::governance::voting::WeightSource),
+
+    #[error("{voter} has no {weight_source} voting power")]
+    NoWeight {
+        voter: String,
+        weight_source: crate::governance::voting::WeightSource,
+    },
+
+    #[error("Proposal {0} has not passed")]
+    NotPassed(uuid::Uuid),
+
+    #[error("Proposal {0} has already been executed")]
+    AlreadyExecuted(uuid::Uuid),
+
+    #[error("No handler is registered for {0} actions")]
+    NoHandler(String),
+}
+
+/// Defined with the proofs in the shared value flow crate
+pub use rose_forest_value_flow::zkp::ZkpError;
+
+#[derive(Error, Debug)]
+pub enum ModelRegistryError {
+    #[error("Unknown model: {0}")]
+    UnknownModel(String),
+
+    #[error("Model {model} has no version {version}")]
+    UnknownVersion { model: String, version: u32 },
+
+    #[error("Model {model} has {expected} dimensions, got {actual}")]
+    DimensionMismatch {
+        model: String,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum SynonymError {
+    #[error("Synonym group {0:?} needs at least two terms")]
+    GroupTooSmall(Vec<String>),
+
+    #[error("Synonym dictionary contains an empty term")]
+    EmptyTerm,
+}
+
+#[derive(Error, Debug)]
+pub enum ExperimentError {
+    #[error("Experiment already exists: {0}")]
+    DuplicateName(String),
+
+    #[error("Experiment {0} is still running")]
+    AlreadyRunning(String),
+
+    #[error("Unknown experiment: {0}")]
+    UnknownExperiment(String),
+
+    #[error("Treatment share must be between 0 and 1, got {0}")]
+    InvalidShare(f32),
+}
+
+#[derive(Error, Debug)]
+pub enum FeedbackError {
+    #[error("Unknown or expired query: {0}")]
+    UnknownQuery(uuid::Uuid),
+
+    #[error("Result {result_id} was not returned for query {query_id}")]
+    UnknownResult {
+        query_id: uuid::Uuid,
+        result_id: String,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum RollbackError {
+    #[error("Rollback point already exists: {0}")]
+    DuplicatePoint(String),
+
+    #[error("Unknown rollback point: {0}")]
+    UnknownPoint(String),
+
+    #[error("Failed to restore modification {id}: {reason}")]
+    Restore { id: uuid::Uuid, reason: String },
+}
+
+#[derive(Error, Debug)]
+pub enum ScoringError {
+    #[error("Scorer names are letters, digits, '-' and '_', got {0:?}")]
+    InvalidName(String),
+
+    #[error("Invalid scoring module: {0}")]
+    InvalidModule(String),
+
+    #[error("Unknown scorer: {0}")]
+    UnknownScorer(String),
+
+    #[error("Scorer {scorer} ran out of fuel ({fuel} units per call)")]
+    OutOfFuel { scorer: String, fuel: u64 },
+
+    #[error("Scorer {scorer} failed: {reason}")]
+    Failed { scorer: String, reason: String },
+
+    #[error("Scoring plugins require building with the `wasm` feature")]
+    Unsupported,
+}
+
+#[derive(Error, Debug)]
+pub enum AuthError {
+    #[error("Missing credentials; send an API key or a bearer token")]
+    Missing,
+
+    #[error("Invalid API key or token")]
+    Invalid,
+
+    #[error("Token has expired")]
+    Expired,
+
+    #[error("Token is not valid yet")]
+    NotYetValid,
+
+    #[error("Unsupported token algorithm {0:?}; only HS256 is accepted")]
+    UnsupportedAlgorithm(String),
+}
+
+impl AuthError {
+    /// Short label used to break down failure metrics
+    pub fn reason(&self) -> &'static str {
+        match self {
+            AuthError::Missing => "missing",
+            AuthError::Invalid | AuthError::UnsupportedAlgorithm(_) => "invalid",
+            AuthError::Expired | AuthError::NotYetValid => "expired",
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AuthConfigError {
+    #[error("JWT secret is {len} bytes; HS256 secrets must be at least {min}")]
+    WeakJwtSecret { len: usize, min: usize },
+
+    #[error("API keys must not be empty")]
+    EmptyApiKey,
+}
+
+#[derive(Error, Debug)]
+pub enum ThresholdError {
+    #[error("No thresholds given")]
+    Empty,
+
+    #[error("Invalid metric name {0:?}")]
+    InvalidMetric(String),
+
+    #[error("Threshold for {0} must be finite")]
+    NotFinite(String),
+
+    #[error("Removing the {0} threshold needs DAO approval; propose a lower threshold instead")]
+    RemovalNeedsApproval(String),
+
+    #[error("Failed to open approval proposal: {0}")]
+    Governance(#[from] GovernanceError),
+}
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use std::fs::File;
+use std::path::Path;
+
+/// Replace `path` with the output of `write` so readers see either the old
+/// file or the complete new one. The data is written to a file beside it,
+/// synced, renamed over the original, and the directory synced so the
+/// rename itself survives a crash.
+pub fn write_atomic(path: &Path, write: impl FnOnce(&mut File) -> Result<()>) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    {
+        let mut file = File::create(&tmp)?;
+        write(&mut file)?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&tmp, path)?;
+    sync_parent(path)?;
+    Ok(())
+}
+
+/// Flush the directory entry for `path`. Directories can't be opened for
+/// syncing outside Unix, where this is a no-op.
+fn sync_parent(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        File::open(parent)?.sync_all()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn failed_writes_leave_the_original_in_place() {
+        let dir = std::env::temp_dir().join(format!("write-atomic-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.json");
+
+        write_atomic(&path, |file| Ok(file.write_all(b"first")?)).unwrap();
+        write_atomic(&path, |file| Ok(file.write_all(b"second")?)).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+        assert!(!path.with_extension("tmp").exists());
+
+        let failed = write_atomic(&path, |file| {
+            file.write_all(b"partial")?;
+            Err(anyhow::anyhow!("interrupted"))
+        });
+        assert!(failed.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,183 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::evaluation::Evaluation;
+use amazon_rose_forest::query::experiments::{
+    Arm, ArmConfig, ExperimentDefinition, ExperimentReport, Experiments, CLIENT_KEY_HEADER,
+};
+use amazon_rose_forest::query::feedback::SearchLog;
+use amazon_rose_forest::query::fusion::FusionStrategy;
+use amazon_rose_forest::server::api::SearchVectorsResponse;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::utils::errors::ExperimentError;
+use serde_json::json;
+use std::sync::Arc;
+use warp::http::StatusCode;
+
+fn definition(name: &str) -> ExperimentDefinition {
+    ExperimentDefinition {
+        name: name.to_string(),
+        control: ArmConfig::default(),
+        treatment: ArmConfig {
+            fusion: Some(FusionStrategy::Max),
+            ..ArmConfig::default()
+        },
+        treatment_share: 0.5,
+    }
+}
+
+#[test]
+fn clients_keep_their_arm() {
+    let experiment = definition("fusion");
+    let arms: Vec<Arm> = (0..200)
+        .map(|i| experiment.assign(&format!("client-{}", i)))
+        .collect();
+    let treated = arms.iter().filter(|a| **a == Arm::Treatment).count();
+    assert!((60..140).contains(&treated), "{} treated", treated);
+    for (i, arm) in arms.iter().enumerate() {
+        assert_eq!(experiment.assign(&format!("client-{}", i)), *arm);
+    }
+
+    let all_control = ExperimentDefinition {
+        treatment_share: 0.0,
+        ..definition("off")
+    };
+    assert!((0..50).all(|i| all_control.assign(&i.to_string()) == Arm::Control));
+}
+
+#[test]
+fn proportions_differ_only_with_enough_evidence() {
+    let evaluation = Evaluation::new();
+    let clear = evaluation.compare_proportions(20, 100, 45, 100);
+    assert!(clear.significant);
+    assert!((clear.difference - 0.25).abs() < 1e-9);
+    assert!(clear.z > 0.0 && clear.p_value < 0.001);
+
+    assert!(!evaluation.compare_proportions(2, 10, 3, 10).significant);
+    let empty = evaluation.compare_proportions(0, 0, 5, 10);
+    assert_eq!((empty.p_value, empty.significant), (1.0, false));
+}
+
+#[tokio::test]
+async fn one_experiment_runs_at_a_time() {
+    let experiments = Experiments::new();
+    experiments.start(definition("first")).await.unwrap();
+    assert!(matches!(
+        experiments.start(definition("second")).await,
+        Err(ExperimentError::AlreadyRunning(name)) if name == "first"
+    ));
+    assert!(matches!(
+        experiments
+            .start(ExperimentDefinition {
+                treatment_share: 1.5,
+                ..definition("second")
+            })
+            .await,
+        Err(ExperimentError::InvalidShare(_))
+    ));
+
+    experiments.stop("first").await.unwrap();
+    assert!(experiments.running().await.is_none());
+    assert!(matches!(
+        experiments.start(definition("first")).await,
+        Err(ExperimentError::DuplicateName(_))
+    ));
+    experiments.start(definition("second")).await.unwrap();
+    assert_eq!(experiments.running().await.unwrap().name, "second");
+}
+
+#[tokio::test]
+async fn searches_run_with_their_arm_and_feedback_is_reported_per_arm() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let shard_id = manager.create_shard("docs").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for values in [[1.0, 0.0], [0.0, 1.0], [0.7, 0.7]] {
+        manager
+            .add_vector(shard_id, Vector::new(values.to_vec()), None)
+            .await
+            .unwrap();
+    }
+    let filter = Server::new(ServerConfig::default(), metrics, None, Some(manager))
+        .with_search_log(Arc::new(SearchLog::new()))
+        .with_experiments(Arc::new(Experiments::new()))
+        .filter();
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/experiments")
+        .json(&definition("fusion"))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let experiment = definition("fusion");
+    let mut clicks = 0;
+    for i in 0..20 {
+        let client = format!("client-{}", i);
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/api/search")
+            .header(CLIENT_KEY_HEADER, client.as_str())
+            .json(&json!({
+                "shard_id": shard_id,
+                "query_vector": [1.0, 0.0],
+                "additional_queries": [[0.0, 1.0]],
+                "limit": 2,
+            }))
+            .reply(&filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: SearchVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+        let assignment = body.experiment.unwrap();
+        assert_eq!(assignment.arm, experiment.assign(&client));
+
+        // Only treated clients click
+        if assignment.arm == Arm::Treatment {
+            clicks += 1;
+            let resp = warp::test::request()
+                .method("POST")
+                .path("/api/feedback")
+                .json(&json!({
+                    "query_id": body.query_id.unwrap(),
+                    "result_id": body.results[0].id,
+                    "label": "click",
+                }))
+                .reply(&filter)
+                .await;
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+    }
+
+    // Searches without a client key aren't part of the experiment
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search")
+        .json(&json!({ "shard_id": shard_id, "query_vector": [1.0, 0.0], "limit": 2 }))
+        .reply(&filter)
+        .await;
+    let body: SearchVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert!(body.experiment.is_none());
+
+    let resp = warp::test::request()
+        .path("/api/experiments/fusion/report")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let report: ExperimentReport = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(report.control.searches + report.treatment.searches, 20);
+    assert_eq!(report.treatment.successes, clicks);
+    assert_eq!(report.control.successes, 0);
+    assert_eq!(report.treatment.success_rate, 1.0);
+    assert!(report.success_rate_test.significant);
+
+    let resp = warp::test::request()
+        .path("/api/experiments/unknown/report")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
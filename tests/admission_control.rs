@@ -0,0 +1,210 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::network::admission::{
+    AdmissionConfig, AdmissionController, LoadSample, Pressure,
+};
+use amazon_rose_forest::network::priority::Priority;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::utils::errors::AdmissionError;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::http::StatusCode;
+
+const IDLE: LoadSample = LoadSample {
+    cpu: 0.2,
+    memory: 0.3,
+    loop_lag_ms: 1,
+};
+
+fn controller(config: AdmissionConfig) -> (Arc<AdmissionController>, Arc<MetricsCollector>) {
+    let metrics = Arc::new(MetricsCollector::new());
+    (
+        Arc::new(AdmissionController::new(config, metrics.clone())),
+        metrics,
+    )
+}
+
+#[tokio::test]
+async fn pressure_follows_resource_thresholds() {
+    let (admission, metrics) = controller(AdmissionConfig::default());
+    assert_eq!(admission.record_sample(IDLE).await, Pressure::Normal);
+    assert_eq!(
+        admission
+            .record_sample(LoadSample { cpu: 0.9, ..IDLE })
+            .await,
+        Pressure::Saturated
+    );
+    assert_eq!(
+        admission
+            .record_sample(LoadSample {
+                loop_lag_ms: 500,
+                ..IDLE
+            })
+            .await,
+        Pressure::Overloaded
+    );
+    assert_eq!(metrics.get_gauge("admission.loop_lag_ms").await, Some(500));
+
+    let status = admission.status().await;
+    assert_eq!(status.pressure, Pressure::Overloaded);
+    assert_eq!(status.reasons, vec!["event loop lagging 500ms".to_string()]);
+}
+
+#[tokio::test]
+async fn interactive_latency_over_slo_saturates_the_node() {
+    let (admission, _) = controller(AdmissionConfig {
+        interactive_slo: Duration::from_millis(50),
+        ..AdmissionConfig::default()
+    });
+    for _ in 0..10 {
+        admission.record_latency(Duration::from_millis(200)).await;
+    }
+    // Too few samples to judge
+    assert_eq!(admission.record_sample(IDLE).await, Pressure::Normal);
+
+    for _ in 0..30 {
+        admission.record_latency(Duration::from_millis(200)).await;
+    }
+    assert_eq!(admission.record_sample(IDLE).await, Pressure::Saturated);
+    assert_eq!(admission.status().await.interactive_p95_ms, Some(200));
+}
+
+#[tokio::test]
+async fn interactive_requests_are_always_admitted() {
+    let (admission, metrics) = controller(AdmissionConfig::default());
+    admission
+        .record_sample(LoadSample { cpu: 0.99, ..IDLE })
+        .await;
+
+    let permit = admission.admit(Priority::Interactive).await.unwrap();
+    assert_eq!(permit.priority(), Priority::Interactive);
+
+    let shed = admission.admit(Priority::Background).await.unwrap_err();
+    assert!(matches!(shed, AdmissionError::Shed { .. }));
+    assert_eq!(metrics.get_counter("admission.shed").await, Some(1));
+}
+
+#[tokio::test]
+async fn batch_work_waits_while_saturated() {
+    let (admission, _) = controller(AdmissionConfig::default());
+    admission
+        .record_sample(LoadSample {
+            memory: 0.9,
+            ..IDLE
+        })
+        .await;
+
+    let waiting = {
+        let admission = admission.clone();
+        tokio::spawn(async move { admission.admit(Priority::Batch).await })
+    };
+    while admission.status().await.queued == 0 {
+        tokio::task::yield_now().await;
+    }
+    assert!(!waiting.is_finished());
+
+    admission.record_sample(IDLE).await;
+    let permit = waiting.await.unwrap().unwrap();
+    assert_eq!(admission.status().await.in_flight_low_priority, 1);
+    drop(permit);
+    let status = admission.status().await;
+    assert_eq!(status.in_flight_low_priority, 0);
+    assert_eq!(status.queued, 0);
+}
+
+#[tokio::test]
+async fn queued_work_is_shed_on_overload_timeout_or_full_queue() {
+    let (admission, _) = controller(AdmissionConfig {
+        queue_timeout: Duration::from_millis(50),
+        ..AdmissionConfig::default()
+    });
+    admission
+        .record_sample(LoadSample { cpu: 0.9, ..IDLE })
+        .await;
+    assert!(matches!(
+        admission.admit(Priority::Batch).await,
+        Err(AdmissionError::QueueTimeout { .. })
+    ));
+
+    // Pressure rising past critical releases waiters with an error
+    let waiting = {
+        let admission = admission.clone();
+        tokio::spawn(async move { admission.admit(Priority::Batch).await })
+    };
+    while admission.status().await.queued == 0 {
+        tokio::task::yield_now().await;
+    }
+    admission
+        .record_sample(LoadSample { cpu: 0.99, ..IDLE })
+        .await;
+    assert!(matches!(
+        waiting.await.unwrap(),
+        Err(AdmissionError::Shed { .. })
+    ));
+
+    let (full, _) = controller(AdmissionConfig {
+        max_queue: 0,
+        ..AdmissionConfig::default()
+    });
+    full.record_sample(LoadSample { cpu: 0.9, ..IDLE }).await;
+    match full.admit(Priority::Batch).await {
+        Err(AdmissionError::Shed { reason, .. }) => assert!(reason.contains("queue full")),
+        other => panic!("expected shed, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn saturated_server_sheds_bulk_imports() {
+    let (admission, _) = controller(AdmissionConfig {
+        queue_timeout: Duration::from_secs(3),
+        ..AdmissionConfig::default()
+    });
+    admission
+        .record_sample(LoadSample { cpu: 0.99, ..IDLE })
+        .await;
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(Arc::new(ShardManager::new(Arc::new(
+            MetricsCollector::new(),
+        )))),
+    )
+    .with_admission_controller(admission.clone());
+    let filter = server.filter();
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/import")
+        .json(&json!({
+            "shard_id": uuid::Uuid::new_v4(),
+            "source": {"type": "qdrant", "url": "http://localhost:6333", "collection": "c"}
+        }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(resp.headers()["retry-after"], "3");
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/api/admission")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let status: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(status["pressure"], "overloaded");
+
+    // Once load drops the same import is let through to the shard lookup
+    admission.record_sample(IDLE).await;
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/import")
+        .json(&json!({
+            "shard_id": uuid::Uuid::new_v4(),
+            "source": {"type": "qdrant", "url": "http://localhost:6333", "collection": "c"}
+        }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
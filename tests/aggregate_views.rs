@@ -0,0 +1,130 @@
+use amazon_rose_forest::{
+    core::metrics::MetricsCollector,
+    server::{Server, ServerConfig},
+    sharding::{
+        aggregates::{AggregateFunction, AggregateSnapshot, AggregateViewDefinition},
+        manager::ShardManager,
+        vector_index::DistanceMetric,
+    },
+    Vector,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+fn metadata(category: &str, price: &str) -> Option<HashMap<String, String>> {
+    let mut m = HashMap::new();
+    m.insert("category".to_string(), category.to_string());
+    m.insert("price".to_string(), price.to_string());
+    Some(m)
+}
+
+async fn setup() -> (Arc<ShardManager>, Uuid) {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics));
+    let shard_id = manager.create_shard("products").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    (manager, shard_id)
+}
+
+#[tokio::test]
+async fn views_are_backfilled_and_maintained() {
+    let (manager, shard_id) = setup().await;
+
+    manager
+        .add_vector(shard_id, Vector::random(3), metadata("book", "10"))
+        .await
+        .unwrap();
+
+    let snapshot = manager
+        .create_aggregate_view(
+            shard_id,
+            AggregateViewDefinition {
+                name: "avg_price".into(),
+                group_by: "category".into(),
+                function: AggregateFunction::Avg {
+                    field: "price".into(),
+                },
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(snapshot.groups.len(), 1);
+    assert_eq!(snapshot.groups[0].value, Some(10.0));
+
+    let removed = manager
+        .add_vector(shard_id, Vector::random(3), metadata("book", "20"))
+        .await
+        .unwrap();
+    manager
+        .add_vector(shard_id, Vector::random(3), metadata("toy", "5"))
+        .await
+        .unwrap();
+
+    let snapshot = manager
+        .get_aggregate_view(shard_id, "avg_price")
+        .await
+        .unwrap();
+    assert_eq!(snapshot.groups.len(), 2);
+    assert_eq!(snapshot.groups[0].key, "book");
+    assert_eq!(snapshot.groups[0].count, 2);
+    assert_eq!(snapshot.groups[0].value, Some(15.0));
+
+    manager.remove_vector(shard_id, removed).await.unwrap();
+    let snapshot = manager
+        .get_aggregate_view(shard_id, "avg_price")
+        .await
+        .unwrap();
+    assert_eq!(snapshot.groups[0].count, 1);
+    assert_eq!(snapshot.groups[0].value, Some(10.0));
+}
+
+#[tokio::test]
+async fn aggregate_endpoints_serve_views() {
+    let (manager, shard_id) = setup().await;
+    manager
+        .add_vector(shard_id, Vector::random(3), metadata("book", "10"))
+        .await
+        .unwrap();
+    manager
+        .add_vector(shard_id, Vector::random(3), metadata("book", "12"))
+        .await
+        .unwrap();
+
+    let metrics = Arc::new(MetricsCollector::new());
+    let server = Server::new(ServerConfig::default(), metrics, None, Some(manager));
+    let filter = server.filter();
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/collections/products/aggregates")
+        .json(&serde_json::json!({
+            "name": "by_category",
+            "group_by": "category",
+            "function": "count"
+        }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/api/collections/products/aggregates/by_category")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let snapshot: AggregateSnapshot = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(snapshot.groups.len(), 1);
+    assert_eq!(snapshot.groups[0].value, Some(2.0));
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/api/collections/products/aggregates/missing")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,121 @@
+use amazon_rose_forest::{
+    core::{audit::AuditLog, metrics::MetricsCollector},
+    sharding::{
+        autosplit::{AutoSharder, AutoSplitConfig, SplitTrigger},
+        manager::ShardManager,
+        vector_index::DistanceMetric,
+    },
+    Vector,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+async fn collection(manager: &ShardManager, vectors: usize) -> (Uuid, Vec<(Uuid, Vector)>) {
+    let shard_id = manager.create_shard("docs").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let mut added = Vec::new();
+    for i in 0..vectors {
+        let x = i as f32 / vectors as f32 * 2.0 - 1.0;
+        let vector = Vector::new(vec![x, -x * 0.5]);
+        let id = manager
+            .add_vector(shard_id, vector.clone(), None)
+            .await
+            .unwrap();
+        added.push((id, vector));
+    }
+    (shard_id, added)
+}
+
+#[tokio::test]
+async fn split_moves_the_upper_key_range_and_keeps_routing() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = ShardManager::new(metrics.clone());
+    let (shard_id, added) = collection(&manager, 40).await;
+
+    let split = manager.split_shard(shard_id).await.unwrap();
+    assert_eq!(split.moved + split.kept, 40);
+    assert!(split.moved > 0 && split.kept > 0);
+    assert_eq!(
+        manager.key_routes(shard_id).await,
+        vec![(split.split_key, split.new_shard_id)]
+    );
+    assert_eq!(
+        manager
+            .get_shard(split.new_shard_id)
+            .await
+            .unwrap()
+            .vector_count,
+        split.moved
+    );
+    assert_eq!(metrics.get_counter("shards.split").await, Some(1));
+
+    // Every vector is still found through the original shard
+    for (id, vector) in &added {
+        let results = manager.search_vectors(shard_id, vector, 1).await.unwrap();
+        assert_eq!(results[0].id, *id);
+    }
+
+    // New writes land in the shard owning their key
+    let new_index = manager.get_vector_index(split.new_shard_id).await.unwrap();
+    let (_, upper) = added
+        .iter()
+        .find(|(_, v)| new_index.hilbert_key(v) >= split.split_key)
+        .unwrap();
+    let id = manager
+        .add_vector(shard_id, upper.clone(), None)
+        .await
+        .unwrap();
+    assert!(new_index.get(id).await.is_some());
+
+    // And deletes find the vector wherever it moved
+    manager.remove_vector(shard_id, id).await.unwrap();
+    assert!(new_index.get(id).await.is_none());
+}
+
+#[tokio::test]
+async fn auto_sharder_splits_shards_over_their_limits() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let (shard_id, _) = collection(&manager, 12).await;
+    let audit_log = Arc::new(AuditLog::new());
+    let sharder = AutoSharder::new(
+        manager.clone(),
+        metrics.clone(),
+        audit_log.clone(),
+        AutoSplitConfig {
+            max_vectors: Some(10),
+            max_memory_mb: None,
+            max_p99_ms: None,
+            min_vectors: 2,
+            ..Default::default()
+        },
+    );
+
+    let splits = sharder.run_once().await;
+    assert_eq!(splits.len(), 1);
+    assert_eq!(splits[0].shard_id, shard_id);
+    assert_eq!(
+        splits[0].trigger,
+        Some(SplitTrigger::VectorCount {
+            count: 12,
+            limit: 10
+        })
+    );
+
+    let events = audit_log.by_category("autosplit", 10).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].action, "split");
+    assert_eq!(events[0].subject, shard_id.to_string());
+    assert_eq!(
+        events[0].details["new_shard_id"],
+        splits[0].new_shard_id.to_string()
+    );
+
+    // Both halves are within the limit now
+    assert!(sharder.run_once().await.is_empty());
+    assert_eq!(manager.get_shards().await.len(), 2);
+    assert_eq!(metrics.get_counter("autosplit.splits").await, Some(1));
+}
@@ -0,0 +1,192 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::sharding::backup::{BackupKind, BackupStore};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::storage::{FileStorage, StorageBackend};
+use amazon_rose_forest::sharding::vector_index::{DistanceMetric, VectorEntry};
+use amazon_rose_forest::Vector;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("rose-forest-{}-{}", name, Uuid::new_v4()))
+}
+
+fn storage(dir: &PathBuf) -> Arc<dyn StorageBackend> {
+    Arc::new(FileStorage::open(dir).unwrap())
+}
+
+async fn shard(manager: &ShardManager, name: &str, vectors: usize) -> (Uuid, Vec<Uuid>) {
+    let shard_id = manager.create_shard(name).await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 4, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+    let mut ids = Vec::new();
+    for i in 0..vectors {
+        let metadata = HashMap::from([
+            ("n".to_string(), i.to_string()),
+            ("shard".to_string(), name.to_string()),
+        ]);
+        ids.push(
+            manager
+                .add_vector(shard_id, Vector::random(4), Some(metadata))
+                .await
+                .unwrap(),
+        );
+    }
+    (shard_id, ids)
+}
+
+async fn restored(backups: &BackupStore, id: u64) -> (ShardManager, PathBuf) {
+    let dir = temp_dir("restored");
+    let storage = storage(&dir);
+    backups.restore(id, storage.as_ref()).await.unwrap();
+    let manager = ShardManager::new(Arc::new(MetricsCollector::new())).with_storage(storage);
+    manager.restore(false).await.unwrap();
+    (manager, dir)
+}
+
+async fn vector_count(manager: &ShardManager, shard_id: Uuid) -> usize {
+    manager
+        .get_vector_index(shard_id)
+        .await
+        .unwrap()
+        .entries()
+        .await
+        .len()
+}
+
+#[tokio::test]
+async fn incremental_backups_hold_only_changes_and_restore_as_a_chain() {
+    let data = temp_dir("data");
+    let backup_dir = temp_dir("backups");
+    let manager = ShardManager::new(Arc::new(MetricsCollector::new())).with_storage(storage(&data));
+    let backups = BackupStore::open(&backup_dir).unwrap();
+    assert!(manager
+        .backup(&backups, BackupKind::Incremental)
+        .await
+        .is_err());
+
+    let (docs, ids) = shard(&manager, "docs", 50).await;
+    let (scratch, _) = shard(&manager, "scratch", 5).await;
+    let full = manager.backup(&backups, BackupKind::Full).await.unwrap();
+    assert_eq!(full.parent, None);
+    assert_eq!(full.vectors_written(), 55);
+
+    for id in &ids[..5] {
+        manager.remove_vector(docs, *id).await.unwrap();
+    }
+    for _ in 0..10 {
+        manager
+            .add_vector(docs, Vector::random(4), None)
+            .await
+            .unwrap();
+    }
+    manager.delete_shard(scratch).await.unwrap();
+    let delta = manager
+        .backup(&backups, BackupKind::Incremental)
+        .await
+        .unwrap();
+    assert_eq!(delta.parent, Some(full.id));
+    assert_eq!(delta.vectors_written(), 10);
+    assert_eq!(delta.shards[0].removed.len(), 5);
+    assert_eq!(delta.shards[0].vector_count, 55);
+    assert_eq!(delta.removed_shards, vec![scratch]);
+
+    // Nothing changed, so nothing is written
+    let empty = manager
+        .backup(&backups, BackupKind::Incremental)
+        .await
+        .unwrap();
+    assert!(empty.shards.is_empty());
+    assert_eq!(backups.list().unwrap().len(), 3);
+
+    let report = backups.verify(empty.id);
+    assert!(report.is_ok(), "{:?}", report.problems);
+    assert_eq!(report.chain, vec![full.id, delta.id, empty.id]);
+    assert_eq!((report.shards, report.vectors), (1, 55));
+
+    let (latest, latest_dir) = restored(&backups, empty.id).await;
+    assert_eq!(vector_count(&latest, docs).await, 55);
+    assert!(latest.get_shard(scratch).await.is_err());
+    let by_id = |entries: Vec<VectorEntry>| {
+        entries
+            .into_iter()
+            .map(|e| (e.id, (e.vector.values, e.metadata)))
+            .collect::<HashMap<_, _>>()
+    };
+    assert_eq!(
+        by_id(latest.get_vector_index(docs).await.unwrap().entries().await),
+        by_id(
+            manager
+                .get_vector_index(docs)
+                .await
+                .unwrap()
+                .entries()
+                .await
+        )
+    );
+
+    // Earlier backups restore the state they saw
+    let (first, first_dir) = restored(&backups, full.id).await;
+    assert_eq!(vector_count(&first, docs).await, 50);
+    assert_eq!(vector_count(&first, scratch).await, 5);
+
+    for dir in [data, backup_dir, latest_dir, first_dir] {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn verify_finds_damage_anywhere_in_the_chain() {
+    let data = temp_dir("data");
+    let backup_dir = temp_dir("backups");
+    let manager = ShardManager::new(Arc::new(MetricsCollector::new())).with_storage(storage(&data));
+    let backups = BackupStore::open(&backup_dir).unwrap();
+    let (docs, _) = shard(&manager, "docs", 20).await;
+    let full = manager.backup(&backups, BackupKind::Full).await.unwrap();
+    manager
+        .add_vector(docs, Vector::random(4), None)
+        .await
+        .unwrap();
+    let delta = manager
+        .backup(&backups, BackupKind::Incremental)
+        .await
+        .unwrap();
+    assert!(backups.verify(delta.id).is_ok());
+
+    // A flipped byte in the full backup breaks every backup built on it
+    let vectors = backup_dir
+        .join(format!("{:08}", full.id))
+        .join("vectors")
+        .join(format!("{}.jsonl", docs));
+    let mut contents = std::fs::read(&vectors).unwrap();
+    let position = contents.iter().position(|&b| b == b'n').unwrap();
+    contents[position] = b'm';
+    std::fs::write(&vectors, &contents).unwrap();
+    let report = backups.verify(delta.id);
+    assert!(!report.is_ok());
+    assert!(report.problems[0].contains("checksum mismatch"));
+    let unused = temp_dir("unused");
+    assert!(backups
+        .restore(delta.id, storage(&unused).as_ref())
+        .await
+        .is_err());
+    assert!(FileStorage::open(&unused)
+        .unwrap()
+        .load_shards()
+        .await
+        .unwrap()
+        .is_empty());
+
+    // So does a missing parent
+    std::fs::remove_dir_all(backup_dir.join(format!("{:08}", full.id))).unwrap();
+    let report = backups.verify(delta.id);
+    assert!(report.problems[0].contains("missing its parent"));
+
+    for dir in [data, backup_dir, unused] {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::network::bandwidth::{
+    BandwidthConfig, BandwidthLimit, BandwidthThrottle, TrafficClass,
+};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn throttle(metrics: Arc<MetricsCollector>) -> BandwidthThrottle {
+    BandwidthThrottle::new(
+        BandwidthConfig::default().with_limit(
+            TrafficClass::Replication,
+            BandwidthLimit::new(10_000).with_burst(1_000),
+        ),
+        metrics,
+    )
+}
+
+#[tokio::test]
+async fn sends_past_the_burst_wait_for_tokens() {
+    let throttle = throttle(Arc::new(MetricsCollector::new()));
+    assert_eq!(
+        throttle
+            .acquire(TrafficClass::Replication, "peer-a", 1_000)
+            .await,
+        Duration::ZERO
+    );
+
+    // The bucket is empty, so 1000 more bytes take 100ms at 10kB/s
+    let started = Instant::now();
+    let waited = throttle
+        .acquire(TrafficClass::Replication, "peer-a", 1_000)
+        .await;
+    assert!(waited >= Duration::from_millis(90), "{:?}", waited);
+    assert!(started.elapsed() >= Duration::from_millis(90));
+
+    // Each peer has its own bucket
+    assert_eq!(
+        throttle
+            .acquire(TrafficClass::Replication, "peer-b", 1_000)
+            .await,
+        Duration::ZERO
+    );
+}
+
+#[tokio::test]
+async fn large_transfers_leave_the_bucket_in_debt() {
+    let throttle = throttle(Arc::new(MetricsCollector::new()));
+    // Larger than the burst: goes out once the bucket is full...
+    assert_eq!(
+        throttle
+            .acquire(TrafficClass::Replication, "peer-a", 3_000)
+            .await,
+        Duration::ZERO
+    );
+    // ...and the next send pays for it
+    let waited = throttle
+        .acquire(TrafficClass::Replication, "peer-a", 100)
+        .await;
+    assert!(waited >= Duration::from_millis(200), "{:?}", waited);
+}
+
+#[tokio::test]
+async fn unlimited_classes_are_measured() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let throttle = throttle(metrics.clone());
+    for _ in 0..5 {
+        assert_eq!(
+            throttle
+                .acquire(TrafficClass::SnapshotTransfer, "peer-a", 50_000)
+                .await,
+            Duration::ZERO
+        );
+    }
+    throttle
+        .acquire(TrafficClass::Replication, "peer-a", 500)
+        .await;
+
+    assert_eq!(
+        metrics
+            .get_counter("bandwidth.snapshot_transfer.bytes")
+            .await,
+        Some(250_000)
+    );
+    assert_eq!(
+        metrics
+            .get_gauge("bandwidth.snapshot_transfer.bytes_per_sec")
+            .await,
+        Some(50_000)
+    );
+
+    let throughput = throttle.throughput().await;
+    assert_eq!(throughput.len(), 2);
+    assert_eq!(throughput[0].class, TrafficClass::Replication);
+    assert_eq!(
+        throughput[0].limit,
+        throttle.limit(TrafficClass::Replication)
+    );
+    assert_eq!(throughput[1].total_bytes, 250_000);
+    assert!(throughput[1].limit.is_none());
+}
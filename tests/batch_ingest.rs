@@ -0,0 +1,245 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::server::api::AddVectorsBatchResponse;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::{IdScheme, ShardManager};
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::Vector;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+const DIMENSIONS: usize = 4;
+
+async fn shard(manager: &ShardManager, name: &str) -> Uuid {
+    let shard_id = manager.create_shard(name).await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", DIMENSIONS, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    shard_id
+}
+
+fn batch(count: usize) -> Vec<(Vector, Option<HashMap<String, String>>)> {
+    (0..count)
+        .map(|i| {
+            let metadata = HashMap::from([("n".to_string(), i.to_string())]);
+            (Vector::random(DIMENSIONS), Some(metadata))
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn batches_insert_all_or_nothing() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = ShardManager::new(metrics.clone());
+    let shard_id = shard(&manager, "docs").await;
+
+    let vectors = batch(2500);
+    let ids = manager
+        .add_vectors_batch(shard_id, vectors.clone())
+        .await
+        .unwrap();
+    assert_eq!(ids.len(), 2500);
+    assert_eq!(
+        manager.get_shard(shard_id).await.unwrap().vector_count,
+        2500
+    );
+
+    // IDs come back in input order
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    let entry = index.get(ids[1234]).await.unwrap();
+    assert_eq!(entry.vector, vectors[1234].0);
+    assert_eq!(entry.metadata.unwrap()["n"], "1234");
+    let results = manager
+        .search_vectors(shard_id, &vectors[1234].0, 1)
+        .await
+        .unwrap();
+    assert_eq!(results[0].id, ids[1234]);
+
+    // One change event per vector, in order
+    let changes = manager
+        .change_feed(shard_id)
+        .await
+        .unwrap()
+        .read(0, 10_000)
+        .await
+        .unwrap();
+    assert_eq!(changes.events.len(), 2500);
+    assert_eq!(changes.next_offset, 2500);
+
+    // A single bad vector rejects the whole batch
+    let mut bad = batch(10);
+    bad[7].0 = Vector::random(DIMENSIONS + 1);
+    let err = manager
+        .add_vectors_batch(shard_id, bad)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Vector 7"), "{}", err);
+    assert_eq!(index.count().await, 2500);
+
+    assert!(manager
+        .add_vectors_batch(Uuid::new_v4(), batch(1))
+        .await
+        .is_err());
+    assert_eq!(
+        metrics.get_counter("vectors.batch_inserted").await,
+        Some(2500)
+    );
+}
+
+#[tokio::test]
+async fn batches_follow_id_schemes_and_splits() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = ShardManager::new(metrics.clone());
+    let shard_id = shard(&manager, "docs").await;
+    manager
+        .set_id_scheme(shard_id, IdScheme::ContentAddressed)
+        .await
+        .unwrap();
+
+    // Repeats within the batch and of stored vectors share one ID
+    let existing = Vector::random(DIMENSIONS);
+    let existing_id = manager
+        .add_vector(shard_id, existing.clone(), None)
+        .await
+        .unwrap();
+    let fresh = Vector::random(DIMENSIONS);
+    let ids = manager
+        .add_vectors_batch(
+            shard_id,
+            vec![
+                (fresh.clone(), None),
+                (existing, None),
+                (fresh.clone(), None),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(ids[0], fresh.content_id(None));
+    assert_eq!(ids[1], existing_id);
+    assert_eq!(ids[2], ids[0]);
+    assert_eq!(manager.get_shard(shard_id).await.unwrap().vector_count, 2);
+    assert_eq!(metrics.get_counter("vectors.deduplicated").await, Some(2));
+
+    // Once split, each vector lands in the family member owning its key
+    let other = shard(&manager, "other").await;
+    manager.add_vectors_batch(other, batch(200)).await.unwrap();
+    let split = manager.split_shard(other).await.unwrap();
+    let ids = manager.add_vectors_batch(other, batch(200)).await.unwrap();
+    let original = manager.get_vector_index(other).await.unwrap();
+    let split_off = manager.get_vector_index(split.new_shard_id).await.unwrap();
+    assert_eq!(original.count().await + split_off.count().await, 400);
+    let mut holders = HashSet::new();
+    for id in ids {
+        let (holder, _) = manager.find_vector(id, Some(other)).await.unwrap();
+        holders.insert(holder);
+    }
+    assert_eq!(holders, HashSet::from([other, split.new_shard_id]));
+}
+
+#[tokio::test]
+async fn failed_batches_roll_back_earlier_shards() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = ShardManager::new(metrics.clone());
+    let shard_id = shard(&manager, "docs").await;
+    manager
+        .add_vectors_batch(shard_id, batch(200))
+        .await
+        .unwrap();
+    let split = manager.split_shard(shard_id).await.unwrap();
+    let original = manager.get_vector_index(shard_id).await.unwrap();
+    let kept = original.entries().await[0].vector.clone();
+    let moved = manager
+        .get_vector_index(split.new_shard_id)
+        .await
+        .unwrap()
+        .entries()
+        .await[0]
+        .vector
+        .clone();
+
+    // The split-off shard's index no longer fits the family, so the second
+    // group fails after the first was written to the original shard
+    manager
+        .create_vector_index(
+            split.new_shard_id,
+            "main",
+            DIMENSIONS + 1,
+            DistanceMetric::Euclidean,
+        )
+        .await
+        .unwrap();
+    let count = original.count().await;
+    let err = manager
+        .add_vectors_batch(shard_id, vec![(kept.clone(), None), (moved, None)])
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("rolled back"), "{}", err);
+    assert!(err.contains(&split.new_shard_id.to_string()), "{}", err);
+    assert_eq!(original.count().await, count);
+    assert_eq!(
+        manager.get_shard(shard_id).await.unwrap().vector_count,
+        count
+    );
+    assert_eq!(
+        metrics.get_counter("vectors.batch_rolled_back").await,
+        Some(1)
+    );
+    let copies = original.entries().await;
+    assert_eq!(copies.iter().filter(|e| e.vector == kept).count(), 1);
+}
+
+#[tokio::test]
+async fn batch_endpoint_returns_ids_in_order() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = shard(&manager, "docs").await;
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager.clone()),
+    )
+    .filter();
+    let post = |body: serde_json::Value| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/vectors/batch")
+            .json(&body)
+            .reply(&filter)
+    };
+
+    let vectors: Vec<_> = (0..1000)
+        .map(|i| {
+            json!({
+                "vector": [i as f32, 0.0, 0.0, 0.0],
+                "metadata": { "n": i.to_string() },
+            })
+        })
+        .collect();
+    let resp = post(json!({ "shard_id": shard_id, "vectors": vectors })).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: AddVectorsBatchResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.vector_ids.len(), 1000);
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    let entry = index.get(body.vector_ids[999]).await.unwrap();
+    assert_eq!(entry.vector.values[0], 999.0);
+
+    let resp = post(json!({
+        "shard_id": shard_id,
+        "vectors": [{ "vector": [1.0, 2.0, 3.0, 4.0] }, { "vector": [1.0] }],
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(index.count().await, 1000);
+
+    let resp = post(json!({
+        "shard_id": shard_id,
+        "vectors": vec![json!({ "vector": [0.0] }); 10_001],
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
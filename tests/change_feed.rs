@@ -0,0 +1,132 @@
+use amazon_rose_forest::{
+    core::metrics::MetricsCollector,
+    server::{Server, ServerConfig},
+    sharding::{
+        changefeed::{ChangeBatch, ChangeFeed, ChangeOp},
+        manager::ShardManager,
+        vector_index::DistanceMetric,
+    },
+    utils::errors::ChangeFeedError,
+    Vector,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+#[tokio::test]
+async fn feed_records_mutations_in_order() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics));
+    let shard_id = manager.create_shard("cdc").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+
+    let first = manager
+        .add_vector(shard_id, Vector::random(3), None)
+        .await
+        .unwrap();
+    let second = manager
+        .add_vector(shard_id, Vector::random(3), None)
+        .await
+        .unwrap();
+    manager.remove_vector(shard_id, first).await.unwrap();
+
+    let feed = manager.change_feed(shard_id).await.unwrap();
+    let batch = feed.read(0, 10).await.unwrap();
+    assert_eq!(batch.events.len(), 3);
+    assert_eq!(batch.next_offset, 3);
+    assert!(matches!(batch.events[0].op, ChangeOp::Insert { vector_id, .. } if vector_id == first));
+    assert!(
+        matches!(batch.events[1].op, ChangeOp::Insert { vector_id, .. } if vector_id == second)
+    );
+    assert!(matches!(batch.events[2].op, ChangeOp::Delete { vector_id } if vector_id == first));
+
+    // Resuming from a committed offset only returns later events
+    let resumed = feed.read(2, 10).await.unwrap();
+    assert_eq!(resumed.events.len(), 1);
+    assert_eq!(resumed.events[0].offset, 2);
+}
+
+#[tokio::test]
+async fn expired_offsets_are_rejected() {
+    let feed = ChangeFeed::with_retention(Uuid::new_v4(), 2);
+    for _ in 0..5 {
+        feed.append(ChangeOp::Delete {
+            vector_id: Uuid::new_v4(),
+        })
+        .await;
+    }
+
+    assert!(matches!(
+        feed.read(1, 10).await,
+        Err(ChangeFeedError::OffsetExpired { earliest: 3, .. })
+    ));
+    assert!(matches!(
+        feed.read(9, 10).await,
+        Err(ChangeFeedError::OffsetAhead { next: 5, .. })
+    ));
+    assert_eq!(feed.read(3, 10).await.unwrap().events.len(), 2);
+}
+
+#[tokio::test]
+async fn long_poll_wakes_on_append() {
+    let feed = Arc::new(ChangeFeed::new(Uuid::new_v4()));
+
+    let waiter = {
+        let feed = feed.clone();
+        tokio::spawn(async move { feed.wait(0, 10, Duration::from_secs(5)).await })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    feed.append(ChangeOp::Delete {
+        vector_id: Uuid::new_v4(),
+    })
+    .await;
+
+    let batch = waiter.await.unwrap().unwrap();
+    assert_eq!(batch.events.len(), 1);
+
+    let empty = feed.wait(1, 10, Duration::from_millis(20)).await.unwrap();
+    assert!(empty.events.is_empty());
+    assert_eq!(empty.next_offset, 1);
+}
+
+#[tokio::test]
+async fn changes_endpoint_pages_by_offset() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let shard_id = manager.create_shard("cdc").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for _ in 0..3 {
+        manager
+            .add_vector(shard_id, Vector::random(3), None)
+            .await
+            .unwrap();
+    }
+
+    let server = Server::new(ServerConfig::default(), metrics, None, Some(manager));
+    let filter = server.filter();
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!("/api/shards/{}/changes?from=1&limit=1", shard_id))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let batch: ChangeBatch = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(batch.events.len(), 1);
+    assert_eq!(batch.events[0].offset, 1);
+    assert_eq!(batch.next_offset, 2);
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!("/api/shards/{}/changes", Uuid::new_v4()))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
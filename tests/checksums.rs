@@ -0,0 +1,216 @@
+use amazon_rose_forest::core::checksum::{self, ChecksumError, Quarantine};
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::darwin::lifecycle::{LifecycleEventKind, LifecycleLog};
+use amazon_rose_forest::nerv::jobs::{ClusteringJob, JobKind, JobQueue};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::mmap::{MmapStorage, MmapVectorFile, MmapVectorWriter};
+use amazon_rose_forest::sharding::segments::SegmentConfig;
+use amazon_rose_forest::sharding::vector_index::{DistanceMetric, VectorIndex};
+use serde_json::json;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn scratch_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rose-forest-checksums-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Flip every bit of one byte in place, as a failing disk might
+fn flip_byte(path: &Path, offset: u64) {
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .unwrap();
+    let byte = std::fs::read(path).unwrap()[offset as usize];
+    file.seek(SeekFrom::Start(offset)).unwrap();
+    file.write_all(&[!byte]).unwrap();
+}
+
+/// Replace one line of a text file
+fn corrupt_line(path: &Path, line_no: usize) {
+    let contents = std::fs::read_to_string(path).unwrap();
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+    lines[line_no] = lines[line_no].replacen('"', "'", 1);
+    std::fs::write(path, lines.join("\n") + "\n").unwrap();
+}
+
+#[test]
+fn sealed_lines_detect_changes() {
+    let sealed = checksum::seal(r#"{"a":1}"#);
+    assert_eq!(checksum::unseal(&sealed), Ok(r#"{"a":1}"#));
+
+    let tampered = sealed.replace("1", "2");
+    assert!(matches!(
+        checksum::unseal(&tampered),
+        Err(ChecksumError::Mismatch { .. })
+    ));
+    assert!(checksum::unseal(&format!("{}\tzz", r#"{"a":1}"#)).is_err());
+
+    // Lines written before checksums were added are read unverified
+    assert_eq!(checksum::unseal(r#"{"a":1}"#), Ok(r#"{"a":1}"#));
+}
+
+/// A queue accepting clustering jobs; no worker is started, so they stay
+/// queued
+async fn clustering_queue(path: &Path, metrics: Arc<MetricsCollector>) -> JobQueue {
+    let queue = JobQueue::open(path, metrics.clone()).unwrap();
+    let manager = Arc::new(ShardManager::new(metrics));
+    queue
+        .register(JobKind::Clustering, Arc::new(ClusteringJob::new(manager)))
+        .await;
+    queue
+}
+
+#[tokio::test]
+async fn corrupted_job_records_are_quarantined() {
+    let dir = scratch_dir();
+    let path = dir.join("jobs.jsonl");
+    {
+        let queue = clustering_queue(&path, Arc::new(MetricsCollector::new())).await;
+        for i in 0..3 {
+            queue
+                .submit(JobKind::Clustering, json!({ "n": i }))
+                .await
+                .unwrap();
+        }
+    }
+    corrupt_line(&path, 1);
+
+    let metrics = Arc::new(MetricsCollector::new());
+    let queue = clustering_queue(&path, metrics.clone()).await;
+    assert_eq!(queue.quarantined_records(), 1);
+    assert_eq!(queue.list().await.len(), 2);
+    queue
+        .submit(JobKind::Clustering, json!({ "n": 3 }))
+        .await
+        .unwrap();
+    assert_eq!(metrics.get_gauge("jobs.quarantined_records").await, Some(1));
+
+    let quarantined = Quarantine::beside(&path).records().unwrap();
+    assert_eq!(quarantined.len(), 1);
+    assert_eq!(quarantined[0].position, 2);
+    assert_eq!(quarantined[0].source, path);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn lifecycle_log_survives_a_corrupted_event() {
+    let dir = scratch_dir();
+    let path = dir.join("events.jsonl");
+    let modification = Uuid::new_v4();
+    {
+        let log = LifecycleLog::open(&path).unwrap();
+        log.record(
+            modification,
+            LifecycleEventKind::Proposed {
+                name: "cache".to_string(),
+            },
+        )
+        .await;
+        log.record(modification, LifecycleEventKind::ValidationStarted)
+            .await;
+        log.record(modification, LifecycleEventKind::Accepted).await;
+    }
+    corrupt_line(&path, 2);
+
+    let log = LifecycleLog::open(&path).unwrap();
+    assert_eq!(log.quarantined_records(), 1);
+    let timeline = log.timeline(modification).await;
+    assert_eq!(timeline.len(), 2);
+    assert_eq!(timeline[1].kind, LifecycleEventKind::ValidationStarted);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn corrupted_segment_rows_are_skipped_and_quarantined() {
+    let dir = scratch_dir();
+    let metrics = Arc::new(MetricsCollector::new());
+    let index =
+        VectorIndex::new("disk", 2, DistanceMetric::Euclidean, Some(metrics.clone())).unwrap();
+    index
+        .set_segment_config(SegmentConfig {
+            memtable_limit: 4,
+            ..SegmentConfig::default()
+        })
+        .await;
+    index
+        .set_mmap_storage(Some(MmapStorage::new(&dir)))
+        .await
+        .unwrap();
+    let mut ids = Vec::new();
+    for i in 0..4 {
+        let vector = Vector::new(vec![i as f32, 1.0]);
+        ids.push(index.add(vector, None).await.unwrap());
+    }
+
+    let segment_file = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "vec"))
+        .unwrap();
+    let file = MmapVectorFile::open(&segment_file).unwrap();
+    let row = (0..file.len())
+        .find(|&row| file.read(row).unwrap().0 == ids[2])
+        .unwrap();
+    // Damage the first value of that row: 24-byte header, then 28 bytes per row
+    flip_byte(&segment_file, 24 + row as u64 * 28 + 16);
+    assert!(file.verify(row).is_err());
+    drop(file);
+
+    // Reads skip the damaged row instead of returning a wrong vector
+    assert!(index.get(ids[2]).await.is_none());
+    assert!(index.get(ids[1]).await.is_some());
+    let results = index.search(&Vector::new(vec![2.0, 1.0]), 4).await.unwrap();
+    assert!(results.iter().all(|r| r.id != ids[2]));
+
+    let report = index.verify_integrity(false).await;
+    assert_eq!(report.corrupted, vec![ids[2]]);
+    assert!(report.orphaned.is_empty());
+
+    let report = index.verify_integrity(true).await;
+    assert_eq!(report.corrupted, vec![ids[2]]);
+    assert_eq!(report.repaired, 1);
+    assert_eq!(index.count().await, 3);
+    assert_eq!(index.segment_stats().await.quarantined, 1);
+    assert!(index.verify_integrity(false).await.is_consistent());
+    assert_eq!(
+        metrics
+            .get_counter("vector_index.disk.corrupt_records")
+            .await,
+        Some(1)
+    );
+
+    let quarantined = Quarantine::beside(&segment_file).records().unwrap();
+    assert_eq!(quarantined.len(), 1);
+    assert_eq!(quarantined[0].position, row);
+
+    // The damaged row isn't carried into merged segments
+    let merged = index.merge_segments(true).await.unwrap();
+    assert_eq!(merged.entries, 3);
+    assert_eq!(index.count().await, 3);
+
+    drop(index);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn damaged_headers_are_rejected() {
+    let dir = scratch_dir();
+    let path = dir.join("rows.vec");
+    let mut writer = MmapVectorWriter::create(&path, 2).unwrap();
+    writer.push(Uuid::new_v4(), &[1.0, 2.0]).unwrap();
+    drop(writer.finish().unwrap());
+
+    // Dimensions byte
+    flip_byte(&path, 8);
+    assert!(MmapVectorFile::open(&path).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
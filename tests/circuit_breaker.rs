@@ -1,5 +1,10 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::network::circuit_breaker::{CircuitBreakerMetrics, CircuitBreakerRegistry};
+use amazon_rose_forest::server::{Server, ServerConfig};
 use amazon_rose_forest::{CircuitBreaker, CircuitState};
+use std::sync::Arc;
 use std::time::Duration;
+use warp::http::StatusCode;
 
 #[tokio::test]
 async fn test_circuit_breaker_transitions() {
@@ -21,3 +26,88 @@ async fn test_circuit_breaker_transitions() {
     cb.on_success().await;
     assert_eq!(cb.get_state(), CircuitState::Closed);
 }
+
+#[tokio::test]
+async fn manual_trip_holds_until_reset() {
+    let cb = CircuitBreaker::new(
+        "manual",
+        3,
+        Duration::from_millis(10),
+        Duration::from_millis(5),
+    );
+    cb.trip().await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    // The reset timeout doesn't release a manual trip
+    assert!(!cb.can_execute().await);
+    assert_eq!(cb.get_state(), CircuitState::Open);
+
+    cb.reset().await;
+    assert!(cb.can_execute().await);
+    let metrics = cb.get_metrics().await;
+    assert_eq!(metrics.current_state, CircuitState::Closed);
+    assert!(!metrics.manually_tripped);
+    assert_eq!(metrics.rejected_calls, 1);
+    assert_eq!(metrics.state_transitions.len(), 2);
+}
+
+#[tokio::test]
+async fn admin_endpoints_list_and_override_breakers() {
+    let registry = Arc::new(CircuitBreakerRegistry::new());
+    let peer = registry
+        .register(Arc::new(CircuitBreaker::new(
+            "peer-a",
+            1,
+            Duration::from_millis(10),
+            Duration::from_millis(5),
+        )))
+        .await;
+    peer.on_failure().await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(peer.can_execute().await);
+
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    )
+    .with_circuit_breakers(registry)
+    .filter();
+
+    let resp = warp::test::request()
+        .path("/api/admin/circuit-breakers")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let listed: Vec<CircuitBreakerMetrics> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].name, "peer-a");
+    assert_eq!(listed[0].current_state, CircuitState::HalfOpen);
+    assert_eq!(listed[0].half_open_probes, 1);
+    assert_eq!(listed[0].current_failure_count, 1);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/admin/circuit-breakers/peer-a/trip")
+        .reply(&filter)
+        .await;
+    let tripped: CircuitBreakerMetrics = serde_json::from_slice(resp.body()).unwrap();
+    assert!(tripped.manually_tripped);
+    assert!(!peer.can_execute().await);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/admin/circuit-breakers/peer-a/reset")
+        .reply(&filter)
+        .await;
+    let reset: CircuitBreakerMetrics = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(reset.current_state, CircuitState::Closed);
+    assert_eq!(reset.current_failure_count, 0);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/admin/circuit-breakers/unknown/reset")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,137 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::nerv::cluster_metrics::{
+    ClusterMetricsAggregator, ClusterMetricsConfig, MetricSamples,
+};
+use amazon_rose_forest::network::secure_channel::{NodeIdentity, SecureChannels, SecureClient};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::testing::TestHarness;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::http::StatusCode;
+
+const SHARD_GAUGE: &str = "shards.5f0c3a1e-0000-4000-8000-000000000001.vector_count";
+
+async fn peer(requests: u64, latencies: &[u64]) -> TestHarness {
+    let harness = TestHarness::start().await.unwrap();
+    harness
+        .metrics
+        .increment_counter("search.requests", requests)
+        .await;
+    // Both peers hold a replica of the same shard
+    harness.metrics.set_gauge(SHARD_GAUGE, 10).await;
+    for latency in latencies {
+        harness
+            .metrics
+            .record_histogram("search.latency_ms", *latency)
+            .await;
+    }
+    harness
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn merges_peer_metrics_into_a_cluster_view() {
+    let a = peer(3, &[10, 30]).await;
+    let b = peer(4, &[20]).await;
+    let leader_metrics = Arc::new(MetricsCollector::new());
+    leader_metrics.increment_counter("search.requests", 1).await;
+
+    let config = ClusterMetricsConfig {
+        scrape_timeout: Duration::from_secs(1),
+        ..ClusterMetricsConfig::default()
+    };
+    let aggregator = ClusterMetricsAggregator::new("leader", leader_metrics.clone(), config)
+        .with_peer("a", &format!("{}/metrics", a.base_url()))
+        .with_peer("b", &format!("{}/metrics", b.base_url()))
+        .with_peer("gone", "http://127.0.0.1:9/metrics");
+    assert_eq!(aggregator.scrape().await, 2);
+    assert_eq!(
+        leader_metrics
+            .get_counter("cluster.metrics.scrape_failures")
+            .await,
+        Some(1)
+    );
+
+    let server = Server::new(ServerConfig::default(), leader_metrics, None, None)
+        .with_cluster_metrics(Arc::new(aggregator));
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/metrics/cluster")
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let view = MetricSamples::parse(std::str::from_utf8(resp.body()).unwrap());
+    let value = |series: &str| view.samples.get(series).copied();
+
+    assert_eq!(value("search.requests"), Some(8.0));
+    assert_eq!(value("search.requests{node=\"leader\"}"), Some(1.0));
+    assert_eq!(value("search.requests{node=\"a\"}"), Some(3.0));
+    assert_eq!(value("search.requests{node=\"b\"}"), Some(4.0));
+    assert_eq!(view.types["search.requests"], "counter");
+
+    // Replicas of one shard are not counted twice
+    assert_eq!(value(SHARD_GAUGE), Some(10.0));
+
+    assert_eq!(value("search.latency_ms_count"), Some(3.0));
+    assert_eq!(value("search.latency_ms_sum"), Some(60.0));
+    assert_eq!(value("search.latency_ms_min"), Some(10.0));
+    assert_eq!(value("search.latency_ms_max"), Some(30.0));
+    assert_eq!(value("search.latency_ms_avg"), Some(20.0));
+
+    assert_eq!(value("cluster.scrape.up{node=\"a\"}"), Some(1.0));
+    assert_eq!(value("cluster.scrape.up{node=\"gone\"}"), Some(0.0));
+    assert_eq!(value("search.requests{node=\"gone\"}"), None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scrapes_peers_through_a_secure_channel() {
+    let leader_channels = Arc::new(SecureChannels::new(NodeIdentity::generate("leader")));
+    let peer_channels = Arc::new(SecureChannels::new(NodeIdentity::generate("a")));
+    leader_channels
+        .add_peer("a", peer_channels.identity().public_key())
+        .await;
+    peer_channels
+        .add_peer("leader", leader_channels.identity().public_key())
+        .await;
+
+    let peer_metrics = Arc::new(MetricsCollector::new());
+    peer_metrics.increment_counter("search.requests", 5).await;
+    let peer = Server::new(ServerConfig::default(), peer_metrics, None, None)
+        .with_secure_channels(peer_channels);
+    let (addr, serve) = warp::serve(peer.filter()).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(serve);
+
+    let leader_metrics = Arc::new(MetricsCollector::new());
+    let aggregator =
+        ClusterMetricsAggregator::new("leader", leader_metrics, ClusterMetricsConfig::default())
+            .with_peer("a", &format!("http://{}/metrics", addr))
+            .with_secure_channel(SecureClient::new(leader_channels));
+    assert_eq!(aggregator.scrape().await, 1);
+    let view = MetricSamples::parse(&aggregator.render().await);
+    assert_eq!(
+        view.samples.get("search.requests{node=\"a\"}").copied(),
+        Some(5.0)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cluster_view_needs_an_aggregator() {
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    );
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/metrics/cluster")
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/metrics")
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
@@ -0,0 +1,220 @@
+use amazon_rose_forest::code_analysis::{
+    analyze_file, AnalysisDaemon, Language, ScanSummary, SymbolKind,
+};
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+const RUST_SOURCE: &str = r#"pub struct Classifier {}
+
+impl Classifier {
+    // if this comment counted, complexity would be off
+    pub fn classify(&self, n: i32) -> &'static str {
+        if n < 0 && n > -10 {
+            "small negative"
+        } else if n == 0 {
+            "zero"
+        } else {
+            match n {
+                1 => "one",
+                _ => "many",
+            }
+        }
+    }
+}
+
+fn simple() -> u32 {
+    1
+}
+"#;
+
+const PYTHON_SOURCE: &str = "class Inventory:\n    def check(self, items):\n        for item in items:\n            if item and item.ok:\n                return True\n        return False\n\ndef helper():\n    return 1\n";
+
+fn temp_repo() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("rose-forest-analysis-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("target/debug")).unwrap();
+    std::fs::write(root.join("src/classify.rs"), RUST_SOURCE).unwrap();
+    std::fs::write(root.join("src/inventory.py"), PYTHON_SOURCE).unwrap();
+    std::fs::write(root.join("target/debug/generated.rs"), RUST_SOURCE).unwrap();
+    std::fs::write(root.join("README.md"), "# not analyzed\n").unwrap();
+    root
+}
+
+#[test]
+fn analyzes_rust_symbols_and_complexity() {
+    let analysis = analyze_file("src/classify.rs", RUST_SOURCE);
+    assert_eq!(analysis.language, Language::Rust);
+
+    let names: Vec<(&str, SymbolKind)> = analysis
+        .symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.kind))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("Classifier", SymbolKind::Struct),
+            ("Classifier", SymbolKind::Impl),
+            ("classify", SymbolKind::Function),
+            ("simple", SymbolKind::Function),
+        ]
+    );
+
+    let classify = &analysis.symbols[2];
+    assert_eq!((classify.line, classify.end_line), (5, 16));
+    // if, &&, else if and two match arms
+    assert_eq!(classify.complexity, 6);
+    assert_eq!(analysis.symbols[3].complexity, 1);
+    assert_eq!(analysis.max_complexity(), 6);
+    assert_eq!(analysis.total_complexity(), 7);
+}
+
+#[test]
+fn analyzes_python_by_indentation() {
+    let analysis = analyze_file("inventory.py", PYTHON_SOURCE);
+    assert_eq!(analysis.language, Language::Python);
+
+    let check = analysis.symbols.iter().find(|s| s.name == "check").unwrap();
+    assert_eq!((check.line, check.end_line), (2, 6));
+    // for, if and `and`
+    assert_eq!(check.complexity, 4);
+
+    let class = analysis
+        .symbols
+        .iter()
+        .find(|s| s.name == "Inventory")
+        .unwrap();
+    assert_eq!(class.kind, SymbolKind::Class);
+    assert_eq!(class.end_line, 6);
+}
+
+#[tokio::test]
+async fn scan_skips_unchanged_files_and_drops_deleted_ones() {
+    let root = temp_repo();
+    let metrics = Arc::new(MetricsCollector::new());
+    let daemon = AnalysisDaemon::new(&root, metrics.clone());
+
+    let first = daemon.scan().await.unwrap();
+    assert_eq!(
+        first,
+        ScanSummary {
+            analyzed: 2,
+            unchanged: 0,
+            removed: 0
+        }
+    );
+    assert!(daemon.file("src/classify.rs").await.is_some());
+    assert!(daemon.file("target/debug/generated.rs").await.is_none());
+
+    let second = daemon.scan().await.unwrap();
+    assert_eq!((second.analyzed, second.unchanged), (0, 2));
+    assert_eq!(
+        metrics.get_counter("code_analysis.files_analyzed").await,
+        Some(2)
+    );
+
+    std::fs::remove_file(root.join("src/inventory.py")).unwrap();
+    let third = daemon.scan().await.unwrap();
+    assert_eq!(third.removed, 1);
+    assert!(daemon.file("src/inventory.py").await.is_none());
+    assert_eq!(metrics.get_gauge("code_analysis.files").await, Some(1));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn refresh_reanalyzes_only_changed_files() {
+    let root = temp_repo();
+    let daemon = AnalysisDaemon::new(&root, Arc::new(MetricsCollector::new()));
+    daemon.scan().await.unwrap();
+
+    // Same content is a no-op
+    assert!(!daemon
+        .refresh_path(std::path::Path::new("src/classify.rs"))
+        .await
+        .unwrap());
+
+    std::fs::write(
+        root.join("src/classify.rs"),
+        "fn replacement(x: bool) -> u8 {\n    if x { 1 } else { 0 }\n}\n",
+    )
+    .unwrap();
+    assert!(daemon
+        .refresh_path(&root.join("src/classify.rs"))
+        .await
+        .unwrap());
+    assert!(daemon.find_symbol("classify").await.is_empty());
+    let replacement = daemon.find_symbol("replacement").await;
+    assert_eq!(replacement.len(), 1);
+    assert_eq!(replacement[0].path, "src/classify.rs");
+    assert_eq!(replacement[0].complexity, 2);
+
+    std::fs::remove_file(root.join("src/classify.rs")).unwrap();
+    assert!(daemon
+        .refresh_path(&root.join("src/classify.rs"))
+        .await
+        .unwrap());
+    assert!(daemon.file("src/classify.rs").await.is_none());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn summary_and_hotspots_rank_complex_functions() {
+    let root = temp_repo();
+    let daemon = AnalysisDaemon::new(&root, Arc::new(MetricsCollector::new()));
+    daemon.scan().await.unwrap();
+
+    let hotspots = daemon.hotspots(2).await;
+    let ranked: Vec<(&str, u32)> = hotspots
+        .iter()
+        .map(|s| (s.name.as_str(), s.complexity))
+        .collect();
+    assert_eq!(ranked, vec![("classify", 6), ("check", 4)]);
+
+    let summary = daemon.summary().await;
+    assert_eq!(summary.files, 2);
+    assert_eq!(summary.functions, 4);
+    assert_eq!(summary.max_complexity, 6);
+    assert!((summary.avg_complexity - 3.0).abs() < 1e-6);
+
+    let metrics = daemon.metrics().await;
+    assert_eq!(metrics["cyclomatic_complexity"], 3.0);
+    assert_eq!(metrics["max_cyclomatic_complexity"], 6.0);
+    assert_eq!(metrics["types"], 2.0);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn watcher_picks_up_new_files() {
+    let root = temp_repo();
+    let daemon = Arc::new(AnalysisDaemon::new(
+        &root,
+        Arc::new(MetricsCollector::new()),
+    ));
+    let handle = daemon.clone().start().await.unwrap();
+
+    std::fs::write(
+        root.join("src/added.go"),
+        "package main\n\nfunc (s *Server) Start() error {\n\tif s == nil {\n\t\treturn nil\n\t}\n\treturn nil\n}\n",
+    )
+    .unwrap();
+
+    let mut found = Vec::new();
+    for _ in 0..50 {
+        found = daemon.find_symbol("Start").await;
+        if !found.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, "src/added.go");
+    assert_eq!(found[0].complexity, 2);
+
+    handle.abort();
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,116 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::agent::CodingAgent;
+use amazon_rose_forest::darwin::code_index::{CodeIndex, CodeIndexConfig};
+use amazon_rose_forest::embedding::HashingEmbedder;
+use amazon_rose_forest::sharding::manager::ShardManager;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const CACHE_SOURCE: &str = "pub struct LruCache {}\n\nimpl LruCache {\n    pub fn evict(&mut self) {\n        // cache eviction of the least recently used entry\n    }\n}\n";
+const NETWORK_SOURCE: &str =
+    "pub fn connect(addr: &str) {\n    // open tcp socket and negotiate tls handshake\n}\n";
+const TARGET_SOURCE: &str =
+    "pub fn lookup(cache: &mut LruCache) {\n    // cache lookup before eviction\n}\n";
+
+fn temp_repo() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("rose-forest-index-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("target/debug")).unwrap();
+    std::fs::write(root.join("src/cache.rs"), CACHE_SOURCE).unwrap();
+    std::fs::write(root.join("src/network.rs"), NETWORK_SOURCE).unwrap();
+    std::fs::write(root.join("src/target.rs"), TARGET_SOURCE).unwrap();
+    std::fs::write(root.join("target/debug/build.rs"), CACHE_SOURCE).unwrap();
+    std::fs::write(root.join("notes.bin"), [0u8, 159, 146, 150]).unwrap();
+    root
+}
+
+fn code_index(metrics: Arc<MetricsCollector>) -> Arc<CodeIndex> {
+    let shard_manager = Arc::new(ShardManager::new(metrics.clone()));
+    Arc::new(CodeIndex::new(
+        shard_manager,
+        Arc::new(HashingEmbedder::new(64)),
+        metrics,
+    ))
+}
+
+#[tokio::test]
+async fn chunks_overlap_and_cover_file() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let shard_manager = Arc::new(ShardManager::new(metrics.clone()));
+    let index = CodeIndex::new(shard_manager, Arc::new(HashingEmbedder::new(8)), metrics)
+        .with_config(CodeIndexConfig {
+            chunk_lines: 40,
+            chunk_overlap: 8,
+            ..Default::default()
+        });
+
+    let content: String = (1..=100).map(|i| format!("line {}\n", i)).collect();
+    let chunks = index.chunk_file("a.rs", &content);
+    let ranges: Vec<(usize, usize)> = chunks.iter().map(|c| (c.start_line, c.end_line)).collect();
+    assert_eq!(ranges, vec![(1, 40), (33, 72), (65, 100)]);
+    assert!(chunks[2].text.ends_with("line 100"));
+}
+
+#[tokio::test]
+async fn retrieves_related_chunks_from_other_files() {
+    let root = temp_repo();
+    let metrics = Arc::new(MetricsCollector::new());
+    let index = code_index(metrics.clone());
+
+    let summary = index.index_repository(&root).await.unwrap();
+    // target/ is skipped and .bin isn't an indexed extension
+    assert_eq!(summary.files, 3);
+    assert_eq!(index.chunk_count().await, 3);
+
+    let chunks = index
+        .retrieve("src/target.rs", "performance", TARGET_SOURCE, 1)
+        .await
+        .unwrap();
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].path, "src/cache.rs");
+    assert_eq!(chunks[0].start_line, 1);
+
+    // Re-indexing a file replaces its chunks rather than duplicating them
+    index
+        .index_file("src/cache.rs", CACHE_SOURCE)
+        .await
+        .unwrap();
+    assert_eq!(index.chunk_count().await, 3);
+
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn coding_agent_includes_related_code_in_context() {
+    let root = temp_repo();
+    let metrics = Arc::new(MetricsCollector::new());
+    let index = code_index(metrics.clone());
+    index.index_repository(&root).await.unwrap();
+
+    let agent = CodingAgent::new(metrics.clone());
+    let mut files = HashMap::new();
+    files.insert("src/target.rs".to_string(), TARGET_SOURCE.to_string());
+    agent
+        .update_context(files, root.display().to_string(), HashMap::new())
+        .await
+        .unwrap();
+    agent.enable_code_index(index).await;
+
+    let modification = agent
+        .generate_improvement("src/target.rs", "performance")
+        .await
+        .unwrap();
+    let content = &modification.code_changes[0].modified_content;
+    assert!(content.contains("// Related code: src/cache.rs:1-7"));
+    assert!(!content.contains("// Related code: src/target.rs"));
+    assert!(
+        metrics
+            .get_counter("darwin.agent.context_chunks")
+            .await
+            .unwrap()
+            >= 1
+    );
+
+    std::fs::remove_dir_all(root).ok();
+}
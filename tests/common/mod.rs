@@ -0,0 +1,35 @@
+//! Fixtures shared by the integration tests. Each test crate uses only some
+//! of them.
+#![allow(dead_code)]
+
+use amazon_rose_forest::darwin::self_improvement::{CodeChange, Modification, ModificationStatus};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A proposed modification named `name` making `code_changes`
+pub fn modification(name: &str, code_changes: Vec<CodeChange>) -> Modification {
+    Modification {
+        id: Uuid::new_v4(),
+        name: name.into(),
+        description: String::new(),
+        code_changes,
+        validation_metrics: HashMap::new(),
+        created_at: chrono::Utc::now(),
+        status: ModificationStatus::Proposed,
+        consciousness_level: None,
+        paradigm_shift_potential: None,
+        integrated_paradoxes: Vec::new(),
+    }
+}
+
+/// A change writing `modified` to `path`, over `original` if it existed
+pub fn code_change(path: &str, original: Option<&str>, modified: &str) -> CodeChange {
+    CodeChange {
+        file_path: path.into(),
+        original_content: original.map(Into::into),
+        modified_content: modified.into(),
+        diff: String::new(),
+        evolution_hooks: Vec::new(),
+        reality_branch: None,
+    }
+}
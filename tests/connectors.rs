@@ -0,0 +1,248 @@
+use amazon_rose_forest::{
+    connectors::{
+        import_into_shard, payload_to_metadata, pgvector::parse_pgvector, FaissSource,
+        ImportPolicy, SourceConfig,
+    },
+    core::metrics::MetricsCollector,
+    server::{Server, ServerConfig},
+    sharding::{manager::ShardManager, vector_index::DistanceMetric},
+    Vector,
+};
+use serde_json::json;
+use std::io::Write;
+use std::sync::Arc;
+use warp::http::StatusCode;
+use warp::Filter;
+
+/// Write an `IndexFlatL2` file in FAISS's on-disk layout
+fn write_flat_index(path: &std::path::Path, dimensions: i32, vectors: &[Vec<f32>]) {
+    let mut file = std::fs::File::create(path).unwrap();
+    file.write_all(b"IxF2").unwrap();
+    file.write_all(&dimensions.to_le_bytes()).unwrap();
+    file.write_all(&(vectors.len() as i64).to_le_bytes())
+        .unwrap();
+    file.write_all(&(1i64 << 20).to_le_bytes()).unwrap();
+    file.write_all(&(1i64 << 20).to_le_bytes()).unwrap();
+    file.write_all(&[1u8]).unwrap();
+    file.write_all(&1i32.to_le_bytes()).unwrap();
+    file.write_all(&((vectors.len() as i64) * dimensions as i64).to_le_bytes())
+        .unwrap();
+    for vector in vectors {
+        for value in vector {
+            file.write_all(&value.to_le_bytes()).unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn imports_faiss_flat_index_into_shard() {
+    let path = std::env::temp_dir().join(format!("rose-forest-{}.faiss", uuid::Uuid::new_v4()));
+    let vectors = vec![
+        vec![1.0, 0.0, 0.0],
+        vec![0.0, 1.0, 0.0],
+        vec![0.0, 0.0, 1.0],
+    ];
+    write_flat_index(&path, 3, &vectors);
+
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics));
+    let shard_id = manager.create_shard("imported").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+
+    let mut source = FaissSource::open(path.to_str().unwrap()).unwrap();
+    assert_eq!(source.dimensions(), 3);
+    assert_eq!(source.len(), 3);
+
+    let summary = import_into_shard(&mut source, manager.clone(), shard_id, 2)
+        .await
+        .unwrap();
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(summary.imported, 3);
+    assert_eq!(summary.failed, 0);
+    assert_eq!(summary.batches, 2);
+
+    let results = manager
+        .search_vectors(shard_id, &Vector::new(vec![0.0, 1.0, 0.0]), 1)
+        .await
+        .unwrap();
+    let metadata = results[0].metadata.as_ref().unwrap();
+    assert_eq!(metadata.get("source_id").map(String::as_str), Some("1"));
+}
+
+#[test]
+fn rejects_non_flat_faiss_index() {
+    let path = std::env::temp_dir().join(format!("rose-forest-{}.faiss", uuid::Uuid::new_v4()));
+    std::fs::write(&path, b"IHNf\0\0\0\0").unwrap();
+    assert!(FaissSource::open(path.to_str().unwrap()).is_err());
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn flattens_payload_into_metadata() {
+    let payload = serde_json::json!({
+        "title": "rose",
+        "year": 2024,
+        "tags": ["a", "b"],
+        "author": { "name": "kali" },
+        "missing": null,
+    });
+    let metadata = payload_to_metadata(&payload);
+    assert_eq!(metadata["title"], "rose");
+    assert_eq!(metadata["year"], "2024");
+    assert_eq!(metadata["tags"], "[\"a\",\"b\"]");
+    assert_eq!(metadata["author.name"], "kali");
+    assert!(!metadata.contains_key("missing"));
+}
+
+#[test]
+fn parses_pgvector_text() {
+    assert_eq!(parse_pgvector("[1,2.5,-3]").unwrap(), vec![1.0, 2.5, -3.0]);
+    assert!(parse_pgvector("[]").unwrap().is_empty());
+    assert!(parse_pgvector("1,2").is_err());
+}
+
+#[tokio::test]
+async fn import_endpoint_reads_only_allowed_files_and_hosts() {
+    let dir = std::env::temp_dir().join(format!("rose-forest-imports-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    write_flat_index(
+        &dir.join("flat.faiss"),
+        2,
+        &[vec![1.0, 0.0], vec![0.0, 1.0]],
+    );
+    let outside = dir.with_extension("faiss");
+    write_flat_index(&outside, 2, &[vec![1.0, 0.0]]);
+
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("imported").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let server = |import: ImportPolicy| {
+        Server::new(
+            ServerConfig {
+                import,
+                ..ServerConfig::default()
+            },
+            Arc::new(MetricsCollector::new()),
+            None,
+            Some(manager.clone()),
+        )
+        .filter()
+    };
+    let import = |source: serde_json::Value| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/import")
+            .json(&json!({ "shard_id": shard_id, "source": source }))
+    };
+
+    // Nothing is readable until configured
+    let closed = server(ImportPolicy::default());
+    let resp = import(json!({"type": "faiss", "path": outside}))
+        .reply(&closed)
+        .await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let open = server(
+        ImportPolicy::new()
+            .with_dir(&dir)
+            .with_allowed_host("qdrant.internal"),
+    );
+    let resp = import(json!({"type": "faiss", "path": "flat.faiss"}))
+        .reply(&open)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        manager
+            .get_vector_index(shard_id)
+            .await
+            .unwrap()
+            .count()
+            .await,
+        2
+    );
+
+    for source in [
+        json!({"type": "faiss", "path": outside}),
+        json!({"type": "faiss", "path": format!("../{}", outside.file_name().unwrap().to_string_lossy())}),
+        json!({"type": "qdrant", "url": "http://169.254.169.254/", "collection": "c"}),
+    ] {
+        let resp = import(source).reply(&open).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    std::fs::remove_file(&outside).unwrap();
+}
+
+#[test]
+fn import_policy_checks_every_postgres_host() {
+    let policy = ImportPolicy::new().with_allowed_host("db.internal");
+    let source = |connection_string: &str| SourceConfig::Pgvector {
+        connection_string: connection_string.into(),
+        table: "items".into(),
+        id_column: "id".into(),
+        vector_column: "embedding".into(),
+    };
+    assert!(policy
+        .check_source(&source("postgres://app@db.internal/vectors"))
+        .is_ok());
+    assert!(policy
+        .check_source(&source("host=db.internal user=app"))
+        .is_ok());
+    for denied in [
+        "postgres://app@10.0.0.5/vectors",
+        "postgres://app@db.internal/vectors?host=10.0.0.5",
+        "host=db.internal,10.0.0.5 user=app",
+        "user=app",
+    ] {
+        assert!(policy.check_source(&source(denied)).is_err(), "{}", denied);
+    }
+}
+
+#[tokio::test]
+async fn qdrant_source_does_not_follow_redirects() {
+    // Stands in for an allowed host redirecting to one the policy forbids
+    let redirect = warp::any().map(|| {
+        warp::redirect::temporary(warp::http::Uri::from_static(
+            "http://169.254.169.254/collections/c/points/scroll",
+        ))
+    });
+    let (addr, server) = warp::serve(redirect).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let mut source = SourceConfig::Qdrant {
+        url: format!("http://{}", addr),
+        collection: "c".into(),
+        api_key: None,
+        vector_name: None,
+    }
+    .open()
+    .await
+    .unwrap();
+    let err = source.next_batch(10).await.unwrap_err();
+    assert!(err.to_string().contains("redirect"), "{}", err);
+}
+
+#[test]
+fn import_policy_is_read_from_the_environment() {
+    let dir = std::env::temp_dir().join(format!("rose-forest-imports-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("flat.faiss"), b"").unwrap();
+    std::env::set_var("ROSE_FOREST_IMPORT_DIR", &dir);
+    std::env::set_var("ROSE_FOREST_IMPORT_HOSTS", "qdrant.internal, db.internal");
+    let config = ServerConfig::from_env().unwrap();
+    std::env::remove_var("ROSE_FOREST_IMPORT_DIR");
+    std::env::remove_var("ROSE_FOREST_IMPORT_HOSTS");
+
+    assert!(config.import.check_path("flat.faiss").is_ok());
+    assert!(config.import.check_host("db.internal").is_ok());
+    assert!(config.import.check_host("169.254.169.254").is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
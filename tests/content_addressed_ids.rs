@@ -0,0 +1,76 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::sharding::manager::{IdScheme, ShardManager};
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+async fn content_addressed_shard(manager: &ShardManager) -> Uuid {
+    let shard_id = manager.create_shard("docs").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+    manager
+        .set_id_scheme(shard_id, IdScheme::ContentAddressed)
+        .await
+        .unwrap();
+    shard_id
+}
+
+#[tokio::test]
+async fn ingestion_is_idempotent() {
+    let manager = ShardManager::new(Arc::new(MetricsCollector::new()));
+    let shard_id = content_addressed_shard(&manager).await;
+
+    let metadata = HashMap::from([("doc".to_string(), "readme".to_string())]);
+    let vector = Vector::new(vec![0.1, 0.2, 0.3]);
+    let first = manager
+        .add_vector(shard_id, vector.clone(), Some(metadata.clone()))
+        .await
+        .unwrap();
+    let second = manager
+        .add_vector(shard_id, vector.clone(), Some(metadata.clone()))
+        .await
+        .unwrap();
+    assert_eq!(first, second);
+    assert_eq!(first, vector.content_id(Some(&metadata)));
+
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    assert_eq!(index.count().await, 1);
+
+    // Different metadata is different content
+    manager.add_vector(shard_id, vector, None).await.unwrap();
+    assert_eq!(index.count().await, 2);
+}
+
+#[tokio::test]
+async fn replicas_dedupe_independent_ingestion() {
+    let primary = ShardManager::new(Arc::new(MetricsCollector::new()));
+    let replica = ShardManager::new(Arc::new(MetricsCollector::new()));
+    let primary_shard = content_addressed_shard(&primary).await;
+    let replica_shard = content_addressed_shard(&replica).await;
+
+    // Both nodes ingest the same document before replicating
+    let vector = Vector::new(vec![0.5, 0.5, 0.0]);
+    primary
+        .add_vector(primary_shard, vector.clone(), None)
+        .await
+        .unwrap();
+    replica
+        .add_vector(replica_shard, vector, None)
+        .await
+        .unwrap();
+
+    let feed = primary.change_feed(primary_shard).await.unwrap();
+    for event in feed.read(0, 10).await.unwrap().events {
+        let applied = replica
+            .apply_replicated_change(replica_shard, event.op, "primary")
+            .await
+            .unwrap();
+        assert!(!applied);
+    }
+    let index = replica.get_vector_index(replica_shard).await.unwrap();
+    assert_eq!(index.count().await, 1);
+}
@@ -1,6 +1,8 @@
 use amazon_rose_forest::core::metrics::MetricsCollector;
 use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
-use amazon_rose_forest::darwin::ritual::{RitualManager, RitualStage, RitualStageStatus};
+use amazon_rose_forest::darwin::ritual::{
+    RitualManager, RitualStage, RitualStageStatus, RitualTrigger,
+};
 use amazon_rose_forest::darwin::self_improvement::{Modification, ModificationStatus};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -18,6 +20,9 @@ async fn test_add_to_archive() {
         validation_metrics: HashMap::new(),
         created_at: chrono::Utc::now(),
         status: ModificationStatus::Proposed,
+        consciousness_level: None,
+        paradigm_shift_potential: None,
+        integrated_paradoxes: Vec::new(),
     };
     let mut metric_map = HashMap::new();
     metric_map.insert("score".to_string(), 1.0);
@@ -53,7 +58,10 @@ async fn test_ritual_stage_dependencies() {
             completed_at: None,
         },
     ];
-    let ritual_id = manager.create_ritual("r", "d", stages).await.unwrap();
+    let ritual_id = manager
+        .create_ritual("r", "d", stages, RitualTrigger::Manual)
+        .await
+        .unwrap();
     manager.start_stage(ritual_id, "A").await.unwrap();
     // Starting B should fail because A not completed
     assert!(manager.start_stage(ritual_id, "B").await.is_err());
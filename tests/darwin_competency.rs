@@ -0,0 +1,195 @@
+mod common;
+
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::agent::{CodingAgent, ProgrammingLanguage};
+use amazon_rose_forest::darwin::competency::{
+    CompetencyConfig, CompetencyOutcome, CompetencyTracker,
+};
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::lifecycle::LifecycleEventKind;
+use amazon_rose_forest::darwin::self_improvement::{Modification, SelfImprovementEngine};
+use amazon_rose_forest::darwin::validation::ValidationPipeline;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use chrono::Utc;
+use std::sync::Arc;
+use warp::http::StatusCode;
+
+fn modification(paths: &[&str]) -> Modification {
+    let code_changes = paths
+        .iter()
+        .map(|path| common::code_change(path, None, "x"))
+        .collect();
+    common::modification("m", code_changes)
+}
+
+fn new_engine(pipeline: ValidationPipeline) -> SelfImprovementEngine {
+    let metrics = Arc::new(MetricsCollector::new());
+    SelfImprovementEngine::new(
+        metrics.clone(),
+        Arc::new(pipeline),
+        Arc::new(ExplorationStrategy::new(metrics)),
+    )
+}
+
+#[tokio::test]
+async fn outcomes_move_scores_and_decay_back_to_the_prior() {
+    let tracker = CompetencyTracker::new(CompetencyConfig::default());
+    tracker.set_prior(&ProgrammingLanguage::Python, 0.8).await;
+    let t0 = Utc::now();
+
+    for _ in 0..2 {
+        tracker
+            .record_at(
+                &ProgrammingLanguage::Python,
+                CompetencyOutcome::Accepted,
+                t0,
+            )
+            .await;
+    }
+    for _ in 0..3 {
+        tracker
+            .record_at(&ProgrammingLanguage::Go, CompetencyOutcome::Rejected, t0)
+            .await;
+    }
+
+    let python = tracker
+        .competency_at(&ProgrammingLanguage::Python, t0)
+        .await;
+    assert_eq!(python.acceptance_rate, Some(1.0));
+    assert_eq!(python.test_pass_rate, None);
+    // Untested dimensions stay at the prior: (5 * 0.8 + 2 * 0.9) / 7
+    assert!((python.score - 5.8 / 7.0).abs() < 1e-4);
+
+    // Go has no prior, so it starts from 0.5: (5 * 0.5 + 3 * 0.25) / 8
+    let go = tracker.competency_at(&ProgrammingLanguage::Go, t0).await;
+    assert_eq!(go.prior, 0.5);
+    assert!((go.score - 3.25 / 8.0).abs() < 1e-4);
+
+    // A half-life later the evidence counts half as much
+    let later = t0 + chrono::Duration::days(7);
+    let go_later = tracker.competency_at(&ProgrammingLanguage::Go, later).await;
+    assert!((go_later.evidence - 1.5).abs() < 1e-3);
+    assert!((go_later.score - 2.875 / 6.5).abs() < 1e-3);
+    assert_eq!(go_later.last_outcome, Some(t0));
+
+    // Long-idle languages are back at their prior
+    let much_later = t0 + chrono::Duration::days(365);
+    let go_idle = tracker
+        .competency_at(&ProgrammingLanguage::Go, much_later)
+        .await;
+    assert!((go_idle.score - 0.5).abs() < 1e-3);
+
+    let matrix = tracker.matrix_at(t0).await;
+    let languages: Vec<&str> = matrix.iter().map(|c| c.language.as_str()).collect();
+    assert_eq!(languages, vec!["python", "go"]);
+}
+
+#[tokio::test]
+async fn test_pass_and_rollback_rates_are_tracked() {
+    let tracker = CompetencyTracker::default();
+    let rust = ProgrammingLanguage::Rust;
+    for rate in [1.0, 0.5] {
+        tracker.record(&rust, CompetencyOutcome::Tested(rate)).await;
+    }
+    for _ in 0..4 {
+        tracker.record(&rust, CompetencyOutcome::Deployed).await;
+    }
+    tracker.record(&rust, CompetencyOutcome::RolledBack).await;
+
+    let competency = tracker.competency_at(&rust, Utc::now()).await;
+    assert!((competency.test_pass_rate.unwrap() - 0.75).abs() < 1e-3);
+    assert!((competency.rollback_rate.unwrap() - 0.25).abs() < 1e-3);
+    // Nothing validated yet, so the score is still the prior
+    assert_eq!(competency.evidence, 0.0);
+    assert_eq!(competency.score, 0.5);
+}
+
+#[tokio::test]
+async fn engine_records_validation_outcomes_per_language() {
+    let engine = new_engine(ValidationPipeline::new(Arc::new(MetricsCollector::new())));
+
+    let accepted = modification(&["app/main.py", "app/util.py", "web/index.ts"]);
+    engine.propose_modification(accepted.clone()).await.unwrap();
+    assert!(engine.validate_modification(accepted.id).await.unwrap());
+
+    engine
+        .record_rollback(accepted.id, "latency regression")
+        .await
+        .unwrap();
+    let timeline = engine.modification_timeline(accepted.id).await;
+    assert!(timeline.iter().any(|e| e.kind
+        == LifecycleEventKind::RolledBack {
+            reason: "latency regression".into()
+        }));
+
+    let matrix = engine.competency_matrix().await;
+    let python = matrix.iter().find(|c| c.language == "python").unwrap();
+    // Two Python files, but one modification
+    assert!((python.evidence - 1.0).abs() < 1e-3);
+    assert_eq!(python.acceptance_rate, Some(1.0));
+    assert!(matrix.iter().any(|c| c.language == "typescript"));
+    assert!(!matrix.iter().any(|c| c.language == "rust"));
+
+    let strict = ValidationPipeline::new(Arc::new(MetricsCollector::new()));
+    strict.set_threshold("unit_tests.pass_rate", 1.0);
+    let strict_engine = new_engine(strict);
+    let rejected = modification(&["cmd/main.go"]);
+    strict_engine
+        .propose_modification(rejected.clone())
+        .await
+        .unwrap();
+    assert!(!strict_engine
+        .validate_modification(rejected.id)
+        .await
+        .unwrap());
+    let go = strict_engine
+        .competency_tracker()
+        .competency_at(&ProgrammingLanguage::Go, Utc::now())
+        .await;
+    assert_eq!(go.acceptance_rate, Some(0.0));
+    assert!(go.score < 0.5);
+}
+
+#[tokio::test]
+async fn agent_uses_tracked_competency() {
+    let agent = CodingAgent::new(Arc::new(MetricsCollector::new()));
+    let tracker = Arc::new(CompetencyTracker::default());
+    agent.enable_competency_tracking(tracker.clone()).await;
+
+    // Static competencies become the priors
+    let rust = agent.language_competency(&ProgrammingLanguage::Rust).await;
+    assert!((rust - 0.9).abs() < 1e-6);
+
+    for _ in 0..5 {
+        tracker
+            .record(&ProgrammingLanguage::Rust, CompetencyOutcome::Failed)
+            .await;
+    }
+    assert!(agent.language_competency(&ProgrammingLanguage::Rust).await < rust);
+}
+
+#[tokio::test]
+async fn competency_matrix_is_served() {
+    let engine = new_engine(ValidationPipeline::new(Arc::new(MetricsCollector::new())));
+    engine
+        .competency_tracker()
+        .record(&ProgrammingLanguage::Python, CompetencyOutcome::Accepted)
+        .await;
+
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    )
+    .with_self_improvement_engine(Arc::new(engine));
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/api/darwin/competencies")
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body[0]["language"], "python");
+    assert_eq!(body[0]["acceptance_rate"], 1.0);
+}
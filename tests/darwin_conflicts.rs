@@ -0,0 +1,111 @@
+mod common;
+
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::conflicts::{detect_conflicts, LineRange};
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::self_improvement::{
+    CodeChange, Modification, SelfImprovementEngine,
+};
+use amazon_rose_forest::darwin::validation::ValidationPipeline;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use std::sync::Arc;
+use warp::http::StatusCode;
+
+const ORIGINAL: &str = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+
+fn modification(path: &str, original: Option<&str>, modified: &str, diff: &str) -> Modification {
+    let change = CodeChange {
+        diff: diff.into(),
+        ..common::code_change(path, original, modified)
+    };
+    common::modification("m", vec![change])
+}
+
+#[test]
+fn overlapping_hunks_conflict() {
+    let top = modification(
+        "src/lib.rs",
+        Some(ORIGINAL),
+        "A\nb\nc\nd\ne\nf\ng\nh\ni\nj\n",
+        "",
+    );
+    let bottom = modification(
+        "src/lib.rs",
+        Some(ORIGINAL),
+        "a\nb\nc\nd\ne\nf\ng\nh\ni\nJ\n",
+        "",
+    );
+    let top_again = modification(
+        "./src/lib.rs",
+        Some(ORIGINAL),
+        "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\n",
+        "",
+    );
+    let elsewhere = modification("src/main.rs", Some(ORIGINAL), "", "");
+
+    let conflicts = detect_conflicts(&[top.clone(), bottom.clone(), top_again.clone(), elsewhere]);
+    assert_eq!(conflicts.len(), 1);
+    assert!(conflicts[0].involves(top.id) && conflicts[0].involves(top_again.id));
+    assert_eq!(conflicts[0].first_lines, LineRange { start: 1, end: 2 });
+
+    // Hunk headers from the diff are preferred when present
+    let hunk_a = modification("x.rs", Some(ORIGINAL), "changed", "@@ -1,3 +1,3 @@\n");
+    let hunk_b = modification("x.rs", Some(ORIGINAL), "changed", "@@ -8,2 +8,3 @@\n");
+    assert!(detect_conflicts(&[hunk_a.clone(), hunk_b]).is_empty());
+    let hunk_c = modification("x.rs", Some(ORIGINAL), "changed", "@@ -3,0 +4,2 @@\n");
+    assert_eq!(detect_conflicts(&[hunk_a, hunk_c]).len(), 1);
+
+    // Two modifications creating the same file always conflict
+    let new_a = modification("new.rs", None, "x", "");
+    let new_b = modification("new.rs", None, "y", "");
+    assert_eq!(detect_conflicts(&[new_a, new_b]).len(), 1);
+}
+
+#[tokio::test]
+async fn newer_conflicting_modification_is_blocked() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let engine = Arc::new(SelfImprovementEngine::new(
+        metrics.clone(),
+        Arc::new(ValidationPipeline::new(metrics.clone())),
+        Arc::new(ExplorationStrategy::new(metrics)),
+    ));
+
+    let older = modification(
+        "src/lib.rs",
+        Some(ORIGINAL),
+        "A\nb\nc\nd\ne\nf\ng\nh\ni\nj\n",
+        "",
+    );
+    let mut newer = modification(
+        "src/lib.rs",
+        Some(ORIGINAL),
+        "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\n",
+        "",
+    );
+    newer.created_at = older.created_at + chrono::Duration::seconds(1);
+    for m in [&older, &newer] {
+        engine.propose_modification(m.clone()).await.unwrap();
+        assert!(engine.validate_modification(m.id).await.unwrap());
+    }
+
+    let conflicts = engine.pending_conflicts().await;
+    assert_eq!(conflicts.len(), 1);
+    let err = engine.deploy_modification(newer.id).await.unwrap_err();
+    assert!(err.to_string().contains(&older.id.to_string()));
+
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    )
+    .with_self_improvement_engine(engine);
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/api/darwin/conflicts")
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body[0]["path"], "src/lib.rs");
+}
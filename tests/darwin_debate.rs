@@ -0,0 +1,71 @@
+mod common;
+
+use amazon_rose_forest::darwin::debate::{CandidateCritic, Critique, DebateMode};
+use amazon_rose_forest::darwin::self_improvement::{Modification, ModificationStatus};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Endorses careful candidates, refuses to review when it wrote a flaky one.
+struct NameCritic;
+
+#[async_trait]
+impl CandidateCritic for NameCritic {
+    async fn critique(
+        &self,
+        reviewer: &Modification,
+        target: &Modification,
+    ) -> anyhow::Result<Critique> {
+        if reviewer.name == "flaky" {
+            anyhow::bail!("reviewer unavailable");
+        }
+        let score = if target.name == "careful" { 1.5 } else { 0.2 };
+        Ok(Critique {
+            reviewer: reviewer.id,
+            target: target.id,
+            score,
+            rationale: format!("{} reviewed {}", reviewer.name, target.name),
+        })
+    }
+}
+
+fn candidate(name: &str) -> Modification {
+    Modification {
+        status: ModificationStatus::Accepted,
+        ..common::modification(name, Vec::new())
+    }
+}
+
+#[tokio::test]
+async fn candidates_review_each_other() {
+    let careful = candidate("careful");
+    let risky = candidate("risky");
+    let flaky = candidate("flaky");
+    let mode = DebateMode::new(Arc::new(NameCritic), 2.0);
+
+    let outcome = mode
+        .run(&[careful.clone(), risky.clone(), flaky.clone()])
+        .await;
+
+    // 6 pairings, minus the 2 the flaky author failed to review
+    assert_eq!(outcome.critiques.len(), 4);
+    assert!(outcome.critiques.iter().all(|c| c.reviewer != c.target));
+    // Scores are clamped to [0, 1]
+    assert_eq!(outcome.score(careful.id), Some(1.0));
+    assert!((outcome.score(risky.id).unwrap() - 0.2).abs() < 1e-6);
+    assert!((outcome.score(flaky.id).unwrap() - 0.2).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn critique_scores_can_outweigh_validation_lead() {
+    let careful = candidate("careful");
+    let risky = candidate("risky");
+    let mode = DebateMode::new(Arc::new(NameCritic), 2.0);
+    let outcome = mode.run(&[careful.clone(), risky.clone()]).await;
+
+    let careful_score = mode.combined_score(1.0, outcome.score(careful.id));
+    let risky_score = mode.combined_score(2.0, outcome.score(risky.id));
+    assert!(careful_score > risky_score);
+
+    // Candidates without critiques keep their validation score
+    assert_eq!(mode.combined_score(1.5, None), 1.5);
+}
@@ -0,0 +1,203 @@
+mod common;
+
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::chat::{ChatBackend, ChatClient, ChatMessage, ChatRequest};
+use amazon_rose_forest::darwin::degradation::{DegradationConfig, ProviderMonitor};
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::self_improvement::SelfImprovementEngine;
+use amazon_rose_forest::darwin::validation::ValidationPipeline;
+use amazon_rose_forest::network::circuit_breaker::{CircuitBreakerMetrics, CircuitBreakerRegistry};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::CircuitState;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Provider that fails while switched down
+#[derive(Default)]
+struct FlakyBackend {
+    down: AtomicBool,
+    calls: AtomicUsize,
+}
+
+impl FlakyBackend {
+    fn set_up(&self, up: bool) {
+        self.down.store(!up, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl ChatBackend for FlakyBackend {
+    async fn complete(&self, _request: &ChatRequest) -> Result<String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.down.load(Ordering::SeqCst) {
+            Err(anyhow!("503 Service Unavailable"))
+        } else {
+            Ok("OK".to_string())
+        }
+    }
+}
+
+fn monitor(
+    metrics: Arc<MetricsCollector>,
+) -> (Arc<ProviderMonitor>, Arc<FlakyBackend>, Arc<FlakyBackend>) {
+    let primary = Arc::new(FlakyBackend::default());
+    let fallback = Arc::new(FlakyBackend::default());
+    let monitor = ProviderMonitor::new(metrics, DegradationConfig::default())
+        .with_provider("primary", primary.clone())
+        .with_provider("fallback", fallback.clone());
+    (Arc::new(monitor), primary, fallback)
+}
+
+#[tokio::test]
+async fn degrades_only_when_every_provider_fails_and_recovers_on_check() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let (monitor, primary, fallback) = monitor(metrics.clone());
+
+    primary.set_up(false);
+    let status = monitor.check().await;
+    assert!(!status.degraded);
+    assert!(!monitor.is_degraded());
+    assert_eq!(
+        status.providers[0].last_error.as_deref(),
+        Some("503 Service Unavailable")
+    );
+
+    fallback.set_up(false);
+    let status = monitor.check().await;
+    assert!(status.degraded);
+    assert!(status.since.is_some());
+    assert_eq!(
+        metrics.get_counter("darwin.degraded.entered").await,
+        Some(1)
+    );
+    assert_eq!(metrics.get_gauge("darwin.degraded").await, Some(1));
+
+    // Still down: no second alert
+    monitor.check().await;
+    assert_eq!(
+        metrics.get_counter("darwin.degraded.entered").await,
+        Some(1)
+    );
+
+    fallback.set_up(true);
+    let status = monitor.check().await;
+    assert!(!status.degraded);
+    assert!(status.since.is_none());
+    assert_eq!(
+        metrics.get_counter("darwin.degraded.recovered").await,
+        Some(1)
+    );
+    assert_eq!(metrics.get_gauge("darwin.degraded").await, Some(0));
+}
+
+#[tokio::test]
+async fn failed_requests_through_monitored_backends_count() {
+    let (monitor, primary, fallback) = monitor(Arc::new(MetricsCollector::new()));
+    primary.set_up(false);
+    fallback.set_up(false);
+
+    for name in ["primary", "fallback"] {
+        let chat = ChatClient::with_backend("model", monitor.monitored(name).unwrap());
+        assert!(chat.complete(&[ChatMessage::user("hi")]).await.is_err());
+    }
+    assert!(monitor.is_degraded());
+    assert!(monitor.monitored("unknown").is_none());
+}
+
+#[tokio::test]
+async fn provider_breakers_are_listed_and_overridden_by_operators() {
+    let (monitor, primary, fallback) = monitor(Arc::new(MetricsCollector::new()));
+    let registry = Arc::new(CircuitBreakerRegistry::new());
+    monitor.register_circuit_breakers(&registry).await;
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    )
+    .with_circuit_breakers(registry)
+    .filter();
+
+    // Repeated failures open the primary's breaker, after which it isn't called
+    primary.set_up(false);
+    let chat = ChatClient::with_backend("model", monitor.monitored("primary").unwrap());
+    for _ in 0..DegradationConfig::default().breaker_failures + 1 {
+        assert!(chat.complete(&[ChatMessage::user("hi")]).await.is_err());
+    }
+    assert_eq!(
+        primary.calls.load(Ordering::SeqCst) as u64,
+        DegradationConfig::default().breaker_failures
+    );
+
+    let resp = warp::test::request()
+        .path("/api/admin/circuit-breakers")
+        .reply(&filter)
+        .await;
+    let listed: Vec<CircuitBreakerMetrics> = serde_json::from_slice(resp.body()).unwrap();
+    let names: Vec<&str> = listed.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["llm:fallback", "llm:primary"]);
+    assert_eq!(listed[0].current_state, CircuitState::Closed);
+    assert_eq!(listed[1].current_state, CircuitState::Open);
+    assert_eq!(listed[1].rejected_calls, 1);
+
+    // A tripped breaker holds requests back from a healthy provider
+    warp::test::request()
+        .method("POST")
+        .path("/api/admin/circuit-breakers/llm:fallback/trip")
+        .reply(&filter)
+        .await;
+    let chat = ChatClient::with_backend("model", monitor.monitored("fallback").unwrap());
+    assert!(chat.complete(&[ChatMessage::user("hi")]).await.is_err());
+    assert_eq!(fallback.calls.load(Ordering::SeqCst), 0);
+
+    warp::test::request()
+        .method("POST")
+        .path("/api/admin/circuit-breakers/llm:fallback/reset")
+        .reply(&filter)
+        .await;
+    assert!(chat.complete(&[ChatMessage::user("hi")]).await.is_ok());
+    assert_eq!(fallback.calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn generation_pauses_while_validation_continues() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let engine = SelfImprovementEngine::new(
+        metrics.clone(),
+        Arc::new(ValidationPipeline::new(metrics.clone())),
+        Arc::new(ExplorationStrategy::new(metrics.clone())),
+    );
+    let (monitor, primary, fallback) = monitor(metrics.clone());
+    engine.enable_provider_monitor(monitor.clone()).await;
+
+    let id = engine
+        .propose_modification(common::modification(
+            "tidy",
+            vec![common::code_change("docs/notes.md", Some("a\n"), "b\n")],
+        ))
+        .await
+        .unwrap();
+
+    primary.set_up(false);
+    fallback.set_up(false);
+    monitor.check().await;
+    assert!(engine.provider_status().await.unwrap().degraded);
+
+    assert!(engine.generate_modifications().await.unwrap().is_empty());
+    assert_eq!(
+        metrics.get_counter("darwin.generation.paused").await,
+        Some(1)
+    );
+    assert!(engine.validate_modification(id).await.is_ok());
+
+    primary.set_up(true);
+    monitor.check().await;
+    assert!(!engine.provider_status().await.unwrap().degraded);
+    engine.generate_modifications().await.unwrap();
+    assert_eq!(
+        metrics.get_counter("darwin.generation.paused").await,
+        Some(1)
+    );
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::governance::{
+    modification_from_subject, modification_subject, ArbitrationRollback, DisputeDecision,
+    DisputeOutcome, GovernanceAction,
+};
+use amazon_rose_forest::darwin::lifecycle::LifecycleEventKind;
+use amazon_rose_forest::darwin::self_improvement::{
+    CodeChange, Modification, ModificationStatus, SelfImprovementEngine,
+};
+use amazon_rose_forest::darwin::validation::ValidationPipeline;
+use amazon_rose_forest::darwin::workspace::WorkspaceApplier;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn project() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("governance-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+    root
+}
+
+fn change(path: &str, original: Option<&str>, modified: &str) -> CodeChange {
+    CodeChange {
+        file_path: path.into(),
+        original_content: original.map(Into::into),
+        modified_content: modified.into(),
+        diff: String::new(),
+        evolution_hooks: Vec::new(),
+        reality_branch: None,
+    }
+}
+
+fn modification(code_changes: Vec<CodeChange>) -> Modification {
+    common::modification("m", code_changes)
+}
+
+fn decision(modification_id: Uuid, outcome: DisputeOutcome) -> DisputeDecision {
+    DisputeDecision {
+        case_id: "case-1".into(),
+        subject: modification_subject(modification_id),
+        outcome,
+        resolution: "Community resolved this case positively".into(),
+        decision_proof: "ab12".into(),
+        votes: 5,
+        decided_at: 1,
+    }
+}
+
+async fn deployed(
+    root: &Path,
+    metrics: Arc<MetricsCollector>,
+) -> (Arc<SelfImprovementEngine>, Uuid) {
+    let engine = SelfImprovementEngine::new(
+        metrics.clone(),
+        Arc::new(ValidationPipeline::new(metrics.clone())),
+        Arc::new(ExplorationStrategy::new(metrics)),
+    )
+    .with_workspace(WorkspaceApplier::new(root.to_path_buf()).without_build_check());
+    let engine = Arc::new(engine);
+
+    let m = modification(vec![
+        change("src/lib.rs", Some("pub fn a() {}\n"), "pub fn b() {}\n"),
+        change("src/extra.rs", None, "pub fn c() {}\n"),
+    ]);
+    engine.propose_modification(m.clone()).await.unwrap();
+    assert!(engine.validate_modification(m.id).await.unwrap());
+    engine.deploy_modification(m.id).await.unwrap();
+    assert!(root.join("src/extra.rs").exists());
+    (engine, m.id)
+}
+
+#[test]
+fn subjects_round_trip() {
+    let id = Uuid::new_v4();
+    assert_eq!(
+        modification_from_subject(&modification_subject(id)),
+        Some(id)
+    );
+    assert_eq!(modification_from_subject("uhCEk-some-entry-hash"), None);
+    assert_eq!(modification_from_subject("darwin_modification:nope"), None);
+}
+
+#[tokio::test]
+async fn upheld_disputes_roll_back_deployed_modifications() {
+    let root = project();
+    let metrics = Arc::new(MetricsCollector::new());
+    let (engine, id) = deployed(&root, metrics.clone()).await;
+    let rollback = ArbitrationRollback::new(engine.clone(), metrics.clone());
+
+    let payload = serde_json::to_vec(&decision(id, DisputeOutcome::Upheld)).unwrap();
+    assert_eq!(
+        rollback.handle_json(&payload).await.unwrap(),
+        GovernanceAction::RolledBack
+    );
+    assert_eq!(
+        std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub fn a() {}\n"
+    );
+    assert!(!root.join("src/extra.rs").exists());
+    assert_eq!(
+        engine.get_modification(id).await.unwrap().status,
+        ModificationStatus::RolledBack
+    );
+
+    // The decision proof is kept with the rollback
+    let timeline = engine.modification_timeline(id).await;
+    let reason = timeline
+        .iter()
+        .find_map(|e| match &e.kind {
+            LifecycleEventKind::RolledBack { reason } => Some(reason.clone()),
+            _ => None,
+        })
+        .unwrap();
+    assert!(reason.contains("case-1") && reason.contains("ab12"));
+
+    // A redelivered decision changes nothing
+    assert_eq!(
+        rollback.handle_json(&payload).await.unwrap(),
+        GovernanceAction::NotDeployed
+    );
+    assert_eq!(
+        metrics.get_counter("darwin.governance.rollbacks").await,
+        Some(1)
+    );
+    assert_eq!(
+        metrics.get_counter("darwin.governance.ignored").await,
+        Some(1)
+    );
+
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn dismissed_and_unrelated_cases_leave_modifications_alone() {
+    let root = project();
+    let metrics = Arc::new(MetricsCollector::new());
+    let (engine, id) = deployed(&root, metrics.clone()).await;
+    let rollback = ArbitrationRollback::new(engine.clone(), metrics);
+
+    assert_eq!(
+        rollback
+            .handle(&decision(id, DisputeOutcome::Dismissed))
+            .await
+            .unwrap(),
+        GovernanceAction::Dismissed
+    );
+    let mut unrelated = decision(id, DisputeOutcome::Upheld);
+    unrelated.subject = "uhCEk-knowledge-entry".into();
+    assert_eq!(
+        rollback.handle(&unrelated).await.unwrap(),
+        GovernanceAction::NotAModification
+    );
+    assert_eq!(
+        engine.get_modification(id).await.unwrap().status,
+        ModificationStatus::Deployed
+    );
+
+    // Files edited since the deployment are never clobbered
+    std::fs::write(root.join("src/lib.rs"), "pub fn edited() {}\n").unwrap();
+    assert!(rollback
+        .handle(&decision(id, DisputeOutcome::Upheld))
+        .await
+        .is_err());
+    assert!(root.join("src/extra.rs").exists());
+    assert_eq!(
+        engine.get_modification(id).await.unwrap().status,
+        ModificationStatus::Deployed
+    );
+
+    std::fs::remove_dir_all(root).ok();
+}
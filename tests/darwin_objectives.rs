@@ -0,0 +1,128 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::objectives::{Objective, ObjectiveConfig, ObjectiveKind};
+use amazon_rose_forest::evaluation::Evaluation;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn latency_objective() -> Objective {
+    Objective {
+        name: "search-latency".into(),
+        weight: 2.0,
+        kind: ObjectiveKind::Latency {
+            histogram: "search.latency_ms".into(),
+            target_ms: 10.0,
+        },
+        validation_metric: Some("performance.vector_search_latency_ms".into()),
+    }
+}
+
+fn coverage_objective() -> Objective {
+    Objective {
+        name: "coverage".into(),
+        weight: 1.0,
+        kind: ObjectiveKind::TestCoverage {
+            gauge: "tests.coverage_percent".into(),
+            min_ratio: 0.8,
+        },
+        validation_metric: Some("unit_tests.coverage".into()),
+    }
+}
+
+#[test]
+fn loads_objectives_from_json() {
+    let path = std::env::temp_dir().join(format!("objectives-{}.json", uuid::Uuid::new_v4()));
+    std::fs::write(
+        &path,
+        r#"{"objectives": [
+            {"name": "errors", "kind": "error_budget", "errors": "api.errors", "requests": "api.requests", "max_ratio": 0.01},
+            {"name": "memory", "weight": 3.0, "kind": "memory_ceiling", "gauge": "process.rss", "max_bytes": 1048576}
+        ]}"#,
+    )
+    .unwrap();
+
+    let config = ObjectiveConfig::load(&path).unwrap();
+    assert_eq!(config.objectives.len(), 2);
+    assert_eq!(config.objectives[0].weight, 1.0);
+    assert_eq!(config.objectives[1].target(), 1048576.0);
+
+    std::fs::write(
+        &path,
+        r#"{"objectives": [
+            {"name": "dup", "kind": "latency", "histogram": "a", "target_ms": 5},
+            {"name": "dup", "kind": "latency", "histogram": "b", "target_ms": 5}
+        ]}"#,
+    )
+    .unwrap();
+    assert!(ObjectiveConfig::load(&path).is_err());
+
+    std::fs::remove_file(path).ok();
+}
+
+#[tokio::test]
+async fn measures_live_status() {
+    let metrics = MetricsCollector::new();
+    for latency in [5, 8, 40, 40, 40] {
+        metrics.record_histogram("search.latency_ms", latency).await;
+    }
+    metrics.set_gauge("tests.coverage_percent", 85).await;
+    metrics.increment_counter("api.requests", 200).await;
+    metrics.increment_counter("api.errors", 4).await;
+
+    let latency = latency_objective().status(&metrics).await;
+    assert!(!latency.satisfied);
+    assert!((latency.shortfall - 3.0).abs() < 1e-9);
+
+    let coverage = coverage_objective().status(&metrics).await;
+    assert!(coverage.satisfied);
+    assert_eq!(coverage.current, Some(0.85));
+
+    let errors = Objective {
+        name: "errors".into(),
+        weight: 1.0,
+        kind: ObjectiveKind::ErrorBudget {
+            errors: "api.errors".into(),
+            requests: "api.requests".into(),
+            max_ratio: 0.01,
+        },
+        validation_metric: None,
+    }
+    .status(&metrics)
+    .await;
+    assert_eq!(errors.current, Some(0.02));
+    assert!(!errors.satisfied);
+}
+
+#[test]
+fn evaluation_scores_against_objectives() {
+    let objectives = vec![latency_objective(), coverage_objective()];
+    let mut metrics = HashMap::new();
+    metrics.insert("performance.vector_search_latency_ms".to_string(), 20.0);
+
+    let score = Evaluation::new().score_objectives(&objectives, &metrics);
+    assert_eq!(score.per_objective.get("search-latency"), Some(&0.5));
+    assert_eq!(score.unmeasured, vec!["coverage".to_string()]);
+    assert!((score.total - 0.5).abs() < 1e-6);
+
+    metrics.insert("unit_tests.coverage".to_string(), 0.9);
+    let score = Evaluation::new().score_objectives(&objectives, &metrics);
+    // (0.5 * 2 + 1.0 * 1) / 3
+    assert!((score.total - 2.0 / 3.0).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn exploration_proposes_for_objectives() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let strategy = ExplorationStrategy::new(metrics.clone());
+    assert!(strategy.sample_objective().await.is_none());
+
+    strategy
+        .set_objectives(ObjectiveConfig {
+            objectives: vec![latency_objective()],
+        })
+        .await;
+    let proposals = strategy.generate_proposals().await.unwrap();
+    assert_eq!(proposals.len(), 1);
+    assert_eq!(proposals[0].name, "Objective: search-latency");
+    assert!(proposals[0].description.contains("unmeasured"));
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::releases::{module_of, risk_score, Release, ReleaseLog};
+use amazon_rose_forest::darwin::self_improvement::{Modification, SelfImprovementEngine};
+use amazon_rose_forest::darwin::validation::{ValidationPipeline, ValidationStage};
+use amazon_rose_forest::darwin::workspace::WorkspaceApplier;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+struct FixedStage;
+
+impl ValidationStage for FixedStage {
+    fn name(&self) -> &str {
+        "unit_tests"
+    }
+
+    fn validate(&self, _modification: &Modification) -> anyhow::Result<HashMap<String, f32>> {
+        Ok(HashMap::from([("pass_rate".to_string(), 1.0)]))
+    }
+}
+
+fn project() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("releases-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(root.join("src/darwin")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+    root
+}
+
+fn modification(name: &str, changes: &[(&str, Option<&str>, &str)]) -> Modification {
+    let code_changes = changes
+        .iter()
+        .map(|(path, original, modified)| common::code_change(path, *original, modified))
+        .collect();
+    Modification {
+        description: format!("{} description", name),
+        ..common::modification(name, code_changes)
+    }
+}
+
+#[test]
+fn files_map_to_modules_and_risk_grows_with_size() {
+    assert_eq!(module_of("src/darwin/releases.rs"), "darwin");
+    assert_eq!(module_of("./src/network/trust.rs"), "network");
+    assert_eq!(module_of("app/models/user.py"), "app/models");
+    assert_eq!(module_of("src/lib.rs"), "(root)");
+
+    let small = modification("small", &[("src/lib.rs", Some("a\n"), "b\n")]);
+    let big_body: String = (0..400).map(|i| format!("line {}\n", i)).collect();
+    let big = modification("big", &[("src/lib.rs", Some("a\n"), &big_body)]);
+    assert!(risk_score(&small) < risk_score(&big));
+    assert!(risk_score(&big) <= 1.0);
+}
+
+#[tokio::test]
+async fn deployed_batches_become_persisted_releases() {
+    let root = project();
+    let log_path = root.join("releases.jsonl");
+    let metrics = Arc::new(MetricsCollector::new());
+    let mut pipeline = ValidationPipeline::new(metrics.clone());
+    pipeline.add_stage(FixedStage);
+    let engine = SelfImprovementEngine::new(
+        metrics.clone(),
+        Arc::new(pipeline),
+        Arc::new(ExplorationStrategy::new(metrics.clone())),
+    )
+    .with_workspace(WorkspaceApplier::new(root.clone()).without_build_check())
+    .with_release_log(Arc::new(ReleaseLog::open(&log_path).unwrap()));
+    let engine = Arc::new(engine);
+
+    let root_change = modification(
+        "Rename a",
+        &[("src/lib.rs", Some("pub fn a() {}\n"), "pub fn b() {}\n")],
+    );
+    let darwin_change = modification(
+        "Add helper",
+        &[("src/darwin/helper.rs", None, "pub fn h() {}\n")],
+    );
+    // Never proposed, so it can't be deployed
+    let unknown = Uuid::new_v4();
+    for m in [&root_change, &darwin_change] {
+        engine.propose_modification(m.clone()).await.unwrap();
+        assert!(engine.validate_modification(m.id).await.unwrap());
+    }
+
+    let release = engine
+        .deploy_batch(&[root_change.id, darwin_change.id, unknown])
+        .await
+        .unwrap();
+    let modules: Vec<&str> = release.modules.iter().map(|m| m.module.as_str()).collect();
+    assert_eq!(modules, vec!["(root)", "darwin"]);
+    assert_eq!(release.modules[1].entries[0].name, "Add helper");
+    assert_eq!(release.failed.len(), 1);
+    assert_eq!(release.failed[0].modification_id, unknown);
+    assert!(release.notes.contains("## darwin"));
+    assert!(release.notes.contains("**Rename a**"));
+    assert_eq!(
+        release.modules[0].entries[0].validation.passed,
+        vec!["unit_tests".to_string()]
+    );
+    assert!(release.notes.contains("pass_rate=1.000"));
+    assert_eq!(metrics.get_counter("darwin.releases").await, Some(1));
+
+    // Nothing deployable is an error, not an empty release
+    assert!(engine.deploy_batch(&[unknown]).await.is_err());
+
+    let reopened = ReleaseLog::open(&log_path).unwrap();
+    assert_eq!(reopened.release(release.id).await, Some(release.clone()));
+
+    let filter = Server::new(ServerConfig::default(), metrics, None, None)
+        .with_self_improvement_engine(engine)
+        .filter();
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/api/darwin/releases")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let releases: Vec<Release> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(releases, vec![release]);
+}
@@ -0,0 +1,116 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::ritual::{
+    Recurrence, RitualManager, RitualOutcomeStatus, RitualStage, RitualStageStatus, RitualTemplate,
+};
+use chrono::{Duration, TimeZone, Utc, Weekday};
+use std::sync::Arc;
+
+fn template() -> RitualTemplate {
+    RitualTemplate {
+        name: "nightly".into(),
+        description: "d".into(),
+        stages: vec![RitualStage {
+            name: "explore".into(),
+            description: "e".into(),
+            status: RitualStageStatus::Completed,
+            depends_on: Vec::new(),
+            artifacts: vec!["stale".into()],
+            started_at: None,
+            completed_at: None,
+        }],
+    }
+}
+
+#[test]
+fn computes_next_occurrence() {
+    let at = Utc.with_ymd_and_hms(2024, 5, 1, 3, 0, 0).unwrap(); // Wednesday
+    let daily: Recurrence = "daily 02:00".parse().unwrap();
+    assert_eq!(
+        daily.next_after(at),
+        Utc.with_ymd_and_hms(2024, 5, 2, 2, 0, 0).unwrap()
+    );
+
+    let weekly: Recurrence = "weekly sun 03:30".parse().unwrap();
+    assert_eq!(
+        weekly,
+        Recurrence::Weekly {
+            weekday: Weekday::Sun,
+            hour: 3,
+            minute: 30
+        }
+    );
+    assert_eq!(
+        weekly.next_after(at),
+        Utc.with_ymd_and_hms(2024, 5, 5, 3, 30, 0).unwrap()
+    );
+
+    let every: Recurrence = "every 6h".parse().unwrap();
+    assert_eq!(every.next_after(at), at + Duration::hours(6));
+
+    assert!("daily 25:00".parse::<Recurrence>().is_err());
+    assert!("every 0m".parse::<Recurrence>().is_err());
+    assert!("hourly".parse::<Recurrence>().is_err());
+}
+
+#[tokio::test]
+async fn skips_while_previous_run_is_active() {
+    let manager = RitualManager::new(Arc::new(MetricsCollector::new()));
+    let first_run = manager
+        .add_schedule(
+            "nightly",
+            template(),
+            Recurrence::Every { minutes: 60 },
+            true,
+        )
+        .await
+        .unwrap();
+
+    let created = manager.run_due_schedules(first_run).await.unwrap();
+    assert_eq!(created.len(), 1);
+    let ritual = manager.get_ritual(created[0]).await.unwrap();
+    assert_eq!(ritual.stages[0].status, RitualStageStatus::Pending);
+    assert!(ritual.stages[0].artifacts.is_empty());
+
+    // Not due yet
+    assert!(manager
+        .run_due_schedules(first_run)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let second_run = first_run + Duration::hours(1);
+    assert!(manager
+        .run_due_schedules(second_run)
+        .await
+        .unwrap()
+        .is_empty());
+
+    manager.start_stage(created[0], "explore").await.unwrap();
+    manager
+        .complete_stage(created[0], "explore", vec![])
+        .await
+        .unwrap();
+
+    let third = manager
+        .run_due_schedules(second_run + Duration::hours(1))
+        .await
+        .unwrap();
+    assert_eq!(third.len(), 1);
+    manager
+        .fail_stage(third[0], "explore", "validation crashed")
+        .await
+        .unwrap();
+
+    let history = manager.ritual_history("nightly").await;
+    let statuses: Vec<_> = history.iter().map(|o| o.status).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            RitualOutcomeStatus::Skipped,
+            RitualOutcomeStatus::Completed,
+            RitualOutcomeStatus::Failed
+        ]
+    );
+    assert_eq!(history[0].ritual_id, Some(created[0]));
+    assert!(manager.get_active_rituals().await.unwrap().is_empty());
+}
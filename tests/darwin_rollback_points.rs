@@ -0,0 +1,150 @@
+mod common;
+
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::rollback::{RestoreReport, RollbackPoint};
+use amazon_rose_forest::darwin::self_improvement::{
+    Modification, ModificationStatus, SelfImprovementEngine,
+};
+use amazon_rose_forest::darwin::validation::{ValidationPipeline, ValidationStage};
+use amazon_rose_forest::darwin::workspace::WorkspaceApplier;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use chrono::Utc;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+struct FixedStage;
+
+impl ValidationStage for FixedStage {
+    fn name(&self) -> &str {
+        "unit_tests"
+    }
+
+    fn validate(&self, _modification: &Modification) -> anyhow::Result<HashMap<String, f32>> {
+        Ok(HashMap::from([
+            ("pass_rate".to_string(), 1.0),
+            ("coverage".to_string(), 0.8),
+        ]))
+    }
+}
+
+fn project() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("rollback-points-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+    root
+}
+
+fn modification(path: &str, original: Option<&str>, modified: &str) -> Modification {
+    common::modification(
+        &format!("edit {}", path),
+        vec![common::code_change(path, original, modified)],
+    )
+}
+
+async fn deploy(engine: &SelfImprovementEngine, m: &Modification) {
+    engine.propose_modification(m.clone()).await.unwrap();
+    assert!(engine.validate_modification(m.id).await.unwrap());
+    engine.deploy_modification(m.id).await.unwrap();
+}
+
+#[test]
+fn plan_reverts_newest_first_and_redeploys_oldest_first() {
+    let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+    let point = RollbackPoint {
+        name: "p".into(),
+        created_at: Utc::now(),
+        deployed: vec![ids[0], ids[1]],
+        config: Default::default(),
+    };
+    let plan = point.plan(&[ids[0], ids[2], ids[3]]);
+    assert_eq!(plan.revert, vec![ids[3], ids[2]]);
+    assert_eq!(plan.redeploy, vec![ids[1]]);
+}
+
+#[tokio::test]
+async fn restoring_a_point_reverts_and_reapplies_deployments() {
+    let root = project();
+    let metrics = Arc::new(MetricsCollector::new());
+    let mut pipeline = ValidationPipeline::new(metrics.clone());
+    pipeline.add_stage(FixedStage);
+    let pipeline = Arc::new(pipeline);
+    let engine = Arc::new(
+        SelfImprovementEngine::new(
+            metrics.clone(),
+            pipeline.clone(),
+            Arc::new(ExplorationStrategy::new(metrics.clone())),
+        )
+        .with_workspace(WorkspaceApplier::new(root.clone()).without_build_check()),
+    );
+    let lib = || std::fs::read_to_string(root.join("src/lib.rs")).unwrap();
+
+    let first = modification("src/lib.rs", Some("pub fn a() {}\n"), "pub fn b() {}\n");
+    deploy(&engine, &first).await;
+    pipeline.set_threshold("unit_tests.pass_rate", 0.9);
+    engine.create_rollback_point("stable").await.unwrap();
+    assert!(engine.create_rollback_point("stable").await.is_err());
+
+    // The third modification builds on the first one's file
+    let second = modification("src/helper.rs", None, "pub fn h() {}\n");
+    let third = modification("src/lib.rs", Some("pub fn b() {}\n"), "pub fn c() {}\n");
+    deploy(&engine, &second).await;
+    deploy(&engine, &third).await;
+    pipeline.set_threshold("unit_tests.pass_rate", 0.5);
+    pipeline.set_threshold("unit_tests.coverage", 0.7);
+    let latest = engine.create_rollback_point("latest").await.unwrap();
+    assert_eq!(latest.deployed, vec![first.id, second.id, third.id]);
+
+    let filter = Server::new(ServerConfig::default(), metrics.clone(), None, None)
+        .with_self_improvement_engine(engine.clone())
+        .filter();
+    let restore = |point: &str| {
+        warp::test::request()
+            .method("POST")
+            .path(&format!("/api/darwin/rollback/{}", point))
+            .reply(&filter)
+    };
+
+    let resp = restore("stable").await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let report: RestoreReport = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(report.reverted, vec![third.id, second.id]);
+    assert!(report.redeployed.is_empty());
+    assert_eq!(lib(), "pub fn b() {}\n");
+    assert!(!root.join("src/helper.rs").exists());
+    assert_eq!(pipeline.threshold("unit_tests.pass_rate"), Some(0.9));
+    assert_eq!(pipeline.threshold("unit_tests.coverage"), None);
+    assert_eq!(
+        engine.get_modification(third.id).await.unwrap().status,
+        ModificationStatus::RolledBack
+    );
+
+    // Going forward again re-applies what was reverted, in order
+    let report: RestoreReport = serde_json::from_slice(restore("latest").await.body()).unwrap();
+    assert_eq!(report.redeployed, vec![second.id, third.id]);
+    assert_eq!(lib(), "pub fn c() {}\n");
+    assert!(root.join("src/helper.rs").exists());
+    assert_eq!(pipeline.threshold("unit_tests.coverage"), Some(0.7));
+
+    assert_eq!(restore("missing").await.status(), StatusCode::NOT_FOUND);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/darwin/rollback-points")
+        .json(&json!({ "name": "stable" }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/api/darwin/rollback-points")
+        .reply(&filter)
+        .await;
+    let points: Vec<RollbackPoint> = serde_json::from_slice(resp.body()).unwrap();
+    let names: Vec<&str> = points.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["latest", "stable"]);
+}
@@ -0,0 +1,200 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::agent::CodingAgent;
+use amazon_rose_forest::darwin::react::{
+    parse_action, AgentAction, ReActLoop, ReActStep, ReasoningModel,
+};
+use amazon_rose_forest::darwin::tools::{
+    AgentTool, FetchMetricsTool, GrepCodeTool, ReadFileTool, RunTestsTool, ToolRegistry, ToolSpec,
+};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Replays a fixed list of actions, then finishes with the last observation.
+struct ScriptedModel {
+    actions: Vec<(String, Value)>,
+}
+
+#[async_trait]
+impl ReasoningModel for ScriptedModel {
+    async fn next_action(
+        &self,
+        _task: &str,
+        _tools: &[ToolSpec],
+        steps: &[ReActStep],
+    ) -> anyhow::Result<AgentAction> {
+        match self.actions.get(steps.len()) {
+            Some((tool, input)) => Ok(AgentAction::UseTool {
+                thought: format!("call {}", tool),
+                tool: tool.clone(),
+                input: input.clone(),
+            }),
+            None => Ok(AgentAction::Finish {
+                thought: "done".into(),
+                answer: steps
+                    .last()
+                    .map(|s| s.observation.clone())
+                    .unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+/// Never finishes, to exercise the iteration limit.
+struct LoopingModel;
+
+#[async_trait]
+impl ReasoningModel for LoopingModel {
+    async fn next_action(
+        &self,
+        _task: &str,
+        _tools: &[ToolSpec],
+        _steps: &[ReActStep],
+    ) -> anyhow::Result<AgentAction> {
+        Ok(AgentAction::UseTool {
+            thought: "again".into(),
+            tool: "fetch_metrics".into(),
+            input: json!({}),
+        })
+    }
+}
+
+fn temp_repo() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("rose-forest-repo-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(
+        root.join("src/lib.rs"),
+        "pub fn answer() -> u32 {\n    42\n}\n",
+    )
+    .unwrap();
+    root
+}
+
+fn file_tools(root: &Path, metrics: Arc<MetricsCollector>) -> ToolRegistry {
+    let mut tools = ToolRegistry::new();
+    tools.register(Arc::new(ReadFileTool::new(root.to_path_buf())));
+    tools.register(Arc::new(GrepCodeTool::new(root.to_path_buf())));
+    tools.register(Arc::new(FetchMetricsTool::new(metrics)));
+    tools
+}
+
+#[tokio::test]
+async fn react_loop_grounds_answer_in_tool_output() {
+    let root = temp_repo();
+    let metrics = Arc::new(MetricsCollector::new());
+    let tools = file_tools(&root, metrics);
+
+    let model = ScriptedModel {
+        actions: vec![
+            ("grep_code".into(), json!({"pattern": "fn answer"})),
+            (
+                "read_file".into(),
+                json!({"path": "src/lib.rs", "start_line": 2, "end_line": 2}),
+            ),
+        ],
+    };
+    let trace = ReActLoop::new(tools, 5)
+        .run(&model, "find answer")
+        .await
+        .unwrap();
+
+    assert!(!trace.exhausted);
+    assert_eq!(trace.steps.len(), 2);
+    assert!(trace.steps[0].observation.contains("src/lib.rs:1:"));
+    assert_eq!(trace.answer.as_deref(), Some("    2     42"));
+
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn react_loop_is_bounded_and_reports_tool_errors() {
+    let root = temp_repo();
+    let metrics = Arc::new(MetricsCollector::new());
+    let tools = file_tools(&root, metrics);
+
+    let trace = ReActLoop::new(tools.clone(), 3)
+        .run(&LoopingModel, "spin")
+        .await
+        .unwrap();
+    assert!(trace.exhausted);
+    assert!(trace.answer.is_none());
+    assert_eq!(trace.steps.len(), 3);
+
+    let escaped = tools
+        .invoke("read_file", &json!({"path": "../etc/passwd"}))
+        .await;
+    assert!(escaped.starts_with("error:"));
+    let unknown = tools.invoke("rm_rf", &json!({})).await;
+    assert!(unknown.contains("unknown tool"));
+
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn coding_agent_uses_tools_before_generating() {
+    let root = temp_repo();
+    let metrics = Arc::new(MetricsCollector::new());
+    metrics.increment_counter("search.requests", 7).await;
+    let shard_manager = Arc::new(ShardManager::new(metrics.clone()));
+    let tools = ToolRegistry::for_repository(root.clone(), shard_manager, metrics.clone());
+    assert_eq!(tools.specs().len(), 5);
+
+    let agent = CodingAgent::new(metrics.clone());
+    let mut files = HashMap::new();
+    files.insert(
+        "src/lib.rs".to_string(),
+        "pub fn answer() -> u32 { 42 }".to_string(),
+    );
+    agent
+        .update_context(files, root.display().to_string(), HashMap::new())
+        .await
+        .unwrap();
+    agent
+        .enable_tool_use(
+            tools,
+            Arc::new(ScriptedModel {
+                actions: vec![("fetch_metrics".into(), json!({"prefix": "search."}))],
+            }),
+        )
+        .await;
+
+    let modification = agent
+        .generate_improvement("src/lib.rs", "performance")
+        .await
+        .unwrap();
+    let content = &modification.code_changes[0].modified_content;
+    assert!(content.contains("Repository observations"));
+    assert!(content.contains("search.requests 7"));
+    assert_eq!(
+        metrics.get_counter("darwin.agent.tool_calls").await,
+        Some(1)
+    );
+
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn run_tests_rejects_filters_that_look_like_options() {
+    let root = temp_repo();
+    let tool = RunTestsTool::new(root.clone());
+
+    for filter in ["--config=build.rustc-wrapper='sh'", "-Zunstable-options"] {
+        let err = tool.invoke(&json!({ "filter": filter })).await.unwrap_err();
+        assert!(err.to_string().contains("can't start with '-'"), "{}", err);
+    }
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn parse_action_tolerates_surrounding_text() {
+    let reply = "Sure:\n```json\n{\"action\":\"finish\",\"thought\":\"ok\",\"answer\":\"x\"}\n```";
+    match parse_action(reply).unwrap() {
+        AgentAction::Finish { answer, .. } => assert_eq!(answer, "x"),
+        other => panic!("unexpected action {:?}", other),
+    }
+    assert!(parse_action("no json here").is_err());
+}
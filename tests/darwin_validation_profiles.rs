@@ -0,0 +1,79 @@
+mod common;
+
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::self_improvement::Modification;
+use amazon_rose_forest::darwin::validation::{ThresholdProfile, ValidationPipeline};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn touching(paths: &[&str]) -> Modification {
+    let code_changes = paths
+        .iter()
+        .map(|path| common::code_change(path, None, ""))
+        .collect();
+    common::modification("m", code_changes)
+}
+
+fn pipeline() -> ValidationPipeline {
+    let mut pipeline = ValidationPipeline::new(Arc::new(MetricsCollector::new()));
+    pipeline.set_threshold("unit_tests.pass_rate", 0.9);
+    pipeline.add_threshold_profile(
+        ThresholdProfile::new("core-engine", &["src/core/"])
+            .with_min("unit_tests.pass_rate", 0.99)
+            .with_max("performance.latency_ms", 5.0),
+    );
+    pipeline.add_threshold_profile(
+        ThresholdProfile::new("docs-tooling", &["docs/", "*.md"])
+            .with_max("security.vulnerability_score", 0.5),
+    );
+    pipeline
+}
+
+fn metrics(pass_rate: f32, latency: f32) -> HashMap<String, f32> {
+    let mut metrics = HashMap::new();
+    metrics.insert("unit_tests.pass_rate".to_string(), pass_rate);
+    metrics.insert("performance.latency_ms".to_string(), latency);
+    metrics.insert("security.vulnerability_score".to_string(), 0.3);
+    metrics
+}
+
+#[test]
+fn profiles_are_selected_by_touched_paths() {
+    let pipeline = pipeline();
+    let names = |m: &Modification| -> Vec<String> {
+        pipeline
+            .profiles_for(m)
+            .iter()
+            .map(|p| p.name.clone())
+            .collect()
+    };
+
+    assert_eq!(
+        names(&touching(&["src/core/vector.rs"])),
+        vec!["core-engine"]
+    );
+    assert_eq!(names(&touching(&["./README.md"])), vec!["docs-tooling"]);
+    assert_eq!(
+        names(&touching(&["docs/guide.txt", "src/core/metrics.rs"])),
+        vec!["core-engine", "docs-tooling"]
+    );
+    assert!(names(&touching(&["src/server/mod.rs"])).is_empty());
+}
+
+#[test]
+fn core_changes_face_stricter_gates() {
+    let pipeline = pipeline();
+    let borderline = metrics(0.95, 8.0);
+
+    // Only the global threshold applies outside any profile
+    assert!(pipeline.is_valid_for(&touching(&["src/server/mod.rs"]), &borderline));
+    assert!(pipeline.is_valid_for(&touching(&["docs/guide.md"]), &borderline));
+
+    let core = touching(&["src/core/vector.rs"]);
+    assert!(!pipeline.is_valid_for(&core, &borderline));
+    assert!(!pipeline.is_valid_for(&core, &metrics(0.995, 8.0)));
+    assert!(pipeline.is_valid_for(&core, &metrics(0.995, 4.0)));
+
+    // The global threshold still applies under a looser profile
+    assert!(!pipeline.is_valid_for(&touching(&["docs/guide.md"]), &metrics(0.5, 1.0)));
+}
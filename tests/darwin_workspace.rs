@@ -0,0 +1,253 @@
+use amazon_rose_forest::darwin::self_improvement::CodeChange;
+use amazon_rose_forest::darwin::workspace::WorkspaceApplier;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn project() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("workspace-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+    root
+}
+
+/// A project committed to its own git repository
+fn repository() -> PathBuf {
+    let root = project();
+    for args in [
+        &["init", "--quiet"][..],
+        &["add", "."],
+        &[
+            "-c",
+            "user.name=t",
+            "-c",
+            "user.email=t@t",
+            "commit",
+            "-qm",
+            "init",
+        ],
+    ] {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(&root)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+    root
+}
+
+fn worktrees(root: &Path) -> usize {
+    let output = Command::new("git")
+        .args(["worktree", "list", "--porcelain"])
+        .current_dir(root)
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| l.starts_with("worktree "))
+        .count()
+}
+
+fn change(path: &str, original: Option<&str>, modified: &str) -> CodeChange {
+    CodeChange {
+        file_path: path.into(),
+        original_content: original.map(Into::into),
+        modified_content: modified.into(),
+        diff: String::new(),
+        evolution_hooks: Vec::new(),
+        reality_branch: None,
+    }
+}
+
+#[tokio::test]
+async fn applies_all_changes_and_reverts() {
+    let root = project();
+    let applier = WorkspaceApplier::new(root.clone()).with_build_command("true", &[]);
+
+    let applied = applier
+        .apply(&[
+            change("src/lib.rs", Some("pub fn a() {}\n"), "pub fn b() {}\n"),
+            change("src/new/mod.rs", None, "pub fn c() {}\n"),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub fn b() {}\n"
+    );
+    assert!(root.join("src/new/mod.rs").exists());
+    assert!(!root.join("src/.lib.rs.darwin-staged").exists());
+
+    applied.revert().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub fn a() {}\n"
+    );
+    assert!(!root.join("src/new/mod.rs").exists());
+
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn failed_build_leaves_project_untouched() {
+    let root = project();
+    let applier = WorkspaceApplier::new(root.clone()).with_build_command("false", &[]);
+
+    let result = applier
+        .apply(&[
+            change("src/lib.rs", Some("pub fn a() {}\n"), "broken"),
+            change("src/other.rs", None, "pub fn d() {}\n"),
+        ])
+        .await;
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Build check failed"));
+    assert_eq!(
+        std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub fn a() {}\n"
+    );
+    assert!(!root.join("src/other.rs").exists());
+
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn keeps_edits_made_during_the_build_check() {
+    let root = project();
+    // Someone saves the file while the check runs in the scratch copy
+    let edit = format!(
+        "printf 'pub fn edited() {{}}\\n' > {}",
+        root.join("src/lib.rs").display()
+    );
+    let applier = WorkspaceApplier::new(root.clone()).with_build_command("sh", &["-c", &edit]);
+
+    let result = applier
+        .apply(&[
+            change("src/other.rs", None, "pub fn d() {}\n"),
+            change("src/lib.rs", Some("pub fn a() {}\n"), "pub fn b() {}\n"),
+        ])
+        .await;
+    assert!(result.unwrap_err().to_string().contains("has changed"));
+    assert_eq!(
+        std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub fn edited() {}\n"
+    );
+    // The file already swapped in is rolled back, and nothing is left staged
+    assert!(!root.join("src/other.rs").exists());
+    assert!(!root.join("src/.lib.rs.darwin-staged").exists());
+
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn rejects_stale_and_escaping_changes() {
+    let root = project();
+    let applier = WorkspaceApplier::new(root.clone()).without_build_check();
+
+    let stale = applier
+        .apply(&[change("src/lib.rs", Some("pub fn old() {}\n"), "x")])
+        .await;
+    assert!(stale.unwrap_err().to_string().contains("has changed"));
+
+    // Creating a file that already exists would silently replace it
+    let existing = applier.apply(&[change("src/lib.rs", None, "x")]).await;
+    assert!(existing.unwrap_err().to_string().contains("already exists"));
+
+    assert!(applier
+        .apply(&[change("../outside.rs", None, "x")])
+        .await
+        .is_err());
+    assert_eq!(
+        std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub fn a() {}\n"
+    );
+
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn sandbox_builds_and_tests_in_a_git_worktree() {
+    let root = repository();
+    // Uncommitted edits in the project are part of what gets tested
+    std::fs::write(root.join("src/extra.rs"), "pub fn e() {}\n").unwrap();
+    // A worktree's .git is a file pointing back at the repository
+    let applier = WorkspaceApplier::new(root.clone())
+        .with_git_worktree()
+        .with_build_command("test", &["-f", ".git"])
+        .with_test_command("grep", &["-q", "pub fn e", "src/extra.rs"]);
+
+    applier
+        .apply(&[change(
+            "src/lib.rs",
+            Some("pub fn a() {}\n"),
+            "pub fn b() {}\n",
+        )])
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub fn b() {}\n"
+    );
+    assert_eq!(worktrees(&root), 1);
+
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn failing_sandbox_tests_keep_changes_out_of_the_tree() {
+    let root = repository();
+    let applier = WorkspaceApplier::new(root.clone())
+        .with_git_worktree()
+        .with_build_command("true", &[])
+        .with_test_command("false", &[]);
+
+    let result = applier
+        .apply(&[change(
+            "src/lib.rs",
+            Some("pub fn a() {}\n"),
+            "pub fn b() {}\n",
+        )])
+        .await;
+    assert!(result.unwrap_err().to_string().contains("Test run failed"));
+    assert_eq!(
+        std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub fn a() {}\n"
+    );
+    assert_eq!(worktrees(&root), 1);
+
+    // Without a repository there is nothing to check out
+    let plain = project();
+    let result = WorkspaceApplier::new(plain.clone())
+        .with_git_worktree()
+        .apply(&[change("src/lib.rs", Some("pub fn a() {}\n"), "x")])
+        .await;
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("not in a git repository"));
+
+    std::fs::remove_dir_all(root).ok();
+    std::fs::remove_dir_all(plain).ok();
+}
+
+#[tokio::test]
+async fn checks_build_outside_the_project_target_dir() {
+    let root = repository();
+    let applier = WorkspaceApplier::new(root.clone())
+        .with_git_worktree()
+        .with_build_command("sh", &["-c", "mkdir -p \"$CARGO_TARGET_DIR/debug\""]);
+
+    applier
+        .apply(&[change(
+            "src/lib.rs",
+            Some("pub fn a() {}\n"),
+            "pub fn b() {}\n",
+        )])
+        .await
+        .unwrap();
+    assert!(root.join("target/darwin-workspace/debug").is_dir());
+    assert!(!root.join("target/debug").exists());
+
+    std::fs::remove_dir_all(root).ok();
+}
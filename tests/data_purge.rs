@@ -0,0 +1,473 @@
+use amazon_rose_forest::core::audit::AuditLog;
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::query::{PredicateOp, QueryExpr};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::backup::{BackupKind, BackupStore};
+use amazon_rose_forest::sharding::changefeed::ChangeOp;
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::purge::{
+    PurgeCertificate, PurgeRequest, PurgeService, PURGE_COMPLETE_ACTION, PURGE_PENDING_ACTION,
+};
+use amazon_rose_forest::sharding::storage::{FileStorage, StorageBackend};
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::tenancy::TenantKeyring;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+fn audit_path() -> PathBuf {
+    std::env::temp_dir().join(format!("rose-forest-audit-{}.jsonl", Uuid::new_v4()))
+}
+
+fn user(id: &str) -> Option<HashMap<String, String>> {
+    Some(HashMap::from([("user_id".to_string(), id.to_string())]))
+}
+
+#[tokio::test]
+async fn purge_removes_vectors_and_change_history() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let mut shards = Vec::new();
+    for name in ["eu", "us"] {
+        let shard_id = manager.create_shard(name).await.unwrap();
+        manager
+            .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+            .await
+            .unwrap();
+        for id in ["alice", "bob"] {
+            manager
+                .add_vector(shard_id, Vector::random(3), user(id))
+                .await
+                .unwrap();
+        }
+        shards.push(shard_id);
+    }
+
+    let audit_path = audit_path();
+    let audit = Arc::new(AuditLog::open(&audit_path).unwrap());
+    let purge = Arc::new(PurgeService::new(manager.clone(), audit.clone(), [7u8; 32]));
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager.clone()),
+    )
+    .with_purge_service(purge.clone());
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/admin/purge")
+        .json(&serde_json::json!({
+            "filter": QueryExpr::field("user_id", PredicateOp::Eq, "alice".into()),
+            "reason": "erasure request 42",
+        }))
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let certificate: PurgeCertificate = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(certificate.record.vectors_purged, 2);
+    certificate.verify().unwrap();
+
+    // Tampering breaks the signature
+    let mut forged = certificate.clone();
+    forged.record.vectors_purged = 1;
+    assert!(forged.verify().is_err());
+
+    for &shard_id in &shards {
+        let index = manager.get_vector_index(shard_id).await.unwrap();
+        let remaining = index.entries().await;
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].metadata.as_ref().unwrap()["user_id"], "bob");
+        // Compacted: no tombstones left holding the purged rows
+        assert_eq!(index.segment_stats().await.tombstones, 0);
+
+        // No insert carrying alice's data is left in the feed
+        let feed = manager.change_feed(shard_id).await.unwrap();
+        let batch = feed.read(0, 100).await.unwrap();
+        assert!(batch.events.iter().all(|e| match &e.op {
+            ChangeOp::Insert { metadata, .. } => metadata.as_ref().unwrap()["user_id"] != "alice",
+            ChangeOp::Delete { .. } => true,
+        }));
+    }
+
+    // The digest is keyed, so it can't be recomputed from a guessed subject
+    let filter = QueryExpr::field("user_id", PredicateOp::Eq, "alice".into());
+    assert_eq!(
+        certificate.record.filter_digest,
+        purge.filter_digest(&filter).unwrap()
+    );
+    let unkeyed = Sha256::digest(serde_json::to_vec(&filter).unwrap());
+    let unkeyed: String = unkeyed.iter().map(|b| format!("{:02x}", b)).collect();
+    assert_ne!(certificate.record.filter_digest, unkeyed);
+    let other_node = PurgeService::new(manager.clone(), audit.clone(), [8u8; 32]);
+    assert_ne!(
+        certificate.record.filter_digest,
+        other_node.filter_digest(&filter).unwrap()
+    );
+
+    // A pending entry went in before the erase, then the certificate
+    let entries = audit.by_category("purge", 10).await;
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].action, PURGE_COMPLETE_ACTION);
+    assert_eq!(entries[0].subject, certificate.record.id.to_string());
+    assert_eq!(entries[1].action, PURGE_PENDING_ACTION);
+    assert_eq!(entries[1].subject, certificate.record.id.to_string());
+    assert_eq!(
+        entries[1].details["filter_digest"],
+        certificate.record.filter_digest
+    );
+
+    // The certificate outlives the process
+    let reopened = AuditLog::open(&audit_path).unwrap();
+    let entries = reopened.by_category("purge", 10).await;
+    assert_eq!(entries.len(), 2);
+    let recorded: PurgeCertificate = serde_json::from_value(entries[0].details.clone()).unwrap();
+    recorded.verify().unwrap();
+    std::fs::remove_file(&audit_path).unwrap();
+
+    // Nothing is erased without somewhere durable to record the certificate
+    let unrecorded = PurgeService::new(manager.clone(), Arc::new(AuditLog::new()), [7u8; 32]);
+    let err = unrecorded
+        .purge(&PurgeRequest {
+            filter: QueryExpr::field("user_id", PredicateOp::Eq, "bob".into()),
+            reason: None,
+        })
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("persisted"));
+    assert_eq!(
+        manager
+            .get_vector_index(shards[0])
+            .await
+            .unwrap()
+            .entries()
+            .await
+            .len(),
+        1
+    );
+
+    // Similarity clauses aren't accepted as purge criteria
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/admin/purge")
+        .json(&serde_json::json!({
+            "filter": {"similar": {"vector": [0.0, 0.0, 0.0], "max_distance": 10.0}},
+        }))
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn purge_skips_and_reports_undecryptable_vectors() {
+    let keyring = Arc::new(TenantKeyring::new());
+    keyring
+        .add_key("acme", 1, &TenantKeyring::random_key())
+        .await
+        .unwrap();
+    keyring.set_encrypted_fields("acme", &["user_id"]).await;
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    manager.enable_tenant_encryption(keyring).await;
+    let shard_id = manager.create_shard("acme").await.unwrap();
+    manager.assign_tenant(shard_id, "acme").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for id in ["alice", "bob"] {
+        manager
+            .add_vector(shard_id, Vector::random(3), user(id))
+            .await
+            .unwrap();
+    }
+
+    // A replicated record sealed with a key this node doesn't have
+    let corrupt = Uuid::new_v4();
+    manager
+        .apply_replicated_change(
+            shard_id,
+            ChangeOp::Insert {
+                vector_id: corrupt,
+                values: vec![0.0, 0.0, 0.0],
+                metadata: user("enc:v9:AAAA"),
+            },
+            "peer",
+        )
+        .await
+        .unwrap();
+
+    let audit_path = audit_path();
+    let purge = PurgeService::new(
+        manager.clone(),
+        Arc::new(AuditLog::open(&audit_path).unwrap()),
+        [7u8; 32],
+    );
+    let certificate = purge
+        .purge(&PurgeRequest {
+            filter: QueryExpr::field("user_id", PredicateOp::Eq, "alice".into()),
+            reason: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(certificate.record.vectors_purged, 1);
+    assert_eq!(certificate.record.vectors_undecryptable, 1);
+    assert_eq!(certificate.record.shards[0].undecryptable, vec![corrupt]);
+    certificate.verify().unwrap();
+
+    // The skipped record is left for follow-up
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    assert_eq!(index.entries().await.len(), 2);
+    assert!(index.get(corrupt).await.is_some());
+    std::fs::remove_file(&audit_path).unwrap();
+}
+
+#[tokio::test]
+async fn purge_flushes_storage_and_rewrites_backups_holding_the_data() {
+    let root = std::env::temp_dir().join(format!("rose-forest-purge-{}", Uuid::new_v4()));
+    let storage: Arc<dyn StorageBackend> = Arc::new(FileStorage::open(root.join("data")).unwrap());
+    let manager = Arc::new(
+        ShardManager::new(Arc::new(MetricsCollector::new())).with_storage(storage.clone()),
+    );
+    let shard_id = manager.create_shard("eu").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    manager
+        .add_vector(shard_id, Vector::random(3), user("bob"))
+        .await
+        .unwrap();
+    let backups = BackupStore::open(root.join("backups")).unwrap();
+    let before = manager.backup(&backups, BackupKind::Full).await.unwrap();
+    manager
+        .add_vector(shard_id, Vector::random(3), user("alice"))
+        .await
+        .unwrap();
+    let holding = manager
+        .backup(&backups, BackupKind::Incremental)
+        .await
+        .unwrap();
+
+    let audit = Arc::new(AuditLog::open(root.join("audit.jsonl")).unwrap());
+    let purge = PurgeService::new(manager.clone(), audit, [7u8; 32])
+        .with_backups(BackupStore::open(root.join("backups")).unwrap());
+    let certificate = purge
+        .purge(&PurgeRequest {
+            filter: QueryExpr::field("user_id", PredicateOp::Eq, "alice".into()),
+            reason: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(certificate.record.vectors_purged, 1);
+    // Only the backup taken after alice's vector was added held it
+    assert_eq!(certificate.record.backups_rewritten, vec![holding.id]);
+    assert_ne!(holding.id, before.id);
+    certificate.verify().unwrap();
+
+    // The rewritten chain still verifies and restores, without alice
+    let alice: HashSet<Uuid> = certificate.record.shards[0]
+        .vector_ids
+        .iter()
+        .copied()
+        .collect();
+    assert!(backups.holding(&alice).unwrap().is_empty());
+    let report = backups.verify(holding.id);
+    assert!(report.is_ok(), "{:?}", report.problems);
+    assert_eq!(report.vectors, 1);
+
+    // Already gone from storage, without waiting for a periodic flush
+    let restarted = ShardManager::new(Arc::new(MetricsCollector::new())).with_storage(storage);
+    restarted.restore(false).await.unwrap();
+    let entries = restarted
+        .get_vector_index(shard_id)
+        .await
+        .unwrap()
+        .entries()
+        .await;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].metadata.as_ref().unwrap()["user_id"], "bob");
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn purge_finds_matches_that_only_backups_still_hold() {
+    let root = std::env::temp_dir().join(format!("rose-forest-purge-{}", Uuid::new_v4()));
+    let storage: Arc<dyn StorageBackend> = Arc::new(FileStorage::open(root.join("data")).unwrap());
+    let keyring = Arc::new(TenantKeyring::new());
+    keyring
+        .add_key("acme", 1, &TenantKeyring::random_key())
+        .await
+        .unwrap();
+    keyring.set_encrypted_fields("acme", &["user_id"]).await;
+    let manager = Arc::new(
+        ShardManager::new(Arc::new(MetricsCollector::new())).with_storage(storage.clone()),
+    );
+    manager.enable_tenant_encryption(keyring).await;
+    let shard_id = manager.create_shard("acme").await.unwrap();
+    manager.assign_tenant(shard_id, "acme").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let alice = manager
+        .add_vector(shard_id, Vector::random(3), user("alice"))
+        .await
+        .unwrap();
+    manager
+        .add_vector(shard_id, Vector::random(3), user("bob"))
+        .await
+        .unwrap();
+    let backups = BackupStore::open(root.join("backups")).unwrap();
+    let holding = manager.backup(&backups, BackupKind::Full).await.unwrap();
+    // Backed up sealed, so only decrypting it shows whose it is
+    let sealed = backups.vectors(holding.id, shard_id).unwrap();
+    let sealed = sealed.iter().find(|entry| entry.id == alice).unwrap();
+    assert_ne!(sealed.metadata.as_ref().unwrap()["user_id"], "alice");
+
+    // Deleted from the shard before the erasure request came in
+    manager.remove_vector(shard_id, alice).await.unwrap();
+    manager.flush().await.unwrap();
+
+    let audit = Arc::new(AuditLog::open(root.join("audit.jsonl")).unwrap());
+    let purge = PurgeService::new(manager.clone(), audit, [7u8; 32])
+        .with_backups(BackupStore::open(root.join("backups")).unwrap());
+    let certificate = purge
+        .purge(&PurgeRequest {
+            filter: QueryExpr::field("user_id", PredicateOp::Eq, "alice".into()),
+            reason: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(certificate.record.vectors_purged, 0);
+    assert_eq!(certificate.record.backup_vectors, vec![alice]);
+    assert_eq!(certificate.record.backups_rewritten, vec![holding.id]);
+    assert_eq!(certificate.record.vectors_undecryptable, 0);
+    certificate.verify().unwrap();
+
+    assert!(backups.holding(&HashSet::from([alice])).unwrap().is_empty());
+    let report = backups.verify(holding.id);
+    assert!(report.is_ok(), "{:?}", report.problems);
+    assert_eq!(report.vectors, 1);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+/// Purge `filter` from a shard holding alice and bob, expecting a refusal
+/// that leaves both in place
+async fn assert_purge_refused(filter: serde_json::Value, reason: &str) {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("default").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for id in ["alice", "bob"] {
+        manager
+            .add_vector(shard_id, Vector::random(3), user(id))
+            .await
+            .unwrap();
+    }
+
+    let audit_path = audit_path();
+    let audit = Arc::new(AuditLog::open(&audit_path).unwrap());
+    let purge = PurgeService::new(manager.clone(), audit.clone(), [7u8; 32]);
+    let err = purge
+        .purge(&PurgeRequest {
+            filter: serde_json::from_value(filter).unwrap(),
+            reason: None,
+        })
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains(reason), "{}", err);
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    assert_eq!(index.entries().await.len(), 2);
+    assert!(audit.by_category("purge", 10).await.is_empty());
+    std::fs::remove_file(&audit_path).ok();
+}
+
+#[tokio::test]
+async fn purge_refuses_negated_filters() {
+    assert_purge_refused(
+        serde_json::json!({"not": {"field": {"key": "user_id", "op": "eq", "value": "alice"}}}),
+        "`not`",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn purge_refuses_not_equal_predicates() {
+    assert_purge_refused(
+        serde_json::json!({"field": {"key": "user_id", "op": "ne", "value": "alice"}}),
+        "`ne`",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn purge_refuses_exists_predicates() {
+    assert_purge_refused(
+        serde_json::json!({"field": {"key": "user_id", "op": "exists"}}),
+        "`exists`",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn purge_refuses_range_only_filters() {
+    assert_purge_refused(
+        serde_json::json!({"field": {"key": "user_id", "op": "gt", "value": "a"}}),
+        "every branch",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn purge_refuses_disjunctions_with_an_unnamed_branch() {
+    assert_purge_refused(
+        serde_json::json!({"or": [
+            {"field": {"key": "user_id", "op": "eq", "value": "alice"}},
+            {"field": {"key": "user_id", "op": "gte", "value": "a"}},
+        ]}),
+        "every branch",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn purge_erases_nothing_when_the_audit_log_cannot_be_written() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("default").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    manager
+        .add_vector(shard_id, Vector::random(3), user("alice"))
+        .await
+        .unwrap();
+
+    // The log's directory disappears after it was opened
+    let dir = std::env::temp_dir().join(format!("rose-forest-purge-{}", Uuid::new_v4()));
+    let audit = Arc::new(AuditLog::open(dir.join("audit.jsonl")).unwrap());
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let purge = PurgeService::new(manager.clone(), audit, [7u8; 32]);
+    let err = purge
+        .purge(&PurgeRequest {
+            filter: QueryExpr::field("user_id", PredicateOp::Eq, "alice".into()),
+            reason: None,
+        })
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("persist"), "{}", err);
+
+    // Still there to be purged once the log is writable again
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    assert_eq!(index.entries().await.len(), 1);
+    let feed = manager.change_feed(shard_id).await.unwrap();
+    assert_eq!(feed.read(0, 100).await.unwrap().events.len(), 1);
+}
@@ -0,0 +1,197 @@
+use amazon_rose_forest::core::audit::AuditLog;
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::retention::{
+    RetentionEnforcer, RetentionPolicy, RetentionReport, RetentionStatus,
+};
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::Vector;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+async fn collection(manager: &ShardManager, vectors: usize) -> (Uuid, Vec<Uuid>) {
+    let shard_id = manager.create_shard("events").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let mut ids = Vec::new();
+    for i in 0..vectors {
+        let x = i as f32 / vectors as f32;
+        ids.push(
+            manager
+                .add_vector(shard_id, Vector::new(vec![x, 1.0 - x]), None)
+                .await
+                .unwrap(),
+        );
+        // Distinct creation times, so "oldest" is well defined
+        tokio::time::sleep(Duration::from_millis(2)).await;
+    }
+    (shard_id, ids)
+}
+
+#[tokio::test]
+async fn oldest_vectors_are_deleted_until_within_limits() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let (shard_id, ids) = collection(&manager, 10).await;
+    let audit_log = Arc::new(AuditLog::new());
+    let enforcer = RetentionEnforcer::new(
+        manager.clone(),
+        metrics.clone(),
+        audit_log.clone(),
+        Duration::from_secs(60),
+    );
+    enforcer
+        .set_policy(
+            shard_id,
+            RetentionPolicy {
+                max_count: Some(6),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let reports = enforcer.run_once().await;
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].deleted, 4);
+    assert_eq!(reports[0].remaining, 6);
+    // Two values plus a 16-byte ID each
+    assert_eq!(reports[0].reclaimed_bytes, 4 * 24);
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    for id in &ids[..4] {
+        assert!(index.get(*id).await.is_none());
+    }
+    assert!(index.get(ids[4]).await.is_some());
+    assert_eq!(
+        metrics.get_counter("retention.bytes_reclaimed").await,
+        Some(96)
+    );
+    assert_eq!(audit_log.by_category("retention", 10).await.len(), 1);
+
+    // Byte limits count what's left, and paused enforcement deletes nothing
+    enforcer
+        .set_policy(
+            shard_id,
+            RetentionPolicy {
+                max_bytes: Some(3 * 24),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    enforcer.pause();
+    assert!(enforcer.run_once().await.is_empty());
+    assert_eq!(index.count().await, 6);
+    enforcer.resume();
+    assert_eq!(enforcer.run_once().await[0].remaining, 3);
+
+    // Unknown collections are rejected
+    assert!(enforcer
+        .set_policy(Uuid::new_v4(), RetentionPolicy::default())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn expired_vectors_are_removed_across_split_shards() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let (shard_id, _) = collection(&manager, 8).await;
+    let split = manager.split_shard(shard_id).await.unwrap();
+    assert!(split.moved > 0);
+
+    let enforcer = RetentionEnforcer::new(
+        manager.clone(),
+        metrics,
+        Arc::new(AuditLog::new()),
+        Duration::from_secs(60),
+    );
+    enforcer
+        .set_policy(
+            shard_id,
+            RetentionPolicy {
+                max_age_secs: Some(0),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let reports = enforcer.run_once().await;
+    assert_eq!(reports[0].deleted, 8);
+    assert_eq!(
+        manager
+            .get_vector_index(shard_id)
+            .await
+            .unwrap()
+            .count()
+            .await,
+        0
+    );
+    assert_eq!(
+        manager
+            .get_vector_index(split.new_shard_id)
+            .await
+            .unwrap()
+            .count()
+            .await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn admin_api_sets_policies_and_pauses_enforcement() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let (shard_id, _) = collection(&manager, 5).await;
+    let enforcer = Arc::new(RetentionEnforcer::new(
+        manager.clone(),
+        metrics.clone(),
+        Arc::new(AuditLog::new()),
+        Duration::from_secs(60),
+    ));
+    let filter = Server::new(ServerConfig::default(), metrics, None, Some(manager))
+        .with_retention_enforcer(enforcer.clone())
+        .filter();
+
+    let resp = warp::test::request()
+        .method("PUT")
+        .path(&format!("/api/admin/retention/{}", shard_id))
+        .json(&json!({ "max_count": 2 }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(enforcer.policy(shard_id).await.unwrap().max_count, Some(2));
+
+    let post = |action: &str| {
+        warp::test::request()
+            .method("POST")
+            .path(&format!("/api/admin/retention/{}", action))
+            .reply(&filter)
+    };
+    let status: RetentionStatus = serde_json::from_slice(post("pause").await.body()).unwrap();
+    assert!(status.paused);
+    let reports: Vec<RetentionReport> = serde_json::from_slice(post("run").await.body()).unwrap();
+    assert!(reports.is_empty());
+
+    post("resume").await;
+    let reports: Vec<RetentionReport> = serde_json::from_slice(post("run").await.body()).unwrap();
+    assert_eq!(reports[0].deleted, 3);
+    assert_eq!(post("rewind").await.status(), StatusCode::NOT_FOUND);
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/api/admin/retention")
+        .reply(&filter)
+        .await;
+    let status: RetentionStatus = serde_json::from_slice(resp.body()).unwrap();
+    assert!(!status.paused);
+    assert_eq!(status.policies.len(), 1);
+}
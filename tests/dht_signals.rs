@@ -0,0 +1,193 @@
+use amazon_rose_forest::{
+    core::metrics::MetricsCollector,
+    ingest::dht::{
+        DhtApplyOutcome, DhtSignal, DhtSignalHandler, DhtSyncConfig, AUTHOR_KEY, CENTROID_COUNT_KEY,
+    },
+    sharding::{manager::ShardManager, vector_index::DistanceMetric},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, watch};
+use uuid::Uuid;
+
+async fn setup() -> (Arc<ShardManager>, Arc<MetricsCollector>, DhtSyncConfig) {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let vector_shard = manager.create_shard("dht-vectors").await.unwrap();
+    let centroid_shard = manager.create_shard("dht-centroids").await.unwrap();
+    for shard_id in [vector_shard, centroid_shard] {
+        manager
+            .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+            .await
+            .unwrap();
+    }
+    let config = DhtSyncConfig {
+        vector_shard,
+        centroid_shard: Some(centroid_shard),
+    };
+    (manager, metrics, config)
+}
+
+fn centroid(id: Uuid, values: Vec<f32>, count: usize, updated_at: u64) -> DhtSignal {
+    DhtSignal::CentroidCreated {
+        id: id.to_string(),
+        values,
+        count,
+        author: "agent-b".to_string(),
+        updated_at,
+    }
+}
+
+#[tokio::test]
+async fn vector_signals_are_applied_once() {
+    let (manager, metrics, config) = setup().await;
+    let handler = DhtSignalHandler::new(config.clone(), manager.clone(), metrics.clone());
+
+    let id = Uuid::new_v4();
+    let signal = DhtSignal::VectorCreated {
+        id: id.to_string(),
+        values: vec![1.0, 2.0],
+        metadata: Some(HashMap::from([("lang".to_string(), "en".to_string())])),
+        author: "agent-a".to_string(),
+        created_at: 1,
+    };
+    assert_eq!(
+        handler.handle(signal.clone()).await.unwrap(),
+        DhtApplyOutcome::Applied
+    );
+    // Signals can be delivered more than once
+    assert_eq!(
+        handler.handle(signal).await.unwrap(),
+        DhtApplyOutcome::Duplicate
+    );
+
+    let index = manager.get_vector_index(config.vector_shard).await.unwrap();
+    let entry = index.get(id).await.unwrap();
+    assert_eq!(entry.vector.values, vec![1.0, 2.0]);
+    let metadata = entry.metadata.unwrap();
+    assert_eq!(metadata[AUTHOR_KEY], "agent-a");
+    assert_eq!(metadata["lang"], "en");
+
+    assert!(handler
+        .handle(DhtSignal::VectorCreated {
+            id: "not-a-uuid".to_string(),
+            values: vec![0.0, 0.0],
+            metadata: None,
+            author: "agent-a".to_string(),
+            created_at: 2,
+        })
+        .await
+        .is_err());
+
+    assert_eq!(metrics.get_counter("dht.signals.applied").await, Some(1));
+    assert_eq!(metrics.get_counter("dht.signals.skipped").await, Some(1));
+    assert_eq!(metrics.get_counter("dht.signals.failed").await, Some(1));
+}
+
+#[tokio::test]
+async fn only_newer_centroids_replace_older_ones() {
+    let (manager, metrics, config) = setup().await;
+    let centroid_shard = config.centroid_shard.unwrap();
+    let handler = DhtSignalHandler::new(config, manager.clone(), metrics);
+
+    let id = Uuid::new_v4();
+    assert_eq!(
+        handler
+            .handle(centroid(id, vec![0.0, 0.0], 4, 100))
+            .await
+            .unwrap(),
+        DhtApplyOutcome::Applied
+    );
+    assert_eq!(
+        handler
+            .handle(centroid(id, vec![1.0, 1.0], 8, 200))
+            .await
+            .unwrap(),
+        DhtApplyOutcome::Applied
+    );
+    // A late delivery of the first version is ignored
+    assert_eq!(
+        handler
+            .handle(centroid(id, vec![0.0, 0.0], 4, 100))
+            .await
+            .unwrap(),
+        DhtApplyOutcome::Stale
+    );
+
+    let index = manager.get_vector_index(centroid_shard).await.unwrap();
+    assert_eq!(index.count().await, 1);
+    let entry = index.get(id).await.unwrap();
+    assert_eq!(entry.vector.values, vec![1.0, 1.0]);
+    assert_eq!(entry.metadata.unwrap()[CENTROID_COUNT_KEY], "8");
+}
+
+#[tokio::test]
+async fn centroids_are_ignored_without_a_shard() {
+    let (manager, metrics, mut config) = setup().await;
+    config.centroid_shard = None;
+    let handler = DhtSignalHandler::new(config, manager, metrics);
+    assert_eq!(
+        handler
+            .handle(centroid(Uuid::new_v4(), vec![0.0, 0.0], 1, 1))
+            .await
+            .unwrap(),
+        DhtApplyOutcome::Ignored
+    );
+}
+
+#[tokio::test]
+async fn json_signals_from_the_conductor_are_decoded() {
+    let (manager, metrics, config) = setup().await;
+    let handler = DhtSignalHandler::new(config.clone(), manager.clone(), metrics);
+
+    let id = Uuid::new_v4();
+    let payload = json!({
+        "type": "vector_created",
+        "id": id.to_string(),
+        "values": [3.0, 4.0],
+        "author": "agent-c",
+        "created_at": 5
+    });
+    assert_eq!(
+        handler
+            .handle_json(payload.to_string().as_bytes())
+            .await
+            .unwrap(),
+        DhtApplyOutcome::Applied
+    );
+    assert!(handler
+        .handle_json(b"{\"type\":\"unknown\"}")
+        .await
+        .is_err());
+
+    let index = manager.get_vector_index(config.vector_shard).await.unwrap();
+    assert!(index.get(id).await.is_some());
+}
+
+#[tokio::test]
+async fn worker_applies_queued_signals_until_closed() {
+    let (manager, metrics, config) = setup().await;
+    let handler = DhtSignalHandler::new(config.clone(), manager.clone(), metrics);
+    let (tx, rx) = mpsc::channel(8);
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let worker = tokio::spawn(handler.run(rx, shutdown_rx));
+
+    let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+    for (i, id) in ids.iter().enumerate() {
+        tx.send(DhtSignal::VectorCreated {
+            id: id.to_string(),
+            values: vec![i as f32, 0.0],
+            metadata: None,
+            author: "agent-d".to_string(),
+            created_at: i as u64,
+        })
+        .await
+        .unwrap();
+    }
+    drop(tx);
+    worker.await.unwrap();
+
+    let index = manager.get_vector_index(config.vector_shard).await.unwrap();
+    assert_eq!(index.count().await, 3);
+}
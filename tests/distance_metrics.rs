@@ -0,0 +1,126 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::server::api::{distance_metric_to_string, parse_distance_metric};
+use amazon_rose_forest::sharding::hnsw::HnswParams;
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::{DistanceMetric, IndexType, VectorIndex};
+use amazon_rose_forest::Vector;
+use std::sync::Arc;
+use uuid::Uuid;
+
+async fn ranked(index: &VectorIndex, query: &[f32], vectors: &[Vec<f32>]) -> Vec<usize> {
+    let mut ids = Vec::new();
+    for values in vectors {
+        ids.push(index.add(Vector::new(values.clone()), None).await.unwrap());
+    }
+    let results = index
+        .search(&Vector::new(query.to_vec()), vectors.len())
+        .await
+        .unwrap();
+    results
+        .iter()
+        .map(|r| ids.iter().position(|id| *id == r.id).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn inner_product_ranks_the_largest_dot_product_first() {
+    let index = VectorIndex::new("ip", 2, DistanceMetric::InnerProduct, None).unwrap();
+    // Cosine would tie the first two; the dot product favours the longer
+    let vectors = vec![
+        vec![1.0, 0.0],
+        vec![3.0, 0.0],
+        vec![0.0, 1.0],
+        vec![-1.0, 0.0],
+    ];
+    assert_eq!(
+        ranked(&index, &[1.0, 0.0], &vectors).await,
+        vec![1, 0, 2, 3]
+    );
+
+    let results = index.search(&Vector::new(vec![1.0, 0.0]), 2).await.unwrap();
+    assert_eq!(results[0].score, 3.0);
+    assert_eq!(results[1].score, 1.0);
+    assert!(!DistanceMetric::InnerProduct.is_lower_better());
+    assert_eq!(
+        DistanceMetric::InnerProduct
+            .distance(&Vector::new(vec![1.0, 2.0]), &Vector::new(vec![3.0, 4.0])),
+        -11.0
+    );
+}
+
+#[tokio::test]
+async fn hamming_ranks_binary_vectors_by_differing_bits() {
+    let index = VectorIndex::new("bits", 6, DistanceMetric::Hamming, None).unwrap();
+    let vectors = vec![
+        vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0],
+        vec![1.0, 0.0, 1.0, 1.0, 0.0, 0.0],
+        vec![1.0, 0.0, 1.0, 0.0, 0.0, 0.0],
+        vec![0.0, 1.0, 0.0, 1.0, 1.0, 1.0],
+    ];
+    let query = [1.0, 0.0, 1.0, 0.0, 0.0, 0.0];
+    assert_eq!(ranked(&index, &query, &vectors).await, vec![2, 1, 0, 3]);
+
+    let results = index.search(&Vector::new(query.to_vec()), 4).await.unwrap();
+    let scores: Vec<f32> = results.iter().map(|r| r.score).collect();
+    assert_eq!(scores, vec![0.0, 1.0, 4.0, 6.0]);
+}
+
+#[tokio::test]
+async fn inner_product_graph_search_and_split_families_rank_consistently() {
+    let manager = ShardManager::new(Arc::new(MetricsCollector::new()));
+    let shard_id = manager.create_shard("ip").await.unwrap();
+    manager
+        .create_vector_index_with_type(
+            shard_id,
+            "main",
+            2,
+            DistanceMetric::InnerProduct,
+            IndexType::Hnsw(HnswParams::default()),
+        )
+        .await
+        .unwrap();
+    let mut ids: Vec<(Uuid, f32)> = Vec::new();
+    for i in 0..60 {
+        let x = i as f32 / 10.0;
+        let id = manager
+            .add_vector(shard_id, Vector::new(vec![x, 1.0 - x]), None)
+            .await
+            .unwrap();
+        ids.push((id, x));
+    }
+    manager.split_shard(shard_id).await.unwrap();
+
+    // Against [1, 0] the dot product is x, so the largest x come first
+    let results = manager
+        .search_vectors(shard_id, &Vector::new(vec![1.0, 0.0]), 3)
+        .await
+        .unwrap();
+    ids.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let expected: Vec<Uuid> = ids.iter().take(3).map(|(id, _)| *id).collect();
+    assert_eq!(results.iter().map(|r| r.id).collect::<Vec<_>>(), expected);
+    assert!(results
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+}
+
+#[test]
+fn metric_names_round_trip() {
+    for metric in [
+        DistanceMetric::Euclidean,
+        DistanceMetric::Cosine,
+        DistanceMetric::Manhattan,
+        DistanceMetric::Hamming,
+        DistanceMetric::InnerProduct,
+    ] {
+        let name = distance_metric_to_string(metric);
+        assert_eq!(parse_distance_metric(&name).unwrap(), metric);
+    }
+    assert_eq!(
+        parse_distance_metric("dot").unwrap(),
+        DistanceMetric::InnerProduct
+    );
+    assert_eq!(
+        distance_metric_to_string(DistanceMetric::InnerProduct),
+        "inner_product"
+    );
+}
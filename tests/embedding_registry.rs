@@ -0,0 +1,122 @@
+use amazon_rose_forest::{
+    core::metrics::MetricsCollector,
+    embedding::{
+        EmbeddingProvider, EmbeddingRegistry, HashingEmbedder, ReembedStatus, SpaceStatus,
+        EMBEDDING_MODEL_KEY,
+    },
+    sharding::{manager::ShardManager, vector_index::DistanceMetric},
+    Vector,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn model_bound_shards_reject_other_models() {
+    let manager = ShardManager::new(Arc::new(MetricsCollector::new()));
+    let shard_id = manager.create_shard("bound").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+    manager
+        .bind_embedding_model(shard_id, "small@1")
+        .await
+        .unwrap();
+
+    let tagged = |model: &str| {
+        Some(HashMap::from([(
+            EMBEDDING_MODEL_KEY.to_string(),
+            model.to_string(),
+        )]))
+    };
+    assert!(manager
+        .add_vector(shard_id, Vector::new(vec![1.0, 0.0]), tagged("small@2"))
+        .await
+        .is_err());
+
+    let id = manager
+        .add_vector(shard_id, Vector::new(vec![1.0, 0.0]), None)
+        .await
+        .unwrap();
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    let stored = index.get(id).await.unwrap().metadata.unwrap();
+    assert_eq!(stored[EMBEDDING_MODEL_KEY], "small@1");
+}
+
+#[tokio::test]
+async fn reembedding_builds_a_side_by_side_space() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let registry = Arc::new(EmbeddingRegistry::new(manager.clone(), metrics));
+
+    let old_model = registry
+        .register_provider(Arc::new(HashingEmbedder::new(16)))
+        .await;
+    let new_provider = Arc::new(HashingEmbedder::new(32));
+    let new_model = registry.register_provider(new_provider.clone()).await;
+
+    let space = registry
+        .create_space("articles", &old_model, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+    assert_eq!(space.status, SpaceStatus::Active);
+
+    let rose = registry
+        .add_text("articles", "roses bloom in the forest", None)
+        .await
+        .unwrap();
+    registry
+        .add_text("articles", "vector databases index embeddings", None)
+        .await
+        .unwrap();
+    manager
+        .add_vector(space.shard_id, Vector::random(16), None)
+        .await
+        .unwrap();
+
+    let job_id = registry
+        .clone()
+        .start_reembedding("articles", &new_model, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+
+    let job = loop {
+        let job = registry.get_job(job_id).await.unwrap();
+        if job.status != ReembedStatus::Running {
+            break job;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    };
+    assert_eq!(job.status, ReembedStatus::Completed);
+    assert_eq!(job.total, 3);
+    assert_eq!(job.skipped, 1);
+
+    // Reads stay on the old model until the new space is activated
+    let spaces = registry.spaces("articles").await;
+    let new_space = spaces.iter().find(|s| s.model_id == new_model).unwrap();
+    assert_eq!(new_space.status, SpaceStatus::Ready);
+    assert_eq!(
+        registry.active_space("articles").await.unwrap().model_id,
+        old_model
+    );
+
+    registry
+        .activate_space("articles", &new_model)
+        .await
+        .unwrap();
+    let results = registry
+        .search_text("articles", "forest roses", 1)
+        .await
+        .unwrap();
+    assert_eq!(results[0].id, rose);
+    assert_eq!(new_provider.dimensions(), results[0].vector.dimensions);
+
+    let old_space = registry
+        .spaces("articles")
+        .await
+        .into_iter()
+        .find(|s| s.model_id == old_model)
+        .unwrap();
+    assert_eq!(old_space.status, SpaceStatus::Retired);
+}
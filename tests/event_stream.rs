@@ -0,0 +1,99 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::server::events::{
+    negotiate, EventEnvelope, ServerEvent, MIN_SCHEMA_VERSION, SCHEMA_VERSION,
+};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::Vector;
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+#[test]
+fn handshake_picks_the_highest_common_version() {
+    assert_eq!(negotiate(None), Some(MIN_SCHEMA_VERSION));
+    assert_eq!(
+        negotiate(Some("rose-events.v1, rose-events.v2, rose-events.v99")),
+        Some(SCHEMA_VERSION)
+    );
+    assert_eq!(negotiate(Some("rose-events.v1")), Some(1));
+    assert_eq!(negotiate(Some("rose-events.v99, graphql-ws")), None);
+}
+
+#[test]
+fn events_newer_than_the_negotiated_schema_are_withheld() {
+    let lagged = ServerEvent::Lagged { missed: 3 };
+    assert!(EventEnvelope::for_version(1, lagged.clone()).is_none());
+    let envelope = EventEnvelope::for_version(2, lagged).unwrap();
+
+    let value = serde_json::to_value(&envelope).unwrap();
+    assert_eq!(value["type"], "lagged");
+    assert_eq!(value["version"], 2);
+    assert_eq!(
+        serde_json::from_value::<EventEnvelope>(value).unwrap(),
+        envelope
+    );
+
+    // Old clients decode types added after them instead of failing
+    let future: EventEnvelope = serde_json::from_value(json!({
+        "version": 3,
+        "sent_at": "2024-01-01T00:00:00Z",
+        "type": "shard_split",
+        "shard_id": Uuid::new_v4(),
+    }))
+    .unwrap();
+    assert_eq!(future.event, ServerEvent::Unknown);
+}
+
+#[tokio::test]
+async fn subscribers_receive_vector_changes() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("docs").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager.clone()),
+    )
+    .filter();
+
+    let mut client = warp::test::ws()
+        .path("/ws/events")
+        .header("sec-websocket-protocol", "rose-events.v1")
+        .handshake(filter.clone())
+        .await
+        .unwrap();
+    let vector_id = manager
+        .add_vector(shard_id, Vector::new(vec![0.1, 0.2]), None)
+        .await
+        .unwrap();
+    let msg = client.recv().await.unwrap();
+    let envelope: EventEnvelope = serde_json::from_str(msg.to_str().unwrap()).unwrap();
+    assert_eq!(envelope.version, 1);
+    assert_eq!(
+        envelope.event,
+        ServerEvent::VectorInserted {
+            shard_id,
+            vector_id,
+            offset: 0,
+            origin: None,
+        }
+    );
+
+    let resp = warp::test::request()
+        .path("/ws/events")
+        .header("connection", "upgrade")
+        .header("upgrade", "websocket")
+        .header("sec-websocket-version", "13")
+        .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+        .header("sec-websocket-protocol", "rose-events.v99")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
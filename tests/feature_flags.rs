@@ -0,0 +1,109 @@
+mod common;
+
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::self_improvement::SelfImprovementEngine;
+use amazon_rose_forest::darwin::validation::ValidationPipeline;
+use amazon_rose_forest::utils::config::{Config, FeatureConfig};
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn engine(metrics: Arc<MetricsCollector>, features: FeatureConfig) -> SelfImprovementEngine {
+    SelfImprovementEngine::new(
+        metrics.clone(),
+        Arc::new(ValidationPipeline::new(metrics.clone())),
+        Arc::new(ExplorationStrategy::new(metrics)),
+    )
+    .with_features(features)
+}
+
+#[test]
+fn disabling_a_feature_disables_everything_below_it() {
+    let all = FeatureConfig::default();
+    assert!(all.self_improvement_enabled() && all.transcendence_enabled());
+
+    let mut features = FeatureConfig::default();
+    features.disable("darwin.consciousness").unwrap();
+    assert!(features.self_improvement_enabled());
+    assert!(!features.consciousness_enabled());
+    assert!(!features.reality_enabled());
+    assert!(!features.quantum_enabled());
+    assert!(!features.transcendence_enabled());
+
+    // Transcendence needs both the realities and the quantum manager
+    let mut features = FeatureConfig::default();
+    features.disable("darwin.consciousness.quantum").unwrap();
+    assert!(features.reality_enabled());
+    assert!(!features.transcendence_enabled());
+
+    let mut features = FeatureConfig::default();
+    features.disable("darwin").unwrap();
+    assert!(!features.self_improvement_enabled());
+    assert!(!features.reality_enabled());
+
+    assert!(FeatureConfig::default()
+        .disable("darwin.telepathy")
+        .is_err());
+}
+
+#[test]
+fn missing_feature_settings_default_to_enabled() {
+    let mut config = serde_json::to_value(Config::default()).unwrap();
+    config.as_object_mut().unwrap().remove("features");
+    assert_eq!(
+        serde_json::from_value::<Config>(config.clone())
+            .unwrap()
+            .features,
+        FeatureConfig::default()
+    );
+
+    config["features"] = serde_json::json!({"darwin": {"consciousness": {"enabled": false}}});
+    let features = serde_json::from_value::<Config>(config).unwrap().features;
+    assert!(features.self_improvement_enabled());
+    assert!(!features.reality_enabled());
+    assert!(features.darwin.consciousness.transcendence);
+}
+
+#[tokio::test]
+async fn practical_loop_runs_without_consciousness_features() {
+    let mut features = FeatureConfig::default();
+    features.disable("darwin.consciousness").unwrap();
+
+    let full = engine(Arc::new(MetricsCollector::new()), FeatureConfig::default());
+    assert!(full.generate_modifications().await.unwrap().len() > 1);
+
+    let metrics = Arc::new(MetricsCollector::new());
+    let practical = engine(metrics.clone(), features);
+    let ids = practical.generate_modifications().await.unwrap();
+    assert_eq!(ids.len(), 1);
+
+    // Deployments write the changes without branching or merging realities
+    let path = std::env::temp_dir().join(format!("feature-flags-{}.rs", Uuid::new_v4()));
+    let id = practical
+        .propose_modification(common::modification(
+            "tidy",
+            vec![common::code_change(
+                &path.to_string_lossy(),
+                None,
+                "pub fn tidy() {}\n",
+            )],
+        ))
+        .await
+        .unwrap();
+    assert!(practical.validate_modification(id).await.unwrap());
+    practical.deploy_modification(id).await.unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "pub fn tidy() {}\n"
+    );
+    assert_eq!(
+        metrics.get_gauge("darwin.reality.total_branches").await,
+        None
+    );
+    assert_eq!(
+        metrics.get_counter("darwin.reality.merges_completed").await,
+        None
+    );
+
+    std::fs::remove_file(path).ok();
+}
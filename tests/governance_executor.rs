@@ -0,0 +1,244 @@
+mod common;
+
+use amazon_rose_forest::core::audit::AuditLog;
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::self_improvement::{
+    Modification, ModificationStatus, SelfImprovementEngine,
+};
+use amazon_rose_forest::darwin::validation::ValidationPipeline;
+use amazon_rose_forest::governance::dao::{Dao, VoteChoice};
+use amazon_rose_forest::governance::executor::{
+    CapabilityGrants, ConfigStore, ExecutionStatus, ProposalAction, ProposalExecutor,
+};
+use amazon_rose_forest::utils::config::Config;
+use amazon_rose_forest::utils::errors::GovernanceError;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+struct Setup {
+    dao: Arc<Dao>,
+    audit: Arc<AuditLog>,
+    metrics: Arc<MetricsCollector>,
+    config: Arc<ConfigStore>,
+    validation: Arc<ValidationPipeline>,
+    engine: Arc<SelfImprovementEngine>,
+    grants: Arc<CapabilityGrants>,
+}
+
+impl Setup {
+    fn new() -> Self {
+        let metrics = Arc::new(MetricsCollector::new());
+        let validation = Arc::new(ValidationPipeline::new(metrics.clone()));
+        let engine = Arc::new(SelfImprovementEngine::new(
+            metrics.clone(),
+            validation.clone(),
+            Arc::new(ExplorationStrategy::new(metrics.clone())),
+        ));
+        Self {
+            dao: Arc::new(Dao::new()),
+            audit: Arc::new(AuditLog::new()),
+            metrics,
+            config: Arc::new(ConfigStore::new(Config::default())),
+            validation,
+            engine,
+            grants: Arc::new(CapabilityGrants::new()),
+        }
+    }
+
+    fn executor(&self) -> ProposalExecutor {
+        ProposalExecutor::new(self.dao.clone(), self.audit.clone(), self.metrics.clone())
+            .with_handler(self.config.clone())
+            .with_handler(self.validation.clone())
+            .with_handler(self.engine.clone())
+            .with_handler(self.grants.clone())
+    }
+
+    async fn pass(&self, actions: Vec<ProposalAction>) -> Uuid {
+        let id = self.propose(actions).await;
+        self.dao.vote(id, "alice", VoteChoice::For).await.unwrap();
+        assert!(self.dao.close(id).await.unwrap().passes);
+        id
+    }
+
+    async fn propose(&self, actions: Vec<ProposalAction>) -> Uuid {
+        self.dao
+            .propose_actions("alice", "general", "Tune the node", "", actions)
+            .await
+            .unwrap()
+    }
+}
+
+fn modification() -> Modification {
+    common::modification("m", Vec::new())
+}
+
+#[tokio::test]
+async fn passed_proposals_execute_their_actions() {
+    let setup = Setup::new();
+    let m = modification();
+    setup.engine.propose_modification(m.clone()).await.unwrap();
+
+    let id = setup
+        .pass(vec![
+            ProposalAction::SetConfig {
+                key: "network.timeout_ms".into(),
+                value: json!(250),
+            },
+            ProposalAction::SetValidationThreshold {
+                metric: "coverage".into(),
+                threshold: 0.8,
+            },
+            ProposalAction::ApproveModification {
+                modification_id: m.id,
+            },
+            ProposalAction::GrantCapability {
+                agent: "bob".into(),
+                capability: "deploy".into(),
+            },
+        ])
+        .await;
+
+    let record = setup.executor().execute(id).await.unwrap();
+    assert_eq!(record.status, ExecutionStatus::Executed);
+    assert_eq!(record.executed, 4);
+    assert_eq!(setup.config.get().await.network.timeout_ms, 250);
+    assert_eq!(setup.validation.threshold("coverage"), Some(0.8));
+    assert_eq!(
+        setup.engine.get_modification(m.id).await.unwrap().status,
+        ModificationStatus::Accepted
+    );
+    assert!(setup.grants.has("bob", "deploy"));
+
+    assert_eq!(
+        setup.dao.proposal(id).await.unwrap().execution,
+        Some(record)
+    );
+    let events = setup.audit.by_category("governance", 10).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].action, "proposal_executed");
+    assert_eq!(events[0].subject, id.to_string());
+    assert_eq!(
+        setup
+            .metrics
+            .get_counter("governance.proposals.executed")
+            .await,
+        Some(1)
+    );
+}
+
+#[tokio::test]
+async fn failed_actions_compensate_earlier_ones() {
+    let setup = Setup::new();
+    setup.validation.set_threshold("coverage", 0.5);
+
+    let id = setup
+        .pass(vec![
+            ProposalAction::SetValidationThreshold {
+                metric: "coverage".into(),
+                threshold: 0.9,
+            },
+            ProposalAction::GrantCapability {
+                agent: "bob".into(),
+                capability: "deploy".into(),
+            },
+            ProposalAction::SetConfig {
+                key: "network.timeout_ms".into(),
+                value: json!(100),
+            },
+            ProposalAction::SetConfig {
+                key: "network.no_such_key".into(),
+                value: json!(1),
+            },
+        ])
+        .await;
+
+    let record = setup.executor().execute(id).await.unwrap();
+    assert!(matches!(
+        record.status,
+        ExecutionStatus::Compensated {
+            failed_action: 3,
+            ..
+        }
+    ));
+    assert_eq!(record.executed, 3);
+    assert_eq!(setup.validation.threshold("coverage"), Some(0.5));
+    assert!(!setup.grants.has("bob", "deploy"));
+    assert_eq!(setup.config.get().await.network.timeout_ms, 5000);
+
+    let events = setup.audit.by_category("governance", 10).await;
+    assert_eq!(events[0].action, "proposal_compensated");
+    assert_eq!(
+        setup
+            .metrics
+            .get_counter("governance.proposals.compensated")
+            .await,
+        Some(1)
+    );
+}
+
+#[tokio::test]
+async fn only_passed_proposals_execute_once() {
+    let setup = Setup::new();
+    let executor = setup.executor();
+
+    let open = setup.propose(Vec::new()).await;
+    assert!(matches!(
+        executor.execute(open).await,
+        Err(GovernanceError::NotPassed(_))
+    ));
+
+    let id = setup.pass(Vec::new()).await;
+    executor.execute(id).await.unwrap();
+    assert!(matches!(
+        executor.execute(id).await,
+        Err(GovernanceError::AlreadyExecuted(_))
+    ));
+
+    // Nothing runs when an action has no handler
+    let id = setup
+        .pass(vec![ProposalAction::GrantCapability {
+            agent: "bob".into(),
+            capability: "deploy".into(),
+        }])
+        .await;
+    let bare = ProposalExecutor::new(
+        setup.dao.clone(),
+        setup.audit.clone(),
+        setup.metrics.clone(),
+    );
+    assert!(matches!(
+        bare.execute(id).await,
+        Err(GovernanceError::NoHandler(action)) if action == "grant_capability"
+    ));
+    assert!(setup.dao.proposal(id).await.unwrap().execution.is_none());
+}
+
+#[tokio::test]
+async fn executor_runs_proposals_as_they_pass() {
+    let setup = Setup::new();
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let worker = tokio::spawn(setup.executor().run(shutdown_rx));
+    tokio::task::yield_now().await;
+
+    let id = setup
+        .pass(vec![ProposalAction::GrantCapability {
+            agent: "carol".into(),
+            capability: "moderate".into(),
+        }])
+        .await;
+    for _ in 0..50 {
+        if setup.dao.proposal(id).await.unwrap().execution.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(setup.dao.proposal(id).await.unwrap().execution.is_some());
+    assert!(setup.grants.has("carol", "moderate"));
+
+    shutdown_tx.send(true).unwrap();
+    worker.await.unwrap();
+}
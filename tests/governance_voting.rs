@@ -0,0 +1,146 @@
+use amazon_rose_forest::governance::dao::{Dao, DaoConfig, ProposalStatus, VoteChoice};
+use amazon_rose_forest::governance::voting::{
+    ReputationPower, StakePower, ValueFlowRecord, VotingPower, WeightSource,
+};
+use amazon_rose_forest::utils::errors::GovernanceError;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn flow(from: &str, to: &str, shift: f32) -> ValueFlowRecord {
+    ValueFlowRecord {
+        from: from.into(),
+        to: to.into(),
+        utility: 1.0,
+        governance_weight: 1.0,
+        reputation_shift: vec![shift, shift],
+        utility_proof: None,
+    }
+}
+
+fn config() -> DaoConfig {
+    DaoConfig {
+        weight_sources: HashMap::from([
+            ("parameter_change".to_string(), WeightSource::Stake),
+            ("moderation".to_string(), WeightSource::Reputation),
+        ]),
+        ..DaoConfig::default()
+    }
+}
+
+#[test]
+fn reputation_follows_value_flows() {
+    let reputation = ReputationPower::new();
+    assert_eq!(reputation.power("alice"), 0.0);
+
+    reputation.record_flow(&flow("alice", "bob", 0.25)).unwrap();
+    assert_eq!(reputation.reputation("alice"), Some(vec![0.25, 0.25]));
+    assert!((reputation.power("bob") - 0.75).abs() < 1e-6);
+
+    // Reputation can go negative, voting power can't
+    reputation.record_flow(&flow("alice", "bob", 0.5)).unwrap();
+    assert_eq!(reputation.power("alice"), 0.0);
+}
+
+#[tokio::test]
+async fn equal_votes_by_default() {
+    let dao = Dao::new();
+    let id = dao
+        .propose("alice", "general", "Plant trees", "")
+        .await
+        .unwrap();
+    assert_eq!(dao.vote(id, "alice", VoteChoice::For).await.unwrap(), 1.0);
+    dao.vote(id, "bob", VoteChoice::For).await.unwrap();
+    dao.vote(id, "carol", VoteChoice::Against).await.unwrap();
+    assert!(matches!(
+        dao.vote(id, "bob", VoteChoice::Against).await,
+        Err(GovernanceError::AlreadyVoted { .. })
+    ));
+
+    let tally = dao.close(id).await.unwrap();
+    assert_eq!(tally.weight_source, WeightSource::Equal);
+    assert_eq!(tally.for_weight, 2.0);
+    assert!(tally.passes);
+    assert_eq!(
+        dao.proposal(id).await.unwrap().status,
+        ProposalStatus::Passed
+    );
+    assert!(matches!(
+        dao.vote(id, "dave", VoteChoice::For).await,
+        Err(GovernanceError::Closed(_))
+    ));
+}
+
+#[tokio::test]
+async fn proposal_types_use_their_weight_source() {
+    let stake = Arc::new(StakePower::new());
+    stake.stake("whale", 10.0);
+    stake.stake("minnow", 1.0);
+    let reputation = Arc::new(ReputationPower::new());
+    reputation
+        .record_flow(&flow("minnow", "whale", 0.4))
+        .unwrap();
+
+    let dao = Dao::with_config(config())
+        .with_voting_power(stake.clone())
+        .with_voting_power(reputation.clone());
+
+    // Stake decides parameter changes: the whale outweighs everyone else
+    let change = dao
+        .propose("whale", "parameter_change", "Raise quorum", "")
+        .await
+        .unwrap();
+    dao.vote(change, "whale", VoteChoice::Against)
+        .await
+        .unwrap();
+    dao.vote(change, "minnow", VoteChoice::For).await.unwrap();
+    assert!(matches!(
+        dao.vote(change, "nobody", VoteChoice::For).await,
+        Err(GovernanceError::NoWeight {
+            weight_source: WeightSource::Stake,
+            ..
+        })
+    ));
+    // Weights are fixed when votes are cast
+    stake.unstake("whale", 10.0);
+    let tally = dao.close(change).await.unwrap();
+    assert_eq!(tally.against_weight, 10.0);
+    assert!(!tally.passes);
+
+    // Reputation decides moderation
+    let moderation = dao
+        .propose("minnow", "moderation", "Hide spam", "")
+        .await
+        .unwrap();
+    let weight = dao
+        .vote(moderation, "whale", VoteChoice::For)
+        .await
+        .unwrap();
+    assert!((weight - reputation.power("whale")).abs() < 1e-9);
+    assert_eq!(
+        dao.tally(moderation).await.unwrap().weight_source,
+        WeightSource::Reputation
+    );
+}
+
+#[tokio::test]
+async fn missing_providers_and_quorum_are_enforced() {
+    let dao = Dao::with_config(DaoConfig {
+        quorum: 3.0,
+        ..config()
+    });
+    assert!(matches!(
+        dao.propose("alice", "parameter_change", "x", "").await,
+        Err(GovernanceError::NoVotingPower(WeightSource::Stake))
+    ));
+
+    let id = dao.propose("alice", "general", "x", "").await.unwrap();
+    dao.vote(id, "alice", VoteChoice::For).await.unwrap();
+    dao.vote(id, "bob", VoteChoice::Abstain).await.unwrap();
+    let tally = dao.tally(id).await.unwrap();
+    assert!(!tally.quorum_reached && !tally.passes);
+
+    // Abstentions count towards quorum but not the outcome
+    dao.vote(id, "carol", VoteChoice::Abstain).await.unwrap();
+    let tally = dao.close(id).await.unwrap();
+    assert!(tally.quorum_reached && tally.passes);
+}
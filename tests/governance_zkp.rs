@@ -0,0 +1,91 @@
+use amazon_rose_forest::governance::voting::{ReputationPower, ValueFlowRecord};
+use amazon_rose_forest::governance::zkp::{UtilityBounds, UtilityProof, ZKP};
+use amazon_rose_forest::utils::errors::ZkpError;
+
+fn private_flow(proof: UtilityProof) -> ValueFlowRecord {
+    ValueFlowRecord {
+        from: "alice".into(),
+        to: "bob".into(),
+        utility: 0.0,
+        governance_weight: 1.0,
+        reputation_shift: vec![0.1],
+        utility_proof: Some(proof),
+    }
+}
+
+#[test]
+fn proofs_verify_only_against_their_bounds() {
+    let zkp = ZKP::new();
+    let bounds = UtilityBounds::new(10.0, 100.0).unwrap();
+    let (proof, opening) = zkp.prove_utility(12.5, bounds).unwrap();
+
+    zkp.verify_utility(&proof, bounds).unwrap();
+    assert!(zkp.opens(&proof, &opening));
+    assert_eq!(opening.utility(), 12.5);
+
+    // Narrower bounds that exclude the amount reject the proof
+    let narrower = UtilityBounds::new(20.0, 100.0).unwrap();
+    assert!(matches!(
+        zkp.verify_utility(&proof, narrower),
+        Err(ZkpError::InvalidProof)
+    ));
+
+    // A different opening doesn't reveal the commitment
+    let (_, other) = zkp.prove_utility(12.5, bounds).unwrap();
+    assert!(!zkp.opens(&proof, &other));
+}
+
+#[test]
+fn out_of_range_and_malformed_inputs_are_rejected() {
+    let zkp = ZKP::new();
+    let bounds = UtilityBounds::default();
+    assert!(matches!(
+        zkp.prove_utility(bounds.max + 1.0, bounds),
+        Err(ZkpError::OutOfRange { .. })
+    ));
+    assert!(matches!(
+        zkp.prove_utility(-1.0, bounds),
+        Err(ZkpError::OutOfRange { .. })
+    ));
+    assert!(matches!(
+        UtilityBounds::new(5.0, 1.0),
+        Err(ZkpError::InvalidBounds { .. })
+    ));
+    assert!(UtilityBounds::new(0.0, 1e7).is_err());
+
+    let (mut proof, _) = zkp.prove_utility(1.0, bounds).unwrap();
+    proof.commitment.truncate(8);
+    assert!(matches!(
+        zkp.verify_utility(&proof, bounds),
+        Err(ZkpError::Malformed(_))
+    ));
+}
+
+#[test]
+fn reputation_engine_verifies_private_flows() {
+    let zkp = ZKP::new();
+    let reputation = ReputationPower::new();
+    let (proof, _) = zkp.prove_utility(3.0, UtilityBounds::default()).unwrap();
+
+    reputation
+        .record_flow(&private_flow(proof.clone()))
+        .unwrap();
+    assert_eq!(reputation.reputation("bob"), Some(vec![0.6]));
+
+    // A flow that also reveals its amount is refused
+    let mut revealed = private_flow(proof.clone());
+    revealed.utility = 3.0;
+    assert!(matches!(
+        reputation.record_flow(&revealed),
+        Err(ZkpError::RevealedUtility)
+    ));
+
+    // So is one proven against bounds the engine doesn't accept
+    let strict = ReputationPower::new().with_utility_bounds(UtilityBounds::new(5.0, 10.0).unwrap());
+    assert!(matches!(
+        strict.record_flow(&private_flow(proof)),
+        Err(ZkpError::InvalidProof)
+    ));
+    assert_eq!(strict.reputation("bob"), None);
+    assert_eq!(reputation.reputation("bob"), Some(vec![0.6]));
+}
@@ -0,0 +1,186 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::server::api::{CreateIndexResponse, IndexInfo};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::hnsw::HnswParams;
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::storage::{FileStorage, StorageBackend};
+use amazon_rose_forest::sharding::vector_index::{DistanceMetric, IndexType, VectorIndex};
+use amazon_rose_forest::Vector;
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+const DIMENSIONS: usize = 16;
+
+fn hnsw() -> IndexType {
+    IndexType::Hnsw(HnswParams {
+        ef_construction: 100,
+        ..Default::default()
+    })
+}
+
+/// Share of the exact `k` nearest neighbours the index finds, averaged
+/// over `queries`
+async fn recall(index: &VectorIndex, exact: &[(Uuid, Vector)], queries: usize, k: usize) -> f64 {
+    let metric = index.distance_metric();
+    let mut found = 0;
+    for _ in 0..queries {
+        let query = Vector::random(DIMENSIONS);
+        let mut nearest: Vec<(f32, Uuid)> = exact
+            .iter()
+            .map(|(id, vector)| (metric.calculate(&query, vector), *id))
+            .collect();
+        nearest.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let expected: HashSet<Uuid> = nearest.iter().take(k).map(|(_, id)| *id).collect();
+        let results = index.search(&query, k).await.unwrap();
+        assert_eq!(results.len(), k);
+        found += results.iter().filter(|r| expected.contains(&r.id)).count();
+    }
+    found as f64 / (queries * k) as f64
+}
+
+#[tokio::test]
+async fn hnsw_search_matches_brute_force() {
+    let index = VectorIndex::new("graph", DIMENSIONS, DistanceMetric::Euclidean, None)
+        .unwrap()
+        .with_index_type(hnsw())
+        .unwrap();
+    assert_eq!(index.index_type(), hnsw());
+    let mut stored = Vec::new();
+    for _ in 0..2000 {
+        let vector = Vector::random(DIMENSIONS);
+        let id = index.add(vector.clone(), None).await.unwrap();
+        stored.push((id, vector));
+    }
+
+    let recall_all = recall(&index, &stored, 50, 10).await;
+    assert!(recall_all >= 0.9, "recall {}", recall_all);
+
+    // Removed vectors are never returned, and enough removals rebuild the
+    // graph without hurting recall
+    let (removed, kept) = stored.split_at(800);
+    for (id, _) in removed {
+        index.remove(*id).await.unwrap();
+    }
+    let removed: HashSet<Uuid> = removed.iter().map(|(id, _)| *id).collect();
+    for (_, vector) in &stored[..20] {
+        let results = index.search(vector, 20).await.unwrap();
+        assert!(results.iter().all(|r| !removed.contains(&r.id)));
+    }
+    let recall_kept = recall(&index, kept, 50, 10).await;
+    assert!(recall_kept >= 0.9, "recall {}", recall_kept);
+
+    // Graphs can be added to an index that already holds vectors
+    let entries = index.entries().await;
+    let rebuilt = VectorIndex::new("rebuilt", DIMENSIONS, DistanceMetric::Cosine, None).unwrap();
+    rebuilt.add_entries(entries).await.unwrap();
+    let rebuilt = rebuilt.with_index_type(hnsw()).unwrap();
+    let recall_rebuilt = recall(&rebuilt, kept, 20, 10).await;
+    assert!(recall_rebuilt >= 0.9, "recall {}", recall_rebuilt);
+
+    let invalid = IndexType::Hnsw(HnswParams {
+        m: 1,
+        ..Default::default()
+    });
+    assert!(
+        VectorIndex::new("bad", DIMENSIONS, DistanceMetric::Euclidean, None)
+            .unwrap()
+            .with_index_type(invalid)
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn index_type_survives_restarts_and_splits() {
+    let dir = std::env::temp_dir().join(format!("rose-forest-hnsw-{}", Uuid::new_v4()));
+    let storage: Arc<dyn StorageBackend> = Arc::new(FileStorage::open(&dir).unwrap());
+    let manager =
+        ShardManager::new(Arc::new(MetricsCollector::new())).with_storage(storage.clone());
+    let shard_id = manager.create_shard("docs").await.unwrap();
+    manager
+        .create_vector_index_with_type(
+            shard_id,
+            "main",
+            DIMENSIONS,
+            DistanceMetric::Euclidean,
+            hnsw(),
+        )
+        .await
+        .unwrap();
+    for _ in 0..300 {
+        manager
+            .add_vector(shard_id, Vector::random(DIMENSIONS), None)
+            .await
+            .unwrap();
+    }
+    let split = manager.split_shard(shard_id).await.unwrap();
+    let split_off = manager.get_vector_index(split.new_shard_id).await.unwrap();
+    assert_eq!(split_off.index_type(), hnsw());
+    manager.flush().await.unwrap();
+    drop(manager);
+
+    let restored = ShardManager::new(Arc::new(MetricsCollector::new())).with_storage(storage);
+    restored.restore(true).await.unwrap();
+    let index = restored.get_vector_index(shard_id).await.unwrap();
+    assert_eq!(index.index_type(), hnsw());
+    assert_eq!(index.stats().await.index_type, hnsw());
+    let entry = index.entries().await.remove(0);
+    let results = index.search(&entry.vector, 1).await.unwrap();
+    assert_eq!(results[0].id, entry.id);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn indexes_endpoint_accepts_an_index_type() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("docs").await.unwrap();
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager.clone()),
+    )
+    .filter();
+    let create = |index_type: serde_json::Value| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/indexes")
+            .json(&json!({
+                "shard_id": shard_id,
+                "name": "main",
+                "dimensions": 2,
+                "distance_metric": "cosine",
+                "index_type": index_type,
+            }))
+            .reply(&filter)
+    };
+
+    let resp = create(json!({ "type": "hnsw", "m": 0 })).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = create(json!({ "type": "hnsw", "m": 8 })).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let expected = IndexType::Hnsw(HnswParams {
+        m: 8,
+        ..Default::default()
+    });
+    let created: CreateIndexResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(created.index_type, expected);
+
+    let resp = warp::test::request()
+        .path(&format!("/api/shards/{}/indexes", shard_id))
+        .reply(&filter)
+        .await;
+    let indexes: Vec<IndexInfo> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(indexes[0].index_type, expected);
+    assert_eq!(
+        manager
+            .get_vector_index(shard_id)
+            .await
+            .unwrap()
+            .index_type(),
+        expected
+    );
+}
@@ -0,0 +1,269 @@
+use amazon_rose_forest::connectors::{
+    read_embeddings, EmbeddingFormat, EmbeddingMatrix, ImportPolicy,
+};
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::{IndexBuild, ShardManager};
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::Vector;
+use rand::Rng;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+fn temp_file(extension: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "rose-forest-embeddings-{}.{}",
+        Uuid::new_v4(),
+        extension
+    ))
+}
+
+/// A version 1 .npy file of little-endian f32s
+fn npy_bytes(shape: (usize, usize), values: &[f32], fortran_order: bool) -> Vec<u8> {
+    let mut header = format!(
+        "{{'descr': '<f4', 'fortran_order': {}, 'shape': ({}, {}), }}",
+        if fortran_order { "True" } else { "False" },
+        shape.0,
+        shape.1
+    );
+    // Data starts on a 64-byte boundary; the header ends in a newline
+    while (10 + header.len() + 1) % 64 != 0 {
+        header.push(' ');
+    }
+    header.push('\n');
+
+    let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+    bytes.extend((header.len() as u16).to_le_bytes());
+    bytes.extend(header.as_bytes());
+    for value in values {
+        bytes.extend(value.to_le_bytes());
+    }
+    bytes
+}
+
+/// Name, dtype, shape and raw data of one tensor
+type Tensor<'a> = (&'a str, &'a str, (usize, usize), Vec<u8>);
+
+fn safetensors_bytes(tensors: &[Tensor]) -> Vec<u8> {
+    let mut header = serde_json::Map::new();
+    header.insert("__metadata__".into(), json!({ "model": "test" }));
+    let mut data: Vec<u8> = Vec::new();
+    for (name, dtype, shape, bytes) in tensors {
+        header.insert(
+            name.to_string(),
+            json!({
+                "dtype": dtype,
+                "shape": [shape.0, shape.1],
+                "data_offsets": [data.len(), data.len() + bytes.len()],
+            }),
+        );
+        data.extend(bytes);
+    }
+    let header = serde_json::to_vec(&header).unwrap();
+    let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+    bytes.extend(header);
+    bytes.extend(data);
+    bytes
+}
+
+fn random_embeddings(rows: usize, dimensions: usize) -> Vec<f32> {
+    let mut rng = rand::thread_rng();
+    (0..rows * dimensions)
+        .map(|_| rng.gen_range(-1.0..1.0))
+        .collect()
+}
+
+#[test]
+fn embedding_files_are_read_row_major() {
+    let values = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let path = temp_file("npy");
+    std::fs::write(&path, npy_bytes((2, 3), &values, false)).unwrap();
+    let matrix = read_embeddings(&path, None, None).unwrap();
+    assert_eq!(matrix, EmbeddingMatrix::new(3, values.to_vec()).unwrap());
+
+    // Column-major arrays are transposed into rows
+    std::fs::write(&path, npy_bytes((3, 2), &values, true)).unwrap();
+    let rows: Vec<Vec<f32>> = read_embeddings(&path, None, None)
+        .unwrap()
+        .rows()
+        .map(<[f32]>::to_vec)
+        .collect();
+    assert_eq!(rows, vec![vec![1.0, 4.0], vec![2.0, 5.0], vec![3.0, 6.0]]);
+    std::fs::remove_file(&path).unwrap();
+
+    // Half precision: 1.0, -2.0, 0.5, 0.0
+    let half: Vec<u8> = [0x3c00u16, 0xc000, 0x3800, 0x0000]
+        .iter()
+        .flat_map(|bits| bits.to_le_bytes())
+        .collect();
+    let ids: Vec<u8> = [7.0f32, 8.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+    let path = temp_file("safetensors");
+    std::fs::write(
+        &path,
+        safetensors_bytes(&[
+            ("embeddings", "F16", (2, 2), half),
+            ("ids", "F32", (2, 1), ids),
+        ]),
+    )
+    .unwrap();
+    assert!(read_embeddings(&path, None, None).is_err());
+    let matrix = read_embeddings(&path, None, Some("embeddings")).unwrap();
+    assert_eq!(
+        matrix,
+        EmbeddingMatrix::new(2, vec![1.0, -2.0, 0.5, 0.0]).unwrap()
+    );
+    assert!(read_embeddings(&path, None, Some("missing")).is_err());
+    assert!(read_embeddings(&path, Some(EmbeddingFormat::Npy), None).is_err());
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(EmbeddingMatrix::new(2, vec![1.0, f32::NAN]).is_err());
+    assert!(EmbeddingMatrix::new(2, vec![1.0, 2.0, 3.0]).is_err());
+}
+
+#[tokio::test]
+async fn bulk_builds_register_a_searchable_shard() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = ShardManager::new(metrics.clone());
+    let values = random_embeddings(3000, 8);
+    let embeddings = EmbeddingMatrix::new(8, values.clone()).unwrap();
+
+    let build = manager
+        .build_index(
+            "imported",
+            "main",
+            DistanceMetric::Euclidean,
+            embeddings,
+            Some(4),
+        )
+        .await
+        .unwrap();
+    assert_eq!(build.vectors, 3000);
+    assert_eq!(build.dimensions, 8);
+    assert_eq!(build.threads, 4);
+    assert_eq!(
+        manager
+            .get_shard(build.shard_id)
+            .await
+            .unwrap()
+            .vector_count,
+        3000
+    );
+
+    // Written straight to segments, bypassing the memtable
+    let index = manager.get_vector_index(build.shard_id).await.unwrap();
+    let stats = index.segment_stats().await;
+    assert_eq!(stats.memtable_entries, 0);
+    assert_eq!(stats.segments.len(), 3);
+    assert_eq!(index.statistics().await.vector_count, 3000);
+
+    // Every row is findable, and remembers where it came from
+    let row = 1234;
+    let query = Vector::new(values[row * 8..(row + 1) * 8].to_vec());
+    let results = manager
+        .search_vectors(build.shard_id, &query, 1)
+        .await
+        .unwrap();
+    assert_eq!(results[0].score, 0.0);
+    assert_eq!(
+        results[0].metadata.as_ref().unwrap()["source_id"],
+        row.to_string()
+    );
+
+    // Later writes go through the usual path
+    manager
+        .add_vector(build.shard_id, Vector::random(8), None)
+        .await
+        .unwrap();
+    assert_eq!(index.count().await, 3001);
+    assert_eq!(
+        metrics.get_counter("index_builds.vectors").await,
+        Some(3000)
+    );
+}
+
+#[tokio::test]
+async fn build_endpoint_reads_files_on_the_server() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let filter = Server::new(
+        ServerConfig {
+            import: ImportPolicy::new().with_dir(std::env::temp_dir()),
+            ..ServerConfig::default()
+        },
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager.clone()),
+    )
+    .filter();
+    let path = temp_file("npy");
+    std::fs::write(&path, npy_bytes((50, 4), &random_embeddings(50, 4), false)).unwrap();
+    let build = |body: serde_json::Value| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/indexes/build")
+            .json(&body)
+            .reply(&filter)
+    };
+
+    let resp = build(json!({
+        "shard_name": "from-file",
+        "path": path,
+        "distance_metric": "cosine",
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: IndexBuild = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.vectors, 50);
+    let shard = manager.get_shard_by_name("from-file").await.unwrap();
+    assert_eq!(shard.id, body.shard_id);
+    let index = manager.get_vector_index(shard.id).await.unwrap();
+    assert_eq!(index.name(), "main");
+    assert_eq!(index.distance_metric(), DistanceMetric::Cosine);
+
+    let resp = build(json!({
+        "shard_name": "missing",
+        "path": temp_file("npy"),
+        "distance_metric": "cosine",
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = build(json!({
+        "shard_name": "bad-metric",
+        "path": path,
+        "distance_metric": "chebyshev",
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert!(manager.get_shard_by_name("missing").await.is_err());
+
+    // Files outside the import directory, or on a server without one, are
+    // off limits
+    let resp = build(json!({
+        "shard_name": "escaped",
+        "path": "/etc/passwd",
+        "distance_metric": "cosine",
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let closed = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager.clone()),
+    )
+    .filter();
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/indexes/build")
+        .json(&json!({
+            "shard_name": "closed",
+            "path": path,
+            "distance_metric": "cosine",
+        }))
+        .reply(&closed)
+        .await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    std::fs::remove_file(&path).unwrap();
+}
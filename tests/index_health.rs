@@ -0,0 +1,134 @@
+use amazon_rose_forest::core::audit::AuditLog;
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::server::api::ShardStatsResponse;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::health::{HealthConfig, IndexHealthMonitor, RebuildReason};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+async fn shard(manager: &ShardManager, vectors: usize) -> (Uuid, Vec<Uuid>) {
+    let shard_id = manager.create_shard("health").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 4, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let mut ids = Vec::new();
+    for _ in 0..vectors {
+        ids.push(
+            manager
+                .add_vector(shard_id, Vector::random(4), None)
+                .await
+                .unwrap(),
+        );
+    }
+    (shard_id, ids)
+}
+
+fn monitor(manager: Arc<ShardManager>, audit_log: Arc<AuditLog>) -> IndexHealthMonitor {
+    IndexHealthMonitor::new(
+        manager,
+        Arc::new(MetricsCollector::new()),
+        audit_log,
+        HealthConfig {
+            rebuild_below: 0.8,
+            ..Default::default()
+        },
+    )
+}
+
+#[tokio::test]
+async fn a_fresh_index_is_healthy() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let (shard_id, _) = shard(&manager, 60).await;
+    let monitor = monitor(manager, Arc::new(AuditLog::new()));
+
+    let health = monitor.check(shard_id).await.unwrap();
+    assert_eq!(health.centroid_drift, 0.0);
+    assert_eq!(health.tombstone_ratio, 0.0);
+    let recall = health.recall.unwrap();
+    assert!(recall > 0.0 && recall <= 1.0);
+    assert!(health.score > 0.8, "score {}", health.score);
+    assert!(!health.rebuild_recommended);
+    assert!(health.rebuild_reasons.is_empty());
+    assert_eq!(monitor.health(shard_id).await, Some(health));
+}
+
+#[tokio::test]
+async fn drifted_data_gets_a_rebuild_recommendation() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let (shard_id, ids) = shard(&manager, 40).await;
+    let audit_log = Arc::new(AuditLog::new());
+    let monitor = monitor(manager.clone(), audit_log.clone());
+    monitor.check(shard_id).await.unwrap();
+
+    // Replace the data with vectors centred far from the original ones
+    for id in ids {
+        manager.remove_vector(shard_id, id).await.unwrap();
+    }
+    for _ in 0..40 {
+        let values = Vector::random(4).values.iter().map(|v| v + 5.0).collect();
+        manager
+            .add_vector(shard_id, Vector::new(values), None)
+            .await
+            .unwrap();
+    }
+
+    let health = monitor.check(shard_id).await.unwrap();
+    assert!(health.centroid_drift > 1.0);
+    assert!(health.rebuild_recommended);
+    assert!(health
+        .rebuild_reasons
+        .iter()
+        .any(|reason| matches!(reason, RebuildReason::CentroidDrift { .. })));
+    let events = audit_log.by_category("index_health", 10).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].action, "recommend_rebuild");
+
+    // Recommended once until the index recovers
+    monitor.check(shard_id).await.unwrap();
+    assert_eq!(audit_log.by_category("index_health", 10).await.len(), 1);
+
+    // A new baseline accepts the data where it is now
+    monitor.reset_baseline(shard_id).await;
+    let health = monitor.check(shard_id).await.unwrap();
+    assert_eq!(health.centroid_drift, 0.0);
+    assert!(!health.rebuild_recommended);
+}
+
+#[tokio::test]
+async fn stats_api_reports_health_once_checked() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let (shard_id, _) = shard(&manager, 20).await;
+    let monitor = Arc::new(monitor(manager.clone(), Arc::new(AuditLog::new())));
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    )
+    .with_index_health_monitor(monitor.clone());
+    let path = format!("/api/shards/{}/stats", shard_id);
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&path)
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let stats: ShardStatsResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(stats.statistics.vector_count, 20);
+    assert!(stats.health.is_none());
+
+    let health = monitor.check(shard_id).await.unwrap();
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&path)
+        .reply(&server.filter())
+        .await;
+    let stats: ShardStatsResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(stats.health, Some(health));
+}
@@ -0,0 +1,167 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::query::{PlanNode, QueryExpr, QueryPlanner};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::sketch::{HyperLogLog, IndexStatistics};
+use amazon_rose_forest::sharding::vector_index::{DistanceMetric, VectorIndex};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use warp::http::StatusCode;
+
+#[test]
+fn hyperloglog_estimates_within_a_few_percent() {
+    let mut evens = HyperLogLog::new();
+    let mut odds = HyperLogLog::new();
+    for i in 0..50_000u32 {
+        if i % 2 == 0 {
+            evens.add(&i);
+        } else {
+            odds.add(&i);
+        }
+        // Duplicates don't count
+        evens.add(&0u32);
+    }
+    assert!((evens.estimate() - 25_000.0).abs() < 25_000.0 * 0.05);
+
+    evens.merge(&odds);
+    assert!((evens.estimate() - 50_000.0).abs() < 50_000.0 * 0.05);
+
+    let mut small = HyperLogLog::new();
+    for i in 0..10u32 {
+        small.add(&i);
+    }
+    assert_eq!(small.estimate().round(), 10.0);
+}
+
+async fn products() -> VectorIndex {
+    let index = VectorIndex::new("products", 2, DistanceMetric::Euclidean, None).unwrap();
+    for i in 0..100 {
+        let mut metadata = HashMap::from([
+            ("sku".to_string(), format!("sku-{}", i)),
+            ("in_stock".to_string(), (i % 2 == 0).to_string()),
+        ]);
+        if i < 10 {
+            metadata.insert("promo".to_string(), "spring".to_string());
+        }
+        index
+            .add(Vector::new(vec![0.1, 0.1]), Some(metadata))
+            .await
+            .unwrap();
+    }
+    index
+}
+
+#[tokio::test]
+async fn index_tracks_field_cardinality() {
+    let index = products().await;
+    let stats = index.statistics().await;
+    assert_eq!(stats.vector_count, 100);
+    // IDs are random, so their sketch is only approximately exact
+    assert!(stats.vectors_ever_added.abs_diff(100) <= 3);
+    assert_eq!(stats.fields["sku"].present, 100);
+    assert_eq!(stats.fields["sku"].distinct_values, 100);
+    assert_eq!(stats.fields["in_stock"].distinct_values, 2);
+    assert_eq!(stats.fields["promo"].present, 10);
+    assert_eq!(stats.fields["promo"].distinct_values, 1);
+    assert_eq!(index.stats().await.statistics, stats);
+
+    // Presence counts follow removals; distinct estimates wait for a rebuild
+    let promo_ids: Vec<_> = index
+        .entries()
+        .await
+        .into_iter()
+        .filter(|e| e.metadata.as_ref().unwrap().contains_key("promo"))
+        .map(|e| e.id)
+        .collect();
+    for id in promo_ids {
+        index.remove(id).await.unwrap();
+    }
+    let stats = index.statistics().await;
+    assert_eq!(stats.vector_count, 90);
+    assert_eq!(stats.fields["promo"].present, 0);
+    assert_eq!(stats.fields["sku"].present, 90);
+
+    index.rebuild_sketches().await;
+    let stats = index.statistics().await;
+    assert!(stats.vectors_ever_added.abs_diff(90) <= 3);
+    assert!(!stats.fields.contains_key("promo"));
+}
+
+#[tokio::test]
+async fn planner_checks_most_selective_predicate_first() {
+    let index = products().await;
+    let expr: QueryExpr = serde_json::from_value(json!({
+        "and": [
+            {"field": {"key": "in_stock", "op": "eq", "value": "true"}},
+            {"field": {"key": "sku", "op": "eq", "value": "sku-7"}}
+        ]
+    }))
+    .unwrap();
+
+    // Without statistics, source order is kept among equal-cost predicates
+    let plan = QueryPlanner::new(2).plan(&expr).unwrap();
+    assert_eq!(plan.estimated_selectivity, None);
+    let PlanNode::All(children) = plan.root else {
+        panic!("expected a conjunction");
+    };
+    assert!(matches!(&children[0], PlanNode::Metadata { key, .. } if key == "in_stock"));
+
+    let plan = index.planner().await.plan(&expr).unwrap();
+    let estimate = plan.estimated_selectivity.unwrap();
+    assert!((estimate - 0.005).abs() < 0.001, "estimate {}", estimate);
+    let PlanNode::All(children) = plan.root else {
+        panic!("expected a conjunction");
+    };
+    assert!(matches!(&children[0], PlanNode::Metadata { key, .. } if key == "sku"));
+
+    // Fields never seen match nothing
+    let missing: QueryExpr = serde_json::from_value(json!({
+        "field": {"key": "color", "op": "exists"}
+    }))
+    .unwrap();
+    let plan = index.planner().await.plan(&missing).unwrap();
+    assert_eq!(plan.estimated_selectivity, Some(0.0));
+}
+
+#[tokio::test]
+async fn stats_api_reports_index_statistics() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("catalog").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for color in ["red", "blue", "red"] {
+        let metadata = HashMap::from([("color".to_string(), color.to_string())]);
+        manager
+            .add_vector(shard_id, Vector::new(vec![0.2, 0.3]), Some(metadata))
+            .await
+            .unwrap();
+    }
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    );
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!("/api/shards/{}/stats", shard_id))
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let stats: IndexStatistics = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(stats.vector_count, 3);
+    assert_eq!(stats.fields["color"].present, 3);
+    assert_eq!(stats.fields["color"].distinct_values, 2);
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!("/api/shards/{}/stats", uuid::Uuid::new_v4()))
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,253 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::nerv::jobs::{
+    ClusteringJob, Job, JobContext, JobHandler, JobKind, JobQueue, JobState,
+};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::utils::errors::JobError;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+/// Counts to `steps`, reporting progress and stopping when cancelled
+struct Counting;
+
+#[async_trait]
+impl JobHandler for Counting {
+    async fn run(&self, params: serde_json::Value, ctx: JobContext) -> Result<serde_json::Value> {
+        let steps = params["steps"].as_u64().unwrap_or(1);
+        for step in 1..=steps {
+            ctx.checkpoint()?;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            ctx.set_progress(step, steps);
+        }
+        Ok(json!({ "counted": steps }))
+    }
+}
+
+async fn wait_for(queue: &JobQueue, id: Uuid, done: impl Fn(&Job) -> bool) -> Job {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let job = queue.get(id).await.unwrap();
+            if done(&job) {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("job didn't reach the expected state")
+}
+
+async fn shard_with_two_groups() -> (Arc<ShardManager>, Uuid) {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("points").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for values in [[0.0, 0.0], [0.1, 0.1], [0.0, 0.1], [5.0, 5.0], [5.1, 5.0]] {
+        manager
+            .add_vector(shard_id, Vector::new(values.to_vec()), None)
+            .await
+            .unwrap();
+    }
+    (manager, shard_id)
+}
+
+#[tokio::test]
+async fn clustering_job_runs_to_completion() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let queue = Arc::new(JobQueue::new(metrics.clone()));
+    let (manager, shard_id) = shard_with_two_groups().await;
+    queue
+        .register(JobKind::Clustering, Arc::new(ClusteringJob::new(manager)))
+        .await;
+
+    assert!(matches!(
+        queue.submit(JobKind::Compaction, json!({})).await,
+        Err(JobError::NoHandler(_))
+    ));
+
+    let job = queue
+        .submit(
+            JobKind::Clustering,
+            json!({ "shard_id": shard_id, "threshold": 1.0 }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(job.state, JobState::Queued);
+    assert_eq!(metrics.get_gauge("jobs.queued").await, Some(1));
+
+    let _worker = queue.clone().start(2);
+    let job = wait_for(&queue, job.id, |job| job.state.is_finished()).await;
+    assert_eq!(job.state, JobState::Completed);
+    assert_eq!(job.progress_pct, 100);
+    assert_eq!(job.attempts, 1);
+    let result = job.result.unwrap();
+    assert_eq!(result["clusters"], 2);
+    assert_eq!(result["sizes"], json!([3, 2]));
+    assert_eq!(metrics.get_counter("jobs.completed").await, Some(1));
+}
+
+#[tokio::test]
+async fn running_jobs_report_progress_and_can_be_cancelled() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let queue = Arc::new(JobQueue::new(metrics.clone()));
+    queue
+        .register(JobKind::Compaction, Arc::new(Counting))
+        .await;
+    let _worker = queue.clone().start(1);
+
+    let job = queue
+        .submit(JobKind::Compaction, json!({ "steps": 1000 }))
+        .await
+        .unwrap();
+    let running = wait_for(&queue, job.id, |job| job.progress_pct > 0).await;
+    assert_eq!(running.state, JobState::Running);
+
+    // Queued behind the running job, so cancelling it takes effect at once
+    let waiting = queue
+        .submit(JobKind::Compaction, json!({ "steps": 1 }))
+        .await
+        .unwrap();
+    let cancelled = queue.cancel(waiting.id).await.unwrap();
+    assert_eq!(cancelled.state, JobState::Cancelled);
+
+    let requested = queue.cancel(job.id).await.unwrap();
+    assert!(requested.cancel_requested);
+    let job = wait_for(&queue, job.id, |job| job.state.is_finished()).await;
+    assert_eq!(job.state, JobState::Cancelled);
+    assert!(job.progress_pct < 100);
+
+    assert!(matches!(
+        queue.cancel(job.id).await,
+        Err(JobError::Finished { .. })
+    ));
+    assert!(matches!(
+        queue.cancel(Uuid::new_v4()).await,
+        Err(JobError::UnknownJob(_))
+    ));
+    assert_eq!(metrics.get_counter("jobs.cancelled").await, Some(2));
+    assert_eq!(queue.get(waiting.id).await.unwrap().attempts, 0);
+}
+
+#[tokio::test]
+async fn unfinished_jobs_survive_a_restart() {
+    let dir = std::env::temp_dir().join(format!("jobs-{}", Uuid::new_v4()));
+    let path = dir.join("jobs.jsonl");
+
+    let (pending, cancelled) = {
+        let queue = JobQueue::open(&path, Arc::new(MetricsCollector::new())).unwrap();
+        queue.register(JobKind::Snapshot, Arc::new(Counting)).await;
+        let pending = queue
+            .submit(JobKind::Snapshot, json!({ "steps": 2 }))
+            .await
+            .unwrap();
+        let cancelled = queue
+            .submit(JobKind::Snapshot, json!({ "steps": 2 }))
+            .await
+            .unwrap();
+        queue.cancel(cancelled.id).await.unwrap();
+        (pending.id, cancelled.id)
+    };
+
+    let queue = Arc::new(JobQueue::open(&path, Arc::new(MetricsCollector::new())).unwrap());
+    assert_eq!(queue.list().await.len(), 2);
+    assert_eq!(
+        queue.get(cancelled).await.unwrap().state,
+        JobState::Cancelled
+    );
+    assert_eq!(queue.get(pending).await.unwrap().state, JobState::Queued);
+
+    queue.register(JobKind::Snapshot, Arc::new(Counting)).await;
+    let _worker = queue.clone().start(1);
+    let job = wait_for(&queue, pending, |job| job.state.is_finished()).await;
+    assert_eq!(job.state, JobState::Completed);
+    assert_eq!(job.result, Some(json!({ "counted": 2 })));
+
+    // Reopening compacts the file to one record per job
+    drop(queue);
+    let reopened = JobQueue::open(&path, Arc::new(MetricsCollector::new())).unwrap();
+    assert_eq!(
+        reopened.get(pending).await.unwrap().state,
+        JobState::Completed
+    );
+    let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+    assert_eq!(lines, 2);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn jobs_can_be_polled_over_http() {
+    let (manager, shard_id) = shard_with_two_groups().await;
+    let queue = Arc::new(JobQueue::new(Arc::new(MetricsCollector::new())));
+    queue
+        .register(
+            JobKind::Clustering,
+            Arc::new(ClusteringJob::new(manager.clone())),
+        )
+        .await;
+    let _worker = queue.clone().start(1);
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    )
+    .with_job_queue(queue.clone());
+    let filter = server.filter();
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/jobs")
+        .json(&json!({
+            "kind": "clustering",
+            "params": { "shard_id": shard_id, "threshold": 1.0 }
+        }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    let job: Job = serde_json::from_slice(resp.body()).unwrap();
+
+    wait_for(&queue, job.id, |job| job.state.is_finished()).await;
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!("/api/jobs/{}", job.id))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["state"], "completed");
+    assert_eq!(body["progress_pct"], 100);
+    assert_eq!(body["result"]["clusters"], 2);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path(&format!("/api/jobs/{}/cancel", job.id))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!("/api/jobs/{}", Uuid::new_v4()))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/jobs")
+        .json(&json!({ "kind": "snapshot" }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use amazon_rose_forest::darwin::chat::{
+    ChatBackend, ChatClient, ChatMessage, ChatRequest, HttpChatBackend,
+};
+use amazon_rose_forest::darwin::chat_replay::{
+    fixture_key, ChatFixture, RecordingChatBackend, ReplayChatBackend,
+};
+use amazon_rose_forest::darwin::debate::{CandidateCritic, LlmCritic};
+use amazon_rose_forest::darwin::self_improvement::{CodeChange, Modification};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::Filter;
+
+fn fixture_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("chat-fixtures-{}", Uuid::new_v4()))
+}
+
+fn candidate(name: &str, diff: &str) -> Modification {
+    Modification {
+        description: format!("{} approach", name),
+        ..common::modification(
+            name,
+            vec![CodeChange {
+                diff: diff.into(),
+                ..common::code_change("src/lib.rs", None, "")
+            }],
+        )
+    }
+}
+
+/// Stand-in for the provider that counts the requests reaching it
+fn provider(calls: Arc<AtomicUsize>) -> String {
+    let route = warp::path!("v1" / "chat" / "completions")
+        .and(warp::post())
+        .map(move || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            warp::reply::json(&json!({
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "content": "Sure. {\"score\": 0.8, \"rationale\": \"handles the empty case\"}"
+                    }
+                }]
+            }))
+        });
+    let (addr, serving) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(serving);
+    format!("http://{}/v1", addr)
+}
+
+#[tokio::test]
+async fn recorded_replies_replay_without_the_provider() {
+    let dir = fixture_dir();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let endpoint = provider(calls.clone());
+    let live: Arc<dyn ChatBackend> = Arc::new(HttpChatBackend::new(&endpoint, None));
+    let mine = candidate("guard", "+ if items.is_empty() { return None; }");
+    let theirs = candidate("default", "+ items.first().copied().unwrap_or_default()");
+
+    let recording = Arc::new(RecordingChatBackend::new(live, &dir).unwrap());
+    let critic = LlmCritic::new(ChatClient::with_backend("critic-model", recording));
+    let recorded = critic.critique(&mine, &theirs).await.unwrap();
+    assert_eq!(recorded.score, 0.8);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let replay = Arc::new(ReplayChatBackend::open(&dir).unwrap());
+    assert_eq!(replay.len(), 1);
+    let critic = LlmCritic::new(ChatClient::with_backend("critic-model", replay.clone()));
+    // Fresh candidates with the same content send the same request
+    let replayed = critic
+        .critique(
+            &candidate("guard", "+ if items.is_empty() { return None; }"),
+            &candidate("default", "+ items.first().copied().unwrap_or_default()"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(replayed.score, recorded.score);
+    assert_eq!(replayed.rationale, recorded.rationale);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Anything that wasn't recorded fails loudly instead of calling out
+    let err = critic.critique(&theirs, &mine).await.unwrap_err();
+    assert!(err.to_string().contains("ROSE_FOREST_LLM_FIXTURES=record"));
+
+    // A different model is a different request
+    let other_model = ChatClient::with_backend("other-model", replay);
+    assert!(other_model
+        .complete(&[ChatMessage::user("hello")])
+        .await
+        .is_err());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn hand_written_fixtures_match_on_request_content() {
+    let dir = fixture_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    let request = ChatRequest {
+        model: "reasoner".into(),
+        messages: vec![
+            ChatMessage::system("Reply with JSON."),
+            ChatMessage::user("What is next?"),
+        ],
+        temperature: 0.0,
+    };
+    let fixture = ChatFixture {
+        request: request.clone(),
+        reply: "{\"action\":\"finish\",\"thought\":\"done\",\"answer\":\"ship it\"}".into(),
+    };
+    std::fs::write(
+        dir.join("finish-immediately.json"),
+        serde_json::to_string_pretty(&fixture).unwrap(),
+    )
+    .unwrap();
+    std::fs::write(dir.join("notes.txt"), "not a fixture").unwrap();
+
+    let replay = Arc::new(ReplayChatBackend::open(&dir).unwrap());
+    assert_eq!(replay.len(), 1);
+    assert_eq!(replay.complete(&request).await.unwrap(), fixture.reply);
+    assert_eq!(fixture_key(&request), fixture_key(&request.clone()));
+
+    let chat = ChatClient::with_backend("reasoner", replay);
+    let reply = chat
+        .complete(&[
+            ChatMessage::system("Reply with JSON."),
+            ChatMessage::user("What is next?"),
+        ])
+        .await
+        .unwrap();
+    assert!(reply.contains("ship it"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
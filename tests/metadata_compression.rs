@@ -0,0 +1,196 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::query::QueryExpr;
+use amazon_rose_forest::sharding::compression::{self, Codec, CompressionConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn large_text() -> String {
+    "the quick brown fox jumps over the lazy dog. ".repeat(100)
+}
+
+#[test]
+fn codecs_round_trip_values_over_the_threshold() {
+    for codec in [Codec::Lz4, Codec::Zstd] {
+        let config = CompressionConfig::new(codec);
+        let text = large_text();
+        let stored = config.compress(&text).unwrap().unwrap();
+        assert!(stored.starts_with(&format!("z:{}:{}:", codec.as_str(), text.len())));
+        assert!(stored.len() < text.len() / 4);
+        assert_eq!(compression::original_len(&stored), text.len());
+        assert_eq!(compression::decompress(&stored).unwrap(), text);
+
+        // Small values are left alone; envelopes are wrapped again so they
+        // read back as written
+        assert!(config.compress("short").unwrap().is_none());
+        let rewrapped = config.compress(&stored).unwrap().unwrap();
+        assert_eq!(compression::decompress(&rewrapped).unwrap(), stored);
+    }
+
+    // Values that merely look like envelopes decode as themselves
+    for plain in ["plain", "z:", "z:gzip:3:abc", "z:lz4:notanumber:abc"] {
+        assert!(!compression::is_compressed(plain));
+        assert_eq!(compression::decompress(plain).unwrap(), plain);
+    }
+}
+
+#[test]
+fn lookalike_values_are_escaped_and_oversized_envelopes_refused() {
+    let config = CompressionConfig::new(Codec::Lz4);
+
+    // A short value shaped like an envelope is wrapped so it reads back as
+    // written
+    let lookalike = format!("z:lz4:5:{}", "x".repeat(8));
+    let stored = config.compress(&lookalike).unwrap().unwrap();
+    assert_ne!(stored, lookalike);
+    assert_eq!(config.decompress(&stored).unwrap(), lookalike);
+
+    // Envelopes claiming more than the limit aren't decoded
+    let small = CompressionConfig {
+        threshold_bytes: 16,
+        max_value_bytes: 1024,
+        ..CompressionConfig::new(Codec::Zstd)
+    };
+    let huge = format!("z:zstd:{}:AAAA", usize::MAX / 2);
+    assert!(small.decompress(&huge).is_err());
+    let mut metadata = HashMap::from([
+        ("huge".to_string(), huge.clone()),
+        (
+            "body".to_string(),
+            small.compress(&"a".repeat(512)).unwrap().unwrap(),
+        ),
+    ]);
+    assert_eq!(small.decompress_metadata(&mut metadata), 1);
+    assert_eq!(metadata["huge"], huge);
+    assert_eq!(metadata["body"], "a".repeat(512));
+
+    // Values too long to decode again are stored as-is
+    assert!(small.compress(&"a".repeat(2048)).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn only_compressed_shards_decode_envelopes() {
+    let manager = ShardManager::new(Arc::new(MetricsCollector::new()));
+    let plain_shard = manager.create_shard("plain").await.unwrap();
+    let packed_shard = manager.create_shard("packed").await.unwrap();
+    for shard_id in [plain_shard, packed_shard] {
+        manager
+            .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+            .await
+            .unwrap();
+    }
+    manager
+        .set_metadata_compression(packed_shard, Some(CompressionConfig::new(Codec::Lz4)))
+        .await
+        .unwrap();
+
+    // A real envelope written by a client comes back verbatim either way
+    let envelope = CompressionConfig {
+        threshold_bytes: 0,
+        ..CompressionConfig::new(Codec::Lz4)
+    }
+    .compress(&large_text())
+    .unwrap()
+    .unwrap();
+    for shard_id in [plain_shard, packed_shard] {
+        let metadata = HashMap::from([("note".to_string(), envelope.clone())]);
+        let id = manager
+            .add_vector(shard_id, Vector::new(vec![0.1, 0.1]), Some(metadata))
+            .await
+            .unwrap();
+        let (_, entry) = manager.find_vector(id, Some(shard_id)).await.unwrap();
+        assert_eq!(entry.metadata.unwrap()["note"], envelope);
+        let results = manager
+            .search_vectors(shard_id, &Vector::new(vec![0.1, 0.1]), 1)
+            .await
+            .unwrap();
+        assert_eq!(results[0].metadata.as_ref().unwrap()["note"], envelope);
+    }
+
+    // The plain shard now holds a lookalike, so compressing it would change
+    // what that value reads back as
+    assert!(manager
+        .set_metadata_compression(plain_shard, Some(CompressionConfig::new(Codec::Lz4)))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn shard_compresses_large_metadata_transparently() {
+    let manager = ShardManager::new(Arc::new(MetricsCollector::new()));
+    let shard_id = manager.create_shard("articles").await.unwrap();
+    let index = manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    manager
+        .set_metadata_compression(
+            shard_id,
+            Some(CompressionConfig {
+                threshold_bytes: 256,
+                ..CompressionConfig::new(Codec::Zstd)
+            }),
+        )
+        .await
+        .unwrap();
+
+    let body = large_text();
+    let metadata = HashMap::from([
+        ("title".to_string(), "Foxes".to_string()),
+        ("body".to_string(), body.clone()),
+    ]);
+    let id = manager
+        .add_vector(shard_id, Vector::new(vec![0.3, 0.3]), Some(metadata))
+        .await
+        .unwrap();
+
+    // Stored compressed; short fields untouched
+    let stored = index.get(id).await.unwrap().metadata.unwrap();
+    assert!(compression::is_compressed(&stored["body"]));
+    assert_eq!(stored["title"], "Foxes");
+
+    // Filters see the original value and results come back decoded
+    let filter: QueryExpr =
+        serde_json::from_value(json!({"field": {"key": "body", "op": "eq", "value": body}}))
+            .unwrap();
+    let results = manager
+        .search_vectors_filtered(shard_id, &Vector::new(vec![0.3, 0.3]), 1, Some(&filter))
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].metadata.as_ref().unwrap()["body"], body);
+
+    let bytes = index.statistics().await.metadata_bytes;
+    assert_eq!(bytes.compressed_values, 1);
+    assert_eq!(
+        bytes.raw,
+        ("title".len() + 5 + "body".len() + body.len()) as u64
+    );
+    assert!(bytes.compression_ratio > 4.0);
+
+    // Turning compression off affects new values only
+    manager
+        .set_metadata_compression(shard_id, None)
+        .await
+        .unwrap();
+    let metadata = HashMap::from([("body".to_string(), body.clone())]);
+    let plain = manager
+        .add_vector(shard_id, Vector::new(vec![0.3, 0.3]), Some(metadata))
+        .await
+        .unwrap();
+    assert_eq!(
+        index.get(plain).await.unwrap().metadata.unwrap()["body"],
+        body
+    );
+    assert!(compression::is_compressed(
+        &index.get(id).await.unwrap().metadata.unwrap()["body"]
+    ));
+
+    manager.remove_vector(shard_id, id).await.unwrap();
+    let bytes = index.statistics().await.metadata_bytes;
+    assert_eq!(bytes.compressed_values, 0);
+    assert_eq!(bytes.compression_ratio, 1.0);
+}
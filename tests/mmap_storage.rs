@@ -0,0 +1,151 @@
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::sharding::mmap::{
+    AccessPattern, MmapStorage, MmapVectorFile, MmapVectorWriter,
+};
+use amazon_rose_forest::sharding::segments::SegmentConfig;
+use amazon_rose_forest::sharding::vector_index::{DistanceMetric, VectorIndex};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+fn scratch_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("rose-forest-mmap-{}", Uuid::new_v4()))
+}
+
+fn segment_files(dir: &PathBuf) -> usize {
+    std::fs::read_dir(dir)
+        .map(|entries| entries.count())
+        .unwrap_or(0)
+}
+
+#[test]
+fn vector_files_round_trip() {
+    let dir = scratch_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("rows.vec");
+
+    let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+    let mut writer = MmapVectorWriter::create(&path, 3).unwrap();
+    for (i, id) in ids.iter().enumerate() {
+        writer.push(*id, &[i as f32, 0.5, -1.0]).unwrap();
+    }
+    assert!(writer.push(Uuid::new_v4(), &[1.0]).is_err());
+    let file = writer.finish().unwrap();
+
+    assert_eq!(file.len(), 3);
+    assert_eq!(file.dimensions(), 3);
+    assert_eq!(file.read(1).unwrap().0, ids[1]);
+    assert_eq!(file.read(2).unwrap().1, vec![2.0, 0.5, -1.0]);
+    file.advise(AccessPattern::Sequential).unwrap();
+    file.advise(AccessPattern::Random).unwrap();
+    drop(file);
+
+    // Reopening reads the same rows; dropping a reopened map keeps the file
+    let reopened = MmapVectorFile::open(&path).unwrap();
+    assert_eq!(reopened.read(0).unwrap().0, ids[0]);
+    drop(reopened);
+    assert!(path.exists());
+
+    // Truncated files and foreign files are rejected
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &bytes[..bytes.len() - 4]).unwrap();
+    assert!(MmapVectorFile::open(&path).is_err());
+    std::fs::write(&path, b"not a vector file at all").unwrap();
+    assert!(MmapVectorFile::open(&path).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn mapped_segments_serve_reads_and_merges() {
+    let dir = scratch_dir();
+    let index = VectorIndex::new("mapped", 2, DistanceMetric::Euclidean, None).unwrap();
+    index
+        .set_segment_config(SegmentConfig {
+            memtable_limit: 4,
+            max_segments: 2,
+            merge_factor: 2,
+            max_tombstone_ratio: 0.5,
+        })
+        .await;
+    index
+        .set_mmap_storage(Some(MmapStorage::new(&dir)))
+        .await
+        .unwrap();
+    assert_eq!(index.mmap_storage().await.unwrap().dir, dir);
+
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        let metadata = HashMap::from([("n".to_string(), i.to_string())]);
+        let vector = Vector::new(vec![i as f32, 0.0]);
+        ids.push(index.add(vector, Some(metadata)).await.unwrap());
+    }
+
+    let stats = index.segment_stats().await;
+    assert_eq!(stats.segments.len(), 2);
+    assert!(stats.segments.iter().all(|s| s.mapped && !s.pinned));
+    assert_eq!(segment_files(&dir), 2);
+
+    let entry = index.get(ids[5]).await.unwrap();
+    assert_eq!(entry.vector.values, vec![5.0, 0.0]);
+    assert_eq!(entry.metadata.unwrap()["n"], "5");
+    let results = index.search(&Vector::new(vec![3.1, 0.0]), 2).await.unwrap();
+    assert_eq!(results[0].id, ids[3]);
+    assert_eq!(index.entries().await.len(), 10);
+
+    index.remove(ids[1]).await.unwrap();
+    let report = index.merge_segments(true).await.unwrap();
+    assert_eq!(report.dropped, 1);
+    let stats = index.segment_stats().await;
+    assert_eq!(stats.segments.len(), 1);
+    assert!(stats.segments[0].mapped);
+    // Merged inputs delete their files once nothing reads them
+    assert_eq!(segment_files(&dir), 1);
+    assert_eq!(index.count().await, 9);
+    assert!(index.get(ids[1]).await.is_none());
+    assert!(index.verify_integrity(false).await.is_consistent());
+
+    drop(index);
+    assert_eq!(segment_files(&dir), 0);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn only_the_newest_segments_are_pinned() {
+    let dir = scratch_dir();
+    let index = VectorIndex::new("pinned", 2, DistanceMetric::Euclidean, None).unwrap();
+    index
+        .set_segment_config(SegmentConfig {
+            memtable_limit: 2,
+            max_segments: 8,
+            ..SegmentConfig::default()
+        })
+        .await;
+    index
+        .set_mmap_storage(Some(MmapStorage::new(&dir).with_pinned_segments(1)))
+        .await
+        .unwrap();
+    for i in 0..6 {
+        index
+            .add(Vector::new(vec![i as f32, 1.0]), None)
+            .await
+            .unwrap();
+    }
+
+    // Pinning can fail under a low `ulimit -l`; at most the newest is locked
+    let stats = index.segment_stats().await;
+    assert_eq!(stats.segments.len(), 3);
+    assert!(stats.segments.iter().all(|s| s.mapped));
+    assert!(stats.segments[..2].iter().all(|s| !s.pinned));
+
+    // Switching back to the heap keeps existing segments readable
+    index.set_mmap_storage(None).await.unwrap();
+    index.add(Vector::new(vec![9.0, 9.0]), None).await.unwrap();
+    index.add(Vector::new(vec![8.0, 8.0]), None).await.unwrap();
+    let stats = index.segment_stats().await;
+    assert!(!stats.segments.last().unwrap().mapped);
+    assert_eq!(index.count().await, 8);
+
+    drop(index);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,158 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::intelligence::federated_learning::{Client, FederatedLearning, Model};
+use amazon_rose_forest::intelligence::model_registry::{
+    ModelLineage, ModelRegistry, ModelVersion, ModelVersionSummary,
+};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::utils::errors::ModelRegistryError;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use warp::http::StatusCode;
+
+fn coordinator() -> FederatedLearning {
+    let mut fl = FederatedLearning::new(3, 0.1);
+    fl.add_client(Client::new("peer-b", 3, Vec::new()));
+    fl.add_client(Client::new("peer-a", 3, Vec::new()));
+    fl
+}
+
+fn model(weights: Vec<f32>) -> Model {
+    Model { weights }
+}
+
+#[tokio::test]
+async fn versions_record_their_federated_lineage() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let registry = ModelRegistry::new(metrics.clone());
+    let mut fl = coordinator();
+
+    let rounds = fl.train(2);
+    assert_eq!(fl.rounds_completed(), 2);
+    let lineage =
+        ModelLineage::from_rounds(&rounds, HashMap::from([("accuracy".to_string(), 0.9)]));
+    assert_eq!(lineage.round_ids, vec![1, 2]);
+    assert_eq!(lineage.peers, vec!["peer-a", "peer-b"]);
+
+    let first = registry
+        .publish("ranker", &fl.global_model, lineage)
+        .await
+        .unwrap();
+    assert_eq!((first.version, first.parent), (1, None));
+
+    let rounds = fl.train(1);
+    let second = registry
+        .publish(
+            "ranker",
+            &fl.global_model,
+            ModelLineage::from_rounds(&rounds, HashMap::new()),
+        )
+        .await
+        .unwrap();
+    assert_eq!((second.version, second.parent), (2, Some(1)));
+    assert_eq!(second.lineage.round_ids, vec![3]);
+    assert_eq!(registry.current("ranker").await.unwrap().version, 2);
+
+    assert!(matches!(
+        registry
+            .publish("ranker", &model(vec![0.0; 5]), ModelLineage::default())
+            .await,
+        Err(ModelRegistryError::DimensionMismatch {
+            expected: 3,
+            actual: 5,
+            ..
+        })
+    ));
+    assert_eq!(
+        metrics.get_counter("intelligence.models.published").await,
+        Some(2)
+    );
+}
+
+#[tokio::test]
+async fn rollback_keeps_later_versions() {
+    let registry = ModelRegistry::new(Arc::new(MetricsCollector::new()));
+    for weights in [vec![1.0], vec![2.0], vec![3.0]] {
+        registry
+            .publish("ranker", &model(weights), ModelLineage::default())
+            .await
+            .unwrap();
+    }
+
+    let restored = registry.rollback("ranker", 1).await.unwrap();
+    assert_eq!(restored.weights, vec![1.0]);
+    assert_eq!(registry.current("ranker").await.unwrap().version, 1);
+
+    let versions = registry.versions("ranker").await.unwrap();
+    assert_eq!(versions.len(), 3);
+    assert!(versions[0].current && !versions[2].current);
+
+    // The next version builds on the rolled-back one
+    let next = registry
+        .publish("ranker", &model(vec![4.0]), ModelLineage::default())
+        .await
+        .unwrap();
+    assert_eq!((next.version, next.parent), (4, Some(1)));
+
+    assert!(matches!(
+        registry.rollback("ranker", 9).await,
+        Err(ModelRegistryError::UnknownVersion { version: 9, .. })
+    ));
+    assert!(matches!(
+        registry.rollback("nope", 1).await,
+        Err(ModelRegistryError::UnknownModel(_))
+    ));
+}
+
+#[tokio::test]
+async fn clients_download_models_over_http() {
+    let registry = Arc::new(ModelRegistry::new(Arc::new(MetricsCollector::new())));
+    for weights in [vec![1.0, 1.0], vec![2.0, 2.0]] {
+        registry
+            .publish("ranker", &model(weights), ModelLineage::default())
+            .await
+            .unwrap();
+    }
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    )
+    .with_model_registry(registry);
+    let filter = server.filter();
+
+    let resp = warp::test::request()
+        .path("/api/models/ranker")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let current: ModelVersion = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(current.weights, vec![2.0, 2.0]);
+
+    let resp = warp::test::request()
+        .path("/api/models/ranker/versions")
+        .reply(&filter)
+        .await;
+    let versions: Vec<ModelVersionSummary> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(versions.len(), 2);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/models/ranker/rollback")
+        .json(&json!({ "version": 1 }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = warp::test::request()
+        .path("/api/models/ranker/versions/1")
+        .reply(&filter)
+        .await;
+    let first: ModelVersion = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(first.weights, vec![1.0, 1.0]);
+
+    for path in ["/api/models/other", "/api/models/ranker/versions/7"] {
+        let resp = warp::test::request().path(path).reply(&filter).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
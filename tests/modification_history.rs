@@ -0,0 +1,288 @@
+mod common;
+
+use amazon_rose_forest::core::checksum::{self, Quarantine};
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::history::{HistoryQuery, ModificationHistory};
+use amazon_rose_forest::darwin::lifecycle::LifecycleEventKind;
+use amazon_rose_forest::darwin::self_improvement::{
+    Modification, ModificationStatus, SelfImprovementEngine,
+};
+use amazon_rose_forest::darwin::validation::ValidationPipeline;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use chrono::{Duration, Utc};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+fn history_path() -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("modification-history-{}", Uuid::new_v4()))
+        .join("modifications.jsonl")
+}
+
+fn modification(name: &str, age: Duration) -> Modification {
+    Modification {
+        created_at: Utc::now() - age,
+        ..common::modification(name, Vec::new())
+    }
+}
+
+fn engine_with_history(
+    metrics: Arc<MetricsCollector>,
+    history: Arc<ModificationHistory>,
+) -> SelfImprovementEngine {
+    SelfImprovementEngine::new(
+        metrics.clone(),
+        Arc::new(ValidationPipeline::new(metrics.clone())),
+        Arc::new(ExplorationStrategy::new(metrics)),
+    )
+    .with_modification_history(history)
+}
+
+#[tokio::test]
+async fn history_survives_restarts_and_clones() {
+    let path = history_path();
+    let metrics = Arc::new(MetricsCollector::new());
+    let engine = engine_with_history(
+        metrics.clone(),
+        Arc::new(ModificationHistory::open(&path).unwrap()),
+    );
+
+    let accepted = engine
+        .propose_modification(modification("cache-hints", Duration::hours(2)))
+        .await
+        .unwrap();
+    assert!(engine.validate_modification(accepted).await.unwrap());
+    // Recorded without the background validation a proposal gets
+    let pending = modification("tune-probes", Duration::zero());
+    engine
+        .modification_history()
+        .insert(std::slice::from_ref(&pending))
+        .await;
+    let pending = pending.id;
+
+    // Clones see the same history instead of starting empty
+    assert_eq!(
+        engine
+            .clone()
+            .get_modification(accepted)
+            .await
+            .unwrap()
+            .status,
+        ModificationStatus::Accepted
+    );
+
+    let restarted =
+        engine_with_history(metrics, Arc::new(ModificationHistory::open(&path).unwrap()));
+    assert_eq!(restarted.get_all_modifications().await.len(), 2);
+    assert_eq!(
+        restarted.get_modification(accepted).await.unwrap().status,
+        ModificationStatus::Accepted
+    );
+
+    let by_status = restarted
+        .query_modifications(&HistoryQuery {
+            status: Some(ModificationStatus::Proposed),
+            ..HistoryQuery::default()
+        })
+        .await;
+    assert_eq!(by_status.len(), 1);
+    assert_eq!(by_status[0].id, pending);
+
+    let last_hour = restarted
+        .query_modifications(&HistoryQuery {
+            from: Some(Utc::now() - Duration::hours(1)),
+            ..HistoryQuery::default()
+        })
+        .await;
+    assert_eq!(last_hour.len(), 1);
+    assert_eq!(last_hour[0].id, pending);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[tokio::test]
+async fn compaction_keeps_the_newest_modifications() {
+    let path = history_path();
+    let history = ModificationHistory::open(&path).unwrap().with_capacity(2);
+
+    let oldest = modification("oldest", Duration::hours(3));
+    let middle = modification("middle", Duration::hours(2));
+    let newest = modification("newest", Duration::hours(1));
+    history
+        .insert(&[oldest.clone(), middle.clone(), newest.clone()])
+        .await;
+    assert_eq!(history.len().await, 2);
+    assert!(history.get(oldest.id).await.is_none());
+
+    for status in [ModificationStatus::Validating, ModificationStatus::Accepted] {
+        history
+            .update(newest.id, |m| m.status = status)
+            .await
+            .unwrap();
+    }
+    history
+        .update(middle.id, |m| m.status = ModificationStatus::Rejected)
+        .await
+        .unwrap();
+
+    // Five snapshots exceed twice the capacity, so the file was rewritten
+    let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+    assert_eq!(lines, 2);
+
+    let reopened = ModificationHistory::open(&path).unwrap();
+    let ids: Vec<Uuid> = reopened.all().await.iter().map(|m| m.id).collect();
+    assert_eq!(ids, vec![middle.id, newest.id]);
+    assert_eq!(
+        reopened.get(newest.id).await.unwrap().status,
+        ModificationStatus::Accepted
+    );
+
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[tokio::test]
+async fn history_endpoint_filters_by_status_and_date() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let engine = Arc::new(engine_with_history(
+        metrics.clone(),
+        Arc::new(ModificationHistory::new()),
+    ));
+    let old = engine
+        .propose_modification(modification("old", Duration::days(2)))
+        .await
+        .unwrap();
+    assert!(engine.validate_modification(old).await.unwrap());
+    let recent = engine
+        .propose_modification(modification("recent", Duration::zero()))
+        .await
+        .unwrap();
+    assert!(engine.validate_modification(recent).await.unwrap());
+    engine
+        .modification_history()
+        .insert(&[modification("queued", Duration::zero())])
+        .await;
+
+    let filter = Server::new(ServerConfig::default(), metrics, None, None)
+        .with_self_improvement_engine(engine)
+        .filter();
+    let since = (Utc::now() - Duration::days(1)).format("%Y-%m-%dT%H:%M:%SZ");
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!(
+            "/api/darwin/modifications?status=Accepted&from={}",
+            since
+        ))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Vec<Modification> = serde_json::from_slice(resp.body()).unwrap();
+    let ids: Vec<Uuid> = body.iter().map(|m| m.id).collect();
+    assert_eq!(ids, vec![recent]);
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/api/darwin/modifications")
+        .reply(&filter)
+        .await;
+    let body: Vec<Modification> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.len(), 3);
+}
+
+#[tokio::test]
+async fn compaction_keeps_deployed_and_pinned_modifications() {
+    let path = history_path();
+    let history = ModificationHistory::open(&path).unwrap().with_capacity(3);
+
+    let mut deployed = modification("deployed", Duration::hours(5));
+    deployed.status = ModificationStatus::Deployed;
+    let pinned = modification("pinned", Duration::hours(4));
+    let old = modification("old", Duration::hours(3));
+    history.insert(&[deployed.clone(), pinned.clone()]).await;
+    history.pin(&[pinned.id]).await;
+    history.insert(std::slice::from_ref(&old)).await;
+    let newer = modification("newer", Duration::hours(2));
+    history.insert(std::slice::from_ref(&newer)).await;
+
+    // Only unprotected modifications make room
+    let ids: Vec<Uuid> = history.all().await.iter().map(|m| m.id).collect();
+    assert_eq!(ids, vec![deployed.id, pinned.id, newer.id]);
+
+    // The dropped modification stays dropped before any compaction
+    let reopened = ModificationHistory::open(&path).unwrap();
+    let ids: Vec<Uuid> = reopened.all().await.iter().map(|m| m.id).collect();
+    assert_eq!(ids, vec![deployed.id, pinned.id, newer.id]);
+    drop(reopened);
+
+    // Pins and deployments stay protected after a restart
+    let reopened = ModificationHistory::open(&path).unwrap().with_capacity(2);
+    let ids: Vec<Uuid> = reopened.all().await.iter().map(|m| m.id).collect();
+    assert_eq!(ids, vec![deployed.id, pinned.id]);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[tokio::test]
+async fn unreadable_snapshots_are_quarantined() {
+    let path = history_path();
+    let kept = modification("kept", Duration::zero());
+    ModificationHistory::open(&path)
+        .unwrap()
+        .insert(std::slice::from_ref(&kept))
+        .await;
+
+    // Intact checksum, but not a modification
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    writeln!(file, "{}", checksum::seal(r#"{"id":"not-a-modification"}"#)).unwrap();
+    drop(file);
+
+    let reopened = ModificationHistory::open(&path).unwrap();
+    assert_eq!(reopened.quarantined_records(), 1);
+    assert_eq!(reopened.get(kept.id).await.unwrap().name, "kept");
+    let quarantined = Quarantine::beside(&path).records().unwrap();
+    assert_eq!(quarantined.len(), 1);
+    assert_eq!(quarantined[0].position, 2);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[tokio::test]
+async fn proposals_are_validated_without_being_asked() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let engine = engine_with_history(metrics, Arc::new(ModificationHistory::new()));
+    let id = engine
+        .propose_modification(modification("generated", Duration::zero()))
+        .await
+        .unwrap();
+
+    let mut status = ModificationStatus::Proposed;
+    for _ in 0..100 {
+        status = engine.get_modification(id).await.unwrap().status;
+        if status == ModificationStatus::Accepted {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(status, ModificationStatus::Accepted);
+
+    // Validating the returned id right away doesn't validate it twice
+    let second = engine
+        .propose_modification(modification("second", Duration::zero()))
+        .await
+        .unwrap();
+    assert!(engine.validate_modification(second).await.unwrap());
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let started = engine
+        .modification_timeline(second)
+        .await
+        .iter()
+        .filter(|e| matches!(e.kind, LifecycleEventKind::ValidationStarted))
+        .count();
+    assert_eq!(started, 1);
+}
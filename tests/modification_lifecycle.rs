@@ -0,0 +1,139 @@
+mod common;
+
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::lifecycle::{LifecycleEventKind, LifecycleLog};
+use amazon_rose_forest::darwin::self_improvement::{Modification, SelfImprovementEngine};
+use amazon_rose_forest::darwin::validation::{ValidationPipeline, ValidationStage};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+struct FixedStage;
+
+impl ValidationStage for FixedStage {
+    fn name(&self) -> &str {
+        "unit_tests"
+    }
+
+    fn validate(&self, _modification: &Modification) -> anyhow::Result<HashMap<String, f32>> {
+        Ok(HashMap::from([("pass_rate".to_string(), 1.0)]))
+    }
+}
+
+fn modification() -> Modification {
+    common::modification("tune-cache", Vec::new())
+}
+
+#[tokio::test]
+async fn validation_produces_a_timeline() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let mut pipeline = ValidationPipeline::new(metrics.clone());
+    pipeline.add_stage(FixedStage);
+    pipeline.set_threshold("unit_tests.pass_rate", 0.9);
+    let engine = SelfImprovementEngine::new(
+        metrics.clone(),
+        Arc::new(pipeline),
+        Arc::new(ExplorationStrategy::new(metrics)),
+    );
+    let mut events = engine.lifecycle_log().subscribe();
+
+    let id = engine.propose_modification(modification()).await.unwrap();
+    assert!(engine.validate_modification(id).await.unwrap());
+
+    let kinds: Vec<_> = engine
+        .modification_timeline(id)
+        .await
+        .into_iter()
+        .map(|e| e.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            LifecycleEventKind::Proposed {
+                name: "tune-cache".into()
+            },
+            LifecycleEventKind::ValidationStarted,
+            LifecycleEventKind::StagePassed {
+                stage: "unit_tests".into()
+            },
+            LifecycleEventKind::Accepted,
+        ]
+    );
+    let first = events.recv().await.unwrap();
+    assert_eq!(first.modification_id, id);
+}
+
+#[tokio::test]
+async fn persisted_log_survives_reopen() {
+    let path = std::env::temp_dir().join(format!("lifecycle-{}/events.jsonl", Uuid::new_v4()));
+    let id = Uuid::new_v4();
+    {
+        let log = LifecycleLog::open(&path).unwrap();
+        log.record(id, LifecycleEventKind::Deployed).await;
+        log.record(
+            id,
+            LifecycleEventKind::RolledBack {
+                reason: "latency regression".into(),
+            },
+        )
+        .await;
+    }
+
+    let log = LifecycleLog::open(&path).unwrap();
+    let timeline = log.timeline(id).await;
+    assert_eq!(timeline.len(), 2);
+    assert_eq!(timeline[1].sequence, 1);
+    let next = log
+        .record(Uuid::new_v4(), LifecycleEventKind::Accepted)
+        .await;
+    assert_eq!(next.sequence, 2);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[tokio::test]
+async fn timeline_endpoint() {
+    let log = Arc::new(LifecycleLog::new());
+    let id = Uuid::new_v4();
+    log.record(id, LifecycleEventKind::ValidationStarted).await;
+    log.record(
+        id,
+        LifecycleEventKind::StageFailed {
+            stage: "security".into(),
+            error: "audit failed".into(),
+        },
+    )
+    .await;
+
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    )
+    .with_lifecycle_log(log);
+    let filter = server.filter();
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!("/api/darwin/modifications/{}/timeline", id))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["events"][1]["event"], "stage_failed");
+    assert_eq!(body["events"][1]["stage"], "security");
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!(
+            "/api/darwin/modifications/{}/timeline",
+            Uuid::new_v4()
+        ))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
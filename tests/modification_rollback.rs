@@ -0,0 +1,188 @@
+mod common;
+
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::self_improvement::{
+    CodeChange, Modification, ModificationStatus, SelfImprovementEngine,
+};
+use amazon_rose_forest::darwin::validation::ValidationPipeline;
+use amazon_rose_forest::darwin::workspace::WorkspaceApplier;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+fn project() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("modification-rollback-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+    root
+}
+
+fn change(path: &str, original: Option<&str>, modified: &str) -> CodeChange {
+    CodeChange {
+        file_path: path.into(),
+        original_content: original.map(Into::into),
+        modified_content: modified.into(),
+        diff: String::new(),
+        evolution_hooks: Vec::new(),
+        reality_branch: None,
+    }
+}
+
+fn modification(code_changes: Vec<CodeChange>) -> Modification {
+    common::modification("cache-hints", code_changes)
+}
+
+async fn deploy(engine: &SelfImprovementEngine, m: Modification) -> Uuid {
+    let id = engine.propose_modification(m).await.unwrap();
+    assert!(engine.validate_modification(id).await.unwrap());
+    engine.deploy_modification(id).await.unwrap();
+    id
+}
+
+fn engine(root: &Path, metrics: Arc<MetricsCollector>) -> Arc<SelfImprovementEngine> {
+    Arc::new(
+        SelfImprovementEngine::new(
+            metrics.clone(),
+            Arc::new(ValidationPipeline::new(metrics.clone())),
+            Arc::new(ExplorationStrategy::new(metrics)),
+        )
+        .with_workspace(WorkspaceApplier::new(root.to_path_buf()).without_build_check()),
+    )
+}
+
+#[tokio::test]
+async fn rollback_restores_original_files() {
+    let root = project();
+    let metrics = Arc::new(MetricsCollector::new());
+    let engine = engine(&root, metrics.clone());
+
+    let id = deploy(
+        &engine,
+        modification(vec![
+            change("src/lib.rs", Some("pub fn a() {}\n"), "pub fn b() {}\n"),
+            change("src/hints.rs", None, "pub fn h() {}\n"),
+        ]),
+    )
+    .await;
+    assert!(root.join("src/hints.rs").exists());
+
+    let rolled_back = engine.rollback_modification(id).await.unwrap();
+    assert_eq!(rolled_back.status, ModificationStatus::RolledBack);
+    assert_eq!(
+        std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub fn a() {}\n"
+    );
+    assert!(!root.join("src/hints.rs").exists());
+    assert_eq!(
+        metrics
+            .get_counter("darwin.modifications.rolled_back")
+            .await,
+        Some(1)
+    );
+
+    // Only deployed modifications can be rolled back
+    assert!(engine.rollback_modification(id).await.is_err());
+    assert_eq!(
+        metrics
+            .get_counter("darwin.modifications.rollback_failed")
+            .await,
+        Some(1)
+    );
+
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn rollback_endpoint_refuses_after_manual_edits() {
+    let root = project();
+    let metrics = Arc::new(MetricsCollector::new());
+    let engine = engine(&root, metrics.clone());
+    let id = deploy(
+        &engine,
+        modification(vec![change(
+            "src/lib.rs",
+            Some("pub fn a() {}\n"),
+            "pub fn b() {}\n",
+        )]),
+    )
+    .await;
+    let filter = Server::new(ServerConfig::default(), metrics, None, None)
+        .with_self_improvement_engine(engine.clone())
+        .filter();
+    let rollback = |id: Uuid| {
+        warp::test::request()
+            .method("POST")
+            .path(&format!("/api/darwin/modifications/{}/rollback", id))
+    };
+
+    std::fs::write(root.join("src/lib.rs"), "pub fn edited() {}\n").unwrap();
+    let resp = rollback(id).reply(&filter).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    assert_eq!(
+        std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub fn edited() {}\n"
+    );
+    assert_eq!(
+        engine.get_modification(id).await.unwrap().status,
+        ModificationStatus::Deployed
+    );
+
+    std::fs::write(root.join("src/lib.rs"), "pub fn b() {}\n").unwrap();
+    let resp = rollback(id).reply(&filter).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Modification = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.status, ModificationStatus::RolledBack);
+
+    let resp = rollback(Uuid::new_v4()).reply(&filter).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn rollback_keeps_files_that_existed_before() {
+    let root = project();
+    std::fs::write(root.join("src/empty.rs"), "").unwrap();
+    let metrics = Arc::new(MetricsCollector::new());
+    let engine = engine(&root, metrics);
+
+    // An empty file is still a file: rolling back empties it again
+    let id = deploy(
+        &engine,
+        modification(vec![change("src/empty.rs", Some(""), "pub fn e() {}\n")]),
+    )
+    .await;
+    let deployed = engine.get_modification(id).await.unwrap();
+    assert_eq!(
+        deployed.code_changes[0].original_content.as_deref(),
+        Some("")
+    );
+    engine.rollback_modification(id).await.unwrap();
+    assert_eq!(
+        std::fs::read_to_string(root.join("src/empty.rs")).unwrap(),
+        ""
+    );
+
+    // A creation over an existing file is refused rather than recorded as
+    // something a rollback would delete
+    let id = engine
+        .propose_modification(modification(vec![change(
+            "src/lib.rs",
+            None,
+            "pub fn b() {}\n",
+        )]))
+        .await
+        .unwrap();
+    assert!(engine.validate_modification(id).await.unwrap());
+    assert!(engine.deploy_modification(id).await.is_err());
+    assert!(engine.rollback_modification(id).await.is_err());
+    assert_eq!(
+        std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub fn a() {}\n"
+    );
+
+    std::fs::remove_dir_all(root).ok();
+}
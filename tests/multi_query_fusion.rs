@@ -0,0 +1,170 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::query::fusion::{fuse, FusionStrategy};
+use amazon_rose_forest::server::api::SearchVectorsResponse;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::{DistanceMetric, SearchResult};
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+fn result(id: Uuid, score: f32) -> SearchResult {
+    SearchResult {
+        id,
+        vector: Vector::new(vec![0.0]),
+        metadata: None,
+        score,
+    }
+}
+
+#[test]
+fn strategies_score_shared_results_differently() {
+    let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    // Distances, so lower is better
+    let lists = || {
+        vec![
+            vec![result(a, 0.1), result(b, 0.3)],
+            vec![result(b, 0.2), result(c, 0.5)],
+        ]
+    };
+    let order = |strategy| -> Vec<Uuid> {
+        fuse(lists(), strategy, true, 3)
+            .into_iter()
+            .map(|r| r.id)
+            .collect()
+    };
+
+    assert_eq!(order(FusionStrategy::Max), vec![a, b, c]);
+    // a is missing from the second list, so counts there at its worst (0.5)
+    assert_eq!(order(FusionStrategy::Mean), vec![b, a, c]);
+    let mean = fuse(lists(), FusionStrategy::Mean, true, 1);
+    assert!((mean[0].score - 0.25).abs() < 1e-6);
+    assert_eq!(order(FusionStrategy::Rrf), vec![b, a, c]);
+
+    // Similarities keep the highest score instead
+    let best = fuse(lists(), FusionStrategy::Max, false, 1);
+    assert_eq!((best[0].id, best[0].score), (c, 0.5));
+}
+
+#[tokio::test]
+async fn search_endpoint_fuses_additional_queries() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("aspects").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let mut ids = Vec::new();
+    for values in [[1.0, 0.0], [0.0, 1.0], [0.7, 0.7]] {
+        ids.push(
+            manager
+                .add_vector(shard_id, Vector::new(values.to_vec()), None)
+                .await
+                .unwrap()
+                .to_string(),
+        );
+    }
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    )
+    .filter();
+    let search = |body: serde_json::Value| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/search")
+            .json(&body)
+            .reply(&filter)
+    };
+
+    // The diagonal vector is second for both aspects, which RRF rewards
+    let resp = search(json!({
+        "shard_id": shard_id,
+        "query_vector": [1.0, 0.0],
+        "additional_queries": [[0.0, 1.0]],
+        "fusion": "rrf",
+        "limit": 2,
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: SearchVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.results[0].id, ids[2]);
+
+    // The default keeps each aspect's exact match, scored as a distance
+    // like a single-query search: best first, lowest first
+    let resp = search(json!({
+        "shard_id": shard_id,
+        "query_vector": [1.0, 0.0],
+        "additional_queries": [[0.0, 1.0]],
+        "limit": 3,
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: SearchVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+    let found: HashSet<&str> = body.results[..2].iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(found, HashSet::from([ids[0].as_str(), ids[1].as_str()]));
+    assert_eq!(body.results[2].id, ids[2]);
+    let scores: Vec<f32> = body.results.iter().map(|r| r.score).collect();
+    assert!(scores[0] < 1e-6 && scores[1] < 1e-6, "{scores:?}");
+    assert!(scores[2] > scores[1], "{scores:?}");
+
+    let resp = search(json!({
+        "shard_id": shard_id,
+        "query_vector": [1.0, 0.0],
+        "additional_queries": [[0.0, 1.0, 0.0]],
+        "limit": 2,
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn search_accepts_full_size_embeddings() {
+    const DIM: usize = 768;
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("embeddings").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", DIM, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+    let embedding = |seed: usize| -> Vec<f32> {
+        (0..DIM)
+            .map(|i| ((i * 7919 + seed * 104_729) % 1000) as f32 / 997.0 + 0.123_456_7)
+            .collect()
+    };
+    let id = manager
+        .add_vector(shard_id, Vector::new(embedding(0)), None)
+        .await
+        .unwrap();
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    )
+    .filter();
+
+    // Several full-precision 768-dimensional queries are well over the
+    // limit for small API bodies
+    let body = json!({
+        "shard_id": shard_id,
+        "query_vector": embedding(0),
+        "additional_queries": [embedding(1), embedding(2), embedding(3)],
+        "limit": 1,
+    });
+    assert!(serde_json::to_vec(&body).unwrap().len() > 16 * 1024);
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search")
+        .json(&body)
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: SearchVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.results[0].id, id.to_string());
+}
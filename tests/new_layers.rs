@@ -1,11 +1,13 @@
 use amazon_rose_forest::{
     Ad4mBridge, Dao, FederatedLearning, Introspection, Orchestrator, Swarm, ZKP,
 };
+use std::sync::Arc;
+use tokio::sync::RwLock;
 
-#[test]
-fn instantiate_intelligence_modules() {
-    let _ = FederatedLearning::new();
-    let _ = Orchestrator::new();
+#[tokio::test]
+async fn instantiate_intelligence_modules() {
+    let federated = Arc::new(RwLock::new(FederatedLearning::new(4, 0.01)));
+    let _ = Orchestrator::new(federated).await;
 }
 
 #[test]
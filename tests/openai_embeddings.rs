@@ -0,0 +1,143 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::embedding::registry::SOURCE_TEXT_KEY;
+use amazon_rose_forest::embedding::{EmbeddingRegistry, HashingEmbedder};
+use amazon_rose_forest::server::openai::{EmbeddingValue, EmbeddingsResponse};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use warp::http::StatusCode;
+use warp::Filter;
+
+async fn post<F>(filter: &F, body: Value) -> (StatusCode, Value)
+where
+    F: Filter + 'static,
+    F::Extract: warp::Reply + Send,
+{
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/v1/embeddings")
+        .json(&body)
+        .reply(filter)
+        .await;
+    (resp.status(), serde_json::from_slice(resp.body()).unwrap())
+}
+
+#[tokio::test]
+async fn embeddings_are_served_in_openai_shape_and_captured() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let registry = Arc::new(EmbeddingRegistry::new(manager.clone(), metrics.clone()));
+    let embedder = HashingEmbedder::new(8);
+    let model_id = registry.register_provider(Arc::new(embedder.clone())).await;
+    registry
+        .create_space("docs", &model_id, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+    let filter = Server::new(
+        ServerConfig::default(),
+        metrics,
+        None,
+        Some(manager.clone()),
+    )
+    .with_embedding_registry(registry.clone())
+    .with_embedding_capture("docs")
+    .filter();
+
+    // Hosted model names resolve to the only local model
+    let (status, body) = post(
+        &filter,
+        json!({ "model": "text-embedding-3-small", "input": ["red apples", "green pears"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let response: EmbeddingsResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(response.object, "list");
+    assert_eq!(response.model, model_id);
+    assert_eq!(response.usage.prompt_tokens, 4);
+    assert_eq!(response.data.len(), 2);
+    assert_eq!(response.data[1].index, 1);
+    match &response.data[1].embedding {
+        EmbeddingValue::Float(values) => assert_eq!(values, &embedder.embed_text("green pears")),
+        other => panic!("expected floats, got {:?}", other),
+    }
+
+    // What the SDKs ask for by default
+    let (_, body) = post(
+        &filter,
+        json!({ "model": model_id, "input": "red apples", "encoding_format": "base64" }),
+    )
+    .await;
+    let encoded = body["data"][0]["embedding"].as_str().unwrap();
+    let decoded: Vec<f32> = STANDARD
+        .decode(encoded)
+        .unwrap()
+        .chunks(4)
+        .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+        .collect();
+    assert_eq!(decoded, embedder.embed_text("red apples"));
+
+    // Every input was stored along with its text
+    let space = registry.active_space("docs").await.unwrap();
+    let index = manager.get_vector_index(space.shard_id).await.unwrap();
+    assert_eq!(index.entries().await.len(), 3);
+    let found = registry
+        .search_text("docs", "green pears", 1)
+        .await
+        .unwrap();
+    assert_eq!(
+        found[0].metadata.as_ref().unwrap()[SOURCE_TEXT_KEY],
+        "green pears"
+    );
+}
+
+#[tokio::test]
+async fn requests_the_models_cannot_serve_get_openai_errors() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let unconfigured = Server::new(
+        ServerConfig::default(),
+        metrics.clone(),
+        None,
+        Some(manager.clone()),
+    )
+    .filter();
+    let (status, body) = post(&unconfigured, json!({ "model": "m", "input": "text" })).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["error"]["type"], "server_error");
+
+    let registry = Arc::new(EmbeddingRegistry::new(manager.clone(), metrics.clone()));
+    registry
+        .register_provider(Arc::new(HashingEmbedder::new(4)))
+        .await;
+    registry
+        .register_provider(Arc::new(HashingEmbedder::new(8)))
+        .await;
+    let filter = Server::new(ServerConfig::default(), metrics, None, Some(manager))
+        .with_embedding_registry(registry)
+        .filter();
+
+    // With several models the name must match one
+    let (status, body) = post(&filter, json!({ "model": "hashing-4", "input": "text" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["model"], "hashing-4@1");
+    let (status, body) = post(&filter, json!({ "model": "ada", "input": "text" })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["param"], "model");
+
+    let wrong_size = json!({ "model": "hashing-4", "input": "text", "dimensions": 8 });
+    let (status, body) = post(&filter, wrong_size).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["param"], "dimensions");
+    for invalid in [
+        json!({ "model": "hashing-4", "input": [] }),
+        json!({ "model": "hashing-4", "input": [[1, 2, 3]] }),
+    ] {
+        let (status, body) = post(&filter, invalid).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["type"], "invalid_request_error");
+    }
+}
@@ -0,0 +1,118 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::hypothesis::Hypothesis;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::outliers::{OutlierMethod, OutlierParams, OutlierReport};
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+/// A tight 5x4 grid near the origin plus one vector far away from it
+async fn shard_with_outlier() -> (Arc<ShardManager>, Uuid, Uuid) {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("readings").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for i in 0..20 {
+        let values = vec![(i % 5) as f32 * 0.1, (i / 5) as f32 * 0.1];
+        manager
+            .add_vector(shard_id, Vector::new(values), None)
+            .await
+            .unwrap();
+    }
+    let metadata = HashMap::from([("source".to_string(), "sensor-7".to_string())]);
+    let outlier = manager
+        .add_vector(shard_id, Vector::new(vec![10.0, 10.0]), Some(metadata))
+        .await
+        .unwrap();
+    (manager, shard_id, outlier)
+}
+
+#[tokio::test]
+async fn both_methods_rank_the_far_vector_first() {
+    let (manager, shard_id, outlier) = shard_with_outlier().await;
+
+    for params in [
+        OutlierParams::default(),
+        OutlierParams {
+            method: OutlierMethod::Centroid,
+            clusters: 3,
+            ..OutlierParams::default()
+        },
+    ] {
+        let report = manager.find_outliers(shard_id, params).await.unwrap();
+        assert_eq!(report.scanned, 21);
+        assert_eq!(report.outliers.len(), 10);
+        let top = &report.outliers[0];
+        assert_eq!(top.id, outlier, "{:?}", report.method);
+        assert!(top.z_score > 3.0);
+        assert_eq!(top.metadata.as_ref().unwrap()["source"], "sensor-7");
+        assert!(report.outliers[1].score < top.score / 10.0);
+        assert!((report.outlier_fraction - 1.0 / 21.0).abs() < 1e-6);
+    }
+
+    let strict = OutlierParams {
+        min_z_score: Some(3.0),
+        ..OutlierParams::default()
+    };
+    let report = manager.find_outliers(shard_id, strict).await.unwrap();
+    assert_eq!(report.outliers.len(), 1);
+
+    let signals = report.signals();
+    assert!(signals["max_outlier_z_score"] > 3.0);
+    assert_eq!(signals["outlier_scanned"], 21.0);
+
+    let zero_k = OutlierParams {
+        k: 0,
+        ..OutlierParams::default()
+    };
+    assert!(manager.find_outliers(shard_id, zero_k).await.is_err());
+}
+
+#[test]
+fn widespread_outliers_steer_the_hypothesis() {
+    let hypothesis = Hypothesis::new();
+    let quiet = HashMap::from([("outlier_fraction".to_string(), 0.01)]);
+    assert!(!hypothesis.generate(&quiet).contains("outliers"));
+
+    let noisy = HashMap::from([("outlier_fraction".to_string(), 0.2)]);
+    assert!(hypothesis.generate(&noisy).contains("20.0% of vectors"));
+}
+
+#[tokio::test]
+async fn outlier_endpoint_reports_anomalies() {
+    let (manager, shard_id, outlier) = shard_with_outlier().await;
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    );
+    let filter = server.filter();
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/analyze/outliers")
+        .json(&json!({"shard_id": shard_id, "method": "centroid", "limit": 3}))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let report: OutlierReport = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(report.method, OutlierMethod::Centroid);
+    assert_eq!(report.outliers.len(), 3);
+    assert_eq!(report.outliers[0].id, outlier);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/analyze/outliers")
+        .json(&json!({"shard_id": Uuid::new_v4()}))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
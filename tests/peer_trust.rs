@@ -0,0 +1,158 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::intelligence::delegation::{
+    PeerCapacity, PeerHeartbeat, TaskDelegator, TaskKind,
+};
+use amazon_rose_forest::network::trust::{
+    PeerTrust, TrustConfig, TrustEvent, TrustManager, TrustStatus,
+};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::http::StatusCode;
+
+fn heartbeat(peer_id: &str) -> PeerHeartbeat {
+    PeerHeartbeat {
+        capacity: PeerCapacity {
+            peer_id: peer_id.into(),
+            endpoint: format!("http://{}:9000", peer_id),
+            accepts: vec![TaskKind::Embedding],
+            slots: 4,
+        },
+        leases: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn violations_and_bad_signatures_lead_to_quarantine() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let trust = TrustManager::new(metrics.clone());
+    let peer = trust.record("mallory", TrustEvent::SignatureFailure).await;
+    assert_eq!(peer.score, 0.5);
+    assert_eq!(peer.status, TrustStatus::Trusted);
+    assert!(trust.is_allowed("mallory").await);
+
+    let peer = trust.record("mallory", TrustEvent::ProtocolViolation).await;
+    assert!(matches!(peer.status, TrustStatus::Quarantined { .. }));
+    assert_eq!((peer.signature_failures, peer.protocol_violations), (1, 1));
+    assert!(!trust.is_allowed("mallory").await);
+    assert!(trust.is_allowed("stranger").await);
+    assert_eq!(metrics.get_counter("trust.quarantined").await, Some(1));
+}
+
+#[tokio::test]
+async fn errors_cost_trust_only_at_an_excessive_rate() {
+    let trust = TrustManager::new(Arc::new(MetricsCollector::new())).with_config(TrustConfig {
+        min_requests: 4,
+        ..TrustConfig::default()
+    });
+    // One error in four requests is tolerated
+    for event in [
+        TrustEvent::Success,
+        TrustEvent::Success,
+        TrustEvent::Success,
+        TrustEvent::Error,
+    ] {
+        trust.record("flaky", event).await;
+    }
+    assert_eq!(trust.peer("flaky").await.unwrap().score, 1.0);
+
+    let mut peer = trust.record("flaky", TrustEvent::Error).await;
+    for _ in 0..3 {
+        peer = trust.record("flaky", TrustEvent::Error).await;
+    }
+    assert!(peer.error_rate > 0.5);
+    assert!(peer.score < 1.0);
+}
+
+#[tokio::test]
+async fn quarantine_ends_on_probation() {
+    let trust = TrustManager::new(Arc::new(MetricsCollector::new())).with_config(TrustConfig {
+        quarantine_for: Duration::from_millis(20),
+        ..TrustConfig::default()
+    });
+    trust.record("peer", TrustEvent::SignatureFailure).await;
+    trust.record("peer", TrustEvent::SignatureFailure).await;
+    assert!(!trust.is_allowed("peer").await);
+
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    assert!(trust.is_allowed("peer").await);
+    let peer = trust.peer("peer").await.unwrap();
+    assert_eq!(peer.score, 0.5);
+    assert_eq!(peer.quarantines, 1);
+}
+
+#[tokio::test]
+async fn untrusted_peers_get_no_work() {
+    let trust = Arc::new(TrustManager::new(Arc::new(MetricsCollector::new())));
+    let delegator = TaskDelegator::new("origin", Arc::new(MetricsCollector::new()))
+        .without_transport()
+        .with_trust(trust.clone());
+    delegator.heartbeat(heartbeat("blocked")).await;
+    trust.block("blocked", "compromised key").await;
+
+    delegator.submit(TaskKind::Embedding, json!({})).await;
+    assert!(delegator.dispatch().await.is_empty());
+
+    trust.unblock("blocked").await.unwrap();
+    assert_eq!(delegator.dispatch().await.len(), 1);
+}
+
+#[tokio::test]
+async fn operators_block_and_unblock_peers() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let trust = Arc::new(TrustManager::new(metrics.clone()));
+    let delegator = Arc::new(TaskDelegator::new("origin", metrics.clone()).without_transport());
+    let filter = Server::new(ServerConfig::default(), metrics, None, None)
+        .with_task_delegator(delegator)
+        .with_trust_manager(trust)
+        .filter();
+    let send_heartbeat = |peer_id: &str| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/cluster/heartbeat")
+            .json(&heartbeat(peer_id))
+            .reply(&filter)
+    };
+
+    assert_eq!(send_heartbeat("peer-a").await.status(), StatusCode::OK);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/admin/peers/peer-a/block")
+        .json(&json!({ "reason": "sending garbage" }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        send_heartbeat("peer-a").await.status(),
+        StatusCode::FORBIDDEN
+    );
+
+    let resp = warp::test::request()
+        .path("/api/admin/peers")
+        .reply(&filter)
+        .await;
+    let peers: Vec<PeerTrust> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(
+        peers[0].status,
+        TrustStatus::Blocked {
+            reason: "sending garbage".into()
+        }
+    );
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/admin/peers/peer-a/unblock")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(send_heartbeat("peer-a").await.status(), StatusCode::OK);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/admin/peers/unknown/unblock")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
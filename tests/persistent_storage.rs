@@ -0,0 +1,256 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::nerv::runtime::Runtime;
+use amazon_rose_forest::query::Diversification;
+use amazon_rose_forest::sharding::manager::{IdScheme, ShardManager};
+use amazon_rose_forest::sharding::storage::{
+    FileStorage, PersistenceConfig, ShardRecord, StorageBackend, StorageEngine,
+};
+use amazon_rose_forest::sharding::vector_index::{DistanceMetric, VectorEntry};
+use amazon_rose_forest::Vector;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+fn data_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("rose-forest-storage-{}", Uuid::new_v4()))
+}
+
+fn manager(dir: &Path, metrics: Arc<MetricsCollector>) -> ShardManager {
+    let storage: Arc<dyn StorageBackend> = Arc::new(FileStorage::open(dir).unwrap());
+    ShardManager::new(metrics).with_storage(storage)
+}
+
+#[tokio::test]
+async fn shards_and_vectors_survive_a_restart() {
+    let dir = data_dir();
+    let before = manager(&dir, Arc::new(MetricsCollector::new()));
+    let shard_id = before.create_shard("docs").await.unwrap();
+    before
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+    before
+        .set_id_scheme(shard_id, IdScheme::ContentAddressed)
+        .await
+        .unwrap();
+    for i in 0..8 {
+        let x = i as f32 / 8.0;
+        let metadata = HashMap::from([("n".to_string(), i.to_string())]);
+        before
+            .add_vector(shard_id, Vector::new(vec![x, 1.0 - x]), Some(metadata))
+            .await
+            .unwrap();
+    }
+    let split = before.split_shard(shard_id).await.unwrap();
+    let originals = before
+        .get_vector_index(shard_id)
+        .await
+        .unwrap()
+        .entries()
+        .await;
+    assert_eq!(before.flush().await.unwrap(), 2);
+    drop(before);
+
+    let metrics = Arc::new(MetricsCollector::new());
+    let after = manager(&dir, metrics.clone());
+    assert_eq!(after.restore(true).await.unwrap(), 2);
+    assert_eq!(
+        after.get_shard(shard_id).await.unwrap().vector_count,
+        split.kept
+    );
+    assert_eq!(after.id_scheme(shard_id).await, IdScheme::ContentAddressed);
+    assert_eq!(
+        after.key_routes(shard_id).await,
+        vec![(split.split_key, split.new_shard_id)]
+    );
+
+    // Vectors stay on disk until a shard is first used
+    assert!(after.get_vector_indices().await.is_empty());
+    let index = after.get_vector_index(shard_id).await.unwrap();
+    assert_eq!(metrics.get_counter("storage.shards_loaded").await, Some(1));
+    assert_eq!(index.distance_metric(), DistanceMetric::Cosine);
+    for original in &originals {
+        let restored = index.get(original.id).await.unwrap();
+        assert_eq!(restored.vector.values, original.vector.values);
+        assert_eq!(restored.metadata, original.metadata);
+        assert_eq!(restored.created_at, original.created_at);
+    }
+
+    // Searches addressed to the original still cover the whole family
+    let results = after
+        .search_vectors(shard_id, &Vector::new(vec![1.0, 0.0]), 8)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 8);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn only_changed_shards_are_flushed() {
+    let dir = data_dir();
+    let manager = manager(&dir, Arc::new(MetricsCollector::new()));
+    let first = manager.create_shard("first").await.unwrap();
+    let second = manager.create_shard("second").await.unwrap();
+    for shard_id in [first, second] {
+        manager
+            .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+            .await
+            .unwrap();
+    }
+    assert_eq!(manager.flush().await.unwrap(), 2);
+    assert_eq!(manager.flush().await.unwrap(), 0);
+
+    let id = manager
+        .add_vector(second, Vector::new(vec![0.5, 0.5]), None)
+        .await
+        .unwrap();
+    assert_eq!(manager.flush().await.unwrap(), 1);
+    let stored = FileStorage::open(&dir)
+        .unwrap()
+        .load_vectors(second)
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].id, id);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn runtime_restores_what_it_flushed_on_stop() {
+    let dir = data_dir();
+    let config = PersistenceConfig::new(StorageEngine::File, &dir).with_lazy_load(false);
+
+    let mut runtime =
+        Runtime::new(Arc::new(MetricsCollector::new())).with_persistence(config.clone());
+    runtime.start().await.unwrap();
+    let manager = runtime.shard_manager().unwrap();
+    let shard_id = manager.create_shard("docs").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let id = manager
+        .add_vector(shard_id, Vector::new(vec![1.0, 2.0, 3.0]), None)
+        .await
+        .unwrap();
+    runtime.stop().await.unwrap();
+
+    let mut runtime = Runtime::new(Arc::new(MetricsCollector::new())).with_persistence(config);
+    runtime.start().await.unwrap();
+    let manager = runtime.shard_manager().unwrap();
+    // Loaded eagerly, without being asked for
+    assert_eq!(manager.get_vector_indices().await.len(), 1);
+    assert!(manager
+        .get_vector_index(shard_id)
+        .await
+        .unwrap()
+        .get(id)
+        .await
+        .is_some());
+    runtime.stop().await.unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// File storage whose vector loads wait to be released
+#[derive(Debug)]
+struct GatedStorage {
+    inner: FileStorage,
+    loading: Notify,
+    release: Notify,
+}
+
+#[async_trait]
+impl StorageBackend for GatedStorage {
+    fn name(&self) -> &str {
+        "gated"
+    }
+
+    async fn save_shard(&self, record: &ShardRecord) -> Result<()> {
+        self.inner.save_shard(record).await
+    }
+
+    async fn load_shards(&self) -> Result<Vec<ShardRecord>> {
+        self.inner.load_shards().await
+    }
+
+    async fn save_vectors(&self, shard_id: Uuid, entries: &[VectorEntry]) -> Result<()> {
+        self.inner.save_vectors(shard_id, entries).await
+    }
+
+    async fn load_vectors(&self, shard_id: Uuid) -> Result<Vec<VectorEntry>> {
+        self.loading.notify_one();
+        self.release.notified().await;
+        self.inner.load_vectors(shard_id).await
+    }
+
+    async fn delete_shard(&self, shard_id: Uuid) -> Result<()> {
+        self.inner.delete_shard(shard_id).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.inner.flush().await
+    }
+}
+
+#[tokio::test]
+async fn shards_being_warmed_up_are_searched_as_far_as_loaded() {
+    let dir = data_dir();
+    let before = manager(&dir, Arc::new(MetricsCollector::new()));
+    let shard_id = before.create_shard("docs").await.unwrap();
+    before
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for i in 0..10 {
+        before
+            .add_vector(shard_id, Vector::new(vec![i as f32, 0.0]), None)
+            .await
+            .unwrap();
+    }
+    before.flush().await.unwrap();
+    drop(before);
+
+    let storage = Arc::new(GatedStorage {
+        inner: FileStorage::open(&dir).unwrap(),
+        loading: Notify::new(),
+        release: Notify::new(),
+    });
+    let restored = Arc::new(
+        ShardManager::new(Arc::new(MetricsCollector::new())).with_storage(storage.clone()),
+    );
+    assert_eq!(restored.restore(true).await.unwrap(), 1);
+    let warm_up = restored.clone().warm_up();
+    storage.loading.notified().await;
+
+    let search = || async {
+        restored
+            .search_vectors_within(
+                shard_id,
+                &Vector::new(vec![0.0, 0.0]),
+                5,
+                None,
+                &Diversification::default(),
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+    };
+    // Answered straight away rather than after the load
+    let outcome = search().await;
+    assert_eq!(outcome.coverage, 0.0);
+    assert!(outcome.results.is_empty());
+    assert!(restored.get_vector_indices().await.is_empty());
+
+    storage.release.notify_one();
+    warm_up.await.unwrap();
+    let outcome = search().await;
+    assert_eq!(outcome.coverage, 1.0);
+    assert_eq!(outcome.results.len(), 5);
+    assert_eq!(restored.get_vector_indices().await.len(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
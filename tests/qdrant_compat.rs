@@ -0,0 +1,144 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use warp::http::StatusCode;
+use warp::Filter;
+
+fn server() -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    Server::new(ServerConfig::default(), metrics, None, Some(manager)).filter()
+}
+
+async fn call<F>(filter: &F, method: &str, path: &str, body: Option<Value>) -> (StatusCode, Value)
+where
+    F: Filter + 'static,
+    F::Extract: warp::Reply + Send,
+{
+    let mut request = warp::test::request()
+        .method(method)
+        .path(&format!("/qdrant/collections{}", path));
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+    let resp = request.reply(filter).await;
+    (resp.status(), serde_json::from_slice(resp.body()).unwrap())
+}
+
+#[tokio::test]
+async fn collections_are_created_listed_and_deleted() {
+    let filter = server();
+    let create = json!({ "vectors": { "size": 3, "distance": "Cosine" } });
+    let (status, body) = call(&filter, "PUT", "/docs", Some(create.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["result"], true);
+
+    // Qdrant rejects duplicate names
+    let (status, body) = call(&filter, "PUT", "/docs", Some(create)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["status"]["error"]
+        .as_str()
+        .unwrap()
+        .contains("already exists"));
+    let dot = json!({ "vectors": { "size": 3, "distance": "Dot" } });
+    let (status, _) = call(&filter, "PUT", "/dots", Some(dot)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = call(&filter, "GET", "/dots", None).await;
+    assert_eq!(
+        body["result"]["config"]["params"]["vectors"]["distance"],
+        "Dot"
+    );
+    call(&filter, "DELETE", "/dots", None).await;
+
+    let (_, body) = call(&filter, "GET", "", None).await;
+    assert_eq!(body["result"]["collections"], json!([{ "name": "docs" }]));
+    let (_, body) = call(&filter, "GET", "/docs/exists", None).await;
+    assert_eq!(body["result"]["exists"], true);
+    let (_, body) = call(&filter, "GET", "/docs", None).await;
+    assert_eq!(body["result"]["config"]["params"]["vectors"]["size"], 3);
+    assert_eq!(
+        body["result"]["config"]["params"]["vectors"]["distance"],
+        "Cosine"
+    );
+
+    let (_, body) = call(&filter, "DELETE", "/docs", None).await;
+    assert_eq!(body["result"], true);
+    let (_, body) = call(&filter, "GET", "/docs/exists", None).await;
+    assert_eq!(body["result"]["exists"], false);
+    let (status, body) = call(&filter, "GET", "/docs", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body["status"]["error"]
+        .as_str()
+        .unwrap()
+        .contains("doesn't exist"));
+}
+
+#[tokio::test]
+async fn points_round_trip_with_payloads_and_filters() {
+    let filter = server();
+    let create = json!({ "vectors": { "size": 3, "distance": "Cosine" } });
+    call(&filter, "PUT", "/docs", Some(create)).await;
+
+    let uuid = "5c56c793-69f3-4fbf-87e6-c4bf54c28c26";
+    let points = json!({ "points": [
+        { "id": 1, "vector": [1.0, 0.0, 0.0],
+          "payload": { "page_content": "apples", "metadata": { "source": "a", "page": 3 } } },
+        { "id": 2, "vector": [0.9, 0.1, 0.0],
+          "payload": { "page_content": "pears", "metadata": { "source": "b", "page": 7 } } },
+        { "id": uuid, "vector": [0.0, 1.0, 0.0],
+          "payload": { "page_content": "plums", "metadata": { "source": "a", "page": 9 } } },
+    ]});
+    let (status, body) = call(&filter, "PUT", "/docs/points", Some(points)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["result"]["status"], "completed");
+
+    // The nearest point scores highest and payloads come back as sent
+    let search = json!({ "vector": [1.0, 0.0, 0.0], "limit": 3, "with_payload": true });
+    let (_, body) = call(&filter, "POST", "/docs/points/search", Some(search)).await;
+    let hits = body["result"].as_array().unwrap();
+    assert_eq!(hits.len(), 3);
+    assert_eq!(hits[0]["id"], 1);
+    assert!(hits[0]["score"].as_f64().unwrap() > hits[1]["score"].as_f64().unwrap());
+    assert_eq!(
+        hits[0]["payload"],
+        json!({ "page_content": "apples", "metadata": { "source": "a", "page": 3 } })
+    );
+
+    // Filters apply to nested payload fields
+    let query = json!({
+        "query": [1.0, 0.0, 0.0],
+        "filter": { "must": [
+            { "key": "metadata.source", "match": { "value": "a" } },
+            { "key": "metadata.page", "range": { "gt": 5 } },
+        ]},
+    });
+    let (_, body) = call(&filter, "POST", "/docs/points/query", Some(query)).await;
+    let hits = body["result"]["points"].as_array().unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["id"], uuid);
+    assert_eq!(hits[0]["payload"], Value::Null);
+
+    // Upserting an existing ID replaces the point
+    let batch = json!({ "batch": {
+        "ids": [2], "vectors": [[0.0, 0.0, 1.0]], "payloads": [{ "page_content": "figs" }],
+    }});
+    call(&filter, "PUT", "/docs/points", Some(batch)).await;
+    let retrieve = json!({ "ids": [2, 42], "with_vector": true });
+    let (_, body) = call(&filter, "POST", "/docs/points", Some(retrieve)).await;
+    assert_eq!(
+        body["result"],
+        json!([{ "id": 2, "payload": { "page_content": "figs" }, "vector": [0.0, 0.0, 1.0] }])
+    );
+
+    let wrong_size = json!({ "points": [{ "id": 9, "vector": [1.0, 0.0] }] });
+    let (status, _) = call(&filter, "PUT", "/docs/points", Some(wrong_size)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let delete = json!({ "points": [1, uuid] });
+    call(&filter, "POST", "/docs/points/delete", Some(delete)).await;
+    let (_, body) = call(&filter, "GET", "/docs", None).await;
+    assert_eq!(body["result"]["points_count"], 1);
+}
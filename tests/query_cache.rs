@@ -0,0 +1,96 @@
+use amazon_rose_forest::{
+    core::metrics::MetricsCollector,
+    sharding::{
+        manager::ShardManager, query_cache::QueryCacheConfig, vector_index::DistanceMetric,
+    },
+    Vector,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+async fn setup(config: QueryCacheConfig) -> (Arc<MetricsCollector>, ShardManager, uuid::Uuid) {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = ShardManager::new(metrics.clone()).with_query_cache(config);
+    let shard_id = manager.create_shard("cached").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    manager
+        .add_vector(shard_id, Vector::new(vec![0.0, 0.0]), None)
+        .await
+        .unwrap();
+    (metrics, manager, shard_id)
+}
+
+#[tokio::test]
+async fn repeated_queries_hit_the_cache() {
+    let (metrics, manager, shard_id) = setup(QueryCacheConfig::default()).await;
+    let query = Vector::new(vec![0.1, 0.1]);
+
+    let first = manager.search_vectors(shard_id, &query, 5).await.unwrap();
+    let second = manager.search_vectors(shard_id, &query, 5).await.unwrap();
+    assert_eq!(first.len(), second.len());
+    assert_eq!(metrics.get_counter("query_cache.misses").await, Some(1));
+    assert_eq!(metrics.get_counter("query_cache.hits").await, Some(1));
+
+    // A different k is a different query
+    manager.search_vectors(shard_id, &query, 1).await.unwrap();
+    assert_eq!(metrics.get_counter("query_cache.misses").await, Some(2));
+}
+
+#[tokio::test]
+async fn writes_invalidate_cached_results() {
+    let (_metrics, manager, shard_id) = setup(QueryCacheConfig::default()).await;
+    let query = Vector::new(vec![0.1, 0.1]);
+
+    assert_eq!(
+        manager
+            .search_vectors(shard_id, &query, 5)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(manager.query_cache().len(shard_id).await, 1);
+
+    let id = manager
+        .add_vector(shard_id, Vector::new(vec![0.2, 0.2]), None)
+        .await
+        .unwrap();
+    assert_eq!(manager.query_cache().len(shard_id).await, 0);
+    assert_eq!(
+        manager
+            .search_vectors(shard_id, &query, 5)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    manager.remove_vector(shard_id, id).await.unwrap();
+    assert_eq!(
+        manager
+            .search_vectors(shard_id, &query, 5)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn cached_results_expire() {
+    let (metrics, manager, shard_id) = setup(QueryCacheConfig {
+        ttl: Duration::from_millis(20),
+        ..Default::default()
+    })
+    .await;
+    let query = Vector::new(vec![0.1, 0.1]);
+
+    manager.search_vectors(shard_id, &query, 5).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    manager.search_vectors(shard_id, &query, 5).await.unwrap();
+    assert_eq!(metrics.get_counter("query_cache.misses").await, Some(2));
+    assert_eq!(metrics.get_counter("query_cache.hits").await, None);
+}
@@ -0,0 +1,247 @@
+use amazon_rose_forest::{
+    core::metrics::MetricsCollector,
+    query::{MetadataFilter, PlanNode, PredicateOp, QueryExpr, QueryPlanner},
+    server::{api::SearchVectorsResponse, Server, ServerConfig},
+    sharding::{manager::ShardManager, vector_index::DistanceMetric},
+    Vector,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+fn metadata(category: &str, price: u32) -> Option<HashMap<String, String>> {
+    let mut m = HashMap::new();
+    m.insert("category".to_string(), category.to_string());
+    m.insert("price".to_string(), price.to_string());
+    Some(m)
+}
+
+#[test]
+fn parses_nested_boolean_query() {
+    let expr: QueryExpr = serde_json::from_value(json!({
+        "and": [
+            {"field": {"key": "category", "op": "eq", "value": "product"}},
+            {"or": [
+                {"field": {"key": "price", "op": "lt", "value": 10}},
+                {"not": {"field": {"key": "discontinued", "op": "exists"}}}
+            ]}
+        ]
+    }))
+    .unwrap();
+
+    let plan = QueryPlanner::new(3).plan(&expr).unwrap();
+    assert_eq!(plan.metadata_predicates, 3);
+    assert_eq!(plan.similarity_clauses, 0);
+    assert!(matches!(plan.root, PlanNode::All(ref children) if children.len() == 2));
+}
+
+#[test]
+fn planner_normalizes_expressions() {
+    let expr: QueryExpr = serde_json::from_value(json!({
+        "and": [
+            {"similar": {"vector": [0.0, 0.0, 0.0], "max_distance": 1.0}},
+            {"and": [{"field": {"key": "a", "op": "eq", "value": "x"}}]},
+            {"not": {"not": {"field": {"key": "b", "op": "eq", "value": "y"}}}}
+        ]
+    }))
+    .unwrap();
+
+    let plan = QueryPlanner::new(3).plan(&expr).unwrap();
+    match plan.root {
+        PlanNode::All(children) => {
+            assert_eq!(children.len(), 3);
+            // Metadata predicates are evaluated before distance checks
+            assert!(matches!(children[0], PlanNode::Metadata { .. }));
+            assert!(matches!(children[1], PlanNode::Metadata { .. }));
+            assert!(matches!(children[2], PlanNode::Similarity { .. }));
+        }
+        other => panic!("unexpected plan: {:?}", other),
+    }
+}
+
+#[test]
+fn planner_rejects_invalid_queries() {
+    let planner = QueryPlanner::new(3);
+
+    let wrong_dims: QueryExpr = serde_json::from_value(json!({
+        "similar": {"vector": [0.0, 0.0], "max_distance": 1.0}
+    }))
+    .unwrap();
+    assert!(planner.plan(&wrong_dims).is_err());
+
+    let empty_in: QueryExpr = serde_json::from_value(json!({
+        "field": {"key": "category", "op": "in", "value": []}
+    }))
+    .unwrap();
+    assert!(planner.plan(&empty_in).is_err());
+
+    assert!(planner.plan(&QueryExpr::Or(vec![])).is_err());
+}
+
+/// A shard of a book priced 5 at the origin, a product priced 50 next to
+/// it and a product priced 5 far away
+async fn products(manager: &ShardManager) -> Uuid {
+    let shard_id = manager.create_shard("products").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+
+    manager
+        .add_vector(
+            shard_id,
+            Vector::new(vec![0.0, 0.0, 0.0]),
+            metadata("book", 5),
+        )
+        .await
+        .unwrap();
+    manager
+        .add_vector(
+            shard_id,
+            Vector::new(vec![0.1, 0.0, 0.0]),
+            metadata("product", 50),
+        )
+        .await
+        .unwrap();
+    manager
+        .add_vector(
+            shard_id,
+            Vector::new(vec![0.9, 0.9, 0.9]),
+            metadata("product", 5),
+        )
+        .await
+        .unwrap();
+    shard_id
+}
+
+#[tokio::test]
+async fn filtered_search_applies_plan() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = ShardManager::new(metrics);
+    let shard_id = products(&manager).await;
+
+    let expr: QueryExpr = serde_json::from_value(json!({
+        "and": [
+            {"field": {"key": "category", "op": "eq", "value": "product"}},
+            {"field": {"key": "price", "op": "lte", "value": 10}}
+        ]
+    }))
+    .unwrap();
+
+    let results = manager
+        .search_vectors_filtered(shard_id, &Vector::new(vec![0.0, 0.0, 0.0]), 5, Some(&expr))
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 1);
+    let meta = results[0].metadata.as_ref().unwrap();
+    assert_eq!(meta["category"], "product");
+    assert_eq!(meta["price"], "5");
+
+    let near: QueryExpr = serde_json::from_value(json!({
+        "similar": {"vector": [0.0, 0.0, 0.0], "max_distance": 0.5}
+    }))
+    .unwrap();
+    let results = manager
+        .search_vectors_filtered(shard_id, &Vector::new(vec![0.0, 0.0, 0.0]), 5, Some(&near))
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 2);
+}
+
+#[test]
+fn metadata_filters_expand_to_field_predicates() {
+    let filter: MetadataFilter = serde_json::from_value(json!({
+        "category": "product",
+        "price": {"gte": 10, "lt": 100},
+        "discontinued": {"exists": false}
+    }))
+    .unwrap();
+    assert_eq!(
+        filter.to_expr().unwrap(),
+        QueryExpr::And(vec![
+            QueryExpr::field("category", PredicateOp::Eq, json!("product")),
+            QueryExpr::Not(Box::new(QueryExpr::field(
+                "discontinued",
+                PredicateOp::Exists,
+                json!(false)
+            ))),
+            QueryExpr::field("price", PredicateOp::Gte, json!(10)),
+            QueryExpr::field("price", PredicateOp::Lt, json!(100)),
+        ])
+    );
+    assert_eq!(MetadataFilter::default().to_expr(), None);
+
+    let single: MetadataFilter =
+        serde_json::from_value(json!({"tag": {"in": ["a", "b"]}})).unwrap();
+    assert_eq!(
+        single.to_expr().unwrap(),
+        QueryExpr::field("tag", PredicateOp::In, json!(["a", "b"]))
+    );
+}
+
+#[tokio::test]
+async fn metadata_filters_narrow_searches() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = products(&manager).await;
+    let origin = Vector::new(vec![0.0, 0.0, 0.0]);
+
+    let products_only: MetadataFilter =
+        serde_json::from_value(json!({"category": "product"})).unwrap();
+    let results = manager
+        .search_vectors_matching(shard_id, &origin, 1, &products_only)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].metadata.as_ref().unwrap()["price"], "50");
+
+    // Over HTTP the metadata filter is combined with any DSL filter
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    )
+    .filter();
+    let search = |body: serde_json::Value| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/search")
+            .json(&body)
+            .reply(&filter)
+    };
+    let resp = search(json!({
+        "shard_id": shard_id,
+        "query_vector": [0.0, 0.0, 0.0],
+        "limit": 5,
+        "metadata_filter": {"category": {"in": ["product"]}, "price": {"lt": 10}},
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: SearchVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.results.len(), 1);
+    assert_eq!(body.results[0].metadata.as_ref().unwrap()["price"], "5");
+
+    let resp = search(json!({
+        "shard_id": shard_id,
+        "query_vector": [0.0, 0.0, 0.0],
+        "limit": 5,
+        "filter": {"field": {"key": "price", "op": "eq", "value": 5}},
+        "metadata_filter": {"category": "product"},
+    }))
+    .await;
+    let body: SearchVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.results.len(), 1);
+
+    // Unknown operators are rejected rather than silently ignored
+    let resp = search(json!({
+        "shard_id": shard_id,
+        "query_vector": [0.0, 0.0, 0.0],
+        "limit": 5,
+        "metadata_filter": {"price": {"below": 10}},
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
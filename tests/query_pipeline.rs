@@ -0,0 +1,204 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::query::PipelineRequest;
+use amazon_rose_forest::server::api::PipelineSearchResponse;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+async fn catalog(manager: &ShardManager) -> Uuid {
+    let shard_id = manager.create_shard("catalog").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for i in 0..40 {
+        let metadata = HashMap::from([
+            ("brand".to_string(), ["acme", "globex"][i % 2].to_string()),
+            ("in_stock".to_string(), (i % 4 != 0).to_string()),
+        ]);
+        // Components past 1.0 share a Hilbert cell, so searches see every vector
+        let vector = Vector::new(vec![1.0 + i as f32, 0.0]);
+        manager
+            .add_vector(shard_id, vector, Some(metadata))
+            .await
+            .unwrap();
+    }
+    shard_id
+}
+
+fn pipeline(shard_id: Uuid, stages: serde_json::Value) -> PipelineRequest {
+    serde_json::from_value(json!({"shard_id": shard_id, "limit": 5, "stages": stages})).unwrap()
+}
+
+#[tokio::test]
+async fn stages_run_in_order_and_report_their_timings() {
+    let manager = ShardManager::new(Arc::new(MetricsCollector::new()));
+    let shard_id = catalog(&manager).await;
+    let request = pipeline(
+        shard_id,
+        json!([
+            {"stage": "retrieve", "query_vector": [1.0, 0.0], "limit": 20},
+            {"stage": "filter", "metadata_filter": {"in_stock": "true"}},
+            {"stage": "rerank", "group_by": "brand", "group_size": 4, "limit": 8},
+            {"stage": "aggregate", "fields": ["brand"]}
+        ]),
+    );
+
+    let outcome = request.run(&manager, None).await.unwrap();
+    let counts: Vec<(&str, usize, usize)> = outcome
+        .stages
+        .iter()
+        .map(|s| (s.stage.as_str(), s.candidates_in, s.candidates_out))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("retrieve", 0, 20),
+            ("filter", 20, 15),
+            ("rerank", 15, 8),
+            ("aggregate", 8, 8),
+        ]
+    );
+    assert!(outcome.stages.iter().all(|s| s.duration_ms >= 0.0));
+
+    assert_eq!(outcome.results.len(), 5);
+    assert!(outcome
+        .results
+        .iter()
+        .all(|r| r.metadata.as_ref().unwrap()["in_stock"] == "true"));
+    let brands = &outcome.facets.unwrap()["brand"];
+    assert_eq!(brands.len(), 2);
+    assert!(brands.iter().all(|b| b.count == 4));
+}
+
+#[tokio::test]
+async fn invalid_pipelines_name_the_failing_stage() {
+    let manager = ShardManager::new(Arc::new(MetricsCollector::new()));
+    let shard_id = catalog(&manager).await;
+    let retrieve = json!({"stage": "retrieve", "query_vector": [0.0, 0.0], "limit": 10});
+
+    let cases = [
+        (
+            json!([{"stage": "filter", "metadata_filter": {"brand": "acme"}}]),
+            "stage 0",
+        ),
+        (json!([retrieve, retrieve]), "stage 1"),
+        (
+            json!([{"stage": "retrieve", "query_vector": [0.0], "limit": 10}]),
+            "dimensions mismatch",
+        ),
+        (json!([retrieve, {"stage": "filter"}]), "stage 1"),
+        (
+            json!([retrieve, {"stage": "rerank", "limit": 3}]),
+            "stage 1",
+        ),
+        (
+            json!([retrieve, {"stage": "rerank", "scorer": "popularity"}]),
+            "scoring plugins not configured",
+        ),
+        (
+            json!([retrieve, {"stage": "aggregate", "fields": ["brand"]},
+                   {"stage": "aggregate", "fields": ["in_stock"]}]),
+            "stage 2",
+        ),
+    ];
+    for (stages, expected) in cases {
+        let error = pipeline(shard_id, stages.clone())
+            .run(&manager, None)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(expected), "{}: {}", stages, error);
+    }
+}
+
+#[tokio::test]
+async fn pipeline_endpoint_returns_results_and_stage_timings() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = catalog(&manager).await;
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    );
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search/pipeline")
+        .json(&json!({
+            "shard_id": shard_id,
+            "stages": [
+                {"stage": "retrieve", "query_vector": [11.0, 0.0], "limit": 5},
+                {"stage": "filter", "filter": {"field": {"key": "brand", "op": "eq", "value": "acme"}}}
+            ]
+        }))
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: PipelineSearchResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.results.len(), 3);
+    assert_eq!(body.stages.len(), 2);
+    assert!(body.facets.is_none());
+    assert!(body.total_ms >= body.stages.iter().map(|s| s.duration_ms).sum::<f64>());
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search/pipeline")
+        .json(&json!({"shard_id": shard_id, "stages": []}))
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn pipeline_endpoint_accepts_full_size_embeddings() {
+    const DIM: usize = 768;
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("embeddings").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", DIM, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+    let embedding = |seed: usize| -> Vec<f32> {
+        (0..DIM)
+            .map(|i| ((i * 7919 + seed * 104_729) % 1000) as f32 / 997.0 + 0.123_456_7)
+            .collect()
+    };
+    manager
+        .add_vector(shard_id, Vector::new(embedding(0)), None)
+        .await
+        .unwrap();
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    );
+
+    let body = json!({
+        "shard_id": shard_id,
+        "stages": [{
+            "stage": "retrieve",
+            "query_vector": embedding(0),
+            "additional_queries": [embedding(1), embedding(2)],
+            "limit": 1
+        }]
+    });
+    assert!(serde_json::to_vec(&body).unwrap().len() > 16 * 1024);
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search/pipeline")
+        .json(&body)
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: PipelineSearchResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.results.len(), 1);
+}
@@ -0,0 +1,178 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::intelligence::model_registry::ModelRegistry;
+use amazon_rose_forest::intelligence::ranking::{
+    examples, RankingConfig, RankingPipeline, RANKING_MODEL_NAME,
+};
+use amazon_rose_forest::query::feedback::{Feedback, FeedbackLabel, SearchLog, ShownResult};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::utils::errors::FeedbackError;
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+fn shown(id: &str, score: f32) -> ShownResult {
+    ShownResult {
+        id: id.to_string(),
+        score,
+        metadata_fields: 0,
+    }
+}
+
+/// Five results by descending score where clients only ever want the
+/// one carrying metadata, shown fourth
+fn page() -> Vec<ShownResult> {
+    let mut results: Vec<ShownResult> = ["a", "b", "c", "d", "e"]
+        .iter()
+        .enumerate()
+        .map(|(i, id)| shown(id, 0.9 - i as f32 * 0.1))
+        .collect();
+    results[3].metadata_fields = 4;
+    results
+}
+
+#[tokio::test]
+async fn feedback_must_refer_to_a_shown_result() {
+    let log = SearchLog::with_capacity(1);
+    let first = log
+        .record_impression(Uuid::new_v4(), vec![shown("a", 0.9)])
+        .await;
+    log.record_feedback(first, "a", FeedbackLabel::Click)
+        .await
+        .unwrap();
+    assert!(matches!(
+        log.record_feedback(first, "z", FeedbackLabel::Click).await,
+        Err(FeedbackError::UnknownResult { .. })
+    ));
+
+    // Capacity one: the next impression drops the first
+    log.record_impression(Uuid::new_v4(), vec![shown("b", 0.5)])
+        .await;
+    assert!(log.impression(first).await.is_none());
+    assert!(matches!(
+        log.record_feedback(first, "a", FeedbackLabel::Accept).await,
+        Err(FeedbackError::UnknownQuery(_))
+    ));
+    assert_eq!(log.feedback_received(), 1);
+    assert!(log.labelled().await.is_empty());
+}
+
+#[tokio::test]
+async fn examples_skip_results_below_the_last_feedback() {
+    let log = SearchLog::new();
+    let query_id = log.record_impression(Uuid::new_v4(), page()).await;
+    log.record_feedback(query_id, "b", FeedbackLabel::Click)
+        .await
+        .unwrap();
+    log.record_feedback(query_id, "b", FeedbackLabel::Accept)
+        .await
+        .unwrap();
+
+    let examples = examples(&log.labelled().await);
+    assert_eq!(examples.len(), 2);
+    assert!(!examples[0].relevant);
+    assert!(examples[1].relevant);
+    assert_eq!(examples[1].weight, FeedbackLabel::Accept.weight());
+}
+
+#[tokio::test]
+async fn trained_model_promotes_results_clients_choose() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let log = Arc::new(SearchLog::new());
+    let registry = Arc::new(ModelRegistry::new(metrics.clone()));
+    let pipeline = RankingPipeline::new(log.clone(), metrics.clone())
+        .with_config(RankingConfig {
+            min_new_feedback: 10,
+            ..RankingConfig::default()
+        })
+        .with_registry(registry.clone());
+
+    // Untrained, results come back as they were
+    let ids: Vec<String> = page().into_iter().map(|r| r.id).collect();
+    let untouched: Vec<String> = pipeline
+        .rerank(page(), |r| r.clone())
+        .await
+        .into_iter()
+        .map(|r| r.id)
+        .collect();
+    assert_eq!(untouched, ids);
+    assert!(pipeline.train().await.is_none());
+
+    for _ in 0..9 {
+        let query_id = log.record_impression(Uuid::new_v4(), page()).await;
+        log.record_feedback(query_id, "d", FeedbackLabel::Click)
+            .await
+            .unwrap();
+    }
+    assert!(pipeline.train_if_due().await.is_none());
+
+    let query_id = log.record_impression(Uuid::new_v4(), page()).await;
+    log.record_feedback(query_id, "d", FeedbackLabel::Accept)
+        .await
+        .unwrap();
+    let report = pipeline.train_if_due().await.unwrap();
+    assert_eq!((report.examples, report.positives), (40, 10));
+    assert!(report.accuracy >= 0.9);
+    assert!(pipeline.train_if_due().await.is_none());
+
+    let reranked: Vec<String> = pipeline
+        .rerank(page(), |r| r.clone())
+        .await
+        .into_iter()
+        .map(|r| r.id)
+        .collect();
+    assert_eq!(reranked[0], "d");
+    assert_ne!(reranked, ids);
+
+    let published = registry.current(RANKING_MODEL_NAME).await.unwrap();
+    assert_eq!(published.weights, pipeline.model().await.unwrap().weights);
+    assert!(published
+        .lineage
+        .validation_metrics
+        .contains_key("log_loss"));
+    assert_eq!(
+        metrics.get_counter("intelligence.ranking.trainings").await,
+        Some(1)
+    );
+}
+
+#[tokio::test]
+async fn feedback_is_submitted_over_http() {
+    let log = Arc::new(SearchLog::new());
+    let query_id = log.record_impression(Uuid::new_v4(), page()).await;
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    )
+    .with_search_log(log.clone())
+    .filter();
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/feedback")
+        .json(&json!({ "query_id": query_id, "result_id": "c", "label": "accept" }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let feedback: Feedback = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(feedback.label, FeedbackLabel::Accept);
+    assert_eq!(log.impression(query_id).await.unwrap().feedback.len(), 1);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/feedback")
+        .json(&json!({ "query_id": query_id, "result_id": "z", "label": "click" }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/feedback")
+        .json(&json!({ "query_id": Uuid::new_v4(), "result_id": "c", "label": "click" }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,263 @@
+use amazon_rose_forest::{
+    core::metrics::MetricsCollector,
+    nerv::region::{
+        LogSegment, RegionConfig, RegionReplicator, RegionRole, ReplicatedChange, VersionStamp,
+    },
+    network::secure_channel::{NodeIdentity, SecureChannels, SecureClient},
+    server::{auth::AuthConfig, Server, ServerConfig},
+    sharding::{changefeed::ChangeOp, manager::ShardManager, vector_index::DistanceMetric},
+    Vector,
+};
+use std::sync::Arc;
+
+async fn region_manager(metrics: Arc<MetricsCollector>) -> (Arc<ShardManager>, uuid::Uuid) {
+    let manager = Arc::new(ShardManager::new(metrics));
+    let shard_id = manager.create_shard("docs").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    (manager, shard_id)
+}
+
+#[tokio::test]
+async fn ships_changes_to_peer_region() {
+    let metrics_b = Arc::new(MetricsCollector::new());
+    let (manager_b, shard_b) = region_manager(metrics_b.clone()).await;
+    let replicator_b = Arc::new(RegionReplicator::new(
+        RegionConfig::new("eu", "http://unused", RegionRole::Standby),
+        manager_b.clone(),
+        metrics_b.clone(),
+    ));
+    let server_b = Server::new(
+        ServerConfig::default(),
+        metrics_b,
+        None,
+        Some(manager_b.clone()),
+    )
+    .with_region_replicator(replicator_b);
+    let (addr, serve) = warp::serve(server_b.filter()).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(serve);
+
+    let metrics_a = Arc::new(MetricsCollector::new());
+    let (manager_a, shard_a) = region_manager(metrics_a.clone()).await;
+    let replicator_a = RegionReplicator::new(
+        RegionConfig::new("us", &format!("http://{}/api", addr), RegionRole::Active),
+        manager_a.clone(),
+        metrics_a.clone(),
+    );
+
+    let first = manager_a
+        .add_vector(shard_a, Vector::new(vec![1.0, 0.0, 0.0]), None)
+        .await
+        .unwrap();
+    let second = manager_a
+        .add_vector(shard_a, Vector::new(vec![0.0, 1.0, 0.0]), None)
+        .await
+        .unwrap();
+    manager_a.remove_vector(shard_a, first).await.unwrap();
+
+    let acks = replicator_a.ship_once().await.unwrap();
+    assert_eq!(acks.len(), 1);
+    assert_eq!(acks[0].applied, 3);
+    assert_eq!(metrics_a.get_gauge("replication.lag_events").await, Some(0));
+
+    let index_b = manager_b.get_vector_index(shard_b).await.unwrap();
+    assert!(index_b.get(first).await.is_none());
+    assert!(index_b.get(second).await.is_some());
+
+    // Nothing new to ship
+    assert!(replicator_a.ship_once().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn ships_to_peers_that_require_an_api_key() {
+    let metrics_b = Arc::new(MetricsCollector::new());
+    let (manager_b, shard_b) = region_manager(metrics_b.clone()).await;
+    let replicator_b = Arc::new(RegionReplicator::new(
+        RegionConfig::new("eu", "http://unused", RegionRole::Standby),
+        manager_b.clone(),
+        metrics_b.clone(),
+    ));
+    let server_b = Server::new(
+        ServerConfig {
+            auth: Some(AuthConfig::new().with_api_key("region-key")),
+            ..ServerConfig::default()
+        },
+        metrics_b,
+        None,
+        Some(manager_b.clone()),
+    )
+    .with_region_replicator(replicator_b);
+    let (addr, serve) = warp::serve(server_b.filter()).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(serve);
+
+    let metrics_a = Arc::new(MetricsCollector::new());
+    let (manager_a, shard_a) = region_manager(metrics_a.clone()).await;
+    let peer_url = format!("http://{}/api", addr);
+    let vector_id = manager_a
+        .add_vector(shard_a, Vector::new(vec![1.0, 0.0, 0.0]), None)
+        .await
+        .unwrap();
+
+    let without_key = RegionReplicator::new(
+        RegionConfig::new("us", &peer_url, RegionRole::Active),
+        manager_a.clone(),
+        metrics_a.clone(),
+    );
+    // Refused segments stay unshipped
+    assert!(without_key.ship_once().await.unwrap().is_empty());
+    assert!(without_key.check_peer().await.is_err());
+
+    let with_key = RegionReplicator::new(
+        RegionConfig::new("us", &peer_url, RegionRole::Active).with_api_key("region-key"),
+        manager_a,
+        metrics_a,
+    );
+    assert_eq!(with_key.ship_once().await.unwrap().len(), 1);
+    with_key.check_peer().await.unwrap();
+    let index_b = manager_b.get_vector_index(shard_b).await.unwrap();
+    assert!(index_b.get(vector_id).await.is_some());
+}
+
+#[tokio::test]
+async fn ships_through_a_secure_channel() {
+    let channels_a = Arc::new(SecureChannels::new(NodeIdentity::generate("us-node")));
+    let channels_b = Arc::new(SecureChannels::new(NodeIdentity::generate("eu-node")));
+    channels_a
+        .add_peer("eu-node", channels_b.identity().public_key())
+        .await;
+    channels_b
+        .add_peer("us-node", channels_a.identity().public_key())
+        .await;
+
+    let metrics_b = Arc::new(MetricsCollector::new());
+    let (manager_b, shard_b) = region_manager(metrics_b.clone()).await;
+    let replicator_b = Arc::new(RegionReplicator::new(
+        RegionConfig::new("eu", "http://unused", RegionRole::Standby),
+        manager_b.clone(),
+        metrics_b.clone(),
+    ));
+    let server_b = Server::new(
+        ServerConfig::default(),
+        metrics_b,
+        None,
+        Some(manager_b.clone()),
+    )
+    .with_region_replicator(replicator_b)
+    .with_secure_channels(channels_b);
+    let (addr, serve) = warp::serve(server_b.filter()).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(serve);
+
+    let metrics_a = Arc::new(MetricsCollector::new());
+    let (manager_a, shard_a) = region_manager(metrics_a.clone()).await;
+    let peer_url = format!("http://{}/api", addr);
+    let vector_id = manager_a
+        .add_vector(shard_a, Vector::new(vec![1.0, 0.0, 0.0]), None)
+        .await
+        .unwrap();
+
+    // Plain segments are refused
+    let plain = RegionReplicator::new(
+        RegionConfig::new("us", &peer_url, RegionRole::Active),
+        manager_a.clone(),
+        metrics_a.clone(),
+    );
+    assert!(plain.ship_once().await.unwrap().is_empty());
+
+    let sealed = RegionReplicator::new(
+        RegionConfig::new("us", &peer_url, RegionRole::Active),
+        manager_a,
+        metrics_a,
+    )
+    .with_secure_channel(SecureClient::new(channels_a), "eu-node");
+    let acks = sealed.ship_once().await.unwrap();
+    assert_eq!(acks.len(), 1);
+    assert_eq!(acks[0].applied, 1);
+    let index_b = manager_b.get_vector_index(shard_b).await.unwrap();
+    assert!(index_b.get(vector_id).await.is_some());
+}
+
+#[tokio::test]
+async fn stale_changes_lose_to_newer_versions() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let (manager, shard_id) = region_manager(metrics.clone()).await;
+    let replicator = RegionReplicator::new(
+        RegionConfig::new("eu", "http://unused", RegionRole::Active),
+        manager.clone(),
+        metrics,
+    );
+
+    let vector_id = uuid::Uuid::new_v4();
+    let stamp = |micros| VersionStamp {
+        micros,
+        region: "us".to_string(),
+    };
+    let segment = |changes| LogSegment {
+        source_region: "us".to_string(),
+        epoch: 0,
+        shard_name: "docs".to_string(),
+        first_offset: 0,
+        last_offset: 0,
+        changes,
+    };
+
+    let ack = replicator
+        .apply_segment(segment(vec![
+            ReplicatedChange {
+                stamp: stamp(10),
+                op: ChangeOp::Insert {
+                    vector_id,
+                    values: vec![1.0, 2.0, 3.0],
+                    metadata: None,
+                },
+            },
+            ReplicatedChange {
+                stamp: stamp(20),
+                op: ChangeOp::Delete { vector_id },
+            },
+        ]))
+        .await
+        .unwrap();
+    assert_eq!(ack.applied, 2);
+
+    // A delayed copy of the insert must not resurrect the vector
+    let ack = replicator
+        .apply_segment(segment(vec![ReplicatedChange {
+            stamp: stamp(15),
+            op: ChangeOp::Insert {
+                vector_id,
+                values: vec![1.0, 2.0, 3.0],
+                metadata: None,
+            },
+        }]))
+        .await
+        .unwrap();
+    assert_eq!(ack.superseded, 1);
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    assert!(index.get(vector_id).await.is_none());
+}
+
+#[tokio::test]
+async fn standby_rejects_client_writes_until_promoted() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let (manager, shard_id) = region_manager(metrics.clone()).await;
+    let replicator = RegionReplicator::new(
+        RegionConfig::new("eu", "http://unused", RegionRole::Standby),
+        manager.clone(),
+        metrics,
+    );
+
+    replicator.demote().await.unwrap();
+    assert!(manager
+        .add_vector(shard_id, Vector::random(3), None)
+        .await
+        .is_err());
+
+    replicator.promote().await.unwrap();
+    assert_eq!(replicator.role().await, RegionRole::Active);
+    assert!(manager
+        .add_vector(shard_id, Vector::random(3), None)
+        .await
+        .is_ok());
+}
@@ -0,0 +1,168 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::network::admission::{AdmissionConfig, AdmissionController, LoadSample};
+use amazon_rose_forest::network::priority::{PoolConfig, Priority, PriorityPools};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::utils::errors::AdmissionError;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::http::StatusCode;
+
+const SATURATED: LoadSample = LoadSample {
+    cpu: 0.9,
+    memory: 0.3,
+    loop_lag_ms: 1,
+};
+
+#[test]
+fn header_can_lower_but_not_raise_priority() {
+    assert_eq!(
+        Priority::resolve(Priority::Interactive, None),
+        Priority::Interactive
+    );
+    assert_eq!(
+        Priority::resolve(Priority::Interactive, Some("Batch")),
+        Priority::Batch
+    );
+    assert_eq!(
+        Priority::resolve(Priority::Batch, Some("background")),
+        Priority::Background
+    );
+    assert_eq!(
+        Priority::resolve(Priority::Batch, Some("interactive")),
+        Priority::Batch
+    );
+    assert_eq!(
+        Priority::resolve(Priority::Batch, Some("urgent")),
+        Priority::Batch
+    );
+}
+
+#[tokio::test]
+async fn full_batch_pool_never_blocks_interactive_work() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let pools = Arc::new(PriorityPools::new(
+        PoolConfig {
+            interactive: 1,
+            batch: 1,
+            background: 1,
+        },
+        metrics.clone(),
+    ));
+
+    let held = pools.acquire(Priority::Batch).await;
+    assert_eq!(held.priority(), Priority::Batch);
+    assert_eq!(pools.available(Priority::Batch), 0);
+
+    let queued = {
+        let pools = pools.clone();
+        tokio::spawn(async move { pools.run(Priority::Batch, async { "imported" }).await })
+    };
+    // Let the batch job start waiting behind the held permit
+    tokio::task::yield_now().await;
+    let searched = tokio::time::timeout(
+        Duration::from_millis(500),
+        pools.run(Priority::Interactive, async { "searched" }),
+    )
+    .await
+    .expect("interactive work waited on the batch pool");
+    assert_eq!(searched, "searched");
+    assert!(!queued.is_finished());
+
+    drop(held);
+    assert_eq!(queued.await.unwrap(), "imported");
+    assert_eq!(metrics.get_counter("priority.batch.waited").await, Some(1));
+
+    let sum = pools
+        .spawn_blocking(Priority::Background, || (1..=10).sum::<u32>())
+        .await
+        .unwrap();
+    assert_eq!(sum, 55);
+    assert_eq!(pools.available(Priority::Background), 1);
+}
+
+#[tokio::test]
+async fn background_work_is_deferred_while_batch_work_queues() {
+    let admission = Arc::new(AdmissionController::new(
+        AdmissionConfig {
+            queue_timeout: Duration::from_millis(50),
+            ..AdmissionConfig::default()
+        },
+        Arc::new(MetricsCollector::new()),
+    ));
+    admission.record_sample(SATURATED).await;
+
+    match admission.admit(Priority::Background).await {
+        Err(AdmissionError::Shed { reason, .. }) => assert!(reason.contains("deferred")),
+        other => panic!("expected background work to be deferred, got {:?}", other),
+    }
+    assert!(matches!(
+        admission.admit(Priority::Batch).await,
+        Err(AdmissionError::QueueTimeout { .. })
+    ));
+    assert!(admission.admit(Priority::Interactive).await.is_ok());
+}
+
+#[tokio::test]
+async fn routes_apply_their_priority_class() {
+    let admission = Arc::new(AdmissionController::new(
+        AdmissionConfig {
+            queue_timeout: Duration::from_millis(50),
+            ..AdmissionConfig::default()
+        },
+        Arc::new(MetricsCollector::new()),
+    ));
+    admission.record_sample(SATURATED).await;
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(Arc::new(ShardManager::new(Arc::new(
+            MetricsCollector::new(),
+        )))),
+    )
+    .with_admission_controller(admission)
+    .with_priority_pools(Arc::new(PriorityPools::new(
+        PoolConfig::default(),
+        Arc::new(MetricsCollector::new()),
+    )));
+    let filter = server.filter();
+    let search = json!({
+        "shard_id": uuid::Uuid::new_v4(),
+        "query_vector": [0.1, 0.2],
+        "limit": 5
+    });
+
+    // Searches are interactive and get through to the index lookup
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search")
+        .json(&search)
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // ...unless the client lowers them
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search")
+        .header("x-request-priority", "background")
+        .json(&search)
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // Imports are batch work, and asking for more doesn't help
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/import")
+        .header("x-request-priority", "interactive")
+        .json(&json!({
+            "shard_id": uuid::Uuid::new_v4(),
+            "source": {"type": "qdrant", "url": "http://localhost:6333", "collection": "c"}
+        }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
@@ -0,0 +1,167 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::server::api::{
+    DeleteShardResponse, DeleteVectorResponse, IndexInfo, ShardInfo, VectorResponse,
+};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::{ShardManager, ShardStatus};
+use amazon_rose_forest::sharding::storage::{FileStorage, StorageBackend};
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::Vector;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+async fn collection(manager: &ShardManager, name: &str, vectors: usize) -> (Uuid, Vec<Uuid>) {
+    let shard_id = manager.create_shard(name).await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let mut ids = Vec::new();
+    for i in 0..vectors {
+        let x = i as f32 / vectors as f32;
+        let metadata = HashMap::from([("n".to_string(), i.to_string())]);
+        ids.push(
+            manager
+                .add_vector(shard_id, Vector::new(vec![x, 1.0 - x]), Some(metadata))
+                .await
+                .unwrap(),
+        );
+    }
+    (shard_id, ids)
+}
+
+fn body<T: DeserializeOwned, B: AsRef<[u8]>>(resp: &warp::http::Response<B>) -> T {
+    serde_json::from_slice(resp.body().as_ref()).unwrap()
+}
+
+#[tokio::test]
+async fn shards_indexes_and_vectors_can_be_listed_and_fetched() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let (docs, ids) = collection(&manager, "docs", 4).await;
+    let empty = manager.create_shard("empty").await.unwrap();
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager.clone()),
+    )
+    .filter();
+    let get = |path: String| {
+        warp::test::request()
+            .method("GET")
+            .path(&path)
+            .reply(&filter)
+    };
+
+    let shards: Vec<ShardInfo> = body(&get("/api/shards".into()).await);
+    assert_eq!(
+        shards.iter().map(|s| s.shard_id).collect::<Vec<_>>(),
+        vec![docs, empty]
+    );
+    assert_eq!(shards[0].vector_count, 4);
+    assert_eq!(shards[0].status, ShardStatus::Active);
+
+    let indexes: Vec<IndexInfo> = body(&get(format!("/api/shards/{}/indexes", docs)).await);
+    assert_eq!(indexes.len(), 1);
+    assert_eq!(indexes[0].index_name, "main");
+    assert_eq!(indexes[0].distance_metric, "euclidean");
+    assert_eq!(indexes[0].vector_count, 4);
+    let indexes: Vec<IndexInfo> = body(&get(format!("/api/shards/{}/indexes", empty)).await);
+    assert!(indexes.is_empty());
+    let resp = get(format!("/api/shards/{}/indexes", Uuid::new_v4())).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let vector: VectorResponse = body(&get(format!("/api/vectors/{}", ids[2])).await);
+    assert_eq!(vector.shard_id, docs);
+    assert_eq!(vector.vector, vec![0.5, 0.5]);
+    assert_eq!(vector.metadata.unwrap()["n"], "2");
+    // Narrowed to a shard that doesn't hold it
+    let resp = get(format!("/api/vectors/{}?shard_id={}", ids[2], empty)).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let resp = get(format!("/api/vectors/{}", Uuid::new_v4())).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn deletes_cover_split_shards_and_storage() {
+    let dir = std::env::temp_dir().join(format!("rose-forest-deletes-{}", Uuid::new_v4()));
+    let storage: Arc<dyn StorageBackend> = Arc::new(FileStorage::open(&dir).unwrap());
+    let manager = Arc::new(
+        ShardManager::new(Arc::new(MetricsCollector::new())).with_storage(storage.clone()),
+    );
+    let (docs, ids) = collection(&manager, "docs", 8).await;
+    let (kept, _) = collection(&manager, "kept", 2).await;
+    let split = manager.split_shard(docs).await.unwrap();
+    manager.flush().await.unwrap();
+
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager.clone()),
+    )
+    .filter();
+    let delete = |path: String| {
+        warp::test::request()
+            .method("DELETE")
+            .path(&path)
+            .reply(&filter)
+    };
+
+    // Vectors are found wherever the split moved them
+    let moved = manager
+        .get_vector_index(split.new_shard_id)
+        .await
+        .unwrap()
+        .entries()
+        .await[0]
+        .id;
+    let resp = delete(format!("/api/vectors/{}?shard_id={}", moved, docs)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let deleted: DeleteVectorResponse = body(&resp);
+    assert_eq!(deleted.shard_id, split.new_shard_id);
+    assert_eq!(
+        delete(format!("/api/vectors/{}", moved)).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    assert!(ids.contains(&moved));
+
+    // Read-only shards refuse deletes
+    manager
+        .update_shard_status(kept, ShardStatus::ReadOnly)
+        .await
+        .unwrap();
+    let kept_vector = manager
+        .get_vector_index(kept)
+        .await
+        .unwrap()
+        .entries()
+        .await[0]
+        .id;
+    let resp = delete(format!("/api/vectors/{}", kept_vector)).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    // The split-off shard goes with the original, not on its own
+    let resp = delete(format!("/api/shards/{}", split.new_shard_id)).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let resp = delete(format!("/api/shards/{}", docs)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let deleted: DeleteShardResponse = body(&resp);
+    assert_eq!(deleted.deleted, vec![docs, split.new_shard_id]);
+    assert!(manager.get_shard(split.new_shard_id).await.is_err());
+    assert!(manager.get_vector_index(docs).await.is_err());
+    assert_eq!(
+        delete(format!("/api/shards/{}", docs)).await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    // Deleted shards stay deleted across a restart
+    manager.flush().await.unwrap();
+    let restored = ShardManager::new(Arc::new(MetricsCollector::new())).with_storage(storage);
+    assert_eq!(restored.restore(true).await.unwrap(), 1);
+    assert!(restored.get_shard(kept).await.is_ok());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
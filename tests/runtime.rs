@@ -1,5 +1,10 @@
-use amazon_rose_forest::{core::metrics::MetricsCollector, nerv::runtime::Runtime};
+use amazon_rose_forest::{
+    core::metrics::MetricsCollector,
+    nerv::{runtime::Runtime, shutdown::ShutdownToken},
+    server::{Server, ServerConfig},
+};
 use std::sync::Arc;
+use tokio::sync::oneshot;
 use tokio::time::{timeout, Duration};
 
 #[tokio::test]
@@ -27,3 +32,56 @@ async fn runtime_stop_sends_shutdown_signal() {
         panic!("shutdown channel missing");
     }
 }
+
+#[tokio::test]
+async fn shutdown_drains_components_before_flushing() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let mut runtime = Runtime::new(metrics).with_drain_timeout(Duration::from_millis(200));
+    runtime.start().await.unwrap();
+
+    // Finishes its in-flight work after being told to stop
+    let (finished_tx, finished_rx) = oneshot::channel();
+    runtime.spawn_component("test", "worker", |shutdown| async move {
+        shutdown.cancelled().await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        finished_tx.send(()).unwrap();
+    });
+    // Never notices
+    runtime.spawn_component("test", "stuck", |_| std::future::pending());
+    let token = runtime.shutdown_token();
+    assert!(!token.is_cancelled());
+
+    let report = runtime.shutdown().await.unwrap();
+    assert!(token.is_cancelled());
+    assert!(finished_rx.await.is_ok());
+    assert_eq!(report.drained, vec!["worker"]);
+    assert_eq!(report.timed_out, vec!["stuck"]);
+    assert!(!report.is_clean());
+    assert!(report.elapsed >= Duration::from_millis(200));
+    assert!(!ShutdownToken::never().is_cancelled());
+}
+
+#[tokio::test]
+async fn shutdown_stops_the_server_listener() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let mut runtime = Runtime::new(metrics.clone());
+    runtime.start().await.unwrap();
+    let runtime = Arc::new(runtime);
+    let mut server = Server::new(
+        ServerConfig {
+            port: 0,
+            ..ServerConfig::default()
+        },
+        metrics,
+        Some(runtime.clone()),
+        runtime.shard_manager(),
+    );
+    server.start().await.unwrap();
+
+    let report = timeout(Duration::from_secs(5), runtime.shutdown())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(report.drained, vec!["http listener"]);
+    assert!(report.is_clean());
+}
@@ -0,0 +1,251 @@
+mod common;
+
+use amazon_rose_forest::darwin::agent::ProgrammingLanguage;
+use amazon_rose_forest::darwin::sandbox::{language_of, RunnerCommand, SandboxRunner, StepKind};
+use amazon_rose_forest::darwin::self_improvement::CodeChange;
+use amazon_rose_forest::darwin::validation::{MultiLanguageValidationStage, ValidationStage};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const PYTHON_TEST: &str = "import unittest\n\nfrom calc import add\n\n\nclass AddTest(unittest.TestCase):\n    def test_add(self):\n        self.assertEqual(add(2, 3), 5)\n";
+
+fn temp_project(files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("rose-forest-sandbox-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&root).unwrap();
+    for (path, content) in files {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    root
+}
+
+fn change(path: &str, modified: &str) -> CodeChange {
+    common::code_change(path, None, modified)
+}
+
+fn has_program(program: &str) -> bool {
+    std::process::Command::new(program)
+        .arg("--version")
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+#[test]
+fn detects_test_commands_from_project_layout() {
+    let runner = SandboxRunner::new(PathBuf::from("."));
+    let detect = |files: &[(&str, &str)], language: ProgrammingLanguage| {
+        let root = temp_project(files);
+        let command = runner.detect_test_command(&root, &language);
+        std::fs::remove_dir_all(&root).unwrap();
+        command
+    };
+
+    assert_eq!(
+        detect(
+            &[("pyproject.toml", "[tool.pytest.ini_options]\n")],
+            ProgrammingLanguage::Python
+        ),
+        Some(RunnerCommand::new("python3", &["-m", "pytest", "-q"]))
+    );
+    assert_eq!(
+        detect(
+            &[("test_calc.py", PYTHON_TEST)],
+            ProgrammingLanguage::Python
+        ),
+        Some(RunnerCommand::new(
+            "python3",
+            &["-m", "unittest", "discover", "-q"]
+        ))
+    );
+    assert_eq!(
+        detect(&[("calc.py", "")], ProgrammingLanguage::Python),
+        None
+    );
+
+    assert_eq!(
+        detect(
+            &[("package.json", r#"{"scripts": {"test": "jest"}}"#)],
+            ProgrammingLanguage::JavaScript
+        ),
+        Some(RunnerCommand::new("npm", &["test", "--silent"]))
+    );
+    // The script `npm init` writes is not a test suite
+    assert_eq!(
+        detect(
+            &[(
+                "package.json",
+                r#"{"scripts": {"test": "echo \"Error: no test specified\" && exit 1"}}"#
+            )],
+            ProgrammingLanguage::JavaScript
+        ),
+        None
+    );
+    assert_eq!(
+        detect(&[("sum.test.js", "")], ProgrammingLanguage::JavaScript),
+        Some(RunnerCommand::new("node", &["--test"]))
+    );
+    assert_eq!(
+        detect(&[("tsconfig.json", "{}")], ProgrammingLanguage::TypeScript),
+        Some(RunnerCommand::new(
+            "npx",
+            &["--no-install", "tsc", "--noEmit"]
+        ))
+    );
+    assert_eq!(
+        detect(&[("go.mod", "module example\n")], ProgrammingLanguage::Go),
+        Some(RunnerCommand::new("go", &["test", "./..."]))
+    );
+
+    let overridden = SandboxRunner::new(PathBuf::from(".")).with_test_command(
+        ProgrammingLanguage::Go,
+        "make",
+        &["check"],
+    );
+    let root = temp_project(&[("go.mod", "module example\n")]);
+    assert_eq!(
+        overridden.detect_test_command(&root, &ProgrammingLanguage::Go),
+        Some(RunnerCommand::new("make", &["check"]))
+    );
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn maps_file_extensions_to_languages() {
+    assert_eq!(
+        language_of("app/main.py"),
+        Some(ProgrammingLanguage::Python)
+    );
+    assert_eq!(
+        language_of("web/App.tsx"),
+        Some(ProgrammingLanguage::TypeScript)
+    );
+    assert_eq!(
+        language_of("lib/index.mjs"),
+        Some(ProgrammingLanguage::JavaScript)
+    );
+    assert_eq!(language_of("cmd/server.go"), Some(ProgrammingLanguage::Go));
+    assert_eq!(language_of("src/engine.cc"), Some(ProgrammingLanguage::Cpp));
+    assert_eq!(language_of("README.md"), None);
+}
+
+#[test]
+fn python_changes_are_checked_and_tested() {
+    if !has_program("python3") {
+        return;
+    }
+    let root = temp_project(&[
+        ("calc.py", "def add(a, b):\n    return a - b\n"),
+        ("test_calc.py", PYTHON_TEST),
+    ]);
+    let runner = SandboxRunner::new(root.clone()).with_timeout(Duration::from_secs(60));
+
+    let fixed = runner
+        .run(
+            &ProgrammingLanguage::Python,
+            &[change("calc.py", "def add(a, b):\n    return a + b\n")],
+        )
+        .unwrap();
+    assert!(fixed.passed(), "{:?}", fixed.steps);
+    assert!(fixed.tests_detected);
+    let kinds: Vec<StepKind> = fixed.steps.iter().map(|s| s.kind).collect();
+    assert_eq!(kinds, vec![StepKind::Check, StepKind::Test]);
+
+    // Still buggy: parses, but the test fails
+    let buggy = runner
+        .run(
+            &ProgrammingLanguage::Python,
+            &[change("calc.py", "def add(a, b):\n    return a * b\n")],
+        )
+        .unwrap();
+    assert!(!buggy.passed());
+    assert_eq!(buggy.metrics()["check_pass_rate"], 1.0);
+    assert_eq!(buggy.metrics()["test_pass_rate"], 0.0);
+
+    // Doesn't parse: tests are skipped
+    let broken = runner
+        .run(
+            &ProgrammingLanguage::Python,
+            &[change("calc.py", "def add(a, b)\n    return a + b\n")],
+        )
+        .unwrap();
+    assert_eq!(broken.steps.len(), 1);
+    assert!(!broken.steps[0].passed);
+    assert!(broken.steps[0].output.contains("SyntaxError"));
+
+    // The project itself is never touched
+    assert_eq!(
+        std::fs::read_to_string(root.join("calc.py")).unwrap(),
+        "def add(a, b):\n    return a - b\n"
+    );
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn javascript_syntax_errors_fail_the_check() {
+    if !has_program("node") {
+        return;
+    }
+    let root = temp_project(&[("package.json", "{}")]);
+    let runner = SandboxRunner::new(root.clone());
+
+    let good = runner
+        .run(
+            &ProgrammingLanguage::JavaScript,
+            &[change("index.js", "module.exports = (a, b) => a + b;\n")],
+        )
+        .unwrap();
+    assert!(good.passed());
+    assert!(!good.tests_detected);
+
+    let bad = runner
+        .run(
+            &ProgrammingLanguage::JavaScript,
+            &[change("index.js", "module.exports = (a, b) => {\n")],
+        )
+        .unwrap();
+    assert!(!bad.passed());
+    assert_eq!(bad.steps[0].exit_code, Some(1));
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn slow_test_commands_time_out() {
+    let root = temp_project(&[]);
+    let runner = SandboxRunner::new(root.clone())
+        .with_timeout(Duration::from_millis(200))
+        .with_test_command(ProgrammingLanguage::Go, "sleep", &["5"]);
+
+    let run = runner.run(&ProgrammingLanguage::Go, &[]).unwrap();
+    let test = run.steps.iter().find(|s| s.kind == StepKind::Test).unwrap();
+    assert!(!test.passed);
+    assert_eq!(test.exit_code, None);
+    assert!(test.output.contains("timed out"));
+    assert!(test.duration < Duration::from_secs(5));
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn multi_language_stage_runs_each_language_once() {
+    if !has_program("python3") {
+        return;
+    }
+    let root = temp_project(&[("test_calc.py", PYTHON_TEST)]);
+    let runner = Arc::new(SandboxRunner::new(root.clone()));
+    let stage = MultiLanguageValidationStage::new().with_sandbox(runner);
+
+    let modification = common::modification(
+        "fix add",
+        vec![
+            change("calc.py", "def add(a, b):\n    return a + b\n"),
+            change("helpers.py", "def unused():\n    pass\n"),
+        ],
+    );
+    let metrics = stage.validate(&modification).unwrap();
+    assert_eq!(metrics["python.pass_rate"], 1.0);
+    assert_eq!(metrics["python.check_pass_rate"], 1.0);
+    assert_eq!(metrics["python.test_pass_rate"], 1.0);
+    std::fs::remove_dir_all(&root).unwrap();
+}
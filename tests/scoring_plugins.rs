@@ -0,0 +1,275 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::query::ScoringPlugins;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::Vector;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+/// A shard of 20 vectors `[i, 0]`, each padded with `(7 * i) % 12`
+/// characters of metadata
+async fn shard(manager: &ShardManager) -> (Uuid, Vec<Uuid>) {
+    let shard_id = manager.create_shard("docs").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let mut ids = Vec::new();
+    for i in 0..20 {
+        let metadata = HashMap::from([("pad".to_string(), "x".repeat((7 * i) % 12))]);
+        ids.push(
+            manager
+                .add_vector(shard_id, Vector::new(vec![i as f32, 0.0]), Some(metadata))
+                .await
+                .unwrap(),
+        );
+    }
+    (shard_id, ids)
+}
+
+fn server(manager: Arc<ShardManager>, scorers: Option<Arc<ScoringPlugins>>) -> Server {
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    );
+    match scorers {
+        Some(scorers) => server.with_scoring_plugins(scorers),
+        None => server,
+    }
+}
+
+fn search(shard_id: Uuid, scorer: &str) -> serde_json::Value {
+    json!({
+        "shard_id": shard_id,
+        "query_vector": [0.0, 0.0],
+        "limit": 3,
+        "scorer": scorer,
+    })
+}
+
+#[tokio::test]
+async fn scorers_must_be_configured_and_registered() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let (shard_id, _) = shard(&manager).await;
+
+    let filter = server(manager.clone(), None).filter();
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search")
+        .json(&search(shard_id, "boost"))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let scorers = Arc::new(ScoringPlugins::new(Arc::new(MetricsCollector::new())));
+    let filter = server(manager, Some(scorers.clone())).filter();
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search")
+        .json(&search(shard_id, "boost"))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = warp::test::request()
+        .method("PUT")
+        .path("/api/scorers/no%20spaces")
+        .body("(module)")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = warp::test::request()
+        .method("DELETE")
+        .path("/api/scorers/boost")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    #[cfg(not(feature = "wasm"))]
+    {
+        let resp = warp::test::request()
+            .method("PUT")
+            .path("/api/scorers/boost")
+            .body("(module)")
+            .reply(&filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::NOT_IMPLEMENTED);
+    }
+    assert!(scorers.list().await.is_empty());
+}
+
+#[cfg(feature = "wasm")]
+mod wasm {
+    use super::*;
+    use amazon_rose_forest::query::{ScorerInfo, ScorerLimits};
+    use amazon_rose_forest::server::api::SearchVectorsResponse;
+    use std::time::{Duration, Instant};
+
+    /// Bump allocator shared by the test modules
+    const ALLOC: &str = r#"
+        (memory (export "memory") 1)
+        (global $next (mut i32) (i32.const 16))
+        (func (export "alloc") (param $len i32) (result i32)
+            (global.get $next)
+            (global.set $next (i32.add (global.get $next) (local.get $len))))
+    "#;
+
+    fn module(score_body: &str) -> String {
+        format!(
+            "(module {} (func (export \"score\") (param i32 i32 i32 i32 i32 i32 f32) (result f32) {}))",
+            ALLOC, score_body
+        )
+    }
+
+    fn first_component() -> String {
+        module("(f32.load (local.get 2))")
+    }
+
+    fn metadata_length() -> String {
+        module("(f32.convert_i32_u (local.get 5))")
+    }
+
+    fn spin() -> String {
+        module("(loop $spin (br $spin)) (f32.const 0)")
+    }
+
+    async fn put(
+        api: &Server,
+        name: &str,
+        module: &str,
+    ) -> warp::http::Response<warp::hyper::body::Bytes> {
+        warp::test::request()
+            .method("PUT")
+            .path(&format!("/api/scorers/{}", name))
+            .body(module.to_string())
+            .reply(&api.filter())
+            .await
+    }
+
+    #[tokio::test]
+    async fn scorers_rerank_a_wider_pool_of_candidates() {
+        let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+        let (shard_id, ids) = shard(&manager).await;
+        let metrics = Arc::new(MetricsCollector::new());
+        let scorers = Arc::new(ScoringPlugins::new(metrics.clone()));
+        let api = server(manager, Some(scorers));
+        let filter = api.filter();
+
+        let resp = put(&api, "first", &first_component()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let info: ScorerInfo = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(info.fuel_per_call, ScorerLimits::default().fuel_per_call);
+        assert_eq!(
+            put(&api, "pad", &metadata_length()).await.status(),
+            StatusCode::OK
+        );
+
+        let ranked = |scorer: &'static str| {
+            let filter = filter.clone();
+            let ids = ids.clone();
+            async move {
+                let resp = warp::test::request()
+                    .method("POST")
+                    .path("/api/search")
+                    .json(&search(shard_id, scorer))
+                    .reply(&filter)
+                    .await;
+                assert_eq!(resp.status(), StatusCode::OK);
+                let body: SearchVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+                body.results
+                    .iter()
+                    .map(|r| {
+                        let position = ids.iter().position(|id| id.to_string() == r.id).unwrap();
+                        (position, r.score)
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // The 12 nearest vectors are scored and the best 3 kept
+        assert_eq!(
+            ranked("first").await,
+            vec![(11, 11.0), (10, 10.0), (9, 9.0)]
+        );
+        // {"pad":"..."} is 10 bytes plus the padding
+        assert_eq!(ranked("pad").await, vec![(5, 21.0), (10, 20.0), (3, 19.0)]);
+        assert_eq!(
+            metrics.get_counter("scoring.candidates_scored").await,
+            Some(24)
+        );
+
+        let listed: Vec<ScorerInfo> = serde_json::from_slice(
+            warp::test::request()
+                .path("/api/scorers")
+                .reply(&filter)
+                .await
+                .body(),
+        )
+        .unwrap();
+        assert_eq!(
+            listed.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+            vec!["first", "pad"]
+        );
+        let resp = warp::test::request()
+            .method("DELETE")
+            .path("/api/scorers/pad")
+            .reply(&filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/api/search")
+            .json(&search(shard_id, "pad"))
+            .reply(&filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn runaway_and_malformed_scorers_are_contained() {
+        let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+        let (shard_id, _) = shard(&manager).await;
+        let metrics = Arc::new(MetricsCollector::new());
+        let scorers = Arc::new(
+            ScoringPlugins::new(metrics.clone()).with_limits(ScorerLimits {
+                fuel_per_call: 10_000,
+                ..Default::default()
+            }),
+        );
+        let api = server(manager, Some(scorers.clone()));
+
+        // An endless loop burns its fuel and fails the search promptly
+        assert_eq!(put(&api, "spin", &spin()).await.status(), StatusCode::OK);
+        let started = Instant::now();
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/api/search")
+            .json(&search(shard_id, "spin"))
+            .reply(&api.filter())
+            .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert!(String::from_utf8_lossy(resp.body()).contains("ran out of fuel"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(metrics.get_counter("scoring.failures").await, Some(1));
+
+        // Modules can't import anything, and must export the whole ABI
+        let imports = r#"(module (import "env" "clock" (func)) (memory (export "memory") 1))"#;
+        let no_alloc = r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "score") (param i32 i32 i32 i32 i32 i32 f32) (result f32)
+                    (f32.const 0)))
+        "#;
+        for module in [imports, no_alloc, "not wasm"] {
+            let resp = put(&api, "broken", module).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", module);
+        }
+        assert_eq!(scorers.list().await.len(), 1);
+    }
+}
@@ -0,0 +1,200 @@
+use amazon_rose_forest::{
+    core::{audit::AuditLog, metrics::MetricsCollector},
+    sharding::{
+        hnsw::HnswParams,
+        manager::ShardManager,
+        scrubber::{ConsistencyChecker, ScrubberConfig},
+        vector_index::{DistanceMetric, IndexType, VectorEntry},
+    },
+    Vector,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+async fn shard_with_index(index_type: IndexType) -> (Arc<ShardManager>, Uuid) {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("scrubbed").await.unwrap();
+    manager
+        .create_vector_index_with_type(shard_id, "main", 4, DistanceMetric::Euclidean, index_type)
+        .await
+        .unwrap();
+    (manager, shard_id)
+}
+
+fn checker(manager: Arc<ShardManager>, repair: bool) -> (ConsistencyChecker, Arc<AuditLog>) {
+    let audit_log = Arc::new(AuditLog::new());
+    let checker = ConsistencyChecker::new(
+        manager,
+        Arc::new(MetricsCollector::new()),
+        audit_log.clone(),
+        ScrubberConfig {
+            repair,
+            ..ScrubberConfig::default()
+        },
+    );
+    (checker, audit_log)
+}
+
+#[tokio::test]
+async fn scrubber_reports_clean_shards() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let shard_id = manager.create_shard("scrubbed").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 4, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for _ in 0..25 {
+        manager
+            .add_vector(shard_id, Vector::random(4), None)
+            .await
+            .unwrap();
+    }
+
+    let audit_log = Arc::new(AuditLog::new());
+    let checker = ConsistencyChecker::new(
+        manager,
+        metrics.clone(),
+        audit_log.clone(),
+        ScrubberConfig::default(),
+    );
+
+    let reports = checker.run_once().await;
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].shard_id, shard_id);
+    assert_eq!(reports[0].report.checked, 25);
+    assert!(reports[0].report.is_consistent());
+
+    assert_eq!(metrics.get_counter("scrubber.runs").await, Some(1));
+    assert!(audit_log.is_empty().await);
+}
+
+async fn scrub_during_inserts(index_type: IndexType) {
+    let (manager, shard_id) = shard_with_index(index_type).await;
+    let (checker, audit_log) = checker(manager.clone(), true);
+    let writers: Vec<_> = (0..4)
+        .map(|_| {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                for _ in 0..250 {
+                    manager
+                        .add_vector(shard_id, Vector::random(4), None)
+                        .await
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+    while writers.iter().any(|writer| !writer.is_finished()) {
+        for report in checker.run_once().await {
+            assert!(report.report.is_consistent(), "{:?}", report.report);
+        }
+    }
+    for writer in writers {
+        writer.await.unwrap();
+    }
+
+    assert!(audit_log.is_empty().await);
+    let report = manager.verify_shard(shard_id, false).await.unwrap();
+    assert_eq!(report.checked, 1000);
+    assert!(report.is_consistent());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn inserts_during_a_scrub_are_not_reported() {
+    scrub_during_inserts(IndexType::Hilbert).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn inserts_during_a_scrub_of_an_hnsw_shard_are_not_reported() {
+    scrub_during_inserts(IndexType::Hnsw(HnswParams::default())).await;
+}
+
+#[tokio::test]
+async fn vectors_diverging_from_the_change_feed_are_reported() {
+    let (manager, shard_id) = shard_with_index(IndexType::Hilbert).await;
+    let mut metadata = HashMap::new();
+    metadata.insert("source".to_string(), "docs".to_string());
+    let kept = manager
+        .add_vector(shard_id, Vector::random(4), Some(metadata))
+        .await
+        .unwrap();
+    let deleted = manager
+        .add_vector(shard_id, Vector::random(4), None)
+        .await
+        .unwrap();
+    let stored = manager.get_vector_index(shard_id).await.unwrap();
+    let deleted_entry = stored.get(deleted).await.unwrap();
+    manager.remove_vector(shard_id, deleted).await.unwrap();
+
+    // Change the index behind the manager's back, so the feed still logs
+    // `kept` as inserted and `deleted` as deleted
+    stored.remove(kept).await.unwrap();
+    stored.add_entry(deleted_entry).await.unwrap();
+
+    let (checker, audit_log) = checker(manager.clone(), false);
+    let reports = checker.run_once().await;
+    let mut diverged = reports[0].report.diverged.clone();
+    diverged.sort();
+    let mut expected = vec![kept, deleted];
+    expected.sort();
+    assert_eq!(diverged, expected);
+    assert_eq!(reports[0].report.repaired, 0);
+    let events = audit_log.by_category("scrubber", 10).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].action, "inconsistency");
+
+    // Nothing was changed without repair
+    assert!(stored.get(kept).await.is_none());
+    assert!(stored.get(deleted).await.is_some());
+}
+
+#[tokio::test]
+async fn repairing_scrubs_restore_vectors_from_the_change_feed() {
+    let (manager, shard_id) = shard_with_index(IndexType::Hnsw(HnswParams::default())).await;
+    let mut metadata = HashMap::new();
+    metadata.insert("source".to_string(), "docs".to_string());
+    let vector = Vector::random(4);
+    let kept = manager
+        .add_vector(shard_id, vector.clone(), Some(metadata.clone()))
+        .await
+        .unwrap();
+    for _ in 0..10 {
+        manager
+            .add_vector(shard_id, Vector::random(4), None)
+            .await
+            .unwrap();
+    }
+    let stored = manager.get_vector_index(shard_id).await.unwrap();
+
+    // Overwrite the stored copy with values and metadata the feed never saw
+    let original = stored.get(kept).await.unwrap();
+    stored.remove(kept).await.unwrap();
+    stored
+        .add_entry(VectorEntry {
+            vector: Vector::random(4),
+            metadata: None,
+            ..original
+        })
+        .await
+        .unwrap();
+
+    let (checker, audit_log) = checker(manager.clone(), true);
+    let reports = checker.run_once().await;
+    assert_eq!(reports[0].report.diverged, vec![kept]);
+    assert_eq!(reports[0].report.repaired, 1);
+    let events = audit_log.by_category("scrubber", 10).await;
+    assert_eq!(events[0].action, "repair");
+
+    let restored = stored.get(kept).await.unwrap();
+    assert_eq!(restored.vector.values, vector.values);
+    assert_eq!(restored.metadata, Some(metadata));
+    assert!(manager
+        .verify_shard(shard_id, false)
+        .await
+        .unwrap()
+        .is_consistent());
+    let results = stored.search(&vector, 1).await.unwrap();
+    assert_eq!(results[0].id, kept);
+}
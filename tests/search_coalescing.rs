@@ -0,0 +1,163 @@
+use amazon_rose_forest::{
+    core::metrics::MetricsCollector,
+    query::{PredicateOp, QueryExpr},
+    sharding::{
+        coalesce::{SearchCoalescer, SearchKey},
+        manager::ShardManager,
+        query_cache::QueryCacheConfig,
+        vector_index::{DistanceMetric, SearchOutcome},
+    },
+    Vector,
+};
+use futures::future::join_all;
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[test]
+fn equivalent_searches_share_a_key() {
+    let shard_id = Uuid::new_v4();
+    let red = QueryExpr::field("color", PredicateOp::Eq, json!("red"));
+    let cheap = QueryExpr::field("price", PredicateOp::Lt, json!(10));
+    let large = QueryExpr::field("size", PredicateOp::Gte, json!(3));
+
+    let written = QueryExpr::And(vec![
+        red.clone(),
+        QueryExpr::And(vec![cheap.clone(), large.clone()]),
+    ]);
+    let rewritten = QueryExpr::And(vec![
+        large.clone(),
+        QueryExpr::Not(Box::new(QueryExpr::Not(Box::new(cheap.clone())))),
+        red.clone(),
+        red.clone(),
+    ]);
+    let query = Vector::new(vec![0.0, 1.0]);
+    assert_eq!(
+        SearchKey::new(shard_id, &query, 5, Some(&written)),
+        SearchKey::new(shard_id, &query, 5, Some(&rewritten))
+    );
+    assert_eq!(QueryExpr::Or(vec![red.clone()]).canonical(), red.clone());
+
+    // Negative zero scores like zero
+    assert_eq!(
+        SearchKey::new(shard_id, &query, 5, None),
+        SearchKey::new(shard_id, &Vector::new(vec![-0.0, 1.0]), 5, None)
+    );
+    assert_ne!(
+        SearchKey::new(shard_id, &query, 5, None),
+        SearchKey::new(shard_id, &query, 6, None)
+    );
+    assert_ne!(
+        SearchKey::new(shard_id, &query, 5, Some(&written)),
+        SearchKey::new(
+            shard_id,
+            &query,
+            5,
+            Some(&QueryExpr::Or(vec![red, cheap, large]))
+        )
+    );
+}
+
+#[tokio::test]
+async fn concurrent_identical_searches_run_once() {
+    let coalescer = SearchCoalescer::new(true);
+    let key = SearchKey::new(Uuid::new_v4(), &Vector::new(vec![1.0, 2.0]), 3, None);
+    let runs = AtomicUsize::new(0);
+
+    let outcomes = join_all((0..8).map(|_| {
+        coalescer.run(key.clone(), || async {
+            runs.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(SearchOutcome::default())
+        })
+    }))
+    .await;
+
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    assert!(outcomes.iter().all(|(outcome, _)| outcome.is_ok()));
+    assert_eq!(outcomes.iter().filter(|(_, shared)| *shared).count(), 7);
+    let stats = coalescer.stats();
+    assert_eq!(
+        (stats.executions, stats.coalesced, stats.in_flight),
+        (1, 7, 0)
+    );
+
+    // Finished searches aren't shared with later requests
+    let (_, shared) = coalescer
+        .run(key, || async { Ok(SearchOutcome::default()) })
+        .await;
+    assert!(!shared);
+    assert_eq!(coalescer.stats().executions, 2);
+}
+
+#[tokio::test]
+async fn errors_are_shared_and_disabled_coalescers_search_every_time() {
+    let coalescer = SearchCoalescer::new(true);
+    let key = SearchKey::new(Uuid::new_v4(), &Vector::new(vec![1.0]), 1, None);
+    let outcomes = join_all((0..3).map(|_| {
+        coalescer.run(key.clone(), || async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Err("index unavailable".to_string())
+        })
+    }))
+    .await;
+    assert!(outcomes
+        .iter()
+        .all(|(outcome, _)| outcome.as_ref().unwrap_err() == "index unavailable"));
+
+    let coalescer = SearchCoalescer::new(false);
+    let runs = AtomicUsize::new(0);
+    join_all((0..3).map(|_| {
+        coalescer.run(key.clone(), || async {
+            runs.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(SearchOutcome::default())
+        })
+    }))
+    .await;
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn manager_counts_executions_and_coalesced_searches() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = ShardManager::new(metrics.clone()).with_query_cache(QueryCacheConfig {
+        enabled: false,
+        ..Default::default()
+    });
+    let shard_id = manager.create_shard("herd").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for i in 0..100 {
+        manager
+            .add_vector(shard_id, Vector::new(vec![i as f32, 1.0]), None)
+            .await
+            .unwrap();
+    }
+
+    let query = Vector::new(vec![3.0, 1.0]);
+    let results = join_all((0..10).map(|_| manager.search_vectors(shard_id, &query, 3))).await;
+    let ids: Vec<Vec<Uuid>> = results
+        .into_iter()
+        .map(|result| result.unwrap().iter().map(|r| r.id).collect())
+        .collect();
+    assert!(ids.iter().all(|found| *found == ids[0]));
+
+    let stats = manager.coalescing_stats();
+    assert_eq!(stats.executions + stats.coalesced, 10);
+    assert_eq!(
+        metrics.get_counter("search_coalescing.executions").await,
+        Some(stats.executions)
+    );
+    assert_eq!(
+        metrics
+            .get_counter("search_coalescing.coalesced")
+            .await
+            .unwrap_or(0),
+        stats.coalesced
+    );
+}
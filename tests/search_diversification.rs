@@ -0,0 +1,134 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::query::{Diversification, MmrOptions};
+use amazon_rose_forest::server::api::SearchVectorsResponse;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::{DistanceMetric, SearchResult};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+/// Three near-identical chunks of document "a" nearest the origin, then one
+/// chunk each of "b" and "c", ordered by Euclidean distance from the origin
+fn chunks() -> Vec<(&'static str, [f32; 2])> {
+    vec![
+        ("a", [0.10, 0.0]),
+        ("a", [0.11, 0.0]),
+        ("a", [0.12, 0.0]),
+        ("b", [0.0, 0.30]),
+        ("c", [-0.40, 0.0]),
+    ]
+}
+
+fn candidates() -> Vec<SearchResult> {
+    let origin = Vector::new(vec![0.0, 0.0]);
+    chunks()
+        .into_iter()
+        .map(|(doc, values)| {
+            let vector = Vector::new(values.to_vec());
+            SearchResult {
+                id: Uuid::new_v4(),
+                score: DistanceMetric::Euclidean.calculate(&origin, &vector),
+                vector,
+                metadata: Some(HashMap::from([("doc".to_string(), doc.to_string())])),
+            }
+        })
+        .collect()
+}
+
+fn docs(results: &[SearchResult]) -> Vec<String> {
+    results
+        .iter()
+        .map(|r| r.metadata.as_ref().unwrap()["doc"].clone())
+        .collect()
+}
+
+#[test]
+fn group_by_and_mmr_spread_results_across_documents() {
+    let select = |diversify: &Diversification, limit| {
+        docs(&diversify.apply(candidates(), DistanceMetric::Euclidean, limit))
+    };
+
+    assert_eq!(select(&Diversification::default(), 3), ["a", "a", "a"]);
+
+    let grouped = Diversification {
+        group_by: Some("doc".into()),
+        ..Default::default()
+    };
+    assert_eq!(select(&grouped, 3), ["a", "b", "c"]);
+    assert_eq!(grouped.candidate_limit(3), 12);
+
+    let two_per_doc = Diversification {
+        group_size: Some(2),
+        ..grouped
+    };
+    assert_eq!(select(&two_per_doc, 3), ["a", "a", "b"]);
+
+    // MMR skips the redundant second chunk without needing metadata, and
+    // prefers the chunk farthest from what's already selected
+    let mmr = Diversification {
+        mmr: Some(MmrOptions {
+            lambda: 0.5,
+            fetch_k: None,
+        }),
+        ..Default::default()
+    };
+    assert_eq!(select(&mmr, 2), ["a", "c"]);
+
+    // Pure relevance keeps the original order
+    let relevance_only = Diversification {
+        mmr: Some(MmrOptions {
+            lambda: 1.0,
+            fetch_k: Some(20),
+        }),
+        ..Default::default()
+    };
+    assert_eq!(select(&relevance_only, 2), ["a", "a"]);
+    assert_eq!(relevance_only.candidate_limit(2), 20);
+}
+
+#[tokio::test]
+async fn search_api_accepts_group_by() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("chunks").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for (doc, values) in chunks() {
+        let metadata = HashMap::from([("doc".to_string(), doc.to_string())]);
+        manager
+            .add_vector(shard_id, Vector::new(values.to_vec()), Some(metadata))
+            .await
+            .unwrap();
+    }
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    );
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search")
+        .json(&serde_json::json!({
+            "shard_id": shard_id,
+            "query_vector": [0.0, 0.0],
+            "limit": 2,
+            "group_by": "doc",
+        }))
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: SearchVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+    let docs: HashSet<&str> = body
+        .results
+        .iter()
+        .map(|r| r.metadata.as_ref().unwrap()["doc"].as_str())
+        .collect();
+    assert_eq!(body.results.len(), 2);
+    assert_eq!(docs.len(), 2);
+}
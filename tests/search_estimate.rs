@@ -0,0 +1,148 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::query::{
+    Diversification, LatencyBand, PredicateOp, QueryExpr, SearchEstimate,
+};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::Vector;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+const DIMENSIONS: usize = 8;
+
+async fn shard(metrics: Arc<MetricsCollector>, vectors: usize) -> (Arc<ShardManager>, Uuid) {
+    let manager = Arc::new(ShardManager::new(metrics));
+    let shard_id = manager.create_shard("docs").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", DIMENSIONS, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for i in 0..vectors {
+        let kind = if i % 2 == 0 { "article" } else { "comment" };
+        let metadata = HashMap::from([("kind".to_string(), kind.to_string())]);
+        manager
+            .add_vector(shard_id, Vector::random(DIMENSIONS), Some(metadata))
+            .await
+            .unwrap();
+    }
+    (manager, shard_id)
+}
+
+#[tokio::test]
+async fn estimates_follow_the_planner_without_searching() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let (manager, shard_id) = shard(metrics.clone(), 2000).await;
+    let query = vec![Vector::random(DIMENSIONS)];
+    let plain = Diversification::default();
+
+    // Too few neighbours for a large limit falls back to a full scan
+    let unfiltered = manager
+        .estimate_search(shard_id, &query, 1000, None, &plain, None, None)
+        .await
+        .unwrap();
+    assert_eq!(unfiltered.full_scans, 1);
+    assert_eq!(unfiltered.vectors_scanned, 2000);
+    assert_eq!(unfiltered.vectors_scored, 2000);
+    assert_eq!(unfiltered.selectivity, None);
+    assert!(unfiltered.memory_bytes >= 2000 * (DIMENSIONS as u64 * 4));
+    assert_eq!(unfiltered.latency_band, LatencyBand::Fast);
+
+    // A filter matching half the shard halves the vectors scored
+    let filter = QueryExpr::field("kind", PredicateOp::Eq, "article".into());
+    let filtered = manager
+        .estimate_search(shard_id, &query, 1000, Some(&filter), &plain, None, None)
+        .await
+        .unwrap();
+    assert_eq!(filtered.vectors_scanned, 2000);
+    assert!((filtered.selectivity.unwrap() - 0.5).abs() < 0.05);
+    assert!((900..=1100).contains(&filtered.vectors_scored));
+
+    // Each extra query vector is another search
+    let two = vec![query[0].clone(), Vector::random(DIMENSIONS)];
+    let fused = manager
+        .estimate_search(shard_id, &two, 1000, None, &plain, None, None)
+        .await
+        .unwrap();
+    assert_eq!(fused.vectors_scored, 4000);
+    assert_eq!(fused.memory_bytes, unfiltered.memory_bytes);
+    assert!(fused.estimated_latency_ms > unfiltered.estimated_latency_ms);
+
+    let tight = manager
+        .estimate_search(
+            shard_id,
+            &query,
+            1000,
+            None,
+            &plain,
+            None,
+            Some(Duration::ZERO),
+        )
+        .await
+        .unwrap();
+    assert!(tight.likely_partial);
+
+    assert_eq!(
+        metrics.get_counter("vector_index.main.searches").await,
+        None
+    );
+    assert_eq!(metrics.get_counter("search.estimated").await, Some(4));
+}
+
+#[test]
+fn latency_bands_split_at_their_thresholds() {
+    assert_eq!(LatencyBand::for_latency(9.9), LatencyBand::Fast);
+    assert_eq!(LatencyBand::for_latency(10.0), LatencyBand::Moderate);
+    assert_eq!(LatencyBand::for_latency(250.0), LatencyBand::Slow);
+
+    let mut estimate = SearchEstimate::for_scan(1_000_000, 1_000_000, true, 768, 0, None);
+    assert_eq!(estimate.latency_band, LatencyBand::Slow);
+    estimate.merge(SearchEstimate::for_scan(10, 10, false, 768, 0, None));
+    assert_eq!(estimate.vectors_scored, 1_000_010);
+    assert_eq!(estimate.full_scans, 1);
+}
+
+#[tokio::test]
+async fn estimate_endpoint_takes_a_search_request() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let (manager, shard_id) = shard(metrics.clone(), 200).await;
+    let filter = Server::new(ServerConfig::default(), metrics, None, Some(manager)).filter();
+    let estimate = |body: serde_json::Value| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/search/estimate")
+            .json(&body)
+            .reply(&filter)
+    };
+
+    let resp = estimate(json!({
+        "shard_id": shard_id,
+        "query_vector": vec![0.5; DIMENSIONS],
+        "limit": 150,
+        "timeout_ms": 0,
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: SearchEstimate = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.vectors_scanned, 200);
+    assert!(body.likely_partial);
+
+    let resp = estimate(json!({
+        "shard_id": shard_id,
+        "query_vector": [0.5, 0.5],
+        "limit": 10,
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = estimate(json!({
+        "shard_id": shard_id,
+        "query_vector": vec![0.5; DIMENSIONS],
+        "limit": 0,
+    }))
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
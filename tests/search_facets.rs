@@ -0,0 +1,110 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::query::{Diversification, FacetRequest, FacetValue};
+use amazon_rose_forest::server::api::SearchVectorsResponse;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+async fn catalog() -> (Arc<ShardManager>, Uuid) {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("catalog").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    // Identical vectors so every item lands in the scanned neighbourhood
+    for category in ["shoes", "shoes", "hats", "shoes", "hats", ""] {
+        let metadata = (!category.is_empty())
+            .then(|| HashMap::from([("category".to_string(), category.to_string())]));
+        manager
+            .add_vector(shard_id, Vector::new(vec![0.5, 0.5]), metadata)
+            .await
+            .unwrap();
+    }
+    (manager, shard_id)
+}
+
+#[tokio::test]
+async fn facets_count_the_candidate_set() {
+    let (manager, shard_id) = catalog().await;
+    let request = FacetRequest {
+        fields: vec!["category".into(), "brand".into()],
+        ..Default::default()
+    };
+    let (results, facets) = manager
+        .search_vectors_faceted(
+            shard_id,
+            &Vector::new(vec![0.5, 0.5]),
+            2,
+            None,
+            &Diversification::default(),
+            &request,
+        )
+        .await
+        .unwrap();
+
+    // Counts cover the candidates, not just the returned page
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        facets["category"],
+        [
+            FacetValue {
+                value: "shoes".into(),
+                count: 3
+            },
+            FacetValue {
+                value: "hats".into(),
+                count: 2
+            },
+        ]
+    );
+    assert!(facets["brand"].is_empty());
+}
+
+#[tokio::test]
+async fn search_api_returns_facets() {
+    let (manager, shard_id) = catalog().await;
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    );
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search")
+        .json(&serde_json::json!({
+            "shard_id": shard_id,
+            "query_vector": [0.5, 0.5],
+            "limit": 1,
+            "facets": {"fields": ["category"], "max_values": 1},
+        }))
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: SearchVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.results.len(), 1);
+    let facets = body.facets.unwrap();
+    assert_eq!(facets["category"].len(), 1);
+    assert_eq!(facets["category"][0].value, "shoes");
+
+    // Facets are omitted unless requested
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search")
+        .json(&serde_json::json!({
+            "shard_id": shard_id,
+            "query_vector": [0.5, 0.5],
+            "limit": 1,
+        }))
+        .reply(&server.filter())
+        .await;
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert!(body.get("facets").is_none());
+}
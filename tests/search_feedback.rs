@@ -0,0 +1,191 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::evaluation::Evaluation;
+use amazon_rose_forest::hypothesis::Hypothesis;
+use amazon_rose_forest::intelligence::ranking::examples;
+use amazon_rose_forest::query::feedback::{FeedbackLabel, SearchLog, ShownResult};
+use amazon_rose_forest::query::slow_log::{SlowQuery, SlowQueryFeedback, SlowQueryLog};
+use amazon_rose_forest::server::api::FeedbackReport;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::utils::errors::FeedbackError;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+fn shown(ids: &[&str]) -> Vec<ShownResult> {
+    ids.iter()
+        .map(|id| ShownResult {
+            id: id.to_string(),
+            score: 0.5,
+            metadata_fields: 0,
+        })
+        .collect()
+}
+
+fn slow_query(query_id: Option<Uuid>, latency_ms: u64) -> SlowQuery {
+    SlowQuery {
+        query_id,
+        shard_id: Uuid::new_v4(),
+        limit: 3,
+        results: 3,
+        partial: false,
+        latency_ms,
+        recorded_at: chrono::Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn persisted_feedback_survives_reopen() {
+    let path = std::env::temp_dir().join(format!("search-log-{}/log.jsonl", Uuid::new_v4()));
+    let (kept, dropped) = {
+        let log = SearchLog::open(&path, 1).unwrap();
+        let dropped = log.record_impression(Uuid::new_v4(), shown(&["a"])).await;
+        log.record_feedback(dropped, "a", FeedbackLabel::Click)
+            .await
+            .unwrap();
+        let kept = log.record_impression(Uuid::new_v4(), shown(&["b"])).await;
+        log.record_feedback(kept, "b", FeedbackLabel::Accept)
+            .await
+            .unwrap();
+        (kept, dropped)
+    };
+
+    let log = SearchLog::open(&path, 1).unwrap();
+    assert!(log.impression(dropped).await.is_none());
+    let entry = log.impression(kept).await.unwrap();
+    assert_eq!(entry.feedback[0].label, FeedbackLabel::Accept);
+    assert_eq!(log.feedback_received(), 2);
+
+    // Feedback after reopening is appended to the same file
+    log.record_feedback(kept, "b", FeedbackLabel::Click)
+        .await
+        .unwrap();
+    let log = SearchLog::open(&path, 1).unwrap();
+    assert_eq!(log.impression(kept).await.unwrap().feedback.len(), 2);
+}
+
+#[tokio::test]
+async fn relevance_labels_shape_recall_and_training() {
+    let log = SearchLog::new();
+    let query_id = log
+        .record_impression(Uuid::new_v4(), shown(&["a", "b", "c", "d"]))
+        .await;
+    for (id, label) in [
+        ("a", FeedbackLabel::Irrelevant),
+        ("b", FeedbackLabel::Click),
+        ("d", FeedbackLabel::Irrelevant),
+        ("x", FeedbackLabel::Missing),
+    ] {
+        log.record_feedback(query_id, id, label).await.unwrap();
+    }
+    // Only missing results may name something the search didn't return
+    assert!(matches!(
+        log.record_feedback(query_id, "y", FeedbackLabel::Irrelevant)
+            .await,
+        Err(FeedbackError::UnknownResult { .. })
+    ));
+
+    let labelled = log.labelled().await;
+    let report = Evaluation::new().feedback_recall(&labelled);
+    assert_eq!((report.queries, report.found, report.missed), (1, 1, 1));
+    assert_eq!(report.recall, 0.5);
+
+    // a and d are explicit negatives, b is positive, c falls below the click
+    let relevant: Vec<bool> = examples(&labelled).iter().map(|e| e.relevant).collect();
+    assert_eq!(relevant, vec![false, true, false]);
+}
+
+#[tokio::test]
+async fn slow_queries_join_their_feedback() {
+    let log = SearchLog::new();
+    let slow = SlowQueryLog::new(Duration::from_millis(100));
+    let fast_id = log.record_impression(Uuid::new_v4(), shown(&["a"])).await;
+    let slow_id = log.record_impression(Uuid::new_v4(), shown(&["a"])).await;
+    log.record_feedback(fast_id, "a", FeedbackLabel::Click)
+        .await
+        .unwrap();
+    log.record_feedback(slow_id, "z", FeedbackLabel::Missing)
+        .await
+        .unwrap();
+
+    assert!(!slow.record(slow_query(Some(fast_id), 20)).await);
+    assert!(slow.record(slow_query(Some(slow_id), 250)).await);
+    assert!(slow.record(slow_query(None, 400)).await);
+
+    let joined = slow.join(&log).await;
+    assert_eq!(joined.len(), 2);
+    assert_eq!(joined[0].impression.as_ref().unwrap().feedback.len(), 1);
+    assert!(joined[1].impression.is_none());
+
+    let evaluation = Evaluation::new();
+    let all = evaluation.feedback_recall(&log.labelled().await);
+    let slow_recall =
+        evaluation.feedback_recall(joined.iter().filter_map(|j| j.impression.as_ref()));
+    assert_eq!((all.recall, slow_recall.recall), (0.5, 0.0));
+
+    let analysis = evaluation.recall_analysis(&all, &slow_recall);
+    let hypothesis = Hypothesis::new().search_quality(&analysis).unwrap();
+    assert!(hypothesis.contains("slow queries"));
+    assert!(Hypothesis::new()
+        .search_quality(&evaluation.recall_analysis(&all, &Default::default()))
+        .is_some_and(|h| !h.contains("slow queries")));
+}
+
+#[tokio::test]
+async fn feedback_report_and_slow_queries_over_http() {
+    let log = Arc::new(SearchLog::new());
+    let slow = Arc::new(SlowQueryLog::new(Duration::ZERO));
+    let query_id = log
+        .record_impression(Uuid::new_v4(), shown(&["a", "b"]))
+        .await;
+    slow.record(slow_query(Some(query_id), 5)).await;
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    )
+    .with_search_log(log.clone())
+    .with_slow_query_log(slow)
+    .filter();
+
+    for (id, label) in [("a", "click"), ("c", "missing")] {
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/api/feedback")
+            .json(&serde_json::json!({ "query_id": query_id, "result_id": id, "label": label }))
+            .reply(&filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    let resp = warp::test::request()
+        .path("/api/feedback/report")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let report: FeedbackReport = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(report.recall.recall, 0.5);
+    assert_eq!(report.slow_query_recall.queries, 1);
+    assert!(report.hypothesis.is_some());
+
+    let resp = warp::test::request()
+        .path("/api/slow-queries")
+        .reply(&filter)
+        .await;
+    let joined: Vec<SlowQueryFeedback> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(joined[0].impression.as_ref().unwrap().feedback.len(), 2);
+
+    let unconfigured = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    )
+    .filter();
+    let resp = warp::test::request()
+        .path("/api/slow-queries")
+        .reply(&unconfigured)
+        .await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
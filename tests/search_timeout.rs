@@ -0,0 +1,116 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::query::Diversification;
+use amazon_rose_forest::server::api::SearchVectorsResponse;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+async fn crowded_shard(metrics: Arc<MetricsCollector>) -> (Arc<ShardManager>, Uuid) {
+    let manager = Arc::new(ShardManager::new(metrics));
+    let shard_id = manager.create_shard("crowded").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    // Identical vectors so every one is a candidate and the scan is long
+    for _ in 0..1000 {
+        manager
+            .add_vector(shard_id, Vector::new(vec![0.5, 0.5]), None)
+            .await
+            .unwrap();
+    }
+    (manager, shard_id)
+}
+
+#[tokio::test]
+async fn expired_deadline_returns_partial_results() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let (manager, shard_id) = crowded_shard(metrics.clone()).await;
+    let query = Vector::new(vec![0.5, 0.5]);
+    let diversify = Diversification::default();
+
+    let partial = manager
+        .search_vectors_within(
+            shard_id,
+            &query,
+            5,
+            None,
+            &diversify,
+            None,
+            Some(Duration::ZERO),
+        )
+        .await
+        .unwrap();
+    assert!(partial.partial);
+    assert_eq!(partial.results.len(), 5);
+
+    // The partial page isn't cached, so a patient search scans everything
+    let complete = manager
+        .search_vectors_within(
+            shard_id,
+            &query,
+            5,
+            None,
+            &diversify,
+            None,
+            Some(Duration::from_secs(30)),
+        )
+        .await
+        .unwrap();
+    assert!(!complete.partial);
+    assert_eq!(complete.results.len(), 5);
+
+    assert_eq!(metrics.get_counter("search.with_timeout").await, Some(2));
+    assert_eq!(metrics.get_counter("search.partial").await, Some(1));
+    assert_eq!(metrics.get_gauge("search.partial_rate_pct").await, Some(50));
+
+    // Searches without a timeout aren't counted
+    let untimed = manager
+        .search_vectors_within(shard_id, &query, 5, None, &diversify, None, None)
+        .await
+        .unwrap();
+    assert!(!untimed.partial);
+    assert_eq!(metrics.get_counter("search.with_timeout").await, Some(2));
+}
+
+#[tokio::test]
+async fn search_endpoint_flags_partial_results() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let (manager, shard_id) = crowded_shard(metrics.clone()).await;
+    let server = Server::new(ServerConfig::default(), metrics, None, Some(manager));
+    let filter = server.filter();
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search")
+        .json(&serde_json::json!({
+            "shard_id": shard_id,
+            "query_vector": [0.5, 0.5],
+            "limit": 3,
+            "timeout_ms": 0
+        }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: SearchVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert!(body.partial);
+    assert_eq!(body.results.len(), 3);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/search")
+        .json(&serde_json::json!({
+            "shard_id": shard_id,
+            "query_vector": [0.5, 0.5],
+            "limit": 3
+        }))
+        .reply(&filter)
+        .await;
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["partial"], false);
+}
@@ -0,0 +1,111 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::tuning::{
+    LatencySlo, SearchParams, SearchTuner, TuningAction, MAX_PROBE_WINDOW, MIN_PROBE_WINDOW,
+};
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use std::sync::Arc;
+
+/// Observe searches at a fixed latency until the window closes
+fn run_window(tuner: &mut SearchTuner, latency_ms: f64, params: SearchParams) -> SearchParams {
+    loop {
+        if let Some(decision) = tuner.observe(latency_ms, params) {
+            return decision.params;
+        }
+    }
+}
+
+#[test]
+fn tuner_narrows_when_slow_and_widens_with_headroom() {
+    let mut tuner = SearchTuner::new(LatencySlo {
+        window: 10,
+        ..LatencySlo::new(10.0)
+    });
+    let start = SearchParams::default();
+
+    // Nothing happens until a window is full
+    assert!(tuner.observe(50.0, start).is_none());
+
+    let mut params = start;
+    for _ in 0..20 {
+        params = run_window(&mut tuner, 50.0, params);
+    }
+    assert_eq!(params.probe_window, MIN_PROBE_WINDOW);
+    assert_eq!(params.candidate_multiplier, 1);
+    assert_eq!(tuner.decisions().last().unwrap().action, TuningAction::Hold);
+
+    // Within target but without headroom, parameters stay put
+    assert_eq!(run_window(&mut tuner, 9.0, params), params);
+
+    let widened = run_window(&mut tuner, 1.0, params);
+    assert_eq!(widened.probe_window, params.probe_window + 1);
+    assert_eq!(
+        tuner.decisions().last().unwrap().action,
+        TuningAction::Widen
+    );
+
+    // The window maxes out before more candidates are demanded
+    let mut params = widened;
+    for _ in 0..MAX_PROBE_WINDOW {
+        params = run_window(&mut tuner, 1.0, params);
+    }
+    assert_eq!(params.probe_window, MAX_PROBE_WINDOW);
+    assert!(params.candidate_multiplier > 1);
+}
+
+#[tokio::test]
+async fn index_applies_slo_decisions_and_records_metrics() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = ShardManager::new(metrics.clone());
+    let shard_id = manager.create_shard("tuned").await.unwrap();
+    let index = manager
+        .create_vector_index(shard_id, "tuned", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for i in 0..20 {
+        index
+            .add(Vector::new(vec![i as f32 / 20.0, 0.0]), None)
+            .await
+            .unwrap();
+    }
+
+    // No search can meet a zero target
+    manager
+        .set_search_slo(
+            shard_id,
+            Some(LatencySlo {
+                window: 5,
+                ..LatencySlo::new(0.0)
+            }),
+        )
+        .await
+        .unwrap();
+    for _ in 0..5 {
+        index.search(&Vector::new(vec![0.5, 0.0]), 3).await.unwrap();
+    }
+
+    let params = index.search_params().await;
+    assert!(params.probe_window < SearchParams::default().probe_window);
+    let decisions = index.tuning_decisions().await;
+    assert_eq!(decisions.len(), 1);
+    assert_eq!(decisions[0].action, TuningAction::Narrow);
+    assert_eq!(
+        metrics
+            .get_gauge("vector_index.tuned.tuning.probe_window")
+            .await,
+        Some(params.probe_window)
+    );
+    assert_eq!(
+        metrics
+            .get_counter("vector_index.tuned.tuning.narrow")
+            .await,
+        Some(1)
+    );
+
+    // Removing the SLO keeps the tuned parameters
+    manager.set_search_slo(shard_id, None).await.unwrap();
+    index.search(&Vector::new(vec![0.5, 0.0]), 3).await.unwrap();
+    assert_eq!(index.search_params().await, params);
+    assert!(index.tuning_decisions().await.is_empty());
+}
@@ -0,0 +1,217 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::network::secure_channel::{
+    NodeIdentity, ReplyTo, SealedMessage, SecureChannels,
+};
+use amazon_rose_forest::network::trust::{TrustEvent, TrustManager};
+use amazon_rose_forest::utils::errors::SecureChannelError;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::sync::Arc;
+
+/// Two nodes that know each other's identity keys
+async fn pair() -> (SecureChannels, SecureChannels) {
+    let a = SecureChannels::new(NodeIdentity::generate("a"));
+    let b = SecureChannels::new(NodeIdentity::generate("b"));
+    a.add_peer("b", b.identity().public_key()).await;
+    b.add_peer("a", a.identity().public_key()).await;
+    (a, b)
+}
+
+async fn connect(a: &SecureChannels, b: &SecureChannels) {
+    let hello = a.initiate("b").await.unwrap();
+    let reply = b.accept(&hello).await.unwrap();
+    a.complete(&reply).await.unwrap();
+}
+
+#[tokio::test]
+async fn handshake_sets_up_sessions_both_ways() {
+    let (a, b) = pair().await;
+    assert!(matches!(
+        a.seal("b", b"hi").await,
+        Err(SecureChannelError::NoSession(_))
+    ));
+    connect(&a, &b).await;
+
+    let sealed = a.seal("b", b"gossip").await.unwrap();
+    assert!(!sealed.ciphertext.contains("gossip"));
+    assert_eq!(b.open(&sealed).await.unwrap(), b"gossip");
+    let reply = b.seal("a", b"ack").await.unwrap();
+    assert_eq!(a.open(&reply).await.unwrap(), b"ack");
+
+    // A new handshake replaces the session keys
+    let old = a.seal("b", b"before").await.unwrap();
+    connect(&a, &b).await;
+    assert!(b.open(&old).await.is_err());
+    let new = a.seal("b", b"after").await.unwrap();
+    assert_eq!(b.open(&new).await.unwrap(), b"after");
+}
+
+#[tokio::test]
+async fn replays_and_stale_messages_are_rejected() {
+    let (a, b) = pair().await;
+    connect(&a, &b).await;
+    let mut sealed = Vec::new();
+    for i in 0..70u8 {
+        sealed.push(a.seal("b", &[i]).await.unwrap());
+    }
+
+    assert!(b.open(&sealed[69]).await.is_ok());
+    assert!(matches!(
+        b.open(&sealed[69]).await,
+        Err(SecureChannelError::Replay(70))
+    ));
+    // Out of order within the window is fine, once
+    assert_eq!(b.open(&sealed[9]).await.unwrap(), vec![9]);
+    assert!(b.open(&sealed[9]).await.is_err());
+    // Too far behind the newest message
+    assert!(matches!(
+        b.open(&sealed[4]).await,
+        Err(SecureChannelError::Replay(5))
+    ));
+}
+
+#[tokio::test]
+async fn forgeries_naming_a_peer_leave_its_trust_alone() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let trust = Arc::new(TrustManager::new(metrics));
+    let a = SecureChannels::new(NodeIdentity::generate("a"));
+    let b = SecureChannels::new(NodeIdentity::generate("b")).with_trust(trust.clone());
+    a.add_peer("b", b.identity().public_key()).await;
+    b.add_peer("a", a.identity().public_key()).await;
+    connect(&a, &b).await;
+    trust.record("a", TrustEvent::Success).await;
+    let before = trust.peer("a").await.unwrap();
+
+    // Someone claiming to be "a" without its identity key, twice over
+    let impostor = SecureChannels::new(NodeIdentity::generate("a"));
+    impostor.add_peer("b", b.identity().public_key()).await;
+    for _ in 0..2 {
+        let hello = impostor.initiate("b").await.unwrap();
+        assert!(matches!(
+            b.accept(&hello).await,
+            Err(SecureChannelError::BadSignature(_))
+        ));
+    }
+    let mut reply_hello = impostor.initiate("b").await.unwrap();
+    reply_hello.in_reply_to = Some("forged".into());
+    assert!(b.accept(&reply_hello).await.is_err());
+
+    // A tampered message could have been put together by anyone
+    let mut tampered = a.seal("b", b"transfer 10").await.unwrap();
+    tampered.ciphertext = a.seal("b", b"transfer 99").await.unwrap().ciphertext;
+    assert!(matches!(
+        b.open(&tampered).await,
+        Err(SecureChannelError::Decrypt(_))
+    ));
+
+    let after = trust.peer("a").await.unwrap();
+    assert_eq!(after.score, before.score);
+    assert_eq!(after.status, before.status);
+    assert_eq!(
+        (after.signature_failures, after.protocol_violations),
+        (0, 0)
+    );
+
+    // Made-up senders leave no record behind
+    let mut stray = a.seal("b", b"hi").await.unwrap();
+    stray.from = "nobody".into();
+    assert!(b.open(&stray).await.is_err());
+    assert!(trust.peer("nobody").await.is_none());
+
+    // Peers without a configured key can't shake hands
+    let stranger = SecureChannels::new(NodeIdentity::generate("c"));
+    stranger.add_peer("b", b.identity().public_key()).await;
+    let hello = stranger.initiate("b").await.unwrap();
+    assert!(matches!(
+        b.accept(&hello).await,
+        Err(SecureChannelError::UnknownPeer(_))
+    ));
+    assert!(trust.peer("c").await.is_none());
+}
+
+#[tokio::test]
+async fn replies_only_answer_their_own_request() {
+    let (a, b) = pair().await;
+    connect(&a, &b).await;
+    let first = a.seal("b", b"first").await.unwrap();
+    let second = a.seal("b", b"second").await.unwrap();
+    b.open(&first).await.unwrap();
+    b.open(&second).await.unwrap();
+    let reply_to = |request: &SealedMessage| ReplyTo {
+        peer: request.from.clone(),
+        sequence: request.sequence,
+    };
+    let to_first = b.seal_reply(&reply_to(&first), b"one").await.unwrap();
+    let to_second = b.seal_reply(&reply_to(&second), b"two").await.unwrap();
+
+    // Delivered to the wrong request, a reply is refused
+    assert!(matches!(
+        a.open_reply(&to_second, first.sequence).await,
+        Err(SecureChannelError::MismatchedReply(_))
+    ));
+    assert_eq!(
+        a.open_reply(&to_first, first.sequence).await.unwrap(),
+        b"one"
+    );
+}
+
+#[tokio::test]
+async fn replayed_handshakes_leave_the_session_alone() {
+    let (a, b) = pair().await;
+    let hello = a.initiate("b").await.unwrap();
+    let reply = b.accept(&hello).await.unwrap();
+    a.complete(&reply).await.unwrap();
+
+    // Replaying either half of a finished handshake changes nothing
+    assert!(matches!(
+        b.accept(&hello).await,
+        Err(SecureChannelError::StaleHandshake(_))
+    ));
+    assert!(matches!(
+        a.complete(&reply).await,
+        Err(SecureChannelError::StaleHandshake(_))
+    ));
+    let sealed = a.seal("b", b"still here").await.unwrap();
+    assert_eq!(b.open(&sealed).await.unwrap(), b"still here");
+
+    // An old reply doesn't answer a newer handshake, which can still finish
+    let newer = a.initiate("b").await.unwrap();
+    assert!(matches!(
+        a.complete(&reply).await,
+        Err(SecureChannelError::StaleHandshake(_))
+    ));
+    let newer_reply = b.accept(&newer).await.unwrap();
+    a.complete(&newer_reply).await.unwrap();
+
+    // The timestamp is authenticated, so a replay can't be made to look
+    // fresh by altering the hello
+    let mut altered = newer.clone();
+    let mut message = STANDARD.decode(&altered.message).unwrap();
+    *message.last_mut().unwrap() ^= 1;
+    altered.message = STANDARD.encode(message);
+    assert!(matches!(
+        b.accept(&altered).await,
+        Err(SecureChannelError::BadSignature(_))
+    ));
+}
+
+#[tokio::test]
+async fn long_messages_are_sealed_in_chunks_that_cant_be_dropped() {
+    let (a, b) = pair().await;
+    connect(&a, &b).await;
+    let long: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+    let sealed = a.seal("b", &long).await.unwrap();
+    assert_eq!(b.open(&sealed).await.unwrap(), long);
+
+    // Cutting the ciphertext at a chunk boundary leaves a message whose
+    // last chunk wasn't sealed as the last
+    let sealed = a.seal("b", &long).await.unwrap();
+    let mut truncated = sealed.clone();
+    let ciphertext = STANDARD.decode(&sealed.ciphertext).unwrap();
+    truncated.ciphertext = STANDARD.encode(&ciphertext[..2 * 65535]);
+    assert!(matches!(
+        b.open(&truncated).await,
+        Err(SecureChannelError::Decrypt(_))
+    ));
+    assert_eq!(b.open(&sealed).await.unwrap(), long);
+}
@@ -0,0 +1,188 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::nerv::jobs::{CompactionJob, JobKind, JobQueue, JobState};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::segments::{SegmentConfig, SegmentMerger};
+use amazon_rose_forest::sharding::vector_index::{DistanceMetric, VectorIndex};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn small_segments() -> SegmentConfig {
+    SegmentConfig {
+        memtable_limit: 4,
+        max_segments: 2,
+        merge_factor: 2,
+        max_tombstone_ratio: 0.5,
+    }
+}
+
+async fn index_with(count: usize) -> (VectorIndex, Vec<Uuid>) {
+    let index = VectorIndex::new("segmented", 2, DistanceMetric::Euclidean, None).unwrap();
+    index.set_segment_config(small_segments()).await;
+    let mut ids = Vec::new();
+    for i in 0..count {
+        let vector = Vector::new(vec![i as f32 / 10.0, 0.0]);
+        ids.push(index.add(vector, None).await.unwrap());
+    }
+    (index, ids)
+}
+
+#[tokio::test]
+async fn writes_are_frozen_into_segments() {
+    let (index, ids) = index_with(10).await;
+
+    let stats = index.segment_stats().await;
+    assert_eq!(stats.memtable_entries, 2);
+    assert_eq!(
+        stats.segments.iter().map(|s| s.entries).collect::<Vec<_>>(),
+        vec![4, 4]
+    );
+    assert_eq!(index.count().await, 10);
+    for id in &ids {
+        assert!(index.get(*id).await.is_some());
+    }
+    assert_eq!(index.entries().await.len(), 10);
+
+    let results = index.search(&Vector::new(vec![0.0, 0.0]), 3).await.unwrap();
+    assert_eq!(results[0].id, ids[0]);
+
+    assert_eq!(index.flush_memtable().await, Some(2));
+    assert_eq!(index.flush_memtable().await, None);
+    assert_eq!(index.stats().await.segments.segments.len(), 3);
+}
+
+#[tokio::test]
+async fn deletes_tombstone_until_merged() {
+    let (index, ids) = index_with(8).await;
+
+    index.remove(ids[0]).await.unwrap();
+    index.remove(ids[5]).await.unwrap();
+    assert!(index.remove(ids[0]).await.is_err());
+    assert_eq!(index.count().await, 6);
+    assert!(index.get(ids[0]).await.is_none());
+    assert_eq!(index.segment_stats().await.tombstones, 2);
+
+    let results = index.search(&Vector::new(vec![0.0, 0.0]), 8).await.unwrap();
+    assert!(!results.is_empty());
+    assert!(results.iter().all(|r| r.id != ids[0] && r.id != ids[5]));
+
+    // A deleted ID can be written again without the tombstone hiding it
+    index
+        .add_with_id(ids[0], Vector::new(vec![0.0, 0.0]), None)
+        .await
+        .unwrap();
+    assert!(index.get(ids[0]).await.is_some());
+
+    let report = index.merge_segments(true).await.unwrap();
+    assert_eq!(report.inputs, 2);
+    assert_eq!(report.dropped, 2);
+    assert_eq!(report.entries, 6);
+    let stats = index.segment_stats().await;
+    assert_eq!(stats.tombstones, 0);
+    assert_eq!(stats.segments.len(), 1);
+    assert_eq!(index.count().await, 7);
+    assert!(index.get(ids[0]).await.is_some());
+    assert!(index.get(ids[5]).await.is_none());
+    assert!(index.verify_integrity(false).await.is_consistent());
+
+    // Nothing left to merge
+    assert!(index.merge_segments(true).await.is_none());
+}
+
+#[tokio::test]
+async fn snapshots_ignore_later_changes() {
+    let (index, ids) = index_with(6).await;
+    let snapshot = index.snapshot().await;
+    assert_eq!(snapshot.len(), 6);
+    assert_eq!(index.segment_stats().await.memtable_entries, 0);
+
+    index.remove(ids[1]).await.unwrap();
+    index.add(Vector::new(vec![9.0, 9.0]), None).await.unwrap();
+    index.merge_segments(true).await.unwrap();
+
+    let snapshot_ids: Vec<Uuid> = snapshot.entries().map(|entry| entry.id).collect();
+    assert_eq!(snapshot_ids.len(), 6);
+    assert!(snapshot_ids.contains(&ids[1]));
+    assert_eq!(index.count().await, 6);
+}
+
+#[tokio::test]
+async fn background_merges_bound_the_segment_count() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("busy").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    index.set_segment_config(small_segments()).await;
+    for i in 0..20 {
+        manager
+            .add_vector(shard_id, Vector::new(vec![i as f32, 1.0]), None)
+            .await
+            .unwrap();
+    }
+    assert_eq!(index.segment_stats().await.segments.len(), 5);
+
+    let merger = SegmentMerger::new(manager.clone(), Duration::from_secs(60));
+    let merges = merger.run_once().await;
+    assert!(!merges.is_empty());
+    assert!(merges.iter().all(|(id, _)| *id == shard_id));
+    assert!(index.segment_stats().await.segments.len() <= 2);
+    assert_eq!(index.count().await, 20);
+    assert!(merger.run_once().await.is_empty());
+}
+
+#[tokio::test]
+async fn compaction_job_merges_a_shard() {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("compact").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    index.set_segment_config(small_segments()).await;
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        let vector = Vector::new(vec![i as f32, 2.0]);
+        ids.push(manager.add_vector(shard_id, vector, None).await.unwrap());
+    }
+    manager.remove_vector(shard_id, ids[2]).await.unwrap();
+
+    let queue = Arc::new(JobQueue::new(Arc::new(MetricsCollector::new())));
+    queue
+        .register(
+            JobKind::Compaction,
+            Arc::new(CompactionJob::new(manager.clone())),
+        )
+        .await;
+    let job = queue
+        .submit(JobKind::Compaction, json!({ "shard_id": shard_id }))
+        .await
+        .unwrap();
+    let _worker = queue.clone().start(1);
+
+    let job = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let job = queue.get(job.id).await.unwrap();
+            if job.state.is_finished() {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(job.state, JobState::Completed);
+    let result = job.result.unwrap();
+    assert_eq!(result["merged_segments"], 3);
+    assert_eq!(result["tombstones_dropped"], 1);
+
+    let stats = index.segment_stats().await;
+    assert_eq!(stats.segments.len(), 1);
+    assert_eq!(stats.memtable_entries, 0);
+    assert_eq!(index.count().await, 9);
+}
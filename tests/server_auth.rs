@@ -0,0 +1,192 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::server::auth::{encode_jwt, AuthConfig, MIN_JWT_SECRET_LEN};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::utils::errors::AuthConfigError;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use warp::http::StatusCode;
+
+const SECRET: &str = "jwt-secret-long-enough-for-hs256!";
+
+/// Serializes the tests that read configuration from the environment
+static ENV: Mutex<()> = Mutex::new(());
+
+fn server(metrics: Arc<MetricsCollector>) -> Server {
+    let config = ServerConfig {
+        auth: Some(
+            AuthConfig::new()
+                .with_api_key("key-1")
+                .with_jwt_secret(SECRET)
+                .unwrap()
+                .with_jwt_audience("rose-forest"),
+        ),
+        ..ServerConfig::default()
+    };
+    Server::new(config, metrics, None, None)
+}
+
+#[tokio::test]
+async fn api_keys_and_bearer_tokens_are_required_on_protected_routes() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let filter = server(metrics.clone()).filter();
+
+    // Probes and scrapers don't authenticate
+    let resp = warp::test::request().path("/health").reply(&filter).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = warp::test::request()
+        .path("/api/version")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(resp.headers()["www-authenticate"], "Bearer");
+    let body: Value = serde_json::from_slice(resp.body()).unwrap();
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .contains("Missing credentials"));
+
+    for (header, value) in [("x-api-key", "key-1"), ("authorization", "Bearer key-1")] {
+        let resp = warp::test::request()
+            .path("/api/version")
+            .header(header, value)
+            .reply(&filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK, "{}", header);
+    }
+    let resp = warp::test::request()
+        .path("/qdrant/collections")
+        .header("x-api-key", "key-2")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = warp::test::request()
+        .path("/ws/events")
+        .header("connection", "upgrade")
+        .header("upgrade", "websocket")
+        .header("sec-websocket-version", "13")
+        .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    assert_eq!(metrics.get_counter("server.auth_failures").await, Some(3));
+    assert_eq!(
+        metrics.get_counter("server.auth_failures.missing").await,
+        Some(2)
+    );
+    assert_eq!(
+        metrics.get_counter("server.auth_failures.invalid").await,
+        Some(1)
+    );
+}
+
+#[tokio::test]
+async fn jwts_are_checked_for_signature_expiry_and_audience() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let filter = server(metrics.clone()).filter();
+    let now = chrono::Utc::now().timestamp();
+    let status = |token: String| {
+        let filter = filter.clone();
+        async move {
+            warp::test::request()
+                .path("/api/version")
+                .header("authorization", format!("Bearer {}", token))
+                .reply(&filter)
+                .await
+                .status()
+        }
+    };
+
+    let claims = json!({ "sub": "svc", "aud": ["rose-forest"], "exp": now + 600 });
+    assert_eq!(status(encode_jwt(SECRET, &claims)).await, StatusCode::OK);
+
+    let expired = json!({ "aud": "rose-forest", "exp": now - 3600 });
+    let wrong_audience = json!({ "aud": "elsewhere", "exp": now + 600 });
+    for token in [
+        encode_jwt(SECRET, &expired),
+        encode_jwt(SECRET, &wrong_audience),
+        encode_jwt("other-secret", &claims),
+    ] {
+        assert_eq!(status(token).await, StatusCode::UNAUTHORIZED);
+    }
+
+    // Unsigned tokens are refused whatever they claim
+    let token = encode_jwt(SECRET, &claims);
+    let payload = token.split('.').nth(1).unwrap();
+    let unsigned = format!("eyJhbGciOiJub25lIn0.{}.", payload);
+    assert_eq!(status(unsigned).await, StatusCode::UNAUTHORIZED);
+
+    assert_eq!(
+        metrics.get_counter("server.auth_failures.expired").await,
+        Some(1)
+    );
+    assert_eq!(
+        metrics.get_counter("server.auth_failures.invalid").await,
+        Some(3)
+    );
+}
+
+#[tokio::test]
+async fn config_from_the_environment_switches_authentication_on() {
+    let config = {
+        let _env = ENV.lock().unwrap();
+        std::env::set_var("ROSE_FOREST_API_KEYS", "key-1, key-2");
+        let config = ServerConfig::from_env();
+        std::env::remove_var("ROSE_FOREST_API_KEYS");
+        config.unwrap()
+    };
+    let filter = Server::new(config, Arc::new(MetricsCollector::new()), None, None).filter();
+
+    let resp = warp::test::request()
+        .path("/api/version")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = warp::test::request()
+        .path("/api/version")
+        .header("x-api-key", "key-2")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[test]
+fn empty_api_keys_and_weak_jwt_secrets_are_rejected() {
+    assert_eq!(
+        AuthConfig::new().with_jwt_secret("short").err(),
+        Some(AuthConfigError::WeakJwtSecret {
+            len: 5,
+            min: MIN_JWT_SECRET_LEN
+        })
+    );
+    assert!(AuthConfig::new().with_jwt_secret("").is_err());
+
+    let _env = ENV.lock().unwrap();
+    let from_env = |var: &str, value: &str| {
+        std::env::set_var(var, value);
+        let config = ServerConfig::from_env();
+        std::env::remove_var(var);
+        config
+    };
+
+    for keys in ["", " ", "key-1,", "key-1,,key-2"] {
+        let err = from_env("ROSE_FOREST_API_KEYS", keys).unwrap_err();
+        assert!(
+            err.to_string().contains("ROSE_FOREST_API_KEYS"),
+            "{:?}",
+            keys
+        );
+    }
+    for secret in ["", "jwt-secret"] {
+        let err = from_env("ROSE_FOREST_JWT_SECRET", secret).unwrap_err();
+        assert!(
+            err.to_string().contains("ROSE_FOREST_JWT_SECRET"),
+            "{:?}",
+            secret
+        );
+    }
+
+    let config = from_env("ROSE_FOREST_JWT_SECRET", SECRET).unwrap();
+    assert!(config.auth.unwrap().jwt_secret.is_some());
+}
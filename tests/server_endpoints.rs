@@ -7,7 +7,6 @@ use amazon_rose_forest::{
 use std::sync::Arc;
 use warp::http::StatusCode;
 use warp::ws::Message;
-use warp::Filter;
 
 use serde_json::Value;
 
@@ -21,10 +20,11 @@ async fn disabled_endpoints_return_404() {
         metrics_path: "/metrics".into(),
         enable_api: false,
         api_path: "/api".into(),
+        ..ServerConfig::default()
     };
 
     let server = Server::new(config.clone(), metrics.clone(), None, None);
-    let filter = server.routes(metrics, config, None, None);
+    let filter = server.filter();
 
     let resp = warp::test::request()
         .method("GET")
@@ -64,14 +64,19 @@ async fn websocket_search_returns_results() {
 
     let config = ServerConfig::default();
     let server = Server::new(config.clone(), metrics.clone(), None, Some(manager.clone()));
-    let filter = server.routes(metrics, config, None, Some(manager));
+    let filter = server.filter();
 
-    let mut client = warp::test::ws().path("/ws/search").handshake(filter).await;
+    let mut client = warp::test::ws()
+        .path("/ws/search")
+        .handshake(filter)
+        .await
+        .expect("handshake");
 
     let req = SearchVectorsRequest {
         shard_id,
         query_vector: vec![0.0, 0.0, 0.0],
         limit: 1,
+        ..Default::default()
     };
     client
         .send(Message::text(serde_json::to_string(&req).unwrap()))
@@ -95,10 +100,11 @@ async fn enabled_endpoints_return_data() {
         metrics_path: "/metrics".into(),
         enable_api: true,
         api_path: "/api".into(),
+        ..ServerConfig::default()
     };
 
     let server = Server::new(config.clone(), metrics.clone(), None, None);
-    let filter = server.routes(metrics, config, None, None);
+    let filter = server.filter();
 
     let resp = warp::test::request()
         .method("GET")
@@ -127,7 +133,7 @@ async fn api_vector_endpoints_work() {
     let manager = Arc::new(ShardManager::new(metrics.clone()));
     let config = ServerConfig::default();
     let server = Server::new(config.clone(), metrics.clone(), None, Some(manager.clone()));
-    let filter = server.routes(metrics, config, None, Some(manager.clone()));
+    let filter = server.filter();
 
     let create_req = amazon_rose_forest::server::api::CreateShardRequest {
         name: "test".into(),
@@ -148,6 +154,7 @@ async fn api_vector_endpoints_work() {
         name: "main".into(),
         dimensions: 3,
         distance_metric: "euclidean".into(),
+        ..Default::default()
     };
     let resp = warp::test::request()
         .method("POST")
@@ -174,6 +181,7 @@ async fn api_vector_endpoints_work() {
         shard_id,
         query_vector: vec![0.0, 0.0, 0.0],
         limit: 1,
+        ..Default::default()
     };
     let resp = warp::test::request()
         .method("POST")
@@ -0,0 +1,108 @@
+mod common;
+
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::self_improvement::SelfImprovementEngine;
+use amazon_rose_forest::darwin::shadow_replay::{replay, ReplayTarget, ShadowReplay};
+use amazon_rose_forest::darwin::validation::ValidationPipeline;
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::shadow::{RecordedSearch, ShadowRecorder};
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use async_trait::async_trait;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Candidate that returns only the top result, or fails outright
+struct Truncating {
+    fail: bool,
+}
+
+#[async_trait]
+impl ReplayTarget for Truncating {
+    async fn search(&self, request: &RecordedSearch) -> anyhow::Result<Vec<Uuid>> {
+        if self.fail {
+            anyhow::bail!("candidate down");
+        }
+        Ok(request.result_ids.iter().take(1).copied().collect())
+    }
+}
+
+async fn recorded_manager() -> (Arc<ShardManager>, Arc<ShadowRecorder>) {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("shadow").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    for _ in 0..20 {
+        manager
+            .add_vector(shard_id, Vector::random(3), None)
+            .await
+            .unwrap();
+    }
+
+    let recorder = Arc::new(ShadowRecorder::new(1.0, 3));
+    manager.enable_shadow_recording(recorder.clone()).await;
+    for _ in 0..5 {
+        manager
+            .search_vectors(shard_id, &Vector::random(3), 4)
+            .await
+            .unwrap();
+    }
+    (manager, recorder)
+}
+
+#[tokio::test]
+async fn records_and_replays_searches() {
+    let (manager, recorder) = recorded_manager().await;
+    // Bounded to the most recent requests
+    assert_eq!(recorder.len().await, 3);
+
+    let path = std::env::temp_dir().join(format!("shadow-{}.jsonl", Uuid::new_v4()));
+    assert_eq!(recorder.save(&path).await.unwrap(), 3);
+    let requests = ShadowRecorder::load(&path).unwrap();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].result_ids.len(), 4);
+    std::fs::remove_file(path).ok();
+
+    let same = replay(&requests, manager.as_ref()).await;
+    assert_eq!(same.errors, 0);
+    assert_eq!(same.mean_overlap, 1.0);
+
+    let truncated = replay(&requests, &Truncating { fail: false }).await;
+    assert!((truncated.mean_overlap - 0.25).abs() < 1e-9);
+
+    let down = replay(&requests, &Truncating { fail: true }).await;
+    assert_eq!(down.errors, 3);
+    assert_eq!(down.to_metrics()["performance.shadow_error_rate"], 1.0);
+}
+
+#[tokio::test]
+async fn validation_includes_replay_metrics() {
+    let (_, recorder) = recorded_manager().await;
+    let metrics = Arc::new(MetricsCollector::new());
+    let pipeline = ValidationPipeline::new(metrics.clone());
+    pipeline.set_threshold("performance.shadow_result_overlap", 0.9);
+    let engine = SelfImprovementEngine::new(
+        metrics.clone(),
+        Arc::new(pipeline),
+        Arc::new(ExplorationStrategy::new(metrics)),
+    );
+    engine
+        .enable_shadow_replay(ShadowReplay::new(
+            recorder,
+            Arc::new(Truncating { fail: false }),
+        ))
+        .await;
+
+    let modification = common::modification("faster-search", Vec::new());
+    let id = engine.propose_modification(modification).await.unwrap();
+
+    // A candidate that drops most results fails the overlap gate
+    assert!(!engine.validate_modification(id).await.unwrap());
+    let validated = engine.get_modification(id).await.unwrap();
+    assert!(validated
+        .validation_metrics
+        .contains_key("performance.shadow_latency_ratio"));
+}
@@ -0,0 +1,167 @@
+use amazon_rose_forest::{
+    core::{audit::AuditLog, metrics::MetricsCollector},
+    sharding::{
+        manager::ShardManager,
+        rebalance::{RebalanceConfig, RebalanceManager},
+        vector_index::DistanceMetric,
+    },
+    Vector,
+};
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// A collection split once, with the upper range then written three times
+/// as often as the lower
+async fn uneven_family(manager: &ShardManager) -> (Uuid, Vec<(Uuid, Vector)>) {
+    let shard_id = manager.create_shard("docs").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let mut added = Vec::new();
+    for i in 0..40 {
+        let x = i as f32 / 40.0 * 2.0 - 1.0;
+        let vector = Vector::new(vec![x, -x * 0.5]);
+        let id = manager
+            .add_vector(shard_id, vector.clone(), None)
+            .await
+            .unwrap();
+        added.push((id, vector));
+    }
+
+    let split = manager.split_shard(shard_id).await.unwrap();
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    let upper: Vec<Vector> = added
+        .iter()
+        .filter(|(_, v)| index.hilbert_key(v) >= split.split_key)
+        .map(|(_, v)| v.clone())
+        .collect();
+    for vector in upper.iter().chain(upper.iter()) {
+        let id = manager
+            .add_vector(shard_id, vector.clone(), None)
+            .await
+            .unwrap();
+        added.push((id, vector.clone()));
+    }
+    (shard_id, added)
+}
+
+#[tokio::test]
+async fn rebalancer_evens_out_split_ranges() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let (root, added) = uneven_family(&manager).await;
+    let ranges = manager.family_ranges(root).await;
+    assert_eq!(ranges.len(), 2);
+    let (lower, upper) = (ranges[0].1, ranges[1].1);
+    assert_eq!(lower, root);
+    assert_eq!(
+        metrics
+            .get_gauge(&format!("shards.{}.vector_count", upper))
+            .await,
+        Some(manager.get_shard(upper).await.unwrap().vector_count as u64)
+    );
+
+    let audit_log = Arc::new(AuditLog::new());
+    let rebalancer = RebalanceManager::new(
+        manager.clone(),
+        metrics.clone(),
+        audit_log.clone(),
+        RebalanceConfig {
+            interval: Duration::from_secs(60),
+            max_imbalance: 1.5,
+            min_vectors: 2,
+        },
+    );
+    let plan = rebalancer.plan(root).await.unwrap();
+    assert_eq!((plan.lower, plan.upper), (lower, upper));
+    assert!(plan.new_boundary > plan.boundary);
+
+    let moves = rebalancer.run_once().await;
+    assert_eq!(moves.len(), 1);
+    assert_eq!((moves[0].from, moves[0].to), (upper, lower));
+    assert_eq!(moves[0].moved, plan.to_move);
+    assert_eq!(
+        manager.family_ranges(root).await,
+        vec![(0, lower), (plan.new_boundary, upper)]
+    );
+
+    let lower_count = manager.get_shard(lower).await.unwrap().vector_count;
+    let upper_count = manager.get_shard(upper).await.unwrap().vector_count;
+    assert_eq!(lower_count + upper_count, added.len());
+    let (smaller, larger) = (lower_count.min(upper_count), lower_count.max(upper_count));
+    assert!(larger as f64 / smaller as f64 <= 1.5);
+
+    // Every vector lives in the member serving its key
+    let index = manager.get_vector_index(root).await.unwrap();
+    for (id, vector) in &added {
+        let owner = if index.hilbert_key(vector) < plan.new_boundary {
+            lower
+        } else {
+            upper
+        };
+        let owner_index = manager.get_vector_index(owner).await.unwrap();
+        assert!(owner_index.get(*id).await.is_some());
+    }
+
+    let events = audit_log.by_category("rebalance", 10).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].subject, root.to_string());
+    assert_eq!(events[0].details["moved"], moves[0].moved);
+
+    // Even now, so the next check leaves it alone
+    assert!(rebalancer.run_once().await.is_empty());
+    let status = rebalancer.status().await;
+    assert!(status.in_progress.is_none());
+    assert_eq!(status.completed.len(), 1);
+    assert_eq!(status.completed[0].range_move, moves[0]);
+    assert_eq!(status.runs, 2);
+    assert_eq!(
+        metrics.get_counter("rebalance.vectors_moved").await,
+        Some(moves[0].moved as u64)
+    );
+}
+
+#[tokio::test]
+async fn small_families_are_left_alone() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let (root, _) = uneven_family(&manager).await;
+    manager.create_shard("unsplit").await.unwrap();
+    assert_eq!(manager.family_roots().await, vec![root]);
+
+    let rebalancer = RebalanceManager::new(
+        manager.clone(),
+        metrics.clone(),
+        Arc::new(AuditLog::new()),
+        RebalanceConfig::default(),
+    );
+    assert!(rebalancer.plan(root).await.is_none());
+    assert!(rebalancer.run_once().await.is_empty());
+    assert_eq!(manager.family_ranges(root).await.len(), 2);
+}
+
+#[tokio::test]
+async fn boundaries_only_move_within_their_neighbouring_ranges() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = ShardManager::new(metrics);
+    let (root, _) = uneven_family(&manager).await;
+    let boundary = manager.family_ranges(root).await[1].0;
+    let moved = AtomicUsize::new(0);
+
+    assert!(manager
+        .move_range_boundary(root, boundary, 0, &moved)
+        .await
+        .is_err());
+    assert!(manager
+        .move_range_boundary(root, boundary, boundary, &moved)
+        .await
+        .is_err());
+    assert!(manager
+        .move_range_boundary(root, boundary + 1, boundary + 2, &moved)
+        .await
+        .is_err());
+    assert_eq!(manager.family_ranges(root).await[1].0, boundary);
+}
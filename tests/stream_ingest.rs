@@ -0,0 +1,133 @@
+use amazon_rose_forest::{
+    core::metrics::MetricsCollector,
+    ingest::{
+        BatchOutcome, DeadLetterSink, MessageSource, SourceMessage, StreamIngestConfig,
+        StreamIngestWorker, StreamRecordSchema,
+    },
+    sharding::{manager::ShardManager, vector_index::DistanceMetric},
+};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Source backed by a queue, recording which tokens were committed
+struct QueueSource {
+    queue: Vec<SourceMessage>,
+    committed: Arc<Mutex<Vec<u64>>>,
+}
+
+#[async_trait]
+impl MessageSource for QueueSource {
+    async fn poll(&mut self, max: usize, _timeout: Duration) -> anyhow::Result<Vec<SourceMessage>> {
+        let count = max.min(self.queue.len());
+        Ok(self.queue.drain(..count).collect())
+    }
+
+    async fn commit(&mut self, messages: &[SourceMessage]) -> anyhow::Result<()> {
+        self.committed
+            .lock()
+            .unwrap()
+            .extend(messages.iter().map(|m| m.token));
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct CollectingDeadLetter {
+    messages: Mutex<Vec<(u64, String)>>,
+}
+
+#[async_trait]
+impl DeadLetterSink for CollectingDeadLetter {
+    async fn send(&self, message: &SourceMessage, reason: &str) -> anyhow::Result<()> {
+        self.messages
+            .lock()
+            .unwrap()
+            .push((message.token, reason.to_string()));
+        Ok(())
+    }
+}
+
+fn message(token: u64, payload: serde_json::Value) -> SourceMessage {
+    SourceMessage {
+        token,
+        key: None,
+        payload: serde_json::to_vec(&payload).unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn worker_writes_batches_and_dead_letters_bad_records() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let shard_id = manager.create_shard("stream").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 2, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+
+    let committed = Arc::new(Mutex::new(Vec::new()));
+    let source = QueueSource {
+        queue: vec![
+            message(
+                0,
+                serde_json::json!({ "id": "a", "embedding": [0.1, 0.2], "lang": "en" }),
+            ),
+            message(1, serde_json::json!({ "id": "b", "embedding": "oops" })),
+            message(
+                2,
+                serde_json::json!({ "id": "c", "embedding": [0.1, 0.2, 0.3] }),
+            ),
+            message(3, serde_json::json!({ "id": "d", "embedding": [0.3, 0.4] })),
+        ],
+        committed: committed.clone(),
+    };
+    let dead_letter = Arc::new(CollectingDeadLetter::default());
+
+    let mut config = StreamIngestConfig::new(
+        shard_id,
+        StreamRecordSchema {
+            vector: "$.embedding".into(),
+            id: Some("$.id".into()),
+            metadata: HashMap::from([("lang".to_string(), "$.lang".to_string())]),
+        },
+    );
+    config.batch_size = 3;
+    config.max_attempts = 1;
+
+    let mut worker = StreamIngestWorker::new(
+        Box::new(source),
+        dead_letter.clone(),
+        config,
+        manager.clone(),
+        metrics,
+    )
+    .unwrap();
+
+    let first = worker.run_batch().await.unwrap();
+    assert_eq!(
+        first,
+        BatchOutcome {
+            received: 3,
+            written: 1,
+            dead_lettered: 2,
+        }
+    );
+    assert_eq!(*committed.lock().unwrap(), vec![0, 1, 2]);
+
+    let second = worker.run_batch().await.unwrap();
+    assert_eq!(second.written, 1);
+    assert_eq!(*committed.lock().unwrap(), vec![0, 1, 2, 3]);
+
+    let dead: Vec<u64> = dead_letter
+        .messages
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(t, _)| *t)
+        .collect();
+    assert_eq!(dead, vec![1, 2]);
+
+    assert_eq!(manager.get_shard(shard_id).await.unwrap().vector_count, 2);
+}
@@ -0,0 +1,169 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::embedding::{EmbeddingRegistry, HashingEmbedder};
+use amazon_rose_forest::query::fusion::reciprocal_rank_fusion;
+use amazon_rose_forest::query::synonyms::{SynonymDictionary, SynonymStore};
+use amazon_rose_forest::server::api::TextSearchResponse;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::{DistanceMetric, SearchResult};
+use amazon_rose_forest::utils::errors::SynonymError;
+use amazon_rose_forest::Vector;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+fn medical() -> SynonymDictionary {
+    SynonymDictionary {
+        synonyms: vec![vec![
+            "heart attack".to_string(),
+            "myocardial infarction".to_string(),
+        ]],
+        expansions: HashMap::from([(
+            "cardiac".to_string(),
+            vec!["heart".to_string(), "cardiovascular".to_string()],
+        )]),
+    }
+}
+
+fn result(id: Uuid) -> SearchResult {
+    SearchResult {
+        id,
+        vector: Vector::new(vec![0.0]),
+        metadata: None,
+        score: 0.0,
+    }
+}
+
+#[test]
+fn queries_expand_on_whole_terms() {
+    let dictionary = medical();
+    assert_eq!(
+        dictionary.expand("Heart  Attack symptoms", 8),
+        vec![
+            "Heart  Attack symptoms".to_string(),
+            "myocardial infarction symptoms".to_string(),
+        ]
+    );
+    // One-way: a cardiac query searches for heart, not the reverse
+    assert_eq!(dictionary.expand("cardiac arrest", 8).len(), 3);
+    assert_eq!(dictionary.expand("heart rate", 8), vec!["heart rate"]);
+    // Words are matched whole
+    assert_eq!(dictionary.expand("cardiacs", 8), vec!["cardiacs"]);
+    assert_eq!(dictionary.expand("cardiac arrest", 2).len(), 2);
+}
+
+#[tokio::test]
+async fn dictionaries_are_validated_per_collection() {
+    let store = SynonymStore::new();
+    let lonely = SynonymDictionary {
+        synonyms: vec![vec!["alone".to_string()]],
+        ..SynonymDictionary::default()
+    };
+    assert!(matches!(
+        store.set("docs", lonely).await,
+        Err(SynonymError::GroupTooSmall(_))
+    ));
+    let blank = SynonymDictionary {
+        expansions: HashMap::from([("car".to_string(), vec!["  ".to_string()])]),
+        ..SynonymDictionary::default()
+    };
+    assert!(matches!(
+        store.set("docs", blank).await,
+        Err(SynonymError::EmptyTerm)
+    ));
+
+    store.set("docs", medical()).await.unwrap();
+    assert_eq!(store.expand("docs", "heart attack", 8).await.len(), 2);
+    assert_eq!(store.expand("other", "heart attack", 8).await.len(), 1);
+    assert!(store.remove("docs").await);
+    assert!(store.get("docs").await.is_none());
+}
+
+#[test]
+fn fusion_favours_results_found_by_several_queries() {
+    let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let fused = reciprocal_rank_fusion(
+        vec![
+            vec![result(a), result(b)],
+            vec![result(c), result(b)],
+            vec![result(b)],
+        ],
+        2,
+    );
+    assert_eq!(fused.len(), 2);
+    assert_eq!(fused[0].id, b);
+    assert!(fused[0].score > fused[1].score);
+}
+
+#[tokio::test]
+async fn text_search_expands_with_the_collection_dictionary() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let embeddings = Arc::new(EmbeddingRegistry::new(manager, metrics.clone()));
+    let model = embeddings
+        .register_provider(Arc::new(HashingEmbedder::new(256)))
+        .await;
+    embeddings
+        .create_space("articles", &model, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+    let mut ids = Vec::new();
+    for text in [
+        "heart attack warning signs",
+        "myocardial infarction treatment",
+        "gardening tips for spring",
+    ] {
+        ids.push(embeddings.add_text("articles", text, None).await.unwrap());
+    }
+
+    let filter = Server::new(ServerConfig::default(), metrics, None, None)
+        .with_embedding_registry(embeddings)
+        .with_synonyms(Arc::new(SynonymStore::new()))
+        .filter();
+    let search = |body: serde_json::Value| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/collections/articles/search/text")
+            .json(&body)
+            .reply(&filter)
+    };
+
+    let resp = search(json!({ "query": "heart attack", "limit": 2, "expansion": "union" })).await;
+    let unexpanded: TextSearchResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(unexpanded.queries, vec!["heart attack"]);
+    assert_eq!(unexpanded.results[0].id, ids[0].to_string());
+
+    let resp = warp::test::request()
+        .method("PUT")
+        .path("/api/collections/articles/synonyms")
+        .json(&medical())
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = search(json!({ "query": "heart attack", "limit": 2, "expansion": "union" })).await;
+    let expanded: TextSearchResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(expanded.queries.len(), 2);
+    let mut found: Vec<String> = expanded.results.into_iter().map(|r| r.id).collect();
+    found.sort();
+    let mut expected = vec![ids[0].to_string(), ids[1].to_string()];
+    expected.sort();
+    assert_eq!(found, expected);
+
+    let resp = search(json!({ "query": "heart attack", "limit": 3 })).await;
+    let fused: TextSearchResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(fused.queries.len(), 2);
+    assert_eq!(fused.results.len(), 3);
+
+    let resp = search(json!({ "query": "heart attack", "limit": 2, "exact": true })).await;
+    let exact: TextSearchResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(exact.queries.len(), 1);
+
+    let resp = warp::test::request()
+        .path("/api/collections/unknown/synonyms")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,405 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::intelligence::delegation::{
+    DelegationConfig, PeerCapacity, PeerHeartbeat, TaskAssignment, TaskDelegator, TaskKind,
+    TaskOutcome, TaskReport, TaskStatus, TaskTransport, TraceEvent,
+};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::utils::errors::DelegationError;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use warp::http::StatusCode;
+
+/// Records assignments instead of sending them; refuses peers in `down`
+#[derive(Default)]
+struct RecordingTransport {
+    sent: Mutex<Vec<(String, TaskAssignment)>>,
+    down: Vec<String>,
+}
+
+#[async_trait]
+impl TaskTransport for RecordingTransport {
+    async fn send(&self, peer: &PeerCapacity, assignment: &TaskAssignment) -> Result<()> {
+        if self.down.contains(&peer.peer_id) {
+            return Err(anyhow!("connection refused"));
+        }
+        self.sent
+            .lock()
+            .await
+            .push((peer.peer_id.clone(), assignment.clone()));
+        Ok(())
+    }
+}
+
+fn heartbeat(peer_id: &str, accepts: &[TaskKind], slots: usize) -> PeerHeartbeat {
+    PeerHeartbeat {
+        capacity: PeerCapacity {
+            peer_id: peer_id.into(),
+            endpoint: format!("http://{}:9000", peer_id),
+            accepts: accepts.to_vec(),
+            slots,
+        },
+        leases: Vec::new(),
+    }
+}
+
+fn delegator(transport: Arc<RecordingTransport>, config: DelegationConfig) -> TaskDelegator {
+    TaskDelegator::new("origin", Arc::new(MetricsCollector::new()))
+        .with_config(config)
+        .with_transport(transport)
+}
+
+#[tokio::test]
+async fn tasks_go_to_capable_peers_with_free_slots() {
+    let transport = Arc::new(RecordingTransport::default());
+    let delegator = delegator(transport.clone(), DelegationConfig::default());
+    delegator
+        .heartbeat(heartbeat(
+            "small",
+            &[TaskKind::Embedding, TaskKind::Validation],
+            1,
+        ))
+        .await;
+    delegator
+        .heartbeat(heartbeat("large", &[TaskKind::Embedding], 2))
+        .await;
+
+    let validation = delegator.submit(TaskKind::Validation, json!({})).await;
+    let clustering = delegator.submit(TaskKind::Clustering, json!({})).await;
+    let embeddings = [
+        delegator
+            .submit(TaskKind::Embedding, json!({"batch": 1}))
+            .await,
+        delegator
+            .submit(TaskKind::Embedding, json!({"batch": 2}))
+            .await,
+    ];
+
+    let assignments = delegator.dispatch().await;
+    assert_eq!(assignments.len(), 3);
+    let sent = transport.sent.lock().await.clone();
+    let peer_of = |task_id: uuid::Uuid| {
+        sent.iter()
+            .find(|(_, a)| a.task_id == task_id)
+            .map(|(peer, _)| peer.as_str())
+    };
+    // Only "small" validates, which uses its one slot
+    assert_eq!(peer_of(validation), Some("small"));
+    assert_eq!(peer_of(embeddings[0]), Some("large"));
+    assert_eq!(peer_of(embeddings[1]), Some("large"));
+    // Nobody clusters
+    assert_eq!(peer_of(clustering), None);
+    assert_eq!(
+        delegator.task(clustering).await.unwrap().status,
+        TaskStatus::Pending
+    );
+
+    let peers = delegator.peers().await;
+    assert_eq!(peers[0].capacity.peer_id, "large");
+    assert_eq!(peers[0].in_flight, 2);
+    assert!(peers.iter().all(|p| p.available));
+}
+
+#[tokio::test]
+async fn reports_complete_tasks_and_stale_leases_are_rejected() {
+    let transport = Arc::new(RecordingTransport::default());
+    let delegator = delegator(transport.clone(), DelegationConfig::default());
+    delegator
+        .heartbeat(heartbeat("worker", &[TaskKind::Embedding], 4))
+        .await;
+    let task_id = delegator.submit(TaskKind::Embedding, json!({})).await;
+    let assignment = delegator.dispatch().await.remove(0);
+
+    let stale = TaskReport {
+        lease_id: uuid::Uuid::new_v4(),
+        outcome: TaskOutcome::Completed { result: json!(1) },
+        spans: Vec::new(),
+    };
+    assert!(matches!(
+        delegator.report(task_id, stale).await,
+        Err(DelegationError::StaleLease { .. })
+    ));
+    assert!(matches!(
+        delegator
+            .report(
+                uuid::Uuid::new_v4(),
+                TaskReport {
+                    lease_id: assignment.lease_id,
+                    outcome: TaskOutcome::Completed { result: json!(1) },
+                    spans: Vec::new(),
+                }
+            )
+            .await,
+        Err(DelegationError::UnknownTask(_))
+    ));
+
+    let span = TraceEvent {
+        trace_id: assignment.trace_id,
+        task_id,
+        node: "worker".into(),
+        event: "embedded".into(),
+        detail: Some("128 vectors".into()),
+        at: Utc::now(),
+    };
+    let task = delegator
+        .report(
+            task_id,
+            TaskReport {
+                lease_id: assignment.lease_id,
+                outcome: TaskOutcome::Completed {
+                    result: json!({"vectors": 128}),
+                },
+                spans: vec![span.clone()],
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(task.status, TaskStatus::Completed);
+    assert_eq!(task.result, Some(json!({"vectors": 128})));
+
+    let events: Vec<(String, String)> = delegator
+        .trace_events(assignment.trace_id)
+        .await
+        .into_iter()
+        .map(|e| (e.node, e.event))
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            ("origin".to_string(), "submitted".to_string()),
+            ("origin".to_string(), "leased".to_string()),
+            ("worker".to_string(), "embedded".to_string()),
+            ("origin".to_string(), "completed".to_string()),
+        ]
+    );
+
+    // A completed task can't be reported again
+    assert!(matches!(
+        delegator
+            .report(
+                task_id,
+                TaskReport {
+                    lease_id: assignment.lease_id,
+                    outcome: TaskOutcome::Failed {
+                        error: "late".into()
+                    },
+                    spans: Vec::new(),
+                }
+            )
+            .await,
+        Err(DelegationError::StaleLease { .. })
+    ));
+}
+
+#[tokio::test]
+async fn abandoned_tasks_are_reclaimed_and_reassigned() {
+    let transport = Arc::new(RecordingTransport::default());
+    let delegator = delegator(
+        transport.clone(),
+        DelegationConfig {
+            heartbeat_timeout: Duration::from_secs(10),
+            ..DelegationConfig::default()
+        },
+    );
+    delegator
+        .heartbeat(heartbeat("flaky", &[TaskKind::Validation], 1))
+        .await;
+    let task_id = delegator.submit(TaskKind::Validation, json!({})).await;
+    let first = delegator.dispatch().await.remove(0);
+
+    // Heartbeats listing the lease keep it alive
+    let mut renewal = heartbeat("flaky", &[TaskKind::Validation], 1);
+    renewal.leases = vec![first.lease_id];
+    assert_eq!(delegator.heartbeat(renewal).await, 1);
+    assert!(delegator.reclaim().await.is_empty());
+
+    // The peer goes quiet
+    let later = Utc::now() + chrono::Duration::seconds(30);
+    assert_eq!(delegator.reclaim_at(later).await, vec![task_id]);
+    let task = delegator.task(task_id).await.unwrap();
+    assert_eq!(task.status, TaskStatus::Pending);
+    assert!(task.error.unwrap().contains("stopped heartbeating"));
+
+    // Another peer picks it up under a new lease
+    delegator
+        .heartbeat(heartbeat("steady", &[TaskKind::Validation], 1))
+        .await;
+    let second = delegator.dispatch().await;
+    // "flaky" is still in the pool from its last heartbeat, so it's
+    // eligible too; whichever peer gets the task, the lease is new
+    assert_eq!(second.len(), 1);
+    assert_ne!(second[0].lease_id, first.lease_id);
+    assert_eq!(second[0].trace_id, first.trace_id);
+    assert_eq!(delegator.task(task_id).await.unwrap().attempts, 2);
+
+    // The first peer's late report is fenced off
+    assert!(matches!(
+        delegator
+            .report(
+                task_id,
+                TaskReport {
+                    lease_id: first.lease_id,
+                    outcome: TaskOutcome::Completed {
+                        result: json!(null)
+                    },
+                    spans: Vec::new(),
+                }
+            )
+            .await,
+        Err(DelegationError::StaleLease { .. })
+    ));
+
+    let events: Vec<String> = delegator
+        .trace_events(first.trace_id)
+        .await
+        .into_iter()
+        .map(|e| e.event)
+        .collect();
+    assert_eq!(events, vec!["submitted", "leased", "reclaimed", "leased"]);
+}
+
+#[tokio::test]
+async fn tasks_fail_after_max_attempts() {
+    let transport = Arc::new(RecordingTransport {
+        down: vec!["unreachable".into()],
+        ..RecordingTransport::default()
+    });
+    let delegator = delegator(
+        transport,
+        DelegationConfig {
+            max_attempts: 2,
+            ..DelegationConfig::default()
+        },
+    );
+    delegator
+        .heartbeat(heartbeat("unreachable", &[TaskKind::Clustering], 1))
+        .await;
+    let task_id = delegator.submit(TaskKind::Clustering, json!({})).await;
+
+    // Each failed send uses up an attempt and puts the task back
+    assert!(delegator.dispatch().await.is_empty());
+    assert_eq!(
+        delegator.task(task_id).await.unwrap().status,
+        TaskStatus::Pending
+    );
+    assert!(delegator.dispatch().await.is_empty());
+
+    let task = delegator.task(task_id).await.unwrap();
+    assert_eq!(task.status, TaskStatus::Failed);
+    assert_eq!(task.attempts, 2);
+    assert!(task.error.unwrap().contains("connection refused"));
+    assert!(delegator.dispatch().await.is_empty());
+}
+
+#[tokio::test]
+async fn expired_leases_are_reclaimed_without_peer_loss() {
+    let delegator = delegator(
+        Arc::new(RecordingTransport::default()),
+        DelegationConfig {
+            lease_duration: Duration::ZERO,
+            ..DelegationConfig::default()
+        },
+    );
+    delegator
+        .heartbeat(heartbeat("busy", &[TaskKind::Embedding], 1))
+        .await;
+    let task_id = delegator.submit(TaskKind::Embedding, json!({})).await;
+    delegator.dispatch().await;
+
+    assert_eq!(delegator.reclaim().await, vec![task_id]);
+    let task = delegator.task(task_id).await.unwrap();
+    assert!(task.error.unwrap().contains("expired"));
+}
+
+#[tokio::test]
+async fn delegation_routes() {
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    );
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/cluster/heartbeat")
+        .json(&heartbeat("peer", &[TaskKind::Embedding], 1))
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let delegator = Arc::new(
+        TaskDelegator::new("origin", Arc::new(MetricsCollector::new())).without_transport(),
+    );
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    )
+    .with_task_delegator(delegator.clone());
+    let filter = server.filter();
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/cluster/heartbeat")
+        .json(&heartbeat("peer", &[TaskKind::Embedding], 1))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/api/cluster/peers")
+        .reply(&filter)
+        .await;
+    let peers: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(peers[0]["capacity"]["peer_id"], "peer");
+
+    let task_id = delegator.submit(TaskKind::Embedding, json!({})).await;
+    let assignment = delegator.dispatch().await.remove(0);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path(&format!("/api/tasks/{}/report", task_id))
+        .json(&json!({
+            "lease_id": uuid::Uuid::new_v4(),
+            "outcome": "completed",
+            "result": 1
+        }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path(&format!("/api/tasks/{}/report", task_id))
+        .json(&json!({
+            "lease_id": assignment.lease_id,
+            "outcome": "completed",
+            "result": {"ok": true}
+        }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let task: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(task["status"], "completed");
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!("/api/traces/{}", assignment.trace_id))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let events: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(events.as_array().unwrap().len(), 3);
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!("/api/traces/{}", uuid::Uuid::new_v4()))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
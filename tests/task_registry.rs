@@ -0,0 +1,119 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::nerv::tasks::{self, TaskRegistry, TaskSnapshot};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use warp::http::StatusCode;
+
+#[tokio::test]
+async fn tasks_are_listed_until_they_end() {
+    let registry = Arc::new(TaskRegistry::new());
+    let (finish, finished) = oneshot::channel::<()>();
+    let done = registry.spawn("sharding", "finishes", async move {
+        let _ = finished.await;
+        7
+    });
+    let aborted = registry.spawn("sharding", "aborted", std::future::pending::<()>());
+    let panics = registry.spawn("server", "panics", async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        panic!("task failed");
+    });
+
+    let snapshot = registry.snapshot();
+    assert_eq!(snapshot.total, 3);
+    assert_eq!(snapshot.by_subsystem["sharding"], 2);
+    assert_eq!(
+        snapshot
+            .tasks
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["finishes", "aborted", "panics"]
+    );
+    assert_eq!(registry.tasks_of("server").len(), 1);
+
+    // Finishing, panicking and being aborted all deregister a task
+    finish.send(()).unwrap();
+    assert_eq!(done.await.unwrap(), 7);
+    assert!(panics.await.is_err());
+    aborted.abort();
+    assert!(aborted.await.unwrap_err().is_cancelled());
+    assert!(registry.is_empty());
+    assert_eq!(registry.snapshot(), TaskSnapshot::default());
+}
+
+#[tokio::test]
+async fn background_loops_are_registered_and_listed() {
+    // Components spawn into the process-wide registry
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let flusher = manager.start_flushing(Duration::from_secs(60));
+    let flushers = || {
+        tasks::registry()
+            .tasks_of("sharding")
+            .into_iter()
+            .filter(|task| task.name == "storage flusher")
+            .count()
+    };
+    assert_eq!(flushers(), 1);
+    flusher.abort();
+    let _ = flusher.await;
+    assert_eq!(flushers(), 0);
+
+    let registry = Arc::new(TaskRegistry::new());
+    let idle = registry.spawn("nerv", "idle", std::future::pending::<()>());
+    let filter = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        None,
+    )
+    .with_task_registry(registry)
+    .filter();
+    let resp = warp::test::request()
+        .path("/api/admin/tasks")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let snapshot: TaskSnapshot = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(snapshot.total, 1);
+    assert_eq!(snapshot.tasks[0].subsystem, "nerv");
+    assert_eq!(snapshot.tasks[0].name, "idle");
+    idle.abort();
+}
+
+fn rust_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            rust_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+#[test]
+fn tasks_are_only_spawned_through_the_registry() {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let registry = src.join("nerv").join("tasks.rs");
+    let mut files = Vec::new();
+    rust_files(&src, &mut files);
+
+    let mut bare = Vec::new();
+    for file in files.iter().filter(|file| **file != registry) {
+        let source = std::fs::read_to_string(file).unwrap();
+        for (line, text) in source.lines().enumerate() {
+            if text.contains("tokio::spawn") || text.contains("task::spawn(") {
+                bare.push(format!("{}:{}", file.display(), line + 1));
+            }
+        }
+    }
+    assert!(
+        bare.is_empty(),
+        "spawn with nerv::tasks::spawn instead: {:?}",
+        bare
+    );
+}
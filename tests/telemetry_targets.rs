@@ -0,0 +1,120 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::self_improvement::SelfImprovementEngine;
+use amazon_rose_forest::darwin::telemetry::{
+    ErrorHotspots, PainKind, TelemetryBacklog, MEMORY_GAUGE, SEARCH_MODULE,
+};
+use amazon_rose_forest::darwin::validation::ValidationPipeline;
+use amazon_rose_forest::query::slow_log::{SlowQuery, SlowQueryLog};
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing_subscriber::layer::SubscriberExt;
+use uuid::Uuid;
+
+fn slow_query(latency_ms: u64) -> SlowQuery {
+    SlowQuery {
+        query_id: None,
+        shard_id: Uuid::new_v4(),
+        limit: 10,
+        results: 10,
+        partial: false,
+        latency_ms,
+        recorded_at: Utc::now(),
+    }
+}
+
+#[test]
+fn hotspot_layer_counts_warnings_and_errors_per_module() {
+    let hotspots = ErrorHotspots::new();
+    let subscriber = tracing_subscriber::registry().with(hotspots.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::error!(target: "amazon_rose_forest::nerv::region", "replication failed");
+        tracing::error!(target: "amazon_rose_forest::nerv::region", "replication failed");
+        tracing::warn!(target: "amazon_rose_forest::nerv::region", "peer slow");
+        tracing::info!(target: "amazon_rose_forest::nerv::region", "all good");
+        // Other crates' logs don't point at our modules
+        tracing::error!(target: "hyper::proto", "connection reset");
+    });
+
+    let counts = hotspots.counts();
+    assert_eq!(counts.len(), 1);
+    let region = counts["nerv::region"];
+    assert_eq!((region.errors, region.warnings), (2, 1));
+
+    hotspots.clear();
+    assert!(hotspots.counts().is_empty());
+}
+
+#[tokio::test]
+async fn backlog_ranks_pain_points_by_severity() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let slow_log = Arc::new(SlowQueryLog::new(Duration::from_millis(100)));
+    for _ in 0..3 {
+        slow_log.record(slow_query(300)).await;
+    }
+    let hotspots = ErrorHotspots::new();
+    hotspots.record("amazon_rose_forest::nerv::region", tracing::Level::ERROR);
+    hotspots.record("amazon_rose_forest::nerv::region", tracing::Level::ERROR);
+
+    let backlog = TelemetryBacklog::new(metrics.clone())
+        .with_slow_query_log(slow_log)
+        .with_error_hotspots(hotspots);
+
+    // Steady memory is no spike
+    for pct in [40, 41, 40] {
+        metrics.set_gauge(MEMORY_GAUGE, pct).await;
+    }
+    let kinds: Vec<PainKind> = backlog.pain_points().await.iter().map(|p| p.kind).collect();
+    assert_eq!(kinds, vec![PainKind::SlowQueries, PainKind::ErrorHotspot]);
+
+    metrics.set_gauge(MEMORY_GAUGE, 75).await;
+    let points = backlog.pain_points().await;
+    let kinds: Vec<PainKind> = points.iter().map(|p| p.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            PainKind::MemorySpike,
+            PainKind::SlowQueries,
+            PainKind::ErrorHotspot
+        ]
+    );
+    // Three queries at three times the threshold
+    assert!((points[1].severity - 9.0).abs() < 1e-4);
+    assert_eq!(points[1].module, SEARCH_MODULE);
+    assert_eq!(points[2].module, "nerv::region");
+}
+
+#[tokio::test]
+async fn generation_targets_pain_points_instead_of_curiosities() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let engine = SelfImprovementEngine::new(
+        metrics.clone(),
+        Arc::new(ValidationPipeline::new(metrics.clone())),
+        Arc::new(ExplorationStrategy::new(metrics.clone())),
+    );
+    let hotspots = ErrorHotspots::new();
+    hotspots.record("amazon_rose_forest::nerv::region", tracing::Level::ERROR);
+    engine
+        .enable_telemetry(Arc::new(
+            TelemetryBacklog::new(metrics.clone()).with_error_hotspots(hotspots),
+        ))
+        .await;
+
+    engine.generate_modifications().await.unwrap();
+
+    let names: Vec<String> = engine
+        .get_all_modifications()
+        .await
+        .into_iter()
+        .map(|m| m.name)
+        .collect();
+    assert!(names.contains(&"Fix recurring errors in nerv::region".to_string()));
+    assert!(!names.iter().any(|n| n.starts_with("Paradigm shift")));
+    assert_eq!(
+        metrics
+            .get_counter("darwin.generation.telemetry_targets")
+            .await,
+        Some(1)
+    );
+}
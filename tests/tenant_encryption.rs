@@ -0,0 +1,119 @@
+use amazon_rose_forest::core::audit::AuditLog;
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::server::api::ErrorResponse;
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::tenancy::{RedactionPolicy, TenantKeyring, REDACTED};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn sensitive_fields_are_encrypted_at_rest() {
+    let keyring = Arc::new(TenantKeyring::new());
+    keyring
+        .add_key("acme", 1, &TenantKeyring::random_key())
+        .await
+        .unwrap();
+    keyring.set_encrypted_fields("acme", &["email"]).await;
+
+    let manager = ShardManager::new(Arc::new(MetricsCollector::new()));
+    manager.enable_tenant_encryption(keyring.clone()).await;
+    let shard_id = manager.create_shard("acme-docs").await.unwrap();
+    manager.assign_tenant(shard_id, "acme").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+
+    let metadata = HashMap::from([
+        ("email".to_string(), "a@acme.test".to_string()),
+        ("kind".to_string(), "invoice".to_string()),
+    ]);
+    let vector = Vector::new(vec![1.0, 0.0, 0.0]);
+    let id = manager
+        .add_vector(shard_id, vector.clone(), Some(metadata))
+        .await
+        .unwrap();
+
+    // Stored encrypted, other fields untouched
+    let index = manager.get_vector_index(shard_id).await.unwrap();
+    let stored = index.entries().await[0].metadata.clone().unwrap();
+    assert!(TenantKeyring::is_encrypted(&stored["email"]));
+    assert_eq!(stored["kind"], "invoice");
+
+    // Old values stay readable after rotation
+    keyring
+        .rotate("acme", &TenantKeyring::random_key())
+        .await
+        .unwrap();
+    let results = manager.search_vectors(shard_id, &vector, 1).await.unwrap();
+    let returned = results[0].metadata.as_ref().unwrap();
+    assert_eq!(returned["email"], "a@acme.test");
+
+    // Ciphertext is bound to its tenant, vector and field
+    keyring
+        .add_key("other", 1, &TenantKeyring::random_key())
+        .await
+        .unwrap();
+    let email = &stored["email"];
+    assert!(keyring.decrypt("other", id, "email", email).await.is_err());
+    assert!(keyring
+        .decrypt("acme", Uuid::new_v4(), "email", email)
+        .await
+        .is_err());
+    assert!(keyring.decrypt("acme", id, "kind", email).await.is_err());
+    assert_eq!(
+        keyring.decrypt("acme", id, "email", email).await.unwrap(),
+        "a@acme.test"
+    );
+
+    // A client value that looks encrypted is sealed like any other in a
+    // sensitive field, and refused elsewhere
+    let metadata = HashMap::from([("email".to_string(), email.clone())]);
+    let copied = manager
+        .add_vector(shard_id, vector.clone(), Some(metadata))
+        .await
+        .unwrap();
+    let (_, entry) = manager.find_vector(copied, Some(shard_id)).await.unwrap();
+    assert_eq!(&entry.metadata.unwrap()["email"], email);
+    let metadata = HashMap::from([("kind".to_string(), email.clone())]);
+    assert!(manager
+        .add_vector(shard_id, vector, Some(metadata))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn redaction_applies_to_audit_entries_and_errors() {
+    let policy = RedactionPolicy::from_list("ssn, api_key");
+    assert_eq!(
+        policy.redact_text("lookup failed for ssn=123-45-6789, user=bob"),
+        format!("lookup failed for ssn={}, user=bob", REDACTED)
+    );
+    assert_eq!(
+        policy.redact_text(r#"{"API_KEY": "sk-123", "ssn_hint": "last four"}"#),
+        format!(r#"{{"API_KEY": "{}", "ssn_hint": "last four"}}"#, REDACTED)
+    );
+    policy.install();
+
+    let audit = AuditLog::new();
+    let event = audit
+        .record(
+            "ingest",
+            "import",
+            "api_key: sk-999",
+            serde_json::json!({"ssn": "123-45-6789", "rows": 3}),
+        )
+        .await;
+    assert_eq!(event.details["ssn"], REDACTED);
+    assert_eq!(event.details["rows"], 3);
+    assert!(!event.subject.contains("sk-999"));
+
+    let body = serde_json::to_string(&ErrorResponse {
+        error: "invalid metadata: ssn=123-45-6789".into(),
+    })
+    .unwrap();
+    assert!(!body.contains("6789"));
+}
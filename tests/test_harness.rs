@@ -0,0 +1,72 @@
+use amazon_rose_forest::nerv::jobs::JobQueue;
+use amazon_rose_forest::server::api::SearchVectorsResponse;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use amazon_rose_forest::testing::TestHarness;
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn same_seed_gives_same_vectors_and_ids() {
+    let first = TestHarness::builder().with_seed(7).start().await.unwrap();
+    let second = TestHarness::builder().with_seed(7).start().await.unwrap();
+    assert_ne!(first.addr(), second.addr());
+
+    let a = first
+        .create_shard("docs", 4, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+    let b = second
+        .create_shard("docs", 4, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+    assert_eq!(
+        first.seed_vectors(a, 20).await.unwrap(),
+        second.seed_vectors(b, 20).await.unwrap()
+    );
+
+    let other = TestHarness::builder().with_seed(8).start().await.unwrap();
+    assert_ne!(first.random_vector(4), other.random_vector(4));
+}
+
+#[tokio::test]
+async fn serves_the_api_over_http() {
+    let harness = TestHarness::builder()
+        .with_server(|server, ctx| {
+            server.with_job_queue(Arc::new(JobQueue::new(ctx.metrics.clone())))
+        })
+        .start()
+        .await
+        .unwrap();
+    let shard_id = harness
+        .create_shard("docs", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let ids = harness.seed_vectors(shard_id, 10).await.unwrap();
+
+    let http = reqwest::Client::new();
+    let resp = http
+        .post(harness.api_url("search"))
+        .json(&json!({
+            "shard_id": shard_id,
+            "query_vector": [0.5, 0.5, 0.5],
+            "limit": 3
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+    let page: SearchVectorsResponse = resp.json().await.unwrap();
+    assert_eq!(page.results.len(), 3);
+    assert!(page
+        .results
+        .iter()
+        .all(|r| ids.iter().any(|id| id.to_string() == r.id)));
+
+    // The hook's job queue is wired in
+    let resp = http.get(harness.api_url("/jobs")).send().await.unwrap();
+    assert!(resp.status().is_success());
+
+    let url = harness.base_url();
+    harness.shutdown().await;
+    assert!(reqwest::get(format!("{}/health", url)).await.is_err());
+}
@@ -0,0 +1,202 @@
+mod common;
+
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::exploration::ExplorationStrategy;
+use amazon_rose_forest::darwin::self_improvement::{Modification, SelfImprovementEngine};
+use amazon_rose_forest::darwin::validation::{
+    failing_tests, StageOutput, ThresholdBound, ThresholdProfile, ValidationPipeline,
+    ValidationReport, ValidationStage,
+};
+use amazon_rose_forest::server::{Server, ServerConfig};
+use anyhow::anyhow;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+struct TestStage {
+    pass_rate: f32,
+}
+
+impl ValidationStage for TestStage {
+    fn name(&self) -> &str {
+        "unit_tests"
+    }
+
+    fn validate(&self, modification: &Modification) -> anyhow::Result<HashMap<String, f32>> {
+        Ok(self.run(modification)?.metrics)
+    }
+
+    fn run(&self, _modification: &Modification) -> anyhow::Result<StageOutput> {
+        Ok(StageOutput {
+            metrics: HashMap::from([("pass_rate".to_string(), self.pass_rate)]),
+            artifacts: vec!["sharding::split::keeps_ranges".to_string()],
+        })
+    }
+}
+
+struct BrokenStage;
+
+impl ValidationStage for BrokenStage {
+    fn name(&self) -> &str {
+        "security"
+    }
+
+    fn validate(&self, _modification: &Modification) -> anyhow::Result<HashMap<String, f32>> {
+        Err(anyhow!("cargo audit not installed"))
+    }
+}
+
+fn modification(path: &str) -> Modification {
+    common::modification(
+        "tune-cache",
+        vec![common::code_change(path, Some("a\n"), "b\n")],
+    )
+}
+
+fn engine(pipeline: ValidationPipeline, metrics: Arc<MetricsCollector>) -> SelfImprovementEngine {
+    SelfImprovementEngine::new(
+        metrics.clone(),
+        Arc::new(pipeline),
+        Arc::new(ExplorationStrategy::new(metrics)),
+    )
+}
+
+#[tokio::test]
+async fn report_compares_metrics_with_global_and_profile_thresholds() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let mut pipeline = ValidationPipeline::new(metrics.clone());
+    pipeline.add_stage(TestStage { pass_rate: 0.95 });
+    pipeline.set_threshold("unit_tests.pass_rate", 0.9);
+    pipeline.add_threshold_profile(
+        ThresholdProfile::new("core-engine", &["src/core/"])
+            .with_min("unit_tests.pass_rate", 0.99)
+            .with_max("unit_tests.flaky", 0.1),
+    );
+    let engine = engine(pipeline, metrics);
+
+    let id = engine
+        .propose_modification(modification("src/core/cache.rs"))
+        .await
+        .unwrap();
+    assert!(!engine.validate_modification(id).await.unwrap());
+
+    let report = engine.validation_report(id).await.unwrap();
+    assert!(!report.passed);
+    assert_eq!(report.stages.len(), 1);
+    let stage = &report.stages[0];
+    assert!(stage.passed);
+    assert_eq!(stage.metrics["pass_rate"], 0.95);
+    assert_eq!(stage.artifacts, vec!["sharding::split::keeps_ranges"]);
+
+    let checks: Vec<_> = report
+        .thresholds
+        .iter()
+        .map(|c| {
+            (
+                c.metric.as_str(),
+                c.profile.as_deref(),
+                c.bound,
+                c.value,
+                c.passed,
+            )
+        })
+        .collect();
+    assert_eq!(
+        checks,
+        vec![
+            (
+                "unit_tests.pass_rate",
+                None,
+                ThresholdBound::Min,
+                Some(0.95),
+                true
+            ),
+            (
+                "unit_tests.flaky",
+                Some("core-engine"),
+                ThresholdBound::Max,
+                None,
+                false
+            ),
+            (
+                "unit_tests.pass_rate",
+                Some("core-engine"),
+                ThresholdBound::Min,
+                Some(0.95),
+                false
+            ),
+        ]
+    );
+    assert_eq!(report.failed_thresholds().count(), 2);
+
+    // Outside the profile's paths only the global threshold applies
+    let id = engine
+        .propose_modification(modification("docs/cache.md"))
+        .await
+        .unwrap();
+    assert!(engine.validate_modification(id).await.unwrap());
+    let report = engine.validation_report(id).await.unwrap();
+    assert!(report.passed);
+    assert_eq!(report.thresholds.len(), 1);
+}
+
+#[tokio::test]
+async fn failed_stages_are_reported_and_served() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let mut pipeline = ValidationPipeline::new(metrics.clone());
+    pipeline.add_stage(TestStage { pass_rate: 1.0 });
+    pipeline.add_stage(BrokenStage);
+    let engine = Arc::new(engine(pipeline, metrics.clone()));
+
+    let id = engine
+        .propose_modification(modification("src/lib.rs"))
+        .await
+        .unwrap();
+    assert!(engine.validate_modification(id).await.is_err());
+
+    let server = Server::new(ServerConfig::default(), metrics, None, None)
+        .with_self_improvement_engine(engine);
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!("/api/darwin/modifications/{}/report", id))
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let report: ValidationReport = serde_json::from_slice(resp.body()).unwrap();
+    assert!(!report.passed);
+    let outcomes: Vec<_> = report
+        .stages
+        .iter()
+        .map(|s| (s.name.as_str(), s.passed))
+        .collect();
+    assert_eq!(outcomes, vec![("unit_tests", true), ("security", false)]);
+    assert_eq!(
+        report.stages[1].error.as_deref(),
+        Some("cargo audit not installed")
+    );
+    assert!(report.thresholds.is_empty());
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path(&format!(
+            "/api/darwin/modifications/{}/report",
+            Uuid::new_v4()
+        ))
+        .reply(&server.filter())
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn failing_test_names_are_parsed_from_libtest_output() {
+    let output = "running 3 tests\n\
+                  test query::planner::plans ... ok\n\
+                  test sharding::split::keeps_ranges ... FAILED\n\
+                  test darwin::report ... FAILED\n\
+                  test result: FAILED. 1 passed; 2 failed";
+    assert_eq!(
+        failing_tests(output),
+        vec!["sharding::split::keeps_ranges", "darwin::report"]
+    );
+}
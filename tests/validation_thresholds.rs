@@ -0,0 +1,138 @@
+use amazon_rose_forest::core::audit::AuditLog;
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::darwin::thresholds::{
+    ThresholdAdmin, ThresholdSettings, ThresholdUpdateOutcome, AUDIT_CATEGORY,
+};
+use amazon_rose_forest::darwin::validation::ValidationPipeline;
+use amazon_rose_forest::governance::dao::Dao;
+use amazon_rose_forest::governance::executor::ProposalAction;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use warp::http::StatusCode;
+use warp::Filter;
+
+const PATH: &str = "/api/darwin/validation/thresholds";
+
+fn pipeline(metrics: Arc<MetricsCollector>) -> Arc<ValidationPipeline> {
+    let pipeline = ValidationPipeline::new(metrics);
+    pipeline.set_threshold("unit_tests.pass_rate", 0.9);
+    pipeline.set_threshold("security.compliance_score", 0.8);
+    Arc::new(pipeline)
+}
+
+async fn put<F>(filter: &F, body: Value) -> (StatusCode, Value)
+where
+    F: Filter + 'static,
+    F::Extract: warp::Reply + Send,
+{
+    let resp = warp::test::request()
+        .method("PUT")
+        .path(PATH)
+        .json(&body)
+        .reply(filter)
+        .await;
+    (resp.status(), serde_json::from_slice(resp.body()).unwrap())
+}
+
+#[tokio::test]
+async fn thresholds_are_listed_and_changed_with_an_audit_trail() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let unconfigured = Server::new(ServerConfig::default(), metrics.clone(), None, None).filter();
+    let resp = warp::test::request().path(PATH).reply(&unconfigured).await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let pipeline = pipeline(metrics.clone());
+    let audit = Arc::new(AuditLog::new());
+    let admin = Arc::new(ThresholdAdmin::new(pipeline.clone(), audit.clone()));
+    let filter = Server::new(ServerConfig::default(), metrics, None, None)
+        .with_threshold_admin(admin)
+        .filter();
+
+    let resp = warp::test::request().path(PATH).reply(&filter).await;
+    let settings: ThresholdSettings = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(settings.thresholds["unit_tests.pass_rate"], 0.9);
+    assert!(!settings.approval_required);
+
+    // Without a DAO every change applies straight away
+    let (status, body) = put(
+        &filter,
+        json!({
+            "thresholds": { "unit_tests.pass_rate": 0.95, "security.compliance_score": null },
+            "actor": "ops",
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let outcome: ThresholdUpdateOutcome = serde_json::from_value(body).unwrap();
+    assert_eq!(outcome.applied.len(), 2);
+    assert!(outcome.pending.is_empty());
+    assert_eq!(pipeline.threshold("unit_tests.pass_rate"), Some(0.95));
+    assert_eq!(pipeline.threshold("security.compliance_score"), None);
+
+    let events = audit.by_category(AUDIT_CATEGORY, 10).await;
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|e| e.details["actor"] == "ops"));
+    let removed = events
+        .iter()
+        .find(|e| e.action == "remove_threshold")
+        .unwrap();
+    assert_eq!(removed.subject, "security.compliance_score");
+    assert_eq!(removed.details["previous"], 0.8f32 as f64);
+
+    let (status, _) = put(&filter, json!({ "thresholds": {} })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(audit.len().await, 2);
+}
+
+#[tokio::test]
+async fn loosening_security_thresholds_waits_for_dao_approval() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let pipeline = pipeline(metrics.clone());
+    let audit = Arc::new(AuditLog::new());
+    let dao = Arc::new(Dao::new());
+    let admin = ThresholdAdmin::new(pipeline.clone(), audit.clone()).with_dao(dao.clone());
+    let filter = Server::new(ServerConfig::default(), metrics, None, None)
+        .with_threshold_admin(Arc::new(admin))
+        .filter();
+
+    let (status, body) = put(
+        &filter,
+        json!({
+            "thresholds": {
+                "security.compliance_score": 0.5,
+                "unit_tests.pass_rate": 0.7,
+            },
+            "reason": "flaky scanner",
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{}", body);
+    let outcome: ThresholdUpdateOutcome = serde_json::from_value(body).unwrap();
+    // Only security gates need approval to loosen
+    assert_eq!(pipeline.threshold("unit_tests.pass_rate"), Some(0.7));
+    assert_eq!(pipeline.threshold("security.compliance_score"), Some(0.8));
+    assert_eq!(outcome.pending.len(), 1);
+    let proposal = dao.proposal(outcome.pending[0].proposal_id).await.unwrap();
+    assert_eq!(proposal.description, "flaky scanner");
+    assert_eq!(
+        proposal.actions,
+        vec![ProposalAction::SetValidationThreshold {
+            metric: "security.compliance_score".into(),
+            threshold: 0.5,
+        }]
+    );
+    let events = audit.by_category(AUDIT_CATEGORY, 10).await;
+    assert!(events.iter().any(|e| e.action == "propose_threshold"));
+
+    // Tightening doesn't need approval
+    let tighten = json!({ "thresholds": { "security.compliance_score": 0.9 } });
+    assert_eq!(put(&filter, tighten).await.0, StatusCode::OK);
+    assert_eq!(pipeline.threshold("security.compliance_score"), Some(0.9));
+
+    let remove = json!({ "thresholds": { "security.compliance_score": null } });
+    let (status, body) = put(&filter, remove).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("DAO approval"));
+    assert_eq!(pipeline.threshold("security.compliance_score"), Some(0.9));
+}
@@ -0,0 +1,197 @@
+use amazon_rose_forest::core::metrics::MetricsCollector;
+use amazon_rose_forest::core::vector::Vector;
+use amazon_rose_forest::query::compose::compose;
+use amazon_rose_forest::query::{ComposeOp, ComposeTerm};
+use amazon_rose_forest::server::api::ComposeVectorsResponse;
+use amazon_rose_forest::server::{Server, ServerConfig};
+use amazon_rose_forest::sharding::manager::ShardManager;
+use amazon_rose_forest::sharding::vector_index::DistanceMetric;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+#[test]
+fn operations_combine_terms() {
+    let a = Vector::new(vec![4.0, 2.0]);
+    let b = Vector::new(vec![1.0, 1.0]);
+    let c = Vector::new(vec![1.0, -3.0]);
+    let terms = [(a, 2.0), (b, -1.0), (c, 0.5)];
+
+    assert_eq!(
+        compose(ComposeOp::Add, &terms).unwrap().values,
+        vec![6.0, 0.0]
+    );
+    assert_eq!(
+        compose(ComposeOp::Subtract, &terms).unwrap().values,
+        vec![2.0, 4.0]
+    );
+    assert_eq!(
+        compose(ComposeOp::Average, &terms).unwrap().values,
+        vec![2.0, 0.0]
+    );
+    assert_eq!(
+        compose(ComposeOp::Weighted, &terms).unwrap().values,
+        vec![7.5, 1.5]
+    );
+
+    assert!(compose(ComposeOp::Add, &[]).is_err());
+    assert!(compose(
+        ComposeOp::Add,
+        &[
+            (Vector::new(vec![1.0]), 1.0),
+            (Vector::new(vec![1.0, 2.0]), 1.0)
+        ]
+    )
+    .is_err());
+}
+
+/// A shard where `king - man + woman` lands on `queen`
+async fn royalty() -> (Arc<ShardManager>, Uuid, HashMap<&'static str, Uuid>) {
+    let manager = Arc::new(ShardManager::new(Arc::new(MetricsCollector::new())));
+    let shard_id = manager.create_shard("words").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    let mut ids = HashMap::new();
+    for (word, values) in [
+        ("king", [1.0, 1.0, 0.0]),
+        ("man", [1.0, 0.0, 0.0]),
+        ("woman", [0.0, 0.0, 1.0]),
+        ("queen", [0.0, 1.0, 1.0]),
+        ("apple", [5.0, -5.0, 5.0]),
+    ] {
+        let metadata = HashMap::from([("word".to_string(), word.to_string())]);
+        let id = manager
+            .add_vector(shard_id, Vector::new(values.to_vec()), Some(metadata))
+            .await
+            .unwrap();
+        ids.insert(word, id);
+    }
+    (manager, shard_id, ids)
+}
+
+#[tokio::test]
+async fn stored_vectors_are_composed_by_id() {
+    let (manager, shard_id, ids) = royalty().await;
+    let analogy = [
+        ComposeTerm::id(ids["king"]),
+        ComposeTerm::id(ids["man"]).with_weight(-1.0),
+        ComposeTerm::id(ids["woman"]),
+    ];
+
+    let vector = manager
+        .compose_vectors(shard_id, ComposeOp::Weighted, &analogy, false)
+        .await
+        .unwrap();
+    assert_eq!(vector.values, vec![0.0, 1.0, 1.0]);
+
+    let unit = manager
+        .compose_vectors(shard_id, ComposeOp::Weighted, &analogy, true)
+        .await
+        .unwrap();
+    assert!((unit.magnitude() - 1.0).abs() < 1e-6);
+
+    let mixed = manager
+        .compose_vectors(
+            shard_id,
+            ComposeOp::Average,
+            &[
+                ComposeTerm::id(ids["man"]),
+                ComposeTerm::vector(vec![0.0, 2.0, 0.0]),
+            ],
+            false,
+        )
+        .await
+        .unwrap();
+    assert_eq!(mixed.values, vec![0.5, 1.0, 0.0]);
+
+    assert!(manager
+        .compose_vectors(
+            shard_id,
+            ComposeOp::Add,
+            &[ComposeTerm::id(Uuid::new_v4())],
+            false
+        )
+        .await
+        .is_err());
+    assert!(manager
+        .compose_vectors(
+            shard_id,
+            ComposeOp::Add,
+            &[ComposeTerm::vector(vec![1.0, 2.0])],
+            false
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn compose_endpoint_answers_analogy_queries() {
+    let (manager, shard_id, ids) = royalty().await;
+    let server = Server::new(
+        ServerConfig::default(),
+        Arc::new(MetricsCollector::new()),
+        None,
+        Some(manager),
+    );
+    let filter = server.filter();
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/vectors/compose")
+        .json(&json!({
+            "shard_id": shard_id,
+            "operation": "weighted",
+            "terms": [
+                {"id": ids["king"]},
+                {"id": ids["man"], "weight": -1.0},
+                {"id": ids["woman"]}
+            ],
+            "search": {"limit": 1}
+        }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: ComposeVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.vector, vec![0.0, 1.0, 1.0]);
+    let results = body.results.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].id, ids["queen"].to_string());
+
+    // Without a search only the vector comes back, and the inputs can be
+    // kept in the results when asked
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/vectors/compose")
+        .json(&json!({
+            "shard_id": shard_id,
+            "operation": "subtract",
+            "terms": [{"id": ids["queen"]}, {"vector": [0.0, 1.0, 0.0]}],
+            "search": {"limit": 1, "exclude_terms": false}
+        }))
+        .reply(&filter)
+        .await;
+    let body: ComposeVectorsResponse = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body.vector, vec![0.0, 0.0, 1.0]);
+    assert_eq!(body.results.unwrap()[0].id, ids["woman"].to_string());
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/vectors/compose")
+        .json(&json!({"shard_id": shard_id, "terms": [{"id": Uuid::new_v4()}]}))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // Adding vectors still goes to the plain vectors route
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/vectors")
+        .json(&json!({"shard_id": shard_id, "vector": [0.1, 0.2, 0.3]}))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
@@ -0,0 +1,261 @@
+use amazon_rose_forest::{
+    core::metrics::MetricsCollector,
+    nerv::failover::{FailoverConfig, PeerHealth},
+    nerv::region::{RegionConfig, RegionReplicator, RegionRole},
+    server::{Server, ServerConfig},
+    sharding::{manager::ShardManager, vector_index::DistanceMetric},
+    Vector,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+async fn node_manager(metrics: Arc<MetricsCollector>) -> (Arc<ShardManager>, uuid::Uuid) {
+    let manager = Arc::new(ShardManager::new(metrics));
+    let shard_id = manager.create_shard("docs").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 3, DistanceMetric::Euclidean)
+        .await
+        .unwrap();
+    (manager, shard_id)
+}
+
+#[tokio::test]
+async fn standby_promotes_after_missed_heartbeats() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let (manager, shard_id) = node_manager(metrics.clone()).await;
+    // Nothing listens on the peer's port
+    let config = RegionConfig::new("b", "http://127.0.0.1:1/api", RegionRole::Standby)
+        .with_failover(FailoverConfig {
+            heartbeat_timeout: Duration::from_millis(200),
+            failure_threshold: 2,
+            ..Default::default()
+        });
+    let standby = RegionReplicator::new(config, manager.clone(), metrics.clone());
+    standby.demote().await.unwrap();
+
+    assert_eq!(standby.monitor_once().await, PeerHealth::Suspect);
+    assert_eq!(standby.role().await, RegionRole::Standby);
+    assert_eq!(standby.epoch().await, 0);
+
+    assert_eq!(standby.monitor_once().await, PeerHealth::Failed);
+    assert_eq!(standby.role().await, RegionRole::Active);
+    assert_eq!(standby.epoch().await, 1);
+    assert_eq!(standby.status().await.missed_heartbeats, 2);
+    assert_eq!(metrics.get_counter("replication.failovers").await, Some(1));
+    assert!(manager
+        .add_vector(shard_id, Vector::random(3), None)
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn partitioned_primary_refuses_writes_before_standby_promotes() {
+    // Neither node can reach the other
+    let failover = FailoverConfig {
+        heartbeat_timeout: Duration::from_millis(200),
+        ..Default::default()
+    };
+    let metrics_a = Arc::new(MetricsCollector::new());
+    let (manager_a, shard_a) = node_manager(metrics_a.clone()).await;
+    let primary = RegionReplicator::new(
+        RegionConfig::new("a", "http://127.0.0.1:1/api", RegionRole::Active)
+            .with_failover(failover.clone()),
+        manager_a.clone(),
+        metrics_a.clone(),
+    );
+    let metrics_b = Arc::new(MetricsCollector::new());
+    let (manager_b, _) = node_manager(metrics_b.clone()).await;
+    let standby = RegionReplicator::new(
+        RegionConfig::new("b", "http://127.0.0.1:1/api", RegionRole::Standby)
+            .with_failover(failover),
+        manager_b,
+        metrics_b,
+    );
+    standby.demote().await.unwrap();
+
+    let mut primary_writable = Vec::new();
+    loop {
+        primary.monitor_once().await;
+        primary_writable.push(
+            manager_a
+                .add_vector(shard_a, Vector::random(3), None)
+                .await
+                .is_ok(),
+        );
+        standby.monitor_once().await;
+        if standby.role().await == RegionRole::Active {
+            break;
+        }
+    }
+
+    // Writable for the first missed heartbeat, read-only from the lease
+    // threshold on, and never writable once the standby has promoted
+    assert_eq!(primary_writable, vec![true, false, false]);
+    assert_eq!(primary.role().await, RegionRole::Active);
+    assert!(primary.status().await.lease_expired);
+    assert_eq!(
+        metrics_a.get_counter("replication.leases_expired").await,
+        Some(1)
+    );
+}
+
+#[tokio::test]
+async fn old_primary_is_fenced_by_promoted_standby() {
+    // The standby has taken over while the old primary was partitioned
+    let metrics_b = Arc::new(MetricsCollector::new());
+    let (manager_b, shard_b) = node_manager(metrics_b.clone()).await;
+    let new_primary = Arc::new(RegionReplicator::new(
+        RegionConfig::new("b", "http://unused", RegionRole::Standby)
+            .with_failover(FailoverConfig::default()),
+        manager_b.clone(),
+        metrics_b.clone(),
+    ));
+    new_primary.promote().await.unwrap();
+    assert_eq!(new_primary.epoch().await, 1);
+    let server_b = Server::new(
+        ServerConfig::default(),
+        metrics_b.clone(),
+        None,
+        Some(manager_b.clone()),
+    )
+    .with_region_replicator(new_primary);
+    let (addr, serve) = warp::serve(server_b.filter()).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(serve);
+
+    let metrics_a = Arc::new(MetricsCollector::new());
+    let (manager_a, shard_a) = node_manager(metrics_a.clone()).await;
+    let old_primary = RegionReplicator::new(
+        RegionConfig::new("a", &format!("http://{}/api", addr), RegionRole::Active)
+            .with_failover(FailoverConfig::default()),
+        manager_a.clone(),
+        metrics_a.clone(),
+    );
+
+    // A write accepted during the partition is refused by the new primary
+    let stale = manager_a
+        .add_vector(shard_a, Vector::random(3), None)
+        .await
+        .unwrap();
+    assert!(old_primary.ship_once().await.is_err());
+    assert_eq!(
+        metrics_b.get_counter("replication.fenced_segments").await,
+        Some(1)
+    );
+    let index_b = manager_b.get_vector_index(shard_b).await.unwrap();
+    assert!(index_b.get(stale).await.is_none());
+
+    // ...and the old primary steps down once it sees the newer epoch
+    assert_eq!(old_primary.role().await, RegionRole::Standby);
+    assert_eq!(old_primary.epoch().await, 1);
+    assert!(manager_a
+        .add_vector(shard_a, Vector::random(3), None)
+        .await
+        .is_err());
+
+    let heartbeat = old_primary.check_peer().await.unwrap();
+    assert_eq!(heartbeat.region, "b");
+    assert_eq!(heartbeat.role, RegionRole::Active);
+    assert_eq!(old_primary.monitor_once().await, PeerHealth::Healthy);
+}
+
+fn data_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("warm-standby-{}-{}", name, uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn fencing_survives_restarts() {
+    let dir_a = data_dir("a");
+    let dir_b = data_dir("b");
+
+    // The standby takes over, then restarts still holding the new epoch
+    let metrics_b = Arc::new(MetricsCollector::new());
+    let (manager_b, shard_b) = node_manager(metrics_b.clone()).await;
+    let config_b = RegionConfig::new("b", "http://unused", RegionRole::Standby)
+        .with_failover(FailoverConfig::default())
+        .with_data_dir(&dir_b);
+    RegionReplicator::new(config_b.clone(), manager_b.clone(), metrics_b.clone())
+        .promote()
+        .await
+        .unwrap();
+    let new_primary = Arc::new(RegionReplicator::new(
+        config_b,
+        manager_b.clone(),
+        metrics_b.clone(),
+    ));
+    assert_eq!(new_primary.role().await, RegionRole::Active);
+    assert_eq!(new_primary.epoch().await, 1);
+    let server_b = Server::new(
+        ServerConfig::default(),
+        metrics_b.clone(),
+        None,
+        Some(manager_b.clone()),
+    )
+    .with_region_replicator(new_primary);
+    let (addr, serve) = warp::serve(server_b.filter()).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(serve);
+
+    // The old primary is fenced as soon as it ships
+    let metrics_a = Arc::new(MetricsCollector::new());
+    let (manager_a, shard_a) = node_manager(metrics_a.clone()).await;
+    let config_a = RegionConfig::new("a", &format!("http://{}/api", addr), RegionRole::Active)
+        .with_failover(FailoverConfig::default())
+        .with_data_dir(&dir_a);
+    let old_primary = RegionReplicator::new(config_a.clone(), manager_a.clone(), metrics_a.clone());
+    let stale = manager_a
+        .add_vector(shard_a, Vector::random(3), None)
+        .await
+        .unwrap();
+    assert!(old_primary.ship_once().await.is_err());
+    assert_eq!(old_primary.role().await, RegionRole::Standby);
+    drop(old_primary);
+
+    // Restarted with its original config, it comes back fenced rather than
+    // as a primary at epoch 0
+    let restarted = RegionReplicator::new(config_a, manager_a.clone(), metrics_a.clone());
+    assert_eq!(restarted.role().await, RegionRole::Standby);
+    assert_eq!(restarted.epoch().await, 1);
+    assert_eq!(restarted.heartbeat().await.epoch, 1);
+
+    // A primary that lost its state is still refused by the restarted peer
+    let forgetful = RegionReplicator::new(
+        RegionConfig::new("a", &format!("http://{}/api", addr), RegionRole::Active),
+        manager_a,
+        metrics_a,
+    );
+    assert!(forgetful.ship_once().await.is_err());
+    assert_eq!(
+        metrics_b.get_counter("replication.fenced_segments").await,
+        Some(2)
+    );
+    let index_b = manager_b.get_vector_index(shard_b).await.unwrap();
+    assert!(index_b.get(stale).await.is_none());
+
+    std::fs::remove_dir_all(dir_a).unwrap();
+    std::fs::remove_dir_all(dir_b).unwrap();
+}
+
+#[tokio::test]
+async fn unreadable_fencing_state_restores_a_standby() {
+    let dir = data_dir("corrupt");
+    std::fs::write(dir.join("replication_fencing.json"), b"{not json").unwrap();
+    let metrics = Arc::new(MetricsCollector::new());
+    let (manager, _) = node_manager(metrics.clone()).await;
+    let replicator = RegionReplicator::new(
+        RegionConfig::new("a", "http://unused", RegionRole::Active).with_data_dir(&dir),
+        manager,
+        metrics,
+    );
+    assert_eq!(replicator.role().await, RegionRole::Standby);
+
+    // Promoting writes the state afresh
+    replicator.promote().await.unwrap();
+    let replicator_epoch = replicator.epoch().await;
+    let saved: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(dir.join("replication_fencing.json")).unwrap())
+            .unwrap();
+    assert_eq!(saved["role"], "active");
+    assert_eq!(saved["epoch"], replicator_epoch);
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,138 @@
+use amazon_rose_forest::{
+    core::metrics::MetricsCollector,
+    embedding::HashingEmbedder,
+    ingest::{
+        JsonPath, TextRule, WebhookIngestResult, WebhookIngestor, WebhookPipeline,
+        WebhookPipelineConfig,
+    },
+    server::{Server, ServerConfig},
+    sharding::{manager::ShardManager, vector_index::DistanceMetric},
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use warp::http::StatusCode;
+
+#[test]
+fn jsonpath_selects_nested_fields() {
+    let doc = serde_json::json!({
+        "issue": { "title": "Crash", "labels": [{ "name": "bug" }, { "name": "p1" }] },
+        "odd key": 1
+    });
+
+    let title = JsonPath::parse("$.issue.title").unwrap();
+    assert_eq!(title.select(&doc), vec![&serde_json::json!("Crash")]);
+
+    let labels = JsonPath::parse("$.issue.labels[*].name").unwrap();
+    assert_eq!(labels.select(&doc).len(), 2);
+
+    let last = JsonPath::parse("$.issue.labels[-1].name").unwrap();
+    assert_eq!(last.select(&doc), vec![&serde_json::json!("p1")]);
+
+    let quoted = JsonPath::parse("$['odd key']").unwrap();
+    assert_eq!(quoted.select(&doc), vec![&serde_json::json!(1)]);
+
+    assert!(JsonPath::parse("issue.title").is_err());
+    assert!(JsonPath::parse("$.items[").is_err());
+}
+
+#[test]
+fn pipeline_concatenates_text_per_record() {
+    let pipeline = WebhookPipeline::new(WebhookPipelineConfig {
+        name: "tickets".into(),
+        shard_id: uuid::Uuid::new_v4(),
+        records: Some("$.events[*]".into()),
+        text: TextRule {
+            paths: vec!["$.subject".into(), "$.body".into()],
+            separator: " | ".into(),
+        },
+        metadata: HashMap::from([("ticket".to_string(), "$.id".to_string())]),
+    })
+    .unwrap();
+
+    let records = pipeline.transform(&serde_json::json!({
+        "events": [
+            { "id": 7, "subject": "Login fails", "body": "Password reset loops" },
+            { "id": 8, "body": "" }
+        ]
+    }));
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].0, "Login fails | Password reset loops");
+    assert_eq!(records[0].1["ticket"], "7");
+}
+
+#[tokio::test]
+async fn webhook_endpoint_embeds_and_stores_payload() {
+    let metrics = Arc::new(MetricsCollector::new());
+    let manager = Arc::new(ShardManager::new(metrics.clone()));
+    let shard_id = manager.create_shard("support").await.unwrap();
+    manager
+        .create_vector_index(shard_id, "main", 32, DistanceMetric::Cosine)
+        .await
+        .unwrap();
+
+    let ingestor = Arc::new(WebhookIngestor::new(
+        manager.clone(),
+        Arc::new(HashingEmbedder::new(32)),
+        metrics.clone(),
+    ));
+    let server = Server::new(
+        ServerConfig::default(),
+        metrics,
+        None,
+        Some(manager.clone()),
+    )
+    .with_webhook_ingestor(ingestor);
+    let filter = server.filter();
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/ingest/pipelines")
+        .json(&serde_json::json!({
+            "name": "zendesk",
+            "shard_id": shard_id,
+            "text": { "paths": ["$.ticket.subject", "$.ticket.description"] },
+            "metadata": { "requester": "$.ticket.requester.email" }
+        }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/ingest/webhook/zendesk")
+        .json(&serde_json::json!({
+            "ticket": {
+                "subject": "Refund request",
+                "description": "Charged twice for one order",
+                "requester": { "email": "a@example.com" }
+            }
+        }))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    let result: WebhookIngestResult = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(result.vector_ids.len(), 1);
+
+    let stored = manager
+        .get_vector_indices()
+        .await
+        .into_iter()
+        .find(|(id, _)| *id == shard_id)
+        .unwrap()
+        .1
+        .get(result.vector_ids[0])
+        .await
+        .unwrap();
+    let metadata = stored.metadata.unwrap();
+    assert_eq!(metadata["requester"], "a@example.com");
+    assert_eq!(metadata["ingest.pipeline"], "zendesk");
+
+    let resp = warp::test::request()
+        .method("POST")
+        .path("/api/ingest/webhook/unknown")
+        .json(&serde_json::json!({}))
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,83 @@
+//! Rules every value flow has to satisfy.
+//!
+//! The value flow zome validates entries with these rules before they reach
+//! the DHT, and nodes apply the same ones, so the two can't drift apart. The
+//! rules don't depend on how agents are identified: callers describe a flow
+//! as [`FlowTerms`] over their own agent type.
+
+pub mod zkp;
+
+use zkp::{UtilityBounds, UtilityProof, ZKP};
+
+/// Largest utility a single flow may carry
+pub const MAX_FLOW_UTILITY: f32 = 1_000_000.0;
+
+/// Upper bound on the number of reputation dimensions a shift may touch
+pub const MAX_REPUTATION_DIMENSIONS: usize = 64;
+
+/// The parts of a value flow the rules look at
+#[derive(Debug, Clone, Copy)]
+pub struct FlowTerms<'a, A> {
+    pub from: &'a A,
+    pub to: &'a A,
+    /// Zero when the utility is private and committed in `utility_proof`
+    pub utility: f32,
+    pub governance_weight: f32,
+    pub reputation_shift: &'a [f32],
+    pub utility_proof: Option<&'a UtilityProof>,
+}
+
+impl<A: PartialEq> FlowTerms<'_, A> {
+    /// Check the parties, utility bounds, governance weight and reputation
+    /// shift. Private flows publish zero utility and prove their committed
+    /// utility is within the same bounds as public ones.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.from == self.to {
+            return Err("An agent cannot create a value flow to itself".to_string());
+        }
+        match self.utility_proof {
+            Some(_) if self.utility != 0.0 => {
+                return Err("Private flows must not reveal their utility".to_string());
+            }
+            Some(proof) => ZKP::new()
+                .verify_utility(proof, UtilityBounds::default())
+                .map_err(|e| e.to_string())?,
+            None if !self.utility.is_finite()
+                || self.utility <= 0.0
+                || self.utility > MAX_FLOW_UTILITY =>
+            {
+                return Err(format!(
+                    "Utility must be in (0, {}], got {}",
+                    MAX_FLOW_UTILITY, self.utility
+                ));
+            }
+            None => {}
+        }
+        if !(0.0..=1.0).contains(&self.governance_weight) {
+            return Err(format!(
+                "Governance weight must be in [0, 1], got {}",
+                self.governance_weight
+            ));
+        }
+        validate_shift_vector(self.reputation_shift)
+    }
+}
+
+/// Check a reputation shift: non-empty, finite and at most
+/// [`MAX_REPUTATION_DIMENSIONS`] long
+pub fn validate_shift_vector(shift: &[f32]) -> Result<(), String> {
+    if shift.is_empty() {
+        return Err("Reputation shift vector must not be empty".to_string());
+    }
+    if shift.len() > MAX_REPUTATION_DIMENSIONS {
+        return Err(format!(
+            "Reputation shift vector has {} dimensions, maximum is {}",
+            shift.len(),
+            MAX_REPUTATION_DIMENSIONS
+        ));
+    }
+    if shift.iter().any(|v| !v.is_finite()) {
+        return Err("Reputation shift values must be finite".to_string());
+    }
+    Ok(())
+}
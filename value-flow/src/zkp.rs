@@ -0,0 +1,256 @@
+//! Zero-knowledge range proofs for private value flow utility.
+//!
+//! A value flow can publish its utility as a Pedersen commitment together
+//! with a Bulletproofs range proof that the committed amount lies within the
+//! network's [`UtilityBounds`]. Validators and the reputation engine check
+//! the proof without learning the exact amount.
+//!
+//! Utility is committed in fixed point, [`UTILITY_SCALE`] steps per unit. A
+//! bounded proof is an aggregated proof over two commitments derived from the
+//! published one, `C`: `C - min·B` and `max·B - C` must both open to values
+//! in `[0, 2^UTILITY_BITS)`, which only holds if `min <= utility <= max`.
+
+use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
+use curve25519_dalek_ng::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek_ng::scalar::Scalar;
+use merlin::Transcript;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Fixed-point steps per unit of utility
+pub const UTILITY_SCALE: f64 = 1000.0;
+
+/// Bits covered by each half of a bounded proof; bounds may span at most
+/// `2^UTILITY_BITS / UTILITY_SCALE` units
+pub const UTILITY_BITS: usize = 32;
+
+/// Largest fixed-point amount; keeps conversions from `f32` exact
+const MAX_FIXED: u64 = 1 << 52;
+
+const TRANSCRIPT_LABEL: &[u8] = b"amazon-rose-forest.utility-range";
+
+#[derive(Error, Debug)]
+pub enum ZkpError {
+    #[error("Invalid utility bounds [{min}, {max}]")]
+    InvalidBounds { min: f32, max: f32 },
+
+    #[error("Utility {utility} is outside [{min}, {max}]")]
+    OutOfRange { utility: f32, min: f32, max: f32 },
+
+    #[error("Failed to build range proof: {0}")]
+    Proving(String),
+
+    #[error("Malformed {0}")]
+    Malformed(String),
+
+    #[error("Range proof does not verify")]
+    InvalidProof,
+
+    #[error("Flow reveals the utility it commits to")]
+    RevealedUtility,
+}
+
+/// Inclusive range a committed utility must fall in. The default is what
+/// value flows are held to: at least one fixed-point step and at most
+/// [`MAX_FLOW_UTILITY`](crate::MAX_FLOW_UTILITY).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UtilityBounds {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for UtilityBounds {
+    fn default() -> Self {
+        Self {
+            min: 0.001,
+            max: crate::MAX_FLOW_UTILITY,
+        }
+    }
+}
+
+impl UtilityBounds {
+    pub fn new(min: f32, max: f32) -> Result<Self, ZkpError> {
+        let bounds = Self { min, max };
+        bounds.fixed()?;
+        Ok(bounds)
+    }
+
+    fn fixed(&self) -> Result<(u64, u64), ZkpError> {
+        let invalid = || ZkpError::InvalidBounds {
+            min: self.min,
+            max: self.max,
+        };
+        let min = to_fixed(self.min).ok_or_else(invalid)?;
+        let max = to_fixed(self.max).ok_or_else(invalid)?;
+        if min > max || max - min >= 1 << UTILITY_BITS {
+            return Err(invalid());
+        }
+        Ok((min, max))
+    }
+}
+
+fn to_fixed(utility: f32) -> Option<u64> {
+    let scaled = (utility as f64 * UTILITY_SCALE).round();
+    (0.0..=MAX_FIXED as f64)
+        .contains(&scaled)
+        .then_some(scaled as u64)
+}
+
+/// A committed utility and the proof that it lies within bounds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UtilityProof {
+    /// Compressed Ristretto point committing to the fixed-point utility
+    pub commitment: Vec<u8>,
+    /// Serialized aggregated range proof
+    pub proof: Vec<u8>,
+}
+
+/// What the prover keeps to reveal a committed utility later, e.g. to an
+/// arbitrator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UtilityOpening {
+    /// Fixed-point utility
+    pub amount: u64,
+    pub blinding: [u8; 32],
+}
+
+impl UtilityOpening {
+    pub fn utility(&self) -> f32 {
+        (self.amount as f64 / UTILITY_SCALE) as f32
+    }
+}
+
+/// Zero-knowledge proof handler
+pub struct ZKP {
+    bp_gens: BulletproofGens,
+    pc_gens: PedersenGens,
+}
+
+impl std::fmt::Debug for ZKP {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZKP")
+            .field("bits", &UTILITY_BITS)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ZKP {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZKP {
+    /// Create a new ZKP handler
+    pub fn new() -> Self {
+        Self {
+            bp_gens: BulletproofGens::new(UTILITY_BITS, 2),
+            pc_gens: PedersenGens::default(),
+        }
+    }
+
+    /// Commit to `utility` and prove it lies within `bounds`
+    #[cfg(feature = "prove")]
+    pub fn prove_utility(
+        &self,
+        utility: f32,
+        bounds: UtilityBounds,
+    ) -> Result<(UtilityProof, UtilityOpening), ZkpError> {
+        let (min, max) = bounds.fixed()?;
+        let amount = to_fixed(utility)
+            .filter(|amount| (min..=max).contains(amount))
+            .ok_or(ZkpError::OutOfRange {
+                utility,
+                min: bounds.min,
+                max: bounds.max,
+            })?;
+
+        let blinding = Scalar::random(&mut rand::thread_rng());
+        let (proof, _) = RangeProof::prove_multiple(
+            &self.bp_gens,
+            &self.pc_gens,
+            &mut transcript(min, max),
+            &[amount - min, max - amount],
+            &[blinding, -blinding],
+            UTILITY_BITS,
+        )
+        .map_err(|e| ZkpError::Proving(format!("{:?}", e)))?;
+        let commitment = self.pc_gens.commit(Scalar::from(amount), blinding);
+
+        Ok((
+            UtilityProof {
+                commitment: commitment.compress().to_bytes().to_vec(),
+                proof: proof.to_bytes(),
+            },
+            UtilityOpening {
+                amount,
+                blinding: blinding.to_bytes(),
+            },
+        ))
+    }
+
+    /// Check that `proof` commits to a utility within `bounds`
+    pub fn verify_utility(
+        &self,
+        proof: &UtilityProof,
+        bounds: UtilityBounds,
+    ) -> Result<(), ZkpError> {
+        let (min, max) = bounds.fixed()?;
+        let commitment = decompress(&proof.commitment)?;
+        let range_proof = RangeProof::from_bytes(&proof.proof)
+            .map_err(|_| ZkpError::Malformed("range proof".to_string()))?;
+
+        let above_min = commitment - self.pc_gens.B * Scalar::from(min);
+        let below_max = self.pc_gens.B * Scalar::from(max) - commitment;
+        range_proof
+            .verify_multiple(
+                &self.bp_gens,
+                &self.pc_gens,
+                &mut transcript(min, max),
+                &[above_min.compress(), below_max.compress()],
+                UTILITY_BITS,
+            )
+            .map_err(|_| ZkpError::InvalidProof)
+    }
+
+    /// Whether `opening` reveals the utility committed in `proof`
+    pub fn opens(&self, proof: &UtilityProof, opening: &UtilityOpening) -> bool {
+        let Some(blinding) = Scalar::from_canonical_bytes(opening.blinding) else {
+            return false;
+        };
+        let commitment = self.pc_gens.commit(Scalar::from(opening.amount), blinding);
+        commitment.compress().as_bytes()[..] == proof.commitment[..]
+    }
+
+    /// Check a value flow's utility. Flows without a proof are public and
+    /// always pass; flows with one must not also reveal their utility, which
+    /// is published as zero.
+    pub fn verify_flow(
+        &self,
+        utility: f32,
+        proof: Option<&UtilityProof>,
+        bounds: UtilityBounds,
+    ) -> Result<(), ZkpError> {
+        match proof {
+            None => Ok(()),
+            Some(_) if utility != 0.0 => Err(ZkpError::RevealedUtility),
+            Some(proof) => self.verify_utility(proof, bounds),
+        }
+    }
+}
+
+fn transcript(min: u64, max: u64) -> Transcript {
+    let mut transcript = Transcript::new(TRANSCRIPT_LABEL);
+    transcript.append_u64(b"min", min);
+    transcript.append_u64(b"max", max);
+    transcript
+}
+
+fn decompress(bytes: &[u8]) -> Result<RistrettoPoint, ZkpError> {
+    if bytes.len() != 32 {
+        return Err(ZkpError::Malformed("commitment".to_string()));
+    }
+    CompressedRistretto::from_slice(bytes)
+        .decompress()
+        .ok_or_else(|| ZkpError::Malformed("commitment".to_string()))
+}
@@ -0,0 +1,65 @@
+use rose_forest_value_flow::zkp::{UtilityBounds, ZKP};
+use rose_forest_value_flow::{FlowTerms, MAX_FLOW_UTILITY, MAX_REPUTATION_DIMENSIONS};
+
+const SHIFT: [f32; 3] = [0.1, 0.0, -0.1];
+
+fn flow<'a>(from: &'a u8, to: &'a u8, utility: f32) -> FlowTerms<'a, u8> {
+    FlowTerms {
+        from,
+        to,
+        utility,
+        governance_weight: 0.5,
+        reputation_shift: &SHIFT,
+        utility_proof: None,
+    }
+}
+
+#[test]
+fn public_flows_need_distinct_parties_and_bounded_utility() {
+    assert!(flow(&1, &2, 250.0).validate().is_ok());
+    assert!(flow(&1, &2, MAX_FLOW_UTILITY).validate().is_ok());
+
+    assert!(flow(&1, &1, 250.0).validate().is_err());
+    for utility in [0.0, -1.0, MAX_FLOW_UTILITY * 2.0, f32::NAN, f32::INFINITY] {
+        assert!(flow(&1, &2, utility).validate().is_err(), "{}", utility);
+    }
+}
+
+#[test]
+fn governance_weight_and_shift_are_bounded() {
+    let mut terms = flow(&1, &2, 250.0);
+    terms.governance_weight = 1.5;
+    assert!(terms.validate().is_err());
+
+    let mut terms = flow(&1, &2, 250.0);
+    terms.reputation_shift = &[];
+    assert!(terms.validate().is_err());
+
+    let wide = vec![0.0; MAX_REPUTATION_DIMENSIONS + 1];
+    terms.reputation_shift = &wide;
+    assert!(terms.validate().is_err());
+
+    terms.reputation_shift = &[f32::NAN];
+    assert!(terms.validate().is_err());
+}
+
+#[test]
+fn private_flows_prove_their_utility_within_the_shared_bounds() {
+    let zkp = ZKP::new();
+    let (proof, _) = zkp.prove_utility(250.0, UtilityBounds::default()).unwrap();
+
+    let mut terms = flow(&1, &2, 0.0);
+    terms.utility_proof = Some(&proof);
+    assert!(terms.validate().is_ok());
+
+    // Revealing the utility defeats the proof
+    terms.utility = 250.0;
+    assert!(terms.validate().is_err());
+
+    // A proof against other bounds doesn't verify
+    let other_bounds = UtilityBounds::new(0.0, MAX_FLOW_UTILITY).unwrap();
+    let (other, _) = zkp.prove_utility(250.0, other_bounds).unwrap();
+    let mut terms = flow(&1, &2, 0.0);
+    terms.utility_proof = Some(&other);
+    assert!(terms.validate().is_err());
+}